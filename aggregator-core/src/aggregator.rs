@@ -1,97 +1,936 @@
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::sync::{broadcast, mpsc, oneshot, watch, Mutex, RwLock};
 use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
-use crate::config::Config;
+use crate::analysis::{AnalysisEngine, AnalysisEngineRegistry, TopOfBookEngine};
+use crate::backpressure::{BackpressureSnapshot, BoundedReceiver};
+use crate::circuit_breaker::{BreakerState, CircuitBreaker, CircuitBreakerEvent};
+use crate::clock::{Clock, SystemClock, Ticker};
+use crate::config::{AnalysisConfig, BackpressureConfig, Config};
+use crate::config_watcher::{ConfigChange, ConfigChanged};
+use crate::connector::ConnectorRegistry;
+use crate::discovery::select_pairs;
+use crate::error_telemetry::{ErrorRecord, ErrorTelemetry};
+use crate::event_bus::{Event, EventBus};
+use crate::fx::FxRates;
+use crate::instrument::InstrumentRegistry;
+use crate::latency::LatencyTracker;
+use crate::metrics_history::{MetricsHistory, MetricsSample};
+use crate::pair_book::{LevelMapBookFactory, OrderBookView, PairBookFactory, PairBooks};
+use crate::replay::{ReplayPace, ReplayReport, ReplaySource};
+use crate::shutdown::{ShutdownReport, ShutdownStage, ShutdownTracker};
+use crate::storage::{BookSnapshot, Storage};
+use crate::telemetry::MetricsRegistry;
 use crate::types::{
-    ArbitrageOpportunity, Exchange, HealthStatus, Metrics, PriceLevelUpdate, Summary, TradingPair,
+    ArbitrageOpportunity, BookStats, Exchange, HealthEvent, HealthEventKind, HealthLevel,
+    HealthStatus, Metrics, PriceLevel, PriceLevelUpdate, Summary, SymbolHealth, SystemHealth,
+    TradingPair,
 };
 use crate::{AggregatorError, Result};
 
 pub struct Aggregator {
-    config: Arc<Config>,
-    summaries: Arc<RwLock<HashMap<TradingPair, Summary>>>,
+    /// The settings in use, replaced by `apply_config_change`
+    config: Arc<RwLock<Arc<Config>>>,
+    /// Held while the settings are being replaced, so concurrent changes apply one at a time
+    reconfiguring: Arc<Mutex<()>>,
+    /// Pairs subscribed to because discovery selected them rather than the config listing them.
+    /// Only touched while `reconfiguring` is held.
+    discovered_pairs: RwLock<Vec<TradingPair>>,
+    /// Latest summary of every exchange's book for every pair; consolidated summaries are
+    /// merged from these on read
+    summaries: Arc<RwLock<HashMap<TradingPair, HashMap<Exchange, Summary>>>>,
+    /// Every pair's consolidated book, updated by the aggregation processor
+    books: Arc<RwLock<PairBooks>>,
+    /// Where state is restored from on start and snapshotted to while running
+    storage: Option<Arc<dyn Storage>>,
+    engines: AnalysisEngineRegistry,
+    /// Connectors started for the enabled exchanges, by exchange name
+    connectors: ConnectorRegistry,
     health_status: Arc<RwLock<HashMap<Exchange, HealthStatus>>>,
+    /// Stops the connector of each running exchange
+    connector_stops: Arc<RwLock<HashMap<Exchange, RunningConnector>>>,
+    /// When each exchange last updated each of its symbols
+    symbol_updates: Arc<RwLock<SymbolUpdates>>,
+    /// Connector restarts attempted for exchanges that have not recovered since
+    restarts: Arc<RwLock<HashMap<Exchange, RestartState>>>,
     metrics: Arc<RwLock<HashMap<Exchange, Metrics>>>,
-    summary_sender: broadcast::Sender<Summary>,
-    arbitrage_sender: broadcast::Sender<ArbitrageOpportunity>,
+    /// Rolling per exchange and symbol feed activity, for charting
+    metrics_history: MetricsHistory,
+    /// Counters, gauges and histograms exported to Prometheus
+    registry: Arc<MetricsRegistry>,
+    /// Errors by category and the latest ones
+    errors: ErrorTelemetry,
+    book_stats: Arc<RwLock<HashMap<(Exchange, String), BookStats>>>,
+    instruments: InstrumentRegistry,
+    /// Raw updates from every exchange, consumed by the aggregation processor
+    updates: UpdateFeed,
+    /// Summaries, arbitrage opportunities, health and metrics for subscribers
+    events: Arc<EventBus>,
+    /// Tells connectors outside the aggregator to stop
     shutdown_sender: broadcast::Sender<()>,
+    /// The tasks `shutdown` stops, stage by stage
+    shutdown: ShutdownTracker,
+    /// Time as health checks, staleness and periodic tasks see it
+    clock: Arc<dyn Clock>,
+    /// Rates between quote currencies, kept live from the books of `fx.rate_pairs`
+    fx: FxRates,
+    /// Holds back opportunities while prices move too fast or exchanges are down
+    circuit_breaker: CircuitBreaker,
+}
+
+/// When each exchange last updated each of its symbols
+type SymbolUpdates = HashMap<(Exchange, String), DateTime<Utc>>;
+
+/// A running exchange's connector, stopped through `stop`. `stopped` resolves once its price
+/// level processor has forwarded every update that was queued.
+struct RunningConnector {
+    stop: broadcast::Sender<()>,
+    stopped: oneshot::Receiver<()>,
+}
+
+/// The channel price level processors forward updates to the aggregation processor on. Updates
+/// are counted as they are sent and as they are applied, so a caller can wait until everything
+/// sent so far has reached the books.
+#[derive(Clone)]
+struct UpdateFeed {
+    sender: broadcast::Sender<PriceLevelUpdate>,
+    /// Sent under the lock, so the count follows the channel's order
+    sent: Arc<std::sync::Mutex<u64>>,
+    /// Updates the running aggregation processor has applied or skipped, if one runs
+    applied: Arc<std::sync::Mutex<Option<watch::Receiver<u64>>>>,
+}
+
+impl UpdateFeed {
+    fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            sent: Arc::new(std::sync::Mutex::new(0)),
+            applied: Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+
+    fn send(&self, update: PriceLevelUpdate) -> Result<()> {
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        self.sender
+            .send(update)
+            .map_err(|e| AggregatorError::ChannelSend {
+                message: format!("Failed to forward price level update: {}", e),
+            })?;
+        *sent += 1;
+        Ok(())
+    }
+
+    /// Subscribes the aggregation processor, which reports what it has applied on the returned
+    /// sender, counting from the updates sent before it subscribed
+    fn subscribe_processor(&self) -> (broadcast::Receiver<PriceLevelUpdate>, watch::Sender<u64>) {
+        let sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        let update_rx = self.sender.subscribe();
+        let (applied_tx, applied_rx) = watch::channel(*sent);
+        *self.applied.lock().unwrap_or_else(|e| e.into_inner()) = Some(applied_rx);
+        (update_rx, applied_tx)
+    }
+
+    /// Waits until the aggregation processor has applied every update sent so far. Returns at
+    /// once when none runs.
+    async fn applied(&self) {
+        let target = *self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        let applied = self
+            .applied
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(mut applied) = applied {
+            // Fails once the processor has stopped, when nothing more will be applied
+            let _ = applied.wait_for(|applied| *applied >= target).await;
+        }
+    }
+}
+
+/// What connectors and their price level processors share with the aggregator, so tasks can
+/// start and restart them without borrowing it
+#[derive(Clone)]
+struct ConnectorContext {
+    config: Arc<RwLock<Arc<Config>>>,
+    reconfiguring: Arc<Mutex<()>>,
+    connectors: ConnectorRegistry,
+    connector_stops: Arc<RwLock<HashMap<Exchange, RunningConnector>>>,
+    updates: UpdateFeed,
+    summaries: Arc<RwLock<HashMap<TradingPair, HashMap<Exchange, Summary>>>>,
+    books: Arc<RwLock<PairBooks>>,
+    health_status: Arc<RwLock<HashMap<Exchange, HealthStatus>>>,
+    symbol_updates: Arc<RwLock<SymbolUpdates>>,
+    metrics: Arc<RwLock<HashMap<Exchange, Metrics>>>,
+    metrics_history: MetricsHistory,
+    registry: Arc<MetricsRegistry>,
+    errors: ErrorTelemetry,
+    shutdown: ShutdownTracker,
+    clock: Arc<dyn Clock>,
+}
+
+/// Restarts attempted for one exchange's connector
+#[derive(Debug, Clone)]
+struct RestartState {
+    attempts: u32,
+    last_attempt: DateTime<Utc>,
+    /// Whether giving up after `HealthConfig::max_restarts` has been logged
+    gave_up: bool,
 }
 
 impl Aggregator {
     pub fn new(config: Config) -> Self {
-        let (summary_sender, _) = broadcast::channel(1000);
-        let (arbitrage_sender, _) = broadcast::channel(1000);
         let (shutdown_sender, _) = broadcast::channel(1);
 
+        let book_factory = Arc::new(LevelMapBookFactory::new(config.orderbook.max_depth));
+        let fx = FxRates::new(config.fx.clone());
+        let metrics_history = MetricsHistory::new(config.metrics.history.clone());
+        let circuit_breaker = CircuitBreaker::new(config.analysis.circuit_breaker.clone());
+        let registry = Arc::new(MetricsRegistry::new());
+        let errors =
+            ErrorTelemetry::new(config.metrics.errors.clone()).with_registry(registry.clone());
+
         Self {
-            config: Arc::new(config),
+            books: Arc::new(RwLock::new(PairBooks::new(book_factory))),
+            config: Arc::new(RwLock::new(Arc::new(config))),
+            reconfiguring: Arc::new(Mutex::new(())),
+            discovered_pairs: RwLock::new(Vec::new()),
             summaries: Arc::new(RwLock::new(HashMap::new())),
+            storage: None,
+            engines: AnalysisEngineRegistry::new(),
+            connectors: ConnectorRegistry::new(),
             health_status: Arc::new(RwLock::new(HashMap::new())),
+            connector_stops: Arc::new(RwLock::new(HashMap::new())),
+            symbol_updates: Arc::new(RwLock::new(HashMap::new())),
+            restarts: Arc::new(RwLock::new(HashMap::new())),
             metrics: Arc::new(RwLock::new(HashMap::new())),
-            summary_sender,
-            arbitrage_sender,
+            metrics_history,
+            registry,
+            errors,
+            book_stats: Arc::new(RwLock::new(HashMap::new())),
+            instruments: InstrumentRegistry::new(),
+            updates: UpdateFeed::new(10000),
+            events: Arc::new(EventBus::new()),
             shutdown_sender,
+            shutdown: ShutdownTracker::new(),
+            clock: SystemClock::shared(),
+            fx,
+            circuit_breaker,
         }
     }
 
+    /// Builds each pair's consolidated book with `factory` instead of the default
+    /// `LevelMapBook`. Must be set before `start`.
+    pub fn with_book_factory(mut self, factory: Arc<dyn PairBookFactory>) -> Self {
+        self.books = Arc::new(RwLock::new(PairBooks::new(factory)));
+        self
+    }
+
+    /// Restores the books and metrics saved in `storage` on `start`, and snapshots them there
+    /// every `config.storage.snapshot_interval_secs` along with the arbitrage opportunities
+    /// found meanwhile. Must be set before `start`; see `open_storage` to open the backend
+    /// `config.storage` selects.
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    pub fn storage(&self) -> Option<Arc<dyn Storage>> {
+        self.storage.clone()
+    }
+
+    /// Judges health, staleness and update rates, stamps health statuses, and paces health
+    /// checks, arbitrage scans, snapshots and replays by `clock` instead of the system clock.
+    /// A `SimulatedClock` lets tests step through them deterministically. Must be set before
+    /// `start`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// The rates prices are converted between quote currencies with. Clones share them, so
+    /// rates set on the handle are used by the built-in arbitrage detection.
+    pub fn fx(&self) -> FxRates {
+        self.fx.clone()
+    }
+
+    /// The breaker holding back arbitrage opportunities, see [`CircuitBreaker`]. Its changes
+    /// are published as `CircuitBreakerEvent`s.
+    pub fn circuit_breaker(&self) -> CircuitBreaker {
+        self.circuit_breaker.clone()
+    }
+
+    /// Starts the connectors registered in `connectors` for the exchanges they name;
+    /// `exchange_connectors::register_all` registers the built-in ones. Connectors can also be
+    /// registered on `connectors()` later; they are used from the next time their exchange's
+    /// connector starts.
+    pub fn with_connectors(mut self, connectors: ConnectorRegistry) -> Self {
+        self.connectors = connectors;
+        self
+    }
+
+    /// The registry connectors are started from. The returned handle shares the aggregator's
+    /// registry.
+    pub fn connectors(&self) -> ConnectorRegistry {
+        self.connectors.clone()
+    }
+
+    /// The bus the aggregator publishes its events on. Components outside the aggregator, such
+    /// as connectors streaming trades or a data quality monitor, can publish on it too.
+    pub fn events(&self) -> Arc<EventBus> {
+        self.events.clone()
+    }
+
+    /// Receives every event of type `T` published from now on: `Summary`,
+    /// `ArbitrageOpportunity`, `HealthStatus`, `Metrics`, `Trade`, `CircuitBreakerEvent` or any
+    /// other [`Event`]
+    pub fn subscribe<T: Event>(&self) -> broadcast::Receiver<T> {
+        self.events.subscribe()
+    }
+
+    /// Receives the consolidated summary of a pair every time an exchange updates it
     pub fn subscribe_summaries(&self) -> broadcast::Receiver<Summary> {
-        self.summary_sender.subscribe()
+        self.subscribe()
     }
 
     pub fn subscribe_arbitrage(&self) -> broadcast::Receiver<ArbitrageOpportunity> {
-        self.arbitrage_sender.subscribe()
+        self.subscribe()
+    }
+
+    /// Receives every price level update as exchanges send it, before it is aggregated, for
+    /// consumers following each exchange's own book
+    pub fn subscribe_price_levels(&self) -> broadcast::Receiver<PriceLevelUpdate> {
+        self.updates.sender.subscribe()
+    }
+
+    /// Receives summaries through a buffer of its own bounded by `config`, for consumers such
+    /// as network clients that may read slower than summaries are published
+    pub fn subscribe_summaries_bounded(
+        &self,
+        config: &BackpressureConfig,
+    ) -> BoundedReceiver<Summary> {
+        self.events.subscribe_bounded(config)
     }
 
+    pub fn subscribe_arbitrage_bounded(
+        &self,
+        config: &BackpressureConfig,
+    ) -> BoundedReceiver<ArbitrageOpportunity> {
+        self.events.subscribe_bounded(config)
+    }
+
+    /// Summaries dropped, conflated and disconnected across every bounded summary subscriber
+    pub fn summary_backpressure(&self) -> BackpressureSnapshot {
+        self.events.backpressure::<Summary>()
+    }
+
+    /// Fires when `stop` is called, or when `shutdown` stops the connectors, for connectors
+    /// running outside the aggregator
     pub fn subscribe_shutdown(&self) -> broadcast::Receiver<()> {
         self.shutdown_sender.subscribe()
     }
 
-    pub async fn start(&self) -> Result<Vec<JoinHandle<Result<()>>>> {
+    /// Adds an engine to the arbitrage loop. Every second all registered engines run
+    /// concurrently over the latest summary of each exchange and symbol, and their merged
+    /// opportunities are published to `subscribe_arbitrage` receivers. Engines can be
+    /// registered before or after `start`; while none is, the built-in `TopOfBookEngine`
+    /// configured from `config.analysis` runs instead.
+    pub async fn register_engine(&self, engine: Arc<dyn AnalysisEngine>) {
+        info!("Registering analysis engine {}", engine.name());
+        self.engines.register(engine).await;
+    }
+
+    /// The engines run by the arbitrage loop. The returned handle shares the aggregator's
+    /// registry.
+    pub fn analysis_engines(&self) -> AnalysisEngineRegistry {
+        self.engines.clone()
+    }
+
+    /// The settings currently in use
+    pub async fn config(&self) -> Arc<Config> {
+        self.config.read().await.clone()
+    }
+
+    /// Starts the connectors of the enabled exchanges and the tasks processing their updates,
+    /// which run until `shutdown` or `stop`. With `discovery` enabled, the connectors subscribe
+    /// to the discovered pairs too; see `watch_markets` to keep discovering them.
+    pub async fn start(&self) -> Result<()> {
         info!("Starting cryptocurrency orderbook aggregator");
 
-        let mut handles = Vec::new();
+        self.start_processing().await?;
 
-        self.initialize_health_status().await?;
+        if self.config().await.discovery.enabled {
+            self.refresh_trading_pairs().await?;
+            if self.config().await.trading_pairs.is_empty() {
+                return Err(AggregatorError::validation(
+                    "trading_pairs",
+                    "none are configured and discovery found none",
+                ));
+            }
+        }
 
-        for exchange in self.config.enabled_exchanges() {
-            let exchange_handles = self.start_exchange_connector(exchange).await?;
-            handles.extend(exchange_handles);
+        for exchange in self.config().await.enabled_exchanges() {
+            self.start_exchange_connector(exchange).await?;
         }
 
+        let health_handle = self.start_health_monitor().await?;
+        self.shutdown
+            .track(ShutdownStage::Connectors, "health monitor", health_handle);
+
+        info!("Aggregator started successfully");
+        Ok(())
+    }
+
+    /// Runs the aggregator on the updates of `source` instead of live connectors. Books,
+    /// summaries, arbitrage, snapshots and servers work as after `start`, and each exchange in
+    /// the recording gets a price level processor, so health and metrics cover it too. The
+    /// health monitor is left out, as there are no connectors to restart.
+    ///
+    /// Updates are fed at `pace`: `ReplayPace::REAL_TIME`, `ReplayPace::Recorded { speed: 10.0 }`
+    /// for ten times as fast, or `ReplayPace::Unpaced` for as fast as they are processed. A
+    /// `ReplayReport` is published once the source is exhausted, after which the aggregator
+    /// keeps serving the final state. `shutdown` and `stop` end a replay early.
+    pub async fn start_replay(
+        &self,
+        source: impl ReplaySource + 'static,
+        pace: ReplayPace,
+    ) -> Result<()> {
+        info!("Starting aggregator replay at {:?}", pace);
+
+        self.start_processing().await?;
+
+        let replay_handle = self.start_replay_feed(Box::new(source), pace);
+        self.shutdown
+            .track(ShutdownStage::Connectors, "replay", replay_handle);
+
+        info!("Aggregator replay started successfully");
+        Ok(())
+    }
+
+    /// Starts every task that consumes price level updates, whatever feeds them
+    async fn start_processing(&self) -> Result<()> {
+        self.initialize_health_status().await?;
+
+        // Subscribe to updates before any connector can send one
         let aggregation_handle = self.start_aggregation_processor().await?;
-        handles.push(aggregation_handle);
+        self.shutdown.track(
+            ShutdownStage::Processing,
+            "aggregation processor",
+            aggregation_handle,
+        );
 
         let arbitrage_handle = self.start_arbitrage_detector().await?;
-        handles.push(arbitrage_handle);
+        self.shutdown.track(
+            ShutdownStage::Processing,
+            "arbitrage detector",
+            arbitrage_handle,
+        );
 
-        let health_handle = self.start_health_monitor().await?;
-        handles.push(health_handle);
+        if let Some(snapshot_handle) = self.start_snapshotter().await? {
+            self.shutdown
+                .track(ShutdownStage::Persistence, "snapshotter", snapshot_handle);
+        }
 
-        info!("Aggregator started successfully");
-        Ok(handles)
+        Ok(())
+    }
+
+    /// Switches to the settings of `changed` while running: connectors of newly enabled
+    /// exchanges are started and those of disabled exchanges stopped, running connectors are
+    /// restarted to pick up added or removed trading pairs, and exchange fees and analysis
+    /// thresholds take effect with the next update or arbitrage run. Changes to sections only
+    /// read at startup are logged and wait for a restart.
+    ///
+    /// While discovery stays enabled, the pairs it subscribed to are kept even though a
+    /// reloaded file does not list them.
+    pub async fn apply_config_change(&self, changed: &ConfigChanged) -> Result<()> {
+        let _reconfiguring = self.reconfiguring.lock().await;
+        let mut discovered = self.discovered_pairs.write().await;
+        if discovered.is_empty() {
+            return self.apply_config_change_locked(changed).await;
+        }
+
+        // `changed` compares against the file, which never listed the discovered pairs
+        let mut config = (*changed.config).clone();
+        if config.discovery.enabled {
+            discovered.retain(|pair| !config.trading_pairs.contains(pair));
+            config.trading_pairs.extend(discovered.iter().cloned());
+        } else {
+            discovered.clear();
+        }
+        match ConfigChanged::between(&*self.config().await, config) {
+            Some(changed) => self.apply_config_change_locked(&changed).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Adds `pair` to the configured trading pairs and resubscribes the running connectors.
+    /// Returns `false` if the pair was already subscribed to. A discovered pair added this way
+    /// is kept once discovery no longer selects it.
+    pub async fn add_trading_pair(&self, pair: TradingPair) -> Result<bool> {
+        let _reconfiguring = self.reconfiguring.lock().await;
+        self.discovered_pairs
+            .write()
+            .await
+            .retain(|discovered| discovered != &pair);
+        self.update_config_locked(|config| {
+            if !config.trading_pairs.contains(&pair) {
+                config.trading_pairs.push(pair);
+            }
+        })
+        .await
+    }
+
+    /// Removes `pair` from the configured trading pairs, drops its book and summaries and
+    /// resubscribes the running connectors. Returns `false` if the pair was not configured;
+    /// removing the last pair fails validation. A discovered pair removed this way is not
+    /// brought back by a config reload.
+    pub async fn remove_trading_pair(&self, pair: &TradingPair) -> Result<bool> {
+        let _reconfiguring = self.reconfiguring.lock().await;
+        let mut discovered = self.discovered_pairs.write().await;
+        let changed = self
+            .update_config_locked(|config| config.trading_pairs.retain(|p| p != pair))
+            .await?;
+        discovered.retain(|discovered| discovered != pair);
+        Ok(changed)
     }
 
+    /// Enables `exchange`, with default settings if it has none, and starts its connector.
+    /// Returns `false` if it was already enabled.
+    pub async fn enable_exchange(&self, exchange: Exchange) -> Result<bool> {
+        self.update_config(|config| {
+            config.exchanges.entry(exchange).or_default().enabled = true;
+        })
+        .await
+    }
+
+    /// Disables `exchange`, stops its connector and drops its levels from every book. Returns
+    /// `false` if it was not enabled; disabling the last enabled exchange fails validation.
+    pub async fn disable_exchange(&self, exchange: &Exchange) -> Result<bool> {
+        self.update_config(|config| {
+            if let Some(exchange_config) = config.exchanges.get_mut(exchange) {
+                exchange_config.enabled = false;
+            }
+        })
+        .await
+    }
+
+    /// Restarts the running connector of `exchange`, which drops its levels from every book,
+    /// reconnects to the exchange and rebuilds them from fresh snapshots. Returns `false` if the
+    /// exchange has no running connector.
+    pub async fn reconnect_exchange(&self, exchange: &Exchange) -> Result<bool> {
+        let _reconfiguring = self.reconfiguring.lock().await;
+        if !self.connector_stops.read().await.contains_key(exchange) {
+            return Ok(false);
+        }
+        info!("Reconnecting to {}", exchange);
+        Self::retire_connector(&self.connector_context(), exchange).await;
+        self.start_exchange_connector(exchange.clone()).await?;
+        Ok(true)
+    }
+
+    /// Replaces the analysis settings, which take effect with the next update or arbitrage
+    /// run. Returns `false` if they did not change.
+    pub async fn update_analysis(&self, analysis: AnalysisConfig) -> Result<bool> {
+        self.update_config(|config| config.analysis = analysis)
+            .await
+    }
+
+    /// Lists the markets of every enabled exchange with a market discovery registered on
+    /// `connectors()` and returns the pairs the `discovery` settings select among them, without
+    /// subscribing to them. Exchanges whose markets cannot be listed are left out.
+    pub async fn discover_trading_pairs(&self) -> Vec<TradingPair> {
+        let config = self.config().await;
+        let mut listings = Vec::new();
+        for exchange in config.enabled_exchanges() {
+            let exchange_config = config.exchanges.get(&exchange).cloned().unwrap_or_default();
+            let listed = match self
+                .connectors
+                .create_discovery(&exchange, &exchange_config)
+                .await
+            {
+                Some(Ok(discovery)) => discovery.list_markets().await,
+                Some(Err(e)) => Err(e),
+                None => {
+                    warn!("{} has no market discovery, skipping it", exchange);
+                    continue;
+                }
+            };
+            match listed {
+                Ok(markets) => {
+                    info!("Discovered {} markets on {}", markets.len(), exchange);
+                    listings.extend(markets);
+                }
+                Err(e) => {
+                    warn!("Failed to discover the markets of {}: {}", exchange, e);
+                    self.record_error("discovery", Some(&exchange), &e);
+                }
+            }
+        }
+        select_pairs(&config.discovery, &listings)
+    }
+
+    /// Subscribes to the pairs `discover_trading_pairs` selects on top of the configured ones,
+    /// and unsubscribes from those discovered earlier that it no longer selects. A discovery
+    /// finding nothing keeps the current pairs. Returns `false` if the pairs did not change.
+    pub async fn refresh_trading_pairs(&self) -> Result<bool> {
+        let selected = self.discover_trading_pairs().await;
+        if selected.is_empty() {
+            warn!("Pair discovery found no pairs, keeping the current ones");
+            return Ok(false);
+        }
+
+        let _reconfiguring = self.reconfiguring.lock().await;
+        let mut discovered = self.discovered_pairs.write().await;
+        let mut added = Vec::new();
+        let changed = self
+            .update_config_locked(|config| {
+                config
+                    .trading_pairs
+                    .retain(|pair| !discovered.contains(pair));
+                for pair in selected {
+                    if !config.trading_pairs.contains(&pair) {
+                        config.trading_pairs.push(pair.clone());
+                        added.push(pair);
+                    }
+                }
+            })
+            .await?;
+        *discovered = added;
+        Ok(changed)
+    }
+
+    /// Spawns a task that calls `refresh_trading_pairs` every `discovery.refresh_interval_secs`
+    /// until the aggregator stops. It returns at once when discovery is disabled or only runs
+    /// at startup, and skips refreshes while discovery is disabled by a config change.
+    pub fn watch_markets(self: Arc<Self>) -> JoinHandle<Result<()>> {
+        let stopping = self.shutdown.signal(ShutdownStage::Connectors);
+        tokio::spawn(async move {
+            let settings = self.config().await.discovery.clone();
+            if !settings.enabled || settings.refresh_interval_secs == 0 {
+                return Ok(());
+            }
+
+            tokio::pin!(stopping);
+            let mut interval = Ticker::delayed(
+                self.clock.clone(),
+                std::time::Duration::from_secs(settings.refresh_interval_secs),
+            );
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        if !self.config().await.discovery.enabled {
+                            continue;
+                        }
+                        if let Err(e) = self.refresh_trading_pairs().await {
+                            error!("Failed to apply discovered trading pairs: {}", e);
+                            self.record_error("discovery", None, &e);
+                        }
+                    }
+                    _ = &mut stopping => {
+                        info!("Pair discovery shutting down");
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Applies `edit` to a copy of the current settings and switches to it if it is valid.
+    /// A later reload of the config file replaces these edits with the file's settings.
+    async fn update_config(&self, edit: impl FnOnce(&mut Config)) -> Result<bool> {
+        let _reconfiguring = self.reconfiguring.lock().await;
+        self.update_config_locked(edit).await
+    }
+
+    async fn update_config_locked(&self, edit: impl FnOnce(&mut Config)) -> Result<bool> {
+        let current = self.config().await;
+        let mut edited = (*current).clone();
+        edit(&mut edited);
+        edited.validate()?;
+        match ConfigChanged::between(&current, edited) {
+            Some(changed) => {
+                self.apply_config_change_locked(&changed).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn apply_config_change_locked(&self, changed: &ConfigChanged) -> Result<()> {
+        *self.config.write().await = changed.config.clone();
+
+        let mut started = Vec::new();
+        let mut pairs_changed = false;
+        for change in &changed.changes {
+            match change {
+                ConfigChange::ExchangeEnabled(exchange) => {
+                    if self.connector_stops.read().await.contains_key(exchange) {
+                        continue;
+                    }
+                    self.start_exchange_connector(exchange.clone()).await?;
+                    started.push(exchange.clone());
+                }
+                ConfigChange::ExchangeDisabled(exchange) => {
+                    Self::retire_connector(&self.connector_context(), exchange).await;
+                }
+                ConfigChange::TradingPairAdded(pair) => {
+                    info!("Subscribing to {}", pair);
+                    pairs_changed = true;
+                }
+                ConfigChange::TradingPairRemoved(pair) => {
+                    info!("Unsubscribing from {}", pair);
+                    self.summaries.write().await.remove(pair);
+                    self.books.write().await.remove(pair);
+                    pairs_changed = true;
+                }
+                ConfigChange::AnalysisUpdated => {
+                    info!("Updating analysis settings");
+                    self.circuit_breaker
+                        .configure(changed.config.analysis.circuit_breaker.clone());
+                }
+                ConfigChange::FxUpdated => {
+                    info!("Updating FX settings");
+                    self.fx.configure(changed.config.fx.clone());
+                }
+                ConfigChange::RestartRequired(section) => {
+                    warn!(
+                        "Changes to {} settings take effect after a restart",
+                        section
+                    );
+                }
+                change => info!("Applied configuration change {:?}", change),
+            }
+        }
+
+        if pairs_changed {
+            let running: Vec<Exchange> =
+                self.connector_stops.read().await.keys().cloned().collect();
+            for exchange in running.into_iter().filter(|e| !started.contains(e)) {
+                self.stop_exchange_connector(&exchange).await;
+                self.start_exchange_connector(exchange).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Spawns a task that applies every change received on `config_rx`, usually from a
+    /// `ConfigWatcher`, until the aggregator stops or the channel closes.
+    pub fn watch_config(
+        self: Arc<Self>,
+        mut config_rx: broadcast::Receiver<ConfigChanged>,
+    ) -> JoinHandle<Result<()>> {
+        // Stop before the connectors, so no change starts one during shutdown
+        let stopping = self.shutdown.signal(ShutdownStage::Connectors);
+        tokio::spawn(async move {
+            tokio::pin!(stopping);
+            loop {
+                tokio::select! {
+                    received = config_rx.recv() => {
+                        let changed = match received {
+                            Ok(changed) => changed,
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                warn!("Config listener lagged, skipped {} changes", skipped);
+                                continue;
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        };
+                        if let Err(e) = self.apply_config_change(&changed).await {
+                            error!("Failed to apply configuration change: {}", e);
+                            self.record_error("config", None, &e);
+                        }
+                    }
+                    _ = &mut stopping => {
+                        info!("Config listener shutting down");
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Signals every task to stop at once, without waiting for them. See `shutdown` to stop
+    /// them in order.
     pub async fn stop(&self) -> Result<()> {
         info!("Stopping aggregator");
-        self.shutdown_sender
-            .send(())
-            .map_err(|e| AggregatorError::ChannelSend {
-                message: format!("Failed to send shutdown signal: {}", e),
-            })?;
+        self.halt_connectors().await;
+        self.shutdown.reach(ShutdownStage::Servers);
+        // Sending only fails when nobody is subscribed
+        let _ = self.shutdown_sender.send(());
         Ok(())
     }
 
+    /// Stops the aggregator stage by stage, see [`ShutdownStage`]: connectors first, with the
+    /// updates already received forwarded, then the processing of those updates, then a final
+    /// snapshot to storage, then the servers registered with `track_task`. Each stage starts
+    /// once the previous one has stopped. Components still running `timeout` after the call are
+    /// aborted.
+    ///
+    /// Returns the components that stopped and those that failed to, timed out or panicked.
+    pub async fn shutdown(&self, timeout: std::time::Duration) -> ShutdownReport {
+        info!("Shutting down aggregator");
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut report = ShutdownReport::default();
+        for stage in ShutdownStage::ALL {
+            info!("Stopping {}", stage);
+            self.shutdown.reach(stage);
+            if stage == ShutdownStage::Connectors {
+                self.halt_connectors().await;
+                // Sending only fails when nobody is subscribed
+                let _ = self.shutdown_sender.send(());
+            }
+            self.shutdown.stop(stage, deadline, &mut report).await;
+        }
+        if report.is_clean() {
+            info!("Aggregator shut down");
+        } else {
+            warn!(
+                "Aggregator shut down, {} component(s) failed to stop",
+                report.failed.len()
+            );
+        }
+        report
+    }
+
+    /// Has `shutdown` wait for `handle` at `stage`, for tasks started outside the aggregator
+    /// such as servers
+    pub fn track_task(
+        &self,
+        stage: ShutdownStage,
+        component: impl Into<String>,
+        handle: JoinHandle<Result<()>>,
+    ) {
+        self.shutdown.track(stage, component, handle);
+    }
+
+    /// Resolves once `shutdown` reaches `stage`, or `stop` is called. Tasks passed to
+    /// `track_task` stop when it resolves.
+    pub fn stopping(
+        &self,
+        stage: ShutdownStage,
+    ) -> impl std::future::Future<Output = ()> + Send + 'static {
+        self.shutdown.signal(stage)
+    }
+
+    /// Saves the current books, consolidated summaries and metrics to the storage set with
+    /// `with_storage`, if any. Arbitrage opportunities and metrics history are only saved by the
+    /// snapshot task `start` runs.
+    pub async fn save_snapshot(&self) -> Result<()> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        Self::write_snapshot(
+            storage.as_ref(),
+            &self.config,
+            &self.summaries,
+            &self.metrics,
+            &[],
+        )
+        .await
+    }
+
+    /// The consolidated summary of `pair`, see `get_consolidated_summary`
     pub async fn get_summary(&self, pair: &TradingPair) -> Option<Summary> {
-        let summaries = self.summaries.read().await;
-        summaries.get(pair).cloned()
+        self.get_consolidated_summary(pair).await
     }
 
+    /// The consolidated summary of every pair with data
     pub async fn get_all_summaries(&self) -> HashMap<TradingPair, Summary> {
+        let depth = self.config().await.orderbook.max_depth;
+        let summaries = self.summaries.read().await;
+        summaries
+            .iter()
+            .filter_map(|(pair, by_exchange)| {
+                let summary = Self::consolidate(by_exchange, depth)?;
+                Some((pair.clone(), summary))
+            })
+            .collect()
+    }
+
+    /// The latest summary of `exchange`'s book alone for `pair`
+    pub async fn get_summary_for_exchange(
+        &self,
+        pair: &TradingPair,
+        exchange: &Exchange,
+    ) -> Option<Summary> {
+        let summaries = self.summaries.read().await;
+        summaries.get(pair)?.get(exchange).cloned()
+    }
+
+    /// The latest summary of every exchange quoting `pair`
+    pub async fn get_exchange_summaries(&self, pair: &TradingPair) -> HashMap<Exchange, Summary> {
         let summaries = self.summaries.read().await;
-        summaries.clone()
+        summaries.get(pair).cloned().unwrap_or_default()
+    }
+
+    /// `pair`'s book to `depth` levels per side, read from the live book rather than the stored
+    /// summaries, which stop at `orderbook.max_depth`, along with each exchange's own levels.
+    /// A `depth` past what the book keeps returns every level it has. `None` until an exchange
+    /// updates the pair.
+    pub async fn get_order_book(&self, pair: &TradingPair, depth: usize) -> Option<OrderBookView> {
+        let exchanges: Vec<Exchange> = self
+            .summaries
+            .read()
+            .await
+            .get(pair)?
+            .keys()
+            .cloned()
+            .collect();
+        self.books.read().await.view(pair, &exchanges, depth).await
+    }
+
+    /// The name of the book implementation kept for `pair`, see `PairBook::name`. `None` until
+    /// an exchange updates the pair.
+    pub async fn book_name(&self, pair: &TradingPair) -> Option<String> {
+        self.books.read().await.book_name(pair)
+    }
+
+    /// The best `max_depth` levels per side of `pair` across every exchange, each attributed to
+    /// the exchange quoting it, stamped with the newest exchange summary
+    pub async fn get_consolidated_summary(&self, pair: &TradingPair) -> Option<Summary> {
+        let depth = self.config().await.orderbook.max_depth;
+        let summaries = self.summaries.read().await;
+        Self::consolidate(summaries.get(pair)?, depth)
+    }
+
+    fn consolidate(by_exchange: &HashMap<Exchange, Summary>, depth: usize) -> Option<Summary> {
+        let newest = by_exchange
+            .values()
+            .max_by_key(|summary| summary.timestamp)?;
+        let merged =
+            |side: fn(&Summary) -> &Vec<PriceLevel>,
+             best_first: fn(&PriceLevel, &PriceLevel) -> std::cmp::Ordering| {
+                let mut levels: Vec<PriceLevel> = by_exchange
+                    .values()
+                    .flat_map(|summary| side(summary).iter().cloned())
+                    .collect();
+                levels.sort_by(|a, b| best_first(a, b).then_with(|| a.exchange.cmp(&b.exchange)));
+                levels.truncate(depth);
+                levels
+            };
+        let bids = merged(|summary| &summary.bids, |a, b| b.price.total_cmp(&a.price));
+        let asks = merged(|summary| &summary.asks, |a, b| a.price.total_cmp(&b.price));
+        let spread = match (bids.first(), asks.first()) {
+            (Some(best_bid), Some(best_ask)) => best_ask.price - best_bid.price,
+            _ => 0.0,
+        };
+
+        Some(Summary {
+            symbol: newest.symbol.clone(),
+            spread,
+            bids,
+            asks,
+            timestamp: newest.timestamp,
+            market_type: newest.market_type.clone(),
+        })
     }
 
     pub async fn get_health_status(&self, exchange: &Exchange) -> Option<HealthStatus> {
@@ -104,6 +943,106 @@ impl Aggregator {
         health_status.clone()
     }
 
+    /// Applies a connector health event, so stalls and disconnects show up immediately rather
+    /// than after the health monitor notices missing updates.
+    pub async fn record_health_event(&self, event: &HealthEvent) {
+        if !matches!(event.kind, HealthEventKind::Connected) {
+            warn!("Exchange {} unhealthy: {:?}", event.exchange, event.kind);
+        }
+        let status = event.health_status();
+        Self::record_health_gauge(&self.registry, &status);
+        let mut health_status = self.health_status.write().await;
+        health_status.insert(event.exchange.clone(), status.clone());
+        self.events.publish(status);
+    }
+
+    /// The health of every enabled exchange and of each symbol it updates, judged against the
+    /// current `health` settings. The health monitor publishes it as an event whenever its
+    /// level changes.
+    pub async fn system_health(&self) -> SystemHealth {
+        Self::assess_health(
+            &*self.config().await,
+            &self.health_status,
+            &self.symbol_updates,
+            &self.restarts,
+            self.clock.now(),
+        )
+        .await
+    }
+
+    async fn assess_health(
+        config: &Config,
+        health_status: &RwLock<HashMap<Exchange, HealthStatus>>,
+        symbol_updates: &RwLock<SymbolUpdates>,
+        restarts: &RwLock<HashMap<Exchange, RestartState>>,
+        now: DateTime<Utc>,
+    ) -> SystemHealth {
+        let stale_after = chrono::Duration::seconds(config.health.stale_after_secs as i64);
+        let mut enabled = config.enabled_exchanges();
+        enabled.sort();
+
+        let statuses = health_status.read().await;
+        let exchanges: Vec<HealthStatus> = enabled
+            .iter()
+            .map(|exchange| {
+                statuses
+                    .get(exchange)
+                    .cloned()
+                    .unwrap_or_else(|| HealthStatus {
+                        exchange: exchange.clone(),
+                        is_healthy: false,
+                        last_update: now,
+                        error_message: Some("Not started".to_string()),
+                    })
+            })
+            .collect();
+        drop(statuses);
+
+        let mut symbols: Vec<SymbolHealth> = symbol_updates
+            .read()
+            .await
+            .iter()
+            .filter(|((exchange, _), _)| enabled.contains(exchange))
+            .map(|((exchange, symbol), last_update)| SymbolHealth {
+                exchange: exchange.clone(),
+                symbol: symbol.clone(),
+                last_update: *last_update,
+                is_stale: now - *last_update > stale_after,
+            })
+            .collect();
+        symbols.sort_by(|a, b| (&a.exchange, &a.symbol).cmp(&(&b.exchange, &b.symbol)));
+
+        let restarts = restarts
+            .read()
+            .await
+            .iter()
+            .map(|(exchange, state)| (exchange.clone(), state.attempts))
+            .collect();
+
+        let healthy = exchanges.iter().filter(|status| status.is_healthy).count();
+        let level = if healthy == 0 {
+            HealthLevel::Unhealthy
+        } else if healthy == exchanges.len() && !symbols.iter().any(|symbol| symbol.is_stale) {
+            HealthLevel::Healthy
+        } else {
+            HealthLevel::Degraded
+        };
+
+        SystemHealth {
+            level,
+            exchanges,
+            symbols,
+            restarts,
+            timestamp: now,
+        }
+    }
+
+    /// Instrument metadata shared with connectors and analysis. The returned handle shares the
+    /// aggregator's registry, so instruments loaded through it are visible everywhere.
+    pub fn instruments(&self) -> InstrumentRegistry {
+        self.instruments.clone()
+    }
+
     pub async fn get_metrics(&self, exchange: &Exchange) -> Option<Metrics> {
         let metrics = self.metrics.read().await;
         metrics.get(exchange).cloned()
@@ -114,6 +1053,96 @@ impl Aggregator {
         metrics.clone()
     }
 
+    /// The rolling history of `exchange`'s feed of `symbol`, or of all its symbols, from
+    /// `since` on, oldest first. Intervals that have ended are included even if the feed has
+    /// gone quiet since; the interval in progress is not.
+    pub async fn get_metrics_history(
+        &self,
+        exchange: &Exchange,
+        symbol: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> Vec<MetricsSample> {
+        self.metrics_history.flush(self.clock.now());
+        self.metrics_history.samples(exchange, symbol, since)
+    }
+
+    /// Every exchange and symbol with metrics history
+    pub fn metrics_history_feeds(&self) -> Vec<(Exchange, String)> {
+        self.metrics_history.feeds()
+    }
+
+    /// Counters, gauges and histograms of every feed, book and analysis run, labelled by
+    /// exchange and symbol. `MetricsRegistry::render` gives them in the Prometheus text format.
+    pub fn registry(&self) -> Arc<MetricsRegistry> {
+        self.registry.clone()
+    }
+
+    /// The errors the aggregator ran into, counted by category with the latest ones kept. The
+    /// returned handle shares the counters.
+    pub fn errors(&self) -> ErrorTelemetry {
+        self.errors.clone()
+    }
+
+    /// Counts `error`, hit by `source` for `exchange`, alongside the aggregator's own errors.
+    /// For connectors and servers to report what they ran into.
+    pub fn record_error(
+        &self,
+        source: &str,
+        exchange: Option<&Exchange>,
+        error: &AggregatorError,
+    ) -> ErrorRecord {
+        self.errors
+            .record(source, exchange, error, self.clock.now())
+    }
+
+    /// Stores the latest counters of the order book an exchange keeps for a symbol, replacing
+    /// the previous reading.
+    pub async fn record_book_stats(&self, exchange: Exchange, symbol: &str, stats: BookStats) {
+        let exchange_label = exchange.to_string();
+        let labels = [("exchange", exchange_label.as_str()), ("symbol", symbol)];
+        self.registry
+            .gauge(
+                "aggregator_book_levels_trimmed",
+                "Levels an exchange's book dropped to stay within the maximum depth",
+                &labels,
+            )
+            .set(stats.levels_trimmed as f64);
+        self.registry
+            .gauge(
+                "aggregator_book_lock_wait_us",
+                "Mean time updates to an exchange's book waited for its write lock",
+                &labels,
+            )
+            .set(stats.mean_lock_wait_us());
+        let mut book_stats = self.book_stats.write().await;
+        book_stats.insert((exchange, symbol.to_string()), stats);
+    }
+
+    pub async fn get_book_stats(&self, exchange: &Exchange, symbol: &str) -> Option<BookStats> {
+        let book_stats = self.book_stats.read().await;
+        book_stats
+            .get(&(exchange.clone(), symbol.to_string()))
+            .cloned()
+    }
+
+    pub async fn get_all_book_stats(&self) -> HashMap<(Exchange, String), BookStats> {
+        let book_stats = self.book_stats.read().await;
+        book_stats.clone()
+    }
+
+    /// Every recorded book's counters in the Prometheus text exposition format, ordered by
+    /// exchange and symbol.
+    pub async fn book_stats_prometheus(&self) -> String {
+        let book_stats = self.book_stats.read().await;
+        let mut books: Vec<_> = book_stats.iter().collect();
+        books.sort_by(|a, b| a.0.cmp(b.0));
+        BookStats::render_prometheus(
+            books
+                .into_iter()
+                .map(|((exchange, symbol), stats)| (exchange, symbol.as_str(), stats)),
+        )
+    }
+
     async fn initialize_health_status(&self) -> Result<()> {
         let mut health_status = self.health_status.write().await;
 
@@ -123,7 +1152,7 @@ impl Aggregator {
                 HealthStatus {
                     exchange: exchange.clone(),
                     is_healthy: false,
-                    last_update: chrono::Utc::now(),
+                    last_update: self.clock.now(),
                     error_message: None,
                 },
             );
@@ -132,230 +1161,764 @@ impl Aggregator {
         Ok(())
     }
 
-    async fn start_exchange_connector(
-        &self,
-        exchange: Exchange,
-    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+    fn connector_context(&self) -> ConnectorContext {
+        ConnectorContext {
+            config: self.config.clone(),
+            reconfiguring: self.reconfiguring.clone(),
+            connectors: self.connectors.clone(),
+            connector_stops: self.connector_stops.clone(),
+            updates: self.updates.clone(),
+            summaries: self.summaries.clone(),
+            books: self.books.clone(),
+            health_status: self.health_status.clone(),
+            symbol_updates: self.symbol_updates.clone(),
+            metrics: self.metrics.clone(),
+            metrics_history: self.metrics_history.clone(),
+            registry: self.registry.clone(),
+            errors: self.errors.clone(),
+            shutdown: self.shutdown.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+
+    async fn start_exchange_connector(&self, exchange: Exchange) -> Result<()> {
+        Self::launch_connector(&self.connector_context(), exchange).await
+    }
+
+    async fn stop_exchange_connector(&self, exchange: &Exchange) {
+        Self::halt_connector(&self.connector_context(), exchange).await;
+    }
+
+    /// Starts the connector registered for `exchange` and its price level processor, which
+    /// `shutdown` stops with the other connectors. An exchange without a registered connector is
+    /// left stopped.
+    async fn launch_connector(context: &ConnectorContext, exchange: Exchange) -> Result<()> {
         info!("Starting exchange connector for {}", exchange);
 
-        let (price_level_tx, price_level_rx) = mpsc::channel(10000);
-        let mut handles = Vec::new();
+        let config = context.config.read().await.clone();
+        let exchange_config = config.exchanges.get(&exchange).cloned().unwrap_or_default();
+        let registered = context
+            .connectors
+            .create(&exchange, &exchange_config)
+            .await
+            .transpose()?;
 
-        let processor_handle = self
-            .start_price_level_processor(exchange.clone(), price_level_rx)
-            .await?;
-        handles.push(processor_handle);
-
-        match exchange {
-            Exchange::Binance => {
-                // Use the actual Binance connector implementation
-                // For now, we'll create a placeholder that demonstrates the pattern
-                let handle = tokio::spawn(async move {
-                    info!("Binance connector started");
-                    // This would use exchange_connectors::Binance::new().spawn_order_book_service()
-                    // with the trading pairs from config
-                    loop {
-                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                        // Simulate price level updates
-                        let update = PriceLevelUpdate {
-                            id: uuid::Uuid::new_v4(),
-                            symbol: "BTCUSDT".to_string(),
-                            exchange: Exchange::Binance,
-                            bids: vec![],
-                            asks: vec![],
-                            timestamp: chrono::Utc::now(),
-                        };
-                        if price_level_tx.send(update).await.is_err() {
-                            break;
-                        }
-                    }
-                    Ok(())
-                });
-                handles.push(handle);
+        let Some(service) = registered else {
+            warn!(
+                "No connector registered for {}, see exchange_connectors::register_all",
+                exchange
+            );
+            return Ok(());
+        };
+
+        let (price_level_tx, connector_stop_rx) = Self::launch_feed(context, &exchange).await?;
+        let spawned = service
+            .spawn_order_book_service(
+                &config.trading_pairs,
+                config.orderbook.max_depth,
+                exchange_config.websocket.buffer_size,
+                price_level_tx,
+                connector_stop_rx,
+            )
+            .await;
+        let handles = match spawned {
+            Ok(handles) => handles,
+            Err(e) => {
+                // Otherwise the exchange would look running with nothing feeding it
+                Self::retire_connector(context, &exchange).await;
+                return Err(e);
             }
-            Exchange::Bybit => {
-                let handle = tokio::spawn(async move {
-                    info!("Bybit connector started");
-                    // This would use exchange_connectors::Bybit::new().spawn_order_book_service()
-                    loop {
-                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                        let update = PriceLevelUpdate {
-                            id: uuid::Uuid::new_v4(),
-                            symbol: "BTCUSDT".to_string(),
-                            exchange: Exchange::Bybit,
-                            bids: vec![],
-                            asks: vec![],
-                            timestamp: chrono::Utc::now(),
-                        };
-                        if price_level_tx.send(update).await.is_err() {
-                            break;
-                        }
+        };
+        for handle in handles {
+            context.shutdown.track(
+                ShutdownStage::Connectors,
+                format!("{} connector", exchange),
+                handle,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Registers `exchange` as running and starts its price level processor, returning the
+    /// sender its updates go to and the receiver its connector is stopped through. The
+    /// connector stops once the processor closes its receiver.
+    async fn launch_feed(
+        context: &ConnectorContext,
+        exchange: &Exchange,
+    ) -> Result<(mpsc::Sender<PriceLevelUpdate>, broadcast::Receiver<()>)> {
+        let (price_level_tx, price_level_rx) = mpsc::channel(10000);
+        let (stop_sender, stop_rx) = broadcast::channel(1);
+        let (stopped_tx, stopped_rx) = oneshot::channel();
+        let connector_stop_rx = stop_sender.subscribe();
+        context.connector_stops.write().await.insert(
+            exchange.clone(),
+            RunningConnector {
+                stop: stop_sender,
+                stopped: stopped_rx,
+            },
+        );
+
+        let processor_handle = Self::launch_price_level_processor(
+            context,
+            exchange.clone(),
+            price_level_rx,
+            stop_rx,
+            stopped_tx,
+        )
+        .await?;
+        context.shutdown.track(
+            ShutdownStage::Connectors,
+            format!("{} price level processor", exchange),
+            processor_handle,
+        );
+
+        Ok((price_level_tx, connector_stop_rx))
+    }
+
+    /// Hands the updates of `source` to a price level processor per exchange, started as each
+    /// exchange first appears, waiting between them as `pace` asks
+    fn start_replay_feed(
+        &self,
+        mut source: Box<dyn ReplaySource>,
+        pace: ReplayPace,
+    ) -> JoinHandle<Result<()>> {
+        let context = self.connector_context();
+        let clock = self.clock.clone();
+        let report_sender = self.events.sender::<ReplayReport>();
+        let stopping = self.shutdown.signal(ShutdownStage::Connectors);
+
+        tokio::spawn(async move {
+            tokio::pin!(stopping);
+            let mut feeds: HashMap<Exchange, mpsc::Sender<PriceLevelUpdate>> = HashMap::new();
+            let mut report = ReplayReport::default();
+            let mut previous: Option<DateTime<Utc>> = None;
+
+            report.completed = loop {
+                let next = tokio::select! {
+                    next = source.next_update() => next,
+                    _ = &mut stopping => break false,
+                };
+                let update = match next {
+                    Some(Ok(update)) => update,
+                    Some(Err(e)) => {
+                        warn!("Skipping replayed update: {}", e);
+                        context.errors.record("replay", None, &e, clock.now());
+                        report.skipped += 1;
+                        continue;
                     }
-                    Ok(())
-                });
-                handles.push(handle);
-            }
-            Exchange::Kraken => {
-                let handle = tokio::spawn(async move {
-                    info!("Kraken connector started");
-                    // This would use exchange_connectors::Kraken::new().spawn_order_book_service()
-                    loop {
-                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                        let update = PriceLevelUpdate {
-                            id: uuid::Uuid::new_v4(),
-                            symbol: "BTCUSDT".to_string(),
-                            exchange: Exchange::Kraken,
-                            bids: vec![],
-                            asks: vec![],
-                            timestamp: chrono::Utc::now(),
-                        };
-                        if price_level_tx.send(update).await.is_err() {
-                            break;
-                        }
+                    None => break true,
+                };
+
+                if let Some(delay) =
+                    previous.and_then(|previous| pace.delay(previous, update.timestamp))
+                {
+                    tokio::select! {
+                        _ = clock.sleep(delay) => {}
+                        _ = &mut stopping => break false,
                     }
-                    Ok(())
-                });
-                handles.push(handle);
-            }
-            _ => {
-                warn!("Exchange connector not implemented for {}", exchange);
+                }
+                previous = Some(update.timestamp);
+
+                if !feeds.contains_key(&update.exchange) {
+                    let (feed, _) = Self::launch_feed(&context, &update.exchange).await?;
+                    feeds.insert(update.exchange.clone(), feed);
+                }
+                // Fails once the exchange was stopped, so the rest of its updates are skipped
+                match feeds[&update.exchange].send(update).await {
+                    Ok(()) => report.updates += 1,
+                    Err(_) => report.skipped += 1,
+                }
+            };
+
+            // Closing the feeds lets each processor forward what is queued and stop
+            let mut connector_stops = context.connector_stops.write().await;
+            for exchange in feeds.into_keys() {
+                connector_stops.remove(&exchange);
             }
+            drop(connector_stops);
+
+            info!(
+                "Replay {} after {} updates, {} skipped",
+                if report.completed {
+                    "finished"
+                } else {
+                    "stopped"
+                },
+                report.updates,
+                report.skipped
+            );
+            // Sending only fails when nobody is subscribed
+            let _ = report_sender.send(report);
+            Ok(())
+        })
+    }
+
+    async fn halt_connectors(&self) {
+        let context = self.connector_context();
+        let running: Vec<Exchange> = context
+            .connector_stops
+            .read()
+            .await
+            .keys()
+            .cloned()
+            .collect();
+        for exchange in running {
+            Self::halt_connector(&context, &exchange).await;
         }
+    }
 
-        Ok(handles)
+    /// Signals the connector of `exchange` to stop, returning what resolves once its price level
+    /// processor has forwarded the updates already queued
+    async fn halt_connector(
+        context: &ConnectorContext,
+        exchange: &Exchange,
+    ) -> Option<oneshot::Receiver<()>> {
+        let running = context.connector_stops.write().await.remove(exchange)?;
+        info!("Stopping exchange connector for {}", exchange);
+        // Sending only fails when the connector has already stopped
+        let _ = running.stop.send(());
+        Some(running.stopped)
     }
 
-    async fn start_price_level_processor(
-        &self,
+    /// Stops the connector of `exchange` and drops its levels once every update it queued has
+    /// been applied, so none of them land in the books after the clear
+    async fn retire_connector(context: &ConnectorContext, exchange: &Exchange) {
+        if let Some(stopped) = Self::halt_connector(context, exchange).await {
+            // Fails if the processor ended without signalling, which is just as final
+            let _ = stopped.await;
+            context.updates.applied().await;
+        }
+        Self::clear_exchange(context, exchange).await;
+    }
+
+    /// Drops the levels and summaries of `exchange` from every pair, so none of its quotes
+    /// outlive its connector
+    async fn clear_exchange(context: &ConnectorContext, exchange: &Exchange) {
+        context.books.write().await.remove_exchange(exchange).await;
+        context.summaries.write().await.retain(|_, by_exchange| {
+            by_exchange.remove(exchange);
+            !by_exchange.is_empty()
+        });
+    }
+
+    async fn launch_price_level_processor(
+        context: &ConnectorContext,
         exchange: Exchange,
         mut price_level_rx: mpsc::Receiver<PriceLevelUpdate>,
+        mut stop_rx: broadcast::Receiver<()>,
+        stopped_tx: oneshot::Sender<()>,
     ) -> Result<JoinHandle<Result<()>>> {
-        let summary_sender = self.summary_sender.clone();
-        let health_status = self.health_status.clone();
-        let metrics = self.metrics.clone();
-        let mut shutdown_rx = self.shutdown_sender.subscribe();
+        let updates = context.updates.clone();
+        let health_status = context.health_status.clone();
+        let symbol_updates = context.symbol_updates.clone();
+        let metrics = context.metrics.clone();
+        let metrics_history = context.metrics_history.clone();
+        let registry = context.registry.clone();
+        let errors = context.errors.clone();
+        let clock = context.clock.clone();
+        let stopping = context.shutdown.signal(ShutdownStage::Connectors);
 
         let handle = tokio::spawn(async move {
-            let mut last_update = chrono::Utc::now();
-            let mut update_count = 0u64;
+            tokio::pin!(stopping);
+            // Once stopped, updates already queued are still forwarded
+            let mut draining = false;
+            let mut latency = LatencyTracker::default();
+            let exchange_label = exchange.to_string();
+            let update_errors = registry.counter(
+                "aggregator_price_update_errors_total",
+                "Price level updates from an exchange that could not be processed",
+                &[("exchange", &exchange_label)],
+            );
+            let updates_per_second_gauge = registry.gauge(
+                "aggregator_updates_per_second",
+                "Price level updates an exchange sent per second, over the last full second",
+                &[("exchange", &exchange_label)],
+            );
+            // Updates are counted over windows of at least a second to derive the rate
+            let mut window_start = clock.now();
+            let mut window_count = 0u64;
+            let mut updates_per_second = 0.0;
 
             loop {
                 tokio::select! {
-                    Some(update) = price_level_rx.recv() => {
-                        // Process price level update
-                        match Self::process_price_level_update(update, &summary_sender).await {
+                    received = price_level_rx.recv() => {
+                        // Empty once the connector is gone, or once drained after a stop
+                        let Some(update) = received else {
+                            break;
+                        };
+                        let symbol = update.symbol.clone();
+                        let labels = [("exchange", exchange_label.as_str()), ("symbol", symbol.as_str())];
+                        let latency_ms = update.receive_latency_ms();
+                        if let Some(latency_ms) = latency_ms {
+                            latency.record(latency_ms);
+                            registry
+                                .histogram(
+                                    "aggregator_feed_latency_ms",
+                                    "Time from an exchange emitting an update to receiving it",
+                                    &labels,
+                                )
+                                .observe(latency_ms);
+                        }
+
+                        // Hand the update to the aggregation processor
+                        match Self::process_price_level_update(update, &updates) {
                             Ok(_) => {
-                                update_count += 1;
-                                last_update = chrono::Utc::now();
+                                let last_update = clock.now();
+                                metrics_history.record_update(&exchange, &symbol, last_update, latency_ms);
+                                symbol_updates
+                                    .write()
+                                    .await
+                                    .insert((exchange.clone(), symbol.clone()), last_update);
+                                registry
+                                    .counter(
+                                        "aggregator_price_updates_total",
+                                        "Price level updates received from an exchange",
+                                        &labels,
+                                    )
+                                    .inc();
+                                window_count += 1;
+                                let elapsed = last_update - window_start;
+                                if elapsed >= chrono::Duration::seconds(1) {
+                                    updates_per_second = window_count as f64
+                                        / (elapsed.num_microseconds().unwrap_or(i64::MAX) as f64 / 1e6);
+                                    updates_per_second_gauge.set(updates_per_second);
+                                    window_start = last_update;
+                                    window_count = 0;
+                                }
 
-                                // Update health status
+                                // Update health status, tracking exchanges first seen here such as
+                                // custom ones
                                 let mut health = health_status.write().await;
-                                if let Some(status) = health.get_mut(&exchange) {
-                                    status.is_healthy = true;
-                                    status.last_update = last_update;
-                                    status.error_message = None;
-                                }
+                                let status = health.entry(exchange.clone()).or_insert_with(|| HealthStatus {
+                                    exchange: exchange.clone(),
+                                    is_healthy: true,
+                                    last_update,
+                                    error_message: None,
+                                });
+                                status.is_healthy = true;
+                                status.last_update = last_update;
+                                status.error_message = None;
+                                Self::record_health_gauge(&registry, status);
 
                                 // Update metrics
                                 let mut metrics_map = metrics.write().await;
-                                if let Some(metric) = metrics_map.get_mut(&exchange) {
-                                    metric.updates_per_second = update_count as f64 / last_update.timestamp() as f64;
-                                    metric.last_update = last_update;
+                                let metric = metrics_map.entry(exchange.clone()).or_insert_with(|| Metrics {
+                                    exchange: exchange.clone(),
+                                    symbol: symbol.clone(),
+                                    updates_per_second: 0.0,
+                                    latency_ms: 0.0,
+                                    latency_p50_ms: 0.0,
+                                    latency_p95_ms: 0.0,
+                                    latency_p99_ms: 0.0,
+                                    error_count: 0,
+                                    last_update,
+                                });
+                                metric.symbol = symbol;
+                                metric.updates_per_second = updates_per_second;
+                                metric.last_update = last_update;
+                                if !latency.is_empty() {
+                                    metric.latency_ms = latency.mean();
+                                    metric.latency_p50_ms = latency.percentile(50.0);
+                                    metric.latency_p95_ms = latency.percentile(95.0);
+                                    metric.latency_p99_ms = latency.percentile(99.0);
                                 }
                             }
                             Err(e) => {
                                 error!("Failed to process price level update: {}", e);
+                                update_errors.inc();
+                                let now = clock.now();
+                                errors.record("price_level_processor", Some(&exchange), &e, now);
+                                metrics_history.record_error(&exchange, &symbol, now);
+                                if let Some(metric) = metrics.write().await.get_mut(&exchange) {
+                                    metric.error_count += 1;
+                                }
 
                                 // Update health status with error
                                 let mut health = health_status.write().await;
                                 if let Some(status) = health.get_mut(&exchange) {
                                     status.is_healthy = false;
                                     status.error_message = Some(e.to_string());
+                                    Self::record_health_gauge(&registry, status);
                                 }
                             }
                         }
                     }
-                    _ = shutdown_rx.recv() => {
+                    _ = &mut stopping, if !draining => {
                         info!("Price level processor for {} shutting down", exchange);
-                        break;
+                        price_level_rx.close();
+                        draining = true;
+                    }
+                    _ = stop_rx.recv(), if !draining => {
+                        info!("Price level processor for {} stopped", exchange);
+                        price_level_rx.close();
+                        draining = true;
                     }
                 }
             }
 
+            // Sending only fails when nobody waits for the processor to finish
+            let _ = stopped_tx.send(());
             Ok(())
         });
 
         Ok(handle)
     }
 
-    async fn process_price_level_update(
-        update: PriceLevelUpdate,
-        summary_sender: &broadcast::Sender<Summary>,
-    ) -> Result<()> {
-        // Create a summary from the price level update
-        let mut bids = Vec::new();
-        let mut asks = Vec::new();
-
-        for bid in update.bids {
-            bids.push(crate::types::PriceLevel {
-                price: bid.price,
-                quantity: bid.quantity,
-                exchange: bid.exchange,
-                timestamp: bid.timestamp,
-            });
+    fn process_price_level_update(update: PriceLevelUpdate, updates: &UpdateFeed) -> Result<()> {
+        updates.send(update)
+    }
+
+    /// Loads the books and metrics of the last run, returning the books of configured pairs for
+    /// the aggregation processor to rebuild. If the storage fails to load, the aggregator
+    /// starts cold.
+    async fn restore(&self) -> Vec<BookSnapshot> {
+        let Some(storage) = &self.storage else {
+            return Vec::new();
+        };
+
+        let config = self.config().await;
+        let snapshots: Vec<BookSnapshot> = match storage.load_book_snapshots().await {
+            Ok(snapshots) => snapshots
+                .into_iter()
+                .filter(|snapshot| config.trading_pairs.contains(&snapshot.pair))
+                .collect(),
+            Err(e) => {
+                warn!("Failed to restore books from {}: {}", storage.name(), e);
+                self.record_error("storage", None, &e);
+                Vec::new()
+            }
+        };
+        let mut summaries = self.summaries.write().await;
+        for snapshot in &snapshots {
+            summaries
+                .entry(snapshot.pair.clone())
+                .or_default()
+                .insert(snapshot.exchange.clone(), snapshot.summary.clone());
         }
+        drop(summaries);
 
-        for ask in update.asks {
-            asks.push(crate::types::PriceLevel {
-                price: ask.price,
-                quantity: ask.quantity,
-                exchange: ask.exchange,
-                timestamp: ask.timestamp,
-            });
+        match storage.load_metrics().await {
+            Ok(restored) => {
+                let mut metrics = self.metrics.write().await;
+                for metric in restored {
+                    metrics.insert(metric.exchange.clone(), metric);
+                }
+            }
+            Err(e) => {
+                warn!("Failed to restore metrics from {}: {}", storage.name(), e);
+                self.record_error("storage", None, &e);
+            }
         }
 
-        let spread = if let (Some(best_bid), Some(best_ask)) = (bids.first(), asks.first()) {
-            best_ask.price - best_bid.price
-        } else {
-            0.0
-        };
+        let retention = &config.metrics.history;
+        if retention.enabled {
+            let since = self.clock.now()
+                - chrono::Duration::seconds(retention.retention_secs.min(i64::MAX as u64) as i64);
+            match storage.load_metrics_history(since).await {
+                Ok(samples) => self.metrics_history.restore(samples),
+                Err(e) => {
+                    warn!(
+                        "Failed to restore metrics history from {}: {}",
+                        storage.name(),
+                        e
+                    );
+                    self.record_error("storage", None, &e);
+                }
+            }
+        }
 
-        let summary = Summary {
-            symbol: update.symbol,
-            spread,
-            bids,
-            asks,
-            timestamp: update.timestamp,
+        info!(
+            "Restored {} exchange books from {}",
+            snapshots.len(),
+            storage.name()
+        );
+        snapshots
+    }
+
+    /// Snapshots state every `config.storage.snapshot_interval_secs`, and once more on
+    /// shutdown, when a storage is set
+    async fn start_snapshotter(&self) -> Result<Option<JoinHandle<Result<()>>>> {
+        let Some(storage) = self.storage.clone() else {
+            return Ok(None);
         };
+        let config = self.config.clone();
+        let summaries = self.summaries.clone();
+        let metrics = self.metrics.clone();
+        let metrics_history = self.metrics_history.clone();
+        // Starts collecting the samples closed from now on
+        metrics_history.take_completed();
+        let errors = self.errors.clone();
+        let clock = self.clock.clone();
+        let interval_secs = self.config().await.storage.snapshot_interval_secs.max(1);
+        let mut opportunity_rx = self.events.subscribe::<ArbitrageOpportunity>();
+        let stopping = self.shutdown.signal(ShutdownStage::Persistence);
+        let mut interval = Ticker::delayed(
+            self.clock.clone(),
+            std::time::Duration::from_secs(interval_secs),
+        );
 
-        summary_sender
-            .send(summary)
-            .map_err(|e| AggregatorError::ChannelSend {
-                message: format!("Failed to send summary: {}", e),
-            })?;
+        let handle = tokio::spawn(async move {
+            tokio::pin!(stopping);
+            // Opportunities found since the last snapshot that saved them
+            let mut pending: Vec<ArbitrageOpportunity> = Vec::new();
+            // Likewise for metrics samples
+            let mut pending_samples: Vec<MetricsSample> = Vec::new();
+
+            loop {
+                let shutting_down = tokio::select! {
+                    received = opportunity_rx.recv() => {
+                        match received {
+                            Ok(opportunity) => pending.push(opportunity),
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                warn!("Snapshotter lagged, skipped {} opportunities", skipped);
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        }
+                        continue;
+                    }
+                    _ = interval.tick() => false,
+                    _ = &mut stopping => true,
+                };
+
+                match Self::write_snapshot(
+                    storage.as_ref(),
+                    &config,
+                    &summaries,
+                    &metrics,
+                    &pending,
+                )
+                .await
+                {
+                    Ok(()) => pending.clear(),
+                    Err(e) => {
+                        error!("Failed to snapshot to {}: {}", storage.name(), e);
+                        errors.record("storage", None, &e, clock.now());
+                        // Keep what could not be saved for the next attempt, within the retention
+                        let keep = config.read().await.storage.max_opportunities;
+                        let excess = pending.len().saturating_sub(keep);
+                        pending.drain(..excess);
+                    }
+                }
+
+                let now = clock.now();
+                metrics_history.flush(now);
+                pending_samples.extend(metrics_history.take_completed());
+                let retention = config.read().await.metrics.history.clone();
+                let cutoff = now
+                    - chrono::Duration::seconds(
+                        retention.retention_secs.min(i64::MAX as u64) as i64
+                    );
+                match Self::write_metrics_history(storage.as_ref(), &pending_samples, cutoff).await
+                {
+                    Ok(()) => pending_samples.clear(),
+                    Err(e) => {
+                        error!(
+                            "Failed to save metrics history to {}: {}",
+                            storage.name(),
+                            e
+                        );
+                        errors.record("storage", None, &e, clock.now());
+                        pending_samples.retain(|sample| sample.timestamp >= cutoff);
+                    }
+                }
+                if shutting_down {
+                    info!("Snapshotter shutting down");
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        Ok(Some(handle))
+    }
 
+    /// Saves every exchange's book, the consolidated summaries, the metrics and `opportunities`
+    async fn write_snapshot(
+        storage: &dyn Storage,
+        config: &RwLock<Arc<Config>>,
+        summaries: &RwLock<HashMap<TradingPair, HashMap<Exchange, Summary>>>,
+        metrics: &RwLock<HashMap<Exchange, Metrics>>,
+        opportunities: &[ArbitrageOpportunity],
+    ) -> Result<()> {
+        let config = config.read().await.clone();
+        let (books, consolidated) = {
+            let summaries = summaries.read().await;
+            let books: Vec<BookSnapshot> = summaries
+                .iter()
+                .flat_map(|(pair, by_exchange)| {
+                    by_exchange.iter().map(|(exchange, summary)| BookSnapshot {
+                        pair: pair.clone(),
+                        exchange: exchange.clone(),
+                        summary: summary.clone(),
+                    })
+                })
+                .collect();
+            let consolidated: Vec<Summary> = summaries
+                .values()
+                .filter_map(|by_exchange| {
+                    Self::consolidate(by_exchange, config.orderbook.max_depth)
+                })
+                .collect();
+            (books, consolidated)
+        };
+        let metrics: Vec<Metrics> = metrics.read().await.values().cloned().collect();
+
+        storage.save_book_snapshots(&books).await?;
+        storage.save_summaries(&consolidated).await?;
+        storage.save_metrics(&metrics).await?;
+        if !opportunities.is_empty() {
+            storage.save_opportunities(opportunities).await?;
+            storage
+                .prune_opportunities(config.storage.max_opportunities)
+                .await?;
+        }
         Ok(())
     }
 
+    /// Saves `samples` and deletes those of intervals starting before `cutoff`
+    async fn write_metrics_history(
+        storage: &dyn Storage,
+        samples: &[MetricsSample],
+        cutoff: DateTime<Utc>,
+    ) -> Result<()> {
+        if !samples.is_empty() {
+            storage.save_metrics_history(samples).await?;
+        }
+        storage.prune_metrics_history(cutoff).await
+    }
+
     async fn start_aggregation_processor(&self) -> Result<JoinHandle<Result<()>>> {
+        let restored = self.restore().await;
         let summaries = self.summaries.clone();
-        let mut summary_rx = self.summary_sender.subscribe();
-        let mut shutdown_rx = self.shutdown_sender.subscribe();
+        let summary_sender = self.events.sender::<Summary>();
+        let config = self.config.clone();
+        let registry = self.registry.clone();
+        let errors = self.errors.clone();
+        let books = self.books.clone();
+        let fx = self.fx.clone();
+        let circuit_breaker = self.circuit_breaker.clone();
+        let breaker_sender = self.events.sender::<CircuitBreakerEvent>();
+        let clock = self.clock.clone();
+        let (mut update_rx, applied_tx) = self.updates.subscribe_processor();
+        let stopping = self.shutdown.signal(ShutdownStage::Processing);
 
         let handle = tokio::spawn(async move {
+            // Rebuild the books of the last run before applying live updates on top
+            let current = config.read().await.clone();
+            for snapshot in &restored {
+                if let Err(e) = books
+                    .write()
+                    .await
+                    .apply(&snapshot.to_update(), &current)
+                    .await
+                {
+                    warn!(
+                        "Failed to restore {} book for {}: {}",
+                        snapshot.exchange, snapshot.pair, e
+                    );
+                }
+            }
+            drop(current);
+
+            tokio::pin!(stopping);
+            // Once stopped, updates already in the channel are still applied
+            let mut draining = false;
             loop {
-                tokio::select! {
-                    Ok(summary) = summary_rx.recv() => {
-                        // Update summaries map
-                        let pair = TradingPair::new(&summary.symbol, "USDT"); // Simplified
-                        let mut summaries_map = summaries.write().await;
-                        summaries_map.insert(pair, summary);
+                let received = if draining {
+                    match update_rx.try_recv() {
+                        Ok(update) => Ok(update),
+                        Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                            Err(broadcast::error::RecvError::Lagged(skipped))
+                        }
+                        Err(_) => break,
                     }
-                    _ = shutdown_rx.recv() => {
-                        info!("Aggregation processor shutting down");
-                        break;
+                } else {
+                    tokio::select! {
+                        received = update_rx.recv() => received,
+                        _ = &mut stopping => {
+                            info!("Aggregation processor shutting down");
+                            draining = true;
+                            continue;
+                        }
+                    }
+                };
+                let update = match received {
+                    Ok(update) => update,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Aggregation processor lagged, skipped {} updates", skipped);
+                        applied_tx.send_modify(|applied| *applied += skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let config = config.read().await.clone();
+                let mut pair_books = books.write().await;
+                match pair_books.apply(&update, &config).await {
+                    Ok(Some((pair, summary))) => {
+                        let depth = config.orderbook.max_depth;
+                        let exchange_summary = pair_books
+                            .exchange_summary(&pair, &update.exchange, depth)
+                            .await;
+                        drop(pair_books);
+                        fx.observe(&pair, &summary);
+                        let best_bid = summary
+                            .bids
+                            .iter()
+                            .map(|level| level.price)
+                            .reduce(f64::max);
+                        let best_ask = summary
+                            .asks
+                            .iter()
+                            .map(|level| level.price)
+                            .reduce(f64::min);
+                        if let (Some(bid), Some(ask)) = (best_bid, best_ask) {
+                            let mid = (bid + ask) / 2.0;
+                            if let Some(event) =
+                                circuit_breaker.observe_price(&summary.symbol, mid, clock.now())
+                            {
+                                warn!(
+                                    "Pausing arbitrage for {}: {:?}",
+                                    summary.symbol, event.state
+                                );
+                                // Sending only fails when nobody is subscribed
+                                let _ = breaker_sender.send(event);
+                            }
+                        }
+                        if let Some(exchange_summary) = exchange_summary {
+                            summaries
+                                .write()
+                                .await
+                                .entry(pair)
+                                .or_default()
+                                .insert(update.exchange.clone(), exchange_summary);
+                        }
+                        registry
+                            .gauge(
+                                "aggregator_spread",
+                                "Spread of a pair's consolidated book",
+                                &[("symbol", &summary.symbol)],
+                            )
+                            .set(summary.spread);
+                        // Sending only fails when nobody is subscribed
+                        let _ = summary_sender.send(summary);
+                    }
+                    Ok(None) => {
+                        warn!(
+                            "No trading pair for {} symbol {}",
+                            update.exchange, update.symbol
+                        );
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to apply {} update for {}: {}",
+                            update.exchange, update.symbol, e
+                        );
+                        errors.record("aggregation", Some(&update.exchange), &e, clock.now());
                     }
                 }
+                applied_tx.send_modify(|applied| *applied += 1);
             }
             Ok(())
         });
@@ -364,29 +1927,104 @@ impl Aggregator {
     }
 
     async fn start_arbitrage_detector(&self) -> Result<JoinHandle<Result<()>>> {
-        let arbitrage_sender = self.arbitrage_sender.clone();
+        let arbitrage_sender = self.events.sender::<ArbitrageOpportunity>();
         let summaries = self.summaries.clone();
-        let mut shutdown_rx = self.shutdown_sender.subscribe();
+        let registry = self.registry.clone();
+        let engines = self.engines.clone();
+        let config = self.config.clone();
+        let mut engine_config = self.config().await;
+        let clock = self.clock.clone();
+        let fx = self.fx.clone();
+        let mut built_in = Self::built_in_engines(&engine_config, &clock, &fx).await;
+        let health_status = self.health_status.clone();
+        let circuit_breaker = self.circuit_breaker.clone();
+        let breaker_sender = self.events.sender::<CircuitBreakerEvent>();
+        let stopping = self.shutdown.signal(ShutdownStage::Processing);
 
         let handle = tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
+            tokio::pin!(stopping);
+            let mut interval = Ticker::new(clock.clone(), std::time::Duration::from_secs(1));
 
             loop {
                 tokio::select! {
                     _ = interval.tick() => {
-                        // Check for arbitrage opportunities
-                        let summaries_map = summaries.read().await;
-
-                        // Simple arbitrage detection logic
-                        for (pair, summary) in summaries_map.iter() {
-                            if let Some(opportunity) = Self::detect_arbitrage_opportunity(pair, summary).await {
-                                if let Err(e) = arbitrage_sender.send(opportunity) {
-                                    error!("Failed to send arbitrage opportunity: {}", e);
+                        let now = clock.now();
+                        let healthy = health_status
+                            .read()
+                            .await
+                            .values()
+                            .filter(|status| status.is_healthy)
+                            .count();
+                        let changes = circuit_breaker
+                            .observe_healthy_exchanges(healthy, now)
+                            .into_iter()
+                            .chain(circuit_breaker.expire(now));
+                        for event in changes {
+                            match (&event.symbol, &event.state) {
+                                (Some(symbol), BreakerState::Closed) => {
+                                    info!("Resuming arbitrage for {}", symbol)
+                                }
+                                (None, BreakerState::Closed) => info!("Resuming arbitrage"),
+                                (_, BreakerState::Open(reason)) => {
+                                    warn!("Pausing arbitrage: {:?}", reason)
                                 }
                             }
+                            // Sending only fails when nobody is subscribed
+                            let _ = breaker_sender.send(event);
+                        }
+
+                        // Run every registered engine over the same snapshot of each exchange's
+                        // book, keyed `exchange:symbol`
+                        let batch: HashMap<String, Summary> = summaries
+                            .read()
+                            .await
+                            .values()
+                            .flat_map(|by_exchange| by_exchange.iter())
+                            .map(|(exchange, summary)| {
+                                (format!("{}:{}", exchange, summary.symbol), summary.clone())
+                            })
+                            .collect();
+                        let batch = Arc::new(batch);
+                        let opportunities = if engines.is_empty().await {
+                            // Pick up reloaded thresholds and fees
+                            let current = config.read().await.clone();
+                            if !Arc::ptr_eq(&current, &engine_config) {
+                                built_in = Self::built_in_engines(&current, &clock, &fx).await;
+                                engine_config = current;
+                            }
+                            built_in.run(batch).await
+                        } else {
+                            engines.run(batch).await
+                        };
+                        for opportunity in opportunities {
+                            if !circuit_breaker.allows(&opportunity.symbol, now) {
+                                registry
+                                    .counter(
+                                        "aggregator_arbitrage_suppressed_total",
+                                        "Arbitrage opportunities held back by the circuit breaker",
+                                        &[("symbol", opportunity.symbol.as_str())],
+                                    )
+                                    .inc();
+                                continue;
+                            }
+                            let buy_exchange = opportunity.buy_exchange.to_string();
+                            let sell_exchange = opportunity.sell_exchange.to_string();
+                            registry
+                                .counter(
+                                    "aggregator_arbitrage_opportunities_total",
+                                    "Arbitrage opportunities found between two exchanges",
+                                    &[
+                                        ("symbol", opportunity.symbol.as_str()),
+                                        ("buy_exchange", buy_exchange.as_str()),
+                                        ("sell_exchange", sell_exchange.as_str()),
+                                    ],
+                                )
+                                .inc();
+                            // Sending only fails when nobody is subscribed
+                            let _ = arbitrage_sender.send(opportunity);
                         }
                     }
-                    _ = shutdown_rx.recv() => {
+                    _ = &mut stopping => {
                         info!("Arbitrage detector shutting down");
                         break;
                     }
@@ -398,43 +2036,202 @@ impl Aggregator {
         Ok(handle)
     }
 
-    async fn detect_arbitrage_opportunity(
-        _pair: &TradingPair,
-        _summary: &Summary,
-    ) -> Option<ArbitrageOpportunity> {
-        // TODO: Implement actual arbitrage detection logic
-        // This would compare prices across exchanges and identify opportunities
-        None
+    fn record_health_gauge(registry: &MetricsRegistry, status: &HealthStatus) {
+        registry
+            .gauge(
+                "aggregator_exchange_healthy",
+                "Whether an exchange feed is healthy, 1 if so and 0 if not",
+                &[("exchange", &status.exchange.to_string())],
+            )
+            .set(if status.is_healthy { 1.0 } else { 0.0 });
     }
 
+    async fn built_in_engines(
+        config: &Config,
+        clock: &Arc<dyn Clock>,
+        fx: &FxRates,
+    ) -> AnalysisEngineRegistry {
+        let mut engine = TopOfBookEngine::from_config(config).with_clock(clock.clone());
+        if config.fx.enabled {
+            engine = engine.with_fx(fx.clone());
+        }
+        let built_in = AnalysisEngineRegistry::new();
+        built_in.register(Arc::new(engine)).await;
+        built_in
+    }
+
+    /// Checks every `health.check_interval_secs` which exchanges have gone silent, marking them
+    /// unhealthy and restarting their connectors up to `health.max_restarts` times, and
+    /// publishes the latest metrics and, when its level changes, the `SystemHealth`
     async fn start_health_monitor(&self) -> Result<JoinHandle<Result<()>>> {
-        let health_status = self.health_status.clone();
-        let mut shutdown_rx = self.shutdown_sender.subscribe();
+        let config = self.config.clone();
+        let context = self.connector_context();
+        let restarts = self.restarts.clone();
+        let health_sender = self.events.sender::<HealthStatus>();
+        let metrics_sender = self.events.sender::<Metrics>();
+        let system_health_sender = self.events.sender::<SystemHealth>();
+        let stopping = self.shutdown.signal(ShutdownStage::Connectors);
+        let mut check_interval_secs = self.config().await.health.check_interval_secs.max(1);
 
         let handle = tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(10));
+            tokio::pin!(stopping);
+            let mut interval = Ticker::new(
+                context.clock.clone(),
+                std::time::Duration::from_secs(check_interval_secs),
+            );
+            let mut level = None;
 
             loop {
                 tokio::select! {
                     _ = interval.tick() => {
-                        // Check health status of all exchanges
-                        let mut health_map = health_status.write().await;
-                        let now = chrono::Utc::now();
+                        let current = config.read().await.clone();
+                        let settings = &current.health;
+                        let now = context.clock.now();
+                        let stale_after = chrono::Duration::seconds(settings.stale_after_secs as i64);
 
+                        // An exchange is silent once all the symbols it updated are stale, or
+                        // when it never updated one for as long
+                        let mut latest: HashMap<Exchange, DateTime<Utc>> = HashMap::new();
+                        for ((exchange, _), updated) in context.symbol_updates.read().await.iter() {
+                            let entry = latest.entry(exchange.clone()).or_insert(*updated);
+                            *entry = (*entry).max(*updated);
+                        }
+                        let mut health_map = context.health_status.write().await;
+                        let mut silent = Vec::new();
                         for (exchange, status) in health_map.iter_mut() {
-                            let time_since_update = now - status.last_update;
+                            let last_activity = latest.get(exchange).copied().unwrap_or(status.last_update);
+                            if now - last_activity <= stale_after {
+                                Self::record_health_gauge(&context.registry, status);
+                                continue;
+                            }
+                            silent.push(exchange.clone());
 
-                            // Mark as unhealthy if no updates for more than 30 seconds
-                            if time_since_update.num_seconds() > 30 {
+                            // Publish the change to unhealthy once
+                            if status.is_healthy {
                                 status.is_healthy = false;
                                 if status.error_message.is_none() {
-                                    status.error_message = Some("No recent updates".to_string());
+                                    status.error_message = Some(format!(
+                                        "No updates for {} seconds",
+                                        settings.stale_after_secs
+                                    ));
                                 }
                                 warn!("Exchange {} marked as unhealthy", exchange);
+                                // Sending only fails when nobody is subscribed
+                                let _ = health_sender.send(status.clone());
+                            }
+                            Self::record_health_gauge(&context.registry, status);
+                        }
+
+                        // Restart the connectors of silent exchanges, forgetting the attempts of
+                        // those that recovered or stopped
+                        let running: Vec<Exchange> =
+                            context.connector_stops.read().await.keys().cloned().collect();
+                        let mut restart_states = restarts.write().await;
+                        restart_states.retain(|exchange, _| {
+                            running.contains(exchange)
+                                && !health_map.get(exchange).is_some_and(|status| status.is_healthy)
+                        });
+                        drop(health_map);
+                        let mut to_restart = Vec::new();
+                        for exchange in running.into_iter().filter(|exchange| silent.contains(exchange)) {
+                            if settings.max_restarts == 0 {
+                                break;
                             }
+                            let state = restart_states.entry(exchange.clone()).or_insert(RestartState {
+                                attempts: 0,
+                                last_attempt: DateTime::<Utc>::MIN_UTC,
+                                gave_up: false,
+                            });
+                            if state.attempts >= settings.max_restarts {
+                                if !state.gave_up {
+                                    error!(
+                                        "Giving up restarting the {} connector after {} attempts",
+                                        exchange, state.attempts
+                                    );
+                                    state.gave_up = true;
+                                }
+                                continue;
+                            }
+                            if now - state.last_attempt
+                                < chrono::Duration::seconds(settings.restart_backoff_secs as i64)
+                            {
+                                continue;
+                            }
+                            state.attempts += 1;
+                            state.last_attempt = now;
+                            warn!(
+                                "Restarting the {} connector (attempt {} of {})",
+                                exchange, state.attempts, settings.max_restarts
+                            );
+                            to_restart.push(exchange);
+                        }
+                        drop(restart_states);
+                        for exchange in to_restart {
+                            // A reconfiguration may have stopped or disabled it since it was picked
+                            let _reconfiguring = context.reconfiguring.lock().await;
+                            let enabled = context
+                                .config
+                                .read()
+                                .await
+                                .exchanges
+                                .get(&exchange)
+                                .is_some_and(|exchange_config| exchange_config.enabled);
+                            let running = context.connector_stops.read().await.contains_key(&exchange);
+                            if !enabled || !running {
+                                continue;
+                            }
+                            context
+                                .registry
+                                .counter(
+                                    "aggregator_connector_restarts_total",
+                                    "Connector restarts attempted after an exchange went silent",
+                                    &[("exchange", &exchange.to_string())],
+                                )
+                                .inc();
+                            Self::retire_connector(&context, &exchange).await;
+                            if let Err(e) = Self::launch_connector(&context, exchange.clone()).await {
+                                error!("Failed to restart the {} connector: {}", exchange, e);
+                                context.errors.record("connector", Some(&exchange), &e, now);
+                            }
+                        }
+
+                        let system_health = Self::assess_health(
+                            &current,
+                            &context.health_status,
+                            &context.symbol_updates,
+                            &restarts,
+                            now,
+                        )
+                        .await;
+                        context
+                            .registry
+                            .gauge(
+                                "aggregator_stale_symbols",
+                                "Symbols of enabled exchanges with no recent update",
+                                &[],
+                            )
+                            .set(system_health.stale_symbols().count() as f64);
+                        if level != Some(system_health.level) {
+                            level = Some(system_health.level);
+                            // Sending only fails when nobody is subscribed
+                            let _ = system_health_sender.send(system_health);
+                        }
+
+                        for metric in context.metrics.read().await.values() {
+                            let _ = metrics_sender.send(metric.clone());
+                        }
+
+                        // Pick up a reloaded check interval
+                        let reloaded = settings.check_interval_secs.max(1);
+                        if reloaded != check_interval_secs {
+                            check_interval_secs = reloaded;
+                            interval = Ticker::delayed(
+                                context.clock.clone(),
+                                std::time::Duration::from_secs(check_interval_secs),
+                            );
                         }
                     }
-                    _ = shutdown_rx.recv() => {
+                    _ = &mut stopping => {
                         info!("Health monitor shutting down");
                         break;
                     }
@@ -446,3 +2243,7 @@ impl Aggregator {
         Ok(handle)
     }
 }
+
+#[cfg(test)]
+#[path = "../tests/aggregator-core/aggregator_tests.rs"]
+mod aggregator_tests;
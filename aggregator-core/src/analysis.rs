@@ -0,0 +1,406 @@
+//! Pluggable analysis engines run by the aggregator's arbitrage loop
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+use tracing::warn;
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::{AnalysisConfig, Config, FeeSchedule};
+use crate::fx::FxRates;
+use crate::types::{ArbitrageOpportunity, Exchange, PriceLevel, Summary};
+use crate::Result;
+
+#[async_trait]
+/// Trait representing an analysis engine for processing market summaries and extracting insights.
+///
+/// Implementors of this trait are expected to provide asynchronous methods for analyzing
+/// collections of summaries, calculating spreads, and computing volume-weighted prices.
+///
+/// # Required Methods
+///
+/// - `analyze_summaries`: Analyzes a set of summaries and returns a list of arbitrage opportunities.
+/// - `calculate_spread`: Calculates the spread for a given summary, if possible.
+/// - `calculate_volume_weighted_price`: Computes the volume-weighted price for a given summary, if possible.
+///
+/// # Provided Methods
+///
+/// - `name`: Identifies the engine in logs, the type name by default.
+pub trait AnalysisEngine: Send + Sync {
+    async fn analyze_summaries(
+        &self,
+        summaries: &HashMap<String, Summary>,
+    ) -> Result<Vec<ArbitrageOpportunity>>;
+    async fn calculate_spread(&self, summary: &Summary) -> Option<f64>;
+    async fn calculate_volume_weighted_price(&self, summary: &Summary) -> Option<f64>;
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// The analysis engines registered with an aggregator. Cloning shares the registry, so engines
+/// registered after the aggregator started are picked up by its next run.
+#[derive(Clone, Default)]
+pub struct AnalysisEngineRegistry {
+    engines: Arc<RwLock<Vec<Arc<dyn AnalysisEngine>>>>,
+}
+
+impl AnalysisEngineRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn register(&self, engine: Arc<dyn AnalysisEngine>) {
+        self.engines.write().await.push(engine);
+    }
+
+    pub async fn len(&self) -> usize {
+        self.engines.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.engines.read().await.is_empty()
+    }
+
+    /// Runs every registered engine concurrently over `summaries` and merges their outputs.
+    /// When several engines report the same symbol, buy exchange and sell exchange, only the
+    /// most profitable report is kept. An engine that fails is logged and skipped, so it cannot
+    /// hold back the others. The merged opportunities are returned most profitable first.
+    pub async fn run(&self, summaries: Arc<HashMap<String, Summary>>) -> Vec<ArbitrageOpportunity> {
+        let engines = self.engines.read().await.clone();
+
+        let mut tasks = JoinSet::new();
+        for engine in engines {
+            let summaries = summaries.clone();
+            tasks.spawn(async move {
+                let result = engine.analyze_summaries(&summaries).await;
+                (engine.name().to_string(), result)
+            });
+        }
+
+        let mut merged: HashMap<(String, Exchange, Exchange), ArbitrageOpportunity> =
+            HashMap::new();
+        while let Some(joined) = tasks.join_next().await {
+            let opportunities = match joined {
+                Ok((_, Ok(opportunities))) => opportunities,
+                Ok((name, Err(e))) => {
+                    warn!("Analysis engine {} failed: {}", name, e);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Analysis engine task panicked: {}", e);
+                    continue;
+                }
+            };
+            for opportunity in opportunities {
+                let key = (
+                    opportunity.symbol.clone(),
+                    opportunity.buy_exchange.clone(),
+                    opportunity.sell_exchange.clone(),
+                );
+                match merged.get(&key) {
+                    Some(kept) if kept.profit_percentage >= opportunity.profit_percentage => {}
+                    _ => {
+                        merged.insert(key, opportunity);
+                    }
+                }
+            }
+        }
+
+        let mut opportunities: Vec<ArbitrageOpportunity> = merged.into_values().collect();
+        opportunities.sort_by(|a, b| b.profit_percentage.total_cmp(&a.profit_percentage));
+        opportunities
+    }
+}
+
+/// Time-to-live of an opportunity when no maximum book age is configured
+const DEFAULT_VALIDITY_WINDOW_MS: f64 = 1000.0;
+
+/// Best bid and ask of each exchange for one symbol, with the time of the book they came from
+type Best<'a> = HashMap<Exchange, (&'a PriceLevel, DateTime<Utc>)>;
+
+/// Best bids and best asks of one symbol
+type Sides<'a> = (Best<'a>, Best<'a>);
+
+/// The engine the aggregator runs when no other engine is registered.
+///
+/// For every symbol it compares the best ask of each exchange with the best bid of every other,
+/// and reports buying at the ask and selling at the bid when that clears the thresholds of
+/// `config.analysis`. Net of fees, each leg pays its exchange's taker fee. Books older than
+/// `max_opportunity_age_ms` are skipped, and an opportunity's time-to-live is what remains of
+/// that age (one second by default) once the older book's age is taken off.
+///
+/// With FX rates set by `with_fx`, books of the same base asset quoted in different currencies
+/// are compared too, such as BTC/USDT on one exchange against BTC/USDC on another. Their prices
+/// are converted to the reference currency and reported under the base asset and that currency,
+/// `BTCUSD` for a USD reference. Books whose quote has no rate to the reference are left out.
+///
+/// `analysis_tools::DefaultAnalysisEngine` also measures exchange latency and walks the books
+/// for size; registering it, or any other engine, replaces this one.
+#[derive(Debug, Clone)]
+pub struct TopOfBookEngine {
+    analysis: AnalysisConfig,
+    fees: HashMap<Exchange, FeeSchedule>,
+    /// Books' ages are measured against it
+    clock: Arc<dyn Clock>,
+    /// Converts prices for cross-quote comparisons, which are skipped without it
+    fx: Option<FxRates>,
+    /// Quote currencies symbols are split on, longest first
+    quotes: Vec<String>,
+}
+
+impl TopOfBookEngine {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            analysis: config.analysis.clone(),
+            fees: config
+                .exchanges
+                .iter()
+                .map(|(exchange, exchange_config)| (exchange.clone(), exchange_config.fees.clone()))
+                .collect(),
+            clock: SystemClock::shared(),
+            fx: None,
+            quotes: Self::known_quotes(config),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Also compares books across quote currencies, converting their prices with `fx`
+    pub fn with_fx(mut self, fx: FxRates) -> Self {
+        self.fx = Some(fx);
+        self
+    }
+
+    /// Every quote currency `config` mentions: those of the trading pairs, discovery and FX
+    /// settings
+    fn known_quotes(config: &Config) -> Vec<String> {
+        let fx = &config.fx;
+        let mut quotes: Vec<String> = config
+            .trading_pairs
+            .iter()
+            .chain(&fx.rate_pairs)
+            .map(|pair| &pair.quote)
+            .chain(&config.discovery.quote_assets)
+            .chain(fx.equivalences.keys())
+            .chain(fx.equivalences.values())
+            .chain(std::iter::once(&fx.reference))
+            .map(|quote| quote.to_uppercase())
+            .filter(|quote| !quote.is_empty())
+            .collect();
+        quotes.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        quotes.dedup();
+        quotes
+    }
+
+    /// The base and quote of a compact symbol such as `BTCUSDT`, if it ends in a known quote
+    fn split_symbol<'a>(&self, symbol: &'a str) -> Option<(&'a str, &'a str)> {
+        self.quotes.iter().find_map(|quote| {
+            symbol
+                .strip_suffix(quote.as_str())
+                .filter(|base| !base.is_empty())
+                .map(|base| (base, &symbol[base.len()..]))
+        })
+    }
+
+    fn taker_fee(&self, exchange: &Exchange) -> f64 {
+        if self.analysis.net_of_fees {
+            self.fees.get(exchange).map_or(0.0, |fees| fees.taker_fee)
+        } else {
+            0.0
+        }
+    }
+
+    fn validity_window_ms(&self) -> f64 {
+        self.analysis
+            .max_opportunity_age_ms
+            .map_or(DEFAULT_VALIDITY_WINDOW_MS, |max_age_ms| max_age_ms as f64)
+    }
+
+    /// Buying at `ask` and selling at `bid`, from books the older of which was published at
+    /// `published`, if it clears the thresholds
+    fn opportunity(
+        &self,
+        symbol: &str,
+        ask: &PriceLevel,
+        bid: &PriceLevel,
+        published: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<ArbitrageOpportunity> {
+        let cost = ask.price * (1.0 + self.taker_fee(&ask.exchange));
+        let proceeds = bid.price * (1.0 - self.taker_fee(&bid.exchange));
+        let profit_percentage = (proceeds - cost) / cost * 100.0;
+        let volume = ask.quantity.min(bid.quantity);
+        if profit_percentage <= self.analysis.min_profit_percentage
+            || volume < self.analysis.min_volume
+        {
+            return None;
+        }
+
+        let window_ms = self.validity_window_ms();
+        let age_ms = (now - published).num_milliseconds().max(0) as f64;
+        let time_to_live_ms = (window_ms - age_ms).max(0.0);
+        Some(ArbitrageOpportunity {
+            buy_exchange: ask.exchange.clone(),
+            sell_exchange: bid.exchange.clone(),
+            symbol: symbol.to_string(),
+            buy_price: ask.price,
+            sell_price: bid.price,
+            profit_percentage,
+            volume,
+            average_buy_price: ask.price,
+            average_sell_price: bid.price,
+            time_to_live_ms,
+            confidence: time_to_live_ms / window_ms,
+            timestamp: now,
+        })
+    }
+
+    /// Opportunities between symbols sharing a base asset but not a quote, priced in the
+    /// reference currency of `fx`
+    fn cross_quote_opportunities(
+        &self,
+        fx: &FxRates,
+        by_symbol: &HashMap<String, Sides>,
+        now: DateTime<Utc>,
+    ) -> Vec<ArbitrageOpportunity> {
+        let reference = fx.reference();
+        let mut by_base: HashMap<&str, Vec<(&str, f64, &Sides)>> = HashMap::new();
+        for (symbol, sides) in by_symbol {
+            let Some((base, quote)) = self.split_symbol(symbol) else {
+                continue;
+            };
+            if let Some(rate) = fx.rate(quote, &reference, now) {
+                by_base.entry(base).or_default().push((quote, rate, sides));
+            }
+        }
+
+        let converted = |level: &PriceLevel, rate: f64| PriceLevel {
+            price: level.price * rate,
+            ..level.clone()
+        };
+        let mut opportunities = Vec::new();
+        for (base, books) in &by_base {
+            let symbol = format!("{}{}", base, reference);
+            for (buy_quote, buy_rate, (_, asks)) in books {
+                for (sell_quote, sell_rate, (bids, _)) in books {
+                    if buy_quote == sell_quote {
+                        continue;
+                    }
+                    for (buy_exchange, (ask, ask_time)) in asks {
+                        for (sell_exchange, (bid, bid_time)) in bids {
+                            if buy_exchange == sell_exchange {
+                                continue;
+                            }
+                            let published = (*ask_time).min(*bid_time);
+                            opportunities.extend(self.opportunity(
+                                &symbol,
+                                &converted(ask, *buy_rate),
+                                &converted(bid, *sell_rate),
+                                published,
+                                now,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        opportunities
+    }
+}
+
+#[async_trait]
+impl AnalysisEngine for TopOfBookEngine {
+    async fn analyze_summaries(
+        &self,
+        summaries: &HashMap<String, Summary>,
+    ) -> Result<Vec<ArbitrageOpportunity>> {
+        let now = self.clock.now();
+        let max_age = self
+            .analysis
+            .max_opportunity_age_ms
+            .map(|max_age_ms| chrono::Duration::milliseconds(max_age_ms as i64));
+
+        let mut by_symbol: HashMap<String, Sides> = HashMap::new();
+        for summary in summaries.values() {
+            if max_age.is_some_and(|max_age| now - summary.timestamp > max_age) {
+                continue;
+            }
+            let symbol: String = summary
+                .symbol
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .collect::<String>()
+                .to_uppercase();
+            let (bids, asks) = by_symbol.entry(symbol).or_default();
+            for bid in &summary.bids {
+                let best = bids
+                    .entry(bid.exchange.clone())
+                    .or_insert((bid, summary.timestamp));
+                if bid.price > best.0.price {
+                    *best = (bid, summary.timestamp);
+                }
+            }
+            for ask in &summary.asks {
+                let best = asks
+                    .entry(ask.exchange.clone())
+                    .or_insert((ask, summary.timestamp));
+                if ask.price < best.0.price {
+                    *best = (ask, summary.timestamp);
+                }
+            }
+        }
+
+        let mut opportunities = Vec::new();
+        for (symbol, (bids, asks)) in &by_symbol {
+            for (buy_exchange, (ask, ask_time)) in asks {
+                for (sell_exchange, (bid, bid_time)) in bids {
+                    if buy_exchange == sell_exchange {
+                        continue;
+                    }
+                    let published = (*ask_time).min(*bid_time);
+                    opportunities.extend(self.opportunity(symbol, ask, bid, published, now));
+                }
+            }
+        }
+        if let Some(fx) = &self.fx {
+            opportunities.extend(self.cross_quote_opportunities(fx, &by_symbol, now));
+        }
+        Ok(opportunities)
+    }
+
+    async fn calculate_spread(&self, summary: &Summary) -> Option<f64> {
+        let best_bid = summary
+            .bids
+            .iter()
+            .map(|level| level.price)
+            .reduce(f64::max)?;
+        let best_ask = summary
+            .asks
+            .iter()
+            .map(|level| level.price)
+            .reduce(f64::min)?;
+        Some(best_ask - best_bid)
+    }
+
+    async fn calculate_volume_weighted_price(&self, summary: &Summary) -> Option<f64> {
+        let (notional, quantity) = summary.bids.iter().chain(&summary.asks).fold(
+            (0.0, 0.0),
+            |(notional, quantity), level| {
+                (
+                    notional + level.price * level.quantity,
+                    quantity + level.quantity,
+                )
+            },
+        );
+        (quantity > 0.0).then(|| notional / quantity)
+    }
+}
@@ -0,0 +1,262 @@
+//! Bounded per-subscriber buffers for consumers slower than the events they read
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, Notify};
+use tokio::task::JoinHandle;
+
+use crate::config::{BackpressureConfig, BackpressurePolicy};
+use crate::types::{ArbitrageOpportunity, Summary};
+
+/// An event that `BackpressurePolicy::ConflateBySymbol` can replace with a newer one.
+pub trait Conflate {
+    /// Events with the same key replace each other
+    fn conflation_key(&self) -> &str;
+}
+
+impl Conflate for Summary {
+    fn conflation_key(&self) -> &str {
+        &self.symbol
+    }
+}
+
+impl Conflate for ArbitrageOpportunity {
+    fn conflation_key(&self) -> &str {
+        &self.symbol
+    }
+}
+
+/// Counters of what happened to the events offered to one or more subscribers.
+#[derive(Debug, Default)]
+pub struct BackpressureStats {
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    conflated: AtomicU64,
+    disconnected: AtomicU64,
+}
+
+impl BackpressureStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> BackpressureSnapshot {
+        BackpressureSnapshot {
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            conflated: self.conflated.load(Ordering::Relaxed),
+            disconnected: self.disconnected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A reading of [`BackpressureStats`].
+///
+/// - `delivered`: Events handed to the subscriber.
+/// - `dropped`: Events discarded unread, to make room or because the subscriber lagged behind
+///   the broadcast channel.
+/// - `conflated`: Events replaced by a newer one for the same symbol before being read.
+/// - `disconnected`: Subscriptions ended by `BackpressurePolicy::Disconnect`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BackpressureSnapshot {
+    pub delivered: u64,
+    pub dropped: u64,
+    pub conflated: u64,
+    pub disconnected: u64,
+}
+
+struct Buffer<T> {
+    events: VecDeque<T>,
+    closed: bool,
+    disconnected: bool,
+}
+
+struct Shared<T> {
+    policy: BackpressurePolicy,
+    capacity: usize,
+    buffer: Mutex<Buffer<T>>,
+    notify: Notify,
+    /// This subscriber's counters
+    stats: BackpressureStats,
+    /// Counters shared with the other subscribers of the same source
+    totals: Arc<BackpressureStats>,
+}
+
+impl<T: Conflate> Shared<T> {
+    fn record(&self, counter: fn(&BackpressureStats) -> &AtomicU64, count: u64) {
+        counter(&self.stats).fetch_add(count, Ordering::Relaxed);
+        counter(&self.totals).fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn push(&self, event: T) {
+        let mut buffer = self.buffer.lock().unwrap_or_else(PoisonError::into_inner);
+        if buffer.closed {
+            return;
+        }
+
+        if self.policy == BackpressurePolicy::ConflateBySymbol {
+            let key = event.conflation_key();
+            if let Some(buffered) = buffer
+                .events
+                .iter_mut()
+                .find(|buffered| buffered.conflation_key() == key)
+            {
+                *buffered = event;
+                self.record(|stats| &stats.conflated, 1);
+                return;
+            }
+        }
+
+        if buffer.events.len() >= self.capacity {
+            if self.policy == BackpressurePolicy::Disconnect {
+                drop(buffer);
+                self.disconnect(1);
+                return;
+            }
+            buffer.events.pop_front();
+            self.record(|stats| &stats.dropped, 1);
+        }
+        buffer.events.push_back(event);
+        drop(buffer);
+        self.notify.notify_one();
+    }
+
+    /// The source skipped `skipped` events because the forwarding task fell behind it
+    fn lagged(&self, skipped: u64) {
+        if self.policy == BackpressurePolicy::Disconnect {
+            self.disconnect(skipped);
+        } else {
+            self.record(|stats| &stats.dropped, skipped);
+        }
+    }
+
+    /// Ends the subscription, counting the buffered events and `unbuffered` others as dropped
+    fn disconnect(&self, unbuffered: u64) {
+        let mut buffer = self.buffer.lock().unwrap_or_else(PoisonError::into_inner);
+        let discarded = buffer.events.len() as u64 + unbuffered;
+        buffer.events.clear();
+        buffer.closed = true;
+        buffer.disconnected = true;
+        drop(buffer);
+        self.record(|stats| &stats.dropped, discarded);
+        self.record(|stats| &stats.disconnected, 1);
+        self.notify.notify_one();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .closed
+    }
+
+    fn close(&self) {
+        self.buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .closed = true;
+        self.notify.notify_one();
+    }
+}
+
+/// A subscription with its own bounded buffer, so a slow reader never holds back the broadcast
+/// channel it reads from.
+///
+/// A background task drains the channel as fast as events arrive and buffers them under the
+/// configured [`BackpressurePolicy`]. Events that are discarded, conflated or cut off by a
+/// disconnect are counted in the subscriber's own stats and in the `totals` shared with the
+/// other subscribers of the same source. Dropping the receiver stops the task.
+pub struct BoundedReceiver<T> {
+    shared: Arc<Shared<T>>,
+    forwarder: JoinHandle<()>,
+}
+
+impl<T: Conflate + Clone + Send + 'static> BoundedReceiver<T> {
+    /// Buffers the events of `receiver` under `config`. Must be called within a Tokio runtime.
+    pub fn new(
+        mut receiver: broadcast::Receiver<T>,
+        config: &BackpressureConfig,
+        totals: Arc<BackpressureStats>,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            policy: config.policy,
+            capacity: config.buffer_size.max(1),
+            buffer: Mutex::new(Buffer {
+                events: VecDeque::new(),
+                closed: false,
+                disconnected: false,
+            }),
+            notify: Notify::new(),
+            stats: BackpressureStats::new(),
+            totals,
+        });
+
+        let forwarding = shared.clone();
+        let forwarder = tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => forwarding.push(event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => forwarding.lagged(skipped),
+                    Err(broadcast::error::RecvError::Closed) => {
+                        forwarding.close();
+                        break;
+                    }
+                }
+                if forwarding.is_closed() {
+                    break;
+                }
+            }
+        });
+
+        Self { shared, forwarder }
+    }
+
+    /// The next buffered event, waiting for one if the buffer is empty. Returns `None` once
+    /// the source has closed and the buffer is drained, or as soon as the subscriber has been
+    /// disconnected.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            {
+                let mut buffer = self
+                    .shared
+                    .buffer
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                if let Some(event) = buffer.events.pop_front() {
+                    drop(buffer);
+                    self.shared.record(|stats| &stats.delivered, 1);
+                    return Some(event);
+                }
+                if buffer.closed {
+                    return None;
+                }
+            }
+            // A notification sent since the buffer was checked is kept for this call
+            self.shared.notify.notified().await;
+        }
+    }
+
+    /// Whether the subscription was ended by `BackpressurePolicy::Disconnect`
+    pub fn is_disconnected(&self) -> bool {
+        self.shared
+            .buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .disconnected
+    }
+
+    /// What has happened to the events offered to this subscriber so far
+    pub fn stats(&self) -> BackpressureSnapshot {
+        self.shared.stats.snapshot()
+    }
+}
+
+impl<T> Drop for BoundedReceiver<T> {
+    fn drop(&mut self) {
+        self.forwarder.abort();
+    }
+}
@@ -0,0 +1,255 @@
+//! Snapshots and deltas of consolidated books, so deep books can be streamed without resending
+//! every level on each change
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AggregatorError, Result};
+use crate::types::{Exchange, PriceLevel, Summary};
+
+/// One level of a book, or in a delta the new quantity of a level, 0 removing it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookLevel {
+    pub price: f64,
+    pub quantity: f64,
+    pub exchange: Exchange,
+}
+
+impl From<&PriceLevel> for BookLevel {
+    fn from(level: &PriceLevel) -> Self {
+        Self {
+            price: level.price,
+            quantity: level.quantity,
+            exchange: level.exchange.clone(),
+        }
+    }
+}
+
+/// The whole book of a symbol, which the deltas that follow it build on.
+///
+/// - `sequence`: Position in the symbol's stream, shared with its deltas. A snapshot, sent as
+///   `BookUpdate::Snapshot`, replaces whatever book the reader held.
+/// - `bids`: Best first, levels at the same price ordered by exchange.
+/// - `asks`: Best first, levels at the same price ordered by exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookState {
+    pub symbol: String,
+    pub sequence: u64,
+    pub spread: f64,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The levels of a symbol's book that changed since the update numbered `sequence - 1`.
+///
+/// - `bids`, `asks`: Levels added or resized, with their new quantity, and levels removed, with
+///   a quantity of 0.
+/// - `spread`: The spread of the book once the delta is applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookDelta {
+    pub symbol: String,
+    pub sequence: u64,
+    pub spread: f64,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// What a [`BookDeltaEncoder`] emits for a summary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum BookUpdate {
+    #[serde(rename = "book-snapshot")]
+    Snapshot(BookState),
+    #[serde(rename = "book-delta")]
+    Delta(BookDelta),
+}
+
+impl BookState {
+    /// The book of `summary`, numbered `sequence`
+    pub fn from_summary(summary: &Summary, sequence: u64) -> Self {
+        let mut bids: Vec<BookLevel> = summary.bids.iter().map(BookLevel::from).collect();
+        let mut asks: Vec<BookLevel> = summary.asks.iter().map(BookLevel::from).collect();
+        sort_levels(&mut bids, true);
+        sort_levels(&mut asks, false);
+        Self {
+            symbol: summary.symbol.clone(),
+            sequence,
+            spread: summary.spread,
+            bids,
+            asks,
+            timestamp: summary.timestamp,
+        }
+    }
+
+    /// Brings the book up to `delta`.
+    ///
+    /// Returns:
+    ///
+    /// An `AggregatorError::Validation` if the delta is of another symbol, or does not directly
+    /// follow this book's sequence, meaning an update was missed and a new snapshot is needed.
+    pub fn apply(&mut self, delta: &BookDelta) -> Result<()> {
+        if delta.symbol != self.symbol {
+            return Err(AggregatorError::validation(
+                "symbol",
+                format!("delta of {} applied to {}", delta.symbol, self.symbol),
+            ));
+        }
+        if delta.sequence != self.sequence + 1 {
+            return Err(AggregatorError::validation(
+                "sequence",
+                format!("expected {}, got {}", self.sequence + 1, delta.sequence),
+            ));
+        }
+        apply_changes(&mut self.bids, &delta.bids, true);
+        apply_changes(&mut self.asks, &delta.asks, false);
+        self.sequence = delta.sequence;
+        self.spread = delta.spread;
+        self.timestamp = delta.timestamp;
+        Ok(())
+    }
+}
+
+/// Turns the successive summaries of each symbol into a snapshot followed by deltas, numbering
+/// them per symbol. A reader that sees a sequence number skipped has missed an update.
+///
+/// ```rust
+/// use aggregator_core::{BookDeltaEncoder, BookUpdate, Exchange, PriceLevel, Summary};
+///
+/// let level = |price: f64, quantity: f64| PriceLevel {
+///     price,
+///     quantity,
+///     exchange: Exchange::Binance,
+///     timestamp: chrono::Utc::now(),
+/// };
+/// let summary = |bid: PriceLevel| Summary {
+///     symbol: "BTCUSDT".to_string(),
+///     spread: 1.0,
+///     bids: vec![bid],
+///     asks: vec![level(101.0, 2.0)],
+///     timestamp: chrono::Utc::now(),
+///     market_type: None,
+/// };
+///
+/// let mut encoder = BookDeltaEncoder::default();
+/// let Some(BookUpdate::Snapshot(mut book)) = encoder.encode(&summary(level(100.0, 1.0))) else {
+///     panic!("the first update of a symbol is a snapshot");
+/// };
+/// let Some(BookUpdate::Delta(delta)) = encoder.encode(&summary(level(100.0, 3.0))) else {
+///     panic!("later updates are deltas");
+/// };
+/// // Only the resized bid is sent
+/// assert_eq!((delta.bids.len(), delta.asks.len()), (1, 0));
+/// book.apply(&delta).unwrap();
+/// assert_eq!(book.bids[0].quantity, 3.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BookDeltaEncoder {
+    books: HashMap<String, BookState>,
+}
+
+impl BookDeltaEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A snapshot of `summary`, which the next deltas of its symbol build on, numbered after
+    /// the symbol's last update
+    pub fn snapshot(&mut self, summary: &Summary) -> BookState {
+        let sequence = self.next_sequence(&summary.symbol);
+        let snapshot = BookState::from_summary(summary, sequence);
+        self.books.insert(summary.symbol.clone(), snapshot.clone());
+        snapshot
+    }
+
+    /// A snapshot for the first summary of a symbol, then the delta from the previous one.
+    /// Returns `None` when no level and not the spread changed, without using a sequence
+    /// number.
+    pub fn encode(&mut self, summary: &Summary) -> Option<BookUpdate> {
+        let Some(previous) = self.books.get(&summary.symbol) else {
+            return Some(BookUpdate::Snapshot(self.snapshot(summary)));
+        };
+        let next = BookState::from_summary(summary, previous.sequence + 1);
+        let bids = changes(&previous.bids, &next.bids);
+        let asks = changes(&previous.asks, &next.asks);
+        if bids.is_empty() && asks.is_empty() && previous.spread == next.spread {
+            return None;
+        }
+        let delta = BookDelta {
+            symbol: next.symbol.clone(),
+            sequence: next.sequence,
+            spread: next.spread,
+            bids,
+            asks,
+            timestamp: next.timestamp,
+        };
+        self.books.insert(summary.symbol.clone(), next);
+        Some(BookUpdate::Delta(delta))
+    }
+
+    /// Forgets the books of the symbols `keep` rejects, so their next update is a snapshot
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.books.retain(|symbol, _| keep(symbol));
+    }
+
+    fn next_sequence(&self, symbol: &str) -> u64 {
+        self.books.get(symbol).map_or(1, |book| book.sequence + 1)
+    }
+}
+
+/// Identifies a level within one side of a book
+fn level_key(level: &BookLevel) -> (u64, &Exchange) {
+    (level.price.to_bits(), &level.exchange)
+}
+
+/// Levels of `next` that are new or resized, then levels of `previous` gone from it with a
+/// quantity of 0
+fn changes(previous: &[BookLevel], next: &[BookLevel]) -> Vec<BookLevel> {
+    let before: HashMap<(u64, &Exchange), f64> = previous
+        .iter()
+        .map(|level| (level_key(level), level.quantity))
+        .collect();
+    let after: HashSet<(u64, &Exchange)> = next.iter().map(level_key).collect();
+
+    let mut changed: Vec<BookLevel> = next
+        .iter()
+        .filter(|level| before.get(&level_key(level)) != Some(&level.quantity))
+        .cloned()
+        .collect();
+    changed.extend(
+        previous
+            .iter()
+            .filter(|level| !after.contains(&level_key(level)))
+            .map(|level| BookLevel {
+                quantity: 0.0,
+                ..level.clone()
+            }),
+    );
+    changed
+}
+
+fn apply_changes(levels: &mut Vec<BookLevel>, changes: &[BookLevel], descending: bool) {
+    for change in changes {
+        let key = level_key(change);
+        levels.retain(|level| level_key(level) != key);
+        if change.quantity > 0.0 {
+            levels.push(change.clone());
+        }
+    }
+    sort_levels(levels, descending);
+}
+
+fn sort_levels(levels: &mut [BookLevel], descending: bool) {
+    levels.sort_by(|a, b| {
+        let by_price = a.price.total_cmp(&b.price);
+        if descending {
+            by_price.reverse()
+        } else {
+            by_price
+        }
+        .then_with(|| a.exchange.cmp(&b.exchange))
+    });
+}
@@ -0,0 +1,238 @@
+//! Pausing arbitrage while prices move too fast or too few exchanges are healthy to trust them
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::CircuitBreakerConfig;
+
+/// Why a circuit breaker opened.
+///
+/// * `PriceMove`: The symbol's mid price moved `move_percentage` percent within the window.
+/// * `TooFewHealthyExchanges`: Only `healthy` exchanges were healthy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TripReason {
+    PriceMove { move_percentage: f64 },
+    TooFewHealthyExchanges { healthy: usize },
+}
+
+/// Whether opportunities of a symbol are reported (`Closed`) or held back (`Open`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BreakerState {
+    Closed,
+    Open(TripReason),
+}
+
+impl BreakerState {
+    pub fn is_open(&self) -> bool {
+        matches!(self, BreakerState::Open(_))
+    }
+}
+
+/// A circuit breaker opening or closing.
+///
+/// - `symbol`: The symbol paused or resumed, or `None` for every symbol.
+/// - `state`: The state it moved to.
+/// - `timestamp`: When it moved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitBreakerEvent {
+    pub symbol: Option<String>,
+    pub state: BreakerState,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Holds back arbitrage opportunities when the books they come from are likely garbage: per
+/// symbol, while its mid price moves faster than `max_move_percentage` over `window_secs`, and
+/// for every symbol, while fewer than `min_healthy_exchanges` exchanges are healthy. A symbol
+/// paused for a price move resumes `cooldown_secs` after its last excessive move. Clones share
+/// the same state.
+///
+/// Symbols are compared in their compact form, so `BTC/USDT` and `btcusdt` are the same.
+///
+/// ```rust
+/// use aggregator_core::{CircuitBreaker, CircuitBreakerConfig};
+///
+/// let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+///     enabled: true,
+///     ..CircuitBreakerConfig::default()
+/// });
+/// let now = chrono::Utc::now();
+/// breaker.observe_price("BTCUSDT", 100.0, now);
+/// // A 10% jump trips the default 5% threshold
+/// assert!(breaker.observe_price("BTCUSDT", 110.0, now).is_some());
+/// assert!(!breaker.allows("BTCUSDT", now));
+/// assert!(breaker.allows("ETHUSDT", now));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CircuitBreaker {
+    state: Arc<Mutex<BreakerInner>>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerInner {
+    config: CircuitBreakerConfig,
+    /// Mid prices of each symbol within the window, oldest first
+    prices: HashMap<String, VecDeque<(DateTime<Utc>, f64)>>,
+    /// Symbols paused for a price move, with the move and when they resume
+    tripped: HashMap<String, (f64, DateTime<Utc>)>,
+    /// The healthy exchange count while it is below the minimum
+    outage: Option<usize>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BreakerInner {
+                config,
+                ..BreakerInner::default()
+            })),
+        }
+    }
+
+    /// Switches to the thresholds of `config`. Symbols already paused stay paused until their
+    /// cooldown ends; turning the breaker off resumes everything at once.
+    pub fn configure(&self, config: CircuitBreakerConfig) {
+        let mut state = self.lock();
+        if !config.enabled {
+            state.prices.clear();
+            state.tripped.clear();
+            state.outage = None;
+        }
+        state.config = config;
+    }
+
+    /// Records the mid price of `symbol` at `at`. Returns the event if this move paused the
+    /// symbol; a move while it is already paused only extends the pause.
+    pub fn observe_price(
+        &self,
+        symbol: &str,
+        price: f64,
+        at: DateTime<Utc>,
+    ) -> Option<CircuitBreakerEvent> {
+        let mut state = self.lock();
+        if !(state.config.enabled && price > 0.0 && price.is_finite()) {
+            return None;
+        }
+        let symbol = compact(symbol);
+        let window = chrono::Duration::seconds(state.config.window_secs as i64);
+        let cooldown = chrono::Duration::seconds(state.config.cooldown_secs as i64);
+        let max_move = state.config.max_move_percentage;
+
+        let prices = state.prices.entry(symbol.clone()).or_default();
+        prices.push_back((at, price));
+        while prices.front().is_some_and(|(time, _)| at - *time > window) {
+            prices.pop_front();
+        }
+        let (low, high) = prices
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(low, high), (_, price)| {
+                (low.min(*price), high.max(*price))
+            });
+        let move_percentage = (high - low) / low * 100.0;
+        if move_percentage <= max_move {
+            return None;
+        }
+
+        let was_open = state
+            .tripped
+            .get(&symbol)
+            .is_some_and(|(_, until)| *until > at);
+        state
+            .tripped
+            .insert(symbol.clone(), (move_percentage, at + cooldown));
+        (!was_open).then_some(CircuitBreakerEvent {
+            symbol: Some(symbol),
+            state: BreakerState::Open(TripReason::PriceMove { move_percentage }),
+            timestamp: at,
+        })
+    }
+
+    /// Records how many exchanges are healthy. Returns the event if this paused or resumed
+    /// every symbol.
+    pub fn observe_healthy_exchanges(
+        &self,
+        healthy: usize,
+        at: DateTime<Utc>,
+    ) -> Option<CircuitBreakerEvent> {
+        let mut state = self.lock();
+        if !state.config.enabled {
+            return None;
+        }
+        let outage = (healthy < state.config.min_healthy_exchanges).then_some(healthy);
+        let changed = outage.is_some() != state.outage.is_some();
+        state.outage = outage;
+        changed.then_some(CircuitBreakerEvent {
+            symbol: None,
+            state: match outage {
+                Some(healthy) => BreakerState::Open(TripReason::TooFewHealthyExchanges { healthy }),
+                None => BreakerState::Closed,
+            },
+            timestamp: at,
+        })
+    }
+
+    /// Resumes the symbols whose cooldown has ended by `now`, returning an event for each
+    pub fn expire(&self, now: DateTime<Utc>) -> Vec<CircuitBreakerEvent> {
+        let mut state = self.lock();
+        let mut resumed: Vec<String> = state
+            .tripped
+            .iter()
+            .filter(|(_, (_, until))| *until <= now)
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        resumed.sort();
+        resumed
+            .into_iter()
+            .map(|symbol| {
+                state.tripped.remove(&symbol);
+                CircuitBreakerEvent {
+                    symbol: Some(symbol),
+                    state: BreakerState::Closed,
+                    timestamp: now,
+                }
+            })
+            .collect()
+    }
+
+    /// Whether opportunities of `symbol` are held back as of `now`, and why. An exchange
+    /// outage takes precedence over a price move.
+    pub fn state(&self, symbol: &str, now: DateTime<Utc>) -> BreakerState {
+        let state = self.lock();
+        if !state.config.enabled {
+            return BreakerState::Closed;
+        }
+        if let Some(healthy) = state.outage {
+            return BreakerState::Open(TripReason::TooFewHealthyExchanges { healthy });
+        }
+        match state.tripped.get(&compact(symbol)) {
+            Some((move_percentage, until)) if *until > now => {
+                BreakerState::Open(TripReason::PriceMove {
+                    move_percentage: *move_percentage,
+                })
+            }
+            _ => BreakerState::Closed,
+        }
+    }
+
+    /// Whether opportunities of `symbol` may be reported as of `now`
+    pub fn allows(&self, symbol: &str, now: DateTime<Utc>) -> bool {
+        !self.state(symbol, now).is_open()
+    }
+
+    // A panic while holding the lock cannot leave the state half-updated, so poisoning is ignored
+    fn lock(&self) -> MutexGuard<'_, BreakerInner> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn compact(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
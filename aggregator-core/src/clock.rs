@@ -0,0 +1,158 @@
+//! The time health checks, staleness, periodic tasks and throttling are judged by, real or
+//! simulated so tests can step through it deterministically
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+
+/// A source of the current time and of waiting for it to pass.
+///
+/// # Required Methods
+///
+/// - `now`: The current time.
+/// - `sleep_until`: Waits until `now` reaches a deadline.
+///
+/// # Provided Methods
+///
+/// - `sleep`: Waits for a duration to pass by this clock.
+#[async_trait]
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Returns once `now` reaches `deadline`, at once if it already has
+    async fn sleep_until(&self, deadline: DateTime<Utc>);
+
+    async fn sleep(&self, duration: Duration) {
+        match self.now().checked_add_signed(to_chrono(duration)) {
+            Some(deadline) => self.sleep_until(deadline).await,
+            // Past the end of time
+            None => std::future::pending().await,
+        }
+    }
+}
+
+/// Wall-clock time, waited for with `tokio::time`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// A shared handle, the default clock of everything that takes one
+    pub fn shared() -> Arc<dyn Clock> {
+        Arc::new(SystemClock)
+    }
+}
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        if let Ok(wait) = (deadline - Utc::now()).to_std() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Time that only moves when told to. Sleepers wake once `advance` or `set` moves the clock to
+/// their deadline. Clones share the same time.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use aggregator_core::{Clock, SimulatedClock};
+///
+/// let clock = SimulatedClock::new(chrono::Utc::now());
+/// let start = clock.now();
+/// clock.advance(Duration::from_secs(30));
+/// assert_eq!(clock.now() - start, chrono::Duration::seconds(30));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SimulatedClock {
+    now: Arc<watch::Sender<DateTime<Utc>>>,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(watch::channel(start).0),
+        }
+    }
+
+    /// Moves the clock forward by `duration`, waking the sleepers it passes
+    pub fn advance(&self, duration: Duration) {
+        self.now.send_modify(|now| {
+            *now = now
+                .checked_add_signed(to_chrono(duration))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC)
+        });
+    }
+
+    /// Moves the clock to `time`. Moving it back wakes nobody.
+    pub fn set(&self, time: DateTime<Utc>) {
+        self.now.send_replace(time);
+    }
+}
+
+#[async_trait]
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        let mut now_rx = self.now.subscribe();
+        // Only fails once the clock is dropped, which `self` prevents
+        let _ = now_rx.wait_for(|now| *now >= deadline).await;
+    }
+}
+
+/// Ticks every `period` of a clock's time. Ticks missed while the owner was busy, or because
+/// the clock jumped ahead, are skipped rather than fired in a burst.
+#[derive(Debug)]
+pub struct Ticker {
+    clock: Arc<dyn Clock>,
+    period: chrono::Duration,
+    next: DateTime<Utc>,
+}
+
+impl Ticker {
+    /// Ticks at once, then every `period`
+    pub fn new(clock: Arc<dyn Clock>, period: Duration) -> Self {
+        let next = clock.now();
+        Self {
+            clock,
+            period: to_chrono(period.max(Duration::from_millis(1))),
+            next,
+        }
+    }
+
+    /// First ticks one `period` from now
+    pub fn delayed(clock: Arc<dyn Clock>, period: Duration) -> Self {
+        let mut ticker = Self::new(clock, period);
+        ticker.next += ticker.period;
+        ticker
+    }
+
+    /// Waits for the next tick and returns the time it was due. Dropping the future before it
+    /// completes leaves the tick pending, so it can be used in `select!`.
+    pub async fn tick(&mut self) -> DateTime<Utc> {
+        self.clock.sleep_until(self.next).await;
+        let due = self.next;
+        let now = self.clock.now();
+        self.next = due + self.period;
+        if self.next <= now {
+            let behind = (now - due).num_milliseconds() / self.period.num_milliseconds();
+            self.next = due + self.period * (behind.min(i32::MAX as i64 - 1) as i32 + 1);
+        }
+        due
+    }
+}
+
+fn to_chrono(duration: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX)
+}
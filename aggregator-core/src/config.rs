@@ -1,3 +1,4 @@
+use crate::symbol::SymbolMapper;
 use crate::types::{Exchange, MarketType, TradingPair};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -30,6 +31,16 @@ use thiserror::Error;
 /// collection and monitoring in the application. This configuration likely includes settings related to
 /// collecting and reporting metrics such as performance metrics, system health metrics, and other
 /// relevant data for monitoring the application's behavior and performance.
+/// * `analysis`: Thresholds and cost model the analysis engines judge arbitrage opportunities with.
+///   Omitted settings take their defaults.
+/// * `storage`: Where the aggregator snapshots its state so a restart resumes warm. Disabled when
+///   omitted.
+/// * `health`: When feeds count as stale and how stale connectors are restarted. Omitted settings
+///   take their defaults.
+/// * `discovery`: Subscribing to the pairs the exchanges list instead of only `trading_pairs`.
+///   Disabled when omitted.
+/// * `fx`: How quote currencies convert into each other, and whether arbitrage is detected
+///   across them. Disabled when omitted.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub exchanges: HashMap<Exchange, ExchangeConfig>,
@@ -38,6 +49,16 @@ pub struct Config {
     pub server: ServerConfig,
     pub logging: LoggingConfig,
     pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub analysis: AnalysisConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    #[serde(default)]
+    pub fx: FxConfig,
 }
 
 /// The `ExchangeConfig` struct represents configuration settings for an exchange, including API key,
@@ -70,6 +91,11 @@ pub struct Config {
 /// `WebSocketConfig`, which may contain details such as the WebSocket endpoint URL, connection
 /// settings, authentication details, and any other configurations related to WebSocket communication
 /// with the exchange
+/// * `proxy`: Optional proxy URL that every connection to this exchange is routed through, either
+///   `http://[user:pass@]host:port` or `socks5://[user:pass@]host:port`. SOCKS5 proxies only carry
+///   WebSocket traffic.
+/// * `fees`: The trading and withdrawal fees charged by the exchange, used to judge arbitrage net of
+///   costs. Omitted fees default to zero.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExchangeConfig {
     pub enabled: bool,
@@ -79,6 +105,56 @@ pub struct ExchangeConfig {
     pub sandbox: bool,
     pub rate_limit: RateLimitConfig,
     pub websocket: WebSocketConfig,
+    #[serde(default)]
+    pub proxy: Option<String>,
+    #[serde(default)]
+    pub fees: FeeSchedule,
+}
+
+/// The `FeeSchedule` struct holds the costs an exchange charges for trading and for moving assets
+/// off the exchange.
+///
+/// Properties:
+///
+/// * `maker_fee`: Fee charged on orders that add liquidity, as a fraction of the traded notional
+///   (0.001 is 0.1%).
+/// * `taker_fee`: Fee charged on orders that take liquidity, as a fraction of the traded notional.
+///   Arbitrage legs cross the book, so this is the rate they pay.
+/// * `withdrawal_fees`: Flat fee charged to withdraw an asset, keyed by upper-case asset symbol
+///   and expressed in units of that asset. Assets that are not listed are withdrawn for free.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    #[serde(default)]
+    pub maker_fee: f64,
+    #[serde(default)]
+    pub taker_fee: f64,
+    #[serde(default)]
+    pub withdrawal_fees: HashMap<String, f64>,
+}
+
+impl FeeSchedule {
+    /// Creates a schedule with the given maker and taker fees and no withdrawal fees
+    pub fn new(maker_fee: f64, taker_fee: f64) -> Self {
+        Self {
+            maker_fee,
+            taker_fee,
+            withdrawal_fees: HashMap::new(),
+        }
+    }
+
+    /// Adds a flat withdrawal fee for `asset`, in units of that asset
+    pub fn with_withdrawal_fee(mut self, asset: &str, fee: f64) -> Self {
+        self.withdrawal_fees.insert(asset.to_uppercase(), fee);
+        self
+    }
+
+    /// The flat fee to withdraw `asset`, or zero if none is configured
+    pub fn withdrawal_fee(&self, asset: &str) -> f64 {
+        self.withdrawal_fees
+            .get(&asset.to_uppercase())
+            .copied()
+            .unwrap_or(0.0)
+    }
 }
 
 /// The `RateLimitConfig` struct in Rust represents configuration settings for rate limiting with fields
@@ -93,7 +169,7 @@ pub struct ExchangeConfig {
 /// number of requests that can be allowed to pass through the rate limiter in a short period of time,
 /// even if the average rate is lower. It allows for temporary bursts of traffic to be processed without
 /// being subject to
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RateLimitConfig {
     pub requests_per_second: u32,
     pub burst_size: u32,
@@ -116,12 +192,16 @@ pub struct RateLimitConfig {
 /// * `buffer_size`: The `buffer_size` property in the `WebSocketConfig` struct specifies the size of
 /// the buffer used for reading and writing data in the WebSocket connection. It determines the maximum
 /// amount of data that can be stored in memory before it needs to be processed or cleared.
+/// * `stale_timeout`: How long (in milliseconds) a connection may go without receiving any message
+///   before the connector treats it as stalled, reports it unhealthy and reconnects. 0 disables the
+///   check.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketConfig {
     pub reconnect_interval: u64,
     pub ping_interval: u64,
     pub max_reconnect_attempts: u32,
     pub buffer_size: usize,
+    pub stale_timeout: u64,
 }
 
 /// The `OrderBookConfig` struct in Rust represents configuration settings for an order book.
@@ -155,8 +235,8 @@ pub struct OrderBookConfig {
     pub implementation: OrderBookImplementation,
 }
 
-/// The above Rust code defines an enum `OrderBookImplementation` with four variants: `BTreeSet`,
-/// `AvlTree`, `RbTree`, and `HashMap`. This enum can be used to represent different implementations for
+/// The above Rust code defines an enum `OrderBookImplementation` with six variants: `BTreeSet`,
+/// `AvlTree`, `RbTree`, `HashMap`, `Decimal` and `CopyOnWrite`. This enum can be used to represent different implementations for
 /// an order book in a trading system. The enum derives `Debug`, `Clone`, `Serialize`, and `Deserialize`
 /// traits, allowing for debugging, cloning, and serialization/deserialization of instances of this
 /// enum.
@@ -166,6 +246,10 @@ pub enum OrderBookImplementation {
     AvlTree,
     RbTree,
     HashMap,
+    /// Exact decimal prices; needs the `decimal` feature of `orderbook-implementations`
+    Decimal,
+    /// Lock-free reads for many concurrent readers
+    CopyOnWrite,
 }
 
 /// The `ServerConfig` struct contains configurations for gRPC, REST, and WebSocket servers.
@@ -181,11 +265,115 @@ pub enum OrderBookImplementation {
 /// * `websocket`: The `websocket` property in the `ServerConfig` struct represents the configuration
 /// settings for a WebSocket server. It likely includes details such as the host, port, protocols, and
 /// any additional settings required to set up and configure the WebSocket server for communication.
+/// * `auth`: Who may use the servers and what they may do, shared by all three.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub grpc: GrpcConfig,
     pub rest: RestConfig,
     pub websocket: WebSocketServerConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+}
+
+/// What an API key or token permits.
+///
+/// * `ReadOnly`: Reads data and subscribes to streams.
+/// * `Admin`: Everything `ReadOnly` does, and controls the aggregator at runtime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthRole {
+    #[default]
+    ReadOnly,
+    Admin,
+}
+
+/// Streams a client can subscribe to, and `ApiKeyConfig::channels` can restrict a key to. The
+/// gRPC summary and book streams are the `summary` channel.
+pub const AUTH_CHANNELS: [&str; 4] = ["summary", "orderbook-delta", "arbitrage", "health"];
+
+/// The `ApiKeyConfig` struct grants the client presenting `key` access to the servers.
+///
+/// Properties:
+///
+/// * `name`: Identifies the client in logs and errors, so the key itself never appears there.
+///   Keys sharing a name share a rate limit.
+/// * `key`: The secret the client sends, as an `X-API-Key` header or a bearer token.
+/// * `role`: What the key permits, `read_only` unless set.
+/// * `channels`: Channels of [`AUTH_CHANNELS`] the key may subscribe to. Empty allows all.
+/// * `rate_limit`: Requests the key may make, instead of `AuthConfig::rate_limit`.
+///
+/// `Debug` leaves the key out, so configurations can be logged.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    pub key: String,
+    #[serde(default)]
+    pub role: AuthRole,
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+/// The `JwtConfig` struct accepts JSON Web Tokens signed with HS256 as well as API keys.
+///
+/// Properties:
+///
+/// * `secret`: The key tokens are signed with.
+/// * `issuer`: The `iss` claim tokens must carry, when set.
+/// * `audience`: The `aud` claim tokens must carry, when set.
+///
+/// Tokens must carry `sub`, which names the client like `ApiKeyConfig::name`, and `exp`. Their
+/// optional `role` and `channels` claims work like the fields of an `ApiKeyConfig`. `Debug`
+/// leaves the secret out.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct JwtConfig {
+    pub secret: String,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub audience: Option<String>,
+}
+
+impl std::fmt::Debug for ApiKeyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKeyConfig")
+            .field("name", &self.name)
+            .field("key", &"<redacted>")
+            .field("role", &self.role)
+            .field("channels", &self.channels)
+            .field("rate_limit", &self.rate_limit)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for JwtConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtConfig")
+            .field("secret", &"<redacted>")
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .finish()
+    }
+}
+
+/// The `AuthConfig` struct controls who may use the gRPC, REST and WebSocket servers.
+///
+/// Properties:
+///
+/// * `enabled`: Whether clients must authenticate. While disabled, any client may read and
+///   subscribe to everything without a rate limit, and none may administer.
+/// * `api_keys`: The keys clients may present.
+/// * `jwt`: Also accept signed tokens, when set.
+/// * `rate_limit`: Requests each client may make, counted per key name or token subject. A
+///   WebSocket client counts its connection and each message it sends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub enabled: bool,
+    pub api_keys: Vec<ApiKeyConfig>,
+    pub jwt: Option<JwtConfig>,
+    pub rate_limit: RateLimitConfig,
 }
 
 /// The `GrpcConfig` struct represents configuration settings for a gRPC connection in Rust.
@@ -202,12 +390,16 @@ pub struct ServerConfig {
 /// * `tls`: The `tls` property in the `GrpcConfig` struct is an optional field of type `TlsConfig`.
 /// This field allows you to configure Transport Layer Security (TLS) settings for the gRPC connection.
 /// If the `tls` field is `Some`, it means that TLS is enabled and
+/// * `backpressure`: How each streaming client is buffered when it reads slower than events are
+///   published.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GrpcConfig {
     pub enabled: bool,
     pub host: String,
     pub port: u16,
     pub tls: Option<TlsConfig>,
+    #[serde(default)]
+    pub backpressure: BackpressureConfig,
 }
 
 /// The `RestConfig` struct represents configuration settings for a REST API in Rust.
@@ -251,12 +443,45 @@ pub struct RestConfig {
 /// * `max_connections`: The `max_connections` property in the `WebSocketServerConfig` struct represents
 /// the maximum number of connections that the WebSocket server can handle simultaneously. This value
 /// determines the capacity of the server to accept incoming connections from clients.
+/// * `backpressure`: How each connected client is buffered when it reads slower than summaries
+///   are published.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketServerConfig {
     pub enabled: bool,
     pub host: String,
     pub port: u16,
     pub max_connections: usize,
+    #[serde(default)]
+    pub backpressure: BackpressureConfig,
+}
+
+/// What a subscriber's buffer does with a new event once it holds `buffer_size` undelivered ones.
+///
+/// * `DropOldest`: Discards the oldest buffered event to make room.
+/// * `ConflateBySymbol`: Replaces the buffered event for the same symbol, so a slow client gets
+///   the latest state of every symbol rather than every intermediate one. The oldest event is
+///   discarded only when the buffer holds `buffer_size` different symbols.
+/// * `Disconnect`: Discards everything buffered and ends the subscription, for clients that
+///   must not miss events silently.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackpressurePolicy {
+    #[default]
+    DropOldest,
+    ConflateBySymbol,
+    Disconnect,
+}
+
+/// The `BackpressureConfig` struct bounds the events buffered for one subscriber.
+///
+/// Properties:
+///
+/// * `policy`: What to do with a new event when the buffer is full.
+/// * `buffer_size`: Most undelivered events kept for the subscriber.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackpressureConfig {
+    pub policy: BackpressurePolicy,
+    pub buffer_size: usize,
 }
 
 /// The `TlsConfig` struct in Rust represents configuration settings for TLS with fields for certificate
@@ -299,6 +524,15 @@ pub struct CorsConfig {
     pub allowed_headers: Vec<String>,
 }
 
+/// Levels `LoggingConfig::level` and its module overrides accept
+pub const LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+/// Formats `LoggingConfig::format` accepts
+pub const LOG_FORMATS: [&str; 3] = ["json", "pretty", "compact"];
+
+/// Destinations `LoggingConfig::output` accepts
+pub const LOG_OUTPUTS: [&str; 3] = ["stdout", "stderr", "file"];
+
 /// The `LoggingConfig` struct represents configuration settings for logging in Rust.
 ///
 /// Properties:
@@ -320,6 +554,11 @@ pub struct CorsConfig {
 /// * `max_files`: The `max_files` property in the `LoggingConfig` struct represents the maximum number
 /// of log files that can be created before old log files are rotated or deleted. This property
 /// specifies the limit for the number of log files that can be retained for logging purposes.
+/// * `modules`: Levels overriding `level` for the modules, and everything inside them, named by
+///   their path such as `exchange_connectors::binance`. Empty by default.
+///
+/// `format` is one of `json`, `pretty` or `compact`, and `output` one of `stdout`, `stderr` or
+/// `file`. See `init_logging` for how they are applied.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
     pub level: String,
@@ -328,6 +567,77 @@ pub struct LoggingConfig {
     pub file_path: Option<String>,
     pub max_file_size: u64,
     pub max_files: u32,
+    #[serde(default)]
+    pub modules: HashMap<String, String>,
+}
+
+impl LoggingConfig {
+    /// Problems with these settings, reported by [`Config::issues`] under `logging`
+    pub fn issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        let mut issue = |field: String, message: &str| {
+            issues.push(ConfigIssue {
+                field,
+                message: message.to_string(),
+            })
+        };
+
+        let is_level = |level: &str| LOG_LEVELS.contains(&level.to_ascii_lowercase().as_str());
+        if !is_level(&self.level) {
+            issue(
+                "logging.level".to_string(),
+                "must be one of trace, debug, info, warn or error",
+            );
+        }
+        let mut modules: Vec<(&String, &String)> = self.modules.iter().collect();
+        modules.sort();
+        for (module, level) in modules {
+            if module.trim().is_empty() || module.contains(['=', ',']) {
+                issue(
+                    format!("logging.modules.{}", module),
+                    "must be a module path such as exchange_connectors::binance",
+                );
+            } else if !is_level(level) {
+                issue(
+                    format!("logging.modules.{}", module),
+                    "must be one of trace, debug, info, warn or error",
+                );
+            }
+        }
+        if !LOG_FORMATS.contains(&self.format.as_str()) {
+            issue(
+                "logging.format".to_string(),
+                "must be one of json, pretty or compact",
+            );
+        }
+        if !LOG_OUTPUTS.contains(&self.output.as_str()) {
+            issue(
+                "logging.output".to_string(),
+                "must be one of stdout, stderr or file",
+            );
+        }
+        if self.output == "file" {
+            if self.file_path.is_none() {
+                issue(
+                    "logging.file_path".to_string(),
+                    "is required when logging.output is \"file\"",
+                );
+            }
+            if self.max_file_size == 0 {
+                issue(
+                    "logging.max_file_size".to_string(),
+                    "must be positive when logging.output is \"file\"",
+                );
+            }
+            if self.max_files == 0 {
+                issue(
+                    "logging.max_files".to_string(),
+                    "must be positive when logging.output is \"file\"",
+                );
+            }
+        }
+        issues
+    }
 }
 
 /// The `MetricsConfig` struct in Rust represents configuration settings for metrics, including
@@ -339,10 +649,45 @@ pub struct LoggingConfig {
 /// whether metrics collection is enabled or not. If `enabled` is set to `true`, it means that metrics
 /// collection is active, while if it is set to `false`, metrics collection is disabled.
 /// * `prometheus`: The `MetricsConfig` struct has two properties:
+/// * `history`: How much per exchange and symbol feed history is kept for charting. Kept for a
+///   day at one minute resolution when omitted.
+/// * `errors`: How many of the latest errors are kept for inspection.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsConfig {
     pub enabled: bool,
     pub prometheus: PrometheusConfig,
+    #[serde(default)]
+    pub history: MetricsHistoryConfig,
+    #[serde(default)]
+    pub errors: ErrorTelemetryConfig,
+}
+
+/// The `MetricsHistoryConfig` struct controls the rolling feed history, see
+/// [`crate::MetricsHistory`].
+///
+/// Properties:
+///
+/// * `enabled`: Whether history is recorded.
+/// * `resolution_secs`: Seconds each sample covers.
+/// * `retention_secs`: Seconds of samples kept, in memory and in storage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsHistoryConfig {
+    pub enabled: bool,
+    pub resolution_secs: u64,
+    pub retention_secs: u64,
+}
+
+/// The `ErrorTelemetryConfig` struct controls the error counters and recent errors, see
+/// [`crate::ErrorTelemetry`].
+///
+/// Properties:
+///
+/// * `max_recent`: Latest errors kept; older ones only remain in the counters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ErrorTelemetryConfig {
+    pub max_recent: usize,
 }
 
 /// The `PrometheusConfig` struct represents configuration settings for Prometheus monitoring with
@@ -369,6 +714,155 @@ pub struct PrometheusConfig {
     pub path: String,
 }
 
+/// The `AnalysisConfig` struct holds the settings analysis engines use to decide which price
+/// differences between exchanges are worth reporting as arbitrage opportunities.
+///
+/// Properties:
+///
+/// * `min_profit_percentage`: Smallest profit, in percent of the amount spent, an opportunity must
+///   offer to be reported.
+/// * `min_volume`: Smallest quantity of the base asset an opportunity must be tradable for.
+/// * `max_opportunity_age_ms`: Books last updated longer ago than this are left out of detection,
+///   so an exchange that stopped updating cannot produce opportunities. `None` keeps every book.
+/// * `net_of_fees`: Whether profit is judged after each exchange's `fees` (`true`) or on the price
+///   difference alone (`false`).
+/// * `circuit_breaker`: When to stop reporting opportunities because the data cannot be trusted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct AnalysisConfig {
+    pub min_profit_percentage: f64,
+    pub min_volume: f64,
+    pub max_opportunity_age_ms: Option<u64>,
+    pub net_of_fees: bool,
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+/// The `CircuitBreakerConfig` struct sets when arbitrage opportunities stop being reported, see
+/// [`crate::CircuitBreaker`].
+///
+/// Properties:
+///
+/// * `enabled`: Whether the breaker is in use.
+/// * `max_move_percentage`: How far, in percent of the lowest price, a symbol's mid price may move
+///   within `window_secs` before its opportunities are paused.
+/// * `window_secs`: Seconds of prices a move is measured over.
+/// * `cooldown_secs`: Seconds a symbol stays paused after its last excessive move.
+/// * `min_healthy_exchanges`: Fewer healthy exchanges than this pause every symbol until enough
+///   recover. 0 never pauses for health.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    pub max_move_percentage: f64,
+    pub window_secs: u64,
+    pub cooldown_secs: u64,
+    pub min_healthy_exchanges: usize,
+}
+
+/// The embedded database a [`StorageConfig`] opens.
+///
+/// * `Sqlite`: A SQLite file at `path`; needs the `sqlite` feature.
+/// * `Memory`: Kept in the process only, so nothing survives a restart. Mostly useful for tests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageBackend {
+    #[default]
+    Sqlite,
+    Memory,
+}
+
+/// The `StorageConfig` struct controls persisting the aggregator's state between runs.
+///
+/// Properties:
+///
+/// * `enabled`: Whether state is snapshotted and restored at all.
+/// * `backend`: The database state is kept in.
+/// * `path`: File of the `Sqlite` backend.
+/// * `snapshot_interval_secs`: Seconds between snapshots. A last snapshot is also taken on
+///   shutdown.
+/// * `max_opportunities`: Most arbitrage opportunities kept; older ones are deleted as new ones
+///   are saved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub enabled: bool,
+    pub backend: StorageBackend,
+    pub path: String,
+    pub snapshot_interval_secs: u64,
+    pub max_opportunities: usize,
+}
+
+/// The `HealthConfig` struct controls how the health monitor judges feeds and recovers them.
+///
+/// Properties:
+///
+/// * `check_interval_secs`: Seconds between health checks.
+/// * `stale_after_secs`: Seconds without an update after which a symbol of an exchange is stale.
+///   An exchange whose symbols are all stale is unhealthy.
+/// * `max_restarts`: Restarts attempted for an unhealthy exchange's connector before giving up
+///   until it recovers. 0 disables restarts.
+/// * `restart_backoff_secs`: Seconds to wait after restarting a connector before restarting it
+///   again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    pub check_interval_secs: u64,
+    pub stale_after_secs: u64,
+    pub max_restarts: u32,
+    pub restart_backoff_secs: u64,
+}
+
+/// The `DiscoveryConfig` struct controls subscribing to the pairs the enabled exchanges list,
+/// found through the market discoveries registered on `ConnectorRegistry`.
+///
+/// Properties:
+///
+/// * `enabled`: Whether pairs are discovered. The configured `trading_pairs` are always kept, and
+///   may be left empty.
+/// * `quote_assets`: Quote assets a pair must have, such as `USDT`. Empty allows any.
+/// * `min_quote_volume`: Smallest 24 hour volume, in quote currency, a pair must trade on an
+///   exchange for that exchange to count towards it.
+/// * `min_exchanges`: Exchanges that must list a pair, 2 or more to only follow pairs that can be
+///   arbitraged.
+/// * `max_pairs`: Most pairs discovered, those trading the highest volume across exchanges. No
+///   limit when omitted.
+/// * `refresh_interval_secs`: Seconds between discoveries after the one at startup. 0 only
+///   discovers at startup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscoveryConfig {
+    pub enabled: bool,
+    pub quote_assets: Vec<String>,
+    pub min_quote_volume: f64,
+    pub min_exchanges: usize,
+    pub max_pairs: Option<usize>,
+    pub refresh_interval_secs: u64,
+}
+
+/// The `FxConfig` struct controls normalizing prices quoted in different currencies, see
+/// [`crate::FxRates`].
+///
+/// Properties:
+///
+/// * `enabled`: Whether arbitrage detection also compares books of the same base asset quoted
+///   in different currencies, such as BTC/USDT against BTC/USD.
+/// * `reference`: The currency prices are normalized to.
+/// * `equivalences`: Currencies treated as worth one unit of another, keyed by the pegged
+///   currency, such as `USDT = "USD"`. A live rate replaces the peg while it is fresh.
+/// * `rate_pairs`: Pairs whose mid price is the live rate between their base and quote, such as
+///   USDT/USD. They must be subscribed to like any other pair.
+/// * `max_rate_age_secs`: Seconds a live rate is used for after it was observed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FxConfig {
+    pub enabled: bool,
+    pub reference: String,
+    pub equivalences: HashMap<String, String>,
+    pub rate_pairs: Vec<TradingPair>,
+    pub max_rate_age_secs: u64,
+}
+
 /// The above Rust code is defining an enum `ConfigError` that represents different types of errors that
 /// can occur related to configuration. It has one variant `FileNotFound` which includes a string
 /// message indicating the file that was not found. The `#[derive(Error, Debug)]` attribute is used to
@@ -379,6 +873,78 @@ pub struct PrometheusConfig {
 pub enum ConfigError {
     #[error("File not found: {0}")]
     FileNotFound(String),
+    #[error("Invalid {format} config: {message}")]
+    Parse {
+        format: ConfigFormat,
+        message: String,
+    },
+    #[error("Invalid value for {variable}: {message}")]
+    EnvOverride { variable: String, message: String },
+    #[error("Failed to watch config file: {0}")]
+    Watch(String),
+}
+
+/// One problem found by [`Config::validate`]: the dotted path to the setting and what is wrong
+/// with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Prefix of the environment variables that override configuration values, see
+/// [`Config::with_env_overrides`]
+pub const ENV_PREFIX: &str = "AGG_";
+
+/// The file formats a `Config` can be read from and written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// The format named by the extension of `path`: `.toml`, `.yaml` or `.yml`, and JSON for
+    /// anything else.
+    pub fn from_path(path: &str) -> Self {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    /// The error for a format whose cargo feature is not enabled
+    #[cfg(not(all(feature = "toml", feature = "yaml")))]
+    fn unsupported(self) -> String {
+        format!(
+            "{} support is not compiled in, enable the `{}` feature of aggregator-core",
+            self,
+            self.to_string().to_ascii_lowercase()
+        )
+    }
+}
+
+impl std::fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Yaml => "YAML",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 /// The above Rust code is implementing the `Default` trait for a struct named `Config`. The `Default`
@@ -403,6 +969,11 @@ impl Default for Config {
             server: ServerConfig::default(),
             logging: LoggingConfig::default(),
             metrics: MetricsConfig::default(),
+            analysis: AnalysisConfig::default(),
+            storage: StorageConfig::default(),
+            health: HealthConfig::default(),
+            discovery: DiscoveryConfig::default(),
+            fx: FxConfig::default(),
         }
     }
 }
@@ -423,6 +994,8 @@ impl Default for ExchangeConfig {
             sandbox: false,
             rate_limit: RateLimitConfig::default(),
             websocket: WebSocketConfig::default(),
+            proxy: None,
+            fees: FeeSchedule::default(),
         }
     }
 }
@@ -444,8 +1017,8 @@ impl Default for RateLimitConfig {
 /// The above code is implementing the `Default` trait for the `WebSocketConfig` struct in Rust. By
 /// implementing the `Default` trait, it provides a default implementation for creating instances of
 /// `WebSocketConfig` when no specific values are provided. In this implementation, the default values
-/// for `reconnect_interval`, `ping_interval`, `max_reconnect_attempts`, and `buffer_size` are set to
-/// 5000, 30000, 10, and 1000 respectively.
+/// for `reconnect_interval`, `ping_interval`, `max_reconnect_attempts`, `buffer_size` and
+/// `stale_timeout` are set to 5000, 30000, 10, 1000 and 20000 respectively.
 impl Default for WebSocketConfig {
     fn default() -> Self {
         Self {
@@ -453,6 +1026,7 @@ impl Default for WebSocketConfig {
             ping_interval: 30000,
             max_reconnect_attempts: 10,
             buffer_size: 1000,
+            stale_timeout: 20000,
         }
     }
 }
@@ -483,6 +1057,23 @@ impl Default for ServerConfig {
             grpc: GrpcConfig::default(),
             rest: RestConfig::default(),
             websocket: WebSocketServerConfig::default(),
+            auth: AuthConfig::default(),
+        }
+    }
+}
+
+/// Defaults to authentication disabled, with 20 requests a second and bursts of 40 per client
+/// once enabled.
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_keys: Vec::new(),
+            jwt: None,
+            rate_limit: RateLimitConfig {
+                requests_per_second: 20,
+                burst_size: 40,
+            },
         }
     }
 }
@@ -498,6 +1089,7 @@ impl Default for GrpcConfig {
             host: "0.0.0.0".to_string(),
             port: 50051,
             tls: None,
+            backpressure: BackpressureConfig::default(),
         }
     }
 }
@@ -529,6 +1121,16 @@ impl Default for WebSocketServerConfig {
             host: "0.0.0.0".to_string(),
             port: 8081,
             max_connections: 1000,
+            backpressure: BackpressureConfig::default(),
+        }
+    }
+}
+
+impl Default for BackpressureConfig {
+    fn default() -> Self {
+        Self {
+            policy: BackpressurePolicy::DropOldest,
+            buffer_size: 256,
         }
     }
 }
@@ -562,6 +1164,7 @@ impl Default for LoggingConfig {
             file_path: None,
             max_file_size: 100 * 1024 * 1024, // 100MB
             max_files: 10,
+            modules: HashMap::new(),
         }
     }
 }
@@ -576,6 +1179,26 @@ impl Default for MetricsConfig {
         Self {
             enabled: true,
             prometheus: PrometheusConfig::default(),
+            history: MetricsHistoryConfig::default(),
+            errors: ErrorTelemetryConfig::default(),
+        }
+    }
+}
+
+/// Defaults to keeping the last 100 errors.
+impl Default for ErrorTelemetryConfig {
+    fn default() -> Self {
+        Self { max_recent: 100 }
+    }
+}
+
+/// Defaults to the last 24 hours at one minute resolution.
+impl Default for MetricsHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            resolution_secs: 60,
+            retention_secs: 24 * 60 * 60,
         }
     }
 }
@@ -595,9 +1218,98 @@ impl Default for PrometheusConfig {
     }
 }
 
+/// Defaults to disabled; once enabled, a snapshot every 30 seconds to `aggregator.db` keeping the
+/// latest 10,000 opportunities.
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: StorageBackend::Sqlite,
+            path: "aggregator.db".to_string(),
+            snapshot_interval_secs: 30,
+            max_opportunities: 10_000,
+        }
+    }
+}
+
+/// Defaults to checking every 10 seconds, symbols going stale after 30 seconds without updates,
+/// and up to 3 connector restarts 30 seconds apart.
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 10,
+            stale_after_secs: 30,
+            max_restarts: 3,
+            restart_backoff_secs: 30,
+        }
+    }
+}
+
+/// Defaults to disabled; once enabled, the 20 USDT pairs trading the most volume, refreshed
+/// hourly.
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quote_assets: vec!["USDT".to_string()],
+            min_quote_volume: 0.0,
+            min_exchanges: 1,
+            max_pairs: Some(20),
+            refresh_interval_secs: 3600,
+        }
+    }
+}
+
+/// Defaults to disabled, normalizing to USD with USDT, USDC and DAI pegged to it and live rates
+/// used for a minute.
+impl Default for FxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            reference: "USD".to_string(),
+            equivalences: ["USDT", "USDC", "DAI"]
+                .into_iter()
+                .map(|stablecoin| (stablecoin.to_string(), "USD".to_string()))
+                .collect(),
+            rate_pairs: Vec::new(),
+            max_rate_age_secs: 60,
+        }
+    }
+}
+
+/// Defaults to a 0.1% minimum profit on at least 0.01 of the base asset, judged net of fees, with
+/// no limit on book age.
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            min_profit_percentage: 0.1,
+            min_volume: 0.01,
+            max_opportunity_age_ms: None,
+            net_of_fees: true,
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
+}
+
+/// Defaults to disabled, pausing a symbol for 30 seconds after a 5% move within 10 seconds, and
+/// everything while fewer than 2 exchanges are healthy.
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_move_percentage: 5.0,
+            window_secs: 10,
+            cooldown_secs: 30,
+            min_healthy_exchanges: 2,
+        }
+    }
+}
+
 impl Config {
-    /// The function `from_file` reads a JSON file, parses its content into a `Config` struct using serde,
-    /// and returns a result.
+    /// The function `from_file` reads a JSON, TOML or YAML file, picked by its extension (see
+    /// [`ConfigFormat::from_path`]), parses its content into a `Config` struct using serde and
+    /// checks it with [`Config::validate`]. Environment overrides are not applied; chain
+    /// [`Config::with_env_overrides`] for those.
     ///
     /// Arguments:
     ///
@@ -611,21 +1323,41 @@ impl Config {
     /// during the process.
     pub fn from_file(path: &str) -> crate::Result<Self> {
         let content = std::fs::read_to_string(path).map_err(|e| {
-            crate::AggregatorError::parsing(
-                "Config",
-                format!("Failed to write config file: {}", e).as_str(),
-            )
+            if e.kind() == std::io::ErrorKind::NotFound {
+                crate::AggregatorError::Config(ConfigError::FileNotFound(path.to_string()))
+            } else {
+                crate::AggregatorError::parsing(
+                    "Config",
+                    format!("Failed to read config file: {}", e).as_str(),
+                )
+            }
         })?;
 
-        let config: Config = serde_json::from_str(&content)?;
+        let config = Self::parse(&content, ConfigFormat::from_path(path))?;
+        config.validate()?;
         Ok(config)
     }
 
-    /// The function `to_file` serializes a struct to JSON and writes it to a file in Rust.
+    /// Parses `content` written in `format` into a `Config`.
+    pub fn parse(content: &str, format: ConfigFormat) -> crate::Result<Self> {
+        let parsed = match format {
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            #[cfg(feature = "yaml")]
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            #[cfg(not(all(feature = "toml", feature = "yaml")))]
+            _ => Err(format.unsupported()),
+        };
+        parsed.map_err(|message| ConfigError::Parse { format, message }.into())
+    }
+
+    /// The function `to_file` serializes a struct to JSON, TOML or YAML, picked by the extension
+    /// of `path`, and writes it to a file in Rust.
     ///
     /// Arguments:
     ///
-    /// * `path`: The `path` parameter in the `to_file` function represents the file path where the JSON
+    /// * `path`: The `path` parameter in the `to_file` function represents the file path where the
     /// content will be written. It is a reference to a string (`&str`) that specifies the location where
     /// the content will be saved.
     ///
@@ -634,7 +1366,17 @@ impl Config {
     /// The `to_file` function returns a `Result` with the success type `()` (unit) and an error type
     /// defined in the `crate` module.
     pub fn to_file(&self, path: &str) -> crate::Result<()> {
-        let content = serde_json::to_string_pretty(self)?;
+        let format = ConfigFormat::from_path(path);
+        let content = match format {
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            ConfigFormat::Toml => toml::to_string_pretty(self).map_err(|e| e.to_string()),
+            #[cfg(feature = "yaml")]
+            ConfigFormat::Yaml => serde_yaml::to_string(self).map_err(|e| e.to_string()),
+            #[cfg(not(all(feature = "toml", feature = "yaml")))]
+            _ => Err(format.unsupported()),
+        }
+        .map_err(|message| ConfigError::Parse { format, message })?;
         std::fs::write(path, content).map_err(|e| {
             crate::AggregatorError::parsing(
                 "Config",
@@ -644,6 +1386,86 @@ impl Config {
         Ok(())
     }
 
+    /// The default configuration with the overrides of the process environment applied, see
+    /// [`Config::with_env_overrides`].
+    pub fn from_env() -> crate::Result<Self> {
+        Self::default().with_env_overrides()
+    }
+
+    /// Applies every `AGG_`-prefixed variable of the process environment on top of this
+    /// configuration, so secrets and per-deployment settings need not be written to the file.
+    ///
+    /// The rest of the variable name is the path to the value, with `__` between levels and
+    /// matched case-insensitively: `AGG_SERVER__REST__PORT=8080` sets `server.rest.port` and
+    /// `AGG_EXCHANGES__BINANCE__API_KEY=...` sets the Binance API key. Values are read as JSON
+    /// (numbers, booleans, arrays) where the setting accepts that, and as plain strings otherwise.
+    /// Overrides are checked one at a time and the first invalid one is reported.
+    pub fn with_env_overrides(self) -> crate::Result<Self> {
+        self.with_overrides(std::env::vars())
+    }
+
+    /// Applies `variables` as [`Config::with_env_overrides`] applies the environment. Variables
+    /// without the `AGG_` prefix are ignored. When any override applies, the result is checked
+    /// with [`Config::validate`].
+    pub fn with_overrides<I, K, V>(self, variables: I) -> crate::Result<Self>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut tree = serde_json::to_value(&self)?;
+        let mut overridden = false;
+        for (variable, value) in variables {
+            let variable = variable.as_ref();
+            let Some(path) = variable.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let override_error = |message: String| ConfigError::EnvOverride {
+                variable: variable.to_string(),
+                message,
+            };
+            let segments: Vec<&str> = path.split("__").collect();
+            if segments.iter().any(|segment| segment.is_empty()) {
+                return Err(override_error("empty path segment".to_string()).into());
+            }
+
+            // Try the value as JSON first and fall back to a string, so `12345` can still be an
+            // API key
+            let value = value.as_ref();
+            let mut candidates = vec![serde_json::Value::String(value.to_string())];
+            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(value) {
+                if !parsed.is_string() {
+                    candidates.insert(0, parsed);
+                }
+            }
+            let mut applied = None;
+            let mut last_error = None;
+            for candidate in candidates {
+                let mut updated = tree.clone();
+                set_override(&mut updated, &segments, candidate).map_err(override_error)?;
+                match serde_json::from_value::<Config>(updated.clone()) {
+                    Ok(_) => {
+                        applied = Some(updated);
+                        break;
+                    }
+                    Err(e) => last_error = Some(e.to_string()),
+                }
+            }
+            match applied {
+                Some(updated) => tree = updated,
+                None => return Err(override_error(last_error.unwrap_or_default()).into()),
+            }
+            overridden = true;
+        }
+        if !overridden {
+            return Ok(self);
+        }
+
+        let config: Config = serde_json::from_value(tree)?;
+        config.validate()?;
+        Ok(config)
+    }
+
     /// The `enabled_exchanges` function returns a vector of enabled exchanges based on a given
     /// configuration.
     ///
@@ -657,4 +1479,433 @@ impl Config {
             .map(|(exchange, _)| exchange.clone())
             .collect()
     }
+
+    /// Resolves an exchange's native symbol, such as `BTCUSDT`, `BTC-USDT` or `btc/usdt`, to
+    /// one of the configured trading pairs.
+    ///
+    /// Returns:
+    ///
+    /// The configured pair whose base and quote make up the symbol once separators are removed,
+    /// else the pair spelled out by a symbol with exactly one `/`, `-` or `_` separator, else
+    /// `None`.
+    pub fn pair_for_symbol(&self, symbol: &str) -> Option<TradingPair> {
+        let compact: String = symbol
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_uppercase();
+        if let Some(pair) = self
+            .trading_pairs
+            .iter()
+            .find(|pair| format!("{}{}", pair.base, pair.quote) == compact)
+        {
+            return Some(pair.clone());
+        }
+
+        let parts: Vec<&str> = symbol.split(['/', '-', '_']).collect();
+        match parts.as_slice() {
+            [base, quote] if !base.is_empty() && !quote.is_empty() => {
+                Some(TradingPair::new(base, quote))
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolves the symbol `exchange` reports updates under to a trading pair, translating the
+    /// exchange's own format and asset codes with [`SymbolMapper`], so Kraken's `XBT/USD`,
+    /// Bitfinex's `tBTCUST`, Hyperliquid's `BTC` and Kraken Futures' `PF_XBTUSD` land on the
+    /// same pairs as other exchanges' symbols.
+    ///
+    /// Returns:
+    ///
+    /// The configured pair the symbol maps to, else the pair [`Config::pair_for_symbol`]
+    /// resolves, else the pair the symbol maps to, such as `BTC/USDC` for Hyperliquid's `BTC`,
+    /// or `None` if the symbol cannot be read.
+    pub fn pair_for_exchange_symbol(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+    ) -> Option<TradingPair> {
+        let mapped = SymbolMapper::new().from_exchange(exchange, symbol).ok();
+        if let Some(pair) = mapped
+            .as_ref()
+            .and_then(|mapped| self.trading_pairs.iter().find(|pair| *pair == mapped))
+        {
+            return Some(pair.clone());
+        }
+        self.pair_for_symbol(symbol).or(mapped)
+    }
+
+    /// Checks the configuration for settings that cannot work: colliding server ports, missing
+    /// trading pairs, zero buffer sizes, missing TLS files and settings that contradict each
+    /// other.
+    ///
+    /// Returns:
+    ///
+    /// `Ok(())`, or an `AggregatorError::Validation` listing every problem found, see
+    /// [`Config::issues`].
+    pub fn validate(&self) -> crate::Result<()> {
+        let issues = self.issues();
+        if issues.is_empty() {
+            return Ok(());
+        }
+        let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        Err(crate::AggregatorError::validation(
+            "config",
+            format!("{} problem(s): {}", issues.len(), messages.join("; ")),
+        ))
+    }
+
+    /// Every problem [`Config::validate`] reports, in the order of the config sections.
+    pub fn issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        let mut issue = |field: String, message: &str| {
+            issues.push(ConfigIssue {
+                field,
+                message: message.to_string(),
+            })
+        };
+
+        let mut exchanges: Vec<(&Exchange, &ExchangeConfig)> = self.exchanges.iter().collect();
+        exchanges.sort_by(|a, b| a.0.cmp(b.0));
+        if !exchanges.iter().any(|(_, config)| config.enabled) {
+            issue("exchanges".to_string(), "no exchange is enabled");
+        }
+        for (exchange, config) in exchanges.into_iter().filter(|(_, config)| config.enabled) {
+            let field = |name: &str| format!("exchanges.{}.{}", exchange, name);
+            if config.rate_limit.requests_per_second == 0 {
+                issue(field("rate_limit.requests_per_second"), "must be positive");
+            }
+            if config.rate_limit.burst_size == 0 {
+                issue(field("rate_limit.burst_size"), "must be positive");
+            }
+            if config.websocket.buffer_size == 0 {
+                issue(field("websocket.buffer_size"), "must be positive");
+            }
+            if config.api_secret.is_some() && config.api_key.is_none() {
+                issue(field("api_key"), "is required when api_secret is set");
+            }
+            if let Some(proxy) = &config.proxy {
+                match url::Url::parse(proxy) {
+                    Ok(url) if matches!(url.scheme(), "http" | "socks5" | "socks5h") => {}
+                    Ok(_) => issue(field("proxy"), "must be an http:// or socks5:// URL"),
+                    Err(_) => issue(field("proxy"), "is not a valid URL"),
+                }
+            }
+            let fees = &config.fees;
+            // Negative maker fees are rebates
+            if !(fees.maker_fee > -1.0 && fees.maker_fee < 1.0) {
+                issue(
+                    field("fees.maker_fee"),
+                    "must be a fraction between -1 and 1",
+                );
+            }
+            if !(0.0..1.0).contains(&fees.taker_fee) {
+                issue(field("fees.taker_fee"), "must be a fraction from 0 up to 1");
+            }
+        }
+
+        if self.trading_pairs.is_empty() && !self.discovery.enabled {
+            issue(
+                "trading_pairs".to_string(),
+                "at least one trading pair is required unless discovery is enabled",
+            );
+        }
+        for (index, pair) in self.trading_pairs.iter().enumerate() {
+            if pair.base.is_empty() || pair.quote.is_empty() {
+                issue(
+                    format!("trading_pairs[{}]", index),
+                    "base and quote must not be empty",
+                );
+            } else if self.trading_pairs[..index].contains(pair) {
+                issue(
+                    format!("trading_pairs[{}]", index),
+                    "duplicates an earlier pair",
+                );
+            }
+        }
+
+        if self.orderbook.max_depth == 0 {
+            issue("orderbook.max_depth".to_string(), "must be positive");
+        }
+
+        let server = &self.server;
+        let mut listeners: Vec<(&str, &str, u16)> = Vec::new();
+        if server.grpc.enabled {
+            listeners.push(("server.grpc.port", &server.grpc.host, server.grpc.port));
+        }
+        if server.rest.enabled {
+            listeners.push(("server.rest.port", &server.rest.host, server.rest.port));
+        }
+        if server.websocket.enabled {
+            listeners.push((
+                "server.websocket.port",
+                &server.websocket.host,
+                server.websocket.port,
+            ));
+            if server.websocket.max_connections == 0 {
+                issue(
+                    "server.websocket.max_connections".to_string(),
+                    "must be positive while the WebSocket server is enabled",
+                );
+            }
+        }
+        if self.metrics.enabled && self.metrics.prometheus.enabled {
+            let prometheus = &self.metrics.prometheus;
+            listeners.push(("metrics.prometheus.port", &prometheus.host, prometheus.port));
+            if !prometheus.path.starts_with('/') {
+                issue("metrics.prometheus.path".to_string(), "must start with '/'");
+            }
+        }
+        // Port 0 asks the OS for a free port, so it never collides
+        for (index, (field, host, port)) in listeners.iter().enumerate() {
+            let overlaps = |other: &str| {
+                *host == other
+                    || ["0.0.0.0", "::"].contains(host)
+                    || ["0.0.0.0", "::"].contains(&other)
+            };
+            if let Some((other, _, _)) =
+                listeners[..index]
+                    .iter()
+                    .find(|(_, other_host, other_port)| {
+                        *port != 0 && other_port == port && overlaps(other_host)
+                    })
+            {
+                issue(
+                    field.to_string(),
+                    &format!("{} is already used by {}", port, other),
+                );
+            }
+        }
+        for (field, enabled, backpressure) in [
+            (
+                "server.grpc",
+                server.grpc.enabled,
+                &server.grpc.backpressure,
+            ),
+            (
+                "server.websocket",
+                server.websocket.enabled,
+                &server.websocket.backpressure,
+            ),
+        ] {
+            if enabled && backpressure.buffer_size == 0 {
+                issue(
+                    format!("{}.backpressure.buffer_size", field),
+                    "must be positive",
+                );
+            }
+        }
+        if let Some(tls) = server.grpc.tls.as_ref().filter(|_| server.grpc.enabled) {
+            for (name, path) in [("cert_path", &tls.cert_path), ("key_path", &tls.key_path)] {
+                if !std::path::Path::new(path).is_file() {
+                    issue(
+                        format!("server.grpc.tls.{}", name),
+                        &format!("{} does not exist", path),
+                    );
+                }
+            }
+        }
+
+        let auth = &server.auth;
+        if auth.enabled {
+            if auth.api_keys.is_empty() && auth.jwt.is_none() {
+                issue(
+                    "server.auth".to_string(),
+                    "needs api_keys or jwt while authentication is enabled",
+                );
+            }
+            let mut rate_limits = vec![("server.auth.rate_limit".to_string(), &auth.rate_limit)];
+            for (index, key) in auth.api_keys.iter().enumerate() {
+                let field = |name: &str| format!("server.auth.api_keys[{}].{}", index, name);
+                if key.name.trim().is_empty() {
+                    issue(field("name"), "must not be empty");
+                }
+                if key.key.is_empty() {
+                    issue(field("key"), "must not be empty");
+                } else if auth.api_keys[..index]
+                    .iter()
+                    .any(|other| other.key == key.key)
+                {
+                    issue(field("key"), "duplicates an earlier key");
+                }
+                if let Some(channel) = key
+                    .channels
+                    .iter()
+                    .find(|channel| !AUTH_CHANNELS.contains(&channel.as_str()))
+                {
+                    issue(
+                        field("channels"),
+                        &format!("'{}' is not one of {}", channel, AUTH_CHANNELS.join(", ")),
+                    );
+                }
+                if let Some(rate_limit) = &key.rate_limit {
+                    rate_limits.push((field("rate_limit"), rate_limit));
+                }
+            }
+            for (field, rate_limit) in rate_limits {
+                if rate_limit.requests_per_second == 0 {
+                    issue(format!("{}.requests_per_second", field), "must be positive");
+                }
+                if rate_limit.burst_size == 0 {
+                    issue(format!("{}.burst_size", field), "must be positive");
+                }
+            }
+            if auth.jwt.as_ref().is_some_and(|jwt| jwt.secret.is_empty()) {
+                issue("server.auth.jwt.secret".to_string(), "must not be empty");
+            }
+        }
+
+        for logging_issue in self.logging.issues() {
+            issue(logging_issue.field, &logging_issue.message);
+        }
+
+        let history = &self.metrics.history;
+        if history.enabled {
+            if history.resolution_secs == 0 {
+                issue(
+                    "metrics.history.resolution_secs".to_string(),
+                    "must be positive",
+                );
+            }
+            if history.retention_secs < history.resolution_secs {
+                issue(
+                    "metrics.history.retention_secs".to_string(),
+                    "must be at least resolution_secs",
+                );
+            }
+        }
+
+        let analysis = &self.analysis;
+        if analysis.min_volume < 0.0 {
+            issue("analysis.min_volume".to_string(), "must not be negative");
+        }
+        if analysis.max_opportunity_age_ms == Some(0) {
+            issue(
+                "analysis.max_opportunity_age_ms".to_string(),
+                "must be positive, or omitted for no limit",
+            );
+        }
+        let breaker = &analysis.circuit_breaker;
+        if breaker.enabled {
+            if breaker.max_move_percentage <= 0.0 {
+                issue(
+                    "analysis.circuit_breaker.max_move_percentage".to_string(),
+                    "must be positive",
+                );
+            }
+            if breaker.window_secs == 0 {
+                issue(
+                    "analysis.circuit_breaker.window_secs".to_string(),
+                    "must be positive",
+                );
+            }
+        }
+
+        let storage = &self.storage;
+        if storage.enabled {
+            if storage.snapshot_interval_secs == 0 {
+                issue(
+                    "storage.snapshot_interval_secs".to_string(),
+                    "must be positive",
+                );
+            }
+            if storage.backend == StorageBackend::Sqlite {
+                if storage.path.trim().is_empty() {
+                    issue(
+                        "storage.path".to_string(),
+                        "is required by the Sqlite backend",
+                    );
+                }
+                if !cfg!(feature = "sqlite") {
+                    issue(
+                        "storage.backend".to_string(),
+                        "Sqlite needs the `sqlite` feature of aggregator-core",
+                    );
+                }
+            }
+        }
+
+        if self.health.check_interval_secs == 0 {
+            issue("health.check_interval_secs".to_string(), "must be positive");
+        }
+        if self.health.stale_after_secs == 0 {
+            issue("health.stale_after_secs".to_string(), "must be positive");
+        }
+
+        let discovery = &self.discovery;
+        if discovery.enabled {
+            if discovery.min_exchanges == 0 {
+                issue("discovery.min_exchanges".to_string(), "must be positive");
+            }
+            if discovery.max_pairs == Some(0) {
+                issue(
+                    "discovery.max_pairs".to_string(),
+                    "must be positive, or omitted for no limit",
+                );
+            }
+            if discovery.min_quote_volume < 0.0 {
+                issue(
+                    "discovery.min_quote_volume".to_string(),
+                    "must not be negative",
+                );
+            }
+        }
+
+        let fx = &self.fx;
+        if fx.reference.trim().is_empty() {
+            issue("fx.reference".to_string(), "must not be empty");
+        }
+        if fx.max_rate_age_secs == 0 && !fx.rate_pairs.is_empty() {
+            issue(
+                "fx.max_rate_age_secs".to_string(),
+                "must be positive while rate pairs are set",
+            );
+        }
+        let mut pegs: Vec<(&String, &String)> = fx.equivalences.iter().collect();
+        pegs.sort();
+        for (currency, peg) in pegs {
+            if currency.eq_ignore_ascii_case(peg) {
+                issue(
+                    format!("fx.equivalences.{}", currency),
+                    "pegs a currency to itself",
+                );
+            }
+        }
+
+        issues
+    }
+}
+
+/// Sets the value at `segments` below `tree`, creating missing objects. Each segment matches an
+/// existing key ignoring case and underscores, so `CRYPTO_DOT_COM` finds `CryptoDotCom`; a new
+/// key is the lower-cased segment.
+fn set_override(
+    tree: &mut serde_json::Value,
+    segments: &[&str],
+    value: serde_json::Value,
+) -> std::result::Result<(), String> {
+    let Some((segment, rest)) = segments.split_first() else {
+        *tree = value;
+        return Ok(());
+    };
+
+    if tree.is_null() {
+        *tree = serde_json::Value::Object(serde_json::Map::new());
+    }
+    let serde_json::Value::Object(map) = tree else {
+        return Err(format!("{} is not a table", segment.to_lowercase()));
+    };
+    let normalize = |key: &str| key.replace('_', "").to_ascii_lowercase();
+    let key = map
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(segment))
+        .or_else(|| map.keys().find(|key| normalize(key) == normalize(segment)))
+        .cloned()
+        .unwrap_or_else(|| segment.to_lowercase());
+    set_override(
+        map.entry(key).or_insert(serde_json::Value::Null),
+        rest,
+        value,
+    )
 }
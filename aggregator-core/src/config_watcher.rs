@@ -0,0 +1,269 @@
+//! Reloading the configuration file while the aggregator runs
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[cfg(feature = "config-watch")]
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use tokio::sync::{broadcast, RwLock};
+
+use crate::config::Config;
+use crate::types::{Exchange, TradingPair};
+use crate::Result;
+
+/// How long to wait for a file being saved to settle before reading it
+#[cfg(feature = "config-watch")]
+const SETTLE_DELAY: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// One difference between the running configuration and a reloaded one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigChange {
+    ExchangeEnabled(Exchange),
+    ExchangeDisabled(Exchange),
+    /// Credentials, fees, rate limits or other settings of an exchange whose enabled state is
+    /// unchanged
+    ExchangeUpdated(Exchange),
+    TradingPairAdded(TradingPair),
+    TradingPairRemoved(TradingPair),
+    /// Arbitrage thresholds or cost model
+    AnalysisUpdated,
+    /// Staleness and restart settings, used from the next health check
+    HealthUpdated,
+    /// Pair discovery filters, used from the next refresh. Discovery turned on only starts at
+    /// the next `Aggregator::start`.
+    DiscoveryUpdated,
+    /// Reference currency, equivalences or rate pairs, applied at once. Rate pairs still have to
+    /// be among the trading pairs to be observed.
+    FxUpdated,
+    /// A section only read at startup: `orderbook`, `server`, `logging`, `metrics` or `storage`
+    RestartRequired(String),
+}
+
+/// A reloaded configuration and how it differs from the one it replaces.
+#[derive(Debug, Clone)]
+pub struct ConfigChanged {
+    pub config: Arc<Config>,
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ConfigChanged {
+    /// The changes from `previous` to `current`, or `None` if they are the same
+    pub fn between(previous: &Config, current: Config) -> Option<Self> {
+        let changes = diff(previous, &current);
+        if changes.is_empty() {
+            return None;
+        }
+        Some(Self {
+            config: Arc::new(current),
+            changes,
+        })
+    }
+}
+
+/// Every difference between `previous` and `current`, exchanges first in `Exchange` order, then
+/// trading pairs, analysis, health, discovery and FX settings, and startup-only sections.
+fn diff(previous: &Config, current: &Config) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+
+    let mut exchanges: Vec<&Exchange> = previous
+        .exchanges
+        .keys()
+        .chain(current.exchanges.keys())
+        .collect();
+    exchanges.sort();
+    exchanges.dedup();
+    for exchange in exchanges {
+        let before = previous.exchanges.get(exchange);
+        let after = current.exchanges.get(exchange);
+        let was_enabled = before.is_some_and(|config| config.enabled);
+        let is_enabled = after.is_some_and(|config| config.enabled);
+        match (was_enabled, is_enabled) {
+            (false, true) => changes.push(ConfigChange::ExchangeEnabled(exchange.clone())),
+            (true, false) => changes.push(ConfigChange::ExchangeDisabled(exchange.clone())),
+            _ if !same(&before, &after) => {
+                changes.push(ConfigChange::ExchangeUpdated(exchange.clone()))
+            }
+            _ => {}
+        }
+    }
+
+    for pair in &current.trading_pairs {
+        if !previous.trading_pairs.contains(pair) {
+            changes.push(ConfigChange::TradingPairAdded(pair.clone()));
+        }
+    }
+    for pair in &previous.trading_pairs {
+        if !current.trading_pairs.contains(pair) {
+            changes.push(ConfigChange::TradingPairRemoved(pair.clone()));
+        }
+    }
+
+    if !same(&previous.analysis, &current.analysis) {
+        changes.push(ConfigChange::AnalysisUpdated);
+    }
+    if !same(&previous.health, &current.health) {
+        changes.push(ConfigChange::HealthUpdated);
+    }
+    if !same(&previous.discovery, &current.discovery) {
+        changes.push(ConfigChange::DiscoveryUpdated);
+    }
+    if previous.fx != current.fx {
+        changes.push(ConfigChange::FxUpdated);
+    }
+    for (section, unchanged) in [
+        ("orderbook", same(&previous.orderbook, &current.orderbook)),
+        ("server", same(&previous.server, &current.server)),
+        ("logging", same(&previous.logging, &current.logging)),
+        ("metrics", same(&previous.metrics, &current.metrics)),
+        ("storage", same(&previous.storage, &current.storage)),
+    ] {
+        if !unchanged {
+            changes.push(ConfigChange::RestartRequired(section.to_string()));
+        }
+    }
+
+    changes
+}
+
+/// Compares settings through their serialized form, as the config types do not implement
+/// `PartialEq`
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Watches a configuration file and broadcasts a [`ConfigChanged`] event whenever it is saved
+/// with different, valid settings.
+///
+/// Reloaded files get the `AGG_` environment overrides applied, like
+/// `Config::from_file(path)?.with_env_overrides()`. A file that fails to load is logged and
+/// the running configuration is kept. Pass the events to `Aggregator::watch_config` to apply
+/// them. Cloning the watcher shares its state.
+#[derive(Clone)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    current: Arc<RwLock<Arc<Config>>>,
+    sender: broadcast::Sender<ConfigChanged>,
+}
+
+impl ConfigWatcher {
+    /// Watches `path`, treating `config` as the settings currently in use
+    pub fn new(path: impl Into<PathBuf>, config: Config) -> Self {
+        let (sender, _) = broadcast::channel(16);
+        Self {
+            path: path.into(),
+            current: Arc::new(RwLock::new(Arc::new(config))),
+            sender,
+        }
+    }
+
+    /// Loads `path` with the environment overrides applied and watches it from there
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let config = Self::read(&path)?;
+        Ok(Self::new(path, config))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The settings currently in use
+    pub async fn current(&self) -> Arc<Config> {
+        self.current.read().await.clone()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ConfigChanged> {
+        self.sender.subscribe()
+    }
+
+    /// Reads the file again and, if its settings differ from the current ones, makes them
+    /// current and broadcasts the change.
+    ///
+    /// Returns the change, or `None` if nothing changed. An invalid file returns its error and
+    /// leaves the current settings in place.
+    pub async fn reload(&self) -> Result<Option<ConfigChanged>> {
+        let config = Self::read(&self.path)?;
+        let mut current = self.current.write().await;
+        let Some(changed) = ConfigChanged::between(&current, config) else {
+            return Ok(None);
+        };
+        *current = changed.config.clone();
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(changed.clone());
+        Ok(Some(changed))
+    }
+
+    /// Spawns a task that reloads the file every time it is written, until the task is
+    /// aborted. The directory holding the file is watched, so editors that save by replacing
+    /// the file are picked up too.
+    #[cfg(feature = "config-watch")]
+    pub fn spawn(&self) -> Result<tokio::task::JoinHandle<Result<()>>> {
+        use tracing::{error, info, warn};
+
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            // The receiver only goes away with the task
+            let _ = event_tx.send(event);
+        })
+        .map_err(|e| crate::config::ConfigError::Watch(e.to_string()))?;
+
+        let directory = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        watcher
+            .watch(directory, RecursiveMode::NonRecursive)
+            .map_err(|e| crate::config::ConfigError::Watch(e.to_string()))?;
+
+        let config_watcher = self.clone();
+        let file_name = self.path.file_name().map(|name| name.to_os_string());
+        let handle = tokio::spawn(async move {
+            // Dropping the watcher stops the events
+            let _watcher = watcher;
+            while let Some(event) = event_rx.recv().await {
+                let event: notify::Event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        warn!("Error watching {}: {}", config_watcher.path.display(), e);
+                        continue;
+                    }
+                };
+                let touches_file = event
+                    .paths
+                    .iter()
+                    .any(|path| path.file_name().map(|name| name.to_os_string()) == file_name);
+                if !touches_file
+                    || !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                {
+                    continue;
+                }
+
+                // A save often arrives as several events; read the file once they stop
+                tokio::time::sleep(SETTLE_DELAY).await;
+                while event_rx.try_recv().is_ok() {}
+
+                match config_watcher.reload().await {
+                    Ok(Some(changed)) => info!(
+                        "Reloaded {}: {:?}",
+                        config_watcher.path.display(),
+                        changed.changes
+                    ),
+                    Ok(None) => {}
+                    Err(e) => error!(
+                        "Keeping the running configuration, {} is invalid: {}",
+                        config_watcher.path.display(),
+                        e
+                    ),
+                }
+            }
+            Ok(())
+        });
+
+        Ok(handle)
+    }
+
+    fn read(path: &Path) -> Result<Config> {
+        Config::from_file(&path.to_string_lossy())?.with_env_overrides()
+    }
+}
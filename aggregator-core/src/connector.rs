@@ -0,0 +1,159 @@
+//! Order book connectors and the registry third-party venues plug into
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::config::ExchangeConfig;
+use crate::discovery::MarketDiscovery;
+use crate::types::{Exchange, PriceLevelUpdate, TradingPair};
+use crate::Result;
+
+#[async_trait]
+pub trait OrderBookService {
+    /// Spawns an order book service to stream order book data and handle stream events for the
+    /// specified pairs. Connectors multiplex all pairs over a single WebSocket connection.
+    ///
+    /// Once `shutdown` fires (see `Aggregator::subscribe_shutdown`), the tasks close their
+    /// connections and return `Ok(())` instead of reconnecting.
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>>;
+}
+
+/// Builds an exchange's connector from its settings in `Config::exchanges`
+pub type ConnectorFactory =
+    Arc<dyn Fn(&ExchangeConfig) -> Result<Arc<dyn OrderBookService + Send + Sync>> + Send + Sync>;
+
+/// Builds an exchange's market discovery from its settings in `Config::exchanges`
+pub type DiscoveryFactory =
+    Arc<dyn Fn(&ExchangeConfig) -> Result<Arc<dyn MarketDiscovery>> + Send + Sync>;
+
+/// Connector factories by exchange name, which the aggregator starts connectors from; an
+/// enabled exchange without one is not started. `exchange_connectors::register_all` registers
+/// the built-in connectors. A venue without a built-in `Exchange` variant is registered under
+/// its own name and enabled in the config as `Exchange::named(name)`. Registering an exchange's
+/// name again replaces its connector. Market discoveries, which pair discovery lists an
+/// exchange's pairs with, are registered alongside. Cloning shares the registry.
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use aggregator_core::{ConnectorRegistry, Exchange, OrderBookService};
+///
+/// # async fn example(connector: Arc<dyn OrderBookService + Send + Sync>) {
+/// let connectors = ConnectorRegistry::new();
+/// connectors
+///     .register("acme", move |_config| Ok(connector.clone()))
+///     .await;
+/// assert!(connectors.contains(&Exchange::named("Acme")).await);
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ConnectorRegistry {
+    factories: Arc<RwLock<HashMap<String, ConnectorFactory>>>,
+    discoveries: Arc<RwLock<HashMap<String, DiscoveryFactory>>>,
+}
+
+impl ConnectorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `factory` for the exchange called `name`, ignoring case. Returns `false` if
+    /// it replaced a factory registered under the same name.
+    pub async fn register<F>(&self, name: &str, factory: F) -> bool
+    where
+        F: Fn(&ExchangeConfig) -> Result<Arc<dyn OrderBookService + Send + Sync>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.factories
+            .write()
+            .await
+            .insert(Exchange::named(name).to_string(), Arc::new(factory))
+            .is_none()
+    }
+
+    pub async fn unregister(&self, exchange: &Exchange) -> bool {
+        self.factories
+            .write()
+            .await
+            .remove(&exchange.to_string())
+            .is_some()
+    }
+
+    pub async fn contains(&self, exchange: &Exchange) -> bool {
+        self.factories
+            .read()
+            .await
+            .contains_key(&exchange.to_string())
+    }
+
+    /// The exchanges with a registered factory, sorted
+    pub async fn exchanges(&self) -> Vec<Exchange> {
+        let mut exchanges: Vec<Exchange> = self
+            .factories
+            .read()
+            .await
+            .keys()
+            .map(|name| Exchange::named(name))
+            .collect();
+        exchanges.sort();
+        exchanges
+    }
+
+    /// Builds the connector of `exchange` from `config`, or `None` if no factory is registered
+    /// for it
+    pub async fn create(
+        &self,
+        exchange: &Exchange,
+        config: &ExchangeConfig,
+    ) -> Option<Result<Arc<dyn OrderBookService + Send + Sync>>> {
+        let factory = self
+            .factories
+            .read()
+            .await
+            .get(&exchange.to_string())
+            .cloned()?;
+        Some(factory(config))
+    }
+
+    /// Registers `factory` as the market discovery of the exchange called `name`, ignoring
+    /// case. Returns `false` if it replaced one registered under the same name.
+    pub async fn register_discovery<F>(&self, name: &str, factory: F) -> bool
+    where
+        F: Fn(&ExchangeConfig) -> Result<Arc<dyn MarketDiscovery>> + Send + Sync + 'static,
+    {
+        self.discoveries
+            .write()
+            .await
+            .insert(Exchange::named(name).to_string(), Arc::new(factory))
+            .is_none()
+    }
+
+    /// Builds the market discovery of `exchange` from `config`, or `None` if none is registered
+    /// for it
+    pub async fn create_discovery(
+        &self,
+        exchange: &Exchange,
+        config: &ExchangeConfig,
+    ) -> Option<Result<Arc<dyn MarketDiscovery>>> {
+        let factory = self
+            .discoveries
+            .read()
+            .await
+            .get(&exchange.to_string())
+            .cloned()?;
+        Some(factory(config))
+    }
+}
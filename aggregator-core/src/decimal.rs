@@ -0,0 +1,140 @@
+//! Exact decimal prices and quantities
+//!
+//! `Bid`, `Ask` and `PriceLevel` carry `f64` prices, which cannot represent most exchange prices
+//! exactly and drift when quantities are summed. This module converts them to and from
+//! `rust_decimal::Decimal` at the edges of code that needs exact arithmetic, and parses the
+//! decimal strings exchanges send straight into `Decimal` without going through `f64`.
+
+use crate::{AggregatorError, Ask, Bid, Exchange, PriceLevel, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+pub use rust_decimal::Decimal;
+
+/// Converts an `f64` price or quantity to a `Decimal`, rejecting NaN and infinite values
+///
+/// `field` names the value in the validation error.
+pub fn to_decimal(value: f64, field: &str) -> Result<Decimal> {
+    if !value.is_finite() {
+        return Err(AggregatorError::validation(
+            field,
+            format!("{} is not a finite number", value),
+        ));
+    }
+    Decimal::from_f64(value).ok_or_else(|| {
+        AggregatorError::validation(field, format!("{} is out of decimal range", value))
+    })
+}
+
+/// Converts a `Decimal` back to the closest `f64`
+pub fn from_decimal(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+/// Parses an exchange's decimal string, including scientific notation such as `1e-8`
+pub fn parse_decimal(value: &str, field: &str) -> Result<Decimal> {
+    Decimal::from_str(value)
+        .or_else(|_| Decimal::from_scientific(value))
+        .map_err(|e| AggregatorError::parsing(field, format!("{}: {}", value, e)))
+}
+
+/// One exchange's liquidity at one price, with an exact price and quantity
+///
+/// This is the decimal counterpart of `Bid`, `Ask` and `PriceLevel`; which side it belongs to is
+/// up to the container holding it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DecimalLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub exchange: Exchange,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl DecimalLevel {
+    fn from_parts(
+        price: f64,
+        quantity: f64,
+        exchange: &Exchange,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Self> {
+        Ok(Self {
+            price: to_decimal(price, "price")?,
+            quantity: to_decimal(quantity, "quantity")?,
+            exchange: exchange.clone(),
+            timestamp,
+        })
+    }
+
+    /// Builds a level from the price and quantity strings an exchange sent
+    pub fn parse(
+        price: &str,
+        quantity: &str,
+        exchange: Exchange,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Self> {
+        Ok(Self {
+            price: parse_decimal(price, "price")?,
+            quantity: parse_decimal(quantity, "quantity")?,
+            exchange,
+            timestamp,
+        })
+    }
+
+    pub fn to_bid(&self) -> Bid {
+        Bid {
+            price: from_decimal(self.price),
+            quantity: from_decimal(self.quantity),
+            exchange: self.exchange.clone(),
+            timestamp: self.timestamp,
+        }
+    }
+
+    pub fn to_ask(&self) -> Ask {
+        Ask {
+            price: from_decimal(self.price),
+            quantity: from_decimal(self.quantity),
+            exchange: self.exchange.clone(),
+            timestamp: self.timestamp,
+        }
+    }
+
+    pub fn to_price_level(&self) -> PriceLevel {
+        PriceLevel {
+            price: from_decimal(self.price),
+            quantity: from_decimal(self.quantity),
+            exchange: self.exchange.clone(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+impl TryFrom<&Bid> for DecimalLevel {
+    type Error = AggregatorError;
+
+    fn try_from(bid: &Bid) -> Result<Self> {
+        Self::from_parts(bid.price, bid.quantity, &bid.exchange, bid.timestamp)
+    }
+}
+
+impl TryFrom<&Ask> for DecimalLevel {
+    type Error = AggregatorError;
+
+    fn try_from(ask: &Ask) -> Result<Self> {
+        Self::from_parts(ask.price, ask.quantity, &ask.exchange, ask.timestamp)
+    }
+}
+
+impl TryFrom<&PriceLevel> for DecimalLevel {
+    type Error = AggregatorError;
+
+    fn try_from(level: &PriceLevel) -> Result<Self> {
+        Self::from_parts(
+            level.price,
+            level.quantity,
+            &level.exchange,
+            level.timestamp,
+        )
+    }
+}
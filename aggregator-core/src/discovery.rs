@@ -0,0 +1,78 @@
+//! Finding the trading pairs to subscribe to from what the exchanges list
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::config::DiscoveryConfig;
+use crate::types::{Exchange, TradingPair};
+use crate::Result;
+
+/// A pair an exchange lists for trading.
+///
+/// - `exchange`: The exchange listing the pair.
+/// - `pair`: The pair, in the aggregator's base and quote names.
+/// - `symbol`: The exchange's native symbol (e.g., "BTCUSDT").
+/// - `quote_volume`: Volume traded over the last 24 hours in quote currency, if the exchange
+///   reports it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketListing {
+    pub exchange: Exchange,
+    pub pair: TradingPair,
+    pub symbol: String,
+    pub quote_volume: Option<f64>,
+}
+
+/// Lists the pairs an exchange trades, usually from its public instruments and ticker endpoints.
+/// Registered on `ConnectorRegistry` for `Aggregator` pair discovery.
+#[async_trait]
+pub trait MarketDiscovery: Send + Sync {
+    /// Every pair currently open for trading
+    async fn list_markets(&self) -> Result<Vec<MarketListing>>;
+}
+
+/// The pairs of `listings` that `config` subscribes to, highest volume first.
+///
+/// A listing counts towards its pair when its quote asset is allowed and it trades at least
+/// `min_quote_volume`; listings without a volume only count when no minimum is set. Pairs counted
+/// by at least `min_exchanges` exchanges are ranked by their volume summed over those exchanges
+/// and cut to `max_pairs`, ties going to the pair that sorts first.
+pub fn select_pairs(config: &DiscoveryConfig, listings: &[MarketListing]) -> Vec<TradingPair> {
+    let mut counted: HashMap<&TradingPair, (Vec<&Exchange>, f64)> = HashMap::new();
+    for listing in listings {
+        let quote_allowed = config.quote_assets.is_empty()
+            || config
+                .quote_assets
+                .iter()
+                .any(|quote| quote.eq_ignore_ascii_case(&listing.pair.quote));
+        let volume = listing.quote_volume.unwrap_or(0.0);
+        let liquid = match listing.quote_volume {
+            Some(volume) => volume >= config.min_quote_volume,
+            None => config.min_quote_volume <= 0.0,
+        };
+        if !quote_allowed || !liquid {
+            continue;
+        }
+
+        let (exchanges, total) = counted.entry(&listing.pair).or_default();
+        if !exchanges.contains(&&listing.exchange) {
+            exchanges.push(&listing.exchange);
+        }
+        *total += volume;
+    }
+
+    let mut ranked: Vec<(&TradingPair, f64)> = counted
+        .into_iter()
+        .filter(|(_, (exchanges, _))| exchanges.len() >= config.min_exchanges)
+        .map(|(pair, (_, total))| (pair, total))
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.1.total_cmp(&a.1)
+            .then_with(|| (&a.0.base, &a.0.quote).cmp(&(&b.0.base, &b.0.quote)))
+    });
+    if let Some(max_pairs) = config.max_pairs {
+        ranked.truncate(max_pairs);
+    }
+    ranked.into_iter().map(|(pair, _)| pair.clone()).collect()
+}
@@ -73,6 +73,10 @@ pub enum AggregatorError {
     #[error("Authentication error: {message}")]
     Authentication { message: String },
 
+    /// Represents an authenticated client attempting something it is not permitted to.
+    #[error("Forbidden: {message}")]
+    Forbidden { message: String },
+
     /// Represents a rate limit exceeded error.
     #[error("Rate limit exceeded for {resource}: {message}")]
     RateLimit { resource: String, message: String },
@@ -101,6 +105,7 @@ pub type Result<T> = std::result::Result<T, AggregatorError>;
 #[derive(Error, Debug)]
 pub enum HttpError {
     /// Represents a failed HTTP request.
+    #[cfg(feature = "http")]
     #[error("Request failed: {0}")]
     Request(#[from] reqwest::Error),
 
@@ -175,6 +180,7 @@ pub enum ExchangeError {
 impl From<HttpError> for AggregatorError {
     fn from(err: HttpError) -> Self {
         match err {
+            #[cfg(feature = "http")]
             HttpError::Request(e) => AggregatorError::NetworkError {
                 message: e.to_string(),
             },
@@ -297,6 +303,7 @@ impl From<tokio::time::error::Elapsed> for AggregatorError {
 /// defines a `From` trait implementation for converting a `reqwest::Error` into an `AggregatorError`.
 /// Inside the implementation, it creates a new `AggregatorError` with a variant `Network` and sets the
 /// error message to the string representation of the original `reqwest::Error`.
+#[cfg(feature = "http")]
 impl From<reqwest::Error> for AggregatorError {
     fn from(e: reqwest::Error) -> Self {
         AggregatorError::NetworkError {
@@ -310,6 +317,7 @@ impl From<reqwest::Error> for AggregatorError {
 /// `tungstenite::Error` is encountered, it will be converted into an `AggregatorError` with a variant
 /// `WebSocket`, containing the error message from the original `tungstenite::Error` converted to a
 /// string.
+#[cfg(feature = "websocket")]
 impl From<tungstenite::Error> for AggregatorError {
     fn from(e: tungstenite::Error) -> Self {
         AggregatorError::WebSocketError {
@@ -334,7 +342,7 @@ impl AggregatorError {
     /// An AggregatorError enum variant called Parsing is being returned, with the data_type and message
     /// fields set to the string representations of the input data_type and message parameters,
     /// respectively.
-    pub fn parsing<T: AsRef<str>>(data_type: T, message: T) -> Self {
+    pub fn parsing<D: AsRef<str>, M: AsRef<str>>(data_type: D, message: M) -> Self {
         AggregatorError::Parsing {
             data_type: data_type.as_ref().to_string(),
             message: message.as_ref().to_string(),
@@ -346,17 +354,17 @@ impl AggregatorError {
     ///
     /// Arguments:
     ///
-    /// * `exchange`: The `exchange` parameter is of type `T`, which must implement the `AsRef<str>` trait.
+    /// * `exchange`: The `exchange` parameter is of type `E`, which must implement the `AsRef<str>` trait.
     /// This means that `exchange` can be any type that can be converted into a string reference.
-    /// * `message`: The `message` parameter in the `exchange` function is of type `T`, which must implement
-    /// the `AsRef<str>` trait. This means that `message` can be any type that can be converted into a
-    /// string reference.
+    /// * `message`: The `message` parameter in the `exchange` function is of type `M`, which must implement
+    ///   the `AsRef<str>` trait. It does not need to be the same type as `exchange`, so a `&str` exchange
+    ///   name can be paired with a `String` built by `format!`.
     ///
     /// Returns:
     ///
     /// An `AggregatorError` enum variant `ExchangeError` is being returned with the exchange and message
     /// converted to strings.
-    pub fn exchange<T: AsRef<str>>(exchange: T, message: T) -> Self {
+    pub fn exchange<E: AsRef<str>, M: AsRef<str>>(exchange: E, message: M) -> Self {
         AggregatorError::ExchangeError {
             exchange: exchange.as_ref().to_string(),
             message: message.as_ref().to_string(),
@@ -420,7 +428,7 @@ impl AggregatorError {
     ///
     /// An AggregatorError enum variant called Validation is being returned, with the field and message
     /// values converted to strings using the as_ref() method.
-    pub fn validation<T: AsRef<str>>(field: T, message: T) -> Self {
+    pub fn validation<F: AsRef<str>, M: AsRef<str>>(field: F, message: M) -> Self {
         AggregatorError::Validation {
             field: field.as_ref().to_string(),
             message: message.as_ref().to_string(),
@@ -447,6 +455,63 @@ impl AggregatorError {
         }
     }
 
+    /// The function `rate_limit` creates an `AggregatorError::RateLimit` instance for a resource whose
+    /// request budget has been exhausted.
+    ///
+    /// Arguments:
+    ///
+    /// * `resource`: The `resource` parameter names the rate limited resource, such as an exchange REST
+    ///   endpoint. It must implement `AsRef<str>`.
+    /// * `message`: The `message` parameter describes why the request was rejected.
+    ///
+    /// Returns:
+    ///
+    /// An `AggregatorError` enum variant `RateLimit` with the `resource` and `message` values converted
+    /// to strings.
+    pub fn rate_limit<R: AsRef<str>, M: AsRef<str>>(resource: R, message: M) -> Self {
+        AggregatorError::RateLimit {
+            resource: resource.as_ref().to_string(),
+            message: message.as_ref().to_string(),
+        }
+    }
+
+    /// The function `forbidden` creates an `AggregatorError::Forbidden` instance for a client
+    /// that authenticated but lacks the permission an action requires.
+    ///
+    /// Arguments:
+    ///
+    /// * `message`: The `message` parameter describes what was refused, such as the channel or
+    ///   endpoint the client is not allowed to use.
+    ///
+    /// Returns:
+    ///
+    /// An `AggregatorError` enum variant `Forbidden` with the `message` converted to a string.
+    pub fn forbidden<M: AsRef<str>>(message: M) -> Self {
+        AggregatorError::Forbidden {
+            message: message.as_ref().to_string(),
+        }
+    }
+
+    /// The function `database` creates an `AggregatorError::Database` instance for a storage
+    /// operation that failed.
+    ///
+    /// Arguments:
+    ///
+    /// * `operation`: The `operation` parameter names what was being done, such as `insert` or
+    ///   `query`.
+    /// * `message`: The `message` parameter describes why the operation failed.
+    ///
+    /// Returns:
+    ///
+    /// An `AggregatorError` enum variant `Database` with the `operation` and `message` values
+    /// converted to strings.
+    pub fn database<O: AsRef<str>, M: AsRef<str>>(operation: O, message: M) -> Self {
+        AggregatorError::Database {
+            operation: operation.as_ref().to_string(),
+            message: message.as_ref().to_string(),
+        }
+    }
+
     /// The function `is_recoverable` in Rust checks if an error is recoverable based on specific error
     /// types.
     ///
@@ -475,8 +540,8 @@ impl AggregatorError {
     /// The `category` method returns a string representing the category of the `AggregatorError` enum
     /// variant that is being matched. The returned string corresponds to different error categories such as
     /// configuration, serialization, channel, exchange, orderbook, network, http, websocket, parsing,
-    /// timeout, io, url, uuid, validation, database, authentication, rate_limit, internal, not_found,
-    /// already_exists, and
+    /// timeout, io, url, uuid, validation, database, authentication, forbidden, rate_limit, internal,
+    /// not_found, already_exists, and
     pub fn category(&self) -> &'static str {
         match self {
             AggregatorError::Config(..) => "configuration",
@@ -496,6 +561,7 @@ impl AggregatorError {
             AggregatorError::Validation { .. } => "validation",
             AggregatorError::Database { .. } => "database",
             AggregatorError::Authentication { .. } => "authentication",
+            AggregatorError::Forbidden { .. } => "forbidden",
             AggregatorError::RateLimit { .. } => "rate_limit",
             AggregatorError::Internal { .. } => "internal",
             AggregatorError::NotFound { .. } => "not_found",
@@ -0,0 +1,167 @@
+//! Counting errors by category and keeping the latest ones, so what is failing can be seen
+//! without searching the logs
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ErrorTelemetryConfig;
+use crate::error::AggregatorError;
+use crate::telemetry::MetricsRegistry;
+use crate::types::Exchange;
+
+/// One error the aggregator ran into.
+///
+/// - `category`: `AggregatorError::category` of the error, such as "parsing" or "database".
+/// - `source`: The part of the aggregator that hit it, such as "price_level_processor".
+/// - `exchange`: The exchange it concerns, if any.
+/// - `message`: The error as displayed.
+/// - `recoverable`: `AggregatorError::is_recoverable` of the error.
+/// - `timestamp`: When it was recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorRecord {
+    pub category: String,
+    pub source: String,
+    pub exchange: Option<Exchange>,
+    pub message: String,
+    pub recoverable: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Errors counted since start and the latest ones.
+///
+/// - `total`: Errors recorded.
+/// - `by_category`: Errors recorded per category, only listing categories seen.
+/// - `recent`: The latest errors still kept, newest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorReport {
+    pub total: u64,
+    pub by_category: BTreeMap<String, u64>,
+    pub recent: Vec<ErrorRecord>,
+}
+
+#[derive(Debug, Default)]
+struct TelemetryState {
+    config: ErrorTelemetryConfig,
+    counts: BTreeMap<String, u64>,
+    /// Oldest first, at most `config.max_recent`
+    recent: VecDeque<ErrorRecord>,
+}
+
+/// Per category error counters and a ring buffer of the latest errors. With a registry, the
+/// counters are also exported as `aggregator_errors_total{category}`. Clones share the same
+/// counters.
+///
+/// ```rust
+/// use aggregator_core::{AggregatorError, ErrorTelemetry, ErrorTelemetryConfig};
+///
+/// let errors = ErrorTelemetry::new(ErrorTelemetryConfig::default());
+/// let now = chrono::Utc::now();
+/// errors.record("storage", None, &AggregatorError::database("insert", "disk full"), now);
+/// errors.record("storage", None, &AggregatorError::timeout("save", 5000), now);
+/// assert_eq!(errors.count("database"), 1);
+/// let report = errors.report(None, None);
+/// assert_eq!(report.total, 2);
+/// assert_eq!(report.recent[0].category, "timeout");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ErrorTelemetry {
+    state: Arc<Mutex<TelemetryState>>,
+    registry: Option<Arc<MetricsRegistry>>,
+}
+
+impl ErrorTelemetry {
+    pub fn new(config: ErrorTelemetryConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(TelemetryState {
+                config,
+                ..TelemetryState::default()
+            })),
+            registry: None,
+        }
+    }
+
+    /// Also counts errors in `registry`, for Prometheus
+    pub fn with_registry(mut self, registry: Arc<MetricsRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Counts `error`, hit by `source` for `exchange` at `at`, and keeps it among the recent
+    /// ones. Returns what was recorded.
+    pub fn record(
+        &self,
+        source: &str,
+        exchange: Option<&Exchange>,
+        error: &AggregatorError,
+        at: DateTime<Utc>,
+    ) -> ErrorRecord {
+        let record = ErrorRecord {
+            category: error.category().to_string(),
+            source: source.to_string(),
+            exchange: exchange.cloned(),
+            message: error.to_string(),
+            recoverable: error.is_recoverable(),
+            timestamp: at,
+        };
+        if let Some(registry) = &self.registry {
+            registry
+                .counter(
+                    "aggregator_errors_total",
+                    "Errors the aggregator ran into, by category",
+                    &[("category", &record.category)],
+                )
+                .inc();
+        }
+
+        let mut state = self.lock();
+        *state.counts.entry(record.category.clone()).or_default() += 1;
+        state.recent.push_back(record.clone());
+        let excess = state.recent.len().saturating_sub(state.config.max_recent);
+        state.recent.drain(..excess);
+        record
+    }
+
+    /// Errors of `category` recorded since start
+    pub fn count(&self, category: &str) -> u64 {
+        self.lock().counts.get(category).copied().unwrap_or(0)
+    }
+
+    /// Errors recorded since start, per category
+    pub fn counts(&self) -> BTreeMap<String, u64> {
+        self.lock().counts.clone()
+    }
+
+    /// The latest errors, of `category` only if given, newest first and at most `limit`
+    pub fn recent(&self, category: Option<&str>, limit: Option<usize>) -> Vec<ErrorRecord> {
+        self.lock()
+            .recent
+            .iter()
+            .rev()
+            .filter(|record| category.is_none_or(|category| record.category == category))
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    /// The counters with the latest errors as `recent` gives them
+    pub fn report(&self, category: Option<&str>, limit: Option<usize>) -> ErrorReport {
+        let by_category = self.counts();
+        ErrorReport {
+            total: by_category.values().sum(),
+            by_category,
+            recent: self.recent(category, limit),
+        }
+    }
+
+    // Each update leaves the counters and buffer consistent, so a poisoned lock is still usable
+    fn lock(&self) -> MutexGuard<'_, TelemetryState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
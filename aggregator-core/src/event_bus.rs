@@ -0,0 +1,176 @@
+//! Typed publish/subscribe topics shared by the aggregator and its consumers
+
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use tokio::sync::broadcast;
+
+use crate::backpressure::{BackpressureSnapshot, BackpressureStats, BoundedReceiver, Conflate};
+use crate::circuit_breaker::CircuitBreakerEvent;
+use crate::config::BackpressureConfig;
+use crate::replay::ReplayReport;
+use crate::types::{ArbitrageOpportunity, HealthStatus, Metrics, Summary, SystemHealth, Trade};
+
+/// A type published on an [`EventBus`]. Each event type is its own topic.
+///
+/// Implement it for a new type to give it a topic; nothing else needs to change. Subscribers
+/// that fall more than `CAPACITY` events behind miss the oldest ones and are told how many
+/// with `RecvError::Lagged`.
+pub trait Event: Clone + Send + Sync + 'static {
+    /// Name of the topic, used in logs
+    const TOPIC: &'static str;
+    const CAPACITY: usize = 1000;
+}
+
+/// Consolidated summary of a pair, published every time an exchange updates it
+impl Event for Summary {
+    const TOPIC: &'static str = "summaries";
+}
+
+/// Opportunities found by the arbitrage loop
+impl Event for ArbitrageOpportunity {
+    const TOPIC: &'static str = "arbitrage";
+}
+
+/// An exchange's health, published when it is reported or found stale
+impl Event for HealthStatus {
+    const TOPIC: &'static str = "health";
+}
+
+/// The aggregator's overall health, published by the health monitor when its level changes
+impl Event for SystemHealth {
+    const TOPIC: &'static str = "system-health";
+}
+
+/// Throughput and latency of each exchange feed, published by the health monitor
+impl Event for Metrics {
+    const TOPIC: &'static str = "metrics";
+}
+
+/// Arbitrage being paused or resumed, published by the arbitrage loop
+impl Event for CircuitBreakerEvent {
+    const TOPIC: &'static str = "circuit-breaker";
+}
+
+/// How a replay ended, published once by `Aggregator::start_replay`
+impl Event for ReplayReport {
+    const TOPIC: &'static str = "replay";
+}
+
+impl Event for Trade {
+    const TOPIC: &'static str = "trades";
+    const CAPACITY: usize = 10000;
+}
+
+struct Topic {
+    name: &'static str,
+    /// A `broadcast::Sender<T>` for the event type the topic is keyed by
+    sender: Box<dyn Any + Send + Sync>,
+    /// Counters of every bounded subscriber of the topic
+    backpressure: Arc<BackpressureStats>,
+}
+
+/// One broadcast channel per [`Event`] type, created on first use.
+///
+/// ```rust
+/// use aggregator_core::{EventBus, Summary};
+///
+/// let bus = EventBus::new();
+/// let mut summaries = bus.subscribe::<Summary>();
+/// assert_eq!(bus.subscriber_count::<Summary>(), 1);
+/// ```
+#[derive(Default)]
+pub struct EventBus {
+    topics: RwLock<HashMap<TypeId, Topic>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sending half of the `T` topic, for tasks that publish often
+    pub fn sender<T: Event>(&self) -> broadcast::Sender<T> {
+        self.with_topic::<T, _>(Self::typed)
+    }
+
+    pub fn subscribe<T: Event>(&self) -> broadcast::Receiver<T> {
+        self.sender::<T>().subscribe()
+    }
+
+    /// Subscribes to `T` through a buffer bounded by `config`, so a slow subscriber loses
+    /// events under its policy rather than lagging behind the topic. What it loses is counted
+    /// in [`EventBus::backpressure`].
+    pub fn subscribe_bounded<T: Event + Conflate>(
+        &self,
+        config: &BackpressureConfig,
+    ) -> BoundedReceiver<T> {
+        let (receiver, totals) = self.with_topic::<T, _>(|topic| {
+            (Self::typed(topic).subscribe(), topic.backpressure.clone())
+        });
+        BoundedReceiver::new(receiver, config, totals)
+    }
+
+    /// Totals of every bounded subscriber of `T` so far
+    pub fn backpressure<T: Event>(&self) -> BackpressureSnapshot {
+        self.with_topic::<T, _>(|topic| topic.backpressure.snapshot())
+    }
+
+    /// Totals of the bounded subscribers of every topic used so far, by topic name
+    pub fn backpressure_by_topic(&self) -> BTreeMap<&'static str, BackpressureSnapshot> {
+        let topics = self.topics.read().unwrap_or_else(PoisonError::into_inner);
+        topics
+            .values()
+            .map(|topic| (topic.name, topic.backpressure.snapshot()))
+            .collect()
+    }
+
+    /// Publishes `event` to every current subscriber of its topic and returns how many there
+    /// were. Publishing with no subscribers drops the event.
+    pub fn publish<T: Event>(&self, event: T) -> usize {
+        // Sending only fails when nobody is subscribed
+        self.sender::<T>().send(event).unwrap_or(0)
+    }
+
+    pub fn subscriber_count<T: Event>(&self) -> usize {
+        self.sender::<T>().receiver_count()
+    }
+
+    /// Names of the topics used so far, sorted
+    pub fn topics(&self) -> Vec<&'static str> {
+        let topics = self.topics.read().unwrap_or_else(PoisonError::into_inner);
+        let mut names: Vec<&'static str> = topics.values().map(|topic| topic.name).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Runs `f` on the `T` topic, creating it on first use
+    fn with_topic<T: Event, R>(&self, f: impl FnOnce(&Topic) -> R) -> R {
+        let key = TypeId::of::<T>();
+        if let Some(topic) = self
+            .topics
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+        {
+            return f(topic);
+        }
+
+        let mut topics = self.topics.write().unwrap_or_else(PoisonError::into_inner);
+        let topic = topics.entry(key).or_insert_with(|| Topic {
+            name: T::TOPIC,
+            sender: Box::new(broadcast::channel::<T>(T::CAPACITY).0),
+            backpressure: Arc::new(BackpressureStats::new()),
+        });
+        f(topic)
+    }
+
+    fn typed<T: Event>(topic: &Topic) -> broadcast::Sender<T> {
+        topic
+            .sender
+            .downcast_ref::<broadcast::Sender<T>>()
+            .expect("topics are keyed by the TypeId of their event type")
+            .clone()
+    }
+}
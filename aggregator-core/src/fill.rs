@@ -0,0 +1,105 @@
+//! Cost-to-fill estimates from walking an order book ladder
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Summary, TradeSide};
+
+/// Expected execution of a market order that walks the book.
+///
+/// # Fields
+/// - `side`: The side of the order; a `Buy` walks the asks, a `Sell` walks the bids.
+/// - `requested_quantity`: The quantity the order asked for, in base currency.
+/// - `filled_quantity`: The quantity the visible book can fill, at most `requested_quantity`.
+/// - `average_price`: The volume-weighted average execution price.
+/// - `worst_price`: The price of the deepest level the order reaches.
+/// - `total_cost`: The quote currency spent (buys) or received (sells).
+/// - `levels_consumed`: The number of ladder entries the order touches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FillEstimate {
+    pub side: TradeSide,
+    pub requested_quantity: f64,
+    pub filled_quantity: f64,
+    pub average_price: f64,
+    pub worst_price: f64,
+    pub total_cost: f64,
+    pub levels_consumed: usize,
+}
+
+impl FillEstimate {
+    /// Walks `(price, quantity)` levels, best first, until `quantity` is filled or the levels
+    /// run out.
+    ///
+    /// Returns `None` when `quantity` is not positive or no level has any quantity.
+    pub fn from_levels(
+        side: TradeSide,
+        levels: impl IntoIterator<Item = (f64, f64)>,
+        quantity: f64,
+    ) -> Option<Self> {
+        if quantity.is_nan() || quantity <= 0.0 {
+            return None;
+        }
+
+        let mut remaining = quantity;
+        let mut total_cost = 0.0;
+        let mut worst_price = None;
+        let mut levels_consumed = 0;
+
+        for (price, available) in levels {
+            if remaining <= 0.0 {
+                break;
+            }
+            if available <= 0.0 {
+                continue;
+            }
+            let taken = available.min(remaining);
+            total_cost += taken * price;
+            remaining -= taken;
+            worst_price = Some(price);
+            levels_consumed += 1;
+        }
+
+        let filled_quantity = quantity - remaining.max(0.0);
+        Some(Self {
+            side,
+            requested_quantity: quantity,
+            filled_quantity,
+            average_price: total_cost / filled_quantity,
+            worst_price: worst_price?,
+            total_cost,
+            levels_consumed,
+        })
+    }
+
+    /// Whether the visible book holds enough liquidity for the whole order.
+    pub fn is_complete(&self) -> bool {
+        self.filled_quantity >= self.requested_quantity
+    }
+
+    /// Difference between the average price and `reference` (usually the best price or mid), as
+    /// a percentage of `reference`; positive means the order pays to move through the book.
+    pub fn slippage_percentage(&self, reference: f64) -> f64 {
+        let slippage = match self.side {
+            TradeSide::Buy => self.average_price - reference,
+            TradeSide::Sell => reference - self.average_price,
+        };
+        slippage / reference * 100.0
+    }
+}
+
+impl Summary {
+    /// Estimates filling a market order of `quantity` against the levels in this summary.
+    ///
+    /// A summary only carries the top of the book, so large orders may come back incomplete.
+    pub fn cost_to_fill(&self, side: TradeSide, quantity: f64) -> Option<FillEstimate> {
+        let levels = match side {
+            TradeSide::Buy => &self.asks,
+            TradeSide::Sell => &self.bids,
+        };
+        FillEstimate::from_levels(
+            side,
+            levels.iter().map(|level| (level.price, level.quantity)),
+            quantity,
+        )
+    }
+}
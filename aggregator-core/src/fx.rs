@@ -0,0 +1,176 @@
+//! Converting prices between quote currencies, so books quoted in USDT, USD and USDC can be
+//! compared
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use chrono::{DateTime, Utc};
+
+use crate::config::FxConfig;
+use crate::types::{Summary, TradingPair};
+
+/// Shared, thread-safe exchange rates between currencies. Clones share the same rates, so the
+/// aggregator can keep them live from its books while analysis engines read them.
+///
+/// A conversion uses, in order: the identity, a live rate between the two currencies in either
+/// direction, and otherwise the product of each currency's rate to `FxConfig::reference`. A
+/// currency's rate to the reference is a live one when there is one, else that of the currency
+/// `FxConfig::equivalences` pegs it to, 1:1. Live rates older than `max_rate_age_secs` are
+/// ignored.
+///
+/// ```rust
+/// use aggregator_core::{FxConfig, FxRates};
+///
+/// let fx = FxRates::new(FxConfig::default());
+/// let now = chrono::Utc::now();
+/// // USDT and USDC are pegged to USD until a live rate says otherwise
+/// assert_eq!(fx.rate("USDT", "USDC", now), Some(1.0));
+/// fx.set_rate("USDT", "USD", 0.998, now);
+/// let usd = fx.convert(100.0, "USDT", "USD", now).unwrap();
+/// assert!((usd - 99.8).abs() < 1e-9);
+/// assert_eq!(fx.rate("EUR", "USD", now), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FxRates {
+    state: Arc<RwLock<FxState>>,
+}
+
+#[derive(Debug, Default)]
+struct FxState {
+    config: FxConfig,
+    /// Units of the second currency one unit of the first buys, and when that was observed
+    rates: HashMap<(String, String), (f64, DateTime<Utc>)>,
+}
+
+impl FxRates {
+    pub fn new(config: FxConfig) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(FxState {
+                config,
+                rates: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Switches to the equivalences and rate pairs of `config`, keeping the live rates
+    pub fn configure(&self, config: FxConfig) {
+        self.write().config = config;
+    }
+
+    /// The currency prices are normalized to
+    pub fn reference(&self) -> String {
+        self.read().config.reference.to_uppercase()
+    }
+
+    /// Records that one `base` bought `rate` of `quote` at `at`. Rates that are not positive
+    /// are ignored.
+    pub fn set_rate(&self, base: &str, quote: &str, rate: f64, at: DateTime<Utc>) {
+        if rate > 0.0 && rate.is_finite() {
+            self.write()
+                .rates
+                .insert((base.to_uppercase(), quote.to_uppercase()), (rate, at));
+        }
+    }
+
+    /// Takes the mid price of `summary` as the live rate of `pair` if it is one of the
+    /// configured `rate_pairs`. Returns whether a rate was recorded.
+    pub fn observe(&self, pair: &TradingPair, summary: &Summary) -> bool {
+        if !self.read().config.rate_pairs.contains(pair) {
+            return false;
+        }
+        let best_bid = summary
+            .bids
+            .iter()
+            .map(|level| level.price)
+            .reduce(f64::max);
+        let best_ask = summary
+            .asks
+            .iter()
+            .map(|level| level.price)
+            .reduce(f64::min);
+        let (Some(bid), Some(ask)) = (best_bid, best_ask) else {
+            return false;
+        };
+        self.set_rate(
+            &pair.base,
+            &pair.quote,
+            (bid + ask) / 2.0,
+            summary.timestamp,
+        );
+        true
+    }
+
+    /// Units of `to` one unit of `from` is worth as of `now`, if the currencies can be related
+    pub fn rate(&self, from: &str, to: &str, now: DateTime<Utc>) -> Option<f64> {
+        let state = self.read();
+        let (from, to) = (from.to_uppercase(), to.to_uppercase());
+        if let Some(rate) = state.direct(&from, &to, now) {
+            return Some(rate);
+        }
+        let reference = state.config.reference.to_uppercase();
+        Some(
+            state.to_reference(&from, &reference, now)?
+                / state.to_reference(&to, &reference, now)?,
+        )
+    }
+
+    /// `amount` of `from` expressed in `to`
+    pub fn convert(&self, amount: f64, from: &str, to: &str, now: DateTime<Utc>) -> Option<f64> {
+        self.rate(from, to, now).map(|rate| amount * rate)
+    }
+
+    /// Whether prices in `a` and `b` compare without a live rate: the same currency, or pegged
+    /// to the same one
+    pub fn equivalent(&self, a: &str, b: &str) -> bool {
+        let state = self.read();
+        state.pegged(&a.to_uppercase()) == state.pegged(&b.to_uppercase())
+    }
+
+    // A panic while holding the lock cannot leave the rates half-updated, so poisoning is ignored
+    fn read(&self) -> RwLockReadGuard<'_, FxState> {
+        self.state
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, FxState> {
+        self.state
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl FxState {
+    /// The identity, or a fresh live rate between `from` and `to` in either direction
+    fn direct(&self, from: &str, to: &str, now: DateTime<Utc>) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        let max_age = chrono::Duration::seconds(self.config.max_rate_age_secs as i64);
+        let fresh = |key: (&str, &str)| {
+            self.rates
+                .get(&(key.0.to_string(), key.1.to_string()))
+                .filter(|(_, at)| now - *at <= max_age)
+                .map(|(rate, _)| *rate)
+        };
+        fresh((from, to)).or_else(|| fresh((to, from)).map(|rate| 1.0 / rate))
+    }
+
+    fn to_reference(&self, currency: &str, reference: &str, now: DateTime<Utc>) -> Option<f64> {
+        self.direct(currency, reference, now).or_else(|| {
+            let pegged = self.pegged(currency);
+            (pegged != currency)
+                .then(|| self.direct(&pegged, reference, now))
+                .flatten()
+        })
+    }
+
+    /// The currency `currency` is pegged to, or itself
+    fn pegged(&self, currency: &str) -> String {
+        self.config
+            .equivalences
+            .iter()
+            .find(|(asset, _)| asset.eq_ignore_ascii_case(currency))
+            .map_or_else(|| currency.to_string(), |(_, peg)| peg.to_uppercase())
+    }
+}
@@ -0,0 +1,201 @@
+//! Instrument metadata (tick size, lot size, order minimums) per exchange
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::types::{Exchange, TradingPair};
+use crate::{AggregatorError, Result};
+
+/// Tolerance, in steps, when checking whether a value sits on a tick or lot boundary.
+const STEP_TOLERANCE: f64 = 1e-6;
+
+/// Most decimal places a step is assumed to have; f64 holds about 15 significant digits.
+const MAX_PRECISION: u32 = 15;
+
+/// Rounds `price` to the nearest multiple of `tick_size`, or returns it unchanged when the tick
+/// size is not positive.
+pub fn round_to_tick(price: f64, tick_size: f64) -> f64 {
+    InstrumentInfo::round_to_step(price, tick_size, f64::round)
+}
+
+/// Trading rules of one instrument on one exchange.
+///
+/// # Fields
+/// - `exchange`: The exchange listing the instrument.
+/// - `pair`: The trading pair the instrument trades.
+/// - `symbol`: The exchange's native symbol (e.g., "BTCUSDT").
+/// - `tick_size`: The smallest price increment.
+/// - `lot_size`: The smallest quantity increment, in base currency.
+/// - `min_quantity`: The smallest order quantity accepted, in base currency.
+/// - `min_notional`: The smallest order value accepted, in quote currency, if the exchange has one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstrumentInfo {
+    pub exchange: Exchange,
+    pub pair: TradingPair,
+    pub symbol: String,
+    pub tick_size: f64,
+    pub lot_size: f64,
+    pub min_quantity: f64,
+    pub min_notional: Option<f64>,
+}
+
+impl InstrumentInfo {
+    /// Number of decimal places prices are quoted with.
+    pub fn price_precision(&self) -> u32 {
+        Self::precision(self.tick_size)
+    }
+
+    /// Number of decimal places quantities are quoted with.
+    pub fn quantity_precision(&self) -> u32 {
+        Self::precision(self.lot_size)
+    }
+
+    /// Rounds `price` to the nearest tick.
+    pub fn round_price(&self, price: f64) -> f64 {
+        round_to_tick(price, self.tick_size)
+    }
+
+    /// Rounds `quantity` down to a whole number of lots, so it never exceeds what was requested.
+    pub fn round_quantity(&self, quantity: f64) -> f64 {
+        Self::round_to_step(quantity, self.lot_size, f64::floor)
+    }
+
+    /// Checks an order against the instrument's rules: price on a tick, quantity on a lot and at
+    /// least `min_quantity`, and a value of at least `min_notional`.
+    pub fn validate_order(&self, price: f64, quantity: f64) -> Result<()> {
+        if price <= 0.0 || !Self::on_step(price, self.tick_size) {
+            return Err(AggregatorError::validation(
+                "price",
+                format!(
+                    "{} is not a multiple of tick size {}",
+                    price, self.tick_size
+                ),
+            ));
+        }
+        if !Self::on_step(quantity, self.lot_size) {
+            return Err(AggregatorError::validation(
+                "quantity",
+                format!(
+                    "{} is not a multiple of lot size {}",
+                    quantity, self.lot_size
+                ),
+            ));
+        }
+        if quantity <= 0.0 || quantity < self.min_quantity {
+            return Err(AggregatorError::validation(
+                "quantity",
+                format!("{} is below the minimum of {}", quantity, self.min_quantity),
+            ));
+        }
+        if let Some(min_notional) = self.min_notional {
+            let notional = price * quantity;
+            if notional < min_notional {
+                return Err(AggregatorError::validation(
+                    "notional",
+                    format!("{} is below the minimum of {}", notional, min_notional),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn precision(step: f64) -> u32 {
+        if step <= 0.0 || step >= 1.0 {
+            return 0;
+        }
+        // Fewest decimal places that hold the step exactly, so 0.25 needs 2 rather than 1
+        (0..MAX_PRECISION)
+            .find(|&places| {
+                let scaled = step * 10f64.powi(places as i32);
+                (scaled - scaled.round()).abs() < STEP_TOLERANCE
+            })
+            .unwrap_or(MAX_PRECISION)
+    }
+
+    fn round_to_step(value: f64, step: f64, round: fn(f64) -> f64) -> f64 {
+        if step <= 0.0 {
+            return value;
+        }
+        // Nudge by the tolerance so values already on a step are not floored one step down
+        let steps = round(value / step + STEP_TOLERANCE.copysign(value));
+        // Trim the floating point noise left by the multiplication
+        let scale = 10f64.powi(Self::precision(step) as i32);
+        (steps * step * scale).round() / scale
+    }
+
+    fn on_step(value: f64, step: f64) -> bool {
+        if step <= 0.0 {
+            return true;
+        }
+        let steps = value / step;
+        (steps - steps.round()).abs() < STEP_TOLERANCE
+    }
+}
+
+/// Shared, thread-safe store of instrument metadata keyed by exchange and pair. Clones share the
+/// same underlying map, so connectors can fill a registry that analysis code reads.
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    instruments: Arc<RwLock<HashMap<(Exchange, TradingPair), InstrumentInfo>>>,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces the metadata for `info.exchange` and `info.pair`.
+    pub fn insert(&self, info: InstrumentInfo) {
+        self.write()
+            .insert((info.exchange.clone(), info.pair.clone()), info);
+    }
+
+    pub fn extend(&self, instruments: impl IntoIterator<Item = InstrumentInfo>) {
+        let mut map = self.write();
+        for info in instruments {
+            map.insert((info.exchange.clone(), info.pair.clone()), info);
+        }
+    }
+
+    pub fn get(&self, exchange: &Exchange, pair: &TradingPair) -> Option<InstrumentInfo> {
+        self.read().get(&(exchange.clone(), pair.clone())).cloned()
+    }
+
+    /// Returns every instrument known for `exchange`.
+    pub fn for_exchange(&self, exchange: &Exchange) -> Vec<InstrumentInfo> {
+        self.read()
+            .values()
+            .filter(|info| &info.exchange == exchange)
+            .cloned()
+            .collect()
+    }
+
+    /// Rounds `price` to the tick size of `pair` on `exchange`, or returns it unchanged when the
+    /// instrument is unknown.
+    pub fn round_price(&self, exchange: &Exchange, pair: &TradingPair, price: f64) -> f64 {
+        self.get(exchange, pair)
+            .map_or(price, |info| info.round_price(price))
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    // A panic while holding the lock cannot leave the map half-updated, so poisoning is ignored
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<(Exchange, TradingPair), InstrumentInfo>> {
+        self.instruments
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<(Exchange, TradingPair), InstrumentInfo>> {
+        self.instruments
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
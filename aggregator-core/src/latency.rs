@@ -0,0 +1,66 @@
+//! Rolling latency statistics for exchange feeds
+
+use std::collections::VecDeque;
+
+/// Number of recent samples kept by `LatencyTracker::default()`.
+pub const DEFAULT_LATENCY_WINDOW: usize = 1000;
+
+/// Keeps the most recent latency samples, in milliseconds, and reports statistics over them.
+/// Once the window is full each new sample evicts the oldest one.
+#[derive(Debug, Clone)]
+pub struct LatencyTracker {
+    samples: VecDeque<f64>,
+    capacity: usize,
+}
+
+impl LatencyTracker {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn record(&mut self, latency_ms: f64) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency_ms);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Mean of the samples in the window, or 0 when there are none.
+    pub fn mean(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+
+    /// Nearest-rank percentile of the samples in the window, with `percentile` between 0 and
+    /// 100. Returns 0 when there are no samples.
+    pub fn percentile(&self, percentile: f64) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let mut sorted: Vec<f64> = self.samples.iter().copied().collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted[rank.saturating_sub(1)]
+    }
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_LATENCY_WINDOW)
+    }
+}
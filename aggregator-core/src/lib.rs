@@ -1,11 +1,59 @@
 //! Core types and traits for cryptocurrency orderbook aggregation
 
 pub mod aggregator;
+pub mod analysis;
+pub mod backpressure;
+pub mod book_delta;
+pub mod circuit_breaker;
+pub mod clock;
 pub mod config;
+pub mod config_watcher;
+pub mod connector;
+#[cfg(feature = "decimal")]
+pub mod decimal;
+pub mod discovery;
 pub mod error;
+pub mod error_telemetry;
+pub mod event_bus;
+pub mod fill;
+pub mod fx;
+pub mod instrument;
+pub mod latency;
+#[cfg(feature = "logging")]
+pub mod logging;
+pub mod metrics_history;
+pub mod pair_book;
+pub mod replay;
+pub mod shutdown;
+pub mod storage;
+pub mod symbol;
+pub mod telemetry;
 pub mod types;
 
 pub use aggregator::*;
+pub use analysis::*;
+pub use backpressure::*;
+pub use book_delta::*;
+pub use circuit_breaker::*;
+pub use clock::*;
 pub use config::*;
+pub use config_watcher::*;
+pub use connector::*;
+pub use discovery::*;
 pub use error::*;
+pub use error_telemetry::*;
+pub use event_bus::*;
+pub use fill::*;
+pub use fx::*;
+pub use instrument::*;
+pub use latency::*;
+#[cfg(feature = "logging")]
+pub use logging::*;
+pub use metrics_history::*;
+pub use pair_book::*;
+pub use replay::*;
+pub use shutdown::*;
+pub use storage::*;
+pub use symbol::*;
+pub use telemetry::*;
 pub use types::*;
@@ -0,0 +1,172 @@
+//! `tracing` output set up from `LoggingConfig`
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::LoggingConfig;
+use crate::error::{AggregatorError, Result};
+
+/// Installs the global `tracing` subscriber described by `config`:
+///
+/// - `level` applies to every module, except those `modules` sets a level of their own for.
+/// - `format` is `json` for one JSON object per event, `pretty` for multi-line human readable
+///   events or `compact` for single-line ones.
+/// - `output` is `stdout`, `stderr` or `file`. Files are written to `file_path` and rotated by
+///   [`RotatingFileWriter`] once they would grow past `max_file_size`, keeping `max_files`.
+///
+/// Fails if `config` has any of the problems `LoggingConfig::issues` reports, if the log file
+/// cannot be opened, or if a global subscriber is already installed.
+pub fn init_logging(config: &LoggingConfig) -> Result<()> {
+    if let Some(issue) = config.issues().into_iter().next() {
+        return Err(AggregatorError::validation(issue.field, issue.message));
+    }
+    let filter = EnvFilter::try_new(filter_directives(config))
+        .map_err(|e| AggregatorError::validation("logging.modules", e.to_string()))?;
+
+    let (writer, ansi) = match config.output.as_str() {
+        "stdout" => (BoxMakeWriter::new(io::stdout), true),
+        "stderr" => (BoxMakeWriter::new(io::stderr), true),
+        _ => {
+            // `issues` guarantees a path for file output
+            let path = config.file_path.as_deref().unwrap_or_default();
+            let file = RotatingFileWriter::open(path, config.max_file_size, config.max_files)?;
+            (BoxMakeWriter::new(file), false)
+        }
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(ansi);
+    let installed = match config.format.as_str() {
+        "json" => builder.json().try_init(),
+        "pretty" => builder.pretty().try_init(),
+        _ => builder.compact().try_init(),
+    };
+    installed.map_err(|e| AggregatorError::Internal {
+        message: format!("Failed to install the logging subscriber: {}", e),
+    })
+}
+
+/// The `EnvFilter` directives for `config`: the default level, then each module's own level,
+/// sorted by module
+pub fn filter_directives(config: &LoggingConfig) -> String {
+    let mut modules: Vec<(&String, &String)> = config.modules.iter().collect();
+    modules.sort();
+    std::iter::once(config.level.to_ascii_lowercase())
+        .chain(
+            modules
+                .into_iter()
+                .map(|(module, level)| format!("{}={}", module, level.to_ascii_lowercase())),
+        )
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Debug)]
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_file_size: u64,
+    max_files: u32,
+}
+
+impl RotatingFile {
+    /// Path of the `index`th most recently rotated file, `index` 0 being the live one
+    fn rotated_path(&self, index: u32) -> PathBuf {
+        if index == 0 {
+            return self.path.clone();
+        }
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    /// Shifts every kept file one index older, dropping the oldest, and starts a new live file
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let oldest = self.max_files.saturating_sub(1);
+        if let Err(e) = fs::remove_file(self.rotated_path(oldest)) {
+            if e.kind() != io::ErrorKind::NotFound {
+                return Err(e);
+            }
+        }
+        for index in (0..oldest).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Appends to a log file, moving it to `<path>.1` once a write would grow it past
+/// `max_file_size` bytes. Earlier rotations move to `<path>.2`, `<path>.3` and so on, and at most
+/// `max_files` files are kept including the live one. Clones write to the same file.
+///
+/// A single write larger than `max_file_size` still goes to one file, so events are never split.
+#[derive(Debug, Clone)]
+pub struct RotatingFileWriter {
+    inner: Arc<Mutex<RotatingFile>>,
+}
+
+impl RotatingFileWriter {
+    /// Opens `path` for appending, creating it and its parent directories if needed
+    pub fn open(path: impl AsRef<Path>, max_file_size: u64, max_files: u32) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            inner: Arc::new(Mutex::new(RotatingFile {
+                path,
+                file,
+                size,
+                max_file_size: max_file_size.max(1),
+                max_files: max_files.max(1),
+            })),
+        })
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        if inner.size > 0 && inner.size + buf.len() as u64 > inner.max_file_size {
+            inner.rotate()?;
+        }
+        inner.file.write_all(buf)?;
+        inner.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .file
+            .flush()
+    }
+}
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for RotatingFileWriter {
+    type Writer = RotatingFileWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
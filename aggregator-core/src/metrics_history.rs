@@ -0,0 +1,300 @@
+//! A rolling history of each feed's update rate, latency and errors, for charting them over time
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::MetricsHistoryConfig;
+use crate::types::Exchange;
+
+/// What one exchange's feed of one symbol did over one interval of
+/// `MetricsHistoryConfig::resolution_secs`.
+///
+/// - `timestamp`: Start of the interval.
+/// - `updates`: Updates received.
+/// - `updates_per_second`: `updates` spread over the interval.
+/// - `latency_ms`, `max_latency_ms`: Mean and highest receive latency of the updates that
+///   carried an event time, 0 if none did.
+/// - `errors`: Updates that could not be processed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSample {
+    pub exchange: Exchange,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub updates: u64,
+    pub updates_per_second: f64,
+    pub latency_ms: f64,
+    pub max_latency_ms: f64,
+    pub errors: u64,
+}
+
+/// The interval of a series still being recorded
+#[derive(Debug)]
+struct OpenInterval {
+    start: DateTime<Utc>,
+    updates: u64,
+    latency_total_ms: f64,
+    latencies: u64,
+    max_latency_ms: f64,
+    errors: u64,
+}
+
+/// Samples of one exchange and symbol, oldest first
+#[derive(Debug, Default)]
+struct Series {
+    samples: VecDeque<MetricsSample>,
+    open: Option<OpenInterval>,
+}
+
+#[derive(Debug, Default)]
+struct HistoryState {
+    config: MetricsHistoryConfig,
+    series: HashMap<(Exchange, String), Series>,
+    /// Samples closed since the last `take_completed`, once it has been called
+    completed: Option<Vec<MetricsSample>>,
+}
+
+/// Records feed activity into fixed intervals per exchange and symbol, keeping
+/// `retention_secs` of them. An interval becomes a [`MetricsSample`] once a later one starts
+/// or `flush` passes its end. Clones share the same history.
+///
+/// ```rust
+/// use aggregator_core::{Exchange, MetricsHistory, MetricsHistoryConfig};
+/// use chrono::{Duration, TimeZone, Utc};
+///
+/// let history = MetricsHistory::new(MetricsHistoryConfig::default());
+/// let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+/// history.record_update(&Exchange::Binance, "BTCUSDT", start, Some(4.0));
+/// history.record_update(&Exchange::Binance, "BTCUSDT", start, Some(6.0));
+/// history.flush(start + Duration::minutes(1));
+/// let samples = history.samples(&Exchange::Binance, Some("BTCUSDT"), None);
+/// assert_eq!(samples[0].updates, 2);
+/// assert_eq!(samples[0].latency_ms, 5.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MetricsHistory {
+    state: Arc<Mutex<HistoryState>>,
+}
+
+impl MetricsHistory {
+    pub fn new(config: MetricsHistoryConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(HistoryState {
+                config,
+                ..HistoryState::default()
+            })),
+        }
+    }
+
+    /// Records an update of `symbol` received from `exchange` at `at`, with its receive latency
+    /// if known
+    pub fn record_update(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        at: DateTime<Utc>,
+        latency_ms: Option<f64>,
+    ) {
+        let mut state = self.lock();
+        if let Some(interval) = state.interval(exchange, symbol, at) {
+            interval.updates += 1;
+            if let Some(latency_ms) = latency_ms {
+                interval.latency_total_ms += latency_ms;
+                interval.latencies += 1;
+                interval.max_latency_ms = interval.max_latency_ms.max(latency_ms);
+            }
+        }
+    }
+
+    /// Records an update of `symbol` from `exchange` that could not be processed
+    pub fn record_error(&self, exchange: &Exchange, symbol: &str, at: DateTime<Utc>) {
+        let mut state = self.lock();
+        if let Some(interval) = state.interval(exchange, symbol, at) {
+            interval.errors += 1;
+        }
+    }
+
+    /// Closes every interval that ended by `now`, so feeds that went quiet still get their
+    /// last sample
+    pub fn flush(&self, now: DateTime<Utc>) {
+        let mut state = self.lock();
+        let HistoryState {
+            config,
+            series,
+            completed,
+        } = &mut *state;
+        let resolution = resolution(config);
+        for ((exchange, symbol), series) in series.iter_mut() {
+            if series
+                .open
+                .as_ref()
+                .is_some_and(|open| open.start + resolution <= now)
+            {
+                let sample = series.close(exchange, symbol, config);
+                if let (Some(completed), Some(sample)) = (completed.as_mut(), sample) {
+                    completed.push(sample);
+                }
+            }
+        }
+    }
+
+    /// Samples of `exchange`, of `symbol` or of all its symbols, from `since` on, oldest first
+    pub fn samples(
+        &self,
+        exchange: &Exchange,
+        symbol: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> Vec<MetricsSample> {
+        let state = self.lock();
+        let mut samples: Vec<MetricsSample> = state
+            .series
+            .iter()
+            .filter(|((series_exchange, series_symbol), _)| {
+                series_exchange == exchange && symbol.is_none_or(|symbol| symbol == series_symbol)
+            })
+            .flat_map(|(_, series)| series.samples.iter())
+            .filter(|sample| since.is_none_or(|since| sample.timestamp >= since))
+            .cloned()
+            .collect();
+        samples.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.symbol.cmp(&b.symbol))
+        });
+        samples
+    }
+
+    /// Every exchange and symbol with history, sorted
+    pub fn feeds(&self) -> Vec<(Exchange, String)> {
+        let mut feeds: Vec<(Exchange, String)> = self.lock().series.keys().cloned().collect();
+        feeds.sort();
+        feeds
+    }
+
+    /// Adds samples loaded from storage, such as those of a previous run. Samples older than
+    /// the retention, or for an interval already held, are dropped.
+    pub fn restore(&self, samples: impl IntoIterator<Item = MetricsSample>) {
+        let mut state = self.lock();
+        let HistoryState { config, series, .. } = &mut *state;
+        for sample in samples {
+            let key = (sample.exchange.clone(), sample.symbol.clone());
+            series.entry(key).or_default().samples.push_back(sample);
+        }
+        for series in series.values_mut() {
+            let mut samples = Vec::from(std::mem::take(&mut series.samples));
+            // Stable, so samples already held win over restored ones for the same interval
+            samples.sort_by_key(|sample| sample.timestamp);
+            samples.dedup_by_key(|sample| sample.timestamp);
+            series.samples = samples.into();
+            series.trim(config);
+        }
+    }
+
+    /// The samples closed since the previous call, for persisting them. Samples are only
+    /// collected once this has been called.
+    pub fn take_completed(&self) -> Vec<MetricsSample> {
+        self.lock()
+            .completed
+            .replace(Vec::new())
+            .unwrap_or_default()
+    }
+
+    // A poisoned lock still holds whole samples, so it is used as is
+    fn lock(&self) -> MutexGuard<'_, HistoryState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl HistoryState {
+    /// The open interval of `exchange` and `symbol` covering `at`, closing the previous one
+    /// if it ended. Updates arriving after a later interval opened count towards that one.
+    fn interval(
+        &mut self,
+        exchange: &Exchange,
+        symbol: &str,
+        at: DateTime<Utc>,
+    ) -> Option<&mut OpenInterval> {
+        if !self.config.enabled {
+            return None;
+        }
+        let resolution = resolution(&self.config);
+        let start = interval_start(at, resolution);
+        let series = self
+            .series
+            .entry((exchange.clone(), symbol.to_string()))
+            .or_default();
+        if series.open.as_ref().is_some_and(|open| open.start < start) {
+            let sample = series.close(exchange, symbol, &self.config);
+            if let (Some(completed), Some(sample)) = (self.completed.as_mut(), sample) {
+                completed.push(sample);
+            }
+        }
+        Some(series.open.get_or_insert(OpenInterval {
+            start,
+            updates: 0,
+            latency_total_ms: 0.0,
+            latencies: 0,
+            max_latency_ms: 0.0,
+            errors: 0,
+        }))
+    }
+}
+
+impl Series {
+    /// Turns the open interval into a sample, returning it
+    fn close(
+        &mut self,
+        exchange: &Exchange,
+        symbol: &str,
+        config: &MetricsHistoryConfig,
+    ) -> Option<MetricsSample> {
+        let open = self.open.take()?;
+        let sample = MetricsSample {
+            exchange: exchange.clone(),
+            symbol: symbol.to_string(),
+            timestamp: open.start,
+            updates: open.updates,
+            updates_per_second: open.updates as f64 / config.resolution_secs.max(1) as f64,
+            latency_ms: if open.latencies > 0 {
+                open.latency_total_ms / open.latencies as f64
+            } else {
+                0.0
+            },
+            max_latency_ms: open.max_latency_ms,
+            errors: open.errors,
+        };
+        self.samples.push_back(sample.clone());
+        self.trim(config);
+        Some(sample)
+    }
+
+    /// Drops samples older than the retention, counted back from the newest
+    fn trim(&mut self, config: &MetricsHistoryConfig) {
+        let Some(newest) = self.samples.back().map(|sample| sample.timestamp) else {
+            return;
+        };
+        let retention = Duration::seconds(config.retention_secs.min(i64::MAX as u64) as i64);
+        while self
+            .samples
+            .front()
+            .is_some_and(|sample| newest - sample.timestamp >= retention)
+        {
+            self.samples.pop_front();
+        }
+    }
+}
+
+fn resolution(config: &MetricsHistoryConfig) -> Duration {
+    Duration::seconds(config.resolution_secs.clamp(1, i64::MAX as u64) as i64)
+}
+
+/// The start of the interval `at` falls in, intervals being aligned to the Unix epoch
+fn interval_start(at: DateTime<Utc>, resolution: Duration) -> DateTime<Utc> {
+    let resolution_ms = resolution.num_milliseconds().max(1);
+    let at_ms = at.timestamp_millis();
+    DateTime::from_timestamp_millis(at_ms - at_ms.rem_euclid(resolution_ms)).unwrap_or(at)
+}
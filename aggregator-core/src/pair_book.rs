@@ -0,0 +1,351 @@
+//! Per-pair consolidated order books maintained by the aggregator
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::types::{Exchange, MarketType, PriceLevel, PriceLevelUpdate, Summary, TradingPair};
+use crate::Result;
+
+/// Order book of one trading pair merged across exchanges.
+///
+/// The aggregator keeps one per configured pair, applies every exchange's updates to it and
+/// publishes its summary. `orderbook-implementations` implements it for
+/// `ConsolidatedOrderBook` over each of its book types.
+///
+/// # Required Methods
+///
+/// - `apply_update`: Applies one exchange's price level update; levels with quantity 0.0 are
+///   removed.
+/// - `summary`: The best `depth` levels per side across every exchange, best first.
+/// - `exchange_summary`: The best `depth` levels per side of one exchange, best first, or `None`
+///   if that exchange has not updated the book.
+/// - `remove_exchange`: Drops every level of one exchange, e.g. once it is disabled.
+#[async_trait]
+pub trait PairBook: Send + Sync {
+    async fn apply_update(&mut self, update: &PriceLevelUpdate) -> Result<()>;
+    async fn summary(&self, depth: usize) -> Summary;
+    async fn exchange_summary(&self, exchange: &Exchange, depth: usize) -> Option<Summary>;
+    async fn remove_exchange(&mut self, exchange: &Exchange);
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// A pair's consolidated book to a chosen depth, from `Aggregator::get_order_book`.
+///
+/// - `pair`: The trading pair.
+/// - `consolidated`: The best levels per side across every exchange, best first, each carrying
+///   the exchange quoting it.
+/// - `exchanges`: Each exchange's own best levels per side, to the same depth.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OrderBookView {
+    pub pair: TradingPair,
+    pub consolidated: Summary,
+    pub exchanges: HashMap<Exchange, Summary>,
+}
+
+/// What one exchange contributes to the consolidated levels of an [`OrderBookView`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExchangeAttribution {
+    pub bid_levels: usize,
+    pub ask_levels: usize,
+    pub bid_quantity: f64,
+    pub ask_quantity: f64,
+}
+
+impl OrderBookView {
+    /// The levels and quantity each exchange quotes among the consolidated levels. Exchanges
+    /// outquoted on both sides are left out.
+    pub fn attribution(&self) -> HashMap<Exchange, ExchangeAttribution> {
+        let mut attribution: HashMap<Exchange, ExchangeAttribution> = HashMap::new();
+        for bid in &self.consolidated.bids {
+            let entry = attribution.entry(bid.exchange.clone()).or_default();
+            entry.bid_levels += 1;
+            entry.bid_quantity += bid.quantity;
+        }
+        for ask in &self.consolidated.asks {
+            let entry = attribution.entry(ask.exchange.clone()).or_default();
+            entry.ask_levels += 1;
+            entry.ask_quantity += ask.quantity;
+        }
+        attribution
+    }
+}
+
+/// Creates the book the aggregator keeps for a trading pair.
+///
+/// Set one with `Aggregator::with_book_factory`; `orderbook-implementations` provides a factory
+/// that picks the book type from `OrderBookConfig::implementation`.
+pub trait PairBookFactory: Send + Sync {
+    /// Creates an empty book for `pair`, whose summaries carry `symbol`
+    fn create(&self, pair: &TradingPair, symbol: &str) -> Box<dyn PairBook>;
+}
+
+/// The book used when no factory is set: each exchange's levels are kept in a price-keyed map
+/// per side, trimmed to `max_depth`, and merged on read.
+#[derive(Debug, Clone)]
+pub struct LevelMapBook {
+    symbol: String,
+    max_depth: usize,
+    bids: HashMap<Exchange, BTreeMap<u64, PriceLevel>>,
+    asks: HashMap<Exchange, BTreeMap<u64, PriceLevel>>,
+    market_type: Option<MarketType>,
+    last_update: Option<DateTime<Utc>>,
+}
+
+impl LevelMapBook {
+    pub fn new(symbol: &str, max_depth: usize) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            max_depth,
+            bids: HashMap::new(),
+            asks: HashMap::new(),
+            market_type: None,
+            last_update: None,
+        }
+    }
+
+    /// Applies `levels` to one side, where `best_last` says whether the best price is the highest
+    fn apply_side(
+        side: &mut BTreeMap<u64, PriceLevel>,
+        levels: &[PriceLevel],
+        max_depth: usize,
+        best_last: bool,
+    ) {
+        for level in levels {
+            if !level.price.is_finite() || level.price <= 0.0 {
+                continue;
+            }
+            // Prices are positive, so the bit pattern orders the same way as the value
+            let key = level.price.to_bits();
+            if level.quantity > 0.0 {
+                side.insert(key, level.clone());
+            } else {
+                side.remove(&key);
+            }
+        }
+        while side.len() > max_depth {
+            if best_last {
+                side.pop_first();
+            } else {
+                side.pop_last();
+            }
+        }
+    }
+
+    fn merged(
+        sides: &HashMap<Exchange, BTreeMap<u64, PriceLevel>>,
+        depth: usize,
+        best_first: impl Fn(&PriceLevel, &PriceLevel) -> std::cmp::Ordering,
+    ) -> Vec<PriceLevel> {
+        let mut levels: Vec<PriceLevel> = sides
+            .values()
+            .flat_map(|side| side.values().cloned())
+            .collect();
+        levels.sort_by(|a, b| best_first(a, b).then_with(|| a.exchange.cmp(&b.exchange)));
+        levels.truncate(depth);
+        levels
+    }
+
+    fn to_summary(
+        &self,
+        bids: Vec<PriceLevel>,
+        asks: Vec<PriceLevel>,
+        timestamp: DateTime<Utc>,
+    ) -> Summary {
+        let spread = match (bids.first(), asks.first()) {
+            (Some(best_bid), Some(best_ask)) => best_ask.price - best_bid.price,
+            _ => 0.0,
+        };
+
+        Summary {
+            symbol: self.symbol.clone(),
+            spread,
+            bids,
+            asks,
+            timestamp,
+            market_type: self.market_type.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl PairBook for LevelMapBook {
+    async fn apply_update(&mut self, update: &PriceLevelUpdate) -> Result<()> {
+        // Levels are kept under the exchange of the update whatever they are tagged with
+        let level = |price, quantity, timestamp| PriceLevel {
+            price,
+            quantity,
+            exchange: update.exchange.clone(),
+            timestamp,
+        };
+        let bids: Vec<PriceLevel> = update
+            .bids
+            .iter()
+            .map(|bid| level(bid.price, bid.quantity, bid.timestamp))
+            .collect();
+        let asks: Vec<PriceLevel> = update
+            .asks
+            .iter()
+            .map(|ask| level(ask.price, ask.quantity, ask.timestamp))
+            .collect();
+
+        Self::apply_side(
+            self.bids.entry(update.exchange.clone()).or_default(),
+            &bids,
+            self.max_depth,
+            true,
+        );
+        Self::apply_side(
+            self.asks.entry(update.exchange.clone()).or_default(),
+            &asks,
+            self.max_depth,
+            false,
+        );
+        if update.market_type.is_some() {
+            self.market_type = update.market_type.clone();
+        }
+        self.last_update = Some(update.timestamp);
+        Ok(())
+    }
+
+    async fn summary(&self, depth: usize) -> Summary {
+        let bids = Self::merged(&self.bids, depth, |a, b| b.price.total_cmp(&a.price));
+        let asks = Self::merged(&self.asks, depth, |a, b| a.price.total_cmp(&b.price));
+        self.to_summary(bids, asks, self.last_update.unwrap_or_else(Utc::now))
+    }
+
+    async fn exchange_summary(&self, exchange: &Exchange, depth: usize) -> Option<Summary> {
+        let (bids, asks) = (self.bids.get(exchange)?, self.asks.get(exchange)?);
+        let bids: Vec<PriceLevel> = bids.values().rev().take(depth).cloned().collect();
+        let asks: Vec<PriceLevel> = asks.values().take(depth).cloned().collect();
+        let timestamp = bids
+            .iter()
+            .chain(&asks)
+            .map(|level| level.timestamp)
+            .max()
+            .or(self.last_update)
+            .unwrap_or_else(Utc::now);
+        Some(self.to_summary(bids, asks, timestamp))
+    }
+
+    async fn remove_exchange(&mut self, exchange: &Exchange) {
+        self.bids.remove(exchange);
+        self.asks.remove(exchange);
+    }
+}
+
+/// Creates a [`LevelMapBook`] per pair.
+#[derive(Debug, Clone)]
+pub struct LevelMapBookFactory {
+    max_depth: usize,
+}
+
+impl LevelMapBookFactory {
+    pub fn new(max_depth: usize) -> Self {
+        Self { max_depth }
+    }
+}
+
+impl PairBookFactory for LevelMapBookFactory {
+    fn create(&self, _pair: &TradingPair, symbol: &str) -> Box<dyn PairBook> {
+        Box::new(LevelMapBook::new(symbol, self.max_depth))
+    }
+}
+
+/// The consolidated books of every pair the aggregator has seen updates for.
+pub(crate) struct PairBooks {
+    factory: Arc<dyn PairBookFactory>,
+    books: HashMap<TradingPair, Box<dyn PairBook>>,
+}
+
+impl PairBooks {
+    pub(crate) fn new(factory: Arc<dyn PairBookFactory>) -> Self {
+        Self {
+            factory,
+            books: HashMap::new(),
+        }
+    }
+
+    /// Applies `update` to the book of its pair under `config`, creating the book on first use,
+    /// and returns the pair with the book's new summary. The pair is resolved from the symbol
+    /// with `Config::pair_for_exchange_symbol`; updates whose symbol cannot be resolved are
+    /// ignored and return `None`.
+    pub(crate) async fn apply(
+        &mut self,
+        update: &PriceLevelUpdate,
+        config: &Config,
+    ) -> Result<Option<(TradingPair, Summary)>> {
+        let Some(pair) = config.pair_for_exchange_symbol(&update.exchange, &update.symbol) else {
+            return Ok(None);
+        };
+
+        let book = self.books.entry(pair.clone()).or_insert_with(|| {
+            self.factory
+                .create(&pair, &format!("{}{}", pair.base, pair.quote))
+        });
+        book.apply_update(update).await?;
+        let summary = book.summary(config.orderbook.max_depth).await;
+        Ok(Some((pair, summary)))
+    }
+
+    /// Drops the book of `pair`, e.g. once it is no longer configured
+    pub(crate) fn remove(&mut self, pair: &TradingPair) {
+        self.books.remove(pair);
+    }
+
+    /// Drops the levels of `exchange` from the book of every pair
+    pub(crate) async fn remove_exchange(&mut self, exchange: &Exchange) {
+        for book in self.books.values_mut() {
+            book.remove_exchange(exchange).await;
+        }
+    }
+
+    /// The book of `pair` to `depth` levels, with the levels of each of `exchanges` that has
+    /// updated it
+    pub(crate) async fn view(
+        &self,
+        pair: &TradingPair,
+        exchanges: &[Exchange],
+        depth: usize,
+    ) -> Option<OrderBookView> {
+        let book = self.books.get(pair)?;
+        let mut by_exchange = HashMap::new();
+        for exchange in exchanges {
+            if let Some(summary) = book.exchange_summary(exchange, depth).await {
+                by_exchange.insert(exchange.clone(), summary);
+            }
+        }
+        Some(OrderBookView {
+            pair: pair.clone(),
+            consolidated: book.summary(depth).await,
+            exchanges: by_exchange,
+        })
+    }
+
+    /// The name of the book kept for `pair`
+    pub(crate) fn book_name(&self, pair: &TradingPair) -> Option<String> {
+        self.books.get(pair).map(|book| book.name().to_string())
+    }
+
+    /// The best `depth` levels of `exchange` alone in the book of `pair`
+    pub(crate) async fn exchange_summary(
+        &self,
+        pair: &TradingPair,
+        exchange: &Exchange,
+        depth: usize,
+    ) -> Option<Summary> {
+        self.books
+            .get(pair)?
+            .exchange_summary(exchange, depth)
+            .await
+    }
+}
@@ -0,0 +1,141 @@
+//! Driving the aggregator from recorded price level updates instead of live connectors
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::types::PriceLevelUpdate;
+use crate::{AggregatorError, Result};
+
+/// How fast recorded data is replayed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayPace {
+    /// As fast as the receiver accepts them.
+    Unpaced,
+    /// Keeping the recorded gaps between records, divided by `speed` (10.0 replays ten times as
+    /// fast).
+    Recorded { speed: f64 },
+}
+
+impl ReplayPace {
+    /// The recorded gaps, unchanged
+    pub const REAL_TIME: ReplayPace = ReplayPace::Recorded { speed: 1.0 };
+
+    /// How long to wait before a record made at `next`, after one made at `previous`. Records
+    /// out of order, and any record when `speed` is not positive, are not waited for.
+    pub fn delay(&self, previous: DateTime<Utc>, next: DateTime<Utc>) -> Option<Duration> {
+        match *self {
+            ReplayPace::Recorded { speed } if speed > 0.0 => (next - previous)
+                .to_std()
+                .ok()
+                .map(|gap| gap.div_f64(speed)),
+            _ => None,
+        }
+    }
+}
+
+/// Recorded price level updates for `Aggregator::start_replay`, in the order they were
+/// received. Replays are paced by each update's `timestamp`.
+#[async_trait]
+pub trait ReplaySource: Send {
+    /// The next update, or `None` once the recording is exhausted. A record that cannot be
+    /// loaded is returned as an error and skipped.
+    async fn next_update(&mut self) -> Option<Result<PriceLevelUpdate>>;
+}
+
+/// Updates held in memory
+#[derive(Debug, Clone, Default)]
+pub struct RecordedUpdates {
+    updates: VecDeque<PriceLevelUpdate>,
+}
+
+impl RecordedUpdates {
+    pub fn new(updates: impl IntoIterator<Item = PriceLevelUpdate>) -> Self {
+        Self {
+            updates: updates.into_iter().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.updates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty()
+    }
+}
+
+impl From<Vec<PriceLevelUpdate>> for RecordedUpdates {
+    fn from(updates: Vec<PriceLevelUpdate>) -> Self {
+        Self::new(updates)
+    }
+}
+
+#[async_trait]
+impl ReplaySource for RecordedUpdates {
+    async fn next_update(&mut self) -> Option<Result<PriceLevelUpdate>> {
+        self.updates.pop_front().map(Ok)
+    }
+}
+
+/// Updates read from a file holding one JSON serialized `PriceLevelUpdate` per line. Blank
+/// lines are ignored.
+pub struct UpdateLog {
+    path: PathBuf,
+    lines: Lines<BufReader<File>>,
+}
+
+impl UpdateLog {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path).map_err(|e| {
+            AggregatorError::Io(std::io::Error::new(
+                e.kind(),
+                format!("{}: {}", path.display(), e),
+            ))
+        })?;
+        Ok(Self {
+            path,
+            lines: BufReader::new(file).lines(),
+        })
+    }
+}
+
+#[async_trait]
+impl ReplaySource for UpdateLog {
+    async fn next_update(&mut self) -> Option<Result<PriceLevelUpdate>> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(AggregatorError::Io(e))),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(serde_json::from_str(&line).map_err(|e| {
+                AggregatorError::parsing(
+                    "PriceLevelUpdate",
+                    format!("Failed to parse update in {}: {}", self.path.display(), e),
+                )
+            }));
+        }
+    }
+}
+
+/// The outcome of `Aggregator::start_replay`, published once the replay ends.
+///
+/// - `updates`: Updates handed to the aggregator.
+/// - `skipped`: Records the source failed to load, and updates of exchanges stopped during the
+///   replay.
+/// - `completed`: Whether the source was exhausted, rather than the aggregator stopping first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub updates: usize,
+    pub skipped: usize,
+    pub completed: bool,
+}
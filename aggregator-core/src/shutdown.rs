@@ -0,0 +1,199 @@
+//! Ordered shutdown of the aggregator's tasks and of the servers reading from it
+
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::Result;
+
+/// The steps of `Aggregator::shutdown`, in the order they run. Each stage's tasks are signalled
+/// once every task of the previous stages has stopped or the deadline has passed.
+///
+/// - `Connectors`: Exchange connectors, their price level processors, which forward the
+///   updates still queued before stopping, and the health monitor that restarts them.
+/// - `Processing`: The aggregation processor, which applies the updates still in its channel,
+///   and the arbitrage detector.
+/// - `Persistence`: The snapshot task, which saves the final state to storage.
+/// - `Servers`: Servers registered with `Aggregator::track_task`, which stop accepting
+///   clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShutdownStage {
+    Connectors,
+    Processing,
+    Persistence,
+    Servers,
+}
+
+impl ShutdownStage {
+    /// Every stage, in shutdown order
+    pub const ALL: [ShutdownStage; 4] = [
+        ShutdownStage::Connectors,
+        ShutdownStage::Processing,
+        ShutdownStage::Persistence,
+        ShutdownStage::Servers,
+    ];
+}
+
+impl fmt::Display for ShutdownStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ShutdownStage::Connectors => "connectors",
+            ShutdownStage::Processing => "processing",
+            ShutdownStage::Persistence => "persistence",
+            ShutdownStage::Servers => "servers",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Why a component did not stop cleanly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopError {
+    /// Still running at the deadline, so it was aborted
+    TimedOut,
+    Panicked,
+    /// Stopped, returning this error
+    Failed(String),
+}
+
+impl fmt::Display for StopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopError::TimedOut => f.write_str("did not stop before the deadline"),
+            StopError::Panicked => f.write_str("panicked"),
+            StopError::Failed(message) => write!(f, "failed: {}", message),
+        }
+    }
+}
+
+/// A component that did not stop cleanly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownFailure {
+    pub component: String,
+    pub stage: ShutdownStage,
+    pub error: StopError,
+}
+
+/// The outcome of `Aggregator::shutdown`: the components that stopped cleanly and those that
+/// did not, each in the order they were awaited
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    pub stopped: Vec<String>,
+    pub failed: Vec<ShutdownFailure>,
+}
+
+impl ShutdownReport {
+    /// Whether every component stopped cleanly before the deadline
+    pub fn is_clean(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+struct TrackedTask {
+    stage: ShutdownStage,
+    component: String,
+    handle: JoinHandle<Result<()>>,
+}
+
+/// The tasks a shutdown waits for, with the stage it has reached. Clones share both.
+#[derive(Clone)]
+pub(crate) struct ShutdownTracker {
+    stage: Arc<watch::Sender<Option<ShutdownStage>>>,
+    tasks: Arc<Mutex<Vec<TrackedTask>>>,
+}
+
+impl ShutdownTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            stage: Arc::new(watch::channel(None).0),
+            tasks: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Waits for `handle` when shutdown reaches `stage`. Tasks that already finished are
+    /// forgotten, so restarted components do not pile up.
+    pub(crate) fn track(
+        &self,
+        stage: ShutdownStage,
+        component: impl Into<String>,
+        handle: JoinHandle<Result<()>>,
+    ) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(PoisonError::into_inner);
+        tasks.retain(|task| !task.handle.is_finished());
+        tasks.push(TrackedTask {
+            stage,
+            component: component.into(),
+            handle,
+        });
+    }
+
+    /// Resolves once shutdown reaches `stage`, or the tracker is dropped
+    pub(crate) fn signal(&self, stage: ShutdownStage) -> impl Future<Output = ()> + Send + 'static {
+        let mut stage_rx = self.stage.subscribe();
+        async move {
+            // Only fails once the tracker is dropped, which stops every task too
+            let _ = stage_rx
+                .wait_for(|reached| reached.is_some_and(|reached| reached >= stage))
+                .await;
+        }
+    }
+
+    /// Signals the tasks of `stage` and every earlier one
+    pub(crate) fn reach(&self, stage: ShutdownStage) {
+        self.stage.send_if_modified(|reached| match reached {
+            Some(reached) if *reached >= stage => false,
+            _ => {
+                *reached = Some(stage);
+                true
+            }
+        });
+    }
+
+    /// Awaits the tasks of `stage` and any earlier stage tracked since, aborting those still
+    /// running at `deadline`
+    pub(crate) async fn stop(
+        &self,
+        stage: ShutdownStage,
+        deadline: Instant,
+        report: &mut ShutdownReport,
+    ) {
+        let stopping: Vec<TrackedTask> = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(PoisonError::into_inner);
+            let (stopping, remaining) = tasks.drain(..).partition(|task| task.stage <= stage);
+            *tasks = remaining;
+            stopping
+        };
+
+        for mut task in stopping {
+            let outcome = match tokio::time::timeout_at(deadline, &mut task.handle).await {
+                Ok(Ok(Ok(()))) => Ok(()),
+                Ok(Ok(Err(e))) => Err(StopError::Failed(e.to_string())),
+                Ok(Err(e)) if e.is_panic() => Err(StopError::Panicked),
+                Ok(Err(e)) => Err(StopError::Failed(e.to_string())),
+                Err(_) => {
+                    task.handle.abort();
+                    Err(StopError::TimedOut)
+                }
+            };
+            match outcome {
+                Ok(()) => {
+                    info!("{} stopped", task.component);
+                    report.stopped.push(task.component);
+                }
+                Err(error) => {
+                    warn!("{} {}", task.component, error);
+                    report.failed.push(ShutdownFailure {
+                        component: task.component,
+                        stage: task.stage,
+                        error,
+                    });
+                }
+            }
+        }
+    }
+}
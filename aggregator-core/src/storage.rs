@@ -0,0 +1,594 @@
+//! Persisting aggregator state so a restart resumes from where the last run left off
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::config::{StorageBackend, StorageConfig};
+use crate::metrics_history::MetricsSample;
+use crate::types::{
+    ArbitrageOpportunity, Ask, Bid, Exchange, Metrics, PriceLevelUpdate, Summary, TradingPair,
+};
+use crate::Result;
+
+/// The book one exchange keeps for a trading pair, as of a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookSnapshot {
+    pub pair: TradingPair,
+    pub exchange: Exchange,
+    /// The exchange's levels, best first
+    pub summary: Summary,
+}
+
+impl BookSnapshot {
+    /// An update that rebuilds the snapshotted levels in an empty book
+    pub fn to_update(&self) -> PriceLevelUpdate {
+        PriceLevelUpdate {
+            id: uuid::Uuid::new_v4(),
+            symbol: self.summary.symbol.clone(),
+            exchange: self.exchange.clone(),
+            bids: self
+                .summary
+                .bids
+                .iter()
+                .map(|level| Bid {
+                    price: level.price,
+                    quantity: level.quantity,
+                    exchange: self.exchange.clone(),
+                    timestamp: level.timestamp,
+                })
+                .collect(),
+            asks: self
+                .summary
+                .asks
+                .iter()
+                .map(|level| Ask {
+                    price: level.price,
+                    quantity: level.quantity,
+                    exchange: self.exchange.clone(),
+                    timestamp: level.timestamp,
+                })
+                .collect(),
+            timestamp: self.summary.timestamp,
+            funding: None,
+            event_time: None,
+            market_type: self.summary.market_type.clone(),
+        }
+    }
+}
+
+/// Where the aggregator keeps its state between runs.
+///
+/// Summaries, metrics and book snapshots hold the latest state: saving one replaces the stored
+/// entry with the same key, the symbol, the exchange or the pair and exchange. Opportunities
+/// are a history: saving appends, and `prune_opportunities` bounds it. Metrics samples are a
+/// history too, keyed by exchange, symbol and interval so saving one again replaces it.
+///
+/// # Required Methods
+///
+/// - `save_summaries`, `load_summaries`: Consolidated summaries, by symbol.
+/// - `save_opportunities`, `load_opportunities`: Arbitrage opportunities, loaded newest first.
+/// - `prune_opportunities`: Deletes all but the newest `keep` opportunities.
+/// - `save_metrics`, `load_metrics`: Feed metrics, by exchange.
+/// - `save_metrics_history`, `load_metrics_history`: Metrics samples, loaded oldest first from
+///   a given time.
+/// - `prune_metrics_history`: Deletes the samples of intervals starting before a given time.
+/// - `save_book_snapshots`, `load_book_snapshots`: Each exchange's book, by pair and exchange.
+///
+/// # Provided Methods
+///
+/// - `name`: Identifies the backend in logs, the type name by default.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn save_summaries(&self, summaries: &[Summary]) -> Result<()>;
+    async fn load_summaries(&self) -> Result<Vec<Summary>>;
+    async fn save_opportunities(&self, opportunities: &[ArbitrageOpportunity]) -> Result<()>;
+    async fn load_opportunities(&self, limit: usize) -> Result<Vec<ArbitrageOpportunity>>;
+    async fn prune_opportunities(&self, keep: usize) -> Result<()>;
+    async fn save_metrics(&self, metrics: &[Metrics]) -> Result<()>;
+    async fn load_metrics(&self) -> Result<Vec<Metrics>>;
+    async fn save_metrics_history(&self, samples: &[MetricsSample]) -> Result<()>;
+    async fn load_metrics_history(&self, since: DateTime<Utc>) -> Result<Vec<MetricsSample>>;
+    async fn prune_metrics_history(&self, before: DateTime<Utc>) -> Result<()>;
+    async fn save_book_snapshots(&self, snapshots: &[BookSnapshot]) -> Result<()>;
+    async fn load_book_snapshots(&self) -> Result<Vec<BookSnapshot>>;
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Opens the backend `config` selects, or returns `None` when storage is disabled
+pub fn open_storage(config: &StorageConfig) -> Result<Option<Arc<dyn Storage>>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    let storage: Arc<dyn Storage> = match config.backend {
+        StorageBackend::Memory => Arc::new(MemoryStorage::new()),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => Arc::new(SqliteStorage::open(&config.path)?),
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => {
+            return Err(crate::AggregatorError::validation(
+                "storage.backend",
+                "Sqlite needs the `sqlite` feature of aggregator-core",
+            ))
+        }
+    };
+    Ok(Some(storage))
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    summaries: HashMap<String, Summary>,
+    /// Oldest first
+    opportunities: VecDeque<ArbitrageOpportunity>,
+    metrics: HashMap<Exchange, Metrics>,
+    /// Ordered by interval
+    metrics_history: BTreeMap<(DateTime<Utc>, Exchange, String), MetricsSample>,
+    books: HashMap<(TradingPair, Exchange), BookSnapshot>,
+}
+
+/// Keeps state in memory, so it only survives as long as the process. Cloning shares the
+/// state.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    state: Arc<RwLock<MemoryState>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn save_summaries(&self, summaries: &[Summary]) -> Result<()> {
+        let mut state = self.state.write().await;
+        for summary in summaries {
+            state
+                .summaries
+                .insert(summary.symbol.clone(), summary.clone());
+        }
+        Ok(())
+    }
+
+    async fn load_summaries(&self) -> Result<Vec<Summary>> {
+        Ok(self
+            .state
+            .read()
+            .await
+            .summaries
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    async fn save_opportunities(&self, opportunities: &[ArbitrageOpportunity]) -> Result<()> {
+        self.state
+            .write()
+            .await
+            .opportunities
+            .extend(opportunities.iter().cloned());
+        Ok(())
+    }
+
+    async fn load_opportunities(&self, limit: usize) -> Result<Vec<ArbitrageOpportunity>> {
+        let state = self.state.read().await;
+        let mut opportunities: Vec<ArbitrageOpportunity> =
+            state.opportunities.iter().cloned().collect();
+        // Stable, so opportunities with the same timestamp stay newest saved first
+        opportunities.reverse();
+        opportunities.sort_by_key(|opportunity| std::cmp::Reverse(opportunity.timestamp));
+        opportunities.truncate(limit);
+        Ok(opportunities)
+    }
+
+    async fn prune_opportunities(&self, keep: usize) -> Result<()> {
+        let mut state = self.state.write().await;
+        let excess = state.opportunities.len().saturating_sub(keep);
+        state.opportunities.drain(..excess);
+        Ok(())
+    }
+
+    async fn save_metrics(&self, metrics: &[Metrics]) -> Result<()> {
+        let mut state = self.state.write().await;
+        for metric in metrics {
+            state
+                .metrics
+                .insert(metric.exchange.clone(), metric.clone());
+        }
+        Ok(())
+    }
+
+    async fn load_metrics(&self) -> Result<Vec<Metrics>> {
+        Ok(self.state.read().await.metrics.values().cloned().collect())
+    }
+
+    async fn save_metrics_history(&self, samples: &[MetricsSample]) -> Result<()> {
+        let mut state = self.state.write().await;
+        for sample in samples {
+            state.metrics_history.insert(
+                (
+                    sample.timestamp,
+                    sample.exchange.clone(),
+                    sample.symbol.clone(),
+                ),
+                sample.clone(),
+            );
+        }
+        Ok(())
+    }
+
+    async fn load_metrics_history(&self, since: DateTime<Utc>) -> Result<Vec<MetricsSample>> {
+        Ok(self
+            .state
+            .read()
+            .await
+            .metrics_history
+            .values()
+            .filter(|sample| sample.timestamp >= since)
+            .cloned()
+            .collect())
+    }
+
+    async fn prune_metrics_history(&self, before: DateTime<Utc>) -> Result<()> {
+        self.state
+            .write()
+            .await
+            .metrics_history
+            .retain(|(timestamp, _, _), _| *timestamp >= before);
+        Ok(())
+    }
+
+    async fn save_book_snapshots(&self, snapshots: &[BookSnapshot]) -> Result<()> {
+        let mut state = self.state.write().await;
+        for snapshot in snapshots {
+            state.books.insert(
+                (snapshot.pair.clone(), snapshot.exchange.clone()),
+                snapshot.clone(),
+            );
+        }
+        Ok(())
+    }
+
+    async fn load_book_snapshots(&self) -> Result<Vec<BookSnapshot>> {
+        Ok(self.state.read().await.books.values().cloned().collect())
+    }
+
+    fn name(&self) -> &str {
+        "memory"
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::{BookSnapshot, Storage};
+    use crate::metrics_history::MetricsSample;
+    use crate::types::{ArbitrageOpportunity, Metrics, Summary};
+    use crate::{AggregatorError, Result};
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use rusqlite::{params, Connection};
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS summaries (
+            symbol TEXT PRIMARY KEY,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS opportunities (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp_us INTEGER NOT NULL,
+            data TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS opportunities_time ON opportunities (timestamp_us);
+        CREATE TABLE IF NOT EXISTS metrics (
+            exchange TEXT PRIMARY KEY,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS metrics_history (
+            exchange TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            timestamp_us INTEGER NOT NULL,
+            data TEXT NOT NULL,
+            PRIMARY KEY (exchange, symbol, timestamp_us)
+        );
+        CREATE INDEX IF NOT EXISTS metrics_history_time ON metrics_history (timestamp_us);
+        CREATE TABLE IF NOT EXISTS book_snapshots (
+            pair TEXT NOT NULL,
+            exchange TEXT NOT NULL,
+            data TEXT NOT NULL,
+            PRIMARY KEY (pair, exchange)
+        );
+    ";
+
+    /// Keeps state in a SQLite database, each entry as JSON under its key. Statements run on
+    /// the blocking thread pool, and each save is one transaction. Cloning the storage shares
+    /// its connection.
+    #[derive(Debug, Clone)]
+    pub struct SqliteStorage {
+        connection: Arc<Mutex<Connection>>,
+    }
+
+    impl SqliteStorage {
+        /// Opens the database at `path`, creating it and its tables if they do not exist
+        pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+            let connection = Connection::open(path)
+                .map_err(|e| AggregatorError::database("open", e.to_string()))?;
+            Self::from_connection(connection)
+        }
+
+        /// Opens a private in-memory database, mostly useful for tests
+        pub fn open_in_memory() -> Result<Self> {
+            let connection = Connection::open_in_memory()
+                .map_err(|e| AggregatorError::database("open", e.to_string()))?;
+            Self::from_connection(connection)
+        }
+
+        fn from_connection(connection: Connection) -> Result<Self> {
+            connection
+                .execute_batch(SCHEMA)
+                .map_err(|e| AggregatorError::database("create schema", e.to_string()))?;
+            Ok(Self {
+                connection: Arc::new(Mutex::new(connection)),
+            })
+        }
+
+        /// Runs `f` against the connection on the blocking thread pool
+        async fn with_connection<T, F>(&self, operation: &'static str, f: F) -> Result<T>
+        where
+            T: Send + 'static,
+            F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+        {
+            let connection = self.connection.clone();
+            tokio::task::spawn_blocking(move || {
+                let mut connection = connection
+                    .lock()
+                    .map_err(|_| AggregatorError::database(operation, "connection poisoned"))?;
+                f(&mut connection).map_err(|e| AggregatorError::database(operation, e.to_string()))
+            })
+            .await
+            .map_err(|e| AggregatorError::database(operation, e.to_string()))?
+        }
+
+        /// Runs `sql` once per row, binding the row's keys followed by the JSON of its item, in
+        /// one transaction
+        async fn upsert<T: Serialize>(
+            &self,
+            operation: &'static str,
+            sql: &'static str,
+            rows: Vec<(Vec<String>, &T)>,
+        ) -> Result<()> {
+            let rows = rows
+                .into_iter()
+                .map(|(keys, item)| Ok((keys, to_json(operation, item)?)))
+                .collect::<Result<Vec<_>>>()?;
+            self.with_connection(operation, move |connection| {
+                let transaction = connection.transaction()?;
+                {
+                    let mut statement = transaction.prepare(sql)?;
+                    for (mut values, data) in rows {
+                        values.push(data);
+                        statement.execute(rusqlite::params_from_iter(values))?;
+                    }
+                }
+                transaction.commit()
+            })
+            .await
+        }
+
+        async fn load_all<T: DeserializeOwned + Send + 'static>(
+            &self,
+            operation: &'static str,
+            sql: &'static str,
+        ) -> Result<Vec<T>> {
+            let rows: Vec<String> = self
+                .with_connection(operation, move |connection| {
+                    let mut statement = connection.prepare(sql)?;
+                    let rows = statement.query_map([], |row| row.get(0))?;
+                    rows.collect()
+                })
+                .await?;
+            rows.iter().map(|data| from_json(operation, data)).collect()
+        }
+    }
+
+    fn to_json<T: Serialize>(operation: &str, item: &T) -> Result<String> {
+        serde_json::to_string(item).map_err(|e| AggregatorError::database(operation, e.to_string()))
+    }
+
+    fn from_json<T: DeserializeOwned>(operation: &str, data: &str) -> Result<T> {
+        serde_json::from_str(data).map_err(|e| AggregatorError::database(operation, e.to_string()))
+    }
+
+    #[async_trait]
+    impl Storage for SqliteStorage {
+        async fn save_summaries(&self, summaries: &[Summary]) -> Result<()> {
+            let rows = summaries
+                .iter()
+                .map(|summary| (vec![summary.symbol.clone()], summary))
+                .collect();
+            self.upsert(
+                "save summaries",
+                "INSERT OR REPLACE INTO summaries (symbol, data) VALUES (?1, ?2)",
+                rows,
+            )
+            .await
+        }
+
+        async fn load_summaries(&self) -> Result<Vec<Summary>> {
+            self.load_all("load summaries", "SELECT data FROM summaries")
+                .await
+        }
+
+        async fn save_opportunities(&self, opportunities: &[ArbitrageOpportunity]) -> Result<()> {
+            let rows = opportunities
+                .iter()
+                .map(|opportunity| {
+                    Ok((
+                        opportunity.timestamp.timestamp_micros(),
+                        to_json("save opportunities", opportunity)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.with_connection("save opportunities", move |connection| {
+                let transaction = connection.transaction()?;
+                {
+                    let mut statement = transaction.prepare(
+                        "INSERT INTO opportunities (timestamp_us, data) VALUES (?1, ?2)",
+                    )?;
+                    for (timestamp_us, data) in rows {
+                        statement.execute(params![timestamp_us, data])?;
+                    }
+                }
+                transaction.commit()
+            })
+            .await
+        }
+
+        async fn load_opportunities(&self, limit: usize) -> Result<Vec<ArbitrageOpportunity>> {
+            let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+            let rows: Vec<String> = self
+                .with_connection("load opportunities", move |connection| {
+                    let mut statement = connection.prepare(
+                        "SELECT data FROM opportunities
+                        ORDER BY timestamp_us DESC, id DESC LIMIT ?1",
+                    )?;
+                    let rows = statement.query_map([limit], |row| row.get(0))?;
+                    rows.collect()
+                })
+                .await?;
+            rows.iter()
+                .map(|data| from_json("load opportunities", data))
+                .collect()
+        }
+
+        async fn prune_opportunities(&self, keep: usize) -> Result<()> {
+            let keep = i64::try_from(keep).unwrap_or(i64::MAX);
+            self.with_connection("prune opportunities", move |connection| {
+                connection.execute(
+                    "DELETE FROM opportunities WHERE id NOT IN (
+                        SELECT id FROM opportunities ORDER BY timestamp_us DESC, id DESC LIMIT ?1
+                    )",
+                    [keep],
+                )?;
+                Ok(())
+            })
+            .await
+        }
+
+        async fn save_metrics(&self, metrics: &[Metrics]) -> Result<()> {
+            let rows = metrics
+                .iter()
+                .map(|metric| (vec![metric.exchange.to_string()], metric))
+                .collect();
+            self.upsert(
+                "save metrics",
+                "INSERT OR REPLACE INTO metrics (exchange, data) VALUES (?1, ?2)",
+                rows,
+            )
+            .await
+        }
+
+        async fn load_metrics(&self) -> Result<Vec<Metrics>> {
+            self.load_all("load metrics", "SELECT data FROM metrics")
+                .await
+        }
+
+        async fn save_metrics_history(&self, samples: &[MetricsSample]) -> Result<()> {
+            let rows = samples
+                .iter()
+                .map(|sample| {
+                    Ok((
+                        sample.exchange.to_string(),
+                        sample.symbol.clone(),
+                        sample.timestamp.timestamp_micros(),
+                        to_json("save metrics history", sample)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.with_connection("save metrics history", move |connection| {
+                let transaction = connection.transaction()?;
+                {
+                    let mut statement = transaction.prepare(
+                        "INSERT OR REPLACE INTO metrics_history (exchange, symbol, timestamp_us, data)
+                        VALUES (?1, ?2, ?3, ?4)",
+                    )?;
+                    for (exchange, symbol, timestamp_us, data) in rows {
+                        statement.execute(params![exchange, symbol, timestamp_us, data])?;
+                    }
+                }
+                transaction.commit()
+            })
+            .await
+        }
+
+        async fn load_metrics_history(&self, since: DateTime<Utc>) -> Result<Vec<MetricsSample>> {
+            let since_us = since.timestamp_micros();
+            let rows: Vec<String> = self
+                .with_connection("load metrics history", move |connection| {
+                    let mut statement = connection.prepare(
+                        "SELECT data FROM metrics_history WHERE timestamp_us >= ?1
+                        ORDER BY timestamp_us, exchange, symbol",
+                    )?;
+                    let rows = statement.query_map([since_us], |row| row.get(0))?;
+                    rows.collect()
+                })
+                .await?;
+            rows.iter()
+                .map(|data| from_json("load metrics history", data))
+                .collect()
+        }
+
+        async fn prune_metrics_history(&self, before: DateTime<Utc>) -> Result<()> {
+            let before_us = before.timestamp_micros();
+            self.with_connection("prune metrics history", move |connection| {
+                connection.execute(
+                    "DELETE FROM metrics_history WHERE timestamp_us < ?1",
+                    [before_us],
+                )?;
+                Ok(())
+            })
+            .await
+        }
+
+        async fn save_book_snapshots(&self, snapshots: &[BookSnapshot]) -> Result<()> {
+            let rows = snapshots
+                .iter()
+                .map(|snapshot| {
+                    (
+                        vec![snapshot.pair.to_string(), snapshot.exchange.to_string()],
+                        snapshot,
+                    )
+                })
+                .collect();
+            self.upsert(
+                "save book snapshots",
+                "INSERT OR REPLACE INTO book_snapshots (pair, exchange, data) VALUES (?1, ?2, ?3)",
+                rows,
+            )
+            .await
+        }
+
+        async fn load_book_snapshots(&self) -> Result<Vec<BookSnapshot>> {
+            self.load_all("load book snapshots", "SELECT data FROM book_snapshots")
+                .await
+        }
+
+        fn name(&self) -> &str {
+            "sqlite"
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Symbol Normalization Module
+//! Converts between `TradingPair` and each exchange's native symbol format
+
+use crate::types::{Exchange, TradingPair};
+use crate::{AggregatorError, Result};
+
+/// Quote assets recognised when splitting concatenated symbols such as `BTCUSDT`.
+/// Longer codes come first so `FDUSD` is not mistaken for `USD`.
+const DEFAULT_QUOTE_ASSETS: &[&str] = &[
+    "FDUSD", "USDT", "USDC", "BUSD", "TUSD", "DAI", "USD", "EUR", "GBP", "TRY", "BRL", "JPY",
+    "BTC", "ETH", "BNB",
+];
+
+/// Asset codes that differ from the common ticker on a given exchange, as `(common, native)`.
+const KRAKEN_ASSET_ALIASES: &[(&str, &str)] = &[("BTC", "XBT"), ("DOGE", "XDG")];
+const BITFINEX_ASSET_ALIASES: &[(&str, &str)] = &[("USDT", "UST"), ("DASH", "DSH")];
+
+/// Prefix of Kraken Futures multi-collateral perpetuals; inverse perpetuals use `PI_`. Both are
+/// quoted in USD.
+const KRAKEN_FUTURES_PERPETUAL_PREFIX: &str = "PF_";
+const KRAKEN_FUTURES_INVERSE_PREFIX: &str = "PI_";
+const KRAKEN_FUTURES_QUOTE_ASSET: &str = "USD";
+
+/// Hyperliquid perpetuals are named by their base coin alone and settle in USDC.
+const HYPERLIQUID_QUOTE_ASSET: &str = "USDC";
+
+/// Maps trading pairs to exchange-native symbols and back.
+///
+/// | Exchange      | Native format |
+/// |---------------|---------------|
+/// | Binance       | `BTCUSDT`     |
+/// | Bybit         | `BTCUSDT`     |
+/// | Bitstamp      | `btcusd`      |
+/// | Coinbase      | `BTC-USD`     |
+/// | Kraken        | `XBT/USD`     |
+/// | CryptoDotCom  | `BTC_USDT`    |
+/// | GateIo        | `BTC_USDT`    |
+/// | KuCoin        | `BTC-USDT`    |
+/// | Bitfinex      | `tBTCUST`     |
+/// | OKX           | `BTC-USDT`    |
+/// | Hyperliquid   | `BTC`         |
+/// | KrakenFutures | `PF_XBTUSD`   |
+/// | Mexc          | `BTCUSDT`     |
+/// | Bitget        | `BTCUSDT`     |
+/// | Custom        | `BTCUSDT`     |
+///
+/// Custom exchanges get the concatenated format, which their connectors translate if needed.
+#[derive(Debug, Clone)]
+pub struct SymbolMapper {
+    quote_assets: Vec<String>,
+}
+
+impl SymbolMapper {
+    pub fn new() -> Self {
+        Self {
+            quote_assets: DEFAULT_QUOTE_ASSETS.iter().map(|q| q.to_string()).collect(),
+        }
+    }
+
+    /// Adds a quote asset used when splitting concatenated symbols. Longer assets are tried first.
+    pub fn with_quote_asset(mut self, quote: &str) -> Self {
+        let quote = quote.to_uppercase();
+        if !self.quote_assets.contains(&quote) {
+            self.quote_assets.push(quote);
+            self.quote_assets
+                .sort_by_key(|q| std::cmp::Reverse(q.len()));
+        }
+        self
+    }
+
+    /// Converts a trading pair to the exchange's native symbol.
+    pub fn to_exchange(&self, exchange: &Exchange, pair: &TradingPair) -> String {
+        match exchange {
+            Exchange::Binance
+            | Exchange::Bybit
+            | Exchange::Mexc
+            | Exchange::Bitget
+            | Exchange::Custom(_) => format!("{}{}", pair.base, pair.quote),
+            Exchange::Bitstamp => format!("{}{}", pair.base, pair.quote).to_lowercase(),
+            Exchange::Coinbase | Exchange::KuCoin | Exchange::OKX => {
+                format!("{}-{}", pair.base, pair.quote)
+            }
+            Exchange::CryptoDotCom | Exchange::GateIo => format!("{}_{}", pair.base, pair.quote),
+            Exchange::Kraken => format!(
+                "{}/{}",
+                Self::native_asset(KRAKEN_ASSET_ALIASES, &pair.base),
+                Self::native_asset(KRAKEN_ASSET_ALIASES, &pair.quote)
+            ),
+            Exchange::Bitfinex => {
+                let base = Self::native_asset(BITFINEX_ASSET_ALIASES, &pair.base);
+                let quote = Self::native_asset(BITFINEX_ASSET_ALIASES, &pair.quote);
+                // Pairs with an asset longer than three letters are separated by a colon
+                if base.len() == 3 && quote.len() == 3 {
+                    format!("t{}{}", base, quote)
+                } else {
+                    format!("t{}:{}", base, quote)
+                }
+            }
+            Exchange::Hyperliquid => pair.base.clone(),
+            Exchange::KrakenFutures => format!(
+                "{}{}{}",
+                KRAKEN_FUTURES_PERPETUAL_PREFIX,
+                Self::native_asset(KRAKEN_ASSET_ALIASES, &pair.base),
+                Self::native_asset(KRAKEN_ASSET_ALIASES, &pair.quote)
+            ),
+        }
+    }
+
+    /// Converts an exchange-native symbol back to a trading pair.
+    pub fn from_exchange(&self, exchange: &Exchange, symbol: &str) -> Result<TradingPair> {
+        let symbol = symbol.to_uppercase();
+        let (base, quote) = match exchange {
+            Exchange::Binance
+            | Exchange::Bybit
+            | Exchange::Bitstamp
+            | Exchange::Mexc
+            | Exchange::Bitget
+            | Exchange::Custom(_) => self.split_concatenated(&symbol)?,
+            Exchange::Coinbase | Exchange::KuCoin | Exchange::OKX => Self::split_on(&symbol, '-')?,
+            Exchange::CryptoDotCom | Exchange::GateIo => Self::split_on(&symbol, '_')?,
+            Exchange::Kraken => {
+                let (base, quote) = Self::split_on(&symbol, '/')?;
+                (
+                    Self::common_asset(KRAKEN_ASSET_ALIASES, &base),
+                    Self::common_asset(KRAKEN_ASSET_ALIASES, &quote),
+                )
+            }
+            Exchange::Bitfinex => {
+                let (base, quote) = Self::split_bitfinex(&symbol)?;
+                (
+                    Self::common_asset(BITFINEX_ASSET_ALIASES, &base),
+                    Self::common_asset(BITFINEX_ASSET_ALIASES, &quote),
+                )
+            }
+            Exchange::Hyperliquid => (symbol, HYPERLIQUID_QUOTE_ASSET.to_string()),
+            Exchange::KrakenFutures => {
+                let base = symbol
+                    .strip_prefix(KRAKEN_FUTURES_PERPETUAL_PREFIX)
+                    .or_else(|| symbol.strip_prefix(KRAKEN_FUTURES_INVERSE_PREFIX))
+                    .and_then(|contract| contract.strip_suffix(KRAKEN_FUTURES_QUOTE_ASSET))
+                    .filter(|base| !base.is_empty())
+                    .ok_or_else(|| {
+                        AggregatorError::parsing(
+                            "TradingPair",
+                            format!("Unsupported Kraken Futures contract: {}", symbol),
+                        )
+                    })?;
+                (
+                    Self::common_asset(KRAKEN_ASSET_ALIASES, base),
+                    KRAKEN_FUTURES_QUOTE_ASSET.to_string(),
+                )
+            }
+        };
+
+        Ok(TradingPair::new(&base, &quote))
+    }
+
+    /// Converts every pair of a subscription, rejecting an empty list.
+    pub fn to_exchange_all(
+        &self,
+        exchange: &Exchange,
+        pairs: &[TradingPair],
+    ) -> Result<Vec<String>> {
+        if pairs.is_empty() {
+            return Err(AggregatorError::validation(
+                "pairs",
+                "At least one trading pair is required",
+            ));
+        }
+        Ok(pairs
+            .iter()
+            .map(|pair| self.to_exchange(exchange, pair))
+            .collect())
+    }
+
+    fn split_concatenated(&self, symbol: &str) -> Result<(String, String)> {
+        self.quote_assets
+            .iter()
+            .find(|quote| symbol.len() > quote.len() && symbol.ends_with(quote.as_str()))
+            .map(|quote| {
+                let base = &symbol[..symbol.len() - quote.len()];
+                (base.to_string(), quote.clone())
+            })
+            .ok_or_else(|| {
+                AggregatorError::parsing(
+                    "TradingPair",
+                    format!("Unknown quote asset in symbol: {}", symbol),
+                )
+            })
+    }
+
+    fn split_on(symbol: &str, separator: char) -> Result<(String, String)> {
+        match symbol.split_once(separator) {
+            Some((base, quote)) if !base.is_empty() && !quote.is_empty() => {
+                Ok((base.to_string(), quote.to_string()))
+            }
+            _ => Err(AggregatorError::parsing(
+                "TradingPair",
+                format!("Invalid symbol format: {}", symbol),
+            )),
+        }
+    }
+
+    fn split_bitfinex(symbol: &str) -> Result<(String, String)> {
+        let invalid = || {
+            AggregatorError::parsing("TradingPair", format!("Invalid symbol format: {}", symbol))
+        };
+        // Trading pair symbols carry a `t` prefix, uppercased along with the rest
+        let pair = symbol.strip_prefix('T').ok_or_else(invalid)?;
+        if pair.contains(':') {
+            return Self::split_on(pair, ':');
+        }
+        if pair.len() != 6 {
+            return Err(invalid());
+        }
+        let (base, quote) = pair.split_at(3);
+        Ok((base.to_string(), quote.to_string()))
+    }
+
+    fn native_asset<'a>(aliases: &[(&str, &'a str)], asset: &'a str) -> &'a str {
+        aliases
+            .iter()
+            .find(|(common, _)| *common == asset)
+            .map(|(_, native)| *native)
+            .unwrap_or(asset)
+    }
+
+    fn common_asset(aliases: &[(&str, &str)], asset: &str) -> String {
+        aliases
+            .iter()
+            .find(|(_, native)| *native == asset)
+            .map(|(common, _)| common.to_string())
+            .unwrap_or_else(|| asset.to_string())
+    }
+}
+
+impl Default for SymbolMapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,323 @@
+//! Counters, gauges and histograms of the aggregator's activity, exported in the Prometheus
+//! text format
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Upper bounds, in milliseconds, of the buckets `MetricsRegistry::histogram` creates.
+pub const DEFAULT_LATENCY_BUCKETS_MS: [f64; 12] = [
+    1.0, 2.5, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0,
+];
+
+/// A value that only goes up. Clones share the value.
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, count: u64) {
+        self.0.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A value that can go up and down. Clones share the value.
+#[derive(Debug, Clone, Default)]
+pub struct Gauge(Arc<AtomicU64>);
+
+impl Gauge {
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug)]
+struct HistogramState {
+    bounds: Vec<f64>,
+    /// Observations per bucket, not cumulative; the last one is above every bound
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    /// Bits of the `f64` sum of observations
+    sum: AtomicU64,
+}
+
+/// Observations counted into buckets by upper bound. Clones share the buckets.
+#[derive(Debug, Clone)]
+pub struct Histogram(Arc<HistogramState>);
+
+impl Histogram {
+    /// A histogram with the given bucket upper bounds, sorted and without duplicates
+    pub fn new(bounds: &[f64]) -> Self {
+        let mut bounds = bounds.to_vec();
+        bounds.sort_by(|a, b| a.total_cmp(b));
+        bounds.dedup();
+        Self(Arc::new(HistogramState {
+            buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            bounds,
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0f64.to_bits()),
+        }))
+    }
+
+    pub fn observe(&self, value: f64) {
+        let state = &self.0;
+        let bucket = state.bounds.partition_point(|bound| *bound < value);
+        state.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        state.count.fetch_add(1, Ordering::Relaxed);
+        // Atomics have no floating point add, so retry until no other observation interleaves
+        let _ = state
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+    }
+
+    pub fn count(&self) -> u64 {
+        self.0.count.load(Ordering::Relaxed)
+    }
+
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.0.sum.load(Ordering::Relaxed))
+    }
+
+    /// Each upper bound with the number of observations at or below it, ending with
+    /// `f64::INFINITY` and the total count
+    pub fn cumulative_buckets(&self) -> Vec<(f64, u64)> {
+        let state = &self.0;
+        let mut total = 0;
+        state
+            .bounds
+            .iter()
+            .copied()
+            .chain(std::iter::once(f64::INFINITY))
+            .zip(&state.buckets)
+            .map(|(bound, bucket)| {
+                total += bucket.load(Ordering::Relaxed);
+                (bound, total)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Series {
+    Counter(Counter),
+    Gauge(Gauge),
+    Histogram(Histogram),
+}
+
+impl Series {
+    fn kind(&self) -> &'static str {
+        match self {
+            Series::Counter(_) => "counter",
+            Series::Gauge(_) => "gauge",
+            Series::Histogram(_) => "histogram",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Family {
+    help: &'static str,
+    kind: &'static str,
+    series: BTreeMap<Vec<(String, String)>, Series>,
+}
+
+/// Named metric families, each with one series per distinct set of labels.
+///
+/// Asking for a series that does not exist yet creates it, so callers fetch their handles where
+/// they record and keep them if they record often.
+///
+/// ```rust
+/// use aggregator_core::MetricsRegistry;
+///
+/// let registry = MetricsRegistry::new();
+/// let updates = registry.counter(
+///     "aggregator_price_updates_total",
+///     "Price level updates received",
+///     &[("exchange", "binance")],
+/// );
+/// updates.inc();
+/// assert!(registry
+///     .render()
+///     .contains("aggregator_price_updates_total{exchange=\"binance\"} 1"));
+/// ```
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    families: RwLock<BTreeMap<&'static str, Family>>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Panics if `name` is already registered as another kind of metric
+    pub fn counter(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+    ) -> Counter {
+        match self.series(name, help, labels, || Series::Counter(Counter::default())) {
+            Series::Counter(counter) => counter,
+            other => panic!("{} is a {}, not a counter", name, other.kind()),
+        }
+    }
+
+    /// Panics if `name` is already registered as another kind of metric
+    pub fn gauge(&self, name: &'static str, help: &'static str, labels: &[(&str, &str)]) -> Gauge {
+        match self.series(name, help, labels, || Series::Gauge(Gauge::default())) {
+            Series::Gauge(gauge) => gauge,
+            other => panic!("{} is a {}, not a gauge", name, other.kind()),
+        }
+    }
+
+    /// A histogram with `DEFAULT_LATENCY_BUCKETS_MS`. Panics if `name` is already registered as
+    /// another kind of metric.
+    pub fn histogram(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+    ) -> Histogram {
+        match self.series(name, help, labels, || {
+            Series::Histogram(Histogram::new(&DEFAULT_LATENCY_BUCKETS_MS))
+        }) {
+            Series::Histogram(histogram) => histogram,
+            other => panic!("{} is a {}, not a histogram", name, other.kind()),
+        }
+    }
+
+    /// Every series in the Prometheus text exposition format, families sorted by name
+    pub fn render(&self) -> String {
+        let families = self.families.read().unwrap_or_else(PoisonError::into_inner);
+        let mut out = String::new();
+        for (name, family) in families.iter() {
+            // Writing to a String cannot fail
+            let _ = writeln!(out, "# HELP {} {}", name, family.help);
+            let _ = writeln!(out, "# TYPE {} {}", name, family.kind);
+            for (labels, series) in &family.series {
+                match series {
+                    Series::Counter(counter) => {
+                        let _ = writeln!(
+                            out,
+                            "{}{} {}",
+                            name,
+                            render_labels(labels, None),
+                            counter.get()
+                        );
+                    }
+                    Series::Gauge(gauge) => {
+                        let _ = writeln!(
+                            out,
+                            "{}{} {}",
+                            name,
+                            render_labels(labels, None),
+                            gauge.get()
+                        );
+                    }
+                    Series::Histogram(histogram) => {
+                        for (bound, count) in histogram.cumulative_buckets() {
+                            let le = if bound.is_infinite() {
+                                "+Inf".to_string()
+                            } else {
+                                bound.to_string()
+                            };
+                            let _ = writeln!(
+                                out,
+                                "{}_bucket{} {}",
+                                name,
+                                render_labels(labels, Some(&le)),
+                                count
+                            );
+                        }
+                        let _ = writeln!(
+                            out,
+                            "{}_sum{} {}",
+                            name,
+                            render_labels(labels, None),
+                            histogram.sum()
+                        );
+                        let _ = writeln!(
+                            out,
+                            "{}_count{} {}",
+                            name,
+                            render_labels(labels, None),
+                            histogram.count()
+                        );
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn series(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        create: impl FnOnce() -> Series,
+    ) -> Series {
+        let mut key: Vec<(String, String)> = labels
+            .iter()
+            .map(|(label, value)| (label.to_string(), value.to_string()))
+            .collect();
+        key.sort();
+
+        if let Some(series) = self
+            .families
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .and_then(|family| family.series.get(&key))
+        {
+            return series.clone();
+        }
+
+        let mut families = self
+            .families
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let created = create();
+        let family = families.entry(name).or_insert_with(|| Family {
+            help,
+            kind: created.kind(),
+            series: BTreeMap::new(),
+        });
+        family.series.entry(key).or_insert(created).clone()
+    }
+}
+
+fn render_labels(labels: &[(String, String)], le: Option<&str>) -> String {
+    let rendered: Vec<String> = labels
+        .iter()
+        .map(|(label, value)| (label.as_str(), value.as_str()))
+        .chain(le.map(|le| ("le", le)))
+        .map(|(label, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", label, value)
+        })
+        .collect();
+    if rendered.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", rendered.join(","))
+    }
+}
@@ -7,7 +7,11 @@ use uuid::Uuid;
 /// The `#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]`
 /// attribute on the `Exchange` enum in Rust is implementing several traits and functionalities for the
 /// enum automatically. Here's what each trait does:
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+///
+/// Venues without a built-in variant are `Custom`, named after the connector registered for them
+/// in a `ConnectorRegistry`. Build them with `Exchange::named`, which lower-cases the name.
+/// Exchanges serialize as strings: the variant name for built-in ones, the name for custom ones.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Exchange {
     Binance,
     Bitstamp,
@@ -16,6 +20,14 @@ pub enum Exchange {
     Coinbase,
     CryptoDotCom,
     OKX,
+    GateIo,
+    KuCoin,
+    Bitfinex,
+    Hyperliquid,
+    KrakenFutures,
+    Mexc,
+    Bitget,
+    Custom(String),
 }
 
 /// The `impl Exchange { ... }` block with the `all()` function is defining a method associated with the
@@ -30,10 +42,91 @@ impl Exchange {
             Exchange::Coinbase,
             Exchange::CryptoDotCom,
             Exchange::OKX,
+            Exchange::GateIo,
+            Exchange::KuCoin,
+            Exchange::Bitfinex,
+            Exchange::Hyperliquid,
+            Exchange::KrakenFutures,
+            Exchange::Mexc,
+            Exchange::Bitget,
         ]
     }
+
+    /// The built-in exchange called `name`, by variant name (`KrakenFutures`) or display name
+    /// (`kraken_futures`), or else a custom exchange of that name
+    pub fn named(name: &str) -> Exchange {
+        Exchange::all()
+            .into_iter()
+            .find(|exchange| exchange.variant_name() == Some(name))
+            .or_else(|| name.parse().ok())
+            .unwrap_or_else(|| Exchange::Custom(name.to_lowercase()))
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Exchange::Custom(_))
+    }
+
+    fn variant_name(&self) -> Option<&'static str> {
+        let name = match self {
+            Exchange::Binance => "Binance",
+            Exchange::Bitstamp => "Bitstamp",
+            Exchange::Bybit => "Bybit",
+            Exchange::Kraken => "Kraken",
+            Exchange::Coinbase => "Coinbase",
+            Exchange::CryptoDotCom => "CryptoDotCom",
+            Exchange::OKX => "OKX",
+            Exchange::GateIo => "GateIo",
+            Exchange::KuCoin => "KuCoin",
+            Exchange::Bitfinex => "Bitfinex",
+            Exchange::Hyperliquid => "Hyperliquid",
+            Exchange::KrakenFutures => "KrakenFutures",
+            Exchange::Mexc => "Mexc",
+            Exchange::Bitget => "Bitget",
+            Exchange::Custom(_) => return None,
+        };
+        Some(name)
+    }
+}
+
+impl Serialize for Exchange {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Exchange::Custom(name) => serializer.serialize_str(name),
+            builtin => serializer.serialize_str(builtin.variant_name().unwrap_or_default()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Exchange {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Exchange::named(&name))
+    }
+}
+
+/// Exchanges are documented as the strings they serialize to, since custom exchanges can be
+/// named anything
+#[cfg(feature = "openapi")]
+impl utoipa::PartialSchema for Exchange {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        let builtin: Vec<&str> = Exchange::all()
+            .iter()
+            .filter_map(Exchange::variant_name)
+            .collect();
+        utoipa::openapi::ObjectBuilder::new()
+            .schema_type(utoipa::openapi::Type::String)
+            .description(Some(format!(
+                "A built-in exchange, one of {}, or the name of a custom one",
+                builtin.join(", ")
+            )))
+            .examples(["Binance"])
+            .into()
+    }
 }
 
+#[cfg(feature = "openapi")]
+impl utoipa::ToSchema for Exchange {}
+
 /// The `impl fmt::Display for Exchange { ... }` block in Rust is implementing the `fmt::Display` trait
 /// for the `Exchange` enum. This trait allows instances of the `Exchange` enum to be formatted as
 /// strings when using formatting macros like `println!` or `format!`.
@@ -47,13 +140,22 @@ impl fmt::Display for Exchange {
             Exchange::Coinbase => "coinbase",
             Exchange::CryptoDotCom => "crypto_dot_com",
             Exchange::OKX => "okx",
+            Exchange::GateIo => "gateio",
+            Exchange::KuCoin => "kucoin",
+            Exchange::Bitfinex => "bitfinex",
+            Exchange::Hyperliquid => "hyperliquid",
+            Exchange::KrakenFutures => "kraken_futures",
+            Exchange::Mexc => "mexc",
+            Exchange::Bitget => "bitget",
+            Exchange::Custom(name) => name,
         };
         write!(f, "{}", name)
     }
 }
 
 /// The `impl FromStr for Exchange` block in Rust is implementing the `FromStr` trait for the `Exchange`
-/// enum. This trait allows a string to be parsed into an `Exchange` enum variant.
+/// enum. This trait allows a string to be parsed into an `Exchange` enum variant. Only built-in
+/// exchanges parse; see `Exchange::named` for custom ones.
 impl FromStr for Exchange {
     type Err = crate::AggregatorError;
 
@@ -66,6 +168,13 @@ impl FromStr for Exchange {
             "coinbase" => Ok(Exchange::Coinbase),
             "crypto_dot_com" => Ok(Exchange::CryptoDotCom),
             "okx" => Ok(Exchange::OKX),
+            "gateio" => Ok(Exchange::GateIo),
+            "kucoin" => Ok(Exchange::KuCoin),
+            "bitfinex" => Ok(Exchange::Bitfinex),
+            "hyperliquid" => Ok(Exchange::Hyperliquid),
+            "kraken_futures" => Ok(Exchange::KrakenFutures),
+            "mexc" => Ok(Exchange::Mexc),
+            "bitget" => Ok(Exchange::Bitget),
             _ => Err(crate::AggregatorError::Parsing {
                 message: format!("Unknown exchange: {}", s),
                 data_type: "Exchange".to_string(),
@@ -90,6 +199,7 @@ impl FromStr for Exchange {
 /// the price level data was recorded. It is of type `DateTime<Utc>`, which is a datetime type provided
 /// by the `chrono` crate that represents a datetime in the UTC timezone.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct PriceLevel {
     pub price: f64,
     pub quantity: f64,
@@ -120,21 +230,25 @@ pub struct Bid {
 }
 
 /// The `impl PartialOrd for Bid { ... }` block is implementing the `PartialOrd` trait for the `Bid`
-/// struct in Rust. This trait allows instances of the `Bid` struct to be compared in a partial ordering
-/// context, meaning that not all pairs of elements need to have a defined ordering relationship.
+/// struct in Rust. It agrees with the total ordering defined by `Ord`.
 impl PartialOrd for Bid {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        // Higher price is better for bids
-        other.price.partial_cmp(&self.price)
+        Some(self.cmp(other))
     }
 }
 
 /// The `impl Ord for Bid { ... }` block in Rust is implementing the `Ord` trait for the `Bid` struct.
-/// The `Ord` trait is used for types that have a total ordering, meaning that all instances of the type
-/// can be compared and ordered relative to each other.
+///
+/// Bids are ordered best first by price, highest first, then by exchange. Keying on
+/// `(price, exchange)` keeps liquidity from different exchanges at the same price as distinct
+/// entries in sorted collections such as `BTreeSet`; quantity and timestamp are not part of the key.
 impl Ord for Bid {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap_or(std::cmp::Ordering::Equal)
+        // Higher price is better for bids
+        other
+            .price
+            .total_cmp(&self.price)
+            .then_with(|| self.exchange.cmp(&other.exchange))
     }
 }
 
@@ -170,40 +284,27 @@ pub struct Ask {
     pub timestamp: DateTime<Utc>,
 }
 
-/// Implements partial ordering for the `Ask` type based on price.
-///
-/// Lower prices are considered better for asks, so this implementation
-/// compares the `price` fields of two `Ask` instances using their
-/// `partial_cmp` method.
-///
-/// # Arguments
-///
-/// * `other` - Another `Ask` instance to compare with.
-///
-/// # Returns
-///
-/// An `Option<std::cmp::Ordering>` indicating the ordering between
-/// `self` and `other` based on their prices.
+/// Implements partial ordering for the `Ask` type, agreeing with the total ordering defined by
+/// `Ord`.
 impl PartialOrd for Ask {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        // Lower price is better for asks
-        self.price.partial_cmp(&other.price)
+        Some(self.cmp(other))
     }
 }
 
 /// Implements the `Ord` trait for the `Ask` type, allowing for total ordering comparisons.
 ///
-/// This implementation delegates to the `partial_cmp` method and returns `Ordering::Equal`
-/// if the comparison is not possible (i.e., if either value is NaN or otherwise not comparable).
-/// This is useful for sorting or ordering collections of `Ask` values.
-///
-/// # Panics
-///
-/// This implementation does not panic, but it may return `Ordering::Equal` for values that
-/// are not strictly equal if `partial_cmp` returns `None`.
+/// Asks are ordered best first by price, lowest first, then by exchange, so two asks compare
+/// equal exactly when they are the same `(price, exchange)` level. This keeps liquidity from
+/// different exchanges at the same price distinct in sorted collections such as `BTreeSet`.
+/// Prices are compared with `f64::total_cmp`, so NaN prices sort after every other price instead
+/// of comparing equal to them.
 impl Ord for Ask {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap_or(std::cmp::Ordering::Equal)
+        // Lower price is better for asks
+        self.price
+            .total_cmp(&other.price)
+            .then_with(|| self.exchange.cmp(&other.exchange))
     }
 }
 
@@ -241,6 +342,10 @@ impl Default for Ask {
 /// - `bids`: A vector of bid levels, representing buy orders.
 /// - `asks`: A vector of ask levels, representing sell orders.
 /// - `timestamp`: The time at which this update was generated.
+/// - `funding`: The latest funding information for perpetual futures books, `None` for spot.
+/// - `event_time`: When the exchange says the event happened, if its feed reports it.
+/// - `market_type`: The market the book belongs to, if the connector tags it. Connectors streaming
+///   the same symbol from several markets set it so each market keeps its own book.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceLevelUpdate {
     pub id: Uuid,
@@ -249,6 +354,207 @@ pub struct PriceLevelUpdate {
     pub bids: Vec<Bid>,
     pub asks: Vec<Ask>,
     pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub funding: Option<FundingRate>,
+    #[serde(default)]
+    pub event_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub market_type: Option<MarketType>,
+}
+
+impl PriceLevelUpdate {
+    /// Milliseconds between the exchange's event and this update being generated, when the
+    /// exchange reports event times.
+    pub fn receive_latency_ms(&self) -> Option<f64> {
+        self.event_time.map(|event_time| {
+            let latency = self.timestamp - event_time;
+            latency.num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0
+        })
+    }
+
+    /// Appends zero-quantity levels for every price present in `previous` but no longer in this
+    /// update, turning consecutive full snapshots into a delta the order books can apply.
+    pub fn mark_removed_levels(&mut self, previous: &PriceLevelUpdate) {
+        let removed_bids: Vec<Bid> = previous
+            .bids
+            .iter()
+            .filter(|old| !self.bids.iter().any(|bid| bid.price == old.price))
+            .map(|old| Bid {
+                quantity: 0.0,
+                timestamp: self.timestamp,
+                ..old.clone()
+            })
+            .collect();
+        let removed_asks: Vec<Ask> = previous
+            .asks
+            .iter()
+            .filter(|old| !self.asks.iter().any(|ask| ask.price == old.price))
+            .map(|old| Ask {
+                quantity: 0.0,
+                timestamp: self.timestamp,
+                ..old.clone()
+            })
+            .collect();
+
+        self.bids.extend(removed_bids);
+        self.asks.extend(removed_asks);
+    }
+}
+
+/// Funding information for a perpetual futures contract.
+///
+/// # Fields
+/// - `rate`: The current funding rate as a fraction (e.g., 0.0001 is 0.01%).
+/// - `mark_price`: The mark price used for funding and liquidations, if reported.
+/// - `next_funding_time`: When the next funding payment settles, if reported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub rate: f64,
+    pub mark_price: Option<f64>,
+    pub next_funding_time: Option<DateTime<Utc>>,
+}
+
+/// The side of the aggressor (taker) in a trade.
+///
+/// A `Buy` trade lifted an ask, a `Sell` trade hit a bid. Exchanges that report the maker
+/// side instead are converted by their connectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// Represents a single trade print reported by an exchange.
+///
+/// # Fields
+/// - `id`: Unique identifier assigned when the trade was received.
+/// - `trade_id`: The exchange's own identifier for the trade.
+/// - `symbol`: The trading symbol as reported by the exchange (e.g., "BTCUSDT").
+/// - `exchange`: The exchange where the trade was executed.
+/// - `price`: The execution price.
+/// - `quantity`: The executed quantity in base currency.
+/// - `side`: The aggressor side of the trade.
+/// - `timestamp`: The execution time reported by the exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trade {
+    pub id: Uuid,
+    pub trade_id: String,
+    pub symbol: String,
+    pub exchange: Exchange,
+    pub price: f64,
+    pub quantity: f64,
+    pub side: TradeSide,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Lifecycle state of an order reported on a private user channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+    Expired,
+}
+
+/// Represents a change to one of the account's own orders.
+///
+/// # Fields
+/// - `id`: Unique identifier assigned when the update was received.
+/// - `order_id`: The exchange's identifier for the order.
+/// - `client_order_id`: The identifier supplied by the client when placing the order, if any.
+/// - `symbol`: The trading symbol as reported by the exchange (e.g., "BTCUSDT").
+/// - `exchange`: The exchange the order lives on.
+/// - `side`: Whether the order buys or sells the base currency.
+/// - `status`: The order's state after this update.
+/// - `price`: The limit price, if the order has one.
+/// - `quantity`: The original order quantity, if reported.
+/// - `filled_quantity`: The cumulative filled quantity, if reported.
+/// - `timestamp`: The time of the update reported by the exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderUpdate {
+    pub id: Uuid,
+    pub order_id: String,
+    pub client_order_id: Option<String>,
+    pub symbol: String,
+    pub exchange: Exchange,
+    pub side: TradeSide,
+    pub status: OrderStatus,
+    pub price: Option<f64>,
+    pub quantity: Option<f64>,
+    pub filled_quantity: Option<f64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Represents the account's balance of a single asset after a change.
+///
+/// # Fields
+/// - `id`: Unique identifier assigned when the update was received.
+/// - `exchange`: The exchange holding the balance.
+/// - `asset`: The asset code (e.g., "BTC").
+/// - `free`: The amount available for trading.
+/// - `locked`: The amount reserved by open orders.
+/// - `timestamp`: The time of the update reported by the exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceUpdate {
+    pub id: Uuid,
+    pub exchange: Exchange,
+    pub asset: String,
+    pub free: f64,
+    pub locked: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// What happened to an order in an order-by-order (level 3) feed.
+///
+/// - `Received`: The exchange accepted the order; it is not on the book yet.
+/// - `Open`: The order now rests on the book with its remaining quantity.
+/// - `Match`: Part of the resting order traded against `taker_order_id`.
+/// - `Change`: The resting order's size was changed to a new quantity.
+/// - `Done`: The order left the book, or never rested on it; `reason` is e.g. "filled" or "canceled".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrderEventKind {
+    Received,
+    Open,
+    Match {
+        taker_order_id: String,
+        trade_id: String,
+    },
+    Change,
+    Done {
+        reason: String,
+    },
+}
+
+/// Represents a single event from an exchange's order-by-order feed, covering every order on the
+/// book rather than the account's own.
+///
+/// # Fields
+/// - `id`: Unique identifier assigned when the event was received.
+/// - `exchange`: The exchange the order lives on.
+/// - `symbol`: The trading symbol as reported by the exchange (e.g., "BTC-USD").
+/// - `order_id`: The exchange's identifier for the order; the resting (maker) order for matches.
+/// - `sequence`: The exchange's sequence number, used to detect gaps in the feed.
+/// - `side`: Whether the order buys or sells the base currency.
+/// - `kind`: What happened to the order.
+/// - `price`: The order's limit price, if it has one.
+/// - `quantity`: The order size on `Received`, remaining size on `Open` and `Done`, traded size on
+///   `Match` and new size on `Change`, if reported.
+/// - `timestamp`: The time of the event reported by the exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderEvent {
+    pub id: Uuid,
+    pub exchange: Exchange,
+    pub symbol: String,
+    pub order_id: String,
+    pub sequence: u64,
+    pub side: TradeSide,
+    pub kind: OrderEventKind,
+    pub price: Option<f64>,
+    pub quantity: Option<f64>,
+    pub timestamp: DateTime<Utc>,
 }
 
 /// Represents a summary of market data for a specific trading symbol.
@@ -259,13 +565,17 @@ pub struct PriceLevelUpdate {
 /// - `bids`: A list of bid price levels, typically sorted by price descending.
 /// - `asks`: A list of ask price levels, typically sorted by price ascending.
 /// - `timestamp`: The UTC timestamp indicating when this summary was generated
+/// - `market_type`: The market of the book the summary was built from, if the update was tagged.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Summary {
     pub symbol: String,
     pub spread: f64,
     pub bids: Vec<PriceLevel>,
     pub asks: Vec<PriceLevel>,
     pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub market_type: Option<MarketType>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -280,6 +590,7 @@ pub struct Summary {
 ///     base: "BTC".to_string(),
 ///     quote: "USD".to_string(),
 /// };
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TradingPair {
     pub base: String,
     pub quote: String,
@@ -344,7 +655,8 @@ impl FromStr for TradingPair {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum MarketType {
     Spot,
     Futures,
@@ -367,6 +679,7 @@ impl Default for OrderBookDepth {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ArbitrageOpportunity {
     pub buy_exchange: Exchange,
     pub sell_exchange: Exchange,
@@ -375,10 +688,17 @@ pub struct ArbitrageOpportunity {
     pub sell_price: f64,
     pub profit_percentage: f64,
     pub volume: f64,
+    pub average_buy_price: f64,
+    pub average_sell_price: f64,
+    #[serde(default)]
+    pub time_to_live_ms: f64,
+    #[serde(default)]
+    pub confidence: f64,
     pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct HealthStatus {
     pub exchange: Exchange,
     pub is_healthy: bool,
@@ -386,12 +706,233 @@ pub struct HealthStatus {
     pub error_message: Option<String>,
 }
 
+/// Overall state of the aggregator's feeds, from best to worst.
+///
+/// - `Healthy`: Every enabled exchange is healthy and none of their symbols is stale.
+/// - `Degraded`: At least one enabled exchange is healthy, but others are not or some symbols
+///   are stale.
+/// - `Unhealthy`: No enabled exchange is healthy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum HealthLevel {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+/// When an exchange last updated one of its symbols.
+///
+/// # Fields
+/// - `exchange`: The exchange sending the updates.
+/// - `symbol`: The exchange's symbol, as it sends it.
+/// - `last_update`: When the last update for the symbol was processed.
+/// - `is_stale`: Whether that was longer ago than `HealthConfig::stale_after_secs`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SymbolHealth {
+    pub exchange: Exchange,
+    pub symbol: String,
+    pub last_update: DateTime<Utc>,
+    pub is_stale: bool,
+}
+
+/// Health of the whole aggregator, as health endpoints report it.
+///
+/// # Fields
+/// - `level`: The overall state, see [`HealthLevel`].
+/// - `exchanges`: Status of every enabled exchange, in `Exchange` order.
+/// - `symbols`: Every symbol each enabled exchange has updated, by exchange then symbol.
+/// - `restarts`: Connector restarts attempted for each exchange that has not recovered since.
+/// - `timestamp`: When the health was assessed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SystemHealth {
+    pub level: HealthLevel,
+    pub exchanges: Vec<HealthStatus>,
+    pub symbols: Vec<SymbolHealth>,
+    pub restarts: std::collections::BTreeMap<Exchange, u32>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl SystemHealth {
+    /// Symbols with no recent update
+    pub fn stale_symbols(&self) -> impl Iterator<Item = &SymbolHealth> {
+        self.symbols.iter().filter(|symbol| symbol.is_stale)
+    }
+}
+
+/// A change in an exchange connection's state, emitted by connectors as they connect, drop and
+/// reconnect.
+///
+/// # Fields
+/// - `exchange`: The exchange whose connection changed.
+/// - `kind`: What happened to the connection.
+/// - `timestamp`: When the event was emitted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthEvent {
+    pub exchange: Exchange,
+    pub kind: HealthEventKind,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The kinds of connection events a connector can report.
+///
+/// - `Connected`: A connection was established (or re-established).
+/// - `Disconnected`: The connection was lost or could not be established.
+/// - `Reconnecting`: A reconnect will be attempted after `delay_ms`; `attempt` counts consecutive failures.
+/// - `ReconnectFailed`: The reconnect policy gave up after `attempts` consecutive failures.
+/// - `Stale`: No message arrived for `silent_ms`, so the connection is being torn down and retried.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HealthEventKind {
+    Connected,
+    Disconnected { reason: String },
+    Reconnecting { attempt: u32, delay_ms: u64 },
+    ReconnectFailed { attempts: u32 },
+    Stale { silent_ms: u64 },
+}
+
+impl HealthEvent {
+    pub fn new(exchange: Exchange, kind: HealthEventKind) -> Self {
+        Self {
+            exchange,
+            kind,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// The health status implied by this event. Only `Connected` is healthy.
+    pub fn health_status(&self) -> HealthStatus {
+        let error_message = match &self.kind {
+            HealthEventKind::Connected => None,
+            HealthEventKind::Disconnected { reason } => Some(reason.clone()),
+            HealthEventKind::Reconnecting { attempt, .. } => {
+                Some(format!("Reconnecting (attempt {})", attempt))
+            }
+            HealthEventKind::ReconnectFailed { attempts } => {
+                Some(format!("Gave up after {} reconnect attempts", attempts))
+            }
+            HealthEventKind::Stale { silent_ms } => {
+                Some(format!("No messages received for {}ms", silent_ms))
+            }
+        };
+
+        HealthStatus {
+            exchange: self.exchange.clone(),
+            is_healthy: error_message.is_none(),
+            last_update: self.timestamp,
+            error_message,
+        }
+    }
+}
+
+/// Throughput and latency figures for one exchange feed.
+///
+/// `latency_ms` is the mean receive latency (exchange event time to receipt) over a rolling
+/// window of recent updates, and `latency_p50_ms`, `latency_p95_ms` and `latency_p99_ms` are
+/// percentiles over the same window. Exchanges that do not report event times leave them at 0.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Metrics {
     pub exchange: Exchange,
     pub symbol: String,
     pub updates_per_second: f64,
     pub latency_ms: f64,
+    #[serde(default)]
+    pub latency_p50_ms: f64,
+    #[serde(default)]
+    pub latency_p95_ms: f64,
+    #[serde(default)]
+    pub latency_p99_ms: f64,
     pub error_count: u64,
     pub last_update: DateTime<Utc>,
 }
+
+/// Name, help text and value of one exported `BookStats` counter.
+type CounterFamily = (&'static str, &'static str, fn(&BookStats) -> u64);
+
+/// Activity counters of one order book since its instrumentation was enabled.
+///
+/// Every counter only grows, so churn rates come from the difference between two readings, see
+/// `since`. Books that take no lock leave the lock counters at 0.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookStats {
+    /// Levels received through updates, including removals.
+    pub updates_applied: u64,
+    /// Levels dropped to stay within the maximum depth.
+    pub levels_trimmed: u64,
+    /// Times an update acquired the book's write lock.
+    pub lock_acquisitions: u64,
+    /// Total time updates waited for the write lock, in microseconds.
+    pub lock_wait_us: u64,
+}
+
+impl BookStats {
+    /// Mean time an update waited for the write lock, in microseconds.
+    pub fn mean_lock_wait_us(&self) -> f64 {
+        if self.lock_acquisitions == 0 {
+            0.0
+        } else {
+            self.lock_wait_us as f64 / self.lock_acquisitions as f64
+        }
+    }
+
+    /// Activity between an `earlier` reading of the same book and this one.
+    pub fn since(&self, earlier: &BookStats) -> BookStats {
+        BookStats {
+            updates_applied: self.updates_applied.saturating_sub(earlier.updates_applied),
+            levels_trimmed: self.levels_trimmed.saturating_sub(earlier.levels_trimmed),
+            lock_acquisitions: self
+                .lock_acquisitions
+                .saturating_sub(earlier.lock_acquisitions),
+            lock_wait_us: self.lock_wait_us.saturating_sub(earlier.lock_wait_us),
+        }
+    }
+
+    /// Renders the counters of several books in the Prometheus text exposition format, one
+    /// series per book labelled with its exchange and symbol.
+    pub fn render_prometheus<'a>(
+        books: impl IntoIterator<Item = (&'a Exchange, &'a str, &'a BookStats)>,
+    ) -> String {
+        let books: Vec<_> = books.into_iter().collect();
+        let families: [CounterFamily; 4] = [
+            (
+                "orderbook_updates_applied_total",
+                "Levels received through updates, including removals",
+                |stats| stats.updates_applied,
+            ),
+            (
+                "orderbook_levels_trimmed_total",
+                "Levels dropped to stay within the maximum depth",
+                |stats| stats.levels_trimmed,
+            ),
+            (
+                "orderbook_lock_acquisitions_total",
+                "Times an update acquired the book write lock",
+                |stats| stats.lock_acquisitions,
+            ),
+            (
+                "orderbook_lock_wait_microseconds_total",
+                "Time updates waited for the book write lock",
+                |stats| stats.lock_wait_us,
+            ),
+        ];
+
+        let mut output = String::new();
+        for (name, help, value) in families {
+            output.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} counter\n",
+                name, help, name
+            ));
+            for (exchange, symbol, stats) in &books {
+                output.push_str(&format!(
+                    "{}{{exchange=\"{}\",symbol=\"{}\"}} {}\n",
+                    name,
+                    exchange,
+                    symbol,
+                    value(stats)
+                ));
+            }
+        }
+        output
+    }
+}
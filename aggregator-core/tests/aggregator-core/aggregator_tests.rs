@@ -1,12 +1,23 @@
 use super::*;
+use crate::analysis::TopOfBookEngine;
+use crate::circuit_breaker::{BreakerState, CircuitBreakerEvent, TripReason};
+use crate::clock::SimulatedClock;
 use crate::config::Config;
+use crate::config_watcher::ConfigChanged;
+use crate::connector::{ConnectorRegistry, OrderBookService};
+use crate::discovery::{MarketDiscovery, MarketListing};
+use crate::metrics_history::MetricsSample;
+use crate::replay::{RecordedUpdates, ReplayPace, ReplayReport};
+use crate::shutdown::{ShutdownReport, ShutdownStage};
+use crate::storage::MemoryStorage;
 use crate::types::{
-    ArbitrageOpportunity, Exchange, HealthStatus, Metrics, PriceLevel, PriceLevelUpdate, Summary,
-    TradingPair,
+    Ask, Bid, Exchange, HealthEvent, HealthEventKind, HealthLevel, HealthStatus, PriceLevel,
+    PriceLevelUpdate, Summary, SystemHealth, TradingPair,
 };
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
 use tokio::time::timeout;
 
 #[tokio::test]
@@ -58,51 +69,666 @@ async fn test_initialize_health_status() {
     }
 }
 
+fn price_level_update(
+    symbol: &str,
+    exchange: Exchange,
+    bids: Vec<(f64, f64)>,
+    asks: Vec<(f64, f64)>,
+) -> PriceLevelUpdate {
+    let timestamp = chrono::Utc::now();
+    PriceLevelUpdate {
+        id: uuid::Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        exchange: exchange.clone(),
+        bids: bids
+            .into_iter()
+            .map(|(price, quantity)| Bid {
+                price,
+                quantity,
+                exchange: exchange.clone(),
+                timestamp,
+            })
+            .collect(),
+        asks: asks
+            .into_iter()
+            .map(|(price, quantity)| Ask {
+                price,
+                quantity,
+                exchange: exchange.clone(),
+                timestamp,
+            })
+            .collect(),
+        timestamp,
+        funding: None,
+        event_time: None,
+        market_type: None,
+    }
+}
+
 #[tokio::test]
 async fn test_process_price_level_update_and_summary_broadcast() {
     let config = Config::default();
     let aggregator = Aggregator::new(config);
-    let summary_sender = aggregator.summary_sender.clone();
-    let price_level_update = PriceLevelUpdate {
-        id: uuid::Uuid::new_v4(),
-        symbol: "BTCUSDT".to_string(),
-        exchange: Exchange::Binance,
-        bids: vec![PriceLevel {
-            price: 100.0,
-            quantity: 1.0,
-            exchange: Exchange::Binance,
-            timestamp: chrono::Utc::now(),
-        }],
-        asks: vec![PriceLevel {
-            price: 101.0,
-            quantity: 1.0,
-            exchange: Exchange::Binance,
-            timestamp: chrono::Utc::now(),
-        }],
-        timestamp: chrono::Utc::now(),
-    };
-    let result = Aggregator::process_price_level_update(price_level_update, &summary_sender).await;
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+
+    let update = price_level_update(
+        "BTCUSDT",
+        Exchange::Binance,
+        vec![(100.0, 1.0)],
+        vec![(101.0, 1.0)],
+    );
+    let result = Aggregator::process_price_level_update(update, &aggregator.updates);
     assert!(result.is_ok());
+
     // Check that a summary was broadcast
-    let mut rx = summary_sender.subscribe();
-    let summary = timeout(std::time::Duration::from_millis(100), rx.recv()).await;
+    let summary = timeout(std::time::Duration::from_millis(100), summary_rx.recv()).await;
     assert!(summary.is_ok());
 }
 
+#[tokio::test]
+async fn test_aggregation_merges_exchanges_per_pair() {
+    let config = Config::default();
+    let aggregator = Aggregator::new(config);
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+
+    for update in [
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(100.0, 1.0), (99.0, 2.0)],
+            vec![(101.0, 1.0)],
+        ),
+        price_level_update(
+            "BTC-USDT",
+            Exchange::Kraken,
+            vec![(100.5, 0.5)],
+            vec![(100.8, 2.0)],
+        ),
+    ] {
+        Aggregator::process_price_level_update(update, &aggregator.updates).unwrap();
+    }
+
+    let mut summary = None;
+    for _ in 0..2 {
+        summary = timeout(std::time::Duration::from_millis(100), summary_rx.recv())
+            .await
+            .unwrap()
+            .ok();
+    }
+    let summary = summary.unwrap();
+    assert_eq!(summary.symbol, "BTCUSDT");
+    assert_eq!(summary.bids.len(), 3);
+    assert_eq!(summary.bids[0].exchange, Exchange::Kraken);
+    assert_eq!(summary.asks[0].price, 100.8);
+    assert!((summary.spread - 0.3).abs() < 1e-9);
+
+    // Both native symbols resolve to the same configured pair
+    let pair = TradingPair::new("BTC", "USDT");
+    let stored = aggregator.get_consolidated_summary(&pair).await.unwrap();
+    assert_eq!(stored.bids, summary.bids);
+    assert_eq!(stored.asks, summary.asks);
+    assert_eq!(aggregator.get_all_summaries().await.len(), 1);
+
+    // Each exchange's levels are also kept apart
+    let binance = aggregator
+        .get_summary_for_exchange(&pair, &Exchange::Binance)
+        .await
+        .unwrap();
+    assert_eq!(binance.bids.len(), 2);
+    assert!(binance
+        .bids
+        .iter()
+        .chain(&binance.asks)
+        .all(|level| level.exchange == Exchange::Binance));
+    assert!((binance.spread - 1.0).abs() < 1e-9);
+    assert_eq!(aggregator.get_exchange_summaries(&pair).await.len(), 2);
+    assert!(aggregator
+        .get_summary_for_exchange(&pair, &Exchange::Bybit)
+        .await
+        .is_none());
+
+    // A zero quantity removes the level
+    Aggregator::process_price_level_update(
+        price_level_update("BTCUSDT", Exchange::Kraken, vec![(100.5, 0.0)], vec![]),
+        &aggregator.updates,
+    )
+    .unwrap();
+    let summary = timeout(std::time::Duration::from_millis(100), summary_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary.bids[0].price, 100.0);
+}
+
+#[tokio::test]
+async fn test_aggregation_merges_venue_aliases() {
+    let config = Config {
+        trading_pairs: vec![
+            TradingPair::new("BTC", "USD"),
+            TradingPair::new("BTC", "USDT"),
+        ],
+        ..Config::default()
+    };
+    let aggregator = Aggregator::new(config);
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+
+    let updates = [
+        ("BTC-USD", Exchange::Coinbase),
+        ("XBT/USD", Exchange::Kraken),
+        ("PF_XBTUSD", Exchange::KrakenFutures),
+        ("BTCUSDT", Exchange::Binance),
+        ("tBTCUST", Exchange::Bitfinex),
+        ("BTC", Exchange::Hyperliquid),
+    ];
+    for (symbol, exchange) in &updates {
+        Aggregator::process_price_level_update(
+            price_level_update(symbol, exchange.clone(), vec![(100.0, 1.0)], vec![]),
+            &aggregator.updates,
+        )
+        .unwrap();
+    }
+    for _ in &updates {
+        timeout(std::time::Duration::from_millis(100), summary_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    // Each venue's own asset codes land on the pair other venues quote, while Hyperliquid
+    // settles in USDC
+    for (pair, expected) in [
+        (
+            TradingPair::new("BTC", "USD"),
+            vec![
+                Exchange::Kraken,
+                Exchange::Coinbase,
+                Exchange::KrakenFutures,
+            ],
+        ),
+        (
+            TradingPair::new("BTC", "USDT"),
+            vec![Exchange::Binance, Exchange::Bitfinex],
+        ),
+        (TradingPair::new("BTC", "USDC"), vec![Exchange::Hyperliquid]),
+    ] {
+        let mut exchanges: Vec<Exchange> = aggregator
+            .get_exchange_summaries(&pair)
+            .await
+            .into_keys()
+            .collect();
+        exchanges.sort();
+        assert_eq!(exchanges, expected, "{:?}", pair);
+    }
+    assert_eq!(aggregator.get_all_summaries().await.len(), 3);
+}
+
 #[tokio::test]
 async fn test_arbitrage_detector_no_opportunity() {
     let config = Config::default();
     let aggregator = Aggregator::new(config);
-    let pair = TradingPair::new("BTCUSDT", "USDT");
     let summary = Summary {
         symbol: "BTCUSDT".to_string(),
         spread: 0.0,
         bids: vec![],
         asks: vec![],
         timestamp: chrono::Utc::now(),
+        market_type: None,
     };
-    let result = Aggregator::detect_arbitrage_opportunity(&pair, &summary).await;
-    assert!(result.is_none());
+    // With no engines registered there is nothing to report
+    let batch = Arc::new(HashMap::from([("binance:BTCUSDT".to_string(), summary)]));
+    assert!(aggregator.analysis_engines().run(batch).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_built_in_engine_reports_cross_exchange_opportunity() {
+    let mut config = Config::default();
+    config.analysis.min_profit_percentage = 0.1;
+    config.analysis.net_of_fees = false;
+    let aggregator = Aggregator::new(config);
+    let mut arbitrage_rx = aggregator.subscribe_arbitrage();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+    let _detector = aggregator.start_arbitrage_detector().await.unwrap();
+
+    // Bybit asks 100 while Binance bids 101
+    for update in [
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(101.0, 2.0)],
+            vec![(102.0, 1.0)],
+        ),
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Bybit,
+            vec![(99.0, 1.0)],
+            vec![(100.0, 0.5)],
+        ),
+    ] {
+        Aggregator::process_price_level_update(update, &aggregator.updates).unwrap();
+    }
+
+    let opportunity = timeout(std::time::Duration::from_secs(3), arbitrage_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(opportunity.buy_exchange, Exchange::Bybit);
+    assert_eq!(opportunity.sell_exchange, Exchange::Binance);
+    assert_eq!(opportunity.volume, 0.5);
+    assert!((opportunity.profit_percentage - 1.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_holds_back_opportunities_during_outage() {
+    let mut config = Config::default();
+    config.analysis.min_profit_percentage = 0.1;
+    config.analysis.net_of_fees = false;
+    config.analysis.circuit_breaker.enabled = true;
+    let aggregator = Aggregator::new(config);
+    aggregator.initialize_health_status().await.unwrap();
+    let mut arbitrage_rx = aggregator.subscribe_arbitrage();
+    let mut breaker_rx = aggregator.subscribe::<CircuitBreakerEvent>();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+    let _detector = aggregator.start_arbitrage_detector().await.unwrap();
+
+    for update in [
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(101.0, 2.0)],
+            vec![(102.0, 1.0)],
+        ),
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Bybit,
+            vec![(99.0, 1.0)],
+            vec![(100.0, 0.5)],
+        ),
+    ] {
+        Aggregator::process_price_level_update(update, &aggregator.updates).unwrap();
+    }
+
+    // No exchange is healthy, so everything is paused
+    let paused = timeout(std::time::Duration::from_secs(3), breaker_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(paused.symbol, None);
+    assert_eq!(
+        paused.state,
+        BreakerState::Open(TripReason::TooFewHealthyExchanges { healthy: 0 })
+    );
+    assert!(!aggregator
+        .circuit_breaker()
+        .allows("BTCUSDT", chrono::Utc::now()));
+    assert!(arbitrage_rx.try_recv().is_err());
+
+    for exchange in [Exchange::Binance, Exchange::Bybit] {
+        aggregator
+            .health_status
+            .write()
+            .await
+            .get_mut(&exchange)
+            .unwrap()
+            .is_healthy = true;
+    }
+    let resumed = timeout(std::time::Duration::from_secs(3), breaker_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(resumed.state, BreakerState::Closed);
+    let opportunity = timeout(std::time::Duration::from_secs(3), arbitrage_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(opportunity.buy_exchange, Exchange::Bybit);
+}
+
+#[tokio::test]
+async fn test_top_of_book_engine_charges_taker_fees() {
+    let mut config = Config::default();
+    config.analysis.min_profit_percentage = 0.1;
+    config.analysis.net_of_fees = true;
+    for exchange_config in config.exchanges.values_mut() {
+        exchange_config.fees.taker_fee = 0.006;
+    }
+    let engine = TopOfBookEngine::from_config(&config);
+
+    let summary = |exchange: Exchange, bid: f64, ask: f64| {
+        let level = |price| PriceLevel {
+            price,
+            quantity: 1.0,
+            exchange: exchange.clone(),
+            timestamp: chrono::Utc::now(),
+        };
+        Summary {
+            symbol: "BTCUSDT".to_string(),
+            spread: ask - bid,
+            bids: vec![level(bid)],
+            asks: vec![level(ask)],
+            timestamp: chrono::Utc::now(),
+            market_type: None,
+        }
+    };
+    let summaries = HashMap::from([
+        (
+            "binance:BTCUSDT".to_string(),
+            summary(Exchange::Binance, 101.0, 102.0),
+        ),
+        (
+            "bybit:BTCUSDT".to_string(),
+            summary(Exchange::Bybit, 99.0, 100.0),
+        ),
+    ]);
+
+    // A 1% gap does not cover 0.6% taker fees on both legs
+    assert!(engine
+        .analyze_summaries(&summaries)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_top_of_book_engine_compares_across_quotes() {
+    let mut config = Config::default();
+    config.analysis.min_profit_percentage = 0.1;
+    config.fx.enabled = true;
+    let aggregator = Aggregator::new(config.clone());
+
+    let summary = |exchange: Exchange, symbol: &str, bid: f64, ask: f64| {
+        let level = |price| PriceLevel {
+            price,
+            quantity: 1.0,
+            exchange: exchange.clone(),
+            timestamp: chrono::Utc::now(),
+        };
+        Summary {
+            symbol: symbol.to_string(),
+            spread: ask - bid,
+            bids: vec![level(bid)],
+            asks: vec![level(ask)],
+            timestamp: chrono::Utc::now(),
+            market_type: None,
+        }
+    };
+    // Binance bids 101 USDT while Bybit asks 100 USDC
+    let summaries = HashMap::from([
+        (
+            "binance:BTCUSDT".to_string(),
+            summary(Exchange::Binance, "BTCUSDT", 101.0, 102.0),
+        ),
+        (
+            "bybit:BTCUSDC".to_string(),
+            summary(Exchange::Bybit, "BTCUSDC", 99.0, 100.0),
+        ),
+    ]);
+
+    // Without FX the quotes are never compared
+    let engine = TopOfBookEngine::from_config(&config);
+    assert!(engine
+        .analyze_summaries(&summaries)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let engine = TopOfBookEngine::from_config(&config).with_fx(aggregator.fx());
+    let opportunities = engine.analyze_summaries(&summaries).await.unwrap();
+    assert_eq!(opportunities.len(), 1);
+    assert_eq!(opportunities[0].symbol, "BTCUSD");
+    assert_eq!(opportunities[0].buy_exchange, Exchange::Bybit);
+    assert_eq!(opportunities[0].sell_exchange, Exchange::Binance);
+
+    // A live rate valuing USDT at 0.98 USD wipes the gap out
+    aggregator
+        .fx()
+        .set_rate("USDT", "USD", 0.98, chrono::Utc::now());
+    assert!(engine
+        .analyze_summaries(&summaries)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_apply_config_change_toggles_connectors_and_pairs() {
+    let mut config = Config::default();
+    for exchange_config in config.exchanges.values_mut() {
+        exchange_config.enabled = false;
+    }
+    let aggregator =
+        Aggregator::new(config.clone()).with_connectors(idle_connectors(&[Exchange::Bybit]).await);
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+
+    let mut reloaded = config.clone();
+    reloaded
+        .exchanges
+        .get_mut(&Exchange::Bybit)
+        .unwrap()
+        .enabled = true;
+    reloaded.trading_pairs.push(TradingPair::new("SOL", "USDT"));
+    let changed = ConfigChanged::between(&config, reloaded.clone()).unwrap();
+    aggregator.apply_config_change(&changed).await.unwrap();
+    assert!(aggregator
+        .connector_stops
+        .read()
+        .await
+        .contains_key(&Exchange::Bybit));
+    assert_eq!(aggregator.config().await.trading_pairs.len(), 4);
+
+    // The new pair resolves without a restart
+    Aggregator::process_price_level_update(
+        price_level_update("SOLUSDT", Exchange::Kraken, vec![(150.0, 1.0)], vec![]),
+        &aggregator.updates,
+    )
+    .unwrap();
+    let summary = timeout(std::time::Duration::from_millis(100), summary_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary.symbol, "SOLUSDT");
+
+    let changed = ConfigChanged::between(&reloaded, config).unwrap();
+    aggregator.apply_config_change(&changed).await.unwrap();
+    assert!(aggregator.connector_stops.read().await.is_empty());
+    let report = aggregator.shutdown(std::time::Duration::from_secs(3)).await;
+    assert!(report.is_clean(), "{:?}", report.failed);
+    assert!(report.stopped.contains(&"bybit connector".to_string()));
+    assert!(report
+        .stopped
+        .contains(&"bybit price level processor".to_string()));
+}
+
+#[tokio::test]
+async fn test_subscription_management() {
+    let mut config = Config::default();
+    for (exchange, exchange_config) in config.exchanges.iter_mut() {
+        exchange_config.enabled = *exchange == Exchange::Kraken;
+    }
+    config.trading_pairs = vec![TradingPair::new("BTC", "USDT")];
+    let aggregator =
+        Aggregator::new(config).with_connectors(idle_connectors(&[Exchange::Bybit]).await);
+    let sol = TradingPair::new("SOL", "USDT");
+
+    assert!(aggregator.enable_exchange(Exchange::Bybit).await.unwrap());
+    assert!(!aggregator.enable_exchange(Exchange::Bybit).await.unwrap());
+    assert!(aggregator
+        .connector_stops
+        .read()
+        .await
+        .contains_key(&Exchange::Bybit));
+
+    // Running connectors are restarted with the new pair
+    assert!(aggregator.add_trading_pair(sol.clone()).await.unwrap());
+    assert!(!aggregator.add_trading_pair(sol.clone()).await.unwrap());
+    assert!(aggregator.config().await.trading_pairs.contains(&sol));
+    assert_eq!(aggregator.connector_stops.read().await.len(), 1);
+
+    assert!(aggregator.remove_trading_pair(&sol).await.unwrap());
+    assert!(!aggregator.remove_trading_pair(&sol).await.unwrap());
+    // The last pair cannot be removed
+    assert!(aggregator
+        .remove_trading_pair(&TradingPair::new("BTC", "USDT"))
+        .await
+        .is_err());
+    assert_eq!(aggregator.config().await.trading_pairs.len(), 1);
+
+    assert!(aggregator
+        .reconnect_exchange(&Exchange::Bybit)
+        .await
+        .unwrap());
+    assert!(aggregator
+        .connector_stops
+        .read()
+        .await
+        .contains_key(&Exchange::Bybit));
+    // Only running connectors are reconnected
+    assert!(!aggregator
+        .reconnect_exchange(&Exchange::Binance)
+        .await
+        .unwrap());
+
+    let mut analysis = aggregator.config().await.analysis.clone();
+    analysis.min_profit_percentage = 0.5;
+    assert!(aggregator.update_analysis(analysis.clone()).await.unwrap());
+    assert!(!aggregator.update_analysis(analysis.clone()).await.unwrap());
+    assert_eq!(
+        aggregator.config().await.analysis.min_profit_percentage,
+        0.5
+    );
+    analysis.min_volume = -1.0;
+    assert!(aggregator.update_analysis(analysis).await.is_err());
+
+    assert!(aggregator.disable_exchange(&Exchange::Bybit).await.unwrap());
+    assert!(aggregator.connector_stops.read().await.is_empty());
+    // Nor can the last exchange be disabled
+    assert!(aggregator
+        .disable_exchange(&Exchange::Kraken)
+        .await
+        .is_err());
+    assert_eq!(
+        aggregator.config().await.enabled_exchanges(),
+        vec![Exchange::Kraken]
+    );
+}
+
+#[tokio::test]
+async fn test_disabled_exchanges_and_removed_pairs_leave_the_books() {
+    let mut config = Config::default();
+    for (exchange, exchange_config) in config.exchanges.iter_mut() {
+        exchange_config.enabled = matches!(exchange, Exchange::Binance | Exchange::Bybit);
+    }
+    let btc = TradingPair::new("BTC", "USDT");
+    let eth = TradingPair::new("ETH", "USDT");
+    config.trading_pairs = vec![btc.clone(), eth.clone()];
+    let aggregator = Aggregator::new(config);
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+
+    for update in [
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(100.0, 1.0)],
+            vec![(101.0, 1.0)],
+        ),
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Bybit,
+            vec![(100.5, 1.0)],
+            vec![(100.8, 1.0)],
+        ),
+        price_level_update(
+            "ETHUSDT",
+            Exchange::Bybit,
+            vec![(2000.0, 1.0)],
+            vec![(2001.0, 1.0)],
+        ),
+    ] {
+        Aggregator::process_price_level_update(update, &aggregator.updates).unwrap();
+        timeout(std::time::Duration::from_secs(1), summary_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    let book = aggregator.get_order_book(&btc, 10).await.unwrap();
+    assert_eq!(book.consolidated.bids[0].exchange, Exchange::Bybit);
+
+    // Quotes of a disabled exchange do not linger in the consolidated book
+    assert!(aggregator.disable_exchange(&Exchange::Bybit).await.unwrap());
+    let book = aggregator.get_order_book(&btc, 10).await.unwrap();
+    assert!(book
+        .consolidated
+        .bids
+        .iter()
+        .chain(&book.consolidated.asks)
+        .all(|level| level.exchange == Exchange::Binance));
+    assert_eq!(book.consolidated.spread, 1.0);
+    assert!(aggregator
+        .get_summary_for_exchange(&btc, &Exchange::Bybit)
+        .await
+        .is_none());
+    assert!(aggregator.get_exchange_summaries(&eth).await.is_empty());
+
+    assert!(aggregator.remove_trading_pair(&eth).await.unwrap());
+    assert!(aggregator.get_order_book(&eth, 10).await.is_none());
+    assert!(aggregator.get_consolidated_summary(&eth).await.is_none());
+    assert!(aggregator.get_order_book(&btc, 10).await.is_some());
+}
+
+#[tokio::test]
+async fn test_reconnect_drops_the_levels_of_the_exchange() {
+    let aggregator = Aggregator::new(config_with_exchanges(&[Exchange::Kraken, Exchange::Bybit]));
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+    let (stop_tx, mut stop_rx) = broadcast::channel(1);
+    aggregator
+        .connector_stops
+        .write()
+        .await
+        .insert(Exchange::Bybit, running_connector(stop_tx));
+
+    for update in [
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Kraken,
+            vec![(100.0, 1.0)],
+            vec![(101.0, 1.0)],
+        ),
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Bybit,
+            vec![(100.5, 1.0)],
+            vec![(100.8, 1.0)],
+        ),
+    ] {
+        Aggregator::process_price_level_update(update, &aggregator.updates).unwrap();
+        timeout(std::time::Duration::from_secs(1), summary_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    // The levels seen before the reconnect are not kept alongside the fresh snapshots
+    assert!(aggregator
+        .reconnect_exchange(&Exchange::Bybit)
+        .await
+        .unwrap());
+    stop_rx.try_recv().unwrap();
+    let pair = TradingPair::new("BTC", "USDT");
+    let book = aggregator.get_order_book(&pair, 10).await.unwrap();
+    assert_eq!(book.consolidated.bids.len(), 1);
+    assert_eq!(book.consolidated.bids[0].exchange, Exchange::Kraken);
+    assert_eq!(book.consolidated.asks[0].exchange, Exchange::Kraken);
+    assert!(aggregator
+        .get_summary_for_exchange(&pair, &Exchange::Bybit)
+        .await
+        .is_none());
+
+    aggregator.stop().await.unwrap();
 }
 
 #[tokio::test]
@@ -145,3 +771,1141 @@ async fn test_health_monitor_marks_unhealthy() {
         assert_eq!(status.error_message.as_deref(), Some("No recent updates"));
     }
 }
+
+#[tokio::test]
+async fn test_health_events_published() {
+    let aggregator = Aggregator::new(Config::default());
+    let mut health = aggregator.subscribe::<HealthStatus>();
+    let mut summaries = aggregator.events().subscribe::<Summary>();
+
+    aggregator
+        .record_health_event(&HealthEvent::new(
+            Exchange::Binance,
+            HealthEventKind::Disconnected {
+                reason: "reset".to_string(),
+            },
+        ))
+        .await;
+
+    let status = health.try_recv().unwrap();
+    assert_eq!(status.exchange, Exchange::Binance);
+    assert!(!status.is_healthy);
+    assert!(summaries.try_recv().is_err());
+    assert_eq!(aggregator.events().subscriber_count::<Summary>(), 1);
+}
+
+#[tokio::test]
+async fn test_restart_restores_snapshot() {
+    let storage = Arc::new(MemoryStorage::new());
+    let aggregator = Aggregator::new(Config::default()).with_storage(storage.clone());
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+    Aggregator::process_price_level_update(
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(100.0, 1.0)],
+            vec![(101.0, 1.0)],
+        ),
+        &aggregator.updates,
+    )
+    .unwrap();
+    timeout(std::time::Duration::from_millis(100), summary_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    aggregator.save_snapshot().await.unwrap();
+    assert_eq!(storage.load_summaries().await.unwrap().len(), 1);
+
+    // A new aggregator over the same storage starts with the saved books
+    let restarted = Aggregator::new(Config::default()).with_storage(storage);
+    let mut summary_rx = restarted.subscribe_summaries();
+    let _processor = restarted.start_aggregation_processor().await.unwrap();
+    let pair = TradingPair::new("BTC", "USDT");
+    let restored = restarted.get_consolidated_summary(&pair).await.unwrap();
+    assert_eq!(restored.bids[0].price, 100.0);
+
+    // Live updates apply on top of the restored levels
+    Aggregator::process_price_level_update(
+        price_level_update("BTCUSDT", Exchange::Binance, vec![(99.0, 2.0)], vec![]),
+        &restarted.updates,
+    )
+    .unwrap();
+    let summary = timeout(std::time::Duration::from_millis(100), summary_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary.bids.len(), 2);
+    assert_eq!(summary.asks[0].price, 101.0);
+}
+
+#[tokio::test]
+async fn test_price_updates_populate_registry() {
+    let aggregator = Aggregator::new(Config::default());
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _aggregation = aggregator.start_aggregation_processor().await.unwrap();
+    aggregator.initialize_health_status().await.unwrap();
+    let (price_level_tx, price_level_rx) = tokio::sync::mpsc::channel(10);
+    let (_stop_tx, stop_rx) = broadcast::channel(1);
+    let (stopped_tx, _stopped_rx) = tokio::sync::oneshot::channel();
+    let _processor = Aggregator::launch_price_level_processor(
+        &aggregator.connector_context(),
+        Exchange::Binance,
+        price_level_rx,
+        stop_rx,
+        stopped_tx,
+    )
+    .await
+    .unwrap();
+
+    price_level_tx
+        .send(price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(100.0, 1.0)],
+            vec![(100.5, 1.0)],
+        ))
+        .await
+        .unwrap();
+    timeout(std::time::Duration::from_millis(100), summary_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let rendered = aggregator.registry().render();
+    assert!(rendered
+        .contains("aggregator_price_updates_total{exchange=\"binance\",symbol=\"BTCUSDT\"} 1\n"));
+    assert!(rendered.contains("aggregator_exchange_healthy{exchange=\"binance\"} 1\n"));
+    assert!(rendered.contains("aggregator_spread{symbol=\"BTCUSDT\"} 0.5\n"));
+    let metrics = aggregator.get_metrics(&Exchange::Binance).await.unwrap();
+    assert_eq!(metrics.error_count, 0);
+    // Less than a second of updates gives no rate yet
+    assert_eq!(metrics.updates_per_second, 0.0);
+}
+
+#[tokio::test]
+async fn test_metrics_history_is_recorded_and_restored() {
+    let clock = SimulatedClock::new(chrono::Utc::now());
+    let storage = Arc::new(MemoryStorage::new());
+    let earlier = MetricsSample {
+        exchange: Exchange::Binance,
+        symbol: "BTCUSDT".to_string(),
+        timestamp: clock.now() - chrono::Duration::hours(1),
+        updates: 120,
+        updates_per_second: 2.0,
+        latency_ms: 3.0,
+        max_latency_ms: 9.0,
+        errors: 0,
+    };
+    storage
+        .save_metrics_history(std::slice::from_ref(&earlier))
+        .await
+        .unwrap();
+    let aggregator = Aggregator::new(Config::default())
+        .with_storage(storage)
+        .with_clock(Arc::new(clock.clone()));
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _aggregation = aggregator.start_aggregation_processor().await.unwrap();
+    let (price_level_tx, price_level_rx) = tokio::sync::mpsc::channel(10);
+    let (_stop_tx, stop_rx) = broadcast::channel(1);
+    let (stopped_tx, _stopped_rx) = tokio::sync::oneshot::channel();
+    let _processor = Aggregator::launch_price_level_processor(
+        &aggregator.connector_context(),
+        Exchange::Binance,
+        price_level_rx,
+        stop_rx,
+        stopped_tx,
+    )
+    .await
+    .unwrap();
+
+    price_level_tx
+        .send(price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(100.0, 1.0)],
+            vec![(100.5, 1.0)],
+        ))
+        .await
+        .unwrap();
+    timeout(std::time::Duration::from_millis(100), summary_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    // The interval in progress is left out until it ends
+    let history = aggregator
+        .get_metrics_history(&Exchange::Binance, Some("BTCUSDT"), None)
+        .await;
+    assert_eq!(history, vec![earlier.clone()]);
+
+    clock.advance(std::time::Duration::from_secs(60));
+    let history = aggregator
+        .get_metrics_history(&Exchange::Binance, None, None)
+        .await;
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].updates, 1);
+    assert_eq!(history[1].errors, 0);
+    assert_eq!(
+        aggregator.metrics_history_feeds(),
+        vec![(Exchange::Binance, "BTCUSDT".to_string())]
+    );
+    let since = clock.now() - chrono::Duration::minutes(5);
+    assert_eq!(
+        aggregator
+            .get_metrics_history(&Exchange::Binance, None, Some(since))
+            .await
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_failed_updates_are_counted_by_category() {
+    // Without the aggregation processor nothing receives forwarded updates
+    let aggregator = Aggregator::new(Config::default());
+    let (price_level_tx, price_level_rx) = tokio::sync::mpsc::channel(10);
+    let (_stop_tx, stop_rx) = broadcast::channel(1);
+    let (stopped_tx, _stopped_rx) = tokio::sync::oneshot::channel();
+    let _processor = Aggregator::launch_price_level_processor(
+        &aggregator.connector_context(),
+        Exchange::Binance,
+        price_level_rx,
+        stop_rx,
+        stopped_tx,
+    )
+    .await
+    .unwrap();
+
+    price_level_tx
+        .send(price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(100.0, 1.0)],
+            vec![(100.5, 1.0)],
+        ))
+        .await
+        .unwrap();
+    let errors = aggregator.errors();
+    timeout(std::time::Duration::from_secs(1), async {
+        while errors.count("channel") == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    aggregator.record_error(
+        "connector",
+        Some(&Exchange::Bybit),
+        &AggregatorError::network("connection reset"),
+    );
+    let report = errors.report(None, None);
+    assert_eq!(report.total, 2);
+    assert_eq!(report.by_category.get("channel"), Some(&1));
+    assert_eq!(report.recent[0].source, "connector");
+    assert!(report.recent[0].recoverable);
+    assert_eq!(report.recent[1].source, "price_level_processor");
+    assert_eq!(report.recent[1].exchange, Some(Exchange::Binance));
+    assert!(aggregator
+        .registry()
+        .render()
+        .contains("aggregator_errors_total{category=\"network\"} 1"));
+}
+
+fn config_with_exchanges(exchanges: &[Exchange]) -> Config {
+    let mut config = Config::default();
+    for (exchange, exchange_config) in config.exchanges.iter_mut() {
+        exchange_config.enabled = exchanges.contains(exchange);
+    }
+    config
+}
+
+/// Stands in for a connector stopped through `stop`, whose processor has already finished
+fn running_connector(stop: broadcast::Sender<()>) -> RunningConnector {
+    let (stopped_tx, stopped) = tokio::sync::oneshot::channel();
+    let _ = stopped_tx.send(());
+    RunningConnector { stop, stopped }
+}
+
+#[tokio::test]
+async fn test_system_health_levels() {
+    let aggregator = Aggregator::new(config_with_exchanges(&[Exchange::Binance, Exchange::Bybit]));
+    let health = aggregator.system_health().await;
+    assert_eq!(health.level, HealthLevel::Unhealthy);
+    assert_eq!(health.exchanges.len(), 2);
+    assert!(health.symbols.is_empty());
+
+    let now = chrono::Utc::now();
+    {
+        let mut statuses = aggregator.health_status.write().await;
+        for exchange in [Exchange::Binance, Exchange::Bybit] {
+            statuses.insert(
+                exchange.clone(),
+                HealthStatus {
+                    exchange,
+                    is_healthy: true,
+                    last_update: now,
+                    error_message: None,
+                },
+            );
+        }
+        let mut updates = aggregator.symbol_updates.write().await;
+        updates.insert((Exchange::Binance, "BTCUSDT".to_string()), now);
+        updates.insert((Exchange::Bybit, "BTCUSDT".to_string()), now);
+        // Disabled exchanges are left out
+        updates.insert((Exchange::Kraken, "BTCUSDT".to_string()), now);
+    }
+    let health = aggregator.system_health().await;
+    assert_eq!(health.level, HealthLevel::Healthy);
+    assert_eq!(health.symbols.len(), 2);
+
+    aggregator.symbol_updates.write().await.insert(
+        (Exchange::Bybit, "ETHUSDT".to_string()),
+        now - chrono::Duration::seconds(60),
+    );
+    let health = aggregator.system_health().await;
+    assert_eq!(health.level, HealthLevel::Degraded);
+    let stale: Vec<_> = health.stale_symbols().collect();
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].exchange, Exchange::Bybit);
+    assert_eq!(stale[0].symbol, "ETHUSDT");
+}
+
+#[tokio::test]
+async fn test_health_monitor_restarts_silent_connector() {
+    let mut config = config_with_exchanges(&[Exchange::Binance]);
+    config.health.stale_after_secs = 5;
+    config.health.max_restarts = 1;
+    config.health.restart_backoff_secs = 0;
+    let aggregator =
+        Aggregator::new(config).with_connectors(idle_connectors(&[Exchange::Binance]).await);
+    let mut system_health = aggregator.subscribe::<SystemHealth>();
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+    Aggregator::process_price_level_update(
+        price_level_update("BTCUSDT", Exchange::Binance, vec![(100.0, 1.0)], vec![]),
+        &aggregator.updates,
+    )
+    .unwrap();
+    timeout(std::time::Duration::from_secs(1), summary_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    aggregator.initialize_health_status().await.unwrap();
+    aggregator
+        .health_status
+        .write()
+        .await
+        .get_mut(&Exchange::Binance)
+        .unwrap()
+        .last_update = chrono::Utc::now() - chrono::Duration::seconds(10);
+    let (stop_tx, mut stop_rx) = broadcast::channel(1);
+    aggregator
+        .connector_stops
+        .write()
+        .await
+        .insert(Exchange::Binance, running_connector(stop_tx));
+
+    let _monitor = aggregator.start_health_monitor().await.unwrap();
+    let health = timeout(std::time::Duration::from_secs(1), system_health.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    // The silent connector was stopped and started again, without the levels it had sent
+    stop_rx.try_recv().unwrap();
+    assert_eq!(health.restarts.get(&Exchange::Binance), Some(&1));
+    let pair = TradingPair::new("BTC", "USDT");
+    assert!(aggregator.get_consolidated_summary(&pair).await.is_none());
+    assert!(aggregator
+        .books
+        .read()
+        .await
+        .exchange_summary(&pair, &Exchange::Binance, 10)
+        .await
+        .is_none());
+    assert!(aggregator
+        .connector_stops
+        .read()
+        .await
+        .contains_key(&Exchange::Binance));
+    assert!(aggregator
+        .registry()
+        .render()
+        .contains("aggregator_connector_restarts_total{exchange=\"binance\"} 1\n"));
+
+    aggregator.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_health_monitor_leaves_disabled_exchanges_stopped() {
+    let mut config = config_with_exchanges(&[Exchange::Kraken]);
+    config.health.stale_after_secs = 5;
+    config.health.max_restarts = 1;
+    config.health.restart_backoff_secs = 0;
+    let aggregator =
+        Aggregator::new(config).with_connectors(idle_connectors(&[Exchange::Binance]).await);
+    let mut system_health = aggregator.subscribe::<SystemHealth>();
+    aggregator.initialize_health_status().await.unwrap();
+    aggregator.health_status.write().await.insert(
+        Exchange::Binance,
+        HealthStatus {
+            exchange: Exchange::Binance,
+            is_healthy: true,
+            last_update: chrono::Utc::now() - chrono::Duration::seconds(10),
+            error_message: None,
+        },
+    );
+    // Still running while a reconfiguration disables it
+    let (stop_tx, mut stop_rx) = broadcast::channel(1);
+    aggregator
+        .connector_stops
+        .write()
+        .await
+        .insert(Exchange::Binance, running_connector(stop_tx));
+
+    let _monitor = aggregator.start_health_monitor().await.unwrap();
+    timeout(std::time::Duration::from_secs(1), system_health.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert!(stop_rx.try_recv().is_err());
+    assert!(!aggregator
+        .registry()
+        .render()
+        .contains("aggregator_connector_restarts_total{exchange=\"binance\"}"));
+
+    aggregator.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_in_stage_order() {
+    let storage = Arc::new(MemoryStorage::new());
+    let aggregator = Aggregator::new(config_with_exchanges(&[Exchange::Binance]))
+        .with_storage(storage.clone())
+        .with_connectors(idle_connectors(&[Exchange::Binance]).await);
+    aggregator.start().await.unwrap();
+
+    // A server only stops once the final snapshot is saved
+    let server_storage = storage.clone();
+    let stopping = aggregator.stopping(ShutdownStage::Servers);
+    aggregator.track_task(
+        ShutdownStage::Servers,
+        "test server",
+        tokio::spawn(async move {
+            stopping.await;
+            let books = server_storage.load_book_snapshots().await?;
+            assert_eq!(books.len(), 1);
+            assert_eq!(books[0].summary.bids[0].price, 100.0);
+            Ok(())
+        }),
+    );
+    // Still in flight when shutdown starts
+    Aggregator::process_price_level_update(
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(100.0, 1.0)],
+            vec![(101.0, 1.0)],
+        ),
+        &aggregator.updates,
+    )
+    .unwrap();
+
+    let report = aggregator.shutdown(std::time::Duration::from_secs(5)).await;
+    assert!(report.is_clean(), "{:?}", report.failed);
+    let position = |component: &str| {
+        report
+            .stopped
+            .iter()
+            .position(|stopped| stopped == component)
+            .unwrap_or_else(|| panic!("{} did not stop", component))
+    };
+    assert!(position("binance connector") < position("aggregation processor"));
+    assert!(position("binance price level processor") < position("aggregation processor"));
+    assert!(position("health monitor") < position("arbitrage detector"));
+    assert!(position("aggregation processor") < position("snapshotter"));
+    assert!(position("snapshotter") < position("test server"));
+    assert!(aggregator.connector_stops.read().await.is_empty());
+
+    // Nothing is left for a second shutdown
+    assert_eq!(
+        aggregator.shutdown(std::time::Duration::from_secs(1)).await,
+        ShutdownReport::default()
+    );
+}
+
+/// Sends nothing, waiting to be stopped
+struct IdleConnector;
+
+#[async_trait::async_trait]
+impl OrderBookService for IdleConnector {
+    async fn spawn_order_book_service(
+        &self,
+        _pairs: &[TradingPair],
+        _order_book_depth: usize,
+        _exchange_stream_buffer: usize,
+        _price_level_tx: mpsc::Sender<PriceLevelUpdate>,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        Ok(vec![tokio::spawn(async move {
+            let _ = shutdown.recv().await;
+            Ok(())
+        })])
+    }
+}
+
+/// A registry running an `IdleConnector` for each of `exchanges`
+async fn idle_connectors(exchanges: &[Exchange]) -> ConnectorRegistry {
+    let connectors = ConnectorRegistry::new();
+    for exchange in exchanges {
+        connectors
+            .register(&exchange.to_string(), |_config| Ok(Arc::new(IdleConnector)))
+            .await;
+    }
+    connectors
+}
+
+/// Sends one update per pair, then waits to be stopped
+struct OneShotConnector {
+    exchange: Exchange,
+}
+
+#[async_trait::async_trait]
+impl OrderBookService for OneShotConnector {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        _order_book_depth: usize,
+        _exchange_stream_buffer: usize,
+        price_level_tx: mpsc::Sender<PriceLevelUpdate>,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let exchange = self.exchange.clone();
+        let symbols: Vec<String> = pairs
+            .iter()
+            .map(|pair| format!("{}{}", pair.base, pair.quote))
+            .collect();
+        Ok(vec![tokio::spawn(async move {
+            for symbol in symbols {
+                let update = price_level_update(
+                    &symbol,
+                    exchange.clone(),
+                    vec![(100.0, 1.0)],
+                    vec![(101.0, 1.0)],
+                );
+                if price_level_tx.send(update).await.is_err() {
+                    return Ok(());
+                }
+            }
+            let _ = shutdown.recv().await;
+            Ok(())
+        })])
+    }
+}
+
+/// Fills the price level queue before its service is even running, then waits to be stopped
+struct FloodConnector {
+    exchange: Exchange,
+}
+
+#[async_trait::async_trait]
+impl OrderBookService for FloodConnector {
+    async fn spawn_order_book_service(
+        &self,
+        _pairs: &[TradingPair],
+        _order_book_depth: usize,
+        _exchange_stream_buffer: usize,
+        price_level_tx: mpsc::Sender<PriceLevelUpdate>,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        for i in 0..10_000 {
+            let price = 100.0 + i as f64 * 0.01;
+            let update = price_level_update(
+                "BTCUSDT",
+                self.exchange.clone(),
+                vec![(price, 1.0)],
+                vec![(price + 1.0, 1.0)],
+            );
+            if price_level_tx.try_send(update).is_err() {
+                break;
+            }
+        }
+        Ok(vec![tokio::spawn(async move {
+            let _ = shutdown.recv().await;
+            Ok(())
+        })])
+    }
+}
+
+#[tokio::test]
+async fn test_disabling_an_exchange_drops_its_queued_updates() {
+    let config = config_with_exchanges(&[Exchange::Binance, Exchange::Kraken]);
+    let connectors = idle_connectors(&[Exchange::Kraken]).await;
+    connectors
+        .register(&Exchange::Binance.to_string(), |_config| {
+            Ok(Arc::new(FloodConnector {
+                exchange: Exchange::Binance,
+            }))
+        })
+        .await;
+    let aggregator = Aggregator::new(config).with_connectors(connectors);
+    aggregator.start().await.unwrap();
+
+    // Updates still queued when the exchange is disabled must not refill its books
+    assert!(aggregator
+        .disable_exchange(&Exchange::Binance)
+        .await
+        .unwrap());
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    let btc = TradingPair::new("BTC", "USDT");
+    if let Some(book) = aggregator.get_order_book(&btc, 10).await {
+        assert!(book
+            .consolidated
+            .bids
+            .iter()
+            .chain(&book.consolidated.asks)
+            .all(|level| level.exchange != Exchange::Binance));
+    }
+    assert!(aggregator
+        .get_summary_for_exchange(&btc, &Exchange::Binance)
+        .await
+        .is_none());
+
+    aggregator.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_reconnecting_drops_the_queued_updates_of_the_old_connector() {
+    let config = config_with_exchanges(&[Exchange::Binance]);
+    let connectors = ConnectorRegistry::new();
+    let flooded = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    connectors
+        .register(&Exchange::Binance.to_string(), move |_config| {
+            // Only the first connector floods, the reconnected one stays quiet
+            if flooded.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                Ok(Arc::new(IdleConnector))
+            } else {
+                Ok(Arc::new(FloodConnector {
+                    exchange: Exchange::Binance,
+                }))
+            }
+        })
+        .await;
+    let aggregator = Aggregator::new(config).with_connectors(connectors);
+    aggregator.start().await.unwrap();
+
+    assert!(aggregator
+        .reconnect_exchange(&Exchange::Binance)
+        .await
+        .unwrap());
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    let btc = TradingPair::new("BTC", "USDT");
+    assert!(aggregator
+        .get_summary_for_exchange(&btc, &Exchange::Binance)
+        .await
+        .is_none());
+    if let Some(book) = aggregator.get_order_book(&btc, 10).await {
+        assert!(book.consolidated.bids.is_empty());
+        assert!(book.consolidated.asks.is_empty());
+    }
+
+    aggregator.stop().await.unwrap();
+}
+
+/// Fails to start its service
+struct FailingConnector;
+
+#[async_trait::async_trait]
+impl OrderBookService for FailingConnector {
+    async fn spawn_order_book_service(
+        &self,
+        _pairs: &[TradingPair],
+        _order_book_depth: usize,
+        _exchange_stream_buffer: usize,
+        _price_level_tx: mpsc::Sender<PriceLevelUpdate>,
+        _shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        Err(AggregatorError::network("connection refused"))
+    }
+}
+
+#[tokio::test]
+async fn test_connectors_that_fail_to_start_are_not_left_running() {
+    let connectors = ConnectorRegistry::new();
+    let failed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    connectors
+        .register(&Exchange::Binance.to_string(), move |_config| {
+            // Only the first attempt fails
+            if failed.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                Ok(Arc::new(IdleConnector))
+            } else {
+                Ok(Arc::new(FailingConnector))
+            }
+        })
+        .await;
+    let aggregator =
+        Aggregator::new(config_with_exchanges(&[Exchange::Binance])).with_connectors(connectors);
+
+    assert!(aggregator
+        .start_exchange_connector(Exchange::Binance)
+        .await
+        .is_err());
+    assert!(aggregator.connector_stops.read().await.is_empty());
+    assert!(!aggregator
+        .reconnect_exchange(&Exchange::Binance)
+        .await
+        .unwrap());
+
+    // A later attempt starts it
+    aggregator
+        .start_exchange_connector(Exchange::Binance)
+        .await
+        .unwrap();
+    assert!(aggregator
+        .connector_stops
+        .read()
+        .await
+        .contains_key(&Exchange::Binance));
+
+    aggregator.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_exchanges_without_a_connector_are_not_started() {
+    let aggregator = Aggregator::new(config_with_exchanges(&[
+        Exchange::Binance,
+        Exchange::Kraken,
+    ]))
+    .with_connectors(idle_connectors(&[Exchange::Kraken]).await);
+    aggregator.start().await.unwrap();
+
+    let running: Vec<Exchange> = aggregator
+        .connector_stops
+        .read()
+        .await
+        .keys()
+        .cloned()
+        .collect();
+    assert_eq!(running, vec![Exchange::Kraken]);
+
+    aggregator.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_registered_connector_for_custom_exchange() {
+    let acme = Exchange::named("acme");
+    let mut config = config_with_exchanges(&[]);
+    config.trading_pairs = vec![TradingPair::new("BTC", "USDT")];
+    config.exchanges.entry(acme.clone()).or_default().enabled = true;
+
+    let connectors = ConnectorRegistry::new();
+    let exchange = acme.clone();
+    connectors
+        .register("Acme", move |_config| {
+            Ok(Arc::new(OneShotConnector {
+                exchange: exchange.clone(),
+            }))
+        })
+        .await;
+    let aggregator = Aggregator::new(config).with_connectors(connectors);
+    assert!(aggregator.connectors().contains(&acme).await);
+    let mut summary_rx = aggregator.subscribe_summaries();
+    aggregator.start().await.unwrap();
+
+    let summary = timeout(std::time::Duration::from_secs(1), summary_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary.symbol, "BTCUSDT");
+    assert_eq!(summary.bids[0].exchange, acme);
+    let health = aggregator.get_health_status(&acme).await.unwrap();
+    assert!(health.is_healthy);
+
+    let report = aggregator.shutdown(std::time::Duration::from_secs(5)).await;
+    assert!(report.is_clean(), "{:?}", report.failed);
+    assert!(report.stopped.contains(&"acme connector".to_string()));
+}
+
+/// Lists whatever markets the test currently puts in it
+struct FixedMarkets {
+    listings: Arc<std::sync::Mutex<Vec<MarketListing>>>,
+}
+
+#[async_trait::async_trait]
+impl MarketDiscovery for FixedMarkets {
+    async fn list_markets(&self) -> Result<Vec<MarketListing>> {
+        Ok(self.listings.lock().unwrap().clone())
+    }
+}
+
+fn market(exchange: &Exchange, base: &str, quote: &str, volume: f64) -> MarketListing {
+    MarketListing {
+        exchange: exchange.clone(),
+        pair: TradingPair::new(base, quote),
+        symbol: format!("{}{}", base, quote),
+        quote_volume: Some(volume),
+    }
+}
+
+#[tokio::test]
+async fn test_discovered_pairs_are_subscribed() {
+    let acme = Exchange::named("acme");
+    let mut config = config_with_exchanges(&[]);
+    config.trading_pairs.clear();
+    config.exchanges.entry(acme.clone()).or_default().enabled = true;
+    config.discovery.enabled = true;
+    config.discovery.refresh_interval_secs = 0;
+    let file_config = config.clone();
+
+    let listings = Arc::new(std::sync::Mutex::new(vec![
+        market(&acme, "ETH", "USDT", 10.0),
+        market(&acme, "BTC", "USDT", 20.0),
+        market(&acme, "BTC", "EUR", 30.0),
+    ]));
+    let connectors = ConnectorRegistry::new();
+    let exchange = acme.clone();
+    connectors
+        .register("acme", move |_config| {
+            Ok(Arc::new(OneShotConnector {
+                exchange: exchange.clone(),
+            }))
+        })
+        .await;
+    let shared = listings.clone();
+    connectors
+        .register_discovery("acme", move |_config| {
+            Ok(Arc::new(FixedMarkets {
+                listings: shared.clone(),
+            }))
+        })
+        .await;
+    let aggregator = Aggregator::new(config).with_connectors(connectors);
+    let mut summary_rx = aggregator.subscribe_summaries();
+    aggregator.start().await.unwrap();
+
+    let btc_usdt = TradingPair::new("BTC", "USDT");
+    assert_eq!(
+        aggregator.config().await.trading_pairs,
+        vec![btc_usdt.clone(), TradingPair::new("ETH", "USDT")]
+    );
+    let summary = timeout(std::time::Duration::from_secs(1), summary_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary.symbol, "BTCUSDT");
+
+    // Pairs no longer selected are dropped; a configured pair stays
+    let sol_usdt = TradingPair::new("SOL", "USDT");
+    *listings.lock().unwrap() = vec![market(&acme, "SOL", "USDT", 5.0)];
+    aggregator.add_trading_pair(btc_usdt.clone()).await.unwrap();
+    assert!(aggregator.refresh_trading_pairs().await.unwrap());
+    assert_eq!(
+        aggregator.config().await.trading_pairs,
+        vec![btc_usdt.clone(), sol_usdt.clone()]
+    );
+    assert!(aggregator.get_summary(&btc_usdt).await.is_some());
+
+    // Nothing found keeps the current pairs
+    listings.lock().unwrap().clear();
+    assert!(!aggregator.refresh_trading_pairs().await.unwrap());
+
+    // Reloading a file that only lists BTC/USDT keeps the discovered SOL/USDT
+    let mut reloaded = file_config.clone();
+    reloaded.trading_pairs = vec![btc_usdt.clone()];
+    reloaded.analysis.min_profit_percentage = 1.0;
+    let changed = ConfigChanged::between(&file_config, reloaded).unwrap();
+    aggregator.apply_config_change(&changed).await.unwrap();
+    let config = aggregator.config().await;
+    assert_eq!(config.trading_pairs, vec![btc_usdt, sol_usdt]);
+    assert_eq!(config.analysis.min_profit_percentage, 1.0);
+
+    let report = aggregator.shutdown(std::time::Duration::from_secs(5)).await;
+    assert!(report.is_clean(), "{:?}", report.failed);
+}
+
+#[tokio::test]
+async fn test_removed_discovered_pair_stays_removed_on_reload() {
+    let acme = Exchange::named("acme");
+    let btc_usdt = TradingPair::new("BTC", "USDT");
+    let mut config = config_with_exchanges(&[]);
+    config.trading_pairs = vec![btc_usdt.clone()];
+    config.exchanges.entry(acme.clone()).or_default().enabled = true;
+    config.discovery.enabled = true;
+    config.discovery.refresh_interval_secs = 0;
+    let file_config = config.clone();
+
+    let listings = Arc::new(std::sync::Mutex::new(vec![
+        market(&acme, "ETH", "USDT", 10.0),
+        market(&acme, "SOL", "USDT", 5.0),
+    ]));
+    let connectors = ConnectorRegistry::new();
+    connectors
+        .register_discovery("acme", move |_config| {
+            Ok(Arc::new(FixedMarkets {
+                listings: listings.clone(),
+            }))
+        })
+        .await;
+    let aggregator = Aggregator::new(config).with_connectors(connectors);
+
+    let eth_usdt = TradingPair::new("ETH", "USDT");
+    let sol_usdt = TradingPair::new("SOL", "USDT");
+    assert!(aggregator.refresh_trading_pairs().await.unwrap());
+    assert!(aggregator.remove_trading_pair(&eth_usdt).await.unwrap());
+    assert_eq!(
+        aggregator.config().await.trading_pairs,
+        vec![btc_usdt.clone(), sol_usdt.clone()]
+    );
+
+    // The reload keeps the discovered SOL/USDT but not the removed ETH/USDT
+    let mut reloaded = file_config.clone();
+    reloaded.analysis.min_profit_percentage = 1.0;
+    let changed = ConfigChanged::between(&file_config, reloaded).unwrap();
+    aggregator.apply_config_change(&changed).await.unwrap();
+    assert_eq!(
+        aggregator.config().await.trading_pairs,
+        vec![btc_usdt, sol_usdt]
+    );
+}
+
+#[tokio::test]
+async fn test_replay_drives_the_pipeline() {
+    let mut config = config_with_exchanges(&[Exchange::Binance, Exchange::Bybit]);
+    config.analysis.min_profit_percentage = 0.1;
+    config.analysis.net_of_fees = false;
+    let aggregator = Aggregator::new(config);
+    let mut arbitrage_rx = aggregator.subscribe_arbitrage();
+    let mut report_rx = aggregator.subscribe::<ReplayReport>();
+
+    // Bybit asks 100 while Binance bids 101
+    let recording = RecordedUpdates::from(vec![
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(101.0, 2.0)],
+            vec![(102.0, 1.0)],
+        ),
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Bybit,
+            vec![(99.0, 1.0)],
+            vec![(100.0, 0.5)],
+        ),
+    ]);
+    aggregator
+        .start_replay(recording, ReplayPace::Unpaced)
+        .await
+        .unwrap();
+
+    let report = timeout(std::time::Duration::from_secs(1), report_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        report,
+        ReplayReport {
+            updates: 2,
+            skipped: 0,
+            completed: true,
+        }
+    );
+    let opportunity = timeout(std::time::Duration::from_secs(3), arbitrage_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(opportunity.buy_exchange, Exchange::Bybit);
+    assert_eq!(opportunity.sell_exchange, Exchange::Binance);
+
+    let pair = TradingPair::new("BTC", "USDT");
+    let summary = aggregator.get_consolidated_summary(&pair).await.unwrap();
+    assert_eq!(summary.bids[0].price, 101.0);
+    assert_eq!(summary.asks[0].price, 100.0);
+    assert_eq!(aggregator.system_health().await.level, HealthLevel::Healthy);
+    // Finished feeds are no longer running
+    assert!(aggregator.connector_stops.read().await.is_empty());
+
+    let report = aggregator.shutdown(std::time::Duration::from_secs(5)).await;
+    assert!(report.is_clean(), "{:?}", report.failed);
+    assert!(report.stopped.contains(&"replay".to_string()));
+}
+
+#[tokio::test]
+async fn test_replay_keeps_the_recorded_pace() {
+    let aggregator = Aggregator::new(config_with_exchanges(&[Exchange::Binance]));
+    let mut report_rx = aggregator.subscribe::<ReplayReport>();
+    let first = price_level_update(
+        "BTCUSDT",
+        Exchange::Binance,
+        vec![(100.0, 1.0)],
+        vec![(101.0, 1.0)],
+    );
+    let mut second = first.clone();
+    second.timestamp = first.timestamp + chrono::Duration::seconds(1);
+
+    let started = tokio::time::Instant::now();
+    aggregator
+        .start_replay(
+            RecordedUpdates::from(vec![first, second]),
+            ReplayPace::Recorded { speed: 10.0 },
+        )
+        .await
+        .unwrap();
+    let report = timeout(std::time::Duration::from_secs(1), report_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(report.updates, 2);
+    // A second recorded ten times as fast
+    let elapsed = started.elapsed();
+    assert!(
+        elapsed >= std::time::Duration::from_millis(100),
+        "{:?}",
+        elapsed
+    );
+    assert!(
+        elapsed < std::time::Duration::from_millis(900),
+        "{:?}",
+        elapsed
+    );
+
+    aggregator.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_ends_a_replay_early() {
+    let aggregator = Aggregator::new(config_with_exchanges(&[Exchange::Binance]));
+    let mut report_rx = aggregator.subscribe::<ReplayReport>();
+    let first = price_level_update(
+        "BTCUSDT",
+        Exchange::Binance,
+        vec![(100.0, 1.0)],
+        vec![(101.0, 1.0)],
+    );
+    let mut later = first.clone();
+    later.timestamp = first.timestamp + chrono::Duration::hours(1);
+    aggregator
+        .start_replay(
+            RecordedUpdates::from(vec![first, later]),
+            ReplayPace::REAL_TIME,
+        )
+        .await
+        .unwrap();
+
+    let shutdown = aggregator.shutdown(std::time::Duration::from_secs(5)).await;
+    assert!(shutdown.is_clean(), "{:?}", shutdown.failed);
+    let report = report_rx.recv().await.unwrap();
+    assert!(!report.completed);
+    assert!(report.updates <= 1);
+}
+
+#[tokio::test]
+async fn test_health_follows_the_simulated_clock() {
+    let mut config = config_with_exchanges(&[Exchange::Binance]);
+    config.health.stale_after_secs = 30;
+    config.health.check_interval_secs = 5;
+    config.health.max_restarts = 0;
+    let clock = SimulatedClock::new(chrono::Utc::now());
+    let aggregator = Aggregator::new(config).with_clock(Arc::new(clock.clone()));
+    let mut health_rx = aggregator.subscribe::<HealthStatus>();
+    aggregator.initialize_health_status().await.unwrap();
+    {
+        let mut statuses = aggregator.health_status.write().await;
+        let status = statuses.get_mut(&Exchange::Binance).unwrap();
+        status.is_healthy = true;
+        status.last_update = clock.now();
+        aggregator
+            .symbol_updates
+            .write()
+            .await
+            .insert((Exchange::Binance, "BTCUSDT".to_string()), clock.now());
+    }
+    let _monitor = aggregator.start_health_monitor().await.unwrap();
+    assert_eq!(aggregator.system_health().await.level, HealthLevel::Healthy);
+
+    // However long it really takes, nothing is stale until the clock moves
+    clock.advance(std::time::Duration::from_secs(25));
+    assert_eq!(aggregator.system_health().await.level, HealthLevel::Healthy);
+    assert!(
+        timeout(std::time::Duration::from_millis(50), health_rx.recv())
+            .await
+            .is_err()
+    );
+
+    clock.advance(std::time::Duration::from_secs(6));
+    assert_eq!(
+        aggregator.system_health().await.level,
+        HealthLevel::Degraded
+    );
+    // The next check marks the silent exchange unhealthy
+    let status = timeout(std::time::Duration::from_secs(1), health_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(status.exchange, Exchange::Binance);
+    assert!(!status.is_healthy);
+    let health = aggregator.system_health().await;
+    assert_eq!(health.level, HealthLevel::Unhealthy);
+    assert_eq!(health.timestamp, clock.now());
+
+    aggregator.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_get_order_book_beyond_summary_depth() {
+    let mut config = Config::default();
+    config.orderbook.max_depth = 2;
+    let aggregator = Aggregator::new(config);
+    let mut summary_rx = aggregator.subscribe_summaries();
+    let _processor = aggregator.start_aggregation_processor().await.unwrap();
+    let pair = TradingPair::new("BTC", "USDT");
+    assert!(aggregator.get_order_book(&pair, 10).await.is_none());
+
+    for update in [
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            vec![(100.0, 1.0), (99.0, 2.0)],
+            vec![(101.0, 1.0), (102.0, 2.0)],
+        ),
+        price_level_update(
+            "BTCUSDT",
+            Exchange::Bybit,
+            vec![(99.5, 3.0), (98.0, 1.0)],
+            vec![(103.0, 1.0), (104.0, 1.0)],
+        ),
+    ] {
+        Aggregator::process_price_level_update(update, &aggregator.updates).unwrap();
+        timeout(std::time::Duration::from_secs(1), summary_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    // Stored summaries stop at `max_depth`
+    let summary = aggregator.get_consolidated_summary(&pair).await.unwrap();
+    assert_eq!(summary.bids.len(), 2);
+
+    let book = aggregator.get_order_book(&pair, 10).await.unwrap();
+    assert_eq!(book.pair, pair);
+    let bid_prices: Vec<f64> = book.consolidated.bids.iter().map(|l| l.price).collect();
+    assert_eq!(bid_prices, vec![100.0, 99.5, 99.0, 98.0]);
+    assert_eq!(book.consolidated.asks.len(), 4);
+    assert_eq!(book.consolidated.spread, 1.0);
+    assert_eq!(book.exchanges.len(), 2);
+    assert_eq!(book.exchanges[&Exchange::Bybit].bids[0].price, 99.5);
+
+    let attribution = book.attribution();
+    assert_eq!(attribution[&Exchange::Binance].bid_levels, 2);
+    assert_eq!(attribution[&Exchange::Binance].bid_quantity, 3.0);
+    assert_eq!(attribution[&Exchange::Bybit].ask_quantity, 2.0);
+
+    let top = aggregator.get_order_book(&pair, 1).await.unwrap();
+    assert_eq!(top.consolidated.bids.len(), 1);
+    assert_eq!(top.consolidated.asks[0].exchange, Exchange::Binance);
+    assert_eq!(top.exchanges[&Exchange::Binance].bids.len(), 1);
+    // Bybit is outquoted on both sides at this depth
+    assert!(!top.attribution().contains_key(&Exchange::Bybit));
+}
@@ -0,0 +1,142 @@
+// aggregator-core/tests/aggregator-core/backpressure_tests.rs
+// Unit tests for backpressure.rs
+
+use aggregator_core::backpressure::*;
+use aggregator_core::config::*;
+use aggregator_core::event_bus::*;
+use aggregator_core::types::*;
+use chrono::Utc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+fn summary(symbol: &str, spread: f64) -> Summary {
+    Summary {
+        symbol: symbol.to_string(),
+        spread,
+        bids: vec![],
+        asks: vec![],
+        timestamp: Utc::now(),
+        market_type: None,
+    }
+}
+
+fn config(policy: BackpressurePolicy, buffer_size: usize) -> BackpressureConfig {
+    BackpressureConfig {
+        policy,
+        buffer_size,
+    }
+}
+
+/// Lets the forwarding task buffer everything published so far
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(20)).await;
+}
+
+#[tokio::test]
+async fn test_drop_oldest() {
+    let bus = EventBus::new();
+    let mut receiver = bus.subscribe_bounded::<Summary>(&config(BackpressurePolicy::DropOldest, 2));
+    for spread in 0..5 {
+        bus.publish(summary("BTCUSDT", spread as f64));
+    }
+    settle().await;
+
+    assert_eq!(receiver.recv().await.unwrap().spread, 3.0);
+    assert_eq!(receiver.recv().await.unwrap().spread, 4.0);
+    assert_eq!(
+        receiver.stats(),
+        BackpressureSnapshot {
+            delivered: 2,
+            dropped: 3,
+            conflated: 0,
+            disconnected: 0,
+        }
+    );
+    assert_eq!(bus.backpressure::<Summary>(), receiver.stats());
+}
+
+#[tokio::test]
+async fn test_conflate_by_symbol() {
+    let bus = EventBus::new();
+    let mut receiver =
+        bus.subscribe_bounded::<Summary>(&config(BackpressurePolicy::ConflateBySymbol, 2));
+    bus.publish(summary("BTCUSDT", 1.0));
+    bus.publish(summary("ETHUSDT", 2.0));
+    bus.publish(summary("BTCUSDT", 3.0));
+    settle().await;
+
+    // The newer BTC summary takes the place of the older one
+    let first = receiver.recv().await.unwrap();
+    assert_eq!((first.symbol.as_str(), first.spread), ("BTCUSDT", 3.0));
+    assert_eq!(receiver.recv().await.unwrap().symbol, "ETHUSDT");
+    assert_eq!(receiver.stats().conflated, 1);
+
+    // A third symbol does not fit and pushes out the oldest
+    bus.publish(summary("BTCUSDT", 4.0));
+    bus.publish(summary("ETHUSDT", 5.0));
+    bus.publish(summary("SOLUSDT", 6.0));
+    settle().await;
+    assert_eq!(receiver.recv().await.unwrap().symbol, "ETHUSDT");
+    assert_eq!(receiver.recv().await.unwrap().symbol, "SOLUSDT");
+    assert_eq!(receiver.stats().dropped, 1);
+}
+
+#[tokio::test]
+async fn test_disconnect() {
+    let bus = EventBus::new();
+    let mut slow = bus.subscribe_bounded::<Summary>(&config(BackpressurePolicy::Disconnect, 2));
+    let mut fast = bus.subscribe_bounded::<Summary>(&config(BackpressurePolicy::Disconnect, 2));
+    for spread in 0..2 {
+        bus.publish(summary("BTCUSDT", spread as f64));
+    }
+    settle().await;
+    assert_eq!(fast.recv().await.unwrap().spread, 0.0);
+    assert_eq!(fast.recv().await.unwrap().spread, 1.0);
+
+    bus.publish(summary("BTCUSDT", 2.0));
+    settle().await;
+    assert!(slow.recv().await.is_none());
+    assert!(slow.is_disconnected());
+    assert_eq!(slow.stats().dropped, 3);
+    assert_eq!(fast.recv().await.unwrap().spread, 2.0);
+    assert!(!fast.is_disconnected());
+
+    let totals = bus.backpressure::<Summary>();
+    assert_eq!(totals.disconnected, 1);
+    assert_eq!(totals.delivered, 3);
+    assert_eq!(bus.backpressure_by_topic()["summaries"], totals);
+}
+
+#[tokio::test]
+async fn test_recv_waits_for_events() {
+    let bus = std::sync::Arc::new(EventBus::new());
+    let mut receiver =
+        bus.subscribe_bounded::<ArbitrageOpportunity>(&BackpressureConfig::default());
+    assert!(timeout(Duration::from_millis(20), receiver.recv())
+        .await
+        .is_err());
+
+    let publisher = bus.clone();
+    tokio::spawn(async move {
+        settle().await;
+        publisher.publish(ArbitrageOpportunity {
+            buy_exchange: Exchange::Binance,
+            sell_exchange: Exchange::Bybit,
+            symbol: "BTCUSDT".to_string(),
+            buy_price: 100.0,
+            sell_price: 101.0,
+            profit_percentage: 1.0,
+            volume: 1.0,
+            average_buy_price: 100.0,
+            average_sell_price: 101.0,
+            time_to_live_ms: 1000.0,
+            confidence: 1.0,
+            timestamp: Utc::now(),
+        });
+    });
+    let opportunity = timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("opportunity should be delivered")
+        .unwrap();
+    assert_eq!(opportunity.symbol, "BTCUSDT");
+}
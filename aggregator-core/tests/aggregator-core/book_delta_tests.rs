@@ -0,0 +1,135 @@
+// aggregator-core/tests/aggregator-core/book_delta_tests.rs
+// Unit tests for book_delta.rs
+
+use aggregator_core::book_delta::*;
+use aggregator_core::types::{Exchange, PriceLevel, Summary};
+use chrono::Utc;
+
+fn level(price: f64, quantity: f64, exchange: Exchange) -> PriceLevel {
+    PriceLevel {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+fn summary(bids: Vec<PriceLevel>, asks: Vec<PriceLevel>) -> Summary {
+    let spread = match (bids.first(), asks.first()) {
+        (Some(bid), Some(ask)) => ask.price - bid.price,
+        _ => 0.0,
+    };
+    Summary {
+        symbol: "BTCUSDT".to_string(),
+        spread,
+        bids,
+        asks,
+        timestamp: Utc::now(),
+        market_type: None,
+    }
+}
+
+#[test]
+fn test_deltas_carry_only_changed_levels_and_rebuild_the_book() {
+    let mut encoder = BookDeltaEncoder::new();
+    let first = summary(
+        vec![
+            level(100.0, 1.0, Exchange::Binance),
+            level(100.0, 2.0, Exchange::Kraken),
+            level(99.0, 5.0, Exchange::Binance),
+        ],
+        vec![
+            level(101.0, 1.0, Exchange::Kraken),
+            level(102.0, 3.0, Exchange::Binance),
+        ],
+    );
+    let Some(BookUpdate::Snapshot(mut book)) = encoder.encode(&first) else {
+        panic!("expected a snapshot");
+    };
+    assert_eq!(book.sequence, 1);
+
+    // Kraken's bid grows, Binance's 99 bid goes, a new ask arrives and the rest stays
+    let second = summary(
+        vec![
+            level(100.0, 1.0, Exchange::Binance),
+            level(100.0, 4.0, Exchange::Kraken),
+        ],
+        vec![
+            level(100.5, 0.5, Exchange::Bybit),
+            level(101.0, 1.0, Exchange::Kraken),
+            level(102.0, 3.0, Exchange::Binance),
+        ],
+    );
+    let Some(BookUpdate::Delta(delta)) = encoder.encode(&second) else {
+        panic!("expected a delta");
+    };
+    assert_eq!(delta.sequence, 2);
+    assert_eq!(delta.spread, 0.5);
+    assert_eq!(
+        delta.bids,
+        vec![
+            BookLevel {
+                price: 100.0,
+                quantity: 4.0,
+                exchange: Exchange::Kraken
+            },
+            BookLevel {
+                price: 99.0,
+                quantity: 0.0,
+                exchange: Exchange::Binance
+            },
+        ]
+    );
+    assert_eq!(delta.asks.len(), 1);
+
+    book.apply(&delta).unwrap();
+    assert_eq!(book, BookState::from_summary(&second, 2));
+
+    // Nothing changed, so nothing is sent and no sequence number is used
+    assert!(encoder.encode(&second).is_none());
+    let third = summary(
+        vec![level(100.0, 1.0, Exchange::Binance)],
+        second.asks.clone(),
+    );
+    let Some(BookUpdate::Delta(delta)) = encoder.encode(&third) else {
+        panic!("expected a delta");
+    };
+    assert_eq!(delta.sequence, 3);
+}
+
+#[test]
+fn test_apply_rejects_gaps_and_other_symbols() {
+    let mut encoder = BookDeltaEncoder::new();
+    let bid = |quantity| summary(vec![level(100.0, quantity, Exchange::Binance)], vec![]);
+    let Some(BookUpdate::Snapshot(mut book)) = encoder.encode(&bid(1.0)) else {
+        panic!("expected a snapshot");
+    };
+    encoder.encode(&bid(2.0));
+    let Some(BookUpdate::Delta(third)) = encoder.encode(&bid(3.0)) else {
+        panic!("expected a delta");
+    };
+    let error = book.apply(&third).unwrap_err();
+    assert_eq!(error.category(), "validation");
+    assert_eq!(book.sequence, 1);
+
+    let mut other = third.clone();
+    other.symbol = "ETHUSDT".to_string();
+    other.sequence = 2;
+    assert!(book.apply(&other).is_err());
+}
+
+#[test]
+fn test_snapshots_continue_the_sequence_and_serialize_tagged() {
+    let mut encoder = BookDeltaEncoder::new();
+    let book = summary(vec![level(100.0, 1.0, Exchange::Binance)], vec![]);
+    encoder.encode(&book);
+    assert_eq!(encoder.snapshot(&book).sequence, 2);
+
+    // A forgotten symbol starts over with a snapshot
+    encoder.retain(|symbol| symbol != "BTCUSDT");
+    let update = encoder.encode(&book).unwrap();
+    let json = serde_json::to_value(&update).unwrap();
+    assert_eq!(json["type"], "book-snapshot");
+    assert_eq!(json["data"]["sequence"], 1);
+    assert_eq!(json["data"]["bids"][0]["exchange"], "Binance");
+}
@@ -0,0 +1,83 @@
+// aggregator-core/tests/aggregator-core/circuit_breaker_tests.rs
+// Unit tests for circuit_breaker.rs
+
+use aggregator_core::circuit_breaker::*;
+use aggregator_core::config::CircuitBreakerConfig;
+use chrono::{Duration, Utc};
+
+fn breaker() -> CircuitBreaker {
+    CircuitBreaker::new(CircuitBreakerConfig {
+        enabled: true,
+        max_move_percentage: 5.0,
+        window_secs: 10,
+        cooldown_secs: 30,
+        min_healthy_exchanges: 2,
+    })
+}
+
+#[test]
+fn test_price_moves_pause_a_symbol_until_the_cooldown_ends() {
+    let breaker = breaker();
+    let start = Utc::now();
+    assert!(breaker.observe_price("BTC/USDT", 100.0, start).is_none());
+    // Slow drifts stay within the window's threshold
+    assert!(breaker
+        .observe_price("BTCUSDT", 104.0, start + Duration::seconds(5))
+        .is_none());
+    assert!(breaker
+        .observe_price("BTCUSDT", 108.0, start + Duration::seconds(16))
+        .is_none());
+
+    let tripped = start + Duration::seconds(20);
+    let event = breaker.observe_price("BTCUSDT", 96.0, tripped).unwrap();
+    assert_eq!(event.symbol.as_deref(), Some("BTCUSDT"));
+    assert_eq!(
+        event.state,
+        BreakerState::Open(TripReason::PriceMove {
+            move_percentage: 12.5
+        })
+    );
+    assert!(!breaker.allows("btc/usdt", tripped));
+    assert!(breaker.allows("ETHUSDT", tripped));
+
+    // Another excessive move extends the pause without a second event
+    let extended = tripped + Duration::seconds(5);
+    assert!(breaker.observe_price("BTCUSDT", 108.0, extended).is_none());
+    assert!(breaker.expire(tripped + Duration::seconds(31)).is_empty());
+    let resumed = breaker.expire(extended + Duration::seconds(30));
+    assert_eq!(resumed.len(), 1);
+    assert_eq!(resumed[0].state, BreakerState::Closed);
+    assert!(breaker.allows("BTCUSDT", extended + Duration::seconds(30)));
+}
+
+#[test]
+fn test_outages_pause_every_symbol() {
+    let breaker = breaker();
+    let now = Utc::now();
+    assert!(breaker.observe_healthy_exchanges(3, now).is_none());
+    let paused = breaker.observe_healthy_exchanges(1, now).unwrap();
+    assert_eq!(paused.symbol, None);
+    assert_eq!(
+        breaker.state("ETHUSDT", now),
+        BreakerState::Open(TripReason::TooFewHealthyExchanges { healthy: 1 })
+    );
+    // Only changes are reported
+    assert!(breaker.observe_healthy_exchanges(0, now).is_none());
+    let resumed = breaker.observe_healthy_exchanges(2, now).unwrap();
+    assert_eq!(resumed.state, BreakerState::Closed);
+    assert!(breaker.allows("ETHUSDT", now));
+}
+
+#[test]
+fn test_disabled_breaker_allows_everything() {
+    let breaker = breaker();
+    let now = Utc::now();
+    breaker.observe_healthy_exchanges(0, now);
+    assert!(!breaker.allows("BTCUSDT", now));
+
+    breaker.configure(CircuitBreakerConfig::default());
+    assert!(breaker.allows("BTCUSDT", now));
+    breaker.observe_price("BTCUSDT", 100.0, now);
+    assert!(breaker.observe_price("BTCUSDT", 200.0, now).is_none());
+    assert!(breaker.observe_healthy_exchanges(0, now).is_none());
+}
@@ -0,0 +1,70 @@
+// aggregator-core/tests/aggregator-core/clock_tests.rs
+// Unit tests for clock.rs
+
+use aggregator_core::clock::*;
+use chrono::{TimeZone, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_simulated_sleep_wakes_when_advanced() {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let clock = SimulatedClock::new(start);
+    let sleeper = tokio::spawn({
+        let clock = clock.clone();
+        async move {
+            clock.sleep(Duration::from_secs(10)).await;
+            clock.now()
+        }
+    });
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    clock.advance(Duration::from_secs(9));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!sleeper.is_finished());
+
+    clock.advance(Duration::from_secs(1));
+    let woke_at = tokio::time::timeout(Duration::from_secs(1), sleeper)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(woke_at, start + chrono::Duration::seconds(10));
+
+    // Deadlines already passed return at once
+    clock.sleep_until(start).await;
+    clock.set(start);
+    assert_eq!(clock.now(), start);
+}
+
+#[tokio::test]
+async fn test_ticker_skips_missed_ticks() {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let clock = SimulatedClock::new(start);
+    let shared: Arc<dyn Clock> = Arc::new(clock.clone());
+
+    let mut ticker = Ticker::new(shared.clone(), Duration::from_secs(5));
+    assert_eq!(ticker.tick().await, start);
+
+    clock.advance(Duration::from_secs(5));
+    assert_eq!(ticker.tick().await, start + chrono::Duration::seconds(5));
+
+    // Jumping past several ticks fires one, then resumes on the period
+    clock.advance(Duration::from_secs(17));
+    assert_eq!(ticker.tick().await, start + chrono::Duration::seconds(10));
+    clock.advance(Duration::from_secs(3));
+    assert_eq!(ticker.tick().await, start + chrono::Duration::seconds(25));
+
+    let mut delayed = Ticker::delayed(shared, Duration::from_secs(5));
+    let pending = tokio::time::timeout(Duration::from_millis(20), delayed.tick()).await;
+    assert!(pending.is_err());
+    clock.advance(Duration::from_secs(5));
+    assert_eq!(delayed.tick().await, start + chrono::Duration::seconds(30));
+}
+
+#[tokio::test]
+async fn test_system_clock() {
+    let clock = SystemClock::shared();
+    let before = Utc::now();
+    clock.sleep(Duration::from_millis(20)).await;
+    assert!(clock.now() - before >= chrono::Duration::milliseconds(20));
+}
@@ -3,6 +3,7 @@
 
 use aggregator_core::config::*;
 use aggregator_core::types::*;
+use aggregator_core::AggregatorError;
 
 #[test]
 fn test_exchange_config_defaults() {
@@ -15,6 +16,7 @@ fn test_exchange_config_defaults() {
         ping_interval: 5000,
         max_reconnect_attempts: 3,
         buffer_size: 1024,
+        stale_timeout: 20000,
     };
     let ex_cfg = ExchangeConfig {
         enabled: true,
@@ -24,6 +26,8 @@ fn test_exchange_config_defaults() {
         sandbox: false,
         rate_limit: rate_limit.clone(),
         websocket: ws_config.clone(),
+        proxy: Some("socks5://127.0.0.1:1080".to_string()),
+        fees: FeeSchedule::new(0.0002, 0.001).with_withdrawal_fee("btc", 0.0005),
     };
     assert!(ex_cfg.enabled);
     assert_eq!(ex_cfg.api_key.as_deref(), Some("key"));
@@ -31,6 +35,45 @@ fn test_exchange_config_defaults() {
     assert!(!ex_cfg.sandbox);
     assert_eq!(ex_cfg.rate_limit.requests_per_second, 10);
     assert_eq!(ex_cfg.websocket.reconnect_interval, 1000);
+    assert_eq!(ex_cfg.proxy.as_deref(), Some("socks5://127.0.0.1:1080"));
+    assert_eq!(ex_cfg.fees.taker_fee, 0.001);
+    assert_eq!(ex_cfg.fees.withdrawal_fee("BTC"), 0.0005);
+    assert_eq!(ex_cfg.fees.withdrawal_fee("ETH"), 0.0);
+}
+
+#[test]
+fn test_fee_schedule_defaults_to_zero() {
+    let json = r#"{
+        "enabled": true,
+        "api_key": null,
+        "api_secret": null,
+        "passphrase": null,
+        "sandbox": false,
+        "rate_limit": {"requests_per_second": 10, "burst_size": 20},
+        "websocket": {
+            "reconnect_interval": 1000,
+            "ping_interval": 5000,
+            "max_reconnect_attempts": 3,
+            "buffer_size": 1024,
+            "stale_timeout": 20000
+        },
+        "fees": {"taker_fee": 0.001}
+    }"#;
+    let ex_cfg: ExchangeConfig = serde_json::from_str(json).unwrap();
+    assert_eq!(ex_cfg.fees.taker_fee, 0.001);
+    assert_eq!(ex_cfg.fees.maker_fee, 0.0);
+    assert!(ex_cfg.fees.withdrawal_fees.is_empty());
+    assert_eq!(ExchangeConfig::default().fees, FeeSchedule::default());
+}
+
+#[test]
+fn test_analysis_config_partial_section_keeps_defaults() {
+    let analysis: AnalysisConfig =
+        serde_json::from_str(r#"{"min_profit_percentage": 0.25}"#).unwrap();
+    assert_eq!(analysis.min_profit_percentage, 0.25);
+    assert_eq!(analysis.min_volume, AnalysisConfig::default().min_volume);
+    assert!(analysis.net_of_fees);
+    assert_eq!(analysis.max_opportunity_age_ms, None);
 }
 
 #[test]
@@ -48,6 +91,219 @@ fn test_orderbook_config() {
     assert_eq!(ob_cfg.cleanup_interval, 1000);
 }
 
+#[test]
+fn test_pair_for_symbol() {
+    let config = Config {
+        trading_pairs: vec![
+            TradingPair::new("BTC", "USDT"),
+            TradingPair::new("ETH", "BTC"),
+        ],
+        ..Config::default()
+    };
+    let btc_usdt = Some(TradingPair::new("BTC", "USDT"));
+    assert_eq!(config.pair_for_symbol("BTCUSDT"), btc_usdt);
+    assert_eq!(config.pair_for_symbol("btc-usdt"), btc_usdt);
+    assert_eq!(
+        config.pair_for_symbol("ETHBTC"),
+        Some(TradingPair::new("ETH", "BTC"))
+    );
+    // Unconfigured pairs resolve only when the symbol separates base and quote
+    assert_eq!(
+        config.pair_for_symbol("SOL/USDC"),
+        Some(TradingPair::new("SOL", "USDC"))
+    );
+    assert_eq!(config.pair_for_symbol("SOLUSDC"), None);
+}
+
+#[test]
+fn test_pair_for_exchange_symbol() {
+    let config = Config {
+        trading_pairs: vec![
+            TradingPair::new("BTC", "USD"),
+            TradingPair::new("BTC", "USDT"),
+            TradingPair::new("DOGE", "USD"),
+        ],
+        ..Config::default()
+    };
+    let btc_usd = Some(TradingPair::new("BTC", "USD"));
+    let btc_usdt = Some(TradingPair::new("BTC", "USDT"));
+    for (exchange, symbol, pair) in [
+        (Exchange::Kraken, "XBT/USD", &btc_usd),
+        (
+            Exchange::Kraken,
+            "XDG/USD",
+            &Some(TradingPair::new("DOGE", "USD")),
+        ),
+        (Exchange::KrakenFutures, "PF_XBTUSD", &btc_usd),
+        (Exchange::Coinbase, "BTC-USD", &btc_usd),
+        (Exchange::Bitstamp, "btcusd", &btc_usd),
+        (Exchange::Bitfinex, "tBTCUST", &btc_usdt),
+        (Exchange::Binance, "BTCUSDT", &btc_usdt),
+        (Exchange::GateIo, "BTC_USDT", &btc_usdt),
+        // Hyperliquid names perpetuals by coin and settles them in USDC
+        (
+            Exchange::Hyperliquid,
+            "BTC",
+            &Some(TradingPair::new("BTC", "USDC")),
+        ),
+    ] {
+        assert_eq!(
+            &config.pair_for_exchange_symbol(&exchange, symbol),
+            pair,
+            "{} {}",
+            exchange,
+            symbol
+        );
+    }
+
+    // Symbols in another exchange's format still resolve as `pair_for_symbol` does
+    assert_eq!(
+        config.pair_for_exchange_symbol(&Exchange::Kraken, "BTCUSDT"),
+        btc_usdt
+    );
+    assert_eq!(
+        config.pair_for_exchange_symbol(&Exchange::Kraken, "XBTUSD"),
+        None
+    );
+}
+
+#[test]
+fn test_config_file_formats_round_trip() {
+    assert_eq!(ConfigFormat::from_path("config.toml"), ConfigFormat::Toml);
+    assert_eq!(ConfigFormat::from_path("config.YML"), ConfigFormat::Yaml);
+    assert_eq!(ConfigFormat::from_path("config"), ConfigFormat::Json);
+
+    let mut config = Config::default();
+    config.server.rest.port = 9091;
+    config.analysis.max_opportunity_age_ms = Some(500);
+    let dir = std::env::temp_dir();
+    for extension in ["json", "toml", "yaml"] {
+        let path = dir.join(format!(
+            "aggregator-config-{}.{}",
+            std::process::id(),
+            extension
+        ));
+        let path = path.to_str().unwrap();
+        config.to_file(path).unwrap();
+        let loaded = Config::from_file(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.server.rest.port, 9091);
+        assert_eq!(loaded.analysis.max_opportunity_age_ms, Some(500));
+        assert_eq!(loaded.exchanges.len(), config.exchanges.len());
+    }
+
+    assert!(Config::parse("server = 1", ConfigFormat::Toml).is_err());
+    assert!(Config::from_file("/nonexistent/aggregator.toml").is_err());
+}
+
+#[test]
+fn test_env_overrides() {
+    let config = Config::default()
+        .with_overrides([
+            ("AGG_SERVER__REST__PORT", "8080"),
+            ("AGG_EXCHANGES__BINANCE__API_KEY", "12345"),
+            ("AGG_EXCHANGES__CRYPTO_DOT_COM__ENABLED", "false"),
+            ("AGG_ANALYSIS__MIN_PROFIT_PERCENTAGE", "0.5"),
+            ("PATH", "/usr/bin"),
+        ])
+        .unwrap();
+    assert_eq!(config.server.rest.port, 8080);
+    assert_eq!(
+        config.exchanges[&Exchange::Binance].api_key.as_deref(),
+        Some("12345")
+    );
+    assert!(!config.exchanges[&Exchange::CryptoDotCom].enabled);
+    assert_eq!(config.analysis.min_profit_percentage, 0.5);
+
+    // A value of the wrong type names the variable
+    let error = Config::default()
+        .with_overrides([("AGG_SERVER__REST__PORT", "http")])
+        .unwrap_err();
+    assert!(error.to_string().contains("AGG_SERVER__REST__PORT"));
+}
+
+#[test]
+fn test_validate_reports_every_problem() {
+    assert!(Config::default().validate().is_ok());
+
+    let mut config = Config::default();
+    config.trading_pairs.clear();
+    config.server.rest.port = config.server.grpc.port;
+    config.server.grpc.tls = Some(TlsConfig {
+        cert_path: "/nonexistent/cert.pem".to_string(),
+        key_path: "/nonexistent/key.pem".to_string(),
+    });
+    let binance = config.exchanges.get_mut(&Exchange::Binance).unwrap();
+    binance.websocket.buffer_size = 0;
+    binance.api_secret = Some("secret".to_string());
+    // Disabled exchanges are not checked
+    let kraken = config.exchanges.get_mut(&Exchange::Kraken).unwrap();
+    kraken.enabled = false;
+    kraken.rate_limit.requests_per_second = 0;
+
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(fields.len(), 6);
+    for field in [
+        "exchanges.binance.websocket.buffer_size",
+        "exchanges.binance.api_key",
+        "trading_pairs",
+        "server.rest.port",
+        "server.grpc.tls.cert_path",
+        "server.grpc.tls.key_path",
+    ] {
+        assert!(fields.contains(&field.to_string()), "missing {}", field);
+    }
+    match config.validate() {
+        Err(AggregatorError::Validation { field, message }) => {
+            assert_eq!(field, "config");
+            assert!(message.starts_with("6 problem(s)"));
+        }
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+
+    // A port moved onto another one by the environment is caught too
+    assert!(Config::default()
+        .with_overrides([("AGG_SERVER__WEBSOCKET__PORT", "8080")])
+        .is_err());
+}
+
+#[test]
+fn test_backpressure_config() {
+    // Server sections written before backpressure was configurable keep loading
+    let websocket: WebSocketServerConfig = serde_json::from_value(serde_json::json!({
+        "enabled": true,
+        "host": "0.0.0.0",
+        "port": 8081,
+        "max_connections": 10,
+    }))
+    .unwrap();
+    assert_eq!(
+        websocket.backpressure.policy,
+        BackpressurePolicy::DropOldest
+    );
+    assert_eq!(websocket.backpressure.buffer_size, 256);
+
+    let backpressure: BackpressureConfig =
+        serde_json::from_value(serde_json::json!({ "policy": "Disconnect" })).unwrap();
+    assert_eq!(backpressure.policy, BackpressurePolicy::Disconnect);
+    assert_eq!(backpressure.buffer_size, 256);
+
+    let mut config = Config::default();
+    config.server.grpc.backpressure.buffer_size = 0;
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(fields, vec!["server.grpc.backpressure.buffer_size"]);
+    config.server.grpc.enabled = false;
+    assert!(config.validate().is_ok());
+}
+
 #[test]
 fn test_server_config() {
     let grpc = GrpcConfig {
@@ -55,6 +311,7 @@ fn test_server_config() {
         host: "localhost".to_string(),
         port: 50051,
         tls: None,
+        backpressure: BackpressureConfig::default(),
     };
     let rest = RestConfig {
         enabled: true,
@@ -71,11 +328,13 @@ fn test_server_config() {
         host: "localhost".to_string(),
         port: 9000,
         max_connections: 100,
+        backpressure: BackpressureConfig::default(),
     };
     let server_cfg = ServerConfig {
         grpc,
         rest,
         websocket: ws,
+        auth: AuthConfig::default(),
     };
     assert!(server_cfg.grpc.enabled);
     assert!(server_cfg.rest.enabled);
@@ -102,7 +361,10 @@ fn test_config_struct() {
                 ping_interval: 1000,
                 max_reconnect_attempts: 1,
                 buffer_size: 256,
+                stale_timeout: 20000,
             },
+            proxy: None,
+            fees: FeeSchedule::default(),
         },
     );
     let config = Config {
@@ -121,6 +383,7 @@ fn test_config_struct() {
                 host: "localhost".to_string(),
                 port: 1,
                 tls: None,
+                backpressure: BackpressureConfig::default(),
             },
             rest: RestConfig {
                 enabled: true,
@@ -137,7 +400,12 @@ fn test_config_struct() {
                 host: "localhost".to_string(),
                 port: 3,
                 max_connections: 10,
+                backpressure: BackpressureConfig {
+                    policy: BackpressurePolicy::ConflateBySymbol,
+                    buffer_size: 64,
+                },
             },
+            auth: AuthConfig::default(),
         },
         logging: LoggingConfig {
             level: "info".to_string(),
@@ -146,6 +414,10 @@ fn test_config_struct() {
             file_path: None,
             max_file_size: 1024 * 1024,
             max_files: 5,
+            modules: std::collections::HashMap::from([(
+                "exchange_connectors::binance".to_string(),
+                "debug".to_string(),
+            )]),
         },
         metrics: MetricsConfig {
             enabled: true,
@@ -155,9 +427,329 @@ fn test_config_struct() {
                 port: 9000,
                 path: "/metrics".to_string(),
             },
+            history: MetricsHistoryConfig {
+                enabled: true,
+                resolution_secs: 300,
+                retention_secs: 7 * 24 * 60 * 60,
+            },
+            errors: ErrorTelemetryConfig { max_recent: 50 },
+        },
+        analysis: AnalysisConfig {
+            min_profit_percentage: 0.5,
+            min_volume: 0.1,
+            max_opportunity_age_ms: Some(2000),
+            net_of_fees: false,
+            circuit_breaker: CircuitBreakerConfig {
+                enabled: true,
+                max_move_percentage: 3.0,
+                window_secs: 5,
+                cooldown_secs: 60,
+                min_healthy_exchanges: 3,
+            },
+        },
+        storage: StorageConfig {
+            enabled: true,
+            backend: StorageBackend::Memory,
+            path: "aggregator.db".to_string(),
+            snapshot_interval_secs: 10,
+            max_opportunities: 100,
+        },
+        health: HealthConfig {
+            check_interval_secs: 10,
+            stale_after_secs: 30,
+            max_restarts: 3,
+            restart_backoff_secs: 30,
+        },
+        discovery: DiscoveryConfig {
+            enabled: true,
+            quote_assets: vec!["USDT".to_string(), "USDC".to_string()],
+            min_quote_volume: 1_000_000.0,
+            min_exchanges: 2,
+            max_pairs: Some(10),
+            refresh_interval_secs: 600,
+        },
+        fx: FxConfig {
+            enabled: true,
+            reference: "USD".to_string(),
+            equivalences: std::collections::HashMap::from([(
+                "USDT".to_string(),
+                "USD".to_string(),
+            )]),
+            rate_pairs: vec![TradingPair::new("USDT", "USD")],
+            max_rate_age_secs: 30,
         },
     };
     assert_eq!(config.trading_pairs[0].base, "BTC");
     assert_eq!(config.orderbook.max_depth, 5);
     assert!(config.server.grpc.enabled);
 }
+
+#[test]
+fn test_storage_config() {
+    let storage: StorageConfig =
+        serde_json::from_value(serde_json::json!({ "enabled": true })).unwrap();
+    assert_eq!(storage.backend, StorageBackend::Sqlite);
+    assert_eq!(storage.snapshot_interval_secs, 30);
+    assert!(!Config::default().storage.enabled);
+
+    let mut config = Config {
+        storage: StorageConfig {
+            enabled: true,
+            snapshot_interval_secs: 0,
+            path: String::new(),
+            ..StorageConfig::default()
+        },
+        ..Config::default()
+    };
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(
+        fields,
+        vec!["storage.snapshot_interval_secs", "storage.path"]
+    );
+    config.storage.backend = StorageBackend::Memory;
+    config.storage.snapshot_interval_secs = 5;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_logging_config_issues() {
+    assert!(LoggingConfig::default().issues().is_empty());
+
+    let mut logging = LoggingConfig {
+        level: "verbose".to_string(),
+        format: "text".to_string(),
+        output: "file".to_string(),
+        file_path: None,
+        max_file_size: 0,
+        ..LoggingConfig::default()
+    };
+    logging
+        .modules
+        .insert("exchange_connectors".to_string(), "loud".to_string());
+    let fields: Vec<String> = logging
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "logging.level",
+            "logging.modules.exchange_connectors",
+            "logging.format",
+            "logging.file_path",
+            "logging.max_file_size",
+        ]
+    );
+
+    let config = Config {
+        logging,
+        ..Config::default()
+    };
+    assert_eq!(
+        config
+            .issues()
+            .iter()
+            .filter(|issue| issue.field.starts_with("logging."))
+            .count(),
+        5
+    );
+}
+
+#[test]
+fn test_validate_discovery() {
+    let mut config = Config::default();
+    assert!(!config.discovery.enabled);
+    config.trading_pairs.clear();
+    config.discovery.enabled = true;
+    // Discovery finds the pairs, so none need to be configured
+    assert!(config.validate().is_ok());
+
+    config.discovery.min_exchanges = 0;
+    config.discovery.max_pairs = Some(0);
+    config.discovery.min_quote_volume = -1.0;
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "discovery.min_exchanges",
+            "discovery.max_pairs",
+            "discovery.min_quote_volume"
+        ]
+    );
+
+    config.discovery.enabled = false;
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(fields, vec!["trading_pairs"]);
+}
+
+#[test]
+fn test_validate_fx() {
+    let mut config = Config::default();
+    assert!(!config.fx.enabled);
+    assert_eq!(config.fx.reference, "USD");
+    assert_eq!(
+        config.fx.equivalences.get("USDT").map(String::as_str),
+        Some("USD")
+    );
+    assert!(config.validate().is_ok());
+
+    config.fx.reference = " ".to_string();
+    config.fx.rate_pairs = vec![TradingPair::new("USDT", "USD")];
+    config.fx.max_rate_age_secs = 0;
+    config
+        .fx
+        .equivalences
+        .insert("usd".to_string(), "USD".to_string());
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "fx.reference",
+            "fx.max_rate_age_secs",
+            "fx.equivalences.usd"
+        ]
+    );
+}
+
+#[test]
+fn test_validate_auth() {
+    let mut config = Config::default();
+    assert!(!config.server.auth.enabled);
+    config.server.auth.enabled = true;
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(fields, vec!["server.auth"]);
+
+    let auth: AuthConfig = serde_json::from_value(serde_json::json!({
+        "enabled": true,
+        "api_keys": [
+            {"name": "dashboard", "key": "k1", "channels": ["summary", "health"]},
+            {"name": "ops", "key": "k2", "role": "admin",
+             "rate_limit": {"requests_per_second": 0, "burst_size": 5}},
+            {"name": " ", "key": "k1", "channels": ["trades"]}
+        ],
+        "jwt": {"secret": ""}
+    }))
+    .unwrap();
+    assert_eq!(auth.api_keys[0].role, AuthRole::ReadOnly);
+    assert_eq!(auth.api_keys[1].role, AuthRole::Admin);
+    // Sections left out keep their defaults
+    assert_eq!(auth.rate_limit, AuthConfig::default().rate_limit);
+    config.server.auth = auth;
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "server.auth.api_keys[2].name",
+            "server.auth.api_keys[2].key",
+            "server.auth.api_keys[2].channels",
+            "server.auth.api_keys[1].rate_limit.requests_per_second",
+            "server.auth.jwt.secret"
+        ]
+    );
+}
+
+#[test]
+fn test_validate_circuit_breaker() {
+    let mut config = Config::default();
+    assert!(!config.analysis.circuit_breaker.enabled);
+    config.analysis.circuit_breaker.max_move_percentage = 0.0;
+    config.analysis.circuit_breaker.window_secs = 0;
+    // Only checked while the breaker is in use
+    assert!(config.validate().is_ok());
+
+    config.analysis.circuit_breaker.enabled = true;
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "analysis.circuit_breaker.max_move_percentage",
+            "analysis.circuit_breaker.window_secs"
+        ]
+    );
+}
+
+#[test]
+fn test_metrics_history_config() {
+    let metrics: MetricsConfig = serde_json::from_value(serde_json::json!({
+        "enabled": true,
+        "prometheus": serde_json::to_value(PrometheusConfig::default()).unwrap(),
+    }))
+    .unwrap();
+    assert_eq!(metrics.history, MetricsHistoryConfig::default());
+    assert_eq!(metrics.errors.max_recent, 100);
+    assert_eq!(metrics.history.retention_secs, 86_400);
+
+    let mut config = Config::default();
+    config.metrics.history.resolution_secs = 0;
+    config.metrics.history.retention_secs = 0;
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(fields, vec!["metrics.history.resolution_secs"]);
+    config.metrics.history.resolution_secs = 60;
+    let fields: Vec<String> = config
+        .issues()
+        .into_iter()
+        .map(|issue| issue.field)
+        .collect();
+    assert_eq!(fields, vec!["metrics.history.retention_secs"]);
+    config.metrics.history.enabled = false;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_auth_debug_redacts_secrets() {
+    let auth = AuthConfig {
+        enabled: true,
+        api_keys: vec![ApiKeyConfig {
+            name: "dashboard".to_string(),
+            key: "k3y-s3cr3t".to_string(),
+            role: AuthRole::Admin,
+            channels: vec!["summary".to_string()],
+            rate_limit: None,
+        }],
+        jwt: Some(JwtConfig {
+            secret: "jwt-s3cr3t".to_string(),
+            issuer: Some("aggre-gate".to_string()),
+            audience: None,
+        }),
+        ..AuthConfig::default()
+    };
+
+    let debug = format!("{:?}", auth);
+    assert!(!debug.contains("k3y-s3cr3t"));
+    assert!(!debug.contains("jwt-s3cr3t"));
+    assert!(debug.contains("dashboard"));
+    assert!(debug.contains("aggre-gate"));
+}
@@ -0,0 +1,95 @@
+// aggregator-core/tests/aggregator-core/config_watcher_tests.rs
+// Unit tests for config_watcher.rs
+
+use aggregator_core::config::*;
+use aggregator_core::config_watcher::*;
+use aggregator_core::types::*;
+use std::time::Duration;
+
+fn temp_config_path(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("aggregator-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join("config.json")
+}
+
+#[test]
+fn test_config_changed_between() {
+    let previous = Config::default();
+    assert!(ConfigChanged::between(&previous, previous.clone()).is_none());
+
+    let mut current = previous.clone();
+    current
+        .exchanges
+        .get_mut(&Exchange::Kraken)
+        .unwrap()
+        .enabled = false;
+    current
+        .exchanges
+        .get_mut(&Exchange::Binance)
+        .unwrap()
+        .fees
+        .taker_fee = 0.001;
+    current.trading_pairs.push(TradingPair::new("SOL", "USDT"));
+    current.analysis.min_profit_percentage = 0.5;
+    current.health.max_restarts = 5;
+    current.server.rest.port += 1;
+
+    let changed = ConfigChanged::between(&previous, current).unwrap();
+    assert_eq!(
+        changed.changes,
+        vec![
+            ConfigChange::ExchangeUpdated(Exchange::Binance),
+            ConfigChange::ExchangeDisabled(Exchange::Kraken),
+            ConfigChange::TradingPairAdded(TradingPair::new("SOL", "USDT")),
+            ConfigChange::AnalysisUpdated,
+            ConfigChange::HealthUpdated,
+            ConfigChange::RestartRequired("server".to_string()),
+        ]
+    );
+    assert_eq!(changed.config.analysis.min_profit_percentage, 0.5);
+}
+
+#[tokio::test]
+async fn test_reload_keeps_running_config_when_invalid() {
+    let path = temp_config_path("reload");
+    Config::default().to_file(path.to_str().unwrap()).unwrap();
+    let watcher = ConfigWatcher::load(&path).unwrap();
+    let mut changes = watcher.subscribe();
+    assert!(watcher.reload().await.unwrap().is_none());
+
+    let mut config = Config::default();
+    config.trading_pairs.truncate(1);
+    config.to_file(path.to_str().unwrap()).unwrap();
+    let changed = watcher.reload().await.unwrap().unwrap();
+    assert_eq!(changed.changes.len(), 2);
+    assert_eq!(changes.recv().await.unwrap().changes, changed.changes);
+
+    std::fs::write(&path, "{ not json").unwrap();
+    assert!(watcher.reload().await.is_err());
+    assert_eq!(watcher.current().await.trading_pairs.len(), 1);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_watcher_broadcasts_saved_changes() {
+    let path = temp_config_path("watch");
+    Config::default().to_file(path.to_str().unwrap()).unwrap();
+    let watcher = ConfigWatcher::load(&path).unwrap();
+    let mut changes = watcher.subscribe();
+    let handle = watcher.spawn().unwrap();
+
+    let mut config = Config::default();
+    config.analysis.min_volume = 1.0;
+    config.to_file(path.to_str().unwrap()).unwrap();
+
+    let changed = tokio::time::timeout(Duration::from_secs(5), changes.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(changed.changes, vec![ConfigChange::AnalysisUpdated]);
+    assert_eq!(watcher.current().await.analysis.min_volume, 1.0);
+
+    handle.abort();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,104 @@
+// aggregator-core/tests/aggregator-core/connector_tests.rs
+// Unit tests for connector.rs
+
+use aggregator_core::config::ExchangeConfig;
+use aggregator_core::connector::*;
+use aggregator_core::types::{Exchange, PriceLevelUpdate, TradingPair};
+use aggregator_core::{AggregatorError, Result};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+
+struct IdleConnector;
+
+#[async_trait]
+impl OrderBookService for IdleConnector {
+    async fn spawn_order_book_service(
+        &self,
+        _pairs: &[TradingPair],
+        _order_book_depth: usize,
+        _exchange_stream_buffer: usize,
+        _price_level_tx: mpsc::Sender<PriceLevelUpdate>,
+        _shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        Ok(Vec::new())
+    }
+}
+
+#[tokio::test]
+async fn test_register_and_unregister() {
+    let connectors = ConnectorRegistry::new();
+    assert!(connectors.exchanges().await.is_empty());
+
+    assert!(
+        connectors
+            .register("Acme", |_| Ok(Arc::new(IdleConnector)))
+            .await
+    );
+    // Names ignore case, so this replaces the first factory
+    assert!(
+        !connectors
+            .register("acme", |_| Ok(Arc::new(IdleConnector)))
+            .await
+    );
+    assert!(
+        connectors
+            .register("Kraken", |_| Ok(Arc::new(IdleConnector)))
+            .await
+    );
+
+    assert!(connectors.contains(&Exchange::named("ACME")).await);
+    assert!(connectors.contains(&Exchange::Kraken).await);
+    assert!(!connectors.contains(&Exchange::Binance).await);
+    assert_eq!(
+        connectors.exchanges().await,
+        vec![Exchange::Kraken, Exchange::Custom("acme".to_string())]
+    );
+
+    // Clones share the registry
+    let shared = connectors.clone();
+    assert!(shared.unregister(&Exchange::Kraken).await);
+    assert!(!connectors.unregister(&Exchange::Kraken).await);
+    assert_eq!(connectors.exchanges().await, vec![Exchange::named("acme")]);
+}
+
+#[tokio::test]
+async fn test_create_passes_exchange_config() {
+    let connectors = ConnectorRegistry::new();
+    connectors
+        .register("acme", |config: &ExchangeConfig| {
+            if config.api_key.is_none() {
+                return Err(AggregatorError::validation(
+                    "api_key",
+                    "acme needs an API key",
+                ));
+            }
+            Ok(Arc::new(IdleConnector))
+        })
+        .await;
+
+    let acme = Exchange::named("acme");
+    assert!(connectors
+        .create(&Exchange::Binance, &ExchangeConfig::default())
+        .await
+        .is_none());
+    assert!(connectors
+        .create(&acme, &ExchangeConfig::default())
+        .await
+        .unwrap()
+        .is_err());
+
+    let config = ExchangeConfig {
+        api_key: Some("key".to_string()),
+        ..ExchangeConfig::default()
+    };
+    let connector = connectors.create(&acme, &config).await.unwrap().unwrap();
+    let (price_level_tx, _price_level_rx) = mpsc::channel(1);
+    let (_shutdown_tx, shutdown_rx) = broadcast::channel(1);
+    let handles = connector
+        .spawn_order_book_service(&[], 10, 100, price_level_tx, shutdown_rx)
+        .await
+        .unwrap();
+    assert!(handles.is_empty());
+}
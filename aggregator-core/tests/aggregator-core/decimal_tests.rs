@@ -0,0 +1,71 @@
+// aggregator-core/tests/aggregator-core/decimal_tests.rs
+// Unit tests for decimal.rs
+
+#![cfg(feature = "decimal")]
+
+use aggregator_core::decimal::*;
+use aggregator_core::{Bid, Exchange};
+use chrono::Utc;
+use std::str::FromStr;
+
+/**
+ * @notice Tests that f64 values round-trip through the shortest decimal representation.
+ * @dev 0.1 + 0.2 drifts in f64 but must sum exactly once converted.
+ */
+#[test]
+fn test_to_decimal_is_exact_for_exchange_prices() {
+    let a = to_decimal(0.1, "price").unwrap();
+    let b = to_decimal(0.2, "price").unwrap();
+    assert_eq!(a + b, Decimal::from_str("0.3").unwrap());
+    assert_eq!(from_decimal(a + b), 0.3);
+}
+
+/**
+ * @notice Tests that non-finite values are rejected as validation errors.
+ */
+#[test]
+fn test_to_decimal_rejects_non_finite() {
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let err = to_decimal(value, "price").unwrap_err();
+        assert_eq!(err.category(), "validation");
+    }
+}
+
+/**
+ * @notice Tests parsing exchange strings, including scientific notation.
+ */
+#[test]
+fn test_parse_decimal() {
+    assert_eq!(
+        parse_decimal("30000.10", "price").unwrap(),
+        Decimal::from_str("30000.10").unwrap()
+    );
+    assert_eq!(
+        parse_decimal("1e-8", "quantity").unwrap(),
+        Decimal::from_str("0.00000001").unwrap()
+    );
+    assert!(parse_decimal("abc", "price").is_err());
+}
+
+/**
+ * @notice Tests conversions between bids and decimal levels.
+ */
+#[test]
+fn test_decimal_level_round_trip() {
+    let bid = Bid {
+        price: 101.25,
+        quantity: 0.5,
+        exchange: Exchange::Binance,
+        timestamp: Utc::now(),
+    };
+
+    let level = DecimalLevel::try_from(&bid).unwrap();
+    assert_eq!(level.price, Decimal::from_str("101.25").unwrap());
+    assert_eq!(level.to_bid(), bid);
+
+    let invalid = Bid {
+        price: f64::NAN,
+        ..bid
+    };
+    assert!(DecimalLevel::try_from(&invalid).is_err());
+}
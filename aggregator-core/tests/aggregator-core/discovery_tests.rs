@@ -0,0 +1,94 @@
+// aggregator-core/tests/aggregator-core/discovery_tests.rs
+// Unit tests for discovery.rs
+
+use aggregator_core::config::DiscoveryConfig;
+use aggregator_core::discovery::*;
+use aggregator_core::types::{Exchange, TradingPair};
+
+fn listing(exchange: Exchange, base: &str, quote: &str, volume: Option<f64>) -> MarketListing {
+    MarketListing {
+        exchange,
+        pair: TradingPair::new(base, quote),
+        symbol: format!("{}{}", base, quote),
+        quote_volume: volume,
+    }
+}
+
+fn listings() -> Vec<MarketListing> {
+    vec![
+        listing(Exchange::Binance, "BTC", "USDT", Some(900.0)),
+        listing(Exchange::Bybit, "BTC", "USDT", Some(300.0)),
+        listing(Exchange::Binance, "ETH", "USDT", Some(700.0)),
+        listing(Exchange::Binance, "SOL", "USDT", Some(50.0)),
+        listing(Exchange::Bybit, "SOL", "USDT", Some(40.0)),
+        listing(Exchange::Binance, "ETH", "BTC", Some(5000.0)),
+        listing(Exchange::Bybit, "DOGE", "USDT", None),
+    ]
+}
+
+#[test]
+fn test_select_pairs_by_quote_and_volume() {
+    let config = DiscoveryConfig {
+        enabled: true,
+        max_pairs: None,
+        ..DiscoveryConfig::default()
+    };
+    // Ranked by volume summed over exchanges; ETH/BTC has the wrong quote
+    assert_eq!(
+        select_pairs(&config, &listings()),
+        vec![
+            TradingPair::new("BTC", "USDT"),
+            TradingPair::new("ETH", "USDT"),
+            TradingPair::new("SOL", "USDT"),
+            TradingPair::new("DOGE", "USDT"),
+        ]
+    );
+
+    let config = DiscoveryConfig {
+        quote_assets: Vec::new(),
+        min_quote_volume: 100.0,
+        max_pairs: Some(2),
+        ..config
+    };
+    // Listings without a volume fall short of any minimum; Bybit's SOL volume is too low
+    assert_eq!(
+        select_pairs(&config, &listings()),
+        vec![
+            TradingPair::new("ETH", "BTC"),
+            TradingPair::new("BTC", "USDT")
+        ]
+    );
+}
+
+#[test]
+fn test_select_pairs_listed_on_several_exchanges() {
+    let config = DiscoveryConfig {
+        enabled: true,
+        min_exchanges: 2,
+        ..DiscoveryConfig::default()
+    };
+    assert_eq!(
+        select_pairs(&config, &listings()),
+        vec![
+            TradingPair::new("BTC", "USDT"),
+            TradingPair::new("SOL", "USDT")
+        ]
+    );
+
+    // Only exchanges trading enough count towards a pair
+    let config = DiscoveryConfig {
+        min_quote_volume: 45.0,
+        ..config
+    };
+    assert_eq!(
+        select_pairs(&config, &listings()),
+        vec![TradingPair::new("BTC", "USDT")]
+    );
+
+    // The same exchange listing a pair twice counts once
+    let duplicated = vec![
+        listing(Exchange::Binance, "BTC", "USDT", Some(100.0)),
+        listing(Exchange::Binance, "BTC", "USDT", Some(100.0)),
+    ];
+    assert!(select_pairs(&config, &duplicated).is_empty());
+}
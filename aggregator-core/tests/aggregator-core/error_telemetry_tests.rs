@@ -0,0 +1,85 @@
+// aggregator-core/tests/aggregator-core/error_telemetry_tests.rs
+// Unit tests for error_telemetry.rs
+
+use std::sync::Arc;
+
+use aggregator_core::config::ErrorTelemetryConfig;
+use aggregator_core::error::AggregatorError;
+use aggregator_core::error_telemetry::*;
+use aggregator_core::telemetry::MetricsRegistry;
+use aggregator_core::types::Exchange;
+use chrono::Utc;
+
+#[test]
+fn test_counts_outlive_the_recent_errors() {
+    let errors = ErrorTelemetry::new(ErrorTelemetryConfig { max_recent: 2 });
+    let now = Utc::now();
+    let binance = Exchange::Binance;
+    errors.record(
+        "price_level_processor",
+        Some(&binance),
+        &AggregatorError::parsing("depth", "missing bids"),
+        now,
+    );
+    errors.record(
+        "storage",
+        None,
+        &AggregatorError::database("save", "locked"),
+        now,
+    );
+    let record = errors.record(
+        "connector",
+        Some(&binance),
+        &AggregatorError::parsing("trade", "bad price"),
+        now,
+    );
+    assert_eq!(record.category, "parsing");
+    assert!(!record.recoverable);
+    assert_eq!(record.exchange, Some(binance));
+
+    assert_eq!(errors.count("parsing"), 2);
+    assert_eq!(errors.count("database"), 1);
+    assert_eq!(errors.count("network"), 0);
+    let report = errors.report(None, None);
+    assert_eq!(report.total, 3);
+    assert_eq!(report.by_category.len(), 2);
+    // Only the two latest are kept, newest first
+    let sources: Vec<&str> = report
+        .recent
+        .iter()
+        .map(|record| record.source.as_str())
+        .collect();
+    assert_eq!(sources, vec!["connector", "storage"]);
+
+    assert_eq!(errors.recent(Some("database"), None).len(), 1);
+    assert_eq!(errors.recent(None, Some(1)), vec![record]);
+    assert!(errors.recent(Some("timeout"), None).is_empty());
+}
+
+#[test]
+fn test_clones_share_counters_and_registry() {
+    let registry = Arc::new(MetricsRegistry::new());
+    let errors =
+        ErrorTelemetry::new(ErrorTelemetryConfig::default()).with_registry(registry.clone());
+    let clone = errors.clone();
+    clone.record(
+        "discovery",
+        None,
+        &AggregatorError::network("timed out"),
+        Utc::now(),
+    );
+    assert_eq!(errors.count("network"), 1);
+    assert!(registry
+        .render()
+        .contains("aggregator_errors_total{category=\"network\"} 1"));
+
+    let none_kept = ErrorTelemetry::new(ErrorTelemetryConfig { max_recent: 0 });
+    none_kept.record(
+        "config",
+        None,
+        &AggregatorError::network("down"),
+        Utc::now(),
+    );
+    assert_eq!(none_kept.count("network"), 1);
+    assert!(none_kept.recent(None, None).is_empty());
+}
@@ -0,0 +1,72 @@
+// aggregator-core/tests/aggregator-core/event_bus_tests.rs
+// Unit tests for event_bus.rs
+
+use aggregator_core::event_bus::*;
+use aggregator_core::types::*;
+use chrono::Utc;
+use tokio::sync::broadcast::error::TryRecvError;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Heartbeat(u32);
+
+impl Event for Heartbeat {
+    const TOPIC: &'static str = "heartbeat";
+    const CAPACITY: usize = 2;
+}
+
+fn summary(symbol: &str) -> Summary {
+    Summary {
+        symbol: symbol.to_string(),
+        spread: 0.0,
+        bids: vec![],
+        asks: vec![],
+        timestamp: Utc::now(),
+        market_type: None,
+    }
+}
+
+#[test]
+fn test_topics_are_separate() {
+    let bus = EventBus::new();
+    let mut summaries = bus.subscribe::<Summary>();
+    let mut heartbeats = bus.subscribe::<Heartbeat>();
+
+    assert_eq!(bus.publish(summary("BTCUSDT")), 1);
+    assert_eq!(bus.publish(Heartbeat(1)), 1);
+
+    assert_eq!(summaries.try_recv().unwrap().symbol, "BTCUSDT");
+    assert!(matches!(summaries.try_recv(), Err(TryRecvError::Empty)));
+    assert_eq!(heartbeats.try_recv().unwrap(), Heartbeat(1));
+    assert_eq!(bus.topics(), vec!["heartbeat", "summaries"]);
+}
+
+#[test]
+fn test_publish_without_subscribers() {
+    let bus = EventBus::new();
+    assert_eq!(bus.publish(Heartbeat(1)), 0);
+    assert_eq!(bus.subscriber_count::<Heartbeat>(), 0);
+
+    // Events published before subscribing are not replayed
+    let mut heartbeats = bus.subscribe::<Heartbeat>();
+    assert!(matches!(heartbeats.try_recv(), Err(TryRecvError::Empty)));
+
+    // Senders taken from the bus publish on the same topic
+    let sender = bus.sender::<Heartbeat>();
+    sender.send(Heartbeat(2)).unwrap();
+    assert_eq!(heartbeats.try_recv().unwrap(), Heartbeat(2));
+    assert_eq!(bus.subscriber_count::<Heartbeat>(), 1);
+}
+
+#[test]
+fn test_topic_capacity() {
+    let bus = EventBus::new();
+    let mut heartbeats = bus.subscribe::<Heartbeat>();
+    for beat in 0..3 {
+        bus.publish(Heartbeat(beat));
+    }
+    assert!(matches!(
+        heartbeats.try_recv(),
+        Err(TryRecvError::Lagged(1))
+    ));
+    assert_eq!(heartbeats.try_recv().unwrap(), Heartbeat(1));
+}
@@ -0,0 +1,62 @@
+// aggregator-core/tests/aggregator-core/fill_tests.rs
+// Unit tests for fill.rs
+
+use aggregator_core::{Exchange, FillEstimate, PriceLevel, Summary, TradeSide};
+use chrono::Utc;
+
+fn level(price: f64, quantity: f64) -> PriceLevel {
+    PriceLevel {
+        price,
+        quantity,
+        exchange: Exchange::Binance,
+        timestamp: Utc::now(),
+    }
+}
+
+fn summary() -> Summary {
+    Summary {
+        symbol: "BTCUSDT".to_string(),
+        spread: 1.0,
+        bids: vec![level(100.0, 1.0), level(99.0, 2.0)],
+        asks: vec![level(101.0, 1.0), level(102.0, 1.0), level(104.0, 2.0)],
+        timestamp: Utc::now(),
+        market_type: None,
+    }
+}
+
+/**
+ * @notice Tests walking the asks for a buy across several levels.
+ * @dev 1 @ 101 + 1 @ 102 + 0.5 @ 104 averages 102.
+ */
+#[test]
+fn test_cost_to_fill_buy() {
+    let fill = summary().cost_to_fill(TradeSide::Buy, 2.5).unwrap();
+    assert!(fill.is_complete());
+    assert_eq!(fill.total_cost, 255.0);
+    assert_eq!(fill.average_price, 102.0);
+    assert_eq!(fill.worst_price, 104.0);
+    assert_eq!(fill.levels_consumed, 3);
+    assert!((fill.slippage_percentage(101.0) - 100.0 / 101.0).abs() < 1e-9);
+}
+
+/**
+ * @notice Tests a sell larger than the visible bids.
+ */
+#[test]
+fn test_cost_to_fill_sell_incomplete() {
+    let fill = summary().cost_to_fill(TradeSide::Sell, 5.0).unwrap();
+    assert!(!fill.is_complete());
+    assert_eq!(fill.filled_quantity, 3.0);
+    assert_eq!(fill.total_cost, 298.0);
+    assert_eq!(fill.worst_price, 99.0);
+}
+
+/**
+ * @notice Tests the cases that have no estimate.
+ */
+#[test]
+fn test_cost_to_fill_without_estimate() {
+    assert!(summary().cost_to_fill(TradeSide::Buy, 0.0).is_none());
+    assert!(summary().cost_to_fill(TradeSide::Buy, f64::NAN).is_none());
+    assert!(FillEstimate::from_levels(TradeSide::Sell, Vec::new(), 1.0).is_none());
+}
@@ -0,0 +1,79 @@
+// aggregator-core/tests/aggregator-core/fx_tests.rs
+// Unit tests for fx.rs
+
+use aggregator_core::config::FxConfig;
+use aggregator_core::fx::*;
+use aggregator_core::types::{Exchange, PriceLevel, Summary, TradingPair};
+use chrono::{Duration, Utc};
+
+fn level(price: f64) -> PriceLevel {
+    PriceLevel {
+        price,
+        quantity: 1.0,
+        exchange: Exchange::Kraken,
+        timestamp: Utc::now(),
+    }
+}
+
+#[test]
+fn test_rates_through_pegs_and_live_rates() {
+    let fx = FxRates::new(FxConfig::default());
+    let now = Utc::now();
+    assert_eq!(fx.reference(), "USD");
+    assert_eq!(fx.rate("usdc", "usd", now), Some(1.0));
+    assert!(fx.equivalent("USDT", "USDC"));
+    assert!(!fx.equivalent("USDT", "EUR"));
+    assert_eq!(fx.rate("BTC", "USD", now), None);
+
+    // A live rate replaces the peg, in either direction
+    fx.set_rate("USDT", "USD", 0.5, now);
+    assert_eq!(fx.rate("USDT", "USD", now), Some(0.5));
+    assert_eq!(fx.rate("USD", "USDT", now), Some(2.0));
+    assert_eq!(fx.convert(10.0, "USDT", "USDC", now), Some(5.0));
+    assert_eq!(fx.convert(10.0, "USDC", "USDT", now), Some(20.0));
+
+    // Non-positive rates are ignored
+    fx.set_rate("USDT", "USD", 0.0, now);
+    assert_eq!(fx.rate("USDT", "USD", now), Some(0.5));
+
+    // Once stale, the peg applies again
+    let later = now + Duration::seconds(61);
+    assert_eq!(fx.rate("USDT", "USD", later), Some(1.0));
+}
+
+#[test]
+fn test_observe_rate_pairs_and_configure() {
+    let fx = FxRates::new(FxConfig {
+        rate_pairs: vec![TradingPair::new("EUR", "USD")],
+        ..FxConfig::default()
+    });
+    let now = Utc::now();
+    let summary = Summary {
+        symbol: "EURUSD".to_string(),
+        spread: 0.02,
+        bids: vec![level(1.09)],
+        asks: vec![level(1.11)],
+        timestamp: now,
+        market_type: None,
+    };
+    assert!(!fx.observe(&TradingPair::new("BTC", "USD"), &summary));
+    assert!(fx.observe(&TradingPair::new("EUR", "USD"), &summary));
+    let rate = fx.rate("EUR", "USDT", now).unwrap();
+    assert!((rate - 1.10).abs() < 1e-9);
+
+    // A one-sided book has no mid price
+    let one_sided = Summary {
+        asks: Vec::new(),
+        ..summary
+    };
+    assert!(!fx.observe(&TradingPair::new("EUR", "USD"), &one_sided));
+
+    // Reconfiguring keeps the live rates
+    fx.configure(FxConfig {
+        reference: "EUR".to_string(),
+        ..FxConfig::default()
+    });
+    assert_eq!(fx.reference(), "EUR");
+    let rate = fx.rate("USDT", "EUR", now).unwrap();
+    assert!((rate - 1.0 / 1.10).abs() < 1e-9);
+}
@@ -0,0 +1,102 @@
+// aggregator-core/tests/aggregator-core/instrument_tests.rs
+// Unit tests for instrument.rs
+
+use aggregator_core::instrument::*;
+use aggregator_core::types::{Exchange, TradingPair};
+
+fn btc_usdt() -> InstrumentInfo {
+    InstrumentInfo {
+        exchange: Exchange::Binance,
+        pair: TradingPair::new("BTC", "USDT"),
+        symbol: "BTCUSDT".to_string(),
+        tick_size: 0.01,
+        lot_size: 0.00001,
+        min_quantity: 0.00001,
+        min_notional: Some(5.0),
+    }
+}
+
+/**
+ * @notice Tests rounding prices and quantities to the instrument's steps.
+ * @dev Quantities round down so an order never exceeds the requested size.
+ */
+#[test]
+fn test_instrument_rounding() {
+    let info = btc_usdt();
+    assert_eq!(info.price_precision(), 2);
+    assert_eq!(info.quantity_precision(), 5);
+    assert_eq!(info.round_price(50000.126), 50000.13);
+    assert_eq!(info.round_price(50000.124), 50000.12);
+    assert_eq!(info.round_quantity(0.123459), 0.12345);
+    // Values already on a step are left alone despite floating point error
+    assert_eq!(info.round_quantity(0.3), 0.3);
+
+    let coarse = InstrumentInfo {
+        tick_size: 0.5,
+        ..btc_usdt()
+    };
+    assert_eq!(coarse.price_precision(), 1);
+    assert_eq!(coarse.round_price(100.74), 100.5);
+    assert_eq!(coarse.round_price(100.76), 101.0);
+
+    let quarter = InstrumentInfo {
+        tick_size: 0.25,
+        ..btc_usdt()
+    };
+    assert_eq!(quarter.price_precision(), 2);
+    assert_eq!(quarter.round_price(100.26), 100.25);
+}
+
+/**
+ * @notice Tests rounding a price to a tick size without instrument metadata.
+ * @dev Floating point noise is trimmed, and a non-positive tick leaves the price alone.
+ */
+#[test]
+fn test_round_to_tick() {
+    assert_eq!(round_to_tick(100.10000000000001, 0.1), 100.1);
+    assert_eq!(round_to_tick(0.1 + 0.2, 0.1), 0.3);
+    assert_eq!(round_to_tick(100.26, 0.25), 100.25);
+    assert_eq!(round_to_tick(100.26, 0.0), 100.26);
+}
+
+/**
+ * @notice Tests order validation against tick, lot, minimum size and minimum notional.
+ * @dev Each rule is broken in turn from a valid order.
+ */
+#[test]
+fn test_instrument_validate_order() {
+    let info = btc_usdt();
+    assert!(info.validate_order(50000.01, 0.001).is_ok());
+    assert!(info.validate_order(50000.015, 0.001).is_err());
+    assert!(info.validate_order(50000.01, 0.0010001).is_err());
+    assert!(info.validate_order(50000.01, 0.0).is_err());
+    assert!(info.validate_order(100.0, 0.01).is_err());
+    assert!(info.validate_order(-1.0, 0.001).is_err());
+}
+
+/**
+ * @notice Tests that registry clones share instruments.
+ * @dev Connectors fill one handle while analysis reads another.
+ */
+#[test]
+fn test_instrument_registry() {
+    let registry = InstrumentRegistry::new();
+    let reader = registry.clone();
+    assert!(reader.is_empty());
+
+    registry.insert(btc_usdt());
+    registry.extend([InstrumentInfo {
+        exchange: Exchange::Kraken,
+        symbol: "XBT/USDT".to_string(),
+        tick_size: 0.1,
+        ..btc_usdt()
+    }]);
+
+    let pair = TradingPair::new("BTC", "USDT");
+    assert_eq!(reader.len(), 2);
+    assert_eq!(reader.get(&Exchange::Binance, &pair), Some(btc_usdt()));
+    assert_eq!(reader.for_exchange(&Exchange::Kraken).len(), 1);
+    assert_eq!(reader.round_price(&Exchange::Kraken, &pair, 100.06), 100.1);
+    // Unknown instruments are passed through
+    assert_eq!(reader.round_price(&Exchange::Bybit, &pair, 100.06), 100.06);
+}
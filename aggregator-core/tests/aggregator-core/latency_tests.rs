@@ -0,0 +1,40 @@
+// aggregator-core/tests/aggregator-core/latency_tests.rs
+// Unit tests for latency.rs
+
+use aggregator_core::latency::*;
+
+/**
+ * @notice Tests percentile and mean calculation over the window.
+ * @dev Uses 1..=100 so the nearest-rank percentiles equal the percentile itself.
+ */
+#[test]
+fn test_latency_percentiles() {
+    let mut tracker = LatencyTracker::new(100);
+    assert!(tracker.is_empty());
+    assert_eq!(tracker.percentile(99.0), 0.0);
+
+    for latency in (1..=100).rev() {
+        tracker.record(latency as f64);
+    }
+    assert_eq!(tracker.len(), 100);
+    assert_eq!(tracker.percentile(50.0), 50.0);
+    assert_eq!(tracker.percentile(95.0), 95.0);
+    assert_eq!(tracker.percentile(99.0), 99.0);
+    assert_eq!(tracker.percentile(100.0), 100.0);
+    assert_eq!(tracker.mean(), 50.5);
+}
+
+/**
+ * @notice Tests that the window only keeps the most recent samples.
+ * @dev Old samples are evicted once the capacity is reached.
+ */
+#[test]
+fn test_latency_window_rolls() {
+    let mut tracker = LatencyTracker::new(3);
+    for latency in [100.0, 1.0, 2.0, 3.0] {
+        tracker.record(latency);
+    }
+    assert_eq!(tracker.len(), 3);
+    assert_eq!(tracker.percentile(100.0), 3.0);
+    assert_eq!(tracker.mean(), 2.0);
+}
@@ -0,0 +1,85 @@
+// aggregator-core/tests/aggregator-core/logging_tests.rs
+// Unit tests for logging.rs
+
+use aggregator_core::config::*;
+use aggregator_core::logging::*;
+use aggregator_core::AggregatorError;
+use std::io::Write;
+
+fn temp_dir() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("aggregator-logs-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_filter_directives() {
+    let mut config = LoggingConfig {
+        level: "INFO".to_string(),
+        ..LoggingConfig::default()
+    };
+    assert_eq!(filter_directives(&config), "info");
+
+    config.modules.insert(
+        "exchange_connectors::binance".to_string(),
+        "debug".to_string(),
+    );
+    config
+        .modules
+        .insert("aggregator_core".to_string(), "Warn".to_string());
+    assert_eq!(
+        filter_directives(&config),
+        "info,aggregator_core=warn,exchange_connectors::binance=debug"
+    );
+}
+
+#[test]
+fn test_rotating_file_writer() {
+    let dir = temp_dir();
+    let path = dir.join("nested").join("aggregator.log");
+    let mut writer = RotatingFileWriter::open(&path, 10, 3).unwrap();
+
+    for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+        writer.write_all(line.as_bytes()).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let read = |name: &str| std::fs::read_to_string(dir.join("nested").join(name)).unwrap();
+    assert_eq!(read("aggregator.log"), "fourth\n");
+    assert_eq!(read("aggregator.log.1"), "third\n");
+    assert_eq!(read("aggregator.log.2"), "second\n");
+    // Only three files are kept, so the oldest is gone
+    assert!(!dir.join("nested").join("aggregator.log.3").exists());
+
+    // Reopening appends to the live file and counts its size
+    let mut reopened = RotatingFileWriter::open(&path, 10, 3).unwrap();
+    reopened.write_all(b"x\n").unwrap();
+    assert_eq!(read("aggregator.log"), "fourth\nx\n");
+    reopened.write_all(b"fifth\n").unwrap();
+    assert_eq!(read("aggregator.log"), "fifth\n");
+    assert_eq!(read("aggregator.log.1"), "fourth\nx\n");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_init_logging_rejects_invalid_config() {
+    let config = LoggingConfig {
+        format: "xml".to_string(),
+        ..LoggingConfig::default()
+    };
+    match init_logging(&config) {
+        Err(AggregatorError::Validation { field, .. }) => assert_eq!(field, "logging.format"),
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+
+    let config = LoggingConfig {
+        output: "file".to_string(),
+        file_path: None,
+        ..LoggingConfig::default()
+    };
+    assert!(matches!(
+        init_logging(&config),
+        Err(AggregatorError::Validation { .. })
+    ));
+}
@@ -0,0 +1,116 @@
+// aggregator-core/tests/aggregator-core/metrics_history_tests.rs
+// Unit tests for metrics_history.rs
+
+use aggregator_core::config::MetricsHistoryConfig;
+use aggregator_core::metrics_history::*;
+use aggregator_core::types::Exchange;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()
+}
+
+fn history(retention_secs: u64) -> MetricsHistory {
+    MetricsHistory::new(MetricsHistoryConfig {
+        enabled: true,
+        resolution_secs: 60,
+        retention_secs,
+    })
+}
+
+#[test]
+fn test_intervals_close_when_the_next_starts() {
+    let history = history(3600);
+    let start = start();
+    let binance = Exchange::Binance;
+    history.record_update(&binance, "BTCUSDT", start, Some(2.0));
+    history.record_update(&binance, "BTCUSDT", start + Duration::seconds(59), None);
+    history.record_error(&binance, "BTCUSDT", start + Duration::seconds(30));
+    history.record_update(&binance, "ETHUSDT", start, Some(1.0));
+    assert!(history.samples(&binance, None, None).is_empty());
+
+    // The next interval closes BTCUSDT's first one; ETHUSDT's waits for a flush
+    history.record_update(
+        &binance,
+        "BTCUSDT",
+        start + Duration::seconds(90),
+        Some(8.0),
+    );
+    let samples = history.samples(&binance, Some("BTCUSDT"), None);
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].timestamp, start);
+    assert_eq!(samples[0].updates, 2);
+    assert_eq!(samples[0].errors, 1);
+    assert_eq!(samples[0].latency_ms, 2.0);
+    assert_eq!(samples[0].max_latency_ms, 2.0);
+    assert!((samples[0].updates_per_second - 2.0 / 60.0).abs() < 1e-12);
+
+    history.flush(start + Duration::seconds(120));
+    let samples = history.samples(&binance, None, None);
+    let feeds: Vec<(&str, DateTime<Utc>)> = samples
+        .iter()
+        .map(|sample| (sample.symbol.as_str(), sample.timestamp))
+        .collect();
+    assert_eq!(
+        feeds,
+        vec![
+            ("BTCUSDT", start),
+            ("ETHUSDT", start),
+            ("BTCUSDT", start + Duration::seconds(60)),
+        ]
+    );
+    assert_eq!(
+        history
+            .samples(&binance, None, Some(start + Duration::seconds(60)))
+            .len(),
+        1
+    );
+    assert!(history.samples(&Exchange::Bybit, None, None).is_empty());
+    assert_eq!(history.feeds().len(), 2);
+}
+
+#[test]
+fn test_retention_restore_and_completed_samples() {
+    let history = history(180);
+    let start = start();
+    let kraken = Exchange::Kraken;
+    // Nothing is collected for persisting before the first call
+    history.record_update(&kraken, "XBTUSD", start, None);
+    history.flush(start + Duration::minutes(1));
+    assert!(history.take_completed().is_empty());
+
+    for minute in 1..5 {
+        history.record_update(&kraken, "XBTUSD", start + Duration::minutes(minute), None);
+    }
+    history.flush(start + Duration::minutes(5));
+    let completed = history.take_completed();
+    assert_eq!(completed.len(), 4);
+    assert!(history.take_completed().is_empty());
+
+    // Three minutes are kept, counted back from the newest sample
+    let kept = history.samples(&kraken, None, None);
+    assert_eq!(kept.len(), 3);
+    assert_eq!(kept[0].timestamp, start + Duration::minutes(2));
+
+    // Restored samples fill gaps but do not replace held intervals or outlive the retention
+    let restored = history.samples(&kraken, None, None)[0].clone();
+    let mut replaced = restored.clone();
+    replaced.updates = 100;
+    let mut old = restored.clone();
+    old.timestamp = start - Duration::hours(1);
+    history.restore([replaced, old]);
+    let kept = history.samples(&kraken, None, None);
+    assert_eq!(kept.len(), 3);
+    assert_eq!(kept[0], restored);
+}
+
+#[test]
+fn test_disabled_history_records_nothing() {
+    let history = MetricsHistory::new(MetricsHistoryConfig {
+        enabled: false,
+        ..MetricsHistoryConfig::default()
+    });
+    history.record_update(&Exchange::Binance, "BTCUSDT", start(), None);
+    history.flush(start() + Duration::hours(1));
+    assert!(history.feeds().is_empty());
+}
@@ -0,0 +1,95 @@
+// aggregator-core/tests/aggregator-core/replay_tests.rs
+// Unit tests for replay.rs
+
+use aggregator_core::replay::*;
+use aggregator_core::types::{Bid, Exchange, PriceLevelUpdate};
+use chrono::{Duration, Utc};
+use std::io::Write;
+
+fn update(symbol: &str, price: f64) -> PriceLevelUpdate {
+    PriceLevelUpdate {
+        id: uuid::Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        exchange: Exchange::Binance,
+        bids: vec![Bid {
+            price,
+            quantity: 1.0,
+            exchange: Exchange::Binance,
+            timestamp: Utc::now(),
+        }],
+        asks: vec![],
+        timestamp: Utc::now(),
+        funding: None,
+        event_time: None,
+        market_type: None,
+    }
+}
+
+#[test]
+fn test_pace_delay() {
+    let start = Utc::now();
+    let later = start + Duration::seconds(2);
+
+    assert_eq!(ReplayPace::Unpaced.delay(start, later), None);
+    assert_eq!(
+        ReplayPace::REAL_TIME.delay(start, later),
+        Some(std::time::Duration::from_secs(2))
+    );
+    assert_eq!(
+        ReplayPace::Recorded { speed: 10.0 }.delay(start, later),
+        Some(std::time::Duration::from_millis(200))
+    );
+    // Out of order records and non-positive speeds are not waited for
+    assert_eq!(ReplayPace::REAL_TIME.delay(later, start), None);
+    assert_eq!(
+        ReplayPace::Recorded { speed: 0.0 }.delay(start, later),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_recorded_updates_in_order() {
+    let mut source = RecordedUpdates::from(vec![update("BTCUSDT", 100.0), update("ETHUSDT", 10.0)]);
+    assert_eq!(source.len(), 2);
+
+    let first = source.next_update().await.unwrap().unwrap();
+    assert_eq!(first.symbol, "BTCUSDT");
+    let second = source.next_update().await.unwrap().unwrap();
+    assert_eq!(second.symbol, "ETHUSDT");
+    assert!(source.next_update().await.is_none());
+    assert!(source.is_empty());
+}
+
+#[tokio::test]
+async fn test_update_log_skips_bad_records() {
+    let path =
+        std::env::temp_dir().join(format!("aggre-gate-replay-{}.jsonl", uuid::Uuid::new_v4()));
+    {
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(
+            file,
+            "{}",
+            serde_json::to_string(&update("BTCUSDT", 100.0)).unwrap()
+        )
+        .unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "not json").unwrap();
+        writeln!(
+            file,
+            "{}",
+            serde_json::to_string(&update("BTCUSDT", 101.0)).unwrap()
+        )
+        .unwrap();
+    }
+
+    let mut log = UpdateLog::open(&path).unwrap();
+    let first = log.next_update().await.unwrap().unwrap();
+    assert_eq!(first.bids[0].price, 100.0);
+    assert!(log.next_update().await.unwrap().is_err());
+    let second = log.next_update().await.unwrap().unwrap();
+    assert_eq!(second.bids[0].price, 101.0);
+    assert!(log.next_update().await.is_none());
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(UpdateLog::open(&path).is_err());
+}
@@ -0,0 +1,76 @@
+// aggregator-core/tests/aggregator-core/shutdown_tests.rs
+// Unit tests for shutdown.rs
+
+use aggregator_core::config::Config;
+use aggregator_core::shutdown::*;
+use aggregator_core::{Aggregator, AggregatorError, Result};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_shutdown_reports_components_that_fail_to_stop() {
+    let aggregator = Aggregator::new(Config::default());
+    let stopping = aggregator.stopping(ShutdownStage::Processing);
+    aggregator.track_task(
+        ShutdownStage::Processing,
+        "worker",
+        tokio::spawn(async move {
+            stopping.await;
+            Ok(())
+        }),
+    );
+    aggregator.track_task(
+        ShutdownStage::Persistence,
+        "writer",
+        tokio::spawn(async { Err(AggregatorError::database("flush", "disk full")) }),
+    );
+    aggregator.track_task(
+        ShutdownStage::Servers,
+        "stuck server",
+        tokio::spawn(std::future::pending::<Result<()>>()),
+    );
+
+    let report = aggregator.shutdown(Duration::from_millis(100)).await;
+    assert!(!report.is_clean());
+    assert_eq!(report.stopped, vec!["worker".to_string()]);
+    assert_eq!(report.failed.len(), 2);
+    assert_eq!(report.failed[0].component, "writer");
+    assert_eq!(report.failed[0].stage, ShutdownStage::Persistence);
+    assert!(matches!(report.failed[0].error, StopError::Failed(_)));
+    assert_eq!(
+        report.failed[1],
+        ShutdownFailure {
+            component: "stuck server".to_string(),
+            stage: ShutdownStage::Servers,
+            error: StopError::TimedOut,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_stop_signals_every_stage() {
+    let aggregator = Aggregator::new(Config::default());
+    let connectors = aggregator.stopping(ShutdownStage::Connectors);
+    let servers = aggregator.stopping(ShutdownStage::Servers);
+    let mut shutdown_rx = aggregator.subscribe_shutdown();
+
+    aggregator.stop().await.unwrap();
+    tokio::time::timeout(Duration::from_millis(100), async {
+        connectors.await;
+        servers.await;
+    })
+    .await
+    .unwrap();
+    shutdown_rx.try_recv().unwrap();
+}
+
+#[test]
+fn test_stages_in_shutdown_order() {
+    let mut sorted = ShutdownStage::ALL;
+    sorted.sort();
+    assert_eq!(sorted, ShutdownStage::ALL);
+    assert_eq!(ShutdownStage::Persistence.to_string(), "persistence");
+    assert_eq!(
+        StopError::TimedOut.to_string(),
+        "did not stop before the deadline"
+    );
+}
@@ -0,0 +1,229 @@
+// aggregator-core/tests/aggregator-core/storage_tests.rs
+// Unit tests for storage.rs
+
+use aggregator_core::config::*;
+use aggregator_core::metrics_history::MetricsSample;
+use aggregator_core::storage::*;
+use aggregator_core::types::*;
+use chrono::{DateTime, Duration, Utc};
+
+fn summary(symbol: &str, spread: f64) -> Summary {
+    Summary {
+        symbol: symbol.to_string(),
+        spread,
+        bids: vec![PriceLevel {
+            price: 100.0,
+            quantity: 1.0,
+            exchange: Exchange::Binance,
+            timestamp: Utc::now(),
+        }],
+        asks: vec![],
+        timestamp: Utc::now(),
+        market_type: None,
+    }
+}
+
+fn opportunity(symbol: &str, age_ms: i64) -> ArbitrageOpportunity {
+    ArbitrageOpportunity {
+        buy_exchange: Exchange::Binance,
+        sell_exchange: Exchange::Bybit,
+        symbol: symbol.to_string(),
+        buy_price: 100.0,
+        sell_price: 101.0,
+        profit_percentage: 1.0,
+        volume: 1.0,
+        average_buy_price: 100.0,
+        average_sell_price: 101.0,
+        time_to_live_ms: 1000.0,
+        confidence: 1.0,
+        timestamp: Utc::now() - Duration::milliseconds(age_ms),
+    }
+}
+
+fn metrics(exchange: Exchange, error_count: u64) -> Metrics {
+    Metrics {
+        exchange,
+        symbol: "BTCUSDT".to_string(),
+        updates_per_second: 10.0,
+        latency_ms: 5.0,
+        latency_p50_ms: 4.0,
+        latency_p95_ms: 8.0,
+        latency_p99_ms: 9.0,
+        error_count,
+        last_update: Utc::now(),
+    }
+}
+
+fn sample(exchange: Exchange, timestamp: DateTime<Utc>, updates: u64) -> MetricsSample {
+    MetricsSample {
+        exchange,
+        symbol: "BTCUSDT".to_string(),
+        timestamp,
+        updates,
+        updates_per_second: updates as f64 / 60.0,
+        latency_ms: 5.0,
+        max_latency_ms: 8.0,
+        errors: 0,
+    }
+}
+
+/// Checks the behaviour every backend must share
+async fn exercise(storage: &dyn Storage) {
+    // Saving again under the same key replaces the entry
+    storage
+        .save_summaries(&[summary("BTCUSDT", 1.0), summary("ETHUSDT", 2.0)])
+        .await
+        .unwrap();
+    storage
+        .save_summaries(&[summary("BTCUSDT", 3.0)])
+        .await
+        .unwrap();
+    let mut summaries = storage.load_summaries().await.unwrap();
+    summaries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].spread, 3.0);
+    assert_eq!(summaries[0].bids[0].price, 100.0);
+
+    storage
+        .save_metrics(&[metrics(Exchange::Binance, 1), metrics(Exchange::Bybit, 2)])
+        .await
+        .unwrap();
+    storage
+        .save_metrics(&[metrics(Exchange::Binance, 5)])
+        .await
+        .unwrap();
+    let mut restored = storage.load_metrics().await.unwrap();
+    restored.sort_by_key(|metric| metric.error_count);
+    assert_eq!(restored.len(), 2);
+    assert_eq!(restored[0].exchange, Exchange::Bybit);
+    assert_eq!(restored[1].error_count, 5);
+
+    // Oldest first from the given time, and saving an interval again replaces it
+    let start = Utc::now() - Duration::minutes(10);
+    storage
+        .save_metrics_history(&[
+            sample(Exchange::Binance, start, 1),
+            sample(Exchange::Bybit, start, 2),
+            sample(Exchange::Binance, start + Duration::minutes(1), 3),
+        ])
+        .await
+        .unwrap();
+    storage
+        .save_metrics_history(&[sample(Exchange::Binance, start, 4)])
+        .await
+        .unwrap();
+    let history = storage.load_metrics_history(start).await.unwrap();
+    let updates: Vec<u64> = history.iter().map(|sample| sample.updates).collect();
+    assert_eq!(updates, vec![4, 2, 3]);
+    assert_eq!(
+        storage
+            .load_metrics_history(start + Duration::seconds(30))
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    storage
+        .prune_metrics_history(start + Duration::seconds(30))
+        .await
+        .unwrap();
+    let history = storage.load_metrics_history(start).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].updates, 3);
+
+    let pair = TradingPair::new("BTC", "USDT");
+    let snapshot = BookSnapshot {
+        pair: pair.clone(),
+        exchange: Exchange::Binance,
+        summary: summary("BTCUSDT", 1.0),
+    };
+    storage
+        .save_book_snapshots(std::slice::from_ref(&snapshot))
+        .await
+        .unwrap();
+    let books = storage.load_book_snapshots().await.unwrap();
+    assert_eq!(books.len(), 1);
+    assert_eq!(books[0].pair, pair);
+    assert_eq!(books[0].summary.bids.len(), 1);
+
+    // Newest first, and pruning keeps the newest
+    storage
+        .save_opportunities(&[opportunity("OLD", 300), opportunity("MID", 200)])
+        .await
+        .unwrap();
+    storage
+        .save_opportunities(&[opportunity("NEW", 100)])
+        .await
+        .unwrap();
+    let symbols = |opportunities: Vec<ArbitrageOpportunity>| -> Vec<String> {
+        opportunities.into_iter().map(|o| o.symbol).collect()
+    };
+    assert_eq!(
+        symbols(storage.load_opportunities(2).await.unwrap()),
+        vec!["NEW", "MID"]
+    );
+    storage.prune_opportunities(2).await.unwrap();
+    assert_eq!(
+        symbols(storage.load_opportunities(10).await.unwrap()),
+        vec!["NEW", "MID"]
+    );
+}
+
+#[tokio::test]
+async fn test_memory_storage() {
+    exercise(&MemoryStorage::new()).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_storage() {
+    exercise(&SqliteStorage::open_in_memory().unwrap()).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_storage_survives_reopening() {
+    let path = std::env::temp_dir().join(format!("aggregator-{}.db", uuid::Uuid::new_v4()));
+    {
+        let storage = SqliteStorage::open(&path).unwrap();
+        storage
+            .save_summaries(&[summary("BTCUSDT", 1.0)])
+            .await
+            .unwrap();
+    }
+    let storage = SqliteStorage::open(&path).unwrap();
+    let summaries = storage.load_summaries().await.unwrap();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].symbol, "BTCUSDT");
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_open_storage() {
+    assert!(open_storage(&StorageConfig::default()).unwrap().is_none());
+
+    let storage = open_storage(&StorageConfig {
+        enabled: true,
+        backend: StorageBackend::Memory,
+        ..StorageConfig::default()
+    })
+    .unwrap()
+    .unwrap();
+    assert_eq!(storage.name(), "memory");
+}
+
+#[test]
+fn test_book_snapshot_to_update() {
+    let snapshot = BookSnapshot {
+        pair: TradingPair::new("BTC", "USDT"),
+        exchange: Exchange::Kraken,
+        summary: summary("BTCUSDT", 1.0),
+    };
+    let update = snapshot.to_update();
+    assert_eq!(update.symbol, "BTCUSDT");
+    assert_eq!(update.exchange, Exchange::Kraken);
+    assert_eq!(update.bids.len(), 1);
+    assert_eq!(update.bids[0].exchange, Exchange::Kraken);
+    assert!(update.asks.is_empty());
+}
@@ -0,0 +1,81 @@
+// aggregator-core/tests/aggregator-core/telemetry_tests.rs
+// Unit tests for telemetry.rs
+
+use aggregator_core::telemetry::*;
+
+#[test]
+fn test_series_are_shared_by_labels() {
+    let registry = MetricsRegistry::new();
+    let binance = registry.counter("updates_total", "Updates", &[("exchange", "binance")]);
+    binance.inc();
+    registry
+        .counter("updates_total", "Updates", &[("exchange", "binance")])
+        .inc_by(2);
+    registry
+        .counter("updates_total", "Updates", &[("exchange", "kraken")])
+        .inc();
+    assert_eq!(binance.get(), 3);
+
+    // Label order does not matter
+    let gauge = registry.gauge("spread", "Spread", &[("a", "1"), ("b", "2")]);
+    registry
+        .gauge("spread", "Spread", &[("b", "2"), ("a", "1")])
+        .set(0.5);
+    assert_eq!(gauge.get(), 0.5);
+
+    assert_eq!(
+        registry.render(),
+        "# HELP spread Spread\n\
+         # TYPE spread gauge\n\
+         spread{a=\"1\",b=\"2\"} 0.5\n\
+         # HELP updates_total Updates\n\
+         # TYPE updates_total counter\n\
+         updates_total{exchange=\"binance\"} 3\n\
+         updates_total{exchange=\"kraken\"} 1\n"
+    );
+}
+
+#[test]
+fn test_histogram_buckets() {
+    let histogram = Histogram::new(&[10.0, 1.0, 5.0]);
+    for value in [0.5, 1.0, 3.0, 7.0, 20.0] {
+        histogram.observe(value);
+    }
+    assert_eq!(
+        histogram.cumulative_buckets(),
+        vec![(1.0, 2), (5.0, 3), (10.0, 4), (f64::INFINITY, 5)]
+    );
+    assert_eq!(histogram.count(), 5);
+    assert_eq!(histogram.sum(), 31.5);
+
+    let registry = MetricsRegistry::new();
+    registry
+        .histogram("latency_ms", "Latency", &[("exchange", "okx")])
+        .observe(3.0);
+    let rendered = registry.render();
+    assert!(rendered.contains("# TYPE latency_ms histogram\n"));
+    assert!(rendered.contains("latency_ms_bucket{exchange=\"okx\",le=\"2.5\"} 0\n"));
+    assert!(rendered.contains("latency_ms_bucket{exchange=\"okx\",le=\"5\"} 1\n"));
+    assert!(rendered.contains("latency_ms_bucket{exchange=\"okx\",le=\"+Inf\"} 1\n"));
+    assert!(rendered.contains("latency_ms_sum{exchange=\"okx\"} 3\n"));
+    assert!(rendered.contains("latency_ms_count{exchange=\"okx\"} 1\n"));
+}
+
+#[test]
+fn test_label_values_are_escaped() {
+    let registry = MetricsRegistry::new();
+    registry
+        .counter("errors_total", "Errors", &[("message", "a \"b\"\\\n")])
+        .inc();
+    assert!(registry
+        .render()
+        .contains("errors_total{message=\"a \\\"b\\\"\\\\\\n\"} 1\n"));
+}
+
+#[test]
+#[should_panic(expected = "is a counter, not a gauge")]
+fn test_kind_mismatch_panics() {
+    let registry = MetricsRegistry::new();
+    registry.counter("updates", "Updates", &[]);
+    registry.gauge("updates", "Updates", &[]);
+}
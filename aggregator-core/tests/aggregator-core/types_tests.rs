@@ -2,7 +2,7 @@
 // Unit tests for types.rs
 
 use aggregator_core::types::*;
-use chrono::{TimeZone, Utc};
+use chrono::Utc;
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -20,6 +20,36 @@ fn test_exchange_display_and_fromstr() {
     assert!(Exchange::from_str("unknown").is_err());
 }
 
+/**
+ * @notice Tests custom exchanges and how exchanges are named and serialized.
+ * @dev Built-in names resolve to their variant, anything else to a lowercase custom exchange.
+ */
+#[test]
+fn test_exchange_named_and_custom_serde() {
+    assert_eq!(Exchange::named("Binance"), Exchange::Binance);
+    assert_eq!(Exchange::named("gateio"), Exchange::GateIo);
+    let acme = Exchange::named("Acme");
+    assert_eq!(acme, Exchange::Custom("acme".to_string()));
+    assert!(acme.is_custom());
+    assert!(!Exchange::Binance.is_custom());
+    assert_eq!(acme.to_string(), "acme");
+    assert!(Exchange::from_str("acme").is_err());
+
+    assert_eq!(
+        serde_json::to_string(&Exchange::Binance).unwrap(),
+        "\"Binance\""
+    );
+    assert_eq!(serde_json::to_string(&acme).unwrap(), "\"acme\"");
+    let parsed: Exchange = serde_json::from_str("\"acme\"").unwrap();
+    assert_eq!(parsed, acme);
+
+    // Exchanges key the config's exchange map
+    let map: std::collections::HashMap<Exchange, u32> =
+        serde_json::from_str(r#"{"Kraken": 1, "acme": 2}"#).unwrap();
+    assert_eq!(map.get(&Exchange::Kraken), Some(&1));
+    assert_eq!(map.get(&acme), Some(&2));
+}
+
 /**
  * @notice Tests TradingPair::new and its Display implementation.
  * @dev Ensures base and quote are uppercased and formatted correctly.
@@ -112,6 +142,9 @@ fn test_price_level_update() {
         bids: vec![Bid::default()],
         asks: vec![Ask::default()],
         timestamp: now,
+        funding: None,
+        event_time: None,
+        market_type: None,
     };
     assert_eq!(plu.id, id);
     assert_eq!(plu.symbol, "BTCUSD");
@@ -119,6 +152,13 @@ fn test_price_level_update() {
     assert_eq!(plu.bids.len(), 1);
     assert_eq!(plu.asks.len(), 1);
     assert_eq!(plu.timestamp, now);
+    assert!(plu.receive_latency_ms().is_none());
+
+    let delayed = PriceLevelUpdate {
+        event_time: Some(now - chrono::Duration::microseconds(12_500)),
+        ..plu
+    };
+    assert_eq!(delayed.receive_latency_ms(), Some(12.5));
 }
 
 /**
@@ -139,6 +179,7 @@ fn test_summary() {
         }],
         asks: vec![],
         timestamp: now,
+        market_type: None,
     };
     assert_eq!(s.symbol, "ETHUSD");
     assert_eq!(s.spread, 0.5);
@@ -173,6 +214,10 @@ fn test_arbitrage_opportunity() {
         sell_price: 105.0,
         profit_percentage: 5.0,
         volume: 1.0,
+        average_buy_price: 100.5,
+        average_sell_price: 104.5,
+        time_to_live_ms: 250.0,
+        confidence: 0.25,
         timestamp: now,
     };
     assert_eq!(arb.buy_exchange, Exchange::Binance);
@@ -182,6 +227,10 @@ fn test_arbitrage_opportunity() {
     assert_eq!(arb.sell_price, 105.0);
     assert_eq!(arb.profit_percentage, 5.0);
     assert_eq!(arb.volume, 1.0);
+    assert_eq!(arb.average_buy_price, 100.5);
+    assert_eq!(arb.average_sell_price, 104.5);
+    assert_eq!(arb.time_to_live_ms, 250.0);
+    assert_eq!(arb.confidence, 0.25);
     assert_eq!(arb.timestamp, now);
 }
 
@@ -216,6 +265,9 @@ fn test_metrics() {
         symbol: "BTCUSD".to_string(),
         updates_per_second: 10.0,
         latency_ms: 5.0,
+        latency_p50_ms: 4.0,
+        latency_p95_ms: 9.0,
+        latency_p99_ms: 12.0,
         error_count: 0,
         last_update: now,
     };
@@ -223,6 +275,56 @@ fn test_metrics() {
     assert_eq!(m.symbol, "BTCUSD");
     assert_eq!(m.updates_per_second, 10.0);
     assert_eq!(m.latency_ms, 5.0);
+    assert_eq!(m.latency_p99_ms, 12.0);
     assert_eq!(m.error_count, 0);
     assert_eq!(m.last_update, now);
 }
+
+/**
+ * @notice Tests BookStats deltas and mean lock wait.
+ * @dev A zero-acquisition delta reports a zero mean instead of dividing by zero.
+ */
+#[test]
+fn test_book_stats_since() {
+    let earlier = BookStats {
+        updates_applied: 10,
+        levels_trimmed: 2,
+        lock_acquisitions: 4,
+        lock_wait_us: 40,
+    };
+    let later = BookStats {
+        updates_applied: 25,
+        levels_trimmed: 2,
+        lock_acquisitions: 6,
+        lock_wait_us: 100,
+    };
+    let delta = later.since(&earlier);
+    assert_eq!(delta.updates_applied, 15);
+    assert_eq!(delta.levels_trimmed, 0);
+    assert_eq!(delta.mean_lock_wait_us(), 30.0);
+    assert_eq!(BookStats::default().mean_lock_wait_us(), 0.0);
+}
+
+/**
+ * @notice Tests rendering BookStats in the Prometheus text format.
+ * @dev Each counter family gets HELP and TYPE lines and one sample per book.
+ */
+#[test]
+fn test_book_stats_render_prometheus() {
+    let stats = BookStats {
+        updates_applied: 7,
+        levels_trimmed: 1,
+        lock_acquisitions: 3,
+        lock_wait_us: 12,
+    };
+    let output = BookStats::render_prometheus([(&Exchange::Binance, "BTCUSDT", &stats)]);
+    let label = format!("{{exchange=\"{}\",symbol=\"BTCUSDT\"}}", Exchange::Binance);
+    assert!(output.contains("# TYPE orderbook_updates_applied_total counter\n"));
+    assert!(output.contains(&format!("orderbook_updates_applied_total{} 7\n", label)));
+    assert!(output.contains(&format!("orderbook_levels_trimmed_total{} 1\n", label)));
+    assert!(output.contains(&format!("orderbook_lock_acquisitions_total{} 3\n", label)));
+    assert!(output.contains(&format!(
+        "orderbook_lock_wait_microseconds_total{} 12\n",
+        label
+    )));
+}
@@ -0,0 +1,730 @@
+//! # Alerts Module
+//!
+//! This module turns arbitrage opportunities and exchange health events into notifications.
+//! An [`AlertManager`] checks every event against user-defined [`AlertRule`]s, such as "profit
+//! above X%" or "exchange unhealthy for more than N seconds", and dispatches the resulting
+//! [`Alert`]s to every configured [`AlertSink`]. Repeats of the same alert are held back for a
+//! cooldown so a persistent opportunity or outage does not flood the channel. With the
+//! `webhook-alerts` feature, sinks for generic webhooks, Slack and Telegram are provided.
+
+use crate::normalize_symbol;
+use aggregator_core::{ArbitrageOpportunity, Exchange, HealthEvent, HealthEventKind, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Alert Rule
+///
+/// A condition that raises an alert when an event meets it.
+///
+/// ## Variants
+///
+/// - `MinProfit`: An arbitrage opportunity's profit percentage is above
+///   `min_profit_percentage`. `symbol` limits the rule to one symbol, compared with separators
+///   removed.
+/// - `ExchangeUnhealthy`: An exchange has stayed unhealthy for more than `unhealthy_secs`.
+///   `exchange` limits the rule to one exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertRule {
+    MinProfit {
+        min_profit_percentage: f64,
+        #[serde(default)]
+        symbol: Option<String>,
+    },
+    ExchangeUnhealthy {
+        unhealthy_secs: u64,
+        #[serde(default)]
+        exchange: Option<Exchange>,
+    },
+}
+
+/// # Alert Event
+///
+/// What an alert was raised for.
+///
+/// ## Variants
+///
+/// - `Arbitrage`: The opportunity that met a `MinProfit` rule.
+/// - `ExchangeUnhealthy`: An exchange that met an `ExchangeUnhealthy` rule, when it became
+///   unhealthy, and the error its latest health event reported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertEvent {
+    Arbitrage(ArbitrageOpportunity),
+    ExchangeUnhealthy {
+        exchange: Exchange,
+        unhealthy_since: DateTime<Utc>,
+        error_message: Option<String>,
+    },
+}
+
+/// # Alert
+///
+/// A notification ready to be dispatched.
+///
+/// ## Fields
+///
+/// - `key`: Identifies repeats of the same alert for rate limiting, e.g. the symbol and
+///   exchange pair of an opportunity.
+/// - `message`: Human-readable text sinks deliver.
+/// - `event`: The event that raised the alert.
+/// - `timestamp`: When the alert was raised.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub key: String,
+    pub message: String,
+    pub event: AlertEvent,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Alert {
+    fn arbitrage(opportunity: &ArbitrageOpportunity) -> Self {
+        Self {
+            key: format!(
+                "arbitrage:{}:{}:{}",
+                normalize_symbol(&opportunity.symbol),
+                opportunity.buy_exchange,
+                opportunity.sell_exchange
+            ),
+            message: format!(
+                "Arbitrage on {}: buy on {} at {:.8}, sell on {} at {:.8}, {:.3}% profit on {}",
+                opportunity.symbol,
+                opportunity.buy_exchange,
+                opportunity.buy_price,
+                opportunity.sell_exchange,
+                opportunity.sell_price,
+                opportunity.profit_percentage,
+                opportunity.volume
+            ),
+            event: AlertEvent::Arbitrage(opportunity.clone()),
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn exchange_unhealthy(exchange: &Exchange, state: &UnhealthyState, now: DateTime<Utc>) -> Self {
+        let mut message = format!(
+            "Exchange {} unhealthy for {}s",
+            exchange,
+            (now - state.since).num_seconds()
+        );
+        if let Some(error_message) = &state.error_message {
+            message.push_str(": ");
+            message.push_str(error_message);
+        }
+        Self {
+            key: format!("unhealthy:{}", exchange),
+            message,
+            event: AlertEvent::ExchangeUnhealthy {
+                exchange: exchange.clone(),
+                unhealthy_since: state.since,
+                error_message: state.error_message.clone(),
+            },
+            timestamp: now,
+        }
+    }
+}
+
+/// # Alert Sink
+///
+/// A destination alerts are delivered to.
+///
+/// ## Required Methods
+///
+/// - `name`: Short name used when logging delivery failures.
+/// - `send`: Delivers one alert.
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> &str;
+    async fn send(&self, alert: &Alert) -> Result<()>;
+}
+
+/// # Alerts Config
+///
+/// Rules, sinks and rate limiting for an [`AlertManager`], loadable from JSON.
+///
+/// ## Fields
+///
+/// - `rules`: Conditions that raise alerts.
+/// - `sinks`: Where alerts are delivered.
+/// - `cooldown_secs`: Minimum time between two alerts with the same key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertsConfig {
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+    #[serde(default)]
+    pub sinks: Vec<SinkConfig>,
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+fn default_cooldown_secs() -> u64 {
+    300
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            sinks: Vec::new(),
+            cooldown_secs: default_cooldown_secs(),
+        }
+    }
+}
+
+/// # Sink Config
+///
+/// Settings for one alert sink.
+///
+/// ## Variants
+///
+/// - `Webhook`: POSTs every alert as JSON to `url`.
+/// - `Slack`: Posts the alert message to a Slack incoming webhook at `webhook_url`.
+/// - `Telegram`: Sends the alert message to `chat_id` through the bot identified by
+///   `bot_token`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    Webhook { url: String },
+    Slack { webhook_url: String },
+    Telegram { bot_token: String, chat_id: String },
+}
+
+/// Health of one exchange as tracked from its events.
+#[derive(Debug, Clone)]
+struct UnhealthyState {
+    since: DateTime<Utc>,
+    error_message: Option<String>,
+    alerted: bool,
+}
+
+/// # Alert Manager
+///
+/// Applies alert rules to arbitrage opportunities and exchange health events and dispatches
+/// the alerts they raise to every sink. An alert is held back if one with the same key was
+/// dispatched within the cooldown, and an unhealthy exchange is alerted on once per outage.
+/// Cloning the manager shares its state.
+#[derive(Clone)]
+pub struct AlertManager {
+    rules: Vec<AlertRule>,
+    sinks: Vec<Arc<dyn AlertSink>>,
+    cooldown: Duration,
+    last_sent: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+    unhealthy: Arc<RwLock<HashMap<Exchange, UnhealthyState>>>,
+}
+
+impl AlertManager {
+    /// ## New
+    ///
+    /// Creates a manager with no rules or sinks that holds back repeats of an alert for
+    /// `cooldown`.
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            rules: Vec::new(),
+            sinks: Vec::new(),
+            cooldown,
+            last_sent: Arc::new(RwLock::new(HashMap::new())),
+            unhealthy: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// ## From Config
+    ///
+    /// Creates a manager with the rules, cooldown and sinks of `config`. Sinks need the
+    /// `webhook-alerts` feature; without it they are skipped with a warning.
+    pub fn from_config(config: &AlertsConfig) -> Self {
+        let mut manager = Self::new(Duration::seconds(config.cooldown_secs as i64));
+        for rule in &config.rules {
+            manager = manager.with_rule(rule.clone());
+        }
+        for sink in &config.sinks {
+            #[cfg(feature = "webhook-alerts")]
+            {
+                manager = manager.with_sink(webhook::sink_from_config(sink));
+            }
+            #[cfg(not(feature = "webhook-alerts"))]
+            warn!(
+                "Alert sink {:?} needs the webhook-alerts feature, skipping",
+                sink
+            );
+        }
+        manager
+    }
+
+    /// Adds a rule alerts are raised by.
+    pub fn with_rule(mut self, rule: AlertRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Adds a sink alerts are delivered to.
+    pub fn with_sink(mut self, sink: Arc<dyn AlertSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// ## On Opportunity
+    ///
+    /// Raises an alert if `opportunity` meets any `MinProfit` rule and dispatches it.
+    ///
+    /// ### Returns
+    ///
+    /// The alert, if one was raised and not held back by the cooldown.
+    pub async fn on_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Option<Alert> {
+        let matched = self.rules.iter().any(|rule| match rule {
+            AlertRule::MinProfit {
+                min_profit_percentage,
+                symbol,
+            } => {
+                opportunity.profit_percentage > *min_profit_percentage
+                    && symbol.as_ref().is_none_or(|symbol| {
+                        normalize_symbol(symbol) == normalize_symbol(&opportunity.symbol)
+                    })
+            }
+            AlertRule::ExchangeUnhealthy { .. } => false,
+        });
+        if !matched {
+            return None;
+        }
+        self.dispatch(Alert::arbitrage(opportunity)).await
+    }
+
+    /// ## On Health Event
+    ///
+    /// Tracks when the event's exchange became unhealthy, or clears it once the exchange
+    /// connects again, then checks the `ExchangeUnhealthy` rules.
+    ///
+    /// ### Returns
+    ///
+    /// The alerts dispatched by the check.
+    pub async fn on_health_event(&self, event: &HealthEvent) -> Vec<Alert> {
+        {
+            let mut unhealthy = self.unhealthy.write().await;
+            if matches!(event.kind, HealthEventKind::Connected) {
+                unhealthy.remove(&event.exchange);
+            } else {
+                let error_message = event.health_status().error_message;
+                unhealthy
+                    .entry(event.exchange.clone())
+                    .and_modify(|state| state.error_message = error_message.clone())
+                    .or_insert(UnhealthyState {
+                        since: event.timestamp,
+                        error_message,
+                        alerted: false,
+                    });
+            }
+        }
+        self.check_health(Utc::now()).await
+    }
+
+    /// ## Check Health
+    ///
+    /// Raises an alert for every exchange that has been unhealthy for longer than a matching
+    /// `ExchangeUnhealthy` rule allows at `now` and has not been alerted on during this outage.
+    /// Outages only end with a health event, so this runs on a timer as well as on events.
+    ///
+    /// ### Returns
+    ///
+    /// The alerts dispatched.
+    pub async fn check_health(&self, now: DateTime<Utc>) -> Vec<Alert> {
+        let mut due = Vec::new();
+        {
+            let mut unhealthy = self.unhealthy.write().await;
+            for (exchange, state) in unhealthy.iter_mut() {
+                let overdue = self.rules.iter().any(|rule| match rule {
+                    AlertRule::ExchangeUnhealthy {
+                        unhealthy_secs,
+                        exchange: only,
+                    } => {
+                        only.as_ref().is_none_or(|only| only == exchange)
+                            && now - state.since > Duration::seconds(*unhealthy_secs as i64)
+                    }
+                    AlertRule::MinProfit { .. } => false,
+                });
+                if overdue && !state.alerted {
+                    state.alerted = true;
+                    due.push(Alert::exchange_unhealthy(exchange, state, now));
+                }
+            }
+        }
+
+        let mut dispatched = Vec::new();
+        for alert in due {
+            dispatched.extend(self.dispatch(alert).await);
+        }
+        dispatched
+    }
+
+    /// ## Spawn
+    ///
+    /// Spawns a task that checks every opportunity received on `arbitrage_rx` and every health
+    /// event received on `health_rx`, and re-checks exchange health every second, until either
+    /// channel closes.
+    pub fn spawn(
+        &self,
+        mut arbitrage_rx: broadcast::Receiver<ArbitrageOpportunity>,
+        mut health_rx: broadcast::Receiver<HealthEvent>,
+    ) -> JoinHandle<Result<()>> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                tokio::select! {
+                    opportunity = arbitrage_rx.recv() => match opportunity {
+                        Ok(opportunity) => {
+                            manager.on_opportunity(&opportunity).await;
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Alert manager lagged, skipped {} opportunities", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    event = health_rx.recv() => match event {
+                        Ok(event) => {
+                            manager.on_health_event(&event).await;
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Alert manager lagged, skipped {} health events", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = ticker.tick() => {
+                        manager.check_health(Utc::now()).await;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Sends `alert` to every sink unless one with the same key went out within the cooldown
+    async fn dispatch(&self, alert: Alert) -> Option<Alert> {
+        {
+            let mut last_sent = self.last_sent.write().await;
+            if let Some(sent) = last_sent.get(&alert.key) {
+                if alert.timestamp - *sent < self.cooldown {
+                    return None;
+                }
+            }
+            last_sent.insert(alert.key.clone(), alert.timestamp);
+        }
+
+        for sink in &self.sinks {
+            if let Err(e) = sink.send(&alert).await {
+                warn!("Failed to send alert to {}: {}", sink.name(), e);
+            }
+        }
+        Some(alert)
+    }
+}
+
+impl Default for AlertManager {
+    fn default() -> Self {
+        Self::from_config(&AlertsConfig::default())
+    }
+}
+
+#[cfg(feature = "webhook-alerts")]
+pub use webhook::{SlackSink, TelegramSink, WebhookSink};
+
+#[cfg(feature = "webhook-alerts")]
+mod webhook {
+    use super::{Alert, AlertSink, SinkConfig};
+    use aggregator_core::{AggregatorError, Result};
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// How long a sink waits for the remote end before giving up on an alert
+    const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub(super) fn sink_from_config(config: &SinkConfig) -> Arc<dyn AlertSink> {
+        match config {
+            SinkConfig::Webhook { url } => Arc::new(WebhookSink::new(url)),
+            SinkConfig::Slack { webhook_url } => Arc::new(SlackSink::new(webhook_url)),
+            SinkConfig::Telegram { bot_token, chat_id } => {
+                Arc::new(TelegramSink::new(bot_token, chat_id))
+            }
+        }
+    }
+
+    fn client() -> reqwest::Client {
+        reqwest::Client::builder()
+            .timeout(SEND_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    }
+
+    async fn post_json(client: &reqwest::Client, url: &str, body: &Value) -> Result<()> {
+        let response = client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Alert delivery failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AggregatorError::network(format!(
+                "HTTP error: {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    /// # Webhook Sink
+    ///
+    /// POSTs every alert, serialized as JSON, to a URL.
+    #[derive(Debug, Clone)]
+    pub struct WebhookSink {
+        url: String,
+        client: reqwest::Client,
+    }
+
+    impl WebhookSink {
+        pub fn new(url: &str) -> Self {
+            Self {
+                url: url.to_string(),
+                client: client(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AlertSink for WebhookSink {
+        fn name(&self) -> &str {
+            "webhook"
+        }
+
+        async fn send(&self, alert: &Alert) -> Result<()> {
+            post_json(&self.client, &self.url, &json!(alert)).await
+        }
+    }
+
+    /// # Slack Sink
+    ///
+    /// Posts alert messages to a Slack incoming webhook.
+    #[derive(Debug, Clone)]
+    pub struct SlackSink {
+        webhook_url: String,
+        client: reqwest::Client,
+    }
+
+    impl SlackSink {
+        pub fn new(webhook_url: &str) -> Self {
+            Self {
+                webhook_url: webhook_url.to_string(),
+                client: client(),
+            }
+        }
+
+        pub(super) fn body(alert: &Alert) -> Value {
+            json!({ "text": alert.message })
+        }
+    }
+
+    #[async_trait]
+    impl AlertSink for SlackSink {
+        fn name(&self) -> &str {
+            "slack"
+        }
+
+        async fn send(&self, alert: &Alert) -> Result<()> {
+            post_json(&self.client, &self.webhook_url, &Self::body(alert)).await
+        }
+    }
+
+    /// # Telegram Sink
+    ///
+    /// Sends alert messages to a Telegram chat through the Bot API.
+    #[derive(Debug, Clone)]
+    pub struct TelegramSink {
+        bot_token: String,
+        chat_id: String,
+        client: reqwest::Client,
+    }
+
+    impl TelegramSink {
+        pub fn new(bot_token: &str, chat_id: &str) -> Self {
+            Self {
+                bot_token: bot_token.to_string(),
+                chat_id: chat_id.to_string(),
+                client: client(),
+            }
+        }
+
+        pub(super) fn body(&self, alert: &Alert) -> Value {
+            json!({ "chat_id": self.chat_id, "text": alert.message })
+        }
+    }
+
+    #[async_trait]
+    impl AlertSink for TelegramSink {
+        fn name(&self) -> &str {
+            "telegram"
+        }
+
+        async fn send(&self, alert: &Alert) -> Result<()> {
+            let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+            post_json(&self.client, &url, &self.body(alert)).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        alerts: Mutex<Vec<Alert>>,
+    }
+
+    #[async_trait]
+    impl AlertSink for RecordingSink {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn send(&self, alert: &Alert) -> Result<()> {
+            self.alerts.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    fn opportunity(symbol: &str, profit_percentage: f64) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            buy_exchange: Exchange::Bybit,
+            sell_exchange: Exchange::Binance,
+            symbol: symbol.to_string(),
+            buy_price: 50000.0,
+            sell_price: 50300.0,
+            profit_percentage,
+            volume: 1.0,
+            average_buy_price: 50000.0,
+            average_sell_price: 50300.0,
+            time_to_live_ms: 500.0,
+            confidence: 0.5,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_profit_rule_and_cooldown() {
+        let sink = Arc::new(RecordingSink::default());
+        let manager = AlertManager::new(Duration::seconds(60))
+            .with_rule(AlertRule::MinProfit {
+                min_profit_percentage: 0.5,
+                symbol: Some("BTC-USDT".to_string()),
+            })
+            .with_sink(sink.clone());
+
+        assert!(manager
+            .on_opportunity(&opportunity("BTC/USDT", 0.4))
+            .await
+            .is_none());
+        assert!(manager
+            .on_opportunity(&opportunity("ETH/USDT", 0.9))
+            .await
+            .is_none());
+
+        let alert = manager
+            .on_opportunity(&opportunity("BTC/USDT", 0.6))
+            .await
+            .unwrap();
+        assert_eq!(alert.key, "arbitrage:BTCUSDT:bybit:binance");
+        assert!(alert.message.contains("0.600% profit"));
+
+        // The same opportunity again within the cooldown is held back
+        assert!(manager
+            .on_opportunity(&opportunity("BTC/USDT", 0.7))
+            .await
+            .is_none());
+        assert_eq!(sink.alerts.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_exchange_alerted_once_per_outage() {
+        let sink = Arc::new(RecordingSink::default());
+        let manager = AlertManager::new(Duration::zero())
+            .with_rule(AlertRule::ExchangeUnhealthy {
+                unhealthy_secs: 30,
+                exchange: None,
+            })
+            .with_sink(sink.clone());
+
+        let mut event = HealthEvent::new(
+            Exchange::Kraken,
+            HealthEventKind::Disconnected {
+                reason: "connection reset".to_string(),
+            },
+        );
+        event.timestamp = Utc::now() - Duration::seconds(10);
+        assert!(manager.on_health_event(&event).await.is_empty());
+
+        let later = Utc::now() + Duration::seconds(25);
+        let alerts = manager.check_health(later).await;
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].message.contains("connection reset"));
+        assert!(manager.check_health(later).await.is_empty());
+
+        // Recovering ends the outage, so the next one is alerted on again
+        manager
+            .on_health_event(&HealthEvent::new(
+                Exchange::Kraken,
+                HealthEventKind::Connected,
+            ))
+            .await;
+        event.timestamp = Utc::now();
+        manager.on_health_event(&event).await;
+        assert_eq!(
+            manager
+                .check_health(later + Duration::seconds(30))
+                .await
+                .len(),
+            1
+        );
+        assert_eq!(sink.alerts.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_config_deserializes_rules_and_sinks() {
+        let config: AlertsConfig = serde_json::from_str(
+            r#"{
+                "rules": [
+                    {"type": "min_profit", "min_profit_percentage": 0.5},
+                    {"type": "exchange_unhealthy", "unhealthy_secs": 60, "exchange": "Kraken"}
+                ],
+                "sinks": [{"type": "telegram", "bot_token": "token", "chat_id": "42"}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.rules.len(), 2);
+        assert_eq!(config.cooldown_secs, 300);
+        assert_eq!(
+            config.sinks[0],
+            SinkConfig::Telegram {
+                bot_token: "token".to_string(),
+                chat_id: "42".to_string()
+            }
+        );
+    }
+
+    #[cfg(feature = "webhook-alerts")]
+    #[test]
+    fn test_chat_sink_bodies_carry_message() {
+        let alert = Alert::arbitrage(&opportunity("BTC/USDT", 0.6));
+        assert_eq!(SlackSink::body(&alert)["text"], alert.message);
+
+        let telegram = TelegramSink::new("token", "42");
+        let body = telegram.body(&alert);
+        assert_eq!(body["chat_id"], "42");
+        assert_eq!(body["text"], alert.message);
+    }
+}
@@ -4,9 +4,34 @@
 //! It includes functionalities for identifying simple, triangular, and more complex arbitrage
 //! scenarios.
 
-use aggregator_core::{ArbitrageOpportunity, Summary, TradingPair};
-use chrono::Utc;
+use aggregator_core::{
+    ArbitrageOpportunity, Config, Exchange, FeeSchedule, FillEstimate, Metrics, Summary, TradeSide,
+    TradingPair,
+};
+use chrono::{DateTime, Duration, Utc};
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// How long an opportunity is assumed to stay open after its books were published when no
+/// staleness bound is configured
+const DEFAULT_VALIDITY_WINDOW_MS: f64 = 1000.0;
+
+/// # Profit Basis
+///
+/// Whether `ArbitrageDetector` reports and filters opportunities on their profit before or
+/// after costs.
+///
+/// ## Variants
+///
+/// - `Gross`: The price difference alone.
+/// - `Net`: What is left after the taker fee on both legs and the cost of withdrawing the
+///   bought asset from the buy exchange.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProfitBasis {
+    Gross,
+    #[default]
+    Net,
+}
 
 /// # Arbitrage Detector
 ///
@@ -19,9 +44,19 @@ use std::collections::HashMap;
 /// - `min_profit_threshold`: The minimum profit percentage required to consider an
 ///   opportunity as valid.
 /// - `min_volume_threshold`: The minimum trade volume required for an opportunity.
+/// - `fees`: The fee schedule of each exchange; exchanges without one trade for free.
+/// - `profit_basis`: Whether profit is measured before or after fees.
+/// - `latencies`: Measured latency of each exchange in milliseconds, shared so it can be
+///   updated while the detector is in use; exchanges without one are assumed instant.
+/// - `max_staleness`: Summaries older than this are left out of detection. Also the window
+///   an opportunity's time-to-live is measured against.
 pub struct ArbitrageDetector {
     min_profit_threshold: f64,
     min_volume_threshold: f64,
+    fees: HashMap<Exchange, FeeSchedule>,
+    profit_basis: ProfitBasis,
+    latencies: Arc<RwLock<HashMap<Exchange, f64>>>,
+    max_staleness: Option<Duration>,
 }
 
 impl ArbitrageDetector {
@@ -37,6 +72,240 @@ impl ArbitrageDetector {
         Self {
             min_profit_threshold,
             min_volume_threshold,
+            fees: HashMap::new(),
+            profit_basis: ProfitBasis::default(),
+            latencies: Arc::new(RwLock::new(HashMap::new())),
+            max_staleness: None,
+        }
+    }
+
+    /// ## From Config
+    ///
+    /// Creates a detector with the thresholds, fee model and staleness bound of
+    /// `config.analysis`, charging the fee schedule of every exchange in `config` when profit
+    /// is judged net of fees.
+    pub fn from_config(config: &Config) -> Self {
+        let analysis = &config.analysis;
+        let profit_basis = if analysis.net_of_fees {
+            ProfitBasis::Net
+        } else {
+            ProfitBasis::Gross
+        };
+        let detector = Self::new(analysis.min_profit_percentage, analysis.min_volume)
+            .with_config_fees(config)
+            .with_profit_basis(profit_basis);
+        match analysis.max_opportunity_age_ms {
+            Some(max_age_ms) => {
+                detector.with_max_staleness(Duration::milliseconds(max_age_ms as i64))
+            }
+            None => detector,
+        }
+    }
+
+    /// ## With Fee Schedule
+    ///
+    /// Charges the fees in `schedule` on every leg traded on `exchange`.
+    pub fn with_fee_schedule(mut self, exchange: Exchange, schedule: FeeSchedule) -> Self {
+        self.fees.insert(exchange, schedule);
+        self
+    }
+
+    /// ## With Config Fees
+    ///
+    /// Takes the fee schedule of every exchange in `config`.
+    pub fn with_config_fees(mut self, config: &Config) -> Self {
+        for (exchange, exchange_config) in &config.exchanges {
+            self.fees
+                .insert(exchange.clone(), exchange_config.fees.clone());
+        }
+        self
+    }
+
+    /// ## With Profit Basis
+    ///
+    /// Sets whether opportunities are reported and filtered on gross or net profit. Net is
+    /// the default.
+    pub fn with_profit_basis(mut self, profit_basis: ProfitBasis) -> Self {
+        self.profit_basis = profit_basis;
+        self
+    }
+
+    /// ## With Max Staleness
+    ///
+    /// Leaves summaries older than `max_staleness` out of detection, so opportunities built
+    /// on books that may already have moved are never reported, and measures each
+    /// opportunity's time-to-live against this window instead of the default second.
+    pub fn with_max_staleness(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness = Some(max_staleness);
+        self
+    }
+
+    /// ## With Latency
+    ///
+    /// Sets the measured latency of `exchange` in milliseconds.
+    pub fn with_latency(self, exchange: Exchange, latency_ms: f64) -> Self {
+        self.record_latency(exchange, latency_ms);
+        self
+    }
+
+    /// ## Record Latency
+    ///
+    /// Updates the measured latency of `exchange` in milliseconds. Takes `&self`, so the
+    /// latency of a detector shared with a running engine can be kept current.
+    pub fn record_latency(&self, exchange: Exchange, latency_ms: f64) {
+        if let Ok(mut latencies) = self.latencies.write() {
+            latencies.insert(exchange, latency_ms.max(0.0));
+        }
+    }
+
+    /// ## Record Metrics
+    ///
+    /// Updates the latency of every exchange in `metrics`, typically from
+    /// `Aggregator::get_all_metrics()`. The 95th percentile is used so estimates hold for
+    /// most updates, falling back to the mean while no percentile has been computed.
+    pub fn record_metrics(&self, metrics: &HashMap<Exchange, Metrics>) {
+        for (exchange, metrics) in metrics {
+            let latency_ms = if metrics.latency_p95_ms > 0.0 {
+                metrics.latency_p95_ms
+            } else {
+                metrics.latency_ms
+            };
+            self.record_latency(exchange.clone(), latency_ms);
+        }
+    }
+
+    /// Whether a summary published at `published` is within the staleness bound at `now`
+    pub(crate) fn is_fresh(&self, published: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.max_staleness
+            .is_none_or(|max_staleness| now - published <= max_staleness)
+    }
+
+    /// ## Validity
+    ///
+    /// Estimates how long an opportunity between `buy_exchange` and `sell_exchange`, built on
+    /// books the older of which was published at `published`, stays open at `now`. The
+    /// validity window, `max_staleness` or one second by default, is reduced by the age of
+    /// the books and by the time orders take to reach the slower of the two exchanges.
+    ///
+    /// ### Returns
+    ///
+    /// The estimated time-to-live in milliseconds, zero once the window is used up, and the
+    /// confidence that the opportunity is still there when the orders arrive: the share of
+    /// the window that remains, from 0 to 1.
+    pub fn validity(
+        &self,
+        buy_exchange: &Exchange,
+        sell_exchange: &Exchange,
+        published: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> (f64, f64) {
+        let window_ms = self
+            .max_staleness
+            .map_or(DEFAULT_VALIDITY_WINDOW_MS, |max_staleness| {
+                max_staleness.num_milliseconds() as f64
+            });
+        if window_ms <= 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let age_ms =
+            ((now - published).num_microseconds().unwrap_or(i64::MAX) as f64 / 1000.0).max(0.0);
+        let execution_ms = self.latencies.read().map_or(0.0, |latencies| {
+            let latency = |exchange| latencies.get(exchange).copied().unwrap_or(0.0);
+            latency(buy_exchange).max(latency(sell_exchange))
+        });
+
+        let time_to_live_ms = (window_ms - age_ms - execution_ms).max(0.0);
+        (time_to_live_ms, time_to_live_ms / window_ms)
+    }
+
+    /// ## Net Profit Percentage
+    ///
+    /// The profit of buying `quantity` of `base` at `buy_price` on `buy_exchange` and selling
+    /// it at `sell_price` on `sell_exchange`, after the taker fee on both legs and the flat
+    /// fee for withdrawing `base` from the buy exchange, as a percentage of the amount spent.
+    /// The withdrawal fee is valued at `buy_price` and spread over `quantity`, so small trades
+    /// carry more of it.
+    pub fn net_profit_percentage(
+        &self,
+        base: &str,
+        buy_exchange: &Exchange,
+        sell_exchange: &Exchange,
+        buy_price: f64,
+        sell_price: f64,
+        quantity: f64,
+    ) -> f64 {
+        let buy_fees = self.fees.get(buy_exchange);
+        let buy_fee = buy_fees.map_or(0.0, |fees| fees.taker_fee);
+        let sell_fee = self
+            .fees
+            .get(sell_exchange)
+            .map_or(0.0, |fees| fees.taker_fee);
+        let withdrawal_fee = buy_fees.map_or(0.0, |fees| fees.withdrawal_fee(base));
+
+        let cost = buy_price * (1.0 + buy_fee);
+        let proceeds = sell_price * (1.0 - sell_fee);
+        let transfer = if withdrawal_fee > 0.0 {
+            withdrawal_fee * buy_price / quantity
+        } else {
+            0.0
+        };
+        (proceeds - cost - transfer) / cost * 100.0
+    }
+
+    /// Whether buying at `buy_price` and selling at `sell_price` still makes money on the
+    /// configured `ProfitBasis`, ignoring withdrawal fees, which do not grow with size
+    fn crosses(
+        &self,
+        buy_exchange: &Exchange,
+        sell_exchange: &Exchange,
+        buy_price: f64,
+        sell_price: f64,
+    ) -> bool {
+        match self.profit_basis {
+            ProfitBasis::Gross => sell_price > buy_price,
+            ProfitBasis::Net => {
+                let taker_fee = |exchange| {
+                    self.fees
+                        .get(exchange)
+                        .map_or(0.0, |fees: &FeeSchedule| fees.taker_fee)
+                };
+                sell_price * (1.0 - taker_fee(sell_exchange))
+                    > buy_price * (1.0 + taker_fee(buy_exchange))
+            }
+        }
+    }
+
+    /// The minimum profit percentage an opportunity must offer
+    pub(crate) fn min_profit_threshold(&self) -> f64 {
+        self.min_profit_threshold
+    }
+
+    /// The minimum volume an opportunity must be tradable for
+    pub(crate) fn min_volume_threshold(&self) -> f64 {
+        self.min_volume_threshold
+    }
+
+    /// Profit percentage on the configured `ProfitBasis`
+    pub(crate) fn profit_percentage(
+        &self,
+        base: &str,
+        buy_exchange: &Exchange,
+        sell_exchange: &Exchange,
+        buy_price: f64,
+        sell_price: f64,
+        quantity: f64,
+    ) -> f64 {
+        match self.profit_basis {
+            ProfitBasis::Gross => (sell_price - buy_price) / buy_price * 100.0,
+            ProfitBasis::Net => self.net_profit_percentage(
+                base,
+                buy_exchange,
+                sell_exchange,
+                buy_price,
+                sell_price,
+                quantity,
+            ),
         }
     }
 
@@ -53,14 +322,23 @@ impl ArbitrageDetector {
     /// ### Returns
     ///
     /// A `Vec` of `ArbitrageOpportunity` structs, each representing a profitable arbitrage
-    /// opportunity.
+    /// opportunity. `volume` is the size found by `size_opportunity` and the average prices
+    /// are what filling it is expected to cost and return. On the `Net` basis
+    /// `profit_percentage` is the top-of-book profit after fees, with any withdrawal fee
+    /// spread over `volume`. `time_to_live_ms` and `confidence` come from `validity`.
+    /// Summaries older than `max_staleness` are ignored.
     pub async fn detect_opportunities(
         &self,
         summaries: &HashMap<TradingPair, Vec<Summary>>,
     ) -> Vec<ArbitrageOpportunity> {
         let mut opportunities = Vec::new();
+        let now = Utc::now();
 
         for (pair, exchange_summaries) in summaries {
+            let exchange_summaries: Vec<&Summary> = exchange_summaries
+                .iter()
+                .filter(|summary| self.is_fresh(summary.timestamp, now))
+                .collect();
             if exchange_summaries.len() < 2 {
                 continue; // Need at least 2 exchanges for arbitrage
             }
@@ -88,29 +366,46 @@ impl ArbitrageDetector {
                 (best_bid, best_ask)
             {
                 if bid_price > ask_price {
-                    let profit = bid_price - ask_price;
-                    let profit_percentage = (profit / ask_price) * 100.0;
-
-                    if profit_percentage >= self.min_profit_threshold {
-                        // Calculate available volume
-                        let bid_volume =
-                            bid_summary.bids.first().map(|b| b.quantity).unwrap_or(0.0);
-                        let ask_volume =
-                            ask_summary.asks.first().map(|a| a.quantity).unwrap_or(0.0);
-                        let available_volume = bid_volume.min(ask_volume);
-
-                        if available_volume >= self.min_volume_threshold {
-                            opportunities.push(ArbitrageOpportunity {
-                                buy_exchange: ask_summary.asks.first().unwrap().exchange.clone(),
-                                sell_exchange: bid_summary.bids.first().unwrap().exchange.clone(),
-                                symbol: pair.to_string(),
-                                buy_price: ask_price,
-                                sell_price: bid_price,
-                                profit_percentage,
-                                volume: available_volume,
-                                timestamp: Utc::now(),
-                            });
-                        }
+                    let buy_exchange = ask_summary.asks.first().unwrap().exchange.clone();
+                    let sell_exchange = bid_summary.bids.first().unwrap().exchange.clone();
+
+                    // Size the trade by walking both ladders
+                    let Some((buy_fill, sell_fill)) =
+                        self.size_opportunity(pair, ask_summary, bid_summary)
+                    else {
+                        continue;
+                    };
+                    let available_volume = buy_fill.filled_quantity;
+
+                    let profit_percentage = self.profit_percentage(
+                        &pair.base,
+                        &buy_exchange,
+                        &sell_exchange,
+                        ask_price,
+                        bid_price,
+                        available_volume,
+                    );
+
+                    if profit_percentage >= self.min_profit_threshold
+                        && available_volume >= self.min_volume_threshold
+                    {
+                        let published = ask_summary.timestamp.min(bid_summary.timestamp);
+                        let (time_to_live_ms, confidence) =
+                            self.validity(&buy_exchange, &sell_exchange, published, now);
+                        opportunities.push(ArbitrageOpportunity {
+                            buy_exchange,
+                            sell_exchange,
+                            symbol: pair.to_string(),
+                            buy_price: ask_price,
+                            sell_price: bid_price,
+                            profit_percentage,
+                            volume: available_volume,
+                            average_buy_price: buy_fill.average_price,
+                            average_sell_price: sell_fill.average_price,
+                            time_to_live_ms,
+                            confidence,
+                            timestamp: now,
+                        });
                     }
                 }
             }
@@ -119,10 +414,111 @@ impl ArbitrageDetector {
         opportunities
     }
 
+    /// ## Size Opportunity
+    ///
+    /// Finds how much can be bought on `buy` and sold on `sell` while the trade stays
+    /// profitable, by walking the ask ladder of one summary and the bid ladder of the other.
+    /// The size grows one ladder level at a time until the deepest levels no longer cross,
+    /// since every further unit would lose money, and the largest size whose profit on the
+    /// average fill prices meets `min_profit_threshold` is kept. When slippage pulls the
+    /// profit below the threshold partway through a level, the size is narrowed down to
+    /// where it does. Profit is measured on the configured `ProfitBasis`; a withdrawal fee
+    /// can make small sizes unprofitable while larger ones are not.
+    ///
+    /// ### Arguments
+    ///
+    /// - `pair`: The pair traded, whose base asset is withdrawn from the buy exchange.
+    /// - `buy`: The summary whose asks the trade lifts.
+    /// - `sell`: The summary whose bids the trade hits.
+    ///
+    /// ### Returns
+    ///
+    /// The buy and sell `FillEstimate`s for the largest profitable size, or `None` if even
+    /// the best levels are not profitable.
+    pub fn size_opportunity(
+        &self,
+        pair: &TradingPair,
+        buy: &Summary,
+        sell: &Summary,
+    ) -> Option<(FillEstimate, FillEstimate)> {
+        // Candidate sizes are the points where either ladder moves to its next level
+        let mut sizes: Vec<f64> = Vec::new();
+        for levels in [&buy.asks, &sell.bids] {
+            let mut cumulative = 0.0;
+            for level in levels.iter() {
+                cumulative += level.quantity;
+                sizes.push(cumulative);
+            }
+        }
+        sizes.sort_by(f64::total_cmp);
+        sizes.dedup();
+
+        let buy_exchange = &buy.asks.first()?.exchange;
+        let sell_exchange = &sell.bids.first()?.exchange;
+
+        let fill = |size: f64| {
+            let buy_fill = buy.cost_to_fill(TradeSide::Buy, size)?;
+            let sell_fill = sell.cost_to_fill(TradeSide::Sell, size)?;
+            let profit_percentage = self.profit_percentage(
+                &pair.base,
+                buy_exchange,
+                sell_exchange,
+                buy_fill.average_price,
+                sell_fill.average_price,
+                size,
+            );
+            Some((buy_fill, sell_fill, profit_percentage))
+        };
+
+        let mut best = None;
+        let mut last_profitable: Option<f64> = None;
+        for size in sizes {
+            let (buy_fill, sell_fill, profit_percentage) = fill(size)?;
+            if !buy_fill.is_complete() || !sell_fill.is_complete() {
+                break;
+            }
+            if !self.crosses(
+                buy_exchange,
+                sell_exchange,
+                buy_fill.worst_price,
+                sell_fill.worst_price,
+            ) {
+                break;
+            }
+
+            if profit_percentage >= self.min_profit_threshold {
+                best = Some((buy_fill, sell_fill));
+                last_profitable = Some(size);
+                continue;
+            }
+
+            // The previous size was profitable and this one is not, so the threshold is
+            // crossed inside this step; bisect for the last size that still meets it
+            if let Some(mut low) = last_profitable.take() {
+                let mut high = size;
+                for _ in 0..64 {
+                    let mid = (low + high) / 2.0;
+                    if mid <= low || mid >= high {
+                        break;
+                    }
+                    match fill(mid) {
+                        Some((_, _, profit)) if profit >= self.min_profit_threshold => low = mid,
+                        _ => high = mid,
+                    }
+                }
+                if let Some((buy_fill, sell_fill, _)) = fill(low) {
+                    best = Some((buy_fill, sell_fill));
+                }
+            }
+        }
+        best
+    }
+
     /// ## Detect Triangular Arbitrage
     ///
-    /// Placeholder for detecting triangular arbitrage opportunities. This involves finding
-    /// price discrepancies between three different trading pairs.
+    /// A three-legged cycle does not fit in an `ArbitrageOpportunity`, which has one buy and
+    /// one sell. Use `TriangularArbitrageDetector`, which reports `TriangularOpportunity`
+    /// instead.
     ///
     /// ### Arguments
     ///
@@ -130,13 +526,11 @@ impl ArbitrageDetector {
     ///
     /// ### Returns
     ///
-    /// An empty `Vec` as this feature is not yet implemented.
+    /// An empty `Vec`.
     pub async fn detect_triangular_arbitrage(
         &self,
         _summaries: &HashMap<TradingPair, Vec<Summary>>,
     ) -> Vec<ArbitrageOpportunity> {
-        // TODO: Implement triangular arbitrage detection
-        // This would look for opportunities like BTC/USD -> ETH/BTC -> USD/ETH
         vec![]
     }
 
@@ -197,6 +591,7 @@ mod tests {
                 timestamp: Utc::now(),
             }],
             timestamp: Utc::now(),
+            market_type: None,
         };
 
         let summary2 = Summary {
@@ -215,6 +610,7 @@ mod tests {
                 timestamp: Utc::now(),
             }],
             timestamp: Utc::now(),
+            market_type: None,
         };
 
         summaries.insert(pair, vec![summary1, summary2]);
@@ -227,4 +623,171 @@ mod tests {
         assert_eq!(opportunity.sell_exchange, Exchange::Binance);
         assert!(opportunity.profit_percentage > 0.1);
     }
+
+    fn summary_with_depth(exchange: Exchange, bids: &[(f64, f64)], asks: &[(f64, f64)]) -> Summary {
+        let levels = |levels: &[(f64, f64)]| {
+            levels
+                .iter()
+                .map(|&(price, quantity)| PriceLevel {
+                    price,
+                    quantity,
+                    exchange: exchange.clone(),
+                    timestamp: Utc::now(),
+                })
+                .collect()
+        };
+        Summary {
+            symbol: "BTCUSDT".to_string(),
+            spread: 0.0,
+            bids: levels(bids),
+            asks: levels(asks),
+            timestamp: Utc::now(),
+            market_type: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_size_opportunity_walks_ladders() {
+        let detector = ArbitrageDetector::new(0.1, 0.01);
+        let buy = summary_with_depth(
+            Exchange::Bybit,
+            &[],
+            &[(100.0, 1.0), (100.5, 2.0), (103.0, 5.0)],
+        );
+        let sell = summary_with_depth(
+            Exchange::Binance,
+            &[(102.0, 2.0), (101.0, 2.0), (99.0, 5.0)],
+            &[],
+        );
+
+        // At 3.0 the buy averages 100.33 and the sell 101.67; at 4.0 the next ask (103)
+        // no longer crosses the next bid (101)
+        let pair = TradingPair::new("BTC", "USDT");
+        let (buy_fill, sell_fill) = detector.size_opportunity(&pair, &buy, &sell).unwrap();
+        assert_eq!(buy_fill.filled_quantity, 3.0);
+        assert_eq!(sell_fill.filled_quantity, 3.0);
+        assert_eq!(buy_fill.worst_price, 100.5);
+        assert_eq!(sell_fill.worst_price, 101.0);
+
+        let mut summaries = HashMap::new();
+        summaries.insert(pair, vec![buy, sell]);
+        let opportunities = detector.detect_opportunities(&summaries).await;
+        assert_eq!(opportunities[0].volume, 3.0);
+        assert_eq!(opportunities[0].buy_price, 100.0);
+        assert_eq!(opportunities[0].sell_price, 102.0);
+        assert!((opportunities[0].average_buy_price - 301.0 / 3.0).abs() < 1e-9);
+        assert!((opportunities[0].average_sell_price - 305.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_size_opportunity_stops_inside_level_at_threshold() {
+        let detector = ArbitrageDetector::new(1.5, 0.01);
+        let pair = TradingPair::new("BTC", "USDT");
+        let buy = summary_with_depth(Exchange::Bybit, &[], &[(100.0, 1.0), (101.0, 10.0)]);
+        let sell = summary_with_depth(Exchange::Binance, &[(102.0, 20.0)], &[]);
+
+        // Buying q costs 101q - 1, so the profit stays at 1.5% up to q = 1.015 / 0.515
+        let (buy_fill, sell_fill) = detector.size_opportunity(&pair, &buy, &sell).unwrap();
+        let expected = 1.015 / 0.515;
+        assert!((buy_fill.filled_quantity - expected).abs() < 1e-9);
+        assert!((sell_fill.filled_quantity - expected).abs() < 1e-9);
+        assert!((buy_fill.average_price - (101.0 * expected - 1.0) / expected).abs() < 1e-9);
+        assert_eq!(sell_fill.average_price, 102.0);
+    }
+
+    #[test]
+    fn test_size_opportunity_stops_where_fees_eat_the_spread() {
+        let detector = ArbitrageDetector::new(0.0, 0.01)
+            .with_fee_schedule(Exchange::Bybit, FeeSchedule::new(0.0, 0.005))
+            .with_fee_schedule(Exchange::Binance, FeeSchedule::new(0.0, 0.005));
+        let pair = TradingPair::new("BTC", "USDT");
+        let buy = summary_with_depth(Exchange::Bybit, &[], &[(100.0, 1.0), (101.0, 1.0)]);
+        let sell = summary_with_depth(Exchange::Binance, &[(102.0, 2.0)], &[]);
+
+        // 101 still crosses 102 before fees, but not after 0.5% on each side
+        let (buy_fill, _) = detector.size_opportunity(&pair, &buy, &sell).unwrap();
+        assert_eq!(buy_fill.filled_quantity, 1.0);
+
+        let gross = ArbitrageDetector::new(0.0, 0.01).with_profit_basis(ProfitBasis::Gross);
+        let (buy_fill, _) = gross.size_opportunity(&pair, &buy, &sell).unwrap();
+        assert_eq!(buy_fill.filled_quantity, 2.0);
+    }
+
+    #[test]
+    fn test_validity_subtracts_book_age_and_slowest_latency() {
+        let detector = ArbitrageDetector::new(0.1, 0.01)
+            .with_max_staleness(Duration::milliseconds(2000))
+            .with_latency(Exchange::Bybit, 150.0)
+            .with_latency(Exchange::Binance, 300.0);
+        let now = Utc::now();
+
+        // 2000ms window - 500ms book age - 300ms to reach Binance
+        let published = now - Duration::milliseconds(500);
+        let (ttl, confidence) =
+            detector.validity(&Exchange::Bybit, &Exchange::Binance, published, now);
+        assert!((ttl - 1200.0).abs() < 1e-9);
+        assert!((confidence - 0.6).abs() < 1e-9);
+
+        let published = now - Duration::milliseconds(1900);
+        let (ttl, confidence) =
+            detector.validity(&Exchange::Bybit, &Exchange::Binance, published, now);
+        assert_eq!(ttl, 0.0);
+        assert_eq!(confidence, 0.0);
+
+        // Unknown exchanges are assumed instant, within the default one second window
+        let (ttl, confidence) = ArbitrageDetector::new(0.1, 0.01).validity(
+            &Exchange::Coinbase,
+            &Exchange::Kraken,
+            now,
+            now,
+        );
+        assert_eq!(ttl, 1000.0);
+        assert_eq!(confidence, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_stale_summaries_expire_opportunities() {
+        let detector =
+            ArbitrageDetector::new(0.1, 0.01).with_max_staleness(Duration::milliseconds(1000));
+        let pair = TradingPair::new("BTC", "USDT");
+        let buy = summary_with_depth(Exchange::Bybit, &[], &[(100.0, 1.0)]);
+        let mut sell = summary_with_depth(Exchange::Binance, &[(102.0, 1.0)], &[]);
+
+        let mut summaries = HashMap::from([(pair.clone(), vec![buy.clone(), sell.clone()])]);
+        let opportunities = detector.detect_opportunities(&summaries).await;
+        assert_eq!(opportunities.len(), 1);
+        assert!(opportunities[0].time_to_live_ms > 0.0);
+        assert!(opportunities[0].confidence > 0.9);
+
+        sell.timestamp = Utc::now() - Duration::seconds(5);
+        summaries.insert(pair, vec![buy, sell]);
+        assert!(detector.detect_opportunities(&summaries).await.is_empty());
+    }
+
+    #[test]
+    fn test_record_metrics_prefers_p95_latency() {
+        let detector = ArbitrageDetector::new(0.1, 0.01);
+        let metrics = |exchange: Exchange, latency_ms, latency_p95_ms| Metrics {
+            exchange,
+            symbol: "BTCUSDT".to_string(),
+            updates_per_second: 10.0,
+            latency_ms,
+            latency_p50_ms: 0.0,
+            latency_p95_ms,
+            latency_p99_ms: 0.0,
+            error_count: 0,
+            last_update: Utc::now(),
+        };
+        let metrics = HashMap::from([
+            (Exchange::Binance, metrics(Exchange::Binance, 50.0, 200.0)),
+            (Exchange::Bybit, metrics(Exchange::Bybit, 100.0, 0.0)),
+        ]);
+        detector.record_metrics(&metrics);
+
+        let now = Utc::now();
+        let (ttl, _) = detector.validity(&Exchange::Binance, &Exchange::Coinbase, now, now);
+        assert_eq!(ttl, 800.0);
+        let (ttl, _) = detector.validity(&Exchange::Bybit, &Exchange::Coinbase, now, now);
+        assert_eq!(ttl, 900.0);
+    }
 }
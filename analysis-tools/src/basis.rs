@@ -0,0 +1,412 @@
+//! # Basis Module
+//!
+//! This module tracks the spread between perpetual futures and spot prices of the same
+//! symbol across venues. Every spot book is paired with every perpetual book, including
+//! books on different exchanges, and each pairing reports the basis and, once the perpetual's
+//! funding rate is known, the carry earned per year by holding spot against a short
+//! perpetual. Pairings whose basis or carry clear the configured thresholds are published as
+//! `BasisOpportunity` events.
+
+use crate::normalize_symbol;
+use aggregator_core::{Exchange, FundingRate, MarketType, PriceLevelUpdate, Result, Summary};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Basis Config
+///
+/// Thresholds and funding settings for basis analytics.
+///
+/// ## Fields
+///
+/// - `min_basis_percentage`: A pairing is published when the absolute basis, as a percentage
+///   of the spot price, reaches this value.
+/// - `min_annualized_carry`: A pairing is also published when the absolute annualized carry,
+///   in percent, reaches this value.
+/// - `funding_interval_hours`: Hours between funding payments, used to annualize funding
+///   rates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BasisConfig {
+    pub min_basis_percentage: f64,
+    pub min_annualized_carry: f64,
+    pub funding_interval_hours: f64,
+}
+
+impl Default for BasisConfig {
+    fn default() -> Self {
+        Self {
+            min_basis_percentage: 0.2,
+            min_annualized_carry: 10.0,
+            funding_interval_hours: 8.0,
+        }
+    }
+}
+
+/// # Basis Opportunity
+///
+/// The basis between one spot book and one perpetual book of the same symbol.
+///
+/// ## Fields
+///
+/// - `symbol`: The symbol with separators removed, e.g. `BTCUSDT`.
+/// - `spot_exchange`: The exchange of the spot book.
+/// - `perp_exchange`: The exchange of the perpetual book.
+/// - `spot_price`: Mid price of the spot book.
+/// - `perp_price`: Mid price of the perpetual book.
+/// - `basis`: `perp_price - spot_price`.
+/// - `basis_percentage`: `basis` as a percentage of `spot_price`.
+/// - `funding_rate`: The perpetual's latest funding rate as a fraction, if known.
+/// - `annualized_carry`: `funding_rate` times the number of funding intervals in a year, in
+///   percent. Positive when longs pay shorts, so holding spot against a short perpetual earns
+///   it.
+/// - `timestamp`: Time of the newer of the two books.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BasisOpportunity {
+    pub symbol: String,
+    pub spot_exchange: Exchange,
+    pub perp_exchange: Exchange,
+    pub spot_price: f64,
+    pub perp_price: f64,
+    pub basis: f64,
+    pub basis_percentage: f64,
+    pub funding_rate: Option<f64>,
+    pub annualized_carry: Option<f64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Latest mid price of one book.
+#[derive(Debug, Clone, Copy)]
+struct Mid {
+    price: f64,
+    timestamp: DateTime<Utc>,
+}
+
+/// Latest spot and perpetual mids of one symbol, and the funding of each perpetual.
+#[derive(Debug, Default)]
+struct SymbolBooks {
+    spot: BTreeMap<Exchange, Mid>,
+    perp: BTreeMap<Exchange, Mid>,
+    funding: HashMap<Exchange, FundingRate>,
+}
+
+/// # Basis Analyzer
+///
+/// Computes spot-perpetual basis and annualized carry per symbol across venues. Feed it
+/// summaries with [`BasisAnalyzer::record_summary`] or [`BasisAnalyzer::spawn`], funding
+/// rates with [`BasisAnalyzer::record_funding`] or [`BasisAnalyzer::record_update`], and
+/// read opportunities from [`BasisAnalyzer::subscribe`]. Summaries tagged
+/// `MarketType::Futures` are treated as perpetual books and untagged or `Spot` summaries as
+/// spot books; perpetual symbols must match their spot symbol once separators are removed.
+/// Cloning the analyzer shares its state and channel.
+///
+/// ## Fields
+///
+/// - `config`: Thresholds and funding interval.
+/// - `books`: Latest mids and funding, keyed by symbol with separators removed.
+#[derive(Debug, Clone)]
+pub struct BasisAnalyzer {
+    config: BasisConfig,
+    books: Arc<RwLock<HashMap<String, SymbolBooks>>>,
+    opportunity_sender: broadcast::Sender<BasisOpportunity>,
+}
+
+impl BasisAnalyzer {
+    /// ## New
+    ///
+    /// Creates an analyzer with the given thresholds and no books.
+    pub fn new(config: BasisConfig) -> Self {
+        let (opportunity_sender, _) = broadcast::channel(1000);
+        Self {
+            config,
+            books: Arc::new(RwLock::new(HashMap::new())),
+            opportunity_sender,
+        }
+    }
+
+    /// ## Subscribe
+    ///
+    /// Returns a receiver for the opportunities published by `record_summary`.
+    pub fn subscribe(&self) -> broadcast::Receiver<BasisOpportunity> {
+        self.opportunity_sender.subscribe()
+    }
+
+    /// ## Record Summary
+    ///
+    /// Updates the mid price of every exchange the summary carries both a bid and an ask
+    /// for, then publishes every pairing of the summary's symbol that clears the thresholds.
+    /// Options books are ignored.
+    ///
+    /// ### Returns
+    ///
+    /// The published opportunities.
+    pub async fn record_summary(&self, summary: &Summary) -> Vec<BasisOpportunity> {
+        let symbol = normalize_symbol(&summary.symbol);
+        let mut books = self.books.write().await;
+        let symbol_books = books.entry(symbol.clone()).or_default();
+        let side = match summary.market_type {
+            None | Some(MarketType::Spot) => &mut symbol_books.spot,
+            Some(MarketType::Futures) => &mut symbol_books.perp,
+            Some(MarketType::Options) => return Vec::new(),
+        };
+
+        for (exchange, price) in exchange_mids(summary) {
+            side.insert(
+                exchange,
+                Mid {
+                    price,
+                    timestamp: summary.timestamp,
+                },
+            );
+        }
+
+        let opportunities: Vec<BasisOpportunity> = self
+            .pairings(&symbol, symbol_books)
+            .into_iter()
+            .filter(|opportunity| self.clears_thresholds(opportunity))
+            .collect();
+        drop(books);
+
+        for opportunity in &opportunities {
+            // Sending only fails when nobody is subscribed
+            let _ = self.opportunity_sender.send(opportunity.clone());
+        }
+        opportunities
+    }
+
+    /// ## Record Funding
+    ///
+    /// Stores the latest funding rate of the perpetual for `symbol` on `exchange`.
+    pub async fn record_funding(&self, symbol: &str, exchange: Exchange, funding: FundingRate) {
+        let mut books = self.books.write().await;
+        books
+            .entry(normalize_symbol(symbol))
+            .or_default()
+            .funding
+            .insert(exchange, funding);
+    }
+
+    /// ## Record Update
+    ///
+    /// Stores the funding rate carried by a perpetual futures update, if any.
+    pub async fn record_update(&self, update: &PriceLevelUpdate) {
+        if let Some(funding) = &update.funding {
+            self.record_funding(&update.symbol, update.exchange.clone(), funding.clone())
+                .await;
+        }
+    }
+
+    /// ## Basis
+    ///
+    /// Returns every current spot-perpetual pairing of `symbol`, whether or not it clears
+    /// the thresholds, ordered by spot then perpetual exchange.
+    pub async fn basis(&self, symbol: &str) -> Vec<BasisOpportunity> {
+        let symbol = normalize_symbol(symbol);
+        let books = self.books.read().await;
+        books
+            .get(&symbol)
+            .map(|symbol_books| self.pairings(&symbol, symbol_books))
+            .unwrap_or_default()
+    }
+
+    /// ## Spawn
+    ///
+    /// Spawns a task that records every summary received on `summary_rx`, typically from
+    /// `Aggregator::subscribe_summaries()`, until the channel closes.
+    pub fn spawn(&self, mut summary_rx: broadcast::Receiver<Summary>) -> JoinHandle<Result<()>> {
+        let analyzer = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match summary_rx.recv().await {
+                    Ok(summary) => {
+                        analyzer.record_summary(&summary).await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Basis analyzer lagged, skipped {} summaries", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Pairs every spot book of a symbol with every perpetual book.
+    fn pairings(&self, symbol: &str, books: &SymbolBooks) -> Vec<BasisOpportunity> {
+        let periods_per_year = 365.0 * 24.0 / self.config.funding_interval_hours;
+
+        let mut pairings = Vec::new();
+        for (spot_exchange, spot) in &books.spot {
+            for (perp_exchange, perp) in &books.perp {
+                let basis = perp.price - spot.price;
+                let funding_rate = books.funding.get(perp_exchange).map(|funding| funding.rate);
+                pairings.push(BasisOpportunity {
+                    symbol: symbol.to_string(),
+                    spot_exchange: spot_exchange.clone(),
+                    perp_exchange: perp_exchange.clone(),
+                    spot_price: spot.price,
+                    perp_price: perp.price,
+                    basis,
+                    basis_percentage: basis / spot.price * 100.0,
+                    funding_rate,
+                    annualized_carry: funding_rate.map(|rate| rate * periods_per_year * 100.0),
+                    timestamp: spot.timestamp.max(perp.timestamp),
+                });
+            }
+        }
+        pairings
+    }
+
+    fn clears_thresholds(&self, opportunity: &BasisOpportunity) -> bool {
+        opportunity.basis_percentage.abs() >= self.config.min_basis_percentage
+            || opportunity
+                .annualized_carry
+                .is_some_and(|carry| carry.abs() >= self.config.min_annualized_carry)
+    }
+}
+
+impl Default for BasisAnalyzer {
+    fn default() -> Self {
+        Self::new(BasisConfig::default())
+    }
+}
+
+/// The mid of each exchange's best bid and ask in `summary`, skipping exchanges missing a
+/// side or quoting a non-positive or non-finite price.
+pub(crate) fn exchange_mids(summary: &Summary) -> Vec<(Exchange, f64)> {
+    let usable = |price: f64| price.is_finite() && price > 0.0;
+
+    let mut best: HashMap<&Exchange, (Option<f64>, Option<f64>)> = HashMap::new();
+    for bid in summary.bids.iter().filter(|level| usable(level.price)) {
+        let (best_bid, _) = best.entry(&bid.exchange).or_default();
+        if best_bid.is_none_or(|price| bid.price > price) {
+            *best_bid = Some(bid.price);
+        }
+    }
+    for ask in summary.asks.iter().filter(|level| usable(level.price)) {
+        let (_, best_ask) = best.entry(&ask.exchange).or_default();
+        if best_ask.is_none_or(|price| ask.price < price) {
+            *best_ask = Some(ask.price);
+        }
+    }
+
+    best.into_iter()
+        .filter_map(|(exchange, (bid, ask))| Some((exchange.clone(), (bid? + ask?) / 2.0)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregator_core::PriceLevel;
+
+    fn book(
+        symbol: &str,
+        exchange: Exchange,
+        market_type: Option<MarketType>,
+        bid: f64,
+        ask: f64,
+    ) -> Summary {
+        let level = |price| PriceLevel {
+            price,
+            quantity: 1.0,
+            exchange: exchange.clone(),
+            timestamp: Utc::now(),
+        };
+        Summary {
+            symbol: symbol.to_string(),
+            spread: ask - bid,
+            bids: vec![level(bid)],
+            asks: vec![level(ask)],
+            timestamp: Utc::now(),
+            market_type,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_basis_pairs_every_spot_and_perp_venue() {
+        let analyzer = BasisAnalyzer::default();
+        analyzer
+            .record_summary(&book("BTCUSDT", Exchange::Binance, None, 49990.0, 50010.0))
+            .await;
+        analyzer
+            .record_summary(&book(
+                "BTC-USDT",
+                Exchange::Bybit,
+                Some(MarketType::Spot),
+                50040.0,
+                50060.0,
+            ))
+            .await;
+        let published = analyzer
+            .record_summary(&book(
+                "BTCUSDT",
+                Exchange::Binance,
+                Some(MarketType::Futures),
+                50190.0,
+                50210.0,
+            ))
+            .await;
+
+        let pairings = analyzer.basis("BTC/USDT").await;
+        assert_eq!(pairings.len(), 2);
+        assert_eq!(pairings[0].spot_exchange, Exchange::Binance);
+        assert_eq!(pairings[0].basis, 200.0);
+        assert!((pairings[0].basis_percentage - 0.4).abs() < 1e-12);
+        assert_eq!(pairings[1].spot_exchange, Exchange::Bybit);
+        assert_eq!(pairings[1].basis, 150.0);
+
+        // Both pairings clear the default 0.2% threshold
+        assert_eq!(published.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_funding_is_annualized_and_can_trigger_alone() {
+        let analyzer = BasisAnalyzer::new(BasisConfig {
+            min_basis_percentage: 1.0,
+            min_annualized_carry: 10.0,
+            funding_interval_hours: 8.0,
+        });
+        let mut receiver = analyzer.subscribe();
+
+        let spot = book("ETHUSDT", Exchange::Kraken, None, 2999.0, 3001.0);
+        let perp = book(
+            "ETHUSDT",
+            Exchange::Bybit,
+            Some(MarketType::Futures),
+            3002.0,
+            3004.0,
+        );
+        analyzer.record_summary(&spot).await;
+        assert!(analyzer.record_summary(&perp).await.is_empty());
+
+        analyzer
+            .record_update(&PriceLevelUpdate {
+                id: uuid::Uuid::new_v4(),
+                symbol: "ETHUSDT".to_string(),
+                exchange: Exchange::Bybit,
+                bids: vec![],
+                asks: vec![],
+                timestamp: Utc::now(),
+                funding: Some(FundingRate {
+                    rate: 0.0001,
+                    mark_price: None,
+                    next_funding_time: None,
+                }),
+                event_time: None,
+                market_type: Some(MarketType::Futures),
+            })
+            .await;
+
+        // 0.01% three times a day is 10.95% a year
+        let published = analyzer.record_summary(&perp).await;
+        assert_eq!(published.len(), 1);
+        let carry = published[0].annualized_carry.unwrap();
+        assert!((carry - 10.95).abs() < 1e-9);
+        assert_eq!(receiver.recv().await.unwrap(), published[0]);
+    }
+}
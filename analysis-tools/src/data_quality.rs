@@ -0,0 +1,578 @@
+//! # Data Quality Module
+//!
+//! This module watches exchange feeds for data that should not be traded on: a mid price that
+//! jumps by half in one update, a book with an empty side, a book whose best bid reaches its
+//! best ask, or an exchange whose update rate collapses. Each finding is published as a
+//! `DataQualityEvent`, and the affected book, or the whole exchange for a rate collapse, is
+//! quarantined so `StreamingAnalysisEngine::with_data_quality` leaves it out of arbitrage
+//! detection until it recovers.
+
+use crate::normalize_symbol;
+use aggregator_core::{Event, EventBus, Exchange, Result, Summary};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Data Quality Config
+///
+/// Detection thresholds and quarantine settings.
+///
+/// ## Fields
+///
+/// - `max_price_jump_percentage`: A change of the mid price between consecutive updates of a
+///   book larger than this percentage is a price jump.
+/// - `quarantine_secs`: How long a book stays excluded after its last price jump, empty side
+///   or crossed state.
+/// - `rate_window_secs`: Window the recent update rate of an exchange is measured over.
+/// - `baseline_window_secs`: Window the usual update rate of an exchange is measured over.
+///   Collapses are only detected once an exchange has been updating for this long.
+/// - `min_rate_ratio`: The update rate has collapsed when the recent rate falls below this
+///   fraction of the baseline rate.
+/// - `min_baseline_rate`: Baseline updates per second below which an exchange is too quiet
+///   for a collapse to be told apart from a lull.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataQualityConfig {
+    pub max_price_jump_percentage: f64,
+    pub quarantine_secs: u64,
+    pub rate_window_secs: u64,
+    pub baseline_window_secs: u64,
+    pub min_rate_ratio: f64,
+    pub min_baseline_rate: f64,
+}
+
+impl Default for DataQualityConfig {
+    fn default() -> Self {
+        Self {
+            max_price_jump_percentage: 50.0,
+            quarantine_secs: 30,
+            rate_window_secs: 10,
+            baseline_window_secs: 300,
+            min_rate_ratio: 0.1,
+            min_baseline_rate: 0.5,
+        }
+    }
+}
+
+/// # Data Quality Issue
+///
+/// What was found wrong with a feed.
+///
+/// - `PriceJump`: The mid price moved by `change_percentage` from `previous_mid` to `mid` in
+///   one update.
+/// - `EmptyBook`: The book has no levels on the flagged sides.
+/// - `CrossedBook`: The book's best bid is at or above its best ask.
+/// - `UpdateRateCollapse`: The exchange's recent updates per second fell to `recent_rate`
+///   from a usual `baseline_rate`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DataQualityIssue {
+    PriceJump {
+        previous_mid: f64,
+        mid: f64,
+        change_percentage: f64,
+    },
+    EmptyBook {
+        empty_bids: bool,
+        empty_asks: bool,
+    },
+    CrossedBook {
+        best_bid: f64,
+        best_ask: f64,
+    },
+    UpdateRateCollapse {
+        recent_rate: f64,
+        baseline_rate: f64,
+    },
+}
+
+/// # Data Quality Event
+///
+/// A data quality issue found on one exchange.
+///
+/// ## Fields
+///
+/// - `exchange`: The exchange whose feed the issue was found in.
+/// - `symbol`: The book the issue was found in, with separators removed. `None` for issues
+///   affecting the whole exchange.
+/// - `issue`: What was found.
+/// - `timestamp`: When it was found.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataQualityEvent {
+    pub exchange: Exchange,
+    pub symbol: Option<String>,
+    pub issue: DataQualityIssue,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Event for DataQualityEvent {
+    const TOPIC: &'static str = "data-quality";
+}
+
+/// Times of the recent updates of one exchange.
+#[derive(Debug)]
+struct UpdateTimes {
+    first_seen: DateTime<Utc>,
+    times: VecDeque<DateTime<Utc>>,
+}
+
+/// Everything the monitor remembers between updates.
+#[derive(Debug, Default)]
+struct QualityState {
+    mids: HashMap<(Exchange, String), f64>,
+    updates: HashMap<Exchange, UpdateTimes>,
+    quarantined_until: HashMap<(Exchange, String), DateTime<Utc>>,
+    collapsed: HashSet<Exchange>,
+}
+
+/// # Data Quality Monitor
+///
+/// Checks every book it is given and the update rate of every exchange. Feed it summaries
+/// with [`DataQualityMonitor::record_summary`] or [`DataQualityMonitor::spawn`], ask which
+/// books to leave out with [`DataQualityMonitor::excluded_exchanges`], and read events from
+/// [`DataQualityMonitor::subscribe`]. Cloning the monitor shares its state and channel.
+///
+/// ## Fields
+///
+/// - `config`: Thresholds and quarantine settings.
+/// - `state`: Latest mids, update times and quarantines.
+#[derive(Debug, Clone)]
+pub struct DataQualityMonitor {
+    config: DataQualityConfig,
+    state: Arc<RwLock<QualityState>>,
+    event_sender: broadcast::Sender<DataQualityEvent>,
+}
+
+impl DataQualityMonitor {
+    /// ## New
+    ///
+    /// Creates a monitor with the given thresholds and no history.
+    pub fn new(config: DataQualityConfig) -> Self {
+        let (event_sender, _) = broadcast::channel(1000);
+        Self {
+            config,
+            state: Arc::new(RwLock::new(QualityState::default())),
+            event_sender,
+        }
+    }
+
+    /// ## With Event Bus
+    ///
+    /// Creates a monitor that publishes its events on the `data-quality` topic of `bus`, so
+    /// they reach `Aggregator::subscribe::<DataQualityEvent>()` receivers as well as its own.
+    pub fn with_event_bus(config: DataQualityConfig, bus: &EventBus) -> Self {
+        Self {
+            config,
+            state: Arc::new(RwLock::new(QualityState::default())),
+            event_sender: bus.sender(),
+        }
+    }
+
+    /// ## Subscribe
+    ///
+    /// Returns a receiver for every event the monitor publishes.
+    pub fn subscribe(&self) -> broadcast::Receiver<DataQualityEvent> {
+        self.event_sender.subscribe()
+    }
+
+    /// ## Record Summary
+    ///
+    /// Checks the book of every exchange the summary carries levels for; a consolidated
+    /// summary is split by the exchange on each level. A summary with no levels at all cannot
+    /// be attributed to an exchange and is ignored, use `record_book` for those.
+    ///
+    /// ### Returns
+    ///
+    /// The events published.
+    pub async fn record_summary(&self, summary: &Summary) -> Vec<DataQualityEvent> {
+        let mut exchanges: Vec<&Exchange> = Vec::new();
+        for level in summary.bids.iter().chain(&summary.asks) {
+            if !exchanges.contains(&&level.exchange) {
+                exchanges.push(&level.exchange);
+            }
+        }
+
+        let mut events = Vec::new();
+        for exchange in exchanges {
+            events.extend(self.record_book(exchange, summary).await);
+        }
+        events
+    }
+
+    /// ## Record Book
+    ///
+    /// Checks the levels of `exchange` in `summary` for an empty side, a crossed book and a
+    /// price jump since the book's previous update, and counts the update towards the
+    /// exchange's rate. A book with an issue is quarantined for `quarantine_secs`.
+    ///
+    /// ### Returns
+    ///
+    /// The events published.
+    pub async fn record_book(
+        &self,
+        exchange: &Exchange,
+        summary: &Summary,
+    ) -> Vec<DataQualityEvent> {
+        let symbol = normalize_symbol(&summary.symbol);
+        let now = summary.timestamp;
+        let best_bid = summary
+            .bids
+            .iter()
+            .filter(|level| &level.exchange == exchange)
+            .map(|level| level.price)
+            .reduce(f64::max);
+        let best_ask = summary
+            .asks
+            .iter()
+            .filter(|level| &level.exchange == exchange)
+            .map(|level| level.price)
+            .reduce(f64::min);
+
+        let mut issues = Vec::new();
+        let mut state = self.state.write().await;
+        match (best_bid, best_ask) {
+            (Some(best_bid), Some(best_ask)) if best_bid >= best_ask => {
+                issues.push(DataQualityIssue::CrossedBook { best_bid, best_ask });
+            }
+            (Some(best_bid), Some(best_ask)) => {
+                let mid = (best_bid + best_ask) / 2.0;
+                let key = (exchange.clone(), symbol.clone());
+                if let Some(previous_mid) = state.mids.insert(key, mid) {
+                    let change_percentage = (mid - previous_mid).abs() / previous_mid * 100.0;
+                    if change_percentage > self.config.max_price_jump_percentage {
+                        issues.push(DataQualityIssue::PriceJump {
+                            previous_mid,
+                            mid,
+                            change_percentage,
+                        });
+                    }
+                }
+            }
+            (best_bid, best_ask) => {
+                issues.push(DataQualityIssue::EmptyBook {
+                    empty_bids: best_bid.is_none(),
+                    empty_asks: best_ask.is_none(),
+                });
+            }
+        }
+
+        let updates = state
+            .updates
+            .entry(exchange.clone())
+            .or_insert_with(|| UpdateTimes {
+                first_seen: now,
+                times: VecDeque::new(),
+            });
+        updates.times.push_back(now);
+        let cutoff = now - Duration::seconds(self.config.baseline_window_secs as i64);
+        while updates.times.front().is_some_and(|time| *time < cutoff) {
+            updates.times.pop_front();
+        }
+
+        if !issues.is_empty() {
+            let until = now + Duration::seconds(self.config.quarantine_secs as i64);
+            state
+                .quarantined_until
+                .insert((exchange.clone(), symbol.clone()), until);
+        }
+        drop(state);
+
+        let events: Vec<DataQualityEvent> = issues
+            .into_iter()
+            .map(|issue| DataQualityEvent {
+                exchange: exchange.clone(),
+                symbol: Some(symbol.clone()),
+                issue,
+                timestamp: now,
+            })
+            .collect();
+        self.publish(&events);
+        events
+    }
+
+    /// ## Check Update Rates
+    ///
+    /// Compares each exchange's update rate over the last `rate_window_secs` with its rate
+    /// over the rest of the baseline window. An exchange whose rate collapses is reported
+    /// once and excluded until its rate recovers.
+    ///
+    /// ### Returns
+    ///
+    /// The events published.
+    pub async fn check_update_rates(&self, now: DateTime<Utc>) -> Vec<DataQualityEvent> {
+        let rate_window = Duration::seconds(self.config.rate_window_secs as i64);
+        let baseline_window = Duration::seconds(self.config.baseline_window_secs as i64);
+        let baseline_secs = (baseline_window - rate_window).num_milliseconds() as f64 / 1000.0;
+        if baseline_secs <= 0.0 {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        let mut state = self.state.write().await;
+        let QualityState {
+            updates, collapsed, ..
+        } = &mut *state;
+        for (exchange, times) in updates.iter() {
+            if now - times.first_seen < baseline_window {
+                continue;
+            }
+
+            let recent_start = now - rate_window;
+            let baseline_start = now - baseline_window;
+            let recent = times
+                .times
+                .iter()
+                .filter(|time| **time >= recent_start)
+                .count();
+            let baseline = times
+                .times
+                .iter()
+                .filter(|time| **time >= baseline_start && **time < recent_start)
+                .count();
+            let recent_rate = recent as f64 / rate_window.num_milliseconds().max(1) as f64 * 1000.0;
+            let baseline_rate = baseline as f64 / baseline_secs;
+
+            let collapsing = baseline_rate >= self.config.min_baseline_rate
+                && recent_rate < baseline_rate * self.config.min_rate_ratio;
+            if !collapsing {
+                collapsed.remove(exchange);
+            } else if collapsed.insert(exchange.clone()) {
+                events.push(DataQualityEvent {
+                    exchange: exchange.clone(),
+                    symbol: None,
+                    issue: DataQualityIssue::UpdateRateCollapse {
+                        recent_rate,
+                        baseline_rate,
+                    },
+                    timestamp: now,
+                });
+            }
+        }
+        drop(state);
+
+        self.publish(&events);
+        events
+    }
+
+    /// ## Excluded Exchanges
+    ///
+    /// Returns the exchanges whose book for `symbol` should be left out of detection at
+    /// `now`: those quarantined for a book issue and those whose update rate has collapsed.
+    pub async fn excluded_exchanges(&self, symbol: &str, now: DateTime<Utc>) -> HashSet<Exchange> {
+        let symbol = normalize_symbol(symbol);
+        let state = self.state.read().await;
+        let mut excluded = state.collapsed.clone();
+        excluded.extend(
+            state
+                .quarantined_until
+                .iter()
+                .filter(|((_, quarantined), until)| *quarantined == symbol && **until > now)
+                .map(|((exchange, _), _)| exchange.clone()),
+        );
+        excluded
+    }
+
+    /// ## Is Excluded
+    ///
+    /// Whether the book of `exchange` for `symbol` should be left out of detection at `now`.
+    pub async fn is_excluded(&self, exchange: &Exchange, symbol: &str, now: DateTime<Utc>) -> bool {
+        self.excluded_exchanges(symbol, now)
+            .await
+            .contains(exchange)
+    }
+
+    /// ## Spawn
+    ///
+    /// Spawns a task that records every summary received on `summary_rx`, typically from
+    /// `Aggregator::subscribe_summaries()`, and checks update rates every second, until the
+    /// channel closes. Summaries a lagging monitor skipped count as missing updates.
+    pub fn spawn(&self, mut summary_rx: broadcast::Receiver<Summary>) -> JoinHandle<Result<()>> {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                tokio::select! {
+                    summary = summary_rx.recv() => match summary {
+                        Ok(summary) => {
+                            monitor.record_summary(&summary).await;
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Data quality monitor lagged, skipped {} summaries", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = ticker.tick() => {
+                        monitor.check_update_rates(Utc::now()).await;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// ## Spawn Rate Checks
+    ///
+    /// Spawns a task that checks update rates every second, for monitors fed by another
+    /// component such as `StreamingAnalysisEngine::with_data_quality` rather than by `spawn`.
+    pub fn spawn_rate_checks(&self) -> JoinHandle<Result<()>> {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                ticker.tick().await;
+                monitor.check_update_rates(Utc::now()).await;
+            }
+        })
+    }
+
+    fn publish(&self, events: &[DataQualityEvent]) {
+        for event in events {
+            warn!(
+                "Data quality issue on {} {}: {:?}",
+                event.exchange,
+                event.symbol.as_deref().unwrap_or("*"),
+                event.issue
+            );
+            // Sending only fails when nobody is subscribed
+            let _ = self.event_sender.send(event.clone());
+        }
+    }
+}
+
+impl Default for DataQualityMonitor {
+    fn default() -> Self {
+        Self::new(DataQualityConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregator_core::PriceLevel;
+
+    fn book(exchange: Exchange, bids: &[f64], asks: &[f64], timestamp: DateTime<Utc>) -> Summary {
+        let levels = |prices: &[f64]| {
+            prices
+                .iter()
+                .map(|&price| PriceLevel {
+                    price,
+                    quantity: 1.0,
+                    exchange: exchange.clone(),
+                    timestamp,
+                })
+                .collect()
+        };
+        Summary {
+            symbol: "BTC-USDT".to_string(),
+            spread: 0.0,
+            bids: levels(bids),
+            asks: levels(asks),
+            timestamp,
+            market_type: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_book_issues_are_flagged_and_quarantined() {
+        let monitor = DataQualityMonitor::default();
+        let now = Utc::now();
+
+        let events = monitor
+            .record_summary(&book(Exchange::Binance, &[100.0], &[101.0], now))
+            .await;
+        assert!(events.is_empty());
+
+        let events = monitor
+            .record_summary(&book(Exchange::Binance, &[200.0], &[201.0], now))
+            .await;
+        assert!(matches!(
+            events[0].issue,
+            DataQualityIssue::PriceJump { previous_mid, .. } if previous_mid == 100.5
+        ));
+
+        let events = monitor
+            .record_book(&Exchange::Bybit, &book(Exchange::Bybit, &[], &[], now))
+            .await;
+        assert_eq!(
+            events[0].issue,
+            DataQualityIssue::EmptyBook {
+                empty_bids: true,
+                empty_asks: true
+            }
+        );
+
+        let events = monitor
+            .record_summary(&book(Exchange::Kraken, &[101.0], &[100.0], now))
+            .await;
+        assert_eq!(
+            events[0].issue,
+            DataQualityIssue::CrossedBook {
+                best_bid: 101.0,
+                best_ask: 100.0
+            }
+        );
+
+        let excluded = monitor.excluded_exchanges("BTCUSDT", now).await;
+        assert_eq!(
+            excluded,
+            HashSet::from([Exchange::Binance, Exchange::Bybit, Exchange::Kraken])
+        );
+        assert!(monitor
+            .excluded_exchanges("BTCUSDT", now + Duration::seconds(31))
+            .await
+            .is_empty());
+        assert!(monitor.excluded_exchanges("ETHUSDT", now).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_rate_collapse_excludes_exchange_until_recovery() {
+        let monitor = DataQualityMonitor::new(DataQualityConfig {
+            rate_window_secs: 10,
+            baseline_window_secs: 60,
+            ..Default::default()
+        });
+        let start = Utc::now() - Duration::seconds(60);
+
+        // Two updates a second for 50 seconds, then silence
+        for i in 0..100 {
+            let at = start + Duration::milliseconds(i * 500);
+            monitor
+                .record_summary(&book(Exchange::Binance, &[100.0], &[101.0], at))
+                .await;
+        }
+
+        let now = start + Duration::seconds(61);
+        let events = monitor.check_update_rates(now).await;
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0].issue,
+            DataQualityIssue::UpdateRateCollapse { recent_rate, .. } if recent_rate == 0.0
+        ));
+        assert!(
+            monitor
+                .is_excluded(&Exchange::Binance, "ETHUSDT", now)
+                .await
+        );
+
+        // Reported once while it lasts
+        assert!(monitor.check_update_rates(now).await.is_empty());
+
+        for i in 0..20 {
+            let at = now + Duration::milliseconds(i * 500);
+            monitor
+                .record_summary(&book(Exchange::Binance, &[100.0], &[101.0], at))
+                .await;
+        }
+        let later = now + Duration::seconds(10);
+        assert!(monitor.check_update_rates(later).await.is_empty());
+        assert!(
+            !monitor
+                .is_excluded(&Exchange::Binance, "ETHUSDT", later)
+                .await
+        );
+    }
+}
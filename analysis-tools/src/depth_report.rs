@@ -0,0 +1,313 @@
+//! # Depth Report Module
+//!
+//! This module measures how much liquidity sits close to the mid price of a symbol and which
+//! exchanges hold it. For each band around the mid, e.g. within 10, 25 and 50 basis points,
+//! a report gives the bid and ask liquidity per exchange and a Herfindahl index of how
+//! concentrated that liquidity is, which helps decide where to route size.
+
+use crate::normalize_symbol;
+use aggregator_core::{Exchange, PriceLevel, Summary};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Bands reported when none are requested, in basis points from the mid price.
+pub const DEFAULT_DEPTH_BANDS_BPS: [f64; 3] = [10.0, 25.0, 50.0];
+
+/// # Depth Liquidity
+///
+/// Resting liquidity within one band.
+///
+/// ## Fields
+///
+/// - `bid_quantity`, `ask_quantity`: Base quantity on each side.
+/// - `bid_notional`, `ask_notional`: Quote value on each side, price times quantity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DepthLiquidity {
+    pub bid_quantity: f64,
+    pub ask_quantity: f64,
+    pub bid_notional: f64,
+    pub ask_notional: f64,
+}
+
+impl DepthLiquidity {
+    /// Quote value of both sides.
+    pub fn notional(&self) -> f64 {
+        self.bid_notional + self.ask_notional
+    }
+
+    fn add_bid(&mut self, level: &PriceLevel) {
+        self.bid_quantity += level.quantity;
+        self.bid_notional += level.price * level.quantity;
+    }
+
+    fn add_ask(&mut self, level: &PriceLevel) {
+        self.ask_quantity += level.quantity;
+        self.ask_notional += level.price * level.quantity;
+    }
+}
+
+/// # Depth Band
+///
+/// Liquidity within `within_bps` basis points of the mid price.
+///
+/// ## Fields
+///
+/// - `within_bps`: Half-width of the band: bids at or above `mid * (1 - bps / 10000)` and
+///   asks at or below `mid * (1 + bps / 10000)` are counted.
+/// - `total`: Liquidity across all exchanges.
+/// - `by_exchange`: Liquidity of each exchange with any inside the band.
+/// - `herfindahl_index`: Sum of the squared shares of each exchange in the band's notional,
+///   from `1 / n` when `n` exchanges hold equal shares to 1 when one holds it all. 0 for an
+///   empty band.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthBand {
+    pub within_bps: f64,
+    pub total: DepthLiquidity,
+    pub by_exchange: BTreeMap<Exchange, DepthLiquidity>,
+    pub herfindahl_index: f64,
+}
+
+/// # Depth Report
+///
+/// Liquidity near the mid price of one symbol, per band and exchange.
+///
+/// ## Fields
+///
+/// - `symbol`: The symbol the report is for.
+/// - `mid_price`: Midpoint of the best bid and best ask across all exchanges.
+/// - `bands`: One entry per requested band, narrowest first.
+/// - `timestamp`: Time of the newest summary the report was built from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthReport {
+    pub symbol: String,
+    pub mid_price: f64,
+    pub bands: Vec<DepthBand>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl DepthReport {
+    /// ## From Summaries
+    ///
+    /// Builds the report for `symbol` from the levels of `summaries`, which may be
+    /// per-exchange or consolidated; levels are attributed to the exchange they carry. Every
+    /// summary's levels are counted, so each exchange's book should be passed once.
+    ///
+    /// ### Returns
+    ///
+    /// `None` when there is no usable bid or ask to take a mid price from.
+    pub fn from_summaries<'a>(
+        symbol: &str,
+        summaries: impl IntoIterator<Item = &'a Summary>,
+        bands_bps: &[f64],
+    ) -> Option<Self> {
+        let usable = |level: &&PriceLevel| {
+            level.price.is_finite()
+                && level.price > 0.0
+                && level.quantity.is_finite()
+                && level.quantity > 0.0
+        };
+
+        let mut bids = Vec::new();
+        let mut asks = Vec::new();
+        let mut timestamp = None;
+        for summary in summaries {
+            bids.extend(summary.bids.iter().filter(usable));
+            asks.extend(summary.asks.iter().filter(usable));
+            timestamp = timestamp.max(Some(summary.timestamp));
+        }
+
+        let best_bid = bids.iter().map(|level| level.price).reduce(f64::max)?;
+        let best_ask = asks.iter().map(|level| level.price).reduce(f64::min)?;
+        let mid_price = (best_bid + best_ask) / 2.0;
+
+        let mut bands_bps = bands_bps.to_vec();
+        bands_bps.sort_by(f64::total_cmp);
+        let bands = bands_bps
+            .into_iter()
+            .map(|within_bps| {
+                let offset = mid_price * within_bps / 10_000.0;
+                let mut by_exchange: BTreeMap<Exchange, DepthLiquidity> = BTreeMap::new();
+                for bid in bids.iter().filter(|bid| bid.price >= mid_price - offset) {
+                    by_exchange
+                        .entry(bid.exchange.clone())
+                        .or_default()
+                        .add_bid(bid);
+                }
+                for ask in asks.iter().filter(|ask| ask.price <= mid_price + offset) {
+                    by_exchange
+                        .entry(ask.exchange.clone())
+                        .or_default()
+                        .add_ask(ask);
+                }
+                Self::band(within_bps, by_exchange)
+            })
+            .collect();
+
+        Some(Self {
+            symbol: symbol.to_string(),
+            mid_price,
+            bands,
+            timestamp: timestamp?,
+        })
+    }
+
+    /// Totals one band and computes its concentration.
+    fn band(within_bps: f64, by_exchange: BTreeMap<Exchange, DepthLiquidity>) -> DepthBand {
+        let mut total = DepthLiquidity::default();
+        for liquidity in by_exchange.values() {
+            total.bid_quantity += liquidity.bid_quantity;
+            total.ask_quantity += liquidity.ask_quantity;
+            total.bid_notional += liquidity.bid_notional;
+            total.ask_notional += liquidity.ask_notional;
+        }
+
+        let notional = total.notional();
+        let herfindahl_index = if notional > 0.0 {
+            by_exchange
+                .values()
+                .map(|liquidity| (liquidity.notional() / notional).powi(2))
+                .sum()
+        } else {
+            0.0
+        };
+
+        DepthBand {
+            within_bps,
+            total,
+            by_exchange,
+            herfindahl_index,
+        }
+    }
+}
+
+/// ## Depth Reports
+///
+/// Groups `summaries` by symbol, treating `BTC-USDT`, `BTC/USDT` and `BTCUSDT` as the same
+/// symbol, and builds one `DepthReport` per symbol with liquidity inside each of
+/// `bands_bps`, see [`DEFAULT_DEPTH_BANDS_BPS`].
+///
+/// ### Returns
+///
+/// The reports sorted by symbol. Symbols without both a bid and an ask are left out.
+pub fn depth_reports(summaries: &HashMap<String, Summary>, bands_bps: &[f64]) -> Vec<DepthReport> {
+    let mut by_symbol: BTreeMap<String, Vec<&Summary>> = BTreeMap::new();
+    for summary in summaries.values() {
+        by_symbol
+            .entry(normalize_symbol(&summary.symbol))
+            .or_default()
+            .push(summary);
+    }
+
+    by_symbol
+        .into_iter()
+        .filter_map(|(symbol, summaries)| {
+            DepthReport::from_summaries(&symbol, summaries, bands_bps)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(exchange: Exchange, price: f64, quantity: f64) -> PriceLevel {
+        PriceLevel {
+            price,
+            quantity,
+            exchange,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn summary(symbol: &str, bids: Vec<PriceLevel>, asks: Vec<PriceLevel>) -> Summary {
+        Summary {
+            symbol: symbol.to_string(),
+            spread: 0.0,
+            bids,
+            asks,
+            timestamp: Utc::now(),
+            market_type: None,
+        }
+    }
+
+    #[test]
+    fn test_depth_bands_around_mid() {
+        // Mid is 10000, so 10 bps is ±10, 25 bps ±25 and 50 bps ±50
+        let binance = summary(
+            "BTCUSDT",
+            vec![
+                level(Exchange::Binance, 9995.0, 1.0),
+                level(Exchange::Binance, 9960.0, 4.0),
+            ],
+            vec![level(Exchange::Binance, 10005.0, 1.0)],
+        );
+        let bybit = summary(
+            "BTCUSDT",
+            vec![level(Exchange::Bybit, 9980.0, 2.0)],
+            vec![
+                level(Exchange::Bybit, 10020.0, 3.0),
+                level(Exchange::Bybit, 10100.0, 9.0),
+            ],
+        );
+
+        let report =
+            DepthReport::from_summaries("BTCUSDT", [&binance, &bybit], &DEFAULT_DEPTH_BANDS_BPS)
+                .unwrap();
+        assert_eq!(report.mid_price, 10000.0);
+        assert_eq!(report.bands.len(), 3);
+
+        // Within 10 bps only Binance's top level on each side counts
+        let narrow = &report.bands[0];
+        assert_eq!(narrow.by_exchange.len(), 1);
+        assert_eq!(narrow.total.bid_quantity, 1.0);
+        assert_eq!(narrow.total.ask_quantity, 1.0);
+        assert_eq!(narrow.herfindahl_index, 1.0);
+
+        let middle = &report.bands[1];
+        assert_eq!(middle.total.bid_quantity, 3.0);
+        assert_eq!(middle.total.ask_quantity, 4.0);
+        assert_eq!(middle.by_exchange[&Exchange::Bybit].ask_notional, 30060.0);
+
+        let wide = &report.bands[2];
+        assert_eq!(wide.total.bid_quantity, 7.0);
+        assert_eq!(wide.total.ask_quantity, 4.0);
+        let binance_notional: f64 = 9995.0 + 4.0 * 9960.0 + 10005.0;
+        let bybit_notional: f64 = 2.0 * 9980.0 + 3.0 * 10020.0;
+        let total = binance_notional + bybit_notional;
+        let expected = (binance_notional / total).powi(2) + (bybit_notional / total).powi(2);
+        assert!((wide.herfindahl_index - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_depth_reports_group_symbols() {
+        let summaries = HashMap::from([
+            (
+                "binance".to_string(),
+                summary(
+                    "BTC-USDT",
+                    vec![level(Exchange::Binance, 99.0, 1.0)],
+                    vec![level(Exchange::Binance, 101.0, 1.0)],
+                ),
+            ),
+            (
+                "bybit".to_string(),
+                summary(
+                    "BTCUSDT",
+                    vec![level(Exchange::Bybit, 99.0, 1.0)],
+                    vec![level(Exchange::Bybit, 101.0, 1.0)],
+                ),
+            ),
+            (
+                "kraken".to_string(),
+                summary("ETHUSDT", vec![level(Exchange::Kraken, 10.0, 1.0)], vec![]),
+            ),
+        ]);
+
+        let reports = depth_reports(&summaries, &[100.0]);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].symbol, "BTCUSDT");
+        assert_eq!(reports[0].bands[0].total.bid_quantity, 2.0);
+        assert!((reports[0].bands[0].herfindahl_index - 0.5).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,348 @@
+//! # Execution Simulation Module
+//!
+//! This module estimates what a market order would cost if it were routed across every
+//! exchange quoting a symbol. The latest book of each exchange is merged into one ladder and
+//! the order takes the best prices first, wherever they are, which is what a smart order
+//! router does with no fees or latency to weigh. The result gives the expected fill price, the
+//! slippage against the consolidated mid and how much of the order each venue would fill.
+
+use crate::normalize_symbol;
+use aggregator_core::{Exchange, FillEstimate, PriceLevel, Result, Summary, TradeSide};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Venue Allocation
+///
+/// The part of a simulated order filled on one exchange.
+///
+/// ## Fields
+///
+/// - `exchange`: The exchange the part is routed to.
+/// - `quantity`: Base quantity filled there.
+/// - `average_price`: Volume-weighted price of that quantity.
+/// - `notional`: Quote value of that quantity.
+/// - `share`: Fraction of the filled quantity, from 0 to 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenueAllocation {
+    pub exchange: Exchange,
+    pub quantity: f64,
+    pub average_price: f64,
+    pub notional: f64,
+    pub share: f64,
+}
+
+/// # Execution Simulation
+///
+/// Expected execution of a market order against the consolidated book of one symbol.
+///
+/// ## Fields
+///
+/// - `symbol`: The symbol simulated, normalized to e.g. `BTCUSDT`.
+/// - `fill`: The walk through the consolidated ladder: filled quantity, average (expected fill)
+///   price, worst price and total cost.
+/// - `mid_price`: Midpoint of the best bid and best ask across exchanges, `None` when one side
+///   of the book is empty.
+/// - `slippage_percentage`: How far `fill.average_price` is from `mid_price`, as a percentage;
+///   positive means the order pays to cross the spread and walk the book. `None` without a mid.
+/// - `allocations`: One entry per exchange the order reaches, largest quantity first.
+/// - `timestamp`: Time of the newest book the simulation used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionSimulation {
+    pub symbol: String,
+    pub fill: FillEstimate,
+    pub mid_price: Option<f64>,
+    pub slippage_percentage: Option<f64>,
+    pub allocations: Vec<VenueAllocation>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ExecutionSimulation {
+    /// ## From Summaries
+    ///
+    /// Simulates a market order of `quantity` on `side` against the levels of `summaries`,
+    /// which may be per-exchange or consolidated; levels are attributed to the exchange they
+    /// carry. Each exchange's book should be passed once.
+    ///
+    /// ### Returns
+    ///
+    /// `None` when `quantity` is not positive or the side the order takes from is empty.
+    pub fn from_summaries<'a>(
+        symbol: &str,
+        summaries: impl IntoIterator<Item = &'a Summary>,
+        side: TradeSide,
+        quantity: f64,
+    ) -> Option<Self> {
+        let usable = |level: &&PriceLevel| {
+            level.price.is_finite()
+                && level.price > 0.0
+                && level.quantity.is_finite()
+                && level.quantity > 0.0
+        };
+
+        let mut bids: Vec<&PriceLevel> = Vec::new();
+        let mut asks: Vec<&PriceLevel> = Vec::new();
+        let mut timestamp = None;
+        for summary in summaries {
+            bids.extend(summary.bids.iter().filter(usable));
+            asks.extend(summary.asks.iter().filter(usable));
+            timestamp = timestamp.max(Some(summary.timestamp));
+        }
+        bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+        asks.sort_by(|a, b| a.price.total_cmp(&b.price));
+
+        let mid_price = match (bids.first(), asks.first()) {
+            (Some(bid), Some(ask)) => Some((bid.price + ask.price) / 2.0),
+            _ => None,
+        };
+
+        let ladder = match side {
+            TradeSide::Buy => &asks,
+            TradeSide::Sell => &bids,
+        };
+        let fill = FillEstimate::from_levels(
+            side,
+            ladder.iter().map(|level| (level.price, level.quantity)),
+            quantity,
+        )?;
+
+        // Replay the walk to see which exchange each filled level belongs to
+        let mut by_exchange: BTreeMap<Exchange, (f64, f64)> = BTreeMap::new();
+        let mut remaining = fill.filled_quantity;
+        for level in ladder.iter() {
+            if remaining <= 0.0 {
+                break;
+            }
+            let taken = level.quantity.min(remaining);
+            let (filled, notional) = by_exchange.entry(level.exchange.clone()).or_default();
+            *filled += taken;
+            *notional += taken * level.price;
+            remaining -= taken;
+        }
+
+        let mut allocations: Vec<VenueAllocation> = by_exchange
+            .into_iter()
+            .map(|(exchange, (quantity, notional))| VenueAllocation {
+                exchange,
+                quantity,
+                average_price: notional / quantity,
+                notional,
+                share: quantity / fill.filled_quantity,
+            })
+            .collect();
+        allocations.sort_by(|a, b| b.quantity.total_cmp(&a.quantity));
+
+        Some(Self {
+            symbol: normalize_symbol(symbol),
+            slippage_percentage: mid_price.map(|mid| fill.slippage_percentage(mid)),
+            fill,
+            mid_price,
+            allocations,
+            timestamp: timestamp?,
+        })
+    }
+}
+
+/// # Execution Simulator
+///
+/// Keeps the latest book of every exchange for each symbol, fed from the aggregator's summary
+/// broadcast, and simulates orders against their consolidation. A summary carrying levels from
+/// several exchanges replaces the book of each. Cloning the simulator shares the books.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionSimulator {
+    books: Arc<RwLock<HashMap<(String, Exchange), Summary>>>,
+}
+
+impl ExecutionSimulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ## Record
+    ///
+    /// Stores the levels of `summary` as the latest book of each exchange they come from.
+    pub async fn record(&self, summary: &Summary) {
+        let mut split: HashMap<Exchange, Summary> = HashMap::new();
+        for (level, is_bid) in summary
+            .bids
+            .iter()
+            .map(|level| (level, true))
+            .chain(summary.asks.iter().map(|level| (level, false)))
+        {
+            let book = split
+                .entry(level.exchange.clone())
+                .or_insert_with(|| Summary {
+                    symbol: summary.symbol.clone(),
+                    spread: summary.spread,
+                    bids: Vec::new(),
+                    asks: Vec::new(),
+                    timestamp: summary.timestamp,
+                    market_type: summary.market_type.clone(),
+                });
+            if is_bid {
+                book.bids.push(level.clone());
+            } else {
+                book.asks.push(level.clone());
+            }
+        }
+
+        let symbol = normalize_symbol(&summary.symbol);
+        let mut books = self.books.write().await;
+        for (exchange, book) in split {
+            books.insert((symbol.clone(), exchange), book);
+        }
+    }
+
+    /// ## Simulate Execution
+    ///
+    /// Simulates a market order of `quantity` on `side` for `symbol` against the latest book
+    /// of every exchange quoting it, see [`ExecutionSimulation::from_summaries`]. `BTC-USDT`,
+    /// `BTC/USDT` and `BTCUSDT` name the same symbol.
+    ///
+    /// ### Returns
+    ///
+    /// `None` when no book has liquidity on the side the order takes from. A simulation whose
+    /// `fill` is not complete means the visible books could not absorb the whole order.
+    pub async fn simulate_execution(
+        &self,
+        symbol: &str,
+        side: TradeSide,
+        quantity: f64,
+    ) -> Option<ExecutionSimulation> {
+        let symbol = normalize_symbol(symbol);
+        let books = self.books.read().await;
+        ExecutionSimulation::from_summaries(
+            &symbol,
+            books
+                .iter()
+                .filter(|((s, _), _)| *s == symbol)
+                .map(|(_, book)| book),
+            side,
+            quantity,
+        )
+    }
+
+    /// ## Spawn
+    ///
+    /// Spawns a task that records every summary received on `summary_rx` until the channel
+    /// closes. Lagged receivers skip the missed summaries and keep going.
+    pub fn spawn(&self, mut summary_rx: broadcast::Receiver<Summary>) -> JoinHandle<Result<()>> {
+        let simulator = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match summary_rx.recv().await {
+                    Ok(summary) => simulator.record(&summary).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Execution simulator lagged, skipped {} summaries", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(exchange: Exchange, price: f64, quantity: f64) -> PriceLevel {
+        PriceLevel {
+            price,
+            quantity,
+            exchange,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn summary(symbol: &str, bids: Vec<PriceLevel>, asks: Vec<PriceLevel>) -> Summary {
+        Summary {
+            symbol: symbol.to_string(),
+            spread: 0.0,
+            bids,
+            asks,
+            timestamp: Utc::now(),
+            market_type: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_buy_routes_to_cheapest_asks_first() {
+        let simulator = ExecutionSimulator::new();
+        simulator
+            .record(&summary(
+                "BTC-USDT",
+                vec![level(Exchange::Binance, 99.0, 5.0)],
+                vec![
+                    level(Exchange::Binance, 101.0, 1.0),
+                    level(Exchange::Binance, 103.0, 5.0),
+                ],
+            ))
+            .await;
+        simulator
+            .record(&summary(
+                "BTCUSDT",
+                vec![level(Exchange::Bybit, 98.0, 5.0)],
+                vec![level(Exchange::Bybit, 102.0, 3.0)],
+            ))
+            .await;
+
+        // 5 units take Binance 101, Bybit 102 x3 and Binance 103
+        let simulation = simulator
+            .simulate_execution("BTC/USDT", TradeSide::Buy, 5.0)
+            .await
+            .unwrap();
+        assert_eq!(simulation.symbol, "BTCUSDT");
+        assert!(simulation.fill.is_complete());
+        assert_eq!(simulation.fill.average_price, 102.0);
+        assert_eq!(simulation.mid_price, Some(100.0));
+        assert_eq!(simulation.slippage_percentage, Some(2.0));
+
+        assert_eq!(simulation.allocations.len(), 2);
+        let bybit = &simulation.allocations[0];
+        let binance = &simulation.allocations[1];
+        assert_eq!(bybit.exchange, Exchange::Bybit);
+        assert_eq!(bybit.quantity, 3.0);
+        assert_eq!(bybit.share, 0.6);
+        assert_eq!(binance.average_price, 102.0);
+        assert_eq!(binance.notional, 204.0);
+    }
+
+    #[tokio::test]
+    async fn test_sell_beyond_visible_depth_is_partial() {
+        let simulator = ExecutionSimulator::new();
+        simulator
+            .record(&summary(
+                "ETHUSDT",
+                vec![
+                    level(Exchange::Binance, 10.0, 1.0),
+                    level(Exchange::Kraken, 9.0, 1.0),
+                ],
+                vec![],
+            ))
+            .await;
+
+        let simulation = simulator
+            .simulate_execution("ETHUSDT", TradeSide::Sell, 5.0)
+            .await
+            .unwrap();
+        assert!(!simulation.fill.is_complete());
+        assert_eq!(simulation.fill.filled_quantity, 2.0);
+        assert_eq!(simulation.mid_price, None);
+        assert_eq!(simulation.allocations.len(), 2);
+
+        assert!(simulator
+            .simulate_execution("ETHUSDT", TradeSide::Buy, 1.0)
+            .await
+            .is_none());
+        assert!(simulator
+            .simulate_execution("SOLUSDT", TradeSide::Sell, 1.0)
+            .await
+            .is_none());
+    }
+}
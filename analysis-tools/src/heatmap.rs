@@ -0,0 +1,353 @@
+//! # Depth Heatmap Module
+//!
+//! This module samples consolidated order book summaries into a time × price × quantity
+//! matrix that visualization clients can render as an order book heatmap without storing
+//! and re-aggregating every summary themselves.
+
+use aggregator_core::{PriceLevel, Result, Summary};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Heatmap Config
+///
+/// Resolution and retention settings for a depth heatmap.
+///
+/// ## Fields
+///
+/// - `price_resolution`: Width of a price bucket. Levels are snapped down to the nearest
+///   multiple of this value and their quantities summed.
+/// - `time_resolution_ms`: Width of a time column in milliseconds. Summaries received within
+///   the same column replace each other, so each column holds the latest book state.
+/// - `window_secs`: How far back the heatmap reaches. Older columns are discarded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapConfig {
+    pub price_resolution: f64,
+    pub time_resolution_ms: u64,
+    pub window_secs: u64,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            price_resolution: 1.0,
+            time_resolution_ms: 1000,
+            window_secs: 300,
+        }
+    }
+}
+
+/// A single time column of the heatmap, holding bucketed bid and ask liquidity.
+#[derive(Debug, Clone)]
+struct HeatmapColumn {
+    timestamp: DateTime<Utc>,
+    bids: BTreeMap<i64, f64>,
+    asks: BTreeMap<i64, f64>,
+}
+
+/// # Heatmap Matrix
+///
+/// Dense, serializable view of a heatmap suitable for sending to frontend clients.
+///
+/// ## Fields
+///
+/// - `symbol`: The symbol the heatmap was built for.
+/// - `timestamps`: Start time of each column, oldest first.
+/// - `prices`: Lower bound of each price bucket, ascending.
+/// - `bid_quantities`: `bid_quantities[t][p]` is the bid quantity at `timestamps[t]` and `prices[p]`.
+/// - `ask_quantities`: `ask_quantities[t][p]` is the ask quantity at `timestamps[t]` and `prices[p]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HeatmapMatrix {
+    pub symbol: String,
+    pub price_resolution: f64,
+    pub timestamps: Vec<DateTime<Utc>>,
+    pub prices: Vec<f64>,
+    pub bid_quantities: Vec<Vec<f64>>,
+    pub ask_quantities: Vec<Vec<f64>>,
+}
+
+/// # Depth Heatmap
+///
+/// Rolling heatmap for a single symbol. Feed it summaries with [`DepthHeatmap::record`] and
+/// read it back with [`DepthHeatmap::matrix`].
+#[derive(Debug, Clone)]
+pub struct DepthHeatmap {
+    symbol: String,
+    config: HeatmapConfig,
+    columns: VecDeque<HeatmapColumn>,
+}
+
+impl DepthHeatmap {
+    /// ## New
+    ///
+    /// Creates an empty heatmap for `symbol` using the given resolution and window.
+    pub fn new(symbol: &str, config: HeatmapConfig) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            config,
+            columns: VecDeque::new(),
+        }
+    }
+
+    /// ## Record
+    ///
+    /// Samples a summary into the column covering its timestamp and drops columns that have
+    /// fallen out of the configured window. Summaries older than the newest column are ignored.
+    pub fn record(&mut self, summary: &Summary) {
+        let slot = self.slot_start(summary.timestamp);
+
+        if let Some(last) = self.columns.back() {
+            if slot < last.timestamp {
+                return;
+            }
+        }
+
+        let column = HeatmapColumn {
+            timestamp: slot,
+            bids: self.bucket_levels(&summary.bids),
+            asks: self.bucket_levels(&summary.asks),
+        };
+
+        match self.columns.back_mut() {
+            Some(last) if last.timestamp == slot => *last = column,
+            _ => self.columns.push_back(column),
+        }
+
+        let cutoff = slot - Duration::seconds(self.config.window_secs as i64);
+        while let Some(front) = self.columns.front() {
+            if front.timestamp > cutoff {
+                break;
+            }
+            self.columns.pop_front();
+        }
+    }
+
+    /// ## Matrix
+    ///
+    /// Builds the dense time × price matrix over every price bucket seen in the window.
+    pub fn matrix(&self) -> HeatmapMatrix {
+        let buckets: BTreeSet<i64> = self
+            .columns
+            .iter()
+            .flat_map(|c| c.bids.keys().chain(c.asks.keys()).copied())
+            .collect();
+
+        let row = |levels: &BTreeMap<i64, f64>| -> Vec<f64> {
+            buckets
+                .iter()
+                .map(|b| levels.get(b).copied().unwrap_or(0.0))
+                .collect()
+        };
+
+        HeatmapMatrix {
+            symbol: self.symbol.clone(),
+            price_resolution: self.config.price_resolution,
+            timestamps: self.columns.iter().map(|c| c.timestamp).collect(),
+            prices: buckets
+                .iter()
+                .map(|b| *b as f64 * self.config.price_resolution)
+                .collect(),
+            bid_quantities: self.columns.iter().map(|c| row(&c.bids)).collect(),
+            ask_quantities: self.columns.iter().map(|c| row(&c.asks)).collect(),
+        }
+    }
+
+    /// Returns the number of time columns currently held.
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns `true` if no summaries have been recorded within the window.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    fn slot_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let resolution = self.config.time_resolution_ms.max(1) as i64;
+        let millis = timestamp.timestamp_millis();
+        let slot = millis - millis.rem_euclid(resolution);
+        DateTime::from_timestamp_millis(slot).unwrap_or(timestamp)
+    }
+
+    fn bucket_levels(&self, levels: &[PriceLevel]) -> BTreeMap<i64, f64> {
+        let mut buckets = BTreeMap::new();
+        if self.config.price_resolution <= 0.0 {
+            return buckets;
+        }
+
+        for level in levels {
+            if !level.price.is_finite() || !level.quantity.is_finite() {
+                continue;
+            }
+            let bucket = (level.price / self.config.price_resolution).floor() as i64;
+            *buckets.entry(bucket).or_insert(0.0) += level.quantity;
+        }
+        buckets
+    }
+}
+
+/// # Heatmap Collector
+///
+/// Keeps one [`DepthHeatmap`] per symbol and can be fed directly from the aggregator's
+/// summary broadcast. Cloning the collector shares the underlying heatmaps, so servers can
+/// hold a handle for reads while a background task records.
+#[derive(Debug, Clone)]
+pub struct HeatmapCollector {
+    config: HeatmapConfig,
+    heatmaps: Arc<RwLock<HashMap<String, DepthHeatmap>>>,
+}
+
+impl HeatmapCollector {
+    /// ## New
+    ///
+    /// Creates a collector that builds every per-symbol heatmap with `config`.
+    pub fn new(config: HeatmapConfig) -> Self {
+        Self {
+            config,
+            heatmaps: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// ## Record
+    ///
+    /// Samples a summary into the heatmap for its symbol, creating it on first sight.
+    pub async fn record(&self, summary: &Summary) {
+        let mut heatmaps = self.heatmaps.write().await;
+        heatmaps
+            .entry(summary.symbol.clone())
+            .or_insert_with(|| DepthHeatmap::new(&summary.symbol, self.config.clone()))
+            .record(summary);
+    }
+
+    /// ## Matrix
+    ///
+    /// Returns the current heatmap matrix for `symbol`, if any summaries were recorded for it.
+    pub async fn matrix(&self, symbol: &str) -> Option<HeatmapMatrix> {
+        let heatmaps = self.heatmaps.read().await;
+        heatmaps.get(symbol).map(DepthHeatmap::matrix)
+    }
+
+    /// Returns the symbols that currently have a heatmap.
+    pub async fn symbols(&self) -> Vec<String> {
+        let heatmaps = self.heatmaps.read().await;
+        heatmaps.keys().cloned().collect()
+    }
+
+    /// ## Spawn
+    ///
+    /// Spawns a task that records every summary received on `summary_rx` until the channel
+    /// closes. Lagged receivers skip the missed summaries and keep going.
+    pub fn spawn(&self, mut summary_rx: broadcast::Receiver<Summary>) -> JoinHandle<Result<()>> {
+        let collector = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match summary_rx.recv().await {
+                    Ok(summary) => collector.record(&summary).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Heatmap collector lagged, skipped {} summaries", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Default for HeatmapCollector {
+    fn default() -> Self {
+        Self::new(HeatmapConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregator_core::Exchange;
+
+    fn level(price: f64, quantity: f64) -> PriceLevel {
+        PriceLevel {
+            price,
+            quantity,
+            exchange: Exchange::Binance,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn summary_at(millis: i64, bids: Vec<PriceLevel>, asks: Vec<PriceLevel>) -> Summary {
+        Summary {
+            symbol: "BTCUSDT".to_string(),
+            spread: 0.0,
+            bids,
+            asks,
+            timestamp: DateTime::from_timestamp_millis(millis).unwrap(),
+            market_type: None,
+        }
+    }
+
+    #[test]
+    fn test_heatmap_buckets_prices() {
+        let config = HeatmapConfig {
+            price_resolution: 10.0,
+            time_resolution_ms: 1000,
+            window_secs: 60,
+        };
+        let mut heatmap = DepthHeatmap::new("BTCUSDT", config);
+
+        heatmap.record(&summary_at(
+            0,
+            vec![level(101.0, 1.0), level(105.0, 2.0), level(95.0, 4.0)],
+            vec![level(111.0, 3.0)],
+        ));
+
+        let matrix = heatmap.matrix();
+        assert_eq!(matrix.prices, vec![90.0, 100.0, 110.0]);
+        assert_eq!(matrix.bid_quantities, vec![vec![4.0, 3.0, 0.0]]);
+        assert_eq!(matrix.ask_quantities, vec![vec![0.0, 0.0, 3.0]]);
+    }
+
+    #[test]
+    fn test_heatmap_time_columns_and_window() {
+        let config = HeatmapConfig {
+            price_resolution: 1.0,
+            time_resolution_ms: 1000,
+            window_secs: 2,
+        };
+        let mut heatmap = DepthHeatmap::new("BTCUSDT", config);
+
+        heatmap.record(&summary_at(0, vec![level(100.0, 1.0)], vec![]));
+        // Same column: latest state replaces the earlier one
+        heatmap.record(&summary_at(500, vec![level(100.0, 5.0)], vec![]));
+        assert_eq!(heatmap.len(), 1);
+        assert_eq!(heatmap.matrix().bid_quantities, vec![vec![5.0]]);
+
+        heatmap.record(&summary_at(1000, vec![level(100.0, 2.0)], vec![]));
+        heatmap.record(&summary_at(2500, vec![level(100.0, 3.0)], vec![]));
+
+        // The column at t=0 falls outside the 2s window
+        let matrix = heatmap.matrix();
+        assert_eq!(matrix.timestamps.len(), 2);
+        assert_eq!(matrix.bid_quantities, vec![vec![2.0], vec![3.0]]);
+    }
+
+    #[tokio::test]
+    async fn test_collector_tracks_symbols() {
+        let collector = HeatmapCollector::default();
+        collector
+            .record(&summary_at(
+                0,
+                vec![level(100.0, 1.0)],
+                vec![level(101.0, 1.0)],
+            ))
+            .await;
+
+        assert_eq!(collector.symbols().await, vec!["BTCUSDT".to_string()]);
+        assert!(collector.matrix("BTCUSDT").await.is_some());
+        assert!(collector.matrix("ETHUSDT").await.is_none());
+    }
+}
@@ -0,0 +1,332 @@
+//! # Implied Cross Arbitrage Module
+//!
+//! This module compares the price of a direct pair such as ETH/BTC with the price implied by
+//! two pairs sharing a third currency, ETH/USDT ÷ BTC/USDT. Each leg is taken on whichever
+//! exchange quotes it best, so the direct pair and the cross can trade on different venues.
+//! When the direct ask is below the implied bid, buying ETH with BTC directly and converting
+//! it back through USDT returns more BTC than it cost; when the direct bid is above the
+//! implied ask, the reverse cycle does.
+
+use crate::triangular::TriangularArbitrageDetector;
+use aggregator_core::{Exchange, Summary, TradeSide, TradingPair};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// # Implied Cross Leg
+///
+/// One conversion needed to capture an implied cross opportunity.
+///
+/// ## Fields
+///
+/// - `exchange`: The exchange quoting the best price for this leg.
+/// - `pair`: The pair traded.
+/// - `side`: `Buy` lifts the ask to buy the base with the quote, `Sell` hits the bid to sell
+///   the base for the quote.
+/// - `price`: The best ask for a buy or the best bid for a sell, in quote per base.
+/// - `from`: The currency spent.
+/// - `to`: The currency received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImpliedCrossLeg {
+    pub exchange: Exchange,
+    pub pair: TradingPair,
+    pub side: TradeSide,
+    pub price: f64,
+    pub from: String,
+    pub to: String,
+}
+
+impl ImpliedCrossLeg {
+    /// Units of `to` received for one unit of `from`.
+    pub fn rate(&self) -> f64 {
+        match self.side {
+            TradeSide::Buy => 1.0 / self.price,
+            TradeSide::Sell => self.price,
+        }
+    }
+}
+
+/// # Implied Cross Opportunity
+///
+/// A mispricing between a direct pair and the cross implied through a third currency, at
+/// top-of-book prices.
+///
+/// ## Fields
+///
+/// - `direct_pair`: The pair traded directly, e.g. ETH/BTC.
+/// - `via`: The currency both cross pairs are quoted in, e.g. USDT.
+/// - `direct_price`: The direct ask when buying the base directly, or the direct bid when
+///   selling it.
+/// - `implied_price`: The price of the direct pair implied by the cross on the opposite side:
+///   the implied bid when buying directly, the implied ask when selling.
+/// - `legs`: The three conversions in order; the cycle starts and ends in the quote currency
+///   of `direct_pair`.
+/// - `implied_rate`: Units of the start currency returned per unit put in.
+/// - `profit_percentage`: `(implied_rate - 1) * 100`.
+/// - `volume`: The largest amount of the start currency the best levels of all three legs can
+///   absorb.
+/// - `timestamp`: When the opportunity was detected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpliedCrossOpportunity {
+    pub direct_pair: TradingPair,
+    pub via: String,
+    pub direct_price: f64,
+    pub implied_price: f64,
+    pub legs: Vec<ImpliedCrossLeg>,
+    pub implied_rate: f64,
+    pub profit_percentage: f64,
+    pub volume: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Best bid and ask of one pair across every exchange, as `(price, quantity, exchange)`.
+#[derive(Debug, Default)]
+struct BestQuote {
+    bid: Option<(f64, f64, Exchange)>,
+    ask: Option<(f64, f64, Exchange)>,
+}
+
+/// # Implied Cross Detector
+///
+/// Searches every pair whose base and quote are both also quoted against a common third
+/// currency for a direct price outside the implied cross's bid and ask.
+///
+/// ## Fields
+///
+/// - `min_profit_threshold`: The minimum profit percentage required to report a cycle.
+pub struct ImpliedCrossDetector {
+    min_profit_threshold: f64,
+}
+
+impl ImpliedCrossDetector {
+    /// ## New
+    ///
+    /// Creates a new `ImpliedCrossDetector` with the specified profit threshold.
+    ///
+    /// ### Arguments
+    ///
+    /// - `min_profit_threshold`: A `f64` representing the minimum profit percentage.
+    pub fn new(min_profit_threshold: f64) -> Self {
+        Self {
+            min_profit_threshold,
+        }
+    }
+
+    /// ## Detect Opportunities
+    ///
+    /// Takes the best bid and ask of every pair across all exchanges in `summaries` and, for
+    /// each direct pair `B/Q` with both `B/C` and `Q/C` quoted, checks both directions:
+    /// buying `B` directly and selling it through `C`, and buying `B` through `C` and selling
+    /// it directly. Only crosses quoted as `B/C` and `Q/C` are considered, not their inverses.
+    ///
+    /// ### Arguments
+    ///
+    /// - `summaries`: A `HashMap` where the key is a `TradingPair` and the value is a `Vec`
+    ///   of `Summary` objects for that pair.
+    ///
+    /// ### Returns
+    ///
+    /// A `Vec` of `ImpliedCrossOpportunity`, most profitable first.
+    pub async fn detect_opportunities(
+        &self,
+        summaries: &HashMap<TradingPair, Vec<Summary>>,
+    ) -> Vec<ImpliedCrossOpportunity> {
+        let quotes = Self::best_quotes(summaries);
+
+        let mut opportunities = Vec::new();
+        for (direct_pair, direct) in &quotes {
+            if direct_pair.base == direct_pair.quote {
+                continue;
+            }
+            for (base_pair, base_cross) in &quotes {
+                let via = &base_pair.quote;
+                if base_pair.base != direct_pair.base
+                    || via == &direct_pair.base
+                    || via == &direct_pair.quote
+                {
+                    continue;
+                }
+                let quote_pair = TradingPair::new(&direct_pair.quote, via);
+                let Some(quote_cross) = quotes.get(&quote_pair) else {
+                    continue;
+                };
+
+                opportunities.extend(self.buy_direct(
+                    (direct_pair, direct),
+                    (base_pair, base_cross),
+                    (&quote_pair, quote_cross),
+                ));
+                opportunities.extend(self.sell_direct(
+                    (direct_pair, direct),
+                    (base_pair, base_cross),
+                    (&quote_pair, quote_cross),
+                ));
+            }
+        }
+        opportunities.sort_by(|a, b| b.profit_percentage.total_cmp(&a.profit_percentage));
+        opportunities
+    }
+
+    /// Reduces the quotes of every exchange to the best bid and ask of each pair.
+    fn best_quotes(
+        summaries: &HashMap<TradingPair, Vec<Summary>>,
+    ) -> HashMap<TradingPair, BestQuote> {
+        let mut best: HashMap<TradingPair, BestQuote> = HashMap::new();
+        for (exchange, quotes) in TriangularArbitrageDetector::quotes_by_exchange(summaries) {
+            for (pair, quote) in quotes {
+                let entry = best.entry(pair).or_default();
+                if let Some((price, quantity)) = quote.bid {
+                    if entry.bid.as_ref().is_none_or(|(best, _, _)| price > *best) {
+                        entry.bid = Some((price, quantity, exchange.clone()));
+                    }
+                }
+                if let Some((price, quantity)) = quote.ask {
+                    if entry.ask.as_ref().is_none_or(|(best, _, _)| price < *best) {
+                        entry.ask = Some((price, quantity, exchange.clone()));
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Buys `B` with `Q` on the direct pair, sells it for `C`, and buys `Q` back with `C`.
+    fn buy_direct(
+        &self,
+        (direct_pair, direct): (&TradingPair, &BestQuote),
+        (base_pair, base_cross): (&TradingPair, &BestQuote),
+        (quote_pair, quote_cross): (&TradingPair, &BestQuote),
+    ) -> Option<ImpliedCrossOpportunity> {
+        let (direct_ask, direct_quantity, direct_exchange) = direct.ask.as_ref()?;
+        let (base_bid, base_quantity, base_exchange) = base_cross.bid.as_ref()?;
+        let (quote_ask, quote_quantity, quote_exchange) = quote_cross.ask.as_ref()?;
+
+        let legs = [
+            (
+                ImpliedCrossLeg {
+                    exchange: direct_exchange.clone(),
+                    pair: direct_pair.clone(),
+                    side: TradeSide::Buy,
+                    price: *direct_ask,
+                    from: direct_pair.quote.clone(),
+                    to: direct_pair.base.clone(),
+                },
+                direct_quantity * direct_ask,
+            ),
+            (
+                ImpliedCrossLeg {
+                    exchange: base_exchange.clone(),
+                    pair: base_pair.clone(),
+                    side: TradeSide::Sell,
+                    price: *base_bid,
+                    from: base_pair.base.clone(),
+                    to: base_pair.quote.clone(),
+                },
+                *base_quantity,
+            ),
+            (
+                ImpliedCrossLeg {
+                    exchange: quote_exchange.clone(),
+                    pair: quote_pair.clone(),
+                    side: TradeSide::Buy,
+                    price: *quote_ask,
+                    from: quote_pair.quote.clone(),
+                    to: quote_pair.base.clone(),
+                },
+                quote_quantity * quote_ask,
+            ),
+        ];
+        self.evaluate(direct_pair, *direct_ask, base_bid / quote_ask, legs)
+    }
+
+    /// Sells `Q` for `C`, buys `B` with `C`, and sells `B` for `Q` on the direct pair.
+    fn sell_direct(
+        &self,
+        (direct_pair, direct): (&TradingPair, &BestQuote),
+        (base_pair, base_cross): (&TradingPair, &BestQuote),
+        (quote_pair, quote_cross): (&TradingPair, &BestQuote),
+    ) -> Option<ImpliedCrossOpportunity> {
+        let (direct_bid, direct_quantity, direct_exchange) = direct.bid.as_ref()?;
+        let (base_ask, base_quantity, base_exchange) = base_cross.ask.as_ref()?;
+        let (quote_bid, quote_quantity, quote_exchange) = quote_cross.bid.as_ref()?;
+
+        let legs = [
+            (
+                ImpliedCrossLeg {
+                    exchange: quote_exchange.clone(),
+                    pair: quote_pair.clone(),
+                    side: TradeSide::Sell,
+                    price: *quote_bid,
+                    from: quote_pair.base.clone(),
+                    to: quote_pair.quote.clone(),
+                },
+                *quote_quantity,
+            ),
+            (
+                ImpliedCrossLeg {
+                    exchange: base_exchange.clone(),
+                    pair: base_pair.clone(),
+                    side: TradeSide::Buy,
+                    price: *base_ask,
+                    from: base_pair.quote.clone(),
+                    to: base_pair.base.clone(),
+                },
+                base_quantity * base_ask,
+            ),
+            (
+                ImpliedCrossLeg {
+                    exchange: direct_exchange.clone(),
+                    pair: direct_pair.clone(),
+                    side: TradeSide::Sell,
+                    price: *direct_bid,
+                    from: direct_pair.base.clone(),
+                    to: direct_pair.quote.clone(),
+                },
+                *direct_quantity,
+            ),
+        ];
+        self.evaluate(direct_pair, *direct_bid, base_ask / quote_bid, legs)
+    }
+
+    /// Prices one cycle, returning it if it clears the profit threshold. Each leg comes with
+    /// how much of the currency it spends its best level absorbs.
+    fn evaluate(
+        &self,
+        direct_pair: &TradingPair,
+        direct_price: f64,
+        implied_price: f64,
+        legs: [(ImpliedCrossLeg, f64); 3],
+    ) -> Option<ImpliedCrossOpportunity> {
+        let implied_rate: f64 = legs.iter().map(|(leg, _)| leg.rate()).product();
+        let profit_percentage = (implied_rate - 1.0) * 100.0;
+        if implied_rate <= 1.0 || profit_percentage < self.min_profit_threshold {
+            return None;
+        }
+
+        // Each leg's capacity is in the currency it spends; scale it back to the start
+        let mut volume = f64::INFINITY;
+        let mut reached = 1.0;
+        for (leg, capacity) in &legs {
+            volume = volume.min(capacity / reached);
+            reached *= leg.rate();
+        }
+
+        Some(ImpliedCrossOpportunity {
+            direct_pair: direct_pair.clone(),
+            via: legs[1].0.pair.quote.clone(),
+            direct_price,
+            implied_price,
+            legs: legs.into_iter().map(|(leg, _)| leg).collect(),
+            implied_rate,
+            profit_percentage,
+            volume,
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+impl Default for ImpliedCrossDetector {
+    fn default() -> Self {
+        Self::new(0.1) // 0.1% profit threshold
+    }
+}
@@ -2,42 +2,120 @@
 
 //! Analysis tools for crypto orderbook aggregator
 
+pub mod alerts;
 pub mod arbitrage;
-
-use aggregator_core::{ArbitrageOpportunity, Result, Summary};
+pub mod basis;
+pub mod data_quality;
+pub mod depth_report;
+pub mod execution;
+pub mod heatmap;
+pub mod implied_cross;
+pub mod market_stats;
+pub mod opportunity_store;
+pub mod order_flow;
+pub mod stat_arb;
+pub mod streaming;
+pub mod triangular;
+pub mod windowed_price;
+
+pub use aggregator_core::AnalysisEngine;
+use aggregator_core::{ArbitrageOpportunity, Config, PriceLevel, Result, Summary};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
-#[async_trait]
-/// Trait representing an analysis engine for processing market summaries and extracting insights.
-///
-/// Implementors of this trait are expected to provide asynchronous methods for analyzing
-/// collections of summaries, calculating spreads, and computing volume-weighted prices.
+/// Upper-cases `symbol` and removes everything but letters and digits, so `btc-usdt`,
+/// `BTC/USDT` and `BTCUSDT` compare equal
+pub(crate) fn normalize_symbol(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Analysis engine that compares the top of book of every pair of summaries for a symbol.
 ///
-/// # Required Methods
+/// # Fields
 ///
-/// - `analyze_summaries`: Analyzes a set of summaries and returns a list of arbitrage opportunities.
-/// - `calculate_spread`: Calculates the spread for a given summary, if possible.
-/// - `calculate_volume_weighted_price`: Computes the volume-weighted price for a given summary, if possible.
-pub trait AnalysisEngine: Send + Sync {
-    async fn analyze_summaries(
-        &self,
-        summaries: &HashMap<String, Summary>,
-    ) -> Result<Vec<ArbitrageOpportunity>>;
-    async fn calculate_spread(&self, summary: &Summary) -> Option<f64>;
-    async fn calculate_volume_weighted_price(&self, summary: &Summary) -> Option<f64>;
+/// - `detector`: Holds the profit and volume thresholds, the fee model and exchange
+///   latencies opportunities are judged with, and the staleness bound past which summaries
+///   are left out of detection.
+pub struct DefaultAnalysisEngine {
+    detector: ArbitrageDetector,
 }
 
-pub struct DefaultAnalysisEngine;
-
 /// Creates a new instance of `DefaultAnalysisEngine`.
 ///
 /// # Examples
 ///
 /// let engine = DefaultAnalysisEngine::new();
+/// let engine = DefaultAnalysisEngine::from_config(&config);
 impl DefaultAnalysisEngine {
     pub fn new() -> Self {
-        Self
+        Self::from_config(&Config::default())
+    }
+
+    /// Creates an engine with the thresholds, fee model and maximum book age of
+    /// `config.analysis`. Summaries carry no base asset, so only taker fees are charged, not
+    /// withdrawal fees.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            detector: ArbitrageDetector::from_config(config),
+        }
+    }
+
+    /// The detector opportunities are judged with, e.g. to record exchange latencies
+    pub fn detector(&self) -> &ArbitrageDetector {
+        &self.detector
+    }
+
+    /// The opportunity of buying at `ask` and selling at `bid`, from books the older of which
+    /// was published at `published`, if it clears the thresholds
+    fn opportunity(
+        &self,
+        symbol: &str,
+        ask: &PriceLevel,
+        bid: &PriceLevel,
+        published: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<ArbitrageOpportunity> {
+        if ask.price >= bid.price {
+            return None;
+        }
+
+        let volume = ask.quantity.min(bid.quantity);
+        let profit_percentage = self.detector.profit_percentage(
+            "",
+            &ask.exchange,
+            &bid.exchange,
+            ask.price,
+            bid.price,
+            volume,
+        );
+        if profit_percentage <= self.detector.min_profit_threshold()
+            || volume < self.detector.min_volume_threshold()
+        {
+            return None;
+        }
+
+        let (time_to_live_ms, confidence) =
+            self.detector
+                .validity(&ask.exchange, &bid.exchange, published, now);
+        Some(ArbitrageOpportunity {
+            buy_exchange: ask.exchange.clone(),
+            sell_exchange: bid.exchange.clone(),
+            symbol: symbol.to_string(),
+            buy_price: ask.price,
+            sell_price: bid.price,
+            profit_percentage,
+            volume,
+            average_buy_price: ask.price,
+            average_sell_price: bid.price,
+            time_to_live_ms,
+            confidence,
+            timestamp: now,
+        })
     }
 }
 
@@ -51,7 +129,9 @@ impl DefaultAnalysisEngine {
 ///
 /// - `analyze_summaries`: Asynchronously analyzes a collection of market summaries grouped by symbol to find
 ///   potential arbitrage opportunities between exchanges. It checks for profitable buy and sell pairs where
-///   the profit percentage exceeds a minimum threshold (0.1%). Returns a vector of `ArbitrageOpportunity`.
+///   the profit percentage exceeds the configured minimum (0.1% by default) and the volume meets the
+///   configured minimum. Summaries older than the configured maximum age are skipped. Returns a vector of
+///   `ArbitrageOpportunity`.
 ///
 /// - `calculate_spread`: Asynchronously calculates the spread between the best ask and best bid prices in a
 ///   given summary. Returns the spread as an `Option<f64>`, or `None` if bids or asks are missing.
@@ -85,8 +165,12 @@ impl AnalysisEngine for DefaultAnalysisEngine {
         let mut opportunities = Vec::new();
 
         // Group summaries by symbol
+        let now = Utc::now();
         let mut symbol_summaries: HashMap<String, Vec<&Summary>> = HashMap::new();
-        for summary in summaries.values() {
+        for summary in summaries
+            .values()
+            .filter(|summary| self.detector.is_fresh(summary.timestamp, now))
+        {
             symbol_summaries
                 .entry(summary.symbol.clone())
                 .or_insert_with(Vec::new)
@@ -110,45 +194,17 @@ impl AnalysisEngine for DefaultAnalysisEngine {
                         summary2.bids.first(),
                         summary2.asks.first(),
                     ) {
+                        let published = summary1.timestamp.min(summary2.timestamp);
+
                         // Check if we can buy on exchange 1 and sell on exchange 2
-                        if best_ask1.price < best_bid2.price {
-                            let profit = best_bid2.price - best_ask1.price;
-                            let profit_percentage = (profit / best_ask1.price) * 100.0;
-
-                            if profit_percentage > 0.1 {
-                                // Minimum 0.1% profit
-                                opportunities.push(ArbitrageOpportunity {
-                                    buy_exchange: best_ask1.exchange.clone(),
-                                    sell_exchange: best_bid2.exchange.clone(),
-                                    symbol: symbol.clone(),
-                                    buy_price: best_ask1.price,
-                                    sell_price: best_bid2.price,
-                                    profit_percentage,
-                                    volume: best_ask1.quantity.min(best_bid2.quantity),
-                                    timestamp: chrono::Utc::now(),
-                                });
-                            }
-                        }
+                        opportunities.extend(
+                            self.opportunity(&symbol, best_ask1, best_bid2, published, now),
+                        );
 
                         // Check if we can buy on exchange 2 and sell on exchange 1
-                        if best_ask2.price < best_bid1.price {
-                            let profit = best_bid1.price - best_ask2.price;
-                            let profit_percentage = (profit / best_ask2.price) * 100.0;
-
-                            if profit_percentage > 0.1 {
-                                // Minimum 0.1% profit
-                                opportunities.push(ArbitrageOpportunity {
-                                    buy_exchange: best_ask2.exchange.clone(),
-                                    sell_exchange: best_bid1.exchange.clone(),
-                                    symbol: symbol.clone(),
-                                    buy_price: best_ask2.price,
-                                    sell_price: best_bid1.price,
-                                    profit_percentage,
-                                    volume: best_ask2.quantity.min(best_bid1.quantity),
-                                    timestamp: chrono::Utc::now(),
-                                });
-                            }
-                        }
+                        opportunities.extend(
+                            self.opportunity(&symbol, best_ask2, best_bid1, published, now),
+                        );
                     }
                 }
             }
@@ -216,6 +272,7 @@ mod tests {
                 timestamp: Utc::now(),
             }],
             timestamp: Utc::now(),
+            market_type: None,
         };
 
         let summary2 = Summary {
@@ -234,6 +291,7 @@ mod tests {
                 timestamp: Utc::now(),
             }],
             timestamp: Utc::now(),
+            market_type: None,
         };
 
         summaries.insert("binance_btcusdt".to_string(), summary1);
@@ -270,6 +328,7 @@ mod tests {
                 timestamp: Utc::now(),
             }],
             timestamp: Utc::now(),
+            market_type: None,
         };
 
         let spread = engine.calculate_spread(&summary).await;
@@ -312,6 +371,7 @@ mod tests {
                 },
             ],
             timestamp: Utc::now(),
+            market_type: None,
         };
 
         let vwap = engine.calculate_volume_weighted_price(&summary).await;
@@ -323,4 +383,18 @@ mod tests {
     }
 }
 
+pub use alerts::*;
 pub use arbitrage::*;
+pub use basis::*;
+pub use data_quality::*;
+pub use depth_report::*;
+pub use execution::*;
+pub use heatmap::*;
+pub use implied_cross::*;
+pub use market_stats::*;
+pub use opportunity_store::*;
+pub use order_flow::*;
+pub use stat_arb::*;
+pub use streaming::*;
+pub use triangular::*;
+pub use windowed_price::*;
@@ -0,0 +1,404 @@
+//! # Market Statistics Module
+//!
+//! This module keeps a rolling history of top-of-book samples per symbol and exchange and
+//! reports mid-price volatility and spread statistics over a requested window, for monitoring
+//! dashboards that want market quality figures without storing every summary themselves.
+
+use aggregator_core::{Exchange, Result, Summary};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Market Stats Config
+///
+/// Retention settings for market statistics.
+///
+/// ## Fields
+///
+/// - `window_secs`: How far back samples are kept. Statistics can be requested over any
+///   window up to this length.
+/// - `max_samples`: Upper bound on the samples kept per symbol and exchange; the oldest are
+///   dropped first when a busy feed exceeds it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketStatsConfig {
+    pub window_secs: u64,
+    pub max_samples: usize,
+}
+
+impl Default for MarketStatsConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 3600,
+            max_samples: 10_000,
+        }
+    }
+}
+
+/// One top-of-book observation.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    timestamp: DateTime<Utc>,
+    mid_price: f64,
+    spread: f64,
+}
+
+/// # Market Stats Snapshot
+///
+/// Statistics for one symbol on one exchange over a window.
+///
+/// ## Fields
+///
+/// - `symbol`: The symbol the statistics are for.
+/// - `exchange`: The exchange the statistics are for.
+/// - `window_secs`: The window the statistics cover, ending at the latest sample.
+/// - `samples`: Number of samples in the window.
+/// - `mid_price`: The latest mid price.
+/// - `volatility`: Standard deviation of the log returns between consecutive mid prices in
+///   the window, not annualised. Zero with fewer than two returns.
+/// - `average_spread`: Mean best ask minus best bid.
+/// - `spread_p50`, `spread_p95`, `spread_p99`: Nearest-rank percentiles of the spread.
+/// - `timestamp`: Time of the latest sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MarketStatsSnapshot {
+    pub symbol: String,
+    pub exchange: Exchange,
+    pub window_secs: u64,
+    pub samples: usize,
+    pub mid_price: f64,
+    pub volatility: f64,
+    pub average_spread: f64,
+    pub spread_p50: f64,
+    pub spread_p95: f64,
+    pub spread_p99: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// # Market Stats
+///
+/// Rolling top-of-book history for a single symbol on a single exchange. Feed it summaries
+/// with [`MarketStats::record`] and read statistics back with [`MarketStats::snapshot`].
+#[derive(Debug, Clone)]
+pub struct MarketStats {
+    symbol: String,
+    exchange: Exchange,
+    config: MarketStatsConfig,
+    samples: VecDeque<Sample>,
+}
+
+impl MarketStats {
+    /// ## New
+    ///
+    /// Creates an empty history for `symbol` on `exchange`.
+    pub fn new(symbol: &str, exchange: Exchange, config: MarketStatsConfig) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            exchange,
+            config,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// ## Record
+    ///
+    /// Samples the best bid and ask of this exchange from `summary`. Summaries missing either
+    /// side, or whose best prices are not positive and finite, are skipped. Samples older
+    /// than the retention window are dropped.
+    pub fn record(&mut self, summary: &Summary) {
+        let best_bid = summary.bids.iter().find(|l| l.exchange == self.exchange);
+        let best_ask = summary.asks.iter().find(|l| l.exchange == self.exchange);
+        let (Some(bid), Some(ask)) = (best_bid, best_ask) else {
+            return;
+        };
+        let usable = |price: f64| price.is_finite() && price > 0.0;
+        if !usable(bid.price) || !usable(ask.price) {
+            return;
+        }
+
+        self.samples.push_back(Sample {
+            timestamp: summary.timestamp,
+            mid_price: (bid.price + ask.price) / 2.0,
+            spread: ask.price - bid.price,
+        });
+        while self.samples.len() > self.config.max_samples.max(1) {
+            self.samples.pop_front();
+        }
+        let cutoff = summary.timestamp - Duration::seconds(self.config.window_secs as i64);
+        while self
+            .samples
+            .front()
+            .is_some_and(|sample| sample.timestamp < cutoff)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// ## Snapshot
+    ///
+    /// Computes statistics over the last `window_secs` seconds before the latest sample,
+    /// capped at the retention window. Returns `None` if nothing has been recorded.
+    pub fn snapshot(&self, window_secs: u64) -> Option<MarketStatsSnapshot> {
+        let latest = self.samples.back()?;
+        let window_secs = window_secs.min(self.config.window_secs);
+        let cutoff = latest.timestamp - Duration::seconds(window_secs as i64);
+        let window: Vec<&Sample> = self
+            .samples
+            .iter()
+            .filter(|sample| sample.timestamp >= cutoff)
+            .collect();
+
+        let returns: Vec<f64> = window
+            .windows(2)
+            .map(|pair| (pair[1].mid_price / pair[0].mid_price).ln())
+            .collect();
+        let volatility = if returns.len() < 2 {
+            0.0
+        } else {
+            let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+            let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>()
+                / (returns.len() - 1) as f64;
+            variance.sqrt()
+        };
+
+        let mut spreads: Vec<f64> = window.iter().map(|sample| sample.spread).collect();
+        spreads.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |percentile: f64| {
+            let rank = (percentile / 100.0 * spreads.len() as f64).ceil() as usize;
+            spreads[rank.saturating_sub(1)]
+        };
+
+        Some(MarketStatsSnapshot {
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            window_secs,
+            samples: window.len(),
+            mid_price: latest.mid_price,
+            volatility,
+            average_spread: spreads.iter().sum::<f64>() / spreads.len() as f64,
+            spread_p50: percentile(50.0),
+            spread_p95: percentile(95.0),
+            spread_p99: percentile(99.0),
+            timestamp: latest.timestamp,
+        })
+    }
+
+    /// Returns the number of samples currently held.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns `true` if no samples are held.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+/// # Market Stats Collector
+///
+/// Keeps one [`MarketStats`] per symbol and exchange and can be fed directly from the
+/// aggregator's summary broadcast. A summary carrying levels from several exchanges updates
+/// the history of each. Cloning the collector shares the underlying histories, so servers can
+/// hold a handle for reads while a background task records.
+#[derive(Debug, Clone)]
+pub struct MarketStatsCollector {
+    config: MarketStatsConfig,
+    stats: Arc<RwLock<HashMap<(String, Exchange), MarketStats>>>,
+}
+
+impl MarketStatsCollector {
+    /// ## New
+    ///
+    /// Creates a collector that keeps every history with `config`.
+    pub fn new(config: MarketStatsConfig) -> Self {
+        Self {
+            config,
+            stats: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// ## Record
+    ///
+    /// Samples a summary into the history of every exchange it carries levels for.
+    pub async fn record(&self, summary: &Summary) {
+        let mut exchanges: Vec<&Exchange> = Vec::new();
+        for level in summary.bids.iter().chain(&summary.asks) {
+            if !exchanges.contains(&&level.exchange) {
+                exchanges.push(&level.exchange);
+            }
+        }
+
+        let mut stats = self.stats.write().await;
+        for exchange in exchanges {
+            stats
+                .entry((summary.symbol.clone(), exchange.clone()))
+                .or_insert_with(|| {
+                    MarketStats::new(&summary.symbol, exchange.clone(), self.config.clone())
+                })
+                .record(summary);
+        }
+    }
+
+    /// ## Snapshot
+    ///
+    /// Returns statistics over `window_secs` for `symbol` on `exchange`, if any samples were
+    /// recorded for it.
+    pub async fn snapshot(
+        &self,
+        symbol: &str,
+        exchange: &Exchange,
+        window_secs: u64,
+    ) -> Option<MarketStatsSnapshot> {
+        let stats = self.stats.read().await;
+        stats
+            .get(&(symbol.to_string(), exchange.clone()))
+            .and_then(|stats| stats.snapshot(window_secs))
+    }
+
+    /// ## Symbol Snapshots
+    ///
+    /// Returns statistics over `window_secs` for `symbol` on every exchange that quotes it,
+    /// ordered by exchange.
+    pub async fn symbol_snapshots(
+        &self,
+        symbol: &str,
+        window_secs: u64,
+    ) -> Vec<MarketStatsSnapshot> {
+        let stats = self.stats.read().await;
+        let mut snapshots: Vec<MarketStatsSnapshot> = stats
+            .iter()
+            .filter(|((s, _), _)| s == symbol)
+            .filter_map(|(_, stats)| stats.snapshot(window_secs))
+            .collect();
+        snapshots.sort_by(|a, b| a.exchange.cmp(&b.exchange));
+        snapshots
+    }
+
+    /// ## Spawn
+    ///
+    /// Spawns a task that records every summary received on `summary_rx` until the channel
+    /// closes. Lagged receivers skip the missed summaries and keep going.
+    pub fn spawn(&self, mut summary_rx: broadcast::Receiver<Summary>) -> JoinHandle<Result<()>> {
+        let collector = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match summary_rx.recv().await {
+                    Ok(summary) => collector.record(&summary).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "Market stats collector lagged, skipped {} summaries",
+                            skipped
+                        );
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Default for MarketStatsCollector {
+    fn default() -> Self {
+        Self::new(MarketStatsConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregator_core::PriceLevel;
+
+    fn summary_at(secs: i64, exchange: Exchange, bid: f64, ask: f64) -> Summary {
+        let timestamp = DateTime::from_timestamp(secs, 0).unwrap();
+        let level = |price| PriceLevel {
+            price,
+            quantity: 1.0,
+            exchange: exchange.clone(),
+            timestamp,
+        };
+        Summary {
+            symbol: "BTCUSDT".to_string(),
+            spread: ask - bid,
+            bids: vec![level(bid)],
+            asks: vec![level(ask)],
+            timestamp,
+            market_type: None,
+        }
+    }
+
+    #[test]
+    fn test_spread_statistics() {
+        let mut stats =
+            MarketStats::new("BTCUSDT", Exchange::Binance, MarketStatsConfig::default());
+        for (secs, spread) in [(0, 1.0), (1, 2.0), (2, 3.0), (3, 10.0)] {
+            stats.record(&summary_at(secs, Exchange::Binance, 100.0, 100.0 + spread));
+        }
+
+        let snapshot = stats.snapshot(60).unwrap();
+        assert_eq!(snapshot.samples, 4);
+        assert_eq!(snapshot.average_spread, 4.0);
+        assert_eq!(snapshot.spread_p50, 2.0);
+        assert_eq!(snapshot.spread_p95, 10.0);
+        assert_eq!(snapshot.mid_price, 105.0);
+    }
+
+    #[test]
+    fn test_volatility_of_log_returns() {
+        let mut stats =
+            MarketStats::new("BTCUSDT", Exchange::Binance, MarketStatsConfig::default());
+        // Mid alternates 100, 110, 100: returns ln(1.1) and -ln(1.1)
+        for (secs, mid) in [(0, 100.0), (1, 110.0), (2, 100.0)] {
+            stats.record(&summary_at(secs, Exchange::Binance, mid - 0.5, mid + 0.5));
+        }
+
+        let snapshot = stats.snapshot(60).unwrap();
+        let expected = (2.0 * 1.1f64.ln().powi(2)).sqrt();
+        assert!((snapshot.volatility - expected).abs() < 1e-12);
+
+        // A flat window has no volatility
+        assert_eq!(stats.snapshot(0).unwrap().volatility, 0.0);
+    }
+
+    #[test]
+    fn test_window_and_retention() {
+        let config = MarketStatsConfig {
+            window_secs: 10,
+            max_samples: 100,
+        };
+        let mut stats = MarketStats::new("BTCUSDT", Exchange::Binance, config);
+        for secs in 0..20 {
+            stats.record(&summary_at(secs, Exchange::Binance, 100.0, 101.0));
+        }
+
+        // Samples from before t=9 fall out of the 10s retention
+        assert_eq!(stats.len(), 11);
+        assert_eq!(stats.snapshot(4).unwrap().samples, 5);
+        // Windows longer than the retention are capped
+        assert_eq!(stats.snapshot(60).unwrap().window_secs, 10);
+    }
+
+    #[tokio::test]
+    async fn test_collector_splits_exchanges() {
+        let collector = MarketStatsCollector::default();
+        let mut summary = summary_at(0, Exchange::Binance, 100.0, 101.0);
+        let bybit = summary_at(0, Exchange::Bybit, 99.0, 102.0);
+        summary.bids.extend(bybit.bids);
+        summary.asks.extend(bybit.asks);
+        collector.record(&summary).await;
+
+        let snapshots = collector.symbol_snapshots("BTCUSDT", 60).await;
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].exchange, Exchange::Binance);
+        assert_eq!(snapshots[0].average_spread, 1.0);
+        assert_eq!(snapshots[1].exchange, Exchange::Bybit);
+        assert_eq!(snapshots[1].average_spread, 3.0);
+        assert!(collector
+            .snapshot("ETHUSDT", &Exchange::Binance, 60)
+            .await
+            .is_none());
+    }
+}
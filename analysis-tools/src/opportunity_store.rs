@@ -0,0 +1,463 @@
+//! # Opportunity Store Module
+//!
+//! Arbitrage opportunities are broadcast once and then gone. This module persists every
+//! opportunity so they can be looked back on: an [`OpportunityStore`] records opportunities and
+//! answers queries by symbol, exchange pair, time range and minimum profit. Two stores are
+//! provided, [`InMemoryOpportunityStore`] for a bounded recent history and, with the `sqlite`
+//! feature, [`SqliteOpportunityStore`] for history that outlives the process. An
+//! [`ExchangePairMatrix`] summarizes stored opportunities by the exchanges they buy and sell
+//! on, to show which venue pairs are systematically mispriced.
+
+use crate::normalize_symbol;
+use aggregator_core::{ArbitrageOpportunity, Exchange, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Opportunity Query
+///
+/// Filters for looking up stored opportunities. Every filter left as `None` matches everything.
+///
+/// ## Fields
+///
+/// - `symbol`: Symbol of the opportunity, compared with separators removed so `BTC/USDT`
+///   matches `BTCUSDT`.
+/// - `buy_exchange`, `sell_exchange`: Exchange the opportunity buys on and sells on.
+/// - `from`, `to`: Inclusive bounds on the opportunity's timestamp.
+/// - `min_profit`: Smallest profit percentage to return.
+/// - `limit`: Most opportunities to return.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpportunityQuery {
+    pub symbol: Option<String>,
+    pub buy_exchange: Option<Exchange>,
+    pub sell_exchange: Option<Exchange>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub min_profit: Option<f64>,
+    pub limit: Option<usize>,
+}
+
+impl OpportunityQuery {
+    /// Whether `opportunity` passes every filter of the query
+    pub fn matches(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        self.symbol
+            .as_ref()
+            .is_none_or(|symbol| normalize_symbol(symbol) == normalize_symbol(&opportunity.symbol))
+            && self
+                .buy_exchange
+                .as_ref()
+                .is_none_or(|exchange| exchange == &opportunity.buy_exchange)
+            && self
+                .sell_exchange
+                .as_ref()
+                .is_none_or(|exchange| exchange == &opportunity.sell_exchange)
+            && self.from.is_none_or(|from| opportunity.timestamp >= from)
+            && self.to.is_none_or(|to| opportunity.timestamp <= to)
+            && self
+                .min_profit
+                .is_none_or(|min_profit| opportunity.profit_percentage >= min_profit)
+    }
+}
+
+/// # Exchange Pair Matrix
+///
+/// Opportunities counted by the exchange they buy on and the exchange they sell on.
+///
+/// ## Fields
+///
+/// - `exchanges`: Every exchange that appears on either side, in order; rows and columns of the
+///   matrices follow it.
+/// - `counts`: `counts[b][s]` is the number of opportunities buying on `exchanges[b]` and
+///   selling on `exchanges[s]`.
+/// - `average_profit_percentages`: `average_profit_percentages[b][s]` is the mean profit
+///   percentage of those opportunities, 0 where there are none.
+/// - `total`: Number of opportunities summarized.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExchangePairMatrix {
+    pub exchanges: Vec<Exchange>,
+    pub counts: Vec<Vec<usize>>,
+    pub average_profit_percentages: Vec<Vec<f64>>,
+    pub total: usize,
+}
+
+impl ExchangePairMatrix {
+    /// ## From Opportunities
+    ///
+    /// Builds the matrix from `opportunities`.
+    pub fn from_opportunities<'a>(
+        opportunities: impl IntoIterator<Item = &'a ArbitrageOpportunity>,
+    ) -> Self {
+        let mut cells: BTreeMap<(&Exchange, &Exchange), (usize, f64)> = BTreeMap::new();
+        let mut exchanges = BTreeSet::new();
+        let mut total = 0;
+        for opportunity in opportunities {
+            let (count, profit) = cells
+                .entry((&opportunity.buy_exchange, &opportunity.sell_exchange))
+                .or_default();
+            *count += 1;
+            *profit += opportunity.profit_percentage;
+            exchanges.insert(&opportunity.buy_exchange);
+            exchanges.insert(&opportunity.sell_exchange);
+            total += 1;
+        }
+
+        let exchanges: Vec<Exchange> = exchanges.into_iter().cloned().collect();
+        let mut counts = vec![vec![0; exchanges.len()]; exchanges.len()];
+        let mut average_profit_percentages = vec![vec![0.0; exchanges.len()]; exchanges.len()];
+        for ((buy, sell), (count, profit)) in cells {
+            if let (Ok(b), Ok(s)) = (exchanges.binary_search(buy), exchanges.binary_search(sell)) {
+                counts[b][s] = count;
+                average_profit_percentages[b][s] = profit / count as f64;
+            }
+        }
+
+        Self {
+            exchanges,
+            counts,
+            average_profit_percentages,
+            total,
+        }
+    }
+
+    /// Number of opportunities and their mean profit percentage for buying on `buy` and
+    /// selling on `sell`, if there were any.
+    pub fn cell(&self, buy: &Exchange, sell: &Exchange) -> Option<(usize, f64)> {
+        let b = self.exchanges.binary_search(buy).ok()?;
+        let s = self.exchanges.binary_search(sell).ok()?;
+        let count = self.counts[b][s];
+        (count > 0).then(|| (count, self.average_profit_percentages[b][s]))
+    }
+}
+
+/// # Opportunity Store
+///
+/// Persists arbitrage opportunities and looks them up again.
+///
+/// ## Required Methods
+///
+/// - `insert`: Stores one opportunity.
+/// - `query`: Returns the stored opportunities matching a query, newest first.
+///
+/// ## Provided Methods
+///
+/// - `exchange_pair_matrix`: Summarizes the opportunities matching a query by exchange pair.
+#[async_trait]
+pub trait OpportunityStore: Send + Sync {
+    async fn insert(&self, opportunity: &ArbitrageOpportunity) -> Result<()>;
+    async fn query(&self, query: &OpportunityQuery) -> Result<Vec<ArbitrageOpportunity>>;
+
+    async fn exchange_pair_matrix(&self, query: &OpportunityQuery) -> Result<ExchangePairMatrix> {
+        Ok(ExchangePairMatrix::from_opportunities(
+            &self.query(query).await?,
+        ))
+    }
+}
+
+/// ## Spawn Opportunity Recorder
+///
+/// Spawns a task that inserts every opportunity received on `opportunity_rx`, typically from
+/// `Aggregator::subscribe_arbitrage()` or `StreamingAnalysisEngine::subscribe_opportunities()`,
+/// into `store` until the channel closes. Failed inserts are logged and skipped.
+pub fn spawn_opportunity_recorder(
+    store: Arc<dyn OpportunityStore>,
+    mut opportunity_rx: broadcast::Receiver<ArbitrageOpportunity>,
+) -> JoinHandle<Result<()>> {
+    tokio::spawn(async move {
+        loop {
+            match opportunity_rx.recv().await {
+                Ok(opportunity) => {
+                    if let Err(e) = store.insert(&opportunity).await {
+                        warn!("Failed to store arbitrage opportunity: {}", e);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "Opportunity recorder lagged, skipped {} opportunities",
+                        skipped
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        Ok(())
+    })
+}
+
+/// # In-Memory Opportunity Store
+///
+/// Keeps the most recent opportunities in memory, dropping the oldest once `capacity` is
+/// reached. Cloning the store shares its contents.
+#[derive(Debug, Clone)]
+pub struct InMemoryOpportunityStore {
+    capacity: usize,
+    opportunities: Arc<RwLock<VecDeque<ArbitrageOpportunity>>>,
+}
+
+impl InMemoryOpportunityStore {
+    /// ## New
+    ///
+    /// Creates an empty store that holds at most `capacity` opportunities.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            opportunities: Arc::new(RwLock::new(VecDeque::new())),
+        }
+    }
+
+    /// Number of opportunities held.
+    pub async fn len(&self) -> usize {
+        self.opportunities.read().await.len()
+    }
+
+    /// Whether no opportunity is held.
+    pub async fn is_empty(&self) -> bool {
+        self.opportunities.read().await.is_empty()
+    }
+}
+
+impl Default for InMemoryOpportunityStore {
+    fn default() -> Self {
+        Self::new(100_000)
+    }
+}
+
+#[async_trait]
+impl OpportunityStore for InMemoryOpportunityStore {
+    async fn insert(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
+        let mut opportunities = self.opportunities.write().await;
+        opportunities.push_back(opportunity.clone());
+        while opportunities.len() > self.capacity {
+            opportunities.pop_front();
+        }
+        Ok(())
+    }
+
+    async fn query(&self, query: &OpportunityQuery) -> Result<Vec<ArbitrageOpportunity>> {
+        let opportunities = self.opportunities.read().await;
+        let mut matching: Vec<ArbitrageOpportunity> = opportunities
+            .iter()
+            .filter(|opportunity| query.matches(opportunity))
+            .cloned()
+            .collect();
+        // Newest first; the sort is stable so equal timestamps stay newest-inserted first
+        matching.reverse();
+        matching.sort_by_key(|opportunity| Reverse(opportunity.timestamp));
+        if let Some(limit) = query.limit {
+            matching.truncate(limit);
+        }
+        Ok(matching)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteOpportunityStore;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::{OpportunityQuery, OpportunityStore};
+    use crate::normalize_symbol;
+    use aggregator_core::{AggregatorError, ArbitrageOpportunity, Exchange, Result};
+    use async_trait::async_trait;
+    use chrono::DateTime;
+    use rusqlite::types::{Type, Value};
+    use rusqlite::{params, params_from_iter, Connection, Row};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS arbitrage_opportunities (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            symbol_key TEXT NOT NULL,
+            buy_exchange TEXT NOT NULL,
+            sell_exchange TEXT NOT NULL,
+            buy_price REAL NOT NULL,
+            sell_price REAL NOT NULL,
+            profit_percentage REAL NOT NULL,
+            volume REAL NOT NULL,
+            average_buy_price REAL NOT NULL,
+            average_sell_price REAL NOT NULL,
+            time_to_live_ms REAL NOT NULL,
+            confidence REAL NOT NULL,
+            timestamp_us INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS arbitrage_opportunities_symbol_time
+            ON arbitrage_opportunities (symbol_key, timestamp_us);
+        CREATE INDEX IF NOT EXISTS arbitrage_opportunities_time
+            ON arbitrage_opportunities (timestamp_us);
+    ";
+
+    /// # SQLite Opportunity Store
+    ///
+    /// Persists opportunities to a SQLite database. Symbols are stored alongside their form
+    /// with separators removed, which symbol queries match on, and timestamps are stored in
+    /// microseconds. Queries run on the blocking thread pool. Cloning the store shares its
+    /// connection.
+    #[derive(Debug, Clone)]
+    pub struct SqliteOpportunityStore {
+        connection: Arc<Mutex<Connection>>,
+    }
+
+    impl SqliteOpportunityStore {
+        /// ## Open
+        ///
+        /// Opens the database at `path`, creating it and its table if they do not exist.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+            let connection = Connection::open(path)
+                .map_err(|e| AggregatorError::database("open", e.to_string()))?;
+            Self::from_connection(connection)
+        }
+
+        /// ## Open In Memory
+        ///
+        /// Opens a private in-memory database, mostly useful for tests.
+        pub fn open_in_memory() -> Result<Self> {
+            let connection = Connection::open_in_memory()
+                .map_err(|e| AggregatorError::database("open", e.to_string()))?;
+            Self::from_connection(connection)
+        }
+
+        fn from_connection(connection: Connection) -> Result<Self> {
+            connection
+                .execute_batch(SCHEMA)
+                .map_err(|e| AggregatorError::database("create schema", e.to_string()))?;
+            Ok(Self {
+                connection: Arc::new(Mutex::new(connection)),
+            })
+        }
+
+        /// Runs `f` against the connection on the blocking thread pool
+        async fn with_connection<T, F>(&self, operation: &'static str, f: F) -> Result<T>
+        where
+            T: Send + 'static,
+            F: FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+        {
+            let connection = self.connection.clone();
+            tokio::task::spawn_blocking(move || {
+                let connection = connection
+                    .lock()
+                    .map_err(|_| AggregatorError::database(operation, "connection poisoned"))?;
+                f(&connection).map_err(|e| AggregatorError::database(operation, e.to_string()))
+            })
+            .await
+            .map_err(|e| AggregatorError::database(operation, e.to_string()))?
+        }
+    }
+
+    #[async_trait]
+    impl OpportunityStore for SqliteOpportunityStore {
+        async fn insert(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
+            let opportunity = opportunity.clone();
+            self.with_connection("insert", move |connection| {
+                connection.execute(
+                    "INSERT INTO arbitrage_opportunities (
+                        symbol, symbol_key, buy_exchange, sell_exchange, buy_price, sell_price,
+                        profit_percentage, volume, average_buy_price, average_sell_price,
+                        time_to_live_ms, confidence, timestamp_us
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                    params![
+                        opportunity.symbol,
+                        normalize_symbol(&opportunity.symbol),
+                        opportunity.buy_exchange.to_string(),
+                        opportunity.sell_exchange.to_string(),
+                        opportunity.buy_price,
+                        opportunity.sell_price,
+                        opportunity.profit_percentage,
+                        opportunity.volume,
+                        opportunity.average_buy_price,
+                        opportunity.average_sell_price,
+                        opportunity.time_to_live_ms,
+                        opportunity.confidence,
+                        opportunity.timestamp.timestamp_micros(),
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+        }
+
+        async fn query(&self, query: &OpportunityQuery) -> Result<Vec<ArbitrageOpportunity>> {
+            let mut conditions = Vec::new();
+            let mut values = Vec::new();
+            if let Some(symbol) = &query.symbol {
+                conditions.push("symbol_key = ?");
+                values.push(Value::Text(normalize_symbol(symbol)));
+            }
+            if let Some(exchange) = &query.buy_exchange {
+                conditions.push("buy_exchange = ?");
+                values.push(Value::Text(exchange.to_string()));
+            }
+            if let Some(exchange) = &query.sell_exchange {
+                conditions.push("sell_exchange = ?");
+                values.push(Value::Text(exchange.to_string()));
+            }
+            if let Some(from) = query.from {
+                conditions.push("timestamp_us >= ?");
+                values.push(Value::Integer(from.timestamp_micros()));
+            }
+            if let Some(to) = query.to {
+                conditions.push("timestamp_us <= ?");
+                values.push(Value::Integer(to.timestamp_micros()));
+            }
+            if let Some(min_profit) = query.min_profit {
+                conditions.push("profit_percentage >= ?");
+                values.push(Value::Real(min_profit));
+            }
+
+            let mut sql = "SELECT symbol, buy_exchange, sell_exchange, buy_price, sell_price,
+                    profit_percentage, volume, average_buy_price, average_sell_price,
+                    time_to_live_ms, confidence, timestamp_us
+                FROM arbitrage_opportunities"
+                .to_string();
+            if !conditions.is_empty() {
+                sql.push_str(" WHERE ");
+                sql.push_str(&conditions.join(" AND "));
+            }
+            sql.push_str(" ORDER BY timestamp_us DESC, id DESC");
+            if let Some(limit) = query.limit {
+                sql.push_str(" LIMIT ?");
+                values.push(Value::Integer(limit as i64));
+            }
+
+            self.with_connection("query", move |connection| {
+                let mut statement = connection.prepare(&sql)?;
+                let rows = statement.query_map(params_from_iter(values), opportunity_from_row)?;
+                rows.collect()
+            })
+            .await
+        }
+    }
+
+    fn opportunity_from_row(row: &Row<'_>) -> rusqlite::Result<ArbitrageOpportunity> {
+        let exchange = |index: usize| -> rusqlite::Result<Exchange> {
+            row.get::<_, String>(index)?.parse().map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e))
+            })
+        };
+        let timestamp_us: i64 = row.get(11)?;
+        let timestamp = DateTime::from_timestamp_micros(timestamp_us)
+            .ok_or_else(|| rusqlite::Error::IntegralValueOutOfRange(11, timestamp_us))?;
+
+        Ok(ArbitrageOpportunity {
+            symbol: row.get(0)?,
+            buy_exchange: exchange(1)?,
+            sell_exchange: exchange(2)?,
+            buy_price: row.get(3)?,
+            sell_price: row.get(4)?,
+            profit_percentage: row.get(5)?,
+            volume: row.get(6)?,
+            average_buy_price: row.get(7)?,
+            average_sell_price: row.get(8)?,
+            time_to_live_ms: row.get(9)?,
+            confidence: row.get(10)?,
+            timestamp,
+        })
+    }
+}
@@ -0,0 +1,383 @@
+//! # Order Flow Module
+//!
+//! This module turns trade prints into order flow indicators per symbol: cumulative volume
+//! delta (aggressive buy volume minus aggressive sell volume since the first trade seen), and
+//! the imbalance and buy/sell ratio of aggressive volume over a rolling window. Snapshots are
+//! published whenever a summary for the symbol arrives, so signal consumers receive order flow
+//! in step with the book.
+
+use crate::normalize_symbol;
+use aggregator_core::{Result, Summary, Trade, TradeSide};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Order Flow Config
+///
+/// Window settings for order flow indicators.
+///
+/// ## Fields
+///
+/// - `window_secs`: How far back trades count towards the windowed volumes, imbalance and
+///   ratio. Cumulative volume delta is not windowed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderFlowConfig {
+    pub window_secs: u64,
+}
+
+impl Default for OrderFlowConfig {
+    fn default() -> Self {
+        Self { window_secs: 60 }
+    }
+}
+
+/// # Order Flow Snapshot
+///
+/// Order flow indicators for one symbol across every exchange trading it.
+///
+/// ## Fields
+///
+/// - `symbol`: The symbol with separators removed, e.g. `BTCUSDT` for `BTC-USDT`.
+/// - `cumulative_volume_delta`: Aggressive buy volume minus aggressive sell volume since the
+///   first trade seen.
+/// - `buy_volume`, `sell_volume`: Aggressive buy and sell volume within the window.
+/// - `buy_trades`, `sell_trades`: Number of aggressive buy and sell trades within the window.
+/// - `trade_imbalance`: `(buy_volume - sell_volume) / (buy_volume + sell_volume)`, from -1
+///   (only sellers) to 1 (only buyers); zero with no trades in the window.
+/// - `buy_sell_ratio`: `buy_volume / sell_volume`, or `None` when no sell volume traded.
+/// - `window_secs`: The window the windowed figures cover, ending at the latest trade.
+/// - `timestamp`: Time of the latest trade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderFlowSnapshot {
+    pub symbol: String,
+    pub cumulative_volume_delta: f64,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    pub buy_trades: usize,
+    pub sell_trades: usize,
+    pub trade_imbalance: f64,
+    pub buy_sell_ratio: Option<f64>,
+    pub window_secs: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// One trade kept for the window.
+#[derive(Debug, Clone, Copy)]
+struct FlowTrade {
+    timestamp: DateTime<Utc>,
+    side: TradeSide,
+    quantity: f64,
+}
+
+/// # Order Flow
+///
+/// Order flow state for a single symbol. Feed it trades with [`OrderFlow::record`] and read
+/// the indicators back with [`OrderFlow::snapshot`].
+#[derive(Debug, Clone)]
+pub struct OrderFlow {
+    symbol: String,
+    config: OrderFlowConfig,
+    cumulative_volume_delta: f64,
+    trades: VecDeque<FlowTrade>,
+}
+
+impl OrderFlow {
+    /// ## New
+    ///
+    /// Creates empty order flow state for `symbol`.
+    pub fn new(symbol: &str, config: OrderFlowConfig) -> Self {
+        Self {
+            symbol: normalize_symbol(symbol),
+            config,
+            cumulative_volume_delta: 0.0,
+            trades: VecDeque::new(),
+        }
+    }
+
+    /// ## Record
+    ///
+    /// Adds a trade to the cumulative delta and the window. Trades with a non-positive or
+    /// non-finite quantity are skipped. Trades older than the window, measured from the
+    /// latest trade, are dropped.
+    pub fn record(&mut self, trade: &Trade) {
+        if !trade.quantity.is_finite() || trade.quantity <= 0.0 {
+            return;
+        }
+        self.cumulative_volume_delta += match trade.side {
+            TradeSide::Buy => trade.quantity,
+            TradeSide::Sell => -trade.quantity,
+        };
+
+        // Exchanges deliver prints slightly out of order; keep the window sorted by time
+        let index = self
+            .trades
+            .partition_point(|kept| kept.timestamp <= trade.timestamp);
+        self.trades.insert(
+            index,
+            FlowTrade {
+                timestamp: trade.timestamp,
+                side: trade.side,
+                quantity: trade.quantity,
+            },
+        );
+
+        if let Some(latest) = self.trades.back().map(|kept| kept.timestamp) {
+            let cutoff = latest - Duration::seconds(self.config.window_secs as i64);
+            while self
+                .trades
+                .front()
+                .is_some_and(|kept| kept.timestamp < cutoff)
+            {
+                self.trades.pop_front();
+            }
+        }
+    }
+
+    /// ## Snapshot
+    ///
+    /// Returns the current indicators, or `None` if no trade has been recorded.
+    pub fn snapshot(&self) -> Option<OrderFlowSnapshot> {
+        let latest = self.trades.back()?;
+
+        let (mut buy_volume, mut sell_volume) = (0.0, 0.0);
+        let (mut buy_trades, mut sell_trades) = (0, 0);
+        for trade in &self.trades {
+            match trade.side {
+                TradeSide::Buy => {
+                    buy_volume += trade.quantity;
+                    buy_trades += 1;
+                }
+                TradeSide::Sell => {
+                    sell_volume += trade.quantity;
+                    sell_trades += 1;
+                }
+            }
+        }
+
+        let total = buy_volume + sell_volume;
+        Some(OrderFlowSnapshot {
+            symbol: self.symbol.clone(),
+            cumulative_volume_delta: self.cumulative_volume_delta,
+            buy_volume,
+            sell_volume,
+            buy_trades,
+            sell_trades,
+            trade_imbalance: if total > 0.0 {
+                (buy_volume - sell_volume) / total
+            } else {
+                0.0
+            },
+            buy_sell_ratio: (sell_volume > 0.0).then(|| buy_volume / sell_volume),
+            window_secs: self.config.window_secs,
+            timestamp: latest.timestamp,
+        })
+    }
+}
+
+/// # Order Flow Analyzer
+///
+/// Keeps one [`OrderFlow`] per symbol, fed from the connectors' trade streams, and publishes
+/// a snapshot for a symbol every time a summary for it arrives. Trades and summaries are
+/// matched by symbol with separators removed, so `BTC-USDT` trades line up with `BTCUSDT`
+/// summaries. Cloning the analyzer shares its state and channel.
+#[derive(Debug, Clone)]
+pub struct OrderFlowAnalyzer {
+    config: OrderFlowConfig,
+    flows: Arc<RwLock<HashMap<String, OrderFlow>>>,
+    sender: broadcast::Sender<OrderFlowSnapshot>,
+}
+
+impl OrderFlowAnalyzer {
+    /// ## New
+    ///
+    /// Creates an analyzer that keeps every symbol's state with `config`.
+    pub fn new(config: OrderFlowConfig) -> Self {
+        let (sender, _) = broadcast::channel(1000);
+        Self {
+            config,
+            flows: Arc::new(RwLock::new(HashMap::new())),
+            sender,
+        }
+    }
+
+    /// Subscribes to the snapshots published alongside summaries.
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderFlowSnapshot> {
+        self.sender.subscribe()
+    }
+
+    /// ## Record Trade
+    ///
+    /// Adds a trade to the order flow of its symbol, creating it on first sight.
+    pub async fn record_trade(&self, trade: &Trade) {
+        let mut flows = self.flows.write().await;
+        flows
+            .entry(normalize_symbol(&trade.symbol))
+            .or_insert_with(|| OrderFlow::new(&trade.symbol, self.config.clone()))
+            .record(trade);
+    }
+
+    /// ## Snapshot
+    ///
+    /// Returns the current indicators for `symbol`, if any trades were recorded for it.
+    pub async fn snapshot(&self, symbol: &str) -> Option<OrderFlowSnapshot> {
+        let flows = self.flows.read().await;
+        flows
+            .get(&normalize_symbol(symbol))
+            .and_then(OrderFlow::snapshot)
+    }
+
+    /// ## Publish For
+    ///
+    /// Publishes the current snapshot of the summary's symbol, returning it. Symbols without
+    /// trades publish nothing.
+    pub async fn publish_for(&self, summary: &Summary) -> Option<OrderFlowSnapshot> {
+        let snapshot = self.snapshot(&summary.symbol).await?;
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(snapshot.clone());
+        Some(snapshot)
+    }
+
+    /// ## Spawn
+    ///
+    /// Spawns a task that records every trade received on `trade_rx`, typically the channel
+    /// handed to `TradeStreamService::spawn_trade_service`, and publishes a snapshot for every
+    /// summary received on `summary_rx`. The task ends when either channel closes. Lagged
+    /// summary receivers skip the missed summaries and keep going.
+    pub fn spawn(
+        &self,
+        mut trade_rx: mpsc::Receiver<Trade>,
+        mut summary_rx: broadcast::Receiver<Summary>,
+    ) -> JoinHandle<Result<()>> {
+        let analyzer = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    trade = trade_rx.recv() => match trade {
+                        Some(trade) => analyzer.record_trade(&trade).await,
+                        None => break,
+                    },
+                    summary = summary_rx.recv() => match summary {
+                        Ok(summary) => {
+                            analyzer.publish_for(&summary).await;
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Order flow analyzer lagged, skipped {} summaries", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Default for OrderFlowAnalyzer {
+    fn default() -> Self {
+        Self::new(OrderFlowConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregator_core::Exchange;
+    use uuid::Uuid;
+
+    fn trade_at(secs: i64, symbol: &str, side: TradeSide, quantity: f64) -> Trade {
+        Trade {
+            id: Uuid::new_v4(),
+            trade_id: secs.to_string(),
+            symbol: symbol.to_string(),
+            exchange: Exchange::Binance,
+            price: 100.0,
+            quantity,
+            side,
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_cumulative_delta_and_imbalance() {
+        let mut flow = OrderFlow::new("BTCUSDT", OrderFlowConfig::default());
+        flow.record(&trade_at(0, "BTCUSDT", TradeSide::Buy, 3.0));
+        flow.record(&trade_at(1, "BTCUSDT", TradeSide::Sell, 1.0));
+        flow.record(&trade_at(2, "BTCUSDT", TradeSide::Buy, 1.0));
+
+        let snapshot = flow.snapshot().unwrap();
+        assert_eq!(snapshot.cumulative_volume_delta, 3.0);
+        assert_eq!(snapshot.buy_volume, 4.0);
+        assert_eq!(snapshot.sell_volume, 1.0);
+        assert_eq!(snapshot.buy_trades, 2);
+        assert_eq!(snapshot.sell_trades, 1);
+        assert_eq!(snapshot.trade_imbalance, 0.6);
+        assert_eq!(snapshot.buy_sell_ratio, Some(4.0));
+    }
+
+    #[test]
+    fn test_window_drops_old_trades_but_not_delta() {
+        let mut flow = OrderFlow::new("BTCUSDT", OrderFlowConfig { window_secs: 10 });
+        flow.record(&trade_at(0, "BTCUSDT", TradeSide::Sell, 5.0));
+        flow.record(&trade_at(30, "BTCUSDT", TradeSide::Buy, 2.0));
+        // Arrives late but still inside the window
+        flow.record(&trade_at(25, "BTCUSDT", TradeSide::Buy, 1.0));
+
+        let snapshot = flow.snapshot().unwrap();
+        assert_eq!(snapshot.cumulative_volume_delta, -2.0);
+        assert_eq!(snapshot.buy_volume, 3.0);
+        assert_eq!(snapshot.sell_volume, 0.0);
+        assert_eq!(snapshot.trade_imbalance, 1.0);
+        assert_eq!(snapshot.buy_sell_ratio, None);
+        assert_eq!(snapshot.timestamp, DateTime::from_timestamp(30, 0).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_analyzer_matches_symbols_and_publishes_with_summaries() {
+        let analyzer = OrderFlowAnalyzer::default();
+        let mut snapshots = analyzer.subscribe();
+
+        let (trade_tx, trade_rx) = mpsc::channel(16);
+        let (summary_tx, summary_rx) = broadcast::channel(16);
+        let handle = analyzer.spawn(trade_rx, summary_rx);
+
+        trade_tx
+            .send(trade_at(0, "BTC-USDT", TradeSide::Buy, 2.0))
+            .await
+            .unwrap();
+        trade_tx
+            .send(trade_at(1, "btcusdt", TradeSide::Sell, 0.5))
+            .await
+            .unwrap();
+        while analyzer
+            .snapshot("BTCUSDT")
+            .await
+            .is_none_or(|snapshot| snapshot.buy_trades + snapshot.sell_trades < 2)
+        {
+            tokio::task::yield_now().await;
+        }
+
+        summary_tx
+            .send(Summary {
+                symbol: "BTCUSDT".to_string(),
+                spread: 0.0,
+                bids: vec![],
+                asks: vec![],
+                timestamp: Utc::now(),
+                market_type: None,
+            })
+            .unwrap();
+        let snapshot = tokio::time::timeout(std::time::Duration::from_secs(1), snapshots.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.symbol, "BTCUSDT");
+        assert_eq!(snapshot.cumulative_volume_delta, 1.5);
+
+        drop(trade_tx);
+        handle.await.unwrap().unwrap();
+    }
+}
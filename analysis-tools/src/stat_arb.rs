@@ -0,0 +1,466 @@
+//! # Statistical Arbitrage Module
+//!
+//! This module monitors the spread between two mid-price series, either two correlated
+//! symbols or the same symbol on two exchanges, and signals when it strays from its rolling
+//! mean. The spread is `ln(first) - hedge_ratio * ln(second)`, with the hedge ratio either
+//! fixed or estimated by least squares over the window, the usual first step of an
+//! Engle-Granger cointegration test. A signal is published when the spread's z-score crosses
+//! the entry threshold, and the pair is re-armed once it falls back inside.
+
+use crate::basis::exchange_mids;
+use crate::normalize_symbol;
+use aggregator_core::{Exchange, Result, Summary};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Stat Arb Config
+///
+/// Window and threshold settings for spread monitoring.
+///
+/// ## Fields
+///
+/// - `window`: Number of spread samples the mean and standard deviation are taken over. A
+///   sample is taken every time either leg's mid price changes.
+/// - `min_samples`: Samples required before any z-score is reported.
+/// - `entry_z`: Absolute z-score at which a signal is published.
+/// - `hedge_ratio`: Units of the second leg's log price per unit of the first's. `None`
+///   estimates it over the window by regressing the first leg on the second.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatArbConfig {
+    pub window: usize,
+    pub min_samples: usize,
+    pub entry_z: f64,
+    pub hedge_ratio: Option<f64>,
+}
+
+impl Default for StatArbConfig {
+    fn default() -> Self {
+        Self {
+            window: 500,
+            min_samples: 30,
+            entry_z: 2.0,
+            hedge_ratio: None,
+        }
+    }
+}
+
+/// # Spread Leg
+///
+/// One mid-price series: a symbol on an exchange.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SpreadLeg {
+    pub symbol: String,
+    pub exchange: Exchange,
+}
+
+impl SpreadLeg {
+    /// Creates a leg for `symbol` on `exchange`. Symbols are matched with separators removed,
+    /// so `BTC-USDT` and `BTCUSDT` name the same series.
+    pub fn new(symbol: &str, exchange: Exchange) -> Self {
+        Self {
+            symbol: normalize_symbol(symbol),
+            exchange,
+        }
+    }
+}
+
+/// # Spread Direction
+///
+/// Which side of its mean the spread has moved to.
+///
+/// - `Above`: The first leg is rich relative to the second; the reversion trade sells the
+///   first and buys the second.
+/// - `Below`: The first leg is cheap relative to the second; the reversion trade buys the
+///   first and sells the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpreadDirection {
+    Above,
+    Below,
+}
+
+/// # Spread Statistics
+///
+/// The state of one monitored spread after its latest sample.
+///
+/// ## Fields
+///
+/// - `name`: The name the pair was registered under.
+/// - `first`, `second`: The legs of the spread.
+/// - `hedge_ratio`: The hedge ratio the spread was computed with.
+/// - `spread`: The latest spread.
+/// - `mean`, `std_dev`: Mean and population standard deviation of the spread over the window.
+/// - `z_score`: `(spread - mean) / std_dev`, 0 when the spread has not varied.
+/// - `samples`: Number of samples in the window.
+/// - `timestamp`: Time of the summary that produced the latest sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpreadStatistics {
+    pub name: String,
+    pub first: SpreadLeg,
+    pub second: SpreadLeg,
+    pub hedge_ratio: f64,
+    pub spread: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub z_score: f64,
+    pub samples: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// # Spread Signal
+///
+/// Published when a spread's z-score crosses the entry threshold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpreadSignal {
+    pub direction: SpreadDirection,
+    pub statistics: SpreadStatistics,
+}
+
+/// One registered pair and its rolling history of log prices.
+#[derive(Debug)]
+struct PairState {
+    name: String,
+    first: SpreadLeg,
+    second: SpreadLeg,
+    samples: VecDeque<(f64, f64)>,
+    latest: Option<SpreadStatistics>,
+    signalled: bool,
+}
+
+/// Latest mid of every leg and the state of every pair.
+#[derive(Debug, Default)]
+struct MonitorState {
+    mids: HashMap<SpreadLeg, f64>,
+    pairs: Vec<PairState>,
+}
+
+/// # Stat Arb Monitor
+///
+/// Rolling z-score monitor over registered spreads. Register pairs with
+/// [`StatArbMonitor::add_pair`], feed it summaries with [`StatArbMonitor::record_summary`]
+/// or [`StatArbMonitor::spawn`], and read signals from [`StatArbMonitor::subscribe`].
+/// Cloning the monitor shares its state and channel.
+///
+/// ## Fields
+///
+/// - `config`: Window, thresholds and hedge ratio.
+/// - `state`: Latest leg mids and the history of every pair.
+#[derive(Debug, Clone)]
+pub struct StatArbMonitor {
+    config: StatArbConfig,
+    state: Arc<RwLock<MonitorState>>,
+    signal_sender: broadcast::Sender<SpreadSignal>,
+}
+
+impl StatArbMonitor {
+    /// ## New
+    ///
+    /// Creates a monitor with the given settings and no pairs.
+    pub fn new(config: StatArbConfig) -> Self {
+        let (signal_sender, _) = broadcast::channel(1000);
+        Self {
+            config,
+            state: Arc::new(RwLock::new(MonitorState::default())),
+            signal_sender,
+        }
+    }
+
+    /// ## Add Pair
+    ///
+    /// Registers the spread between `first` and `second` under `name`. Sampling starts with
+    /// the next summary for either leg.
+    pub async fn add_pair(&self, name: &str, first: SpreadLeg, second: SpreadLeg) {
+        let mut state = self.state.write().await;
+        state.pairs.push(PairState {
+            name: name.to_string(),
+            first,
+            second,
+            samples: VecDeque::new(),
+            latest: None,
+            signalled: false,
+        });
+    }
+
+    /// ## Subscribe
+    ///
+    /// Returns a receiver for the signals published by `record_summary`.
+    pub fn subscribe(&self) -> broadcast::Receiver<SpreadSignal> {
+        self.signal_sender.subscribe()
+    }
+
+    /// ## Record Summary
+    ///
+    /// Updates the mid of every leg the summary quotes and samples every pair with a leg
+    /// among them, once both of its legs have a mid.
+    ///
+    /// ### Returns
+    ///
+    /// The signals published: one for each pair whose z-score reached `entry_z` on this
+    /// summary after being inside it.
+    pub async fn record_summary(&self, summary: &Summary) -> Vec<SpreadSignal> {
+        let symbol = normalize_symbol(&summary.symbol);
+        let mut state = self.state.write().await;
+        let MonitorState { mids, pairs } = &mut *state;
+
+        let mut updated = Vec::new();
+        for (exchange, mid) in exchange_mids(summary) {
+            let leg = SpreadLeg {
+                symbol: symbol.clone(),
+                exchange,
+            };
+            mids.insert(leg.clone(), mid);
+            updated.push(leg);
+        }
+
+        let mut signals = Vec::new();
+        for pair in pairs
+            .iter_mut()
+            .filter(|pair| updated.contains(&pair.first) || updated.contains(&pair.second))
+        {
+            let (Some(first), Some(second)) = (mids.get(&pair.first), mids.get(&pair.second))
+            else {
+                continue;
+            };
+            pair.samples.push_back((first.ln(), second.ln()));
+            while pair.samples.len() > self.config.window.max(2) {
+                pair.samples.pop_front();
+            }
+
+            let Some(statistics) = self.statistics(pair, summary.timestamp) else {
+                continue;
+            };
+            let outside = statistics.z_score.abs() >= self.config.entry_z;
+            if outside && !pair.signalled {
+                let direction = if statistics.z_score > 0.0 {
+                    SpreadDirection::Above
+                } else {
+                    SpreadDirection::Below
+                };
+                signals.push(SpreadSignal {
+                    direction,
+                    statistics: statistics.clone(),
+                });
+            }
+            pair.signalled = outside;
+            pair.latest = Some(statistics);
+        }
+        drop(state);
+
+        for signal in &signals {
+            // Sending only fails when nobody is subscribed
+            let _ = self.signal_sender.send(signal.clone());
+        }
+        signals
+    }
+
+    /// ## Statistics
+    ///
+    /// Returns the latest statistics of the pair registered as `name`, once it has at least
+    /// `min_samples` samples.
+    pub async fn statistics_for(&self, name: &str) -> Option<SpreadStatistics> {
+        let state = self.state.read().await;
+        state
+            .pairs
+            .iter()
+            .find(|pair| pair.name == name)
+            .and_then(|pair| pair.latest.clone())
+    }
+
+    /// ## Spawn
+    ///
+    /// Spawns a task that records every summary received on `summary_rx`, typically from
+    /// `Aggregator::subscribe_summaries()`, until the channel closes.
+    pub fn spawn(&self, mut summary_rx: broadcast::Receiver<Summary>) -> JoinHandle<Result<()>> {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match summary_rx.recv().await {
+                    Ok(summary) => {
+                        monitor.record_summary(&summary).await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Stat arb monitor lagged, skipped {} summaries", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Computes the spread statistics of a pair over its window.
+    fn statistics(&self, pair: &PairState, timestamp: DateTime<Utc>) -> Option<SpreadStatistics> {
+        let samples = pair.samples.len();
+        if samples < self.config.min_samples.max(2) {
+            return None;
+        }
+
+        let n = samples as f64;
+        let hedge_ratio = self.config.hedge_ratio.unwrap_or_else(|| {
+            let mean_first = pair.samples.iter().map(|(first, _)| first).sum::<f64>() / n;
+            let mean_second = pair.samples.iter().map(|(_, second)| second).sum::<f64>() / n;
+            let (covariance, variance) =
+                pair.samples
+                    .iter()
+                    .fold((0.0, 0.0), |(covariance, variance), (first, second)| {
+                        let deviation = second - mean_second;
+                        (
+                            covariance + (first - mean_first) * deviation,
+                            variance + deviation * deviation,
+                        )
+                    });
+            // A flat second leg carries no information about the ratio
+            if variance > 0.0 {
+                covariance / variance
+            } else {
+                1.0
+            }
+        });
+
+        let spreads: Vec<f64> = pair
+            .samples
+            .iter()
+            .map(|(first, second)| first - hedge_ratio * second)
+            .collect();
+        let mean = spreads.iter().sum::<f64>() / n;
+        let variance = spreads
+            .iter()
+            .map(|spread| (spread - mean).powi(2))
+            .sum::<f64>()
+            / n;
+        let std_dev = variance.sqrt();
+        let spread = *spreads.last()?;
+        let z_score = if std_dev > 0.0 {
+            (spread - mean) / std_dev
+        } else {
+            0.0
+        };
+
+        Some(SpreadStatistics {
+            name: pair.name.clone(),
+            first: pair.first.clone(),
+            second: pair.second.clone(),
+            hedge_ratio,
+            spread,
+            mean,
+            std_dev,
+            z_score,
+            samples,
+            timestamp,
+        })
+    }
+}
+
+impl Default for StatArbMonitor {
+    fn default() -> Self {
+        Self::new(StatArbConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregator_core::PriceLevel;
+
+    fn book(symbol: &str, exchange: Exchange, mid: f64) -> Summary {
+        let level = |price| PriceLevel {
+            price,
+            quantity: 1.0,
+            exchange: exchange.clone(),
+            timestamp: Utc::now(),
+        };
+        Summary {
+            symbol: symbol.to_string(),
+            spread: mid * 0.0002,
+            bids: vec![level(mid * 0.9999)],
+            asks: vec![level(mid * 1.0001)],
+            timestamp: Utc::now(),
+            market_type: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signal_when_venue_spread_diverges() {
+        let monitor = StatArbMonitor::new(StatArbConfig {
+            window: 50,
+            min_samples: 10,
+            entry_z: 2.0,
+            hedge_ratio: Some(1.0),
+        });
+        monitor
+            .add_pair(
+                "btc-venues",
+                SpreadLeg::new("BTCUSDT", Exchange::Binance),
+                SpreadLeg::new("BTC-USDT", Exchange::Kraken),
+            )
+            .await;
+        let mut receiver = monitor.subscribe();
+
+        // Kraken oscillates a little around Binance
+        monitor
+            .record_summary(&book("BTCUSDT", Exchange::Binance, 50000.0))
+            .await;
+        for i in 0..20 {
+            let offset = if i % 2 == 0 { 5.0 } else { -5.0 };
+            let signals = monitor
+                .record_summary(&book("BTCUSDT", Exchange::Kraken, 50000.0 + offset))
+                .await;
+            assert!(signals.is_empty());
+        }
+
+        // Binance jumps well above Kraken
+        let signals = monitor
+            .record_summary(&book("BTCUSDT", Exchange::Binance, 50100.0))
+            .await;
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].direction, SpreadDirection::Above);
+        assert!(signals[0].statistics.z_score > 2.0);
+        assert_eq!(receiver.recv().await.unwrap(), signals[0]);
+
+        // Still outside the threshold: no repeated signal until the pair re-arms
+        let signals = monitor
+            .record_summary(&book("BTCUSDT", Exchange::Binance, 50100.0))
+            .await;
+        assert!(signals.is_empty());
+        let statistics = monitor.statistics_for("btc-venues").await.unwrap();
+        assert_eq!(statistics.samples, 22);
+    }
+
+    #[tokio::test]
+    async fn test_hedge_ratio_estimated_from_log_prices() {
+        let monitor = StatArbMonitor::new(StatArbConfig {
+            window: 100,
+            min_samples: 5,
+            ..Default::default()
+        });
+        monitor
+            .add_pair(
+                "eth-btc",
+                SpreadLeg::new("ETHUSDT", Exchange::Binance),
+                SpreadLeg::new("BTCUSDT", Exchange::Binance),
+            )
+            .await;
+
+        // ETH moves with the square of BTC, so the log-price ratio is 2
+        for i in 0..10 {
+            let btc = 2000.0 + i as f64 * 20.0;
+            let eth = btc * btc / 1000.0;
+            monitor
+                .record_summary(&book("ETHUSDT", Exchange::Binance, eth))
+                .await;
+            monitor
+                .record_summary(&book("BTCUSDT", Exchange::Binance, btc))
+                .await;
+        }
+
+        assert!(monitor.statistics_for("missing").await.is_none());
+        let statistics = monitor.statistics_for("eth-btc").await.unwrap();
+        assert!((statistics.hedge_ratio - 2.0).abs() < 0.05);
+        assert!(statistics.std_dev < 0.01);
+    }
+}
@@ -0,0 +1,344 @@
+//! # Streaming Analysis Module
+//!
+//! `DefaultAnalysisEngine` and `ArbitrageDetector` rescan a full snapshot of summaries on every
+//! call. `StreamingAnalysisEngine` consumes the aggregator's summary broadcast instead, keeps the
+//! latest book of every exchange for every pair, and re-evaluates only the pair an incoming
+//! summary belongs to. Opportunities and per-book metrics are published on broadcast channels as
+//! soon as each summary is processed.
+
+use crate::arbitrage::ArbitrageDetector;
+use crate::data_quality::DataQualityMonitor;
+use crate::normalize_symbol;
+use aggregator_core::{ArbitrageOpportunity, Config, Exchange, Result, Summary, TradingPair};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Quote currencies recognised at the end of a symbol with no separator, longest first so
+/// `USDT` wins over `USD`.
+const KNOWN_QUOTES: &[&str] = &[
+    "FDUSD", "USDT", "USDC", "BUSD", "TUSD", "USD", "EUR", "GBP", "BTC", "ETH", "BNB",
+];
+
+/// # Book Metrics
+///
+/// Top-of-book metrics for one exchange's book, published every time a summary for it is
+/// processed.
+///
+/// ## Fields
+///
+/// - `pair`: The pair the book quotes.
+/// - `exchange`: The exchange the book belongs to.
+/// - `spread`: Best ask minus best bid, if both sides are present.
+/// - `mid_price`: Midpoint of the best bid and ask, if both sides are present.
+/// - `volume_weighted_price`: Average price of every level on both sides weighted by quantity,
+///   if the book holds any quantity.
+/// - `timestamp`: The timestamp of the summary the metrics were taken from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookMetrics {
+    pub pair: TradingPair,
+    pub exchange: Exchange,
+    pub spread: Option<f64>,
+    pub mid_price: Option<f64>,
+    pub volume_weighted_price: Option<f64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl BookMetrics {
+    fn from_summary(pair: TradingPair, exchange: Exchange, summary: &Summary) -> Self {
+        let best = summary.bids.first().zip(summary.asks.first());
+        let (volume, notional) = summary.bids.iter().chain(&summary.asks).fold(
+            (0.0, 0.0),
+            |(volume, notional), level| {
+                (
+                    volume + level.quantity,
+                    notional + level.price * level.quantity,
+                )
+            },
+        );
+        Self {
+            pair,
+            exchange,
+            spread: best.map(|(bid, ask)| ask.price - bid.price),
+            mid_price: best.map(|(bid, ask)| (ask.price + bid.price) / 2.0),
+            volume_weighted_price: (volume > 0.0).then(|| notional / volume),
+            timestamp: summary.timestamp,
+        }
+    }
+}
+
+/// # Streaming Analysis Engine
+///
+/// Incremental arbitrage detection over the aggregator's summary broadcast. Feed it summaries
+/// with [`StreamingAnalysisEngine::process_summary`] or hand it a receiver with
+/// [`StreamingAnalysisEngine::spawn`], and read results from
+/// [`StreamingAnalysisEngine::subscribe_opportunities`] and
+/// [`StreamingAnalysisEngine::subscribe_metrics`]. Cloning the engine shares its state and
+/// channels.
+///
+/// ## Fields
+///
+/// - `detector`: Decides which cross-exchange prices are opportunities.
+/// - `pairs`: Pairs registered with `with_pairs`, keyed by their symbol with separators
+///   removed.
+/// - `books`: The latest book of every exchange for every pair.
+/// - `max_age`: Books whose summary is older than this are left out of detection.
+/// - `data_quality`: Checks every summary before detection; books it excludes are left out.
+pub struct StreamingAnalysisEngine {
+    detector: Arc<ArbitrageDetector>,
+    pairs: Arc<HashMap<String, TradingPair>>,
+    books: Arc<RwLock<HashMap<TradingPair, HashMap<Exchange, Summary>>>>,
+    max_age: Option<Duration>,
+    data_quality: Option<DataQualityMonitor>,
+    opportunity_sender: broadcast::Sender<ArbitrageOpportunity>,
+    metrics_sender: broadcast::Sender<BookMetrics>,
+}
+
+impl StreamingAnalysisEngine {
+    /// ## New
+    ///
+    /// Creates an engine that judges opportunities with `detector`.
+    pub fn new(detector: ArbitrageDetector) -> Self {
+        let (opportunity_sender, _) = broadcast::channel(1000);
+        let (metrics_sender, _) = broadcast::channel(1000);
+        Self {
+            detector: Arc::new(detector),
+            pairs: Arc::new(HashMap::new()),
+            books: Arc::new(RwLock::new(HashMap::new())),
+            max_age: None,
+            data_quality: None,
+            opportunity_sender,
+            metrics_sender,
+        }
+    }
+
+    /// ## From Config
+    ///
+    /// Creates an engine that judges opportunities with [`ArbitrageDetector::from_config`],
+    /// resolves the symbols of `config.trading_pairs`, and leaves out books older than
+    /// `config.analysis.max_opportunity_age_ms` when it is set.
+    pub fn from_config(config: &Config) -> Self {
+        let engine = Self::new(ArbitrageDetector::from_config(config))
+            .with_pairs(config.trading_pairs.iter().cloned());
+        match config.analysis.max_opportunity_age_ms {
+            Some(max_age_ms) => engine.with_max_age(Duration::milliseconds(max_age_ms as i64)),
+            None => engine,
+        }
+    }
+
+    /// ## With Pairs
+    ///
+    /// Registers the pairs summaries are expected for, so their symbols resolve to the right
+    /// base and quote whatever separator the exchange uses. Symbols of unregistered pairs are
+    /// split on `/`, `-` or `_`, or else on a known quote currency suffix.
+    pub fn with_pairs(mut self, pairs: impl IntoIterator<Item = TradingPair>) -> Self {
+        let mut registered = HashMap::new();
+        for pair in pairs {
+            registered.insert(format!("{}{}", pair.base, pair.quote), pair);
+        }
+        self.pairs = Arc::new(registered);
+        self
+    }
+
+    /// ## With Max Age
+    ///
+    /// Leaves books whose last summary is older than `max_age` out of detection, so an
+    /// exchange that stopped updating cannot produce opportunities from stale prices.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// ## With Data Quality
+    ///
+    /// Passes every summary through `monitor` before detection and leaves out the books it
+    /// excludes, so crossed, empty or jumping books and exchanges whose update rate collapsed
+    /// cannot produce opportunities. The engine feeds the monitor itself; run
+    /// `DataQualityMonitor::spawn_rate_checks` to detect rate collapses.
+    pub fn with_data_quality(mut self, monitor: DataQualityMonitor) -> Self {
+        self.data_quality = Some(monitor);
+        self
+    }
+
+    /// Subscribes to opportunities as they are found.
+    pub fn subscribe_opportunities(&self) -> broadcast::Receiver<ArbitrageOpportunity> {
+        self.opportunity_sender.subscribe()
+    }
+
+    /// Subscribes to the metrics of every book as it is updated.
+    pub fn subscribe_metrics(&self) -> broadcast::Receiver<BookMetrics> {
+        self.metrics_sender.subscribe()
+    }
+
+    /// ## Process Summary
+    ///
+    /// Replaces the stored book of every exchange the summary carries levels for, publishes
+    /// their metrics, and re-runs detection for the summary's pair only. A consolidated
+    /// summary is split by the exchange on each level. Books excluded by the data quality
+    /// monitor, if one is set, are left out of detection but still stored.
+    ///
+    /// ### Returns
+    ///
+    /// The opportunities found for the pair, which are also published to subscribers.
+    pub async fn process_summary(&self, summary: Summary) -> Vec<ArbitrageOpportunity> {
+        let pair = self.pair_for(&summary.symbol);
+        let excluded = match &self.data_quality {
+            Some(monitor) => {
+                monitor.record_summary(&summary).await;
+                monitor
+                    .excluded_exchanges(&summary.symbol, Utc::now())
+                    .await
+            }
+            None => HashSet::new(),
+        };
+
+        let mut books = self.books.write().await;
+        let pair_books = books.entry(pair.clone()).or_default();
+        for (exchange, book) in split_by_exchange(summary) {
+            // Sending only fails when nobody is subscribed
+            let _ = self.metrics_sender.send(BookMetrics::from_summary(
+                pair.clone(),
+                exchange.clone(),
+                &book,
+            ));
+            pair_books.insert(exchange, book);
+        }
+
+        let cutoff = self.max_age.map(|max_age| Utc::now() - max_age);
+        let current: Vec<Summary> = pair_books
+            .iter()
+            .filter(|(exchange, book)| {
+                !excluded.contains(*exchange)
+                    && cutoff.is_none_or(|cutoff| book.timestamp >= cutoff)
+            })
+            .map(|(_, book)| book.clone())
+            .collect();
+        drop(books);
+
+        if current.len() < 2 {
+            return Vec::new();
+        }
+
+        let snapshot = HashMap::from([(pair, current)]);
+        let opportunities = self.detector.detect_opportunities(&snapshot).await;
+        for opportunity in &opportunities {
+            let _ = self.opportunity_sender.send(opportunity.clone());
+        }
+        opportunities
+    }
+
+    /// ## Books
+    ///
+    /// Returns the latest stored book of every exchange for `pair`.
+    pub async fn books(&self, pair: &TradingPair) -> HashMap<Exchange, Summary> {
+        let books = self.books.read().await;
+        books.get(pair).cloned().unwrap_or_default()
+    }
+
+    /// ## Spawn
+    ///
+    /// Spawns a task that processes every summary received on `summary_rx`, typically from
+    /// `Aggregator::subscribe_summaries()`, until the channel closes. A lagging engine skips
+    /// the summaries it missed rather than working through a backlog, so results never
+    /// trail the market by more than the channel's capacity.
+    pub fn spawn(&self, mut summary_rx: broadcast::Receiver<Summary>) -> JoinHandle<Result<()>> {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match summary_rx.recv().await {
+                    Ok(summary) => {
+                        engine.process_summary(summary).await;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Streaming analysis lagged, skipped {} summaries", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Resolves a summary symbol such as `BTCUSDT`, `BTC-USDT` or `btc/usdt` to its pair.
+    fn pair_for(&self, symbol: &str) -> TradingPair {
+        let normalized = normalize_symbol(symbol);
+        if let Some(pair) = self.pairs.get(&normalized) {
+            return pair.clone();
+        }
+
+        if let Some((base, quote)) = symbol.split_once(['/', '-', '_']) {
+            return TradingPair::new(base, quote);
+        }
+        KNOWN_QUOTES
+            .iter()
+            .find_map(|quote| {
+                normalized
+                    .strip_suffix(quote)
+                    .filter(|base| !base.is_empty())
+                    .map(|base| TradingPair::new(base, quote))
+            })
+            .unwrap_or_else(|| TradingPair::new(&normalized, ""))
+    }
+}
+
+impl Clone for StreamingAnalysisEngine {
+    fn clone(&self) -> Self {
+        Self {
+            detector: self.detector.clone(),
+            pairs: self.pairs.clone(),
+            books: self.books.clone(),
+            max_age: self.max_age,
+            data_quality: self.data_quality.clone(),
+            opportunity_sender: self.opportunity_sender.clone(),
+            metrics_sender: self.metrics_sender.clone(),
+        }
+    }
+}
+
+impl Default for StreamingAnalysisEngine {
+    fn default() -> Self {
+        Self::new(ArbitrageDetector::default())
+    }
+}
+
+/// Splits a summary into one summary per exchange, keeping each side's level order
+fn split_by_exchange(mut summary: Summary) -> Vec<(Exchange, Summary)> {
+    let bids = std::mem::take(&mut summary.bids);
+    let asks = std::mem::take(&mut summary.asks);
+
+    let mut books: Vec<(Exchange, Summary)> = Vec::new();
+    for bid in bids {
+        book_for(&mut books, &summary, &bid.exchange).bids.push(bid);
+    }
+    for ask in asks {
+        book_for(&mut books, &summary, &ask.exchange).asks.push(ask);
+    }
+
+    for (_, book) in books.iter_mut() {
+        book.spread = match (book.bids.first(), book.asks.first()) {
+            (Some(bid), Some(ask)) => ask.price - bid.price,
+            _ => 0.0,
+        };
+    }
+    books
+}
+
+/// The book of `exchange` in `books`, added as an empty copy of `template` on first sight
+fn book_for<'a>(
+    books: &'a mut Vec<(Exchange, Summary)>,
+    template: &Summary,
+    exchange: &Exchange,
+) -> &'a mut Summary {
+    let index = match books.iter().position(|(e, _)| e == exchange) {
+        Some(index) => index,
+        None => {
+            books.push((exchange.clone(), template.clone()));
+            books.len() - 1
+        }
+    };
+    &mut books[index].1
+}
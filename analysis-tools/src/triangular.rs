@@ -0,0 +1,308 @@
+//! # Triangular Arbitrage Module
+//!
+//! This module looks for profitable cycles through three currencies on a single exchange, such
+//! as BTC→ETH→USDT→BTC. Every pair quoted on the exchange contributes two edges to a currency
+//! graph: selling the base at the best bid, and buying it at the best ask. A cycle is an
+//! opportunity when converting one unit around it returns more than one unit.
+
+use aggregator_core::{Exchange, Summary, TradeSide, TradingPair};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// # Triangular Leg
+///
+/// One conversion in a triangular cycle.
+///
+/// ## Fields
+///
+/// - `pair`: The pair traded.
+/// - `side`: `Buy` lifts the ask to buy the base with the quote, `Sell` hits the bid to sell
+///   the base for the quote.
+/// - `price`: The best ask for a buy or the best bid for a sell, in quote per base.
+/// - `from`: The currency spent.
+/// - `to`: The currency received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriangularLeg {
+    pub pair: TradingPair,
+    pub side: TradeSide,
+    pub price: f64,
+    pub from: String,
+    pub to: String,
+}
+
+impl TriangularLeg {
+    /// Units of `to` received for one unit of `from`.
+    pub fn rate(&self) -> f64 {
+        match self.side {
+            TradeSide::Buy => 1.0 / self.price,
+            TradeSide::Sell => self.price,
+        }
+    }
+}
+
+/// # Triangular Opportunity
+///
+/// A profitable cycle through three currencies on one exchange, at top-of-book prices.
+///
+/// ## Fields
+///
+/// - `exchange`: The exchange all three legs trade on.
+/// - `legs`: The conversions in order; the first leg starts and the last leg ends in the same
+///   currency.
+/// - `implied_rate`: Units of the start currency returned per unit put in.
+/// - `profit_percentage`: `(implied_rate - 1) * 100`.
+/// - `volume`: The largest amount of the start currency the best levels of all three legs can
+///   absorb.
+/// - `timestamp`: When the opportunity was detected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriangularOpportunity {
+    pub exchange: Exchange,
+    pub legs: Vec<TriangularLeg>,
+    pub implied_rate: f64,
+    pub profit_percentage: f64,
+    pub volume: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl TriangularOpportunity {
+    /// The currency the cycle starts and ends in.
+    pub fn start_currency(&self) -> &str {
+        &self.legs[0].from
+    }
+
+    /// The currencies visited, starting and ending with the start currency.
+    pub fn path(&self) -> Vec<&str> {
+        let mut path = vec![self.start_currency()];
+        path.extend(self.legs.iter().map(|leg| leg.to.as_str()));
+        path
+    }
+}
+
+/// Best bid and ask of one pair on one exchange, as `(price, quantity)`.
+#[derive(Debug, Default)]
+pub(crate) struct Quote {
+    pub(crate) bid: Option<(f64, f64)>,
+    pub(crate) ask: Option<(f64, f64)>,
+}
+
+/// A conversion out of a currency, with how much of that currency the best level absorbs.
+struct Edge {
+    leg: TriangularLeg,
+    capacity: f64,
+}
+
+/// # Triangular Arbitrage Detector
+///
+/// Searches the pairs quoted on each exchange for three-currency cycles that return more than
+/// they cost at the best bid and ask.
+///
+/// ## Fields
+///
+/// - `min_profit_threshold`: The minimum profit percentage required to report a cycle.
+pub struct TriangularArbitrageDetector {
+    min_profit_threshold: f64,
+}
+
+impl TriangularArbitrageDetector {
+    /// ## New
+    ///
+    /// Creates a new `TriangularArbitrageDetector` with the specified profit threshold.
+    ///
+    /// ### Arguments
+    ///
+    /// - `min_profit_threshold`: A `f64` representing the minimum profit percentage.
+    pub fn new(min_profit_threshold: f64) -> Self {
+        Self {
+            min_profit_threshold,
+        }
+    }
+
+    /// ## Detect Opportunities
+    ///
+    /// Builds one currency graph per exchange from the levels in `summaries` and returns the
+    /// profitable cycles on every exchange. Levels are attributed to the exchange they carry,
+    /// so consolidated summaries spanning several exchanges are split correctly and no cycle
+    /// mixes exchanges.
+    ///
+    /// ### Arguments
+    ///
+    /// - `summaries`: A `HashMap` where the key is a `TradingPair` and the value is a `Vec`
+    ///   of `Summary` objects for that pair.
+    ///
+    /// ### Returns
+    ///
+    /// A `Vec` of `TriangularOpportunity`, most profitable first. Each cycle is reported once,
+    /// starting from the alphabetically first of its currencies.
+    pub async fn detect_opportunities(
+        &self,
+        summaries: &HashMap<TradingPair, Vec<Summary>>,
+    ) -> Vec<TriangularOpportunity> {
+        let mut opportunities: Vec<TriangularOpportunity> = Self::quotes_by_exchange(summaries)
+            .into_iter()
+            .flat_map(|(exchange, quotes)| self.find_cycles(exchange, &quotes))
+            .collect();
+        opportunities.sort_by(|a, b| b.profit_percentage.total_cmp(&a.profit_percentage));
+        opportunities
+    }
+
+    /// ## Detect On Exchange
+    ///
+    /// Same as `detect_opportunities`, restricted to the levels of `exchange`.
+    pub async fn detect_on_exchange(
+        &self,
+        exchange: &Exchange,
+        summaries: &HashMap<TradingPair, Vec<Summary>>,
+    ) -> Vec<TriangularOpportunity> {
+        let mut opportunities = Self::quotes_by_exchange(summaries)
+            .remove(exchange)
+            .map(|quotes| self.find_cycles(exchange.clone(), &quotes))
+            .unwrap_or_default();
+        opportunities.sort_by(|a, b| b.profit_percentage.total_cmp(&a.profit_percentage));
+        opportunities
+    }
+
+    /// Collects the best bid and ask of every pair on every exchange, skipping levels with a
+    /// non-positive or non-finite price or quantity.
+    pub(crate) fn quotes_by_exchange(
+        summaries: &HashMap<TradingPair, Vec<Summary>>,
+    ) -> HashMap<Exchange, HashMap<TradingPair, Quote>> {
+        let usable = |price: f64, quantity: f64| {
+            price.is_finite() && price > 0.0 && quantity.is_finite() && quantity > 0.0
+        };
+
+        let mut quotes: HashMap<Exchange, HashMap<TradingPair, Quote>> = HashMap::new();
+        for (pair, pair_summaries) in summaries {
+            for summary in pair_summaries {
+                for bid in &summary.bids {
+                    if !usable(bid.price, bid.quantity) {
+                        continue;
+                    }
+                    let quote = quotes
+                        .entry(bid.exchange.clone())
+                        .or_default()
+                        .entry(pair.clone())
+                        .or_default();
+                    if quote.bid.is_none_or(|(price, _)| bid.price > price) {
+                        quote.bid = Some((bid.price, bid.quantity));
+                    }
+                }
+                for ask in &summary.asks {
+                    if !usable(ask.price, ask.quantity) {
+                        continue;
+                    }
+                    let quote = quotes
+                        .entry(ask.exchange.clone())
+                        .or_default()
+                        .entry(pair.clone())
+                        .or_default();
+                    if quote.ask.is_none_or(|(price, _)| ask.price < price) {
+                        quote.ask = Some((ask.price, ask.quantity));
+                    }
+                }
+            }
+        }
+        quotes
+    }
+
+    /// Finds every profitable three-currency cycle among one exchange's quotes.
+    fn find_cycles(
+        &self,
+        exchange: Exchange,
+        quotes: &HashMap<TradingPair, Quote>,
+    ) -> Vec<TriangularOpportunity> {
+        let mut graph: HashMap<&str, Vec<Edge>> = HashMap::new();
+        for (pair, quote) in quotes {
+            if pair.base == pair.quote {
+                continue;
+            }
+            if let Some((price, quantity)) = quote.bid {
+                graph.entry(&pair.base).or_default().push(Edge {
+                    leg: TriangularLeg {
+                        pair: pair.clone(),
+                        side: TradeSide::Sell,
+                        price,
+                        from: pair.base.clone(),
+                        to: pair.quote.clone(),
+                    },
+                    capacity: quantity,
+                });
+            }
+            if let Some((price, quantity)) = quote.ask {
+                graph.entry(&pair.quote).or_default().push(Edge {
+                    leg: TriangularLeg {
+                        pair: pair.clone(),
+                        side: TradeSide::Buy,
+                        price,
+                        from: pair.quote.clone(),
+                        to: pair.base.clone(),
+                    },
+                    capacity: quantity * price,
+                });
+            }
+        }
+
+        let mut currencies: Vec<&str> = graph.keys().copied().collect();
+        currencies.sort_unstable();
+
+        let mut opportunities = Vec::new();
+        for start in currencies {
+            // Only cycles whose other currencies sort after `start` are walked, so each cycle
+            // is found once
+            for first in &graph[start] {
+                let middle = first.leg.to.as_str();
+                if middle <= start {
+                    continue;
+                }
+                for second in graph.get(middle).into_iter().flatten() {
+                    let last = second.leg.to.as_str();
+                    if last <= start || last == middle {
+                        continue;
+                    }
+                    for third in graph.get(last).into_iter().flatten() {
+                        if third.leg.to != start {
+                            continue;
+                        }
+                        if let Some(opportunity) = self.evaluate(&exchange, [first, second, third])
+                        {
+                            opportunities.push(opportunity);
+                        }
+                    }
+                }
+            }
+        }
+        opportunities
+    }
+
+    /// Prices one cycle, returning it if it clears the profit threshold.
+    fn evaluate(&self, exchange: &Exchange, edges: [&Edge; 3]) -> Option<TriangularOpportunity> {
+        let implied_rate: f64 = edges.iter().map(|edge| edge.leg.rate()).product();
+        let profit_percentage = (implied_rate - 1.0) * 100.0;
+        if implied_rate <= 1.0 || profit_percentage < self.min_profit_threshold {
+            return None;
+        }
+
+        // Each leg's capacity is in the currency it spends; scale it back to the start
+        let mut volume = f64::INFINITY;
+        let mut reached = 1.0;
+        for edge in edges {
+            volume = volume.min(edge.capacity / reached);
+            reached *= edge.leg.rate();
+        }
+
+        Some(TriangularOpportunity {
+            exchange: exchange.clone(),
+            legs: edges.iter().map(|edge| edge.leg.clone()).collect(),
+            implied_rate,
+            profit_percentage,
+            volume,
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+impl Default for TriangularArbitrageDetector {
+    fn default() -> Self {
+        Self::new(0.1) // 0.1% profit threshold
+    }
+}
@@ -0,0 +1,486 @@
+//! # Windowed Price Module
+//!
+//! `calculate_volume_weighted_price` averages the levels of a single book snapshot. This module
+//! averages prices over time instead: it keeps a rolling history of prices per symbol, fed by
+//! trade prints or by summaries, and computes the volume-weighted (VWAP) and time-weighted
+//! (TWAP) average price over any window up to the retention, both across exchanges and broken
+//! down per exchange.
+
+use crate::normalize_symbol;
+use aggregator_core::{Exchange, Result, Summary, Trade};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// # Windowed Price Config
+///
+/// Retention settings for price history.
+///
+/// ## Fields
+///
+/// - `window_secs`: How far back history is kept. Averages can be asked for over any window
+///   up to this length.
+/// - `max_samples`: Upper bound on the samples kept per symbol, oldest dropped first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowedPriceConfig {
+    pub window_secs: u64,
+    pub max_samples: usize,
+}
+
+impl Default for WindowedPriceConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 3600,
+            max_samples: 100_000,
+        }
+    }
+}
+
+/// # Price Average
+///
+/// Average prices of one series of samples over a window.
+///
+/// ## Fields
+///
+/// - `vwap`: Prices weighted by their volume, or `None` if no volume traded.
+/// - `twap`: Prices weighted by how long each one held until the next sample, or `None` with
+///   no samples. Samples that all share one timestamp are averaged evenly.
+/// - `volume`: Total volume of the samples.
+/// - `samples`: Number of samples in the window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceAverage {
+    pub vwap: Option<f64>,
+    pub twap: Option<f64>,
+    pub volume: f64,
+    pub samples: usize,
+}
+
+/// # Windowed Averages
+///
+/// VWAP and TWAP of one symbol over a window, across every exchange and per exchange.
+///
+/// ## Fields
+///
+/// - `symbol`: The symbol with separators removed, e.g. `BTCUSDT` for `BTC-USDT`.
+/// - `window_secs`: The window the averages cover, ending at `timestamp`.
+/// - `overall`: Averages over the samples of every exchange.
+/// - `by_exchange`: Averages over the samples of each exchange on its own.
+/// - `timestamp`: Time of the latest sample for the symbol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowedAverages {
+    pub symbol: String,
+    pub window_secs: u64,
+    pub overall: PriceAverage,
+    pub by_exchange: BTreeMap<Exchange, PriceAverage>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// One price observation kept for the window.
+#[derive(Debug, Clone)]
+struct PriceSample {
+    timestamp: DateTime<Utc>,
+    exchange: Exchange,
+    price: f64,
+    volume: f64,
+}
+
+/// # Windowed Price
+///
+/// Price history for a single symbol. Feed it observations with [`WindowedPrice::record`]
+/// and read averages back with [`WindowedPrice::averages`].
+#[derive(Debug, Clone)]
+pub struct WindowedPrice {
+    symbol: String,
+    config: WindowedPriceConfig,
+    samples: VecDeque<PriceSample>,
+}
+
+impl WindowedPrice {
+    /// ## New
+    ///
+    /// Creates an empty history for `symbol`.
+    pub fn new(symbol: &str, config: WindowedPriceConfig) -> Self {
+        Self {
+            symbol: normalize_symbol(symbol),
+            config,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// ## Record
+    ///
+    /// Adds a price observed on `exchange` at `timestamp` with the volume it should carry in
+    /// the VWAP. Observations with a non-positive or non-finite price, or a negative or
+    /// non-finite volume, are skipped. Observations older than the retention, measured from
+    /// the latest one, are dropped.
+    pub fn record(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        exchange: Exchange,
+        price: f64,
+        volume: f64,
+    ) {
+        if !price.is_finite() || price <= 0.0 || !volume.is_finite() || volume < 0.0 {
+            return;
+        }
+
+        // Exchanges deliver updates slightly out of order; keep the history sorted by time
+        let index = self
+            .samples
+            .partition_point(|kept| kept.timestamp <= timestamp);
+        self.samples.insert(
+            index,
+            PriceSample {
+                timestamp,
+                exchange,
+                price,
+                volume,
+            },
+        );
+
+        if let Some(latest) = self.samples.back().map(|kept| kept.timestamp) {
+            let cutoff = latest - Duration::seconds(self.config.window_secs as i64);
+            while self
+                .samples
+                .front()
+                .is_some_and(|kept| kept.timestamp < cutoff)
+            {
+                self.samples.pop_front();
+            }
+        }
+        while self.samples.len() > self.config.max_samples {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Number of samples held.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether no sample is held.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// ## Averages
+    ///
+    /// Computes the averages over the last `window_secs`, capped at the retention, ending at
+    /// the latest sample.
+    ///
+    /// ### Returns
+    ///
+    /// The averages, or `None` if no sample has been recorded.
+    pub fn averages(&self, window_secs: u64) -> Option<WindowedAverages> {
+        let window_secs = window_secs.min(self.config.window_secs);
+        let end = self.samples.back()?.timestamp;
+        let start = end - Duration::seconds(window_secs as i64);
+        let first = self.samples.partition_point(|kept| kept.timestamp < start);
+        let window: Vec<&PriceSample> = self.samples.range(first..).collect();
+
+        let mut per_exchange: BTreeMap<Exchange, Vec<&PriceSample>> = BTreeMap::new();
+        for sample in &window {
+            per_exchange
+                .entry(sample.exchange.clone())
+                .or_default()
+                .push(sample);
+        }
+
+        Some(WindowedAverages {
+            symbol: self.symbol.clone(),
+            window_secs,
+            overall: average(&window, end),
+            by_exchange: per_exchange
+                .into_iter()
+                .map(|(exchange, samples)| (exchange, average(&samples, end)))
+                .collect(),
+            timestamp: end,
+        })
+    }
+}
+
+/// Averages time-sorted `samples`, holding each price until the next sample and the last
+/// one until `end`
+fn average(samples: &[&PriceSample], end: DateTime<Utc>) -> PriceAverage {
+    let (mut volume, mut notional) = (0.0, 0.0);
+    let (mut duration, mut weighted) = (0.0, 0.0);
+    for (i, sample) in samples.iter().enumerate() {
+        volume += sample.volume;
+        notional += sample.price * sample.volume;
+
+        let until = samples.get(i + 1).map_or(end, |next| next.timestamp);
+        let held = (until - sample.timestamp).num_milliseconds() as f64;
+        duration += held;
+        weighted += sample.price * held;
+    }
+
+    let twap = if samples.is_empty() {
+        None
+    } else if duration > 0.0 {
+        Some(weighted / duration)
+    } else {
+        Some(samples.iter().map(|sample| sample.price).sum::<f64>() / samples.len() as f64)
+    };
+
+    PriceAverage {
+        vwap: (volume > 0.0).then(|| notional / volume),
+        twap,
+        volume,
+        samples: samples.len(),
+    }
+}
+
+/// # Windowed Price Collector
+///
+/// Keeps one [`WindowedPrice`] per symbol, fed from trade prints, summaries, or both. A trade
+/// contributes its price and quantity. A summary contributes, for each exchange it carries
+/// levels for, the midpoint of that exchange's best bid and ask weighted by their combined
+/// quantity. Cloning the collector shares its state.
+#[derive(Debug, Clone)]
+pub struct WindowedPriceCollector {
+    config: WindowedPriceConfig,
+    prices: Arc<RwLock<HashMap<String, WindowedPrice>>>,
+}
+
+impl WindowedPriceCollector {
+    /// ## New
+    ///
+    /// Creates a collector that keeps every symbol's history with `config`.
+    pub fn new(config: WindowedPriceConfig) -> Self {
+        Self {
+            config,
+            prices: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// ## Record Trade
+    ///
+    /// Adds a trade print to the history of its symbol.
+    pub async fn record_trade(&self, trade: &Trade) {
+        let mut prices = self.prices.write().await;
+        self.history(&mut prices, &trade.symbol).record(
+            trade.timestamp,
+            trade.exchange.clone(),
+            trade.price,
+            trade.quantity,
+        );
+    }
+
+    /// ## Record Summary
+    ///
+    /// Adds the top-of-book midpoint of every exchange in the summary to the history of its
+    /// symbol. Exchanges with only one side in the summary are skipped.
+    pub async fn record_summary(&self, summary: &Summary) {
+        let mut exchanges: Vec<&Exchange> = Vec::new();
+        for level in summary.bids.iter().chain(&summary.asks) {
+            if !exchanges.contains(&&level.exchange) {
+                exchanges.push(&level.exchange);
+            }
+        }
+
+        let mut prices = self.prices.write().await;
+        let history = self.history(&mut prices, &summary.symbol);
+        for exchange in exchanges {
+            // Levels are sorted best first, so the first level of an exchange is its best
+            let bid = summary
+                .bids
+                .iter()
+                .find(|level| &level.exchange == exchange);
+            let ask = summary
+                .asks
+                .iter()
+                .find(|level| &level.exchange == exchange);
+            if let Some((bid, ask)) = bid.zip(ask) {
+                history.record(
+                    summary.timestamp,
+                    exchange.clone(),
+                    (bid.price + ask.price) / 2.0,
+                    bid.quantity + ask.quantity,
+                );
+            }
+        }
+    }
+
+    /// ## Averages
+    ///
+    /// Returns the averages for `symbol` over the last `window_secs`, or `None` if nothing has
+    /// been recorded for it.
+    pub async fn averages(&self, symbol: &str, window_secs: u64) -> Option<WindowedAverages> {
+        let prices = self.prices.read().await;
+        prices.get(&normalize_symbol(symbol))?.averages(window_secs)
+    }
+
+    /// ## Spawn Trades
+    ///
+    /// Spawns a task that records every trade received on `trade_rx`, typically the sender
+    /// side handed to `TradeStreamService::spawn_trade_service`, until the channel closes.
+    pub fn spawn_trades(&self, mut trade_rx: mpsc::Receiver<Trade>) -> JoinHandle<Result<()>> {
+        let collector = self.clone();
+        tokio::spawn(async move {
+            while let Some(trade) = trade_rx.recv().await {
+                collector.record_trade(&trade).await;
+            }
+            Ok(())
+        })
+    }
+
+    /// ## Spawn Summaries
+    ///
+    /// Spawns a task that records every summary received on `summary_rx`, typically from
+    /// `Aggregator::subscribe_summaries()`, until the channel closes.
+    pub fn spawn_summaries(
+        &self,
+        mut summary_rx: broadcast::Receiver<Summary>,
+    ) -> JoinHandle<Result<()>> {
+        let collector = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match summary_rx.recv().await {
+                    Ok(summary) => collector.record_summary(&summary).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "Windowed price collector lagged, skipped {} summaries",
+                            skipped
+                        );
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// The history of `symbol`, created on first sight
+    fn history<'a>(
+        &self,
+        prices: &'a mut HashMap<String, WindowedPrice>,
+        symbol: &str,
+    ) -> &'a mut WindowedPrice {
+        prices
+            .entry(normalize_symbol(symbol))
+            .or_insert_with(|| WindowedPrice::new(symbol, self.config.clone()))
+    }
+}
+
+impl Default for WindowedPriceCollector {
+    fn default() -> Self {
+        Self::new(WindowedPriceConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregator_core::{PriceLevel, TradeSide};
+    use uuid::Uuid;
+
+    fn trade(exchange: Exchange, price: f64, quantity: f64, at: DateTime<Utc>) -> Trade {
+        Trade {
+            id: Uuid::new_v4(),
+            trade_id: "1".to_string(),
+            symbol: "BTC-USDT".to_string(),
+            exchange,
+            price,
+            quantity,
+            side: TradeSide::Buy,
+            timestamp: at,
+        }
+    }
+
+    fn level(exchange: Exchange, price: f64, quantity: f64) -> PriceLevel {
+        PriceLevel {
+            price,
+            quantity,
+            exchange,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_vwap_and_twap_over_window() {
+        let start = Utc::now();
+        let mut history = WindowedPrice::new("BTCUSDT", WindowedPriceConfig::default());
+        history.record(start, Exchange::Binance, 100.0, 1.0);
+        history.record(start + Duration::seconds(30), Exchange::Binance, 110.0, 3.0);
+        history.record(start + Duration::seconds(40), Exchange::Binance, 120.0, 0.0);
+
+        let averages = history.averages(60).unwrap();
+        // (100 * 1 + 110 * 3) / 4
+        assert_eq!(averages.overall.vwap, Some(107.5));
+        // 100 held for 30s, 110 for 10s, 120 for none
+        assert_eq!(averages.overall.twap, Some(102.5));
+        assert_eq!(averages.overall.samples, 3);
+
+        // A 15 second window only reaches the last two samples
+        let recent = history.averages(15).unwrap();
+        assert_eq!(recent.overall.samples, 2);
+        assert_eq!(recent.overall.vwap, Some(110.0));
+    }
+
+    #[test]
+    fn test_retention_caps_window_and_drops_old_samples() {
+        let start = Utc::now();
+        let config = WindowedPriceConfig {
+            window_secs: 60,
+            max_samples: 100,
+        };
+        let mut history = WindowedPrice::new("BTCUSDT", config);
+        history.record(start, Exchange::Binance, 100.0, 1.0);
+        history.record(start + Duration::seconds(90), Exchange::Binance, 200.0, 1.0);
+
+        assert_eq!(history.len(), 1);
+        let averages = history.averages(3600).unwrap();
+        assert_eq!(averages.window_secs, 60);
+        assert_eq!(averages.overall.twap, Some(200.0));
+    }
+
+    #[tokio::test]
+    async fn test_trades_broken_down_by_exchange() {
+        let collector = WindowedPriceCollector::default();
+        let now = Utc::now();
+        collector
+            .record_trade(&trade(Exchange::Binance, 100.0, 2.0, now))
+            .await;
+        collector
+            .record_trade(&trade(Exchange::Bybit, 103.0, 1.0, now))
+            .await;
+
+        let averages = collector.averages("btc/usdt", 60).await.unwrap();
+        assert_eq!(averages.symbol, "BTCUSDT");
+        assert_eq!(averages.overall.vwap, Some(101.0));
+        assert_eq!(averages.by_exchange[&Exchange::Binance].vwap, Some(100.0));
+        assert_eq!(averages.by_exchange[&Exchange::Bybit].vwap, Some(103.0));
+        assert!(collector.averages("ETHUSDT", 60).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_summary_records_mid_per_exchange() {
+        let collector = WindowedPriceCollector::default();
+        let summary = Summary {
+            symbol: "BTCUSDT".to_string(),
+            spread: 0.0,
+            bids: vec![
+                level(Exchange::Binance, 100.0, 1.0),
+                level(Exchange::Bybit, 99.0, 2.0),
+            ],
+            asks: vec![
+                level(Exchange::Binance, 102.0, 1.0),
+                level(Exchange::Bybit, 103.0, 2.0),
+                level(Exchange::Kraken, 104.0, 1.0),
+            ],
+            timestamp: Utc::now(),
+            market_type: None,
+        };
+        collector.record_summary(&summary).await;
+
+        let averages = collector.averages("BTCUSDT", 60).await.unwrap();
+        assert_eq!(averages.by_exchange.len(), 2);
+        assert_eq!(averages.by_exchange[&Exchange::Binance].twap, Some(101.0));
+        assert_eq!(averages.by_exchange[&Exchange::Bybit].twap, Some(101.0));
+        assert_eq!(averages.overall.volume, 6.0);
+    }
+}
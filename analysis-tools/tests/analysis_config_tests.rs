@@ -0,0 +1,107 @@
+//! Tests for building analysis engines from the `analysis` section of Config
+
+mod common;
+
+use aggregator_core::{Config, Exchange, FeeSchedule, Summary, TradingPair};
+use analysis_tools::{
+    AnalysisEngine, ArbitrageDetector, DefaultAnalysisEngine, StreamingAnalysisEngine,
+};
+use chrono::{Duration, Utc};
+use common::TestDataFactory;
+use std::collections::HashMap;
+
+/// Bybit asks 50000 and Binance bids 50300, a 0.6% gross spread
+fn crossed_books() -> HashMap<String, Summary> {
+    HashMap::from([
+        (
+            "binance".to_string(),
+            TestDataFactory::create_summary(
+                "BTCUSDT",
+                Exchange::Binance,
+                50300.0,
+                50400.0,
+                1.0,
+                1.0,
+            ),
+        ),
+        (
+            "bybit".to_string(),
+            TestDataFactory::create_summary("BTCUSDT", Exchange::Bybit, 49900.0, 50000.0, 1.0, 1.0),
+        ),
+    ])
+}
+
+fn with_taker_fee(mut config: Config, fee: f64) -> Config {
+    for exchange_config in config.exchanges.values_mut() {
+        exchange_config.fees = FeeSchedule::new(0.0, fee);
+    }
+    config
+}
+
+#[tokio::test]
+async fn test_default_engine_uses_configured_profit_threshold() {
+    let mut config = Config::default();
+    config.analysis.min_profit_percentage = 1.0;
+    let engine = DefaultAnalysisEngine::from_config(&config);
+    assert!(engine
+        .analyze_summaries(&crossed_books())
+        .await
+        .unwrap()
+        .is_empty());
+
+    config.analysis.min_profit_percentage = 0.5;
+    let engine = DefaultAnalysisEngine::from_config(&config);
+    assert_eq!(
+        engine
+            .analyze_summaries(&crossed_books())
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_fee_toggle_switches_profit_basis() {
+    // A 0.3% taker fee on each leg eats the 0.6% spread
+    let mut config = with_taker_fee(Config::default(), 0.003);
+    let net = DefaultAnalysisEngine::from_config(&config);
+    assert!(net
+        .analyze_summaries(&crossed_books())
+        .await
+        .unwrap()
+        .is_empty());
+
+    config.analysis.net_of_fees = false;
+    let gross = DefaultAnalysisEngine::from_config(&config);
+    let opportunities = gross.analyze_summaries(&crossed_books()).await.unwrap();
+    assert_eq!(opportunities.len(), 1);
+    assert!((opportunities[0].profit_percentage - 0.6).abs() < 1e-9);
+
+    let books = crossed_books().into_values().collect();
+    let scenarios = HashMap::from([(TradingPair::new("BTC", "USDT"), books)]);
+    assert_eq!(
+        ArbitrageDetector::from_config(&config)
+            .detect_opportunities(&scenarios)
+            .await
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_max_opportunity_age_skips_stale_books() {
+    let mut config = Config::default();
+    config.analysis.max_opportunity_age_ms = Some(5_000);
+
+    let mut books = crossed_books();
+    books.get_mut("bybit").unwrap().timestamp = Utc::now() - Duration::seconds(60);
+
+    let engine = DefaultAnalysisEngine::from_config(&config);
+    assert!(engine.analyze_summaries(&books).await.unwrap().is_empty());
+
+    let streaming = StreamingAnalysisEngine::from_config(&config);
+    for summary in books.into_values() {
+        assert!(streaming.process_summary(summary).await.is_empty());
+    }
+}
@@ -35,6 +35,7 @@ impl TestDataFactory {
                 timestamp,
             }],
             timestamp,
+            market_type: None,
         }
     }
 
@@ -76,6 +77,7 @@ impl TestDataFactory {
             bids,
             asks,
             timestamp,
+            market_type: None,
         }
     }
 
@@ -235,6 +237,7 @@ impl TestDataFactory {
                 timestamp,
             }],
             timestamp,
+            market_type: None,
         }
     }
 
@@ -253,6 +256,7 @@ impl TestDataFactory {
             }],
             asks: vec![], // Empty asks
             timestamp,
+            market_type: None,
         }
     }
 
@@ -276,6 +280,7 @@ impl TestDataFactory {
                 timestamp,
             }],
             timestamp,
+            market_type: None,
         }
     }
 
@@ -299,6 +304,7 @@ impl TestDataFactory {
                 timestamp,
             }],
             timestamp,
+            market_type: None,
         }
     }
 
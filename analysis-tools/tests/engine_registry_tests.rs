@@ -0,0 +1,128 @@
+//! Tests for running several AnalysisEngine implementations through the aggregator's registry
+
+mod common;
+
+use aggregator_core::{
+    AggregatorError, AnalysisEngineRegistry, ArbitrageOpportunity, Exchange, Result, Summary,
+};
+use analysis_tools::{AnalysisEngine, DefaultAnalysisEngine};
+use async_trait::async_trait;
+use chrono::Utc;
+use common::TestDataFactory;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Reports one fixed opportunity whatever it is given
+struct FixedEngine {
+    profit_percentage: f64,
+}
+
+#[async_trait]
+impl AnalysisEngine for FixedEngine {
+    async fn analyze_summaries(
+        &self,
+        _summaries: &HashMap<String, Summary>,
+    ) -> Result<Vec<ArbitrageOpportunity>> {
+        Ok(vec![ArbitrageOpportunity {
+            buy_exchange: Exchange::Bybit,
+            sell_exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            buy_price: 50000.0,
+            sell_price: 50000.0 * (1.0 + self.profit_percentage / 100.0),
+            profit_percentage: self.profit_percentage,
+            volume: 1.0,
+            average_buy_price: 50000.0,
+            average_sell_price: 50000.0 * (1.0 + self.profit_percentage / 100.0),
+            time_to_live_ms: 0.0,
+            confidence: 0.0,
+            timestamp: Utc::now(),
+        }])
+    }
+
+    async fn calculate_spread(&self, _summary: &Summary) -> Option<f64> {
+        None
+    }
+
+    async fn calculate_volume_weighted_price(&self, _summary: &Summary) -> Option<f64> {
+        None
+    }
+
+    fn name(&self) -> &str {
+        "fixed"
+    }
+}
+
+/// Always fails
+struct FailingEngine;
+
+#[async_trait]
+impl AnalysisEngine for FailingEngine {
+    async fn analyze_summaries(
+        &self,
+        _summaries: &HashMap<String, Summary>,
+    ) -> Result<Vec<ArbitrageOpportunity>> {
+        Err(AggregatorError::parsing("analysis", "malformed"))
+    }
+
+    async fn calculate_spread(&self, _summary: &Summary) -> Option<f64> {
+        None
+    }
+
+    async fn calculate_volume_weighted_price(&self, _summary: &Summary) -> Option<f64> {
+        None
+    }
+}
+
+/// Binance bids 50300 and Bybit asks 50000
+fn crossed_batch() -> Arc<HashMap<String, Summary>> {
+    Arc::new(HashMap::from([
+        (
+            "binance:BTCUSDT".to_string(),
+            TestDataFactory::create_summary(
+                "BTCUSDT",
+                Exchange::Binance,
+                50300.0,
+                50400.0,
+                1.0,
+                1.0,
+            ),
+        ),
+        (
+            "bybit:BTCUSDT".to_string(),
+            TestDataFactory::create_summary("BTCUSDT", Exchange::Bybit, 49900.0, 50000.0, 1.0, 1.0),
+        ),
+    ]))
+}
+
+#[tokio::test]
+async fn test_registry_merges_engines_keeping_most_profitable() {
+    let registry = AnalysisEngineRegistry::new();
+    registry
+        .register(Arc::new(DefaultAnalysisEngine::new()))
+        .await;
+    registry
+        .register(Arc::new(FixedEngine {
+            profit_percentage: 5.0,
+        }))
+        .await;
+    assert_eq!(registry.len().await, 2);
+
+    // Both engines report Bybit -> Binance on BTCUSDT; the fixed engine's 5% wins
+    let opportunities = registry.run(crossed_batch()).await;
+    assert_eq!(opportunities.len(), 1);
+    assert_eq!(opportunities[0].profit_percentage, 5.0);
+}
+
+#[tokio::test]
+async fn test_failing_engine_does_not_hold_back_others() {
+    let registry = AnalysisEngineRegistry::new();
+    registry.register(Arc::new(FailingEngine)).await;
+    registry
+        .register(Arc::new(DefaultAnalysisEngine::new()))
+        .await;
+
+    let opportunities = registry.run(crossed_batch()).await;
+    assert_eq!(opportunities.len(), 1);
+    assert_eq!(opportunities[0].buy_exchange, Exchange::Bybit);
+    assert!(FailingEngine.name().ends_with("FailingEngine"));
+}
@@ -0,0 +1,133 @@
+//! Tests for fee-aware ArbitrageDetector profitability
+
+mod common;
+
+use aggregator_core::{Config, Exchange, FeeSchedule, TradingPair};
+use analysis_tools::{ArbitrageDetector, ProfitBasis};
+use common::{assert_no_arbitrage_opportunities, assert_within_tolerance, TestDataFactory};
+use std::collections::HashMap;
+
+fn taker_fees(detector: ArbitrageDetector, fee: f64) -> ArbitrageDetector {
+    detector
+        .with_fee_schedule(Exchange::Binance, FeeSchedule::new(0.0, fee))
+        .with_fee_schedule(Exchange::Bybit, FeeSchedule::new(0.0, fee))
+}
+
+#[tokio::test]
+async fn test_fees_remove_thin_opportunity_on_net_basis() {
+    // 0.2% gross spread, 0.1% taker fee on each leg leaves nothing
+    let scenarios = TestDataFactory::create_arbitrage_scenario(
+        "BTCUSDT",
+        Exchange::Bybit,
+        Exchange::Binance,
+        50000.0,
+        50100.0,
+        1.0,
+    );
+
+    let net = taker_fees(ArbitrageDetector::new(0.1, 0.01), 0.001);
+    assert_no_arbitrage_opportunities(&net.detect_opportunities(&scenarios).await);
+
+    let gross =
+        taker_fees(ArbitrageDetector::new(0.1, 0.01), 0.001).with_profit_basis(ProfitBasis::Gross);
+    let opportunities = gross.detect_opportunities(&scenarios).await;
+    assert_eq!(opportunities.len(), 1);
+    assert_within_tolerance(
+        opportunities[0].profit_percentage,
+        0.2,
+        1e-9,
+        "gross profit",
+    );
+}
+
+#[tokio::test]
+async fn test_net_profit_after_taker_fees() {
+    let scenarios = TestDataFactory::create_arbitrage_scenario(
+        "BTCUSDT",
+        Exchange::Bybit,
+        Exchange::Binance,
+        50000.0,
+        50500.0,
+        1.0,
+    );
+    let detector = taker_fees(ArbitrageDetector::new(0.1, 0.01), 0.001);
+
+    let opportunities = detector.detect_opportunities(&scenarios).await;
+    assert_eq!(opportunities.len(), 1);
+
+    // Spend 50000 * 1.001, receive 50500 * 0.999
+    let expected = (50500.0 * 0.999 - 50050.0) / 50050.0 * 100.0;
+    assert_within_tolerance(
+        opportunities[0].profit_percentage,
+        expected,
+        1e-9,
+        "net profit",
+    );
+}
+
+#[tokio::test]
+async fn test_withdrawal_fee_is_spread_over_size() {
+    let pair = TradingPair::new("BTC", "USDT");
+    let buy = TestDataFactory::create_summary_with_depth(
+        "BTCUSDT",
+        Exchange::Bybit,
+        vec![],
+        vec![(100.0, 1.0), (100.1, 9.0)],
+    );
+    let sell = TestDataFactory::create_summary_with_depth(
+        "BTCUSDT",
+        Exchange::Binance,
+        vec![(102.0, 10.0)],
+        vec![],
+    );
+    let detector = ArbitrageDetector::new(0.1, 0.01).with_fee_schedule(
+        Exchange::Bybit,
+        FeeSchedule::default().with_withdrawal_fee("BTC", 0.05),
+    );
+
+    // Withdrawing 0.05 BTC costs 5% of a 1 BTC trade but only 0.5% of a 10 BTC trade
+    let (buy_fill, _) = detector.size_opportunity(&pair, &buy, &sell).unwrap();
+    assert_eq!(buy_fill.filled_quantity, 10.0);
+
+    let mut summaries = HashMap::new();
+    summaries.insert(pair, vec![buy, sell]);
+    let opportunities = detector.detect_opportunities(&summaries).await;
+    assert_eq!(opportunities.len(), 1);
+    assert_eq!(opportunities[0].volume, 10.0);
+    assert_within_tolerance(
+        opportunities[0].profit_percentage,
+        1.5,
+        1e-9,
+        "profit after withdrawal fee",
+    );
+}
+
+#[tokio::test]
+async fn test_fees_from_config() {
+    let mut config = Config::default();
+    for exchange in [Exchange::Binance, Exchange::Bybit] {
+        config.exchanges.get_mut(&exchange).unwrap().fees = FeeSchedule::new(0.0, 0.001);
+    }
+    let detector = ArbitrageDetector::new(0.1, 0.01).with_config_fees(&config);
+
+    let net = detector.net_profit_percentage(
+        "BTC",
+        &Exchange::Bybit,
+        &Exchange::Binance,
+        50000.0,
+        50100.0,
+        1.0,
+    );
+    assert!(net < 0.0);
+
+    // Exchanges left on the default schedule trade for free
+    let free = detector.net_profit_percentage(
+        "BTC",
+        &Exchange::Coinbase,
+        &Exchange::Coinbase,
+        50000.0,
+        50100.0,
+        1.0,
+    );
+    assert_within_tolerance(free, 0.2, 1e-9, "profit without fees");
+}
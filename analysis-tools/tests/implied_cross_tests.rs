@@ -0,0 +1,144 @@
+//! Tests for ImpliedCrossDetector across direct pairs and their implied crosses
+
+mod common;
+
+use aggregator_core::{Exchange, Summary, TradeSide, TradingPair};
+use analysis_tools::ImpliedCrossDetector;
+use common::{assert_within_tolerance, TestDataFactory};
+use std::collections::HashMap;
+
+fn by_pair(books: Vec<(TradingPair, Summary)>) -> HashMap<TradingPair, Vec<Summary>> {
+    let mut summaries: HashMap<TradingPair, Vec<Summary>> = HashMap::new();
+    for (pair, summary) in books {
+        summaries.entry(pair).or_default().push(summary);
+    }
+    summaries
+}
+
+/// ETH/BTC on `direct` at `eth_btc_bid`/`eth_btc_ask`, and BTC/USDT and ETH/USDT on Binance
+/// implying ETH/BTC between 3000 / 50010 and 3001 / 50000
+fn cross_books(
+    direct: Exchange,
+    eth_btc_bid: f64,
+    eth_btc_ask: f64,
+) -> Vec<(TradingPair, Summary)> {
+    vec![
+        (
+            TradingPair::new("BTC", "USDT"),
+            TestDataFactory::create_summary(
+                "BTCUSDT",
+                Exchange::Binance,
+                50000.0,
+                50010.0,
+                1.0,
+                1.0,
+            ),
+        ),
+        (
+            TradingPair::new("ETH", "USDT"),
+            TestDataFactory::create_summary("ETHUSDT", Exchange::Binance, 3000.0, 3001.0, 5.0, 5.0),
+        ),
+        (
+            TradingPair::new("ETH", "BTC"),
+            TestDataFactory::create_summary("ETHBTC", direct, eth_btc_bid, eth_btc_ask, 10.0, 10.0),
+        ),
+    ]
+}
+
+#[tokio::test]
+async fn test_cheap_direct_pair_is_bought_directly() {
+    let detector = ImpliedCrossDetector::new(0.1);
+    let summaries = by_pair(cross_books(Exchange::Kraken, 0.0594, 0.0595));
+
+    let opportunities = detector.detect_opportunities(&summaries).await;
+    assert_eq!(opportunities.len(), 1);
+    let opportunity = &opportunities[0];
+    assert_eq!(opportunity.direct_pair, TradingPair::new("ETH", "BTC"));
+    assert_eq!(opportunity.via, "USDT");
+    assert_eq!(opportunity.direct_price, 0.0595);
+    assert_within_tolerance(
+        opportunity.implied_price,
+        3000.0 / 50010.0,
+        1e-12,
+        "implied bid",
+    );
+
+    let legs: Vec<(Exchange, TradeSide, f64)> = opportunity
+        .legs
+        .iter()
+        .map(|leg| (leg.exchange.clone(), leg.side, leg.price))
+        .collect();
+    assert_eq!(
+        legs,
+        vec![
+            (Exchange::Kraken, TradeSide::Buy, 0.0595),
+            (Exchange::Binance, TradeSide::Sell, 3000.0),
+            (Exchange::Binance, TradeSide::Buy, 50010.0),
+        ]
+    );
+
+    let expected_rate = 3000.0 / (0.0595 * 50010.0);
+    assert_within_tolerance(opportunity.implied_rate, expected_rate, 1e-12, "rate");
+    assert_within_tolerance(
+        opportunity.profit_percentage,
+        (expected_rate - 1.0) * 100.0,
+        1e-9,
+        "profit percentage",
+    );
+    // The ETH/USDT bid (5 ETH) is the tightest leg: 5 ETH cost 0.2975 BTC
+    assert_within_tolerance(opportunity.volume, 5.0 * 0.0595, 1e-12, "volume");
+}
+
+#[tokio::test]
+async fn test_rich_direct_pair_is_sold_directly() {
+    let detector = ImpliedCrossDetector::new(0.1);
+    let summaries = by_pair(cross_books(Exchange::Kraken, 0.0605, 0.0606));
+
+    let opportunities = detector.detect_opportunities(&summaries).await;
+    assert_eq!(opportunities.len(), 1);
+    let opportunity = &opportunities[0];
+    assert_eq!(opportunity.direct_price, 0.0605);
+    assert_within_tolerance(
+        opportunity.implied_price,
+        3001.0 / 50000.0,
+        1e-12,
+        "implied ask",
+    );
+
+    let path: Vec<&str> = opportunity.legs.iter().map(|leg| leg.to.as_str()).collect();
+    assert_eq!(path, vec!["USDT", "ETH", "BTC"]);
+    assert_eq!(opportunity.legs[0].from, "BTC");
+    assert_eq!(opportunity.legs[2].exchange, Exchange::Kraken);
+    assert_within_tolerance(
+        opportunity.implied_rate,
+        50000.0 / 3001.0 * 0.0605,
+        1e-12,
+        "rate",
+    );
+}
+
+#[tokio::test]
+async fn test_fairly_priced_cross_and_missing_legs_report_nothing() {
+    let detector = ImpliedCrossDetector::new(0.1);
+    let fair = by_pair(cross_books(Exchange::Kraken, 0.05995, 0.06001));
+    assert!(detector.detect_opportunities(&fair).await.is_empty());
+
+    let mut missing = by_pair(cross_books(Exchange::Kraken, 0.0594, 0.0595));
+    missing.remove(&TradingPair::new("BTC", "USDT"));
+    assert!(detector.detect_opportunities(&missing).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_each_leg_uses_best_exchange() {
+    let detector = ImpliedCrossDetector::new(0.1);
+    let mut books = cross_books(Exchange::Kraken, 0.0594, 0.0595);
+    books.push((
+        TradingPair::new("ETH", "USDT"),
+        TestDataFactory::create_summary("ETHUSDT", Exchange::Bybit, 3003.0, 3004.0, 2.0, 2.0),
+    ));
+
+    let opportunities = detector.detect_opportunities(&by_pair(books)).await;
+    assert_eq!(opportunities.len(), 1);
+    assert_eq!(opportunities[0].legs[1].exchange, Exchange::Bybit);
+    assert_eq!(opportunities[0].legs[1].price, 3003.0);
+}
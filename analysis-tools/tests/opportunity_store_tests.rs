@@ -0,0 +1,204 @@
+//! Tests for persisting and querying arbitrage opportunities
+
+use aggregator_core::{ArbitrageOpportunity, Exchange};
+use analysis_tools::{
+    spawn_opportunity_recorder, InMemoryOpportunityStore, OpportunityQuery, OpportunityStore,
+    SqliteOpportunityStore,
+};
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+fn opportunity(
+    symbol: &str,
+    buy_exchange: Exchange,
+    sell_exchange: Exchange,
+    profit_percentage: f64,
+    timestamp: DateTime<Utc>,
+) -> ArbitrageOpportunity {
+    ArbitrageOpportunity {
+        buy_exchange,
+        sell_exchange,
+        symbol: symbol.to_string(),
+        buy_price: 50000.0,
+        sell_price: 50000.0 * (1.0 + profit_percentage / 100.0),
+        profit_percentage,
+        volume: 1.5,
+        average_buy_price: 50000.0,
+        average_sell_price: 50000.0 * (1.0 + profit_percentage / 100.0),
+        time_to_live_ms: 400.0,
+        confidence: 0.4,
+        timestamp,
+    }
+}
+
+/// Inserts four opportunities a minute apart, oldest first, and returns the newest timestamp.
+/// Timestamps are kept to milliseconds, like exchange timestamps, so they survive storage.
+async fn populate(store: &dyn OpportunityStore) -> DateTime<Utc> {
+    let now = Utc::now().trunc_subsecs(3);
+    let opportunities = [
+        opportunity(
+            "BTC/USDT",
+            Exchange::Bybit,
+            Exchange::Binance,
+            0.2,
+            now - Duration::minutes(3),
+        ),
+        opportunity(
+            "BTC/USDT",
+            Exchange::Binance,
+            Exchange::Kraken,
+            0.5,
+            now - Duration::minutes(2),
+        ),
+        opportunity(
+            "ETH/USDT",
+            Exchange::Bybit,
+            Exchange::Binance,
+            0.8,
+            now - Duration::minutes(1),
+        ),
+        opportunity("BTC/USDT", Exchange::Bybit, Exchange::Binance, 1.1, now),
+    ];
+    for opportunity in &opportunities {
+        store.insert(opportunity).await.unwrap();
+    }
+    now
+}
+
+async fn check_queries(store: &dyn OpportunityStore) {
+    let now = populate(store).await;
+
+    let all = store.query(&OpportunityQuery::default()).await.unwrap();
+    assert_eq!(all.len(), 4);
+    assert_eq!(all[0].profit_percentage, 1.1, "newest first");
+    assert_eq!(all[0].timestamp, now);
+    assert_eq!(all[0].buy_exchange, Exchange::Bybit);
+    assert_eq!(all[0].volume, 1.5);
+    assert_eq!(all[0].time_to_live_ms, 400.0);
+    assert_eq!(all[0].confidence, 0.4);
+
+    let btc = OpportunityQuery {
+        symbol: Some("btcusdt".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(store.query(&btc).await.unwrap().len(), 3);
+
+    let pair = OpportunityQuery {
+        buy_exchange: Some(Exchange::Bybit),
+        sell_exchange: Some(Exchange::Binance),
+        ..Default::default()
+    };
+    assert_eq!(store.query(&pair).await.unwrap().len(), 3);
+
+    let window = OpportunityQuery {
+        from: Some(now - Duration::seconds(150)),
+        to: Some(now - Duration::seconds(30)),
+        ..Default::default()
+    };
+    let found = store.query(&window).await.unwrap();
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].symbol, "ETH/USDT");
+
+    let profitable = OpportunityQuery {
+        symbol: Some("BTC-USDT".to_string()),
+        min_profit: Some(0.5),
+        limit: Some(1),
+        ..Default::default()
+    };
+    let found = store.query(&profitable).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].profit_percentage, 1.1);
+}
+
+#[tokio::test]
+async fn test_in_memory_store_queries() {
+    check_queries(&InMemoryOpportunityStore::default()).await;
+}
+
+#[tokio::test]
+async fn test_sqlite_store_queries() {
+    check_queries(&SqliteOpportunityStore::open_in_memory().unwrap()).await;
+}
+
+#[tokio::test]
+async fn test_sqlite_store_survives_reopen() {
+    let path = std::env::temp_dir().join(format!("opportunities-{}.db", uuid::Uuid::new_v4()));
+    let store = SqliteOpportunityStore::open(&path).unwrap();
+    populate(&store).await;
+    drop(store);
+
+    let reopened = SqliteOpportunityStore::open(&path).unwrap();
+    let all = reopened.query(&OpportunityQuery::default()).await.unwrap();
+    assert_eq!(all.len(), 4);
+    drop(reopened);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_in_memory_store_drops_oldest_beyond_capacity() {
+    let store = InMemoryOpportunityStore::new(2);
+    populate(&store).await;
+    assert_eq!(store.len().await, 2);
+
+    let kept = store.query(&OpportunityQuery::default()).await.unwrap();
+    assert_eq!(kept[0].profit_percentage, 1.1);
+    assert_eq!(kept[1].profit_percentage, 0.8);
+}
+
+#[tokio::test]
+async fn test_recorder_persists_broadcast_opportunities() {
+    let store = InMemoryOpportunityStore::default();
+    let (opportunity_tx, opportunity_rx) = broadcast::channel(16);
+    let handle = spawn_opportunity_recorder(Arc::new(store.clone()), opportunity_rx);
+
+    opportunity_tx
+        .send(opportunity(
+            "BTC/USDT",
+            Exchange::Bybit,
+            Exchange::Binance,
+            0.3,
+            Utc::now(),
+        ))
+        .unwrap();
+    drop(opportunity_tx);
+    handle.await.unwrap().unwrap();
+
+    assert_eq!(store.len().await, 1);
+}
+
+#[tokio::test]
+async fn test_exchange_pair_matrix() {
+    let store = InMemoryOpportunityStore::default();
+    populate(&store).await;
+
+    let matrix = store
+        .exchange_pair_matrix(&OpportunityQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(matrix.total, 4);
+    assert_eq!(
+        matrix.exchanges,
+        vec![Exchange::Binance, Exchange::Bybit, Exchange::Kraken]
+    );
+
+    // Bybit -> Binance three times at 0.2, 0.8 and 1.1
+    let (count, average) = matrix.cell(&Exchange::Bybit, &Exchange::Binance).unwrap();
+    assert_eq!(count, 3);
+    assert!((average - 0.7).abs() < 1e-12);
+    assert_eq!(matrix.counts[1][0], 3);
+    assert_eq!(
+        matrix.cell(&Exchange::Binance, &Exchange::Kraken),
+        Some((1, 0.5))
+    );
+    assert_eq!(matrix.cell(&Exchange::Binance, &Exchange::Bybit), None);
+    assert_eq!(matrix.counts[0][1], 0);
+
+    let eth = OpportunityQuery {
+        symbol: Some("ETHUSDT".to_string()),
+        ..Default::default()
+    };
+    let matrix = store.exchange_pair_matrix(&eth).await.unwrap();
+    assert_eq!(matrix.total, 1);
+    assert_eq!(matrix.exchanges.len(), 2);
+}
@@ -398,6 +398,7 @@ async fn test_integration_with_aggregator_core_types() {
             bids: vec![bid_level],
             asks: vec![ask_level],
             timestamp,
+            market_type: None,
         };
 
         // Add to detector summaries (grouped by TradingPair)
@@ -0,0 +1,180 @@
+//! Tests for StreamingAnalysisEngine incremental detection over a summary stream
+
+mod common;
+
+use aggregator_core::{EventBus, Exchange, Summary, TradingPair};
+use analysis_tools::{
+    ArbitrageDetector, DataQualityConfig, DataQualityEvent, DataQualityMonitor,
+    StreamingAnalysisEngine,
+};
+use chrono::{Duration, Utc};
+use common::TestDataFactory;
+use tokio::sync::broadcast;
+use tokio::time::timeout;
+
+fn btc_summary(exchange: Exchange, bid: f64, ask: f64) -> Summary {
+    TestDataFactory::create_summary("BTCUSDT", exchange, bid, ask, 1.0, 1.0)
+}
+
+#[tokio::test]
+async fn test_opportunity_found_once_second_exchange_arrives() {
+    let engine = StreamingAnalysisEngine::new(ArbitrageDetector::new(0.1, 0.01));
+    let mut opportunities = engine.subscribe_opportunities();
+
+    let found = engine
+        .process_summary(btc_summary(Exchange::Binance, 50100.0, 50110.0))
+        .await;
+    assert!(found.is_empty());
+
+    let found = engine
+        .process_summary(btc_summary(Exchange::Bybit, 49900.0, 49950.0))
+        .await;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].buy_exchange, Exchange::Bybit);
+    assert_eq!(found[0].sell_exchange, Exchange::Binance);
+    assert_eq!(found[0].symbol, "BTC/USDT");
+
+    let published = opportunities.try_recv().unwrap();
+    assert_eq!(published.buy_price, 49950.0);
+    assert_eq!(published.sell_price, 50100.0);
+}
+
+#[tokio::test]
+async fn test_newer_summary_replaces_exchange_book() {
+    let engine = StreamingAnalysisEngine::default();
+    engine
+        .process_summary(btc_summary(Exchange::Binance, 50100.0, 50110.0))
+        .await;
+    let found = engine
+        .process_summary(btc_summary(Exchange::Bybit, 49900.0, 49950.0))
+        .await;
+    assert_eq!(found.len(), 1);
+
+    // Binance's bid falls back below Bybit's ask, closing the gap
+    let found = engine
+        .process_summary(btc_summary(Exchange::Binance, 49940.0, 49960.0))
+        .await;
+    assert!(found.is_empty());
+
+    let books = engine.books(&TradingPair::new("BTC", "USDT")).await;
+    assert_eq!(books.len(), 2);
+    assert_eq!(books[&Exchange::Binance].bids[0].price, 49940.0);
+}
+
+#[tokio::test]
+async fn test_consolidated_summary_split_by_exchange() {
+    let engine = StreamingAnalysisEngine::default();
+    let mut metrics = engine.subscribe_metrics();
+
+    let mut summary = btc_summary(Exchange::Binance, 50100.0, 50110.0);
+    let bybit = btc_summary(Exchange::Bybit, 49900.0, 49950.0);
+    summary.bids.extend(bybit.bids);
+    summary.asks.insert(0, bybit.asks[0].clone());
+
+    let found = engine.process_summary(summary).await;
+    assert_eq!(found.len(), 1);
+
+    let first = metrics.try_recv().unwrap();
+    let second = metrics.try_recv().unwrap();
+    assert_eq!(first.exchange, Exchange::Binance);
+    assert_eq!(first.spread, Some(10.0));
+    assert_eq!(first.mid_price, Some(50105.0));
+    assert_eq!(second.exchange, Exchange::Bybit);
+    assert_eq!(second.spread, Some(50.0));
+}
+
+#[tokio::test]
+async fn test_stale_books_are_ignored() {
+    let engine = StreamingAnalysisEngine::default().with_max_age(Duration::seconds(5));
+
+    let mut stale = btc_summary(Exchange::Binance, 50100.0, 50110.0);
+    stale.timestamp = Utc::now() - Duration::seconds(60);
+    engine.process_summary(stale).await;
+
+    let found = engine
+        .process_summary(btc_summary(Exchange::Bybit, 49900.0, 49950.0))
+        .await;
+    assert!(found.is_empty());
+}
+
+#[tokio::test]
+async fn test_registered_pairs_resolve_symbols() {
+    let engine = StreamingAnalysisEngine::default().with_pairs([
+        TradingPair::new("DOGE", "TRY"),
+        TradingPair::new("ETH", "BTC"),
+    ]);
+
+    for symbol in ["DOGETRY", "ETH-BTC"] {
+        let mut summary = btc_summary(Exchange::Binance, 1.0, 1.1);
+        summary.symbol = symbol.to_string();
+        engine.process_summary(summary).await;
+    }
+
+    assert_eq!(
+        engine.books(&TradingPair::new("DOGE", "TRY")).await.len(),
+        1
+    );
+    assert_eq!(engine.books(&TradingPair::new("ETH", "BTC")).await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_spawned_engine_consumes_broadcast() {
+    let engine = StreamingAnalysisEngine::default();
+    let mut opportunities = engine.subscribe_opportunities();
+
+    let (summary_tx, summary_rx) = broadcast::channel(16);
+    let handle = engine.spawn(summary_rx);
+
+    summary_tx
+        .send(btc_summary(Exchange::Binance, 50100.0, 50110.0))
+        .unwrap();
+    summary_tx
+        .send(btc_summary(Exchange::Bybit, 49900.0, 49950.0))
+        .unwrap();
+
+    let opportunity = timeout(std::time::Duration::from_secs(1), opportunities.recv())
+        .await
+        .expect("opportunity should be published")
+        .unwrap();
+    assert_eq!(opportunity.buy_exchange, Exchange::Bybit);
+
+    drop(summary_tx);
+    handle.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_data_quality_excludes_crossed_book() {
+    let monitor = DataQualityMonitor::default();
+    let mut events = monitor.subscribe();
+    let engine = StreamingAnalysisEngine::default().with_data_quality(monitor);
+
+    engine
+        .process_summary(btc_summary(Exchange::Binance, 50100.0, 50110.0))
+        .await;
+    // Bybit's own bid is above its ask, so its cheap ask cannot be trusted
+    let found = engine
+        .process_summary(btc_summary(Exchange::Bybit, 49960.0, 49950.0))
+        .await;
+    assert!(found.is_empty());
+    assert_eq!(events.try_recv().unwrap().exchange, Exchange::Bybit);
+
+    // The book is still stored, only left out of detection
+    let books = engine.books(&TradingPair::new("BTC", "USDT")).await;
+    assert_eq!(books.len(), 2);
+}
+
+#[tokio::test]
+async fn test_data_quality_events_published_on_bus() {
+    let bus = EventBus::new();
+    let mut events = bus.subscribe::<DataQualityEvent>();
+    let monitor = DataQualityMonitor::with_event_bus(DataQualityConfig::default(), &bus);
+    let engine = StreamingAnalysisEngine::default().with_data_quality(monitor);
+
+    engine
+        .process_summary(btc_summary(Exchange::Bybit, 49960.0, 49950.0))
+        .await;
+
+    let event = events.try_recv().unwrap();
+    assert_eq!(event.exchange, Exchange::Bybit);
+    assert_eq!(bus.topics(), vec!["data-quality"]);
+}
@@ -0,0 +1,122 @@
+//! Tests for TriangularArbitrageDetector cycle search and sizing
+
+mod common;
+
+use aggregator_core::{Exchange, Summary, TradeSide, TradingPair};
+use analysis_tools::TriangularArbitrageDetector;
+use common::{assert_within_tolerance, TestDataFactory};
+use std::collections::HashMap;
+
+/// BTC/USDT, ETH/USDT and ETH/BTC on `exchange`, with ETH cheap in BTC so that
+/// BTC→ETH→USDT→BTC returns about 0.82%
+fn triangle_books(exchange: Exchange) -> Vec<(TradingPair, Summary)> {
+    vec![
+        (
+            TradingPair::new("BTC", "USDT"),
+            TestDataFactory::create_summary(
+                "BTCUSDT",
+                exchange.clone(),
+                50000.0,
+                50010.0,
+                1.0,
+                1.0,
+            ),
+        ),
+        (
+            TradingPair::new("ETH", "USDT"),
+            TestDataFactory::create_summary("ETHUSDT", exchange.clone(), 3000.0, 3001.0, 5.0, 5.0),
+        ),
+        (
+            TradingPair::new("ETH", "BTC"),
+            TestDataFactory::create_summary("ETHBTC", exchange, 0.0594, 0.0595, 10.0, 10.0),
+        ),
+    ]
+}
+
+fn by_pair(books: Vec<(TradingPair, Summary)>) -> HashMap<TradingPair, Vec<Summary>> {
+    let mut summaries: HashMap<TradingPair, Vec<Summary>> = HashMap::new();
+    for (pair, summary) in books {
+        summaries.entry(pair).or_default().push(summary);
+    }
+    summaries
+}
+
+#[tokio::test]
+async fn test_triangular_cycle_detected() {
+    let detector = TriangularArbitrageDetector::new(0.1);
+    let summaries = by_pair(triangle_books(Exchange::Binance));
+
+    let opportunities = detector.detect_opportunities(&summaries).await;
+
+    // Only one direction around the triangle is profitable
+    assert_eq!(opportunities.len(), 1);
+    let opportunity = &opportunities[0];
+    assert_eq!(opportunity.exchange, Exchange::Binance);
+    assert_eq!(opportunity.path(), vec!["BTC", "ETH", "USDT", "BTC"]);
+
+    let sides: Vec<TradeSide> = opportunity.legs.iter().map(|leg| leg.side).collect();
+    assert_eq!(sides, vec![TradeSide::Buy, TradeSide::Sell, TradeSide::Buy]);
+    let prices: Vec<f64> = opportunity.legs.iter().map(|leg| leg.price).collect();
+    assert_eq!(prices, vec![0.0595, 3000.0, 50010.0]);
+
+    let expected_rate = 3000.0 / (0.0595 * 50010.0);
+    assert_within_tolerance(
+        opportunity.implied_rate,
+        expected_rate,
+        1e-12,
+        "implied rate",
+    );
+    assert_within_tolerance(
+        opportunity.profit_percentage,
+        (expected_rate - 1.0) * 100.0,
+        1e-9,
+        "profit percentage",
+    );
+
+    // The ETH/USDT bid (5 ETH) is the tightest leg: 5 ETH cost 0.2975 BTC
+    assert_within_tolerance(opportunity.volume, 5.0 * 0.0595, 1e-12, "volume");
+}
+
+#[tokio::test]
+async fn test_triangular_cycle_below_threshold() {
+    let detector = TriangularArbitrageDetector::new(1.0);
+    let summaries = by_pair(triangle_books(Exchange::Binance));
+
+    assert!(detector.detect_opportunities(&summaries).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_triangular_cycle_does_not_mix_exchanges() {
+    let detector = TriangularArbitrageDetector::default();
+    let mut books = triangle_books(Exchange::Binance);
+    // Move the ETH/BTC book to another exchange: no single exchange has all three legs
+    let (pair, _) = books.pop().unwrap();
+    books.push((
+        pair,
+        TestDataFactory::create_summary("ETHBTC", Exchange::Kraken, 0.0594, 0.0595, 10.0, 10.0),
+    ));
+    let summaries = by_pair(books);
+
+    assert!(detector.detect_opportunities(&summaries).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_triangular_detect_on_exchange() {
+    let detector = TriangularArbitrageDetector::default();
+    let mut books = triangle_books(Exchange::Binance);
+    books.extend(triangle_books(Exchange::Kraken));
+    let summaries = by_pair(books);
+
+    assert_eq!(detector.detect_opportunities(&summaries).await.len(), 2);
+
+    let kraken = detector
+        .detect_on_exchange(&Exchange::Kraken, &summaries)
+        .await;
+    assert_eq!(kraken.len(), 1);
+    assert_eq!(kraken[0].exchange, Exchange::Kraken);
+
+    assert!(detector
+        .detect_on_exchange(&Exchange::Bybit, &summaries)
+        .await
+        .is_empty());
+}
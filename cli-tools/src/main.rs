@@ -1,3 +1,116 @@
-fn main() {
-    println!("Hello, world!");
+//! Command line tools for running the aggregator
+//!
+//! `cli-tools validate <config>` checks a JSON, TOML or YAML config file and lists every
+//! problem found, exiting with status 1 if there is any.
+//!
+//! `cli-tools run <config>` runs the aggregator with the built-in connectors and the servers
+//! the config enables, until interrupted with Ctrl-C.
+
+use aggregator_core::{Config, ConfigFormat, init_logging};
+use server_implementations::{aggregator_from_config, create_servers_from_config};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+const USAGE: &str = "usage: cli-tools <validate|run> <config.json|config.toml|config.yaml>";
+
+/// How long `run` waits for the aggregator and servers to stop once interrupted
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["validate", path] => validate(path),
+        ["run", path] => run(path),
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn validate(path: &str) -> ExitCode {
+    let config = match std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            Config::parse(&content, ConfigFormat::from_path(path)).map_err(|e| e.to_string())
+        }) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let issues = config.issues();
+    if issues.is_empty() {
+        println!("{} is valid", path);
+        return ExitCode::SUCCESS;
+    }
+    eprintln!("{} has {} problem(s):", path, issues.len());
+    for issue in issues {
+        eprintln!("  {}", issue);
+    }
+    ExitCode::FAILURE
+}
+
+fn run(path: &str) -> ExitCode {
+    let config = match Config::from_file(path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = init_logging(&config.logging) {
+        eprintln!("{}: {}", path, e);
+        return ExitCode::FAILURE;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("failed to start the runtime: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    runtime.block_on(async move {
+        let servers = create_servers_from_config(&config);
+        let aggregator = match aggregator_from_config(config).await {
+            Ok(aggregator) => Arc::new(aggregator),
+            Err(e) => {
+                eprintln!("failed to create the aggregator: {}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+        if let Err(e) = aggregator.start().await {
+            eprintln!("failed to start the aggregator: {}", e);
+            return ExitCode::FAILURE;
+        }
+        if let Err(e) = servers.start_all(aggregator.clone()).await {
+            eprintln!("failed to start the servers: {}", e);
+            aggregator.shutdown(SHUTDOWN_TIMEOUT).await;
+            return ExitCode::FAILURE;
+        }
+
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            eprintln!("failed to wait for Ctrl-C: {}", e);
+        }
+        let report = aggregator.shutdown(SHUTDOWN_TIMEOUT).await;
+        if report.is_clean() {
+            return ExitCode::SUCCESS;
+        }
+        for failure in report.failed {
+            eprintln!(
+                "{} did not stop cleanly: {:?}",
+                failure.component, failure.error
+            );
+        }
+        ExitCode::FAILURE
+    })
 }
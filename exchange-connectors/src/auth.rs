@@ -0,0 +1,113 @@
+//! Authentication Module
+//! API credentials and shared plumbing for private user data channels
+
+use std::fmt;
+#[cfg(any(feature = "binance", feature = "coinbase"))]
+use tokio::sync::mpsc::Sender;
+
+use aggregator_core::{AggregatorError, BalanceUpdate, ExchangeConfig, OrderUpdate, Result};
+
+/// API credentials for an exchange account. The secret is never printed by `Debug`.
+#[derive(Clone)]
+pub struct Credentials {
+    pub api_key: String,
+    api_secret: String,
+    pub passphrase: Option<String>,
+}
+
+impl Credentials {
+    pub fn new(api_key: &str, api_secret: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+            passphrase: None,
+        }
+    }
+
+    /// Sets the passphrase required by exchanges such as Coinbase.
+    pub fn with_passphrase(mut self, passphrase: &str) -> Self {
+        self.passphrase = Some(passphrase.to_string());
+        self
+    }
+
+    /// Reads the credentials from `config`, returning `None` when the key or secret is missing.
+    pub fn from_config(config: &ExchangeConfig) -> Option<Self> {
+        let api_key = config.api_key.as_deref().filter(|key| !key.is_empty())?;
+        let api_secret = config
+            .api_secret
+            .as_deref()
+            .filter(|secret| !secret.is_empty())?;
+        Some(Self {
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+            passphrase: config.passphrase.clone(),
+        })
+    }
+
+    pub fn api_secret(&self) -> &str {
+        &self.api_secret
+    }
+
+    /// Returns the passphrase, or an authentication error naming `exchange` when it is missing.
+    pub fn require_passphrase(&self, exchange: &str) -> Result<&str> {
+        self.passphrase
+            .as_deref()
+            .ok_or_else(|| AggregatorError::Authentication {
+                message: format!("{} requires an API passphrase", exchange),
+            })
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("api_key", &self.api_key)
+            .field("api_secret", &"<redacted>")
+            .field(
+                "passphrase",
+                &self.passphrase.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+/// A single event parsed from a private user data channel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserDataEvent {
+    Order(OrderUpdate),
+    Balance(BalanceUpdate),
+}
+
+/// Returns the configured credentials or an authentication error naming `exchange`.
+#[cfg(any(feature = "binance", feature = "coinbase"))]
+pub(crate) fn require_credentials<'a>(
+    credentials: &'a Option<Credentials>,
+    exchange: &str,
+) -> Result<&'a Credentials> {
+    credentials
+        .as_ref()
+        .ok_or_else(|| AggregatorError::Authentication {
+            message: format!("{} API key and secret are not configured", exchange),
+        })
+}
+
+/// Forwards parsed events to their channels. Returns `false` once both receivers are gone.
+#[cfg(any(feature = "binance", feature = "coinbase"))]
+pub(crate) async fn forward_user_data(
+    events: Vec<UserDataEvent>,
+    order_tx: &Sender<OrderUpdate>,
+    balance_tx: &Sender<BalanceUpdate>,
+) -> bool {
+    for event in events {
+        // A dropped receiver only stops that kind of update
+        match event {
+            UserDataEvent::Order(update) => {
+                let _ = order_tx.send(update).await;
+            }
+            UserDataEvent::Balance(update) => {
+                let _ = balance_tx.send(update).await;
+            }
+        }
+    }
+    !(order_tx.is_closed() && balance_tx.is_closed())
+}
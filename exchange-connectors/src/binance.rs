@@ -2,79 +2,516 @@
 //! Handles connectivity and interaction with Binance's API
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
-use serde::{Deserialize, Serialize};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::Sender;
 use tokio::task::JoinHandle;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::Message;
 use tracing::{error, info, warn};
 
-use crate::OrderBookService;
-use aggregator_core::{AggregatorError, Ask, Bid, Exchange, PriceLevelUpdate, Result};
+use crate::auth::{forward_user_data, require_credentials, Credentials, UserDataEvent};
+use crate::capture::Capture;
+use crate::exchange_info::{decimal, ensure_complete, get_body, parse_body, requested_symbols};
+use crate::proxy::{connect_websocket, http_client, Proxy};
+use crate::rate_limit::RateLimiter;
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::snapshot_sync::{ForwardedBook, SequencedUpdate, SnapshotSync, SyncStep};
+use crate::trade_stream::{spawn_trade_stream, TradeStreamSpec};
+use crate::{
+    AuthenticatedService, ExchangeInfoService, OrderBookService, SymbolMapper, TradeStreamService,
+};
+use aggregator_core::{
+    AggregatorError, Ask, BalanceUpdate, Bid, Exchange, ExchangeConfig, FundingRate, HealthEvent,
+    InstrumentInfo, MarketDiscovery, MarketListing, MarketType, OrderStatus, OrderUpdate,
+    PriceLevelUpdate, RateLimitConfig, Result, Trade, TradeSide, TradingPair,
+};
 
-const WS_BASE_ENDPOINT: &str = "wss://stream.binance.com:9443/ws/";
+const COMBINED_STREAM_BASE_ENDPOINT: &str = "wss://stream.binance.com:9443/stream?streams=";
 const ORDER_BOOK_SNAPSHOT_BASE_ENDPOINT: &str = "https://api.binance.com/api/v3/depth?symbol=";
-const DEPTH_UPDATE_EVENT: &str = "depthUpdate";
+const TESTNET_COMBINED_STREAM_BASE_ENDPOINT: &str = "wss://testnet.binance.vision/stream?streams=";
+const TESTNET_ORDER_BOOK_SNAPSHOT_BASE_ENDPOINT: &str =
+    "https://testnet.binance.vision/api/v3/depth?symbol=";
+const FUTURES_COMBINED_STREAM_BASE_ENDPOINT: &str = "wss://fstream.binance.com/stream?streams=";
+const FUTURES_ORDER_BOOK_SNAPSHOT_BASE_ENDPOINT: &str =
+    "https://fapi.binance.com/fapi/v1/depth?symbol=";
+const TESTNET_FUTURES_COMBINED_STREAM_BASE_ENDPOINT: &str =
+    "wss://stream.binancefuture.com/stream?streams=";
+const TESTNET_FUTURES_ORDER_BOOK_SNAPSHOT_BASE_ENDPOINT: &str =
+    "https://testnet.binancefuture.com/fapi/v1/depth?symbol=";
+const EXCHANGE_INFO_ENDPOINT: &str = "https://api.binance.com/api/v3/exchangeInfo";
+const TESTNET_EXCHANGE_INFO_ENDPOINT: &str = "https://testnet.binance.vision/api/v3/exchangeInfo";
+const FUTURES_EXCHANGE_INFO_ENDPOINT: &str = "https://fapi.binance.com/fapi/v1/exchangeInfo";
+const TESTNET_FUTURES_EXCHANGE_INFO_ENDPOINT: &str =
+    "https://testnet.binancefuture.com/fapi/v1/exchangeInfo";
+const TICKER_24H_ENDPOINT: &str = "https://api.binance.com/api/v3/ticker/24hr";
+const TESTNET_TICKER_24H_ENDPOINT: &str = "https://testnet.binance.vision/api/v3/ticker/24hr";
+const FUTURES_TICKER_24H_ENDPOINT: &str = "https://fapi.binance.com/fapi/v1/ticker/24hr";
+const TESTNET_FUTURES_TICKER_24H_ENDPOINT: &str =
+    "https://testnet.binancefuture.com/fapi/v1/ticker/24hr";
+/// Status of symbols open for trading in `exchangeInfo`
+const TRADING_STATUS: &str = "TRADING";
+const USER_DATA_STREAM_ENDPOINT: &str = "https://api.binance.com/api/v3/userDataStream";
+const TESTNET_USER_DATA_STREAM_ENDPOINT: &str =
+    "https://testnet.binance.vision/api/v3/userDataStream";
+const USER_STREAM_BASE_ENDPOINT: &str = "wss://stream.binance.com:9443/ws/";
+const TESTNET_USER_STREAM_BASE_ENDPOINT: &str = "wss://testnet.binance.vision/ws/";
+const EXECUTION_REPORT_EVENT: &str = "executionReport";
+const ACCOUNT_POSITION_EVENT: &str = "outboundAccountPosition";
+// Listen keys expire after 60 minutes without a keepalive
+const LISTEN_KEY_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30 * 60);
 const GET_ORDER_BOOK_SNAPSHOT: Vec<u8> = vec![];
+/// Wait before retrying a failed snapshot request
+const SNAPSHOT_RETRY_DELAY: Duration = Duration::from_secs(1);
 
-pub struct Binance;
+pub struct Binance {
+    sandbox: bool,
+    market_type: MarketType,
+    credentials: Option<Credentials>,
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    rate_limiter: RateLimiter,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+    websocket_url: Option<String>,
+    snapshot_url: Option<String>,
+}
+
+/// REST access to depth snapshots on the selected market.
+#[derive(Clone)]
+struct SnapshotClient {
+    client: reqwest::Client,
+    endpoint: String,
+    rate_limiter: RateLimiter,
+}
 
 #[async_trait]
 impl OrderBookService for Binance {
     async fn spawn_order_book_service(
         &self,
-        pair: [&str; 2],
+        pairs: &[TradingPair],
         order_book_depth: usize,
         exchange_stream_buffer: usize,
         price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
     ) -> Result<Vec<JoinHandle<Result<()>>>> {
-        let pair_str = pair.join("");
-        let stream_pair = pair_str.to_lowercase();
-        let snapshot_pair = pair_str.to_uppercase();
+        let snapshot_pairs = SymbolMapper::new().to_exchange_all(&Exchange::Binance, pairs)?;
+        let mut streams: Vec<String> = snapshot_pairs
+            .iter()
+            .map(|pair| format!("{}@depth", pair.to_lowercase()))
+            .collect();
+        if self.market_type == MarketType::Futures {
+            // Mark price events carry the funding rate attached to every update
+            streams.extend(
+                snapshot_pairs
+                    .iter()
+                    .map(|pair| format!("{}@markPrice", pair.to_lowercase())),
+            );
+        }
+        let order_book_endpoint = self.order_book_stream_endpoint(&streams)?;
+        let snapshots = SnapshotClient {
+            client: http_client(self.proxy.as_ref())?,
+            endpoint: self.snapshot_base_endpoint().to_string(),
+            rate_limiter: self.rate_limiter.clone(),
+        };
 
-        info!("Spawning Binance order book stream for {}", stream_pair);
+        info!(
+            "Spawning Binance {:?} order book stream for {}",
+            self.market_type,
+            streams.join(", ")
+        );
 
         // Spawn WebSocket stream handler
-        let (ws_stream_rx, stream_handle) =
-            Self::spawn_order_book_stream(stream_pair, exchange_stream_buffer);
+        let (ws_stream_rx, stream_handle) = self.spawn_order_book_stream(
+            order_book_endpoint,
+            streams,
+            exchange_stream_buffer,
+            Shutdown::new(shutdown),
+        );
 
         info!("Spawning Binance order book stream processor");
 
         // Spawn stream processor
         let processor_handle = Self::spawn_stream_processor(
-            snapshot_pair,
+            snapshot_pairs,
             order_book_depth,
             ws_stream_rx,
             price_level_tx,
+            snapshots,
+            self.capture.clone(),
         );
 
         Ok(vec![stream_handle, processor_handle])
     }
 }
 
+#[async_trait]
+impl TradeStreamService for Binance {
+    async fn spawn_trade_service(
+        &self,
+        pairs: &[TradingPair],
+        trade_tx: Sender<Trade>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let streams: Vec<String> = SymbolMapper::new()
+            .to_exchange_all(&Exchange::Binance, pairs)?
+            .iter()
+            .map(|pair| format!("{}@trade", pair.to_lowercase()))
+            .collect();
+
+        info!("Spawning Binance trade stream for {}", streams.join(", "));
+
+        let spec = TradeStreamSpec {
+            exchange: "binance",
+            url: self.combined_stream_endpoint(&streams),
+            subscriptions: vec![],
+            ping: None,
+            reconnect_policy: self.reconnect_policy.clone(),
+            health: self.health_reporter(),
+            proxy: self.proxy.clone(),
+            capture: self.capture.clone(),
+        };
+
+        Ok(vec![spawn_trade_stream(spec, Self::parse_trades, trade_tx)])
+    }
+}
+
 impl Binance {
     pub fn new() -> Self {
-        Binance
+        Self {
+            sandbox: false,
+            market_type: MarketType::Spot,
+            credentials: None,
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            rate_limiter: RateLimiter::new("binance", &RateLimitConfig::default()),
+            proxy: None,
+            capture: None,
+            websocket_url: None,
+            snapshot_url: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings, using the Binance Spot testnet when
+    /// `sandbox` is set.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        let mut binance = Self::new()
+            .with_sandbox(config.sandbox)
+            .with_rate_limit(&config.rate_limit)
+            .with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        binance.credentials = Credentials::from_config(config);
+        binance.proxy = Proxy::from_config(config)?;
+        Ok(binance)
+    }
+
+    /// Select the market order books are streamed from: `Spot` or USDT-M perpetual `Futures`.
+    /// Trade and user data streams always use spot.
+    pub fn with_market_type(mut self, market_type: MarketType) -> Self {
+        self.market_type = market_type;
+        self
+    }
+
+    /// Authenticate private user data streams with `credentials`.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Connect to the Binance Spot testnet instead of production.
+    pub fn with_sandbox(mut self, sandbox: bool) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    /// Limit REST snapshot requests according to `config`.
+    pub fn with_rate_limit(mut self, config: &RateLimitConfig) -> Self {
+        self.rate_limiter = RateLimiter::new("binance", config);
+        self
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket and REST traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Stream order books from a custom combined stream base, such as a local mock server. Stream
+    /// names are appended to it.
+    pub fn with_websocket_url(mut self, websocket_url: &str) -> Self {
+        self.websocket_url = Some(websocket_url.to_string());
+        self
+    }
+
+    /// Fetch depth snapshots from a custom base, such as a local mock server. The symbol and
+    /// limit are appended to it.
+    pub fn with_snapshot_url(mut self, snapshot_url: &str) -> Self {
+        self.snapshot_url = Some(snapshot_url.to_string());
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::Binance, self.health_tx.clone())
+    }
+
+    /// Builds a combined stream endpoint so every stream shares one connection.
+    pub fn combined_stream_endpoint(&self, streams: &[String]) -> String {
+        let base = if self.sandbox {
+            TESTNET_COMBINED_STREAM_BASE_ENDPOINT
+        } else {
+            COMBINED_STREAM_BASE_ENDPOINT
+        };
+        format!("{}{}", base, streams.join("/"))
+    }
+
+    /// Builds the combined stream endpoint for order book streams on the selected market.
+    pub fn order_book_stream_endpoint(&self, streams: &[String]) -> Result<String> {
+        if let Some(base) = &self.websocket_url {
+            return Ok(format!("{}{}", base, streams.join("/")));
+        }
+        let base = match (&self.market_type, self.sandbox) {
+            (MarketType::Spot, _) => return Ok(self.combined_stream_endpoint(streams)),
+            (MarketType::Futures, false) => FUTURES_COMBINED_STREAM_BASE_ENDPOINT,
+            (MarketType::Futures, true) => TESTNET_FUTURES_COMBINED_STREAM_BASE_ENDPOINT,
+            (MarketType::Options, _) => {
+                return Err(AggregatorError::validation(
+                    "market_type",
+                    "Binance order books support Spot and Futures markets",
+                ))
+            }
+        };
+        Ok(format!("{}{}", base, streams.join("/")))
+    }
+
+    fn snapshot_base_endpoint(&self) -> &str {
+        if let Some(base) = &self.snapshot_url {
+            return base;
+        }
+        match (&self.market_type, self.sandbox) {
+            (MarketType::Futures, false) => FUTURES_ORDER_BOOK_SNAPSHOT_BASE_ENDPOINT,
+            (MarketType::Futures, true) => TESTNET_FUTURES_ORDER_BOOK_SNAPSHOT_BASE_ENDPOINT,
+            (_, false) => ORDER_BOOK_SNAPSHOT_BASE_ENDPOINT,
+            (_, true) => TESTNET_ORDER_BOOK_SNAPSHOT_BASE_ENDPOINT,
+        }
+    }
+
+    /// Builds the exchange info endpoint for `symbols` on the selected market. The futures
+    /// endpoint cannot filter by symbol, so it always lists every contract.
+    pub fn exchange_info_endpoint(&self, symbols: &[String]) -> String {
+        let base = self.markets_endpoint();
+        if self.market_type == MarketType::Futures {
+            return base.to_string();
+        }
+        let symbols = serde_json::to_string(symbols).unwrap_or_default();
+        format!(
+            "{}?symbols={}",
+            base,
+            utf8_percent_encode(&symbols, NON_ALPHANUMERIC)
+        )
+    }
+
+    /// The exchange info endpoint listing every symbol of the selected market.
+    pub fn markets_endpoint(&self) -> &'static str {
+        match (&self.market_type, self.sandbox) {
+            (MarketType::Futures, false) => FUTURES_EXCHANGE_INFO_ENDPOINT,
+            (MarketType::Futures, true) => TESTNET_FUTURES_EXCHANGE_INFO_ENDPOINT,
+            (_, false) => EXCHANGE_INFO_ENDPOINT,
+            (_, true) => TESTNET_EXCHANGE_INFO_ENDPOINT,
+        }
+    }
+
+    /// The 24 hour ticker endpoint of the selected market, covering every symbol.
+    pub fn ticker_endpoint(&self) -> &'static str {
+        match (&self.market_type, self.sandbox) {
+            (MarketType::Futures, false) => FUTURES_TICKER_24H_ENDPOINT,
+            (MarketType::Futures, true) => TESTNET_FUTURES_TICKER_24H_ENDPOINT,
+            (_, false) => TICKER_24H_ENDPOINT,
+            (_, true) => TESTNET_TICKER_24H_ENDPOINT,
+        }
+    }
+
+    fn user_data_stream_endpoint(&self) -> &'static str {
+        if self.sandbox {
+            TESTNET_USER_DATA_STREAM_ENDPOINT
+        } else {
+            USER_DATA_STREAM_ENDPOINT
+        }
+    }
+
+    fn user_stream_base_endpoint(&self) -> &'static str {
+        if self.sandbox {
+            TESTNET_USER_STREAM_BASE_ENDPOINT
+        } else {
+            USER_STREAM_BASE_ENDPOINT
+        }
+    }
+
+    /// Extracts the event payload, unwrapping the `{"stream": .., "data": ..}` envelope used by
+    /// combined streams.
+    fn stream_payload(message: &str) -> Result<serde_json::Value> {
+        let mut value: serde_json::Value = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("BinanceEvent", format!("Failed to parse event: {}", e))
+        })?;
+        if value.get("stream").is_some() {
+            if let Some(data) = value.get_mut("data") {
+                return Ok(data.take());
+            }
+        }
+        Ok(value)
+    }
+
+    /// Parse a raw stream message, plain or combined, into the market data event it carries.
+    /// The message is parsed once, whatever the event.
+    fn parse_stream_event(message: &str) -> Result<StreamEvent> {
+        serde_json::from_value(Self::stream_payload(message)?).map_err(|e| {
+            AggregatorError::parsing("BinanceEvent", format!("Failed to parse event: {}", e))
+        })
+    }
+
+    /// Parse a raw `@trade` stream message, plain or combined, into trades. Non-trade events
+    /// yield no trades.
+    pub fn parse_trades(message: &str) -> Result<Vec<Trade>> {
+        let StreamEvent::Trade(trade) = Self::parse_stream_event(message)? else {
+            return Ok(vec![]);
+        };
+
+        let price: f64 = trade.price.parse().map_err(|e| {
+            AggregatorError::parsing("Trade", format!("Invalid trade price: {}", e))
+        })?;
+        let quantity: f64 = trade.quantity.parse().map_err(|e| {
+            AggregatorError::parsing("Trade", format!("Invalid trade quantity: {}", e))
+        })?;
+
+        // The buyer being the maker means the seller crossed the spread
+        let side = if trade.buyer_is_maker {
+            TradeSide::Sell
+        } else {
+            TradeSide::Buy
+        };
+
+        Ok(vec![Trade {
+            id: uuid::Uuid::new_v4(),
+            trade_id: trade.trade_id.to_string(),
+            symbol: trade.symbol,
+            exchange: Exchange::Binance,
+            price,
+            quantity,
+            side,
+            timestamp: DateTime::from_timestamp_millis(trade.trade_time).unwrap_or_else(Utc::now),
+        }])
+    }
+
+    /// Parse a raw futures `@markPrice` stream message into the symbol and its funding
+    /// information. Other events yield `None`.
+    pub fn parse_funding_rate(message: &str) -> Result<Option<(String, FundingRate)>> {
+        match Self::parse_stream_event(message)? {
+            StreamEvent::MarkPrice(mark) => Self::funding_rate(mark).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Convert a mark price update into its symbol and funding information
+    fn funding_rate(mark: MarkPriceUpdate) -> Result<(String, FundingRate)> {
+        let funding = FundingRate {
+            rate: parse_decimal("funding_rate", &mark.funding_rate)?,
+            mark_price: Some(parse_decimal("mark_price", &mark.mark_price)?),
+            next_funding_time: DateTime::from_timestamp_millis(mark.next_funding_time),
+        };
+        Ok((mark.symbol, funding))
+    }
+
+    /// Parse a raw user data stream message into order and balance events. Other events yield
+    /// nothing.
+    pub fn parse_user_data(message: &str) -> Result<Vec<UserDataEvent>> {
+        let payload = Self::stream_payload(message)?;
+        let event: OrderBookEvent = serde_json::from_value(payload.clone()).map_err(|e| {
+            AggregatorError::parsing("BinanceEvent", format!("Failed to parse event: {}", e))
+        })?;
+
+        match event.event.as_str() {
+            EXECUTION_REPORT_EVENT => {
+                let report: ExecutionReport = serde_json::from_value(payload).map_err(|e| {
+                    AggregatorError::parsing(
+                        "ExecutionReport",
+                        format!("Failed to parse execution report: {}", e),
+                    )
+                })?;
+                Ok(vec![UserDataEvent::Order(report.into_order_update()?)])
+            }
+            ACCOUNT_POSITION_EVENT => {
+                let position: AccountPosition = serde_json::from_value(payload).map_err(|e| {
+                    AggregatorError::parsing(
+                        "AccountPosition",
+                        format!("Failed to parse account position: {}", e),
+                    )
+                })?;
+                let timestamp =
+                    DateTime::from_timestamp_millis(position.event_time).unwrap_or_else(Utc::now);
+                position
+                    .balances
+                    .into_iter()
+                    .map(|balance| {
+                        Ok(UserDataEvent::Balance(BalanceUpdate {
+                            id: uuid::Uuid::new_v4(),
+                            exchange: Exchange::Binance,
+                            free: parse_decimal("free", &balance.free)?,
+                            locked: parse_decimal("locked", &balance.locked)?,
+                            asset: balance.asset,
+                            timestamp,
+                        }))
+                    })
+                    .collect()
+            }
+            _ => Ok(vec![]),
+        }
     }
 
     /// Spawn WebSocket stream for order book updates
     fn spawn_order_book_stream(
-        pair: String,
+        &self,
+        order_book_endpoint: String,
+        streams: Vec<String>,
         exchange_stream_buffer: usize,
+        mut shutdown: Shutdown,
     ) -> (tokio::sync::mpsc::Receiver<Message>, JoinHandle<Result<()>>) {
         let (ws_stream_tx, ws_stream_rx) =
             tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
 
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+        let proxy = self.proxy.clone();
+
         let stream_handle = tokio::spawn(async move {
-            let ws_stream_tx = ws_stream_tx.clone();
+            let mut backoff = reconnect_policy.backoff();
             loop {
-                let order_book_endpoint = format!("{}{}{}", WS_BASE_ENDPOINT, pair, "@depth");
-
-                match connect_async(&order_book_endpoint).await {
+                match connect_websocket(&order_book_endpoint, proxy.as_ref()).await {
                     Ok((mut ws_stream, _)) => {
-                        info!("WebSocket connection established for {}", pair);
+                        info!(
+                            "WebSocket connection established for {}",
+                            streams.join(", ")
+                        );
+                        backoff.reset();
+                        health.connected();
 
                         // Signal to get initial snapshot
                         if let Err(e) = ws_stream_tx
@@ -82,11 +519,29 @@ impl Binance {
                             .await
                         {
                             error!("Failed to send snapshot signal: {}", e);
-                            continue;
+                            return Ok(());
                         }
 
                         // Process messages from WebSocket
-                        while let Some(msg) = ws_stream.next().await {
+                        let mut watchdog = reconnect_policy.watchdog();
+                        loop {
+                            let msg = tokio::select! {
+                                msg = ws_stream.next() => match msg {
+                                    Some(msg) => msg,
+                                    None => break,
+                                },
+                                silent_for = watchdog.stalled() => {
+                                    error!("{}", health.stalled(silent_for));
+                                    break;
+                                }
+                                _ = shutdown.recv() => {
+                                    info!("Closing Binance order book stream");
+                                    let _ = ws_stream.close(None).await;
+                                    return Ok(());
+                                }
+                            };
+                            watchdog.touch();
+
                             match msg {
                                 Ok(Message::Text(_)) => {
                                     if let Err(e) = ws_stream_tx.send(msg.unwrap()).await {
@@ -111,162 +566,253 @@ impl Binance {
                                 _ => {}
                             }
                         }
+                        health.disconnected("WebSocket connection closed");
                     }
                     Err(e) => {
                         error!("Failed to connect to Binance WebSocket: {}", e);
-                        tokio::time::sleep(Duration::from_secs(5)).await;
+                        health.disconnected(e.to_string());
                     }
                 }
+
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
             }
         });
 
         (ws_stream_rx, stream_handle)
     }
 
-    /// Spawn stream processor for handling order book updates
+    /// Spawn stream processor for handling order book updates. Each pair follows the documented
+    /// local book algorithm: diffs are buffered while its snapshot is fetched, then applied on top
+    /// of it, and a gap in the diffs triggers a new snapshot.
     fn spawn_stream_processor(
-        pair: String,
+        pairs: Vec<String>,
         order_book_depth: usize,
         mut ws_stream_rx: tokio::sync::mpsc::Receiver<Message>,
         price_level_tx: Sender<PriceLevelUpdate>,
+        snapshots: SnapshotClient,
+        capture: Option<Capture>,
     ) -> JoinHandle<Result<()>> {
         tokio::spawn(async move {
-            let mut last_update_id = 0u64;
-
-            while let Some(message) = ws_stream_rx.recv().await {
-                match message {
-                    Message::Text(text) => {
-                        if let Err(e) =
-                            Self::process_depth_update(&text, &mut last_update_id, &price_level_tx)
-                                .await
-                        {
-                            error!("Failed to process depth update: {}", e);
+            let mut books: HashMap<String, SnapshotSync<OrderBookUpdate>> = pairs
+                .iter()
+                .map(|pair| (pair.clone(), SnapshotSync::new()))
+                .collect();
+            let mut funding: HashMap<String, FundingRate> = HashMap::new();
+            // What was sent downstream per pair, to remove what a fresh snapshot no longer has
+            let mut forwarded: HashMap<String, ForwardedBook> = HashMap::new();
+            // Pairs with a snapshot request in flight
+            let mut pending: HashSet<String> = HashSet::new();
+            let (snapshot_tx, mut snapshot_rx) = tokio::sync::mpsc::channel::<(
+                String,
+                Result<OrderBookSnapshot>,
+            )>(pairs.len().max(1));
+            let request_snapshot = |pair: &str, delay: Duration| {
+                let pair = pair.to_string();
+                let snapshots = snapshots.clone();
+                let snapshot_tx = snapshot_tx.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    info!("Getting order book snapshot for {}", pair);
+                    let snapshot =
+                        Self::get_order_book_snapshot(&snapshots, &pair, order_book_depth).await;
+                    let _ = snapshot_tx.send((pair, snapshot)).await;
+                });
+            };
+
+            loop {
+                tokio::select! {
+                    message = ws_stream_rx.recv() => match message {
+                        Some(Message::Text(text)) => {
+                            if let Some(capture) = &capture {
+                                capture.record(&Exchange::Binance, &text);
+                            }
+                            let update = match Self::parse_stream_event(&text) {
+                                Ok(StreamEvent::Depth(update)) => update,
+                                Ok(StreamEvent::MarkPrice(mark)) => {
+                                    match Self::funding_rate(mark) {
+                                        Ok((symbol, rate)) => {
+                                            funding.insert(symbol, rate);
+                                        }
+                                        Err(e) => {
+                                            error!("Failed to process mark price update: {}", e)
+                                        }
+                                    }
+                                    continue;
+                                }
+                                Ok(_) => continue,
+                                Err(e) => {
+                                    error!("Failed to process Binance event: {}", e);
+                                    continue;
+                                }
+                            };
+                            let symbol = update.symbol.clone();
+                            let Some(book) = books.get_mut(&symbol) else {
+                                continue;
+                            };
+                            match book.push(update) {
+                                SyncStep::Apply(update) => {
+                                    match Self::depth_update(update, funding.get(&symbol)) {
+                                        Ok(update) => {
+                                            forwarded
+                                                .entry(symbol.clone())
+                                                .or_default()
+                                                .record(&update);
+                                            if price_level_tx.send(update).await.is_err() {
+                                                return Ok(());
+                                            }
+                                        }
+                                        Err(e) => error!("Failed to process depth update: {}", e),
+                                    }
+                                }
+                                SyncStep::Resync => {
+                                    warn!(
+                                        "Binance update gap for {}, refreshing snapshot",
+                                        symbol
+                                    );
+                                    if pending.insert(symbol.clone()) {
+                                        request_snapshot(&symbol, Duration::ZERO);
+                                    }
+                                }
+                                SyncStep::Buffered | SyncStep::Stale => {}
+                            }
                         }
-                    }
-                    Message::Binary(data) => {
-                        if data.is_empty() {
-                            // Get order book snapshot
-                            if let Err(e) = Self::process_snapshot(
-                                &pair,
-                                order_book_depth,
-                                &mut last_update_id,
-                                &price_level_tx,
-                            )
-                            .await
-                            {
-                                error!("Failed to process snapshot: {}", e);
+                        // A new connection: every book starts over from a fresh snapshot
+                        Some(Message::Binary(data)) if data.is_empty() => {
+                            for (pair, book) in books.iter_mut() {
+                                book.reset();
+                                if pending.insert(pair.clone()) {
+                                    request_snapshot(pair, Duration::ZERO);
+                                }
+                            }
+                        }
+                        Some(_) => {}
+                        None => return Ok(()),
+                    },
+                    Some((pair, snapshot)) = snapshot_rx.recv() => {
+                        pending.remove(&pair);
+                        let Some(book) = books.get_mut(&pair) else {
+                            continue;
+                        };
+                        let funding = funding.get(&pair);
+                        let snapshot = snapshot.and_then(|snapshot| {
+                            let last_update_id = snapshot.last_update_id;
+                            Ok((last_update_id, Self::snapshot_update(&pair, snapshot, funding)?))
+                        });
+                        let (last_update_id, mut snapshot) = match snapshot {
+                            Ok(snapshot) => snapshot,
+                            Err(e) => {
+                                error!("Failed to get snapshot for {}: {}", pair, e);
+                                pending.insert(pair.clone());
+                                request_snapshot(&pair, SNAPSHOT_RETRY_DELAY);
+                                continue;
+                            }
+                        };
+
+                        let Some(buffered) = book.apply_snapshot(last_update_id) else {
+                            warn!(
+                                "Binance snapshot for {} is older than its updates, refetching",
+                                pair
+                            );
+                            pending.insert(pair.clone());
+                            request_snapshot(&pair, Duration::ZERO);
+                            continue;
+                        };
+                        let forwarded = forwarded.entry(pair.clone()).or_default();
+                        forwarded.replace(&mut snapshot);
+                        let mut updates = vec![snapshot];
+                        for update in buffered {
+                            match Self::depth_update(update, funding) {
+                                Ok(update) => {
+                                    forwarded.record(&update);
+                                    updates.push(update);
+                                }
+                                Err(e) => error!("Failed to process depth update: {}", e),
+                            }
+                        }
+                        for update in updates {
+                            if price_level_tx.send(update).await.is_err() {
+                                return Ok(());
                             }
                         }
                     }
-                    _ => {}
                 }
             }
-            Ok(())
         })
     }
 
-    /// Process depth update message
-    async fn process_depth_update(
-        message: &str,
-        last_update_id: &mut u64,
-        price_level_tx: &Sender<PriceLevelUpdate>,
-    ) -> Result<()> {
-        // Parse the event to check if it's a depth update
-        let event: OrderBookEvent = serde_json::from_str(message)
-            .map_err(|e| AggregatorError::Parsing(format!("Failed to parse event: {}", e)))?;
-
-        if event.event == DEPTH_UPDATE_EVENT {
-            let update: OrderBookUpdate = serde_json::from_str(message).map_err(|e| {
-                AggregatorError::Parsing(format!("Failed to parse depth update: {}", e))
+    /// Convert a depth update into a price level update
+    fn depth_update(
+        update: OrderBookUpdate,
+        funding: Option<&FundingRate>,
+    ) -> Result<PriceLevelUpdate> {
+        let mut bids = Vec::new();
+        for bid_data in update.bids {
+            let price: f64 = bid_data[0].parse().map_err(|e| {
+                AggregatorError::parsing("Bid", format!("Invalid bid price: {}", e))
+            })?;
+            let quantity: f64 = bid_data[1].parse().map_err(|e| {
+                AggregatorError::parsing("Bid", format!("Invalid bid quantity: {}", e))
             })?;
 
-            // Validate update sequence
-            if update.final_updated_id <= *last_update_id {
-                warn!("Received out of order update, ignoring");
-                return Ok(());
-            }
-
-            if update.first_update_id <= *last_update_id + 1
-                && update.final_updated_id >= *last_update_id + 1
-            {
-                // Process bids and asks
-                let mut bids = Vec::new();
-                for bid_data in update.bids {
-                    let price: f64 = bid_data[0].parse().map_err(|e| {
-                        AggregatorError::Parsing(format!("Invalid bid price: {}", e))
-                    })?;
-                    let quantity: f64 = bid_data[1].parse().map_err(|e| {
-                        AggregatorError::Parsing(format!("Invalid bid quantity: {}", e))
-                    })?;
-
-                    bids.push(Bid {
-                        price,
-                        quantity,
-                        exchange: Exchange::Binance,
-                        timestamp: Utc::now(),
-                    });
-                }
-
-                let mut asks = Vec::new();
-                for ask_data in update.asks {
-                    let price: f64 = ask_data[0].parse().map_err(|e| {
-                        AggregatorError::Parsing(format!("Invalid ask price: {}", e))
-                    })?;
-                    let quantity: f64 = ask_data[1].parse().map_err(|e| {
-                        AggregatorError::Parsing(format!("Invalid ask quantity: {}", e))
-                    })?;
-
-                    asks.push(Ask {
-                        price,
-                        quantity,
-                        exchange: Exchange::Binance,
-                        timestamp: Utc::now(),
-                    });
-                }
+            bids.push(Bid {
+                price,
+                quantity,
+                exchange: Exchange::Binance,
+                timestamp: Utc::now(),
+            });
+        }
 
-                let price_level_update = PriceLevelUpdate {
-                    id: uuid::Uuid::new_v4(),
-                    symbol: update.symbol.clone(),
-                    exchange: Exchange::Binance,
-                    bids,
-                    asks,
-                    timestamp: Utc::now(),
-                };
-
-                price_level_tx.send(price_level_update).await.map_err(|e| {
-                    AggregatorError::ChannelSend(format!(
-                        "Failed to send price level update: {}",
-                        e
-                    ))
-                })?;
+        let mut asks = Vec::new();
+        for ask_data in update.asks {
+            let price: f64 = ask_data[0].parse().map_err(|e| {
+                AggregatorError::parsing("Ask", format!("Invalid ask price: {}", e))
+            })?;
+            let quantity: f64 = ask_data[1].parse().map_err(|e| {
+                AggregatorError::parsing("Ask", format!("Invalid ask quantity: {}", e))
+            })?;
 
-                *last_update_id = update.final_updated_id;
-            } else {
-                return Err(AggregatorError::Exchange(
-                    "Invalid update sequence".to_string(),
-                ));
-            }
+            asks.push(Ask {
+                price,
+                quantity,
+                exchange: Exchange::Binance,
+                timestamp: Utc::now(),
+            });
         }
 
-        Ok(())
+        Ok(PriceLevelUpdate {
+            id: uuid::Uuid::new_v4(),
+            symbol: update.symbol,
+            exchange: Exchange::Binance,
+            bids,
+            asks,
+            timestamp: Utc::now(),
+            funding: funding.cloned(),
+            event_time: DateTime::from_timestamp_millis(update.event_time as i64),
+            market_type: None,
+        })
     }
 
-    /// Process initial order book snapshot
-    async fn process_snapshot(
+    /// Convert an order book snapshot into a price level update
+    fn snapshot_update(
         pair: &str,
-        order_book_depth: usize,
-        last_update_id: &mut u64,
-        price_level_tx: &Sender<PriceLevelUpdate>,
-    ) -> Result<()> {
-        info!("Getting order book snapshot for {}", pair);
-
-        let snapshot = Self::get_order_book_snapshot(pair, order_book_depth).await?;
-
+        snapshot: OrderBookSnapshot,
+        funding: Option<&FundingRate>,
+    ) -> Result<PriceLevelUpdate> {
         let mut bids = Vec::new();
         for bid_data in snapshot.bids {
+            let price: f64 = bid_data[0].parse().map_err(|e| {
+                AggregatorError::parsing("Bid", format!("Invalid bid price: {}", e))
+            })?;
+            let quantity: f64 = bid_data[1].parse().map_err(|e| {
+                AggregatorError::parsing("Bid", format!("Invalid bid quantity: {}", e))
+            })?;
+
             bids.push(Bid {
-                price: bid_data[0],
-                quantity: bid_data[1],
+                price,
+                quantity,
                 exchange: Exchange::Binance,
                 timestamp: Utc::now(),
             });
@@ -274,49 +820,66 @@ impl Binance {
 
         let mut asks = Vec::new();
         for ask_data in snapshot.asks {
+            let price: f64 = ask_data[0].parse().map_err(|e| {
+                AggregatorError::parsing("Ask", format!("Invalid ask price: {}", e))
+            })?;
+            let quantity: f64 = ask_data[1].parse().map_err(|e| {
+                AggregatorError::parsing("Ask", format!("Invalid ask quantity: {}", e))
+            })?;
+
             asks.push(Ask {
-                price: ask_data[0],
-                quantity: ask_data[1],
+                price,
+                quantity,
                 exchange: Exchange::Binance,
                 timestamp: Utc::now(),
             });
         }
 
-        let price_level_update = PriceLevelUpdate {
+        Ok(PriceLevelUpdate {
             id: uuid::Uuid::new_v4(),
             symbol: pair.to_string(),
             exchange: Exchange::Binance,
             bids,
             asks,
             timestamp: Utc::now(),
-        };
-
-        price_level_tx
-            .send(price_level_update)
-            .await
-            .map_err(|e| AggregatorError::ChannelSend(format!("Failed to send snapshot: {}", e)))?;
-
-        *last_update_id = snapshot.last_update_id;
-        Ok(())
+            funding: funding.cloned(),
+            event_time: None,
+            market_type: None,
+        })
     }
 
     /// Get order book snapshot from REST API
     async fn get_order_book_snapshot(
+        snapshots: &SnapshotClient,
         pair: &str,
         order_book_depth: usize,
     ) -> Result<OrderBookSnapshot> {
-        let url = format!(
-            "{}{}&limit={}",
-            ORDER_BOOK_SNAPSHOT_BASE_ENDPOINT, pair, order_book_depth
-        );
+        snapshots.rate_limiter.try_acquire()?;
+
+        let url = format!("{}{}&limit={}", snapshots.endpoint, pair, order_book_depth);
 
-        let response = reqwest::get(&url)
+        let response = snapshots
+            .client
+            .get(&url)
+            .send()
             .await
-            .map_err(|e| AggregatorError::Network(format!("Failed to get snapshot: {}", e)))?;
+            .map_err(|e| AggregatorError::network(format!("Failed to get snapshot: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || response.status() == reqwest::StatusCode::IM_A_TEAPOT
+        {
+            return Err(AggregatorError::rate_limit(
+                "binance",
+                format!("Snapshot request rejected with HTTP {}", response.status()),
+            ));
+        }
 
         if response.status().is_success() {
             let snapshot: OrderBookSnapshot = response.json().await.map_err(|e| {
-                AggregatorError::Parsing(format!("Failed to parse snapshot: {}", e))
+                AggregatorError::parsing(
+                    "OrderBookSnapshot",
+                    format!("Failed to parse snapshot: {}", e),
+                )
             })?;
             Ok(snapshot)
         } else {
@@ -324,20 +887,318 @@ impl Binance {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(AggregatorError::Network(format!(
+            Err(AggregatorError::network(format!(
                 "HTTP error: {}",
                 error_text
             )))
         }
     }
+
+    /// Parse an `exchangeInfo` response listing every symbol and a 24 hour ticker response into
+    /// the symbols open for trading, with their quote volume.
+    pub fn parse_markets(exchange_info: &str, tickers: &str) -> Result<Vec<MarketListing>> {
+        let response: ExchangeInfoResponse = parse_body(exchange_info, "ExchangeInfoResponse")?;
+        let tickers: Vec<Ticker24h> = parse_body(tickers, "Ticker24h")?;
+        let volumes: HashMap<String, f64> = tickers
+            .into_iter()
+            .filter_map(|ticker| Some((ticker.symbol, ticker.quote_volume.parse().ok()?)))
+            .collect();
+
+        Ok(response
+            .symbols
+            .into_iter()
+            .filter(|symbol| {
+                symbol.status == TRADING_STATUS
+                    && !symbol.base_asset.is_empty()
+                    && !symbol.quote_asset.is_empty()
+            })
+            .map(|symbol| MarketListing {
+                exchange: Exchange::Binance,
+                pair: TradingPair::new(&symbol.base_asset, &symbol.quote_asset),
+                quote_volume: volumes.get(&symbol.symbol).copied(),
+                symbol: symbol.symbol,
+            })
+            .collect())
+    }
+
+    /// Parse an `exchangeInfo` response, spot or futures, into metadata for `pairs`.
+    pub fn parse_instruments(body: &str, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let requested = requested_symbols(&Exchange::Binance, pairs)?;
+        let response: ExchangeInfoResponse = parse_body(body, "ExchangeInfoResponse")?;
+
+        let mut instruments = Vec::new();
+        for symbol in response.symbols {
+            let Some(pair) = requested.get(&symbol.symbol) else {
+                continue;
+            };
+            let mut info = InstrumentInfo {
+                exchange: Exchange::Binance,
+                pair: pair.clone(),
+                symbol: symbol.symbol,
+                tick_size: 0.0,
+                lot_size: 0.0,
+                min_quantity: 0.0,
+                min_notional: None,
+            };
+            for filter in symbol.filters {
+                match filter {
+                    SymbolFilter::Price { tick_size } => {
+                        info.tick_size = decimal(&tick_size, "tickSize")?;
+                    }
+                    SymbolFilter::LotSize { min_qty, step_size } => {
+                        info.min_quantity = decimal(&min_qty, "minQty")?;
+                        info.lot_size = decimal(&step_size, "stepSize")?;
+                    }
+                    SymbolFilter::Notional { min_notional }
+                    | SymbolFilter::MinNotional {
+                        notional: min_notional,
+                    } => {
+                        info.min_notional = Some(decimal(&min_notional, "minNotional")?);
+                    }
+                    SymbolFilter::Other => {}
+                }
+            }
+            instruments.push(info);
+        }
+
+        ensure_complete(&Exchange::Binance, &requested, &instruments)?;
+        Ok(instruments)
+    }
+}
+
+#[async_trait]
+impl ExchangeInfoService for Binance {
+    async fn fetch_instruments(&self, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let symbols = SymbolMapper::new().to_exchange_all(&Exchange::Binance, pairs)?;
+        let client = http_client(self.proxy.as_ref())?;
+        let body = get_body(
+            &client,
+            &self.exchange_info_endpoint(&symbols),
+            Some(&self.rate_limiter),
+        )
+        .await?;
+        Self::parse_instruments(&body, pairs)
+    }
+}
+
+#[async_trait]
+impl MarketDiscovery for Binance {
+    async fn list_markets(&self) -> Result<Vec<MarketListing>> {
+        let client = http_client(self.proxy.as_ref())?;
+        let exchange_info =
+            get_body(&client, self.markets_endpoint(), Some(&self.rate_limiter)).await?;
+        let tickers = get_body(&client, self.ticker_endpoint(), Some(&self.rate_limiter)).await?;
+        Self::parse_markets(&exchange_info, &tickers)
+    }
+}
+
+#[async_trait]
+impl AuthenticatedService for Binance {
+    async fn spawn_user_data_service(
+        &self,
+        _pairs: &[TradingPair],
+        order_tx: Sender<OrderUpdate>,
+        balance_tx: Sender<BalanceUpdate>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let credentials = require_credentials(&self.credentials, "binance")?;
+
+        info!("Spawning Binance user data stream");
+
+        let stream = UserDataStream {
+            client: http_client(self.proxy.as_ref())?,
+            proxy: self.proxy.clone(),
+            listen_key_endpoint: self.user_data_stream_endpoint(),
+            ws_base_endpoint: self.user_stream_base_endpoint(),
+            api_key: credentials.api_key.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+        };
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                let result = stream
+                    .run(&order_tx, &balance_tx, &mut backoff, &health)
+                    .await;
+                if order_tx.is_closed() && balance_tx.is_closed() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("Binance user data stream closed");
+                        health.disconnected("User data stream closed");
+                    }
+                    // Rejected credentials will not start working on a retry
+                    Err(e @ AggregatorError::Authentication { .. }) => {
+                        error!("Binance user data stream rejected: {}", e);
+                        health.disconnected(e.to_string());
+                        return Err(e);
+                    }
+                    Err(e) => {
+                        error!("Binance user data stream error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+
+                health.wait_to_reconnect(&mut backoff).await?;
+            }
+        });
+
+        Ok(vec![handle])
+    }
+}
+
+/// A listen-key based user data stream. A fresh listen key is created for every connection and
+/// kept alive while it is open.
+struct UserDataStream {
+    client: reqwest::Client,
+    proxy: Option<Proxy>,
+    listen_key_endpoint: &'static str,
+    ws_base_endpoint: &'static str,
+    api_key: String,
+    rate_limiter: RateLimiter,
+}
+
+impl UserDataStream {
+    async fn run(
+        &self,
+        order_tx: &Sender<OrderUpdate>,
+        balance_tx: &Sender<BalanceUpdate>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+    ) -> Result<()> {
+        let listen_key = self.create_listen_key().await?;
+        let endpoint = format!("{}{}", self.ws_base_endpoint, listen_key);
+
+        let (mut ws_stream, _) = connect_websocket(&endpoint, self.proxy.as_ref()).await?;
+
+        info!("Connected to Binance user data stream");
+        backoff.reset();
+        health.connected();
+
+        let mut keepalive = tokio::time::interval(LISTEN_KEY_KEEPALIVE_INTERVAL);
+        keepalive.tick().await;
+
+        loop {
+            tokio::select! {
+                msg = ws_stream.next() => {
+                    match msg {
+                        Some(Ok(Message::Text(text))) => match Binance::parse_user_data(&text) {
+                            Ok(events) => {
+                                if !forward_user_data(events, order_tx, balance_tx).await {
+                                    return Ok(());
+                                }
+                            }
+                            Err(e) => warn!("Failed to parse Binance user data: {}", e),
+                        },
+                        Some(Ok(Message::Ping(payload))) => {
+                            if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                                error!("Failed to send pong: {}", e);
+                                return Ok(());
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => return Ok(()),
+                        Some(Err(e)) => {
+                            return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                        }
+                        _ => {}
+                    }
+                }
+                _ = keepalive.tick() => {
+                    if let Err(e) = self.keep_alive_listen_key(&listen_key).await {
+                        warn!("Failed to keep Binance listen key alive: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn create_listen_key(&self) -> Result<String> {
+        self.rate_limiter.try_acquire()?;
+
+        let response = self
+            .client
+            .post(self.listen_key_endpoint)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to create listen key: {}", e)))?;
+
+        let response = Self::check_status(response).await?;
+        let listen_key: ListenKeyResponse = response.json().await.map_err(|e| {
+            AggregatorError::parsing(
+                "ListenKeyResponse",
+                format!("Failed to parse listen key: {}", e),
+            )
+        })?;
+        Ok(listen_key.listen_key)
+    }
+
+    async fn keep_alive_listen_key(&self, listen_key: &str) -> Result<()> {
+        self.rate_limiter.try_acquire()?;
+
+        let response = self
+            .client
+            .put(self.listen_key_endpoint)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .query(&[("listenKey", listen_key)])
+            .send()
+            .await
+            .map_err(|e| {
+                AggregatorError::network(format!("Failed to keep listen key alive: {}", e))
+            })?;
+
+        Self::check_status(response).await.map(|_| ())
+    }
+
+    async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                Err(AggregatorError::Authentication {
+                    message: format!("binance: {}", error_text),
+                })
+            }
+            reqwest::StatusCode::TOO_MANY_REQUESTS | reqwest::StatusCode::IM_A_TEAPOT => Err(
+                AggregatorError::rate_limit("binance", format!("HTTP {}: {}", status, error_text)),
+            ),
+            _ => Err(AggregatorError::network(format!(
+                "HTTP error: {}",
+                error_text
+            ))),
+        }
+    }
+}
+
+fn parse_decimal(field: &str, value: &str) -> Result<f64> {
+    value
+        .parse()
+        .map_err(|e| AggregatorError::parsing(field, format!("Invalid {}: {}", field, e)))
+}
+
+impl Default for Binance {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct OrderBookSnapshot {
     #[serde(rename = "lastUpdateId")]
     last_update_id: u64,
-    bids: Vec<[f64; 2]>,
-    asks: Vec<[f64; 2]>,
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -350,14 +1211,210 @@ struct OrderBookUpdate {
     first_update_id: u64,
     #[serde(rename = "u")]
     final_updated_id: u64,
+    #[serde(rename = "pu", default)]
+    previous_final_update_id: Option<u64>,
     #[serde(rename = "b")]
     bids: Vec<[String; 2]>,
     #[serde(rename = "a")]
     asks: Vec<[String; 2]>,
 }
 
+impl SequencedUpdate for OrderBookUpdate {
+    fn first_update_id(&self) -> u64 {
+        self.first_update_id
+    }
+
+    fn final_update_id(&self) -> u64 {
+        self.final_updated_id
+    }
+
+    fn previous_final_update_id(&self) -> Option<u64> {
+        self.previous_final_update_id
+    }
+}
+
+/// Market data events of the order book and trade streams, told apart by their `e` field.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "e")]
+enum StreamEvent {
+    #[serde(rename = "depthUpdate")]
+    Depth(OrderBookUpdate),
+    #[serde(rename = "markPriceUpdate")]
+    MarkPrice(MarkPriceUpdate),
+    #[serde(rename = "trade")]
+    Trade(TradeEvent),
+    #[serde(other)]
+    Other,
+}
+
 #[derive(Debug, Deserialize)]
 struct OrderBookEvent {
     #[serde(rename = "e")]
     event: String,
 }
+
+#[derive(Debug, Deserialize)]
+struct MarkPriceUpdate {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "p")]
+    mark_price: String,
+    #[serde(rename = "r")]
+    funding_rate: String,
+    #[serde(rename = "T")]
+    next_funding_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TradeEvent {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "t")]
+    trade_id: u64,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    #[serde(rename = "T")]
+    trade_time: i64,
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListenKeyResponse {
+    #[serde(rename = "listenKey")]
+    listen_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecutionReport {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c")]
+    client_order_id: String,
+    #[serde(rename = "S")]
+    side: String,
+    #[serde(rename = "X")]
+    status: String,
+    #[serde(rename = "i")]
+    order_id: u64,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    #[serde(rename = "z")]
+    filled_quantity: String,
+    #[serde(rename = "T")]
+    transaction_time: i64,
+}
+
+impl ExecutionReport {
+    fn into_order_update(self) -> Result<OrderUpdate> {
+        let side = match self.side.as_str() {
+            "BUY" => TradeSide::Buy,
+            "SELL" => TradeSide::Sell,
+            other => {
+                return Err(AggregatorError::parsing(
+                    "ExecutionReport",
+                    format!("Unknown order side: {}", other),
+                ))
+            }
+        };
+        let status = match self.status.as_str() {
+            "NEW" => OrderStatus::New,
+            "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+            "FILLED" => OrderStatus::Filled,
+            "CANCELED" | "PENDING_CANCEL" => OrderStatus::Canceled,
+            "REJECTED" => OrderStatus::Rejected,
+            "EXPIRED" | "EXPIRED_IN_MATCH" => OrderStatus::Expired,
+            other => {
+                return Err(AggregatorError::parsing(
+                    "ExecutionReport",
+                    format!("Unknown order status: {}", other),
+                ))
+            }
+        };
+        // Market orders report a zero price
+        let price = parse_decimal("price", &self.price)?;
+
+        Ok(OrderUpdate {
+            id: uuid::Uuid::new_v4(),
+            order_id: self.order_id.to_string(),
+            client_order_id: Some(self.client_order_id).filter(|id| !id.is_empty()),
+            symbol: self.symbol,
+            exchange: Exchange::Binance,
+            side,
+            status,
+            price: Some(price).filter(|price| *price > 0.0),
+            quantity: Some(parse_decimal("quantity", &self.quantity)?),
+            filled_quantity: Some(parse_decimal("filled_quantity", &self.filled_quantity)?),
+            timestamp: DateTime::from_timestamp_millis(self.transaction_time)
+                .unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountPosition {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "B")]
+    balances: Vec<AccountBalance>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExchangeInfoResponse {
+    symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolInfo {
+    symbol: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    base_asset: String,
+    #[serde(default)]
+    quote_asset: String,
+    #[serde(default)]
+    filters: Vec<SymbolFilter>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker24h {
+    symbol: String,
+    quote_volume: String,
+}
+
+/// The symbol filters carrying trading rules. Spot reports the minimum order value in
+/// `NOTIONAL` (or the older `MIN_NOTIONAL`), futures in `MIN_NOTIONAL` under `notional`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "filterType")]
+enum SymbolFilter {
+    #[serde(rename = "PRICE_FILTER", rename_all = "camelCase")]
+    Price { tick_size: String },
+    #[serde(rename = "LOT_SIZE", rename_all = "camelCase")]
+    LotSize { min_qty: String, step_size: String },
+    #[serde(rename = "NOTIONAL", rename_all = "camelCase")]
+    Notional { min_notional: String },
+    #[serde(rename = "MIN_NOTIONAL")]
+    MinNotional {
+        #[serde(alias = "minNotional")]
+        notional: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountBalance {
+    #[serde(rename = "a")]
+    asset: String,
+    #[serde(rename = "f")]
+    free: String,
+    #[serde(rename = "l")]
+    locked: String,
+}
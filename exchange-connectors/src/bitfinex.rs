@@ -0,0 +1,464 @@
+//! Bitfinex Exchange Connector
+//! Streams raw price aggregated books from the v2 WebSocket API. Book data arrives on numeric
+//! channel ids assigned per connection, so subscription acks are tracked to map them back to pairs.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::capture::Capture;
+use crate::proxy::{connect_websocket, Proxy};
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::{OrderBookService, SymbolMapper};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, HealthEvent, PriceLevelUpdate, Result,
+    TradingPair,
+};
+
+const BITFINEX_WS_URL: &str = "wss://api-pub.bitfinex.com/ws/2";
+const BOOK_CHANNEL: &str = "book";
+/// Book lengths accepted by the `book` channel
+const BOOK_LENGTHS: [usize; 4] = [1, 25, 100, 250];
+/// Info code sent before the server restarts; clients must reconnect
+const RECONNECT_INFO_CODE: u64 = 20051;
+const RESET_CHANNELS: Vec<u8> = vec![];
+
+pub struct Bitfinex {
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+}
+
+/// Tracks the book channels of one connection and turns their messages into updates.
+///
+/// Each level is `[price, count, amount]`: a positive amount is a bid and a negative one an ask.
+/// A count of zero removes the level, with an amount of `1` for bids and `-1` for asks, and is
+/// emitted as a zero quantity.
+#[derive(Debug, Default)]
+pub struct BitfinexBookParser {
+    channels: HashMap<u64, String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BitfinexSubscription {
+    event: String,
+    channel: String,
+    symbol: String,
+    prec: String,
+    freq: String,
+    len: String,
+}
+
+impl BitfinexBookParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets every channel id, for use when the connection is replaced.
+    pub fn reset(&mut self) {
+        self.channels.clear();
+    }
+
+    /// Returns the pair subscribed on `channel_id`, if any.
+    pub fn symbol(&self, channel_id: u64) -> Option<&str> {
+        self.channels.get(&channel_id).map(String::as_str)
+    }
+
+    /// Handles one raw message. Book snapshots and updates yield a `PriceLevelUpdate`;
+    /// subscription acks are recorded and heartbeats, checksums and other events yield `None`.
+    pub fn handle_message(&mut self, message: &str) -> Result<Option<PriceLevelUpdate>> {
+        let value: Value = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("BitfinexMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        match value {
+            Value::Object(event) => {
+                self.handle_event(&event)?;
+                Ok(None)
+            }
+            Value::Array(data) => self.handle_channel_data(&data),
+            _ => Ok(None),
+        }
+    }
+
+    fn handle_event(&mut self, event: &serde_json::Map<String, Value>) -> Result<()> {
+        match event.get("event").and_then(Value::as_str) {
+            Some("subscribed")
+                if event.get("channel").and_then(Value::as_str) == Some(BOOK_CHANNEL) =>
+            {
+                let channel_id = event.get("chanId").and_then(Value::as_u64);
+                let symbol = event.get("symbol").and_then(Value::as_str);
+                match (channel_id, symbol) {
+                    (Some(channel_id), Some(symbol)) => {
+                        self.channels.insert(channel_id, symbol.to_string());
+                        Ok(())
+                    }
+                    _ => Err(AggregatorError::parsing(
+                        "BitfinexEvent",
+                        "Subscription ack is missing chanId or symbol",
+                    )),
+                }
+            }
+            Some("error") => Err(AggregatorError::exchange(
+                "bitfinex",
+                format!(
+                    "Subscription error {}: {}",
+                    event
+                        .get("code")
+                        .and_then(Value::as_u64)
+                        .unwrap_or_default(),
+                    event.get("msg").and_then(Value::as_str).unwrap_or_default()
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn handle_channel_data(&self, data: &[Value]) -> Result<Option<PriceLevelUpdate>> {
+        let symbol = match data
+            .first()
+            .and_then(Value::as_u64)
+            .and_then(|id| self.symbol(id))
+        {
+            Some(symbol) => symbol,
+            None => return Ok(None),
+        };
+
+        // A snapshot is a list of levels and an update a single level. Heartbeats and checksums
+        // carry a string tag in place of book data.
+        let levels: Vec<&Value> = match data.get(1) {
+            Some(Value::Array(levels)) if levels.first().is_some_and(Value::is_array) => {
+                levels.iter().collect()
+            }
+            Some(level @ Value::Array(_)) => vec![level],
+            _ => return Ok(None),
+        };
+
+        let timestamp = Utc::now();
+        let mut bids = Vec::new();
+        let mut asks = Vec::new();
+        for level in levels {
+            let (price, count, amount) = Self::parse_level(level)?;
+            let quantity = if count == 0.0 { 0.0 } else { amount.abs() };
+            if amount > 0.0 {
+                bids.push(Bid {
+                    price,
+                    quantity,
+                    exchange: Exchange::Bitfinex,
+                    timestamp,
+                });
+            } else {
+                asks.push(Ask {
+                    price,
+                    quantity,
+                    exchange: Exchange::Bitfinex,
+                    timestamp,
+                });
+            }
+        }
+
+        Ok(Some(PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            symbol: symbol.to_string(),
+            exchange: Exchange::Bitfinex,
+            bids,
+            asks,
+            timestamp,
+            funding: None,
+            event_time: None,
+            market_type: None,
+        }))
+    }
+
+    fn parse_level(level: &Value) -> Result<(f64, f64, f64)> {
+        let field = |index: usize, name: &str| {
+            level.get(index).and_then(Value::as_f64).ok_or_else(|| {
+                AggregatorError::parsing("PriceLevel", format!("Invalid {}: {}", name, level))
+            })
+        };
+        Ok((field(0, "price")?, field(1, "count")?, field(2, "amount")?))
+    }
+}
+
+impl Bitfinex {
+    pub fn new() -> Self {
+        Self {
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            proxy: None,
+            capture: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings. Bitfinex has no public sandbox, so
+    /// `sandbox: true` is rejected rather than silently streaming production data.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        if config.sandbox {
+            return Err(AggregatorError::validation(
+                "sandbox",
+                "Bitfinex does not provide a sandbox environment",
+            ));
+        }
+        let mut connector =
+            Self::new().with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        connector.proxy = Proxy::from_config(config)?;
+        Ok(connector)
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::Bitfinex, self.health_tx.clone())
+    }
+
+    /// Returns the smallest book length Bitfinex offers that covers `order_book_depth`.
+    pub fn book_length(order_book_depth: usize) -> usize {
+        BOOK_LENGTHS
+            .into_iter()
+            .find(|length| *length >= order_book_depth)
+            .unwrap_or(BOOK_LENGTHS[BOOK_LENGTHS.len() - 1])
+    }
+
+    fn is_reconnect_request(message: &str) -> bool {
+        serde_json::from_str::<Value>(message).is_ok_and(|value| {
+            value.get("event").and_then(Value::as_str) == Some("info")
+                && value.get("code").and_then(Value::as_u64) == Some(RECONNECT_INFO_CODE)
+        })
+    }
+
+    /// Spawn WebSocket stream for book channels
+    fn spawn_websocket_stream(
+        &self,
+        symbols: Vec<String>,
+        book_length: usize,
+        exchange_stream_buffer: usize,
+        mut shutdown: Shutdown,
+    ) -> (Receiver<Message>, JoinHandle<Result<()>>) {
+        let (ws_tx, ws_rx) = tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+        let proxy = self.proxy.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                let result = Self::connect_websocket(
+                    &symbols,
+                    book_length,
+                    proxy.as_ref(),
+                    &ws_tx,
+                    &mut backoff,
+                    &health,
+                    &mut shutdown,
+                )
+                .await;
+                if shutdown.is_triggered() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("Bitfinex WebSocket connection closed, reconnecting...");
+                        health.disconnected("WebSocket connection closed");
+                    }
+                    Err(e) => {
+                        error!("Bitfinex WebSocket connection error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+                if ws_tx.is_closed() {
+                    return Ok(());
+                }
+
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
+            }
+        });
+
+        (ws_rx, handle)
+    }
+
+    async fn connect_websocket(
+        symbols: &[String],
+        book_length: usize,
+        proxy: Option<&Proxy>,
+        ws_tx: &Sender<Message>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        shutdown: &mut Shutdown,
+    ) -> Result<()> {
+        let (mut ws_stream, _) = connect_websocket(BITFINEX_WS_URL, proxy).await?;
+
+        info!("Connected to Bitfinex WebSocket");
+        backoff.reset();
+        health.connected();
+
+        // Channel ids from the previous connection are no longer valid
+        if ws_tx.send(Message::Binary(RESET_CHANNELS)).await.is_err() {
+            return Ok(());
+        }
+
+        for symbol in symbols {
+            let subscription = BitfinexSubscription {
+                event: "subscribe".to_string(),
+                channel: BOOK_CHANNEL.to_string(),
+                symbol: symbol.clone(),
+                prec: "P0".to_string(),
+                freq: "F0".to_string(),
+                len: book_length.to_string(),
+            };
+            let subscription =
+                serde_json::to_string(&subscription).map_err(AggregatorError::Serialization)?;
+            ws_stream
+                .send(Message::Text(subscription))
+                .await
+                .map_err(|e| {
+                    AggregatorError::network(format!("Failed to send subscription: {}", e))
+                })?;
+        }
+
+        // Bitfinex sends a heartbeat on every idle channel every 15 seconds
+        let mut watchdog = backoff.policy().watchdog();
+        loop {
+            let msg = tokio::select! {
+                msg = ws_stream.next() => match msg {
+                    Some(msg) => msg,
+                    None => return Ok(()),
+                },
+                silent_for = watchdog.stalled() => return Err(health.stalled(silent_for)),
+                _ = shutdown.recv() => {
+                    info!("Closing Bitfinex order book stream");
+                    let _ = ws_stream.close(None).await;
+                    return Ok(());
+                }
+            };
+            watchdog.touch();
+
+            match msg {
+                Ok(Message::Text(text)) => {
+                    if Self::is_reconnect_request(&text) {
+                        info!("Bitfinex requested a reconnect");
+                        return Ok(());
+                    }
+                    if let Err(e) = ws_tx.send(Message::Text(text)).await {
+                        warn!("Bitfinex stream processor stopped: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Ping(payload)) => {
+                    if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                        error!("Failed to send pong: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Err(e) => {
+                    return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Spawn stream processor that maps channel data back to pairs
+    fn spawn_stream_processor(
+        mut ws_rx: Receiver<Message>,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        capture: Option<Capture>,
+    ) -> JoinHandle<Result<()>> {
+        tokio::spawn(async move {
+            let mut parser = BitfinexBookParser::new();
+
+            while let Some(message) = ws_rx.recv().await {
+                match message {
+                    Message::Text(text) => {
+                        if let Some(capture) = &capture {
+                            capture.record(&Exchange::Bitfinex, &text);
+                        }
+                        match parser.handle_message(&text) {
+                            Ok(Some(update)) => {
+                                if price_level_tx.send(update).await.is_err() {
+                                    return Ok(());
+                                }
+                            }
+                            Ok(None) => {}
+                            Err(e) => error!("Failed to process book message: {}", e),
+                        }
+                    }
+                    Message::Binary(data) if data.is_empty() => parser.reset(),
+                    _ => {}
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl OrderBookService for Bitfinex {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let symbols = SymbolMapper::new().to_exchange_all(&Exchange::Bitfinex, pairs)?;
+        info!(
+            "Starting Bitfinex order book service for {}",
+            symbols.join(", ")
+        );
+
+        let (ws_rx, ws_handle) = self.spawn_websocket_stream(
+            symbols,
+            Self::book_length(order_book_depth),
+            exchange_stream_buffer,
+            Shutdown::new(shutdown),
+        );
+        let processor_handle =
+            Self::spawn_stream_processor(ws_rx, price_level_tx, self.capture.clone());
+
+        Ok(vec![ws_handle, processor_handle])
+    }
+}
+
+impl Default for Bitfinex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,469 @@
+//! Bitget Exchange Connector
+//! Streams spot order books from Bitget's v2 public WebSocket `books` channels. The fixed-depth
+//! channels push a full snapshot of the top levels every time, so consecutive snapshots are diffed
+//! into deltas with removed levels reported at zero quantity.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::capture::Capture;
+use crate::proxy::{connect_websocket, Proxy};
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::{OrderBookService, SymbolMapper};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, HealthEvent, PriceLevelUpdate, Result,
+    TradingPair,
+};
+
+const BITGET_WS_URL: &str = "wss://ws.bitget.com/v2/ws/public";
+const BITGET_DEMO_WS_URL: &str = "wss://wspap.bitget.com/v2/ws/public";
+const SPOT_INST_TYPE: &str = "SPOT";
+/// Bitget answers a plain-text `ping` with `pong` and drops connections silent for two minutes
+const PING: &str = "ping";
+const PONG: &str = "pong";
+const PING_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(30);
+
+pub struct Bitget {
+    websocket_url: String,
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+}
+
+/// Turns `books1`/`books5`/`books15` snapshots into updates, keeping the previous snapshot per
+/// symbol so levels that disappeared are emitted with a zero quantity.
+#[derive(Debug, Default)]
+pub struct BitgetBookParser {
+    depth: Option<usize>,
+    previous: HashMap<String, PriceLevelUpdate>,
+}
+
+#[derive(Debug, Serialize)]
+struct BitgetRequest {
+    op: String,
+    args: Vec<BitgetChannel>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitgetChannel {
+    inst_type: String,
+    channel: String,
+    inst_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitgetPush {
+    arg: BitgetChannel,
+    data: Vec<BitgetBook>,
+}
+
+/// A book snapshot. Levels are `[price, size]` pairs, best first.
+#[derive(Debug, Deserialize)]
+struct BitgetBook {
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+    ts: String,
+}
+
+impl BitgetBookParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `depth` levels per side of each snapshot.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Handles one raw message. Book snapshots yield a `PriceLevelUpdate` against the previous
+    /// snapshot of the symbol; pongs and subscription events yield `None`.
+    pub fn handle_message(&mut self, message: &str) -> Result<Option<PriceLevelUpdate>> {
+        if message == PONG {
+            return Ok(None);
+        }
+
+        let value: Value = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("BitgetMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        if let Some(event) = value.get("event").and_then(Value::as_str) {
+            if event == "error" {
+                let message = value.get("msg").and_then(Value::as_str);
+                return Err(AggregatorError::exchange(
+                    "bitget",
+                    message.unwrap_or_default(),
+                ));
+            }
+            return Ok(None);
+        }
+        if value.get("data").is_none() {
+            return Ok(None);
+        }
+
+        let push: BitgetPush = serde_json::from_value(value).map_err(|e| {
+            AggregatorError::parsing("BitgetBook", format!("Failed to parse book: {}", e))
+        })?;
+        if !push.arg.channel.starts_with("books") {
+            return Ok(None);
+        }
+        let Some(book) = push.data.into_iter().last() else {
+            return Ok(None);
+        };
+
+        let depth = self.depth.unwrap_or(usize::MAX);
+        let timestamp = Utc::now();
+        let mut bids = Vec::new();
+        for level in book.bids.iter().take(depth) {
+            let (price, quantity) = Self::parse_level(level)?;
+            bids.push(Bid {
+                price,
+                quantity,
+                exchange: Exchange::Bitget,
+                timestamp,
+            });
+        }
+        let mut asks = Vec::new();
+        for level in book.asks.iter().take(depth) {
+            let (price, quantity) = Self::parse_level(level)?;
+            asks.push(Ask {
+                price,
+                quantity,
+                exchange: Exchange::Bitget,
+                timestamp,
+            });
+        }
+
+        let snapshot = PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            symbol: push.arg.inst_id,
+            exchange: Exchange::Bitget,
+            bids,
+            asks,
+            timestamp,
+            funding: None,
+            event_time: book
+                .ts
+                .parse()
+                .ok()
+                .and_then(DateTime::from_timestamp_millis),
+            market_type: None,
+        };
+
+        let mut update = snapshot.clone();
+        if let Some(previous) = self.previous.insert(snapshot.symbol.clone(), snapshot) {
+            update.mark_removed_levels(&previous);
+        }
+        Ok(Some(update))
+    }
+
+    fn parse_level(level: &[String; 2]) -> Result<(f64, f64)> {
+        let price = level[0]
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("PriceLevel", format!("Invalid price: {}", e)))?;
+        let quantity = level[1].parse::<f64>().map_err(|e| {
+            AggregatorError::parsing("PriceLevel", format!("Invalid quantity: {}", e))
+        })?;
+        Ok((price, quantity))
+    }
+}
+
+impl Bitget {
+    pub fn new() -> Self {
+        Self {
+            websocket_url: BITGET_WS_URL.to_string(),
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            proxy: None,
+            capture: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings, using Bitget demo trading when
+    /// `sandbox` is set.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        let mut connector = Self::new()
+            .with_sandbox(config.sandbox)
+            .with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        connector.proxy = Proxy::from_config(config)?;
+        Ok(connector)
+    }
+
+    /// Stream from Bitget demo trading instead of production.
+    pub fn with_sandbox(mut self, sandbox: bool) -> Self {
+        self.websocket_url = if sandbox {
+            BITGET_DEMO_WS_URL
+        } else {
+            BITGET_WS_URL
+        }
+        .to_string();
+        self
+    }
+
+    /// Stream from a custom endpoint, such as a local mock server.
+    pub fn with_websocket_url(mut self, websocket_url: &str) -> Self {
+        self.websocket_url = websocket_url.to_string();
+        self
+    }
+
+    pub fn websocket_url(&self) -> &str {
+        &self.websocket_url
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::Bitget, self.health_tx.clone())
+    }
+
+    /// The smallest fixed-depth book channel covering `depth` levels, capped at `books15`.
+    pub fn depth_channel(depth: usize) -> &'static str {
+        match depth {
+            0..=1 => "books1",
+            2..=5 => "books5",
+            _ => "books15",
+        }
+    }
+
+    /// Builds one subscription to the `channel` book of every symbol.
+    pub fn subscription(channel: &str, symbols: &[String]) -> Result<String> {
+        let request = BitgetRequest {
+            op: "subscribe".to_string(),
+            args: symbols
+                .iter()
+                .map(|symbol| BitgetChannel {
+                    inst_type: SPOT_INST_TYPE.to_string(),
+                    channel: channel.to_string(),
+                    inst_id: symbol.clone(),
+                })
+                .collect(),
+        };
+        serde_json::to_string(&request).map_err(AggregatorError::Serialization)
+    }
+
+    /// Spawn WebSocket stream for the book channels
+    fn spawn_websocket_stream(
+        &self,
+        subscription: String,
+        exchange_stream_buffer: usize,
+        mut shutdown: Shutdown,
+    ) -> (Receiver<Message>, JoinHandle<Result<()>>) {
+        let (ws_tx, ws_rx) = tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
+        let url = self.websocket_url.clone();
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+        let proxy = self.proxy.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                let result = Self::connect_websocket(
+                    &url,
+                    &subscription,
+                    proxy.as_ref(),
+                    &ws_tx,
+                    &mut backoff,
+                    &health,
+                    &mut shutdown,
+                )
+                .await;
+                if shutdown.is_triggered() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("Bitget WebSocket connection closed, reconnecting...");
+                        health.disconnected("WebSocket connection closed");
+                    }
+                    Err(e) => {
+                        error!("Bitget WebSocket connection error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+                if ws_tx.is_closed() {
+                    return Ok(());
+                }
+
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
+            }
+        });
+
+        (ws_rx, handle)
+    }
+
+    async fn connect_websocket(
+        url: &str,
+        subscription: &str,
+        proxy: Option<&Proxy>,
+        ws_tx: &Sender<Message>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        shutdown: &mut Shutdown,
+    ) -> Result<()> {
+        let (mut ws_stream, _) = connect_websocket(url, proxy).await?;
+
+        info!("Connected to Bitget WebSocket");
+        backoff.reset();
+        health.connected();
+
+        ws_stream
+            .send(Message::Text(subscription.to_string()))
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to send subscription: {}", e)))?;
+
+        let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+        ping_interval.tick().await;
+
+        let mut watchdog = backoff.policy().watchdog();
+        loop {
+            let msg = tokio::select! {
+                msg = ws_stream.next() => match msg {
+                    Some(msg) => msg,
+                    None => return Ok(()),
+                },
+                // Pings keep quiet books alive and their pongs feed the watchdog
+                _ = ping_interval.tick() => {
+                    if let Err(e) = ws_stream.send(Message::Text(PING.to_string())).await {
+                        error!("Failed to send ping: {}", e);
+                        return Ok(());
+                    }
+                    continue;
+                }
+                silent_for = watchdog.stalled() => return Err(health.stalled(silent_for)),
+                _ = shutdown.recv() => {
+                    info!("Closing Bitget order book stream");
+                    let _ = ws_stream.close(None).await;
+                    return Ok(());
+                }
+            };
+            watchdog.touch();
+
+            match msg {
+                Ok(Message::Text(text)) => {
+                    if let Err(e) = ws_tx.send(Message::Text(text)).await {
+                        warn!("Bitget stream processor stopped: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Ping(payload)) => {
+                    if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                        error!("Failed to send pong: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Err(e) => {
+                    return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Spawn stream processor that diffs snapshots into updates
+    fn spawn_stream_processor(
+        mut ws_rx: Receiver<Message>,
+        order_book_depth: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        capture: Option<Capture>,
+    ) -> JoinHandle<Result<()>> {
+        tokio::spawn(async move {
+            let mut parser = BitgetBookParser::new().with_depth(order_book_depth);
+
+            while let Some(message) = ws_rx.recv().await {
+                if let Message::Text(text) = message {
+                    if let Some(capture) = &capture {
+                        capture.record(&Exchange::Bitget, &text);
+                    }
+                    match parser.handle_message(&text) {
+                        Ok(Some(update)) => {
+                            if price_level_tx.send(update).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => error!("Failed to process book message: {}", e),
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl OrderBookService for Bitget {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let symbols = SymbolMapper::new().to_exchange_all(&Exchange::Bitget, pairs)?;
+        info!(
+            "Starting Bitget order book service for {}",
+            symbols.join(", ")
+        );
+
+        let subscription = Self::subscription(Self::depth_channel(order_book_depth), &symbols)?;
+        let (ws_rx, ws_handle) = self.spawn_websocket_stream(
+            subscription,
+            exchange_stream_buffer,
+            Shutdown::new(shutdown),
+        );
+        let processor_handle = Self::spawn_stream_processor(
+            ws_rx,
+            order_book_depth,
+            price_level_tx,
+            self.capture.clone(),
+        );
+
+        Ok(vec![ws_handle, processor_handle])
+    }
+}
+
+impl Default for Bitget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
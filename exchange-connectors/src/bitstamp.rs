@@ -1,16 +1,162 @@
-//! Bitstamp Exchange Connector (placeholder)
+//! Bitstamp Exchange Connector
+//! Order book streaming is a placeholder; trades are streamed from the `live_trades` channel
 
-use crate::OrderBookService;
-use aggregator_core::{PriceLevelUpdate, Result};
+use crate::capture::Capture;
+use crate::proxy::Proxy;
+use crate::reconnect::{HealthReporter, ReconnectPolicy};
+use crate::trade_stream::{spawn_trade_stream, TradeStreamSpec};
+use crate::{OrderBookService, SymbolMapper, TradeStreamService};
+use aggregator_core::{
+    AggregatorError, Exchange, ExchangeConfig, HealthEvent, PriceLevelUpdate, Result, Trade,
+    TradeSide, TradingPair,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::Sender;
 use tokio::task::JoinHandle;
+use tracing::info;
+use uuid::Uuid;
 
-pub struct Bitstamp;
+const BITSTAMP_WS_URL: &str = "wss://ws.bitstamp.net";
+const LIVE_TRADES_CHANNEL_PREFIX: &str = "live_trades_";
+
+pub struct Bitstamp {
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+}
+
+#[derive(Debug, Serialize)]
+struct BitstampSubscription {
+    event: String,
+    data: BitstampChannel,
+}
+
+#[derive(Debug, Serialize)]
+struct BitstampChannel {
+    channel: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitstampMessage {
+    event: String,
+    channel: String,
+    data: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitstampTrade {
+    id: u64,
+    price_str: String,
+    amount_str: String,
+    #[serde(rename = "type")]
+    trade_type: u8,
+    microtimestamp: String,
+}
 
 impl Bitstamp {
     pub fn new() -> Self {
-        Bitstamp
+        Self {
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            proxy: None,
+            capture: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings. Bitstamp has no public sandbox, so
+    /// `sandbox: true` is rejected rather than silently streaming production data.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        if config.sandbox {
+            return Err(AggregatorError::validation(
+                "sandbox",
+                "Bitstamp does not provide a sandbox environment",
+            ));
+        }
+        let mut connector =
+            Self::new().with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        connector.proxy = Proxy::from_config(config)?;
+        Ok(connector)
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::Bitstamp, self.health_tx.clone())
+    }
+
+    /// Parse a raw `live_trades` channel message into trades. Other events yield no trades.
+    pub fn parse_trades(message: &str) -> Result<Vec<Trade>> {
+        let msg: BitstampMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("BitstampMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        if msg.event != "trade" || !msg.channel.starts_with(LIVE_TRADES_CHANNEL_PREFIX) {
+            return Ok(vec![]);
+        }
+
+        let trade: BitstampTrade = serde_json::from_value(msg.data).map_err(|e| {
+            AggregatorError::parsing("BitstampTrade", format!("Failed to parse trade: {}", e))
+        })?;
+
+        let price = trade
+            .price_str
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("Trade", format!("Invalid price: {}", e)))?;
+        let quantity = trade
+            .amount_str
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("Trade", format!("Invalid quantity: {}", e)))?;
+        let micros = trade
+            .microtimestamp
+            .parse::<i64>()
+            .map_err(|e| AggregatorError::parsing("Trade", format!("Invalid timestamp: {}", e)))?;
+        let side = match trade.trade_type {
+            0 => TradeSide::Buy,
+            1 => TradeSide::Sell,
+            other => {
+                return Err(AggregatorError::parsing(
+                    "Trade",
+                    format!("Invalid trade type: {}", other),
+                ))
+            }
+        };
+
+        Ok(vec![Trade {
+            id: Uuid::new_v4(),
+            trade_id: trade.id.to_string(),
+            symbol: msg.channel[LIVE_TRADES_CHANNEL_PREFIX.len()..].to_uppercase(),
+            exchange: Exchange::Bitstamp,
+            price,
+            quantity,
+            side,
+            timestamp: DateTime::from_timestamp_micros(micros).unwrap_or_else(Utc::now),
+        }])
     }
 }
 
@@ -18,16 +164,61 @@ impl Bitstamp {
 impl OrderBookService for Bitstamp {
     async fn spawn_order_book_service(
         &self,
-        _pair: [&str; 2],
+        _pairs: &[TradingPair],
         _order_book_depth: usize,
         _exchange_stream_buffer: usize,
         _price_level_tx: Sender<PriceLevelUpdate>,
+        _shutdown: broadcast::Receiver<()>,
     ) -> Result<Vec<JoinHandle<Result<()>>>> {
         // TODO: Implement Bitstamp WebSocket connection
         Ok(vec![])
     }
 }
 
+#[async_trait]
+impl TradeStreamService for Bitstamp {
+    async fn spawn_trade_service(
+        &self,
+        pairs: &[TradingPair],
+        trade_tx: Sender<Trade>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let channels: Vec<String> = SymbolMapper::new()
+            .to_exchange_all(&Exchange::Bitstamp, pairs)?
+            .into_iter()
+            .map(|symbol| format!("{}{}", LIVE_TRADES_CHANNEL_PREFIX, symbol))
+            .collect();
+        info!(
+            "Starting Bitstamp trade service for {}",
+            channels.join(", ")
+        );
+
+        // Bitstamp takes one channel per subscribe message
+        let subscriptions = channels
+            .into_iter()
+            .map(|channel| {
+                serde_json::to_string(&BitstampSubscription {
+                    event: "bts:subscribe".to_string(),
+                    data: BitstampChannel { channel },
+                })
+                .map_err(AggregatorError::Serialization)
+            })
+            .collect::<Result<Vec<String>>>()?;
+
+        let spec = TradeStreamSpec {
+            exchange: "bitstamp",
+            url: BITSTAMP_WS_URL.to_string(),
+            subscriptions,
+            ping: None,
+            reconnect_policy: self.reconnect_policy.clone(),
+            health: self.health_reporter(),
+            proxy: self.proxy.clone(),
+            capture: self.capture.clone(),
+        };
+
+        Ok(vec![spawn_trade_stream(spec, Self::parse_trades, trade_tx)])
+    }
+}
+
 impl Default for Bitstamp {
     fn default() -> Self {
         Self::new()
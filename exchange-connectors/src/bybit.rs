@@ -2,9 +2,11 @@
 //! Handles WebSocket connections and order book streaming for Bybit
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::task::JoinHandle;
 use tokio_tungstenite::tungstenite::Message;
@@ -12,14 +14,35 @@ use tracing::{error, info, warn};
 use url::Url;
 use uuid::Uuid;
 
-use crate::OrderBookService;
-use aggregator_core::{AggregatorError, Ask, Bid, Exchange, PriceLevelUpdate, Result};
+use crate::capture::Capture;
+use crate::exchange_info::{decimal, ensure_complete, get_body, parse_body, requested_symbols};
+use crate::proxy::{connect_websocket, http_client, Proxy};
+use crate::rate_limit::RateLimiter;
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::trade_stream::{spawn_trade_stream, TradeStreamSpec};
+use crate::{ExchangeInfoService, OrderBookService, SymbolMapper, TradeStreamService};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, FundingRate, HealthEvent, InstrumentInfo,
+    MarketDiscovery, MarketListing, MarketType, PriceLevelUpdate, RateLimitConfig, Result, Trade,
+    TradeSide, TradingPair,
+};
 
 const BYBIT_WS_URL: &str = "wss://stream.bybit.com/v5/public/linear";
 const BYBIT_REST_URL: &str = "https://api.bybit.com/v5/market/orderbook";
+const BYBIT_TESTNET_WS_URL: &str = "wss://stream-testnet.bybit.com/v5/public/linear";
+const BYBIT_TESTNET_REST_URL: &str = "https://api-testnet.bybit.com/v5/market/orderbook";
+/// Most instruments Bybit returns per `instruments-info` page
+const INSTRUMENTS_PAGE_LIMIT: usize = 1000;
+/// Status of instruments open for trading
+const TRADING_STATUS: &str = "Trading";
 
 pub struct Bybit {
     pub config: BybitConfig,
+    market_types: Vec<MarketType>,
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    rate_limiter: RateLimiter,
+    capture: Option<Capture>,
 }
 
 #[derive(Debug, Clone)]
@@ -28,6 +51,8 @@ pub struct BybitConfig {
     pub rest_url: String,
     pub reconnect_interval: u64,
     pub ping_interval: u64,
+    /// Proxy for WebSocket and REST connections, if any.
+    pub proxy: Option<Proxy>,
 }
 
 impl Default for BybitConfig {
@@ -37,6 +62,18 @@ impl Default for BybitConfig {
             rest_url: BYBIT_REST_URL.to_string(),
             reconnect_interval: 5000,
             ping_interval: 20000,
+            proxy: None,
+        }
+    }
+}
+
+impl BybitConfig {
+    /// Settings for the Bybit testnet.
+    pub fn testnet() -> Self {
+        Self {
+            websocket_url: BYBIT_TESTNET_WS_URL.to_string(),
+            rest_url: BYBIT_TESTNET_REST_URL.to_string(),
+            ..Self::default()
         }
     }
 }
@@ -70,6 +107,38 @@ struct BybitDepthData {
     seq: u64,
 }
 
+#[derive(Debug, Deserialize)]
+struct BybitTickerMessage {
+    data: BybitTickerData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitTickerData {
+    symbol: String,
+    mark_price: Option<String>,
+    funding_rate: Option<String>,
+    next_funding_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BybitTradeMessage {
+    topic: String,
+    data: Vec<BybitTradeData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BybitTradeData {
+    #[serde(rename = "T")]
+    trade_time: i64,
+    s: String,
+    #[serde(rename = "S")]
+    side: String,
+    v: String,
+    p: String,
+    i: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct BybitSnapshotResponse {
     #[serde(rename = "retCode")]
@@ -79,6 +148,80 @@ struct BybitSnapshotResponse {
     result: BybitSnapshotResult,
 }
 
+#[derive(Debug, Deserialize)]
+struct BybitInstrumentsResponse {
+    #[serde(rename = "retCode")]
+    ret_code: i32,
+    #[serde(rename = "retMsg")]
+    ret_msg: String,
+    result: Option<BybitInstrumentsResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BybitInstrumentsResult {
+    list: Vec<BybitInstrument>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitInstrument {
+    symbol: String,
+    price_filter: BybitPriceFilter,
+    lot_size_filter: BybitLotSizeFilter,
+}
+
+/// A page of a listing endpoint, `nextPageCursor` being empty on the last page
+#[derive(Debug, Deserialize)]
+struct BybitListResponse<T> {
+    #[serde(rename = "retCode")]
+    ret_code: i32,
+    #[serde(rename = "retMsg")]
+    ret_msg: String,
+    result: Option<BybitList<T>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitList<T> {
+    list: Vec<T>,
+    #[serde(default)]
+    next_page_cursor: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitMarket {
+    symbol: String,
+    base_coin: String,
+    quote_coin: String,
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BybitTicker24h {
+    symbol: String,
+    #[serde(rename = "turnover24h")]
+    turnover_24h: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitPriceFilter {
+    tick_size: String,
+}
+
+/// Spot reports the quantity step as `basePrecision` and the minimum order value as
+/// `minOrderAmt`; linear contracts use `qtyStep` and `minNotionalValue`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitLotSizeFilter {
+    #[serde(alias = "qtyStep")]
+    base_precision: String,
+    min_order_qty: String,
+    #[serde(alias = "minNotionalValue")]
+    min_order_amt: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct BybitSnapshotResult {
     s: String,
@@ -90,17 +233,246 @@ struct BybitSnapshotResult {
 
 impl Bybit {
     pub fn new() -> Self {
+        Self::with_config(BybitConfig::default())
+    }
+
+    pub fn with_config(config: BybitConfig) -> Self {
+        let reconnect_policy = ReconnectPolicy::default().with_initial_delay(
+            tokio::time::Duration::from_millis(config.reconnect_interval),
+        );
         Self {
-            config: BybitConfig::default(),
+            config,
+            // The default feed is the linear perpetual stream
+            market_types: vec![MarketType::Futures],
+            reconnect_policy,
+            health_tx: None,
+            rate_limiter: RateLimiter::new("bybit", &RateLimitConfig::default()),
+            capture: None,
         }
     }
 
-    pub fn with_config(config: BybitConfig) -> Self {
-        Self { config }
+    /// Builds a connector from the shared exchange settings, using the Bybit testnet when
+    /// `sandbox` is set.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        let bybit_config = if config.sandbox {
+            BybitConfig::testnet()
+        } else {
+            BybitConfig::default()
+        };
+
+        Ok(Self::with_config(BybitConfig {
+            reconnect_interval: config.websocket.reconnect_interval,
+            proxy: Proxy::from_config(config)?,
+            ..bybit_config
+        })
+        .with_rate_limit(&config.rate_limit)
+        .with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket)))
+    }
+
+    /// Select the market order books are streamed from: `Spot` or linear perpetual `Futures`.
+    /// Switches the configured WebSocket URL to the matching category.
+    pub fn with_market_type(mut self, market_type: MarketType) -> Self {
+        if let Some(url) = self.websocket_url(&market_type) {
+            self.config.websocket_url = url;
+        }
+        self.market_types = vec![market_type];
+        self
+    }
+
+    /// Stream order books from several markets at once, e.g. `Spot` and `Futures` for the same
+    /// symbols. Each market gets its own connection and its updates are tagged with the market
+    /// type so their books stay apart. The first market is used for trades and instrument
+    /// metadata.
+    pub fn with_market_types(mut self, market_types: &[MarketType]) -> Self {
+        let mut unique: Vec<MarketType> = Vec::new();
+        for market_type in market_types {
+            if !unique.contains(market_type) {
+                unique.push(market_type.clone());
+            }
+        }
+        match unique.first() {
+            Some(first) => {
+                self = self.with_market_type(first.clone());
+                self.market_types = unique;
+            }
+            None => self.market_types = unique,
+        }
+        self
+    }
+
+    /// The markets order books are streamed from.
+    pub fn market_types(&self) -> &[MarketType] {
+        &self.market_types
+    }
+
+    /// The configured WebSocket URL switched to `market_type`'s category, or `None` for
+    /// unsupported markets.
+    pub fn websocket_url(&self, market_type: &MarketType) -> Option<String> {
+        let category = Self::category(market_type).ok()?;
+        let (base, _) = self.config.websocket_url.rsplit_once('/')?;
+        Some(format!("{}/{}", base, category))
+    }
+
+    fn primary_market_type(&self) -> Result<&MarketType> {
+        self.market_types
+            .first()
+            .ok_or_else(|| AggregatorError::validation("market_type", "No market selected"))
+    }
+
+    fn category(market_type: &MarketType) -> Result<&'static str> {
+        match market_type {
+            MarketType::Spot => Ok("spot"),
+            MarketType::Futures => Ok("linear"),
+            MarketType::Options => Err(AggregatorError::validation(
+                "market_type",
+                "Bybit order books support Spot and Futures markets",
+            )),
+        }
+    }
+
+    /// Limit REST snapshot requests according to `config`.
+    pub fn with_rate_limit(mut self, config: &RateLimitConfig) -> Self {
+        self.rate_limiter = RateLimiter::new("bybit", config);
+        self
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket and REST traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::Bybit, self.health_tx.clone())
+    }
+
+    fn format_symbols(&self, pairs: &[TradingPair]) -> Result<Vec<String>> {
+        SymbolMapper::new().to_exchange_all(&Exchange::Bybit, pairs)
+    }
+
+    /// Merge a raw `tickers` topic message into the latest funding information per symbol.
+    /// Deltas only carry changed fields. Returns `false` for other messages.
+    pub fn update_funding_rates(
+        message: &str,
+        funding: &mut HashMap<String, FundingRate>,
+    ) -> Result<bool> {
+        if !message.contains("tickers.") {
+            return Ok(false);
+        }
+
+        let ticker: BybitTickerMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing(
+                "BybitTickerMessage",
+                format!("Failed to parse ticker: {}", e),
+            )
+        })?;
+        let data = ticker.data;
+
+        let parse = |field: &str, value: Option<String>| -> Result<Option<f64>> {
+            value
+                .filter(|value| !value.is_empty())
+                .map(|value| {
+                    value.parse::<f64>().map_err(|e| {
+                        AggregatorError::parsing("FundingRate", format!("Invalid {}: {}", field, e))
+                    })
+                })
+                .transpose()
+        };
+        let rate = parse("funding rate", data.funding_rate)?;
+        let mark_price = parse("mark price", data.mark_price)?;
+        let next_funding_time = parse("next funding time", data.next_funding_time)?
+            .and_then(|millis| DateTime::from_timestamp_millis(millis as i64));
+
+        match funding.get_mut(&data.symbol) {
+            Some(current) => {
+                current.rate = rate.unwrap_or(current.rate);
+                current.mark_price = mark_price.or(current.mark_price);
+                current.next_funding_time = next_funding_time.or(current.next_funding_time);
+            }
+            // A delta cannot start a record until the funding rate is known
+            None => {
+                if let Some(rate) = rate {
+                    funding.insert(
+                        data.symbol,
+                        FundingRate {
+                            rate,
+                            mark_price,
+                            next_funding_time,
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(true)
     }
 
-    fn format_symbol(&self, pair: [&str; 2]) -> String {
-        format!("{}{}", pair[0].to_uppercase(), pair[1].to_uppercase())
+    /// Parse a raw `publicTrade` topic message into trades. Other messages yield no trades.
+    pub fn parse_trades(message: &str) -> Result<Vec<Trade>> {
+        if !message.contains("publicTrade.") {
+            return Ok(vec![]);
+        }
+
+        let trade_msg: BybitTradeMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("BybitTradeMessage", format!("Failed to parse trade: {}", e))
+        })?;
+
+        if !trade_msg.topic.starts_with("publicTrade.") {
+            return Ok(vec![]);
+        }
+
+        trade_msg
+            .data
+            .into_iter()
+            .map(|data| {
+                let price = data.p.parse::<f64>().map_err(|e| {
+                    AggregatorError::parsing("Trade", format!("Invalid price: {}", e))
+                })?;
+                let quantity = data.v.parse::<f64>().map_err(|e| {
+                    AggregatorError::parsing("Trade", format!("Invalid quantity: {}", e))
+                })?;
+                let side = match data.side.as_str() {
+                    "Buy" => TradeSide::Buy,
+                    "Sell" => TradeSide::Sell,
+                    other => {
+                        return Err(AggregatorError::parsing(
+                            "Trade",
+                            format!("Invalid side: {}", other),
+                        ))
+                    }
+                };
+
+                Ok(Trade {
+                    id: Uuid::new_v4(),
+                    trade_id: data.i,
+                    symbol: data.s,
+                    exchange: Exchange::Bybit,
+                    price,
+                    quantity,
+                    side,
+                    timestamp: DateTime::from_timestamp_millis(data.trade_time)
+                        .unwrap_or_else(Utc::now),
+                })
+            })
+            .collect()
     }
 
     async fn get_orderbook_snapshot(
@@ -108,41 +480,184 @@ impl Bybit {
         symbol: &str,
         depth: usize,
     ) -> Result<BybitSnapshotResult> {
+        self.rate_limiter.try_acquire()?;
+
         let url = format!("{}?symbol={}&limit={}", self.config.rest_url, symbol, depth);
 
         let response = reqwest::get(&url)
             .await
-            .map_err(|e| AggregatorError::Network(format!("Failed to get snapshot: {}", e)))?;
+            .map_err(|e| AggregatorError::network(format!("Failed to get snapshot: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(AggregatorError::rate_limit(
+                "bybit",
+                "Snapshot request rejected with HTTP 429",
+            ));
+        }
 
         if !response.status().is_success() {
-            return Err(AggregatorError::Network(format!(
+            return Err(AggregatorError::network(format!(
                 "HTTP error: {}",
                 response.status()
             )));
         }
 
-        let snapshot: BybitSnapshotResponse = response
-            .json()
-            .await
-            .map_err(|e| AggregatorError::Parsing(format!("Failed to parse snapshot: {}", e)))?;
+        let snapshot: BybitSnapshotResponse = response.json().await.map_err(|e| {
+            AggregatorError::parsing(
+                "BybitSnapshotResponse",
+                format!("Failed to parse snapshot: {}", e),
+            )
+        })?;
 
         if snapshot.ret_code != 0 {
-            return Err(AggregatorError::Exchange(format!(
-                "Bybit API error: {}",
-                snapshot.ret_msg
-            )));
+            return Err(AggregatorError::exchange(
+                "bybit",
+                format!("Bybit API error: {}", snapshot.ret_msg),
+            ));
         }
 
         Ok(snapshot.result)
     }
 
+    /// Builds the `instruments-info` URL for `symbol` in the selected market's category.
+    pub fn instruments_url(&self, symbol: &str) -> Result<String> {
+        let category = Self::category(self.primary_market_type()?)?;
+        Ok(format!(
+            "{}/instruments-info?category={}&symbol={}",
+            self.market_base_url(),
+            category,
+            symbol
+        ))
+    }
+
+    /// Builds the `instruments-info` URL listing a page of every instrument in the selected
+    /// market's category, starting from `cursor` when given.
+    pub fn markets_url(&self, cursor: Option<&str>) -> Result<String> {
+        let category = Self::category(self.primary_market_type()?)?;
+        let mut url = format!(
+            "{}/instruments-info?category={}&limit={}",
+            self.market_base_url(),
+            category,
+            INSTRUMENTS_PAGE_LIMIT
+        );
+        // Cursors come URL encoded already
+        if let Some(cursor) = cursor.filter(|cursor| !cursor.is_empty()) {
+            url.push_str("&cursor=");
+            url.push_str(cursor);
+        }
+        Ok(url)
+    }
+
+    /// Builds the `tickers` URL covering every instrument in the selected market's category.
+    pub fn tickers_url(&self) -> Result<String> {
+        let category = Self::category(self.primary_market_type()?)?;
+        Ok(format!(
+            "{}/tickers?category={}",
+            self.market_base_url(),
+            category
+        ))
+    }
+
+    fn market_base_url(&self) -> &str {
+        self.config
+            .rest_url
+            .rsplit_once('/')
+            .map_or(self.config.rest_url.as_str(), |(base, _)| base)
+    }
+
+    /// Parse a page of an `instruments-info` listing into the instruments open for trading,
+    /// without volumes, and the cursor of the next page if there is one.
+    pub fn parse_markets(body: &str) -> Result<(Vec<MarketListing>, Option<String>)> {
+        let page = Self::parse_list::<BybitMarket>(body, "BybitMarketsResponse")?;
+        let markets = page
+            .list
+            .into_iter()
+            .filter(|market| market.status == TRADING_STATUS)
+            .map(|market| MarketListing {
+                exchange: Exchange::Bybit,
+                pair: TradingPair::new(&market.base_coin, &market.quote_coin),
+                symbol: market.symbol,
+                quote_volume: None,
+            })
+            .collect();
+        let cursor = Some(page.next_page_cursor).filter(|cursor| !cursor.is_empty());
+        Ok((markets, cursor))
+    }
+
+    /// Parse a `tickers` response into each symbol's 24 hour turnover, in quote currency.
+    pub fn parse_quote_volumes(body: &str) -> Result<HashMap<String, f64>> {
+        let tickers = Self::parse_list::<BybitTicker24h>(body, "BybitTickersResponse")?;
+        Ok(tickers
+            .list
+            .into_iter()
+            .filter_map(|ticker| Some((ticker.symbol, ticker.turnover_24h.parse().ok()?)))
+            .collect())
+    }
+
+    fn parse_list<T: serde::de::DeserializeOwned>(
+        body: &str,
+        data_type: &str,
+    ) -> Result<BybitList<T>> {
+        let response: BybitListResponse<T> = parse_body(body, data_type)?;
+        if response.ret_code != 0 {
+            return Err(AggregatorError::exchange(
+                "bybit",
+                format!("Bybit API error: {}", response.ret_msg),
+            ));
+        }
+        Ok(response.result.unwrap_or(BybitList {
+            list: Vec::new(),
+            next_page_cursor: String::new(),
+        }))
+    }
+
+    /// Parse an `instruments-info` response, spot or linear, into metadata for `pairs`.
+    pub fn parse_instruments(body: &str, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let requested = requested_symbols(&Exchange::Bybit, pairs)?;
+        let response: BybitInstrumentsResponse = parse_body(body, "BybitInstrumentsResponse")?;
+        if response.ret_code != 0 {
+            return Err(AggregatorError::exchange(
+                "bybit",
+                format!("Bybit API error: {}", response.ret_msg),
+            ));
+        }
+
+        let mut instruments = Vec::new();
+        for instrument in response
+            .result
+            .map(|result| result.list)
+            .unwrap_or_default()
+        {
+            let Some(pair) = requested.get(&instrument.symbol) else {
+                continue;
+            };
+            let lot_size = &instrument.lot_size_filter;
+            instruments.push(InstrumentInfo {
+                exchange: Exchange::Bybit,
+                pair: pair.clone(),
+                tick_size: decimal(&instrument.price_filter.tick_size, "tickSize")?,
+                lot_size: decimal(&lot_size.base_precision, "basePrecision")?,
+                min_quantity: decimal(&lot_size.min_order_qty, "minOrderQty")?,
+                min_notional: lot_size
+                    .min_order_amt
+                    .as_deref()
+                    .map(|amount| decimal(amount, "minOrderAmt"))
+                    .transpose()?,
+                symbol: instrument.symbol,
+            });
+        }
+
+        ensure_complete(&Exchange::Bybit, &requested, &instruments)?;
+        Ok(instruments)
+    }
+
     fn parse_price_level(&self, level: &[String; 2]) -> Result<(f64, f64)> {
         let price = level[0]
             .parse::<f64>()
-            .map_err(|e| AggregatorError::Parsing(format!("Invalid price: {}", e)))?;
-        let quantity = level[1]
-            .parse::<f64>()
-            .map_err(|e| AggregatorError::Parsing(format!("Invalid quantity: {}", e)))?;
+            .map_err(|e| AggregatorError::parsing("PriceLevel", format!("Invalid price: {}", e)))?;
+        let quantity = level[1].parse::<f64>().map_err(|e| {
+            AggregatorError::parsing("PriceLevel", format!("Invalid quantity: {}", e))
+        })?;
         Ok((price, quantity))
     }
 
@@ -185,32 +700,54 @@ impl Bybit {
             bids,
             asks,
             timestamp: Utc::now(),
+            funding: None,
+            event_time: None,
+            market_type: None,
         })
     }
 
     async fn spawn_websocket_stream(
         &self,
-        symbol: String,
+        config: BybitConfig,
+        topics: Vec<String>,
         exchange_stream_buffer: usize,
+        mut shutdown: Shutdown,
     ) -> Result<(Receiver<Message>, JoinHandle<Result<()>>)> {
         let (ws_tx, ws_rx) = tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
-        let config = self.config.clone();
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
 
         let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
             loop {
-                match Self::connect_websocket(&config, &symbol, ws_tx.clone()).await {
+                let result = Self::connect_websocket(
+                    &config,
+                    &topics,
+                    ws_tx.clone(),
+                    &mut backoff,
+                    &health,
+                    &mut shutdown,
+                )
+                .await;
+                if shutdown.is_triggered() {
+                    return Ok(());
+                }
+
+                match result {
                     Ok(_) => {
                         warn!("WebSocket connection closed, reconnecting...");
+                        health.disconnected("WebSocket connection closed");
                     }
                     Err(e) => {
                         error!("WebSocket connection error: {}", e);
+                        health.disconnected(e.to_string());
                     }
                 }
 
-                tokio::time::sleep(tokio::time::Duration::from_millis(
-                    config.reconnect_interval,
-                ))
-                .await;
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
             }
         });
 
@@ -219,22 +756,25 @@ impl Bybit {
 
     async fn connect_websocket(
         config: &BybitConfig,
-        symbol: &str,
+        topics: &[String],
         ws_tx: Sender<Message>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        shutdown: &mut Shutdown,
     ) -> Result<()> {
         let url = Url::parse(&config.websocket_url)
-            .map_err(|e| AggregatorError::Parsing(format!("Invalid URL: {}", e)))?;
+            .map_err(|e| AggregatorError::parsing("Url", format!("Invalid URL: {}", e)))?;
 
-        let (mut ws_stream, _) = tokio_tungstenite::connect_async(url)
-            .await
-            .map_err(|e| AggregatorError::Network(format!("WebSocket connection failed: {}", e)))?;
+        let (mut ws_stream, _) = connect_websocket(url.as_str(), config.proxy.as_ref()).await?;
 
         info!("Connected to Bybit WebSocket");
+        backoff.reset();
+        health.connected();
 
-        // Subscribe to orderbook updates
+        // Subscribe to every topic for every symbol on this connection
         let subscription = BybitSubscription {
             op: "subscribe".to_string(),
-            args: vec![format!("orderbook.50.{}", symbol)],
+            args: topics.to_vec(),
         };
 
         let subscription_msg =
@@ -243,48 +783,60 @@ impl Bybit {
         ws_stream
             .send(Message::Text(subscription_msg))
             .await
-            .map_err(|e| AggregatorError::Network(format!("Failed to send subscription: {}", e)))?;
+            .map_err(|e| AggregatorError::network(format!("Failed to send subscription: {}", e)))?;
 
-        let mut last_ping = std::time::Instant::now();
+        let mut watchdog = backoff.policy().watchdog();
+        let mut ping_interval =
+            tokio::time::interval(std::time::Duration::from_millis(config.ping_interval));
+        ping_interval.tick().await;
 
-        while let Some(msg) = ws_stream.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    if let Err(e) = ws_tx.send(Message::Text(text)).await {
-                        error!("Failed to send message: {}", e);
-                        break;
+        loop {
+            tokio::select! {
+                msg = ws_stream.next() => {
+                    watchdog.touch();
+                    match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            if let Err(e) = ws_tx.send(Message::Text(text)).await {
+                                error!("Failed to send message: {}", e);
+                                break;
+                            }
+                        }
+                        Some(Ok(Message::Ping(_))) => {
+                            if let Err(e) = ws_stream.send(Message::Pong(vec![])).await {
+                                error!("Failed to send pong: {}", e);
+                                break;
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("WebSocket connection closed");
+                            break;
+                        }
+                        Some(Err(e)) => {
+                            error!("WebSocket error: {}", e);
+                            break;
+                        }
+                        _ => {}
                     }
                 }
-                Ok(Message::Ping(_)) => {
-                    if let Err(e) = ws_stream.send(Message::Pong(vec![])).await {
-                        error!("Failed to send pong: {}", e);
+                // Periodic pings keep quiet books alive and their replies feed the watchdog
+                _ = ping_interval.tick() => {
+                    let ping = BybitPong {
+                        op: "ping".to_string(),
+                    };
+                    let ping_msg =
+                        serde_json::to_string(&ping).map_err(AggregatorError::Serialization)?;
+
+                    if let Err(e) = ws_stream.send(Message::Text(ping_msg)).await {
+                        error!("Failed to send ping: {}", e);
                         break;
                     }
                 }
-                Ok(Message::Close(_)) => {
-                    info!("WebSocket connection closed");
+                silent_for = watchdog.stalled() => return Err(health.stalled(silent_for)),
+                _ = shutdown.recv() => {
+                    info!("Closing Bybit order book stream");
+                    let _ = ws_stream.close(None).await;
                     break;
                 }
-                Err(e) => {
-                    error!("WebSocket error: {}", e);
-                    break;
-                }
-                _ => {}
-            }
-
-            // Send periodic pings
-            if last_ping.elapsed().as_millis() > config.ping_interval {
-                let pong = BybitPong {
-                    op: "pong".to_string(),
-                };
-                let pong_msg =
-                    serde_json::to_string(&pong).map_err(|e| AggregatorError::Serialization(e))?;
-
-                if let Err(e) = ws_stream.send(Message::Text(pong_msg)).await {
-                    error!("Failed to send pong: {}", e);
-                    break;
-                }
-                last_ping = std::time::Instant::now();
             }
         }
 
@@ -293,26 +845,41 @@ impl Bybit {
 
     async fn handle_websocket_messages(
         &self,
-        symbol: String,
         mut ws_rx: Receiver<Message>,
+        market_type: MarketType,
         price_level_tx: Sender<PriceLevelUpdate>,
+        capture: Option<Capture>,
     ) -> Result<()> {
-        let mut is_initialized = false;
+        let mut initialized: HashSet<String> = HashSet::new();
+        let mut funding: HashMap<String, FundingRate> = HashMap::new();
 
         while let Some(message) = ws_rx.recv().await {
             match message {
                 Message::Text(text) => {
-                    if text.contains("orderbook.50") {
+                    if let Some(capture) = &capture {
+                        capture.record(&Exchange::Bybit, &text);
+                    }
+                    if text.contains("tickers.") {
+                        if let Err(e) = Self::update_funding_rates(&text, &mut funding) {
+                            error!("Failed to parse ticker message: {}", e);
+                        }
+                    } else if text.contains("orderbook.50") {
                         match serde_json::from_str::<BybitDepthMessage>(&text) {
                             Ok(depth_msg) => {
+                                let symbol = &depth_msg.data.s;
                                 if depth_msg.data_type == "snapshot" {
-                                    is_initialized = true;
+                                    initialized.insert(symbol.clone());
                                     info!("Received orderbook snapshot for {}", symbol);
                                 }
 
-                                if is_initialized {
-                                    match self.create_price_level_update(&symbol, &depth_msg.data) {
-                                        Ok(update) => {
+                                if initialized.contains(symbol) {
+                                    match self.create_price_level_update(symbol, &depth_msg.data) {
+                                        Ok(mut update) => {
+                                            update.funding = funding.get(symbol).cloned();
+                                            update.event_time = DateTime::from_timestamp_millis(
+                                                depth_msg.ts as i64,
+                                            );
+                                            update.market_type = Some(market_type.clone());
                                             if let Err(e) = price_level_tx.send(update).await {
                                                 error!("Failed to send price level update: {}", e);
                                                 break;
@@ -342,26 +909,149 @@ impl Bybit {
 impl OrderBookService for Bybit {
     async fn spawn_order_book_service(
         &self,
-        pair: [&str; 2],
+        pairs: &[TradingPair],
         _order_book_depth: usize,
         exchange_stream_buffer: usize,
         price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
     ) -> Result<Vec<JoinHandle<Result<()>>>> {
-        let symbol = self.format_symbol(pair);
-        info!("Starting Bybit order book service for {}", symbol);
-
-        let (ws_rx, ws_handle) = self
-            .spawn_websocket_stream(symbol.clone(), exchange_stream_buffer)
-            .await?;
-
-        let self_clone = Self::new();
-        let message_handle = tokio::spawn(async move {
-            self_clone
-                .handle_websocket_messages(symbol, ws_rx, price_level_tx)
-                .await
-        });
+        self.primary_market_type()?;
+        for market_type in &self.market_types {
+            Self::category(market_type)?;
+        }
+        let symbols = self.format_symbols(pairs)?;
+
+        // Spot and linear books are served from separate endpoints, so each market gets its
+        // own connection
+        let mut handles = Vec::new();
+        for market_type in &self.market_types {
+            info!(
+                "Starting Bybit {:?} order book service for {}",
+                market_type,
+                symbols.join(", ")
+            );
+
+            let mut topics: Vec<String> = symbols
+                .iter()
+                .map(|symbol| format!("orderbook.50.{}", symbol))
+                .collect();
+            if *market_type == MarketType::Futures {
+                // Tickers carry the funding rate attached to every update
+                topics.extend(symbols.iter().map(|symbol| format!("tickers.{}", symbol)));
+            }
+
+            let mut config = self.config.clone();
+            if let Some(url) = self.websocket_url(market_type) {
+                config.websocket_url = url;
+            }
+            let (ws_rx, ws_handle) = self
+                .spawn_websocket_stream(
+                    config,
+                    topics,
+                    exchange_stream_buffer,
+                    Shutdown::new(shutdown.resubscribe()),
+                )
+                .await?;
+
+            let self_clone = Self::new();
+            let market_type = market_type.clone();
+            let price_level_tx = price_level_tx.clone();
+            let capture = self.capture.clone();
+            let message_handle = tokio::spawn(async move {
+                self_clone
+                    .handle_websocket_messages(ws_rx, market_type, price_level_tx, capture)
+                    .await
+            });
+            handles.push(ws_handle);
+            handles.push(message_handle);
+        }
+
+        Ok(handles)
+    }
+}
 
-        Ok(vec![ws_handle, message_handle])
+#[async_trait]
+impl TradeStreamService for Bybit {
+    async fn spawn_trade_service(
+        &self,
+        pairs: &[TradingPair],
+        trade_tx: Sender<Trade>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let symbols = self.format_symbols(pairs)?;
+        info!("Starting Bybit trade service for {}", symbols.join(", "));
+
+        let subscription = BybitSubscription {
+            op: "subscribe".to_string(),
+            args: symbols
+                .iter()
+                .map(|symbol| format!("publicTrade.{}", symbol))
+                .collect(),
+        };
+        let ping = BybitPong {
+            op: "ping".to_string(),
+        };
+
+        let spec = TradeStreamSpec {
+            exchange: "bybit",
+            url: self.config.websocket_url.clone(),
+            subscriptions: vec![
+                serde_json::to_string(&subscription).map_err(AggregatorError::Serialization)?
+            ],
+            ping: Some((
+                serde_json::to_string(&ping).map_err(AggregatorError::Serialization)?,
+                tokio::time::Duration::from_millis(self.config.ping_interval),
+            )),
+            reconnect_policy: self.reconnect_policy.clone(),
+            health: self.health_reporter(),
+            proxy: self.config.proxy.clone(),
+            capture: self.capture.clone(),
+        };
+
+        Ok(vec![spawn_trade_stream(spec, Self::parse_trades, trade_tx)])
+    }
+}
+
+#[async_trait]
+impl ExchangeInfoService for Bybit {
+    async fn fetch_instruments(&self, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let symbols = self.format_symbols(pairs)?;
+        let client = http_client(self.config.proxy.as_ref())?;
+
+        // Listing a whole category is paginated, so each symbol is requested on its own
+        let mut instruments = Vec::new();
+        for (symbol, pair) in symbols.iter().zip(pairs) {
+            let url = self.instruments_url(symbol)?;
+            let body = get_body(&client, &url, Some(&self.rate_limiter)).await?;
+            instruments.extend(Self::parse_instruments(&body, std::slice::from_ref(pair))?);
+        }
+        Ok(instruments)
+    }
+}
+
+#[async_trait]
+impl MarketDiscovery for Bybit {
+    async fn list_markets(&self) -> Result<Vec<MarketListing>> {
+        let client = http_client(self.config.proxy.as_ref())?;
+
+        let mut markets = Vec::new();
+        let mut cursor = None;
+        loop {
+            let url = self.markets_url(cursor.as_deref())?;
+            let body = get_body(&client, &url, Some(&self.rate_limiter)).await?;
+            let (page, next) = Self::parse_markets(&body)?;
+            markets.extend(page);
+            cursor = next;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let body = get_body(&client, &self.tickers_url()?, Some(&self.rate_limiter)).await?;
+        let volumes = Self::parse_quote_volumes(&body)?;
+        for market in &mut markets {
+            market.quote_volume = volumes.get(&market.symbol).copied();
+        }
+        Ok(markets)
     }
 }
 
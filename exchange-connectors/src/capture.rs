@@ -0,0 +1,318 @@
+//! Capture Module
+//! Records raw WebSocket frames per exchange to JSON lines files, optionally gzipped, and replays
+//! them through a connector's parser for regression tests, offline backtests and
+//! `Aggregator::start_replay`
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use tokio::sync::mpsc::Sender;
+use tracing::error;
+
+use aggregator_core::{AggregatorError, Exchange, PriceLevelUpdate, ReplaySource, Result};
+
+/// How fast `replay` feeds frames, shared with `Aggregator::start_replay`.
+pub use aggregator_core::ReplayPace;
+
+/// One raw frame as received from an exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapturedFrame {
+    pub exchange: Exchange,
+    pub received_at: DateTime<Utc>,
+    pub message: String,
+}
+
+impl CapturedFrame {
+    pub fn new(exchange: Exchange, message: &str) -> Self {
+        Self {
+            exchange,
+            received_at: Utc::now(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Handle connectors use to record raw frames. Clones share one background writer, which keeps a
+/// file per exchange open until every handle is dropped.
+///
+/// Recording never blocks the stream: frames are queued to the writer thread, and write errors
+/// are logged rather than interrupting the connection.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    tx: mpsc::Sender<CapturedFrame>,
+}
+
+impl Capture {
+    /// Starts writing frames under `dir`, to `<exchange>.jsonl`, or `<exchange>.jsonl.gz` when
+    /// `compress` is set. Join the returned handle after the connectors stop to make sure every
+    /// file is flushed and, when compressed, finished.
+    pub fn start(
+        dir: impl AsRef<Path>,
+        compress: bool,
+    ) -> Result<(Self, thread::JoinHandle<Result<()>>)> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+
+        let (tx, rx) = mpsc::channel::<CapturedFrame>();
+        let handle = thread::spawn(move || {
+            let mut writers: HashMap<Exchange, CaptureWriter> = HashMap::new();
+            for frame in rx {
+                if !writers.contains_key(&frame.exchange) {
+                    let extension = if compress { "jsonl.gz" } else { "jsonl" };
+                    let path = dir.join(format!("{}.{}", frame.exchange, extension));
+                    writers.insert(frame.exchange.clone(), CaptureWriter::create(path)?);
+                }
+                if let Some(writer) = writers.get_mut(&frame.exchange) {
+                    if let Err(e) = writer.write(&frame) {
+                        error!("Failed to capture {} frame: {}", frame.exchange, e);
+                    }
+                }
+            }
+            for (_, writer) in writers {
+                writer.finish()?;
+            }
+            Ok(())
+        });
+
+        Ok((Self { tx }, handle))
+    }
+
+    /// Queues `message` received from `exchange` for writing.
+    pub fn record(&self, exchange: &Exchange, message: &str) {
+        // The writer only stops once it failed to open a file, which it has already reported
+        let _ = self.tx.send(CapturedFrame::new(exchange.clone(), message));
+    }
+}
+
+/// Writes captured frames as JSON lines, gzipped when the path ends in `.gz`.
+pub struct CaptureWriter {
+    path: PathBuf,
+    output: Output,
+}
+
+enum Output {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl CaptureWriter {
+    /// Creates or truncates the file at `path`.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path).map_err(|e| io_error(&path, e))?;
+        let output = if is_gzip(&path) {
+            Output::Gzip(GzEncoder::new(BufWriter::new(file), Compression::default()))
+        } else {
+            Output::Plain(BufWriter::new(file))
+        };
+        Ok(Self { path, output })
+    }
+
+    pub fn write(&mut self, frame: &CapturedFrame) -> Result<()> {
+        let mut line = serde_json::to_vec(frame).map_err(AggregatorError::Serialization)?;
+        line.push(b'\n');
+        let written = match &mut self.output {
+            Output::Plain(writer) => writer.write_all(&line),
+            Output::Gzip(writer) => writer.write_all(&line),
+        };
+        written.map_err(|e| io_error(&self.path, e))
+    }
+
+    /// Flushes buffered frames and, for gzip files, writes the trailer.
+    pub fn finish(self) -> Result<()> {
+        let flushed = match self.output {
+            Output::Plain(mut writer) => writer.flush(),
+            Output::Gzip(writer) => writer.finish().and_then(|mut writer| writer.flush()),
+        };
+        flushed.map_err(|e| io_error(&self.path, e))
+    }
+}
+
+/// Reads captured frames back, one per line, from plain or gzipped files.
+pub struct CaptureReader {
+    lines: std::io::Lines<BufReader<Box<dyn Read + Send>>>,
+}
+
+impl CaptureReader {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| io_error(path, e))?;
+        let input: Box<dyn Read + Send> = if is_gzip(path) {
+            Box::new(MultiGzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+        Ok(Self {
+            lines: BufReader::new(input).lines(),
+        })
+    }
+}
+
+impl Iterator for CaptureReader {
+    type Item = Result<CapturedFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(AggregatorError::Io(e))),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(serde_json::from_str(&line).map_err(|e| {
+                AggregatorError::parsing(
+                    "CapturedFrame",
+                    format!("Failed to parse captured frame: {}", e),
+                )
+            }));
+        }
+    }
+}
+
+/// Counts from a replay.
+///
+/// - `frames`: Frames read from the capture.
+/// - `produced`: Items the parser produced from them.
+/// - `errors`: Frames the parser rejected; they are skipped, as a live stream would.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    pub frames: usize,
+    pub produced: usize,
+    pub errors: usize,
+}
+
+/// Feeds every frame of the capture at `path` through `parse` and returns what it produced.
+pub fn replay_collect<T, R, F>(
+    path: impl AsRef<Path>,
+    mut parse: F,
+) -> Result<(Vec<T>, ReplayStats)>
+where
+    F: FnMut(&str) -> Result<R>,
+    R: IntoIterator<Item = T>,
+{
+    let mut items = Vec::new();
+    let mut stats = ReplayStats::default();
+    for frame in CaptureReader::open(path)? {
+        let frame = frame?;
+        stats.frames += 1;
+        match parse(&frame.message) {
+            Ok(produced) => items.extend(produced),
+            Err(_) => stats.errors += 1,
+        }
+    }
+    stats.produced = items.len();
+    Ok((items, stats))
+}
+
+/// Feeds every frame of the capture at `path` through `parse` and sends what it produces to `tx`,
+/// paced as requested. Stops early, without error, when `tx` is closed.
+pub async fn replay<T, R, F>(
+    path: impl AsRef<Path>,
+    mut parse: F,
+    tx: &Sender<T>,
+    pace: ReplayPace,
+) -> Result<ReplayStats>
+where
+    F: FnMut(&str) -> Result<R>,
+    R: IntoIterator<Item = T>,
+{
+    let mut stats = ReplayStats::default();
+    let mut previous: Option<DateTime<Utc>> = None;
+
+    for frame in CaptureReader::open(path)? {
+        let frame = frame?;
+        if let Some(delay) = previous.and_then(|previous| pace.delay(previous, frame.received_at)) {
+            tokio::time::sleep(delay).await;
+        }
+        previous = Some(frame.received_at);
+        stats.frames += 1;
+
+        match parse(&frame.message) {
+            Ok(produced) => {
+                for item in produced {
+                    if tx.send(item).await.is_err() {
+                        return Ok(stats);
+                    }
+                    stats.produced += 1;
+                }
+            }
+            Err(_) => stats.errors += 1,
+        }
+    }
+    Ok(stats)
+}
+
+/// A capture replayed through the aggregator with `Aggregator::start_replay`. Frames go through
+/// `parse`, and the updates it produces are stamped with the time their frame was received, so
+/// the replay keeps the recorded pace. Frames the parser rejects are returned as errors, which
+/// the replay skips.
+pub struct CaptureSource<F> {
+    reader: CaptureReader,
+    parse: F,
+    pending: VecDeque<PriceLevelUpdate>,
+}
+
+impl<F, R> CaptureSource<F>
+where
+    F: FnMut(&str) -> Result<R> + Send,
+    R: IntoIterator<Item = PriceLevelUpdate>,
+{
+    pub fn open(path: impl AsRef<Path>, parse: F) -> Result<Self> {
+        Ok(Self {
+            reader: CaptureReader::open(path)?,
+            parse,
+            pending: VecDeque::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl<F, R> ReplaySource for CaptureSource<F>
+where
+    F: FnMut(&str) -> Result<R> + Send,
+    R: IntoIterator<Item = PriceLevelUpdate>,
+{
+    async fn next_update(&mut self) -> Option<Result<PriceLevelUpdate>> {
+        loop {
+            if let Some(update) = self.pending.pop_front() {
+                return Some(Ok(update));
+            }
+            let frame = match self.reader.next()? {
+                Ok(frame) => frame,
+                Err(e) => return Some(Err(e)),
+            };
+            match (self.parse)(&frame.message) {
+                Ok(produced) => {
+                    self.pending
+                        .extend(produced.into_iter().map(|update| PriceLevelUpdate {
+                            timestamp: frame.received_at,
+                            ..update
+                        }))
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Wraps an I/O error with the path it happened on.
+fn io_error(path: &Path, e: std::io::Error) -> AggregatorError {
+    AggregatorError::Io(std::io::Error::new(
+        e.kind(),
+        format!("{}: {}", path.display(), e),
+    ))
+}
+
+fn is_gzip(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "gz")
+}
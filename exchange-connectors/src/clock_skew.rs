@@ -0,0 +1,432 @@
+//! Clock Skew Module
+//! Estimates how far each exchange's clock is from the local one by sampling its REST server time
+//! endpoint, NTP-style, and moves exchange event times onto the local clock so they can be
+//! compared across venues
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as TimeDelta, Utc};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
+
+use crate::proxy::{http_client, Proxy};
+use crate::reconnect::Shutdown;
+use crate::OrderBookService;
+use aggregator_core::{AggregatorError, Exchange, PriceLevelUpdate, Result, TradingPair};
+
+/// Samples kept per exchange; the estimate comes from the fastest of them.
+const DEFAULT_MAX_SAMPLES: usize = 8;
+const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One round trip to an exchange's time endpoint.
+///
+/// Assuming the request and response took equally long, the server read its clock halfway
+/// through the round trip, so its offset is the server time minus the local midpoint. The error of
+/// that assumption is at most half the round trip.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkewSample {
+    /// Server clock minus local clock.
+    pub offset: TimeDelta,
+    pub round_trip: TimeDelta,
+}
+
+impl SkewSample {
+    /// Builds a sample from the local time the request was `sent`, the `server_time` in the
+    /// response and the local time the response was `received`.
+    pub fn new(sent: DateTime<Utc>, server_time: DateTime<Utc>, received: DateTime<Utc>) -> Self {
+        let round_trip = received - sent;
+        let midpoint = sent + round_trip / 2;
+        Self {
+            offset: server_time - midpoint,
+            round_trip,
+        }
+    }
+}
+
+/// Keeps the latest samples for one exchange and estimates its offset from the sample with the
+/// shortest round trip, which bounds the error most tightly.
+#[derive(Debug, Clone)]
+pub struct ClockSkewEstimator {
+    samples: VecDeque<SkewSample>,
+    max_samples: usize,
+}
+
+impl ClockSkewEstimator {
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+            max_samples: DEFAULT_MAX_SAMPLES,
+        }
+    }
+
+    /// Estimate from at most the latest `max_samples` samples.
+    pub fn with_max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples.max(1);
+        self
+    }
+
+    pub fn push(&mut self, sample: SkewSample) {
+        if self.samples.len() >= self.max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    fn best(&self) -> Option<&SkewSample> {
+        self.samples.iter().min_by_key(|sample| sample.round_trip)
+    }
+
+    /// Server clock minus local clock, if any sample was taken.
+    pub fn offset(&self) -> Option<TimeDelta> {
+        self.best().map(|sample| sample.offset)
+    }
+
+    /// Round trip of the sample behind the estimate; the estimate is off by at most half of it.
+    pub fn round_trip(&self) -> Option<TimeDelta> {
+        self.best().map(|sample| sample.round_trip)
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+impl Default for ClockSkewEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Clock offsets of every sampled exchange, shared between the sampler and the connectors that
+/// apply them. Clones share the same estimates.
+#[derive(Debug, Clone, Default)]
+pub struct ClockSkew {
+    estimators: Arc<RwLock<HashMap<Exchange, ClockSkewEstimator>>>,
+}
+
+impl ClockSkew {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, exchange: &Exchange, sample: SkewSample) {
+        let mut estimators = self.estimators.write().unwrap_or_else(|e| e.into_inner());
+        estimators.entry(exchange.clone()).or_default().push(sample);
+    }
+
+    /// Estimated clock offset of `exchange`: positive when its clock runs ahead of the local one.
+    pub fn offset(&self, exchange: &Exchange) -> Option<TimeDelta> {
+        let estimators = self.estimators.read().unwrap_or_else(|e| e.into_inner());
+        estimators
+            .get(exchange)
+            .and_then(ClockSkewEstimator::offset)
+    }
+
+    /// Converts a time read from the clock of `exchange` to the local clock. Times from exchanges
+    /// without an estimate are returned unchanged.
+    pub fn to_local(&self, exchange: &Exchange, time: DateTime<Utc>) -> DateTime<Utc> {
+        match self.offset(exchange) {
+            Some(offset) => time - offset,
+            None => time,
+        }
+    }
+
+    /// Moves the exchange event time of `update` onto the local clock, so it compares directly
+    /// with `timestamp` and with event times from other exchanges. `timestamp` is already taken
+    /// from the local clock when the update is generated and is left as is.
+    pub fn correct(&self, update: &mut PriceLevelUpdate) {
+        if let Some(event_time) = update.event_time {
+            update.event_time = Some(self.to_local(&update.exchange, event_time));
+        }
+    }
+}
+
+/// Where an exchange publishes its clock: a REST endpoint and a JSON pointer to the time.
+#[derive(Debug, Clone)]
+pub struct ServerTimeEndpoint {
+    pub exchange: Exchange,
+    pub url: String,
+    pub pointer: String,
+    /// Seconds per unit of the value at `pointer`, e.g. `0.001` for milliseconds.
+    pub unit_seconds: f64,
+}
+
+impl ServerTimeEndpoint {
+    /// An endpoint reporting milliseconds since the epoch at `pointer`.
+    pub fn new(exchange: Exchange, url: &str, pointer: &str) -> Self {
+        Self {
+            exchange,
+            url: url.to_string(),
+            pointer: pointer.to_string(),
+            unit_seconds: 0.001,
+        }
+    }
+
+    /// Read the time as seconds, possibly fractional, instead of milliseconds.
+    pub fn in_seconds(mut self) -> Self {
+        self.unit_seconds = 1.0;
+        self
+    }
+
+    /// Returns the public server time endpoint of `exchange`.
+    pub fn for_exchange(exchange: Exchange) -> Result<Self> {
+        let endpoint = match exchange {
+            Exchange::Binance => Self::new(
+                exchange,
+                "https://api.binance.com/api/v3/time",
+                "/serverTime",
+            ),
+            Exchange::Bybit => Self::new(exchange, "https://api.bybit.com/v5/market/time", "/time"),
+            // Kraken only reports whole seconds, so its estimate is off by up to half a second
+            Exchange::Kraken => Self::new(
+                exchange,
+                "https://api.kraken.com/0/public/Time",
+                "/result/unixtime",
+            )
+            .in_seconds(),
+            Exchange::Coinbase => {
+                Self::new(exchange, "https://api.exchange.coinbase.com/time", "/epoch").in_seconds()
+            }
+            Exchange::GateIo => Self::new(
+                exchange,
+                "https://api.gateio.ws/api/v4/spot/time",
+                "/server_time",
+            ),
+            Exchange::KuCoin => {
+                Self::new(exchange, "https://api.kucoin.com/api/v1/timestamp", "/data")
+            }
+            Exchange::OKX => Self::new(
+                exchange,
+                "https://www.okx.com/api/v5/public/time",
+                "/data/0/ts",
+            ),
+            Exchange::Mexc => {
+                Self::new(exchange, "https://api.mexc.com/api/v3/time", "/serverTime")
+            }
+            Exchange::Bitget => Self::new(
+                exchange,
+                "https://api.bitget.com/api/v2/public/time",
+                "/data/serverTime",
+            ),
+            other => {
+                return Err(AggregatorError::validation(
+                    "exchange",
+                    format!("No server time endpoint is known for {}", other),
+                ))
+            }
+        };
+        Ok(endpoint)
+    }
+
+    /// Parses a response body into the server time.
+    pub fn parse_time(&self, body: &str) -> Result<DateTime<Utc>> {
+        let response: Value = serde_json::from_str(body).map_err(|e| {
+            AggregatorError::parsing("ServerTime", format!("Failed to parse response: {}", e))
+        })?;
+        let value = response
+            .pointer(&self.pointer)
+            .and_then(|value| match value {
+                Value::Number(number) => number.as_f64(),
+                Value::String(text) => text.parse().ok(),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                AggregatorError::parsing(
+                    "ServerTime",
+                    format!("Missing server time at '{}'", self.pointer),
+                )
+            })?;
+
+        let micros = (value * self.unit_seconds * 1_000_000.0).round() as i64;
+        DateTime::from_timestamp_micros(micros).ok_or_else(|| {
+            AggregatorError::parsing("ServerTime", format!("Invalid server time: {}", value))
+        })
+    }
+
+    async fn sample(&self, client: &reqwest::Client) -> Result<SkewSample> {
+        let sent = Utc::now();
+        let response = client
+            .get(&self.url)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to get server time: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AggregatorError::network(format!(
+                "HTTP error: {}",
+                response.status()
+            )));
+        }
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to read server time: {}", e)))?;
+        let received = Utc::now();
+
+        Ok(SkewSample::new(sent, self.parse_time(&body)?, received))
+    }
+}
+
+/// Periodically samples the time endpoints of a set of exchanges into a shared `ClockSkew`.
+#[derive(Debug, Clone)]
+pub struct ClockSkewSampler {
+    skew: ClockSkew,
+    endpoints: Vec<ServerTimeEndpoint>,
+    interval: Duration,
+    proxy: Option<Proxy>,
+}
+
+impl ClockSkewSampler {
+    pub fn new(skew: ClockSkew) -> Self {
+        Self {
+            skew,
+            endpoints: Vec::new(),
+            interval: DEFAULT_SAMPLE_INTERVAL,
+            proxy: None,
+        }
+    }
+
+    /// Sample the built-in endpoint of `exchange`.
+    pub fn with_exchange(self, exchange: Exchange) -> Result<Self> {
+        Ok(self.with_endpoint(ServerTimeEndpoint::for_exchange(exchange)?))
+    }
+
+    pub fn with_endpoint(mut self, endpoint: ServerTimeEndpoint) -> Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+
+    /// Sample every endpoint once per `interval`.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Send time requests through `proxy`. Only HTTP proxies can carry REST traffic.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn skew(&self) -> &ClockSkew {
+        &self.skew
+    }
+
+    /// Samples every endpoint once. Failed requests are logged and skipped, keeping the previous
+    /// estimate of that exchange.
+    pub async fn sample_once(&self, client: &reqwest::Client) {
+        for endpoint in &self.endpoints {
+            match endpoint.sample(client).await {
+                Ok(sample) => {
+                    debug!(
+                        "{} clock offset {}ms over a {}ms round trip",
+                        endpoint.exchange,
+                        sample.offset.num_milliseconds(),
+                        sample.round_trip.num_milliseconds()
+                    );
+                    self.skew.record(&endpoint.exchange, sample);
+                }
+                Err(e) => warn!("Failed to sample {} clock: {}", endpoint.exchange, e),
+            }
+        }
+    }
+
+    /// Spawns the sampling task, which runs until `shutdown` fires.
+    pub fn spawn(self, shutdown: broadcast::Receiver<()>) -> Result<JoinHandle<Result<()>>> {
+        let client = http_client(self.proxy.as_ref())?;
+        let mut shutdown = Shutdown::new(shutdown);
+        info!(
+            "Sampling clock skew of {} exchanges every {:?}",
+            self.endpoints.len(),
+            self.interval
+        );
+
+        Ok(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => self.sample_once(&client).await,
+                    _ = shutdown.recv() => return Ok(()),
+                }
+            }
+        }))
+    }
+}
+
+/// Wraps an order book connector so the event times of its updates are on the local clock.
+///
+/// Updates from exchanges without an estimate yet pass through unchanged.
+#[derive(Debug, Clone)]
+pub struct SkewCorrected<S> {
+    inner: S,
+    skew: ClockSkew,
+}
+
+impl<S> SkewCorrected<S> {
+    pub fn new(inner: S, skew: ClockSkew) -> Self {
+        Self { inner, skew }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Spawn the task correcting updates from `update_rx` into `price_level_tx`.
+    fn spawn_correction(
+        skew: ClockSkew,
+        mut update_rx: Receiver<PriceLevelUpdate>,
+        price_level_tx: Sender<PriceLevelUpdate>,
+    ) -> JoinHandle<Result<()>> {
+        tokio::spawn(async move {
+            while let Some(mut update) = update_rx.recv().await {
+                skew.correct(&mut update);
+                if price_level_tx.send(update).await.is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl<S: OrderBookService + Send + Sync> OrderBookService for SkewCorrected<S> {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let (update_tx, update_rx) = tokio::sync::mpsc::channel(exchange_stream_buffer);
+        let mut handles = self
+            .inner
+            .spawn_order_book_service(
+                pairs,
+                order_book_depth,
+                exchange_stream_buffer,
+                update_tx,
+                shutdown,
+            )
+            .await?;
+        handles.push(Self::spawn_correction(
+            self.skew.clone(),
+            update_rx,
+            price_level_tx,
+        ));
+        Ok(handles)
+    }
+}
@@ -1,16 +1,908 @@
-//! Coinbase Exchange Connector (placeholder)
+//! Coinbase Exchange Connector
+//! Order book streaming is a placeholder; trades are streamed from the `matches` channel, the
+//! account's orders from the authenticated `user` channel, and per-order events from the `full`
+//! channel, which also maintain an order-by-order level 3 book
 
-use crate::OrderBookService;
-use aggregator_core::{PriceLevelUpdate, Result};
+use crate::auth::{forward_user_data, require_credentials, Credentials, UserDataEvent};
+use crate::capture::Capture;
+use crate::exchange_info::{decimal, ensure_complete, get_body, parse_body, requested_symbols};
+use crate::proxy::{connect_websocket, http_client, Proxy, WsStream};
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy};
+use crate::trade_stream::{spawn_trade_stream, TradeStreamSpec};
+use crate::{
+    AuthenticatedService, ExchangeInfoService, OrderBookService, OrderEventService, SymbolMapper,
+    TradeStreamService,
+};
+use aggregator_core::{
+    AggregatorError, BalanceUpdate, Exchange, ExchangeConfig, HealthEvent, InstrumentInfo,
+    MarketDiscovery, MarketListing, OrderEvent, OrderEventKind, OrderStatus, OrderUpdate,
+    PriceLevelUpdate, Result, Trade, TradeSide, TradingPair,
+};
 use async_trait::async_trait;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::Sender;
 use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+use uuid::Uuid;
 
-pub struct Coinbase;
+const COINBASE_WS_URL: &str = "wss://ws-feed.exchange.coinbase.com";
+const COINBASE_SANDBOX_WS_URL: &str = "wss://ws-feed-public.sandbox.exchange.coinbase.com";
+const COINBASE_REST_URL: &str = "https://api.exchange.coinbase.com";
+const COINBASE_SANDBOX_REST_URL: &str = "https://api-public.sandbox.exchange.coinbase.com";
+const USER_CHANNEL: &str = "user";
+const FULL_CHANNEL: &str = "full";
+/// Aggregated level sizes at or below this are treated as empty
+const EMPTY_LEVEL: f64 = 1e-12;
+const USER_VERIFY_PATH: &str = "/users/self/verify";
+const ONLINE_STATUS: &str = "online";
+
+pub struct Coinbase {
+    sandbox: bool,
+    credentials: Option<Credentials>,
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+}
+
+#[derive(Debug, Serialize)]
+struct CoinbaseSubscription {
+    #[serde(rename = "type")]
+    message_type: String,
+    product_ids: Vec<String>,
+    channels: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CoinbaseSignedSubscription {
+    #[serde(flatten)]
+    subscription: CoinbaseSubscription,
+    signature: String,
+    key: String,
+    passphrase: String,
+    timestamp: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinbaseOrderMessage {
+    #[serde(rename = "type")]
+    message_type: String,
+    order_id: Option<String>,
+    client_oid: Option<String>,
+    maker_order_id: Option<String>,
+    taker_order_id: Option<String>,
+    taker_user_id: Option<String>,
+    user_id: Option<String>,
+    product_id: String,
+    side: String,
+    price: Option<String>,
+    size: Option<String>,
+    reason: Option<String>,
+    time: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinbaseMessage {
+    #[serde(rename = "type")]
+    message_type: String,
+}
+
+/// A `full` channel message. Which fields are present depends on `message_type`.
+#[derive(Debug, Deserialize)]
+struct CoinbaseFullMessage {
+    #[serde(rename = "type")]
+    message_type: String,
+    product_id: String,
+    sequence: u64,
+    order_id: Option<String>,
+    maker_order_id: Option<String>,
+    taker_order_id: Option<String>,
+    trade_id: Option<u64>,
+    side: String,
+    price: Option<String>,
+    size: Option<String>,
+    remaining_size: Option<String>,
+    new_size: Option<String>,
+    reason: Option<String>,
+    time: DateTime<Utc>,
+}
+
+/// A level 3 book snapshot. Entries are `[price, size, order_id]`.
+#[derive(Debug, Deserialize)]
+struct CoinbaseL3Snapshot {
+    sequence: u64,
+    bids: Vec<[String; 3]>,
+    asks: Vec<[String; 3]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PriceKey(f64);
+
+impl Eq for PriceKey {}
+
+impl PartialOrd for PriceKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PriceKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// An order resting on a level 3 book.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestingOrder {
+    pub side: TradeSide,
+    pub price: f64,
+    pub size: f64,
+}
+
+/// An order-by-order Coinbase book, seeded from a level 3 snapshot and kept current with `full`
+/// channel events. Sizes are also kept aggregated per price level, so reading the top of the
+/// book does not walk every order.
+#[derive(Debug, Clone, Default)]
+pub struct CoinbaseL3Book {
+    sequence: u64,
+    orders: HashMap<String, RestingOrder>,
+    bids: BTreeMap<PriceKey, f64>,
+    asks: BTreeMap<PriceKey, f64>,
+}
+
+impl CoinbaseL3Book {
+    /// Returns an empty book positioned at `sequence`.
+    pub fn new(sequence: u64) -> Self {
+        Self {
+            sequence,
+            ..Self::default()
+        }
+    }
+
+    /// The sequence number of the last event applied.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Applies a `full` channel event. Events at or before the book's sequence are skipped and
+    /// return `false`. A gap in the sequence returns an error and leaves the book untouched; the
+    /// book must then be rebuilt from a new snapshot.
+    pub fn apply(&mut self, event: &OrderEvent) -> Result<bool> {
+        if event.sequence <= self.sequence {
+            return Ok(false);
+        }
+        if event.sequence != self.sequence + 1 {
+            return Err(AggregatorError::exchange(
+                "coinbase",
+                format!(
+                    "Sequence gap on {}: expected {}, got {}",
+                    event.symbol,
+                    self.sequence + 1,
+                    event.sequence
+                ),
+            ));
+        }
+        self.sequence = event.sequence;
+
+        match &event.kind {
+            OrderEventKind::Open => {
+                if let (Some(price), Some(size)) = (event.price, event.quantity) {
+                    self.insert(
+                        event.order_id.clone(),
+                        RestingOrder {
+                            side: event.side,
+                            price,
+                            size,
+                        },
+                    );
+                }
+            }
+            OrderEventKind::Match { .. } => {
+                if let (Some(traded), Some(order)) =
+                    (event.quantity, self.orders.get(&event.order_id))
+                {
+                    let size = order.size - traded;
+                    self.resize(&event.order_id, size);
+                }
+            }
+            OrderEventKind::Change => {
+                if let Some(size) = event.quantity {
+                    self.resize(&event.order_id, size);
+                }
+            }
+            OrderEventKind::Done { .. } => self.remove(&event.order_id),
+            // Received orders only rest on the book once they are opened
+            OrderEventKind::Received => {}
+        }
+        Ok(true)
+    }
+
+    pub fn order(&self, order_id: &str) -> Option<&RestingOrder> {
+        self.orders.get(order_id)
+    }
+
+    pub fn order_count(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// The highest bid as `(price, total size)`.
+    pub fn best_bid(&self) -> Option<(f64, f64)> {
+        self.bids
+            .iter()
+            .next_back()
+            .map(|(price, size)| (price.0, *size))
+    }
+
+    /// The lowest ask as `(price, total size)`.
+    pub fn best_ask(&self) -> Option<(f64, f64)> {
+        self.asks
+            .iter()
+            .next()
+            .map(|(price, size)| (price.0, *size))
+    }
+
+    /// The top `depth` price levels of one side as `(price, total size)`, best first.
+    pub fn levels(&self, side: TradeSide, depth: usize) -> Vec<(f64, f64)> {
+        let level = |(price, size): (&PriceKey, &f64)| (price.0, *size);
+        match side {
+            TradeSide::Buy => self.bids.iter().rev().take(depth).map(level).collect(),
+            TradeSide::Sell => self.asks.iter().take(depth).map(level).collect(),
+        }
+    }
+
+    fn insert(&mut self, order_id: String, order: RestingOrder) {
+        self.remove(&order_id);
+        self.adjust_level(order.side, order.price, order.size);
+        self.orders.insert(order_id, order);
+    }
+
+    fn resize(&mut self, order_id: &str, size: f64) {
+        let Some(order) = self.orders.get_mut(order_id) else {
+            return;
+        };
+        let size = size.max(0.0);
+        let delta = size - order.size;
+        order.size = size;
+        let (side, price) = (order.side, order.price);
+        self.adjust_level(side, price, delta);
+    }
+
+    fn remove(&mut self, order_id: &str) {
+        if let Some(order) = self.orders.remove(order_id) {
+            self.adjust_level(order.side, order.price, -order.size);
+        }
+    }
+
+    fn adjust_level(&mut self, side: TradeSide, price: f64, delta: f64) {
+        let levels = match side {
+            TradeSide::Buy => &mut self.bids,
+            TradeSide::Sell => &mut self.asks,
+        };
+        let total = levels.entry(PriceKey(price)).or_insert(0.0);
+        *total += delta;
+        if *total <= EMPTY_LEVEL {
+            levels.remove(&PriceKey(price));
+        }
+    }
+}
+
+/// A product listing. `base_min_size` has been dropped from the production API, so it may be
+/// absent.
+#[derive(Debug, Deserialize)]
+struct CoinbaseProduct {
+    id: String,
+    #[serde(default)]
+    base_currency: String,
+    #[serde(default)]
+    quote_currency: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    trading_disabled: bool,
+    quote_increment: String,
+    base_increment: String,
+    base_min_size: Option<String>,
+    min_market_funds: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinbaseMatch {
+    trade_id: u64,
+    product_id: String,
+    price: String,
+    size: String,
+    side: String,
+    time: DateTime<Utc>,
+}
 
 impl Coinbase {
     pub fn new() -> Self {
-        Coinbase
+        Self {
+            sandbox: false,
+            credentials: None,
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            proxy: None,
+            capture: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings, using the Coinbase Exchange sandbox
+    /// when `sandbox` is set.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        let mut coinbase = Self::new()
+            .with_sandbox(config.sandbox)
+            .with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        coinbase.credentials = Credentials::from_config(config);
+        coinbase.proxy = Proxy::from_config(config)?;
+        Ok(coinbase)
+    }
+
+    /// Authenticate the private `user` channel with `credentials`, which must include the
+    /// API passphrase.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Connect to the Coinbase Exchange sandbox instead of production.
+    pub fn with_sandbox(mut self, sandbox: bool) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket and REST traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::Coinbase, self.health_tx.clone())
+    }
+
+    /// Returns the WebSocket feed this connector streams from.
+    pub fn websocket_url(&self) -> &'static str {
+        if self.sandbox {
+            COINBASE_SANDBOX_WS_URL
+        } else {
+            COINBASE_WS_URL
+        }
+    }
+
+    /// Returns the REST API this connector queries.
+    pub fn rest_url(&self) -> &'static str {
+        if self.sandbox {
+            COINBASE_SANDBOX_REST_URL
+        } else {
+            COINBASE_REST_URL
+        }
+    }
+
+    /// Parse a `products` listing into the products open for trading, without volumes.
+    pub fn parse_markets(body: &str) -> Result<Vec<MarketListing>> {
+        let products: Vec<CoinbaseProduct> = parse_body(body, "CoinbaseProduct")?;
+        Ok(products
+            .into_iter()
+            .filter(|product| {
+                product.status == ONLINE_STATUS
+                    && !product.trading_disabled
+                    && !product.base_currency.is_empty()
+                    && !product.quote_currency.is_empty()
+            })
+            .map(|product| MarketListing {
+                exchange: Exchange::Coinbase,
+                pair: TradingPair::new(&product.base_currency, &product.quote_currency),
+                symbol: product.id,
+                quote_volume: None,
+            })
+            .collect())
+    }
+
+    /// Parse a `products` listing into metadata for `pairs`. Products without a minimum size
+    /// accept any whole number of `base_increment` lots.
+    pub fn parse_instruments(body: &str, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let requested = requested_symbols(&Exchange::Coinbase, pairs)?;
+        let products: Vec<CoinbaseProduct> = parse_body(body, "CoinbaseProduct")?;
+
+        let mut instruments = Vec::new();
+        for product in products {
+            let Some(pair) = requested.get(&product.id) else {
+                continue;
+            };
+            let lot_size = decimal(&product.base_increment, "base_increment")?;
+            instruments.push(InstrumentInfo {
+                exchange: Exchange::Coinbase,
+                pair: pair.clone(),
+                tick_size: decimal(&product.quote_increment, "quote_increment")?,
+                lot_size,
+                min_quantity: match product.base_min_size.as_deref() {
+                    Some(size) => decimal(size, "base_min_size")?,
+                    None => lot_size,
+                },
+                min_notional: product
+                    .min_market_funds
+                    .as_deref()
+                    .map(|funds| decimal(funds, "min_market_funds"))
+                    .transpose()?,
+                symbol: product.id,
+            });
+        }
+
+        ensure_complete(&Exchange::Coinbase, &requested, &instruments)?;
+        Ok(instruments)
+    }
+
+    /// Parse a level 3 REST book snapshot into a book positioned at the snapshot's sequence.
+    pub fn parse_l3_snapshot(body: &str) -> Result<CoinbaseL3Book> {
+        let snapshot: CoinbaseL3Snapshot = parse_body(body, "CoinbaseL3Snapshot")?;
+
+        let mut book = CoinbaseL3Book::new(snapshot.sequence);
+        for (entries, side) in [
+            (snapshot.bids, TradeSide::Buy),
+            (snapshot.asks, TradeSide::Sell),
+        ] {
+            for [price, size, order_id] in entries {
+                let order = RestingOrder {
+                    side,
+                    price: decimal(&price, "price")?,
+                    size: decimal(&size, "size")?,
+                };
+                book.insert(order_id, order);
+            }
+        }
+        Ok(book)
+    }
+
+    /// Fetches the order-by-order book for `pair`. Apply `full` channel events received after
+    /// subscribing to bring it up to date; earlier events are skipped by sequence.
+    pub async fn fetch_l3_book(&self, pair: &TradingPair) -> Result<CoinbaseL3Book> {
+        let product_id = SymbolMapper::new().to_exchange(&Exchange::Coinbase, pair);
+        let url = format!("{}/products/{}/book?level=3", self.rest_url(), product_id);
+        let client = http_client(self.proxy.as_ref())?;
+        let body = get_body(&client, &url, None).await?;
+        Self::parse_l3_snapshot(&body)
+    }
+
+    /// Parse a raw `full` channel message into an order event. Subscription acks, `activate`
+    /// messages for untriggered stop orders and other messages yield `None`.
+    pub fn parse_order_event(message: &str) -> Result<Option<OrderEvent>> {
+        let msg: CoinbaseMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("CoinbaseMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        if !matches!(
+            msg.message_type.as_str(),
+            "received" | "open" | "match" | "change" | "done"
+        ) {
+            return Ok(None);
+        }
+
+        let event: CoinbaseFullMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing(
+                "CoinbaseFullMessage",
+                format!("Failed to parse full channel message: {}", e),
+            )
+        })?;
+
+        let side = match event.side.as_str() {
+            "buy" => TradeSide::Buy,
+            "sell" => TradeSide::Sell,
+            other => {
+                return Err(AggregatorError::parsing(
+                    "OrderEvent",
+                    format!("Invalid side: {}", other),
+                ))
+            }
+        };
+
+        // Matches are reported against the resting (maker) order
+        let (order_id, kind, quantity) = match event.message_type.as_str() {
+            "received" => (event.order_id, OrderEventKind::Received, event.size),
+            "open" => (event.order_id, OrderEventKind::Open, event.remaining_size),
+            "match" => (
+                event.maker_order_id,
+                OrderEventKind::Match {
+                    taker_order_id: event.taker_order_id.unwrap_or_default(),
+                    trade_id: event
+                        .trade_id
+                        .map(|trade_id| trade_id.to_string())
+                        .unwrap_or_default(),
+                },
+                event.size,
+            ),
+            "change" => (event.order_id, OrderEventKind::Change, event.new_size),
+            _ => (
+                event.order_id,
+                OrderEventKind::Done {
+                    reason: event.reason.unwrap_or_default(),
+                },
+                event.remaining_size,
+            ),
+        };
+
+        let order_id = order_id.ok_or_else(|| {
+            AggregatorError::parsing("OrderEvent", "Full channel message is missing an order id")
+        })?;
+        let price = event
+            .price
+            .as_deref()
+            .map(|price| decimal(price, "price"))
+            .transpose()?;
+        let quantity = quantity
+            .as_deref()
+            .map(|size| decimal(size, "size"))
+            .transpose()?;
+
+        Ok(Some(OrderEvent {
+            id: Uuid::new_v4(),
+            exchange: Exchange::Coinbase,
+            symbol: event.product_id,
+            order_id,
+            sequence: event.sequence,
+            side,
+            kind,
+            price,
+            quantity,
+            timestamp: event.time,
+        }))
+    }
+
+    /// Builds the `full` channel subscription, signed when credentials are configured.
+    fn full_subscription(
+        credentials: Option<&Credentials>,
+        product_ids: &[String],
+    ) -> Result<String> {
+        if let Some(credentials) = credentials {
+            return Self::signed_subscription(credentials, product_ids, FULL_CHANNEL);
+        }
+        let subscription = CoinbaseSubscription {
+            message_type: "subscribe".to_string(),
+            product_ids: product_ids.to_vec(),
+            channels: vec![FULL_CHANNEL.to_string()],
+        };
+        serde_json::to_string(&subscription).map_err(AggregatorError::Serialization)
+    }
+
+    async fn run_full_channel(
+        mut ws_stream: WsStream,
+        credentials: Option<&Credentials>,
+        product_ids: &[String],
+        order_event_tx: &Sender<OrderEvent>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        capture: Option<&Capture>,
+    ) -> Result<()> {
+        let subscription = Self::full_subscription(credentials, product_ids)?;
+
+        info!("Connected to Coinbase full channel");
+        backoff.reset();
+        health.connected();
+
+        ws_stream
+            .send(Message::Text(subscription))
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to send subscription: {}", e)))?;
+
+        let mut sequences: HashMap<String, u64> = HashMap::new();
+        loop {
+            tokio::select! {
+                msg = ws_stream.next() => match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Some(capture) = capture {
+                            capture.record(&Exchange::Coinbase, &text);
+                        }
+                        if text.contains(r#""type":"error""#) {
+                            return Err(AggregatorError::exchange("coinbase", text));
+                        }
+                        match Self::parse_order_event(&text) {
+                            Ok(Some(event)) => {
+                                if let Some(last) = sequences.insert(event.symbol.clone(), event.sequence) {
+                                    if event.sequence > last + 1 {
+                                        warn!(
+                                            "Coinbase full channel skipped {} messages on {}",
+                                            event.sequence - last - 1,
+                                            event.symbol
+                                        );
+                                    }
+                                }
+                                if order_event_tx.send(event).await.is_err() {
+                                    return Ok(());
+                                }
+                            }
+                            Ok(None) => {}
+                            Err(e) => warn!("Failed to parse Coinbase full channel message: {}", e),
+                        }
+                    }
+                    Some(Ok(Message::Ping(payload))) => {
+                        if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                            error!("Failed to send pong: {}", e);
+                            return Ok(());
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Err(e)) => {
+                        return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                    }
+                    _ => {}
+                },
+                _ = order_event_tx.closed() => return Ok(()),
+            }
+        }
+    }
+
+    /// Parse a raw `user` channel message into order events. The user channel carries no
+    /// balances, and messages that do not change an order's state yield nothing.
+    pub fn parse_user_data(message: &str) -> Result<Vec<UserDataEvent>> {
+        let msg: CoinbaseMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("CoinbaseMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        if !matches!(
+            msg.message_type.as_str(),
+            "received" | "open" | "activate" | "match" | "done"
+        ) {
+            return Ok(vec![]);
+        }
+
+        let order: CoinbaseOrderMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing(
+                "CoinbaseOrderMessage",
+                format!("Failed to parse order message: {}", e),
+            )
+        })?;
+
+        let mut side = match order.side.as_str() {
+            "buy" => TradeSide::Buy,
+            "sell" => TradeSide::Sell,
+            other => {
+                return Err(AggregatorError::parsing(
+                    "OrderUpdate",
+                    format!("Invalid side: {}", other),
+                ))
+            }
+        };
+
+        let (order_id, status) = match order.message_type.as_str() {
+            "match" => {
+                // Matches carry the maker side and both order ids; pick the account's own order
+                let is_taker =
+                    order.taker_user_id.is_some() && order.taker_user_id == order.user_id;
+                if is_taker {
+                    side = match side {
+                        TradeSide::Buy => TradeSide::Sell,
+                        TradeSide::Sell => TradeSide::Buy,
+                    };
+                    (order.taker_order_id, OrderStatus::PartiallyFilled)
+                } else {
+                    (order.maker_order_id, OrderStatus::PartiallyFilled)
+                }
+            }
+            "done" => match order.reason.as_deref() {
+                Some("filled") => (order.order_id, OrderStatus::Filled),
+                _ => (order.order_id, OrderStatus::Canceled),
+            },
+            _ => (order.order_id, OrderStatus::New),
+        };
+
+        let order_id = order_id.ok_or_else(|| {
+            AggregatorError::parsing("OrderUpdate", "Order message is missing an order id")
+        })?;
+        let price = order
+            .price
+            .as_deref()
+            .map(|price| price.parse::<f64>())
+            .transpose()
+            .map_err(|e| {
+                AggregatorError::parsing("OrderUpdate", format!("Invalid price: {}", e))
+            })?;
+        // `size` is the order size on `received` and the fill size on `match`
+        let size = order
+            .size
+            .as_deref()
+            .map(|size| size.parse::<f64>())
+            .transpose()
+            .map_err(|e| AggregatorError::parsing("OrderUpdate", format!("Invalid size: {}", e)))?;
+        let quantity = if order.message_type == "received" {
+            size
+        } else {
+            None
+        };
+
+        Ok(vec![UserDataEvent::Order(OrderUpdate {
+            id: Uuid::new_v4(),
+            order_id,
+            client_order_id: order.client_oid.filter(|id| !id.is_empty()),
+            symbol: order.product_id,
+            exchange: Exchange::Coinbase,
+            side,
+            status,
+            price,
+            quantity,
+            filled_quantity: None,
+            timestamp: order.time,
+        })])
+    }
+
+    /// Builds a signed subscription to `channel`. Coinbase rejects signatures older than
+    /// 30 seconds, so this runs on every (re)connect.
+    fn signed_subscription(
+        credentials: &Credentials,
+        product_ids: &[String],
+        channel: &str,
+    ) -> Result<String> {
+        let passphrase = credentials.require_passphrase("coinbase")?;
+        let timestamp = Utc::now().timestamp().to_string();
+        let signature = Self::sign(
+            credentials.api_secret(),
+            &format!("{}GET{}", timestamp, USER_VERIFY_PATH),
+        )?;
+
+        let subscription = CoinbaseSignedSubscription {
+            subscription: CoinbaseSubscription {
+                message_type: "subscribe".to_string(),
+                product_ids: product_ids.to_vec(),
+                channels: vec![channel.to_string()],
+            },
+            signature,
+            key: credentials.api_key.clone(),
+            passphrase: passphrase.to_string(),
+            timestamp,
+        };
+
+        serde_json::to_string(&subscription).map_err(AggregatorError::Serialization)
+    }
+
+    /// Signs `prehash` with HMAC-SHA256 keyed by the base64-decoded API secret.
+    fn sign(api_secret: &str, prehash: &str) -> Result<String> {
+        let key =
+            BASE64_STANDARD
+                .decode(api_secret)
+                .map_err(|e| AggregatorError::Authentication {
+                    message: format!("coinbase: API secret is not valid base64: {}", e),
+                })?;
+
+        let signature = PKey::hmac(&key)
+            .and_then(|pkey| {
+                let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+                signer.update(prehash.as_bytes())?;
+                signer.sign_to_vec()
+            })
+            .map_err(|e| AggregatorError::Internal {
+                message: format!("Failed to sign Coinbase request: {}", e),
+            })?;
+
+        Ok(BASE64_STANDARD.encode(signature))
+    }
+
+    async fn run_user_data_stream(
+        mut ws_stream: WsStream,
+        credentials: &Credentials,
+        product_ids: &[String],
+        order_tx: &Sender<OrderUpdate>,
+        balance_tx: &Sender<BalanceUpdate>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+    ) -> Result<()> {
+        let subscription = Self::signed_subscription(credentials, product_ids, USER_CHANNEL)?;
+
+        info!("Connected to Coinbase user channel");
+        backoff.reset();
+        health.connected();
+
+        ws_stream
+            .send(Message::Text(subscription))
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to send subscription: {}", e)))?;
+
+        while let Some(msg) = ws_stream.next().await {
+            match msg {
+                Ok(Message::Text(text)) => {
+                    if text.contains(r#""type":"error""#) {
+                        return Err(AggregatorError::Authentication {
+                            message: format!("coinbase: {}", text),
+                        });
+                    }
+                    match Self::parse_user_data(&text) {
+                        Ok(events) => {
+                            if !forward_user_data(events, order_tx, balance_tx).await {
+                                return Ok(());
+                            }
+                        }
+                        Err(e) => warn!("Failed to parse Coinbase user data: {}", e),
+                    }
+                }
+                Ok(Message::Ping(payload)) => {
+                    if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                        error!("Failed to send pong: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Err(e) => {
+                    return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse a raw `matches` channel message into trades. Other messages yield no trades.
+    pub fn parse_trades(message: &str) -> Result<Vec<Trade>> {
+        let msg: CoinbaseMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("CoinbaseMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        if msg.message_type != "match" && msg.message_type != "last_match" {
+            return Ok(vec![]);
+        }
+
+        let matched: CoinbaseMatch = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("CoinbaseMatch", format!("Failed to parse match: {}", e))
+        })?;
+
+        let price = matched
+            .price
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("Trade", format!("Invalid price: {}", e)))?;
+        let quantity = matched
+            .size
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("Trade", format!("Invalid quantity: {}", e)))?;
+
+        // Coinbase reports the maker side, so the aggressor is on the opposite side
+        let side = match matched.side.as_str() {
+            "buy" => TradeSide::Sell,
+            "sell" => TradeSide::Buy,
+            other => {
+                return Err(AggregatorError::parsing(
+                    "Trade",
+                    format!("Invalid side: {}", other),
+                ))
+            }
+        };
+
+        Ok(vec![Trade {
+            id: Uuid::new_v4(),
+            trade_id: matched.trade_id.to_string(),
+            symbol: matched.product_id,
+            exchange: Exchange::Coinbase,
+            price,
+            quantity,
+            side,
+            timestamp: matched.time,
+        }])
     }
 }
 
@@ -18,16 +910,209 @@ impl Coinbase {
 impl OrderBookService for Coinbase {
     async fn spawn_order_book_service(
         &self,
-        _pair: [&str; 2],
+        _pairs: &[TradingPair],
         _order_book_depth: usize,
         _exchange_stream_buffer: usize,
         _price_level_tx: Sender<PriceLevelUpdate>,
+        _shutdown: broadcast::Receiver<()>,
     ) -> Result<Vec<JoinHandle<Result<()>>>> {
         // TODO: Implement Coinbase WebSocket connection
         Ok(vec![])
     }
 }
 
+#[async_trait]
+impl TradeStreamService for Coinbase {
+    async fn spawn_trade_service(
+        &self,
+        pairs: &[TradingPair],
+        trade_tx: Sender<Trade>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let product_ids = SymbolMapper::new().to_exchange_all(&Exchange::Coinbase, pairs)?;
+        info!(
+            "Starting Coinbase trade service for {}",
+            product_ids.join(", ")
+        );
+
+        let subscription = CoinbaseSubscription {
+            message_type: "subscribe".to_string(),
+            product_ids,
+            channels: vec!["matches".to_string()],
+        };
+
+        let spec = TradeStreamSpec {
+            exchange: "coinbase",
+            url: self.websocket_url().to_string(),
+            subscriptions: vec![
+                serde_json::to_string(&subscription).map_err(AggregatorError::Serialization)?
+            ],
+            ping: None,
+            reconnect_policy: self.reconnect_policy.clone(),
+            health: self.health_reporter(),
+            proxy: self.proxy.clone(),
+            capture: self.capture.clone(),
+        };
+
+        Ok(vec![spawn_trade_stream(spec, Self::parse_trades, trade_tx)])
+    }
+}
+
+#[async_trait]
+impl OrderEventService for Coinbase {
+    /// Streams the `full` channel. Production requires credentials for it, which are used to
+    /// sign the subscription when configured.
+    async fn spawn_order_event_service(
+        &self,
+        pairs: &[TradingPair],
+        order_event_tx: Sender<OrderEvent>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let product_ids = SymbolMapper::new().to_exchange_all(&Exchange::Coinbase, pairs)?;
+        if let Some(credentials) = &self.credentials {
+            credentials.require_passphrase("coinbase")?;
+        }
+
+        info!(
+            "Starting Coinbase full channel for {}",
+            product_ids.join(", ")
+        );
+
+        let url = self.websocket_url();
+        let credentials = self.credentials.clone();
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+        let proxy = self.proxy.clone();
+        let capture = self.capture.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                let result = match connect_websocket(url, proxy.as_ref()).await {
+                    Ok((ws_stream, _)) => {
+                        Self::run_full_channel(
+                            ws_stream,
+                            credentials.as_ref(),
+                            &product_ids,
+                            &order_event_tx,
+                            &mut backoff,
+                            &health,
+                            capture.as_ref(),
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                if order_event_tx.is_closed() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("Coinbase full channel closed");
+                        health.disconnected("Full channel closed");
+                    }
+                    Err(e) => {
+                        error!("Coinbase full channel error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+
+                health.wait_to_reconnect(&mut backoff).await?;
+            }
+        });
+
+        Ok(vec![handle])
+    }
+}
+
+#[async_trait]
+impl AuthenticatedService for Coinbase {
+    async fn spawn_user_data_service(
+        &self,
+        pairs: &[TradingPair],
+        order_tx: Sender<OrderUpdate>,
+        balance_tx: Sender<BalanceUpdate>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let credentials = require_credentials(&self.credentials, "coinbase")?.clone();
+        credentials.require_passphrase("coinbase")?;
+        let product_ids = SymbolMapper::new().to_exchange_all(&Exchange::Coinbase, pairs)?;
+
+        info!(
+            "Starting Coinbase user channel for {}",
+            product_ids.join(", ")
+        );
+
+        let url = self.websocket_url();
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+        let proxy = self.proxy.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                let result = match connect_websocket(url, proxy.as_ref()).await {
+                    Ok((ws_stream, _)) => {
+                        Self::run_user_data_stream(
+                            ws_stream,
+                            &credentials,
+                            &product_ids,
+                            &order_tx,
+                            &balance_tx,
+                            &mut backoff,
+                            &health,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                if order_tx.is_closed() && balance_tx.is_closed() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("Coinbase user channel closed");
+                        health.disconnected("User channel closed");
+                    }
+                    // Rejected credentials will not start working on a retry
+                    Err(e @ AggregatorError::Authentication { .. }) => {
+                        error!("Coinbase user channel rejected: {}", e);
+                        health.disconnected(e.to_string());
+                        return Err(e);
+                    }
+                    Err(e) => {
+                        error!("Coinbase user channel error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+
+                health.wait_to_reconnect(&mut backoff).await?;
+            }
+        });
+
+        Ok(vec![handle])
+    }
+}
+
+#[async_trait]
+impl ExchangeInfoService for Coinbase {
+    async fn fetch_instruments(&self, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let client = http_client(self.proxy.as_ref())?;
+        let url = format!("{}/products", self.rest_url());
+        let body = get_body(&client, &url, None).await?;
+        Self::parse_instruments(&body, pairs)
+    }
+}
+
+#[async_trait]
+impl MarketDiscovery for Coinbase {
+    async fn list_markets(&self) -> Result<Vec<MarketListing>> {
+        let client = http_client(self.proxy.as_ref())?;
+        let url = format!("{}/products", self.rest_url());
+        let body = get_body(&client, &url, None).await?;
+        Self::parse_markets(&body)
+    }
+}
+
 impl Default for Coinbase {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,211 @@
+//! Conflation Module
+//! Coalesces bursts of depth updates for the same book into one `PriceLevelUpdate` per interval
+//! so a busy market cannot flood the channel to the aggregator
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::info;
+
+use crate::OrderBookService;
+use aggregator_core::{
+    Ask, Bid, Exchange, MarketType, OrderBookConfig, PriceLevelUpdate, Result, TradingPair,
+};
+
+type BookKey = (Exchange, String, Option<MarketType>);
+
+/// Merges updates per book until drained.
+///
+/// A level repeated in a later update replaces the earlier one, so the merged update carries the
+/// latest quantity of every price touched since the last drain, including removals at zero
+/// quantity. The id, timestamps, funding and market type come from the latest update.
+#[derive(Debug, Default)]
+pub struct Conflator {
+    pending: HashMap<BookKey, PriceLevelUpdate>,
+    // Books in the order their first pending update arrived
+    order: Vec<BookKey>,
+    merged: u64,
+}
+
+impl Conflator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `update`, merging it into the pending update for the same book if there is one.
+    pub fn push(&mut self, update: PriceLevelUpdate) {
+        let key = (
+            update.exchange.clone(),
+            update.symbol.clone(),
+            update.market_type.clone(),
+        );
+        let Some(pending) = self.pending.get_mut(&key) else {
+            self.order.push(key.clone());
+            self.pending.insert(key, update);
+            return;
+        };
+
+        for bid in update.bids {
+            merge_bid(&mut pending.bids, bid);
+        }
+        for ask in update.asks {
+            merge_ask(&mut pending.asks, ask);
+        }
+        pending.id = update.id;
+        pending.timestamp = update.timestamp;
+        pending.event_time = update.event_time.or(pending.event_time);
+        pending.funding = update.funding.or(pending.funding.take());
+        self.merged += 1;
+    }
+
+    /// Takes the pending updates, one per book, in the order the books first appeared.
+    pub fn drain(&mut self) -> Vec<PriceLevelUpdate> {
+        let mut pending = std::mem::take(&mut self.pending);
+        self.order
+            .drain(..)
+            .filter_map(|key| pending.remove(&key))
+            .collect()
+    }
+
+    /// Number of books with a pending update.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Total updates folded into an earlier pending one so far.
+    pub fn merged(&self) -> u64 {
+        self.merged
+    }
+}
+
+fn merge_bid(bids: &mut Vec<Bid>, bid: Bid) {
+    match bids.iter_mut().find(|level| level.price == bid.price) {
+        Some(level) => *level = bid,
+        None => bids.push(bid),
+    }
+}
+
+fn merge_ask(asks: &mut Vec<Ask>, ask: Ask) {
+    match asks.iter_mut().find(|level| level.price == ask.price) {
+        Some(level) => *level = ask,
+        None => asks.push(ask),
+    }
+}
+
+/// Wraps an order book connector so it emits at most one update per book every `interval`.
+///
+/// Updates from the wrapped connector are merged by a `Conflator` and flushed on each tick. A zero
+/// interval disables conflation and passes updates straight through.
+#[derive(Debug, Clone)]
+pub struct Conflated<S> {
+    inner: S,
+    interval: Duration,
+}
+
+impl<S> Conflated<S> {
+    pub fn new(inner: S, interval: Duration) -> Self {
+        Self { inner, interval }
+    }
+
+    /// Conflates over `order_book.update_interval` milliseconds.
+    pub fn from_config(inner: S, order_book: &OrderBookConfig) -> Self {
+        Self::new(inner, Duration::from_millis(order_book.update_interval))
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Spawn the task merging updates from `update_rx` and flushing them to `price_level_tx`.
+    /// Pending updates are flushed once every sender of `update_rx` is gone.
+    fn spawn_conflation(
+        interval: Duration,
+        mut update_rx: Receiver<PriceLevelUpdate>,
+        price_level_tx: Sender<PriceLevelUpdate>,
+    ) -> JoinHandle<Result<()>> {
+        tokio::spawn(async move {
+            let mut conflator = Conflator::new();
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    update = update_rx.recv() => match update {
+                        Some(update) => conflator.push(update),
+                        None => break,
+                    },
+                    _ = ticker.tick() => {
+                        for update in conflator.drain() {
+                            if price_level_tx.send(update).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+            }
+
+            for update in conflator.drain() {
+                if price_level_tx.send(update).await.is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl<S: OrderBookService + Send + Sync> OrderBookService for Conflated<S> {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        if self.interval.is_zero() {
+            return self
+                .inner
+                .spawn_order_book_service(
+                    pairs,
+                    order_book_depth,
+                    exchange_stream_buffer,
+                    price_level_tx,
+                    shutdown,
+                )
+                .await;
+        }
+
+        info!("Conflating order book updates every {:?}", self.interval);
+        let (update_tx, update_rx) = tokio::sync::mpsc::channel(exchange_stream_buffer);
+        let mut handles = self
+            .inner
+            .spawn_order_book_service(
+                pairs,
+                order_book_depth,
+                exchange_stream_buffer,
+                update_tx,
+                shutdown,
+            )
+            .await?;
+        handles.push(Self::spawn_conflation(
+            self.interval,
+            update_rx,
+            price_level_tx,
+        ));
+        Ok(handles)
+    }
+}
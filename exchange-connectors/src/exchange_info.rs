@@ -0,0 +1,90 @@
+//! Exchange Info Module
+//! Shared plumbing for fetching and matching instrument metadata over REST
+
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+
+use crate::rate_limit::RateLimiter;
+use crate::SymbolMapper;
+use aggregator_core::{AggregatorError, Exchange, InstrumentInfo, Result, TradingPair};
+
+/// Fetches the instrument listing at `url` and returns the response body.
+pub(crate) async fn get_body(
+    client: &reqwest::Client,
+    url: &str,
+    rate_limiter: Option<&RateLimiter>,
+) -> Result<String> {
+    if let Some(rate_limiter) = rate_limiter {
+        rate_limiter.try_acquire()?;
+    }
+
+    // Some exchanges reject requests without a user agent
+    let response = client
+        .get(url)
+        .header(reqwest::header::USER_AGENT, "aggre-gate")
+        .send()
+        .await
+        .map_err(|e| AggregatorError::network(format!("Failed to get instruments: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AggregatorError::network(format!(
+            "HTTP error: {}",
+            response.status()
+        )));
+    }
+
+    response
+        .text()
+        .await
+        .map_err(|e| AggregatorError::network(format!("Failed to read instruments: {}", e)))
+}
+
+/// Deserializes an instrument listing body as `T`.
+pub(crate) fn parse_body<T: DeserializeOwned>(body: &str, data_type: &str) -> Result<T> {
+    serde_json::from_str(body).map_err(|e| {
+        AggregatorError::parsing(data_type, format!("Failed to parse {}: {}", data_type, e))
+    })
+}
+
+/// Maps the native symbol of each requested pair back to the pair.
+pub(crate) fn requested_symbols(
+    exchange: &Exchange,
+    pairs: &[TradingPair],
+) -> Result<HashMap<String, TradingPair>> {
+    let symbols = SymbolMapper::new().to_exchange_all(exchange, pairs)?;
+    Ok(symbols.into_iter().zip(pairs.iter().cloned()).collect())
+}
+
+/// Fails when the exchange did not return metadata for every requested symbol.
+pub(crate) fn ensure_complete(
+    exchange: &Exchange,
+    requested: &HashMap<String, TradingPair>,
+    instruments: &[InstrumentInfo],
+) -> Result<()> {
+    let mut missing: Vec<&str> = requested
+        .keys()
+        .filter(|symbol| !instruments.iter().any(|info| &info.symbol == *symbol))
+        .map(String::as_str)
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    missing.sort_unstable();
+    Err(AggregatorError::validation(
+        "pairs",
+        format!("{} does not list {}", exchange, missing.join(", ")),
+    ))
+}
+
+/// Parses a decimal field reported as a string.
+pub(crate) fn decimal(value: &str, field: &str) -> Result<f64> {
+    value.parse().map_err(|_| {
+        AggregatorError::parsing("InstrumentInfo", format!("Invalid {}: {}", field, value))
+    })
+}
+
+/// The step size implied by a number of decimal places, e.g. 0.01 for 2.
+#[cfg(any(feature = "gateio", feature = "kraken"))]
+pub(crate) fn step_from_decimals(decimals: u32) -> f64 {
+    10f64.powi(-(decimals as i32))
+}
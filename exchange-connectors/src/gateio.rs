@@ -0,0 +1,674 @@
+//! Gate.io Exchange Connector
+//! Streams spot order books from Gate.io's v4 WebSocket API, seeded by REST depth snapshots
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::capture::Capture;
+use crate::exchange_info::{
+    decimal, ensure_complete, get_body, parse_body, requested_symbols, step_from_decimals,
+};
+use crate::proxy::{connect_websocket, http_client, Proxy};
+use crate::rate_limit::RateLimiter;
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::snapshot_sync::ForwardedBook;
+use crate::{ExchangeInfoService, OrderBookService, SymbolMapper};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, HealthEvent, InstrumentInfo,
+    MarketDiscovery, MarketListing, PriceLevelUpdate, RateLimitConfig, Result, TradingPair,
+};
+
+const GATEIO_WS_URL: &str = "wss://api.gateio.ws/ws/v4/";
+const GATEIO_ORDER_BOOK_URL: &str = "https://api.gateio.ws/api/v4/spot/order_book";
+const GATEIO_CURRENCY_PAIRS_URL: &str = "https://api.gateio.ws/api/v4/spot/currency_pairs";
+const TRADABLE_STATUS: &str = "tradable";
+const ORDER_BOOK_UPDATE_CHANNEL: &str = "spot.order_book_update";
+const PING_CHANNEL: &str = "spot.ping";
+const UPDATE_SPEED: &str = "100ms";
+const PING_INTERVAL: Duration = Duration::from_secs(15);
+const GET_ORDER_BOOK_SNAPSHOT: Vec<u8> = vec![];
+
+pub struct GateIo {
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    rate_limiter: RateLimiter,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+}
+
+/// An incremental depth update with the update id range it covers.
+#[derive(Debug, Clone)]
+pub struct GateIoDepthUpdate {
+    pub first_update_id: u64,
+    pub last_update_id: u64,
+    pub update: PriceLevelUpdate,
+}
+
+#[derive(Debug, Serialize)]
+struct GateIoRequest {
+    time: i64,
+    channel: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GateIoMessage {
+    channel: String,
+    event: String,
+    result: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GateIoOrderBookUpdate {
+    /// Update time in milliseconds
+    t: i64,
+    s: String,
+    #[serde(rename = "U")]
+    first_update_id: u64,
+    #[serde(rename = "u")]
+    last_update_id: u64,
+    #[serde(default)]
+    b: Vec<[String; 2]>,
+    #[serde(default)]
+    a: Vec<[String; 2]>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GateIoOrderBookSnapshot {
+    id: u64,
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+}
+
+/// A spot currency pair. Precisions are decimal places; minimums are absent for some pairs.
+#[derive(Debug, Deserialize)]
+struct GateIoCurrencyPair {
+    id: String,
+    #[serde(default)]
+    base: String,
+    #[serde(default)]
+    quote: String,
+    #[serde(default)]
+    trade_status: String,
+    precision: u32,
+    amount_precision: u32,
+    min_base_amount: Option<String>,
+    min_quote_amount: Option<String>,
+}
+
+impl GateIo {
+    pub fn new() -> Self {
+        Self {
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            rate_limiter: RateLimiter::new("gateio", &RateLimitConfig::default()),
+            proxy: None,
+            capture: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings. Gate.io has no public spot sandbox,
+    /// so `sandbox: true` is rejected rather than silently streaming production data.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        if config.sandbox {
+            return Err(AggregatorError::validation(
+                "sandbox",
+                "GateIo does not provide a sandbox environment",
+            ));
+        }
+        let mut connector = Self::new()
+            .with_rate_limit(&config.rate_limit)
+            .with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        connector.proxy = Proxy::from_config(config)?;
+        Ok(connector)
+    }
+
+    /// Limit REST snapshot requests according to `config`.
+    pub fn with_rate_limit(mut self, config: &RateLimitConfig) -> Self {
+        self.rate_limiter = RateLimiter::new("gateio", config);
+        self
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket and REST traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::GateIo, self.health_tx.clone())
+    }
+
+    /// Parse a `spot/currency_pairs` listing into the pairs open for trading, without volumes.
+    pub fn parse_markets(body: &str) -> Result<Vec<MarketListing>> {
+        let listing: Vec<GateIoCurrencyPair> = parse_body(body, "GateIoCurrencyPair")?;
+        Ok(listing
+            .into_iter()
+            .filter(|currency_pair| {
+                currency_pair.trade_status == TRADABLE_STATUS
+                    && !currency_pair.base.is_empty()
+                    && !currency_pair.quote.is_empty()
+            })
+            .map(|currency_pair| MarketListing {
+                exchange: Exchange::GateIo,
+                pair: TradingPair::new(&currency_pair.base, &currency_pair.quote),
+                symbol: currency_pair.id,
+                quote_volume: None,
+            })
+            .collect())
+    }
+
+    /// Parse a `spot/currency_pairs` listing into metadata for `pairs`.
+    pub fn parse_instruments(body: &str, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let requested = requested_symbols(&Exchange::GateIo, pairs)?;
+        let listing: Vec<GateIoCurrencyPair> = parse_body(body, "GateIoCurrencyPair")?;
+
+        let mut instruments = Vec::new();
+        for currency_pair in listing {
+            let Some(pair) = requested.get(&currency_pair.id) else {
+                continue;
+            };
+            instruments.push(InstrumentInfo {
+                exchange: Exchange::GateIo,
+                pair: pair.clone(),
+                tick_size: step_from_decimals(currency_pair.precision),
+                lot_size: step_from_decimals(currency_pair.amount_precision),
+                min_quantity: currency_pair
+                    .min_base_amount
+                    .as_deref()
+                    .map(|amount| decimal(amount, "min_base_amount"))
+                    .transpose()?
+                    .unwrap_or_default(),
+                min_notional: currency_pair
+                    .min_quote_amount
+                    .as_deref()
+                    .map(|amount| decimal(amount, "min_quote_amount"))
+                    .transpose()?,
+                symbol: currency_pair.id,
+            });
+        }
+
+        ensure_complete(&Exchange::GateIo, &requested, &instruments)?;
+        Ok(instruments)
+    }
+
+    /// Parse a raw `spot.order_book_update` message. Subscription acks, pongs and other channels
+    /// yield `None`.
+    pub fn parse_depth_update(message: &str) -> Result<Option<GateIoDepthUpdate>> {
+        let msg: GateIoMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("GateIoMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        if msg.channel != ORDER_BOOK_UPDATE_CHANNEL || msg.event != "update" {
+            return Ok(None);
+        }
+        let result = match msg.result {
+            Some(result) => result,
+            None => return Ok(None),
+        };
+
+        let update: GateIoOrderBookUpdate = serde_json::from_value(result).map_err(|e| {
+            AggregatorError::parsing(
+                "GateIoOrderBookUpdate",
+                format!("Failed to parse depth update: {}", e),
+            )
+        })?;
+        let event_time = DateTime::from_timestamp_millis(update.t);
+
+        Ok(Some(GateIoDepthUpdate {
+            first_update_id: update.first_update_id,
+            last_update_id: update.last_update_id,
+            update: Self::create_price_level_update(&update.s, &update.b, &update.a, event_time)?,
+        }))
+    }
+
+    fn create_price_level_update(
+        symbol: &str,
+        bids: &[[String; 2]],
+        asks: &[[String; 2]],
+        event_time: Option<DateTime<Utc>>,
+    ) -> Result<PriceLevelUpdate> {
+        let timestamp = Utc::now();
+        let bids = bids
+            .iter()
+            .map(|level| {
+                let (price, quantity) = Self::parse_price_level(level)?;
+                Ok(Bid {
+                    price,
+                    quantity,
+                    exchange: Exchange::GateIo,
+                    timestamp,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let asks = asks
+            .iter()
+            .map(|level| {
+                let (price, quantity) = Self::parse_price_level(level)?;
+                Ok(Ask {
+                    price,
+                    quantity,
+                    exchange: Exchange::GateIo,
+                    timestamp,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            symbol: symbol.to_string(),
+            exchange: Exchange::GateIo,
+            bids,
+            asks,
+            timestamp,
+            funding: None,
+            event_time,
+            market_type: None,
+        })
+    }
+
+    fn parse_price_level(level: &[String; 2]) -> Result<(f64, f64)> {
+        let price = level[0]
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("PriceLevel", format!("Invalid price: {}", e)))?;
+        let quantity = level[1].parse::<f64>().map_err(|e| {
+            AggregatorError::parsing("PriceLevel", format!("Invalid quantity: {}", e))
+        })?;
+        Ok((price, quantity))
+    }
+
+    fn request(channel: &str, event: Option<&str>, payload: Option<Vec<String>>) -> Result<String> {
+        let request = GateIoRequest {
+            time: Utc::now().timestamp(),
+            channel: channel.to_string(),
+            event: event.map(str::to_string),
+            payload,
+        };
+        serde_json::to_string(&request).map_err(AggregatorError::Serialization)
+    }
+
+    /// Spawn WebSocket stream for order book updates
+    fn spawn_websocket_stream(
+        &self,
+        symbols: Vec<String>,
+        exchange_stream_buffer: usize,
+        mut shutdown: Shutdown,
+    ) -> (Receiver<Message>, JoinHandle<Result<()>>) {
+        let (ws_tx, ws_rx) = tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+        let proxy = self.proxy.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                let result = Self::connect_websocket(
+                    &symbols,
+                    proxy.as_ref(),
+                    &ws_tx,
+                    &mut backoff,
+                    &health,
+                    &mut shutdown,
+                )
+                .await;
+                if shutdown.is_triggered() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("Gate.io WebSocket connection closed, reconnecting...");
+                        health.disconnected("WebSocket connection closed");
+                    }
+                    Err(e) => {
+                        error!("Gate.io WebSocket connection error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+                if ws_tx.is_closed() {
+                    return Ok(());
+                }
+
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
+            }
+        });
+
+        (ws_rx, handle)
+    }
+
+    async fn connect_websocket(
+        symbols: &[String],
+        proxy: Option<&Proxy>,
+        ws_tx: &Sender<Message>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        shutdown: &mut Shutdown,
+    ) -> Result<()> {
+        let (mut ws_stream, _) = connect_websocket(GATEIO_WS_URL, proxy).await?;
+
+        info!("Connected to Gate.io WebSocket");
+        backoff.reset();
+        health.connected();
+
+        // Gate.io takes one pair per order book subscription
+        for symbol in symbols {
+            let subscription = Self::request(
+                ORDER_BOOK_UPDATE_CHANNEL,
+                Some("subscribe"),
+                Some(vec![symbol.clone(), UPDATE_SPEED.to_string()]),
+            )?;
+            ws_stream
+                .send(Message::Text(subscription))
+                .await
+                .map_err(|e| {
+                    AggregatorError::network(format!("Failed to send subscription: {}", e))
+                })?;
+        }
+
+        // Signal to get fresh snapshots for every pair
+        if ws_tx
+            .send(Message::Binary(GET_ORDER_BOOK_SNAPSHOT))
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+        ping_interval.tick().await;
+        let mut watchdog = backoff.policy().watchdog();
+
+        loop {
+            tokio::select! {
+                msg = ws_stream.next() => {
+                    watchdog.touch();
+                    match msg {
+                        Some(Ok(message @ Message::Text(_))) => {
+                            // The processor is gone once the receiver is dropped
+                            if let Err(e) = ws_tx.send(message).await {
+                                warn!("Gate.io stream processor stopped: {}", e);
+                                return Ok(());
+                            }
+                        }
+                        Some(Ok(Message::Ping(payload))) => {
+                            if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                                error!("Failed to send pong: {}", e);
+                                return Ok(());
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => return Ok(()),
+                        Some(Err(e)) => {
+                            return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                        }
+                        _ => {}
+                    }
+                }
+                silent_for = watchdog.stalled() => return Err(health.stalled(silent_for)),
+                _ = shutdown.recv() => {
+                    info!("Closing Gate.io order book stream");
+                    let _ = ws_stream.close(None).await;
+                    return Ok(());
+                }
+                _ = ping_interval.tick() => {
+                    let ping = Self::request(PING_CHANNEL, None, None)?;
+                    if let Err(e) = ws_stream.send(Message::Text(ping)).await {
+                        error!("Failed to send ping: {}", e);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Spawn stream processor that applies the snapshot/update sync rules per pair
+    fn spawn_stream_processor(
+        &self,
+        symbols: Vec<String>,
+        order_book_depth: usize,
+        mut ws_rx: Receiver<Message>,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        client: reqwest::Client,
+    ) -> JoinHandle<Result<()>> {
+        let rate_limiter = self.rate_limiter.clone();
+        let capture = self.capture.clone();
+
+        tokio::spawn(async move {
+            // Last applied update id per pair; a missing entry means the pair needs a snapshot
+            let mut last_update_ids: HashMap<String, u64> = HashMap::new();
+            let mut forwarded: HashMap<String, ForwardedBook> = HashMap::new();
+
+            while let Some(message) = ws_rx.recv().await {
+                match message {
+                    Message::Text(text) => {
+                        if let Some(capture) = &capture {
+                            capture.record(&Exchange::GateIo, &text);
+                        }
+                        let depth = match Self::parse_depth_update(&text) {
+                            Ok(Some(depth)) => depth,
+                            Ok(None) => continue,
+                            Err(e) => {
+                                error!("Failed to process depth update: {}", e);
+                                continue;
+                            }
+                        };
+                        let symbol = depth.update.symbol.clone();
+
+                        let last_update_id = match last_update_ids.get(&symbol) {
+                            Some(last_update_id) => *last_update_id,
+                            None => continue,
+                        };
+                        if depth.last_update_id <= last_update_id {
+                            continue;
+                        }
+                        if depth.first_update_id > last_update_id + 1 {
+                            warn!("Gate.io update gap for {}, refreshing snapshot", symbol);
+                            last_update_ids.remove(&symbol);
+                            Self::refresh_snapshot(
+                                &client,
+                                &symbol,
+                                order_book_depth,
+                                &rate_limiter,
+                                &price_level_tx,
+                                &mut last_update_ids,
+                                forwarded.entry(symbol.clone()).or_default(),
+                            )
+                            .await;
+                            continue;
+                        }
+
+                        forwarded
+                            .entry(symbol.clone())
+                            .or_default()
+                            .record(&depth.update);
+                        if price_level_tx.send(depth.update).await.is_err() {
+                            return Ok(());
+                        }
+                        last_update_ids.insert(symbol, depth.last_update_id);
+                    }
+                    Message::Binary(data) if data.is_empty() => {
+                        last_update_ids.clear();
+                        for symbol in &symbols {
+                            Self::refresh_snapshot(
+                                &client,
+                                symbol,
+                                order_book_depth,
+                                &rate_limiter,
+                                &price_level_tx,
+                                &mut last_update_ids,
+                                forwarded.entry(symbol.clone()).or_default(),
+                            )
+                            .await;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Fetches a snapshot for `symbol` and forwards it, with removals for the levels of
+    /// `forwarded` it no longer has.
+    async fn refresh_snapshot(
+        client: &reqwest::Client,
+        symbol: &str,
+        order_book_depth: usize,
+        rate_limiter: &RateLimiter,
+        price_level_tx: &Sender<PriceLevelUpdate>,
+        last_update_ids: &mut HashMap<String, u64>,
+        forwarded: &mut ForwardedBook,
+    ) {
+        info!("Getting Gate.io order book snapshot for {}", symbol);
+
+        let snapshot =
+            match Self::get_order_book_snapshot(client, symbol, order_book_depth, rate_limiter)
+                .await
+            {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    error!("Failed to get snapshot for {}: {}", symbol, e);
+                    return;
+                }
+            };
+
+        match Self::create_price_level_update(symbol, &snapshot.bids, &snapshot.asks, None) {
+            Ok(mut update) => {
+                forwarded.replace(&mut update);
+                if price_level_tx.send(update).await.is_ok() {
+                    last_update_ids.insert(symbol.to_string(), snapshot.id);
+                }
+            }
+            Err(e) => error!("Failed to process snapshot for {}: {}", symbol, e),
+        }
+    }
+
+    /// Get order book snapshot from REST API
+    async fn get_order_book_snapshot(
+        client: &reqwest::Client,
+        symbol: &str,
+        order_book_depth: usize,
+        rate_limiter: &RateLimiter,
+    ) -> Result<GateIoOrderBookSnapshot> {
+        rate_limiter.try_acquire()?;
+
+        let limit = order_book_depth.to_string();
+        let response = client
+            .get(GATEIO_ORDER_BOOK_URL)
+            .query(&[
+                ("currency_pair", symbol),
+                ("limit", limit.as_str()),
+                ("with_id", "true"),
+            ])
+            .send()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to get snapshot: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(AggregatorError::rate_limit(
+                "gateio",
+                "Snapshot request rejected with HTTP 429",
+            ));
+        }
+
+        if !response.status().is_success() {
+            return Err(AggregatorError::network(format!(
+                "HTTP error: {}",
+                response.status()
+            )));
+        }
+
+        response.json().await.map_err(|e| {
+            AggregatorError::parsing(
+                "GateIoOrderBookSnapshot",
+                format!("Failed to parse snapshot: {}", e),
+            )
+        })
+    }
+}
+
+#[async_trait]
+impl OrderBookService for GateIo {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let symbols = SymbolMapper::new().to_exchange_all(&Exchange::GateIo, pairs)?;
+        info!(
+            "Starting Gate.io order book service for {}",
+            symbols.join(", ")
+        );
+
+        let client = http_client(self.proxy.as_ref())?;
+        let (ws_rx, ws_handle) = self.spawn_websocket_stream(
+            symbols.clone(),
+            exchange_stream_buffer,
+            Shutdown::new(shutdown),
+        );
+        let processor_handle =
+            self.spawn_stream_processor(symbols, order_book_depth, ws_rx, price_level_tx, client);
+
+        Ok(vec![ws_handle, processor_handle])
+    }
+}
+
+#[async_trait]
+impl ExchangeInfoService for GateIo {
+    async fn fetch_instruments(&self, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let client = http_client(self.proxy.as_ref())?;
+        let body = get_body(&client, GATEIO_CURRENCY_PAIRS_URL, Some(&self.rate_limiter)).await?;
+        Self::parse_instruments(&body, pairs)
+    }
+}
+
+#[async_trait]
+impl MarketDiscovery for GateIo {
+    async fn list_markets(&self) -> Result<Vec<MarketListing>> {
+        let client = http_client(self.proxy.as_ref())?;
+        let body = get_body(&client, GATEIO_CURRENCY_PAIRS_URL, Some(&self.rate_limiter)).await?;
+        Self::parse_markets(&body)
+    }
+}
+
+impl Default for GateIo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,456 @@
+//! Hyperliquid Exchange Connector
+//! Streams perpetual order books from the on-chain exchange's public `l2Book` WebSocket feed.
+//! Every message is a full snapshot of the top levels, so consecutive snapshots are diffed into
+//! deltas with removed levels reported at zero quantity.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::capture::Capture;
+use crate::proxy::{connect_websocket, Proxy};
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::{OrderBookService, SymbolMapper};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, HealthEvent, MarketType, PriceLevelUpdate,
+    Result, TradingPair,
+};
+
+const HYPERLIQUID_WS_URL: &str = "wss://api.hyperliquid.xyz/ws";
+const HYPERLIQUID_TESTNET_WS_URL: &str = "wss://api.hyperliquid-testnet.xyz/ws";
+const L2_BOOK_CHANNEL: &str = "l2Book";
+/// The server drops connections that send nothing for a minute
+const PING_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(30);
+
+pub struct Hyperliquid {
+    websocket_url: String,
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+}
+
+/// Turns `l2Book` snapshots into updates, keeping the previous snapshot per coin so levels that
+/// disappeared are emitted with a zero quantity.
+#[derive(Debug, Default)]
+pub struct HyperliquidBookParser {
+    depth: Option<usize>,
+    previous: HashMap<String, PriceLevelUpdate>,
+}
+
+#[derive(Debug, Serialize)]
+struct HyperliquidSubscription {
+    method: String,
+    subscription: HyperliquidSubscriptionKind,
+}
+
+#[derive(Debug, Serialize)]
+struct HyperliquidSubscriptionKind {
+    #[serde(rename = "type")]
+    kind: String,
+    coin: String,
+}
+
+#[derive(Debug, Serialize)]
+struct HyperliquidPing {
+    method: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct HyperliquidMessage {
+    channel: String,
+    #[serde(default)]
+    data: Value,
+}
+
+/// A book snapshot. `levels` holds the bids then the asks, best first.
+#[derive(Debug, Deserialize)]
+struct HyperliquidBook {
+    coin: String,
+    time: i64,
+    levels: [Vec<HyperliquidLevel>; 2],
+}
+
+#[derive(Debug, Deserialize)]
+struct HyperliquidLevel {
+    px: String,
+    sz: String,
+}
+
+impl HyperliquidBookParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `depth` levels per side of each snapshot.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Handles one raw message. Book snapshots yield a `PriceLevelUpdate` against the previous
+    /// snapshot of the coin; subscription acks and pongs yield `None`.
+    pub fn handle_message(&mut self, message: &str) -> Result<Option<PriceLevelUpdate>> {
+        let msg: HyperliquidMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing(
+                "HyperliquidMessage",
+                format!("Failed to parse message: {}", e),
+            )
+        })?;
+
+        match msg.channel.as_str() {
+            L2_BOOK_CHANNEL => {}
+            "error" => {
+                return Err(AggregatorError::exchange(
+                    "hyperliquid",
+                    msg.data.as_str().unwrap_or_default(),
+                ))
+            }
+            _ => return Ok(None),
+        }
+
+        let book: HyperliquidBook = serde_json::from_value(msg.data).map_err(|e| {
+            AggregatorError::parsing("HyperliquidBook", format!("Failed to parse book: {}", e))
+        })?;
+        let [bid_levels, ask_levels] = &book.levels;
+        let timestamp = Utc::now();
+
+        let mut bids = Vec::new();
+        for level in bid_levels.iter().take(self.depth.unwrap_or(usize::MAX)) {
+            let (price, quantity) = Self::parse_level(level)?;
+            bids.push(Bid {
+                price,
+                quantity,
+                exchange: Exchange::Hyperliquid,
+                timestamp,
+            });
+        }
+        let mut asks = Vec::new();
+        for level in ask_levels.iter().take(self.depth.unwrap_or(usize::MAX)) {
+            let (price, quantity) = Self::parse_level(level)?;
+            asks.push(Ask {
+                price,
+                quantity,
+                exchange: Exchange::Hyperliquid,
+                timestamp,
+            });
+        }
+
+        let snapshot = PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            symbol: book.coin,
+            exchange: Exchange::Hyperliquid,
+            bids,
+            asks,
+            timestamp,
+            funding: None,
+            event_time: DateTime::from_timestamp_millis(book.time),
+            market_type: Some(MarketType::Futures),
+        };
+
+        let mut update = snapshot.clone();
+        if let Some(previous) = self.previous.insert(snapshot.symbol.clone(), snapshot) {
+            update.mark_removed_levels(&previous);
+        }
+        Ok(Some(update))
+    }
+
+    fn parse_level(level: &HyperliquidLevel) -> Result<(f64, f64)> {
+        let price = level
+            .px
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("PriceLevel", format!("Invalid price: {}", e)))?;
+        let quantity = level.sz.parse::<f64>().map_err(|e| {
+            AggregatorError::parsing("PriceLevel", format!("Invalid quantity: {}", e))
+        })?;
+        Ok((price, quantity))
+    }
+}
+
+impl Hyperliquid {
+    pub fn new() -> Self {
+        Self {
+            websocket_url: HYPERLIQUID_WS_URL.to_string(),
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            proxy: None,
+            capture: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings, using the Hyperliquid testnet when
+    /// `sandbox` is set.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        let mut connector = Self::new()
+            .with_sandbox(config.sandbox)
+            .with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        connector.proxy = Proxy::from_config(config)?;
+        Ok(connector)
+    }
+
+    /// Stream from the Hyperliquid testnet instead of mainnet.
+    pub fn with_sandbox(mut self, sandbox: bool) -> Self {
+        self.websocket_url = if sandbox {
+            HYPERLIQUID_TESTNET_WS_URL
+        } else {
+            HYPERLIQUID_WS_URL
+        }
+        .to_string();
+        self
+    }
+
+    /// Stream from a custom endpoint, such as a local mock server.
+    pub fn with_websocket_url(mut self, websocket_url: &str) -> Self {
+        self.websocket_url = websocket_url.to_string();
+        self
+    }
+
+    pub fn websocket_url(&self) -> &str {
+        &self.websocket_url
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::Hyperliquid, self.health_tx.clone())
+    }
+
+    /// Builds the `l2Book` subscription for `coin`.
+    pub fn subscription(coin: &str) -> Result<String> {
+        let subscription = HyperliquidSubscription {
+            method: "subscribe".to_string(),
+            subscription: HyperliquidSubscriptionKind {
+                kind: L2_BOOK_CHANNEL.to_string(),
+                coin: coin.to_string(),
+            },
+        };
+        serde_json::to_string(&subscription).map_err(AggregatorError::Serialization)
+    }
+
+    /// Spawn WebSocket stream for `l2Book` channels
+    fn spawn_websocket_stream(
+        &self,
+        coins: Vec<String>,
+        exchange_stream_buffer: usize,
+        mut shutdown: Shutdown,
+    ) -> (Receiver<Message>, JoinHandle<Result<()>>) {
+        let (ws_tx, ws_rx) = tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
+        let url = self.websocket_url.clone();
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+        let proxy = self.proxy.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                let result = Self::connect_websocket(
+                    &url,
+                    &coins,
+                    proxy.as_ref(),
+                    &ws_tx,
+                    &mut backoff,
+                    &health,
+                    &mut shutdown,
+                )
+                .await;
+                if shutdown.is_triggered() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("Hyperliquid WebSocket connection closed, reconnecting...");
+                        health.disconnected("WebSocket connection closed");
+                    }
+                    Err(e) => {
+                        error!("Hyperliquid WebSocket connection error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+                if ws_tx.is_closed() {
+                    return Ok(());
+                }
+
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
+            }
+        });
+
+        (ws_rx, handle)
+    }
+
+    async fn connect_websocket(
+        url: &str,
+        coins: &[String],
+        proxy: Option<&Proxy>,
+        ws_tx: &Sender<Message>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        shutdown: &mut Shutdown,
+    ) -> Result<()> {
+        let (mut ws_stream, _) = connect_websocket(url, proxy).await?;
+
+        info!("Connected to Hyperliquid WebSocket");
+        backoff.reset();
+        health.connected();
+
+        for coin in coins {
+            ws_stream
+                .send(Message::Text(Self::subscription(coin)?))
+                .await
+                .map_err(|e| {
+                    AggregatorError::network(format!("Failed to send subscription: {}", e))
+                })?;
+        }
+
+        let ping = serde_json::to_string(&HyperliquidPing {
+            method: "ping".to_string(),
+        })
+        .map_err(AggregatorError::Serialization)?;
+        let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+        ping_interval.tick().await;
+
+        let mut watchdog = backoff.policy().watchdog();
+        loop {
+            let msg = tokio::select! {
+                msg = ws_stream.next() => match msg {
+                    Some(msg) => msg,
+                    None => return Ok(()),
+                },
+                // Pings keep quiet books alive and their pongs feed the watchdog
+                _ = ping_interval.tick() => {
+                    if let Err(e) = ws_stream.send(Message::Text(ping.clone())).await {
+                        error!("Failed to send ping: {}", e);
+                        return Ok(());
+                    }
+                    continue;
+                }
+                silent_for = watchdog.stalled() => return Err(health.stalled(silent_for)),
+                _ = shutdown.recv() => {
+                    info!("Closing Hyperliquid order book stream");
+                    let _ = ws_stream.close(None).await;
+                    return Ok(());
+                }
+            };
+            watchdog.touch();
+
+            match msg {
+                Ok(Message::Text(text)) => {
+                    if let Err(e) = ws_tx.send(Message::Text(text)).await {
+                        warn!("Hyperliquid stream processor stopped: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Ping(payload)) => {
+                    if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                        error!("Failed to send pong: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Err(e) => {
+                    return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Spawn stream processor that diffs snapshots into updates
+    fn spawn_stream_processor(
+        mut ws_rx: Receiver<Message>,
+        order_book_depth: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        capture: Option<Capture>,
+    ) -> JoinHandle<Result<()>> {
+        tokio::spawn(async move {
+            let mut parser = HyperliquidBookParser::new().with_depth(order_book_depth);
+
+            while let Some(message) = ws_rx.recv().await {
+                if let Message::Text(text) = message {
+                    if let Some(capture) = &capture {
+                        capture.record(&Exchange::Hyperliquid, &text);
+                    }
+                    match parser.handle_message(&text) {
+                        Ok(Some(update)) => {
+                            if price_level_tx.send(update).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => error!("Failed to process book message: {}", e),
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl OrderBookService for Hyperliquid {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let coins = SymbolMapper::new().to_exchange_all(&Exchange::Hyperliquid, pairs)?;
+        info!(
+            "Starting Hyperliquid order book service for {}",
+            coins.join(", ")
+        );
+
+        let (ws_rx, ws_handle) =
+            self.spawn_websocket_stream(coins, exchange_stream_buffer, Shutdown::new(shutdown));
+        let processor_handle = Self::spawn_stream_processor(
+            ws_rx,
+            order_book_depth,
+            price_level_tx,
+            self.capture.clone(),
+        );
+
+        Ok(vec![ws_handle, processor_handle])
+    }
+}
+
+impl Default for Hyperliquid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
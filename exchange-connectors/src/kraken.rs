@@ -2,10 +2,13 @@
 //! Handles WebSocket connections and order book streaming for Kraken
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::task::JoinHandle;
 use tokio_tungstenite::tungstenite::Message;
@@ -13,13 +16,29 @@ use tracing::{error, info, warn};
 use url::Url;
 use uuid::Uuid;
 
-use crate::OrderBookService;
-use aggregator_core::{AggregatorError, Ask, Bid, Exchange, PriceLevelUpdate, Result};
+use crate::capture::Capture;
+use crate::exchange_info::{
+    decimal, ensure_complete, get_body, parse_body, requested_symbols, step_from_decimals,
+};
+use crate::proxy::{connect_websocket, http_client, Proxy};
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::snapshot_sync::ForwardedBook;
+use crate::trade_stream::{spawn_trade_stream, TradeStreamSpec};
+use crate::{ExchangeInfoService, OrderBookService, SymbolMapper, TradeStreamService};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, HealthEvent, InstrumentInfo,
+    MarketDiscovery, MarketListing, PriceLevelUpdate, Result, Trade, TradeSide, TradingPair,
+};
 
 const KRAKEN_WS_URL: &str = "wss://ws.kraken.com";
+const KRAKEN_ASSET_PAIRS_URL: &str = "https://api.kraken.com/0/public/AssetPairs";
+const ONLINE_STATUS: &str = "online";
 
 pub struct Kraken {
     pub config: KrakenConfig,
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    capture: Option<Capture>,
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +46,8 @@ pub struct KrakenConfig {
     pub websocket_url: String,
     pub reconnect_interval: u64,
     pub ping_interval: u64,
+    /// Proxy for WebSocket and REST connections, if any.
+    pub proxy: Option<Proxy>,
 }
 
 impl Default for KrakenConfig {
@@ -35,6 +56,7 @@ impl Default for KrakenConfig {
             websocket_url: KRAKEN_WS_URL.to_string(),
             reconnect_interval: 5000,
             ping_interval: 20000,
+            proxy: None,
         }
     }
 }
@@ -49,9 +71,31 @@ struct KrakenSubscription {
 #[derive(Debug, Serialize)]
 struct SubscriptionDetails {
     name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth: Option<usize>,
+}
+
+/// Pairs and depth of the book channel, resubscribed on every connection.
+#[derive(Debug, Clone)]
+struct BookSubscription {
+    symbols: Vec<String>,
     depth: usize,
 }
 
+impl BookSubscription {
+    fn request(&self, event: &str, pairs: Vec<String>) -> Result<String> {
+        let subscription = KrakenSubscription {
+            event: event.to_string(),
+            pair: pairs,
+            subscription: SubscriptionDetails {
+                name: "book".to_string(),
+                depth: Some(self.depth),
+            },
+        };
+        serde_json::to_string(&subscription).map_err(AggregatorError::Serialization)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct KrakenMessage {
     #[serde(flatten)]
@@ -65,44 +109,399 @@ enum KrakenData {
     Book(Vec<Value>),
 }
 
+#[derive(Debug, Deserialize)]
+struct KrakenAssetPairsResponse {
+    error: Vec<String>,
+    #[serde(default)]
+    result: HashMap<String, KrakenAssetPair>,
+}
+
+/// A tradable pair. Dark pool pairs carry no `wsname` and are skipped. `status` is missing
+/// from older responses, which only listed pairs open for trading.
+#[derive(Debug, Deserialize)]
+struct KrakenAssetPair {
+    wsname: Option<String>,
+    status: Option<String>,
+    tick_size: Option<String>,
+    pair_decimals: u32,
+    lot_decimals: u32,
+    ordermin: Option<String>,
+    costmin: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct KrakenOrderBookSnapshot {
     #[serde(rename = "as")]
-    asks: Vec<[String; 3]>,
+    asks: Vec<Vec<String>>,
     #[serde(rename = "bs")]
-    bids: Vec<[String; 3]>,
+    bids: Vec<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct KrakenOrderBookUpdate {
     #[serde(rename = "a")]
-    asks: Option<Vec<[String; 3]>>,
+    asks: Option<Vec<Vec<String>>>,
     #[serde(rename = "b")]
-    bids: Option<Vec<[String; 3]>>,
+    bids: Option<Vec<Vec<String>>>,
+    #[serde(rename = "c")]
+    checksum: Option<String>,
+}
+
+/// Price key ordered numerically, keeping the exact price string Kraken sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PriceKey(f64);
+
+impl Eq for PriceKey {}
+
+impl PartialOrd for PriceKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PriceKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Local mirror of a Kraken book subscription, kept to validate the feed's CRC32 checksums.
+///
+/// Levels are stored as the exact `[price, volume]` strings received, since the checksum is
+/// computed over Kraken's formatting rather than over parsed numbers.
+#[derive(Debug, Clone, Default)]
+pub struct KrakenBook {
+    depth: usize,
+    asks: BTreeMap<PriceKey, (String, String)>,
+    bids: BTreeMap<Reverse<PriceKey>, (String, String)>,
+}
+
+impl KrakenBook {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            ..Default::default()
+        }
+    }
+
+    /// Replaces the book with a snapshot's `[price, volume, timestamp]` levels.
+    pub fn apply_snapshot(&mut self, asks: &[Vec<String>], bids: &[Vec<String>]) -> Result<()> {
+        self.asks.clear();
+        self.bids.clear();
+        self.apply_update(asks, bids).map(|_| ())
+    }
+
+    /// Applies incremental levels. A zero volume removes the level; the book is then truncated
+    /// to the subscribed depth as Kraken requires.
+    ///
+    /// Returns the prices of the `(asks, bids)` truncated away. Kraken sends no removal for
+    /// them, so they have to be removed downstream too.
+    pub fn apply_update(
+        &mut self,
+        asks: &[Vec<String>],
+        bids: &[Vec<String>],
+    ) -> Result<(Vec<f64>, Vec<f64>)> {
+        for level in asks {
+            let (key, price, volume, remove) = Self::parse_level(level)?;
+            if remove {
+                self.asks.remove(&key);
+            } else {
+                self.asks.insert(key, (price, volume));
+            }
+        }
+        for level in bids {
+            let (key, price, volume, remove) = Self::parse_level(level)?;
+            if remove {
+                self.bids.remove(&Reverse(key));
+            } else {
+                self.bids.insert(Reverse(key), (price, volume));
+            }
+        }
+
+        let mut truncated_asks = Vec::new();
+        while self.asks.len() > self.depth {
+            truncated_asks.extend(self.asks.pop_last().map(|(PriceKey(price), _)| price));
+        }
+        let mut truncated_bids = Vec::new();
+        while self.bids.len() > self.depth {
+            truncated_bids.extend(
+                self.bids
+                    .pop_last()
+                    .map(|(Reverse(PriceKey(price)), _)| price),
+            );
+        }
+        Ok((truncated_asks, truncated_bids))
+    }
+
+    /// Computes Kraken's checksum: CRC32 over the top 10 asks (best first) followed by the top
+    /// 10 bids (best first), each level written as price then volume with the decimal point and
+    /// leading zeros removed.
+    pub fn checksum(&self) -> u32 {
+        let mut payload = String::new();
+        for (price, volume) in self
+            .asks
+            .values()
+            .take(10)
+            .chain(self.bids.values().take(10))
+        {
+            payload.push_str(&Self::checksum_field(price));
+            payload.push_str(&Self::checksum_field(volume));
+        }
+        crc32fast::hash(payload.as_bytes())
+    }
+
+    /// Returns `true` if `expected` (as sent in the `c` field) matches the local book.
+    pub fn verify(&self, expected: &str) -> bool {
+        expected
+            .parse::<u32>()
+            .map(|expected| expected == self.checksum())
+            .unwrap_or(false)
+    }
+
+    fn checksum_field(value: &str) -> String {
+        value.replace('.', "").trim_start_matches('0').to_string()
+    }
+
+    fn parse_level(level: &[String]) -> Result<(PriceKey, String, String, bool)> {
+        if level.len() < 2 {
+            return Err(AggregatorError::parsing(
+                "PriceLevel",
+                "Price level has too few fields",
+            ));
+        }
+        let price = level[0]
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("PriceLevel", format!("Invalid price: {}", e)))?;
+        let volume = level[1].parse::<f64>().map_err(|e| {
+            AggregatorError::parsing("PriceLevel", format!("Invalid quantity: {}", e))
+        })?;
+        Ok((
+            PriceKey(price),
+            level[0].clone(),
+            level[1].clone(),
+            volume == 0.0,
+        ))
+    }
 }
 
 impl Kraken {
     pub fn new() -> Self {
+        Self::with_config(KrakenConfig::default())
+    }
+
+    pub fn with_config(config: KrakenConfig) -> Self {
+        let reconnect_policy = ReconnectPolicy::default().with_initial_delay(
+            tokio::time::Duration::from_millis(config.reconnect_interval),
+        );
         Self {
-            config: KrakenConfig::default(),
+            config,
+            reconnect_policy,
+            health_tx: None,
+            capture: None,
         }
     }
 
-    pub fn with_config(config: KrakenConfig) -> Self {
-        Self { config }
+    /// Builds a connector from the shared exchange settings. Kraken has no public sandbox, so
+    /// `sandbox: true` is rejected rather than silently streaming production data.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        if config.sandbox {
+            return Err(AggregatorError::validation(
+                "sandbox",
+                "Kraken does not provide a sandbox environment",
+            ));
+        }
+        Ok(Self::with_config(KrakenConfig {
+            reconnect_interval: config.websocket.reconnect_interval,
+            proxy: Proxy::from_config(config)?,
+            ..KrakenConfig::default()
+        })
+        .with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket)))
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket and REST traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::Kraken, self.health_tx.clone())
     }
 
-    fn format_symbol(&self, pair: [&str; 2]) -> String {
-        format!("{}/{}", pair[0].to_uppercase(), pair[1].to_uppercase())
+    fn format_symbols(&self, pairs: &[TradingPair]) -> Result<Vec<String>> {
+        SymbolMapper::new().to_exchange_all(&Exchange::Kraken, pairs)
     }
 
-    fn parse_price_level(&self, level: &[String; 3]) -> Result<(f64, f64)> {
+    /// Parse an `AssetPairs` response into the pairs open for trading, without volumes, keyed
+    /// on the WebSocket pair name. Pairs whose name cannot be mapped back are skipped.
+    pub fn parse_markets(body: &str) -> Result<Vec<MarketListing>> {
+        let mapper = SymbolMapper::new();
+        let mut markets: Vec<MarketListing> = Self::parse_asset_pairs(body)?
+            .into_values()
+            .filter(|asset_pair| {
+                asset_pair
+                    .status
+                    .as_deref()
+                    .is_none_or(|status| status == ONLINE_STATUS)
+            })
+            .filter_map(|asset_pair| {
+                let wsname = asset_pair.wsname?;
+                Some(MarketListing {
+                    exchange: Exchange::Kraken,
+                    pair: mapper.from_exchange(&Exchange::Kraken, &wsname).ok()?,
+                    symbol: wsname,
+                    quote_volume: None,
+                })
+            })
+            .collect();
+        // `result` is a map, so its order is not the response's
+        markets.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Ok(markets)
+    }
+
+    fn parse_asset_pairs(body: &str) -> Result<HashMap<String, KrakenAssetPair>> {
+        let response: KrakenAssetPairsResponse = parse_body(body, "KrakenAssetPairsResponse")?;
+        if !response.error.is_empty() {
+            return Err(AggregatorError::exchange(
+                "kraken",
+                format!("Kraken API error: {}", response.error.join(", ")),
+            ));
+        }
+        Ok(response.result)
+    }
+
+    /// Parse an `AssetPairs` response into metadata for `pairs`, matching on the WebSocket pair
+    /// name. Pairs without a `tick_size` fall back to `pair_decimals`.
+    pub fn parse_instruments(body: &str, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let requested = requested_symbols(&Exchange::Kraken, pairs)?;
+
+        let mut instruments = Vec::new();
+        for asset_pair in Self::parse_asset_pairs(body)?.into_values() {
+            let Some(wsname) = asset_pair.wsname else {
+                continue;
+            };
+            let Some(pair) = requested.get(&wsname) else {
+                continue;
+            };
+            instruments.push(InstrumentInfo {
+                exchange: Exchange::Kraken,
+                pair: pair.clone(),
+                symbol: wsname,
+                tick_size: match asset_pair.tick_size {
+                    Some(tick_size) => decimal(&tick_size, "tick_size")?,
+                    None => step_from_decimals(asset_pair.pair_decimals),
+                },
+                lot_size: step_from_decimals(asset_pair.lot_decimals),
+                min_quantity: asset_pair
+                    .ordermin
+                    .as_deref()
+                    .map(|amount| decimal(amount, "ordermin"))
+                    .transpose()?
+                    .unwrap_or_default(),
+                min_notional: asset_pair
+                    .costmin
+                    .as_deref()
+                    .map(|cost| decimal(cost, "costmin"))
+                    .transpose()?,
+            });
+        }
+
+        ensure_complete(&Exchange::Kraken, &requested, &instruments)?;
+        Ok(instruments)
+    }
+
+    /// Parse a raw `trade` channel message into trades. Events and other channels yield no trades.
+    ///
+    /// Kraken does not assign trade ids on this feed, so the trade time is used instead.
+    pub fn parse_trades(message: &str) -> Result<Vec<Trade>> {
+        let value: Value = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("KrakenMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        let arr = match value.as_array() {
+            Some(arr) if arr.len() >= 4 && arr[arr.len() - 2] == "trade" => arr,
+            _ => return Ok(vec![]),
+        };
+        let symbol = arr[arr.len() - 1].as_str().unwrap_or_default().to_string();
+        let entries: Vec<Vec<String>> = serde_json::from_value(arr[1].clone()).map_err(|e| {
+            AggregatorError::parsing("KrakenTrade", format!("Failed to parse trades: {}", e))
+        })?;
+
+        entries
+            .into_iter()
+            .map(|entry| {
+                if entry.len() < 4 {
+                    return Err(AggregatorError::parsing(
+                        "KrakenTrade",
+                        "Trade entry has too few fields",
+                    ));
+                }
+                let price = entry[0].parse::<f64>().map_err(|e| {
+                    AggregatorError::parsing("Trade", format!("Invalid price: {}", e))
+                })?;
+                let quantity = entry[1].parse::<f64>().map_err(|e| {
+                    AggregatorError::parsing("Trade", format!("Invalid quantity: {}", e))
+                })?;
+                let time = entry[2].parse::<f64>().map_err(|e| {
+                    AggregatorError::parsing("Trade", format!("Invalid time: {}", e))
+                })?;
+                let side = match entry[3].as_str() {
+                    "b" => TradeSide::Buy,
+                    "s" => TradeSide::Sell,
+                    other => {
+                        return Err(AggregatorError::parsing(
+                            "Trade",
+                            format!("Invalid side: {}", other),
+                        ))
+                    }
+                };
+
+                Ok(Trade {
+                    id: Uuid::new_v4(),
+                    trade_id: entry[2].clone(),
+                    symbol: symbol.clone(),
+                    exchange: Exchange::Kraken,
+                    price,
+                    quantity,
+                    side,
+                    timestamp: DateTime::from_timestamp_micros((time * 1_000_000.0).round() as i64)
+                        .unwrap_or_else(Utc::now),
+                })
+            })
+            .collect()
+    }
+
+    fn parse_price_level(&self, level: &[String]) -> Result<(f64, f64)> {
+        if level.len() < 2 {
+            return Err(AggregatorError::parsing(
+                "PriceLevel",
+                "Price level has too few fields",
+            ));
+        }
         let price = level[0]
             .parse::<f64>()
-            .map_err(|e| AggregatorError::Parsing(format!("Invalid price: {}", e)))?;
-        let quantity = level[1]
-            .parse::<f64>()
-            .map_err(|e| AggregatorError::Parsing(format!("Invalid quantity: {}", e)))?;
+            .map_err(|e| AggregatorError::parsing("PriceLevel", format!("Invalid price: {}", e)))?;
+        let quantity = level[1].parse::<f64>().map_err(|e| {
+            AggregatorError::parsing("PriceLevel", format!("Invalid quantity: {}", e))
+        })?;
         Ok((price, quantity))
     }
 
@@ -141,6 +540,9 @@ impl Kraken {
             bids,
             asks,
             timestamp: Utc::now(),
+            funding: None,
+            event_time: None,
+            market_type: None,
         })
     }
 
@@ -183,33 +585,71 @@ impl Kraken {
             bids,
             asks,
             timestamp: Utc::now(),
+            funding: None,
+            event_time: Self::latest_level_time(update),
+            market_type: None,
         })
     }
 
+    /// Kraken stamps every level with the time it changed, in fractional seconds. The latest of
+    /// them is the time of the update.
+    fn latest_level_time(update: &KrakenOrderBookUpdate) -> Option<DateTime<Utc>> {
+        update
+            .asks
+            .iter()
+            .chain(update.bids.iter())
+            .flatten()
+            .filter_map(|level| level.get(2)?.parse::<f64>().ok())
+            .reduce(f64::max)
+            .and_then(|seconds| DateTime::from_timestamp_micros((seconds * 1e6) as i64))
+    }
+
     async fn spawn_websocket_stream(
         &self,
-        symbol: String,
+        symbols: Vec<String>,
         depth: usize,
         exchange_stream_buffer: usize,
+        mut resync_rx: Receiver<String>,
+        mut shutdown: Shutdown,
     ) -> Result<(Receiver<Message>, JoinHandle<Result<()>>)> {
         let (ws_tx, ws_rx) = tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
         let config = self.config.clone();
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+        let subscription = BookSubscription { symbols, depth };
 
         let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
             loop {
-                match Self::connect_websocket(&config, &symbol, depth, ws_tx.clone()).await {
+                let result = Self::connect_websocket(
+                    &config,
+                    &subscription,
+                    ws_tx.clone(),
+                    &mut resync_rx,
+                    &mut backoff,
+                    &health,
+                    &mut shutdown,
+                )
+                .await;
+                if shutdown.is_triggered() {
+                    return Ok(());
+                }
+
+                match result {
                     Ok(_) => {
                         warn!("WebSocket connection closed, reconnecting...");
+                        health.disconnected("WebSocket connection closed");
                     }
                     Err(e) => {
                         error!("WebSocket connection error: {}", e);
+                        health.disconnected(e.to_string());
                     }
                 }
 
-                tokio::time::sleep(tokio::time::Duration::from_millis(
-                    config.reconnect_interval,
-                ))
-                .await;
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
             }
         });
 
@@ -218,51 +658,70 @@ impl Kraken {
 
     async fn connect_websocket(
         config: &KrakenConfig,
-        symbol: &str,
-        depth: usize,
+        subscription: &BookSubscription,
         ws_tx: Sender<Message>,
+        resync_rx: &mut Receiver<String>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        shutdown: &mut Shutdown,
     ) -> Result<()> {
         let url = Url::parse(&config.websocket_url)
-            .map_err(|e| AggregatorError::Parsing(format!("Invalid URL: {}", e)))?;
+            .map_err(|e| AggregatorError::parsing("Url", format!("Invalid URL: {}", e)))?;
 
-        let (mut ws_stream, _) = tokio_tungstenite::connect_async(url).await.map_err(|e| {
-            AggregatorError::NetworkError(format!("WebSocket connection failed: {}", e))
-        })?;
+        let (mut ws_stream, _) = connect_websocket(url.as_str(), config.proxy.as_ref()).await?;
 
         info!("Connected to Kraken WebSocket");
+        backoff.reset();
+        health.connected();
 
-        // Subscribe to orderbook updates
-        let subscription = KrakenSubscription {
-            event: "subscribe".to_string(),
-            pair: vec![symbol.to_string()],
-            subscription: SubscriptionDetails {
-                name: "book".to_string(),
-                depth,
-            },
-        };
-
-        let subscription_msg =
-            serde_json::to_string(&subscription).map_err(|e| AggregatorError::Serialization(e))?;
-
+        // Subscribe to orderbook updates for every pair on this connection
         ws_stream
-            .send(Message::Text(subscription_msg))
+            .send(Message::Text(
+                subscription.request("subscribe", subscription.symbols.clone())?,
+            ))
             .await
-            .map_err(|e| {
-                AggregatorError::NetworkError(format!("Failed to send subscription: {}", e))
-            })?;
-
-        while let Some(msg) = ws_stream.next().await {
-            match msg {
-                Ok(message) => {
-                    if let Err(e) = ws_tx.send(message).await {
-                        error!("Failed to send message: {}", e);
+            .map_err(|e| AggregatorError::network(format!("Failed to send subscription: {}", e)))?;
+
+        // Drop resync requests raised against the previous connection
+        while resync_rx.try_recv().is_ok() {}
+
+        // Kraken sends a heartbeat every second on an idle connection
+        let mut watchdog = backoff.policy().watchdog();
+
+        loop {
+            tokio::select! {
+                msg = ws_stream.next() => match msg {
+                    Some(Ok(message)) => {
+                        watchdog.touch();
+                        if let Err(e) = ws_tx.send(message).await {
+                            error!("Failed to send message: {}", e);
+                            break;
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("WebSocket error: {}", e);
                         break;
                     }
-                }
-                Err(e) => {
-                    error!("WebSocket error: {}", e);
+                    None => break,
+                },
+                silent_for = watchdog.stalled() => return Err(health.stalled(silent_for)),
+                _ = shutdown.recv() => {
+                    info!("Closing Kraken order book stream");
+                    let _ = ws_stream.close(None).await;
                     break;
                 }
+                Some(symbol) = resync_rx.recv() => {
+                    // Resubscribing makes Kraken send a fresh snapshot for that pair only
+                    warn!("Resubscribing to Kraken book for {} after checksum mismatch", symbol);
+                    for event in ["unsubscribe", "subscribe"] {
+                        ws_stream
+                            .send(Message::Text(subscription.request(event, vec![symbol.clone()])?))
+                            .await
+                            .map_err(|e| {
+                                AggregatorError::network(format!("Failed to resubscribe: {}", e))
+                            })?;
+                    }
+                }
             }
         }
 
@@ -271,49 +730,138 @@ impl Kraken {
 
     async fn handle_websocket_messages(
         &self,
-        symbol: String,
+        depth: usize,
         mut ws_rx: Receiver<Message>,
         price_level_tx: Sender<PriceLevelUpdate>,
+        resync_tx: Sender<String>,
+        capture: Option<Capture>,
     ) -> Result<()> {
+        // Local book and sync state per pair, keyed by Kraken's pair name
+        let mut books: HashMap<String, (KrakenBook, bool)> = HashMap::new();
+        // What was sent downstream per pair, to remove what a fresh snapshot no longer has
+        let mut forwarded: HashMap<String, ForwardedBook> = HashMap::new();
+
         while let Some(message) = ws_rx.recv().await {
             if let Message::Text(text) = message {
-                match serde_json::from_str::<Value>(&text) {
-                    Ok(value) => {
-                        if let Some(arr) = value.as_array() {
-                            if arr.len() > 1 {
-                                let data = &arr[1];
-                                if let Ok(snapshot) =
-                                    serde_json::from_value::<KrakenOrderBookSnapshot>(data.clone())
-                                {
-                                    match self.create_price_level_update(&symbol, &snapshot) {
-                                        Ok(update) => {
-                                            if let Err(e) = price_level_tx.send(update).await {
-                                                error!("Failed to send snapshot update: {}", e);
-                                            }
-                                        }
-                                        Err(e) => {
-                                            error!("Failed to create snapshot update: {}", e);
-                                        }
-                                    }
-                                } else if let Ok(update) =
-                                    serde_json::from_value::<KrakenOrderBookUpdate>(data.clone())
-                                {
-                                    match self.create_price_level_delta(&symbol, &update) {
-                                        Ok(delta) => {
-                                            if let Err(e) = price_level_tx.send(delta).await {
-                                                error!("Failed to send delta update: {}", e);
-                                            }
-                                        }
-                                        Err(e) => {
-                                            error!("Failed to create delta update: {}", e);
-                                        }
-                                    }
-                                }
+                if let Some(capture) = &capture {
+                    capture.record(&Exchange::Kraken, &text);
+                }
+                let value = match serde_json::from_str::<Value>(&text) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        warn!("Failed to parse WebSocket message: {}", e);
+                        continue;
+                    }
+                };
+
+                // Book messages are [channelID, data..., channelName, pair]; updates touching
+                // both sides carry two data objects
+                let arr = match value.as_array() {
+                    Some(arr) if arr.len() >= 4 => arr,
+                    _ => continue,
+                };
+                let data = &arr[1..arr.len() - 2];
+                let symbol = match arr[arr.len() - 1].as_str() {
+                    Some(symbol) => symbol.to_string(),
+                    None => continue,
+                };
+                let (book, synced) = books
+                    .entry(symbol.clone())
+                    .or_insert_with(|| (KrakenBook::new(depth), false));
+
+                if let Ok(snapshot) =
+                    serde_json::from_value::<KrakenOrderBookSnapshot>(data[0].clone())
+                {
+                    if let Err(e) = book.apply_snapshot(&snapshot.asks, &snapshot.bids) {
+                        error!("Failed to apply snapshot to local book: {}", e);
+                        continue;
+                    }
+                    *synced = true;
+
+                    match self.create_price_level_update(&symbol, &snapshot) {
+                        Ok(mut update) => {
+                            forwarded
+                                .entry(symbol.clone())
+                                .or_default()
+                                .replace(&mut update);
+                            if let Err(e) = price_level_tx.send(update).await {
+                                error!("Failed to send snapshot update: {}", e);
                             }
                         }
+                        Err(e) => {
+                            error!("Failed to create snapshot update: {}", e);
+                        }
+                    }
+                    continue;
+                }
+
+                let mut update = KrakenOrderBookUpdate::default();
+                for part in data {
+                    if let Ok(part) = serde_json::from_value::<KrakenOrderBookUpdate>(part.clone())
+                    {
+                        update.asks = update.asks.or(part.asks);
+                        update.bids = update.bids.or(part.bids);
+                        update.checksum = update.checksum.or(part.checksum);
+                    }
+                }
+                if update.asks.is_none() && update.bids.is_none() {
+                    continue;
+                }
+
+                // Wait for the snapshot requested after a mismatch before forwarding deltas
+                if !*synced {
+                    continue;
+                }
+
+                let truncated = book
+                    .apply_update(
+                        update.asks.as_deref().unwrap_or_default(),
+                        update.bids.as_deref().unwrap_or_default(),
+                    )
+                    .ok()
+                    .filter(|_| {
+                        update
+                            .checksum
+                            .as_deref()
+                            .is_none_or(|checksum| book.verify(checksum))
+                    });
+
+                let Some((truncated_asks, truncated_bids)) = truncated else {
+                    warn!(
+                        "Kraken checksum mismatch for {}, requesting fresh snapshot",
+                        symbol
+                    );
+                    *synced = false;
+                    let _ = resync_tx.try_send(symbol.clone());
+                    continue;
+                };
+
+                match self.create_price_level_delta(&symbol, &update) {
+                    Ok(mut delta) => {
+                        let timestamp = delta.timestamp;
+                        delta
+                            .asks
+                            .extend(truncated_asks.into_iter().map(|price| Ask {
+                                price,
+                                quantity: 0.0,
+                                exchange: Exchange::Kraken,
+                                timestamp,
+                            }));
+                        delta
+                            .bids
+                            .extend(truncated_bids.into_iter().map(|price| Bid {
+                                price,
+                                quantity: 0.0,
+                                exchange: Exchange::Kraken,
+                                timestamp,
+                            }));
+                        forwarded.entry(symbol.clone()).or_default().record(&delta);
+                        if let Err(e) = price_level_tx.send(delta).await {
+                            error!("Failed to send delta update: {}", e);
+                        }
                     }
                     Err(e) => {
-                        warn!("Failed to parse WebSocket message: {}", e);
+                        error!("Failed to create delta update: {}", e);
                     }
                 }
             }
@@ -327,22 +875,40 @@ impl Kraken {
 impl OrderBookService for Kraken {
     async fn spawn_order_book_service(
         &self,
-        pair: [&str; 2],
+        pairs: &[TradingPair],
         order_book_depth: usize,
         exchange_stream_buffer: usize,
         price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
     ) -> Result<Vec<JoinHandle<Result<()>>>> {
-        let symbol = self.format_symbol(pair);
-        info!("Starting Kraken order book service for {}", symbol);
+        let symbols = self.format_symbols(pairs)?;
+        info!(
+            "Starting Kraken order book service for {}",
+            symbols.join(", ")
+        );
 
+        let (resync_tx, resync_rx) = tokio::sync::mpsc::channel::<String>(symbols.len());
         let (ws_rx, ws_handle) = self
-            .spawn_websocket_stream(symbol.clone(), order_book_depth, exchange_stream_buffer)
+            .spawn_websocket_stream(
+                symbols,
+                order_book_depth,
+                exchange_stream_buffer,
+                resync_rx,
+                Shutdown::new(shutdown),
+            )
             .await?;
 
         let self_clone = Self::new();
+        let capture = self.capture.clone();
         let message_handle = tokio::spawn(async move {
             self_clone
-                .handle_websocket_messages(symbol, ws_rx, price_level_tx)
+                .handle_websocket_messages(
+                    order_book_depth,
+                    ws_rx,
+                    price_level_tx,
+                    resync_tx,
+                    capture,
+                )
                 .await
         });
 
@@ -350,6 +916,60 @@ impl OrderBookService for Kraken {
     }
 }
 
+#[async_trait]
+impl TradeStreamService for Kraken {
+    async fn spawn_trade_service(
+        &self,
+        pairs: &[TradingPair],
+        trade_tx: Sender<Trade>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let symbols = self.format_symbols(pairs)?;
+        info!("Starting Kraken trade service for {}", symbols.join(", "));
+
+        let subscription = KrakenSubscription {
+            event: "subscribe".to_string(),
+            pair: symbols,
+            subscription: SubscriptionDetails {
+                name: "trade".to_string(),
+                depth: None,
+            },
+        };
+
+        let spec = TradeStreamSpec {
+            exchange: "kraken",
+            url: self.config.websocket_url.clone(),
+            subscriptions: vec![
+                serde_json::to_string(&subscription).map_err(AggregatorError::Serialization)?
+            ],
+            ping: None,
+            reconnect_policy: self.reconnect_policy.clone(),
+            health: self.health_reporter(),
+            proxy: self.config.proxy.clone(),
+            capture: self.capture.clone(),
+        };
+
+        Ok(vec![spawn_trade_stream(spec, Self::parse_trades, trade_tx)])
+    }
+}
+
+#[async_trait]
+impl ExchangeInfoService for Kraken {
+    async fn fetch_instruments(&self, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let client = http_client(self.config.proxy.as_ref())?;
+        let body = get_body(&client, KRAKEN_ASSET_PAIRS_URL, None).await?;
+        Self::parse_instruments(&body, pairs)
+    }
+}
+
+#[async_trait]
+impl MarketDiscovery for Kraken {
+    async fn list_markets(&self) -> Result<Vec<MarketListing>> {
+        let client = http_client(self.config.proxy.as_ref())?;
+        let body = get_body(&client, KRAKEN_ASSET_PAIRS_URL, None).await?;
+        Self::parse_markets(&body)
+    }
+}
+
 impl Default for Kraken {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,604 @@
+//! Kraken Futures Exchange Connector
+//! Streams perpetual order books from the Kraken Futures WebSocket API, which is a separate venue
+//! from Kraken spot with its own endpoint, symbols and message format. Books start from a
+//! `book_snapshot` and are kept up to date by per-level `book` deltas carrying a sequence number;
+//! funding comes from the `ticker` feed.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::capture::Capture;
+use crate::proxy::{connect_websocket, Proxy};
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::{OrderBookService, SymbolMapper};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, FundingRate, HealthEvent, MarketType,
+    PriceLevelUpdate, Result, TradingPair,
+};
+
+const KRAKEN_FUTURES_WS_URL: &str = "wss://futures.kraken.com/ws/v1";
+const KRAKEN_FUTURES_DEMO_WS_URL: &str = "wss://demo-futures.kraken.com/ws/v1";
+const BOOK_FEED: &str = "book";
+const BOOK_SNAPSHOT_FEED: &str = "book_snapshot";
+const TICKER_FEED: &str = "ticker";
+const HEARTBEAT_FEED: &str = "heartbeat";
+/// The server drops connections that send nothing for a minute
+const PING_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(30);
+
+pub struct KrakenFutures {
+    websocket_url: String,
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+}
+
+/// Turns `book_snapshot`, `book` and `ticker` messages into updates.
+///
+/// Deltas must follow the last sequence number of their product. After a gap the product's
+/// deltas are dropped until a new snapshot arrives, and the product is queued for
+/// `take_resyncs` so the connection can resubscribe to it.
+#[derive(Debug, Default)]
+pub struct KrakenFuturesBookParser {
+    depth: Option<usize>,
+    sequences: HashMap<String, u64>,
+    funding: HashMap<String, FundingRate>,
+    resyncs: Vec<String>,
+}
+
+/// Where one book stream connects and what it subscribes to.
+struct StreamTarget {
+    url: String,
+    product_ids: Vec<String>,
+    proxy: Option<Proxy>,
+}
+
+#[derive(Debug, Serialize)]
+struct KrakenFuturesSubscription {
+    event: String,
+    feed: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    product_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KrakenFuturesSnapshot {
+    product_id: String,
+    timestamp: i64,
+    seq: u64,
+    bids: Vec<KrakenFuturesLevel>,
+    asks: Vec<KrakenFuturesLevel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KrakenFuturesLevel {
+    price: f64,
+    qty: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct KrakenFuturesDelta {
+    product_id: String,
+    side: String,
+    seq: u64,
+    price: f64,
+    qty: f64,
+    timestamp: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct KrakenFuturesTicker {
+    product_id: String,
+    #[serde(default)]
+    relative_funding_rate: Option<f64>,
+    #[serde(rename = "markPrice", default)]
+    mark_price: Option<f64>,
+    #[serde(default)]
+    next_funding_rate_time: Option<i64>,
+}
+
+impl KrakenFuturesBookParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `depth` levels per side of each snapshot.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Products whose book went out of sync since the last call.
+    pub fn take_resyncs(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.resyncs)
+    }
+
+    /// Handles one raw message. Snapshots and deltas yield a `PriceLevelUpdate`; tickers,
+    /// heartbeats and subscription events yield `None`.
+    pub fn handle_message(&mut self, message: &str) -> Result<Option<PriceLevelUpdate>> {
+        let value: Value = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing(
+                "KrakenFuturesMessage",
+                format!("Failed to parse message: {}", e),
+            )
+        })?;
+
+        if let Some(event) = value.get("event").and_then(Value::as_str) {
+            if event == "error" {
+                let message = value.get("message").and_then(Value::as_str);
+                return Err(AggregatorError::exchange(
+                    "kraken_futures",
+                    message.unwrap_or_default(),
+                ));
+            }
+            return Ok(None);
+        }
+
+        match value.get("feed").and_then(Value::as_str) {
+            Some(BOOK_SNAPSHOT_FEED) => self.handle_snapshot(value).map(Some),
+            Some(BOOK_FEED) => self.handle_delta(value),
+            Some(TICKER_FEED) => {
+                self.handle_ticker(value)?;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn handle_snapshot(&mut self, value: Value) -> Result<PriceLevelUpdate> {
+        let snapshot: KrakenFuturesSnapshot = serde_json::from_value(value).map_err(|e| {
+            AggregatorError::parsing(
+                "KrakenFuturesSnapshot",
+                format!("Failed to parse book snapshot: {}", e),
+            )
+        })?;
+        self.sequences
+            .insert(snapshot.product_id.clone(), snapshot.seq);
+
+        let depth = self.depth.unwrap_or(usize::MAX);
+        let timestamp = Utc::now();
+        let bids = snapshot
+            .bids
+            .iter()
+            .take(depth)
+            .map(|level| Bid {
+                price: level.price,
+                quantity: level.qty,
+                exchange: Exchange::KrakenFutures,
+                timestamp,
+            })
+            .collect();
+        let asks = snapshot
+            .asks
+            .iter()
+            .take(depth)
+            .map(|level| Ask {
+                price: level.price,
+                quantity: level.qty,
+                exchange: Exchange::KrakenFutures,
+                timestamp,
+            })
+            .collect();
+
+        Ok(PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            funding: self.funding.get(&snapshot.product_id).cloned(),
+            symbol: snapshot.product_id,
+            exchange: Exchange::KrakenFutures,
+            bids,
+            asks,
+            timestamp,
+            event_time: DateTime::from_timestamp_millis(snapshot.timestamp),
+            market_type: Some(MarketType::Futures),
+        })
+    }
+
+    fn handle_delta(&mut self, value: Value) -> Result<Option<PriceLevelUpdate>> {
+        let delta: KrakenFuturesDelta = serde_json::from_value(value).map_err(|e| {
+            AggregatorError::parsing(
+                "KrakenFuturesDelta",
+                format!("Failed to parse book delta: {}", e),
+            )
+        })?;
+
+        // Deltas before the snapshot or after a gap wait for the next snapshot
+        let Some(last) = self.sequences.get(&delta.product_id).copied() else {
+            return Ok(None);
+        };
+        if delta.seq <= last {
+            return Ok(None);
+        }
+        if delta.seq != last + 1 {
+            self.sequences.remove(&delta.product_id);
+            self.resyncs.push(delta.product_id.clone());
+            return Err(AggregatorError::exchange(
+                "kraken_futures",
+                format!(
+                    "Sequence gap on {}: expected {}, received {}",
+                    delta.product_id,
+                    last + 1,
+                    delta.seq
+                ),
+            ));
+        }
+        self.sequences.insert(delta.product_id.clone(), delta.seq);
+
+        let timestamp = Utc::now();
+        let (mut bids, mut asks) = (Vec::new(), Vec::new());
+        match delta.side.as_str() {
+            "buy" => bids.push(Bid {
+                price: delta.price,
+                quantity: delta.qty,
+                exchange: Exchange::KrakenFutures,
+                timestamp,
+            }),
+            "sell" => asks.push(Ask {
+                price: delta.price,
+                quantity: delta.qty,
+                exchange: Exchange::KrakenFutures,
+                timestamp,
+            }),
+            side => {
+                return Err(AggregatorError::parsing(
+                    "KrakenFuturesDelta",
+                    format!("Unknown side: {}", side),
+                ))
+            }
+        }
+
+        Ok(Some(PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            funding: self.funding.get(&delta.product_id).cloned(),
+            symbol: delta.product_id,
+            exchange: Exchange::KrakenFutures,
+            bids,
+            asks,
+            timestamp,
+            event_time: DateTime::from_timestamp_millis(delta.timestamp),
+            market_type: Some(MarketType::Futures),
+        }))
+    }
+
+    fn handle_ticker(&mut self, value: Value) -> Result<()> {
+        let ticker: KrakenFuturesTicker = serde_json::from_value(value).map_err(|e| {
+            AggregatorError::parsing(
+                "KrakenFuturesTicker",
+                format!("Failed to parse ticker: {}", e),
+            )
+        })?;
+        // Fixed-maturity contracts have no funding
+        if let Some(rate) = ticker.relative_funding_rate {
+            self.funding.insert(
+                ticker.product_id,
+                FundingRate {
+                    rate,
+                    mark_price: ticker.mark_price,
+                    next_funding_time: ticker
+                        .next_funding_rate_time
+                        .and_then(DateTime::from_timestamp_millis),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Latest funding information received for `product_id`.
+    pub fn funding(&self, product_id: &str) -> Option<&FundingRate> {
+        self.funding.get(product_id)
+    }
+}
+
+impl KrakenFutures {
+    pub fn new() -> Self {
+        Self {
+            websocket_url: KRAKEN_FUTURES_WS_URL.to_string(),
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            proxy: None,
+            capture: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings, using the Kraken Futures demo
+    /// environment when `sandbox` is set.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        let mut connector = Self::new()
+            .with_sandbox(config.sandbox)
+            .with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        connector.proxy = Proxy::from_config(config)?;
+        Ok(connector)
+    }
+
+    /// Stream from the Kraken Futures demo environment instead of production.
+    pub fn with_sandbox(mut self, sandbox: bool) -> Self {
+        self.websocket_url = if sandbox {
+            KRAKEN_FUTURES_DEMO_WS_URL
+        } else {
+            KRAKEN_FUTURES_WS_URL
+        }
+        .to_string();
+        self
+    }
+
+    /// Stream from a custom endpoint, such as a local mock server.
+    pub fn with_websocket_url(mut self, websocket_url: &str) -> Self {
+        self.websocket_url = websocket_url.to_string();
+        self
+    }
+
+    pub fn websocket_url(&self) -> &str {
+        &self.websocket_url
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::KrakenFutures, self.health_tx.clone())
+    }
+
+    /// Builds a `subscribe` or `unsubscribe` request for `feed`. The heartbeat feed takes no
+    /// products.
+    pub fn subscription(event: &str, feed: &str, product_ids: &[String]) -> Result<String> {
+        let subscription = KrakenFuturesSubscription {
+            event: event.to_string(),
+            feed: feed.to_string(),
+            product_ids: (feed != HEARTBEAT_FEED).then(|| product_ids.to_vec()),
+        };
+        serde_json::to_string(&subscription).map_err(AggregatorError::Serialization)
+    }
+
+    /// Spawn WebSocket stream for the book, ticker and heartbeat feeds
+    fn spawn_websocket_stream(
+        &self,
+        product_ids: Vec<String>,
+        exchange_stream_buffer: usize,
+        mut resync_rx: Receiver<String>,
+        mut shutdown: Shutdown,
+    ) -> (Receiver<Message>, JoinHandle<Result<()>>) {
+        let (ws_tx, ws_rx) = tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
+        let target = StreamTarget {
+            url: self.websocket_url.clone(),
+            product_ids,
+            proxy: self.proxy.clone(),
+        };
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                let result = Self::connect_websocket(
+                    &target,
+                    &ws_tx,
+                    &mut resync_rx,
+                    &mut backoff,
+                    &health,
+                    &mut shutdown,
+                )
+                .await;
+                if shutdown.is_triggered() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("Kraken Futures WebSocket connection closed, reconnecting...");
+                        health.disconnected("WebSocket connection closed");
+                    }
+                    Err(e) => {
+                        error!("Kraken Futures WebSocket connection error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+                if ws_tx.is_closed() {
+                    return Ok(());
+                }
+
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
+            }
+        });
+
+        (ws_rx, handle)
+    }
+
+    async fn connect_websocket(
+        target: &StreamTarget,
+        ws_tx: &Sender<Message>,
+        resync_rx: &mut Receiver<String>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        shutdown: &mut Shutdown,
+    ) -> Result<()> {
+        let (mut ws_stream, _) = connect_websocket(&target.url, target.proxy.as_ref()).await?;
+
+        info!("Connected to Kraken Futures WebSocket");
+        backoff.reset();
+        health.connected();
+
+        // The heartbeat arrives every minute even when the books are quiet
+        for feed in [HEARTBEAT_FEED, TICKER_FEED, BOOK_FEED] {
+            ws_stream
+                .send(Message::Text(Self::subscription(
+                    "subscribe",
+                    feed,
+                    &target.product_ids,
+                )?))
+                .await
+                .map_err(|e| {
+                    AggregatorError::network(format!("Failed to send subscription: {}", e))
+                })?;
+        }
+
+        // Drop resync requests raised against the previous connection
+        while resync_rx.try_recv().is_ok() {}
+
+        let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+        ping_interval.tick().await;
+
+        let mut watchdog = backoff.policy().watchdog();
+        loop {
+            let msg = tokio::select! {
+                msg = ws_stream.next() => match msg {
+                    Some(msg) => msg,
+                    None => return Ok(()),
+                },
+                _ = ping_interval.tick() => {
+                    if let Err(e) = ws_stream.send(Message::Ping(vec![])).await {
+                        error!("Failed to send ping: {}", e);
+                        return Ok(());
+                    }
+                    continue;
+                }
+                Some(product_id) = resync_rx.recv() => {
+                    // Resubscribing makes Kraken Futures send a fresh snapshot of that book
+                    warn!("Resubscribing to Kraken Futures book for {}", product_id);
+                    for event in ["unsubscribe", "subscribe"] {
+                        let product_ids = std::slice::from_ref(&product_id);
+                        let request = Self::subscription(event, BOOK_FEED, product_ids)?;
+                        ws_stream.send(Message::Text(request)).await.map_err(|e| {
+                            AggregatorError::network(format!("Failed to resubscribe: {}", e))
+                        })?;
+                    }
+                    continue;
+                }
+                silent_for = watchdog.stalled() => return Err(health.stalled(silent_for)),
+                _ = shutdown.recv() => {
+                    info!("Closing Kraken Futures order book stream");
+                    let _ = ws_stream.close(None).await;
+                    return Ok(());
+                }
+            };
+            watchdog.touch();
+
+            match msg {
+                Ok(Message::Text(text)) => {
+                    if let Err(e) = ws_tx.send(Message::Text(text)).await {
+                        warn!("Kraken Futures stream processor stopped: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Ping(payload)) => {
+                    if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                        error!("Failed to send pong: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Err(e) => {
+                    return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Spawn stream processor that applies snapshots and sequenced deltas
+    fn spawn_stream_processor(
+        mut ws_rx: Receiver<Message>,
+        order_book_depth: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        resync_tx: Sender<String>,
+        capture: Option<Capture>,
+    ) -> JoinHandle<Result<()>> {
+        tokio::spawn(async move {
+            let mut parser = KrakenFuturesBookParser::new().with_depth(order_book_depth);
+
+            while let Some(message) = ws_rx.recv().await {
+                if let Message::Text(text) = message {
+                    if let Some(capture) = &capture {
+                        capture.record(&Exchange::KrakenFutures, &text);
+                    }
+                    match parser.handle_message(&text) {
+                        Ok(Some(update)) => {
+                            if price_level_tx.send(update).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => error!("Failed to process book message: {}", e),
+                    }
+                    for product_id in parser.take_resyncs() {
+                        let _ = resync_tx.try_send(product_id);
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl OrderBookService for KrakenFutures {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let product_ids = SymbolMapper::new().to_exchange_all(&Exchange::KrakenFutures, pairs)?;
+        info!(
+            "Starting Kraken Futures order book service for {}",
+            product_ids.join(", ")
+        );
+
+        let (resync_tx, resync_rx) = tokio::sync::mpsc::channel::<String>(product_ids.len());
+        let (ws_rx, ws_handle) = self.spawn_websocket_stream(
+            product_ids,
+            exchange_stream_buffer,
+            resync_rx,
+            Shutdown::new(shutdown),
+        );
+        let processor_handle = Self::spawn_stream_processor(
+            ws_rx,
+            order_book_depth,
+            price_level_tx,
+            resync_tx,
+            self.capture.clone(),
+        );
+
+        Ok(vec![ws_handle, processor_handle])
+    }
+}
+
+impl Default for KrakenFutures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
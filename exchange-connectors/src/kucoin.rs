@@ -0,0 +1,818 @@
+//! KuCoin Exchange Connector
+//! Streams spot level2 order books from KuCoin. Every connection first obtains a token from the
+//! public bullet endpoint, which also tells us which server to connect to and how often to ping.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::capture::Capture;
+use crate::exchange_info::{decimal, ensure_complete, get_body, parse_body, requested_symbols};
+use crate::proxy::{connect_websocket, http_client, Proxy};
+use crate::rate_limit::RateLimiter;
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::snapshot_sync::ForwardedBook;
+use crate::{ExchangeInfoService, OrderBookService, SymbolMapper};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, HealthEvent, InstrumentInfo,
+    MarketDiscovery, MarketListing, PriceLevelUpdate, RateLimitConfig, Result, TradingPair,
+};
+
+const KUCOIN_BULLET_PUBLIC_URL: &str = "https://api.kucoin.com/api/v1/bullet-public";
+const KUCOIN_ORDER_BOOK_URL: &str = "https://api.kucoin.com/api/v1/market/orderbook";
+const KUCOIN_SYMBOLS_URL: &str = "https://api.kucoin.com/api/v2/symbols";
+const LEVEL2_TOPIC: &str = "/market/level2:";
+const LEVEL2_UPDATE_SUBJECT: &str = "trade.l2update";
+const SUCCESS_CODE: &str = "200000";
+/// KuCoin accepts at most 100 symbols per subscription topic
+const MAX_TOPIC_SYMBOLS: usize = 100;
+const GET_ORDER_BOOK_SNAPSHOT: Vec<u8> = vec![];
+
+pub struct KuCoin {
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    rate_limiter: RateLimiter,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+}
+
+/// Connection details returned by the bullet endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KuCoinWebSocketToken {
+    pub endpoint: String,
+    pub token: String,
+    pub ping_interval: Duration,
+}
+
+impl KuCoinWebSocketToken {
+    /// Returns the URL to connect to, tagged with `connect_id` so the welcome message can be
+    /// matched to this connection.
+    pub fn url(&self, connect_id: &str) -> String {
+        format!(
+            "{}?token={}&connectId={}",
+            self.endpoint, self.token, connect_id
+        )
+    }
+}
+
+/// An incremental level2 update with the sequence range it covers.
+#[derive(Debug, Clone)]
+pub struct KuCoinDepthUpdate {
+    pub sequence_start: u64,
+    pub sequence_end: u64,
+    pub update: PriceLevelUpdate,
+}
+
+#[derive(Debug, Serialize)]
+struct KuCoinRequest {
+    id: String,
+    #[serde(rename = "type")]
+    request_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    topic: Option<String>,
+    #[serde(rename = "privateChannel", skip_serializing_if = "Option::is_none")]
+    private_channel: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KuCoinResponse<T> {
+    code: String,
+    data: Option<T>,
+    msg: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KuCoinBullet {
+    token: String,
+    #[serde(rename = "instanceServers")]
+    instance_servers: Vec<KuCoinInstanceServer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KuCoinInstanceServer {
+    endpoint: String,
+    /// Milliseconds between client pings
+    #[serde(rename = "pingInterval")]
+    ping_interval: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct KuCoinMessage {
+    #[serde(rename = "type")]
+    message_type: String,
+    subject: Option<String>,
+    data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KuCoinLevel2Update {
+    #[serde(rename = "sequenceStart")]
+    sequence_start: u64,
+    #[serde(rename = "sequenceEnd")]
+    sequence_end: u64,
+    symbol: String,
+    time: Option<i64>,
+    changes: KuCoinLevel2Changes,
+}
+
+#[derive(Debug, Deserialize)]
+struct KuCoinLevel2Changes {
+    /// Entries are `[price, size, sequence]`
+    #[serde(default)]
+    asks: Vec<[String; 3]>,
+    #[serde(default)]
+    bids: Vec<[String; 3]>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KuCoinSymbol {
+    symbol: String,
+    #[serde(default)]
+    base_currency: String,
+    #[serde(default)]
+    quote_currency: String,
+    #[serde(default)]
+    enable_trading: bool,
+    price_increment: String,
+    base_increment: String,
+    base_min_size: String,
+    min_funds: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KuCoinOrderBookSnapshot {
+    sequence: String,
+    time: Option<i64>,
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+}
+
+impl KuCoin {
+    pub fn new() -> Self {
+        Self {
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            rate_limiter: RateLimiter::new("kucoin", &RateLimitConfig::default()),
+            proxy: None,
+            capture: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings. KuCoin's sandbox was retired, so
+    /// `sandbox: true` is rejected rather than silently streaming production data.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        if config.sandbox {
+            return Err(AggregatorError::validation(
+                "sandbox",
+                "KuCoin does not provide a sandbox environment",
+            ));
+        }
+        let mut connector = Self::new()
+            .with_rate_limit(&config.rate_limit)
+            .with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        connector.proxy = Proxy::from_config(config)?;
+        Ok(connector)
+    }
+
+    /// Limit token and snapshot requests according to `config`.
+    pub fn with_rate_limit(mut self, config: &RateLimitConfig) -> Self {
+        self.rate_limiter = RateLimiter::new("kucoin", config);
+        self
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket and REST traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::KuCoin, self.health_tx.clone())
+    }
+
+    /// Parse the bullet endpoint response into the server, token and ping interval to use.
+    pub fn parse_token_response(body: &str) -> Result<KuCoinWebSocketToken> {
+        let response: KuCoinResponse<KuCoinBullet> = serde_json::from_str(body).map_err(|e| {
+            AggregatorError::parsing("KuCoinBullet", format!("Failed to parse token: {}", e))
+        })?;
+        let bullet = Self::response_data(response)?;
+
+        let server = bullet.instance_servers.into_iter().next().ok_or_else(|| {
+            AggregatorError::exchange("kucoin", "Token response listed no WebSocket servers")
+        })?;
+
+        Ok(KuCoinWebSocketToken {
+            endpoint: server.endpoint,
+            token: bullet.token,
+            ping_interval: Duration::from_millis(server.ping_interval),
+        })
+    }
+
+    /// Parse a `v2/symbols` listing into the symbols open for trading, without volumes.
+    pub fn parse_markets(body: &str) -> Result<Vec<MarketListing>> {
+        let response: KuCoinResponse<Vec<KuCoinSymbol>> = parse_body(body, "KuCoinSymbol")?;
+        Ok(Self::response_data(response)?
+            .into_iter()
+            .filter(|symbol| {
+                symbol.enable_trading
+                    && !symbol.base_currency.is_empty()
+                    && !symbol.quote_currency.is_empty()
+            })
+            .map(|symbol| MarketListing {
+                exchange: Exchange::KuCoin,
+                pair: TradingPair::new(&symbol.base_currency, &symbol.quote_currency),
+                symbol: symbol.symbol,
+                quote_volume: None,
+            })
+            .collect())
+    }
+
+    /// Parse a `v2/symbols` listing into metadata for `pairs`.
+    pub fn parse_instruments(body: &str, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let requested = requested_symbols(&Exchange::KuCoin, pairs)?;
+        let response: KuCoinResponse<Vec<KuCoinSymbol>> = parse_body(body, "KuCoinSymbol")?;
+
+        let mut instruments = Vec::new();
+        for symbol in Self::response_data(response)? {
+            let Some(pair) = requested.get(&symbol.symbol) else {
+                continue;
+            };
+            instruments.push(InstrumentInfo {
+                exchange: Exchange::KuCoin,
+                pair: pair.clone(),
+                tick_size: decimal(&symbol.price_increment, "priceIncrement")?,
+                lot_size: decimal(&symbol.base_increment, "baseIncrement")?,
+                min_quantity: decimal(&symbol.base_min_size, "baseMinSize")?,
+                min_notional: symbol
+                    .min_funds
+                    .as_deref()
+                    .map(|funds| decimal(funds, "minFunds"))
+                    .transpose()?,
+                symbol: symbol.symbol,
+            });
+        }
+
+        ensure_complete(&Exchange::KuCoin, &requested, &instruments)?;
+        Ok(instruments)
+    }
+
+    /// Parse a raw `trade.l2update` message. Welcome, ack, pong and other messages yield `None`.
+    /// Sequence-only entries with a price of zero are dropped from the update.
+    pub fn parse_depth_update(message: &str) -> Result<Option<KuCoinDepthUpdate>> {
+        let msg: KuCoinMessage = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("KuCoinMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        if msg.message_type != "message" || msg.subject.as_deref() != Some(LEVEL2_UPDATE_SUBJECT) {
+            return Ok(None);
+        }
+        let data = match msg.data {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+
+        let update: KuCoinLevel2Update = serde_json::from_value(data).map_err(|e| {
+            AggregatorError::parsing(
+                "KuCoinLevel2Update",
+                format!("Failed to parse level2 update: {}", e),
+            )
+        })?;
+        let event_time = update.time.and_then(DateTime::from_timestamp_millis);
+
+        let bids = Self::parse_changes(&update.changes.bids)?;
+        let asks = Self::parse_changes(&update.changes.asks)?;
+
+        Ok(Some(KuCoinDepthUpdate {
+            sequence_start: update.sequence_start,
+            sequence_end: update.sequence_end,
+            update: Self::create_price_level_update(&update.symbol, &bids, &asks, event_time),
+        }))
+    }
+
+    fn parse_changes(changes: &[[String; 3]]) -> Result<Vec<(f64, f64)>> {
+        let mut levels = Vec::with_capacity(changes.len());
+        for change in changes {
+            let (price, quantity) = Self::parse_price_level(&change[0], &change[1])?;
+            if price > 0.0 {
+                levels.push((price, quantity));
+            }
+        }
+        Ok(levels)
+    }
+
+    fn parse_snapshot_levels(levels: &[[String; 2]]) -> Result<Vec<(f64, f64)>> {
+        levels
+            .iter()
+            .map(|level| Self::parse_price_level(&level[0], &level[1]))
+            .collect()
+    }
+
+    fn parse_price_level(price: &str, quantity: &str) -> Result<(f64, f64)> {
+        let price = price
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("PriceLevel", format!("Invalid price: {}", e)))?;
+        let quantity = quantity.parse::<f64>().map_err(|e| {
+            AggregatorError::parsing("PriceLevel", format!("Invalid quantity: {}", e))
+        })?;
+        Ok((price, quantity))
+    }
+
+    fn create_price_level_update(
+        symbol: &str,
+        bids: &[(f64, f64)],
+        asks: &[(f64, f64)],
+        event_time: Option<DateTime<Utc>>,
+    ) -> PriceLevelUpdate {
+        let timestamp = Utc::now();
+        PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            symbol: symbol.to_string(),
+            exchange: Exchange::KuCoin,
+            bids: bids
+                .iter()
+                .map(|&(price, quantity)| Bid {
+                    price,
+                    quantity,
+                    exchange: Exchange::KuCoin,
+                    timestamp,
+                })
+                .collect(),
+            asks: asks
+                .iter()
+                .map(|&(price, quantity)| Ask {
+                    price,
+                    quantity,
+                    exchange: Exchange::KuCoin,
+                    timestamp,
+                })
+                .collect(),
+            timestamp,
+            funding: None,
+            event_time,
+            market_type: None,
+        }
+    }
+
+    fn response_data<T>(response: KuCoinResponse<T>) -> Result<T> {
+        if response.code != SUCCESS_CODE {
+            return Err(AggregatorError::exchange(
+                "kucoin",
+                format!(
+                    "Request failed with code {}: {}",
+                    response.code,
+                    response.msg.unwrap_or_default()
+                ),
+            ));
+        }
+        response
+            .data
+            .ok_or_else(|| AggregatorError::exchange("kucoin", "Response contained no data"))
+    }
+
+    fn request(request_type: &str, topic: Option<String>) -> Result<String> {
+        let subscribe = topic.is_some();
+        let request = KuCoinRequest {
+            id: Uuid::new_v4().to_string(),
+            request_type: request_type.to_string(),
+            topic,
+            private_channel: subscribe.then_some(false),
+            response: subscribe.then_some(true),
+        };
+        serde_json::to_string(&request).map_err(AggregatorError::Serialization)
+    }
+
+    fn check_status(response: &reqwest::Response) -> Result<()> {
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(AggregatorError::rate_limit(
+                "kucoin",
+                "Request rejected with HTTP 429",
+            ));
+        }
+        if !status.is_success() {
+            return Err(AggregatorError::network(format!("HTTP error: {}", status)));
+        }
+        Ok(())
+    }
+
+    /// Request a public WebSocket token
+    async fn get_websocket_token(
+        client: &reqwest::Client,
+        rate_limiter: &RateLimiter,
+    ) -> Result<KuCoinWebSocketToken> {
+        rate_limiter.try_acquire()?;
+
+        let response = client
+            .post(KUCOIN_BULLET_PUBLIC_URL)
+            .send()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to get token: {}", e)))?;
+        Self::check_status(&response)?;
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to read token: {}", e)))?;
+        Self::parse_token_response(&body)
+    }
+
+    /// Spawn WebSocket stream for level2 updates
+    fn spawn_websocket_stream(
+        &self,
+        symbols: Vec<String>,
+        exchange_stream_buffer: usize,
+        client: reqwest::Client,
+        mut shutdown: Shutdown,
+    ) -> (Receiver<Message>, JoinHandle<Result<()>>) {
+        let (ws_tx, ws_rx) = tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
+        let reconnect_policy = self.reconnect_policy.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        let health = self.health_reporter();
+        let proxy = self.proxy.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                // Tokens are single-use, so every connection attempt fetches a new one
+                let result = match Self::get_websocket_token(&client, &rate_limiter).await {
+                    Ok(token) => {
+                        Self::connect_websocket(
+                            &token,
+                            &symbols,
+                            proxy.as_ref(),
+                            &ws_tx,
+                            &mut backoff,
+                            &health,
+                            &mut shutdown,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                if shutdown.is_triggered() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("KuCoin WebSocket connection closed, reconnecting...");
+                        health.disconnected("WebSocket connection closed");
+                    }
+                    Err(e) => {
+                        error!("KuCoin WebSocket connection error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+                if ws_tx.is_closed() {
+                    return Ok(());
+                }
+
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
+            }
+        });
+
+        (ws_rx, handle)
+    }
+
+    async fn connect_websocket(
+        token: &KuCoinWebSocketToken,
+        symbols: &[String],
+        proxy: Option<&Proxy>,
+        ws_tx: &Sender<Message>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        shutdown: &mut Shutdown,
+    ) -> Result<()> {
+        let (mut ws_stream, _) =
+            connect_websocket(&token.url(&Uuid::new_v4().to_string()), proxy).await?;
+
+        info!("Connected to KuCoin WebSocket");
+        backoff.reset();
+        health.connected();
+
+        for chunk in symbols.chunks(MAX_TOPIC_SYMBOLS) {
+            let topic = format!("{}{}", LEVEL2_TOPIC, chunk.join(","));
+            let subscription = Self::request("subscribe", Some(topic))?;
+            ws_stream
+                .send(Message::Text(subscription))
+                .await
+                .map_err(|e| {
+                    AggregatorError::network(format!("Failed to send subscription: {}", e))
+                })?;
+        }
+
+        // Signal to get fresh snapshots for every pair
+        if ws_tx
+            .send(Message::Binary(GET_ORDER_BOOK_SNAPSHOT))
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let mut ping_interval = tokio::time::interval(token.ping_interval);
+        ping_interval.tick().await;
+        let mut watchdog = backoff.policy().watchdog();
+        // Anything received since the last ping proves the connection is alive
+        let mut alive = true;
+
+        loop {
+            tokio::select! {
+                msg = ws_stream.next() => {
+                    watchdog.touch();
+                    alive = true;
+                    match msg {
+                        Some(Ok(message @ Message::Text(_))) => {
+                            // The processor is gone once the receiver is dropped
+                            if let Err(e) = ws_tx.send(message).await {
+                                warn!("KuCoin stream processor stopped: {}", e);
+                                return Ok(());
+                            }
+                        }
+                        Some(Ok(Message::Ping(payload))) => {
+                            if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                                error!("Failed to send pong: {}", e);
+                                return Ok(());
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => return Ok(()),
+                        Some(Err(e)) => {
+                            return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                        }
+                        _ => {}
+                    }
+                }
+                silent_for = watchdog.stalled() => return Err(health.stalled(silent_for)),
+                _ = shutdown.recv() => {
+                    info!("Closing KuCoin order book stream");
+                    let _ = ws_stream.close(None).await;
+                    return Ok(());
+                }
+                _ = ping_interval.tick() => {
+                    if !alive {
+                        return Err(AggregatorError::network(
+                            "No pong received within the ping interval",
+                        ));
+                    }
+                    alive = false;
+                    let ping = Self::request("ping", None)?;
+                    if let Err(e) = ws_stream.send(Message::Text(ping)).await {
+                        error!("Failed to send ping: {}", e);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Spawn stream processor that applies the snapshot/sequence sync rules per pair
+    fn spawn_stream_processor(
+        &self,
+        symbols: Vec<String>,
+        order_book_depth: usize,
+        mut ws_rx: Receiver<Message>,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        client: reqwest::Client,
+    ) -> JoinHandle<Result<()>> {
+        let rate_limiter = self.rate_limiter.clone();
+        let capture = self.capture.clone();
+
+        tokio::spawn(async move {
+            // Last applied sequence per pair; a missing entry means the pair needs a snapshot
+            let mut sequences: HashMap<String, u64> = HashMap::new();
+            let mut forwarded: HashMap<String, ForwardedBook> = HashMap::new();
+
+            while let Some(message) = ws_rx.recv().await {
+                match message {
+                    Message::Text(text) => {
+                        if let Some(capture) = &capture {
+                            capture.record(&Exchange::KuCoin, &text);
+                        }
+                        let depth = match Self::parse_depth_update(&text) {
+                            Ok(Some(depth)) => depth,
+                            Ok(None) => continue,
+                            Err(e) => {
+                                error!("Failed to process level2 update: {}", e);
+                                continue;
+                            }
+                        };
+                        let symbol = depth.update.symbol.clone();
+
+                        let sequence = match sequences.get(&symbol) {
+                            Some(sequence) => *sequence,
+                            None => continue,
+                        };
+                        if depth.sequence_end <= sequence {
+                            continue;
+                        }
+                        if depth.sequence_start > sequence + 1 {
+                            warn!("KuCoin sequence gap for {}, refreshing snapshot", symbol);
+                            sequences.remove(&symbol);
+                            Self::refresh_snapshot(
+                                &client,
+                                &symbol,
+                                order_book_depth,
+                                &rate_limiter,
+                                &price_level_tx,
+                                &mut sequences,
+                                forwarded.entry(symbol.clone()).or_default(),
+                            )
+                            .await;
+                            continue;
+                        }
+
+                        forwarded
+                            .entry(symbol.clone())
+                            .or_default()
+                            .record(&depth.update);
+                        if price_level_tx.send(depth.update).await.is_err() {
+                            return Ok(());
+                        }
+                        sequences.insert(symbol, depth.sequence_end);
+                    }
+                    Message::Binary(data) if data.is_empty() => {
+                        sequences.clear();
+                        for symbol in &symbols {
+                            Self::refresh_snapshot(
+                                &client,
+                                symbol,
+                                order_book_depth,
+                                &rate_limiter,
+                                &price_level_tx,
+                                &mut sequences,
+                                forwarded.entry(symbol.clone()).or_default(),
+                            )
+                            .await;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Fetches a snapshot for `symbol` and forwards it, with removals for the levels of
+    /// `forwarded` it no longer has.
+    async fn refresh_snapshot(
+        client: &reqwest::Client,
+        symbol: &str,
+        order_book_depth: usize,
+        rate_limiter: &RateLimiter,
+        price_level_tx: &Sender<PriceLevelUpdate>,
+        sequences: &mut HashMap<String, u64>,
+        forwarded: &mut ForwardedBook,
+    ) {
+        info!("Getting KuCoin order book snapshot for {}", symbol);
+
+        let result = Self::get_order_book_snapshot(client, symbol, order_book_depth, rate_limiter)
+            .await
+            .and_then(|snapshot| Self::process_snapshot(symbol, snapshot));
+
+        match result {
+            Ok((sequence, mut update)) => {
+                forwarded.replace(&mut update);
+                if price_level_tx.send(update).await.is_ok() {
+                    sequences.insert(symbol.to_string(), sequence);
+                }
+            }
+            Err(e) => error!("Failed to get snapshot for {}: {}", symbol, e),
+        }
+    }
+
+    fn process_snapshot(
+        symbol: &str,
+        snapshot: KuCoinOrderBookSnapshot,
+    ) -> Result<(u64, PriceLevelUpdate)> {
+        let sequence = snapshot.sequence.parse::<u64>().map_err(|e| {
+            AggregatorError::parsing(
+                "KuCoinOrderBookSnapshot",
+                format!("Invalid sequence: {}", e),
+            )
+        })?;
+        let event_time = snapshot.time.and_then(DateTime::from_timestamp_millis);
+        let bids = Self::parse_snapshot_levels(&snapshot.bids)?;
+        let asks = Self::parse_snapshot_levels(&snapshot.asks)?;
+
+        Ok((
+            sequence,
+            Self::create_price_level_update(symbol, &bids, &asks, event_time),
+        ))
+    }
+
+    /// Get order book snapshot from the public REST API, which serves the top 20 or 100 levels
+    async fn get_order_book_snapshot(
+        client: &reqwest::Client,
+        symbol: &str,
+        order_book_depth: usize,
+        rate_limiter: &RateLimiter,
+    ) -> Result<KuCoinOrderBookSnapshot> {
+        rate_limiter.try_acquire()?;
+
+        let levels = if order_book_depth <= 20 { 20 } else { 100 };
+        let url = format!("{}/level2_{}", KUCOIN_ORDER_BOOK_URL, levels);
+        let response = client
+            .get(url)
+            .query(&[("symbol", symbol)])
+            .send()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to get snapshot: {}", e)))?;
+        Self::check_status(&response)?;
+
+        let response: KuCoinResponse<KuCoinOrderBookSnapshot> =
+            response.json().await.map_err(|e| {
+                AggregatorError::parsing(
+                    "KuCoinOrderBookSnapshot",
+                    format!("Failed to parse snapshot: {}", e),
+                )
+            })?;
+        Self::response_data(response)
+    }
+}
+
+#[async_trait]
+impl OrderBookService for KuCoin {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        let symbols = SymbolMapper::new().to_exchange_all(&Exchange::KuCoin, pairs)?;
+        info!(
+            "Starting KuCoin order book service for {}",
+            symbols.join(", ")
+        );
+
+        let client = http_client(self.proxy.as_ref())?;
+        let (ws_rx, ws_handle) = self.spawn_websocket_stream(
+            symbols.clone(),
+            exchange_stream_buffer,
+            client.clone(),
+            Shutdown::new(shutdown),
+        );
+        let processor_handle =
+            self.spawn_stream_processor(symbols, order_book_depth, ws_rx, price_level_tx, client);
+
+        Ok(vec![ws_handle, processor_handle])
+    }
+}
+
+#[async_trait]
+impl ExchangeInfoService for KuCoin {
+    async fn fetch_instruments(&self, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+        let client = http_client(self.proxy.as_ref())?;
+        let body = get_body(&client, KUCOIN_SYMBOLS_URL, Some(&self.rate_limiter)).await?;
+        Self::parse_instruments(&body, pairs)
+    }
+}
+
+#[async_trait]
+impl MarketDiscovery for KuCoin {
+    async fn list_markets(&self) -> Result<Vec<MarketListing>> {
+        let client = http_client(self.proxy.as_ref())?;
+        let body = get_body(&client, KUCOIN_SYMBOLS_URL, Some(&self.rate_limiter)).await?;
+        Self::parse_markets(&body)
+    }
+}
+
+impl Default for KuCoin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,32 +1,178 @@
 //! Exchange connectors for supported cryptocurrency exchanges
+//!
+//! Every connector lives behind a cargo feature of the same name (`binance`, `bitfinex`, `bitget`,
+//! `bitstamp`, `bybit`, `coinbase`, `gateio`, `hyperliquid`, `kraken`, `kraken-futures`, `kucoin`,
+//! `mexc`) so minimal deployments only compile the venues they use. The `rest-polling` feature
+//! adds `RestPollingConnector`, a REST snapshot fallback for venues whose WebSocket is
+//! unavailable, and `clock-skew` adds `ClockSkewSampler`, which estimates exchange clock offsets
+//! from their server time endpoints.
+//! The `full` feature, enabled by default, turns all of them on.
+//!
+//! `register_all` registers every compiled-in connector, and the market discovery of the venues
+//! that support it, on an aggregator's `ConnectorRegistry`.
 
+pub mod auth;
+#[cfg(feature = "binance")]
 pub mod binance;
+#[cfg(feature = "bitfinex")]
+pub mod bitfinex;
+#[cfg(feature = "bitget")]
+pub mod bitget;
+#[cfg(feature = "bitstamp")]
 pub mod bitstamp;
+#[cfg(feature = "bybit")]
 pub mod bybit;
+pub mod capture;
+#[cfg(feature = "clock-skew")]
+pub mod clock_skew;
+#[cfg(feature = "coinbase")]
 pub mod coinbase;
+pub mod conflation;
+#[cfg(any(
+    feature = "binance",
+    feature = "bybit",
+    feature = "coinbase",
+    feature = "gateio",
+    feature = "kraken",
+    feature = "kucoin"
+))]
+mod exchange_info;
+#[cfg(feature = "gateio")]
+pub mod gateio;
+#[cfg(feature = "hyperliquid")]
+pub mod hyperliquid;
+#[cfg(feature = "kraken")]
 pub mod kraken;
+#[cfg(feature = "kraken-futures")]
+pub mod kraken_futures;
+#[cfg(feature = "kucoin")]
+pub mod kucoin;
+#[cfg(feature = "mexc")]
+pub mod mexc;
+pub mod proxy;
+pub mod rate_limit;
+pub mod reconnect;
+mod registry;
+#[cfg(feature = "rest-polling")]
+pub mod rest_polling;
+pub mod snapshot_sync;
+pub mod symbol;
+#[cfg(any(
+    feature = "binance",
+    feature = "bitstamp",
+    feature = "bybit",
+    feature = "coinbase",
+    feature = "kraken"
+))]
+mod trade_stream;
 
 use async_trait::async_trait;
 use tokio::sync::mpsc::Sender;
 use tokio::task::JoinHandle;
 
-use aggregator_core::{AggregatorError, PriceLevelUpdate, Result};
+use aggregator_core::{
+    BalanceUpdate, InstrumentInfo, InstrumentRegistry, OrderEvent, OrderUpdate, Result, Trade,
+    TradingPair,
+};
+
+/// Defined in `aggregator-core` so the aggregator can run connectors registered in its
+/// `ConnectorRegistry`
+pub use aggregator_core::OrderBookService;
 
 #[async_trait]
-pub trait OrderBookService {
-    /// Spawns an order book service to stream order book data and handle stream events for a specified pair.
-    async fn spawn_order_book_service(
+pub trait TradeStreamService {
+    /// Spawns a trade service that streams individual trade prints for the specified pairs over a
+    /// single WebSocket connection.
+    async fn spawn_trade_service(
         &self,
-        pair: [&str; 2],
-        order_book_depth: usize,
-        exchange_stream_buffer: usize,
-        price_level_tx: Sender<PriceLevelUpdate>,
+        pairs: &[TradingPair],
+        trade_tx: Sender<Trade>,
     ) -> Result<Vec<JoinHandle<Result<()>>>>;
 }
 
+#[async_trait]
+pub trait OrderEventService {
+    /// Spawns a service that streams order-by-order (level 3) events for the specified pairs over
+    /// a single WebSocket connection. Events carry the exchange's sequence numbers, so consumers
+    /// can detect gaps and rebuild their books from a fresh snapshot.
+    async fn spawn_order_event_service(
+        &self,
+        pairs: &[TradingPair],
+        order_event_tx: Sender<OrderEvent>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>>;
+}
+
+#[async_trait]
+pub trait AuthenticatedService {
+    /// Spawns a service that streams the account's own order and balance updates from the
+    /// exchange's private channels, using the connector's configured credentials. Account-wide
+    /// streams may report orders for pairs outside `pairs`.
+    async fn spawn_user_data_service(
+        &self,
+        pairs: &[TradingPair],
+        order_tx: Sender<OrderUpdate>,
+        balance_tx: Sender<BalanceUpdate>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>>;
+}
+
+#[async_trait]
+pub trait ExchangeInfoService {
+    /// Fetches tick size, lot size and order minimums for the specified pairs from the exchange's
+    /// public REST API. Fails if the exchange does not list one of the pairs.
+    async fn fetch_instruments(&self, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>>;
+
+    /// Fetches the metadata for `pairs` and stores it in `registry`, returning how many
+    /// instruments were loaded.
+    async fn load_instruments(
+        &self,
+        pairs: &[TradingPair],
+        registry: &InstrumentRegistry,
+    ) -> Result<usize> {
+        let instruments = self.fetch_instruments(pairs).await?;
+        let loaded = instruments.len();
+        registry.extend(instruments);
+        Ok(loaded)
+    }
+}
+
+pub use auth::{Credentials, UserDataEvent};
+pub use capture::{Capture, CaptureSource, CapturedFrame, ReplayPace, ReplayStats};
+#[cfg(feature = "clock-skew")]
+pub use clock_skew::{
+    ClockSkew, ClockSkewEstimator, ClockSkewSampler, ServerTimeEndpoint, SkewCorrected, SkewSample,
+};
+pub use conflation::{Conflated, Conflator};
+pub use proxy::{Proxy, ProxyScheme};
+pub use rate_limit::RateLimiter;
+pub use reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown, Watchdog};
+pub use registry::register_all;
+#[cfg(feature = "rest-polling")]
+pub use rest_polling::RestPollingConnector;
+pub use snapshot_sync::{ForwardedBook, SequencedUpdate, SnapshotSync, SyncStep};
+pub use symbol::SymbolMapper;
+
 // Re-export exchange implementations
+#[cfg(feature = "binance")]
 pub use binance::Binance;
+#[cfg(feature = "bitfinex")]
+pub use bitfinex::Bitfinex;
+#[cfg(feature = "bitget")]
+pub use bitget::Bitget;
+#[cfg(feature = "bitstamp")]
 pub use bitstamp::Bitstamp;
+#[cfg(feature = "bybit")]
 pub use bybit::Bybit;
-pub use coinbase::Coinbase;
+#[cfg(feature = "coinbase")]
+pub use coinbase::{Coinbase, CoinbaseL3Book, RestingOrder};
+#[cfg(feature = "gateio")]
+pub use gateio::GateIo;
+#[cfg(feature = "hyperliquid")]
+pub use hyperliquid::Hyperliquid;
+#[cfg(feature = "kraken")]
 pub use kraken::Kraken;
+#[cfg(feature = "kraken-futures")]
+pub use kraken_futures::KrakenFutures;
+#[cfg(feature = "kucoin")]
+pub use kucoin::KuCoin;
+#[cfg(feature = "mexc")]
+pub use mexc::Mexc;
@@ -0,0 +1,475 @@
+//! MEXC Exchange Connector
+//! Streams spot order books from MEXC's v3 WebSocket `limit.depth` streams. Every push is a full
+//! snapshot of the top levels, so consecutive snapshots are diffed into deltas with removed
+//! levels reported at zero quantity.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::capture::Capture;
+use crate::proxy::{connect_websocket, Proxy};
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy, Shutdown};
+use crate::{OrderBookService, SymbolMapper};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, HealthEvent, PriceLevelUpdate, Result,
+    TradingPair,
+};
+
+const MEXC_WS_URL: &str = "wss://wbs.mexc.com/ws";
+const LIMIT_DEPTH_STREAM: &str = "spot@public.limit.depth.v3.api";
+/// MEXC accepts at most 30 streams per connection
+const MAX_STREAMS: usize = 30;
+/// Connections without a subscription or ping for a minute are closed by the server
+const PING_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(20);
+
+pub struct Mexc {
+    websocket_url: String,
+    reconnect_policy: ReconnectPolicy,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    proxy: Option<Proxy>,
+    capture: Option<Capture>,
+}
+
+/// Turns `limit.depth` snapshots into updates, keeping the previous snapshot per symbol so levels
+/// that disappeared are emitted with a zero quantity.
+#[derive(Debug, Default)]
+pub struct MexcBookParser {
+    depth: Option<usize>,
+    previous: HashMap<String, PriceLevelUpdate>,
+}
+
+#[derive(Debug, Serialize)]
+struct MexcRequest {
+    method: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    params: Vec<String>,
+}
+
+/// A reply to a request: `code` is zero on success and `msg` echoes the stream or `PONG`.
+#[derive(Debug, Deserialize)]
+struct MexcResponse {
+    code: i64,
+    #[serde(default)]
+    msg: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MexcPush {
+    #[serde(rename = "c")]
+    stream: String,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "t")]
+    time: i64,
+    #[serde(rename = "d")]
+    book: MexcBook,
+}
+
+/// A book snapshot, best levels first.
+#[derive(Debug, Deserialize)]
+struct MexcBook {
+    bids: Vec<MexcLevel>,
+    asks: Vec<MexcLevel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MexcLevel {
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "v")]
+    quantity: String,
+}
+
+impl MexcBookParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `depth` levels per side of each snapshot.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Handles one raw message. Book snapshots yield a `PriceLevelUpdate` against the previous
+    /// snapshot of the symbol; subscription replies and pongs yield `None`.
+    pub fn handle_message(&mut self, message: &str) -> Result<Option<PriceLevelUpdate>> {
+        let value: Value = serde_json::from_str(message).map_err(|e| {
+            AggregatorError::parsing("MexcMessage", format!("Failed to parse message: {}", e))
+        })?;
+
+        if value.get("code").is_some() {
+            let response: MexcResponse = serde_json::from_value(value).map_err(|e| {
+                AggregatorError::parsing("MexcResponse", format!("Failed to parse reply: {}", e))
+            })?;
+            if response.code != 0 {
+                return Err(AggregatorError::exchange("mexc", response.msg));
+            }
+            return Ok(None);
+        }
+
+        let push: MexcPush = serde_json::from_value(value).map_err(|e| {
+            AggregatorError::parsing("MexcBook", format!("Failed to parse book: {}", e))
+        })?;
+        if !push.stream.starts_with(LIMIT_DEPTH_STREAM) {
+            return Ok(None);
+        }
+
+        let depth = self.depth.unwrap_or(usize::MAX);
+        let timestamp = Utc::now();
+        let mut bids = Vec::new();
+        for level in push.book.bids.iter().take(depth) {
+            let (price, quantity) = Self::parse_level(level)?;
+            bids.push(Bid {
+                price,
+                quantity,
+                exchange: Exchange::Mexc,
+                timestamp,
+            });
+        }
+        let mut asks = Vec::new();
+        for level in push.book.asks.iter().take(depth) {
+            let (price, quantity) = Self::parse_level(level)?;
+            asks.push(Ask {
+                price,
+                quantity,
+                exchange: Exchange::Mexc,
+                timestamp,
+            });
+        }
+
+        let snapshot = PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            symbol: push.symbol,
+            exchange: Exchange::Mexc,
+            bids,
+            asks,
+            timestamp,
+            funding: None,
+            event_time: DateTime::from_timestamp_millis(push.time),
+            market_type: None,
+        };
+
+        let mut update = snapshot.clone();
+        if let Some(previous) = self.previous.insert(snapshot.symbol.clone(), snapshot) {
+            update.mark_removed_levels(&previous);
+        }
+        Ok(Some(update))
+    }
+
+    fn parse_level(level: &MexcLevel) -> Result<(f64, f64)> {
+        let price = level
+            .price
+            .parse::<f64>()
+            .map_err(|e| AggregatorError::parsing("PriceLevel", format!("Invalid price: {}", e)))?;
+        let quantity = level.quantity.parse::<f64>().map_err(|e| {
+            AggregatorError::parsing("PriceLevel", format!("Invalid quantity: {}", e))
+        })?;
+        Ok((price, quantity))
+    }
+}
+
+impl Mexc {
+    pub fn new() -> Self {
+        Self {
+            websocket_url: MEXC_WS_URL.to_string(),
+            reconnect_policy: ReconnectPolicy::default(),
+            health_tx: None,
+            proxy: None,
+            capture: None,
+        }
+    }
+
+    /// Builds a connector from the shared exchange settings. MEXC has no public sandbox, so
+    /// `sandbox: true` is rejected rather than silently streaming production data.
+    pub fn from_config(config: &ExchangeConfig) -> Result<Self> {
+        if config.sandbox {
+            return Err(AggregatorError::validation(
+                "sandbox",
+                "Mexc does not provide a sandbox environment",
+            ));
+        }
+        let mut connector =
+            Self::new().with_reconnect_policy(ReconnectPolicy::from_config(&config.websocket));
+        connector.proxy = Proxy::from_config(config)?;
+        Ok(connector)
+    }
+
+    /// Stream from a custom endpoint, such as a local mock server.
+    pub fn with_websocket_url(mut self, websocket_url: &str) -> Self {
+        self.websocket_url = websocket_url.to_string();
+        self
+    }
+
+    pub fn websocket_url(&self) -> &str {
+        &self.websocket_url
+    }
+
+    /// Use a custom reconnect policy for every stream this connector spawns.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Route WebSocket traffic through `proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Record every raw WebSocket message to `capture`.
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(Exchange::Mexc, self.health_tx.clone())
+    }
+
+    /// The `limit.depth` stream of `symbol` with the fewest of the offered 5, 10 or 20 levels
+    /// covering `depth`.
+    pub fn depth_stream(symbol: &str, depth: usize) -> String {
+        let levels = match depth {
+            0..=5 => 5,
+            6..=10 => 10,
+            _ => 20,
+        };
+        format!("{}@{}@{}", LIMIT_DEPTH_STREAM, symbol, levels)
+    }
+
+    /// Builds one subscription to every stream in `streams`.
+    pub fn subscription(streams: &[String]) -> Result<String> {
+        let request = MexcRequest {
+            method: "SUBSCRIPTION".to_string(),
+            params: streams.to_vec(),
+        };
+        serde_json::to_string(&request).map_err(AggregatorError::Serialization)
+    }
+
+    /// Spawn WebSocket stream for the depth streams
+    fn spawn_websocket_stream(
+        &self,
+        subscription: String,
+        exchange_stream_buffer: usize,
+        mut shutdown: Shutdown,
+    ) -> (Receiver<Message>, JoinHandle<Result<()>>) {
+        let (ws_tx, ws_rx) = tokio::sync::mpsc::channel::<Message>(exchange_stream_buffer);
+        let url = self.websocket_url.clone();
+        let reconnect_policy = self.reconnect_policy.clone();
+        let health = self.health_reporter();
+        let proxy = self.proxy.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+            loop {
+                let result = Self::connect_websocket(
+                    &url,
+                    &subscription,
+                    proxy.as_ref(),
+                    &ws_tx,
+                    &mut backoff,
+                    &health,
+                    &mut shutdown,
+                )
+                .await;
+                if shutdown.is_triggered() {
+                    return Ok(());
+                }
+
+                match result {
+                    Ok(()) => {
+                        warn!("Mexc WebSocket connection closed, reconnecting...");
+                        health.disconnected("WebSocket connection closed");
+                    }
+                    Err(e) => {
+                        error!("Mexc WebSocket connection error: {}", e);
+                        health.disconnected(e.to_string());
+                    }
+                }
+                if ws_tx.is_closed() {
+                    return Ok(());
+                }
+
+                tokio::select! {
+                    result = health.wait_to_reconnect(&mut backoff) => result?,
+                    _ = shutdown.recv() => return Ok(()),
+                }
+            }
+        });
+
+        (ws_rx, handle)
+    }
+
+    async fn connect_websocket(
+        url: &str,
+        subscription: &str,
+        proxy: Option<&Proxy>,
+        ws_tx: &Sender<Message>,
+        backoff: &mut Backoff,
+        health: &HealthReporter,
+        shutdown: &mut Shutdown,
+    ) -> Result<()> {
+        let (mut ws_stream, _) = connect_websocket(url, proxy).await?;
+
+        info!("Connected to Mexc WebSocket");
+        backoff.reset();
+        health.connected();
+
+        ws_stream
+            .send(Message::Text(subscription.to_string()))
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to send subscription: {}", e)))?;
+
+        let ping = serde_json::to_string(&MexcRequest {
+            method: "PING".to_string(),
+            params: Vec::new(),
+        })
+        .map_err(AggregatorError::Serialization)?;
+        let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+        ping_interval.tick().await;
+
+        let mut watchdog = backoff.policy().watchdog();
+        loop {
+            let msg = tokio::select! {
+                msg = ws_stream.next() => match msg {
+                    Some(msg) => msg,
+                    None => return Ok(()),
+                },
+                // Pings keep quiet books alive and their pongs feed the watchdog
+                _ = ping_interval.tick() => {
+                    if let Err(e) = ws_stream.send(Message::Text(ping.clone())).await {
+                        error!("Failed to send ping: {}", e);
+                        return Ok(());
+                    }
+                    continue;
+                }
+                silent_for = watchdog.stalled() => return Err(health.stalled(silent_for)),
+                _ = shutdown.recv() => {
+                    info!("Closing Mexc order book stream");
+                    let _ = ws_stream.close(None).await;
+                    return Ok(());
+                }
+            };
+            watchdog.touch();
+
+            match msg {
+                Ok(Message::Text(text)) => {
+                    if let Err(e) = ws_tx.send(Message::Text(text)).await {
+                        warn!("Mexc stream processor stopped: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Ping(payload)) => {
+                    if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                        error!("Failed to send pong: {}", e);
+                        return Ok(());
+                    }
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Err(e) => {
+                    return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Spawn stream processor that diffs snapshots into updates
+    fn spawn_stream_processor(
+        mut ws_rx: Receiver<Message>,
+        order_book_depth: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        capture: Option<Capture>,
+    ) -> JoinHandle<Result<()>> {
+        tokio::spawn(async move {
+            let mut parser = MexcBookParser::new().with_depth(order_book_depth);
+
+            while let Some(message) = ws_rx.recv().await {
+                if let Message::Text(text) = message {
+                    if let Some(capture) = &capture {
+                        capture.record(&Exchange::Mexc, &text);
+                    }
+                    match parser.handle_message(&text) {
+                        Ok(Some(update)) => {
+                            if price_level_tx.send(update).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => error!("Failed to process book message: {}", e),
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl OrderBookService for Mexc {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        if pairs.len() > MAX_STREAMS {
+            return Err(AggregatorError::validation(
+                "pairs",
+                format!(
+                    "Mexc streams at most {} pairs per connection, got {}",
+                    MAX_STREAMS,
+                    pairs.len()
+                ),
+            ));
+        }
+        let symbols = SymbolMapper::new().to_exchange_all(&Exchange::Mexc, pairs)?;
+        info!(
+            "Starting Mexc order book service for {}",
+            symbols.join(", ")
+        );
+
+        let streams: Vec<String> = symbols
+            .iter()
+            .map(|symbol| Self::depth_stream(symbol, order_book_depth))
+            .collect();
+        let (ws_rx, ws_handle) = self.spawn_websocket_stream(
+            Self::subscription(&streams)?,
+            exchange_stream_buffer,
+            Shutdown::new(shutdown),
+        );
+        let processor_handle = Self::spawn_stream_processor(
+            ws_rx,
+            order_book_depth,
+            price_level_tx,
+            self.capture.clone(),
+        );
+
+        Ok(vec![ws_handle, processor_handle])
+    }
+}
+
+impl Default for Mexc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,116 @@
+//! Rate Limiting Module
+//! Token bucket limiter applied to connector REST requests
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use aggregator_core::{AggregatorError, Clock, RateLimitConfig, Result, SystemClock};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: DateTime<Utc>,
+}
+
+/// Token bucket rate limiter built from a `RateLimitConfig`.
+///
+/// The bucket holds up to `burst_size` tokens and refills at `requests_per_second`. Every request
+/// takes one token; when none are left the request is rejected with `AggregatorError::RateLimit`
+/// instead of being sent. Clones share the same bucket.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    resource: String,
+    capacity: f64,
+    refill_per_second: f64,
+    bucket: Arc<Mutex<Bucket>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl RateLimiter {
+    /// Creates a full bucket for `resource`, which is used in the error returned when exhausted.
+    pub fn new(resource: &str, config: &RateLimitConfig) -> Self {
+        let capacity = config.burst_size.max(1) as f64;
+        Self {
+            resource: resource.to_string(),
+            capacity,
+            refill_per_second: config.requests_per_second as f64,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: capacity,
+                last_refill: Utc::now(),
+            })),
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// Refills by `clock`'s time instead of the system clock's, starting with a full bucket
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.bucket = Arc::new(Mutex::new(Bucket {
+            tokens: self.capacity,
+            last_refill: clock.now(),
+        }));
+        self.clock = clock;
+        self
+    }
+
+    /// Takes a token if one is available, otherwise returns `AggregatorError::RateLimit`.
+    pub fn try_acquire(&self) -> Result<()> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        self.refill(&mut bucket);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(AggregatorError::rate_limit(
+                &self.resource,
+                format!(
+                    "Request budget exhausted, next token in {:?}",
+                    self.wait_time(&bucket)
+                ),
+            ))
+        }
+    }
+
+    /// Takes a token, waiting up to `max_wait` for one to become available.
+    pub async fn acquire(&self, max_wait: Duration) -> Result<()> {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+            self.refill(&mut bucket);
+            self.wait_time(&bucket)
+        };
+
+        if !wait.is_zero() && wait <= max_wait {
+            self.clock.sleep(wait).await;
+        }
+        self.try_acquire()
+    }
+
+    /// Returns the number of whole tokens currently available.
+    pub fn available(&self) -> u32 {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        self.refill(&mut bucket);
+        bucket.tokens.floor() as u32
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = self.clock.now();
+        // A clock set back refills nothing
+        let elapsed = (now - bucket.last_refill)
+            .to_std()
+            .unwrap_or_default()
+            .as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        bucket.last_refill = bucket.last_refill.max(now);
+    }
+
+    fn wait_time(&self, bucket: &Bucket) -> Duration {
+        if bucket.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+        if self.refill_per_second <= 0.0 {
+            return Duration::MAX;
+        }
+        Duration::from_secs_f64((1.0 - bucket.tokens) / self.refill_per_second)
+    }
+}
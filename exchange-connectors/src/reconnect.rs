@@ -0,0 +1,314 @@
+//! Reconnection Module
+//! Exponential backoff with jitter for connector WebSocket streams, stall detection, shutdown
+//! signalling, plus health event reporting
+
+use rand::Rng;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::time::Instant;
+use tracing::{error, warn};
+
+use aggregator_core::{
+    AggregatorError, Exchange, HealthEvent, HealthEventKind, Result, WebSocketConfig,
+};
+
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(60);
+const DEFAULT_MULTIPLIER: f64 = 2.0;
+const DEFAULT_JITTER: f64 = 0.2;
+
+/// Reconnect settings shared by every connector.
+///
+/// The delay before attempt `n` (starting at 1) is `initial_delay * multiplier^(n-1)`, capped at
+/// `max_delay`, then randomised by up to ±`jitter` of itself so that many connections dropped at
+/// once do not reconnect in lockstep. `max_attempts` of 0 retries forever.
+///
+/// A connection that receives nothing for `stale_timeout` is treated as stalled and replaced. A
+/// zero `stale_timeout` disables the check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+    pub max_attempts: u32,
+    pub stale_timeout: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Builds a policy from the shared WebSocket settings.
+    pub fn from_config(config: &WebSocketConfig) -> Self {
+        Self {
+            initial_delay: Duration::from_millis(config.reconnect_interval),
+            max_delay: DEFAULT_MAX_DELAY.max(Duration::from_millis(config.reconnect_interval)),
+            multiplier: DEFAULT_MULTIPLIER,
+            jitter: DEFAULT_JITTER,
+            max_attempts: config.max_reconnect_attempts,
+            stale_timeout: Duration::from_millis(config.stale_timeout),
+        }
+    }
+
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self.max_delay = self.max_delay.max(initial_delay);
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_stale_timeout(mut self, stale_timeout: Duration) -> Self {
+        self.stale_timeout = stale_timeout;
+        self
+    }
+
+    /// Returns the delay before `attempt` without jitter applied.
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let millis = self.initial_delay.as_millis() as f64 * self.multiplier.powi(exponent);
+        let capped = millis.min(self.max_delay.as_millis() as f64);
+        Duration::from_millis(capped as u64)
+    }
+
+    /// Returns the delay before `attempt` with jitter applied.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self.base_delay(attempt);
+        if self.jitter <= 0.0 {
+            return base;
+        }
+        let factor = rand::thread_rng().gen_range(1.0 - self.jitter..=1.0 + self.jitter);
+        Duration::from_millis((base.as_millis() as f64 * factor) as u64)
+    }
+
+    /// Starts tracking consecutive failures against this policy.
+    pub fn backoff(&self) -> Backoff {
+        Backoff {
+            policy: self.clone(),
+            attempt: 0,
+        }
+    }
+
+    /// Starts watching a fresh connection for stalls.
+    pub fn watchdog(&self) -> Watchdog {
+        Watchdog::new(self.stale_timeout)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::from_config(&WebSocketConfig::default())
+    }
+}
+
+/// Consecutive failure counter for a single connection.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    policy: ReconnectPolicy,
+    attempt: u32,
+}
+
+impl Backoff {
+    /// Records a failure and returns how long to wait before retrying, or `None` once the
+    /// policy's attempt limit is exhausted.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.policy.max_attempts > 0 && self.attempt >= self.policy.max_attempts {
+            return None;
+        }
+        self.attempt += 1;
+        Some(self.policy.delay(self.attempt))
+    }
+
+    /// Clears the failure count after a successful connection.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Returns the number of consecutive failures recorded so far.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Returns the policy this backoff follows.
+    pub fn policy(&self) -> &ReconnectPolicy {
+        &self.policy
+    }
+}
+
+/// Tracks the time since a connection last received a message. Call `touch` for every message
+/// and race `stalled` against the next read.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    timeout: Duration,
+    last_message: Instant,
+}
+
+impl Watchdog {
+    /// A zero `timeout` never reports a stall.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            last_message: Instant::now(),
+        }
+    }
+
+    /// Records that a message was just received.
+    pub fn touch(&mut self) {
+        self.last_message = Instant::now();
+    }
+
+    /// Returns how long the connection has been silent.
+    pub fn silent_for(&self) -> Duration {
+        self.last_message.elapsed()
+    }
+
+    pub fn is_stale(&self) -> bool {
+        !self.timeout.is_zero() && self.silent_for() >= self.timeout
+    }
+
+    /// Completes once the connection has been silent for the timeout, returning how long that
+    /// was. Never completes when the watchdog is disabled.
+    pub async fn stalled(&self) -> Duration {
+        if self.timeout.is_zero() {
+            return std::future::pending().await;
+        }
+        tokio::time::sleep_until(self.last_message + self.timeout).await;
+        self.silent_for()
+    }
+}
+
+/// Shutdown signal for a connector's stream tasks, fed by `Aggregator::subscribe_shutdown`.
+///
+/// A shutdown message, a lagged receiver and a dropped sender all count as shutdown. Once seen,
+/// the signal stays triggered.
+#[derive(Debug)]
+pub struct Shutdown {
+    rx: broadcast::Receiver<()>,
+    triggered: bool,
+}
+
+impl Shutdown {
+    pub fn new(rx: broadcast::Receiver<()>) -> Self {
+        Self {
+            rx,
+            triggered: false,
+        }
+    }
+
+    /// Returns whether shutdown has been signalled, without waiting.
+    pub fn is_triggered(&mut self) -> bool {
+        if !self.triggered && !matches!(self.rx.try_recv(), Err(TryRecvError::Empty)) {
+            self.triggered = true;
+        }
+        self.triggered
+    }
+
+    /// Completes once shutdown has been signalled. Safe to race in `select!`.
+    pub async fn recv(&mut self) {
+        if !self.triggered {
+            let _ = self.rx.recv().await;
+            self.triggered = true;
+        }
+    }
+}
+
+/// Publishes connection health events for one exchange. Sending is best-effort: without a
+/// subscriber the events are dropped.
+#[derive(Debug, Clone)]
+pub struct HealthReporter {
+    exchange: Exchange,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+}
+
+impl HealthReporter {
+    pub fn new(exchange: Exchange, health_tx: Option<broadcast::Sender<HealthEvent>>) -> Self {
+        Self {
+            exchange,
+            health_tx,
+        }
+    }
+
+    pub fn exchange(&self) -> &Exchange {
+        &self.exchange
+    }
+
+    pub fn report(&self, kind: HealthEventKind) {
+        if let Some(health_tx) = &self.health_tx {
+            let _ = health_tx.send(HealthEvent::new(self.exchange.clone(), kind));
+        }
+    }
+
+    pub fn connected(&self) {
+        self.report(HealthEventKind::Connected);
+    }
+
+    pub fn disconnected(&self, reason: impl Into<String>) {
+        self.report(HealthEventKind::Disconnected {
+            reason: reason.into(),
+        });
+    }
+
+    /// Reports a stalled connection and returns the error the stream should end with so that it
+    /// reconnects.
+    pub fn stalled(&self, silent_for: Duration) -> AggregatorError {
+        warn!("{} connection silent for {:?}", self.exchange, silent_for);
+        self.report(HealthEventKind::Stale {
+            silent_ms: silent_for.as_millis() as u64,
+        });
+        AggregatorError::network(format!(
+            "No messages received for {}ms",
+            silent_for.as_millis()
+        ))
+    }
+
+    /// Waits out the next backoff delay, reporting the reconnect. Returns an error once the
+    /// policy gives up so the stream task can end.
+    pub async fn wait_to_reconnect(&self, backoff: &mut Backoff) -> Result<()> {
+        match backoff.next_delay() {
+            Some(delay) => {
+                warn!(
+                    "{} reconnecting in {:?} (attempt {})",
+                    self.exchange,
+                    delay,
+                    backoff.attempt()
+                );
+                self.report(HealthEventKind::Reconnecting {
+                    attempt: backoff.attempt(),
+                    delay_ms: delay.as_millis() as u64,
+                });
+                tokio::time::sleep(delay).await;
+                Ok(())
+            }
+            None => {
+                error!(
+                    "{} giving up after {} reconnect attempts",
+                    self.exchange,
+                    backoff.attempt()
+                );
+                self.report(HealthEventKind::ReconnectFailed {
+                    attempts: backoff.attempt(),
+                });
+                Err(AggregatorError::exchange(
+                    self.exchange.to_string(),
+                    format!("Gave up after {} reconnect attempts", backoff.attempt()),
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Registration of the built-in connectors on an aggregator's `ConnectorRegistry`
+
+#[cfg(any(
+    feature = "binance",
+    feature = "bitfinex",
+    feature = "bitget",
+    feature = "bitstamp",
+    feature = "bybit",
+    feature = "coinbase",
+    feature = "gateio",
+    feature = "hyperliquid",
+    feature = "kraken",
+    feature = "kraken-futures",
+    feature = "kucoin",
+    feature = "mexc"
+))]
+use std::sync::Arc;
+
+use aggregator_core::ConnectorRegistry;
+#[cfg(any(
+    feature = "binance",
+    feature = "bybit",
+    feature = "coinbase",
+    feature = "gateio",
+    feature = "kraken",
+    feature = "kucoin"
+))]
+use aggregator_core::MarketDiscovery;
+#[cfg(any(
+    feature = "binance",
+    feature = "bitfinex",
+    feature = "bitget",
+    feature = "bitstamp",
+    feature = "bybit",
+    feature = "coinbase",
+    feature = "gateio",
+    feature = "hyperliquid",
+    feature = "kraken",
+    feature = "kraken-futures",
+    feature = "kucoin",
+    feature = "mexc"
+))]
+use aggregator_core::{Exchange, ExchangeConfig, OrderBookService, Result};
+
+/// Registers the connector of every exchange compiled in, and the market discovery of those
+/// that list their markets (Binance, Bybit, Coinbase, Gate.io, Kraken and KuCoin), each built
+/// from the exchange's settings with its `from_config` constructor.
+///
+/// Register custom connectors after calling it: registering under an exchange that already has
+/// a factory replaces the built-in one.
+///
+/// ```rust
+/// use aggregator_core::{Aggregator, Config, ConnectorRegistry};
+///
+/// # async fn example() {
+/// let connectors = ConnectorRegistry::new();
+/// exchange_connectors::register_all(&connectors).await;
+/// let aggregator = Aggregator::new(Config::default()).with_connectors(connectors);
+/// # }
+/// ```
+#[cfg_attr(
+    not(any(
+        feature = "binance",
+        feature = "bitfinex",
+        feature = "bitget",
+        feature = "bitstamp",
+        feature = "bybit",
+        feature = "coinbase",
+        feature = "gateio",
+        feature = "hyperliquid",
+        feature = "kraken",
+        feature = "kraken-futures",
+        feature = "kucoin",
+        feature = "mexc"
+    )),
+    allow(unused_variables)
+)]
+pub async fn register_all(connectors: &ConnectorRegistry) {
+    #[cfg(feature = "binance")]
+    {
+        register(connectors, Exchange::Binance, crate::Binance::from_config).await;
+        register_discovery(connectors, Exchange::Binance, crate::Binance::from_config).await;
+    }
+    #[cfg(feature = "bitfinex")]
+    register(connectors, Exchange::Bitfinex, crate::Bitfinex::from_config).await;
+    #[cfg(feature = "bitget")]
+    register(connectors, Exchange::Bitget, crate::Bitget::from_config).await;
+    #[cfg(feature = "bitstamp")]
+    register(connectors, Exchange::Bitstamp, crate::Bitstamp::from_config).await;
+    #[cfg(feature = "bybit")]
+    {
+        register(connectors, Exchange::Bybit, crate::Bybit::from_config).await;
+        register_discovery(connectors, Exchange::Bybit, crate::Bybit::from_config).await;
+    }
+    #[cfg(feature = "coinbase")]
+    {
+        register(connectors, Exchange::Coinbase, crate::Coinbase::from_config).await;
+        register_discovery(connectors, Exchange::Coinbase, crate::Coinbase::from_config).await;
+    }
+    #[cfg(feature = "gateio")]
+    {
+        register(connectors, Exchange::GateIo, crate::GateIo::from_config).await;
+        register_discovery(connectors, Exchange::GateIo, crate::GateIo::from_config).await;
+    }
+    #[cfg(feature = "hyperliquid")]
+    register(
+        connectors,
+        Exchange::Hyperliquid,
+        crate::Hyperliquid::from_config,
+    )
+    .await;
+    #[cfg(feature = "kraken")]
+    {
+        register(connectors, Exchange::Kraken, crate::Kraken::from_config).await;
+        register_discovery(connectors, Exchange::Kraken, crate::Kraken::from_config).await;
+    }
+    #[cfg(feature = "kraken-futures")]
+    register(
+        connectors,
+        Exchange::KrakenFutures,
+        crate::KrakenFutures::from_config,
+    )
+    .await;
+    #[cfg(feature = "kucoin")]
+    {
+        register(connectors, Exchange::KuCoin, crate::KuCoin::from_config).await;
+        register_discovery(connectors, Exchange::KuCoin, crate::KuCoin::from_config).await;
+    }
+    #[cfg(feature = "mexc")]
+    register(connectors, Exchange::Mexc, crate::Mexc::from_config).await;
+}
+
+#[cfg(any(
+    feature = "binance",
+    feature = "bitfinex",
+    feature = "bitget",
+    feature = "bitstamp",
+    feature = "bybit",
+    feature = "coinbase",
+    feature = "gateio",
+    feature = "hyperliquid",
+    feature = "kraken",
+    feature = "kraken-futures",
+    feature = "kucoin",
+    feature = "mexc"
+))]
+async fn register<C>(
+    connectors: &ConnectorRegistry,
+    exchange: Exchange,
+    from_config: fn(&ExchangeConfig) -> Result<C>,
+) where
+    C: OrderBookService + Send + Sync + 'static,
+{
+    connectors
+        .register(&exchange.to_string(), move |config| {
+            Ok(Arc::new(from_config(config)?))
+        })
+        .await;
+}
+
+#[cfg(any(
+    feature = "binance",
+    feature = "bybit",
+    feature = "coinbase",
+    feature = "gateio",
+    feature = "kraken",
+    feature = "kucoin"
+))]
+async fn register_discovery<D>(
+    connectors: &ConnectorRegistry,
+    exchange: Exchange,
+    from_config: fn(&ExchangeConfig) -> Result<D>,
+) where
+    D: MarketDiscovery + 'static,
+{
+    connectors
+        .register_discovery(&exchange.to_string(), move |config| {
+            Ok(Arc::new(from_config(config)?))
+        })
+        .await;
+}
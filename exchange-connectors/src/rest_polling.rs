@@ -0,0 +1,429 @@
+//! REST Polling Connector
+//! Degraded-mode order book feed that polls REST depth snapshots instead of streaming over a
+//! WebSocket
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::proxy::{http_client, Proxy};
+use crate::rate_limit::RateLimiter;
+use crate::reconnect::{HealthReporter, Shutdown};
+use crate::{OrderBookService, SymbolMapper};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, Exchange, ExchangeConfig, HealthEvent, OrderBookConfig,
+    PriceLevelUpdate, RateLimitConfig, Result, TradingPair,
+};
+
+const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_millis(1000);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Polls an exchange's REST depth endpoint for every pair at a fixed interval.
+///
+/// Meant as a fallback for venues whose WebSocket keeps failing: each poll emits the current
+/// snapshot as a `PriceLevelUpdate`, with zero-quantity levels for prices that dropped out since
+/// the previous poll so downstream books stay consistent. Connectivity is reported through the
+/// same health events as the streaming connectors.
+///
+/// The endpoint is described by a URL template with `{symbol}` and `{depth}` placeholders, a JSON
+/// pointer to the object holding the book, and the keys of its bid and ask arrays. Each level is
+/// an array whose first two entries are price and quantity, as strings or numbers.
+#[derive(Debug, Clone)]
+pub struct RestPollingConnector {
+    exchange: Exchange,
+    url_template: String,
+    book_pointer: String,
+    bids_key: String,
+    asks_key: String,
+    event_time_key: Option<String>,
+    update_interval: Duration,
+    health_tx: Option<broadcast::Sender<HealthEvent>>,
+    rate_limiter: RateLimiter,
+    proxy: Option<Proxy>,
+}
+
+impl RestPollingConnector {
+    pub fn new(exchange: Exchange, url_template: &str) -> Self {
+        Self {
+            rate_limiter: RateLimiter::new(
+                &format!("{}-rest", exchange),
+                &RateLimitConfig::default(),
+            ),
+            exchange,
+            url_template: url_template.to_string(),
+            book_pointer: String::new(),
+            bids_key: "bids".to_string(),
+            asks_key: "asks".to_string(),
+            event_time_key: None,
+            update_interval: DEFAULT_UPDATE_INTERVAL,
+            health_tx: None,
+            proxy: None,
+        }
+    }
+
+    /// Returns a connector preconfigured with the public depth endpoint of `exchange`.
+    pub fn for_exchange(exchange: Exchange) -> Result<Self> {
+        let connector = match exchange {
+            Exchange::Binance => Self::new(
+                exchange,
+                "https://api.binance.com/api/v3/depth?symbol={symbol}&limit={depth}",
+            ),
+            Exchange::Bybit => Self::new(
+                exchange,
+                "https://api.bybit.com/v5/market/orderbook?category=spot&symbol={symbol}&limit={depth}",
+            )
+            .with_book_pointer("/result")
+            .with_level_keys("b", "a")
+            .with_event_time_key("ts"),
+            Exchange::Bitstamp => Self::new(
+                exchange,
+                "https://www.bitstamp.net/api/v2/order_book/{symbol}/",
+            ),
+            Exchange::Coinbase => Self::new(
+                exchange,
+                "https://api.exchange.coinbase.com/products/{symbol}/book?level=2",
+            ),
+            Exchange::GateIo => Self::new(
+                exchange,
+                "https://api.gateio.ws/api/v4/spot/order_book?currency_pair={symbol}&limit={depth}",
+            ),
+            Exchange::KuCoin => Self::new(
+                exchange,
+                "https://api.kucoin.com/api/v1/market/orderbook/level2_100?symbol={symbol}",
+            )
+            .with_book_pointer("/data")
+            .with_event_time_key("time"),
+            Exchange::OKX => Self::new(
+                exchange,
+                "https://www.okx.com/api/v5/market/books?instId={symbol}&sz={depth}",
+            )
+            .with_book_pointer("/data/0")
+            .with_event_time_key("ts"),
+            other => {
+                return Err(AggregatorError::validation(
+                    "exchange",
+                    format!("REST polling is not available for {}", other),
+                ))
+            }
+        };
+        Ok(connector)
+    }
+
+    /// Builds a polling connector for `exchange` from the shared settings, polling every
+    /// `order_book.update_interval` milliseconds. The built-in endpoints are production only, so
+    /// `sandbox: true` is rejected.
+    pub fn from_config(
+        exchange: Exchange,
+        config: &ExchangeConfig,
+        order_book: &OrderBookConfig,
+    ) -> Result<Self> {
+        if config.sandbox {
+            return Err(AggregatorError::validation(
+                "sandbox",
+                "REST polling does not support sandbox environments",
+            ));
+        }
+        let mut connector = Self::for_exchange(exchange)?
+            .with_rate_limit(&config.rate_limit)
+            .with_update_interval(Duration::from_millis(order_book.update_interval));
+        connector.proxy = Proxy::from_config(config)?;
+        Ok(connector)
+    }
+
+    /// Read the book from the object at `pointer` (RFC 6901) instead of the response root.
+    pub fn with_book_pointer(mut self, pointer: &str) -> Self {
+        self.book_pointer = pointer.to_string();
+        self
+    }
+
+    /// Read bid and ask levels from `bids_key` and `asks_key` instead of `bids` and `asks`.
+    pub fn with_level_keys(mut self, bids_key: &str, asks_key: &str) -> Self {
+        self.bids_key = bids_key.to_string();
+        self.asks_key = asks_key.to_string();
+        self
+    }
+
+    /// Take the exchange time of each snapshot from `key`, in milliseconds since the epoch.
+    pub fn with_event_time_key(mut self, key: &str) -> Self {
+        self.event_time_key = Some(key.to_string());
+        self
+    }
+
+    /// Poll every pair once per `update_interval`.
+    pub fn with_update_interval(mut self, update_interval: Duration) -> Self {
+        self.update_interval = update_interval;
+        self
+    }
+
+    /// Limit snapshot requests according to `config`.
+    pub fn with_rate_limit(mut self, config: &RateLimitConfig) -> Self {
+        self.rate_limiter = RateLimiter::new(&format!("{}-rest", self.exchange), config);
+        self
+    }
+
+    /// Send snapshot requests through `proxy`. Only HTTP proxies can carry REST traffic.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Publish connection health events on `health_tx`.
+    pub fn with_health_events(mut self, health_tx: broadcast::Sender<HealthEvent>) -> Self {
+        self.health_tx = Some(health_tx);
+        self
+    }
+
+    fn health_reporter(&self) -> HealthReporter {
+        HealthReporter::new(self.exchange.clone(), self.health_tx.clone())
+    }
+
+    /// Fills in the URL template for one native symbol.
+    pub fn snapshot_url(&self, symbol: &str, depth: usize) -> String {
+        self.url_template
+            .replace("{symbol}", symbol)
+            .replace("{depth}", &depth.to_string())
+    }
+
+    /// Parses a snapshot response body into an update holding at most `depth` levels per side.
+    pub fn parse_snapshot(
+        &self,
+        symbol: &str,
+        body: &str,
+        depth: usize,
+    ) -> Result<PriceLevelUpdate> {
+        let response: Value = serde_json::from_str(body).map_err(|e| {
+            AggregatorError::parsing("RestSnapshot", format!("Failed to parse snapshot: {}", e))
+        })?;
+        let book = response.pointer(&self.book_pointer).ok_or_else(|| {
+            AggregatorError::parsing(
+                "RestSnapshot",
+                format!("Missing order book at '{}'", self.book_pointer),
+            )
+        })?;
+
+        let timestamp = Utc::now();
+        let event_time = self
+            .event_time_key
+            .as_ref()
+            .and_then(|key| book.get(key))
+            .and_then(Self::number)
+            .and_then(|millis| DateTime::from_timestamp_millis(millis as i64));
+
+        let bids = Self::parse_levels(book, &self.bids_key, depth)?
+            .into_iter()
+            .map(|(price, quantity)| Bid {
+                price,
+                quantity,
+                exchange: self.exchange.clone(),
+                timestamp,
+            })
+            .collect();
+        let asks = Self::parse_levels(book, &self.asks_key, depth)?
+            .into_iter()
+            .map(|(price, quantity)| Ask {
+                price,
+                quantity,
+                exchange: self.exchange.clone(),
+                timestamp,
+            })
+            .collect();
+
+        Ok(PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            symbol: symbol.to_string(),
+            exchange: self.exchange.clone(),
+            bids,
+            asks,
+            timestamp,
+            funding: None,
+            event_time,
+            market_type: None,
+        })
+    }
+
+    /// Appends zero-quantity levels to `current` for every price present in `previous` but no
+    /// longer in `current`, turning consecutive snapshots into a delta the order books can apply.
+    pub fn mark_removed_levels(previous: &PriceLevelUpdate, current: &mut PriceLevelUpdate) {
+        current.mark_removed_levels(previous);
+    }
+
+    fn parse_levels(book: &Value, key: &str, depth: usize) -> Result<Vec<(f64, f64)>> {
+        let levels = book.get(key).and_then(Value::as_array).ok_or_else(|| {
+            AggregatorError::parsing("RestSnapshot", format!("Missing '{}' levels", key))
+        })?;
+
+        levels
+            .iter()
+            .take(depth)
+            .map(|level| {
+                let price = level.get(0).and_then(Self::number);
+                let quantity = level.get(1).and_then(Self::number);
+                match (price, quantity) {
+                    (Some(price), Some(quantity)) => Ok((price, quantity)),
+                    _ => Err(AggregatorError::parsing(
+                        "PriceLevel",
+                        format!("Invalid price level: {}", level),
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    /// Reads a number that exchanges may send either as a JSON number or a decimal string.
+    fn number(value: &Value) -> Option<f64> {
+        match value {
+            Value::Number(number) => number.as_f64(),
+            Value::String(text) => text.parse().ok(),
+            _ => None,
+        }
+    }
+
+    async fn get_snapshot(
+        &self,
+        client: &reqwest::Client,
+        symbol: &str,
+        depth: usize,
+    ) -> Result<PriceLevelUpdate> {
+        self.rate_limiter.try_acquire()?;
+
+        let response = client
+            .get(self.snapshot_url(symbol, depth))
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to get snapshot: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(AggregatorError::rate_limit(
+                format!("{}-rest", self.exchange),
+                "Snapshot request rejected with HTTP 429",
+            ));
+        }
+
+        if !response.status().is_success() {
+            return Err(AggregatorError::network(format!(
+                "HTTP error: {}",
+                response.status()
+            )));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to read snapshot: {}", e)))?;
+        self.parse_snapshot(symbol, &body, depth)
+    }
+
+    /// Polls every symbol once per interval until shutdown or until the receiver is dropped. Health
+    /// is reported on transitions only: `Connected` after the first clean round, `Disconnected`
+    /// when any request in a round fails.
+    async fn poll(
+        self,
+        client: reqwest::Client,
+        symbols: Vec<String>,
+        order_book_depth: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        mut shutdown: Shutdown,
+    ) -> Result<()> {
+        let health = self.health_reporter();
+        let mut healthy = None;
+        let mut last_snapshots: HashMap<String, PriceLevelUpdate> = HashMap::new();
+
+        let mut interval = tokio::time::interval(self.update_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.recv() => {
+                    info!("{} REST polling shut down", self.exchange);
+                    return Ok(());
+                }
+            }
+            let mut failure = None;
+
+            for symbol in &symbols {
+                let snapshot = match self.get_snapshot(&client, symbol, order_book_depth).await {
+                    Ok(snapshot) => snapshot,
+                    Err(e @ AggregatorError::RateLimit { .. }) => {
+                        warn!("Skipping {} {} poll: {}", self.exchange, symbol, e);
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!(
+                            "{} snapshot poll for {} failed: {}",
+                            self.exchange, symbol, e
+                        );
+                        failure = Some(e.to_string());
+                        continue;
+                    }
+                };
+
+                let mut update = snapshot.clone();
+                if let Some(previous) = last_snapshots.insert(symbol.clone(), snapshot) {
+                    Self::mark_removed_levels(&previous, &mut update);
+                }
+                if price_level_tx.send(update).await.is_err() {
+                    info!("{} REST polling stopped, receiver dropped", self.exchange);
+                    return Ok(());
+                }
+            }
+
+            match failure {
+                Some(reason) if healthy != Some(false) => {
+                    health.disconnected(reason);
+                    healthy = Some(false);
+                }
+                None if healthy != Some(true) => {
+                    health.connected();
+                    healthy = Some(true);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl OrderBookService for RestPollingConnector {
+    async fn spawn_order_book_service(
+        &self,
+        pairs: &[TradingPair],
+        order_book_depth: usize,
+        _exchange_stream_buffer: usize,
+        price_level_tx: Sender<PriceLevelUpdate>,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        if self.update_interval.is_zero() {
+            return Err(AggregatorError::validation(
+                "update_interval",
+                "REST polling interval must be greater than zero",
+            ));
+        }
+        let symbols = SymbolMapper::new().to_exchange_all(&self.exchange, pairs)?;
+        let client = http_client(self.proxy.as_ref())?;
+
+        info!(
+            "Polling {} order books over REST every {:?}",
+            self.exchange, self.update_interval
+        );
+        let connector = self.clone();
+        let handle = tokio::spawn(connector.poll(
+            client,
+            symbols,
+            order_book_depth,
+            price_level_tx,
+            Shutdown::new(shutdown),
+        ));
+
+        Ok(vec![handle])
+    }
+}
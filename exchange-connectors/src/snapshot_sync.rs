@@ -0,0 +1,221 @@
+//! Snapshot Sync Module
+//! Keeps a local book built from a REST snapshot and a stream of sequenced diffs consistent,
+//! following the buffering algorithm Binance documents for managing a local order book:
+//!
+//! 1. Buffer diffs from the stream while the snapshot is fetched.
+//! 2. Drop buffered diffs the snapshot already covers.
+//! 3. Check that the first remaining diff continues the snapshot; otherwise the snapshot is older
+//!    than the stream and must be fetched again.
+//! 4. Apply the buffered diffs, then every live diff that continues the previous one. A gap means
+//!    updates were lost, so the book goes back to step 1.
+//!
+//! Order books downstream only drop a level when they get it with a zero quantity, so the
+//! snapshot that ends a resync cannot be forwarded as it is: levels deleted while the stream was
+//! out of sync would stay behind. [`ForwardedBook`] remembers what was forwarded and adds those
+//! removals.
+
+use std::collections::{HashMap, VecDeque};
+
+use aggregator_core::{Ask, Bid, PriceLevelUpdate};
+use uuid::Uuid;
+
+/// Buffered diffs kept while waiting for a snapshot; older ones are dropped beyond this.
+const DEFAULT_MAX_BUFFERED: usize = 1000;
+
+/// A diff carrying the range of update ids it covers.
+pub trait SequencedUpdate {
+    /// Id of the first change in the diff (`U` on Binance).
+    fn first_update_id(&self) -> u64;
+
+    /// Id of the last change in the diff (`u` on Binance).
+    fn final_update_id(&self) -> u64;
+
+    /// Final id of the previous diff, for feeds that chain diffs explicitly instead of using
+    /// contiguous ids (`pu` on Binance futures).
+    fn previous_final_update_id(&self) -> Option<u64> {
+        None
+    }
+
+    /// Whether the diff continues a book whose last applied update id is `last_update_id`.
+    fn follows(&self, last_update_id: u64) -> bool {
+        self.previous_final_update_id() == Some(last_update_id)
+            || self.first_update_id() <= last_update_id + 1
+    }
+}
+
+/// What to do with a diff passed to `SnapshotSync::push`.
+#[derive(Debug, PartialEq)]
+pub enum SyncStep<U> {
+    /// Apply the diff to the book.
+    Apply(U),
+    /// Held until a snapshot arrives.
+    Buffered,
+    /// Already covered by the book; drop it.
+    Stale,
+    /// Updates were lost. The diff is buffered and a new snapshot must be fetched.
+    Resync,
+}
+
+/// Sync state for one book.
+#[derive(Debug)]
+pub struct SnapshotSync<U> {
+    last_update_id: Option<u64>,
+    buffer: VecDeque<U>,
+    max_buffered: usize,
+}
+
+impl<U: SequencedUpdate> SnapshotSync<U> {
+    pub fn new() -> Self {
+        Self {
+            last_update_id: None,
+            buffer: VecDeque::new(),
+            max_buffered: DEFAULT_MAX_BUFFERED,
+        }
+    }
+
+    /// Buffer at most `max_buffered` diffs while waiting for a snapshot.
+    pub fn with_max_buffered(mut self, max_buffered: usize) -> Self {
+        self.max_buffered = max_buffered.max(1);
+        self
+    }
+
+    /// Whether a snapshot has been applied and the book is following the stream.
+    pub fn is_synced(&self) -> bool {
+        self.last_update_id.is_some()
+    }
+
+    /// Id of the last update applied to the book, if synced.
+    pub fn last_update_id(&self) -> Option<u64> {
+        self.last_update_id
+    }
+
+    /// Number of diffs waiting for a snapshot.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Forgets the book and every buffered diff, for use when the stream is replaced.
+    pub fn reset(&mut self) {
+        self.last_update_id = None;
+        self.buffer.clear();
+    }
+
+    /// Handles a diff from the stream.
+    pub fn push(&mut self, update: U) -> SyncStep<U> {
+        let Some(last_update_id) = self.last_update_id else {
+            self.buffer_update(update);
+            return SyncStep::Buffered;
+        };
+
+        if update.final_update_id() <= last_update_id {
+            SyncStep::Stale
+        } else if update.follows(last_update_id) {
+            self.last_update_id = Some(update.final_update_id());
+            SyncStep::Apply(update)
+        } else {
+            self.reset();
+            self.buffer_update(update);
+            SyncStep::Resync
+        }
+    }
+
+    /// Applies a snapshot whose last update id is `snapshot_update_id` and returns the buffered
+    /// diffs to apply on top of it, in order.
+    ///
+    /// Returns `None` when the snapshot is older than the buffered diffs or they have a gap; the
+    /// diffs stay buffered and a newer snapshot must be fetched.
+    pub fn apply_snapshot(&mut self, snapshot_update_id: u64) -> Option<Vec<U>> {
+        while self
+            .buffer
+            .front()
+            .is_some_and(|update| update.final_update_id() <= snapshot_update_id)
+        {
+            self.buffer.pop_front();
+        }
+
+        let mut last_update_id = snapshot_update_id;
+        for (index, update) in self.buffer.iter().enumerate() {
+            if !update.follows(last_update_id) {
+                // Diffs before the gap are older than any snapshot fetched from now on
+                self.buffer.drain(..index);
+                return None;
+            }
+            last_update_id = update.final_update_id();
+        }
+
+        self.last_update_id = Some(last_update_id);
+        Some(self.buffer.drain(..).collect())
+    }
+
+    fn buffer_update(&mut self, update: U) {
+        if self.buffer.len() >= self.max_buffered {
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back(update);
+    }
+}
+
+impl<U: SequencedUpdate> Default for SnapshotSync<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The levels forwarded downstream for one book, keyed by the bits of their price.
+#[derive(Debug, Clone, Default)]
+pub struct ForwardedBook {
+    bids: HashMap<u64, Bid>,
+    asks: HashMap<u64, Ask>,
+}
+
+impl ForwardedBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an update as forwarded: levels with a quantity are set, the others removed.
+    pub fn record(&mut self, update: &PriceLevelUpdate) {
+        for bid in &update.bids {
+            if bid.quantity > 0.0 {
+                self.bids.insert(bid.price.to_bits(), bid.clone());
+            } else {
+                self.bids.remove(&bid.price.to_bits());
+            }
+        }
+        for ask in &update.asks {
+            if ask.quantity > 0.0 {
+                self.asks.insert(ask.price.to_bits(), ask.clone());
+            } else {
+                self.asks.remove(&ask.price.to_bits());
+            }
+        }
+    }
+
+    /// Turns `snapshot`, a complete book, into the update taking downstream from the levels
+    /// forwarded so far to the snapshot's, and records it. Every forwarded level missing from
+    /// the snapshot is appended with a zero quantity.
+    pub fn replace(&mut self, snapshot: &mut PriceLevelUpdate) {
+        let previous = PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            symbol: snapshot.symbol.clone(),
+            exchange: snapshot.exchange.clone(),
+            bids: self.bids.drain().map(|(_, bid)| bid).collect(),
+            asks: self.asks.drain().map(|(_, ask)| ask).collect(),
+            timestamp: snapshot.timestamp,
+            funding: None,
+            event_time: None,
+            market_type: None,
+        };
+        snapshot.mark_removed_levels(&previous);
+        self.record(snapshot);
+    }
+
+    /// Number of `(bids, asks)` levels downstream holds
+    pub fn len(&self) -> (usize, usize) {
+        (self.bids.len(), self.asks.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+}
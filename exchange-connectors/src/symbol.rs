@@ -0,0 +1,6 @@
+//! Symbol Normalization Module
+//! Converts between `TradingPair` and each exchange's native symbol format
+
+/// Defined in `aggregator-core` so the aggregator resolves the symbols connectors report to the
+/// same pairs they subscribed to
+pub use aggregator_core::SymbolMapper;
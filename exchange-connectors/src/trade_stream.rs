@@ -0,0 +1,129 @@
+//! Shared WebSocket loop for trade streams
+//! Connects, subscribes, parses trade prints and reconnects on failure
+
+use futures_util::{SinkExt, StreamExt};
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+
+use crate::capture::Capture;
+use crate::proxy::{connect_websocket, Proxy};
+use crate::reconnect::{Backoff, HealthReporter, ReconnectPolicy};
+use aggregator_core::{AggregatorError, Result, Trade};
+
+/// Connection details for a single trade stream.
+pub(crate) struct TradeStreamSpec {
+    pub exchange: &'static str,
+    pub url: String,
+    pub subscriptions: Vec<String>,
+    pub ping: Option<(String, Duration)>,
+    pub reconnect_policy: ReconnectPolicy,
+    pub health: HealthReporter,
+    pub proxy: Option<Proxy>,
+    pub capture: Option<Capture>,
+}
+
+/// Spawns a task that keeps a trade stream connected and forwards every parsed trade to `trade_tx`.
+/// The task ends once `trade_tx` is closed or the reconnect policy gives up.
+pub(crate) fn spawn_trade_stream(
+    spec: TradeStreamSpec,
+    parse: fn(&str) -> Result<Vec<Trade>>,
+    trade_tx: Sender<Trade>,
+) -> JoinHandle<Result<()>> {
+    tokio::spawn(async move {
+        let mut backoff = spec.reconnect_policy.backoff();
+        loop {
+            let result = run_trade_stream(&spec, parse, &trade_tx, &mut backoff).await;
+            if trade_tx.is_closed() {
+                return Ok(());
+            }
+
+            match result {
+                Ok(()) => {
+                    warn!("{} trade stream closed", spec.exchange);
+                    spec.health.disconnected("Trade stream closed");
+                }
+                Err(e) => {
+                    error!("{} trade stream error: {}", spec.exchange, e);
+                    spec.health.disconnected(e.to_string());
+                }
+            }
+
+            spec.health.wait_to_reconnect(&mut backoff).await?;
+        }
+    })
+}
+
+async fn run_trade_stream(
+    spec: &TradeStreamSpec,
+    parse: fn(&str) -> Result<Vec<Trade>>,
+    trade_tx: &Sender<Trade>,
+    backoff: &mut Backoff,
+) -> Result<()> {
+    let (mut ws_stream, _) = connect_websocket(&spec.url, spec.proxy.as_ref()).await?;
+
+    info!("Connected to {} trade stream", spec.exchange);
+    backoff.reset();
+    spec.health.connected();
+
+    for subscription in &spec.subscriptions {
+        ws_stream
+            .send(Message::Text(subscription.clone()))
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to send subscription: {}", e)))?;
+    }
+
+    let ping_period = spec
+        .ping
+        .as_ref()
+        .map(|(_, period)| *period)
+        .unwrap_or(Duration::from_secs(3600));
+    let mut ping_interval = tokio::time::interval(ping_period);
+    ping_interval.tick().await;
+
+    loop {
+        tokio::select! {
+            msg = ws_stream.next() => {
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Some(capture) = &spec.capture {
+                            capture.record(spec.health.exchange(), &text);
+                        }
+                        match parse(&text) {
+                            Ok(trades) => {
+                                for trade in trades {
+                                    if trade_tx.send(trade).await.is_err() {
+                                        return Ok(());
+                                    }
+                                }
+                            }
+                            Err(e) => warn!("Failed to parse {} trade message: {}", spec.exchange, e),
+                        }
+                    }
+                    Some(Ok(Message::Ping(payload))) => {
+                        if let Err(e) = ws_stream.send(Message::Pong(payload)).await {
+                            error!("Failed to send pong: {}", e);
+                            return Ok(());
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Err(e)) => {
+                        return Err(AggregatorError::network(format!("WebSocket error: {}", e)));
+                    }
+                    _ => {}
+                }
+            }
+            _ = ping_interval.tick(), if spec.ping.is_some() => {
+                if let Some((ping, _)) = &spec.ping {
+                    if let Err(e) = ws_stream.send(Message::Text(ping.clone())).await {
+                        error!("Failed to send ping: {}", e);
+                        return Ok(());
+                    }
+                }
+            }
+            _ = trade_tx.closed() => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use aggregator_core::{
+    BalanceUpdate, ExchangeConfig, OrderStatus, OrderUpdate, TradeSide, TradingPair,
+};
+use exchange_connectors::{AuthenticatedService, Binance, Coinbase, Credentials, UserDataEvent};
+use tokio::sync::mpsc;
+
+#[cfg(test)]
+mod auth_tests {
+    use super::*;
+
+    fn order(events: Vec<UserDataEvent>) -> OrderUpdate {
+        assert_eq!(events.len(), 1);
+        match events.into_iter().next().unwrap() {
+            UserDataEvent::Order(update) => update,
+            other => panic!("Expected order update, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_credentials_from_config() {
+        assert!(Credentials::from_config(&ExchangeConfig::default()).is_none());
+
+        let config = ExchangeConfig {
+            api_key: Some("key".to_string()),
+            api_secret: Some("s3cr3t".to_string()),
+            passphrase: Some("p4ss".to_string()),
+            ..ExchangeConfig::default()
+        };
+        let credentials = Credentials::from_config(&config).unwrap();
+        assert_eq!(credentials.api_key, "key");
+        assert_eq!(credentials.api_secret(), "s3cr3t");
+        assert_eq!(credentials.require_passphrase("coinbase").unwrap(), "p4ss");
+
+        let debug = format!("{:?}", credentials);
+        assert!(!debug.contains("s3cr3t"));
+        assert!(!debug.contains("p4ss"));
+    }
+
+    #[test]
+    fn test_binance_execution_report_parsing() {
+        let message = r#"{"e":"executionReport","E":1499405658658,"s":"ETHBTC","c":"mUvoqJxFIILMdfAW5iGSOW","S":"BUY","o":"LIMIT","f":"GTC","q":"1.00000000","p":"0.10264410","X":"PARTIALLY_FILLED","i":4293153,"z":"0.25000000","T":1499405658657}"#;
+
+        let update = order(Binance::parse_user_data(message).unwrap());
+        assert_eq!(update.order_id, "4293153");
+        assert_eq!(
+            update.client_order_id.as_deref(),
+            Some("mUvoqJxFIILMdfAW5iGSOW")
+        );
+        assert_eq!(update.symbol, "ETHBTC");
+        assert_eq!(update.side, TradeSide::Buy);
+        assert_eq!(update.status, OrderStatus::PartiallyFilled);
+        assert_eq!(update.price, Some(0.1026441));
+        assert_eq!(update.quantity, Some(1.0));
+        assert_eq!(update.filled_quantity, Some(0.25));
+    }
+
+    #[test]
+    fn test_binance_account_position_parsing() {
+        let message = r#"{"e":"outboundAccountPosition","E":1564034571105,"u":1564034571073,"B":[{"a":"ETH","f":"10000.000000","l":"0.000000"},{"a":"BTC","f":"1.5","l":"0.5"}]}"#;
+
+        let events = Binance::parse_user_data(message).unwrap();
+        let balances: Vec<BalanceUpdate> = events
+            .into_iter()
+            .map(|event| match event {
+                UserDataEvent::Balance(update) => update,
+                other => panic!("Expected balance update, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(balances.len(), 2);
+        assert_eq!(balances[1].asset, "BTC");
+        assert_eq!(balances[1].free, 1.5);
+        assert_eq!(balances[1].locked, 0.5);
+
+        let depth = r#"{"e":"depthUpdate","E":1,"s":"BTCUSDT","U":1,"u":2,"b":[],"a":[]}"#;
+        assert!(Binance::parse_user_data(depth).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_coinbase_user_channel_parsing() {
+        let received = r#"{"type":"received","time":"2024-01-01T00:00:00.000000Z","product_id":"BTC-USD","sequence":10,"order_id":"d50ec984","size":"1.34","price":"502.1","side":"buy","order_type":"limit","client_oid":"abc"}"#;
+        let update = order(Coinbase::parse_user_data(received).unwrap());
+        assert_eq!(update.order_id, "d50ec984");
+        assert_eq!(update.client_order_id.as_deref(), Some("abc"));
+        assert_eq!(update.status, OrderStatus::New);
+        assert_eq!(update.quantity, Some(1.34));
+        assert_eq!(update.price, Some(502.1));
+
+        let done = r#"{"type":"done","time":"2024-01-01T00:00:01.000000Z","product_id":"BTC-USD","sequence":11,"price":"502.1","order_id":"d50ec984","reason":"canceled","side":"buy","remaining_size":"1.34"}"#;
+        let update = order(Coinbase::parse_user_data(done).unwrap());
+        assert_eq!(update.status, OrderStatus::Canceled);
+
+        // The account took liquidity, so its order is the taker and sits opposite the maker side
+        let matched = r#"{"type":"match","trade_id":10,"sequence":12,"maker_order_id":"ac928c66","taker_order_id":"132fb6ae","time":"2024-01-01T00:00:02.000000Z","product_id":"BTC-USD","size":"0.5","price":"400.23","side":"sell","taker_user_id":"u1","user_id":"u1"}"#;
+        let update = order(Coinbase::parse_user_data(matched).unwrap());
+        assert_eq!(update.order_id, "132fb6ae");
+        assert_eq!(update.side, TradeSide::Buy);
+        assert_eq!(update.status, OrderStatus::PartiallyFilled);
+
+        let heartbeat = r#"{"type":"heartbeat","sequence":90,"last_trade_id":20,"product_id":"BTC-USD","time":"2024-01-01T00:00:00.000000Z"}"#;
+        assert!(Coinbase::parse_user_data(heartbeat).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_user_data_service_requires_credentials() {
+        let pairs = [TradingPair::new("BTC", "USD")];
+
+        let (order_tx, _order_rx) = mpsc::channel(10);
+        let (balance_tx, _balance_rx) = mpsc::channel(10);
+        assert!(Binance::new()
+            .spawn_user_data_service(&pairs, order_tx, balance_tx)
+            .await
+            .is_err());
+
+        // Coinbase also needs the passphrase
+        let (order_tx, _order_rx) = mpsc::channel(10);
+        let (balance_tx, _balance_rx) = mpsc::channel(10);
+        assert!(Coinbase::new()
+            .with_credentials(Credentials::new("key", "c2VjcmV0"))
+            .spawn_user_data_service(&pairs, order_tx, balance_tx)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use aggregator_core::{Exchange, ExchangeConfig};
+use exchange_connectors::bitfinex::BitfinexBookParser;
+use exchange_connectors::Bitfinex;
+
+#[cfg(test)]
+mod bitfinex_tests {
+    use super::*;
+
+    const SUBSCRIBED: &str = r#"{
+        "event": "subscribed",
+        "channel": "book",
+        "chanId": 17082,
+        "symbol": "tBTCUSD",
+        "prec": "P0",
+        "freq": "F0",
+        "len": "25",
+        "pair": "BTCUSD"
+    }"#;
+
+    #[test]
+    fn test_snapshot_after_subscription() {
+        let mut parser = BitfinexBookParser::new();
+
+        // Data on an unknown channel is ignored
+        assert!(parser
+            .handle_message("[17082,[[7254.7,3,3.3]]]")
+            .unwrap()
+            .is_none());
+
+        assert!(parser.handle_message(SUBSCRIBED).unwrap().is_none());
+        assert_eq!(parser.symbol(17082), Some("tBTCUSD"));
+
+        let update = parser
+            .handle_message("[17082,[[7254.7,3,3.3],[7254.6,1,0.5],[7255.1,2,-1.25]]]")
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.symbol, "tBTCUSD");
+        assert_eq!(update.exchange, Exchange::Bitfinex);
+        assert_eq!(update.bids.len(), 2);
+        assert_eq!(update.bids[0].price, 7254.7);
+        assert_eq!(update.bids[0].quantity, 3.3);
+        assert_eq!(update.asks.len(), 1);
+        assert_eq!(update.asks[0].quantity, 1.25);
+    }
+
+    #[test]
+    fn test_count_based_add_and_remove() {
+        let mut parser = BitfinexBookParser::new();
+        parser.handle_message(SUBSCRIBED).unwrap();
+
+        let add = parser
+            .handle_message("[17082,[7254.5,2,-0.75]]")
+            .unwrap()
+            .unwrap();
+        assert!(add.bids.is_empty());
+        assert_eq!(add.asks[0].quantity, 0.75);
+
+        let remove_bid = parser
+            .handle_message("[17082,[7254.7,0,1]]")
+            .unwrap()
+            .unwrap();
+        assert_eq!(remove_bid.bids[0].price, 7254.7);
+        assert_eq!(remove_bid.bids[0].quantity, 0.0);
+
+        let remove_ask = parser
+            .handle_message("[17082,[7254.5,0,-1]]")
+            .unwrap()
+            .unwrap();
+        assert_eq!(remove_ask.asks[0].quantity, 0.0);
+    }
+
+    #[test]
+    fn test_heartbeats_and_reset() {
+        let mut parser = BitfinexBookParser::new();
+        parser.handle_message(SUBSCRIBED).unwrap();
+
+        assert!(parser.handle_message(r#"[17082,"hb"]"#).unwrap().is_none());
+        assert!(parser
+            .handle_message(r#"[17082,"cs",-1324931179]"#)
+            .unwrap()
+            .is_none());
+        assert!(parser
+            .handle_message(r#"{"event":"info","version":2}"#)
+            .unwrap()
+            .is_none());
+        assert!(parser
+            .handle_message(r#"{"event":"error","msg":"symbol: invalid","code":10300}"#)
+            .is_err());
+
+        parser.reset();
+        assert!(parser.symbol(17082).is_none());
+    }
+
+    #[test]
+    fn test_book_length_and_sandbox() {
+        assert_eq!(Bitfinex::book_length(1), 1);
+        assert_eq!(Bitfinex::book_length(20), 25);
+        assert_eq!(Bitfinex::book_length(100), 100);
+        assert_eq!(Bitfinex::book_length(1000), 250);
+
+        let config = ExchangeConfig {
+            sandbox: true,
+            ..ExchangeConfig::default()
+        };
+        assert!(Bitfinex::from_config(&config).is_err());
+    }
+}
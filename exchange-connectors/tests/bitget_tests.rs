@@ -0,0 +1,108 @@
+use aggregator_core::{Exchange, ExchangeConfig, TradingPair};
+use exchange_connectors::bitget::BitgetBookParser;
+use exchange_connectors::{Bitget, SymbolMapper};
+
+#[cfg(test)]
+mod bitget_tests {
+    use super::*;
+
+    fn book(ts: &str, bids: &str, asks: &str) -> String {
+        format!(
+            r#"{{"action":"snapshot","arg":{{"instType":"SPOT","channel":"books15","instId":"BTCUSDT"}},"data":[{{"asks":[{}],"bids":[{}],"checksum":0,"seq":1,"ts":"{}"}}],"ts":{}}}"#,
+            asks, bids, ts, ts
+        )
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        let mut parser = BitgetBookParser::new();
+        let message = book(
+            "1700000000123",
+            r#"["36950.0","1.25"],["36949.0","0.5"]"#,
+            r#"["36951.0","2.0"]"#,
+        );
+
+        let update = parser.handle_message(&message).unwrap().unwrap();
+        assert_eq!(update.symbol, "BTCUSDT");
+        assert_eq!(update.exchange, Exchange::Bitget);
+        assert_eq!(update.market_type, None);
+        assert_eq!(update.bids.len(), 2);
+        assert_eq!(update.bids[0].price, 36950.0);
+        assert_eq!(update.bids[0].quantity, 1.25);
+        assert_eq!(update.asks[0].quantity, 2.0);
+        assert_eq!(update.event_time.unwrap().timestamp_millis(), 1700000000123);
+    }
+
+    #[test]
+    fn test_removed_levels_and_depth() {
+        let mut parser = BitgetBookParser::new().with_depth(2);
+        parser
+            .handle_message(&book(
+                "1",
+                r#"["100.0","1.0"],["99.0","2.0"],["98.0","3.0"]"#,
+                r#"["101.0","1.0"]"#,
+            ))
+            .unwrap();
+
+        let update = parser
+            .handle_message(&book("2", r#"["100.0","1.5"]"#, r#"["102.0","1.0"]"#))
+            .unwrap()
+            .unwrap();
+
+        // 99.0 left the book; 98.0 was never kept past the depth limit
+        assert_eq!(update.bids.len(), 2);
+        assert_eq!(update.bids[1].price, 99.0);
+        assert_eq!(update.bids[1].quantity, 0.0);
+        assert_eq!(update.asks.len(), 2);
+        assert_eq!(update.asks[1].price, 101.0);
+        assert_eq!(update.asks[1].quantity, 0.0);
+    }
+
+    #[test]
+    fn test_control_messages() {
+        let mut parser = BitgetBookParser::new();
+        assert!(parser.handle_message("pong").unwrap().is_none());
+        let ack = r#"{"event":"subscribe","arg":{"instType":"SPOT","channel":"books15","instId":"BTCUSDT"}}"#;
+        assert!(parser.handle_message(ack).unwrap().is_none());
+        assert!(parser
+            .handle_message(r#"{"event":"error","code":30001,"msg":"instType:SPOT,channel:books15,instId:FOO doesn't exist"}"#)
+            .is_err());
+        assert!(parser.handle_message("not json").is_err());
+    }
+
+    #[test]
+    fn test_subscription_and_symbols() {
+        assert_eq!(Bitget::depth_channel(1), "books1");
+        assert_eq!(Bitget::depth_channel(5), "books5");
+        assert_eq!(Bitget::depth_channel(50), "books15");
+        assert_eq!(
+            Bitget::subscription("books5", &["BTCUSDT".to_string()]).unwrap(),
+            r#"{"op":"subscribe","args":[{"instType":"SPOT","channel":"books5","instId":"BTCUSDT"}]}"#
+        );
+
+        let mapper = SymbolMapper::new();
+        let pair = TradingPair::new("ETH", "USDT");
+        assert_eq!(mapper.to_exchange(&Exchange::Bitget, &pair), "ETHUSDT");
+        assert_eq!(
+            mapper.from_exchange(&Exchange::Bitget, "ETHUSDT").unwrap(),
+            pair
+        );
+        assert_eq!("bitget".parse::<Exchange>().unwrap(), Exchange::Bitget);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let production = Bitget::from_config(&ExchangeConfig::default()).unwrap();
+        assert_eq!(
+            production.websocket_url(),
+            "wss://ws.bitget.com/v2/ws/public"
+        );
+
+        let demo = Bitget::from_config(&ExchangeConfig {
+            sandbox: true,
+            ..ExchangeConfig::default()
+        })
+        .unwrap();
+        assert_eq!(demo.websocket_url(), "wss://wspap.bitget.com/v2/ws/public");
+    }
+}
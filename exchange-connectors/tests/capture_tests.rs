@@ -0,0 +1,250 @@
+use aggregator_core::{Exchange, PriceLevelUpdate, ReplaySource, TradingPair};
+use exchange_connectors::capture::{replay, replay_collect, CaptureReader, CaptureWriter};
+use exchange_connectors::hyperliquid::HyperliquidBookParser;
+use exchange_connectors::{
+    Capture, CaptureSource, CapturedFrame, Hyperliquid, OrderBookService, ReplayPace, ReplayStats,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+mod mock_exchange;
+use mock_exchange::{MockExchangeServer, Step};
+
+#[cfg(test)]
+mod capture_tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("aggre-gate-capture-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn book(time: i64, bid: &str, ask: &str) -> String {
+        format!(
+            r#"{{"channel":"l2Book","data":{{"coin":"ETH","time":{},"levels":[[{{"px":"{}","sz":"1.0","n":1}}],[{{"px":"{}","sz":"2.0","n":1}}]]}}}}"#,
+            time, bid, ask
+        )
+    }
+
+    fn write_capture(path: &PathBuf, messages: &[String]) {
+        let mut writer = CaptureWriter::create(path).unwrap();
+        for message in messages {
+            writer
+                .write(&CapturedFrame::new(Exchange::Hyperliquid, message))
+                .unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_write_and_read_plain_and_gzip() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let messages = vec![book(1, "100.0", "101.0"), book(2, "100.5", "101.0")];
+
+        for name in ["frames.jsonl", "frames.jsonl.gz"] {
+            let path = dir.join(name);
+            write_capture(&path, &messages);
+
+            let frames: Vec<CapturedFrame> = CaptureReader::open(&path)
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(frames.len(), 2);
+            assert_eq!(frames[0].exchange, Exchange::Hyperliquid);
+            assert_eq!(frames[0].message, messages[0]);
+            assert_eq!(frames[1].message, messages[1]);
+        }
+
+        // Gzipped captures are not readable as plain text
+        let gzip = std::fs::read(dir.join("frames.jsonl.gz")).unwrap();
+        assert_eq!(&gzip[..2], &[0x1f, 0x8b]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_capture_writes_one_file_per_exchange() {
+        let dir = temp_dir();
+        let (capture, writer) = Capture::start(&dir, false).unwrap();
+        capture.record(&Exchange::Hyperliquid, "first");
+        capture.clone().record(&Exchange::Bitfinex, "other");
+        capture.record(&Exchange::Hyperliquid, "second");
+        drop(capture);
+        writer.join().unwrap().unwrap();
+
+        let messages = |name: &str| -> Vec<String> {
+            CaptureReader::open(dir.join(name))
+                .unwrap()
+                .map(|frame| frame.unwrap().message)
+                .collect()
+        };
+        assert_eq!(messages("hyperliquid.jsonl"), vec!["first", "second"]);
+        assert_eq!(messages("bitfinex.jsonl"), vec!["other"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_replay_collect_through_parser() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hyperliquid.jsonl.gz");
+        write_capture(
+            &path,
+            &[
+                r#"{"channel":"subscriptionResponse","data":{}}"#.to_string(),
+                book(1, "100.0", "101.0"),
+                "not json".to_string(),
+                book(2, "100.5", "101.0"),
+            ],
+        );
+
+        let mut parser = HyperliquidBookParser::new();
+        let (updates, stats): (Vec<PriceLevelUpdate>, _) =
+            replay_collect(&path, |message| parser.handle_message(message)).unwrap();
+
+        assert_eq!(
+            stats,
+            ReplayStats {
+                frames: 4,
+                produced: 2,
+                errors: 1,
+            }
+        );
+        // The second snapshot moved the bid, so the old level is removed
+        assert_eq!(updates[1].bids.len(), 2);
+        assert_eq!(updates[1].bids[1].price, 100.0);
+        assert_eq!(updates[1].bids[1].quantity, 0.0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_into_channel() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hyperliquid.jsonl");
+        write_capture(
+            &path,
+            &[book(1, "100.0", "101.0"), book(2, "100.5", "101.0")],
+        );
+
+        let (tx, mut rx) = mpsc::channel::<PriceLevelUpdate>(10);
+        let mut parser = HyperliquidBookParser::new();
+        let stats = replay(
+            &path,
+            |message| parser.handle_message(message),
+            &tx,
+            ReplayPace::Recorded { speed: 10.0 },
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.produced, 2);
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!(first.bids[0].price, 100.0);
+        let second = rx.recv().await.unwrap();
+        assert_eq!(second.bids[0].price, 100.5);
+
+        // A closed receiver ends the replay early
+        drop(rx);
+        let stats = replay(
+            &path,
+            |message| parser.handle_message(message),
+            &tx,
+            ReplayPace::Unpaced,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.produced, 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_capture_source_stamps_receive_times() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hyperliquid.jsonl");
+        write_capture(
+            &path,
+            &[
+                r#"{"channel":"subscriptionResponse","data":{}}"#.to_string(),
+                book(1, "100.0", "101.0"),
+                "not json".to_string(),
+                book(2, "100.5", "101.0"),
+            ],
+        );
+        let frames: Vec<CapturedFrame> = CaptureReader::open(&path)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let mut parser = HyperliquidBookParser::new();
+        let mut source =
+            CaptureSource::open(&path, move |message| parser.handle_message(message)).unwrap();
+
+        // Acks produce nothing and are passed over
+        let first = source.next_update().await.unwrap().unwrap();
+        assert_eq!(first.bids[0].price, 100.0);
+        assert_eq!(first.timestamp, frames[1].received_at);
+        assert!(source.next_update().await.unwrap().is_err());
+        let second = source.next_update().await.unwrap().unwrap();
+        assert_eq!(second.bids[0].price, 100.5);
+        assert_eq!(second.timestamp, frames[3].received_at);
+        assert!(source.next_update().await.is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connector_captures_raw_frames() {
+        let messages = vec![book(1, "100.0", "101.0"), book(2, "100.5", "101.0")];
+        let mut session = vec![Step::Expect("l2Book".to_string())];
+        session.extend(Step::replay(messages.clone()));
+        let server = MockExchangeServer::start(vec![session]).await;
+
+        let dir = temp_dir();
+        let (capture, writer) = Capture::start(&dir, true).unwrap();
+        let connector = Hyperliquid::new()
+            .with_websocket_url(&server.url("/ws"))
+            .with_capture(capture);
+
+        let (price_level_tx, mut price_level_rx) = mpsc::channel(10);
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let handles = connector
+            .spawn_order_book_service(
+                &[TradingPair::new("ETH", "USDC")],
+                10,
+                10,
+                price_level_tx,
+                shutdown_rx,
+            )
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            tokio::time::timeout(Duration::from_secs(5), price_level_rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        shutdown_tx.send(()).unwrap();
+        drop(price_level_rx);
+        for handle in handles {
+            let _ = handle.await;
+        }
+        drop(connector);
+        writer.join().unwrap().unwrap();
+
+        let captured: Vec<String> = CaptureReader::open(dir.join("hyperliquid.jsonl.gz"))
+            .unwrap()
+            .map(|frame| frame.unwrap().message)
+            .collect();
+        assert_eq!(captured, messages);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
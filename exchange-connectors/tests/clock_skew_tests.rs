@@ -0,0 +1,217 @@
+use aggregator_core::{Exchange, PriceLevelUpdate, Result, TradingPair};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as TimeDelta, Utc};
+use exchange_connectors::{
+    ClockSkew, ClockSkewEstimator, ClockSkewSampler, OrderBookService, ServerTimeEndpoint,
+    SkewCorrected, SkewSample,
+};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[cfg(test)]
+mod clock_skew_tests {
+    use super::*;
+
+    fn at(millis: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(millis).unwrap()
+    }
+
+    fn sample(offset_ms: i64, round_trip_ms: i64) -> SkewSample {
+        SkewSample {
+            offset: TimeDelta::milliseconds(offset_ms),
+            round_trip: TimeDelta::milliseconds(round_trip_ms),
+        }
+    }
+
+    fn update(exchange: Exchange, event_time: Option<DateTime<Utc>>) -> PriceLevelUpdate {
+        PriceLevelUpdate {
+            id: uuid::Uuid::new_v4(),
+            symbol: "BTCUSDT".to_string(),
+            exchange,
+            bids: vec![],
+            asks: vec![],
+            timestamp: Utc::now(),
+            funding: None,
+            event_time,
+            market_type: None,
+        }
+    }
+
+    /// Emits a fixed set of updates, then holds the channel open until shutdown.
+    struct FixedConnector {
+        updates: Vec<PriceLevelUpdate>,
+    }
+
+    #[async_trait]
+    impl OrderBookService for FixedConnector {
+        async fn spawn_order_book_service(
+            &self,
+            _pairs: &[TradingPair],
+            _order_book_depth: usize,
+            _exchange_stream_buffer: usize,
+            price_level_tx: mpsc::Sender<PriceLevelUpdate>,
+            mut shutdown: broadcast::Receiver<()>,
+        ) -> Result<Vec<JoinHandle<Result<()>>>> {
+            let updates = self.updates.clone();
+            Ok(vec![tokio::spawn(async move {
+                for update in updates {
+                    let _ = price_level_tx.send(update).await;
+                }
+                let _ = shutdown.recv().await;
+                Ok(())
+            })])
+        }
+    }
+
+    #[test]
+    fn test_sample_uses_round_trip_midpoint() {
+        // Sent at 1000 and received at 1100 locally; the server read 1550 at local 1050
+        let sample = SkewSample::new(at(1000), at(1550), at(1100));
+        assert_eq!(sample.round_trip, TimeDelta::milliseconds(100));
+        assert_eq!(sample.offset, TimeDelta::milliseconds(500));
+
+        let behind = SkewSample::new(at(1000), at(850), at(1100));
+        assert_eq!(behind.offset, TimeDelta::milliseconds(-200));
+    }
+
+    #[test]
+    fn test_estimator_prefers_fastest_recent_sample() {
+        let mut estimator = ClockSkewEstimator::new().with_max_samples(3);
+        assert!(estimator.offset().is_none());
+
+        estimator.push(sample(300, 20));
+        estimator.push(sample(250, 80));
+        estimator.push(sample(280, 40));
+        assert_eq!(estimator.offset(), Some(TimeDelta::milliseconds(300)));
+        assert_eq!(estimator.round_trip(), Some(TimeDelta::milliseconds(20)));
+
+        // The fastest sample ages out of the window
+        estimator.push(sample(260, 60));
+        assert_eq!(estimator.len(), 3);
+        assert_eq!(estimator.offset(), Some(TimeDelta::milliseconds(280)));
+    }
+
+    #[test]
+    fn test_corrects_event_times_per_exchange() {
+        let skew = ClockSkew::new();
+        skew.record(&Exchange::Binance, sample(500, 10));
+        skew.record(&Exchange::Kraken, sample(-200, 10));
+
+        let mut binance = update(Exchange::Binance, Some(at(10_500)));
+        skew.correct(&mut binance);
+        assert_eq!(binance.event_time, Some(at(10_000)));
+
+        let mut kraken = update(Exchange::Kraken, Some(at(9_800)));
+        skew.correct(&mut kraken);
+        assert_eq!(kraken.event_time, Some(at(10_000)));
+
+        // Exchanges without an estimate and updates without an event time are left alone
+        let mut bybit = update(Exchange::Bybit, Some(at(10_000)));
+        skew.correct(&mut bybit);
+        assert_eq!(bybit.event_time, Some(at(10_000)));
+        let mut untimed = update(Exchange::Binance, None);
+        skew.correct(&mut untimed);
+        assert!(untimed.event_time.is_none());
+    }
+
+    #[test]
+    fn test_parse_server_time() {
+        let binance = ServerTimeEndpoint::for_exchange(Exchange::Binance).unwrap();
+        assert_eq!(
+            binance
+                .parse_time(r#"{"serverTime":1700000000123}"#)
+                .unwrap(),
+            at(1700000000123)
+        );
+
+        let coinbase = ServerTimeEndpoint::for_exchange(Exchange::Coinbase).unwrap();
+        assert_eq!(
+            coinbase
+                .parse_time(r#"{"iso":"2023-11-14T22:13:20.25Z","epoch":1700000000.25}"#)
+                .unwrap(),
+            at(1700000000250)
+        );
+
+        let okx = ServerTimeEndpoint::for_exchange(Exchange::OKX).unwrap();
+        assert_eq!(
+            okx.parse_time(r#"{"code":"0","data":[{"ts":"1700000000123"}]}"#)
+                .unwrap(),
+            at(1700000000123)
+        );
+
+        assert!(binance.parse_time(r#"{"time":1}"#).is_err());
+        assert!(ServerTimeEndpoint::for_exchange(Exchange::Bitfinex).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sampler_records_offsets() {
+        let server = MockServer::start().await;
+        // A server clock an hour ahead, far beyond any local round trip
+        let server_time = Utc::now() + TimeDelta::hours(1);
+        Mock::given(method("GET"))
+            .and(path("/time"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                r#"{{"serverTime":{}}}"#,
+                server_time.timestamp_millis()
+            )))
+            .mount(&server)
+            .await;
+
+        let skew = ClockSkew::new();
+        let sampler = ClockSkewSampler::new(skew.clone())
+            .with_endpoint(ServerTimeEndpoint::new(
+                Exchange::Binance,
+                &format!("{}/time", server.uri()),
+                "/serverTime",
+            ))
+            .with_interval(Duration::from_millis(10));
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let handle = sampler.spawn(shutdown_tx.subscribe()).unwrap();
+
+        let offset = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(offset) = skew.offset(&Exchange::Binance) {
+                    return offset;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!((offset - TimeDelta::hours(1)).num_seconds().abs() < 5);
+
+        shutdown_tx.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_skew_corrected_connector() {
+        let skew = ClockSkew::new();
+        skew.record(&Exchange::Binance, sample(500, 10));
+        let connector = SkewCorrected::new(
+            FixedConnector {
+                updates: vec![update(Exchange::Binance, Some(at(10_500)))],
+            },
+            skew,
+        );
+
+        let (price_level_tx, mut price_level_rx) = mpsc::channel(16);
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let handles = connector
+            .spawn_order_book_service(&[], 10, 16, price_level_tx, shutdown_rx)
+            .await
+            .unwrap();
+        assert_eq!(handles.len(), 2);
+
+        let corrected = price_level_rx.recv().await.unwrap();
+        assert_eq!(corrected.event_time, Some(at(10_000)));
+
+        shutdown_tx.send(()).unwrap();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use aggregator_core::{OrderEventKind, TradeSide};
+use exchange_connectors::{Coinbase, CoinbaseL3Book};
+
+#[cfg(test)]
+mod coinbase_l3_tests {
+    use super::*;
+
+    const SNAPSHOT: &str = r#"{
+        "sequence": 100,
+        "bids": [
+            ["295.96", "0.05", "3b0f1225-7f84-490b-a29f-0faef9de823a"],
+            ["295.96", "0.10", "71f3c3e3-5a04-4c07-9a36-8b3f1e0c5d24"],
+            ["295.90", "1.00", "b4e1f0c2-4b8c-43d6-98a6-3f8e0ad5a6b1"]
+        ],
+        "asks": [["296.00", "2.00", "a2a5b5b7-3e9f-4d4f-b2a1-91d8c9f0e6c3"]]
+    }"#;
+
+    fn message(sequence: u64, body: &str) -> String {
+        format!(
+            r#"{{"sequence": {}, "product_id": "BTC-USD", "time": "2024-01-01T00:00:00.000000Z", {}}}"#,
+            sequence, body
+        )
+    }
+
+    #[test]
+    fn test_parse_order_events() {
+        let received = message(
+            1,
+            r#""type": "received", "order_id": "o-1", "side": "buy", "price": "100.00", "size": "0.5", "order_type": "limit""#,
+        );
+        let event = Coinbase::parse_order_event(&received).unwrap().unwrap();
+        assert_eq!(event.kind, OrderEventKind::Received);
+        assert_eq!(event.order_id, "o-1");
+        assert_eq!(event.sequence, 1);
+        assert_eq!(event.side, TradeSide::Buy);
+        assert_eq!(event.price, Some(100.0));
+        assert_eq!(event.quantity, Some(0.5));
+
+        let matched = message(
+            2,
+            r#""type": "match", "trade_id": 10, "maker_order_id": "o-1", "taker_order_id": "o-2", "side": "buy", "price": "100.00", "size": "0.2""#,
+        );
+        let event = Coinbase::parse_order_event(&matched).unwrap().unwrap();
+        assert_eq!(event.order_id, "o-1");
+        assert_eq!(
+            event.kind,
+            OrderEventKind::Match {
+                taker_order_id: "o-2".to_string(),
+                trade_id: "10".to_string()
+            }
+        );
+        assert_eq!(event.quantity, Some(0.2));
+
+        // Market orders finish without a price or remaining size
+        let done = message(
+            3,
+            r#""type": "done", "order_id": "o-2", "side": "sell", "reason": "filled""#,
+        );
+        let event = Coinbase::parse_order_event(&done).unwrap().unwrap();
+        assert_eq!(
+            event.kind,
+            OrderEventKind::Done {
+                reason: "filled".to_string()
+            }
+        );
+        assert_eq!(event.price, None);
+        assert_eq!(event.quantity, None);
+
+        let ack = r#"{"type": "subscriptions", "channels": [{"name": "full", "product_ids": ["BTC-USD"]}]}"#;
+        assert!(Coinbase::parse_order_event(ack).unwrap().is_none());
+
+        let bad_side = message(4, r#""type": "open", "order_id": "o-3", "side": "hold""#);
+        assert!(Coinbase::parse_order_event(&bad_side).is_err());
+    }
+
+    #[test]
+    fn test_parse_l3_snapshot() {
+        let book = Coinbase::parse_l3_snapshot(SNAPSHOT).unwrap();
+        assert_eq!(book.sequence(), 100);
+        assert_eq!(book.order_count(), 4);
+
+        let (price, size) = book.best_bid().unwrap();
+        assert_eq!(price, 295.96);
+        assert!((size - 0.15).abs() < 1e-9);
+        assert_eq!(book.best_ask(), Some((296.0, 2.0)));
+
+        let bids = book.levels(TradeSide::Buy, 5);
+        let asks = book.levels(TradeSide::Sell, 5);
+        assert_eq!(bids.len(), 2);
+        assert_eq!(bids[1], (295.9, 1.0));
+        assert_eq!(asks.len(), 1);
+
+        let order = book.order("b4e1f0c2-4b8c-43d6-98a6-3f8e0ad5a6b1").unwrap();
+        assert_eq!(order.side, TradeSide::Buy);
+        assert_eq!(order.size, 1.0);
+
+        assert!(Coinbase::parse_l3_snapshot(r#"{"sequence": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_book_applies_events() {
+        let mut book = Coinbase::parse_l3_snapshot(SNAPSHOT).unwrap();
+        let apply = |book: &mut CoinbaseL3Book, sequence, body: &str| {
+            let event = Coinbase::parse_order_event(&message(sequence, body))
+                .unwrap()
+                .unwrap();
+            book.apply(&event)
+        };
+
+        // Events from before the snapshot are skipped
+        let stale = r#""type": "open", "order_id": "old", "side": "sell", "price": "297.00", "remaining_size": "1.0""#;
+        assert!(!apply(&mut book, 99, stale).unwrap());
+        assert_eq!(book.order_count(), 4);
+
+        let open = r#""type": "open", "order_id": "new", "side": "sell", "price": "295.98", "remaining_size": "1.5""#;
+        assert!(apply(&mut book, 101, open).unwrap());
+        assert_eq!(book.best_ask(), Some((295.98, 1.5)));
+
+        let matched = r#""type": "match", "trade_id": 1, "maker_order_id": "new", "taker_order_id": "t", "side": "sell", "price": "295.98", "size": "0.5""#;
+        assert!(apply(&mut book, 102, matched).unwrap());
+        assert_eq!(book.order("new").unwrap().size, 1.0);
+        assert_eq!(book.best_ask(), Some((295.98, 1.0)));
+
+        let change = r#""type": "change", "order_id": "new", "side": "sell", "price": "295.98", "old_size": "1.0", "new_size": "0.25""#;
+        assert!(apply(&mut book, 103, change).unwrap());
+        assert_eq!(book.best_ask(), Some((295.98, 0.25)));
+
+        let done = r#""type": "done", "order_id": "new", "side": "sell", "price": "295.98", "remaining_size": "0.25", "reason": "canceled""#;
+        assert!(apply(&mut book, 104, done).unwrap());
+        assert!(book.order("new").is_none());
+        assert_eq!(book.best_ask(), Some((296.0, 2.0)));
+        assert_eq!(book.sequence(), 104);
+    }
+
+    #[test]
+    fn test_book_rejects_sequence_gap() {
+        let mut book = Coinbase::parse_l3_snapshot(SNAPSHOT).unwrap();
+        let open = message(
+            105,
+            r#""type": "open", "order_id": "gap", "side": "buy", "price": "295.99", "remaining_size": "1.0""#,
+        );
+        let event = Coinbase::parse_order_event(&open).unwrap().unwrap();
+
+        assert!(book.apply(&event).is_err());
+        assert_eq!(book.sequence(), 100);
+        assert!(book.order("gap").is_none());
+        assert_eq!(book.best_bid().unwrap().0, 295.96);
+    }
+}
@@ -8,18 +8,18 @@ pub fn create_test_price_level_update(exchange: Exchange, symbol: &str) -> Price
     PriceLevelUpdate {
         id: Uuid::new_v4(),
         symbol: symbol.to_string(),
-        exchange,
+        exchange: exchange.clone(),
         bids: vec![
             Bid {
                 price: 50000.0,
                 quantity: 1.0,
-                exchange,
+                exchange: exchange.clone(),
                 timestamp: Utc::now(),
             },
             Bid {
                 price: 49999.0,
                 quantity: 2.0,
-                exchange,
+                exchange: exchange.clone(),
                 timestamp: Utc::now(),
             },
         ],
@@ -27,17 +27,20 @@ pub fn create_test_price_level_update(exchange: Exchange, symbol: &str) -> Price
             Ask {
                 price: 50001.0,
                 quantity: 1.5,
-                exchange,
+                exchange: exchange.clone(),
                 timestamp: Utc::now(),
             },
             Ask {
                 price: 50002.0,
                 quantity: 0.5,
-                exchange,
+                exchange: exchange.clone(),
                 timestamp: Utc::now(),
             },
         ],
         timestamp: Utc::now(),
+        funding: None,
+        event_time: None,
+        market_type: None,
     }
 }
 
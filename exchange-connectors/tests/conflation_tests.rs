@@ -0,0 +1,197 @@
+use aggregator_core::{
+    Ask, Bid, Exchange, MarketType, OrderBookConfig, OrderBookImplementation, PriceLevelUpdate,
+    Result, TradingPair,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use exchange_connectors::{Conflated, Conflator, OrderBookService};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+
+#[cfg(test)]
+mod conflation_tests {
+    use super::*;
+
+    fn update(symbol: &str, bids: &[(f64, f64)], asks: &[(f64, f64)]) -> PriceLevelUpdate {
+        PriceLevelUpdate {
+            id: uuid::Uuid::new_v4(),
+            symbol: symbol.to_string(),
+            exchange: Exchange::Binance,
+            bids: bids
+                .iter()
+                .map(|&(price, quantity)| Bid {
+                    price,
+                    quantity,
+                    exchange: Exchange::Binance,
+                    timestamp: Utc::now(),
+                })
+                .collect(),
+            asks: asks
+                .iter()
+                .map(|&(price, quantity)| Ask {
+                    price,
+                    quantity,
+                    exchange: Exchange::Binance,
+                    timestamp: Utc::now(),
+                })
+                .collect(),
+            timestamp: Utc::now(),
+            funding: None,
+            event_time: None,
+            market_type: None,
+        }
+    }
+
+    fn bids(update: &PriceLevelUpdate) -> Vec<(f64, f64)> {
+        update
+            .bids
+            .iter()
+            .map(|bid| (bid.price, bid.quantity))
+            .collect()
+    }
+
+    fn asks(update: &PriceLevelUpdate) -> Vec<(f64, f64)> {
+        update
+            .asks
+            .iter()
+            .map(|ask| (ask.price, ask.quantity))
+            .collect()
+    }
+
+    /// Emits a fixed burst of updates, then holds the channel open until shutdown.
+    struct BurstConnector {
+        updates: Vec<PriceLevelUpdate>,
+    }
+
+    #[async_trait]
+    impl OrderBookService for BurstConnector {
+        async fn spawn_order_book_service(
+            &self,
+            _pairs: &[TradingPair],
+            _order_book_depth: usize,
+            _exchange_stream_buffer: usize,
+            price_level_tx: mpsc::Sender<PriceLevelUpdate>,
+            mut shutdown: broadcast::Receiver<()>,
+        ) -> Result<Vec<JoinHandle<Result<()>>>> {
+            let updates = self.updates.clone();
+            Ok(vec![tokio::spawn(async move {
+                for update in updates {
+                    let _ = price_level_tx.send(update).await;
+                }
+                let _ = shutdown.recv().await;
+                Ok(())
+            })])
+        }
+    }
+
+    #[test]
+    fn test_merges_levels_per_book() {
+        let mut conflator = Conflator::new();
+        conflator.push(update(
+            "BTCUSDT",
+            &[(100.0, 1.0), (99.0, 2.0)],
+            &[(101.0, 1.0)],
+        ));
+        conflator.push(update("ETHUSDT", &[(10.0, 5.0)], &[]));
+        let mut latest = update("BTCUSDT", &[(100.0, 0.0), (98.0, 3.0)], &[(101.0, 4.0)]);
+        latest.event_time = Some(Utc::now());
+        let latest_id = latest.id;
+        conflator.push(latest);
+
+        assert_eq!(conflator.len(), 2);
+        assert_eq!(conflator.merged(), 1);
+
+        let drained = conflator.drain();
+        assert!(conflator.is_empty());
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained[0].symbol, "BTCUSDT");
+        assert_eq!(drained[0].id, latest_id);
+        assert!(drained[0].event_time.is_some());
+        assert_eq!(
+            bids(&drained[0]),
+            vec![(100.0, 0.0), (99.0, 2.0), (98.0, 3.0)]
+        );
+        assert_eq!(asks(&drained[0]), vec![(101.0, 4.0)]);
+        assert_eq!(drained[1].symbol, "ETHUSDT");
+    }
+
+    #[test]
+    fn test_markets_are_kept_apart() {
+        let mut conflator = Conflator::new();
+        let mut spot = update("BTCUSDT", &[(100.0, 1.0)], &[]);
+        spot.market_type = Some(MarketType::Spot);
+        let mut futures = update("BTCUSDT", &[(100.5, 1.0)], &[]);
+        futures.market_type = Some(MarketType::Futures);
+        conflator.push(spot);
+        conflator.push(futures);
+
+        assert_eq!(conflator.len(), 2);
+        assert_eq!(conflator.merged(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_conflated_connector_coalesces_bursts() {
+        let burst = (0..50)
+            .map(|i| update("BTCUSDT", &[(100.0, i as f64)], &[(101.0, 1.0)]))
+            .collect();
+        let connector =
+            Conflated::new(BurstConnector { updates: burst }, Duration::from_millis(20));
+
+        let (price_level_tx, mut price_level_rx) = mpsc::channel(100);
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let handles = connector
+            .spawn_order_book_service(
+                &[TradingPair::new("BTC", "USDT")],
+                10,
+                100,
+                price_level_tx,
+                shutdown_rx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(handles.len(), 2);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown_tx.send(()).unwrap();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        let mut received = Vec::new();
+        while let Some(update) = price_level_rx.recv().await {
+            received.push(update);
+        }
+        assert!(!received.is_empty() && received.len() < 50);
+        assert_eq!(bids(received.last().unwrap()), vec![(100.0, 49.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_zero_interval_passes_through() {
+        let config = OrderBookConfig {
+            max_depth: 20,
+            market_type: MarketType::Spot,
+            update_interval: 0,
+            cleanup_interval: 1000,
+            implementation: OrderBookImplementation::BTreeSet,
+        };
+        let burst = (0..5)
+            .map(|i| update("BTCUSDT", &[(100.0, i as f64)], &[]))
+            .collect();
+        let connector = Conflated::from_config(BurstConnector { updates: burst }, &config);
+        assert!(connector.interval().is_zero());
+
+        let (price_level_tx, mut price_level_rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let handles = connector
+            .spawn_order_book_service(&[], 10, 100, price_level_tx, shutdown_rx)
+            .await
+            .unwrap();
+        assert_eq!(handles.len(), 1);
+
+        for i in 0..5 {
+            let update = price_level_rx.recv().await.unwrap();
+            assert_eq!(update.bids[0].quantity, i as f64);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use aggregator_core::{HealthEvent, HealthEventKind, MarketType, PriceLevelUpdate, TradingPair};
+use exchange_connectors::{Binance, Bybit, Hyperliquid, OrderBookService, ReconnectPolicy};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod mock_exchange;
+use mock_exchange::{MockExchangeServer, Step};
+
+#[cfg(test)]
+mod connector_integration_tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn bybit_depth(kind: &str, seq: u64, bid: &str, ask: &str) -> String {
+        format!(
+            r#"{{"topic":"orderbook.50.BTCUSDT","type":"{}","ts":{},"data":{{"s":"BTCUSDT","b":[["{}","1.0"]],"a":[["{}","2.0"]],"u":{},"seq":{}}}}}"#,
+            kind,
+            1700000000000 + seq,
+            bid,
+            ask,
+            seq,
+            seq
+        )
+    }
+
+    fn binance_depth(first: u64, last: u64, bids: &str, asks: &str) -> String {
+        format!(
+            r#"{{"stream":"btcusdt@depth","data":{{"e":"depthUpdate","E":{},"s":"BTCUSDT","U":{},"u":{},"b":[{}],"a":[{}]}}}}"#,
+            1700000000000 + last,
+            first,
+            last,
+            bids,
+            asks
+        )
+    }
+
+    fn hyperliquid_book(time: i64, bids: &str, asks: &str) -> String {
+        format!(
+            r#"{{"channel":"l2Book","data":{{"coin":"ETH","time":{},"levels":[[{}],[{}]]}}}}"#,
+            time, bids, asks
+        )
+    }
+
+    fn fast_reconnect() -> ReconnectPolicy {
+        ReconnectPolicy::default()
+            .with_initial_delay(Duration::from_millis(10))
+            .with_jitter(0.0)
+    }
+
+    fn bybit(server: &MockExchangeServer) -> Bybit {
+        let mut bybit = Bybit::new().with_reconnect_policy(fast_reconnect());
+        bybit.config.websocket_url = server.url("/v5/public/linear");
+        bybit
+    }
+
+    async fn next_update(rx: &mut mpsc::Receiver<PriceLevelUpdate>) -> PriceLevelUpdate {
+        tokio::time::timeout(TIMEOUT, rx.recv())
+            .await
+            .expect("timed out waiting for an update")
+            .expect("update channel closed")
+    }
+
+    async fn next_health(rx: &mut broadcast::Receiver<HealthEvent>) -> HealthEventKind {
+        tokio::time::timeout(TIMEOUT, rx.recv())
+            .await
+            .expect("timed out waiting for a health event")
+            .unwrap()
+            .kind
+    }
+
+    #[tokio::test]
+    async fn test_bybit_deltas_wait_for_snapshot() {
+        let mut session = vec![Step::Expect("orderbook.50.BTCUSDT".to_string())];
+        session.extend(Step::replay([
+            bybit_depth("delta", 1, "99.0", "102.0"),
+            bybit_depth("snapshot", 2, "100.0", "101.0"),
+            bybit_depth("delta", 3, "100.5", "101.5"),
+        ]));
+        let server = MockExchangeServer::start(vec![session]).await;
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let handles = bybit(&server)
+            .spawn_order_book_service(
+                &[TradingPair::new("BTC", "USDT")],
+                50,
+                16,
+                tx,
+                shutdown_tx.subscribe(),
+            )
+            .await
+            .unwrap();
+
+        // The delta sent before the snapshot is dropped
+        let snapshot = next_update(&mut rx).await;
+        assert_eq!(snapshot.bids[0].price, 100.0);
+        assert_eq!(snapshot.market_type, Some(MarketType::Futures));
+        assert_eq!(
+            snapshot.event_time.unwrap().timestamp_millis(),
+            1700000000002
+        );
+        let delta = next_update(&mut rx).await;
+        assert_eq!(delta.bids[0].price, 100.5);
+        assert_eq!(delta.asks[0].quantity, 2.0);
+
+        let subscription = &server.received(0)[0];
+        assert!(subscription.contains("orderbook.50.BTCUSDT"));
+        assert!(subscription.contains("tickers.BTCUSDT"));
+
+        shutdown_tx.send(()).unwrap();
+        for handle in handles {
+            let result = tokio::time::timeout(TIMEOUT, handle).await.unwrap();
+            assert!(result.unwrap().is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bybit_reconnects_and_resubscribes() {
+        let subscribe = || Step::Expect("orderbook.50.BTCUSDT".to_string());
+        let server = MockExchangeServer::start(vec![
+            vec![
+                subscribe(),
+                Step::Send(bybit_depth("snapshot", 1, "100.0", "101.0")),
+                Step::Drop,
+            ],
+            vec![
+                subscribe(),
+                Step::Send(bybit_depth("snapshot", 2, "200.0", "201.0")),
+            ],
+        ])
+        .await;
+
+        let (health_tx, mut health_rx) = broadcast::channel(16);
+        let (tx, mut rx) = mpsc::channel(16);
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let _handles = bybit(&server)
+            .with_health_events(health_tx)
+            .spawn_order_book_service(
+                &[TradingPair::new("BTC", "USDT")],
+                50,
+                16,
+                tx,
+                shutdown_tx.subscribe(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(next_update(&mut rx).await.bids[0].price, 100.0);
+        assert_eq!(next_update(&mut rx).await.bids[0].price, 200.0);
+        server.wait_for_connections(2, TIMEOUT).await;
+        assert!(server.received(1)[0].contains("orderbook.50.BTCUSDT"));
+
+        assert_eq!(
+            next_health(&mut health_rx).await,
+            HealthEventKind::Connected
+        );
+        assert!(matches!(
+            next_health(&mut health_rx).await,
+            HealthEventKind::Disconnected { .. }
+        ));
+        assert!(matches!(
+            next_health(&mut health_rx).await,
+            HealthEventKind::Reconnecting { attempt: 1, .. }
+        ));
+        assert_eq!(
+            next_health(&mut health_rx).await,
+            HealthEventKind::Connected
+        );
+
+        shutdown_tx.send(()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_binance_applies_diffs_on_top_of_snapshot() {
+        let rest = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/depth"))
+            .and(query_param("symbol", "BTCUSDT"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"lastUpdateId":110,"bids":[["100.00","1.5"],["99.00","2.0"]],"asks":[["101.00","1.0"]]}"#,
+            ))
+            .mount(&rest)
+            .await;
+        let server = MockExchangeServer::start(vec![Step::replay([
+            // Already covered by the snapshot, whenever it arrives
+            binance_depth(100, 105, r#"["98.00","4.0"]"#, ""),
+            binance_depth(109, 112, r#"["100.00","0"]"#, r#"["100.50","3.0"]"#),
+            binance_depth(113, 115, r#"["99.50","1.0"]"#, ""),
+        ])])
+        .await;
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let handles = Binance::new()
+            .with_websocket_url(&server.url("/stream?streams="))
+            .with_snapshot_url(&format!("{}/api/v3/depth?symbol=", rest.uri()))
+            .with_reconnect_policy(fast_reconnect())
+            .spawn_order_book_service(
+                &[TradingPair::new("BTC", "USDT")],
+                20,
+                16,
+                tx,
+                shutdown_tx.subscribe(),
+            )
+            .await
+            .unwrap();
+
+        let snapshot = next_update(&mut rx).await;
+        assert_eq!(snapshot.symbol, "BTCUSDT");
+        assert_eq!(snapshot.bids.len(), 2);
+        assert_eq!(snapshot.bids[0].price, 100.0);
+        assert_eq!(snapshot.bids[0].quantity, 1.5);
+        assert_eq!(snapshot.asks[0].price, 101.0);
+
+        // The first diff straddling the snapshot is applied, the covered one never is
+        let diff = next_update(&mut rx).await;
+        assert_eq!(diff.bids[0].price, 100.0);
+        assert_eq!(diff.bids[0].quantity, 0.0);
+        assert_eq!(diff.asks[0].price, 100.5);
+        assert_eq!(diff.event_time.unwrap().timestamp_millis(), 1700000000112);
+        let diff = next_update(&mut rx).await;
+        assert_eq!(diff.bids[0].price, 99.5);
+        assert!(diff.asks.is_empty());
+
+        let requests = rest.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url.query(), Some("symbol=BTCUSDT&limit=20"));
+
+        shutdown_tx.send(()).unwrap();
+        for handle in handles {
+            let result = tokio::time::timeout(TIMEOUT, handle).await.unwrap();
+            assert!(result.unwrap().is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hyperliquid_snapshots_become_deltas() {
+        let mut session = vec![Step::Expect(r#""coin":"ETH""#.to_string())];
+        session.extend(Step::replay([
+            r#"{"channel":"subscriptionResponse","data":{}}"#.to_string(),
+            hyperliquid_book(
+                1,
+                r#"{"px":"2000.0","sz":"1.0","n":1},{"px":"1999.5","sz":"3.0","n":2}"#,
+                r#"{"px":"2000.5","sz":"2.0","n":1}"#,
+            ),
+            hyperliquid_book(
+                2,
+                r#"{"px":"2000.0","sz":"1.5","n":1}"#,
+                r#"{"px":"2000.5","sz":"2.0","n":1}"#,
+            ),
+        ]));
+        let server = MockExchangeServer::start(vec![session]).await;
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let connector = Hyperliquid::new()
+            .with_websocket_url(&server.url("/ws"))
+            .with_reconnect_policy(fast_reconnect());
+        let handles = connector
+            .spawn_order_book_service(
+                &[TradingPair::new("ETH", "USDC")],
+                20,
+                16,
+                tx,
+                shutdown_tx.subscribe(),
+            )
+            .await
+            .unwrap();
+
+        let first = next_update(&mut rx).await;
+        assert_eq!(first.symbol, "ETH");
+        assert_eq!(first.bids.len(), 2);
+
+        let second = next_update(&mut rx).await;
+        assert_eq!(second.bids.len(), 2);
+        assert_eq!(second.bids[0].quantity, 1.5);
+        assert_eq!(second.bids[1].price, 1999.5);
+        assert_eq!(second.bids[1].quantity, 0.0);
+
+        shutdown_tx.send(()).unwrap();
+        for handle in handles {
+            let result = tokio::time::timeout(TIMEOUT, handle).await.unwrap();
+            assert!(result.unwrap().is_ok());
+        }
+        assert_eq!(server.connections(), 1);
+    }
+}
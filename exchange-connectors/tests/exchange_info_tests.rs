@@ -0,0 +1,382 @@
+use aggregator_core::{
+    Exchange, InstrumentInfo, InstrumentRegistry, MarketType, Result, TradingPair,
+};
+use async_trait::async_trait;
+use exchange_connectors::{Binance, Bybit, Coinbase, ExchangeInfoService, GateIo, Kraken, KuCoin};
+
+#[cfg(test)]
+mod exchange_info_tests {
+    use super::*;
+
+    fn btc_usdt() -> Vec<TradingPair> {
+        vec![TradingPair::new("BTC", "USDT")]
+    }
+
+    #[test]
+    fn test_binance_instruments() {
+        let body = r#"{
+            "symbols": [
+                {
+                    "symbol": "ETHUSDT",
+                    "filters": []
+                },
+                {
+                    "symbol": "BTCUSDT",
+                    "filters": [
+                        {"filterType": "PRICE_FILTER", "minPrice": "0.01", "maxPrice": "1000000.00", "tickSize": "0.01"},
+                        {"filterType": "LOT_SIZE", "minQty": "0.00001", "maxQty": "9000.00", "stepSize": "0.00001"},
+                        {"filterType": "ICEBERG_PARTS", "limit": 10},
+                        {"filterType": "NOTIONAL", "minNotional": "5.00", "applyMinToMarket": true}
+                    ]
+                }
+            ]
+        }"#;
+
+        let instruments = Binance::parse_instruments(body, &btc_usdt()).unwrap();
+        assert_eq!(instruments.len(), 1);
+        let info = &instruments[0];
+        assert_eq!(info.symbol, "BTCUSDT");
+        assert_eq!(info.pair, TradingPair::new("BTC", "USDT"));
+        assert_eq!(info.tick_size, 0.01);
+        assert_eq!(info.lot_size, 0.00001);
+        assert_eq!(info.min_quantity, 0.00001);
+        assert_eq!(info.min_notional, Some(5.0));
+
+        // Futures report the minimum order value as `notional`
+        let futures = r#"{"symbols": [{"symbol": "BTCUSDT", "filters": [
+            {"filterType": "PRICE_FILTER", "tickSize": "0.10"},
+            {"filterType": "LOT_SIZE", "minQty": "0.001", "stepSize": "0.001"},
+            {"filterType": "MIN_NOTIONAL", "notional": "100"}
+        ]}]}"#;
+        let instruments = Binance::parse_instruments(futures, &btc_usdt()).unwrap();
+        assert_eq!(instruments[0].min_notional, Some(100.0));
+
+        assert_eq!(
+            Binance::new().exchange_info_endpoint(&["BTCUSDT".to_string()]),
+            "https://api.binance.com/api/v3/exchangeInfo?symbols=%5B%22BTCUSDT%22%5D"
+        );
+    }
+
+    #[test]
+    fn test_missing_instruments_rejected() {
+        let body = r#"{"symbols": [{"symbol": "ETHUSDT", "filters": []}]}"#;
+        assert!(Binance::parse_instruments(body, &btc_usdt()).is_err());
+    }
+
+    #[test]
+    fn test_bybit_instruments() {
+        let spot = r#"{
+            "retCode": 0,
+            "retMsg": "OK",
+            "result": {"category": "spot", "list": [{
+                "symbol": "BTCUSDT",
+                "lotSizeFilter": {"basePrecision": "0.000001", "minOrderQty": "0.000048", "minOrderAmt": "1"},
+                "priceFilter": {"tickSize": "0.01"}
+            }]}
+        }"#;
+        let info = &Bybit::parse_instruments(spot, &btc_usdt()).unwrap()[0];
+        assert_eq!(info.lot_size, 0.000001);
+        assert_eq!(info.min_quantity, 0.000048);
+        assert_eq!(info.min_notional, Some(1.0));
+
+        let linear = r#"{
+            "retCode": 0,
+            "retMsg": "OK",
+            "result": {"category": "linear", "list": [{
+                "symbol": "BTCUSDT",
+                "lotSizeFilter": {"qtyStep": "0.001", "minOrderQty": "0.001", "minNotionalValue": "5"},
+                "priceFilter": {"minPrice": "0.10", "tickSize": "0.10"}
+            }]}
+        }"#;
+        let info = &Bybit::parse_instruments(linear, &btc_usdt()).unwrap()[0];
+        assert_eq!(info.tick_size, 0.1);
+        assert_eq!(info.lot_size, 0.001);
+        assert_eq!(info.min_notional, Some(5.0));
+
+        let error = r#"{"retCode": 10001, "retMsg": "params error", "result": {}}"#;
+        assert!(Bybit::parse_instruments(error, &btc_usdt()).is_err());
+
+        assert_eq!(
+            Bybit::new().instruments_url("BTCUSDT").unwrap(),
+            "https://api.bybit.com/v5/market/instruments-info?category=linear&symbol=BTCUSDT"
+        );
+    }
+
+    #[test]
+    fn test_binance_markets() {
+        let exchange_info = r#"{"symbols": [
+            {"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT", "filters": []},
+            {"symbol": "ETHBTC", "status": "TRADING", "baseAsset": "ETH", "quoteAsset": "BTC", "filters": []},
+            {"symbol": "LUNAUSDT", "status": "BREAK", "baseAsset": "LUNA", "quoteAsset": "USDT", "filters": []}
+        ]}"#;
+        let tickers = r#"[
+            {"symbol": "BTCUSDT", "lastPrice": "65000.00", "quoteVolume": "1500000000.50"},
+            {"symbol": "LUNAUSDT", "lastPrice": "0.00", "quoteVolume": "0.00"}
+        ]"#;
+
+        let markets = Binance::parse_markets(exchange_info, tickers).unwrap();
+        assert_eq!(markets.len(), 2);
+        assert_eq!(markets[0].pair, TradingPair::new("BTC", "USDT"));
+        assert_eq!(markets[0].symbol, "BTCUSDT");
+        assert_eq!(markets[0].quote_volume, Some(1_500_000_000.5));
+        // Symbols missing from the tickers have no volume
+        assert_eq!(markets[1].pair, TradingPair::new("ETH", "BTC"));
+        assert_eq!(markets[1].quote_volume, None);
+        assert!(markets
+            .iter()
+            .all(|market| market.exchange == Exchange::Binance));
+
+        assert_eq!(
+            Binance::new().markets_endpoint(),
+            "https://api.binance.com/api/v3/exchangeInfo"
+        );
+        assert_eq!(
+            Binance::new()
+                .with_market_type(MarketType::Futures)
+                .ticker_endpoint(),
+            "https://fapi.binance.com/fapi/v1/ticker/24hr"
+        );
+    }
+
+    #[test]
+    fn test_bybit_markets() {
+        let page = r#"{
+            "retCode": 0,
+            "retMsg": "OK",
+            "result": {"category": "linear", "nextPageCursor": "first%3D10000", "list": [
+                {"symbol": "BTCUSDT", "baseCoin": "BTC", "quoteCoin": "USDT", "status": "Trading"},
+                {"symbol": "ETHPERP", "baseCoin": "ETH", "quoteCoin": "USDC", "status": "PreLaunch"}
+            ]}
+        }"#;
+        let (markets, cursor) = Bybit::parse_markets(page).unwrap();
+        assert_eq!(markets.len(), 1);
+        assert_eq!(markets[0].pair, TradingPair::new("BTC", "USDT"));
+        assert_eq!(markets[0].quote_volume, None);
+        assert_eq!(cursor.as_deref(), Some("first%3D10000"));
+
+        let last_page =
+            r#"{"retCode": 0, "retMsg": "OK", "result": {"list": [], "nextPageCursor": ""}}"#;
+        assert_eq!(Bybit::parse_markets(last_page).unwrap().1, None);
+
+        let tickers = r#"{
+            "retCode": 0,
+            "retMsg": "OK",
+            "result": {"category": "linear", "list": [
+                {"symbol": "BTCUSDT", "lastPrice": "65000", "turnover24h": "2500000.25"}
+            ]}
+        }"#;
+        let volumes = Bybit::parse_quote_volumes(tickers).unwrap();
+        assert_eq!(volumes.get("BTCUSDT"), Some(&2_500_000.25));
+
+        let error = r#"{"retCode": 10001, "retMsg": "params error", "result": {}}"#;
+        assert!(Bybit::parse_markets(error).is_err());
+
+        let bybit = Bybit::new();
+        assert_eq!(
+            bybit.markets_url(Some("first%3D10000")).unwrap(),
+            "https://api.bybit.com/v5/market/instruments-info?category=linear&limit=1000&cursor=first%3D10000"
+        );
+        assert_eq!(
+            bybit.tickers_url().unwrap(),
+            "https://api.bybit.com/v5/market/tickers?category=linear"
+        );
+    }
+
+    #[test]
+    fn test_gateio_instruments() {
+        let body = r#"[{
+            "id": "BTC_USDT",
+            "base": "BTC",
+            "quote": "USDT",
+            "min_base_amount": "0.0001",
+            "min_quote_amount": "3",
+            "amount_precision": 6,
+            "precision": 1,
+            "trade_status": "tradable"
+        }]"#;
+        let info = &GateIo::parse_instruments(body, &btc_usdt()).unwrap()[0];
+        assert_eq!(info.tick_size, 0.1);
+        assert_eq!(info.lot_size, 0.000001);
+        assert_eq!(info.min_quantity, 0.0001);
+        assert_eq!(info.min_notional, Some(3.0));
+    }
+
+    #[test]
+    fn test_kucoin_instruments() {
+        let body = r#"{"code": "200000", "data": [{
+            "symbol": "BTC-USDT",
+            "baseMinSize": "0.00001",
+            "baseIncrement": "0.00000001",
+            "priceIncrement": "0.1",
+            "minFunds": "0.1"
+        }]}"#;
+        let info = &KuCoin::parse_instruments(body, &btc_usdt()).unwrap()[0];
+        assert_eq!(info.symbol, "BTC-USDT");
+        assert_eq!(info.tick_size, 0.1);
+        assert_eq!(info.lot_size, 0.00000001);
+        assert_eq!(info.min_notional, Some(0.1));
+    }
+
+    #[test]
+    fn test_kraken_instruments() {
+        let body = r#"{"error": [], "result": {
+            "XXBTZUSD": {
+                "altname": "XBTUSD",
+                "wsname": "XBT/USD",
+                "pair_decimals": 1,
+                "lot_decimals": 8,
+                "ordermin": "0.0001",
+                "costmin": "0.5",
+                "tick_size": "0.1"
+            },
+            "XXBTZUSD.d": {"altname": "XBTUSD.d", "pair_decimals": 1, "lot_decimals": 8}
+        }}"#;
+        let pairs = vec![TradingPair::new("BTC", "USD")];
+        let info = &Kraken::parse_instruments(body, &pairs).unwrap()[0];
+        assert_eq!(info.symbol, "XBT/USD");
+        assert_eq!(info.pair, TradingPair::new("BTC", "USD"));
+        assert_eq!(info.tick_size, 0.1);
+        assert_eq!(info.lot_size, 0.00000001);
+        assert_eq!(info.min_quantity, 0.0001);
+        assert_eq!(info.min_notional, Some(0.5));
+
+        let error = r#"{"error": ["EQuery:Unknown asset pair"]}"#;
+        assert!(Kraken::parse_instruments(error, &pairs).is_err());
+    }
+
+    #[test]
+    fn test_coinbase_instruments() {
+        let body = r#"[{
+            "id": "BTC-USD",
+            "base_currency": "BTC",
+            "quote_currency": "USD",
+            "quote_increment": "0.01",
+            "base_increment": "0.00000001",
+            "min_market_funds": "1",
+            "status": "online"
+        }]"#;
+        let pairs = vec![TradingPair::new("BTC", "USD")];
+        let info = &Coinbase::parse_instruments(body, &pairs).unwrap()[0];
+        assert_eq!(info.tick_size, 0.01);
+        assert_eq!(info.min_quantity, 0.00000001);
+        assert_eq!(info.min_notional, Some(1.0));
+    }
+
+    #[test]
+    fn test_gateio_markets() {
+        let body = r#"[
+            {"id": "BTC_USDT", "base": "BTC", "quote": "USDT", "amount_precision": 6, "precision": 1, "trade_status": "tradable"},
+            {"id": "LUNA_USDT", "base": "LUNA", "quote": "USDT", "amount_precision": 2, "precision": 4, "trade_status": "untradable"}
+        ]"#;
+        let markets = GateIo::parse_markets(body).unwrap();
+        assert_eq!(markets.len(), 1);
+        assert_eq!(markets[0].exchange, Exchange::GateIo);
+        assert_eq!(markets[0].pair, TradingPair::new("BTC", "USDT"));
+        assert_eq!(markets[0].symbol, "BTC_USDT");
+        assert_eq!(markets[0].quote_volume, None);
+    }
+
+    #[test]
+    fn test_kucoin_markets() {
+        let body = r#"{"code": "200000", "data": [
+            {"symbol": "BTC-USDT", "baseCurrency": "BTC", "quoteCurrency": "USDT", "enableTrading": true,
+             "baseMinSize": "0.00001", "baseIncrement": "0.00000001", "priceIncrement": "0.1"},
+            {"symbol": "LUNA-USDT", "baseCurrency": "LUNA", "quoteCurrency": "USDT", "enableTrading": false,
+             "baseMinSize": "0.1", "baseIncrement": "0.0001", "priceIncrement": "0.0001"}
+        ]}"#;
+        let markets = KuCoin::parse_markets(body).unwrap();
+        assert_eq!(markets.len(), 1);
+        assert_eq!(markets[0].exchange, Exchange::KuCoin);
+        assert_eq!(markets[0].pair, TradingPair::new("BTC", "USDT"));
+        assert_eq!(markets[0].symbol, "BTC-USDT");
+
+        let error = r#"{"code": "400100", "msg": "Invalid request"}"#;
+        assert!(KuCoin::parse_markets(error).is_err());
+    }
+
+    #[test]
+    fn test_kraken_markets() {
+        let body = r#"{"error": [], "result": {
+            "XXBTZUSD": {"wsname": "XBT/USD", "status": "online", "pair_decimals": 1, "lot_decimals": 8},
+            "XETHZUSD": {"wsname": "ETH/USD", "pair_decimals": 2, "lot_decimals": 8},
+            "LUNAUSD": {"wsname": "LUNA/USD", "status": "delisted", "pair_decimals": 4, "lot_decimals": 8},
+            "XXBTZUSD.d": {"altname": "XBTUSD.d", "pair_decimals": 1, "lot_decimals": 8}
+        }}"#;
+        let markets = Kraken::parse_markets(body).unwrap();
+        let listed: Vec<_> = markets
+            .iter()
+            .map(|market| (market.symbol.as_str(), market.pair.clone()))
+            .collect();
+        // Kraken's asset codes are mapped to the common ones
+        assert_eq!(
+            listed,
+            vec![
+                ("ETH/USD", TradingPair::new("ETH", "USD")),
+                ("XBT/USD", TradingPair::new("BTC", "USD")),
+            ]
+        );
+        assert!(markets
+            .iter()
+            .all(|market| market.exchange == Exchange::Kraken));
+
+        let error = r#"{"error": ["EGeneral:Temporary lockout"]}"#;
+        assert!(Kraken::parse_markets(error).is_err());
+    }
+
+    #[test]
+    fn test_coinbase_markets() {
+        let body = r#"[
+            {"id": "BTC-USD", "base_currency": "BTC", "quote_currency": "USD", "quote_increment": "0.01",
+             "base_increment": "0.00000001", "status": "online", "trading_disabled": false},
+            {"id": "ETH-USD", "base_currency": "ETH", "quote_currency": "USD", "quote_increment": "0.01",
+             "base_increment": "0.00000001", "status": "online", "trading_disabled": true},
+            {"id": "LUNA-USD", "base_currency": "LUNA", "quote_currency": "USD", "quote_increment": "0.0001",
+             "base_increment": "0.01", "status": "delisted"}
+        ]"#;
+        let markets = Coinbase::parse_markets(body).unwrap();
+        assert_eq!(markets.len(), 1);
+        assert_eq!(markets[0].exchange, Exchange::Coinbase);
+        assert_eq!(markets[0].pair, TradingPair::new("BTC", "USD"));
+        assert_eq!(markets[0].symbol, "BTC-USD");
+        assert_eq!(markets[0].quote_volume, None);
+    }
+
+    struct StaticInfo;
+
+    #[async_trait]
+    impl ExchangeInfoService for StaticInfo {
+        async fn fetch_instruments(&self, pairs: &[TradingPair]) -> Result<Vec<InstrumentInfo>> {
+            Ok(pairs
+                .iter()
+                .map(|pair| InstrumentInfo {
+                    exchange: Exchange::Binance,
+                    pair: pair.clone(),
+                    symbol: format!("{}{}", pair.base, pair.quote),
+                    tick_size: 0.01,
+                    lot_size: 0.001,
+                    min_quantity: 0.001,
+                    min_notional: None,
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_instruments_into_registry() {
+        let registry = InstrumentRegistry::new();
+        let pairs = vec![
+            TradingPair::new("BTC", "USDT"),
+            TradingPair::new("ETH", "USDT"),
+        ];
+
+        let loaded = StaticInfo
+            .load_instruments(&pairs, &registry)
+            .await
+            .unwrap();
+        assert_eq!(loaded, 2);
+        assert_eq!(
+            registry.round_price(&Exchange::Binance, &pairs[1], 3000.004),
+            3000.0
+        );
+    }
+}
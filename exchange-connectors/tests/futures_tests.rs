@@ -0,0 +1,156 @@
+use aggregator_core::{FundingRate, MarketType, PriceLevelUpdate, TradingPair};
+use exchange_connectors::{Binance, Bybit, OrderBookService};
+use std::collections::HashMap;
+use tokio::sync::{broadcast, mpsc};
+
+#[cfg(test)]
+mod futures_tests {
+    use super::*;
+
+    #[test]
+    fn test_binance_futures_endpoints() {
+        let streams = vec!["btcusdt@depth".to_string(), "btcusdt@markPrice".to_string()];
+
+        let spot = Binance::new();
+        assert!(spot
+            .order_book_stream_endpoint(&streams)
+            .unwrap()
+            .starts_with("wss://stream.binance.com"));
+
+        let futures = Binance::new().with_market_type(MarketType::Futures);
+        assert_eq!(
+            futures.order_book_stream_endpoint(&streams).unwrap(),
+            "wss://fstream.binance.com/stream?streams=btcusdt@depth/btcusdt@markPrice"
+        );
+
+        let testnet = Binance::new()
+            .with_sandbox(true)
+            .with_market_type(MarketType::Futures);
+        assert!(testnet
+            .order_book_stream_endpoint(&streams)
+            .unwrap()
+            .starts_with("wss://stream.binancefuture.com"));
+
+        let options = Binance::new().with_market_type(MarketType::Options);
+        assert!(options.order_book_stream_endpoint(&streams).is_err());
+    }
+
+    #[test]
+    fn test_binance_mark_price_parsing() {
+        let message = r#"{"stream":"btcusdt@markPrice","data":{"e":"markPriceUpdate","E":1562305380000,"s":"BTCUSDT","p":"11794.15000000","i":"11784.62659091","P":"11784.25641265","r":"0.00038167","T":1562306400000}}"#;
+
+        let (symbol, funding) = Binance::parse_funding_rate(message).unwrap().unwrap();
+        assert_eq!(symbol, "BTCUSDT");
+        assert_eq!(funding.rate, 0.00038167);
+        assert_eq!(funding.mark_price, Some(11794.15));
+        assert_eq!(
+            funding.next_funding_time.unwrap().timestamp_millis(),
+            1562306400000
+        );
+
+        let depth = r#"{"e":"depthUpdate","E":1,"s":"BTCUSDT","U":1,"u":2,"pu":0,"b":[],"a":[]}"#;
+        assert!(Binance::parse_funding_rate(depth).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_bybit_market_type_selects_category() {
+        let bybit = Bybit::new();
+        assert_eq!(
+            bybit.config.websocket_url,
+            "wss://stream.bybit.com/v5/public/linear"
+        );
+
+        let spot = Bybit::new().with_market_type(MarketType::Spot);
+        assert_eq!(
+            spot.config.websocket_url,
+            "wss://stream.bybit.com/v5/public/spot"
+        );
+
+        let linear = spot.with_market_type(MarketType::Futures);
+        assert_eq!(
+            linear.config.websocket_url,
+            "wss://stream.bybit.com/v5/public/linear"
+        );
+    }
+
+    #[test]
+    fn test_bybit_multiple_market_types() {
+        let bybit = Bybit::new().with_market_types(&[
+            MarketType::Spot,
+            MarketType::Futures,
+            MarketType::Spot,
+        ]);
+        assert_eq!(
+            bybit.market_types(),
+            &[MarketType::Spot, MarketType::Futures]
+        );
+        assert_eq!(
+            bybit.config.websocket_url,
+            "wss://stream.bybit.com/v5/public/spot"
+        );
+        assert_eq!(
+            bybit.websocket_url(&MarketType::Futures).unwrap(),
+            "wss://stream.bybit.com/v5/public/linear"
+        );
+        assert!(bybit.websocket_url(&MarketType::Options).is_none());
+        assert!(bybit
+            .instruments_url("BTCUSDT")
+            .unwrap()
+            .contains("category=spot"));
+    }
+
+    #[test]
+    fn test_bybit_ticker_funding_merge() {
+        let mut funding: HashMap<String, FundingRate> = HashMap::new();
+
+        // A delta before the snapshot has no funding rate to start from
+        let early_delta = r#"{"topic":"tickers.BTCUSDT","type":"delta","data":{"symbol":"BTCUSDT","markPrice":"17200.00"},"cs":1,"ts":1}"#;
+        assert!(Bybit::update_funding_rates(early_delta, &mut funding).unwrap());
+        assert!(funding.is_empty());
+
+        let snapshot = r#"{"topic":"tickers.BTCUSDT","type":"snapshot","data":{"symbol":"BTCUSDT","lastPrice":"17216.00","markPrice":"17217.33","fundingRate":"-0.000144","nextFundingTime":"1673280000000"},"cs":2,"ts":2}"#;
+        assert!(Bybit::update_funding_rates(snapshot, &mut funding).unwrap());
+        assert_eq!(funding["BTCUSDT"].rate, -0.000144);
+
+        let delta = r#"{"topic":"tickers.BTCUSDT","type":"delta","data":{"symbol":"BTCUSDT","markPrice":"17220.00"},"cs":3,"ts":3}"#;
+        assert!(Bybit::update_funding_rates(delta, &mut funding).unwrap());
+        assert_eq!(funding["BTCUSDT"].rate, -0.000144);
+        assert_eq!(funding["BTCUSDT"].mark_price, Some(17220.0));
+        assert!(funding["BTCUSDT"].next_funding_time.is_some());
+
+        let depth = r#"{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":1,"data":{"s":"BTCUSDT","b":[],"a":[],"u":1,"seq":1}}"#;
+        assert!(!Bybit::update_funding_rates(depth, &mut funding).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_options_market_is_rejected() {
+        let pairs = [TradingPair::new("BTC", "USDT")];
+
+        let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(10);
+
+        let (shutdown_tx, _) = broadcast::channel(1);
+        assert!(Binance::new()
+            .with_market_type(MarketType::Options)
+            .spawn_order_book_service(&pairs, 100, 100, tx, shutdown_tx.subscribe())
+            .await
+            .is_err());
+
+        let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(10);
+
+        let (shutdown_tx, _) = broadcast::channel(1);
+        assert!(Bybit::new()
+            .with_market_type(MarketType::Options)
+            .spawn_order_book_service(&pairs, 100, 100, tx, shutdown_tx.subscribe())
+            .await
+            .is_err());
+
+        let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(10);
+
+        let (shutdown_tx, _) = broadcast::channel(1);
+        assert!(Bybit::new()
+            .with_market_types(&[MarketType::Spot, MarketType::Options])
+            .spawn_order_book_service(&pairs, 100, 100, tx, shutdown_tx.subscribe())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use aggregator_core::{Exchange, ExchangeConfig};
+use exchange_connectors::GateIo;
+
+#[cfg(test)]
+mod gateio_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_depth_update() {
+        let message = r#"{
+            "time": 1606294781,
+            "time_ms": 1606294781236,
+            "channel": "spot.order_book_update",
+            "event": "update",
+            "result": {
+                "t": 1606294781123,
+                "e": "depthUpdate",
+                "E": 1606294781,
+                "s": "BTC_USDT",
+                "U": 48776301,
+                "u": 48776306,
+                "b": [["19137.74", "0.0001"], ["19088.37", "0"]],
+                "a": [["19137.75", "0.6135"]]
+            }
+        }"#;
+
+        let depth = GateIo::parse_depth_update(message).unwrap().unwrap();
+        assert_eq!(depth.first_update_id, 48776301);
+        assert_eq!(depth.last_update_id, 48776306);
+        assert_eq!(depth.update.symbol, "BTC_USDT");
+        assert_eq!(depth.update.exchange, Exchange::GateIo);
+        assert_eq!(depth.update.bids.len(), 2);
+        assert_eq!(depth.update.bids[0].price, 19137.74);
+        assert_eq!(depth.update.bids[1].quantity, 0.0);
+        assert_eq!(depth.update.asks[0].quantity, 0.6135);
+        assert_eq!(
+            depth.update.event_time.unwrap().timestamp_millis(),
+            1606294781123
+        );
+    }
+
+    #[test]
+    fn test_parse_depth_update_ignores_other_messages() {
+        let subscribed = r#"{
+            "time": 1606292218,
+            "channel": "spot.order_book_update",
+            "event": "subscribe",
+            "result": {"status": "success"}
+        }"#;
+        assert!(GateIo::parse_depth_update(subscribed).unwrap().is_none());
+
+        let pong = r#"{"time": 1606292218, "channel": "spot.pong", "event": "", "result": null}"#;
+        assert!(GateIo::parse_depth_update(pong).unwrap().is_none());
+
+        assert!(GateIo::parse_depth_update("not json").is_err());
+    }
+
+    #[test]
+    fn test_from_config_rejects_sandbox() {
+        let config = ExchangeConfig {
+            sandbox: true,
+            ..ExchangeConfig::default()
+        };
+        assert!(GateIo::from_config(&config).is_err());
+        assert!(GateIo::from_config(&ExchangeConfig::default()).is_ok());
+    }
+}
@@ -0,0 +1,115 @@
+use aggregator_core::{Exchange, ExchangeConfig, MarketType, TradingPair};
+use exchange_connectors::hyperliquid::HyperliquidBookParser;
+use exchange_connectors::{Hyperliquid, SymbolMapper};
+
+#[cfg(test)]
+mod hyperliquid_tests {
+    use super::*;
+
+    fn book(time: i64, bids: &str, asks: &str) -> String {
+        format!(
+            r#"{{"channel":"l2Book","data":{{"coin":"BTC","time":{},"levels":[[{}],[{}]]}}}}"#,
+            time, bids, asks
+        )
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        let mut parser = HyperliquidBookParser::new();
+        let message = book(
+            1700000000123,
+            r#"{"px":"36950.0","sz":"1.25","n":3},{"px":"36949.0","sz":"0.5","n":1}"#,
+            r#"{"px":"36951.0","sz":"2.0","n":2}"#,
+        );
+
+        let update = parser.handle_message(&message).unwrap().unwrap();
+        assert_eq!(update.symbol, "BTC");
+        assert_eq!(update.exchange, Exchange::Hyperliquid);
+        assert_eq!(update.market_type, Some(MarketType::Futures));
+        assert_eq!(update.bids.len(), 2);
+        assert_eq!(update.bids[0].price, 36950.0);
+        assert_eq!(update.bids[0].quantity, 1.25);
+        assert_eq!(update.asks[0].quantity, 2.0);
+        assert_eq!(update.event_time.unwrap().timestamp_millis(), 1700000000123);
+    }
+
+    #[test]
+    fn test_removed_levels_and_depth() {
+        let mut parser = HyperliquidBookParser::new().with_depth(2);
+        parser
+            .handle_message(&book(
+                1,
+                r#"{"px":"100.0","sz":"1.0","n":1},{"px":"99.0","sz":"2.0","n":1},{"px":"98.0","sz":"3.0","n":1}"#,
+                r#"{"px":"101.0","sz":"1.0","n":1}"#,
+            ))
+            .unwrap();
+
+        let update = parser
+            .handle_message(&book(
+                2,
+                r#"{"px":"100.0","sz":"1.5","n":1}"#,
+                r#"{"px":"102.0","sz":"1.0","n":1}"#,
+            ))
+            .unwrap()
+            .unwrap();
+        // 98.0 was beyond the depth of the first snapshot, so it is never reported
+        assert_eq!(update.bids.len(), 2);
+        assert_eq!(update.bids[0].quantity, 1.5);
+        assert_eq!(update.bids[1].price, 99.0);
+        assert_eq!(update.bids[1].quantity, 0.0);
+        assert_eq!(update.asks.len(), 2);
+        assert_eq!(update.asks[1].price, 101.0);
+        assert_eq!(update.asks[1].quantity, 0.0);
+    }
+
+    #[test]
+    fn test_control_messages() {
+        let mut parser = HyperliquidBookParser::new();
+        let ack = r#"{"channel":"subscriptionResponse","data":{"method":"subscribe","subscription":{"type":"l2Book","coin":"BTC"}}}"#;
+        assert!(parser.handle_message(ack).unwrap().is_none());
+        assert!(parser
+            .handle_message(r#"{"channel":"pong"}"#)
+            .unwrap()
+            .is_none());
+        assert!(parser
+            .handle_message(r#"{"channel":"error","data":"Invalid subscription"}"#)
+            .is_err());
+        assert!(parser.handle_message("not json").is_err());
+    }
+
+    #[test]
+    fn test_subscription_and_symbols() {
+        assert_eq!(
+            Hyperliquid::subscription("ETH").unwrap(),
+            r#"{"method":"subscribe","subscription":{"type":"l2Book","coin":"ETH"}}"#
+        );
+
+        let mapper = SymbolMapper::new();
+        let pair = TradingPair::new("ETH", "USDC");
+        assert_eq!(mapper.to_exchange(&Exchange::Hyperliquid, &pair), "ETH");
+        assert_eq!(
+            mapper.from_exchange(&Exchange::Hyperliquid, "ETH").unwrap(),
+            pair
+        );
+        assert_eq!(
+            "hyperliquid".parse::<Exchange>().unwrap(),
+            Exchange::Hyperliquid
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mainnet = Hyperliquid::from_config(&ExchangeConfig::default()).unwrap();
+        assert_eq!(mainnet.websocket_url(), "wss://api.hyperliquid.xyz/ws");
+
+        let testnet = Hyperliquid::from_config(&ExchangeConfig {
+            sandbox: true,
+            ..ExchangeConfig::default()
+        })
+        .unwrap();
+        assert_eq!(
+            testnet.websocket_url(),
+            "wss://api.hyperliquid-testnet.xyz/ws"
+        );
+    }
+}
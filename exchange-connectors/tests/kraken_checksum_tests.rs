@@ -0,0 +1,189 @@
+use aggregator_core::{Exchange, LevelMapBook, PairBook, PriceLevelUpdate, TradingPair};
+use exchange_connectors::kraken::KrakenBook;
+use exchange_connectors::{Kraken, OrderBookService};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+mod mock_exchange;
+use mock_exchange::{MockExchangeServer, Step};
+
+#[cfg(test)]
+mod kraken_checksum_tests {
+    use super::*;
+
+    fn levels(prices: &[&str], volume: &str) -> Vec<Vec<String>> {
+        prices
+            .iter()
+            .map(|price| {
+                vec![
+                    price.to_string(),
+                    volume.to_string(),
+                    "1534614248.123678".to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    // Example book from Kraken's checksum documentation
+    fn documented_book() -> KrakenBook {
+        let asks = levels(
+            &[
+                "0.05005", "0.05010", "0.05015", "0.05020", "0.05025", "0.05030", "0.05035",
+                "0.05040", "0.05045", "0.05050",
+            ],
+            "0.00000500",
+        );
+        let bids = levels(
+            &[
+                "0.05000", "0.04995", "0.04990", "0.04980", "0.04975", "0.04970", "0.04965",
+                "0.04960", "0.04955", "0.04950",
+            ],
+            "0.00000500",
+        );
+
+        let mut book = KrakenBook::new(10);
+        book.apply_snapshot(&asks, &bids).unwrap();
+        book
+    }
+
+    #[test]
+    fn test_checksum_matches_documented_example() {
+        let book = documented_book();
+        assert_eq!(book.checksum(), 974947235);
+        assert!(book.verify("974947235"));
+        assert!(!book.verify("123"));
+        assert!(!book.verify("not a number"));
+    }
+
+    #[test]
+    fn test_checksum_ignores_level_order_in_messages() {
+        let mut book = KrakenBook::new(10);
+        let mut asks = levels(&["0.05050", "0.05005"], "0.00000500");
+        let bids = levels(&["0.04950", "0.05000"], "0.00000500");
+        book.apply_snapshot(&asks, &bids).unwrap();
+
+        asks.reverse();
+        let mut reordered = KrakenBook::new(10);
+        reordered.apply_snapshot(&asks, &bids).unwrap();
+
+        assert_eq!(book.checksum(), reordered.checksum());
+    }
+
+    #[test]
+    fn test_updates_change_checksum_and_truncate() {
+        let mut book = documented_book();
+        let original = book.checksum();
+
+        // Insert a better ask: the worst ask falls off the 10 level book
+        let truncated = book
+            .apply_update(&levels(&["0.05001"], "0.00000100"), &[])
+            .unwrap();
+        assert_eq!(truncated, (vec![0.0505], vec![]));
+        assert_ne!(book.checksum(), original);
+
+        // Removing it and restoring the truncated level brings the original book back
+        book.apply_update(
+            &[
+                vec![
+                    "0.05001".to_string(),
+                    "0.00000000".to_string(),
+                    "1".to_string(),
+                ],
+                vec![
+                    "0.05050".to_string(),
+                    "0.00000500".to_string(),
+                    "1".to_string(),
+                ],
+            ],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(book.checksum(), original);
+    }
+
+    #[test]
+    fn test_invalid_levels_are_rejected() {
+        let mut book = KrakenBook::new(10);
+        assert!(book
+            .apply_update(&[vec!["abc".to_string(), "1.0".to_string()]], &[])
+            .is_err());
+        assert!(book.apply_update(&[vec!["1.0".to_string()]], &[]).is_err());
+    }
+
+    fn book_message(data: &str) -> String {
+        format!(r#"[336,{},"book-2","XBT/USD"]"#, data)
+    }
+
+    async fn next_update(rx: &mut mpsc::Receiver<PriceLevelUpdate>) -> PriceLevelUpdate {
+        tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out waiting for an update")
+            .expect("update channel closed")
+    }
+
+    fn prices(levels: &[aggregator_core::PriceLevel]) -> Vec<f64> {
+        levels.iter().map(|level| level.price).collect()
+    }
+
+    #[tokio::test]
+    async fn test_levels_kraken_drops_are_removed_downstream() {
+        let session = vec![
+            Step::Expect("subscribe".to_string()),
+            Step::Send(book_message(
+                r#"{"as":[["101.0","1.0","1"],["102.0","1.0","1"]],"bs":[["100.0","1.0","1"],["99.0","1.0","1"]]}"#,
+            )),
+            // A better ask pushes 102.0 out of the 2 level book without a removal
+            Step::Send(book_message(r#"{"a":[["100.5","1.0","2"]]}"#)),
+            // A wrong checksum makes the connector resubscribe for a fresh snapshot
+            Step::Send(book_message(r#"{"b":[["100.2","1.0","3"]],"c":"1"}"#)),
+            Step::Expect("unsubscribe".to_string()),
+            // The bids at 100.0 and 99.0 went away in the meantime
+            Step::Send(book_message(
+                r#"{"as":[["100.5","1.0","4"],["101.0","1.0","4"]],"bs":[["100.2","1.0","4"],["98.0","1.0","4"]]}"#,
+            )),
+        ];
+        let server = MockExchangeServer::start(vec![session]).await;
+        let mut kraken = Kraken::new();
+        kraken.config.websocket_url = server.url("/");
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let handles = kraken
+            .spawn_order_book_service(
+                &[TradingPair::new("BTC", "USD")],
+                2,
+                16,
+                tx,
+                shutdown_tx.subscribe(),
+            )
+            .await
+            .unwrap();
+
+        let mut downstream = LevelMapBook::new("XBT/USD", 10);
+        let snapshot = next_update(&mut rx).await;
+        downstream.apply_update(&snapshot).await.unwrap();
+
+        let delta = next_update(&mut rx).await;
+        assert!(delta
+            .asks
+            .iter()
+            .any(|ask| ask.price == 102.0 && ask.quantity == 0.0));
+        downstream.apply_update(&delta).await.unwrap();
+
+        // The update failing its checksum is not forwarded
+        let resync = next_update(&mut rx).await;
+        downstream.apply_update(&resync).await.unwrap();
+
+        let book = downstream
+            .exchange_summary(&Exchange::Kraken, 10)
+            .await
+            .unwrap();
+        assert_eq!(prices(&book.bids), vec![100.2, 98.0]);
+        assert_eq!(prices(&book.asks), vec![100.5, 101.0]);
+
+        let _ = shutdown_tx.send(());
+        for handle in handles {
+            handle.abort();
+        }
+    }
+}
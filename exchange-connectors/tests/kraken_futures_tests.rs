@@ -0,0 +1,169 @@
+use aggregator_core::{Exchange, ExchangeConfig, MarketType, TradingPair};
+use exchange_connectors::kraken_futures::KrakenFuturesBookParser;
+use exchange_connectors::{KrakenFutures, SymbolMapper};
+
+#[cfg(test)]
+mod kraken_futures_tests {
+    use super::*;
+
+    const SNAPSHOT: &str = r#"{"feed":"book_snapshot","product_id":"PF_XBTUSD","timestamp":1700000000123,"seq":100,"tickSize":null,"bids":[{"price":36950.0,"qty":1.25},{"price":36949.0,"qty":0.5},{"price":36948.0,"qty":3.0}],"asks":[{"price":36951.0,"qty":2.0}]}"#;
+
+    fn delta(side: &str, seq: u64, price: f64, qty: f64) -> String {
+        format!(
+            r#"{{"feed":"book","product_id":"PF_XBTUSD","side":"{}","seq":{},"price":{},"qty":{},"timestamp":1700000000200}}"#,
+            side, seq, price, qty
+        )
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        let mut parser = KrakenFuturesBookParser::new().with_depth(2);
+        let update = parser.handle_message(SNAPSHOT).unwrap().unwrap();
+
+        assert_eq!(update.symbol, "PF_XBTUSD");
+        assert_eq!(update.exchange, Exchange::KrakenFutures);
+        assert_eq!(update.market_type, Some(MarketType::Futures));
+        assert_eq!(update.bids.len(), 2);
+        assert_eq!(update.bids[0].price, 36950.0);
+        assert_eq!(update.bids[0].quantity, 1.25);
+        assert_eq!(update.asks[0].quantity, 2.0);
+        assert_eq!(update.event_time.unwrap().timestamp_millis(), 1700000000123);
+    }
+
+    #[test]
+    fn test_deltas_follow_snapshot() {
+        let mut parser = KrakenFuturesBookParser::new();
+        // Deltas before the snapshot are dropped
+        assert!(parser
+            .handle_message(&delta("buy", 99, 36950.0, 1.0))
+            .unwrap()
+            .is_none());
+        parser.handle_message(SNAPSHOT).unwrap();
+
+        let bid = parser
+            .handle_message(&delta("buy", 101, 36950.0, 0.0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(bid.bids.len(), 1);
+        assert!(bid.asks.is_empty());
+        assert_eq!(bid.bids[0].quantity, 0.0);
+
+        let ask = parser
+            .handle_message(&delta("sell", 102, 36952.0, 4.0))
+            .unwrap()
+            .unwrap();
+        assert!(ask.bids.is_empty());
+        assert_eq!(ask.asks[0].price, 36952.0);
+
+        // Replayed deltas are stale
+        assert!(parser
+            .handle_message(&delta("sell", 102, 36952.0, 4.0))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_sequence_gap_requests_resync() {
+        let mut parser = KrakenFuturesBookParser::new();
+        parser.handle_message(SNAPSHOT).unwrap();
+
+        assert!(parser
+            .handle_message(&delta("buy", 105, 36950.0, 1.0))
+            .is_err());
+        assert_eq!(parser.take_resyncs(), vec!["PF_XBTUSD".to_string()]);
+        assert!(parser.take_resyncs().is_empty());
+
+        // Nothing is applied until the next snapshot
+        assert!(parser
+            .handle_message(&delta("buy", 106, 36950.0, 1.0))
+            .unwrap()
+            .is_none());
+        parser.handle_message(SNAPSHOT).unwrap();
+        assert!(parser
+            .handle_message(&delta("buy", 101, 36950.0, 1.0))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_ticker_funding() {
+        let mut parser = KrakenFuturesBookParser::new();
+        let ticker = r#"{"feed":"ticker","product_id":"PF_XBTUSD","markPrice":36950.5,"relative_funding_rate":0.0000125,"next_funding_rate_time":1700003600000}"#;
+        assert!(parser.handle_message(ticker).unwrap().is_none());
+
+        let funding = parser.funding("PF_XBTUSD").unwrap();
+        assert_eq!(funding.rate, 0.0000125);
+        assert_eq!(funding.mark_price, Some(36950.5));
+        assert_eq!(
+            funding.next_funding_time.unwrap().timestamp_millis(),
+            1700003600000
+        );
+
+        let update = parser.handle_message(SNAPSHOT).unwrap().unwrap();
+        assert_eq!(update.funding.unwrap().rate, 0.0000125);
+    }
+
+    #[test]
+    fn test_control_messages() {
+        let mut parser = KrakenFuturesBookParser::new();
+        assert!(parser
+            .handle_message(r#"{"event":"info","version":1}"#)
+            .unwrap()
+            .is_none());
+        assert!(parser
+            .handle_message(r#"{"event":"subscribed","feed":"book","product_ids":["PF_XBTUSD"]}"#)
+            .unwrap()
+            .is_none());
+        assert!(parser
+            .handle_message(r#"{"feed":"heartbeat","time":1700000000000}"#)
+            .unwrap()
+            .is_none());
+        assert!(parser
+            .handle_message(r#"{"event":"error","message":"Invalid product id"}"#)
+            .is_err());
+        assert!(parser.handle_message("not json").is_err());
+    }
+
+    #[test]
+    fn test_subscription_and_symbols() {
+        let product_ids = vec!["PF_XBTUSD".to_string()];
+        assert_eq!(
+            KrakenFutures::subscription("subscribe", "book", &product_ids).unwrap(),
+            r#"{"event":"subscribe","feed":"book","product_ids":["PF_XBTUSD"]}"#
+        );
+        assert_eq!(
+            KrakenFutures::subscription("subscribe", "heartbeat", &product_ids).unwrap(),
+            r#"{"event":"subscribe","feed":"heartbeat"}"#
+        );
+
+        let mapper = SymbolMapper::new();
+        let pair = TradingPair::new("BTC", "USD");
+        assert_eq!(
+            mapper.to_exchange(&Exchange::KrakenFutures, &pair),
+            "PF_XBTUSD"
+        );
+        assert_eq!(
+            mapper
+                .from_exchange(&Exchange::KrakenFutures, "PF_XBTUSD")
+                .unwrap(),
+            pair
+        );
+        assert_eq!(
+            "kraken_futures".parse::<Exchange>().unwrap(),
+            Exchange::KrakenFutures
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let production = KrakenFutures::from_config(&ExchangeConfig::default()).unwrap();
+        assert_eq!(production.websocket_url(), "wss://futures.kraken.com/ws/v1");
+
+        let demo = KrakenFutures::from_config(&ExchangeConfig {
+            sandbox: true,
+            ..ExchangeConfig::default()
+        })
+        .unwrap();
+        assert_eq!(demo.websocket_url(), "wss://demo-futures.kraken.com/ws/v1");
+    }
+}
@@ -0,0 +1,89 @@
+use aggregator_core::{Exchange, ExchangeConfig};
+use exchange_connectors::KuCoin;
+use std::time::Duration;
+
+#[cfg(test)]
+mod kucoin_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token_response() {
+        let body = r#"{
+            "code": "200000",
+            "data": {
+                "token": "2neAiuYvAU61ZDXANAGAsiL4",
+                "instanceServers": [{
+                    "endpoint": "wss://ws-api-spot.kucoin.com/",
+                    "encrypt": true,
+                    "protocol": "websocket",
+                    "pingInterval": 18000,
+                    "pingTimeout": 10000
+                }]
+            }
+        }"#;
+
+        let token = KuCoin::parse_token_response(body).unwrap();
+        assert_eq!(token.ping_interval, Duration::from_secs(18));
+        assert_eq!(
+            token.url("abc"),
+            "wss://ws-api-spot.kucoin.com/?token=2neAiuYvAU61ZDXANAGAsiL4&connectId=abc"
+        );
+
+        let rejected = r#"{"code": "429000", "msg": "Too many requests"}"#;
+        assert!(KuCoin::parse_token_response(rejected).is_err());
+    }
+
+    #[test]
+    fn test_parse_depth_update() {
+        let message = r#"{
+            "type": "message",
+            "topic": "/market/level2:BTC-USDT",
+            "subject": "trade.l2update",
+            "data": {
+                "changes": {
+                    "asks": [["18906", "0.00331", "14103845"], ["18907.3", "0", "14103846"]],
+                    "bids": [["0", "0", "14103847"]]
+                },
+                "sequenceEnd": 14103847,
+                "sequenceStart": 14103845,
+                "symbol": "BTC-USDT",
+                "time": 1663747970273
+            }
+        }"#;
+
+        let depth = KuCoin::parse_depth_update(message).unwrap().unwrap();
+        assert_eq!(depth.sequence_start, 14103845);
+        assert_eq!(depth.sequence_end, 14103847);
+        assert_eq!(depth.update.symbol, "BTC-USDT");
+        assert_eq!(depth.update.exchange, Exchange::KuCoin);
+        assert_eq!(depth.update.asks.len(), 2);
+        assert_eq!(depth.update.asks[1].quantity, 0.0);
+        // The zero-price entry only advances the sequence
+        assert!(depth.update.bids.is_empty());
+        assert_eq!(
+            depth.update.event_time.unwrap().timestamp_millis(),
+            1663747970273
+        );
+    }
+
+    #[test]
+    fn test_parse_depth_update_ignores_control_messages() {
+        let welcome = r#"{"id": "hQvf8jkno", "type": "welcome"}"#;
+        assert!(KuCoin::parse_depth_update(welcome).unwrap().is_none());
+
+        let pong = r#"{"id": "1545910590801", "type": "pong"}"#;
+        assert!(KuCoin::parse_depth_update(pong).unwrap().is_none());
+
+        assert!(KuCoin::parse_depth_update("not json").is_err());
+    }
+
+    #[test]
+    fn test_from_config_rejects_sandbox() {
+        let config = ExchangeConfig {
+            sandbox: true,
+            ..ExchangeConfig::default()
+        };
+        assert!(KuCoin::from_config(&config).is_err());
+        assert!(KuCoin::from_config(&ExchangeConfig::default()).is_ok());
+    }
+}
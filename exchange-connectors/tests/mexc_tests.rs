@@ -0,0 +1,115 @@
+use aggregator_core::{Exchange, ExchangeConfig, TradingPair};
+use exchange_connectors::mexc::MexcBookParser;
+use exchange_connectors::{Mexc, SymbolMapper};
+
+#[cfg(test)]
+mod mexc_tests {
+    use super::*;
+
+    fn book(time: i64, bids: &str, asks: &str) -> String {
+        format!(
+            r#"{{"c":"spot@public.limit.depth.v3.api@BTCUSDT@20","d":{{"bids":[{}],"asks":[{}],"e":"spot@public.limit.depth.v3.api","r":"3407459756"}},"s":"BTCUSDT","t":{}}}"#,
+            bids, asks, time
+        )
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        let mut parser = MexcBookParser::new();
+        let message = book(
+            1700000000123,
+            r#"{"p":"36950.0","v":"1.25"},{"p":"36949.0","v":"0.5"}"#,
+            r#"{"p":"36951.0","v":"2.0"}"#,
+        );
+
+        let update = parser.handle_message(&message).unwrap().unwrap();
+        assert_eq!(update.symbol, "BTCUSDT");
+        assert_eq!(update.exchange, Exchange::Mexc);
+        assert_eq!(update.bids.len(), 2);
+        assert_eq!(update.bids[0].price, 36950.0);
+        assert_eq!(update.bids[0].quantity, 1.25);
+        assert_eq!(update.asks[0].quantity, 2.0);
+        assert_eq!(update.event_time.unwrap().timestamp_millis(), 1700000000123);
+    }
+
+    #[test]
+    fn test_removed_levels_and_depth() {
+        let mut parser = MexcBookParser::new().with_depth(2);
+        parser
+            .handle_message(&book(
+                1,
+                r#"{"p":"100.0","v":"1.0"},{"p":"99.0","v":"2.0"},{"p":"98.0","v":"3.0"}"#,
+                r#"{"p":"101.0","v":"1.0"}"#,
+            ))
+            .unwrap();
+
+        let update = parser
+            .handle_message(&book(
+                2,
+                r#"{"p":"100.0","v":"1.5"}"#,
+                r#"{"p":"102.0","v":"1.0"}"#,
+            ))
+            .unwrap()
+            .unwrap();
+
+        // 99.0 left the book; 98.0 was never kept past the depth limit
+        assert_eq!(update.bids.len(), 2);
+        assert_eq!(update.bids[1].price, 99.0);
+        assert_eq!(update.bids[1].quantity, 0.0);
+        assert_eq!(update.asks.len(), 2);
+        assert_eq!(update.asks[1].price, 101.0);
+        assert_eq!(update.asks[1].quantity, 0.0);
+    }
+
+    #[test]
+    fn test_control_messages() {
+        let mut parser = MexcBookParser::new();
+        let ack = r#"{"id":0,"code":0,"msg":"spot@public.limit.depth.v3.api@BTCUSDT@20"}"#;
+        assert!(parser.handle_message(ack).unwrap().is_none());
+        assert!(parser
+            .handle_message(r#"{"id":0,"code":0,"msg":"PONG"}"#)
+            .unwrap()
+            .is_none());
+        assert!(parser
+            .handle_message(r#"{"id":0,"code":1,"msg":"Not Subscribed successfully!"}"#)
+            .is_err());
+        assert!(parser.handle_message("not json").is_err());
+    }
+
+    #[test]
+    fn test_subscription_and_symbols() {
+        assert_eq!(
+            Mexc::depth_stream("BTCUSDT", 5),
+            "spot@public.limit.depth.v3.api@BTCUSDT@5"
+        );
+        assert_eq!(
+            Mexc::depth_stream("BTCUSDT", 50),
+            "spot@public.limit.depth.v3.api@BTCUSDT@20"
+        );
+        assert_eq!(
+            Mexc::subscription(&[Mexc::depth_stream("BTCUSDT", 10)]).unwrap(),
+            r#"{"method":"SUBSCRIPTION","params":["spot@public.limit.depth.v3.api@BTCUSDT@10"]}"#
+        );
+
+        let mapper = SymbolMapper::new();
+        let pair = TradingPair::new("ETH", "USDT");
+        assert_eq!(mapper.to_exchange(&Exchange::Mexc, &pair), "ETHUSDT");
+        assert_eq!(
+            mapper.from_exchange(&Exchange::Mexc, "ETHUSDT").unwrap(),
+            pair
+        );
+        assert_eq!("mexc".parse::<Exchange>().unwrap(), Exchange::Mexc);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let production = Mexc::from_config(&ExchangeConfig::default()).unwrap();
+        assert_eq!(production.websocket_url(), "wss://wbs.mexc.com/ws");
+
+        assert!(Mexc::from_config(&ExchangeConfig {
+            sandbox: true,
+            ..ExchangeConfig::default()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Mock exchange WebSocket server for connector integration tests
+//!
+//! Each accepted connection plays the next scripted session: canned frames are sent in order,
+//! optionally after waiting for the client's subscription, and the session can end with a clean
+//! close or an abrupt drop to exercise reconnection. Connections beyond the script are held open
+//! without sending anything. Every text frame a client sends is recorded per connection.
+
+#![allow(dead_code)]
+
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// How long an `Expect` step waits for the client before the session is abandoned.
+const EXPECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One step of a scripted session.
+#[derive(Debug, Clone)]
+pub enum Step {
+    /// Sends a text frame.
+    Send(String),
+    /// Waits for a client text frame containing the pattern, e.g. a subscription topic.
+    Expect(String),
+    /// Pauses before the next step.
+    Delay(Duration),
+    /// Sends a close frame and ends the session.
+    Close,
+    /// Drops the TCP connection without a close handshake and ends the session.
+    Drop,
+}
+
+impl Step {
+    /// Sends each of `messages` in order.
+    pub fn replay<I, S>(messages: I) -> Vec<Step>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        messages
+            .into_iter()
+            .map(|message| Step::Send(message.into()))
+            .collect()
+    }
+}
+
+type Received = Arc<Mutex<Vec<Vec<String>>>>;
+
+pub struct MockExchangeServer {
+    addr: SocketAddr,
+    connections: Arc<AtomicUsize>,
+    received: Received,
+    handle: JoinHandle<()>,
+}
+
+impl MockExchangeServer {
+    /// Starts a server on a free local port that plays `sessions[n]` to the `n`th connection.
+    pub async fn start(sessions: Vec<Vec<Step>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let received: Received = Arc::default();
+
+        let handle = {
+            let connections = connections.clone();
+            let received = received.clone();
+            tokio::spawn(async move {
+                let mut sessions = sessions.into_iter();
+                while let Ok((stream, _)) = listener.accept().await {
+                    let index = connections.fetch_add(1, Ordering::SeqCst);
+                    received.lock().unwrap().push(Vec::new());
+                    let steps = sessions.next().unwrap_or_default();
+                    tokio::spawn(Self::serve(stream, steps, index, received.clone()));
+                }
+            })
+        };
+
+        Self {
+            addr,
+            connections,
+            received,
+            handle,
+        }
+    }
+
+    /// The server's URL with `path` appended, e.g. `url("/ws")`.
+    pub fn url(&self, path: &str) -> String {
+        format!("ws://{}{}", self.addr, path)
+    }
+
+    /// Number of connections accepted so far.
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// Text frames received on the `connection`th connection.
+    pub fn received(&self, connection: usize) -> Vec<String> {
+        self.received
+            .lock()
+            .unwrap()
+            .get(connection)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Waits until at least `count` connections were accepted, panicking after `timeout`.
+    pub async fn wait_for_connections(&self, count: usize, timeout: Duration) {
+        tokio::time::timeout(timeout, async {
+            while self.connections() < count {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("expected {} connections, got {}", count, self.connections()));
+    }
+
+    async fn serve(stream: TcpStream, steps: Vec<Step>, index: usize, received: Received) {
+        let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+            return;
+        };
+
+        for step in steps {
+            match step {
+                Step::Send(text) => {
+                    if ws.send(Message::Text(text)).await.is_err() {
+                        return;
+                    }
+                }
+                Step::Expect(pattern) => {
+                    let found = tokio::time::timeout(
+                        EXPECT_TIMEOUT,
+                        Self::read_until(&mut ws, Some(&pattern), index, &received),
+                    )
+                    .await;
+                    if !matches!(found, Ok(true)) {
+                        return;
+                    }
+                }
+                Step::Delay(delay) => tokio::time::sleep(delay).await,
+                Step::Close => {
+                    let _ = ws.close(None).await;
+                    return;
+                }
+                Step::Drop => return,
+            }
+        }
+
+        // Keep the connection open, answering pings, until the client leaves
+        Self::read_until(&mut ws, None, index, &received).await;
+    }
+
+    /// Reads frames, recording text and answering pings, until a text frame contains `pattern`
+    /// (returning `true`) or the client disconnects.
+    async fn read_until(
+        ws: &mut WebSocketStream<TcpStream>,
+        pattern: Option<&str>,
+        index: usize,
+        received: &Received,
+    ) -> bool {
+        while let Some(Ok(message)) = ws.next().await {
+            match message {
+                Message::Text(text) => {
+                    let matched = pattern.is_some_and(|pattern| text.contains(pattern));
+                    received.lock().unwrap()[index].push(text);
+                    if matched {
+                        return true;
+                    }
+                }
+                Message::Ping(payload) => {
+                    let _ = ws.send(Message::Pong(payload)).await;
+                }
+                Message::Close(_) => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+}
+
+impl Drop for MockExchangeServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
@@ -49,12 +49,24 @@ async fn test_multiple_exchange_concurrent_creation() {
     let start = Instant::now();
     
     let handles = vec![
-        tokio::spawn(async { Binance::new() }),
-        tokio::spawn(async { Bybit::new() }),
-        tokio::spawn(async { Kraken::new() }),
-        tokio::spawn(async { Binance::default() }),
-        tokio::spawn(async { Bybit::default() }),
-        tokio::spawn(async { Kraken::default() }),
+        tokio::spawn(async {
+            let _ = Binance::new();
+        }),
+        tokio::spawn(async {
+            let _ = Bybit::new();
+        }),
+        tokio::spawn(async {
+            let _ = Kraken::new();
+        }),
+        tokio::spawn(async {
+            let _ = Binance::default();
+        }),
+        tokio::spawn(async {
+            let _ = Bybit::default();
+        }),
+        tokio::spawn(async {
+            let _ = Kraken::default();
+        }),
     ];
     
     for handle in handles {
@@ -1,6 +1,6 @@
-use aggregator_core::PriceLevelUpdate;
+use aggregator_core::{PriceLevelUpdate, TradingPair};
 use exchange_connectors::{Bitstamp, Coinbase, OrderBookService};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 
 #[cfg(test)]
 mod bitstamp_tests {
@@ -30,9 +30,16 @@ mod bitstamp_tests {
     async fn test_bitstamp_placeholder_service() {
         let bitstamp = Bitstamp::new();
         let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+        let (shutdown_tx, _) = broadcast::channel(1);
 
         let result = bitstamp
-            .spawn_order_book_service(["BTC", "USD"], 100, 1000, tx)
+            .spawn_order_book_service(
+                &[TradingPair::new("BTC", "USD")],
+                100,
+                1000,
+                tx,
+                shutdown_tx.subscribe(),
+            )
             .await;
 
         // Should return Ok with empty vector (placeholder implementation)
@@ -70,9 +77,16 @@ mod coinbase_tests {
     async fn test_coinbase_placeholder_service() {
         let coinbase = Coinbase::new();
         let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+        let (shutdown_tx, _) = broadcast::channel(1);
 
         let result = coinbase
-            .spawn_order_book_service(["BTC", "USD"], 100, 1000, tx)
+            .spawn_order_book_service(
+                &[TradingPair::new("BTC", "USD")],
+                100,
+                1000,
+                tx,
+                shutdown_tx.subscribe(),
+            )
             .await;
 
         // Should return Ok with empty vector (placeholder implementation)
@@ -0,0 +1,91 @@
+use aggregator_core::{AggregatorError, RateLimitConfig, SimulatedClock};
+use exchange_connectors::RateLimiter;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(test)]
+mod rate_limiter_tests {
+    use super::*;
+
+    fn config(requests_per_second: u32, burst_size: u32) -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_second,
+            burst_size,
+        }
+    }
+
+    #[test]
+    fn test_burst_then_exhausted() {
+        let limiter = RateLimiter::new("binance", &config(1, 3));
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire().is_ok());
+        }
+
+        match limiter.try_acquire() {
+            Err(AggregatorError::RateLimit { resource, .. }) => assert_eq!(resource, "binance"),
+            other => panic!("expected rate limit error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_clones_share_bucket() {
+        let limiter = RateLimiter::new("bybit", &config(1, 2));
+        let clone = limiter.clone();
+
+        assert!(limiter.try_acquire().is_ok());
+        assert!(clone.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+        assert_eq!(clone.available(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_tokens_refill_over_time() {
+        let limiter = RateLimiter::new("binance", &config(100, 1));
+
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+
+        // One token refills every 10ms
+        assert!(limiter.acquire(Duration::from_millis(100)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_gives_up_beyond_max_wait() {
+        let limiter = RateLimiter::new("binance", &config(1, 1));
+
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.acquire(Duration::from_millis(10)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refills_by_simulated_time() {
+        let clock = SimulatedClock::new(chrono::Utc::now());
+        let limiter =
+            RateLimiter::new("binance", &config(1, 2)).with_clock(Arc::new(clock.clone()));
+
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(limiter.available(), 0);
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(limiter.available(), 1);
+        // Refills stop at the burst size
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(limiter.available(), 2);
+
+        // Waiting for a token waits for the simulated clock
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        let waiting = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire(Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+        clock.advance(Duration::from_secs(1));
+        assert!(waiting.await.unwrap().is_ok());
+    }
+}
@@ -0,0 +1,174 @@
+use aggregator_core::{Exchange, HealthEventKind, WebSocketConfig};
+use exchange_connectors::{HealthReporter, ReconnectPolicy, Shutdown};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+#[cfg(test)]
+mod reconnect_policy_tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_from_config() {
+        let config = WebSocketConfig {
+            reconnect_interval: 250,
+            ping_interval: 30000,
+            max_reconnect_attempts: 3,
+            buffer_size: 1000,
+            stale_timeout: 20000,
+        };
+        let policy = ReconnectPolicy::from_config(&config);
+
+        assert_eq!(policy.initial_delay, Duration::from_millis(250));
+        assert_eq!(policy.max_attempts, 3);
+        assert_eq!(policy.stale_timeout, Duration::from_millis(20000));
+    }
+
+    #[test]
+    fn test_exponential_backoff_is_capped() {
+        let policy = ReconnectPolicy::default()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(1000))
+            .with_multiplier(2.0);
+
+        assert_eq!(policy.base_delay(1), Duration::from_millis(100));
+        assert_eq!(policy.base_delay(2), Duration::from_millis(200));
+        assert_eq!(policy.base_delay(4), Duration::from_millis(800));
+        assert_eq!(policy.base_delay(5), Duration::from_millis(1000));
+        assert_eq!(policy.base_delay(50), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let policy = ReconnectPolicy::default()
+            .with_initial_delay(Duration::from_millis(1000))
+            .with_jitter(0.25);
+
+        for _ in 0..100 {
+            let delay = policy.delay(1);
+            assert!(delay >= Duration::from_millis(750));
+            assert!(delay <= Duration::from_millis(1250));
+        }
+
+        let no_jitter = policy.with_jitter(0.0);
+        assert_eq!(no_jitter.delay(1), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_backoff_attempt_limit_and_reset() {
+        let policy = ReconnectPolicy::default()
+            .with_initial_delay(Duration::from_millis(10))
+            .with_jitter(0.0)
+            .with_max_attempts(2);
+        let mut backoff = policy.backoff();
+
+        assert_eq!(backoff.next_delay(), Some(Duration::from_millis(10)));
+        assert_eq!(backoff.next_delay(), Some(Duration::from_millis(20)));
+        assert_eq!(backoff.next_delay(), None);
+
+        backoff.reset();
+        assert_eq!(backoff.attempt(), 0);
+        assert_eq!(backoff.next_delay(), Some(Duration::from_millis(10)));
+
+        // Zero attempts means retry forever
+        let mut unlimited = policy.with_max_attempts(0).backoff();
+        for _ in 0..100 {
+            assert!(unlimited.next_delay().is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_events_on_reconnect() {
+        let (health_tx, mut health_rx) = broadcast::channel(16);
+        let reporter = HealthReporter::new(Exchange::Kraken, Some(health_tx));
+        let policy = ReconnectPolicy::default()
+            .with_initial_delay(Duration::from_millis(1))
+            .with_jitter(0.0)
+            .with_max_attempts(1);
+        let mut backoff = policy.backoff();
+
+        reporter.connected();
+        reporter.disconnected("closed");
+        assert!(reporter.wait_to_reconnect(&mut backoff).await.is_ok());
+        assert!(reporter.wait_to_reconnect(&mut backoff).await.is_err());
+
+        let kinds: Vec<HealthEventKind> = std::iter::from_fn(|| health_rx.try_recv().ok())
+            .map(|event| {
+                assert_eq!(event.exchange, Exchange::Kraken);
+                event.kind
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                HealthEventKind::Connected,
+                HealthEventKind::Disconnected {
+                    reason: "closed".to_string()
+                },
+                HealthEventKind::Reconnecting {
+                    attempt: 1,
+                    delay_ms: 1
+                },
+                HealthEventKind::ReconnectFailed { attempts: 1 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_detects_stall() {
+        let policy = ReconnectPolicy::default().with_stale_timeout(Duration::from_millis(20));
+        let mut watchdog = policy.watchdog();
+        assert!(!watchdog.is_stale());
+
+        let silent_for = watchdog.stalled().await;
+        assert!(silent_for >= Duration::from_millis(20));
+        assert!(watchdog.is_stale());
+
+        watchdog.touch();
+        assert!(!watchdog.is_stale());
+
+        // A zero timeout disables the check
+        let disabled = policy.with_stale_timeout(Duration::ZERO).watchdog();
+        let stalled = tokio::time::timeout(Duration::from_millis(30), disabled.stalled()).await;
+        assert!(stalled.is_err());
+        assert!(!disabled.is_stale());
+    }
+
+    #[test]
+    fn test_stall_reports_unhealthy_status() {
+        let (health_tx, mut health_rx) = broadcast::channel(16);
+        let reporter = HealthReporter::new(Exchange::Binance, Some(health_tx));
+
+        let error = reporter.stalled(Duration::from_millis(1500));
+        assert!(error.to_string().contains("1500ms"));
+
+        let event = health_rx.try_recv().unwrap();
+        assert_eq!(event.kind, HealthEventKind::Stale { silent_ms: 1500 });
+
+        let status = event.health_status();
+        assert_eq!(status.exchange, Exchange::Binance);
+        assert!(!status.is_healthy);
+        assert!(status.error_message.is_some());
+
+        reporter.connected();
+        assert!(health_rx.try_recv().unwrap().health_status().is_healthy);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_signal() {
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let mut shutdown = Shutdown::new(shutdown_rx);
+        assert!(!shutdown.is_triggered());
+
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), shutdown.recv())
+            .await
+            .unwrap();
+        assert!(shutdown.is_triggered());
+
+        // Dropping the sender also counts as shutdown
+        let (shutdown_tx, shutdown_rx) = broadcast::channel::<()>(1);
+        let mut shutdown = Shutdown::new(shutdown_rx);
+        drop(shutdown_tx);
+        assert!(shutdown.is_triggered());
+    }
+}
@@ -0,0 +1,155 @@
+use aggregator_core::{Exchange, ExchangeConfig, OrderBookConfig, TradingPair};
+use exchange_connectors::{OrderBookService, RestPollingConnector};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[cfg(test)]
+mod rest_polling_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snapshot() {
+        let connector = RestPollingConnector::for_exchange(Exchange::Binance).unwrap();
+        let body = r#"{
+            "lastUpdateId": 1027024,
+            "bids": [["4.00000000", "431.00000000"], ["3.90000000", "12.00000000"]],
+            "asks": [["4.00000200", "12.00000000"]]
+        }"#;
+
+        let update = connector.parse_snapshot("BNBBTC", body, 1).unwrap();
+        assert_eq!(update.symbol, "BNBBTC");
+        assert_eq!(update.exchange, Exchange::Binance);
+        assert_eq!(update.bids.len(), 1);
+        assert_eq!(update.bids[0].price, 4.0);
+        assert_eq!(update.asks[0].quantity, 12.0);
+        assert!(update.event_time.is_none());
+
+        assert_eq!(
+            connector.snapshot_url("BNBBTC", 20),
+            "https://api.binance.com/api/v3/depth?symbol=BNBBTC&limit=20"
+        );
+    }
+
+    #[test]
+    fn test_parse_nested_snapshot() {
+        let connector = RestPollingConnector::for_exchange(Exchange::OKX).unwrap();
+        let body = r#"{
+            "code": "0",
+            "data": [{
+                "asks": [["41006.8", "0.60038921", "0", "1"]],
+                "bids": [["41006.3", "0.30178218", "0", "2"]],
+                "ts": "1629966436396"
+            }]
+        }"#;
+
+        let update = connector.parse_snapshot("BTC-USDT", body, 20).unwrap();
+        assert_eq!(update.bids[0].price, 41006.3);
+        assert_eq!(update.asks[0].quantity, 0.60038921);
+        assert_eq!(update.event_time.unwrap().timestamp_millis(), 1629966436396);
+
+        assert!(connector
+            .parse_snapshot("BTC-USDT", r#"{"data": []}"#, 20)
+            .is_err());
+    }
+
+    #[test]
+    fn test_mark_removed_levels() {
+        let connector = RestPollingConnector::new(Exchange::Binance, "http://localhost");
+        let previous = connector
+            .parse_snapshot(
+                "BTCUSDT",
+                r#"{"bids": [[100, 1], [99, 2]], "asks": [[101, 1]]}"#,
+                20,
+            )
+            .unwrap();
+        let mut current = connector
+            .parse_snapshot("BTCUSDT", r#"{"bids": [[100, 3]], "asks": [[102, 1]]}"#, 20)
+            .unwrap();
+
+        RestPollingConnector::mark_removed_levels(&previous, &mut current);
+        assert_eq!(current.bids.len(), 2);
+        assert_eq!(current.bids[1].price, 99.0);
+        assert_eq!(current.bids[1].quantity, 0.0);
+        assert_eq!(current.asks.len(), 2);
+        assert_eq!(current.asks[1].price, 101.0);
+        assert_eq!(current.asks[1].quantity, 0.0);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let order_book = OrderBookConfig::default();
+        let sandbox = ExchangeConfig {
+            sandbox: true,
+            ..ExchangeConfig::default()
+        };
+        assert!(RestPollingConnector::from_config(Exchange::Bybit, &sandbox, &order_book).is_err());
+        assert!(RestPollingConnector::from_config(
+            Exchange::Kraken,
+            &ExchangeConfig::default(),
+            &order_book
+        )
+        .is_err());
+        assert!(RestPollingConnector::from_config(
+            Exchange::Bybit,
+            &ExchangeConfig::default(),
+            &order_book
+        )
+        .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_polls_snapshots() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/depth"))
+            .and(query_param("symbol", "BTCUSDT"))
+            .and(query_param("limit", "5"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"bids": [["100.0", "1.5"]], "asks": [["101.0", "2.0"]]}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let connector = RestPollingConnector::new(
+            Exchange::Binance,
+            &format!("{}/depth?symbol={{symbol}}&limit={{depth}}", server.uri()),
+        )
+        .with_update_interval(Duration::from_millis(10));
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let handles = connector
+            .spawn_order_book_service(
+                &[TradingPair::new("BTC", "USDT")],
+                5,
+                16,
+                tx,
+                shutdown_tx.subscribe(),
+            )
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let update = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(update.symbol, "BTCUSDT");
+            assert_eq!(update.bids[0].quantity, 1.5);
+            assert_eq!(update.asks[0].price, 101.0);
+        }
+
+        drop(rx);
+        for handle in handles {
+            let result = tokio::time::timeout(Duration::from_secs(5), handle)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(result.is_ok());
+        }
+    }
+}
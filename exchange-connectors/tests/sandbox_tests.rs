@@ -0,0 +1,70 @@
+use aggregator_core::ExchangeConfig;
+use exchange_connectors::{Binance, Bitstamp, Bybit, Coinbase, Kraken};
+
+#[cfg(test)]
+mod sandbox_tests {
+    use super::*;
+
+    fn sandbox_config() -> ExchangeConfig {
+        ExchangeConfig {
+            sandbox: true,
+            ..ExchangeConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_binance_sandbox_uses_testnet() {
+        let streams = vec!["btcusdt@depth".to_string()];
+
+        let production = Binance::from_config(&ExchangeConfig::default()).unwrap();
+        assert!(production
+            .combined_stream_endpoint(&streams)
+            .starts_with("wss://stream.binance.com"));
+
+        let sandbox = Binance::from_config(&sandbox_config()).unwrap();
+        assert_eq!(
+            sandbox.combined_stream_endpoint(&streams),
+            "wss://testnet.binance.vision/stream?streams=btcusdt@depth"
+        );
+    }
+
+    #[test]
+    fn test_bybit_sandbox_uses_testnet() {
+        let production = Bybit::from_config(&ExchangeConfig::default()).unwrap();
+        assert!(!production.config.websocket_url.contains("testnet"));
+
+        let sandbox = Bybit::from_config(&sandbox_config()).unwrap();
+        assert_eq!(
+            sandbox.config.websocket_url,
+            "wss://stream-testnet.bybit.com/v5/public/linear"
+        );
+        assert!(sandbox
+            .config
+            .rest_url
+            .starts_with("https://api-testnet.bybit.com"));
+    }
+
+    #[test]
+    fn test_coinbase_sandbox_uses_sandbox_feed() {
+        let production = Coinbase::from_config(&ExchangeConfig::default()).unwrap();
+        assert_eq!(
+            production.websocket_url(),
+            "wss://ws-feed.exchange.coinbase.com"
+        );
+
+        let sandbox = Coinbase::from_config(&sandbox_config()).unwrap();
+        assert_eq!(
+            sandbox.websocket_url(),
+            "wss://ws-feed-public.sandbox.exchange.coinbase.com"
+        );
+    }
+
+    #[test]
+    fn test_sandbox_rejected_without_testnet() {
+        assert!(Kraken::from_config(&ExchangeConfig::default()).is_ok());
+        assert!(Bitstamp::from_config(&ExchangeConfig::default()).is_ok());
+
+        assert!(Kraken::from_config(&sandbox_config()).is_err());
+        assert!(Bitstamp::from_config(&sandbox_config()).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+use aggregator_core::{Ask, Bid, Exchange, LevelMapBook, PairBook, PriceLevelUpdate};
+use chrono::Utc;
+use exchange_connectors::{ForwardedBook, SequencedUpdate, SnapshotSync, SyncStep};
+use uuid::Uuid;
+
+#[cfg(test)]
+mod snapshot_sync_tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Diff {
+        first: u64,
+        last: u64,
+        previous: Option<u64>,
+    }
+
+    impl SequencedUpdate for Diff {
+        fn first_update_id(&self) -> u64 {
+            self.first
+        }
+
+        fn final_update_id(&self) -> u64 {
+            self.last
+        }
+
+        fn previous_final_update_id(&self) -> Option<u64> {
+            self.previous
+        }
+    }
+
+    fn diff(first: u64, last: u64) -> Diff {
+        Diff {
+            first,
+            last,
+            previous: None,
+        }
+    }
+
+    fn ids(diffs: &[Diff]) -> Vec<(u64, u64)> {
+        diffs.iter().map(|diff| (diff.first, diff.last)).collect()
+    }
+
+    #[test]
+    fn test_buffers_until_snapshot() {
+        let mut sync = SnapshotSync::new();
+        assert!(!sync.is_synced());
+        assert_eq!(sync.push(diff(90, 100)), SyncStep::Buffered);
+        assert_eq!(sync.push(diff(101, 110)), SyncStep::Buffered);
+        assert_eq!(sync.push(diff(111, 120)), SyncStep::Buffered);
+        assert_eq!(sync.buffered(), 3);
+
+        // The snapshot covers the first diff and part of the second
+        let buffered = sync.apply_snapshot(105).unwrap();
+        assert_eq!(ids(&buffered), vec![(101, 110), (111, 120)]);
+        assert!(sync.is_synced());
+        assert_eq!(sync.last_update_id(), Some(120));
+        assert_eq!(sync.buffered(), 0);
+
+        assert_eq!(sync.push(diff(121, 125)), SyncStep::Apply(diff(121, 125)));
+        assert_eq!(sync.push(diff(122, 124)), SyncStep::Stale);
+    }
+
+    #[test]
+    fn test_snapshot_older_than_buffer_is_rejected() {
+        let mut sync = SnapshotSync::new();
+        sync.push(diff(101, 110));
+        sync.push(diff(111, 120));
+
+        // Updates 96..100 are neither in the snapshot nor buffered
+        assert!(sync.apply_snapshot(95).is_none());
+        assert!(!sync.is_synced());
+        assert_eq!(sync.buffered(), 2);
+
+        let buffered = sync.apply_snapshot(112).unwrap();
+        assert_eq!(ids(&buffered), vec![(111, 120)]);
+    }
+
+    #[test]
+    fn test_snapshot_newer_than_buffer() {
+        let mut sync: SnapshotSync<Diff> = SnapshotSync::new();
+        sync.push(diff(101, 110));
+
+        assert!(sync.apply_snapshot(150).unwrap().is_empty());
+        assert_eq!(sync.last_update_id(), Some(150));
+        assert_eq!(sync.push(diff(141, 149)), SyncStep::Stale);
+        assert_eq!(sync.push(diff(151, 160)), SyncStep::Apply(diff(151, 160)));
+    }
+
+    #[test]
+    fn test_gap_triggers_resync() {
+        let mut sync = SnapshotSync::new();
+        sync.apply_snapshot(100).unwrap();
+        assert!(matches!(sync.push(diff(101, 110)), SyncStep::Apply(_)));
+
+        assert_eq!(sync.push(diff(115, 120)), SyncStep::Resync);
+        assert!(!sync.is_synced());
+        assert_eq!(sync.buffered(), 1);
+        assert_eq!(sync.push(diff(121, 130)), SyncStep::Buffered);
+
+        let buffered = sync.apply_snapshot(117).unwrap();
+        assert_eq!(ids(&buffered), vec![(115, 120), (121, 130)]);
+    }
+
+    #[test]
+    fn test_gap_within_buffer() {
+        let mut sync = SnapshotSync::new();
+        sync.push(diff(101, 110));
+        sync.push(diff(120, 130));
+        sync.push(diff(131, 140));
+
+        // The snapshot bridges the first diff, but the second one does not follow it
+        assert!(sync.apply_snapshot(105).is_none());
+        assert_eq!(sync.buffered(), 2);
+
+        let buffered = sync.apply_snapshot(125).unwrap();
+        assert_eq!(ids(&buffered), vec![(120, 130), (131, 140)]);
+    }
+
+    #[test]
+    fn test_chained_updates() {
+        // Futures diffs overlap and chain through the previous final id instead
+        let chained = |first, last, previous| Diff {
+            first,
+            last,
+            previous: Some(previous),
+        };
+        let mut sync = SnapshotSync::new();
+        sync.push(chained(95, 105, 94));
+        let buffered = sync.apply_snapshot(100).unwrap();
+        assert_eq!(ids(&buffered), vec![(95, 105)]);
+
+        assert!(matches!(
+            sync.push(chained(108, 112, 105)),
+            SyncStep::Apply(_)
+        ));
+        assert_eq!(sync.push(chained(118, 120, 115)), SyncStep::Resync);
+    }
+
+    #[test]
+    fn test_buffer_limit_and_reset() {
+        let mut sync = SnapshotSync::new().with_max_buffered(2);
+        sync.push(diff(1, 10));
+        sync.push(diff(11, 20));
+        sync.push(diff(21, 30));
+        assert_eq!(sync.buffered(), 2);
+
+        // The oldest diff was dropped, so a snapshot from before it no longer bridges
+        assert!(sync.apply_snapshot(5).is_none());
+        assert_eq!(
+            ids(&sync.apply_snapshot(15).unwrap()),
+            vec![(11, 20), (21, 30)]
+        );
+
+        sync.reset();
+        assert!(!sync.is_synced());
+        assert_eq!(sync.push(diff(31, 40)), SyncStep::Buffered);
+    }
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> PriceLevelUpdate {
+        let timestamp = Utc::now();
+        PriceLevelUpdate {
+            id: Uuid::new_v4(),
+            symbol: "BTCUSDT".to_string(),
+            exchange: Exchange::Binance,
+            bids: bids
+                .iter()
+                .map(|&(price, quantity)| Bid {
+                    price,
+                    quantity,
+                    exchange: Exchange::Binance,
+                    timestamp,
+                })
+                .collect(),
+            asks: asks
+                .iter()
+                .map(|&(price, quantity)| Ask {
+                    price,
+                    quantity,
+                    exchange: Exchange::Binance,
+                    timestamp,
+                })
+                .collect(),
+            timestamp,
+            funding: None,
+            event_time: None,
+            market_type: None,
+        }
+    }
+
+    fn removed(update: &PriceLevelUpdate) -> (Vec<f64>, Vec<f64>) {
+        (
+            update
+                .bids
+                .iter()
+                .filter(|bid| bid.quantity == 0.0)
+                .map(|bid| bid.price)
+                .collect(),
+            update
+                .asks
+                .iter()
+                .filter(|ask| ask.quantity == 0.0)
+                .map(|ask| ask.price)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_forwarded_book_removes_levels_missing_from_snapshot() {
+        let mut forwarded = ForwardedBook::new();
+        let mut first = book(&[(100.0, 1.0), (99.0, 1.0)], &[(101.0, 1.0)]);
+        forwarded.replace(&mut first);
+        assert_eq!(removed(&first), (vec![], vec![]));
+
+        // Diffs add an ask and remove a bid
+        forwarded.record(&book(&[(99.0, 0.0)], &[(102.0, 2.0)]));
+        assert_eq!(forwarded.len(), (1, 2));
+
+        let mut second = book(&[(100.5, 1.0)], &[(101.0, 3.0)]);
+        forwarded.replace(&mut second);
+        assert_eq!(removed(&second), (vec![100.0], vec![102.0]));
+        assert_eq!(forwarded.len(), (1, 1));
+        assert!(!forwarded.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_level_deleted_during_a_gap_is_removed_downstream() {
+        let mut sync = SnapshotSync::new();
+        let mut forwarded = ForwardedBook::new();
+        let mut downstream = LevelMapBook::new("BTCUSDT", 100);
+
+        assert_eq!(sync.apply_snapshot(10), Some(vec![]));
+        let mut snapshot = book(&[(100.0, 1.0), (99.0, 1.0)], &[(101.0, 1.0)]);
+        forwarded.replace(&mut snapshot);
+        downstream.apply_update(&snapshot).await.unwrap();
+
+        // The diffs deleting 99.0 and 101.0 are lost, so the next one leaves a gap
+        assert_eq!(sync.push(diff(15, 20)), SyncStep::Resync);
+        assert_eq!(ids(&sync.apply_snapshot(20).unwrap()), vec![]);
+        let mut snapshot = book(&[(100.0, 2.0)], &[(101.5, 1.0)]);
+        forwarded.replace(&mut snapshot);
+        downstream.apply_update(&snapshot).await.unwrap();
+
+        let summary = downstream
+            .exchange_summary(&Exchange::Binance, 10)
+            .await
+            .unwrap();
+        let bids: Vec<(f64, f64)> = summary
+            .bids
+            .iter()
+            .map(|level| (level.price, level.quantity))
+            .collect();
+        let asks: Vec<f64> = summary.asks.iter().map(|level| level.price).collect();
+        assert_eq!(bids, vec![(100.0, 2.0)]);
+        assert_eq!(asks, vec![101.5]);
+    }
+}
@@ -0,0 +1,115 @@
+use aggregator_core::{Exchange, TradingPair};
+use exchange_connectors::SymbolMapper;
+
+#[cfg(test)]
+mod symbol_mapper_tests {
+    use super::*;
+
+    #[test]
+    fn test_to_exchange_formats() {
+        let mapper = SymbolMapper::new();
+        let pair = TradingPair::new("btc", "usd");
+
+        assert_eq!(mapper.to_exchange(&Exchange::Binance, &pair), "BTCUSD");
+        assert_eq!(mapper.to_exchange(&Exchange::Bybit, &pair), "BTCUSD");
+        assert_eq!(mapper.to_exchange(&Exchange::Bitstamp, &pair), "btcusd");
+        assert_eq!(mapper.to_exchange(&Exchange::Coinbase, &pair), "BTC-USD");
+        assert_eq!(mapper.to_exchange(&Exchange::Kraken, &pair), "XBT/USD");
+        assert_eq!(
+            mapper.to_exchange(&Exchange::CryptoDotCom, &pair),
+            "BTC_USD"
+        );
+        assert_eq!(mapper.to_exchange(&Exchange::GateIo, &pair), "BTC_USD");
+        assert_eq!(mapper.to_exchange(&Exchange::KuCoin, &pair), "BTC-USD");
+        assert_eq!(mapper.to_exchange(&Exchange::Bitfinex, &pair), "tBTCUSD");
+        assert_eq!(mapper.to_exchange(&Exchange::OKX, &pair), "BTC-USD");
+    }
+
+    #[test]
+    fn test_round_trip_all_exchanges() {
+        let mapper = SymbolMapper::new();
+        let pair = TradingPair::new("ETH", "USDT");
+
+        for exchange in Exchange::all() {
+            // Hyperliquid perpetuals are named by coin alone and always settle in USDC, and
+            // Kraken Futures perpetuals are all quoted in USD
+            let pair = match exchange {
+                Exchange::Hyperliquid => TradingPair::new("ETH", "USDC"),
+                Exchange::KrakenFutures => TradingPair::new("ETH", "USD"),
+                _ => pair.clone(),
+            };
+            let native = mapper.to_exchange(&exchange, &pair);
+            assert_eq!(mapper.from_exchange(&exchange, &native).unwrap(), pair);
+        }
+    }
+
+    #[test]
+    fn test_from_exchange_prefers_longest_quote() {
+        let mapper = SymbolMapper::new();
+
+        let pair = mapper
+            .from_exchange(&Exchange::Binance, "BTCFDUSD")
+            .unwrap();
+        assert_eq!(pair, TradingPair::new("BTC", "FDUSD"));
+
+        let pair = mapper.from_exchange(&Exchange::Binance, "ethbtc").unwrap();
+        assert_eq!(pair, TradingPair::new("ETH", "BTC"));
+
+        let pair = mapper.from_exchange(&Exchange::Kraken, "XBT/EUR").unwrap();
+        assert_eq!(pair, TradingPair::new("BTC", "EUR"));
+    }
+
+    #[test]
+    fn test_bitfinex_symbols() {
+        let mapper = SymbolMapper::new();
+
+        let pair = TradingPair::new("BTC", "USDT");
+        assert_eq!(mapper.to_exchange(&Exchange::Bitfinex, &pair), "tBTCUST");
+        let pair = TradingPair::new("DOGE", "USD");
+        assert_eq!(mapper.to_exchange(&Exchange::Bitfinex, &pair), "tDOGE:USD");
+
+        let pair = mapper
+            .from_exchange(&Exchange::Bitfinex, "tTESTBTC:TESTUSD")
+            .unwrap();
+        assert_eq!(pair, TradingPair::new("TESTBTC", "TESTUSD"));
+        assert!(mapper.from_exchange(&Exchange::Bitfinex, "BTCUSD").is_err());
+    }
+
+    #[test]
+    fn test_kraken_futures_symbols() {
+        let mapper = SymbolMapper::new();
+
+        let pair = TradingPair::new("BTC", "USD");
+        assert_eq!(
+            mapper.to_exchange(&Exchange::KrakenFutures, &pair),
+            "PF_XBTUSD"
+        );
+        // Inverse perpetuals map to the same pair
+        for symbol in ["PF_XBTUSD", "PI_XBTUSD", "pf_xbtusd"] {
+            assert_eq!(
+                mapper
+                    .from_exchange(&Exchange::KrakenFutures, symbol)
+                    .unwrap(),
+                pair
+            );
+        }
+        assert!(mapper
+            .from_exchange(&Exchange::KrakenFutures, "FI_XBTUSD_260626")
+            .is_err());
+    }
+
+    #[test]
+    fn test_unknown_symbols_are_rejected() {
+        let mapper = SymbolMapper::new();
+
+        assert!(mapper.from_exchange(&Exchange::Binance, "BTCXYZ").is_err());
+        assert!(mapper.from_exchange(&Exchange::Coinbase, "BTCUSD").is_err());
+        assert!(mapper.from_exchange(&Exchange::Kraken, "/USD").is_err());
+
+        let mapper = mapper.with_quote_asset("xyz");
+        assert_eq!(
+            mapper.from_exchange(&Exchange::Binance, "BTCXYZ").unwrap(),
+            TradingPair::new("BTC", "XYZ")
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use aggregator_core::{Exchange, TradeSide};
+use exchange_connectors::{Binance, Bitstamp, Bybit, Coinbase, Kraken};
+
+#[cfg(test)]
+mod trade_parsing_tests {
+    use super::*;
+
+    #[test]
+    fn test_binance_trade_parsing() {
+        let message = r#"{"e":"trade","E":1672515782136,"s":"BTCUSDT","t":12345,"p":"16500.10","q":"0.25","T":1672515782134,"m":true}"#;
+        let trades = Binance::parse_trades(message).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        let trade = &trades[0];
+        assert_eq!(trade.exchange, Exchange::Binance);
+        assert_eq!(trade.symbol, "BTCUSDT");
+        assert_eq!(trade.trade_id, "12345");
+        assert_eq!(trade.price, 16500.10);
+        assert_eq!(trade.quantity, 0.25);
+        // Buyer was the maker, so the aggressor sold
+        assert_eq!(trade.side, TradeSide::Sell);
+        assert_eq!(trade.timestamp.timestamp_millis(), 1672515782134);
+
+        // Combined streams wrap the same payload in an envelope
+        let combined = format!(r#"{{"stream":"btcusdt@trade","data":{}}}"#, message);
+        let trades = Binance::parse_trades(&combined).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].trade_id, "12345");
+
+        let depth = r#"{"e":"depthUpdate","E":1,"s":"BTCUSDT","U":1,"u":2,"b":[],"a":[]}"#;
+        assert!(Binance::parse_trades(depth).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bybit_trade_parsing() {
+        let message = r#"{"topic":"publicTrade.BTCUSDT","type":"snapshot","ts":1672304486868,"data":[{"T":1672304486865,"s":"BTCUSDT","S":"Buy","v":"0.001","p":"16578.50","L":"PlusTick","i":"20f43950-d8dd-5b31-9112-a178eb6023af","BT":false},{"T":1672304486866,"s":"BTCUSDT","S":"Sell","v":"0.002","p":"16578.00","L":"MinusTick","i":"20f43950-d8dd-5b31-9112-a178eb6023b0","BT":false}]}"#;
+        let trades = Bybit::parse_trades(message).unwrap();
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].side, TradeSide::Buy);
+        assert_eq!(trades[1].side, TradeSide::Sell);
+        assert_eq!(trades[1].price, 16578.00);
+        assert_eq!(trades[0].trade_id, "20f43950-d8dd-5b31-9112-a178eb6023af");
+
+        let ack = r#"{"success":true,"ret_msg":"","conn_id":"abc","op":"subscribe"}"#;
+        assert!(Bybit::parse_trades(ack).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_kraken_trade_parsing() {
+        let message = r#"[0,[["5541.20000","0.15850568","1534614057.321597","s","l",""],["6060.00000","0.02455000","1534614057.324998","b","l",""]],"trade","XBT/USD"]"#;
+        let trades = Kraken::parse_trades(message).unwrap();
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].symbol, "XBT/USD");
+        assert_eq!(trades[0].side, TradeSide::Sell);
+        assert_eq!(trades[1].side, TradeSide::Buy);
+        assert_eq!(trades[1].quantity, 0.02455);
+        assert_eq!(trades[0].timestamp.timestamp(), 1534614057);
+
+        let heartbeat = r#"{"event":"heartbeat"}"#;
+        assert!(Kraken::parse_trades(heartbeat).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_coinbase_trade_parsing() {
+        let message = r#"{"type":"match","trade_id":10,"sequence":50,"maker_order_id":"ac928c66-ca53-498f-9c13-a110027a60e8","taker_order_id":"132fb6ae-456b-4654-b4e0-d681ac05cea1","time":"2014-11-07T08:19:27.028459Z","product_id":"BTC-USD","size":"5.23512","price":"400.23","side":"sell"}"#;
+        let trades = Coinbase::parse_trades(message).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].symbol, "BTC-USD");
+        assert_eq!(trades[0].trade_id, "10");
+        // Maker sold, so the taker bought
+        assert_eq!(trades[0].side, TradeSide::Buy);
+
+        let subscriptions = r#"{"type":"subscriptions","channels":[]}"#;
+        assert!(Coinbase::parse_trades(subscriptions).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bitstamp_trade_parsing() {
+        let message = r#"{"data":{"id":123456,"timestamp":"1590000000","amount":0.01,"amount_str":"0.01000000","price":9500.5,"price_str":"9500.50","type":1,"microtimestamp":"1590000000123456","buy_order_id":1,"sell_order_id":2},"channel":"live_trades_btcusd","event":"trade"}"#;
+        let trades = Bitstamp::parse_trades(message).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].symbol, "BTCUSD");
+        assert_eq!(trades[0].side, TradeSide::Sell);
+        assert_eq!(trades[0].price, 9500.50);
+        assert_eq!(trades[0].timestamp.timestamp_micros(), 1590000000123456);
+
+        let ack =
+            r#"{"event":"bts:subscription_succeeded","channel":"live_trades_btcusd","data":{}}"#;
+        assert!(Bitstamp::parse_trades(ack).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_trade_messages() {
+        assert!(Binance::parse_trades("not json").is_err());
+        assert!(Coinbase::parse_trades(r#"{"type":"match","trade_id":1}"#).is_err());
+    }
+}
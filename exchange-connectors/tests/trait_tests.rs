@@ -1,7 +1,7 @@
-use aggregator_core::PriceLevelUpdate;
+use aggregator_core::{ConnectorRegistry, Exchange, ExchangeConfig, PriceLevelUpdate, TradingPair};
 use exchange_connectors::{Binance, Bitstamp, Bybit, Coinbase, Kraken, OrderBookService};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 
 mod common;
 
@@ -10,9 +10,16 @@ mod common;
 async fn test_trait_implementation_binance() {
     let exchange: Box<dyn OrderBookService + Send + Sync> = Box::new(Binance::new());
     let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+    let (shutdown_tx, _) = broadcast::channel(1);
 
     let result = exchange
-        .spawn_order_book_service(["BTC", "USDT"], 100, 1000, tx)
+        .spawn_order_book_service(
+            &[TradingPair::new("BTC", "USDT")],
+            100,
+            1000,
+            tx,
+            shutdown_tx.subscribe(),
+        )
         .await;
 
     // Should return a result (success or failure is both acceptable)
@@ -30,9 +37,16 @@ async fn test_trait_implementation_binance() {
 async fn test_trait_implementation_bybit() {
     let exchange: Box<dyn OrderBookService + Send + Sync> = Box::new(Bybit::new());
     let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+    let (shutdown_tx, _) = broadcast::channel(1);
 
     let result = exchange
-        .spawn_order_book_service(["BTC", "USDT"], 100, 1000, tx)
+        .spawn_order_book_service(
+            &[TradingPair::new("BTC", "USDT")],
+            100,
+            1000,
+            tx,
+            shutdown_tx.subscribe(),
+        )
         .await;
 
     match result {
@@ -49,9 +63,16 @@ async fn test_trait_implementation_bybit() {
 async fn test_trait_implementation_kraken() {
     let exchange: Box<dyn OrderBookService + Send + Sync> = Box::new(Kraken::new());
     let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+    let (shutdown_tx, _) = broadcast::channel(1);
 
     let result = exchange
-        .spawn_order_book_service(["BTC", "USD"], 100, 1000, tx)
+        .spawn_order_book_service(
+            &[TradingPair::new("BTC", "USD")],
+            100,
+            1000,
+            tx,
+            shutdown_tx.subscribe(),
+        )
         .await;
 
     match result {
@@ -68,9 +89,16 @@ async fn test_trait_implementation_kraken() {
 async fn test_trait_implementation_bitstamp() {
     let exchange: Box<dyn OrderBookService + Send + Sync> = Box::new(Bitstamp::new());
     let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+    let (shutdown_tx, _) = broadcast::channel(1);
 
     let result = exchange
-        .spawn_order_book_service(["BTC", "USD"], 100, 1000, tx)
+        .spawn_order_book_service(
+            &[TradingPair::new("BTC", "USD")],
+            100,
+            1000,
+            tx,
+            shutdown_tx.subscribe(),
+        )
         .await;
 
     // Bitstamp is a placeholder, should return empty handles
@@ -83,9 +111,16 @@ async fn test_trait_implementation_bitstamp() {
 async fn test_trait_implementation_coinbase() {
     let exchange: Box<dyn OrderBookService + Send + Sync> = Box::new(Coinbase::new());
     let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+    let (shutdown_tx, _) = broadcast::channel(1);
 
     let result = exchange
-        .spawn_order_book_service(["BTC", "USD"], 100, 1000, tx)
+        .spawn_order_book_service(
+            &[TradingPair::new("BTC", "USD")],
+            100,
+            1000,
+            tx,
+            shutdown_tx.subscribe(),
+        )
         .await;
 
     // Coinbase is a placeholder, should return empty handles
@@ -97,6 +132,7 @@ async fn test_trait_implementation_coinbase() {
 #[tokio::test]
 async fn test_multiple_exchanges_with_same_channel() {
     let (tx, mut rx) = mpsc::channel::<PriceLevelUpdate>(1000);
+    let (shutdown_tx, _) = broadcast::channel(1);
 
     let exchanges: Vec<Box<dyn OrderBookService + Send + Sync>> =
         vec![Box::new(Bitstamp::new()), Box::new(Coinbase::new())];
@@ -105,7 +141,13 @@ async fn test_multiple_exchanges_with_same_channel() {
 
     for exchange in exchanges {
         match exchange
-            .spawn_order_book_service(["BTC", "USD"], 50, 500, tx.clone())
+            .spawn_order_book_service(
+                &[TradingPair::new("BTC", "USD")],
+                50,
+                500,
+                tx.clone(),
+                shutdown_tx.subscribe(),
+            )
             .await
         {
             Ok(mut handles) => {
@@ -121,6 +163,83 @@ async fn test_multiple_exchanges_with_same_channel() {
     assert!(all_handles.is_empty());
 }
 
+#[tokio::test]
+async fn test_empty_pair_list_is_rejected() {
+    let exchanges: Vec<Box<dyn OrderBookService + Send + Sync>> = vec![
+        Box::new(Binance::new()),
+        Box::new(Bybit::new()),
+        Box::new(Kraken::new()),
+    ];
+
+    for exchange in exchanges {
+        let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let result = exchange
+            .spawn_order_book_service(&[], 100, 1000, tx, shutdown_tx.subscribe())
+            .await;
+        assert!(result.is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_multiple_pairs_share_one_connection() {
+    let pairs = [
+        TradingPair::new("BTC", "USDT"),
+        TradingPair::new("ETH", "USDT"),
+        TradingPair::new("SOL", "USDT"),
+    ];
+    let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+    let (shutdown_tx, _) = broadcast::channel(1);
+
+    // One stream task and one processor regardless of how many pairs are requested
+    let handles = Kraken::new()
+        .spawn_order_book_service(&pairs, 10, 1000, tx, shutdown_tx.subscribe())
+        .await
+        .unwrap();
+    assert_eq!(handles.len(), 2);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_stops_connector_tasks() {
+    let exchanges: Vec<Box<dyn OrderBookService + Send + Sync>> = vec![
+        Box::new(Binance::new()),
+        Box::new(Bybit::new()),
+        Box::new(Kraken::new()),
+    ];
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (tx, _rx) = mpsc::channel::<PriceLevelUpdate>(100);
+
+    let mut handles = Vec::new();
+    for exchange in exchanges {
+        handles.extend(
+            exchange
+                .spawn_order_book_service(
+                    &[TradingPair::new("BTC", "USDT")],
+                    10,
+                    100,
+                    tx.clone(),
+                    shutdown_tx.subscribe(),
+                )
+                .await
+                .unwrap(),
+        );
+    }
+
+    // Every stream task exits instead of reconnecting, which in turn ends its processor
+    shutdown_tx.send(()).unwrap();
+    for handle in handles {
+        let result = tokio::time::timeout(std::time::Duration::from_secs(10), handle)
+            .await
+            .expect("connector task did not shut down")
+            .unwrap();
+        assert!(result.is_ok());
+    }
+}
+
 #[test]
 fn test_trait_object_creation() {
     let exchanges: Vec<Box<dyn OrderBookService + Send + Sync>> = vec![
@@ -155,3 +274,40 @@ fn test_arc_wrapped_exchanges() {
     assert!(Arc::ptr_eq(&bybit, &bybit_clone));
     assert!(Arc::ptr_eq(&kraken, &kraken_clone));
 }
+
+#[tokio::test]
+async fn test_register_all_registers_every_connector() {
+    let connectors = ConnectorRegistry::new();
+    exchange_connectors::register_all(&connectors).await;
+
+    let mut expected: Vec<Exchange> = Exchange::all()
+        .into_iter()
+        .filter(|exchange| !matches!(exchange, Exchange::CryptoDotCom | Exchange::OKX))
+        .collect();
+    expected.sort();
+    assert_eq!(connectors.exchanges().await, expected);
+
+    let config = ExchangeConfig::default();
+    for exchange in &expected {
+        let connector = connectors.create(exchange, &config).await.unwrap();
+        assert!(connector.is_ok(), "{}: {:?}", exchange, connector.err());
+    }
+    for exchange in [
+        Exchange::Binance,
+        Exchange::Bybit,
+        Exchange::Coinbase,
+        Exchange::GateIo,
+        Exchange::Kraken,
+        Exchange::KuCoin,
+    ] {
+        assert!(connectors
+            .create_discovery(&exchange, &config)
+            .await
+            .unwrap()
+            .is_ok());
+    }
+    assert!(connectors
+        .create_discovery(&Exchange::Bitstamp, &config)
+        .await
+        .is_none());
+}
@@ -5,8 +5,14 @@
 
 use aggregator_core::{Ask, Bid, Exchange};
 use chrono::Utc;
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
-use orderbook_implementations::{BTreeOrderBook, HashMapOrderBook, OrderBook};
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use orderbook_implementations::{
+    BTreeOrderBook, CowOrderBook, HashMapOrderBook, OrderBook, ShardedOrderBookMap,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Helper function to create a test bid
@@ -43,7 +49,7 @@ fn bench_single_insertion(c: &mut Criterion) {
                     let mut orderbook = BTreeOrderBook::new();
                     for i in 0..size {
                         let bid = create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance);
-                        orderbook.update_bids(vec![bid], 1000).await;
+                        orderbook.update_bids(vec![bid], 1000).await.unwrap();
                     }
                     black_box(orderbook);
                 });
@@ -57,7 +63,7 @@ fn bench_single_insertion(c: &mut Criterion) {
                     let mut orderbook = HashMapOrderBook::new();
                     for i in 0..size {
                         let bid = create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance);
-                        orderbook.update_bids(vec![bid], 1000).await;
+                        orderbook.update_bids(vec![bid], 1000).await.unwrap();
                     }
                     black_box(orderbook);
                 });
@@ -85,7 +91,7 @@ fn bench_batch_insertion(c: &mut Criterion) {
                 let rt = tokio::runtime::Runtime::new().unwrap();
                 rt.block_on(async {
                     let mut orderbook = BTreeOrderBook::new();
-                    orderbook.update_bids(bids.clone(), 1000).await;
+                    orderbook.update_bids(bids.clone(), 1000).await.unwrap();
                     black_box(orderbook);
                 });
             });
@@ -96,7 +102,7 @@ fn bench_batch_insertion(c: &mut Criterion) {
                 let rt = tokio::runtime::Runtime::new().unwrap();
                 rt.block_on(async {
                     let mut orderbook = HashMapOrderBook::new();
-                    orderbook.update_bids(bids.clone(), 1000).await;
+                    orderbook.update_bids(bids.clone(), 1000).await.unwrap();
                     black_box(orderbook);
                 });
             });
@@ -119,7 +125,7 @@ fn bench_get_best_price(c: &mut Criterion) {
             let bids: Vec<Bid> = (0..*size)
                 .map(|i| create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance))
                 .collect();
-            orderbook.update_bids(bids, 1000).await;
+            orderbook.update_bids(bids, 1000).await.unwrap();
             orderbook
         });
 
@@ -128,7 +134,7 @@ fn bench_get_best_price(c: &mut Criterion) {
             let bids: Vec<Bid> = (0..*size)
                 .map(|i| create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance))
                 .collect();
-            orderbook.update_bids(bids, 1000).await;
+            orderbook.update_bids(bids, 1000).await.unwrap();
             orderbook
         });
 
@@ -177,7 +183,7 @@ fn bench_get_top_n(c: &mut Criterion) {
         let bids: Vec<Bid> = (0..orderbook_size)
             .map(|i| create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance))
             .collect();
-        orderbook.update_bids(bids, 1000).await;
+        orderbook.update_bids(bids, 1000).await.unwrap();
         orderbook
     });
 
@@ -186,7 +192,7 @@ fn bench_get_top_n(c: &mut Criterion) {
         let bids: Vec<Bid> = (0..orderbook_size)
             .map(|i| create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance))
             .collect();
-        orderbook.update_bids(bids, 1000).await;
+        orderbook.update_bids(bids, 1000).await.unwrap();
         orderbook
     });
 
@@ -238,8 +244,14 @@ fn bench_order_updates(c: &mut Criterion) {
             b.iter(|| {
                 rt.block_on(async {
                     let mut orderbook = BTreeOrderBook::new();
-                    orderbook.update_bids(initial_bids.clone(), 1000).await;
-                    orderbook.update_bids(update_bids.clone(), 1000).await;
+                    orderbook
+                        .update_bids(initial_bids.clone(), 1000)
+                        .await
+                        .unwrap();
+                    orderbook
+                        .update_bids(update_bids.clone(), 1000)
+                        .await
+                        .unwrap();
                     black_box(orderbook);
                 });
             });
@@ -249,8 +261,14 @@ fn bench_order_updates(c: &mut Criterion) {
             b.iter(|| {
                 rt.block_on(async {
                     let mut orderbook = HashMapOrderBook::new();
-                    orderbook.update_bids(initial_bids.clone(), 1000).await;
-                    orderbook.update_bids(update_bids.clone(), 1000).await;
+                    orderbook
+                        .update_bids(initial_bids.clone(), 1000)
+                        .await
+                        .unwrap();
+                    orderbook
+                        .update_bids(update_bids.clone(), 1000)
+                        .await
+                        .unwrap();
                     black_box(orderbook);
                 });
             });
@@ -278,7 +296,10 @@ fn bench_depth_limiting(c: &mut Criterion) {
                     let rt = tokio::runtime::Runtime::new().unwrap();
                     rt.block_on(async {
                         let mut orderbook = BTreeOrderBook::new();
-                        orderbook.update_bids(bids.clone(), max_depth).await;
+                        orderbook
+                            .update_bids(bids.clone(), max_depth)
+                            .await
+                            .unwrap();
                         black_box(orderbook);
                     });
                 });
@@ -293,7 +314,10 @@ fn bench_depth_limiting(c: &mut Criterion) {
                     let rt = tokio::runtime::Runtime::new().unwrap();
                     rt.block_on(async {
                         let mut orderbook = HashMapOrderBook::new();
-                        orderbook.update_bids(bids.clone(), max_depth).await;
+                        orderbook
+                            .update_bids(bids.clone(), max_depth)
+                            .await
+                            .unwrap();
                         black_box(orderbook);
                     });
                 });
@@ -304,6 +328,80 @@ fn bench_depth_limiting(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark trimming a full 1000-level book as better levels push the worst ones out
+fn bench_depth_trimming(c: &mut Criterion) {
+    let mut group = c.benchmark_group("depth_trimming");
+
+    let max_depth = 1000;
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let initial_bids: Vec<Bid> = (0..max_depth)
+        .map(|i| create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance))
+        .collect();
+
+    for batch_size in [10, 100, 1000].iter() {
+        // Every level beats the current best, so each batch trims `batch_size` levels
+        let batch: Vec<Bid> = (0..*batch_size)
+            .map(|i| create_bid(101.0 + i as f64 * 0.01, 10.0, Exchange::Binance))
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("btree", batch_size), batch_size, |b, _| {
+            b.iter_batched(
+                || {
+                    rt.block_on(async {
+                        let mut orderbook = BTreeOrderBook::new();
+                        orderbook
+                            .update_bids(initial_bids.clone(), max_depth)
+                            .await
+                            .unwrap();
+                        orderbook
+                    })
+                },
+                |mut orderbook| {
+                    rt.block_on(async {
+                        orderbook
+                            .update_bids(batch.clone(), max_depth)
+                            .await
+                            .unwrap();
+                        black_box(orderbook);
+                    });
+                },
+                BatchSize::SmallInput,
+            );
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("hashmap", batch_size),
+            batch_size,
+            |b, _| {
+                b.iter_batched(
+                    || {
+                        rt.block_on(async {
+                            let mut orderbook = HashMapOrderBook::new();
+                            orderbook
+                                .update_bids(initial_bids.clone(), max_depth)
+                                .await
+                                .unwrap();
+                            orderbook
+                        })
+                    },
+                    |mut orderbook| {
+                        rt.block_on(async {
+                            orderbook
+                                .update_bids(batch.clone(), max_depth)
+                                .await
+                                .unwrap();
+                            black_box(orderbook);
+                        });
+                    },
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
 /// Benchmark mixed read/write workload
 fn bench_mixed_workload(c: &mut Criterion) {
     let mut group = c.benchmark_group("mixed_workload");
@@ -320,14 +418,14 @@ fn bench_mixed_workload(c: &mut Criterion) {
                     let initial_bids: Vec<Bid> = (0..size)
                         .map(|i| create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance))
                         .collect();
-                    orderbook.update_bids(initial_bids, 1000).await;
+                    orderbook.update_bids(initial_bids, 1000).await.unwrap();
 
                     // Mixed operations
                     for i in 0..50 {
                         // Update some orders
                         let update_bid =
                             create_bid(100.0 - i as f64 * 0.01, 15.0, Exchange::Binance);
-                        orderbook.update_bids(vec![update_bid], 1000).await;
+                        orderbook.update_bids(vec![update_bid], 1000).await.unwrap();
 
                         // Read operations
                         let _ = orderbook.get_best_bid().await;
@@ -350,14 +448,14 @@ fn bench_mixed_workload(c: &mut Criterion) {
                     let initial_bids: Vec<Bid> = (0..size)
                         .map(|i| create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance))
                         .collect();
-                    orderbook.update_bids(initial_bids, 1000).await;
+                    orderbook.update_bids(initial_bids, 1000).await.unwrap();
 
                     // Mixed operations
                     for i in 0..50 {
                         // Update some orders
                         let update_bid =
                             create_bid(100.0 - i as f64 * 0.01, 15.0, Exchange::Binance);
-                        orderbook.update_bids(vec![update_bid], 1000).await;
+                        orderbook.update_bids(vec![update_bid], 1000).await.unwrap();
 
                         // Read operations
                         let _ = orderbook.get_best_bid().await;
@@ -374,6 +472,115 @@ fn bench_mixed_workload(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark concurrent updates across many symbols, one global lock versus sharded locks
+fn bench_sharded_updates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sharded_updates");
+
+    let tasks = 8;
+    let symbols_per_task = 125;
+    let updates_per_symbol = 10;
+    group.throughput(Throughput::Elements(
+        (tasks * symbols_per_task * updates_per_symbol) as u64,
+    ));
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(tasks)
+        .build()
+        .unwrap();
+
+    for shards in [1, 64].iter() {
+        group.bench_with_input(BenchmarkId::new("shards", shards), shards, |b, &shards| {
+            let books: Arc<ShardedOrderBookMap> =
+                Arc::new(ShardedOrderBookMap::with_shards(shards));
+            b.iter(|| {
+                rt.block_on(async {
+                    let handles: Vec<_> = (0..tasks)
+                        .map(|task| {
+                            let books = books.clone();
+                            tokio::spawn(async move {
+                                for i in 0..symbols_per_task {
+                                    let symbol = format!("SYM{}", task * symbols_per_task + i);
+                                    for level in 0..updates_per_symbol {
+                                        let bid = create_bid(
+                                            100.0 - level as f64 * 0.01,
+                                            10.0,
+                                            Exchange::Binance,
+                                        );
+                                        books
+                                            .update_bids(Exchange::Binance, &symbol, vec![bid], 100)
+                                            .await
+                                            .unwrap();
+                                    }
+                                }
+                            })
+                        })
+                        .collect();
+                    for handle in handles {
+                        handle.await.unwrap();
+                    }
+                });
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark read latency while a writer continuously updates the same book
+fn bench_reads_under_writes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reads_under_writes");
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .build()
+        .unwrap();
+    let initial_bids: Vec<Bid> = (0..1000)
+        .map(|i| create_bid(100.0 - i as f64 * 0.01, 10.0, Exchange::Binance))
+        .collect();
+
+    let mut btree = BTreeOrderBook::new();
+    rt.block_on(btree.update_bids(initial_bids.clone(), 1000))
+        .unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let writer = spawn_writer(&rt, &btree, &stop);
+    group.bench_function("btree", |b| {
+        b.iter(|| black_box(rt.block_on(btree.get_best_n_bids(10))));
+    });
+    stop.store(true, Ordering::Relaxed);
+    rt.block_on(writer).unwrap();
+
+    let mut cow = CowOrderBook::new();
+    rt.block_on(cow.update_bids(initial_bids, 1000)).unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let writer = spawn_writer(&rt, &cow, &stop);
+    group.bench_function("copy_on_write", |b| {
+        b.iter(|| black_box(rt.block_on(cow.get_best_n_bids(10))));
+    });
+    stop.store(true, Ordering::Relaxed);
+    rt.block_on(writer).unwrap();
+
+    group.finish();
+}
+
+/// Spawns a task that keeps updating a clone of `orderbook` until `stop` is set
+fn spawn_writer<B: OrderBook + Clone + 'static>(
+    rt: &tokio::runtime::Runtime,
+    orderbook: &B,
+    stop: &Arc<AtomicBool>,
+) -> tokio::task::JoinHandle<()> {
+    let mut writer = orderbook.clone();
+    let stop = stop.clone();
+    rt.spawn(async move {
+        let mut i = 0u64;
+        while !stop.load(Ordering::Relaxed) {
+            let bid = create_bid(100.0 - (i % 1000) as f64 * 0.01, 15.0, Exchange::Binance);
+            writer.update_bids(vec![bid], 1000).await.unwrap();
+            i += 1;
+            tokio::task::yield_now().await;
+        }
+    })
+}
+
 criterion_group!(
     benches,
     bench_single_insertion,
@@ -382,7 +589,10 @@ criterion_group!(
     bench_get_top_n,
     bench_order_updates,
     bench_depth_limiting,
-    bench_mixed_workload
+    bench_depth_trimming,
+    bench_mixed_workload,
+    bench_sharded_updates,
+    bench_reads_under_writes
 );
 
 criterion_main!(benches);
@@ -0,0 +1,114 @@
+//! Order book analytics
+//!
+//! Short-term signals and execution estimates derived from the book, provided for every
+//! `OrderBook` implementation through the `BookAnalytics` extension trait.
+
+use crate::OrderBook;
+use aggregator_core::{FillEstimate, TradeSide};
+use async_trait::async_trait;
+
+/// Signals computed from the best levels of an order book
+///
+/// Implemented for every `OrderBook`; bring the trait into scope to use it. A price level here
+/// is one price: entries quoted by several exchanges at the same price count as one level and
+/// their quantities are summed.
+#[async_trait]
+pub trait BookAnalytics: OrderBook {
+    /// Volume imbalance over the best `levels` price levels of each side
+    ///
+    /// # Returns
+    ///
+    /// `(bid_volume - ask_volume) / (bid_volume + ask_volume)`, from -1.0 (only asks) to 1.0
+    /// (only bids), or `None` if both sides are empty or `levels` is 0
+    async fn imbalance(&self, levels: usize) -> Option<f64> {
+        let bids = self.get_best_n_bids(self.bid_depth().await).await;
+        let asks = self.get_best_n_asks(self.ask_depth().await).await;
+
+        let bid_volume: f64 = top_levels(bids.iter().map(|bid| (bid.price, bid.quantity)))
+            .take(levels)
+            .map(|(_, quantity)| quantity)
+            .sum();
+        let ask_volume: f64 = top_levels(asks.iter().map(|ask| (ask.price, ask.quantity)))
+            .take(levels)
+            .map(|(_, quantity)| quantity)
+            .sum();
+
+        let total = bid_volume + ask_volume;
+        if total > 0.0 {
+            Some((bid_volume - ask_volume) / total)
+        } else {
+            None
+        }
+    }
+
+    /// Mid price between the best bid and the best ask
+    async fn mid_price(&self) -> Option<f64> {
+        let best_bid = self.get_best_bid().await?;
+        let best_ask = self.get_best_ask().await?;
+        Some((best_bid.price + best_ask.price) / 2.0)
+    }
+
+    /// Size-weighted mid price of the best bid and ask levels
+    ///
+    /// # Returns
+    ///
+    /// `(bid_price * ask_quantity + ask_price * bid_quantity) / (bid_quantity + ask_quantity)`,
+    /// which leans towards the side with less quantity, or `None` if either side is empty
+    async fn microprice(&self) -> Option<f64> {
+        let bids = self.get_best_n_bids(self.bid_depth().await).await;
+        let asks = self.get_best_n_asks(self.ask_depth().await).await;
+
+        let (bid_price, bid_quantity) =
+            top_levels(bids.iter().map(|bid| (bid.price, bid.quantity))).next()?;
+        let (ask_price, ask_quantity) =
+            top_levels(asks.iter().map(|ask| (ask.price, ask.quantity))).next()?;
+
+        let total = bid_quantity + ask_quantity;
+        if total > 0.0 {
+            Some((bid_price * ask_quantity + ask_price * bid_quantity) / total)
+        } else {
+            Some((bid_price + ask_price) / 2.0)
+        }
+    }
+
+    /// Average and worst execution price of a market order of `quantity`
+    ///
+    /// A `Buy` walks the asks and a `Sell` walks the bids, best first, across every exchange in
+    /// the book.
+    ///
+    /// # Returns
+    ///
+    /// The fill estimate, marked incomplete if the book holds less than `quantity`, or `None` if
+    /// `quantity` is not positive or that side is empty
+    async fn cost_to_fill(&self, side: TradeSide, quantity: f64) -> Option<FillEstimate> {
+        let levels: Vec<(f64, f64)> = match side {
+            TradeSide::Buy => self
+                .get_best_n_asks(self.ask_depth().await)
+                .await
+                .into_iter()
+                .map(|ask| (ask.price, ask.quantity))
+                .collect(),
+            TradeSide::Sell => self
+                .get_best_n_bids(self.bid_depth().await)
+                .await
+                .into_iter()
+                .map(|bid| (bid.price, bid.quantity))
+                .collect(),
+        };
+        FillEstimate::from_levels(side, levels, quantity)
+    }
+}
+
+impl<T: OrderBook + ?Sized> BookAnalytics for T {}
+
+/// Merges entries sorted best first into `(price, total quantity)` levels
+fn top_levels(entries: impl Iterator<Item = (f64, f64)>) -> impl Iterator<Item = (f64, f64)> {
+    let mut entries = entries.peekable();
+    std::iter::from_fn(move || {
+        let (price, mut quantity) = entries.next()?;
+        while let Some((_, next_quantity)) = entries.next_if(|(next, _)| *next == price) {
+            quantity += next_quantity;
+        }
+        Some((price, quantity))
+    })
+}
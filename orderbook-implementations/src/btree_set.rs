@@ -24,8 +24,11 @@
 //! All operations are protected by async RwLocks, allowing multiple concurrent readers
 //! or a single writer. The Arc<RwLock<>> pattern enables safe sharing across async tasks.
 
+use crate::stats::{timed_lock, BookStatsRecorder};
+use crate::tick;
+use crate::validation::{validate_asks, validate_bids};
 use crate::{BuySide, OrderBook, SellSide};
-use aggregator_core::{Ask, Bid, Exchange};
+use aggregator_core::{Ask, Bid, BookStats, InstrumentInfo, PriceLevelUpdate, Result};
 use async_trait::async_trait;
 use std::collections::BTreeSet;
 use std::sync::Arc;
@@ -35,7 +38,9 @@ use tokio::sync::RwLock;
 ///
 /// Uses `BTreeSet` to maintain automatically sorted bid and ask orders.
 /// Bids are sorted in descending price order (highest first), while
-/// asks are sorted in ascending price order (lowest first).
+/// asks are sorted in ascending price order (lowest first). Orders are keyed
+/// by `(price, exchange)`, so several exchanges quoting the same price are
+/// kept as separate entries, ordered by exchange.
 ///
 /// # Examples
 ///
@@ -55,7 +60,7 @@ use tokio::sync::RwLock;
 ///         timestamp: Utc::now(),
 ///     };
 ///     
-///     orderbook.update_bids(vec![bid], 100).await;
+///     orderbook.update_bids(vec![bid], 100).await.unwrap();
 ///     let best = orderbook.get_best_bid().await;
 /// }
 /// ```
@@ -65,6 +70,10 @@ pub struct BTreeOrderBook {
     bids: Arc<RwLock<BTreeSet<Bid>>>,
     /// Ask orders sorted by price ascending (lowest first)  
     asks: Arc<RwLock<BTreeSet<Ask>>>,
+    /// Activity counters, when enabled with `with_stats`
+    stats: Option<Arc<BookStatsRecorder>>,
+    /// Tick size incoming prices are rounded to, when set with `with_tick_size`
+    tick_size: Option<f64>,
 }
 
 impl BTreeOrderBook {
@@ -85,9 +94,32 @@ impl BTreeOrderBook {
         Self {
             bids: Arc::new(RwLock::new(BTreeSet::new())),
             asks: Arc::new(RwLock::new(BTreeSet::new())),
+            stats: None,
+            tick_size: None,
         }
     }
 
+    /// Enables activity counters, read back through `OrderBook::stats`
+    ///
+    /// Clones made afterwards share the same counters.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(BookStatsRecorder::new()));
+        self
+    }
+
+    /// Rounds every incoming price to the nearest multiple of `tick_size`
+    ///
+    /// A tick size that is not positive and finite leaves prices unchanged.
+    pub fn with_tick_size(mut self, tick_size: f64) -> Self {
+        self.tick_size = tick::checked_tick_size(tick_size);
+        self
+    }
+
+    /// Rounds every incoming price to the tick size of `instrument`
+    pub fn with_instrument(self, instrument: &InstrumentInfo) -> Self {
+        self.with_tick_size(instrument.tick_size)
+    }
+
     /// Creates a bid-side only view of this order book
     ///
     /// Returns a `BTreeBidSide` that shares the same underlying bid data
@@ -116,44 +148,50 @@ impl BTreeOrderBook {
         }
     }
 
-    /// Trims the bid side to the specified maximum depth
+    /// Trims a side to the specified maximum depth
     ///
-    /// Keeps only the best (highest price) bids up to `max_depth`.
-    /// This is an internal helper method used during updates.
+    /// Sets iterate best first, so this pops levels off the worst end until at most
+    /// `max_depth` remain. Only the excess levels are touched; the levels that stay are never
+    /// copied.
     ///
     /// # Arguments
     ///
-    /// * `max_depth` - Maximum number of bid levels to retain
-    async fn trim_bids(&self, max_depth: usize) {
-        let mut bids = self.bids.write().await;
-        if bids.len() > max_depth {
-            // Keep only the top max_depth bids (highest prices)
-            let mut new_bids = BTreeSet::new();
-            for bid in bids.iter().take(max_depth) {
-                new_bids.insert(bid.clone());
-            }
-            *bids = new_bids;
+    /// * `levels` - The bid or ask set to trim
+    /// * `max_depth` - Maximum number of levels to retain
+    ///
+    /// # Returns
+    ///
+    /// The number of levels removed
+    fn trim_to_depth<T: Ord>(levels: &mut BTreeSet<T>, max_depth: usize) -> usize {
+        let excess = levels.len().saturating_sub(max_depth);
+        for _ in 0..excess {
+            levels.pop_last();
         }
+        excess
     }
 
-    /// Trims the ask side to the specified maximum depth
+    /// Applies updates to a side and trims it to the specified maximum depth
     ///
-    /// Keeps only the best (lowest price) asks up to `max_depth`.
-    /// This is an internal helper method used during updates.
+    /// Positive quantities replace any existing level at the same price and exchange, and zero
+    /// quantities remove it.
     ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// * `max_depth` - Maximum number of ask levels to retain
-    async fn trim_asks(&self, max_depth: usize) {
-        let mut asks = self.asks.write().await;
-        if asks.len() > max_depth {
-            // Keep only the top max_depth asks (lowest prices)
-            let mut new_asks = BTreeSet::new();
-            for ask in asks.iter().take(max_depth) {
-                new_asks.insert(ask.clone());
+    /// The number of levels removed by trimming
+    fn apply_levels<T: Ord>(
+        levels: &mut BTreeSet<T>,
+        updates: impl IntoIterator<Item = T>,
+        max_depth: usize,
+        quantity: impl Fn(&T) -> f64,
+    ) -> usize {
+        for level in updates {
+            if quantity(&level) > 0.0 {
+                levels.replace(level);
+            } else {
+                levels.remove(&level);
             }
-            *asks = new_asks;
         }
+        BTreeOrderBook::trim_to_depth(levels, max_depth)
     }
 }
 
@@ -165,54 +203,65 @@ impl Default for BTreeOrderBook {
 
 #[async_trait]
 impl OrderBook for BTreeOrderBook {
-    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) {
-        let mut bid_set = self.bids.write().await;
-
-        for bid in bids {
-            if bid.quantity > 0.0 {
-                // Remove any existing bid at the same price and exchange
-                bid_set.retain(|b| !(b.price == bid.price && b.exchange == bid.exchange));
-                // Insert the new bid
-                bid_set.insert(bid);
-            } else {
-                // Remove bid if quantity is 0
-                bid_set.retain(|b| !(b.price == bid.price && b.exchange == bid.exchange));
-            }
+    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) -> Result<()> {
+        validate_bids(&bids)?;
+        let bids = tick::round_bids(bids, self.tick_size);
+
+        let stats = self.stats.as_deref();
+        let mut bid_set = timed_lock(stats, self.bids.write()).await;
+        let received = bids.len();
+
+        let trimmed =
+            BTreeOrderBook::apply_levels(&mut bid_set, bids, max_depth, |bid| bid.quantity);
+        if let Some(stats) = stats {
+            stats.record_updates(received);
+            stats.record_trimmed(trimmed);
         }
 
-        // Trim to max depth
-        if bid_set.len() > max_depth {
-            let mut new_bids = BTreeSet::new();
-            for bid in bid_set.iter().take(max_depth) {
-                new_bids.insert(bid.clone());
-            }
-            *bid_set = new_bids;
-        }
+        Ok(())
     }
 
-    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) {
-        let mut ask_set = self.asks.write().await;
+    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) -> Result<()> {
+        validate_asks(&asks)?;
+        let asks = tick::round_asks(asks, self.tick_size);
 
-        for ask in asks {
-            if ask.quantity > 0.0 {
-                // Remove any existing ask at the same price and exchange
-                ask_set.retain(|a| !(a.price == ask.price && a.exchange == ask.exchange));
-                // Insert the new ask
-                ask_set.insert(ask);
-            } else {
-                // Remove ask if quantity is 0
-                ask_set.retain(|a| !(a.price == ask.price && a.exchange == ask.exchange));
-            }
+        let stats = self.stats.as_deref();
+        let mut ask_set = timed_lock(stats, self.asks.write()).await;
+        let received = asks.len();
+
+        let trimmed =
+            BTreeOrderBook::apply_levels(&mut ask_set, asks, max_depth, |ask| ask.quantity);
+        if let Some(stats) = stats {
+            stats.record_updates(received);
+            stats.record_trimmed(trimmed);
         }
 
-        // Trim to max depth
-        if ask_set.len() > max_depth {
-            let mut new_asks = BTreeSet::new();
-            for ask in ask_set.iter().take(max_depth) {
-                new_asks.insert(ask.clone());
-            }
-            *ask_set = new_asks;
+        Ok(())
+    }
+
+    async fn apply_update(&mut self, update: &PriceLevelUpdate, max_depth: usize) -> Result<()> {
+        validate_bids(&update.bids)?;
+        validate_asks(&update.asks)?;
+        let bids = tick::round_bids(update.bids.clone(), self.tick_size);
+        let asks = tick::round_asks(update.asks.clone(), self.tick_size);
+
+        // Both sides are locked before either changes, in the same order as `clear`
+        let stats = self.stats.as_deref();
+        let (mut bid_set, mut ask_set) = timed_lock(stats, async {
+            (self.bids.write().await, self.asks.write().await)
+        })
+        .await;
+
+        let received = bids.len() + asks.len();
+        let trimmed =
+            BTreeOrderBook::apply_levels(&mut bid_set, bids, max_depth, |bid| bid.quantity)
+                + BTreeOrderBook::apply_levels(&mut ask_set, asks, max_depth, |ask| ask.quantity);
+        if let Some(stats) = stats {
+            stats.record_updates(received);
+            stats.record_trimmed(trimmed);
         }
+
+        Ok(())
     }
 
     async fn get_best_bid(&self) -> Option<Bid> {
@@ -236,9 +285,10 @@ impl OrderBook for BTreeOrderBook {
     }
 
     async fn get_spread(&self) -> Option<f64> {
-        let best_bid = self.get_best_bid().await?;
-        let best_ask = self.get_best_ask().await?;
-        Some(best_ask.price - best_bid.price)
+        // Hold both sides so the spread never mixes states from before and after an update
+        let bids = self.bids.read().await;
+        let asks = self.asks.read().await;
+        Some(asks.first()?.price - bids.first()?.price)
     }
 
     async fn clear(&mut self) {
@@ -257,6 +307,10 @@ impl OrderBook for BTreeOrderBook {
         let asks = self.asks.read().await;
         asks.len()
     }
+
+    fn stats(&self) -> Option<BookStats> {
+        self.stats.as_ref().map(|stats| stats.snapshot())
+    }
 }
 
 /// BTreeSet-based bid side implementation
@@ -288,25 +342,13 @@ impl BTreeBidSide {
 
 #[async_trait]
 impl BuySide for BTreeBidSide {
-    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) {
-        let mut bid_set = self.bids.write().await;
+    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) -> Result<()> {
+        validate_bids(&bids)?;
 
-        for bid in bids {
-            if bid.quantity > 0.0 {
-                bid_set.retain(|b| !(b.price == bid.price && b.exchange == bid.exchange));
-                bid_set.insert(bid);
-            } else {
-                bid_set.retain(|b| !(b.price == bid.price && b.exchange == bid.exchange));
-            }
-        }
+        let mut bid_set = self.bids.write().await;
+        BTreeOrderBook::apply_levels(&mut bid_set, bids, max_depth, |bid| bid.quantity);
 
-        if bid_set.len() > max_depth {
-            let mut new_bids = BTreeSet::new();
-            for bid in bid_set.iter().take(max_depth) {
-                new_bids.insert(bid.clone());
-            }
-            *bid_set = new_bids;
-        }
+        Ok(())
     }
 
     async fn get_best_bid(&self) -> Option<Bid> {
@@ -359,25 +401,13 @@ impl BTreeAskSide {
 
 #[async_trait]
 impl SellSide for BTreeAskSide {
-    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) {
-        let mut ask_set = self.asks.write().await;
+    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) -> Result<()> {
+        validate_asks(&asks)?;
 
-        for ask in asks {
-            if ask.quantity > 0.0 {
-                ask_set.retain(|a| !(a.price == ask.price && a.exchange == ask.exchange));
-                ask_set.insert(ask);
-            } else {
-                ask_set.retain(|a| !(a.price == ask.price && a.exchange == ask.exchange));
-            }
-        }
+        let mut ask_set = self.asks.write().await;
+        BTreeOrderBook::apply_levels(&mut ask_set, asks, max_depth, |ask| ask.quantity);
 
-        if ask_set.len() > max_depth {
-            let mut new_asks = BTreeSet::new();
-            for ask in ask_set.iter().take(max_depth) {
-                new_asks.insert(ask.clone());
-            }
-            *ask_set = new_asks;
-        }
+        Ok(())
     }
 
     async fn get_best_ask(&self) -> Option<Ask> {
@@ -404,6 +434,7 @@ impl SellSide for BTreeAskSide {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aggregator_core::Exchange;
     use chrono::Utc;
 
     #[tokio::test]
@@ -426,7 +457,8 @@ mod tests {
 
         orderbook
             .update_bids(vec![bid1.clone(), bid2.clone()], 10)
-            .await;
+            .await
+            .unwrap();
 
         // Test best bid (should be highest price)
         let best_bid = orderbook.get_best_bid().await.unwrap();
@@ -448,7 +480,8 @@ mod tests {
 
         orderbook
             .update_asks(vec![ask1.clone(), ask2.clone()], 10)
-            .await;
+            .await
+            .unwrap();
 
         // Test best ask (should be lowest price)
         let best_ask = orderbook.get_best_ask().await.unwrap();
@@ -474,7 +507,7 @@ mod tests {
             exchange: Exchange::Binance,
             timestamp: Utc::now(),
         };
-        orderbook.update_bids(vec![bid1.clone()], 10).await;
+        orderbook.update_bids(vec![bid1.clone()], 10).await.unwrap();
 
         // Update with new quantity at same price
         let bid2 = Bid {
@@ -483,7 +516,7 @@ mod tests {
             exchange: Exchange::Binance,
             timestamp: Utc::now(),
         };
-        orderbook.update_bids(vec![bid2.clone()], 10).await;
+        orderbook.update_bids(vec![bid2.clone()], 10).await.unwrap();
 
         // Should have only one bid with updated quantity
         assert_eq!(orderbook.bid_depth().await, 1);
@@ -502,7 +535,7 @@ mod tests {
             exchange: Exchange::Binance,
             timestamp: Utc::now(),
         };
-        orderbook.update_bids(vec![bid1.clone()], 10).await;
+        orderbook.update_bids(vec![bid1.clone()], 10).await.unwrap();
         assert_eq!(orderbook.bid_depth().await, 1);
 
         // Remove by setting quantity to 0
@@ -512,7 +545,7 @@ mod tests {
             exchange: Exchange::Binance,
             timestamp: Utc::now(),
         };
-        orderbook.update_bids(vec![bid2.clone()], 10).await;
+        orderbook.update_bids(vec![bid2.clone()], 10).await.unwrap();
 
         // Should have no bids
         assert_eq!(orderbook.bid_depth().await, 0);
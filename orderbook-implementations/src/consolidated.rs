@@ -0,0 +1,441 @@
+//! # Consolidated Cross-Exchange Order Book
+//!
+//! This module merges the books of one symbol across several exchanges into a single ladder.
+//! Each exchange keeps its own book, built with any `OrderBook` implementation, and reads merge
+//! them best price first while keeping track of which exchange quotes what.
+//!
+//! ## Use Cases
+//!
+//! - Producing the top-N mixed-venue `Summary` for a symbol
+//! - Finding where the best price on each side is quoted
+//! - Measuring how much liquidity all exchanges show at a price
+//! - Alerting when a feed crosses its own book or two exchanges cross each other
+
+use crate::crossing::{find_crossings, CrossedBookEvent, CrossingKey};
+use crate::validation::{validate_asks, validate_bids};
+use crate::{BTreeOrderBook, OrderBook};
+use aggregator_core::{
+    Ask, Bid, Exchange, MarketType, PriceLevel, PriceLevelUpdate, Result, Summary,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// One price of the consolidated ladder with the exchanges quoting it
+///
+/// `venues` lists each exchange's quantity at this price, ordered by exchange.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsolidatedLevel {
+    pub price: f64,
+    pub total_quantity: f64,
+    pub venues: Vec<(Exchange, f64)>,
+}
+
+/// Order book for one symbol merged across exchanges
+///
+/// Updates are routed to a per-exchange book of type `B`, trimmed to `max_depth` levels per side
+/// like any single-exchange book. Reads merge the per-exchange books: bids highest price first,
+/// asks lowest price first, and entries at the same price ordered by exchange.
+///
+/// # Examples
+///
+/// ```rust
+/// use orderbook_implementations::ConsolidatedOrderBook;
+/// use aggregator_core::{Bid, Exchange};
+/// use chrono::Utc;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut book: ConsolidatedOrderBook = ConsolidatedOrderBook::new("BTCUSDT", 50);
+///
+///     for (exchange, price) in [(Exchange::Binance, 100.0), (Exchange::Kraken, 100.5)] {
+///         let bid = Bid {
+///             price,
+///             quantity: 1.0,
+///             exchange,
+///             timestamp: Utc::now(),
+///         };
+///         book.update_bids(bid.exchange.clone(), vec![bid]).await.unwrap();
+///     }
+///
+///     let summary = book.summary(10).await;
+///     assert_eq!(summary.bids[0].exchange, Exchange::Kraken);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConsolidatedOrderBook<B: OrderBook + Default = BTreeOrderBook> {
+    symbol: String,
+    max_depth: usize,
+    books: HashMap<Exchange, B>,
+    market_type: Option<MarketType>,
+    last_update: Option<DateTime<Utc>>,
+    crossing_tx: Option<broadcast::Sender<CrossedBookEvent>>,
+    active_crossings: HashSet<CrossingKey>,
+}
+
+impl<B: OrderBook + Default> ConsolidatedOrderBook<B> {
+    /// Creates an empty consolidated book for `symbol`, keeping at most `max_depth` levels per
+    /// side of each exchange's book
+    pub fn new(symbol: &str, max_depth: usize) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            max_depth,
+            books: HashMap::new(),
+            market_type: None,
+            last_update: None,
+            crossing_tx: None,
+            active_crossings: HashSet::new(),
+        }
+    }
+
+    /// Publishes a `CrossedBookEvent` whenever an update leaves an exchange's book, or the
+    /// consolidated book across two exchanges, crossed or locked
+    ///
+    /// Each crossing is reported once when it starts; it is reported again only after it has
+    /// cleared and recurred.
+    pub fn with_crossing_events(
+        mut self,
+        crossing_tx: broadcast::Sender<CrossedBookEvent>,
+    ) -> Self {
+        self.crossing_tx = Some(crossing_tx);
+        self
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Exchanges that have contributed to the book, in exchange order
+    pub fn exchanges(&self) -> Vec<Exchange> {
+        let mut exchanges: Vec<Exchange> = self.books.keys().cloned().collect();
+        exchanges.sort();
+        exchanges
+    }
+
+    /// The book of a single exchange, if it has contributed
+    pub fn exchange_book(&self, exchange: &Exchange) -> Option<&B> {
+        self.books.get(exchange)
+    }
+
+    fn book_mut(&mut self, exchange: Exchange) -> &mut B {
+        self.books.entry(exchange).or_default()
+    }
+
+    /// Applies one exchange's price level update to that exchange's book
+    ///
+    /// Levels are routed by the exchange of the update, so levels tagged with another exchange
+    /// cannot leak into the wrong book. Both sides go through `OrderBook::apply_update`, and the
+    /// whole update is rejected if either side fails validation.
+    pub async fn apply_update(&mut self, update: &PriceLevelUpdate) -> Result<()> {
+        validate_bids(&update.bids)?;
+        validate_asks(&update.asks)?;
+
+        let max_depth = self.max_depth;
+        let mut routed = update.clone();
+        for bid in &mut routed.bids {
+            bid.exchange = update.exchange.clone();
+        }
+        for ask in &mut routed.asks {
+            ask.exchange = update.exchange.clone();
+        }
+
+        self.book_mut(update.exchange.clone())
+            .apply_update(&routed, max_depth)
+            .await?;
+
+        if update.market_type.is_some() {
+            self.market_type = update.market_type.clone();
+        }
+        self.last_update = Some(update.timestamp);
+        self.notify_crossings().await;
+        Ok(())
+    }
+
+    /// Updates the bid side of `exchange`'s book
+    pub async fn update_bids(&mut self, exchange: Exchange, bids: Vec<Bid>) -> Result<()> {
+        let max_depth = self.max_depth;
+        self.book_mut(exchange).update_bids(bids, max_depth).await?;
+        self.last_update = Some(Utc::now());
+        self.notify_crossings().await;
+        Ok(())
+    }
+
+    /// Updates the ask side of `exchange`'s book
+    pub async fn update_asks(&mut self, exchange: Exchange, asks: Vec<Ask>) -> Result<()> {
+        let max_depth = self.max_depth;
+        self.book_mut(exchange).update_asks(asks, max_depth).await?;
+        self.last_update = Some(Utc::now());
+        self.notify_crossings().await;
+        Ok(())
+    }
+
+    /// Drops the book of `exchange`, e.g. after its feed disconnected
+    pub fn remove_exchange(&mut self, exchange: &Exchange) -> Option<B> {
+        self.active_crossings
+            .retain(|(_, bid_exchange, ask_exchange)| {
+                bid_exchange != exchange && ask_exchange != exchange
+            });
+        self.books.remove(exchange)
+    }
+
+    /// Removes levels last updated more than `max_age` ago from every exchange's book
+    ///
+    /// Exchanges left with an empty book are dropped, so a venue that disconnected disappears
+    /// from the ladder once all of its levels have expired.
+    ///
+    /// # Returns
+    ///
+    /// The number of bid and ask entries removed across all exchanges
+    pub async fn purge_older_than(&mut self, max_age: Duration) -> Result<usize> {
+        let mut removed = 0;
+        let mut emptied = Vec::new();
+        for (exchange, book) in self.books.iter_mut() {
+            removed += book.purge_older_than(max_age).await?;
+            if book.bid_depth().await == 0 && book.ask_depth().await == 0 {
+                emptied.push(exchange.clone());
+            }
+        }
+        for exchange in emptied {
+            self.books.remove(&exchange);
+        }
+        self.notify_crossings().await;
+        Ok(removed)
+    }
+
+    /// Every crossed or locked book right now, within single exchanges and across pairs of
+    /// exchanges
+    ///
+    /// An exchange whose own book is crossed is only reported as a `CrossingKind::Venue`
+    /// crossing; its prices are not compared with other exchanges.
+    pub async fn crossings(&self) -> Vec<CrossedBookEvent> {
+        find_crossings(
+            &self.symbol,
+            &self.best_bid_by_exchange().await,
+            &self.best_ask_by_exchange().await,
+        )
+    }
+
+    /// Publishes the crossings that started since the last check
+    async fn notify_crossings(&mut self) {
+        let Some(crossing_tx) = &self.crossing_tx else {
+            return;
+        };
+
+        let crossings = self.crossings().await;
+        let active: HashSet<CrossingKey> = crossings.iter().map(|event| event.key()).collect();
+        for event in crossings {
+            if !self.active_crossings.contains(&event.key()) {
+                // No subscribers is fine; the event is simply dropped
+                let _ = crossing_tx.send(event);
+            }
+        }
+        self.active_crossings = active;
+    }
+
+    /// The best `n` bids across all exchanges, highest price first
+    pub async fn get_best_n_bids(&self, n: usize) -> Vec<Bid> {
+        let mut bids = Vec::new();
+        for book in self.books.values() {
+            bids.extend(book.get_best_n_bids(n).await);
+        }
+        bids.sort();
+        bids.truncate(n);
+        bids
+    }
+
+    /// The best `n` asks across all exchanges, lowest price first
+    pub async fn get_best_n_asks(&self, n: usize) -> Vec<Ask> {
+        let mut asks = Vec::new();
+        for book in self.books.values() {
+            asks.extend(book.get_best_n_asks(n).await);
+        }
+        asks.sort();
+        asks.truncate(n);
+        asks
+    }
+
+    /// The best bid across all exchanges
+    pub async fn get_best_bid(&self) -> Option<Bid> {
+        self.get_best_n_bids(1).await.pop()
+    }
+
+    /// The best ask across all exchanges
+    pub async fn get_best_ask(&self) -> Option<Ask> {
+        self.get_best_n_asks(1).await.pop()
+    }
+
+    /// Best ask minus best bid across exchanges; negative when one exchange's bid crosses
+    /// another's ask
+    pub async fn get_spread(&self) -> Option<f64> {
+        let best_bid = self.get_best_bid().await?;
+        let best_ask = self.get_best_ask().await?;
+        Some(best_ask.price - best_bid.price)
+    }
+
+    /// The best bid of every exchange with bids
+    pub async fn best_bid_by_exchange(&self) -> HashMap<Exchange, Bid> {
+        let mut best = HashMap::new();
+        for (exchange, book) in &self.books {
+            if let Some(bid) = book.get_best_bid().await {
+                best.insert(exchange.clone(), bid);
+            }
+        }
+        best
+    }
+
+    /// The best ask of every exchange with asks
+    pub async fn best_ask_by_exchange(&self) -> HashMap<Exchange, Ask> {
+        let mut best = HashMap::new();
+        for (exchange, book) in &self.books {
+            if let Some(ask) = book.get_best_ask().await {
+                best.insert(exchange.clone(), ask);
+            }
+        }
+        best
+    }
+
+    /// Total quantity quoted at exactly `price` across all exchanges and both sides
+    ///
+    /// Outside a crossed market a price is only quoted on one side, so this is the bid or the ask
+    /// liquidity at that price.
+    pub async fn total_liquidity_at(&self, price: f64) -> f64 {
+        let mut total = 0.0;
+        for book in self.books.values() {
+            let bids = book.get_best_n_bids(self.max_depth).await;
+            let asks = book.get_best_n_asks(self.max_depth).await;
+            total += bids
+                .iter()
+                .filter(|bid| bid.price == price)
+                .map(|bid| bid.quantity)
+                .sum::<f64>();
+            total += asks
+                .iter()
+                .filter(|ask| ask.price == price)
+                .map(|ask| ask.quantity)
+                .sum::<f64>();
+        }
+        total
+    }
+
+    /// The best `n` bid prices, each with the exchanges quoting it
+    pub async fn bid_ladder(&self, n: usize) -> Vec<ConsolidatedLevel> {
+        let bids = self
+            .get_best_n_bids(self.max_depth * self.books.len())
+            .await;
+        Self::ladder(
+            bids.into_iter()
+                .map(|bid| (bid.price, bid.exchange, bid.quantity)),
+            n,
+        )
+    }
+
+    /// The best `n` ask prices, each with the exchanges quoting it
+    pub async fn ask_ladder(&self, n: usize) -> Vec<ConsolidatedLevel> {
+        let asks = self
+            .get_best_n_asks(self.max_depth * self.books.len())
+            .await;
+        Self::ladder(
+            asks.into_iter()
+                .map(|ask| (ask.price, ask.exchange, ask.quantity)),
+            n,
+        )
+    }
+
+    /// Groups entries already sorted best first into at most `n` price levels
+    fn ladder(
+        entries: impl Iterator<Item = (f64, Exchange, f64)>,
+        n: usize,
+    ) -> Vec<ConsolidatedLevel> {
+        let mut levels: Vec<ConsolidatedLevel> = Vec::new();
+        for (price, exchange, quantity) in entries {
+            match levels.last_mut() {
+                Some(level) if level.price == price => {
+                    level.total_quantity += quantity;
+                    level.venues.push((exchange, quantity));
+                }
+                _ => {
+                    if levels.len() == n {
+                        break;
+                    }
+                    levels.push(ConsolidatedLevel {
+                        price,
+                        total_quantity: quantity,
+                        venues: vec![(exchange, quantity)],
+                    });
+                }
+            }
+        }
+        levels
+    }
+
+    /// Builds the top-`n` mixed-venue summary: the best `n` bid and ask entries across exchanges,
+    /// each attributed to the exchange quoting it
+    ///
+    /// The spread is `0.0` while either side is empty, matching the summaries the aggregator
+    /// publishes.
+    pub async fn summary(&self, n: usize) -> Summary {
+        let bids = self.get_best_n_bids(n).await;
+        let asks = self.get_best_n_asks(n).await;
+        self.to_summary(bids, asks, self.last_update.unwrap_or_else(Utc::now))
+    }
+
+    /// The best `n` levels per side of `exchange`'s book alone, stamped with the newest of
+    /// those levels, if the exchange has contributed
+    pub async fn exchange_summary(&self, exchange: &Exchange, n: usize) -> Option<Summary> {
+        let book = self.books.get(exchange)?;
+        let bids = book.get_best_n_bids(n).await;
+        let asks = book.get_best_n_asks(n).await;
+        let timestamp = bids
+            .iter()
+            .map(|bid| bid.timestamp)
+            .chain(asks.iter().map(|ask| ask.timestamp))
+            .max()
+            .or(self.last_update)
+            .unwrap_or_else(Utc::now);
+        Some(self.to_summary(bids, asks, timestamp))
+    }
+
+    fn to_summary(&self, bids: Vec<Bid>, asks: Vec<Ask>, timestamp: DateTime<Utc>) -> Summary {
+        let bids: Vec<PriceLevel> = bids
+            .into_iter()
+            .map(|bid| PriceLevel {
+                price: bid.price,
+                quantity: bid.quantity,
+                exchange: bid.exchange,
+                timestamp: bid.timestamp,
+            })
+            .collect();
+        let asks: Vec<PriceLevel> = asks
+            .into_iter()
+            .map(|ask| PriceLevel {
+                price: ask.price,
+                quantity: ask.quantity,
+                exchange: ask.exchange,
+                timestamp: ask.timestamp,
+            })
+            .collect();
+
+        let spread = match (bids.first(), asks.first()) {
+            (Some(best_bid), Some(best_ask)) => best_ask.price - best_bid.price,
+            _ => 0.0,
+        };
+
+        Summary {
+            symbol: self.symbol.clone(),
+            spread,
+            bids,
+            asks,
+            timestamp,
+            market_type: self.market_type.clone(),
+        }
+    }
+
+    /// Clears every exchange's book
+    pub fn clear(&mut self) {
+        self.books.clear();
+        self.active_crossings.clear();
+        self.market_type = None;
+        self.last_update = None;
+    }
+}
@@ -0,0 +1,262 @@
+//! # Copy-on-Write Order Book Implementation
+//!
+//! This module provides an order book whose sides are immutable sorted vectors published together
+//! through `arc-swap`. Readers load the current version without taking a lock, so they never wait
+//! for a writer and a writer never waits for readers; a writer copies the side it changes, applies
+//! the update and swaps the new version in. Both sides belong to the same version, so a reader
+//! never sees only half of an `apply_update`.
+//!
+//! ## Performance Characteristics
+//!
+//! - **Insertion**: O(n) - The updated side is copied once per batch
+//! - **Best Price**: O(1) - First element of the current version
+//! - **Range Queries**: O(k) - Slice of the current version
+//! - **Memory**: Old versions live until their last reader drops them
+//!
+//! ## Use Cases
+//!
+//! - Server fan-out where many readers poll the same book while it is updated
+//! - Latency-sensitive reads that must not queue behind writers
+//!
+//! ## Thread Safety
+//!
+//! Clones share the same book. Concurrent writers through different clones are applied one after
+//! the other with read-copy-update, so no update is lost.
+
+use crate::stats::BookStatsRecorder;
+use crate::tick;
+use crate::validation::{validate_asks, validate_bids};
+use crate::OrderBook;
+use aggregator_core::{Ask, Bid, BookStats, InstrumentInfo, PriceLevelUpdate, Result};
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// One published version of both sides of the book
+#[derive(Debug, Default)]
+struct Sides {
+    /// Bid orders sorted by price descending (highest first)
+    bids: Arc<Vec<Bid>>,
+    /// Ask orders sorted by price ascending (lowest first)
+    asks: Arc<Vec<Ask>>,
+}
+
+/// Copy-on-write order book implementation
+///
+/// Each side is a vector sorted best first with the same `(price, exchange)` ordering as
+/// `BTreeOrderBook`, so both implementations return identical results.
+///
+/// # Examples
+///
+/// ```rust
+/// use orderbook_implementations::{CowOrderBook, OrderBook};
+/// use aggregator_core::{Bid, Exchange};
+/// use chrono::Utc;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut orderbook = CowOrderBook::new();
+///     let reader = orderbook.clone();
+///
+///     let bid = Bid {
+///         price: 100.0,
+///         quantity: 10.0,
+///         exchange: Exchange::Binance,
+///         timestamp: Utc::now(),
+///     };
+///     orderbook.update_bids(vec![bid], 100).await.unwrap();
+///
+///     assert_eq!(reader.load_bids()[0].price, 100.0);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CowOrderBook {
+    /// Current version of both sides; a side that an update leaves unchanged is shared with
+    /// the previous version
+    sides: Arc<ArcSwap<Sides>>,
+    /// Activity counters, when enabled with `with_stats`; there is no lock to time
+    stats: Option<Arc<BookStatsRecorder>>,
+    /// Tick size incoming prices are rounded to, when set with `with_tick_size`
+    tick_size: Option<f64>,
+}
+
+impl CowOrderBook {
+    /// Creates a new empty copy-on-write order book
+    pub fn new() -> Self {
+        Self {
+            sides: Arc::new(ArcSwap::from_pointee(Sides::default())),
+            stats: None,
+            tick_size: None,
+        }
+    }
+
+    /// Enables activity counters, read back through `OrderBook::stats`
+    ///
+    /// Clones made afterwards share the same counters.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(BookStatsRecorder::new()));
+        self
+    }
+
+    /// Rounds every incoming price to the nearest multiple of `tick_size`
+    ///
+    /// A tick size that is not positive and finite leaves prices unchanged.
+    pub fn with_tick_size(mut self, tick_size: f64) -> Self {
+        self.tick_size = tick::checked_tick_size(tick_size);
+        self
+    }
+
+    /// Rounds every incoming price to the tick size of `instrument`
+    pub fn with_instrument(self, instrument: &InstrumentInfo) -> Self {
+        self.with_tick_size(instrument.tick_size)
+    }
+
+    /// The current bids, best first, as one consistent version
+    ///
+    /// The returned vector is never modified; later updates publish a new one.
+    pub fn load_bids(&self) -> Arc<Vec<Bid>> {
+        self.sides.load().bids.clone()
+    }
+
+    /// The current asks, best first, as one consistent version
+    ///
+    /// The returned vector is never modified; later updates publish a new one.
+    pub fn load_asks(&self) -> Arc<Vec<Ask>> {
+        self.sides.load().asks.clone()
+    }
+
+    /// Applies `updates` to a copy of `levels`: positive quantities replace the entry with the
+    /// same `(price, exchange)` and zero quantities remove it, then the copy is cut to
+    /// `max_depth`, returning the copy and the number of levels cut
+    fn apply<T: Ord + Clone>(
+        levels: &[T],
+        updates: &[T],
+        max_depth: usize,
+        quantity: impl Fn(&T) -> f64,
+    ) -> (Vec<T>, usize) {
+        let mut next = levels.to_vec();
+        for update in updates {
+            match next.binary_search(update) {
+                Ok(index) if quantity(update) > 0.0 => next[index] = update.clone(),
+                Ok(index) => {
+                    next.remove(index);
+                }
+                Err(index) if quantity(update) > 0.0 => next.insert(index, update.clone()),
+                Err(_) => {}
+            }
+        }
+        let trimmed = next.len().saturating_sub(max_depth);
+        next.truncate(max_depth);
+        (next, trimmed)
+    }
+}
+
+impl Default for CowOrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OrderBook for CowOrderBook {
+    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) -> Result<()> {
+        validate_bids(&bids)?;
+        let bids = tick::round_bids(bids, self.tick_size);
+        let mut trimmed = 0;
+        self.sides.rcu(|current| {
+            let (next, cut) =
+                CowOrderBook::apply(&current.bids, &bids, max_depth, |bid: &Bid| bid.quantity);
+            trimmed = cut;
+            Sides {
+                bids: Arc::new(next),
+                asks: current.asks.clone(),
+            }
+        });
+        if let Some(stats) = &self.stats {
+            stats.record_updates(bids.len());
+            stats.record_trimmed(trimmed);
+        }
+        Ok(())
+    }
+
+    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) -> Result<()> {
+        validate_asks(&asks)?;
+        let asks = tick::round_asks(asks, self.tick_size);
+        let mut trimmed = 0;
+        self.sides.rcu(|current| {
+            let (next, cut) =
+                CowOrderBook::apply(&current.asks, &asks, max_depth, |ask: &Ask| ask.quantity);
+            trimmed = cut;
+            Sides {
+                bids: current.bids.clone(),
+                asks: Arc::new(next),
+            }
+        });
+        if let Some(stats) = &self.stats {
+            stats.record_updates(asks.len());
+            stats.record_trimmed(trimmed);
+        }
+        Ok(())
+    }
+
+    async fn apply_update(&mut self, update: &PriceLevelUpdate, max_depth: usize) -> Result<()> {
+        validate_bids(&update.bids)?;
+        validate_asks(&update.asks)?;
+        let bids = tick::round_bids(update.bids.clone(), self.tick_size);
+        let asks = tick::round_asks(update.asks.clone(), self.tick_size);
+        let mut trimmed = 0;
+        self.sides.rcu(|current| {
+            let (next_bids, bids_cut) =
+                CowOrderBook::apply(&current.bids, &bids, max_depth, |bid: &Bid| bid.quantity);
+            let (next_asks, asks_cut) =
+                CowOrderBook::apply(&current.asks, &asks, max_depth, |ask: &Ask| ask.quantity);
+            trimmed = bids_cut + asks_cut;
+            Sides {
+                bids: Arc::new(next_bids),
+                asks: Arc::new(next_asks),
+            }
+        });
+        if let Some(stats) = &self.stats {
+            stats.record_updates(bids.len() + asks.len());
+            stats.record_trimmed(trimmed);
+        }
+        Ok(())
+    }
+
+    async fn get_best_bid(&self) -> Option<Bid> {
+        self.sides.load().bids.first().cloned()
+    }
+
+    async fn get_best_ask(&self) -> Option<Ask> {
+        self.sides.load().asks.first().cloned()
+    }
+
+    async fn get_best_n_bids(&self, n: usize) -> Vec<Bid> {
+        self.sides.load().bids.iter().take(n).cloned().collect()
+    }
+
+    async fn get_best_n_asks(&self, n: usize) -> Vec<Ask> {
+        self.sides.load().asks.iter().take(n).cloned().collect()
+    }
+
+    async fn get_spread(&self) -> Option<f64> {
+        let sides = self.sides.load();
+        Some(sides.asks.first()?.price - sides.bids.first()?.price)
+    }
+
+    async fn clear(&mut self) {
+        self.sides.store(Arc::new(Sides::default()));
+    }
+
+    async fn bid_depth(&self) -> usize {
+        self.sides.load().bids.len()
+    }
+
+    async fn ask_depth(&self) -> usize {
+        self.sides.load().asks.len()
+    }
+
+    fn stats(&self) -> Option<BookStats> {
+        self.stats.as_ref().map(|stats| stats.snapshot())
+    }
+}
@@ -0,0 +1,112 @@
+//! Crossed and locked book detection
+//!
+//! A book is crossed when its best bid is above its best ask and locked when they are equal.
+//! Inside one exchange's book that can only happen when the feed is corrupt or out of sync,
+//! while a bid on one exchange meeting an ask on another is a genuine arbitrage. The two cases
+//! are reported as different kinds so operators can alert on them separately.
+
+use aggregator_core::{Ask, Bid, Exchange};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Where a crossing was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CrossingKind {
+    /// One exchange's own book is crossed or locked, a data error in its feed
+    Venue,
+    /// The best bid of one exchange meets or exceeds the best ask of another, an arbitrage
+    CrossVenue,
+}
+
+/// A crossed or locked book
+///
+/// For `CrossingKind::Venue` both sides come from the same exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossedBookEvent {
+    pub symbol: String,
+    pub kind: CrossingKind,
+    pub best_bid: Bid,
+    pub best_ask: Ask,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl CrossedBookEvent {
+    /// Whether the bid and ask are at the same price rather than strictly crossed
+    pub fn is_locked(&self) -> bool {
+        self.best_bid.price == self.best_ask.price
+    }
+
+    /// How far the bid is above the ask; zero for a locked book
+    pub fn overlap(&self) -> f64 {
+        self.best_bid.price - self.best_ask.price
+    }
+
+    /// Identifies an ongoing crossing so it is only reported when it starts
+    pub(crate) fn key(&self) -> CrossingKey {
+        (
+            self.kind,
+            self.best_bid.exchange.clone(),
+            self.best_ask.exchange.clone(),
+        )
+    }
+}
+
+/// Kind, bid exchange and ask exchange of a crossing
+pub(crate) type CrossingKey = (CrossingKind, Exchange, Exchange);
+
+/// Finds every crossing among the best bid and ask of each exchange
+///
+/// Exchanges whose own book is crossed are reported as venue crossings and left out of the
+/// cross-venue comparison, so a corrupt feed is not mistaken for an arbitrage. Events are
+/// ordered by bid exchange, then ask exchange.
+pub(crate) fn find_crossings(
+    symbol: &str,
+    best_bids: &HashMap<Exchange, Bid>,
+    best_asks: &HashMap<Exchange, Ask>,
+) -> Vec<CrossedBookEvent> {
+    let timestamp = Utc::now();
+    let event = |kind, bid: &Bid, ask: &Ask| CrossedBookEvent {
+        symbol: symbol.to_string(),
+        kind,
+        best_bid: bid.clone(),
+        best_ask: ask.clone(),
+        timestamp,
+    };
+
+    let mut exchanges: Vec<&Exchange> = best_bids.keys().collect();
+    exchanges.sort();
+
+    let mut events = Vec::new();
+    let mut healthy = Vec::new();
+    for exchange in exchanges {
+        let bid = &best_bids[exchange];
+        match best_asks.get(exchange) {
+            Some(ask) if bid.price >= ask.price => {
+                events.push(event(CrossingKind::Venue, bid, ask));
+            }
+            _ => healthy.push(exchange),
+        }
+    }
+
+    for bid_exchange in &healthy {
+        let bid = &best_bids[*bid_exchange];
+        let mut asks: Vec<(&Exchange, &Ask)> = best_asks
+            .iter()
+            .filter(|(exchange, _)| exchange != bid_exchange)
+            .filter(|(exchange, ask)| {
+                let own_bid = best_bids.get(*exchange);
+                own_bid.is_none_or(|own| own.price < ask.price)
+            })
+            .collect();
+        asks.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (_, ask) in asks {
+            if bid.price >= ask.price {
+                events.push(event(CrossingKind::CrossVenue, bid, ask));
+            }
+        }
+    }
+
+    events
+}
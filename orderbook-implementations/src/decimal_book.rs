@@ -0,0 +1,284 @@
+//! Decimal-keyed order book implementation
+//! Stores exact decimal prices so levels match and sum without floating-point drift
+
+use crate::stats::{timed_lock, BookStatsRecorder};
+use crate::tick;
+use crate::validation::{validate_asks, validate_bids};
+use crate::OrderBook;
+use aggregator_core::decimal::{from_decimal, Decimal, DecimalLevel};
+use aggregator_core::{
+    AggregatorError, Ask, Bid, BookStats, Exchange, InstrumentInfo, PriceLevelUpdate, Result,
+};
+use async_trait::async_trait;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Bid keys sort highest price first, then by exchange
+type BidKey = (Reverse<Decimal>, Exchange);
+/// Ask keys sort lowest price first, then by exchange
+type AskKey = (Decimal, Exchange);
+
+/// Order book storing prices and quantities as `Decimal`
+///
+/// Levels arriving through the `OrderBook` trait are converted from `f64` on the way in, and
+/// converted back on the way out.
+/// Each side is keyed on (price, exchange) like the other implementations, so two exchanges
+/// quoting the same price keep separate levels.
+#[derive(Debug, Clone)]
+pub struct DecimalOrderBook {
+    bids: Arc<RwLock<BTreeMap<BidKey, DecimalLevel>>>,
+    asks: Arc<RwLock<BTreeMap<AskKey, DecimalLevel>>>,
+    stats: Option<Arc<BookStatsRecorder>>,
+    tick_size: Option<f64>,
+}
+
+impl DecimalOrderBook {
+    /// Create a new decimal order book
+    pub fn new() -> Self {
+        Self {
+            bids: Arc::new(RwLock::new(BTreeMap::new())),
+            asks: Arc::new(RwLock::new(BTreeMap::new())),
+            stats: None,
+            tick_size: None,
+        }
+    }
+
+    /// Enables activity counters, read back through `OrderBook::stats`
+    ///
+    /// Clones made afterwards share the same counters.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(BookStatsRecorder::new()));
+        self
+    }
+
+    /// Rounds every price arriving through the `OrderBook` trait to the nearest multiple of
+    /// `tick_size` before converting it
+    ///
+    /// Levels passed to `update_decimal_bids` and `update_decimal_asks` are exact already and
+    /// are not rounded. A tick size that is not positive and finite leaves prices unchanged.
+    pub fn with_tick_size(mut self, tick_size: f64) -> Self {
+        self.tick_size = tick::checked_tick_size(tick_size);
+        self
+    }
+
+    /// Rounds every price arriving through the `OrderBook` trait to the tick size of
+    /// `instrument`
+    pub fn with_instrument(self, instrument: &InstrumentInfo) -> Self {
+        self.with_tick_size(instrument.tick_size)
+    }
+
+    /// Applies decimal bid levels directly; a zero quantity removes the level
+    pub async fn update_decimal_bids(&mut self, bids: Vec<DecimalLevel>, max_depth: usize) {
+        let stats = self.stats.as_deref();
+        let mut bid_map = timed_lock(stats, self.bids.write()).await;
+        let received = bids.len();
+        let trimmed = Self::apply_bids(&mut bid_map, bids, max_depth);
+        if let Some(stats) = stats {
+            stats.record_updates(received);
+            stats.record_trimmed(trimmed);
+        }
+    }
+
+    /// Applies decimal ask levels directly; a zero quantity removes the level
+    pub async fn update_decimal_asks(&mut self, asks: Vec<DecimalLevel>, max_depth: usize) {
+        let stats = self.stats.as_deref();
+        let mut ask_map = timed_lock(stats, self.asks.write()).await;
+        let received = asks.len();
+        let trimmed = Self::apply_asks(&mut ask_map, asks, max_depth);
+        if let Some(stats) = stats {
+            stats.record_updates(received);
+            stats.record_trimmed(trimmed);
+        }
+    }
+
+    /// The best `n` bid levels, highest price first, without converting to `f64`
+    pub async fn best_n_decimal_bids(&self, n: usize) -> Vec<DecimalLevel> {
+        self.bids.read().await.values().take(n).cloned().collect()
+    }
+
+    /// The best `n` ask levels, lowest price first, without converting to `f64`
+    pub async fn best_n_decimal_asks(&self, n: usize) -> Vec<DecimalLevel> {
+        self.asks.read().await.values().take(n).cloned().collect()
+    }
+
+    /// Exact total bid quantity at `price` across exchanges
+    pub async fn bid_quantity_at(&self, price: Decimal) -> Decimal {
+        self.bids
+            .read()
+            .await
+            .values()
+            .filter(|level| level.price == price)
+            .map(|level| level.quantity)
+            .sum()
+    }
+
+    /// Exact total ask quantity at `price` across exchanges
+    pub async fn ask_quantity_at(&self, price: Decimal) -> Decimal {
+        self.asks
+            .read()
+            .await
+            .values()
+            .filter(|level| level.price == price)
+            .map(|level| level.quantity)
+            .sum()
+    }
+
+    /// Applies bid levels and trims to `max_depth`, returning how many levels were trimmed
+    fn apply_bids(
+        bid_map: &mut BTreeMap<BidKey, DecimalLevel>,
+        bids: Vec<DecimalLevel>,
+        max_depth: usize,
+    ) -> usize {
+        for bid in bids {
+            let key = (Reverse(bid.price), bid.exchange.clone());
+            if bid.quantity > Decimal::ZERO {
+                bid_map.insert(key, bid);
+            } else {
+                bid_map.remove(&key);
+            }
+        }
+        Self::trim(bid_map, max_depth)
+    }
+
+    /// Applies ask levels and trims to `max_depth`, returning how many levels were trimmed
+    fn apply_asks(
+        ask_map: &mut BTreeMap<AskKey, DecimalLevel>,
+        asks: Vec<DecimalLevel>,
+        max_depth: usize,
+    ) -> usize {
+        for ask in asks {
+            let key = (ask.price, ask.exchange.clone());
+            if ask.quantity > Decimal::ZERO {
+                ask_map.insert(key, ask);
+            } else {
+                ask_map.remove(&key);
+            }
+        }
+        Self::trim(ask_map, max_depth)
+    }
+
+    /// Drops every level past the best `max_depth`, returning how many were dropped
+    fn trim<K: Ord + Clone>(levels: &mut BTreeMap<K, DecimalLevel>, max_depth: usize) -> usize {
+        match levels.keys().nth(max_depth).cloned() {
+            Some(cutoff) => levels.split_off(&cutoff).len(),
+            None => 0,
+        }
+    }
+
+    /// Converts incoming levels, failing on the first that has no decimal representation
+    fn convert<'a, T>(levels: &'a [T]) -> Result<Vec<DecimalLevel>>
+    where
+        DecimalLevel: TryFrom<&'a T, Error = AggregatorError>,
+    {
+        levels.iter().map(DecimalLevel::try_from).collect()
+    }
+}
+
+impl Default for DecimalOrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OrderBook for DecimalOrderBook {
+    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) -> Result<()> {
+        validate_bids(&bids)?;
+        let bids = Self::convert(&tick::round_bids(bids, self.tick_size))?;
+        self.update_decimal_bids(bids, max_depth).await;
+        Ok(())
+    }
+
+    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) -> Result<()> {
+        validate_asks(&asks)?;
+        let asks = Self::convert(&tick::round_asks(asks, self.tick_size))?;
+        self.update_decimal_asks(asks, max_depth).await;
+        Ok(())
+    }
+
+    async fn apply_update(&mut self, update: &PriceLevelUpdate, max_depth: usize) -> Result<()> {
+        validate_bids(&update.bids)?;
+        validate_asks(&update.asks)?;
+        let bids = Self::convert(&tick::round_bids(update.bids.clone(), self.tick_size))?;
+        let asks = Self::convert(&tick::round_asks(update.asks.clone(), self.tick_size))?;
+
+        let stats = self.stats.as_deref();
+        let (mut bid_map, mut ask_map) = timed_lock(stats, async {
+            (self.bids.write().await, self.asks.write().await)
+        })
+        .await;
+        let received = bids.len() + asks.len();
+        let trimmed = Self::apply_bids(&mut bid_map, bids, max_depth)
+            + Self::apply_asks(&mut ask_map, asks, max_depth);
+        if let Some(stats) = stats {
+            stats.record_updates(received);
+            stats.record_trimmed(trimmed);
+        }
+        Ok(())
+    }
+
+    async fn get_best_bid(&self) -> Option<Bid> {
+        self.bids
+            .read()
+            .await
+            .values()
+            .next()
+            .map(DecimalLevel::to_bid)
+    }
+
+    async fn get_best_ask(&self) -> Option<Ask> {
+        self.asks
+            .read()
+            .await
+            .values()
+            .next()
+            .map(DecimalLevel::to_ask)
+    }
+
+    async fn get_best_n_bids(&self, n: usize) -> Vec<Bid> {
+        self.bids
+            .read()
+            .await
+            .values()
+            .take(n)
+            .map(DecimalLevel::to_bid)
+            .collect()
+    }
+
+    async fn get_best_n_asks(&self, n: usize) -> Vec<Ask> {
+        self.asks
+            .read()
+            .await
+            .values()
+            .take(n)
+            .map(DecimalLevel::to_ask)
+            .collect()
+    }
+
+    async fn get_spread(&self) -> Option<f64> {
+        let bids = self.bids.read().await;
+        let asks = self.asks.read().await;
+        let best_bid = bids.values().next()?;
+        let best_ask = asks.values().next()?;
+        Some(from_decimal(best_ask.price - best_bid.price))
+    }
+
+    async fn clear(&mut self) {
+        self.bids.write().await.clear();
+        self.asks.write().await.clear();
+    }
+
+    async fn bid_depth(&self) -> usize {
+        self.bids.read().await.len()
+    }
+
+    async fn ask_depth(&self) -> usize {
+        self.asks.read().await.len()
+    }
+
+    fn stats(&self) -> Option<BookStats> {
+        self.stats.as_ref().map(|stats| stats.snapshot())
+    }
+}
@@ -0,0 +1,100 @@
+//! Price-bucketed depth aggregation
+//!
+//! Groups the levels of an order book into fixed-width price buckets with running totals, the
+//! shape depth charts and liquidity heatmaps draw. Bid buckets are labelled with their lower
+//! bound and ask buckets with their upper bound, so a bid bucket and an ask bucket never share a
+//! price unless the book is crossed.
+
+use aggregator_core::{AggregatorError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Liquidity of one price bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthBucket {
+    /// Bucket boundary closest to the far side of the book
+    pub price: f64,
+    /// Quantity quoted inside the bucket
+    pub quantity: f64,
+    /// Quantity quoted from the best price through the end of this bucket
+    pub cumulative_quantity: f64,
+}
+
+/// Bucketed depth of both sides, best bucket first
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DepthBuckets {
+    pub bids: Vec<DepthBucket>,
+    pub asks: Vec<DepthBucket>,
+}
+
+/// Which way bucket boundaries round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BucketRounding {
+    /// Bids: a bucket covers `[price, price + size)`
+    Down,
+    /// Asks: a bucket covers `(price - size, price]`
+    Up,
+}
+
+/// Checks that `bucket_size` can split a price range into buckets
+pub(crate) fn validate_bucket_size(bucket_size: f64) -> Result<()> {
+    if !bucket_size.is_finite() || bucket_size <= 0.0 {
+        return Err(AggregatorError::validation(
+            "bucket_size",
+            format!("{} is not a positive finite number", bucket_size),
+        ));
+    }
+    Ok(())
+}
+
+/// Groups `(price, quantity)` levels, already sorted best first, into at most `n` buckets
+pub(crate) fn bucket_levels(
+    levels: impl IntoIterator<Item = (f64, f64)>,
+    bucket_size: f64,
+    n: usize,
+    rounding: BucketRounding,
+) -> Vec<DepthBucket> {
+    let mut buckets: Vec<(i64, DepthBucket)> = Vec::new();
+    let mut cumulative = 0.0;
+
+    for (price, quantity) in levels {
+        let index = bucket_index(price, bucket_size, rounding);
+        cumulative += quantity;
+        match buckets.last_mut() {
+            Some((last, bucket)) if *last == index => {
+                bucket.quantity += quantity;
+                bucket.cumulative_quantity = cumulative;
+            }
+            _ => {
+                if buckets.len() == n {
+                    break;
+                }
+                buckets.push((
+                    index,
+                    DepthBucket {
+                        price: index as f64 * bucket_size,
+                        quantity,
+                        cumulative_quantity: cumulative,
+                    },
+                ));
+            }
+        }
+    }
+
+    buckets.into_iter().map(|(_, bucket)| bucket).collect()
+}
+
+/// Index of the bucket holding `price`
+///
+/// Prices that sit on a boundary up to floating-point noise (100.3 / 0.1 = 1002.9999…) are
+/// snapped onto it before rounding.
+fn bucket_index(price: f64, bucket_size: f64, rounding: BucketRounding) -> i64 {
+    let ratio = price / bucket_size;
+    let nearest = ratio.round();
+    if (ratio - nearest).abs() <= 1e-9 * nearest.abs().max(1.0) {
+        return nearest as i64;
+    }
+    match rounding {
+        BucketRounding::Down => ratio.floor() as i64,
+        BucketRounding::Up => ratio.ceil() as i64,
+    }
+}
@@ -0,0 +1,91 @@
+//! # Order Book Factory
+//!
+//! This module connects the implementations of this crate to the aggregator. The aggregator
+//! keeps one consolidated book per trading pair through the `PairBook` trait of
+//! `aggregator-core`; [`OrderBookFactory`] builds those books as `ConsolidatedOrderBook`s over
+//! the implementation named by `OrderBookConfig::implementation`.
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use aggregator_core::{Aggregator, Config};
+//! use orderbook_implementations::OrderBookFactory;
+//! use std::sync::Arc;
+//!
+//! let config = Config::default();
+//! let factory = OrderBookFactory::new(config.orderbook.clone());
+//! let aggregator = Aggregator::new(config).with_book_factory(Arc::new(factory));
+//! ```
+
+use crate::{BTreeOrderBook, ConsolidatedOrderBook, CowOrderBook, HashMapOrderBook, OrderBook};
+use aggregator_core::{
+    Exchange, OrderBookConfig, OrderBookImplementation, PairBook, PairBookFactory,
+    PriceLevelUpdate, Result, Summary, TradingPair,
+};
+use async_trait::async_trait;
+use tracing::warn;
+
+#[async_trait]
+impl<B: OrderBook + Default + 'static> PairBook for ConsolidatedOrderBook<B> {
+    async fn apply_update(&mut self, update: &PriceLevelUpdate) -> Result<()> {
+        ConsolidatedOrderBook::apply_update(self, update).await
+    }
+
+    async fn summary(&self, depth: usize) -> Summary {
+        ConsolidatedOrderBook::summary(self, depth).await
+    }
+
+    async fn exchange_summary(&self, exchange: &Exchange, depth: usize) -> Option<Summary> {
+        ConsolidatedOrderBook::exchange_summary(self, exchange, depth).await
+    }
+
+    async fn remove_exchange(&mut self, exchange: &Exchange) {
+        ConsolidatedOrderBook::remove_exchange(self, exchange);
+    }
+}
+
+/// Creates the aggregator's per-pair books from an `OrderBookConfig`
+///
+/// Each book keeps at most `max_depth` levels per side of every exchange. `AvlTree` and
+/// `RbTree` are placeholders, and `Decimal` needs the `decimal` feature; those fall back to the
+/// BTreeSet implementation with a warning.
+#[derive(Debug, Clone)]
+pub struct OrderBookFactory {
+    config: OrderBookConfig,
+}
+
+impl OrderBookFactory {
+    pub fn new(config: OrderBookConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl PairBookFactory for OrderBookFactory {
+    fn create(&self, pair: &TradingPair, symbol: &str) -> Box<dyn PairBook> {
+        let max_depth = self.config.max_depth;
+        match self.config.implementation {
+            OrderBookImplementation::BTreeSet => Box::new(
+                ConsolidatedOrderBook::<BTreeOrderBook>::new(symbol, max_depth),
+            ),
+            OrderBookImplementation::HashMap => Box::new(
+                ConsolidatedOrderBook::<HashMapOrderBook>::new(symbol, max_depth),
+            ),
+            OrderBookImplementation::CopyOnWrite => Box::new(
+                ConsolidatedOrderBook::<CowOrderBook>::new(symbol, max_depth),
+            ),
+            #[cfg(feature = "decimal")]
+            OrderBookImplementation::Decimal => Box::new(ConsolidatedOrderBook::<
+                crate::DecimalOrderBook,
+            >::new(symbol, max_depth)),
+            ref unavailable => {
+                warn!(
+                    "{:?} order book is not available, using BTreeSet for {}",
+                    unavailable, pair
+                );
+                Box::new(ConsolidatedOrderBook::<BTreeOrderBook>::new(
+                    symbol, max_depth,
+                ))
+            }
+        }
+    }
+}
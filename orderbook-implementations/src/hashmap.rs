@@ -1,20 +1,40 @@
 //! HashMap-based order book implementation
 //! Optimized for fast lookups and updates
+//!
+//! Levels live in a `HashMap` keyed by `(price, exchange)`, so replacing the quantity of a known
+//! level is a single hash lookup. A sorted index of the same keys is kept alongside and updated
+//! only when a level is added or removed, which makes best-price and best-N queries O(log n + k)
+//! without sorting the book.
 
+use crate::stats::{timed_lock, BookStatsRecorder};
+use crate::tick;
+use crate::validation::{validate_asks, validate_bids};
 use crate::OrderBook;
-use aggregator_core::{Ask, Bid, Exchange};
+use aggregator_core::{Ask, Bid, BookStats, Exchange, InstrumentInfo, PriceLevelUpdate, Result};
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Key of one exchange's liquidity at one price: the price's bit pattern and the exchange
+type LevelKey = (u64, Exchange);
+
+/// Bid index entry, ordered best first: highest price, then exchange
+type BidIndexKey = (Reverse<i64>, Exchange);
+
+/// Ask index entry, ordered best first: lowest price, then exchange
+type AskIndexKey = (i64, Exchange);
+
 /// HashMap-based order book implementation
 #[derive(Debug, Clone)]
 pub struct HashMapOrderBook {
-    bids: Arc<RwLock<HashMap<String, Bid>>>, // key: price_exchange
-    asks: Arc<RwLock<HashMap<String, Ask>>>, // key: price_exchange
-    bid_prices: Arc<RwLock<Vec<f64>>>,       // sorted bid prices (descending)
-    ask_prices: Arc<RwLock<Vec<f64>>>,       // sorted ask prices (ascending)
+    bids: Arc<RwLock<HashMap<LevelKey, Bid>>>,
+    asks: Arc<RwLock<HashMap<LevelKey, Ask>>>,
+    bid_index: Arc<RwLock<BTreeSet<BidIndexKey>>>,
+    ask_index: Arc<RwLock<BTreeSet<AskIndexKey>>>,
+    stats: Option<Arc<BookStatsRecorder>>,
+    tick_size: Option<f64>,
 }
 
 impl HashMapOrderBook {
@@ -23,157 +43,243 @@ impl HashMapOrderBook {
         Self {
             bids: Arc::new(RwLock::new(HashMap::new())),
             asks: Arc::new(RwLock::new(HashMap::new())),
-            bid_prices: Arc::new(RwLock::new(Vec::new())),
-            ask_prices: Arc::new(RwLock::new(Vec::new())),
+            bid_index: Arc::new(RwLock::new(BTreeSet::new())),
+            ask_index: Arc::new(RwLock::new(BTreeSet::new())),
+            stats: None,
+            tick_size: None,
         }
     }
 
-    /// Generate key for price level
-    fn generate_key(price: f64, exchange: &Exchange) -> String {
-        format!("{:.8}_{}", price, exchange)
+    /// Enables activity counters, read back through `OrderBook::stats`
+    ///
+    /// Clones made afterwards share the same counters.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Arc::new(BookStatsRecorder::new()));
+        self
     }
 
-    /// Sort and maintain bid prices (descending)
-    async fn sort_bid_prices(&self) {
-        let bids = self.bids.read().await;
-        let mut prices: Vec<f64> = bids.values().map(|b| b.price).collect();
-        prices.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
-        prices.dedup();
+    /// Rounds every incoming price to the nearest multiple of `tick_size`
+    ///
+    /// A tick size that is not positive and finite leaves prices unchanged.
+    pub fn with_tick_size(mut self, tick_size: f64) -> Self {
+        self.tick_size = tick::checked_tick_size(tick_size);
+        self
+    }
 
-        let mut bid_prices = self.bid_prices.write().await;
-        *bid_prices = prices;
+    /// Rounds every incoming price to the tick size of `instrument`
+    pub fn with_instrument(self, instrument: &InstrumentInfo) -> Self {
+        self.with_tick_size(instrument.tick_size)
     }
 
-    /// Sort and maintain ask prices (ascending)
-    async fn sort_ask_prices(&self) {
-        let asks = self.asks.read().await;
-        let mut prices: Vec<f64> = asks.values().map(|a| a.price).collect();
-        prices.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-        prices.dedup();
+    /// Generate key for price level
+    fn generate_key(price: f64, exchange: &Exchange) -> LevelKey {
+        (price.to_bits(), exchange.clone())
+    }
 
-        let mut ask_prices = self.ask_prices.write().await;
-        *ask_prices = prices;
+    /// Maps a price to an integer that sorts like `f64::total_cmp`, the order `Bid` and `Ask`
+    /// use. The mapping is its own inverse on the bit pattern, see `level_key`.
+    fn ordered_price(bits: u64) -> i64 {
+        let bits = bits as i64;
+        bits ^ (((bits >> 63) as u64) >> 1) as i64
     }
-}
 
-impl Default for HashMapOrderBook {
-    fn default() -> Self {
-        Self::new()
+    /// Recovers the map key from the ordered price of an index entry
+    fn level_key(ordered: i64, exchange: &Exchange) -> LevelKey {
+        (Self::ordered_price(ordered as u64) as u64, exchange.clone())
     }
-}
 
-#[async_trait]
-impl OrderBook for HashMapOrderBook {
-    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) {
-        let mut bid_map = self.bids.write().await;
+    fn bid_index_key((bits, exchange): &LevelKey) -> BidIndexKey {
+        (Reverse(Self::ordered_price(*bits)), exchange.clone())
+    }
 
+    fn ask_index_key((bits, exchange): &LevelKey) -> AskIndexKey {
+        (Self::ordered_price(*bits), exchange.clone())
+    }
+
+    /// Applies bid updates to the map and index, then trims to `max_depth` by removing the
+    /// worst levels, returning how many were trimmed
+    fn apply_bids(
+        bid_map: &mut HashMap<LevelKey, Bid>,
+        bid_index: &mut BTreeSet<BidIndexKey>,
+        bids: impl IntoIterator<Item = Bid>,
+        max_depth: usize,
+    ) -> usize {
         for bid in bids {
             let key = Self::generate_key(bid.price, &bid.exchange);
             if bid.quantity > 0.0 {
-                bid_map.insert(key, bid);
-            } else {
-                bid_map.remove(&key);
+                if bid_map.insert(key.clone(), bid).is_none() {
+                    bid_index.insert(Self::bid_index_key(&key));
+                }
+            } else if bid_map.remove(&key).is_some() {
+                bid_index.remove(&Self::bid_index_key(&key));
             }
         }
 
-        // Trim to max depth by removing lowest prices
-        if max_depth == 0 {
-            bid_map.clear();
-        } else if bid_map.len() > max_depth {
-            let mut prices: Vec<f64> = bid_map.values().map(|b| b.price).collect();
-            prices.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
-            prices.truncate(max_depth);
-            if let Some(min_price) = prices.last().copied() {
-                bid_map.retain(|_, bid| bid.price >= min_price);
+        let trimmed = bid_index.len().saturating_sub(max_depth);
+        for _ in 0..trimmed {
+            if let Some((Reverse(price), exchange)) = bid_index.pop_last() {
+                bid_map.remove(&Self::level_key(price, &exchange));
             }
         }
-
-        drop(bid_map);
-        self.sort_bid_prices().await;
+        trimmed
     }
 
-    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) {
-        let mut ask_map = self.asks.write().await;
-
+    /// Applies ask updates to the map and index, then trims to `max_depth` by removing the
+    /// worst levels, returning how many were trimmed
+    fn apply_asks(
+        ask_map: &mut HashMap<LevelKey, Ask>,
+        ask_index: &mut BTreeSet<AskIndexKey>,
+        asks: impl IntoIterator<Item = Ask>,
+        max_depth: usize,
+    ) -> usize {
         for ask in asks {
             let key = Self::generate_key(ask.price, &ask.exchange);
             if ask.quantity > 0.0 {
-                ask_map.insert(key, ask);
-            } else {
-                ask_map.remove(&key);
+                if ask_map.insert(key.clone(), ask).is_none() {
+                    ask_index.insert(Self::ask_index_key(&key));
+                }
+            } else if ask_map.remove(&key).is_some() {
+                ask_index.remove(&Self::ask_index_key(&key));
             }
         }
 
-        // Trim to max depth by removing highest prices
-        if max_depth == 0 {
-            ask_map.clear();
-        } else if ask_map.len() > max_depth {
-            let mut prices: Vec<f64> = ask_map.values().map(|a| a.price).collect();
-            prices.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-            prices.truncate(max_depth);
-            if let Some(max_price) = prices.last().copied() {
-                ask_map.retain(|_, ask| ask.price <= max_price);
+        let trimmed = ask_index.len().saturating_sub(max_depth);
+        for _ in 0..trimmed {
+            if let Some((price, exchange)) = ask_index.pop_last() {
+                ask_map.remove(&Self::level_key(price, &exchange));
             }
         }
+        trimmed
+    }
+}
+
+impl Default for HashMapOrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OrderBook for HashMapOrderBook {
+    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) -> Result<()> {
+        validate_bids(&bids)?;
+        let bids = tick::round_bids(bids, self.tick_size);
+
+        let stats = self.stats.as_deref();
+        let mut bid_map = timed_lock(stats, self.bids.write()).await;
+        let mut bid_index = self.bid_index.write().await;
+        let received = bids.len();
+        let trimmed = Self::apply_bids(&mut bid_map, &mut bid_index, bids, max_depth);
+        if let Some(stats) = stats {
+            stats.record_updates(received);
+            stats.record_trimmed(trimmed);
+        }
 
-        drop(ask_map);
-        self.sort_ask_prices().await;
+        Ok(())
     }
 
-    async fn get_best_bid(&self) -> Option<Bid> {
-        let bid_prices = self.bid_prices.read().await;
-        let best_price = bid_prices.first().copied()?;
+    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) -> Result<()> {
+        validate_asks(&asks)?;
+        let asks = tick::round_asks(asks, self.tick_size);
 
-        let bids = self.bids.read().await;
-        bids.values().find(|b| b.price == best_price).cloned()
+        let stats = self.stats.as_deref();
+        let mut ask_map = timed_lock(stats, self.asks.write()).await;
+        let mut ask_index = self.ask_index.write().await;
+        let received = asks.len();
+        let trimmed = Self::apply_asks(&mut ask_map, &mut ask_index, asks, max_depth);
+        if let Some(stats) = stats {
+            stats.record_updates(received);
+            stats.record_trimmed(trimmed);
+        }
+
+        Ok(())
     }
 
-    async fn get_best_ask(&self) -> Option<Ask> {
-        let ask_prices = self.ask_prices.read().await;
-        let best_price = ask_prices.first().copied()?;
+    async fn apply_update(&mut self, update: &PriceLevelUpdate, max_depth: usize) -> Result<()> {
+        validate_bids(&update.bids)?;
+        validate_asks(&update.asks)?;
+        let bids = tick::round_bids(update.bids.clone(), self.tick_size);
+        let asks = tick::round_asks(update.asks.clone(), self.tick_size);
 
-        let asks = self.asks.read().await;
-        asks.values().find(|a| a.price == best_price).cloned()
+        // All four locks are taken before anything changes, in the same order as `clear`
+        let stats = self.stats.as_deref();
+        let (mut bid_map, mut ask_map, mut bid_index, mut ask_index) = timed_lock(stats, async {
+            (
+                self.bids.write().await,
+                self.asks.write().await,
+                self.bid_index.write().await,
+                self.ask_index.write().await,
+            )
+        })
+        .await;
+
+        let received = bids.len() + asks.len();
+        let trimmed = Self::apply_bids(&mut bid_map, &mut bid_index, bids, max_depth)
+            + Self::apply_asks(&mut ask_map, &mut ask_index, asks, max_depth);
+        if let Some(stats) = stats {
+            stats.record_updates(received);
+            stats.record_trimmed(trimmed);
+        }
+
+        Ok(())
+    }
+
+    async fn get_best_bid(&self) -> Option<Bid> {
+        self.get_best_n_bids(1).await.pop()
+    }
+
+    async fn get_best_ask(&self) -> Option<Ask> {
+        self.get_best_n_asks(1).await.pop()
     }
 
     async fn get_best_n_bids(&self, n: usize) -> Vec<Bid> {
-        let bid_prices = self.bid_prices.read().await;
         let bids = self.bids.read().await;
+        let bid_index = self.bid_index.read().await;
 
-        bid_prices
+        bid_index
             .iter()
             .take(n)
-            .filter_map(|&price| bids.values().find(|b| b.price == price).cloned())
+            .filter_map(|(Reverse(price), exchange)| {
+                bids.get(&Self::level_key(*price, exchange)).cloned()
+            })
             .collect()
     }
 
     async fn get_best_n_asks(&self, n: usize) -> Vec<Ask> {
-        let ask_prices = self.ask_prices.read().await;
         let asks = self.asks.read().await;
+        let ask_index = self.ask_index.read().await;
 
-        ask_prices
+        ask_index
             .iter()
             .take(n)
-            .filter_map(|&price| asks.values().find(|a| a.price == price).cloned())
+            .filter_map(|(price, exchange)| asks.get(&Self::level_key(*price, exchange)).cloned())
             .collect()
     }
 
     async fn get_spread(&self) -> Option<f64> {
-        let best_bid = self.get_best_bid().await?;
-        let best_ask = self.get_best_ask().await?;
+        // Hold both sides so the spread never mixes states from before and after an update
+        let bids = self.bids.read().await;
+        let asks = self.asks.read().await;
+        let bid_index = self.bid_index.read().await;
+        let ask_index = self.ask_index.read().await;
+
+        let (Reverse(bid_price), bid_exchange) = bid_index.first()?;
+        let (ask_price, ask_exchange) = ask_index.first()?;
+        let best_bid = bids.get(&Self::level_key(*bid_price, bid_exchange))?;
+        let best_ask = asks.get(&Self::level_key(*ask_price, ask_exchange))?;
         Some(best_ask.price - best_bid.price)
     }
 
     async fn clear(&mut self) {
         let mut bids = self.bids.write().await;
         let mut asks = self.asks.write().await;
-        let mut bid_prices = self.bid_prices.write().await;
-        let mut ask_prices = self.ask_prices.write().await;
+        let mut bid_index = self.bid_index.write().await;
+        let mut ask_index = self.ask_index.write().await;
 
         bids.clear();
         asks.clear();
-        bid_prices.clear();
-        ask_prices.clear();
+        bid_index.clear();
+        ask_index.clear();
     }
 
     async fn bid_depth(&self) -> usize {
@@ -185,4 +291,8 @@ impl OrderBook for HashMapOrderBook {
         let asks = self.asks.read().await;
         asks.len()
     }
+
+    fn stats(&self) -> Option<BookStats> {
+        self.stats.as_ref().map(|stats| stats.snapshot())
+    }
 }
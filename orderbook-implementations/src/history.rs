@@ -0,0 +1,135 @@
+//! Time-sliced order book history
+//!
+//! `BookHistory` samples the top levels of a book at a fixed interval into a bounded ring buffer,
+//! so the state of the book at a past instant, or how its spread and mid price moved over a
+//! window, can be looked up after the fact. Samples taken sooner than the interval after the
+//! previous one are dropped, which makes it safe to offer one after every update.
+
+use crate::{OrderBook, OrderBookSnapshot};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+/// Ring buffer of periodic top-of-book snapshots, oldest first
+#[derive(Debug, Clone)]
+pub struct BookHistory {
+    depth: usize,
+    interval: Duration,
+    capacity: usize,
+    snapshots: VecDeque<OrderBookSnapshot>,
+}
+
+impl BookHistory {
+    /// Creates a history keeping `capacity` snapshots of the best `depth` levels per side, taken
+    /// at least `interval` apart
+    pub fn new(capacity: usize, interval: std::time::Duration, depth: usize) -> Self {
+        Self {
+            depth,
+            interval: Duration::from_std(interval).unwrap_or(Duration::MAX),
+            capacity: capacity.max(1),
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    /// Samples the best levels of `book` now
+    ///
+    /// # Returns
+    ///
+    /// `true` if a snapshot was stored, `false` if the interval has not elapsed yet
+    pub async fn capture<B: OrderBook + ?Sized>(&mut self, book: &B) -> bool {
+        let snapshot = OrderBookSnapshot {
+            bids: book.get_best_n_bids(self.depth).await,
+            asks: book.get_best_n_asks(self.depth).await,
+            timestamp: Utc::now(),
+        };
+        self.record(snapshot)
+    }
+
+    /// Stores `snapshot`, trimmed to the configured depth, if it is at least one interval newer
+    /// than the latest stored snapshot
+    pub fn record(&mut self, mut snapshot: OrderBookSnapshot) -> bool {
+        if let Some(latest) = self.snapshots.back() {
+            if snapshot.timestamp - latest.timestamp < self.interval {
+                return false;
+            }
+        }
+
+        snapshot.bids.truncate(self.depth);
+        snapshot.asks.truncate(self.depth);
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+        true
+    }
+
+    /// The book as last sampled at or before `timestamp`
+    pub fn book_at(&self, timestamp: DateTime<Utc>) -> Option<&OrderBookSnapshot> {
+        let index = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.timestamp <= timestamp);
+        index.checked_sub(1).and_then(|i| self.snapshots.get(i))
+    }
+
+    /// Snapshots taken between `from` and `to`, inclusive
+    pub fn range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> impl Iterator<Item = &OrderBookSnapshot> {
+        let start = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.timestamp < from);
+        self.snapshots
+            .range(start..)
+            .take_while(move |snapshot| snapshot.timestamp <= to)
+    }
+
+    /// Best ask minus best bid of every snapshot between `from` and `to` with both sides quoted
+    pub fn spread_series(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<(DateTime<Utc>, f64)> {
+        self.range(from, to)
+            .filter_map(|snapshot| {
+                let (bid, ask) = top_of_book(snapshot)?;
+                Some((snapshot.timestamp, ask - bid))
+            })
+            .collect()
+    }
+
+    /// Mid price of every snapshot between `from` and `to` with both sides quoted
+    pub fn mid_price_series(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<(DateTime<Utc>, f64)> {
+        self.range(from, to)
+            .filter_map(|snapshot| {
+                let (bid, ask) = top_of_book(snapshot)?;
+                Some((snapshot.timestamp, (bid + ask) / 2.0))
+            })
+            .collect()
+    }
+
+    pub fn latest(&self) -> Option<&OrderBookSnapshot> {
+        self.snapshots.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+/// Best bid and best ask prices of a snapshot
+fn top_of_book(snapshot: &OrderBookSnapshot) -> Option<(f64, f64)> {
+    Some((snapshot.bids.first()?.price, snapshot.asks.first()?.price))
+}
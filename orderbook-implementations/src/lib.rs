@@ -7,9 +7,15 @@
 //! ## Available Implementations
 //!
 //! - **BTreeSet**: Maintains sorted order automatically, good for general use
-//! - **HashMap**: Fast lookups and updates, requires manual sorting for best prices
+//! - **HashMap**: Fast lookups and updates, with a sorted index for best prices
 //! - **AVL Tree**: Balanced tree implementation (placeholder)
 //! - **Red-Black Tree**: Self-balancing binary search tree (placeholder)
+//! - **Decimal**: Exact `rust_decimal` prices, behind the `decimal` feature
+//! - **Copy-on-Write**: Lock-free reads for read-heavy fan-out
+//!
+//! `ConsolidatedOrderBook` merges per-exchange books of any implementation into one
+//! cross-exchange ladder, and `OrderBookFactory` builds them for the aggregator from
+//! `OrderBookConfig::implementation`.
 //!
 //! ## Usage
 //!
@@ -28,17 +34,35 @@
 //!         timestamp: Utc::now(),
 //!     };
 //!     
-//!     orderbook.update_bids(vec![bid], 100).await;
+//!     orderbook.update_bids(vec![bid], 100).await.unwrap();
 //!     let best_bid = orderbook.get_best_bid().await;
 //! }
 
+pub mod analytics;
 pub mod avl_tree;
 pub mod btree_set;
+pub mod consolidated;
+pub mod copy_on_write;
+pub mod crossing;
+#[cfg(feature = "decimal")]
+pub mod decimal_book;
+pub mod depth;
+pub mod factory;
 pub mod hashmap;
+pub mod history;
+pub mod order_by_order;
+pub mod purge;
 pub mod rb_tree;
+pub mod sharded;
+pub mod snapshot;
+pub mod stats;
+mod tick;
+pub mod validation;
 
-use aggregator_core::{Ask, Bid};
+use aggregator_core::{Ask, Bid, BookStats, PriceLevelUpdate, Result};
 use async_trait::async_trait;
+use chrono::Utc;
+use std::time::Duration;
 
 /// Core trait for order book implementations
 ///
@@ -54,6 +78,13 @@ use async_trait::async_trait;
 /// - `update_bids`/`update_asks`: Should handle batch updates efficiently
 /// - `get_best_*`: Should be O(1) or O(log n) for optimal performance
 /// - `max_depth`: Limits memory usage and maintains only the most relevant price levels
+///
+/// # Price Levels
+///
+/// Orders are keyed by `(price, exchange)`: liquidity quoted by several exchanges at the same
+/// price is kept as one entry per exchange rather than collapsed into one. Depths, depth limits
+/// and the best-N queries all count these entries, and entries at the same price are ordered by
+/// exchange.
 #[async_trait]
 pub trait OrderBook: Send + Sync {
     /// Updates the bid side of the order book with new data
@@ -69,7 +100,12 @@ pub trait OrderBook: Send + Sync {
     /// - Orders with quantity = 0.0 are removed
     /// - Duplicate price/exchange combinations are replaced
     /// - Only the best `max_depth` levels are kept
-    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize);
+    ///
+    /// # Errors
+    ///
+    /// Returns `AggregatorError::Validation` without changing the book if any order has a NaN
+    /// or infinite price, or a NaN, infinite or negative quantity.
+    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) -> Result<()>;
 
     /// Updates the ask side of the order book with new data
     ///
@@ -84,7 +120,37 @@ pub trait OrderBook: Send + Sync {
     /// - Orders with quantity = 0.0 are removed
     /// - Duplicate price/exchange combinations are replaced
     /// - Only the best `max_depth` levels are kept
-    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize);
+    ///
+    /// # Errors
+    ///
+    /// Returns `AggregatorError::Validation` without changing the book if any order has a NaN
+    /// or infinite price, or a NaN, infinite or negative quantity.
+    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) -> Result<()>;
+
+    /// Applies both sides of a price level update as one change
+    ///
+    /// # Arguments
+    ///
+    /// * `update` - Bids and asks to apply, with the same per-level behavior as `update_bids`
+    ///   and `update_asks`
+    /// * `max_depth` - Maximum number of price levels to maintain per side
+    ///
+    /// # Behavior
+    ///
+    /// Implementations with locks take the locks of both sides once and hold them for the
+    /// whole update, so readers see the book either before or after it, never with only one
+    /// side applied. The default applies the sides one after the other.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AggregatorError::Validation` without changing the book if either side fails
+    /// validation
+    async fn apply_update(&mut self, update: &PriceLevelUpdate, max_depth: usize) -> Result<()> {
+        validation::validate_bids(&update.bids)?;
+        validation::validate_asks(&update.asks)?;
+        self.update_bids(update.bids.clone(), max_depth).await?;
+        self.update_asks(update.asks.clone(), max_depth).await
+    }
 
     /// Returns the best (highest price) bid order
     ///
@@ -138,6 +204,144 @@ pub trait OrderBook: Send + Sync {
 
     /// Returns the number of ask price levels  
     async fn ask_depth(&self) -> usize;
+
+    /// Activity counters since instrumentation was enabled
+    ///
+    /// # Returns
+    ///
+    /// `None` unless the book was built with stats enabled, e.g.
+    /// `BTreeOrderBook::new().with_stats()`
+    fn stats(&self) -> Option<BookStats> {
+        None
+    }
+
+    /// Groups both sides into price buckets of width `bucket_size`
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_size` - Width of each bucket in price units
+    /// * `n` - Maximum number of buckets to return per side
+    ///
+    /// # Returns
+    ///
+    /// Up to `n` buckets per side, best first, each with the quantity inside it and the
+    /// cumulative quantity from the best price through it
+    ///
+    /// # Errors
+    ///
+    /// Returns `AggregatorError::Validation` if `bucket_size` is not positive and finite
+    async fn get_depth_buckets(&self, bucket_size: f64, n: usize) -> Result<DepthBuckets> {
+        depth::validate_bucket_size(bucket_size)?;
+        let bids = self.get_best_n_bids(self.bid_depth().await).await;
+        let asks = self.get_best_n_asks(self.ask_depth().await).await;
+
+        Ok(DepthBuckets {
+            bids: depth::bucket_levels(
+                bids.iter().map(|bid| (bid.price, bid.quantity)),
+                bucket_size,
+                n,
+                depth::BucketRounding::Down,
+            ),
+            asks: depth::bucket_levels(
+                asks.iter().map(|ask| (ask.price, ask.quantity)),
+                bucket_size,
+                n,
+                depth::BucketRounding::Up,
+            ),
+        })
+    }
+
+    /// Captures the full state of the order book
+    ///
+    /// # Returns
+    ///
+    /// An `OrderBookSnapshot` with every bid and ask, best first, stamped with the current time
+    async fn snapshot(&self) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            bids: self.get_best_n_bids(self.bid_depth().await).await,
+            asks: self.get_best_n_asks(self.ask_depth().await).await,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Applies the levels that changed since an earlier state
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - Changed levels; levels with quantity 0.0 are removed
+    /// * `max_depth` - Maximum number of price levels to maintain
+    ///
+    /// # Errors
+    ///
+    /// Returns `AggregatorError::Validation` without changing the book if either side of the
+    /// delta fails validation
+    async fn apply_delta(&mut self, delta: OrderBookDelta, max_depth: usize) -> Result<()> {
+        validation::validate_bids(&delta.bids)?;
+        validation::validate_asks(&delta.asks)?;
+        self.update_bids(delta.bids, max_depth).await?;
+        self.update_asks(delta.asks, max_depth).await
+    }
+
+    /// Replaces the contents of the order book with a snapshot
+    ///
+    /// # Errors
+    ///
+    /// Returns `AggregatorError::Validation` without changing the book if the snapshot fails
+    /// validation
+    async fn restore(&mut self, snapshot: OrderBookSnapshot, max_depth: usize) -> Result<()> {
+        validation::validate_bids(&snapshot.bids)?;
+        validation::validate_asks(&snapshot.asks)?;
+        self.clear().await;
+        self.update_bids(snapshot.bids, max_depth).await?;
+        self.update_asks(snapshot.asks, max_depth).await
+    }
+
+    /// Removes every level last updated more than `max_age` ago
+    ///
+    /// Each level is judged by its own timestamp, so the levels of an exchange that stopped
+    /// sending updates age out while fresher levels from other exchanges stay.
+    ///
+    /// # Returns
+    ///
+    /// The number of bid and ask entries removed
+    async fn purge_older_than(&mut self, max_age: Duration) -> Result<usize> {
+        let cutoff = match chrono::Duration::from_std(max_age)
+            .ok()
+            .and_then(|age| Utc::now().checked_sub_signed(age))
+        {
+            Some(cutoff) => cutoff,
+            None => return Ok(0),
+        };
+
+        let snapshot = self.snapshot().await;
+        let stale_bids: Vec<Bid> = snapshot
+            .bids
+            .into_iter()
+            .filter(|bid| bid.timestamp < cutoff)
+            .map(|bid| Bid {
+                quantity: 0.0,
+                ..bid
+            })
+            .collect();
+        let stale_asks: Vec<Ask> = snapshot
+            .asks
+            .into_iter()
+            .filter(|ask| ask.timestamp < cutoff)
+            .map(|ask| Ask {
+                quantity: 0.0,
+                ..ask
+            })
+            .collect();
+
+        let removed = stale_bids.len() + stale_asks.len();
+        if !stale_bids.is_empty() {
+            self.update_bids(stale_bids, usize::MAX).await?;
+        }
+        if !stale_asks.is_empty() {
+            self.update_asks(stale_asks, usize::MAX).await?;
+        }
+        Ok(removed)
+    }
 }
 
 /// Trait for buy-side only order book operations
@@ -146,8 +350,8 @@ pub trait OrderBook: Send + Sync {
 /// useful for scenarios where bid and ask sides are managed separately.
 #[async_trait]
 pub trait BuySide: Send + Sync {
-    /// Updates bid orders with depth management
-    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize);
+    /// Updates bid orders with depth management, rejecting invalid orders like `OrderBook`
+    async fn update_bids(&mut self, bids: Vec<Bid>, max_depth: usize) -> Result<()>;
 
     /// Gets the best (highest price) bid
     async fn get_best_bid(&self) -> Option<Bid>;
@@ -168,8 +372,8 @@ pub trait BuySide: Send + Sync {
 /// useful for scenarios where bid and ask sides are managed separately.
 #[async_trait]
 pub trait SellSide: Send + Sync {
-    /// Updates ask orders with depth management
-    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize);
+    /// Updates ask orders with depth management, rejecting invalid orders like `OrderBook`
+    async fn update_asks(&mut self, asks: Vec<Ask>, max_depth: usize) -> Result<()>;
 
     /// Gets the best (lowest price) ask
     async fn get_best_ask(&self) -> Option<Ask>;
@@ -185,5 +389,21 @@ pub trait SellSide: Send + Sync {
 }
 
 // Re-export implementations
+pub use analytics::BookAnalytics;
 pub use btree_set::BTreeOrderBook;
+pub use consolidated::{ConsolidatedLevel, ConsolidatedOrderBook};
+pub use copy_on_write::CowOrderBook;
+pub use crossing::{CrossedBookEvent, CrossingKind};
+#[cfg(feature = "decimal")]
+pub use decimal_book::DecimalOrderBook;
+pub use depth::{DepthBucket, DepthBuckets};
+pub use factory::OrderBookFactory;
 pub use hashmap::HashMapOrderBook;
+pub use history::BookHistory;
+pub use order_by_order::{
+    L3Order, OrderByOrderBook, OrderLifetimeStats, OrderOutcome, QueuePosition,
+};
+pub use purge::spawn_stale_level_purge;
+pub use sharded::{BookKey, ShardedOrderBookMap};
+pub use snapshot::{OrderBookDelta, OrderBookSnapshot};
+pub use stats::BookStatsRecorder;
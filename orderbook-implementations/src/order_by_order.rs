@@ -0,0 +1,444 @@
+//! # Order-by-Order (L3) Book
+//!
+//! This module tracks individual resting orders for venues that publish L3 data, such as the
+//! Coinbase `full` channel or Bitstamp `live_orders`. Keeping each order in time priority at its
+//! price lets the book answer questions an aggregated L2 book cannot: how much quantity sits
+//! ahead of an order in the queue, and how long orders live before they fill or are cancelled.
+//!
+//! Sides use `TradeSide`: `Buy` orders rest on the bid side and `Sell` orders on the ask side.
+
+use crate::OrderBookSnapshot;
+use aggregator_core::{AggregatorError, Ask, Bid, Exchange, Result, TradeSide};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Number of finished orders kept for lifetime statistics by `OrderByOrderBook::new`
+pub const DEFAULT_LIFETIME_WINDOW: usize = 10_000;
+
+/// One resting order
+#[derive(Debug, Clone, PartialEq)]
+pub struct L3Order {
+    pub id: String,
+    pub side: TradeSide,
+    pub price: f64,
+    pub size: f64,
+    pub placed_at: DateTime<Utc>,
+    /// Arrival order within the book; lower values were placed earlier
+    sequence: u64,
+}
+
+/// Where an order, or a new order joining a price, sits in the queue at its price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueuePosition {
+    /// Orders at the same price with time priority
+    pub orders_ahead: usize,
+    /// Quantity those orders hold
+    pub quantity_ahead: f64,
+    /// Total quantity resting at the price
+    pub level_quantity: f64,
+}
+
+/// How an order left the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderOutcome {
+    Filled,
+    Cancelled,
+}
+
+/// Lifetime statistics over the most recently finished orders
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OrderLifetimeStats {
+    pub filled: usize,
+    pub cancelled: usize,
+    /// Mean time from placement to leaving the book, in milliseconds
+    pub mean_lifetime_ms: f64,
+    /// Median time from placement to leaving the book, in milliseconds
+    pub median_lifetime_ms: f64,
+    /// Mean lifetime of orders that filled completely, in milliseconds
+    pub mean_time_to_fill_ms: Option<f64>,
+}
+
+impl OrderLifetimeStats {
+    /// Share of finished orders that filled completely, or 0 when none have finished
+    pub fn fill_ratio(&self) -> f64 {
+        let finished = self.filled + self.cancelled;
+        if finished == 0 {
+            0.0
+        } else {
+            self.filled as f64 / finished as f64
+        }
+    }
+}
+
+/// Orders resting at one price, in time priority
+#[derive(Debug, Clone, Default)]
+struct PriceQueue {
+    orders: BTreeMap<u64, String>,
+    quantity: f64,
+}
+
+/// Order book tracking every resting order of one exchange
+///
+/// Price levels are keyed on the bit pattern of the price, which orders the same way as the
+/// prices themselves because only positive, finite prices are accepted.
+///
+/// # Examples
+///
+/// ```rust
+/// use aggregator_core::{Exchange, TradeSide};
+/// use chrono::Utc;
+/// use orderbook_implementations::OrderByOrderBook;
+///
+/// let mut book = OrderByOrderBook::new(Exchange::Coinbase);
+/// book.add_order("a", TradeSide::Buy, 100.0, 1.0, Utc::now()).unwrap();
+/// book.add_order("b", TradeSide::Buy, 100.0, 2.0, Utc::now()).unwrap();
+///
+/// let position = book.queue_position("b").unwrap();
+/// assert_eq!(position.orders_ahead, 1);
+/// assert_eq!(position.quantity_ahead, 1.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderByOrderBook {
+    exchange: Exchange,
+    orders: HashMap<String, L3Order>,
+    bids: BTreeMap<u64, PriceQueue>,
+    asks: BTreeMap<u64, PriceQueue>,
+    next_sequence: u64,
+    finished: VecDeque<(i64, OrderOutcome)>,
+    lifetime_window: usize,
+}
+
+impl OrderByOrderBook {
+    pub fn new(exchange: Exchange) -> Self {
+        Self::with_lifetime_window(exchange, DEFAULT_LIFETIME_WINDOW)
+    }
+
+    /// Creates a book that keeps lifetime statistics over the last `window` finished orders
+    pub fn with_lifetime_window(exchange: Exchange, window: usize) -> Self {
+        Self {
+            exchange,
+            orders: HashMap::new(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            next_sequence: 0,
+            finished: VecDeque::new(),
+            lifetime_window: window.max(1),
+        }
+    }
+
+    pub fn exchange(&self) -> &Exchange {
+        &self.exchange
+    }
+
+    /// Number of resting orders
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    pub fn order(&self, id: &str) -> Option<&L3Order> {
+        self.orders.get(id)
+    }
+
+    fn side_levels(&mut self, side: TradeSide) -> &mut BTreeMap<u64, PriceQueue> {
+        match side {
+            TradeSide::Buy => &mut self.bids,
+            TradeSide::Sell => &mut self.asks,
+        }
+    }
+
+    fn validate(price: f64, size: f64) -> Result<()> {
+        if !price.is_finite() || price <= 0.0 {
+            return Err(AggregatorError::validation(
+                "price",
+                format!("{} is not a positive finite price", price),
+            ));
+        }
+        if !size.is_finite() || size <= 0.0 {
+            return Err(AggregatorError::validation(
+                "size",
+                format!("{} is not a positive finite size", size),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Adds a new order at the back of the queue at its price
+    pub fn add_order(
+        &mut self,
+        id: &str,
+        side: TradeSide,
+        price: f64,
+        size: f64,
+        timestamp: DateTime<Utc>,
+    ) -> Result<()> {
+        Self::validate(price, size)?;
+        if self.orders.contains_key(id) {
+            return Err(AggregatorError::validation(
+                "order_id",
+                format!("order {} is already in the book", id),
+            ));
+        }
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+
+        let queue = self.side_levels(side).entry(price.to_bits()).or_default();
+        queue.orders.insert(sequence, id.to_string());
+        queue.quantity += size;
+
+        self.orders.insert(
+            id.to_string(),
+            L3Order {
+                id: id.to_string(),
+                side,
+                price,
+                size,
+                placed_at: timestamp,
+                sequence,
+            },
+        );
+        Ok(())
+    }
+
+    /// Changes the remaining size of an order
+    ///
+    /// A smaller size keeps the order's queue priority. A larger size sends it to the back of
+    /// the queue, as exchanges treat an increase as a new order.
+    pub fn change_order(&mut self, id: &str, new_size: f64) -> Result<()> {
+        let order = self
+            .orders
+            .get(id)
+            .cloned()
+            .ok_or_else(|| AggregatorError::not_found("order", id))?;
+        Self::validate(order.price, new_size)?;
+
+        let mut sequence = order.sequence;
+        if new_size > order.size {
+            sequence = self.next_sequence;
+            self.next_sequence += 1;
+        }
+
+        let queue = self
+            .side_levels(order.side)
+            .get_mut(&order.price.to_bits())
+            .expect("resting order has a price level");
+        queue.quantity += new_size - order.size;
+        if sequence != order.sequence {
+            queue.orders.remove(&order.sequence);
+            queue.orders.insert(sequence, id.to_string());
+        }
+
+        let order = self.orders.get_mut(id).expect("order checked above");
+        order.size = new_size;
+        order.sequence = sequence;
+        Ok(())
+    }
+
+    /// Applies a trade against a resting order, removing it once fully filled
+    pub fn fill_order(&mut self, id: &str, quantity: f64, timestamp: DateTime<Utc>) -> Result<()> {
+        let size = self
+            .orders
+            .get(id)
+            .map(|order| order.size)
+            .ok_or_else(|| AggregatorError::not_found("order", id))?;
+        if !quantity.is_finite() || quantity <= 0.0 {
+            return Err(AggregatorError::validation(
+                "quantity",
+                format!("{} is not a positive finite fill quantity", quantity),
+            ));
+        }
+
+        if quantity >= size {
+            self.finish(id, timestamp, OrderOutcome::Filled);
+            Ok(())
+        } else {
+            self.change_order(id, size - quantity)
+        }
+    }
+
+    /// Removes an order that was cancelled
+    pub fn cancel_order(&mut self, id: &str, timestamp: DateTime<Utc>) -> Result<L3Order> {
+        if !self.orders.contains_key(id) {
+            return Err(AggregatorError::not_found("order", id));
+        }
+        Ok(self.finish(id, timestamp, OrderOutcome::Cancelled))
+    }
+
+    fn finish(&mut self, id: &str, timestamp: DateTime<Utc>, outcome: OrderOutcome) -> L3Order {
+        let order = self.orders.remove(id).expect("caller checked the order");
+        let levels = self.side_levels(order.side);
+        let key = order.price.to_bits();
+        if let Some(queue) = levels.get_mut(&key) {
+            queue.orders.remove(&order.sequence);
+            queue.quantity -= order.size;
+            if queue.orders.is_empty() {
+                levels.remove(&key);
+            }
+        }
+
+        if self.finished.len() == self.lifetime_window {
+            self.finished.pop_front();
+        }
+        let lifetime_ms = (timestamp - order.placed_at).num_milliseconds().max(0);
+        self.finished.push_back((lifetime_ms, outcome));
+        order
+    }
+
+    /// Queue position of a resting order at its price
+    pub fn queue_position(&self, id: &str) -> Option<QueuePosition> {
+        let order = self.orders.get(id)?;
+        let levels = match order.side {
+            TradeSide::Buy => &self.bids,
+            TradeSide::Sell => &self.asks,
+        };
+        let queue = levels.get(&order.price.to_bits())?;
+
+        let ahead: Vec<&String> = queue
+            .orders
+            .range(..order.sequence)
+            .map(|(_, id)| id)
+            .collect();
+        Some(QueuePosition {
+            orders_ahead: ahead.len(),
+            quantity_ahead: ahead
+                .iter()
+                .filter_map(|id| self.orders.get(*id))
+                .map(|order| order.size)
+                .sum(),
+            level_quantity: queue.quantity,
+        })
+    }
+
+    /// Queue position a new order would get by joining `price` on `side` now
+    pub fn estimate_queue_position(&self, side: TradeSide, price: f64) -> QueuePosition {
+        let levels = match side {
+            TradeSide::Buy => &self.bids,
+            TradeSide::Sell => &self.asks,
+        };
+        match levels.get(&price.to_bits()) {
+            Some(queue) => QueuePosition {
+                orders_ahead: queue.orders.len(),
+                quantity_ahead: queue.quantity,
+                level_quantity: queue.quantity,
+            },
+            None => QueuePosition {
+                orders_ahead: 0,
+                quantity_ahead: 0.0,
+                level_quantity: 0.0,
+            },
+        }
+    }
+
+    /// Lifetime statistics over the most recently finished orders
+    pub fn lifetime_stats(&self) -> OrderLifetimeStats {
+        if self.finished.is_empty() {
+            return OrderLifetimeStats::default();
+        }
+
+        let mut lifetimes: Vec<i64> = self.finished.iter().map(|(ms, _)| *ms).collect();
+        lifetimes.sort_unstable();
+        let count = lifetimes.len();
+        let median = if count.is_multiple_of(2) {
+            (lifetimes[count / 2 - 1] + lifetimes[count / 2]) as f64 / 2.0
+        } else {
+            lifetimes[count / 2] as f64
+        };
+
+        let fills: Vec<i64> = self
+            .finished
+            .iter()
+            .filter(|(_, outcome)| *outcome == OrderOutcome::Filled)
+            .map(|(ms, _)| *ms)
+            .collect();
+
+        OrderLifetimeStats {
+            filled: fills.len(),
+            cancelled: count - fills.len(),
+            mean_lifetime_ms: lifetimes.iter().sum::<i64>() as f64 / count as f64,
+            median_lifetime_ms: median,
+            mean_time_to_fill_ms: if fills.is_empty() {
+                None
+            } else {
+                Some(fills.iter().sum::<i64>() as f64 / fills.len() as f64)
+            },
+        }
+    }
+
+    /// Number of bid price levels
+    pub fn bid_depth(&self) -> usize {
+        self.bids.len()
+    }
+
+    /// Number of ask price levels
+    pub fn ask_depth(&self) -> usize {
+        self.asks.len()
+    }
+
+    /// The best `n` bid price levels with the quantity of all orders at each, highest first
+    pub fn get_best_n_bids(&self, n: usize) -> Vec<Bid> {
+        self.bids
+            .iter()
+            .rev()
+            .take(n)
+            .map(|(price, queue)| Bid {
+                price: f64::from_bits(*price),
+                quantity: queue.quantity,
+                exchange: self.exchange.clone(),
+                timestamp: self.level_timestamp(queue),
+            })
+            .collect()
+    }
+
+    /// The best `n` ask price levels with the quantity of all orders at each, lowest first
+    pub fn get_best_n_asks(&self, n: usize) -> Vec<Ask> {
+        self.asks
+            .iter()
+            .take(n)
+            .map(|(price, queue)| Ask {
+                price: f64::from_bits(*price),
+                quantity: queue.quantity,
+                exchange: self.exchange.clone(),
+                timestamp: self.level_timestamp(queue),
+            })
+            .collect()
+    }
+
+    pub fn get_best_bid(&self) -> Option<Bid> {
+        self.get_best_n_bids(1).pop()
+    }
+
+    pub fn get_best_ask(&self) -> Option<Ask> {
+        self.get_best_n_asks(1).pop()
+    }
+
+    /// Latest placement time among the orders at a level
+    fn level_timestamp(&self, queue: &PriceQueue) -> DateTime<Utc> {
+        queue
+            .orders
+            .values()
+            .filter_map(|id| self.orders.get(id))
+            .map(|order| order.placed_at)
+            .max()
+            .unwrap_or_else(Utc::now)
+    }
+
+    /// Aggregated L2 view of the book
+    pub fn snapshot(&self) -> OrderBookSnapshot {
+        OrderBookSnapshot {
+            bids: self.get_best_n_bids(self.bid_depth()),
+            asks: self.get_best_n_asks(self.ask_depth()),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Removes every order, keeping lifetime statistics
+    pub fn clear(&mut self) {
+        self.orders.clear();
+        self.bids.clear();
+        self.asks.clear();
+    }
+}
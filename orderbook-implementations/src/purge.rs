@@ -0,0 +1,43 @@
+//! Periodic expiry of stale price levels
+//!
+//! Exchanges only send the levels that change, so when a connection drops its last levels stay
+//! in the book indefinitely. The task spawned here purges them on the order book's configured
+//! cleanup interval.
+
+use crate::OrderBook;
+use aggregator_core::OrderBookConfig;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Spawns a task that purges stale levels from `book` every `config.cleanup_interval`
+/// milliseconds
+///
+/// A level is stale once it has gone a full cleanup interval without an update. The task stops
+/// when it holds the last reference to `book`, or when the returned handle is aborted.
+pub fn spawn_stale_level_purge<B: OrderBook + 'static>(
+    book: Arc<RwLock<B>>,
+    config: &OrderBookConfig,
+) -> JoinHandle<()> {
+    let max_age = Duration::from_millis(config.cleanup_interval.max(1));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(max_age);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            if Arc::strong_count(&book) == 1 {
+                break;
+            }
+
+            match book.write().await.purge_older_than(max_age).await {
+                Ok(0) => {}
+                Ok(removed) => debug!("Purged {} stale price levels", removed),
+                Err(e) => warn!("Failed to purge stale price levels: {}", e),
+            }
+        }
+    })
+}
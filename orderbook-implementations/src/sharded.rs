@@ -0,0 +1,213 @@
+//! # Sharded Order Book Map
+//!
+//! This module holds the books of many (exchange, symbol) pairs behind a fixed set of locks
+//! instead of one global lock. Each key hashes to one shard, so updates to books in different
+//! shards proceed in parallel and a burst on one symbol only blocks the symbols sharing its
+//! shard.
+//!
+//! ## Use Cases
+//!
+//! - Aggregating thousands of symbols from every connected exchange in one process
+//! - Serving reads for one symbol while other symbols are being updated
+
+use crate::{BTreeOrderBook, OrderBook, OrderBookSnapshot};
+use aggregator_core::{Ask, Bid, Exchange, PriceLevelUpdate, Result};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use tokio::sync::RwLock;
+
+/// Number of shards used by `ShardedOrderBookMap::new`
+pub const DEFAULT_SHARD_COUNT: usize = 64;
+
+/// Identifies one book: the exchange and the symbol it quotes
+pub type BookKey = (Exchange, String);
+
+/// Order books keyed by (exchange, symbol), spread over independently locked shards
+///
+/// Books are created on their first update. Reads of a missing book return empty results.
+///
+/// # Examples
+///
+/// ```rust
+/// use aggregator_core::{Bid, Exchange};
+/// use chrono::Utc;
+/// use orderbook_implementations::ShardedOrderBookMap;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let books: ShardedOrderBookMap = ShardedOrderBookMap::new();
+///     let bid = Bid {
+///         price: 50000.0,
+///         quantity: 1.0,
+///         exchange: Exchange::Binance,
+///         timestamp: Utc::now(),
+///     };
+///     books
+///         .update_bids(Exchange::Binance, "BTCUSDT", vec![bid], 100)
+///         .await
+///         .unwrap();
+///
+///     let best = books.get_best_bid(&Exchange::Binance, "BTCUSDT").await;
+///     assert_eq!(best.unwrap().price, 50000.0);
+/// }
+/// ```
+pub struct ShardedOrderBookMap<B: OrderBook + Default = BTreeOrderBook> {
+    shards: Vec<RwLock<HashMap<BookKey, B>>>,
+    hasher: RandomState,
+}
+
+impl<B: OrderBook + Default> ShardedOrderBookMap<B> {
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARD_COUNT)
+    }
+
+    /// Creates a map with `shards` locks; one shard behaves like a single global lock
+    pub fn with_shards(shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1))
+                .map(|_| RwLock::new(HashMap::new()))
+                .collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn shard(&self, exchange: &Exchange, symbol: &str) -> &RwLock<HashMap<BookKey, B>> {
+        let index = self.hasher.hash_one((exchange, symbol)) as usize % self.shards.len();
+        &self.shards[index]
+    }
+
+    /// Applies bid updates to the book of `exchange` and `symbol`, creating it if needed
+    pub async fn update_bids(
+        &self,
+        exchange: Exchange,
+        symbol: &str,
+        bids: Vec<Bid>,
+        max_depth: usize,
+    ) -> Result<()> {
+        let mut shard = self.shard(&exchange, symbol).write().await;
+        shard
+            .entry((exchange, symbol.to_string()))
+            .or_default()
+            .update_bids(bids, max_depth)
+            .await
+    }
+
+    /// Applies ask updates to the book of `exchange` and `symbol`, creating it if needed
+    pub async fn update_asks(
+        &self,
+        exchange: Exchange,
+        symbol: &str,
+        asks: Vec<Ask>,
+        max_depth: usize,
+    ) -> Result<()> {
+        let mut shard = self.shard(&exchange, symbol).write().await;
+        shard
+            .entry((exchange, symbol.to_string()))
+            .or_default()
+            .update_asks(asks, max_depth)
+            .await
+    }
+
+    /// Applies both sides of `update` to the book of its exchange and symbol with
+    /// `OrderBook::apply_update`, creating the book if needed
+    pub async fn apply_update(&self, update: &PriceLevelUpdate, max_depth: usize) -> Result<()> {
+        let mut shard = self.shard(&update.exchange, &update.symbol).write().await;
+        shard
+            .entry((update.exchange.clone(), update.symbol.clone()))
+            .or_default()
+            .apply_update(update, max_depth)
+            .await
+    }
+
+    pub async fn get_best_bid(&self, exchange: &Exchange, symbol: &str) -> Option<Bid> {
+        let shard = self.shard(exchange, symbol).read().await;
+        shard.get(&key(exchange, symbol))?.get_best_bid().await
+    }
+
+    pub async fn get_best_ask(&self, exchange: &Exchange, symbol: &str) -> Option<Ask> {
+        let shard = self.shard(exchange, symbol).read().await;
+        shard.get(&key(exchange, symbol))?.get_best_ask().await
+    }
+
+    pub async fn get_best_n_bids(&self, exchange: &Exchange, symbol: &str, n: usize) -> Vec<Bid> {
+        let shard = self.shard(exchange, symbol).read().await;
+        match shard.get(&key(exchange, symbol)) {
+            Some(book) => book.get_best_n_bids(n).await,
+            None => Vec::new(),
+        }
+    }
+
+    pub async fn get_best_n_asks(&self, exchange: &Exchange, symbol: &str, n: usize) -> Vec<Ask> {
+        let shard = self.shard(exchange, symbol).read().await;
+        match shard.get(&key(exchange, symbol)) {
+            Some(book) => book.get_best_n_asks(n).await,
+            None => Vec::new(),
+        }
+    }
+
+    pub async fn get_spread(&self, exchange: &Exchange, symbol: &str) -> Option<f64> {
+        let shard = self.shard(exchange, symbol).read().await;
+        shard.get(&key(exchange, symbol))?.get_spread().await
+    }
+
+    /// Full state of one book, or `None` if it has never been updated
+    pub async fn snapshot(&self, exchange: &Exchange, symbol: &str) -> Option<OrderBookSnapshot> {
+        let shard = self.shard(exchange, symbol).read().await;
+        Some(shard.get(&key(exchange, symbol))?.snapshot().await)
+    }
+
+    pub async fn contains(&self, exchange: &Exchange, symbol: &str) -> bool {
+        let shard = self.shard(exchange, symbol).read().await;
+        shard.contains_key(&key(exchange, symbol))
+    }
+
+    /// Drops one book, returning it if it existed
+    pub async fn remove(&self, exchange: &Exchange, symbol: &str) -> Option<B> {
+        let mut shard = self.shard(exchange, symbol).write().await;
+        shard.remove(&key(exchange, symbol))
+    }
+
+    /// Keys of every book, in no particular order
+    pub async fn keys(&self) -> Vec<BookKey> {
+        let mut keys = Vec::new();
+        for shard in &self.shards {
+            keys.extend(shard.read().await.keys().cloned());
+        }
+        keys
+    }
+
+    /// Number of books across all shards
+    pub async fn len(&self) -> usize {
+        let mut len = 0;
+        for shard in &self.shards {
+            len += shard.read().await.len();
+        }
+        len
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Drops every book
+    pub async fn clear(&self) {
+        for shard in &self.shards {
+            shard.write().await.clear();
+        }
+    }
+}
+
+impl<B: OrderBook + Default> Default for ShardedOrderBookMap<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn key(exchange: &Exchange, symbol: &str) -> BookKey {
+    (exchange.clone(), symbol.to_string())
+}
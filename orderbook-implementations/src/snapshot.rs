@@ -0,0 +1,99 @@
+//! Order book snapshots and deltas
+//!
+//! A snapshot is the full state of a book at one point in time; a delta is the set of levels
+//! that changed since an earlier state, with removed levels carried at quantity zero exactly like
+//! exchange updates. Both are serde-serializable so books can be persisted or shipped to clients,
+//! which rebuild them with `OrderBook::restore` and keep them current with
+//! `OrderBook::apply_delta` instead of replaying every update.
+
+use aggregator_core::{Ask, Bid, Exchange};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Full state of an order book, bids highest price first and asks lowest price first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderBookSnapshot {
+    pub bids: Vec<Bid>,
+    pub asks: Vec<Ask>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Levels that changed between two states of an order book
+///
+/// A level with quantity `0.0` was removed; any other level was added or replaced.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct OrderBookDelta {
+    pub bids: Vec<Bid>,
+    pub asks: Vec<Ask>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl OrderBookDelta {
+    /// Computes the delta that turns `from` into `to`
+    pub fn between(from: &OrderBookSnapshot, to: &OrderBookSnapshot) -> Self {
+        let bids = diff_levels(
+            &from.bids,
+            &to.bids,
+            |bid| (bid.price.to_bits(), bid.exchange.clone()),
+            |bid| bid.quantity,
+            |bid| Bid {
+                quantity: 0.0,
+                timestamp: to.timestamp,
+                ..bid.clone()
+            },
+        );
+        let asks = diff_levels(
+            &from.asks,
+            &to.asks,
+            |ask| (ask.price.to_bits(), ask.exchange.clone()),
+            |ask| ask.quantity,
+            |ask| Ask {
+                quantity: 0.0,
+                timestamp: to.timestamp,
+                ..ask.clone()
+            },
+        );
+
+        Self {
+            bids,
+            asks,
+            timestamp: Some(to.timestamp),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+}
+
+/// Levels of `to` that are new or changed quantity, followed by removals for levels of `from`
+/// missing from `to`
+fn diff_levels<T: Clone>(
+    from: &[T],
+    to: &[T],
+    key: impl Fn(&T) -> (u64, Exchange),
+    quantity: impl Fn(&T) -> f64,
+    removed: impl Fn(&T) -> T,
+) -> Vec<T> {
+    let previous: HashMap<(u64, Exchange), f64> = from
+        .iter()
+        .map(|level| (key(level), quantity(level)))
+        .collect();
+    let current: HashMap<(u64, Exchange), f64> = to
+        .iter()
+        .map(|level| (key(level), quantity(level)))
+        .collect();
+
+    let mut changes: Vec<T> = to
+        .iter()
+        .filter(|level| previous.get(&key(level)) != Some(&quantity(level)))
+        .cloned()
+        .collect();
+    changes.extend(
+        from.iter()
+            .filter(|level| !current.contains_key(&key(level)))
+            .map(removed),
+    );
+    changes
+}
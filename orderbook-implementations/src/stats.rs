@@ -0,0 +1,72 @@
+//! Order book activity counters
+//!
+//! Implementations built with `with_stats()` count the levels they receive, the levels they trim
+//! and how long their updates wait for the write lock. The counters are atomics updated in
+//! place, so instrumentation adds no locking of its own. Read them with `OrderBook::stats` and
+//! hand the `BookStats` to `Aggregator::record_book_stats` to export them.
+
+use aggregator_core::BookStats;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// Shared counters behind an instrumented book's `BookStats`
+#[derive(Debug, Default)]
+pub struct BookStatsRecorder {
+    updates_applied: AtomicU64,
+    levels_trimmed: AtomicU64,
+    lock_acquisitions: AtomicU64,
+    lock_wait_us: AtomicU64,
+}
+
+impl BookStatsRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn record_updates(&self, levels: usize) {
+        self.updates_applied
+            .fetch_add(levels as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_trimmed(&self, levels: usize) {
+        if levels > 0 {
+            self.levels_trimmed
+                .fetch_add(levels as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Current values of the counters
+    pub fn snapshot(&self) -> BookStats {
+        BookStats {
+            updates_applied: self.updates_applied.load(Ordering::Relaxed),
+            levels_trimmed: self.levels_trimmed.load(Ordering::Relaxed),
+            lock_acquisitions: self.lock_acquisitions.load(Ordering::Relaxed),
+            lock_wait_us: self.lock_wait_us.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Sets every counter back to zero
+    pub fn reset(&self) {
+        self.updates_applied.store(0, Ordering::Relaxed);
+        self.levels_trimmed.store(0, Ordering::Relaxed);
+        self.lock_acquisitions.store(0, Ordering::Relaxed);
+        self.lock_wait_us.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Awaits a write lock, recording the wait when `stats` is set
+pub(crate) async fn timed_lock<F: Future>(stats: Option<&BookStatsRecorder>, lock: F) -> F::Output {
+    match stats {
+        Some(stats) => {
+            let start = Instant::now();
+            let guard = lock.await;
+            stats.lock_acquisitions.fetch_add(1, Ordering::Relaxed);
+            stats
+                .lock_wait_us
+                .fetch_add(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+            guard
+        }
+        None => lock.await,
+    }
+}
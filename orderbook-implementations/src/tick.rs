@@ -0,0 +1,33 @@
+//! Tick-size rounding of incoming prices
+//!
+//! Prices parsed from exchange feeds can carry floating-point noise, so 100.1 may arrive as
+//! 100.10000000000001 and open a level right next to the one at 100.1. Books built with
+//! `with_tick_size` or `with_instrument` round every incoming price to the nearest tick before
+//! applying it, so both land on the same level, and a removal at a noisy price still finds it.
+
+use aggregator_core::{round_to_tick, Ask, Bid};
+
+/// The tick size to round to, or `None` when `tick_size` is not positive and finite
+pub(crate) fn checked_tick_size(tick_size: f64) -> Option<f64> {
+    (tick_size.is_finite() && tick_size > 0.0).then_some(tick_size)
+}
+
+/// Rounds every bid price to the nearest tick
+pub(crate) fn round_bids(mut bids: Vec<Bid>, tick_size: Option<f64>) -> Vec<Bid> {
+    if let Some(tick_size) = tick_size {
+        for bid in &mut bids {
+            bid.price = round_to_tick(bid.price, tick_size);
+        }
+    }
+    bids
+}
+
+/// Rounds every ask price to the nearest tick
+pub(crate) fn round_asks(mut asks: Vec<Ask>, tick_size: Option<f64>) -> Vec<Ask> {
+    if let Some(tick_size) = tick_size {
+        for ask in &mut asks {
+            ask.price = round_to_tick(ask.price, tick_size);
+        }
+    }
+    asks
+}
@@ -0,0 +1,39 @@
+//! Input validation for order book updates
+//!
+//! Every `OrderBook` implementation checks a batch of levels with these functions before
+//! applying any of it, so a corrupt message from one exchange is rejected as a whole instead of
+//! leaving NaN or infinite prices in the book. NaN breaks the ordering the books rely on and an
+//! infinite bid would sit at the top of the book forever.
+
+use aggregator_core::{AggregatorError, Ask, Bid, Exchange, Result};
+
+/// Checks that every bid has a finite price and a finite, non-negative quantity
+pub fn validate_bids(bids: &[Bid]) -> Result<()> {
+    bids.iter()
+        .try_for_each(|bid| validate_level("bid", bid.price, bid.quantity, &bid.exchange))
+}
+
+/// Checks that every ask has a finite price and a finite, non-negative quantity
+pub fn validate_asks(asks: &[Ask]) -> Result<()> {
+    asks.iter()
+        .try_for_each(|ask| validate_level("ask", ask.price, ask.quantity, &ask.exchange))
+}
+
+fn validate_level(side: &str, price: f64, quantity: f64, exchange: &Exchange) -> Result<()> {
+    if !price.is_finite() {
+        return Err(AggregatorError::validation(
+            format!("{} price", side),
+            format!("{} sent a non-finite price: {}", exchange, price),
+        ));
+    }
+    if !quantity.is_finite() || quantity < 0.0 {
+        return Err(AggregatorError::validation(
+            format!("{} quantity", side),
+            format!(
+                "{} sent an invalid quantity {} at {}",
+                exchange, quantity, price
+            ),
+        ));
+    }
+    Ok(())
+}
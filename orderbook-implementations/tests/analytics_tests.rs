@@ -0,0 +1,111 @@
+//! Tests for order book analytics
+//!
+//! These tests cover imbalance and microprice on every implementation
+
+use aggregator_core::{Ask, Bid, Exchange, TradeSide};
+use chrono::Utc;
+use orderbook_implementations::{BTreeOrderBook, BookAnalytics, HashMapOrderBook, OrderBook};
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, quantity: f64, exchange: Exchange) -> Ask {
+    Ask {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+async fn populate<T: OrderBook>(orderbook: &mut T) {
+    orderbook
+        .update_bids(
+            vec![
+                create_bid(100.0, 2.0, Exchange::Binance),
+                create_bid(100.0, 1.0, Exchange::Kraken),
+                create_bid(99.0, 4.0, Exchange::Binance),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+    orderbook
+        .update_asks(
+            vec![
+                create_ask(101.0, 1.0, Exchange::Binance),
+                create_ask(102.0, 2.0, Exchange::Kraken),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_analytics_btree() {
+    let mut orderbook = BTreeOrderBook::new();
+    populate(&mut orderbook).await;
+    test_analytics(&orderbook).await;
+}
+
+#[tokio::test]
+async fn test_analytics_hashmap() {
+    let mut orderbook = HashMapOrderBook::new();
+    populate(&mut orderbook).await;
+    test_analytics(&orderbook).await;
+}
+
+async fn test_analytics<T: OrderBook>(orderbook: &T) {
+    // Top level: 3.0 bid (two exchanges at 100.0) against 1.0 ask
+    assert_eq!(orderbook.imbalance(1).await, Some(0.5));
+    // Two levels: 7.0 bid against 3.0 ask
+    assert_eq!(orderbook.imbalance(2).await, Some(0.4));
+    assert_eq!(orderbook.imbalance(0).await, None);
+
+    assert_eq!(orderbook.mid_price().await, Some(100.5));
+    // (100 * 1 + 101 * 3) / 4 leans towards the thin ask
+    assert_eq!(orderbook.microprice().await, Some(100.75));
+}
+
+#[tokio::test]
+async fn test_analytics_one_sided_and_empty() {
+    let mut orderbook = BTreeOrderBook::new();
+    assert_eq!(orderbook.imbalance(5).await, None);
+    assert_eq!(orderbook.microprice().await, None);
+
+    orderbook
+        .update_asks(vec![create_ask(101.0, 1.0, Exchange::Binance)], 10)
+        .await
+        .unwrap();
+    assert_eq!(orderbook.imbalance(5).await, Some(-1.0));
+    assert_eq!(orderbook.microprice().await, None);
+    assert_eq!(orderbook.mid_price().await, None);
+}
+
+#[tokio::test]
+async fn test_cost_to_fill_walks_every_exchange() {
+    let mut orderbook = HashMapOrderBook::new();
+    populate(&mut orderbook).await;
+
+    // 3.0 at 100 across two exchanges, then 1.0 of the 4.0 at 99
+    let sell = orderbook.cost_to_fill(TradeSide::Sell, 4.0).await.unwrap();
+    assert!(sell.is_complete());
+    assert_eq!(sell.total_cost, 399.0);
+    assert_eq!(sell.average_price, 99.75);
+    assert_eq!(sell.worst_price, 99.0);
+    assert_eq!(sell.levels_consumed, 3);
+
+    let buy = orderbook.cost_to_fill(TradeSide::Buy, 10.0).await.unwrap();
+    assert!(!buy.is_complete());
+    assert_eq!(buy.filled_quantity, 3.0);
+    assert_eq!(buy.worst_price, 102.0);
+}
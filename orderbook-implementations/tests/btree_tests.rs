@@ -56,7 +56,7 @@ async fn test_btree_bid_side_operations() {
         create_bid(101.0, 8.0, Exchange::Kraken),
     ];
 
-    bid_side.update_bids(bids, 10).await;
+    bid_side.update_bids(bids, 10).await.unwrap();
 
     // Test operations
     assert_eq!(bid_side.bid_depth().await, 3);
@@ -91,7 +91,7 @@ async fn test_btree_ask_side_operations() {
         create_ask(103.0, 6.0, Exchange::Kraken),
     ];
 
-    ask_side.update_asks(asks, 10).await;
+    ask_side.update_asks(asks, 10).await.unwrap();
 
     // Test operations
     assert_eq!(ask_side.ask_depth().await, 3);
@@ -119,8 +119,8 @@ async fn test_btree_side_views() {
     let bids = vec![create_bid(100.0, 10.0, Exchange::Binance)];
     let asks = vec![create_ask(101.0, 8.0, Exchange::Binance)];
 
-    orderbook.update_bids(bids, 10).await;
-    orderbook.update_asks(asks, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
+    orderbook.update_asks(asks, 10).await.unwrap();
 
     // Get side views
     let bid_side = orderbook.bid_side();
@@ -150,7 +150,7 @@ async fn test_btree_sorting_behavior() {
         create_bid(99.0, 10.0, Exchange::Binance),
     ];
 
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
 
     // Should be sorted in descending order (highest first)
     let all_bids = orderbook.get_best_n_bids(10).await;
@@ -169,7 +169,7 @@ async fn test_btree_sorting_behavior() {
         create_ask(102.0, 10.0, Exchange::Binance),
     ];
 
-    orderbook.update_asks(asks, 10).await;
+    orderbook.update_asks(asks, 10).await.unwrap();
 
     // Should be sorted in ascending order (lowest first)
     let all_asks = orderbook.get_best_n_asks(10).await;
@@ -195,7 +195,7 @@ async fn test_btree_depth_trimming() {
     ];
 
     // Limit to 3 levels
-    orderbook.update_bids(bids, 3).await;
+    orderbook.update_bids(bids, 3).await.unwrap();
 
     // Should keep only the best 3 (highest prices)
     assert_eq!(orderbook.bid_depth().await, 3);
@@ -215,7 +215,7 @@ async fn test_btree_depth_trimming() {
         create_ask(106.0, 10.0, Exchange::Binance),
     ];
 
-    orderbook.update_asks(asks, 3).await;
+    orderbook.update_asks(asks, 3).await.unwrap();
 
     // Should keep only the best 3 (lowest prices)
     assert_eq!(orderbook.ask_depth().await, 3);
@@ -237,22 +237,30 @@ async fn test_btree_update_same_price_different_exchange() {
         create_bid(100.0, 5.0, Exchange::Kraken),
     ];
 
-    orderbook.update_bids(bids, 10).await;
-    // BTreeSet only keeps one order per price level due to Ord implementation
-    assert_eq!(orderbook.bid_depth().await, 1);
+    orderbook.update_bids(bids, 10).await.unwrap();
+    // Each exchange keeps its own entry at the shared price
+    assert_eq!(orderbook.bid_depth().await, 3);
 
     // Update one exchange
     let update_bid = create_bid(100.0, 20.0, Exchange::Binance);
-    orderbook.update_bids(vec![update_bid], 10).await;
+    orderbook.update_bids(vec![update_bid], 10).await.unwrap();
 
-    // Should still have 1 entry
-    assert_eq!(orderbook.bid_depth().await, 1);
+    // Should still have 3 entries, ordered by exchange within the price
+    assert_eq!(orderbook.bid_depth().await, 3);
 
-    // Verify the remaining bid has the updated quantity
     let all_bids = orderbook.get_best_n_bids(10).await;
-    assert_eq!(all_bids.len(), 1);
-    assert_eq!(all_bids[0].price, 100.0);
-    assert_eq!(all_bids[0].quantity, 20.0);
+    let levels: Vec<_> = all_bids
+        .iter()
+        .map(|bid| (bid.exchange.clone(), bid.quantity))
+        .collect();
+    assert_eq!(
+        levels,
+        vec![
+            (Exchange::Binance, 20.0),
+            (Exchange::Kraken, 5.0),
+            (Exchange::Coinbase, 15.0),
+        ]
+    );
 }
 
 #[tokio::test]
@@ -266,7 +274,7 @@ async fn test_btree_zero_quantity_removal() {
         create_bid(98.0, 15.0, Exchange::Binance),
     ];
 
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 3);
 
     // Remove middle bid by setting quantity to 0
@@ -277,7 +285,7 @@ async fn test_btree_zero_quantity_removal() {
         timestamp: Utc::now(),
     };
 
-    orderbook.update_bids(vec![remove_bid], 10).await;
+    orderbook.update_bids(vec![remove_bid], 10).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 2);
 
     // Verify the correct bid was removed
@@ -292,21 +300,21 @@ async fn test_btree_empty_update() {
 
     // Add some initial data
     let bids = vec![create_bid(100.0, 10.0, Exchange::Binance)];
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 1);
 
     // Update with empty vector
-    orderbook.update_bids(vec![], 10).await;
+    orderbook.update_bids(vec![], 10).await.unwrap();
 
     // Should still have the original bid
     assert_eq!(orderbook.bid_depth().await, 1);
 
     // Same for asks
     let asks = vec![create_ask(101.0, 10.0, Exchange::Binance)];
-    orderbook.update_asks(asks, 10).await;
+    orderbook.update_asks(asks, 10).await.unwrap();
     assert_eq!(orderbook.ask_depth().await, 1);
 
-    orderbook.update_asks(vec![], 10).await;
+    orderbook.update_asks(vec![], 10).await.unwrap();
     assert_eq!(orderbook.ask_depth().await, 1);
 }
 
@@ -320,7 +328,7 @@ async fn test_btree_get_more_than_available() {
         create_bid(99.0, 5.0, Exchange::Binance),
     ];
 
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
 
     // Request more than available
     let many_bids = orderbook.get_best_n_bids(10).await;
@@ -337,7 +345,7 @@ async fn test_btree_clone_behavior() {
 
     // Add some data
     let bids = vec![create_bid(100.0, 10.0, Exchange::Binance)];
-    orderbook1.update_bids(bids, 10).await;
+    orderbook1.update_bids(bids, 10).await.unwrap();
 
     // Clone the orderbook
     let orderbook2 = orderbook1.clone();
@@ -348,7 +356,7 @@ async fn test_btree_clone_behavior() {
 
     // Modify one
     let new_bids = vec![create_bid(99.0, 5.0, Exchange::Binance)];
-    orderbook1.update_bids(new_bids, 10).await;
+    orderbook1.update_bids(new_bids, 10).await.unwrap();
 
     // Both should reflect the change (they share the same Arc)
     assert_eq!(orderbook1.bid_depth().await, 2);
@@ -364,25 +372,25 @@ async fn test_btree_spread_calculation() {
 
     // No spread with only bids
     let bids = vec![create_bid(100.0, 10.0, Exchange::Binance)];
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
     assert!(orderbook.get_spread().await.is_none());
 
     // No spread with only asks
     orderbook.clear().await;
     let asks = vec![create_ask(101.0, 10.0, Exchange::Binance)];
-    orderbook.update_asks(asks, 10).await;
+    orderbook.update_asks(asks, 10).await.unwrap();
     assert!(orderbook.get_spread().await.is_none());
 
     // Spread with both sides
     let bids = vec![create_bid(100.0, 10.0, Exchange::Binance)];
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
 
     let spread = orderbook.get_spread().await.unwrap();
     assert_eq!(spread, 1.0); // 101.0 - 100.0
 
     // Test with different spread
     let new_asks = vec![create_ask(105.0, 10.0, Exchange::Binance)];
-    orderbook.update_asks(new_asks, 10).await;
+    orderbook.update_asks(new_asks, 10).await.unwrap();
 
     let new_spread = orderbook.get_spread().await.unwrap();
     assert_eq!(new_spread, 5.0); // 105.0 - 100.0
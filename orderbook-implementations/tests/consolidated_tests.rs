@@ -0,0 +1,272 @@
+//! Tests for the consolidated cross-exchange order book
+//!
+//! These tests cover merging per-exchange books and attributing levels to exchanges
+
+use aggregator_core::{
+    Ask, Bid, Exchange, MarketType, OrderBookConfig, OrderBookImplementation, PairBookFactory,
+    PriceLevelUpdate, TradingPair,
+};
+use chrono::Utc;
+use orderbook_implementations::{ConsolidatedOrderBook, HashMapOrderBook, OrderBookFactory};
+use uuid::Uuid;
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, quantity: f64, exchange: Exchange) -> Ask {
+    Ask {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create an update for one exchange
+fn create_update(
+    exchange: Exchange,
+    bids: Vec<(f64, f64)>,
+    asks: Vec<(f64, f64)>,
+) -> PriceLevelUpdate {
+    PriceLevelUpdate {
+        id: Uuid::new_v4(),
+        symbol: "BTCUSDT".to_string(),
+        exchange: exchange.clone(),
+        bids: bids
+            .into_iter()
+            .map(|(price, quantity)| create_bid(price, quantity, exchange.clone()))
+            .collect(),
+        asks: asks
+            .into_iter()
+            .map(|(price, quantity)| create_ask(price, quantity, exchange.clone()))
+            .collect(),
+        timestamp: Utc::now(),
+        funding: None,
+        event_time: None,
+        market_type: Some(MarketType::Spot),
+    }
+}
+
+async fn populated_book() -> ConsolidatedOrderBook {
+    let mut book = ConsolidatedOrderBook::new("BTCUSDT", 10);
+    book.apply_update(&create_update(
+        Exchange::Binance,
+        vec![(100.0, 1.0), (99.0, 2.0)],
+        vec![(101.0, 1.5), (102.0, 3.0)],
+    ))
+    .await
+    .unwrap();
+    book.apply_update(&create_update(
+        Exchange::Kraken,
+        vec![(100.5, 0.5), (100.0, 4.0)],
+        vec![(101.0, 2.5), (101.5, 1.0)],
+    ))
+    .await
+    .unwrap();
+    book
+}
+
+#[tokio::test]
+async fn test_consolidated_summary_mixes_venues() {
+    let book = populated_book().await;
+    let summary = book.summary(3).await;
+
+    assert_eq!(summary.symbol, "BTCUSDT");
+    assert_eq!(summary.market_type, Some(MarketType::Spot));
+
+    let bids: Vec<(f64, Exchange)> = summary
+        .bids
+        .iter()
+        .map(|level| (level.price, level.exchange.clone()))
+        .collect();
+    assert_eq!(
+        bids,
+        vec![
+            (100.5, Exchange::Kraken),
+            (100.0, Exchange::Binance),
+            (100.0, Exchange::Kraken),
+        ]
+    );
+
+    let asks: Vec<(f64, Exchange)> = summary
+        .asks
+        .iter()
+        .map(|level| (level.price, level.exchange.clone()))
+        .collect();
+    assert_eq!(
+        asks,
+        vec![
+            (101.0, Exchange::Binance),
+            (101.0, Exchange::Kraken),
+            (101.5, Exchange::Kraken),
+        ]
+    );
+
+    assert!((summary.spread - 0.5).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn test_consolidated_best_by_exchange() {
+    let book = populated_book().await;
+
+    let best_bids = book.best_bid_by_exchange().await;
+    assert_eq!(best_bids.len(), 2);
+    assert_eq!(best_bids[&Exchange::Binance].price, 100.0);
+    assert_eq!(best_bids[&Exchange::Kraken].price, 100.5);
+
+    let best_asks = book.best_ask_by_exchange().await;
+    assert_eq!(best_asks[&Exchange::Binance].price, 101.0);
+    assert_eq!(best_asks[&Exchange::Kraken].price, 101.0);
+}
+
+#[tokio::test]
+async fn test_consolidated_total_liquidity_at() {
+    let book = populated_book().await;
+
+    assert_eq!(book.total_liquidity_at(100.0).await, 5.0);
+    assert_eq!(book.total_liquidity_at(101.0).await, 4.0);
+    assert_eq!(book.total_liquidity_at(105.0).await, 0.0);
+}
+
+#[tokio::test]
+async fn test_consolidated_ladder_groups_prices() {
+    let book = populated_book().await;
+
+    let bids = book.bid_ladder(2).await;
+    assert_eq!(bids.len(), 2);
+    assert_eq!(bids[1].price, 100.0);
+    assert_eq!(bids[1].total_quantity, 5.0);
+    assert_eq!(
+        bids[1].venues,
+        vec![(Exchange::Binance, 1.0), (Exchange::Kraken, 4.0)]
+    );
+
+    let asks = book.ask_ladder(1).await;
+    assert_eq!(asks.len(), 1);
+    assert_eq!(asks[0].total_quantity, 4.0);
+}
+
+#[tokio::test]
+async fn test_consolidated_removal_and_exchange_drop() {
+    let mut book = populated_book().await;
+
+    book.apply_update(&create_update(Exchange::Kraken, vec![(100.5, 0.0)], vec![]))
+        .await
+        .unwrap();
+    assert_eq!(
+        book.get_best_bid().await.unwrap().exchange,
+        Exchange::Binance
+    );
+
+    book.remove_exchange(&Exchange::Binance);
+    assert_eq!(book.exchanges(), vec![Exchange::Kraken]);
+    assert_eq!(book.get_best_bid().await.unwrap().price, 100.0);
+    assert_eq!(
+        book.get_best_bid().await.unwrap().exchange,
+        Exchange::Kraken
+    );
+}
+
+#[tokio::test]
+async fn test_consolidated_crossed_spread_and_empty() {
+    let mut book: ConsolidatedOrderBook<HashMapOrderBook> =
+        ConsolidatedOrderBook::new("BTCUSDT", 10);
+    assert_eq!(book.get_spread().await, None);
+    assert_eq!(book.summary(5).await.spread, 0.0);
+
+    book.update_bids(
+        Exchange::Binance,
+        vec![create_bid(101.0, 1.0, Exchange::Binance)],
+    )
+    .await
+    .unwrap();
+    book.update_asks(
+        Exchange::Kraken,
+        vec![create_ask(100.0, 1.0, Exchange::Kraken)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(book.get_spread().await, Some(-1.0));
+
+    book.clear();
+    assert!(book.exchanges().is_empty());
+}
+
+#[tokio::test]
+async fn test_consolidated_rejects_invalid_update() {
+    let mut book = populated_book().await;
+
+    let update = create_update(
+        Exchange::Coinbase,
+        vec![(100.2, 1.0)],
+        vec![(f64::INFINITY, 1.0)],
+    );
+    assert!(book.apply_update(&update).await.is_err());
+    assert!(!book.exchanges().contains(&Exchange::Coinbase));
+    assert_eq!(book.get_best_bid().await.unwrap().price, 100.5);
+}
+
+#[tokio::test]
+async fn test_factory_books_merge_exchanges() {
+    for implementation in [
+        OrderBookImplementation::BTreeSet,
+        OrderBookImplementation::HashMap,
+        OrderBookImplementation::CopyOnWrite,
+        OrderBookImplementation::AvlTree,
+    ] {
+        let factory = OrderBookFactory::new(OrderBookConfig {
+            max_depth: 1,
+            market_type: MarketType::Spot,
+            update_interval: 100,
+            cleanup_interval: 1000,
+            implementation,
+        });
+        let mut book = factory.create(&TradingPair::new("BTC", "USDT"), "BTCUSDT");
+
+        book.apply_update(&create_update(
+            Exchange::Binance,
+            vec![(100.0, 1.0), (99.0, 2.0)],
+            vec![(101.0, 1.5)],
+        ))
+        .await
+        .unwrap();
+        book.apply_update(&create_update(
+            Exchange::Kraken,
+            vec![(100.5, 0.5)],
+            vec![(102.0, 1.0)],
+        ))
+        .await
+        .unwrap();
+
+        // Each exchange keeps one level per side, and the summary merges both
+        let summary = book.summary(10).await;
+        assert_eq!(summary.symbol, "BTCUSDT");
+        assert_eq!(summary.bids.len(), 2);
+        assert_eq!(summary.bids[0].exchange, Exchange::Kraken);
+        assert_eq!(summary.bids[1].price, 100.0);
+        assert_eq!(summary.asks.len(), 2);
+        assert_eq!(summary.spread, 0.5);
+
+        let kraken = book.exchange_summary(&Exchange::Kraken, 10).await.unwrap();
+        assert_eq!(kraken.bids[0].price, 100.5);
+        assert_eq!(kraken.asks[0].price, 102.0);
+        assert_eq!(kraken.spread, 1.5);
+        assert!(book.exchange_summary(&Exchange::Bybit, 10).await.is_none());
+
+        // Removing an exchange drops its levels from the merged summary
+        book.remove_exchange(&Exchange::Kraken).await;
+        assert!(book.exchange_summary(&Exchange::Kraken, 10).await.is_none());
+        let summary = book.summary(10).await;
+        assert_eq!(summary.bids.len(), 1);
+        assert_eq!(summary.bids[0].exchange, Exchange::Binance);
+        assert_eq!(summary.spread, 1.0);
+    }
+}
@@ -0,0 +1,127 @@
+//! Tests for crossed and locked book detection
+//!
+//! These tests cover telling feed errors within one exchange apart from arbitrage across
+//! exchanges, and publishing each crossing once
+
+use aggregator_core::{Ask, Bid, Exchange};
+use chrono::Utc;
+use orderbook_implementations::{ConsolidatedOrderBook, CrossedBookEvent, CrossingKind};
+use tokio::sync::broadcast;
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, quantity: f64, exchange: Exchange) -> Ask {
+    Ask {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+async fn quote(book: &mut ConsolidatedOrderBook, exchange: Exchange, bid: f64, ask: f64) {
+    book.update_bids(
+        exchange.clone(),
+        vec![create_bid(bid, 1.0, exchange.clone())],
+    )
+    .await
+    .unwrap();
+    book.update_asks(exchange.clone(), vec![create_ask(ask, 1.0, exchange)])
+        .await
+        .unwrap();
+}
+
+fn drain(rx: &mut broadcast::Receiver<CrossedBookEvent>) -> Vec<CrossedBookEvent> {
+    let mut events = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        events.push(event);
+    }
+    events
+}
+
+#[tokio::test]
+async fn test_healthy_book_has_no_crossings() {
+    let mut book: ConsolidatedOrderBook = ConsolidatedOrderBook::new("BTCUSDT", 10);
+    quote(&mut book, Exchange::Binance, 100.0, 101.0).await;
+    quote(&mut book, Exchange::Kraken, 100.5, 101.5).await;
+    assert!(book.crossings().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_venue_crossing_is_not_arbitrage() {
+    let mut book: ConsolidatedOrderBook = ConsolidatedOrderBook::new("BTCUSDT", 10);
+    quote(&mut book, Exchange::Binance, 100.0, 101.0).await;
+    // Kraken's feed is broken: its bid is above both its own ask and Binance's ask
+    quote(&mut book, Exchange::Kraken, 102.0, 99.0).await;
+
+    let crossings = book.crossings().await;
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].kind, CrossingKind::Venue);
+    assert_eq!(crossings[0].best_bid.exchange, Exchange::Kraken);
+    assert_eq!(crossings[0].overlap(), 3.0);
+}
+
+#[tokio::test]
+async fn test_cross_venue_crossing_and_lock() {
+    let mut book: ConsolidatedOrderBook = ConsolidatedOrderBook::new("BTCUSDT", 10);
+    quote(&mut book, Exchange::Binance, 100.0, 101.0).await;
+    quote(&mut book, Exchange::Kraken, 101.0, 102.0).await;
+
+    let crossings = book.crossings().await;
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].kind, CrossingKind::CrossVenue);
+    assert_eq!(crossings[0].best_bid.exchange, Exchange::Kraken);
+    assert_eq!(crossings[0].best_ask.exchange, Exchange::Binance);
+    assert!(crossings[0].is_locked());
+}
+
+#[tokio::test]
+async fn test_crossing_events_are_published_once() {
+    let (tx, mut rx) = broadcast::channel(16);
+    let mut book: ConsolidatedOrderBook =
+        ConsolidatedOrderBook::new("BTCUSDT", 10).with_crossing_events(tx);
+
+    quote(&mut book, Exchange::Binance, 100.0, 101.0).await;
+    quote(&mut book, Exchange::Kraken, 101.5, 102.0).await;
+    let events = drain(&mut rx);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, CrossingKind::CrossVenue);
+    assert_eq!(events[0].symbol, "BTCUSDT");
+
+    // Still crossed after another update: nothing new is published
+    book.update_bids(
+        Exchange::Kraken,
+        vec![create_bid(101.4, 2.0, Exchange::Kraken)],
+    )
+    .await
+    .unwrap();
+    assert!(drain(&mut rx).is_empty());
+
+    // Clearing and recurring publishes again
+    book.update_bids(
+        Exchange::Kraken,
+        vec![
+            create_bid(101.5, 0.0, Exchange::Kraken),
+            create_bid(101.4, 0.0, Exchange::Kraken),
+        ],
+    )
+    .await
+    .unwrap();
+    assert!(book.crossings().await.is_empty());
+    book.update_bids(
+        Exchange::Kraken,
+        vec![create_bid(101.2, 1.0, Exchange::Kraken)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(drain(&mut rx).len(), 1);
+}
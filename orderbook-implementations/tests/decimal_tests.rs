@@ -0,0 +1,146 @@
+//! Tests for the decimal-keyed order book
+//!
+//! These tests focus on exact price matching and rejecting non-finite levels
+
+use aggregator_core::decimal::{Decimal, DecimalLevel};
+use aggregator_core::{Ask, Bid, Exchange, PriceLevelUpdate};
+use chrono::Utc;
+use orderbook_implementations::{DecimalOrderBook, OrderBook};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, quantity: f64, exchange: Exchange) -> Ask {
+    Ask {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+#[tokio::test]
+async fn test_decimal_ordering_and_depth() {
+    let mut orderbook = DecimalOrderBook::new();
+    orderbook
+        .update_bids(
+            vec![
+                create_bid(99.0, 1.0, Exchange::Binance),
+                create_bid(101.0, 1.0, Exchange::Binance),
+                create_bid(100.0, 1.0, Exchange::Kraken),
+            ],
+            2,
+        )
+        .await
+        .unwrap();
+    orderbook
+        .update_asks(
+            vec![
+                create_ask(103.0, 1.0, Exchange::Binance),
+                create_ask(102.0, 1.0, Exchange::Kraken),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+
+    let bids = orderbook.get_best_n_bids(10).await;
+    assert_eq!(bids.len(), 2);
+    assert_eq!(bids[0].price, 101.0);
+    assert_eq!(bids[1].price, 100.0);
+    assert_eq!(orderbook.get_best_ask().await.unwrap().price, 102.0);
+    assert_eq!(orderbook.get_spread().await, Some(1.0));
+}
+
+#[tokio::test]
+async fn test_decimal_exact_quantity_sum() {
+    let mut orderbook = DecimalOrderBook::new();
+    orderbook
+        .update_bids(
+            vec![
+                create_bid(0.3, 0.1, Exchange::Binance),
+                create_bid(0.3, 0.2, Exchange::Kraken),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(orderbook.bid_quantity_at(dec("0.3")).await, dec("0.3"));
+    assert_eq!(orderbook.ask_quantity_at(dec("0.3")).await, Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn test_decimal_removal_matches_exact_price() {
+    let mut orderbook = DecimalOrderBook::new();
+    let level = DecimalLevel::parse("30000.10", "2", Exchange::Binance, Utc::now()).unwrap();
+    orderbook.update_decimal_asks(vec![level], 10).await;
+
+    orderbook
+        .update_asks(vec![create_ask(30000.1, 0.0, Exchange::Binance)], 10)
+        .await
+        .unwrap();
+    assert_eq!(orderbook.ask_depth().await, 0);
+}
+
+#[tokio::test]
+async fn test_decimal_rejects_non_finite_levels() {
+    let mut orderbook = DecimalOrderBook::new();
+    for bid in [
+        create_bid(f64::NAN, 1.0, Exchange::Binance),
+        create_bid(f64::INFINITY, 1.0, Exchange::Binance),
+        create_bid(100.0, f64::NAN, Exchange::Binance),
+    ] {
+        assert!(orderbook
+            .update_bids(vec![bid, create_bid(100.0, 1.0, Exchange::Kraken)], 10)
+            .await
+            .is_err());
+    }
+
+    assert_eq!(orderbook.bid_depth().await, 0);
+}
+
+#[tokio::test]
+async fn test_decimal_apply_update() {
+    let mut orderbook = DecimalOrderBook::new().with_stats();
+    let update = PriceLevelUpdate {
+        id: Uuid::new_v4(),
+        symbol: "BTCUSDT".to_string(),
+        exchange: Exchange::Binance,
+        bids: vec![
+            create_bid(100.1, 1.0, Exchange::Binance),
+            create_bid(100.0, 1.0, Exchange::Binance),
+        ],
+        asks: vec![create_ask(100.3, 2.0, Exchange::Binance)],
+        timestamp: Utc::now(),
+        funding: None,
+        event_time: None,
+        market_type: None,
+    };
+    orderbook.apply_update(&update, 1).await.unwrap();
+
+    assert_eq!(
+        orderbook.best_n_decimal_bids(5).await[0].price,
+        dec("100.1")
+    );
+    assert_eq!(orderbook.ask_quantity_at(dec("100.3")).await, dec("2"));
+
+    let stats = orderbook.stats().unwrap();
+    assert_eq!(stats.updates_applied, 3);
+    assert_eq!(stats.levels_trimmed, 1);
+    assert_eq!(stats.lock_acquisitions, 1);
+}
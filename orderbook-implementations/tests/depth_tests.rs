@@ -0,0 +1,122 @@
+//! Tests for price-bucketed depth aggregation
+//!
+//! These tests run against every implementation through the `OrderBook` trait
+
+use aggregator_core::{Ask, Bid, Exchange};
+use chrono::Utc;
+use orderbook_implementations::{BTreeOrderBook, HashMapOrderBook, OrderBook};
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, quantity: f64, exchange: Exchange) -> Ask {
+    Ask {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[tokio::test]
+async fn test_depth_buckets_btree() {
+    test_depth_buckets(BTreeOrderBook::new()).await;
+}
+
+#[tokio::test]
+async fn test_depth_buckets_hashmap() {
+    test_depth_buckets(HashMapOrderBook::new()).await;
+}
+
+async fn test_depth_buckets<T: OrderBook>(mut orderbook: T) {
+    orderbook
+        .update_bids(
+            vec![
+                create_bid(100.3, 1.0, Exchange::Binance),
+                create_bid(100.05, 2.0, Exchange::Kraken),
+                create_bid(99.9, 3.0, Exchange::Binance),
+                create_bid(99.0, 4.0, Exchange::Binance),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+    orderbook
+        .update_asks(
+            vec![
+                create_ask(100.4, 1.0, Exchange::Binance),
+                create_ask(100.5, 2.0, Exchange::Kraken),
+                create_ask(100.6, 3.0, Exchange::Binance),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+
+    let depth = orderbook.get_depth_buckets(0.5, 10).await.unwrap();
+
+    let bids: Vec<(f64, f64, f64)> = depth
+        .bids
+        .iter()
+        .map(|bucket| (bucket.price, bucket.quantity, bucket.cumulative_quantity))
+        .collect();
+    assert_eq!(bids.len(), 3);
+    assert_close(bids[0].0, 100.0);
+    assert_close(bids[0].1, 3.0);
+    assert_close(bids[1].0, 99.5);
+    assert_close(bids[1].2, 6.0);
+    assert_close(bids[2].0, 99.0);
+    assert_close(bids[2].2, 10.0);
+
+    // 100.5 sits on a boundary and stays in the bucket it labels
+    assert_eq!(depth.asks.len(), 2);
+    assert_close(depth.asks[0].price, 100.5);
+    assert_close(depth.asks[0].quantity, 3.0);
+    assert_close(depth.asks[1].price, 101.0);
+    assert_close(depth.asks[1].cumulative_quantity, 6.0);
+
+    // Limiting the number of buckets keeps the best ones
+    let top = orderbook.get_depth_buckets(0.5, 1).await.unwrap();
+    assert_eq!(top.bids.len(), 1);
+    assert_eq!(top.asks.len(), 1);
+    assert_close(top.bids[0].cumulative_quantity, 3.0);
+}
+
+#[tokio::test]
+async fn test_depth_buckets_snap_boundaries() {
+    let mut orderbook = BTreeOrderBook::new();
+    orderbook
+        .update_bids(vec![create_bid(100.3, 1.0, Exchange::Binance)], 10)
+        .await
+        .unwrap();
+
+    let depth = orderbook.get_depth_buckets(0.1, 5).await.unwrap();
+    assert_close(depth.bids[0].price, 100.3);
+}
+
+#[tokio::test]
+async fn test_depth_buckets_reject_invalid_size() {
+    let orderbook = HashMapOrderBook::new();
+    for bucket_size in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(orderbook.get_depth_buckets(bucket_size, 5).await.is_err());
+    }
+    let empty = orderbook.get_depth_buckets(1.0, 5).await.unwrap();
+    assert!(empty.bids.is_empty() && empty.asks.is_empty());
+}
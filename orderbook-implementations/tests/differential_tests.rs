@@ -0,0 +1,130 @@
+//! Property-based differential tests across order book implementations
+//!
+//! Random update sequences are fed to every implementation and their observable state is
+//! compared after each step. Prices come from a small grid so that several exchanges regularly
+//! quote the same price and updates regularly hit existing levels.
+
+use aggregator_core::{Ask, Bid, Exchange};
+use chrono::Utc;
+use orderbook_implementations::{BTreeOrderBook, CowOrderBook, HashMapOrderBook, OrderBook};
+use proptest::prelude::*;
+
+/// One update applied to every book
+#[derive(Debug, Clone)]
+enum Update {
+    Bids(Vec<(u8, u8, u8)>),
+    Asks(Vec<(u8, u8, u8)>),
+}
+
+/// What a caller can observe about a book: depths and every level, best first
+type Observed = (
+    usize,
+    usize,
+    Vec<(f64, f64, Exchange)>,
+    Vec<(f64, f64, Exchange)>,
+);
+
+const EXCHANGES: [Exchange; 3] = [Exchange::Binance, Exchange::Kraken, Exchange::Coinbase];
+
+/// `(price step, quantity, exchange)` with a quantity of 0 meaning removal
+fn level() -> impl Strategy<Value = (u8, u8, u8)> {
+    (0u8..20, 0u8..4, 0u8..3)
+}
+
+fn update() -> impl Strategy<Value = Update> {
+    prop_oneof![
+        prop::collection::vec(level(), 1..6).prop_map(Update::Bids),
+        prop::collection::vec(level(), 1..6).prop_map(Update::Asks),
+    ]
+}
+
+fn create_bid((step, quantity, exchange): (u8, u8, u8)) -> Bid {
+    Bid {
+        price: 100.0 - step as f64 * 0.5,
+        quantity: quantity as f64,
+        exchange: EXCHANGES[exchange as usize].clone(),
+        timestamp: Utc::now(),
+    }
+}
+
+fn create_ask((step, quantity, exchange): (u8, u8, u8)) -> Ask {
+    Ask {
+        price: 100.5 + step as f64 * 0.5,
+        quantity: quantity as f64,
+        exchange: EXCHANGES[exchange as usize].clone(),
+        timestamp: Utc::now(),
+    }
+}
+
+async fn apply<T: OrderBook>(orderbook: &mut T, update: &Update, max_depth: usize) {
+    match update {
+        Update::Bids(levels) => orderbook
+            .update_bids(levels.iter().copied().map(create_bid).collect(), max_depth)
+            .await
+            .unwrap(),
+        Update::Asks(levels) => orderbook
+            .update_asks(levels.iter().copied().map(create_ask).collect(), max_depth)
+            .await
+            .unwrap(),
+    }
+}
+
+async fn observe<T: OrderBook>(orderbook: &T) -> Observed {
+    let bid_depth = orderbook.bid_depth().await;
+    let ask_depth = orderbook.ask_depth().await;
+    let bids = orderbook
+        .get_best_n_bids(bid_depth)
+        .await
+        .into_iter()
+        .map(|bid| (bid.price, bid.quantity, bid.exchange))
+        .collect();
+    let asks = orderbook
+        .get_best_n_asks(ask_depth)
+        .await
+        .into_iter()
+        .map(|ask| (ask.price, ask.quantity, ask.exchange))
+        .collect();
+
+    let best_bid = orderbook.get_best_bid().await;
+    let best_ask = orderbook.get_best_ask().await;
+    let observed: Observed = (bid_depth, ask_depth, bids, asks);
+    assert_eq!(
+        best_bid.map(|bid| (bid.price, bid.exchange)),
+        observed
+            .2
+            .first()
+            .map(|(price, _, exchange)| (*price, exchange.clone()))
+    );
+    assert_eq!(
+        best_ask.map(|ask| (ask.price, ask.exchange)),
+        observed
+            .3
+            .first()
+            .map(|(price, _, exchange)| (*price, exchange.clone()))
+    );
+    observed
+}
+
+proptest! {
+    #[test]
+    fn implementations_agree(updates in prop::collection::vec(update(), 1..60), max_depth in 1usize..15) {
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        rt.block_on(async {
+            let mut btree = BTreeOrderBook::new();
+            let mut hashmap = HashMapOrderBook::new();
+            let mut cow = CowOrderBook::new();
+
+            for update in &updates {
+                apply(&mut btree, update, max_depth).await;
+                apply(&mut hashmap, update, max_depth).await;
+                apply(&mut cow, update, max_depth).await;
+
+                let expected = observe(&btree).await;
+                prop_assert!(expected.0 <= max_depth && expected.1 <= max_depth);
+                prop_assert_eq!(&observe(&hashmap).await, &expected);
+                prop_assert_eq!(&observe(&cow).await, &expected);
+            }
+            Ok(())
+        })?;
+    }
+}
@@ -43,7 +43,8 @@ async fn test_extreme_prices<T: OrderBook>(mut orderbook: T) {
     let large_price_bid = create_bid(f64::MAX / 2.0, 10.0, Exchange::Binance);
     orderbook
         .update_bids(vec![large_price_bid.clone()], 10)
-        .await;
+        .await
+        .unwrap();
 
     let best_bid = orderbook.get_best_bid().await.unwrap();
     assert_eq!(best_bid.price, f64::MAX / 2.0);
@@ -52,7 +53,8 @@ async fn test_extreme_prices<T: OrderBook>(mut orderbook: T) {
     let small_price_bid = create_bid(f64::MIN_POSITIVE, 10.0, Exchange::Binance);
     orderbook
         .update_bids(vec![small_price_bid.clone()], 10)
-        .await;
+        .await
+        .unwrap();
 
     // The large price should still be the best
     let best_bid = orderbook.get_best_bid().await.unwrap();
@@ -64,7 +66,8 @@ async fn test_extreme_prices<T: OrderBook>(mut orderbook: T) {
 
     orderbook
         .update_asks(vec![large_price_ask, small_price_ask], 10)
-        .await;
+        .await
+        .unwrap();
 
     // The small price should be the best ask
     let best_ask = orderbook.get_best_ask().await.unwrap();
@@ -85,14 +88,20 @@ async fn test_extreme_quantities_hashmap() {
 async fn test_extreme_quantities<T: OrderBook>(mut orderbook: T) {
     // Test very large quantity
     let large_qty_bid = create_bid(100.0, f64::MAX / 2.0, Exchange::Binance);
-    orderbook.update_bids(vec![large_qty_bid.clone()], 10).await;
+    orderbook
+        .update_bids(vec![large_qty_bid.clone()], 10)
+        .await
+        .unwrap();
 
     let best_bid = orderbook.get_best_bid().await.unwrap();
     assert_eq!(best_bid.quantity, f64::MAX / 2.0);
 
     // Test very small quantity (but positive)
     let small_qty_bid = create_bid(99.0, f64::MIN_POSITIVE, Exchange::Binance);
-    orderbook.update_bids(vec![small_qty_bid.clone()], 10).await;
+    orderbook
+        .update_bids(vec![small_qty_bid.clone()], 10)
+        .await
+        .unwrap();
 
     assert_eq!(orderbook.bid_depth().await, 2);
 
@@ -103,7 +112,7 @@ async fn test_extreme_quantities<T: OrderBook>(mut orderbook: T) {
         exchange: Exchange::Binance,
         timestamp: Utc::now(),
     };
-    orderbook.update_bids(vec![zero_qty_bid], 10).await;
+    orderbook.update_bids(vec![zero_qty_bid], 10).await.unwrap();
 
     assert_eq!(orderbook.bid_depth().await, 1);
     let remaining_bid = orderbook.get_best_bid().await.unwrap();
@@ -122,30 +131,42 @@ async fn test_special_float_values_hashmap() {
 }
 
 async fn test_special_float_values<T: OrderBook>(mut orderbook: T) {
-    // Note: In a real implementation, you might want to validate inputs
-    // and reject NaN/infinity values. This test documents current behavior.
-
     // Test with normal values first
     let normal_bid = create_bid(100.0, 10.0, Exchange::Binance);
-    orderbook.update_bids(vec![normal_bid], 10).await;
+    orderbook.update_bids(vec![normal_bid], 10).await.unwrap();
 
     assert_eq!(orderbook.bid_depth().await, 1);
 
-    // Test infinity price - behavior may vary by implementation
-    let inf_price_bid = Bid {
-        price: f64::INFINITY,
-        quantity: 10.0,
-        exchange: Exchange::Coinbase,
-        timestamp: Utc::now(),
-    };
+    // Non-finite prices and invalid quantities are rejected
+    for (price, quantity) in [
+        (f64::INFINITY, 10.0),
+        (f64::NEG_INFINITY, 10.0),
+        (f64::NAN, 10.0),
+        (101.0, f64::NAN),
+        (101.0, f64::INFINITY),
+        (101.0, -1.0),
+    ] {
+        let invalid_bid = create_bid(price, quantity, Exchange::Coinbase);
+        let err = orderbook
+            .update_bids(vec![invalid_bid], 10)
+            .await
+            .unwrap_err();
+        assert_eq!(err.category(), "validation");
+
+        let invalid_ask = create_ask(price, quantity, Exchange::Coinbase);
+        assert!(orderbook.update_asks(vec![invalid_ask], 10).await.is_err());
+    }
 
-    // This might panic or handle gracefully depending on implementation
-    // In production, you'd want to validate inputs before this point
-    orderbook.update_bids(vec![inf_price_bid], 10).await;
+    // A batch containing one invalid level is rejected as a whole
+    let batch = vec![
+        create_bid(99.0, 1.0, Exchange::Kraken),
+        create_bid(f64::NAN, 1.0, Exchange::Kraken),
+    ];
+    assert!(orderbook.update_bids(batch, 10).await.is_err());
 
-    // If we get here, the implementation handled infinity
-    // The exact behavior depends on how BTreeSet/HashMap handle infinity
-    assert!(orderbook.bid_depth().await >= 1);
+    assert_eq!(orderbook.bid_depth().await, 1);
+    assert_eq!(orderbook.ask_depth().await, 0);
+    assert_eq!(orderbook.get_best_bid().await.unwrap().price, 100.0);
 }
 
 /// Test with maximum depth of 0
@@ -166,7 +187,7 @@ async fn test_zero_max_depth<T: OrderBook>(mut orderbook: T) {
     ];
 
     // Update with max_depth = 0 should keep no orders
-    orderbook.update_bids(bids, 0).await;
+    orderbook.update_bids(bids, 0).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 0);
 
     // Same for asks
@@ -175,7 +196,7 @@ async fn test_zero_max_depth<T: OrderBook>(mut orderbook: T) {
         create_ask(102.0, 5.0, Exchange::Binance),
     ];
 
-    orderbook.update_asks(asks, 0).await;
+    orderbook.update_asks(asks, 0).await.unwrap();
     assert_eq!(orderbook.ask_depth().await, 0);
 }
 
@@ -198,7 +219,7 @@ async fn test_max_depth_one<T: OrderBook>(mut orderbook: T) {
     ];
 
     // Should keep only the best bid
-    orderbook.update_bids(bids, 1).await;
+    orderbook.update_bids(bids, 1).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 1);
 
     let best_bid = orderbook.get_best_bid().await.unwrap();
@@ -211,7 +232,7 @@ async fn test_max_depth_one<T: OrderBook>(mut orderbook: T) {
         create_ask(103.0, 8.0, Exchange::Binance),
     ];
 
-    orderbook.update_asks(asks, 1).await;
+    orderbook.update_asks(asks, 1).await.unwrap();
     assert_eq!(orderbook.ask_depth().await, 1);
 
     let best_ask = orderbook.get_best_ask().await.unwrap();
@@ -235,7 +256,7 @@ async fn test_rapid_updates<T: OrderBook>(mut orderbook: T) {
     // Rapidly update the same price level with different quantities
     for i in 1..=100 {
         let bid = create_bid(price, i as f64, Exchange::Binance);
-        orderbook.update_bids(vec![bid], 10).await;
+        orderbook.update_bids(vec![bid], 10).await.unwrap();
 
         // Should always have exactly one bid at this price
         assert_eq!(orderbook.bid_depth().await, 1);
@@ -263,7 +284,7 @@ async fn test_alternating_add_remove<T: OrderBook>(mut orderbook: T) {
         if i % 2 == 0 {
             // Add order
             let bid = create_bid(price, 10.0, Exchange::Binance);
-            orderbook.update_bids(vec![bid], 10).await;
+            orderbook.update_bids(vec![bid], 10).await.unwrap();
             assert_eq!(orderbook.bid_depth().await, 1);
         } else {
             // Remove order
@@ -273,7 +294,7 @@ async fn test_alternating_add_remove<T: OrderBook>(mut orderbook: T) {
                 exchange: Exchange::Binance,
                 timestamp: Utc::now(),
             };
-            orderbook.update_bids(vec![remove_bid], 10).await;
+            orderbook.update_bids(vec![remove_bid], 10).await.unwrap();
             assert_eq!(orderbook.bid_depth().await, 0);
         }
     }
@@ -314,7 +335,7 @@ async fn test_identical_timestamps<T: OrderBook>(mut orderbook: T) {
         },
     ];
 
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 3);
 
     // Should still be sorted by price, not timestamp
@@ -347,7 +368,7 @@ async fn test_large_batch_updates<T: OrderBook>(mut orderbook: T) {
     }
 
     // Update with limited depth
-    orderbook.update_bids(bids, 100).await;
+    orderbook.update_bids(bids, 100).await.unwrap();
 
     // Should be limited to max depth
     assert_eq!(orderbook.bid_depth().await, 100);
@@ -384,12 +405,12 @@ async fn test_cross_exchange_conflicts<T: OrderBook>(mut orderbook: T) {
         create_bid(100.0, 5.0, Exchange::Kraken),
     ];
 
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 3);
 
     // Update one exchange
     let update_bid = create_bid(100.0, 20.0, Exchange::Binance);
-    orderbook.update_bids(vec![update_bid], 10).await;
+    orderbook.update_bids(vec![update_bid], 10).await.unwrap();
 
     // Should still have 3 orders, with Binance updated
     assert_eq!(orderbook.bid_depth().await, 3);
@@ -419,7 +440,7 @@ async fn test_cross_exchange_conflicts<T: OrderBook>(mut orderbook: T) {
         exchange: Exchange::Coinbase,
         timestamp: Utc::now(),
     };
-    orderbook.update_bids(vec![remove_bid], 10).await;
+    orderbook.update_bids(vec![remove_bid], 10).await.unwrap();
 
     // Should have 2 orders left
     assert_eq!(orderbook.bid_depth().await, 2);
@@ -446,32 +467,35 @@ async fn test_spread_edge_cases<T: OrderBook>(mut orderbook: T) {
 
     // No spread with only bids
     let bid = create_bid(100.0, 10.0, Exchange::Binance);
-    orderbook.update_bids(vec![bid], 10).await;
+    orderbook.update_bids(vec![bid], 10).await.unwrap();
     assert!(orderbook.get_spread().await.is_none());
 
     // No spread with only asks
     orderbook.clear().await;
     let ask = create_ask(101.0, 10.0, Exchange::Binance);
-    orderbook.update_asks(vec![ask], 10).await;
+    orderbook.update_asks(vec![ask], 10).await.unwrap();
     assert!(orderbook.get_spread().await.is_none());
 
     // Negative spread (crossed market)
     let bid = create_bid(102.0, 10.0, Exchange::Binance);
-    orderbook.update_bids(vec![bid], 10).await;
+    orderbook.update_bids(vec![bid], 10).await.unwrap();
 
     let spread = orderbook.get_spread().await.unwrap();
     assert_eq!(spread, -1.0); // 101.0 - 102.0
 
     // Zero spread (same price)
     let same_price_ask = create_ask(102.0, 10.0, Exchange::Binance);
-    orderbook.update_asks(vec![same_price_ask], 10).await;
+    orderbook
+        .update_asks(vec![same_price_ask], 10)
+        .await
+        .unwrap();
 
     let spread = orderbook.get_spread().await.unwrap();
     assert_eq!(spread, 0.0); // 102.0 - 102.0
 
     // Very small spread
     let close_ask = create_ask(102.001, 10.0, Exchange::Binance);
-    orderbook.update_asks(vec![close_ask], 10).await;
+    orderbook.update_asks(vec![close_ask], 10).await.unwrap();
 
     let spread = orderbook.get_spread().await.unwrap();
     assert!((spread - 0.001).abs() < f64::EPSILON);
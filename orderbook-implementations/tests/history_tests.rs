@@ -0,0 +1,110 @@
+//! Tests for the order book history buffer
+//!
+//! These tests cover interval sampling, point-in-time lookups and spread series
+
+use aggregator_core::{Ask, Bid, Exchange};
+use chrono::{DateTime, Duration, Utc};
+use orderbook_implementations::{BTreeOrderBook, BookHistory, OrderBook, OrderBookSnapshot};
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, quantity: f64, exchange: Exchange) -> Ask {
+    Ask {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+fn snapshot_at(timestamp: DateTime<Utc>, bid: f64, ask: f64) -> OrderBookSnapshot {
+    OrderBookSnapshot {
+        bids: vec![
+            create_bid(bid, 1.0, Exchange::Binance),
+            create_bid(bid - 1.0, 1.0, Exchange::Binance),
+        ],
+        asks: vec![
+            create_ask(ask, 1.0, Exchange::Binance),
+            create_ask(ask + 1.0, 1.0, Exchange::Binance),
+        ],
+        timestamp,
+    }
+}
+
+#[test]
+fn test_record_respects_interval_and_capacity() {
+    let mut history = BookHistory::new(3, std::time::Duration::from_secs(1), 1);
+    let start = Utc::now();
+
+    assert!(history.record(snapshot_at(start, 100.0, 101.0)));
+    assert!(!history.record(snapshot_at(
+        start + Duration::milliseconds(500),
+        100.0,
+        101.0
+    )));
+    for i in 1..=3 {
+        assert!(history.record(snapshot_at(start + Duration::seconds(i), 100.0, 101.0)));
+    }
+
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.latest().unwrap().bids.len(), 1);
+    assert!(history.book_at(start).is_none());
+}
+
+#[test]
+fn test_book_at_and_spread_series() {
+    let mut history = BookHistory::new(10, std::time::Duration::from_secs(1), 5);
+    let start = Utc::now();
+    for i in 0..4 {
+        let bid = 100.0 + i as f64;
+        history.record(snapshot_at(
+            start + Duration::seconds(i),
+            bid,
+            bid + 0.5 * (i + 1) as f64,
+        ));
+    }
+
+    let book = history
+        .book_at(start + Duration::milliseconds(2500))
+        .unwrap();
+    assert_eq!(book.bids[0].price, 102.0);
+    assert!(history.book_at(start - Duration::seconds(1)).is_none());
+
+    let spreads = history.spread_series(start + Duration::seconds(1), start + Duration::seconds(2));
+    let values: Vec<f64> = spreads.iter().map(|(_, spread)| *spread).collect();
+    assert_eq!(values, vec![1.0, 1.5]);
+
+    let mids = history.mid_price_series(start, start + Duration::seconds(10));
+    assert_eq!(mids.len(), 4);
+    assert_eq!(mids[0].1, 100.25);
+}
+
+#[tokio::test]
+async fn test_capture_from_book() {
+    let mut orderbook = BTreeOrderBook::new();
+    orderbook
+        .update_bids(vec![create_bid(100.0, 1.0, Exchange::Kraken)], 10)
+        .await
+        .unwrap();
+    orderbook
+        .update_asks(vec![create_ask(100.5, 1.0, Exchange::Kraken)], 10)
+        .await
+        .unwrap();
+
+    let mut history = BookHistory::new(10, std::time::Duration::from_secs(60), 5);
+    assert!(history.capture(&orderbook).await);
+    assert!(!history.capture(&orderbook).await);
+
+    let series = history.spread_series(Utc::now() - Duration::minutes(1), Utc::now());
+    assert_eq!(series.len(), 1);
+    assert_eq!(series[0].1, 0.5);
+}
@@ -3,11 +3,12 @@
 //! These tests verify that all order book implementations behave consistently
 //! and correctly handle various edge cases and scenarios.
 
-use aggregator_core::{Ask, Bid, Exchange};
+use aggregator_core::{Ask, Bid, Exchange, PriceLevelUpdate};
 use chrono::Utc;
-use orderbook_implementations::{BTreeOrderBook, HashMapOrderBook, OrderBook};
+use orderbook_implementations::{BTreeOrderBook, CowOrderBook, HashMapOrderBook, OrderBook};
 use std::time::Duration;
 use tokio::time::sleep;
+use uuid::Uuid;
 
 /// Helper function to create a test bid
 fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
@@ -40,6 +41,11 @@ async fn test_basic_operations_hashmap() {
     test_basic_operations(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_basic_operations_cow() {
+    test_basic_operations(CowOrderBook::new()).await;
+}
+
 async fn test_basic_operations<T: OrderBook>(mut orderbook: T) {
     // Test empty orderbook
     assert!(orderbook.get_best_bid().await.is_none());
@@ -54,7 +60,7 @@ async fn test_basic_operations<T: OrderBook>(mut orderbook: T) {
         create_bid(99.0, 5.0, Exchange::Binance),
         create_bid(98.0, 15.0, Exchange::Coinbase),
     ];
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
 
     // Test bid operations
     assert_eq!(orderbook.bid_depth().await, 3);
@@ -68,7 +74,7 @@ async fn test_basic_operations<T: OrderBook>(mut orderbook: T) {
         create_ask(102.0, 12.0, Exchange::Coinbase),
         create_ask(103.0, 6.0, Exchange::Binance),
     ];
-    orderbook.update_asks(asks, 10).await;
+    orderbook.update_asks(asks, 10).await.unwrap();
 
     // Test ask operations
     assert_eq!(orderbook.ask_depth().await, 3);
@@ -103,15 +109,20 @@ async fn test_order_updates_hashmap() {
     test_order_updates(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_order_updates_cow() {
+    test_order_updates(CowOrderBook::new()).await;
+}
+
 async fn test_order_updates<T: OrderBook>(mut orderbook: T) {
     // Add initial bid
     let initial_bid = create_bid(100.0, 10.0, Exchange::Binance);
-    orderbook.update_bids(vec![initial_bid], 10).await;
+    orderbook.update_bids(vec![initial_bid], 10).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 1);
 
     // Update with new quantity at same price and exchange
     let updated_bid = create_bid(100.0, 20.0, Exchange::Binance);
-    orderbook.update_bids(vec![updated_bid], 10).await;
+    orderbook.update_bids(vec![updated_bid], 10).await.unwrap();
 
     // Should still have only one bid with updated quantity
     assert_eq!(orderbook.bid_depth().await, 1);
@@ -122,7 +133,8 @@ async fn test_order_updates<T: OrderBook>(mut orderbook: T) {
     let different_exchange_bid = create_bid(100.0, 15.0, Exchange::Coinbase);
     orderbook
         .update_bids(vec![different_exchange_bid], 10)
-        .await;
+        .await
+        .unwrap();
 
     // Note: Due to Ord implementation only comparing price, BTreeSet will treat
     // orders with same price as equal regardless of exchange. The behavior
@@ -142,13 +154,18 @@ async fn test_order_removal_hashmap() {
     test_order_removal(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_order_removal_cow() {
+    test_order_removal(CowOrderBook::new()).await;
+}
+
 async fn test_order_removal<T: OrderBook>(mut orderbook: T) {
     // Add some orders
     let bids = vec![
         create_bid(100.0, 10.0, Exchange::Binance),
         create_bid(99.0, 5.0, Exchange::Binance),
     ];
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 2);
 
     // Remove one order by setting quantity to 0
@@ -158,7 +175,7 @@ async fn test_order_removal<T: OrderBook>(mut orderbook: T) {
         exchange: Exchange::Binance,
         timestamp: Utc::now(),
     };
-    orderbook.update_bids(vec![remove_bid], 10).await;
+    orderbook.update_bids(vec![remove_bid], 10).await.unwrap();
 
     // Should have one less bid
     assert_eq!(orderbook.bid_depth().await, 1);
@@ -172,7 +189,10 @@ async fn test_order_removal<T: OrderBook>(mut orderbook: T) {
         exchange: Exchange::Binance,
         timestamp: Utc::now(),
     };
-    orderbook.update_bids(vec![remove_last_bid], 10).await;
+    orderbook
+        .update_bids(vec![remove_last_bid], 10)
+        .await
+        .unwrap();
 
     // Should be empty
     assert_eq!(orderbook.bid_depth().await, 0);
@@ -190,6 +210,11 @@ async fn test_depth_limiting_hashmap() {
     test_depth_limiting(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_depth_limiting_cow() {
+    test_depth_limiting(CowOrderBook::new()).await;
+}
+
 async fn test_depth_limiting<T: OrderBook>(mut orderbook: T) {
     // Add more bids than the max depth
     let bids = vec![
@@ -201,7 +226,7 @@ async fn test_depth_limiting<T: OrderBook>(mut orderbook: T) {
     ];
 
     // Limit to 3 levels
-    orderbook.update_bids(bids, 3).await;
+    orderbook.update_bids(bids, 3).await.unwrap();
 
     // Should only keep the best 3 bids
     assert_eq!(orderbook.bid_depth().await, 3);
@@ -220,7 +245,7 @@ async fn test_depth_limiting<T: OrderBook>(mut orderbook: T) {
         create_ask(105.0, 10.0, Exchange::Binance),
     ];
 
-    orderbook.update_asks(asks, 3).await;
+    orderbook.update_asks(asks, 3).await.unwrap();
 
     assert_eq!(orderbook.ask_depth().await, 3);
     let all_asks = orderbook.get_best_n_asks(10).await;
@@ -241,13 +266,18 @@ async fn test_clear_orderbook_hashmap() {
     test_clear_orderbook(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_clear_orderbook_cow() {
+    test_clear_orderbook(CowOrderBook::new()).await;
+}
+
 async fn test_clear_orderbook<T: OrderBook>(mut orderbook: T) {
     // Add some orders
     let bids = vec![create_bid(100.0, 10.0, Exchange::Binance)];
     let asks = vec![create_ask(101.0, 10.0, Exchange::Binance)];
 
-    orderbook.update_bids(bids, 10).await;
-    orderbook.update_asks(asks, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
+    orderbook.update_asks(asks, 10).await.unwrap();
 
     assert_eq!(orderbook.bid_depth().await, 1);
     assert_eq!(orderbook.ask_depth().await, 1);
@@ -274,10 +304,15 @@ async fn test_edge_cases_hashmap() {
     test_edge_cases(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_edge_cases_cow() {
+    test_edge_cases(CowOrderBook::new()).await;
+}
+
 async fn test_edge_cases<T: OrderBook>(mut orderbook: T) {
     // Test updating with empty vectors
-    orderbook.update_bids(vec![], 10).await;
-    orderbook.update_asks(vec![], 10).await;
+    orderbook.update_bids(vec![], 10).await.unwrap();
+    orderbook.update_asks(vec![], 10).await.unwrap();
     assert_eq!(orderbook.bid_depth().await, 0);
     assert_eq!(orderbook.ask_depth().await, 0);
 
@@ -289,7 +324,7 @@ async fn test_edge_cases<T: OrderBook>(mut orderbook: T) {
 
     // Add one order and test getting more than available
     let bid = create_bid(100.0, 10.0, Exchange::Binance);
-    orderbook.update_bids(vec![bid], 10).await;
+    orderbook.update_bids(vec![bid], 10).await.unwrap();
 
     let many_bids = orderbook.get_best_n_bids(100).await;
     assert_eq!(many_bids.len(), 1);
@@ -297,7 +332,8 @@ async fn test_edge_cases<T: OrderBook>(mut orderbook: T) {
     // Test max_depth of 0
     orderbook
         .update_bids(vec![create_bid(99.0, 10.0, Exchange::Binance)], 0)
-        .await;
+        .await
+        .unwrap();
     assert_eq!(orderbook.bid_depth().await, 0);
 }
 
@@ -312,6 +348,11 @@ async fn test_multiple_exchanges_hashmap() {
     test_multiple_exchanges(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_multiple_exchanges_cow() {
+    test_multiple_exchanges(CowOrderBook::new()).await;
+}
+
 async fn test_multiple_exchanges<T: OrderBook>(mut orderbook: T) {
     // Add bids at same price from different exchanges
     let bids = vec![
@@ -320,7 +361,7 @@ async fn test_multiple_exchanges<T: OrderBook>(mut orderbook: T) {
         create_bid(100.0, 5.0, Exchange::Kraken),
     ];
 
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
 
     // Note: BTreeSet only keeps one order per price level due to Ord implementation
     // HashMap can keep multiple orders at same price with different exchanges
@@ -336,7 +377,7 @@ async fn test_multiple_exchanges<T: OrderBook>(mut orderbook: T) {
 
     // Update one exchange
     let update_bid = create_bid(100.0, 20.0, Exchange::Binance);
-    orderbook.update_bids(vec![update_bid], 10).await;
+    orderbook.update_bids(vec![update_bid], 10).await.unwrap();
 
     // Should still have at least one entry
     let final_depth = orderbook.bid_depth().await;
@@ -363,6 +404,11 @@ async fn test_concurrent_access_hashmap() {
     test_concurrent_access(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_concurrent_access_cow() {
+    test_concurrent_access(CowOrderBook::new()).await;
+}
+
 async fn test_concurrent_access<T: OrderBook + Clone + 'static>(orderbook: T) {
     let orderbook = std::sync::Arc::new(tokio::sync::Mutex::new(orderbook));
 
@@ -375,7 +421,7 @@ async fn test_concurrent_access<T: OrderBook + Clone + 'static>(orderbook: T) {
         for i in 0..10 {
             let bid = create_bid(100.0 + i as f64, 10.0, Exchange::Binance);
             let mut ob = orderbook_writer.lock().await;
-            ob.update_bids(vec![bid], 100).await;
+            ob.update_bids(vec![bid], 100).await.unwrap();
             sleep(Duration::from_millis(1)).await;
         }
     });
@@ -405,6 +451,132 @@ async fn test_concurrent_access<T: OrderBook + Clone + 'static>(orderbook: T) {
     assert!(ob.bid_depth().await > 0);
 }
 
+/// Helper function to create a price level update carrying both sides
+fn create_update(bids: Vec<Bid>, asks: Vec<Ask>) -> PriceLevelUpdate {
+    PriceLevelUpdate {
+        id: Uuid::new_v4(),
+        symbol: "BTCUSDT".to_string(),
+        exchange: Exchange::Binance,
+        bids,
+        asks,
+        timestamp: Utc::now(),
+        funding: None,
+        event_time: None,
+        market_type: None,
+    }
+}
+
+/// Test applying both sides of an update at once
+#[tokio::test]
+async fn test_apply_update_btree() {
+    test_apply_update(BTreeOrderBook::new()).await;
+}
+
+#[tokio::test]
+async fn test_apply_update_hashmap() {
+    test_apply_update(HashMapOrderBook::new()).await;
+}
+
+#[tokio::test]
+async fn test_apply_update_cow() {
+    test_apply_update(CowOrderBook::new()).await;
+}
+
+async fn test_apply_update<T: OrderBook>(mut orderbook: T) {
+    let update = create_update(
+        vec![
+            create_bid(100.0, 1.0, Exchange::Binance),
+            create_bid(99.0, 2.0, Exchange::Binance),
+            create_bid(98.0, 3.0, Exchange::Binance),
+        ],
+        vec![
+            create_ask(101.0, 1.0, Exchange::Binance),
+            create_ask(102.0, 2.0, Exchange::Binance),
+        ],
+    );
+    orderbook.apply_update(&update, 2).await.unwrap();
+
+    assert_eq!(orderbook.bid_depth().await, 2);
+    assert_eq!(orderbook.ask_depth().await, 2);
+    assert_eq!(orderbook.get_spread().await, Some(1.0));
+
+    // Removals and insertions on both sides
+    let update = create_update(
+        vec![create_bid(100.0, 0.0, Exchange::Binance)],
+        vec![create_ask(100.5, 4.0, Exchange::Binance)],
+    );
+    orderbook.apply_update(&update, 2).await.unwrap();
+
+    assert_eq!(orderbook.get_best_bid().await.unwrap().price, 99.0);
+    assert_eq!(orderbook.get_best_ask().await.unwrap().price, 100.5);
+    assert_eq!(orderbook.ask_depth().await, 2);
+
+    // An invalid ask rejects the bids too
+    let update = create_update(
+        vec![create_bid(99.5, 1.0, Exchange::Binance)],
+        vec![create_ask(f64::NAN, 1.0, Exchange::Binance)],
+    );
+    assert!(orderbook.apply_update(&update, 2).await.is_err());
+    assert_eq!(orderbook.get_best_bid().await.unwrap().price, 99.0);
+}
+
+/// Test that readers never see an update with only one side applied
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_apply_update_not_torn_btree() {
+    test_apply_update_not_torn(BTreeOrderBook::new()).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_apply_update_not_torn_hashmap() {
+    test_apply_update_not_torn(HashMapOrderBook::new()).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_apply_update_not_torn_cow() {
+    test_apply_update_not_torn(CowOrderBook::new()).await;
+}
+
+async fn test_apply_update_not_torn<T: OrderBook + Clone + 'static>(orderbook: T) {
+    // Every update moves both sides up by one, keeping the spread at 1.0; a reader that saw
+    // only the bids moved would see a spread of 0.0
+    let mut writer = orderbook.clone();
+    let writer = tokio::spawn(async move {
+        for i in 0..200 {
+            let price = 100.0 + i as f64;
+            let update = create_update(
+                vec![
+                    create_bid(price - 1.0, 0.0, Exchange::Binance),
+                    create_bid(price, 1.0, Exchange::Binance),
+                ],
+                vec![
+                    create_ask(price, 0.0, Exchange::Binance),
+                    create_ask(price + 1.0, 1.0, Exchange::Binance),
+                ],
+            );
+            writer.apply_update(&update, 100).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+    });
+
+    let mut readers = Vec::new();
+    for _ in 0..3 {
+        let reader = orderbook.clone();
+        readers.push(tokio::spawn(async move {
+            for _ in 0..200 {
+                let spread = reader.get_spread().await;
+                assert!(spread.is_none() || spread == Some(1.0), "{:?}", spread);
+                tokio::task::yield_now().await;
+            }
+        }));
+    }
+
+    writer.await.unwrap();
+    for reader in readers {
+        reader.await.unwrap();
+    }
+    assert_eq!(orderbook.get_best_bid().await.unwrap().price, 299.0);
+}
+
 /// Test price precision handling
 #[tokio::test]
 async fn test_price_precision_btree() {
@@ -416,6 +588,11 @@ async fn test_price_precision_hashmap() {
     test_price_precision(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_price_precision_cow() {
+    test_price_precision(CowOrderBook::new()).await;
+}
+
 async fn test_price_precision<T: OrderBook>(mut orderbook: T) {
     // Test with high precision prices
     let bids = vec![
@@ -424,7 +601,7 @@ async fn test_price_precision<T: OrderBook>(mut orderbook: T) {
         create_bid(100.12345679, 15.0, Exchange::Binance),
     ];
 
-    orderbook.update_bids(bids, 10).await;
+    orderbook.update_bids(bids, 10).await.unwrap();
 
     let best_bid = orderbook.get_best_bid().await.unwrap();
     assert_eq!(best_bid.price, 100.12345679);
@@ -448,6 +625,11 @@ async fn test_large_orderbook_hashmap() {
     test_large_orderbook(HashMapOrderBook::new()).await;
 }
 
+#[tokio::test]
+async fn test_large_orderbook_cow() {
+    test_large_orderbook(CowOrderBook::new()).await;
+}
+
 async fn test_large_orderbook<T: OrderBook>(mut orderbook: T) {
     // Create a large number of orders
     let mut bids = Vec::new();
@@ -459,8 +641,8 @@ async fn test_large_orderbook<T: OrderBook>(mut orderbook: T) {
     }
 
     // Update with large batch
-    orderbook.update_bids(bids, 500).await;
-    orderbook.update_asks(asks, 500).await;
+    orderbook.update_bids(bids, 500).await.unwrap();
+    orderbook.update_asks(asks, 500).await.unwrap();
 
     // Verify depth limiting worked
     assert_eq!(orderbook.bid_depth().await, 500);
@@ -486,3 +668,92 @@ async fn test_large_orderbook<T: OrderBook>(mut orderbook: T) {
         assert!(top_100_asks[i - 1].price <= top_100_asks[i].price);
     }
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_cow_concurrent_writers_and_readers() {
+    let orderbook = CowOrderBook::new();
+
+    let mut writers = Vec::new();
+    for exchange in [Exchange::Binance, Exchange::Kraken] {
+        let mut writer = orderbook.clone();
+        writers.push(tokio::spawn(async move {
+            for i in 0..200 {
+                let bid = create_bid(100.0 - i as f64 * 0.01, 1.0, exchange.clone());
+                writer.update_bids(vec![bid], 1000).await.unwrap();
+            }
+        }));
+    }
+
+    let reader = orderbook.clone();
+    let readers = tokio::spawn(async move {
+        for _ in 0..200 {
+            let bids = reader.load_bids();
+            for i in 1..bids.len() {
+                assert!(bids[i - 1] <= bids[i]);
+            }
+            tokio::task::yield_now().await;
+        }
+    });
+
+    for writer in writers {
+        writer.await.unwrap();
+    }
+    readers.await.unwrap();
+
+    // Read-copy-update keeps every writer's levels
+    assert_eq!(orderbook.bid_depth().await, 400);
+}
+
+#[tokio::test]
+async fn test_hashmap_index_matches_btree() {
+    let mut btree = BTreeOrderBook::new();
+    let mut hashmap = HashMapOrderBook::new();
+    let exchanges = [Exchange::Binance, Exchange::Kraken, Exchange::Coinbase];
+
+    for step in 0..300u32 {
+        let price = 100.0 + ((step * 37) % 50) as f64 * 0.25 - 6.0;
+        let quantity = if step % 4 == 0 {
+            0.0
+        } else {
+            (step % 7) as f64
+        };
+        let exchange = exchanges[(step % 3) as usize].clone();
+        let bids = vec![create_bid(price, quantity, exchange.clone())];
+        let asks = vec![create_ask(price + 20.0, quantity, exchange)];
+
+        btree.update_bids(bids.clone(), 40).await.unwrap();
+        hashmap.update_bids(bids, 40).await.unwrap();
+        btree.update_asks(asks.clone(), 40).await.unwrap();
+        hashmap.update_asks(asks, 40).await.unwrap();
+    }
+
+    let key = |price: f64, quantity: f64, exchange: &Exchange| (price, quantity, exchange.clone());
+    let btree_bids: Vec<_> = btree
+        .get_best_n_bids(100)
+        .await
+        .iter()
+        .map(|b| key(b.price, b.quantity, &b.exchange))
+        .collect();
+    let hashmap_bids: Vec<_> = hashmap
+        .get_best_n_bids(100)
+        .await
+        .iter()
+        .map(|b| key(b.price, b.quantity, &b.exchange))
+        .collect();
+    let btree_asks: Vec<_> = btree
+        .get_best_n_asks(100)
+        .await
+        .iter()
+        .map(|a| key(a.price, a.quantity, &a.exchange))
+        .collect();
+    let hashmap_asks: Vec<_> = hashmap
+        .get_best_n_asks(100)
+        .await
+        .iter()
+        .map(|a| key(a.price, a.quantity, &a.exchange))
+        .collect();
+
+    assert_eq!(btree_bids, hashmap_bids);
+    assert_eq!(btree_asks, hashmap_asks);
+    assert_eq!(hashmap.bid_depth().await, hashmap_bids.len());
+}
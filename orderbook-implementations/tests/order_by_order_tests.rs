@@ -0,0 +1,141 @@
+//! Tests for the order-by-order (L3) book
+//!
+//! These tests cover queue priority, aggregated levels and order lifetime statistics
+
+use aggregator_core::{Exchange, TradeSide};
+use chrono::{Duration, Utc};
+use orderbook_implementations::OrderByOrderBook;
+
+#[test]
+fn test_levels_aggregate_orders() {
+    let mut book = OrderByOrderBook::new(Exchange::Coinbase);
+    let now = Utc::now();
+    book.add_order("b1", TradeSide::Buy, 100.0, 1.0, now)
+        .unwrap();
+    book.add_order("b2", TradeSide::Buy, 100.0, 2.0, now)
+        .unwrap();
+    book.add_order("b3", TradeSide::Buy, 99.0, 5.0, now)
+        .unwrap();
+    book.add_order("a1", TradeSide::Sell, 101.0, 1.5, now)
+        .unwrap();
+    book.add_order("a2", TradeSide::Sell, 102.0, 0.5, now)
+        .unwrap();
+
+    assert_eq!(book.len(), 5);
+    assert_eq!(book.bid_depth(), 2);
+    assert_eq!(book.ask_depth(), 2);
+
+    let bids = book.get_best_n_bids(10);
+    assert_eq!(bids[0].price, 100.0);
+    assert_eq!(bids[0].quantity, 3.0);
+    assert_eq!(bids[1].price, 99.0);
+    assert_eq!(book.get_best_ask().unwrap().price, 101.0);
+    assert_eq!(book.get_best_ask().unwrap().exchange, Exchange::Coinbase);
+
+    let snapshot = book.snapshot();
+    assert_eq!(snapshot.bids.len(), 2);
+    assert_eq!(snapshot.asks.len(), 2);
+}
+
+#[test]
+fn test_queue_position() {
+    let mut book = OrderByOrderBook::new(Exchange::Bitstamp);
+    let now = Utc::now();
+    book.add_order("a", TradeSide::Sell, 50.0, 1.0, now)
+        .unwrap();
+    book.add_order("b", TradeSide::Sell, 50.0, 2.0, now)
+        .unwrap();
+    book.add_order("c", TradeSide::Sell, 50.0, 3.0, now)
+        .unwrap();
+
+    let position = book.queue_position("c").unwrap();
+    assert_eq!(position.orders_ahead, 2);
+    assert_eq!(position.quantity_ahead, 3.0);
+    assert_eq!(position.level_quantity, 6.0);
+
+    // Reducing size keeps priority, increasing it moves the order to the back
+    book.change_order("a", 0.5).unwrap();
+    assert_eq!(book.queue_position("a").unwrap().orders_ahead, 0);
+    book.change_order("b", 4.0).unwrap();
+    assert_eq!(book.queue_position("b").unwrap().orders_ahead, 2);
+    assert_eq!(book.queue_position("c").unwrap().quantity_ahead, 0.5);
+
+    let estimate = book.estimate_queue_position(TradeSide::Sell, 50.0);
+    assert_eq!(estimate.orders_ahead, 3);
+    assert_eq!(estimate.quantity_ahead, 7.5);
+    assert_eq!(
+        book.estimate_queue_position(TradeSide::Buy, 50.0)
+            .orders_ahead,
+        0
+    );
+}
+
+#[test]
+fn test_fills_and_cancels() {
+    let mut book = OrderByOrderBook::new(Exchange::Coinbase);
+    let now = Utc::now();
+    book.add_order("a", TradeSide::Buy, 10.0, 2.0, now).unwrap();
+    book.add_order("b", TradeSide::Buy, 10.0, 1.0, now).unwrap();
+
+    book.fill_order("a", 1.5, now).unwrap();
+    assert_eq!(book.order("a").unwrap().size, 0.5);
+    assert_eq!(book.get_best_bid().unwrap().quantity, 1.5);
+
+    book.fill_order("a", 0.5, now).unwrap();
+    assert!(book.order("a").is_none());
+    assert_eq!(book.queue_position("b").unwrap().orders_ahead, 0);
+
+    let cancelled = book.cancel_order("b", now).unwrap();
+    assert_eq!(cancelled.id, "b");
+    assert!(book.is_empty());
+    assert!(book.get_best_bid().is_none());
+
+    assert!(book.cancel_order("b", now).is_err());
+    assert!(book.fill_order("missing", 1.0, now).is_err());
+}
+
+#[test]
+fn test_rejects_invalid_orders() {
+    let mut book = OrderByOrderBook::new(Exchange::Coinbase);
+    let now = Utc::now();
+    assert!(book
+        .add_order("a", TradeSide::Buy, f64::NAN, 1.0, now)
+        .is_err());
+    assert!(book.add_order("a", TradeSide::Buy, 10.0, 0.0, now).is_err());
+    assert!(book.add_order("a", TradeSide::Buy, -1.0, 1.0, now).is_err());
+
+    book.add_order("a", TradeSide::Buy, 10.0, 1.0, now).unwrap();
+    assert!(book
+        .add_order("a", TradeSide::Sell, 11.0, 1.0, now)
+        .is_err());
+    assert!(book.change_order("a", f64::INFINITY).is_err());
+    assert!(book.fill_order("a", -1.0, now).is_err());
+    assert_eq!(book.len(), 1);
+}
+
+#[test]
+fn test_lifetime_stats() {
+    let mut book = OrderByOrderBook::with_lifetime_window(Exchange::Coinbase, 3);
+    let start = Utc::now();
+    assert_eq!(book.lifetime_stats().fill_ratio(), 0.0);
+
+    for (id, lifetime_ms) in [("a", 100), ("b", 200), ("c", 300), ("d", 1000)] {
+        book.add_order(id, TradeSide::Buy, 10.0, 1.0, start)
+            .unwrap();
+        let end = start + Duration::milliseconds(lifetime_ms);
+        if id == "c" {
+            book.cancel_order(id, end).unwrap();
+        } else {
+            book.fill_order(id, 1.0, end).unwrap();
+        }
+    }
+
+    // The window keeps the last three orders: b (filled), c (cancelled), d (filled)
+    let stats = book.lifetime_stats();
+    assert_eq!(stats.filled, 2);
+    assert_eq!(stats.cancelled, 1);
+    assert_eq!(stats.mean_lifetime_ms, 500.0);
+    assert_eq!(stats.median_lifetime_ms, 300.0);
+    assert_eq!(stats.mean_time_to_fill_ms, Some(600.0));
+    assert!((stats.fill_ratio() - 2.0 / 3.0).abs() < 1e-12);
+}
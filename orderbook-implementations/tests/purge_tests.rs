@@ -0,0 +1,164 @@
+//! Tests for expiring stale price levels
+//!
+//! These tests cover purging by level timestamp on every implementation, in the consolidated
+//! book and from the background purge task
+
+use aggregator_core::{Ask, Bid, Exchange, OrderBookConfig};
+use chrono::{DateTime, Duration, Utc};
+use orderbook_implementations::{
+    spawn_stale_level_purge, BTreeOrderBook, ConsolidatedOrderBook, CowOrderBook, HashMapOrderBook,
+    OrderBook,
+};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, exchange: Exchange, timestamp: DateTime<Utc>) -> Bid {
+    Bid {
+        price,
+        quantity: 1.0,
+        exchange,
+        timestamp,
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, exchange: Exchange, timestamp: DateTime<Utc>) -> Ask {
+    Ask {
+        price,
+        quantity: 1.0,
+        exchange,
+        timestamp,
+    }
+}
+
+async fn populate<T: OrderBook>(orderbook: &mut T) {
+    let stale = Utc::now() - Duration::minutes(10);
+    orderbook
+        .update_bids(
+            vec![
+                create_bid(100.0, Exchange::Binance, Utc::now()),
+                create_bid(100.0, Exchange::Kraken, stale),
+                create_bid(99.0, Exchange::Kraken, stale),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+    orderbook
+        .update_asks(
+            vec![
+                create_ask(101.0, Exchange::Kraken, stale),
+                create_ask(102.0, Exchange::Binance, Utc::now()),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+}
+
+async fn test_purge_older_than<T: OrderBook>(mut orderbook: T) {
+    populate(&mut orderbook).await;
+
+    let removed = orderbook
+        .purge_older_than(std::time::Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(removed, 3);
+
+    let bids = orderbook.get_best_n_bids(10).await;
+    let asks = orderbook.get_best_n_asks(10).await;
+    assert_eq!(bids.len(), 1);
+    assert_eq!(bids[0].exchange, Exchange::Binance);
+    assert_eq!(asks.len(), 1);
+    assert_eq!(asks[0].price, 102.0);
+
+    let removed = orderbook
+        .purge_older_than(std::time::Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(removed, 0);
+}
+
+#[tokio::test]
+async fn test_purge_older_than_btree() {
+    test_purge_older_than(BTreeOrderBook::new()).await;
+}
+
+#[tokio::test]
+async fn test_purge_older_than_hashmap() {
+    test_purge_older_than(HashMapOrderBook::new()).await;
+}
+
+#[tokio::test]
+async fn test_purge_older_than_cow() {
+    test_purge_older_than(CowOrderBook::new()).await;
+}
+
+#[tokio::test]
+async fn test_purge_keeps_everything_for_huge_max_age() {
+    let mut orderbook = BTreeOrderBook::new();
+    populate(&mut orderbook).await;
+    let removed = orderbook
+        .purge_older_than(std::time::Duration::MAX)
+        .await
+        .unwrap();
+    assert_eq!(removed, 0);
+    assert_eq!(orderbook.bid_depth().await, 3);
+}
+
+#[tokio::test]
+async fn test_consolidated_purge_drops_expired_exchanges() {
+    let stale = Utc::now() - Duration::minutes(10);
+    let mut book: ConsolidatedOrderBook = ConsolidatedOrderBook::new("BTCUSDT", 10);
+    book.update_bids(
+        Exchange::Binance,
+        vec![create_bid(100.0, Exchange::Binance, Utc::now())],
+    )
+    .await
+    .unwrap();
+    book.update_bids(
+        Exchange::Kraken,
+        vec![create_bid(100.5, Exchange::Kraken, stale)],
+    )
+    .await
+    .unwrap();
+    book.update_asks(
+        Exchange::Kraken,
+        vec![create_ask(101.0, Exchange::Kraken, stale)],
+    )
+    .await
+    .unwrap();
+
+    let removed = book
+        .purge_older_than(std::time::Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(removed, 2);
+    assert_eq!(book.exchanges(), vec![Exchange::Binance]);
+    assert_eq!(book.get_best_bid().await.unwrap().price, 100.0);
+}
+
+#[tokio::test]
+async fn test_spawned_purge_uses_cleanup_interval() {
+    let mut orderbook = BTreeOrderBook::new();
+    populate(&mut orderbook).await;
+    let orderbook = Arc::new(RwLock::new(orderbook));
+
+    let config = OrderBookConfig {
+        cleanup_interval: 20,
+        ..Default::default()
+    };
+    let handle = spawn_stale_level_purge(orderbook.clone(), &config);
+
+    // Nothing was updated for several intervals, so every level has expired
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(orderbook.read().await.bid_depth().await, 0);
+    assert_eq!(orderbook.read().await.ask_depth().await, 0);
+
+    drop(orderbook);
+    tokio::time::timeout(std::time::Duration::from_secs(1), handle)
+        .await
+        .expect("purge task stops once the book is dropped")
+        .unwrap();
+}
@@ -0,0 +1,139 @@
+//! Tests for the sharded order book map
+//!
+//! These tests cover routing updates per (exchange, symbol) and concurrent writers
+
+use aggregator_core::{Ask, Bid, Exchange};
+use chrono::Utc;
+use orderbook_implementations::{HashMapOrderBook, ShardedOrderBookMap};
+use std::sync::Arc;
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, quantity: f64, exchange: Exchange) -> Ask {
+    Ask {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_books_are_keyed_by_exchange_and_symbol() {
+    let books: ShardedOrderBookMap = ShardedOrderBookMap::with_shards(4);
+    books
+        .update_bids(
+            Exchange::Binance,
+            "BTCUSDT",
+            vec![create_bid(100.0, 1.0, Exchange::Binance)],
+            10,
+        )
+        .await
+        .unwrap();
+    books
+        .update_bids(
+            Exchange::Kraken,
+            "BTCUSDT",
+            vec![create_bid(99.0, 1.0, Exchange::Kraken)],
+            10,
+        )
+        .await
+        .unwrap();
+    books
+        .update_asks(
+            Exchange::Binance,
+            "ETHUSDT",
+            vec![create_ask(10.0, 1.0, Exchange::Binance)],
+            10,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(books.len().await, 3);
+    assert_eq!(books.shard_count(), 4);
+    assert_eq!(
+        books
+            .get_best_bid(&Exchange::Binance, "BTCUSDT")
+            .await
+            .unwrap()
+            .price,
+        100.0
+    );
+    assert_eq!(
+        books
+            .get_best_bid(&Exchange::Kraken, "BTCUSDT")
+            .await
+            .unwrap()
+            .price,
+        99.0
+    );
+    assert!(books
+        .get_best_bid(&Exchange::Binance, "ETHUSDT")
+        .await
+        .is_none());
+    assert!(books
+        .get_best_n_asks(&Exchange::Bybit, "ETHUSDT", 5)
+        .await
+        .is_empty());
+    assert!(books.snapshot(&Exchange::Bybit, "ETHUSDT").await.is_none());
+
+    assert!(books.remove(&Exchange::Kraken, "BTCUSDT").await.is_some());
+    assert!(!books.contains(&Exchange::Kraken, "BTCUSDT").await);
+    books.clear().await;
+    assert!(books.is_empty().await);
+}
+
+#[tokio::test]
+async fn test_rejects_invalid_updates() {
+    let books: ShardedOrderBookMap<HashMapOrderBook> = ShardedOrderBookMap::new();
+    let result = books
+        .update_asks(
+            Exchange::Binance,
+            "BTCUSDT",
+            vec![create_ask(f64::NAN, 1.0, Exchange::Binance)],
+            10,
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_updates_across_symbols() {
+    let books: Arc<ShardedOrderBookMap> = Arc::new(ShardedOrderBookMap::new());
+
+    let mut handles = Vec::new();
+    for task in 0..8 {
+        let books = books.clone();
+        handles.push(tokio::spawn(async move {
+            for i in 0..50 {
+                let symbol = format!("SYM{}", task * 50 + i);
+                for level in 0..5 {
+                    let bid = create_bid(100.0 - level as f64, 1.0, Exchange::Binance);
+                    books
+                        .update_bids(Exchange::Binance, &symbol, vec![bid], 3)
+                        .await
+                        .unwrap();
+                }
+            }
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    assert_eq!(books.len().await, 400);
+    let bids = books
+        .get_best_n_bids(&Exchange::Binance, "SYM123", 10)
+        .await;
+    assert_eq!(bids.len(), 3);
+    assert_eq!(bids[0].price, 100.0);
+}
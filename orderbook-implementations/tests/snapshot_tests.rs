@@ -0,0 +1,141 @@
+//! Tests for order book snapshots and deltas
+//!
+//! These tests cover capturing, serializing and rebuilding books on every implementation
+
+use aggregator_core::{Ask, Bid, Exchange};
+use chrono::Utc;
+use orderbook_implementations::{
+    BTreeOrderBook, HashMapOrderBook, OrderBook, OrderBookDelta, OrderBookSnapshot,
+};
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, quantity: f64, exchange: Exchange) -> Ask {
+    Ask {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+async fn populate<T: OrderBook>(orderbook: &mut T) {
+    orderbook
+        .update_bids(
+            vec![
+                create_bid(100.0, 1.0, Exchange::Binance),
+                create_bid(99.5, 2.0, Exchange::Kraken),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+    orderbook
+        .update_asks(
+            vec![
+                create_ask(101.0, 1.5, Exchange::Binance),
+                create_ask(101.5, 3.0, Exchange::Kraken),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_snapshot_round_trip_btree() {
+    test_snapshot_round_trip(BTreeOrderBook::new(), BTreeOrderBook::new()).await;
+}
+
+#[tokio::test]
+async fn test_snapshot_round_trip_hashmap() {
+    test_snapshot_round_trip(HashMapOrderBook::new(), BTreeOrderBook::new()).await;
+}
+
+async fn test_snapshot_round_trip<S: OrderBook, C: OrderBook>(mut source: S, mut client: C) {
+    populate(&mut source).await;
+
+    let snapshot = source.snapshot().await;
+    assert_eq!(snapshot.bids.len(), 2);
+    assert_eq!(snapshot.bids[0].price, 100.0);
+    assert_eq!(snapshot.asks[0].price, 101.0);
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let decoded: OrderBookSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, snapshot);
+
+    client
+        .update_bids(vec![create_bid(50.0, 1.0, Exchange::Coinbase)], 10)
+        .await
+        .unwrap();
+    client.restore(decoded, 10).await.unwrap();
+
+    let rebuilt = client.snapshot().await;
+    assert_eq!(rebuilt.bids, snapshot.bids);
+    assert_eq!(rebuilt.asks, snapshot.asks);
+}
+
+#[tokio::test]
+async fn test_delta_between_snapshots() {
+    let mut source = BTreeOrderBook::new();
+    populate(&mut source).await;
+    let before = source.snapshot().await;
+
+    source
+        .update_bids(
+            vec![
+                create_bid(100.0, 0.0, Exchange::Binance),
+                create_bid(99.5, 4.0, Exchange::Kraken),
+                create_bid(99.0, 1.0, Exchange::Binance),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+    let after = source.snapshot().await;
+
+    let delta = OrderBookDelta::between(&before, &after);
+    assert!(delta.asks.is_empty());
+    let changes: Vec<(f64, f64)> = delta
+        .bids
+        .iter()
+        .map(|bid| (bid.price, bid.quantity))
+        .collect();
+    assert_eq!(changes, vec![(99.5, 4.0), (99.0, 1.0), (100.0, 0.0)]);
+
+    // A client holding the old state catches up with the delta alone
+    let mut client = HashMapOrderBook::new();
+    client.restore(before, 10).await.unwrap();
+    let json = serde_json::to_string(&delta).unwrap();
+    let decoded: OrderBookDelta = serde_json::from_str(&json).unwrap();
+    client.apply_delta(decoded, 10).await.unwrap();
+
+    let rebuilt = client.snapshot().await;
+    assert_eq!(rebuilt.bids, after.bids);
+    assert_eq!(rebuilt.asks, after.asks);
+
+    assert!(OrderBookDelta::between(&after, &after).is_empty());
+}
+
+#[tokio::test]
+async fn test_apply_delta_rejects_invalid_levels() {
+    let mut orderbook = BTreeOrderBook::new();
+    populate(&mut orderbook).await;
+
+    let delta = OrderBookDelta {
+        bids: vec![create_bid(100.5, 1.0, Exchange::Binance)],
+        asks: vec![create_ask(f64::NAN, 1.0, Exchange::Binance)],
+        timestamp: None,
+    };
+    assert!(orderbook.apply_delta(delta, 10).await.is_err());
+    assert_eq!(orderbook.get_best_bid().await.unwrap().price, 100.0);
+}
@@ -0,0 +1,112 @@
+//! Tests for order book activity counters
+//!
+//! These tests cover counting updates, trimmed levels and lock acquisitions on every
+//! implementation
+
+use aggregator_core::{Ask, Bid, Exchange, PriceLevelUpdate};
+use chrono::Utc;
+use orderbook_implementations::{BTreeOrderBook, CowOrderBook, HashMapOrderBook, OrderBook};
+use uuid::Uuid;
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+async fn test_counts_updates_and_trims<T: OrderBook>(mut orderbook: T, takes_lock: bool) {
+    let bids: Vec<Bid> = (0..8)
+        .map(|i| create_bid(100.0 - i as f64, 1.0, Exchange::Binance))
+        .collect();
+    orderbook.update_bids(bids, 5).await.unwrap();
+    orderbook
+        .update_bids(vec![create_bid(100.0, 0.0, Exchange::Binance)], 5)
+        .await
+        .unwrap();
+
+    let stats = orderbook.stats().expect("stats were enabled");
+    assert_eq!(stats.updates_applied, 9);
+    assert_eq!(stats.levels_trimmed, 3);
+    assert_eq!(stats.lock_acquisitions, if takes_lock { 2 } else { 0 });
+
+    // Rejected updates are not counted
+    assert!(orderbook
+        .update_bids(vec![create_bid(f64::NAN, 1.0, Exchange::Binance)], 5)
+        .await
+        .is_err());
+    assert_eq!(orderbook.stats().unwrap().updates_applied, 9);
+}
+
+#[tokio::test]
+async fn test_counts_updates_and_trims_btree() {
+    test_counts_updates_and_trims(BTreeOrderBook::new().with_stats(), true).await;
+}
+
+#[tokio::test]
+async fn test_counts_updates_and_trims_hashmap() {
+    test_counts_updates_and_trims(HashMapOrderBook::new().with_stats(), true).await;
+}
+
+#[tokio::test]
+async fn test_counts_updates_and_trims_cow() {
+    test_counts_updates_and_trims(CowOrderBook::new().with_stats(), false).await;
+}
+
+#[tokio::test]
+async fn test_stats_disabled_by_default() {
+    let mut orderbook = BTreeOrderBook::new();
+    orderbook
+        .update_bids(vec![create_bid(100.0, 1.0, Exchange::Binance)], 5)
+        .await
+        .unwrap();
+    assert!(orderbook.stats().is_none());
+}
+
+#[tokio::test]
+async fn test_clones_share_stats() {
+    let orderbook = HashMapOrderBook::new().with_stats();
+    let mut writer = orderbook.clone();
+    writer
+        .update_bids(vec![create_bid(100.0, 1.0, Exchange::Binance)], 5)
+        .await
+        .unwrap();
+    assert_eq!(orderbook.stats().unwrap().updates_applied, 1);
+}
+
+async fn test_apply_update_locks_once<T: OrderBook>(mut orderbook: T) {
+    let update = PriceLevelUpdate {
+        id: Uuid::new_v4(),
+        symbol: "BTCUSDT".to_string(),
+        exchange: Exchange::Binance,
+        bids: vec![create_bid(100.0, 1.0, Exchange::Binance)],
+        asks: vec![Ask {
+            price: 101.0,
+            quantity: 1.0,
+            exchange: Exchange::Binance,
+            timestamp: Utc::now(),
+        }],
+        timestamp: Utc::now(),
+        funding: None,
+        event_time: None,
+        market_type: None,
+    };
+    orderbook.apply_update(&update, 5).await.unwrap();
+
+    let stats = orderbook.stats().unwrap();
+    assert_eq!(stats.updates_applied, 2);
+    assert_eq!(stats.lock_acquisitions, 1);
+}
+
+#[tokio::test]
+async fn test_apply_update_locks_once_btree() {
+    test_apply_update_locks_once(BTreeOrderBook::new().with_stats()).await;
+}
+
+#[tokio::test]
+async fn test_apply_update_locks_once_hashmap() {
+    test_apply_update_locks_once(HashMapOrderBook::new().with_stats()).await;
+}
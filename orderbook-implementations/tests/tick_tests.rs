@@ -0,0 +1,143 @@
+//! Tests for tick-size rounding of incoming prices
+//!
+//! These tests focus on noisy prices landing on one level and on rounding with instrument
+//! metadata
+
+use aggregator_core::{Ask, Bid, Exchange, InstrumentInfo, PriceLevelUpdate, TradingPair};
+use chrono::Utc;
+use orderbook_implementations::{BTreeOrderBook, CowOrderBook, HashMapOrderBook, OrderBook};
+use uuid::Uuid;
+
+/// Helper function to create a test bid
+fn create_bid(price: f64, quantity: f64, exchange: Exchange) -> Bid {
+    Bid {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Helper function to create a test ask
+fn create_ask(price: f64, quantity: f64, exchange: Exchange) -> Ask {
+    Ask {
+        price,
+        quantity,
+        exchange,
+        timestamp: Utc::now(),
+    }
+}
+
+fn instrument(tick_size: f64) -> InstrumentInfo {
+    InstrumentInfo {
+        exchange: Exchange::Binance,
+        pair: TradingPair::new("BTC", "USDT"),
+        symbol: "BTCUSDT".to_string(),
+        tick_size,
+        lot_size: 0.001,
+        min_quantity: 0.001,
+        min_notional: None,
+    }
+}
+
+async fn test_noisy_prices_share_a_level<T: OrderBook>(mut orderbook: T) {
+    orderbook
+        .update_bids(vec![create_bid(0.1 + 0.2, 1.0, Exchange::Binance)], 10)
+        .await
+        .unwrap();
+    orderbook
+        .update_bids(vec![create_bid(0.3, 2.0, Exchange::Binance)], 10)
+        .await
+        .unwrap();
+
+    let bids = orderbook.get_best_n_bids(10).await;
+    assert_eq!(bids.len(), 1);
+    assert_eq!(bids[0].price, 0.3);
+    assert_eq!(bids[0].quantity, 2.0);
+
+    // A removal at a noisy price finds the level too
+    orderbook
+        .update_bids(
+            vec![create_bid(0.30000000000000004, 0.0, Exchange::Binance)],
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(orderbook.bid_depth().await, 0);
+}
+
+#[tokio::test]
+async fn test_noisy_prices_share_a_level_btree() {
+    test_noisy_prices_share_a_level(BTreeOrderBook::new().with_tick_size(0.1)).await;
+}
+
+#[tokio::test]
+async fn test_noisy_prices_share_a_level_hashmap() {
+    test_noisy_prices_share_a_level(HashMapOrderBook::new().with_tick_size(0.1)).await;
+}
+
+#[tokio::test]
+async fn test_noisy_prices_share_a_level_cow() {
+    test_noisy_prices_share_a_level(CowOrderBook::new().with_tick_size(0.1)).await;
+}
+
+async fn test_rounds_to_instrument_tick<T: OrderBook>(mut orderbook: T) {
+    let update = PriceLevelUpdate {
+        id: Uuid::new_v4(),
+        symbol: "BTCUSDT".to_string(),
+        exchange: Exchange::Binance,
+        bids: vec![create_bid(100.74, 1.0, Exchange::Binance)],
+        asks: vec![create_ask(100.76, 1.0, Exchange::Binance)],
+        timestamp: Utc::now(),
+        funding: None,
+        event_time: None,
+        market_type: None,
+    };
+    orderbook.apply_update(&update, 10).await.unwrap();
+
+    assert_eq!(orderbook.get_best_bid().await.unwrap().price, 100.5);
+    assert_eq!(orderbook.get_best_ask().await.unwrap().price, 101.0);
+}
+
+#[tokio::test]
+async fn test_rounds_to_instrument_tick_btree() {
+    test_rounds_to_instrument_tick(BTreeOrderBook::new().with_instrument(&instrument(0.5))).await;
+}
+
+#[tokio::test]
+async fn test_rounds_to_instrument_tick_hashmap() {
+    test_rounds_to_instrument_tick(HashMapOrderBook::new().with_instrument(&instrument(0.5))).await;
+}
+
+#[tokio::test]
+async fn test_rounds_to_instrument_tick_cow() {
+    test_rounds_to_instrument_tick(CowOrderBook::new().with_instrument(&instrument(0.5))).await;
+}
+
+#[tokio::test]
+async fn test_invalid_tick_size_leaves_prices_unchanged() {
+    for tick_size in [0.0, -0.1, f64::NAN] {
+        let mut orderbook = BTreeOrderBook::new().with_tick_size(tick_size);
+        orderbook
+            .update_asks(vec![create_ask(100.26, 1.0, Exchange::Binance)], 10)
+            .await
+            .unwrap();
+        assert_eq!(orderbook.get_best_ask().await.unwrap().price, 100.26);
+    }
+}
+
+#[tokio::test]
+async fn test_no_rounding_by_default() {
+    let mut orderbook = BTreeOrderBook::new();
+    orderbook
+        .update_bids(
+            vec![
+                create_bid(0.1 + 0.2, 1.0, Exchange::Binance),
+                create_bid(0.3, 1.0, Exchange::Binance),
+            ],
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(orderbook.bid_depth().await, 2);
+}
@@ -2,12 +2,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Only build protobuf if grpc feature is enabled
     #[cfg(feature = "grpc")]
     {
-        if std::path::Path::new("proto/orderbook_service.proto").exists() {
+        let protos = ["proto/orderbook_service.proto", "proto/orderbook.proto"];
+        for proto in protos {
+            println!("cargo:rerun-if-changed={}", proto);
+        }
+        if protos
+            .iter()
+            .all(|proto| std::path::Path::new(proto).exists())
+        {
             // Try to compile protobuf, but don't fail the build if protoc is not available
             match tonic_build::configure()
                 .build_server(true)
                 .build_client(true)
-                .compile(&["proto/orderbook_service.proto"], &["proto"])
+                .compile(&protos, &["proto"])
             {
                 Ok(_) => println!("cargo:warning=Successfully compiled protobuf files"),
                 Err(e) => {
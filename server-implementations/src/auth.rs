@@ -0,0 +1,252 @@
+//! Authentication shared by the gRPC, REST and WebSocket servers
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use aggregator_core::{AggregatorError, AuthConfig, AuthRole, RateLimitConfig, Result};
+use exchange_connectors::rate_limit::RateLimiter;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+/// An authenticated client: the key name or token subject it is known by and what it may do
+#[derive(Debug, Clone, PartialEq)]
+pub struct Principal {
+    pub name: String,
+    pub role: AuthRole,
+    /// Channels the client may subscribe to, all of them when empty
+    pub channels: Vec<String>,
+}
+
+impl Principal {
+    /// Every client while authentication is disabled
+    pub fn anonymous() -> Self {
+        Self {
+            name: "anonymous".to_string(),
+            role: AuthRole::ReadOnly,
+            channels: Vec::new(),
+        }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.role == AuthRole::Admin
+    }
+
+    /// Returns `AggregatorError::Forbidden` unless the client may subscribe to `channel`
+    pub fn require_channel(&self, channel: &str) -> Result<()> {
+        if self.channels.is_empty() || self.channels.iter().any(|allowed| allowed == channel) {
+            Ok(())
+        } else {
+            Err(AggregatorError::forbidden(format!(
+                "{} may not subscribe to {}",
+                self.name, channel
+            )))
+        }
+    }
+
+    /// Returns `AggregatorError::Forbidden` unless the client is an admin
+    pub fn require_admin(&self) -> Result<()> {
+        if self.is_admin() {
+            Ok(())
+        } else {
+            Err(AggregatorError::forbidden(format!(
+                "{} is not an admin",
+                self.name
+            )))
+        }
+    }
+}
+
+/// The claims read from a token, see `JwtConfig`
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    role: AuthRole,
+    #[serde(default)]
+    channels: Vec<String>,
+}
+
+/// Clients tracked before the rate limiters of those whose budget has refilled are dropped
+const LIMITER_SWEEP_THRESHOLD: usize = 1024;
+
+/// Checks the credentials clients present against an [`AuthConfig`] and limits the requests of
+/// each client it identifies. Clients are rate limited by name, so every connection of a client
+/// draws from the same budget. Keys and the token secret are never printed by `Debug`.
+pub struct Authenticator {
+    enabled: bool,
+    keys: HashMap<String, Principal>,
+    jwt: Option<(DecodingKey, Validation)>,
+    rate_limits: HashMap<String, RateLimitConfig>,
+    default_rate_limit: RateLimitConfig,
+    limiters: Mutex<ClientLimiters>,
+}
+
+/// The rate limiters of the clients an [`Authenticator`] has seen, by name
+#[derive(Default)]
+struct ClientLimiters {
+    by_name: HashMap<String, RateLimiter>,
+    /// Clients tracked when the next new one sweeps out idle limiters
+    sweep_at: usize,
+}
+
+impl Authenticator {
+    pub fn new(config: &AuthConfig) -> Self {
+        let keys = config
+            .api_keys
+            .iter()
+            .map(|key| {
+                let principal = Principal {
+                    name: key.name.clone(),
+                    role: key.role,
+                    channels: key.channels.clone(),
+                };
+                (key.key.clone(), principal)
+            })
+            .collect();
+        let rate_limits = config
+            .api_keys
+            .iter()
+            .filter_map(|key| Some((key.name.clone(), key.rate_limit.clone()?)))
+            .collect();
+        let jwt = config.jwt.as_ref().map(|jwt| {
+            let mut validation = Validation::new(Algorithm::HS256);
+            validation.set_required_spec_claims(&["exp", "sub"]);
+            if let Some(issuer) = &jwt.issuer {
+                validation.set_issuer(&[issuer]);
+            }
+            match &jwt.audience {
+                Some(audience) => validation.set_audience(&[audience]),
+                None => validation.validate_aud = false,
+            }
+            (DecodingKey::from_secret(jwt.secret.as_bytes()), validation)
+        });
+
+        Self {
+            enabled: config.enabled,
+            keys,
+            jwt,
+            rate_limits,
+            default_rate_limit: config.rate_limit.clone(),
+            limiters: Mutex::new(ClientLimiters::default()),
+        }
+    }
+
+    /// Lets every client in as [`Principal::anonymous`]
+    pub fn disabled() -> Self {
+        Self::new(&AuthConfig::default())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Identifies the client presenting `credential`, an API key or a token, and counts the
+    /// request against its rate limit.
+    ///
+    /// Returns:
+    ///
+    /// `AggregatorError::Authentication` if the credential is missing or not valid, and
+    /// `AggregatorError::RateLimit` if the client has used up its requests.
+    pub fn authenticate(&self, credential: Option<&str>) -> Result<Principal> {
+        if !self.enabled {
+            return Ok(Principal::anonymous());
+        }
+        let credential = credential.ok_or_else(|| AggregatorError::Authentication {
+            message: "an API key or token is required".to_string(),
+        })?;
+        let principal = match self.keys.get(credential) {
+            Some(principal) => principal.clone(),
+            None => self.decode_token(credential)?,
+        };
+        self.check_rate(&principal)?;
+        Ok(principal)
+    }
+
+    /// Counts another request of `principal` against its rate limit, returning
+    /// `AggregatorError::RateLimit` once it has used up its requests
+    pub fn check_rate(&self, principal: &Principal) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let mut limiters = self.limiters.lock().unwrap_or_else(|e| e.into_inner());
+        let limiters = &mut *limiters;
+        // Token subjects are not known in advance, so without sweeping the map would grow with
+        // every client ever seen. A limiter that has refilled is the same as a new one.
+        if !limiters.by_name.contains_key(&principal.name)
+            && limiters.by_name.len() >= limiters.sweep_at.max(LIMITER_SWEEP_THRESHOLD)
+        {
+            limiters.by_name.retain(|name, limiter| {
+                limiter.available() < self.rate_limit(name).burst_size.max(1)
+            });
+            limiters.sweep_at = limiters.by_name.len() * 2;
+        }
+        limiters
+            .by_name
+            .entry(principal.name.clone())
+            .or_insert_with(|| {
+                let config = self.rate_limit(&principal.name);
+                RateLimiter::new(&format!("client {}", principal.name), config)
+            })
+            .try_acquire()
+    }
+
+    /// Clients whose rate limit is tracked: those seen lately, and any still short of requests
+    pub fn tracked_clients(&self) -> usize {
+        let limiters = self.limiters.lock().unwrap_or_else(|e| e.into_inner());
+        limiters.by_name.len()
+    }
+
+    fn rate_limit(&self, name: &str) -> &RateLimitConfig {
+        self.rate_limits
+            .get(name)
+            .unwrap_or(&self.default_rate_limit)
+    }
+
+    fn decode_token(&self, token: &str) -> Result<Principal> {
+        let Some((key, validation)) = &self.jwt else {
+            return Err(AggregatorError::Authentication {
+                message: "unknown API key".to_string(),
+            });
+        };
+        let claims = jsonwebtoken::decode::<Claims>(token, key, validation)
+            .map_err(|e| AggregatorError::Authentication {
+                message: format!("not a known API key or valid token ({})", e),
+            })?
+            .claims;
+        Ok(Principal {
+            name: claims.sub,
+            role: claims.role,
+            channels: claims.channels,
+        })
+    }
+}
+
+impl fmt::Debug for Authenticator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&str> = self.keys.values().map(|key| key.name.as_str()).collect();
+        names.sort_unstable();
+        f.debug_struct("Authenticator")
+            .field("enabled", &self.enabled)
+            .field("api_keys", &names)
+            .field("jwt", &self.jwt.as_ref().map(|_| "<redacted>"))
+            .field("default_rate_limit", &self.default_rate_limit)
+            .finish()
+    }
+}
+
+impl Default for Authenticator {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+/// The credential of a request: the token of an `Authorization: Bearer` header, or else the
+/// value of an `X-API-Key` header
+pub fn credential<'a>(authorization: Option<&'a str>, api_key: Option<&'a str>) -> Option<&'a str> {
+    authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or(api_key)
+        .map(str::trim)
+        .filter(|credential| !credential.is_empty())
+}
@@ -1,18 +1,20 @@
 //! gRPC server implementation for crypto orderbook aggregator
 
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 use tokio::task::JoinHandle;
-use tokio_stream::wrappers::ReceiverStream;
 use tonic::codegen::tokio_stream::Stream;
-use tonic::{transport::Server, Request, Response, Status};
-use tracing::{error, info};
+use tonic::transport::{Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
 
-use crate::Server as ServerTrait;
+use crate::auth::{self, Authenticator, Principal};
+use crate::{compact_symbol, Server as ServerTrait};
 use aggregator_core::{
-    Aggregator, AggregatorError, ArbitrageOpportunity, Exchange, HealthStatus, Metrics, Result,
-    Summary, TradingPair,
+    Aggregator, AggregatorError, ArbitrageOpportunity, BackpressureConfig, Exchange, FillEstimate,
+    HealthStatus, Metrics, Result, ShutdownStage, Summary, TlsConfig, TradeSide, TradingPair,
 };
 
 // Define the protobuf service
@@ -20,24 +22,63 @@ pub mod orderbook_service {
     tonic::include_proto!("orderbook_service");
 }
 
+/// The classic `OrderbookAggregator` service, streaming consolidated books
+pub mod orderbook {
+    tonic::include_proto!("orderbook");
+}
+
+use orderbook::orderbook_aggregator_server::{OrderbookAggregator, OrderbookAggregatorServer};
+use orderbook::{BookSummary, BookSummaryRequest, Level};
 use orderbook_service::{
     orderbook_service_server::{OrderbookService, OrderbookServiceServer},
-    ArbitrageMessage, GetAllSummariesRequest, GetAllSummariesResponse, GetHealthStatusRequest,
-    GetHealthStatusResponse, GetMetricsRequest, GetMetricsResponse, GetSummaryRequest,
-    GetSummaryResponse, HealthStatusMessage, MetricsMessage, PriceLevel, StreamArbitrageRequest,
+    ArbitrageMessage, FillEstimateMessage, GetAllSummariesRequest, GetAllSummariesResponse,
+    GetCostToFillRequest, GetCostToFillResponse, GetHealthStatusRequest, GetHealthStatusResponse,
+    GetMetricsRequest, GetMetricsResponse, GetSummaryRequest, GetSummaryResponse,
+    HealthStatusMessage, MetricsMessage, PriceLevel, StreamArbitrageRequest,
     StreamSummariesRequest, SummaryMessage,
 };
 
+/// Most opportunities of one symbol held for its next book update; older ones are dropped
+const MAX_PENDING_OPPORTUNITIES: usize = 32;
+
 /// gRPC server implementation
 pub struct GrpcServer {
     host: String,
     port: u16,
+    backpressure: BackpressureConfig,
+    tls: Option<TlsConfig>,
+    authenticator: Arc<Authenticator>,
 }
 
 impl GrpcServer {
     /// Create new gRPC server
     pub fn new(host: String, port: u16) -> Self {
-        Self { host, port }
+        Self {
+            host,
+            port,
+            backpressure: BackpressureConfig::default(),
+            tls: None,
+            authenticator: Arc::new(Authenticator::disabled()),
+        }
+    }
+
+    /// Buffer each streaming client under `backpressure` instead of the default policy
+    pub fn with_backpressure(mut self, backpressure: BackpressureConfig) -> Self {
+        self.backpressure = backpressure;
+        self
+    }
+
+    /// Serve over TLS with the certificate and key `tls` points to, read on `start`
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Require every call to authenticate with `authenticator`, through `authorization:
+    /// Bearer` or `x-api-key` metadata, which the other servers may share
+    pub fn with_authenticator(mut self, authenticator: Arc<Authenticator>) -> Self {
+        self.authenticator = authenticator;
+        self
     }
 }
 
@@ -46,25 +87,52 @@ impl ServerTrait for GrpcServer {
     async fn start(&self, aggregator: Arc<Aggregator>) -> Result<JoinHandle<Result<()>>> {
         let addr = format!("{}:{}", self.host, self.port)
             .parse()
-            .map_err(|e| AggregatorError::Network(format!("Invalid address: {}", e)))?;
-
-        let service = OrderbookServiceImpl::new(aggregator);
+            .map_err(|e| AggregatorError::network(format!("Invalid address: {}", e)))?;
+
+        let mut builder = Server::builder();
+        if let Some(tls) = &self.tls {
+            let identity = load_identity(tls).await?;
+            builder = builder
+                .tls_config(ServerTlsConfig::new().identity(identity))
+                .map_err(|e| AggregatorError::network(format!("Invalid TLS setup: {}", e)))?;
+        }
 
-        info!("Starting gRPC server on {}", addr);
+        let stopping = aggregator.stopping(ShutdownStage::Servers);
+        let service = OrderbookServiceImpl::new(aggregator.clone())
+            .with_backpressure(self.backpressure.clone());
+        let classic =
+            OrderbookAggregatorImpl::new(aggregator).with_backpressure(self.backpressure.clone());
+        let authenticator = self.authenticator.clone();
+        // Interceptors return tonic's `Status` as is
+        #[allow(clippy::result_large_err)]
+        let authenticate = move |request| authenticate_call(&authenticator, request);
+
+        info!(
+            "Starting gRPC server on {}{}",
+            addr,
+            if self.tls.is_some() { " with TLS" } else { "" }
+        );
 
         let handle = tokio::spawn(async move {
-            Server::builder()
-                .add_service(OrderbookServiceServer::new(service))
-                .serve(addr)
+            builder
+                .add_service(OrderbookServiceServer::with_interceptor(
+                    service,
+                    authenticate.clone(),
+                ))
+                .add_service(OrderbookAggregatorServer::with_interceptor(
+                    classic,
+                    authenticate,
+                ))
+                .serve_with_shutdown(addr, stopping)
                 .await
-                .map_err(|e| AggregatorError::Network(format!("gRPC server error: {}", e)))
+                .map_err(|e| AggregatorError::network(format!("gRPC server error: {}", e)))
         });
 
         Ok(handle)
     }
 
     async fn stop(&self) -> Result<()> {
-        // gRPC server shutdown is handled by dropping the server
+        // The server finishes its calls and stops once the aggregator stops its servers
         Ok(())
     }
 
@@ -77,14 +145,74 @@ impl ServerTrait for GrpcServer {
     }
 }
 
+/// Lets a call through once its client authenticates, and hands its `Principal` to the
+/// service as an extension
+#[allow(clippy::result_large_err)]
+fn authenticate_call(
+    authenticator: &Authenticator,
+    mut request: Request<()>,
+) -> std::result::Result<Request<()>, Status> {
+    let metadata = request.metadata();
+    let value = |key: &str| metadata.get(key).and_then(|value| value.to_str().ok());
+    let principal = authenticator
+        .authenticate(auth::credential(value("authorization"), value("x-api-key")))
+        .map_err(auth_status)?;
+    request.extensions_mut().insert(principal);
+    Ok(request)
+}
+
+/// The client `authenticate_call` let `request` in as
+fn principal<T>(request: &Request<T>) -> Principal {
+    request
+        .extensions()
+        .get::<Principal>()
+        .cloned()
+        .unwrap_or_else(Principal::anonymous)
+}
+
+/// The status of a call refused by the `Authenticator`
+fn auth_status(error: AggregatorError) -> Status {
+    match error.category() {
+        "authentication" => Status::unauthenticated(error.to_string()),
+        "forbidden" => Status::permission_denied(error.to_string()),
+        "rate_limit" => Status::resource_exhausted(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
+}
+
+/// Reads the PEM certificate chain and private key of `tls`
+async fn load_identity(tls: &TlsConfig) -> Result<Identity> {
+    let cert = read_pem("cert_path", &tls.cert_path).await?;
+    let key = read_pem("key_path", &tls.key_path).await?;
+    Ok(Identity::from_pem(cert, key))
+}
+
+async fn read_pem(field: &str, path: &str) -> Result<Vec<u8>> {
+    tokio::fs::read(path).await.map_err(|e| {
+        AggregatorError::validation(
+            format!("server.grpc.tls.{}", field),
+            format!("cannot read {}: {}", path, e),
+        )
+    })
+}
+
 /// gRPC service implementation
 pub struct OrderbookServiceImpl {
     aggregator: Arc<Aggregator>,
+    backpressure: BackpressureConfig,
 }
 
 impl OrderbookServiceImpl {
     pub fn new(aggregator: Arc<Aggregator>) -> Self {
-        Self { aggregator }
+        Self {
+            aggregator,
+            backpressure: BackpressureConfig::default(),
+        }
+    }
+
+    pub fn with_backpressure(mut self, backpressure: BackpressureConfig) -> Self {
+        self.backpressure = backpressure;
+        self
     }
 }
 
@@ -115,8 +243,10 @@ impl OrderbookService for OrderbookServiceImpl {
         _request: Request<GetAllSummariesRequest>,
     ) -> std::result::Result<Response<GetAllSummariesResponse>, Status> {
         let summaries = self.aggregator.get_all_summaries().await;
-        let grpc_summaries: Vec<SummaryMessage> =
-            summaries.into_iter().map(convert_summary_to_grpc).collect();
+        let grpc_summaries: Vec<SummaryMessage> = summaries
+            .into_values()
+            .map(convert_summary_to_grpc)
+            .collect();
 
         let response = GetAllSummariesResponse {
             summaries: grpc_summaries,
@@ -131,13 +261,22 @@ impl OrderbookService for OrderbookServiceImpl {
     /// Stream summaries for all trading pairs
     async fn stream_summaries(
         &self,
-        _request: Request<StreamSummariesRequest>,
+        request: Request<StreamSummariesRequest>,
     ) -> std::result::Result<Response<Self::StreamSummariesStream>, Status> {
-        let mut rx = self.aggregator.subscribe_summaries();
+        principal(&request)
+            .require_channel("summary")
+            .map_err(auth_status)?;
+        let mut rx = self
+            .aggregator
+            .subscribe_summaries_bounded(&self.backpressure);
         let stream = async_stream::stream! {
-            while let Ok(summary) = rx.recv().await {
+            while let Some(summary) = rx.recv().await {
                 yield Ok(convert_summary_to_grpc(summary));
             }
+            if rx.is_disconnected() {
+                warn!("Disconnecting slow summary stream: {:?}", rx.stats());
+                yield Err(Status::resource_exhausted("Summary stream fell too far behind"));
+            }
         };
 
         Ok(Response::new(Box::pin(stream)))
@@ -149,66 +288,268 @@ impl OrderbookService for OrderbookServiceImpl {
     /// Stream arbitrage opportunities
     async fn stream_arbitrage(
         &self,
-        _request: Request<StreamArbitrageRequest>,
+        request: Request<StreamArbitrageRequest>,
     ) -> std::result::Result<Response<Self::StreamArbitrageStream>, Status> {
-        let mut rx = self.aggregator.subscribe_arbitrage();
+        principal(&request)
+            .require_channel("arbitrage")
+            .map_err(auth_status)?;
+        let mut rx = self
+            .aggregator
+            .subscribe_arbitrage_bounded(&self.backpressure);
         let stream = async_stream::stream! {
-            while let Ok(opportunity) = rx.recv().await {
+            while let Some(opportunity) = rx.recv().await {
                 yield Ok(convert_arbitrage_to_grpc(opportunity));
             }
+            if rx.is_disconnected() {
+                warn!("Disconnecting slow arbitrage stream: {:?}", rx.stats());
+                yield Err(Status::resource_exhausted("Arbitrage stream fell too far behind"));
+            }
         };
 
         Ok(Response::new(Box::pin(stream)))
     }
 
-    /// Get health status
+    /// Get the health status of an exchange
     async fn get_health_status(
         &self,
-        _request: Request<GetHealthStatusRequest>,
+        request: Request<GetHealthStatusRequest>,
     ) -> std::result::Result<Response<GetHealthStatusResponse>, Status> {
-        let health_status = self.aggregator.get_health_status().await;
+        let exchange: Exchange = request
+            .into_inner()
+            .exchange
+            .parse()
+            .map_err(|e: AggregatorError| Status::invalid_argument(e.to_string()))?;
+        let health_status = self
+            .aggregator
+            .get_health_status(&exchange)
+            .await
+            .ok_or_else(|| Status::not_found("Health status not found"))?;
         let response = GetHealthStatusResponse {
             health_status: Some(convert_health_status_to_grpc(health_status)),
         };
         Ok(Response::new(response))
     }
 
-    /// Get metrics
+    /// Get the metrics of an exchange
     async fn get_metrics(
         &self,
-        _request: Request<GetMetricsRequest>,
+        request: Request<GetMetricsRequest>,
     ) -> std::result::Result<Response<GetMetricsResponse>, Status> {
-        let metrics = self.aggregator.get_metrics().await;
+        let exchange: Exchange = request
+            .into_inner()
+            .exchange
+            .parse()
+            .map_err(|e: AggregatorError| Status::invalid_argument(e.to_string()))?;
+        let metrics = self
+            .aggregator
+            .get_metrics(&exchange)
+            .await
+            .ok_or_else(|| Status::not_found("Metrics not found"))?;
         let response = GetMetricsResponse {
             metrics: Some(convert_metrics_to_grpc(metrics)),
         };
         Ok(Response::new(response))
     }
+
+    /// Estimate the execution of a market order against a summary
+    async fn get_cost_to_fill(
+        &self,
+        request: Request<GetCostToFillRequest>,
+    ) -> std::result::Result<Response<GetCostToFillResponse>, Status> {
+        let req = request.into_inner();
+        let side = match req.side.to_lowercase().as_str() {
+            "buy" => TradeSide::Buy,
+            "sell" => TradeSide::Sell,
+            _ => return Err(Status::invalid_argument("side must be buy or sell")),
+        };
+        let pair = TradingPair::new(&req.base, &req.quote);
+
+        let summary = self
+            .aggregator
+            .get_summary(&pair)
+            .await
+            .ok_or_else(|| Status::not_found("Summary not found"))?;
+        let estimate = summary.cost_to_fill(side, req.quantity).ok_or_else(|| {
+            Status::failed_precondition("No liquidity for the requested quantity")
+        })?;
+
+        Ok(Response::new(GetCostToFillResponse {
+            estimate: Some(convert_fill_estimate_to_grpc(estimate)),
+        }))
+    }
+}
+
+/// The classic `OrderbookAggregator` service
+pub struct OrderbookAggregatorImpl {
+    aggregator: Arc<Aggregator>,
+    backpressure: BackpressureConfig,
+}
+
+impl OrderbookAggregatorImpl {
+    pub fn new(aggregator: Arc<Aggregator>) -> Self {
+        Self {
+            aggregator,
+            backpressure: BackpressureConfig::default(),
+        }
+    }
+
+    pub fn with_backpressure(mut self, backpressure: BackpressureConfig) -> Self {
+        self.backpressure = backpressure;
+        self
+    }
+}
+
+#[async_trait]
+impl OrderbookAggregator for OrderbookAggregatorImpl {
+    type BookSummaryStreamStream =
+        Pin<Box<dyn Stream<Item = std::result::Result<BookSummary, Status>> + Send>>;
+
+    /// Streams each consolidated book the request selects as it changes, cut to its exchanges
+    /// and depth. Opportunities are held until the next update of their symbol and sent along
+    /// with it, to clients allowed on the arbitrage channel.
+    async fn book_summary_stream(
+        &self,
+        request: Request<BookSummaryRequest>,
+    ) -> std::result::Result<Response<Self::BookSummaryStreamStream>, Status> {
+        let principal = principal(&request);
+        principal.require_channel("summary").map_err(auth_status)?;
+        let with_arbitrage = principal.require_channel("arbitrage").is_ok();
+        let filter = BookFilter::from_request(request.into_inner())
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let mut summaries = self
+            .aggregator
+            .subscribe_summaries_bounded(&self.backpressure);
+        let mut opportunities = self
+            .aggregator
+            .subscribe_arbitrage_bounded(&self.backpressure);
+        let stream = async_stream::stream! {
+            let mut pending: HashMap<String, Vec<ArbitrageOpportunity>> = HashMap::new();
+            let mut arbitrage_open = with_arbitrage;
+            loop {
+                tokio::select! {
+                    summary = summaries.recv() => {
+                        let Some(summary) = summary else { break };
+                        let Some(summary) = filter.apply(summary) else { continue };
+                        let found = pending.remove(&summary.symbol).unwrap_or_default();
+                        yield Ok(BookSummary {
+                            summary: Some(convert_summary_to_classic(summary)),
+                            opportunities: found
+                                .into_iter()
+                                .map(convert_arbitrage_to_classic)
+                                .collect(),
+                        });
+                    }
+                    opportunity = opportunities.recv(), if arbitrage_open => {
+                        match opportunity {
+                            Some(opportunity) if filter.wants_opportunity(&opportunity) => {
+                                let held = pending.entry(opportunity.symbol.clone()).or_default();
+                                held.push(opportunity);
+                                let excess = held.len().saturating_sub(MAX_PENDING_OPPORTUNITIES);
+                                held.drain(..excess);
+                            }
+                            Some(_) => {}
+                            None => arbitrage_open = false,
+                        }
+                    }
+                }
+            }
+            if summaries.is_disconnected() {
+                warn!("Disconnecting slow book summary stream: {:?}", summaries.stats());
+                yield Err(Status::resource_exhausted("Book summary stream fell too far behind"));
+            }
+        };
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// What one `BookSummaryStream` client subscribed to, applied before anything is serialized
+struct BookFilter {
+    /// Compact and uppercase, such as "BTCUSDT"; empty for every symbol
+    symbols: Vec<String>,
+    /// Empty for every exchange
+    exchanges: Vec<Exchange>,
+    /// Levels per side, `None` for all
+    depth: Option<usize>,
+}
+
+impl BookFilter {
+    /// Fails on exchange names that are not known
+    fn from_request(request: BookSummaryRequest) -> Result<Self> {
+        let exchanges = request
+            .exchanges
+            .iter()
+            .map(|name| name.parse::<Exchange>())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            symbols: request
+                .symbols
+                .iter()
+                .map(|symbol| compact_symbol(symbol))
+                .collect(),
+            exchanges,
+            depth: (request.depth > 0).then_some(request.depth as usize),
+        })
+    }
+
+    fn wants_symbol(&self, symbol: &str) -> bool {
+        self.symbols.is_empty() || self.symbols.contains(&compact_symbol(symbol))
+    }
+
+    fn wants_exchange(&self, exchange: &Exchange) -> bool {
+        self.exchanges.is_empty() || self.exchanges.contains(exchange)
+    }
+
+    fn wants_opportunity(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        self.wants_symbol(&opportunity.symbol)
+            && self.wants_exchange(&opportunity.buy_exchange)
+            && self.wants_exchange(&opportunity.sell_exchange)
+    }
+
+    /// `summary` with only the wanted exchanges' levels, to the wanted depth and with its
+    /// spread recomputed, or `None` if its symbol is not wanted or no level is left
+    fn apply(&self, mut summary: Summary) -> Option<Summary> {
+        if !self.wants_symbol(&summary.symbol) {
+            return None;
+        }
+        if !self.exchanges.is_empty() {
+            summary
+                .bids
+                .retain(|level| self.wants_exchange(&level.exchange));
+            summary
+                .asks
+                .retain(|level| self.wants_exchange(&level.exchange));
+            if summary.bids.is_empty() && summary.asks.is_empty() {
+                return None;
+            }
+            summary.spread = match (summary.bids.first(), summary.asks.first()) {
+                (Some(best_bid), Some(best_ask)) => best_ask.price - best_bid.price,
+                _ => 0.0,
+            };
+        }
+        if let Some(depth) = self.depth {
+            summary.bids.truncate(depth);
+            summary.asks.truncate(depth);
+        }
+        Some(summary)
+    }
 }
 
 // --- Conversion functions ---
 
 fn convert_summary_to_grpc(summary: Summary) -> SummaryMessage {
+    let convert_level = |level: aggregator_core::PriceLevel| PriceLevel {
+        price: level.price,
+        quantity: level.quantity,
+        exchange: level.exchange.to_string(),
+        timestamp: level.timestamp.timestamp_millis(),
+    };
     SummaryMessage {
         symbol: summary.symbol,
         spread: summary.spread,
-        bids: summary
-            .bids
-            .into_iter()
-            .map(|l| PriceLevel {
-                price: l.price,
-                amount: l.amount,
-            })
-            .collect(),
-        asks: summary
-            .asks
-            .into_iter()
-            .map(|l| PriceLevel {
-                price: l.price,
-                amount: l.amount,
-            })
-            .collect(),
-        timestamp: summary.timestamp,
+        bids: summary.bids.into_iter().map(convert_level).collect(),
+        asks: summary.asks.into_iter().map(convert_level).collect(),
+        timestamp: summary.timestamp.timestamp_millis(),
     }
 }
 
@@ -220,28 +561,77 @@ fn convert_arbitrage_to_grpc(opportunity: ArbitrageOpportunity) -> ArbitrageMess
         sell_exchange: opportunity.sell_exchange.to_string(),
         buy_price: opportunity.buy_price,
         sell_price: opportunity.sell_price,
-        timestamp: opportunity.timestamp,
+        volume: opportunity.volume,
+        average_buy_price: opportunity.average_buy_price,
+        average_sell_price: opportunity.average_sell_price,
+        time_to_live_ms: opportunity.time_to_live_ms,
+        confidence: opportunity.confidence,
+        timestamp: opportunity.timestamp.timestamp_millis(),
     }
 }
 
 fn convert_health_status_to_grpc(health_status: HealthStatus) -> HealthStatusMessage {
     HealthStatusMessage {
+        exchange: health_status.exchange.to_string(),
         is_healthy: health_status.is_healthy,
-        message: health_status.message,
-        last_update: health_status.last_update,
+        last_update: health_status.last_update.timestamp_millis(),
+        error_message: health_status.error_message.unwrap_or_default(),
     }
 }
 
 fn convert_metrics_to_grpc(metrics: Metrics) -> MetricsMessage {
     MetricsMessage {
+        exchange: metrics.exchange.to_string(),
+        symbol: metrics.symbol,
         updates_per_second: metrics.updates_per_second,
-        connected_exchanges: metrics
-            .connected_exchanges
-            .into_iter()
-            .map(|e| e.to_string())
-            .collect(),
-        active_trading_pairs: metrics.active_trading_pairs,
-        memory_usage_bytes: metrics.memory_usage_bytes,
-        cpu_usage_percentage: metrics.cpu_usage_percentage,
+        latency_ms: metrics.latency_ms,
+        error_count: metrics.error_count,
+        last_update: metrics.last_update.timestamp_millis(),
+    }
+}
+
+fn convert_fill_estimate_to_grpc(estimate: FillEstimate) -> FillEstimateMessage {
+    let side = match estimate.side {
+        TradeSide::Buy => "buy",
+        TradeSide::Sell => "sell",
+    };
+    FillEstimateMessage {
+        side: side.to_string(),
+        requested_quantity: estimate.requested_quantity,
+        filled_quantity: estimate.filled_quantity,
+        average_price: estimate.average_price,
+        worst_price: estimate.worst_price,
+        total_cost: estimate.total_cost,
+        levels_consumed: estimate.levels_consumed as u64,
+    }
+}
+
+fn convert_summary_to_classic(summary: Summary) -> orderbook::Summary {
+    let convert_level = |level: aggregator_core::PriceLevel| Level {
+        exchange: level.exchange.to_string(),
+        price: level.price,
+        amount: level.quantity,
+    };
+    orderbook::Summary {
+        symbol: summary.symbol,
+        spread: summary.spread,
+        bids: summary.bids.into_iter().map(convert_level).collect(),
+        asks: summary.asks.into_iter().map(convert_level).collect(),
+        timestamp_ms: summary.timestamp.timestamp_millis(),
+    }
+}
+
+fn convert_arbitrage_to_classic(
+    opportunity: ArbitrageOpportunity,
+) -> orderbook::ArbitrageOpportunity {
+    orderbook::ArbitrageOpportunity {
+        symbol: opportunity.symbol,
+        buy_exchange: opportunity.buy_exchange.to_string(),
+        sell_exchange: opportunity.sell_exchange.to_string(),
+        buy_price: opportunity.buy_price,
+        sell_price: opportunity.sell_price,
+        profit_percentage: opportunity.profit_percentage,
+        volume: opportunity.volume,
+        timestamp_ms: opportunity.timestamp.timestamp_millis(),
     }
 }
@@ -4,23 +4,35 @@
 //! - gRPC server for high-performance streaming
 //! - REST API server for HTTP-based access
 //! - WebSocket server for real-time web clients
+//! - Prometheus endpoint exporting the aggregator's metrics
+//!
+//! The gRPC, REST and WebSocket servers share one [`auth::Authenticator`], built from
+//! `ServerConfig::auth`.
 
+#[cfg(any(feature = "grpc", feature = "rest", feature = "websocket"))]
+pub mod auth;
 #[cfg(feature = "grpc")]
 pub mod grpc;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
 #[cfg(feature = "rest")]
 pub mod rest;
 #[cfg(feature = "websocket")]
 pub mod websocket;
 
-use aggregator_core::{Aggregator, AggregatorError, Config, Result};
+use aggregator_core::{open_storage, Aggregator, Config, ConnectorRegistry, Result, ShutdownStage};
+#[cfg(any(feature = "rest", feature = "websocket"))]
+use analysis_tools::HeatmapCollector;
 use async_trait::async_trait;
+use orderbook_implementations::OrderBookFactory;
 use std::sync::Arc;
 use tokio::task::JoinHandle;
 
 /// Common trait for all server implementations
 #[async_trait]
 pub trait Server: Send + Sync {
-    /// Start the server
+    /// Start the server, which runs until `Aggregator::stopping(ShutdownStage::Servers)`
+    /// resolves
     async fn start(&self, aggregator: Arc<Aggregator>) -> Result<JoinHandle<Result<()>>>;
 
     /// Stop the server
@@ -36,6 +48,9 @@ pub trait Server: Send + Sync {
 /// Server manager to coordinate multiple server types
 pub struct ServerManager {
     servers: Vec<Box<dyn Server>>,
+    /// Fed once for the servers sharing it
+    #[cfg(any(feature = "rest", feature = "websocket"))]
+    heatmap: Option<HeatmapCollector>,
 }
 
 impl ServerManager {
@@ -43,6 +58,8 @@ impl ServerManager {
     pub fn new() -> Self {
         Self {
             servers: Vec::new(),
+            #[cfg(any(feature = "rest", feature = "websocket"))]
+            heatmap: None,
         }
     }
 
@@ -51,19 +68,30 @@ impl ServerManager {
         self.servers.push(server);
     }
 
-    /// Start all servers
-    pub async fn start_all(
-        &self,
-        aggregator: Arc<Aggregator>,
-    ) -> Result<Vec<JoinHandle<Result<()>>>> {
-        let mut handles = Vec::new();
+    /// Feed `heatmap`, which servers share through `with_heatmap`, from the aggregator's
+    /// summaries once `start_all` runs
+    #[cfg(any(feature = "rest", feature = "websocket"))]
+    pub fn feed_heatmap(&mut self, heatmap: HeatmapCollector) {
+        self.heatmap = Some(heatmap);
+    }
 
+    /// Start all servers. They stop once `Aggregator::shutdown` has stopped everything they
+    /// read from, which waits for them.
+    pub async fn start_all(&self, aggregator: Arc<Aggregator>) -> Result<()> {
+        #[cfg(any(feature = "rest", feature = "websocket"))]
+        if let Some(heatmap) = &self.heatmap {
+            feed_heatmap(&aggregator, heatmap);
+        }
         for server in &self.servers {
             let handle = server.start(aggregator.clone()).await?;
-            handles.push(handle);
+            aggregator.track_task(
+                ShutdownStage::Servers,
+                format!("{} server", server.name()),
+                handle,
+            );
         }
 
-        Ok(handles)
+        Ok(())
     }
 
     /// Stop all servers
@@ -81,15 +109,48 @@ impl Default for ServerManager {
     }
 }
 
+/// An aggregator for `config` running the connectors `exchange-connectors` is compiled with,
+/// registered by `exchange_connectors::register_all`, and keeping each pair's book in the
+/// `orderbook-implementations` book `config.orderbook.implementation` names. When
+/// `config.storage` is enabled the aggregator restores its state from the backend it selects
+/// and snapshots there, or this fails if the backend cannot be opened.
+pub async fn aggregator_from_config(config: Config) -> Result<Aggregator> {
+    let connectors = ConnectorRegistry::new();
+    exchange_connectors::register_all(&connectors).await;
+    let book_factory = Arc::new(OrderBookFactory::new(config.orderbook.clone()));
+    let storage = open_storage(&config.storage)?;
+
+    let mut aggregator = Aggregator::new(config)
+        .with_connectors(connectors)
+        .with_book_factory(book_factory);
+    if let Some(storage) = storage {
+        aggregator = aggregator.with_storage(storage);
+    }
+    Ok(aggregator)
+}
+
 /// Helper to create servers from config
 pub fn create_servers_from_config(config: &Config) -> ServerManager {
     let mut manager = ServerManager::new();
 
+    // Clients authenticate the same way on every server, and share their rate limit across them
+    #[cfg(any(feature = "grpc", feature = "rest", feature = "websocket"))]
+    let authenticator = Arc::new(auth::Authenticator::new(&config.server.auth));
+
+    // REST and WebSocket clients read depth heatmaps from the same collector
+    #[cfg(any(feature = "rest", feature = "websocket"))]
+    let heatmap = HeatmapCollector::default();
+
     // Add gRPC server if enabled and feature is available
     #[cfg(feature = "grpc")]
     if config.server.grpc.enabled {
-        let grpc_server =
-            grpc::GrpcServer::new(config.server.grpc.host.clone(), config.server.grpc.port);
+        let mut grpc_server =
+            grpc::GrpcServer::new(config.server.grpc.host.clone(), config.server.grpc.port)
+                .with_backpressure(config.server.grpc.backpressure.clone())
+                .with_authenticator(authenticator.clone());
+        if let Some(tls) = &config.server.grpc.tls {
+            grpc_server = grpc_server.with_tls(tls.clone());
+        }
         manager.add_server(Box::new(grpc_server));
     }
 
@@ -97,7 +158,9 @@ pub fn create_servers_from_config(config: &Config) -> ServerManager {
     #[cfg(feature = "rest")]
     if config.server.rest.enabled {
         let rest_server =
-            rest::RestServer::new(config.server.rest.host.clone(), config.server.rest.port);
+            rest::RestServer::new(config.server.rest.host.clone(), config.server.rest.port)
+                .with_heatmap(heatmap.clone())
+                .with_authenticator(authenticator.clone());
         manager.add_server(Box::new(rest_server));
     }
 
@@ -108,9 +171,51 @@ pub fn create_servers_from_config(config: &Config) -> ServerManager {
             config.server.websocket.host.clone(),
             config.server.websocket.port,
             config.server.websocket.max_connections,
-        );
+        )
+        .with_heatmap(heatmap.clone())
+        .with_backpressure(config.server.websocket.backpressure.clone())
+        .with_authenticator(authenticator.clone());
         manager.add_server(Box::new(ws_server));
     }
 
+    #[cfg(any(feature = "rest", feature = "websocket"))]
+    if config.server.rest.enabled || config.server.websocket.enabled {
+        manager.feed_heatmap(heatmap);
+    }
+
+    // Add the Prometheus endpoint if metrics are enabled and the feature is available
+    #[cfg(feature = "prometheus")]
+    if config.metrics.enabled && config.metrics.prometheus.enabled {
+        let prometheus_server =
+            prometheus::PrometheusServer::from_config(&config.metrics.prometheus);
+        manager.add_server(Box::new(prometheus_server));
+    }
+
     manager
 }
+
+/// Records the summaries of `aggregator` into `heatmap` until it stops its servers, which
+/// wait for the collector to stop
+#[cfg(any(feature = "rest", feature = "websocket"))]
+pub(crate) fn feed_heatmap(aggregator: &Aggregator, heatmap: &HeatmapCollector) {
+    let collecting = heatmap.spawn(aggregator.subscribe_summaries());
+    let stopping = aggregator.stopping(ShutdownStage::Servers);
+    let handle = tokio::spawn(async move {
+        // The summaries only close with the aggregator, so the collector is stopped here
+        stopping.await;
+        collecting.abort();
+        Ok(())
+    });
+    aggregator.track_task(ShutdownStage::Servers, "heatmap collector", handle);
+}
+
+/// `symbol` without separators and upper-cased, so `BTC/USDT`, `btc-usdt` and `BTCUSDT`
+/// compare equal
+#[cfg(any(feature = "grpc", feature = "websocket"))]
+pub(crate) fn compact_symbol(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
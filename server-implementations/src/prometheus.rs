@@ -0,0 +1,82 @@
+//! Prometheus scrape endpoint for the aggregator's metrics registry
+
+use async_trait::async_trait;
+use axum::{http::header, response::IntoResponse, routing::get, Extension, Router};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+use crate::Server as ServerTrait;
+use aggregator_core::{
+    Aggregator, AggregatorError, MetricsRegistry, PrometheusConfig, Result, ShutdownStage,
+};
+
+/// Content type of the Prometheus text exposition format
+const TEXT_FORMAT: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Serves `Aggregator::registry` on its own listener, at the path Prometheus scrapes
+pub struct PrometheusServer {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl PrometheusServer {
+    pub fn new(host: String, port: u16, path: String) -> Self {
+        Self { host, port, path }
+    }
+
+    pub fn from_config(config: &PrometheusConfig) -> Self {
+        Self::new(config.host.clone(), config.port, config.path.clone())
+    }
+}
+
+#[async_trait]
+impl ServerTrait for PrometheusServer {
+    async fn start(&self, aggregator: Arc<Aggregator>) -> Result<JoinHandle<Result<()>>> {
+        let addr = self.address();
+        let listener = TcpListener::bind(&addr)
+            .await
+            .map_err(|e| AggregatorError::network(format!("Failed to bind to {}: {}", addr, e)))?;
+
+        let app = create_app(&self.path, aggregator.registry());
+        let stopping = aggregator.stopping(ShutdownStage::Servers);
+
+        info!("Serving Prometheus metrics on {}{}", addr, self.path);
+
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(stopping)
+                .await
+                .map_err(|e| AggregatorError::network(format!("Prometheus server error: {}", e)))
+        });
+        Ok(handle)
+    }
+
+    async fn stop(&self) -> Result<()> {
+        // Like the REST server, it stops once the aggregator stops its servers
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "Prometheus"
+    }
+
+    fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+fn create_app(path: &str, registry: Arc<MetricsRegistry>) -> Router {
+    Router::new()
+        .route(path, get(metrics_handler))
+        .layer(Extension(registry))
+}
+
+/// Handler rendering every metric in the text exposition format
+async fn metrics_handler(
+    Extension(registry): Extension<Arc<MetricsRegistry>>,
+) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, TEXT_FORMAT)], registry.render())
+}
@@ -1,27 +1,159 @@
 //! REST server implementation for crypto orderbook aggregator
 
 use async_trait::async_trait;
-use axum::response::Json;
-use axum::{extract::Path, routing::get, Extension, Router};
-use serde_json::json;
+use axum::response::{IntoResponse, Json, Response};
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    routing::{delete, get, post},
+    Extension, Router,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tokio::task::JoinHandle;
 use tracing::{error, info};
 
+use crate::auth::{self, Authenticator, Principal};
 use crate::Server as ServerTrait;
-use aggregator_core::{Aggregator, AggregatorError, Result, Summary, TradingPair};
+use aggregator_core::{
+    Aggregator, AggregatorError, AnalysisConfig, ArbitrageOpportunity, BackpressureSnapshot,
+    Config, ErrorReport, Exchange, ExchangeAttribution, FillEstimate, HealthLevel, HealthStatus,
+    Metrics, OrderBookView, Result, ShutdownStage, Summary, SystemHealth, TradeSide, TradingPair,
+};
+use analysis_tools::{
+    spawn_opportunity_recorder, ExchangePairMatrix, HeatmapCollector, HeatmapMatrix,
+    InMemoryOpportunityStore, MarketStatsCollector, MarketStatsSnapshot, OpportunityQuery,
+    OpportunityStore,
+};
+use chrono::{DateTime, Utc};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
 
 /// REST server implementation
 pub struct RestServer {
     host: String,
     port: u16,
+    heatmap: HeatmapCollector,
+    /// Whether `start` feeds `heatmap`; a shared one is fed by whoever shares it
+    feeds_heatmap: bool,
+    market_stats: MarketStatsCollector,
+    opportunity_store: Arc<dyn OpportunityStore>,
+    authenticator: Arc<Authenticator>,
 }
 
+/// Window market statistics cover when a request does not ask for one
+const DEFAULT_STATS_WINDOW_SECS: u64 = 300;
+
+/// Most opportunities the history endpoint returns when a request does not set a limit
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// How long the shutdown endpoint gives the aggregator to stop when a request does not say
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+
+/// An `AggregatorError` answered with the status its category calls for and an [`ErrorBody`]
+#[derive(Debug)]
+struct ApiError(AggregatorError);
+
+/// What a failed request answers
+///
+/// - `error`: The error as displayed.
+/// - `category`: `AggregatorError::category` of the error, such as "not_found" or "validation".
+#[derive(Debug, Serialize, ToSchema)]
+struct ErrorBody {
+    error: String,
+    category: String,
+}
+
+impl From<AggregatorError> for ApiError {
+    fn from(error: AggregatorError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self.0.category() {
+            "not_found" => StatusCode::NOT_FOUND,
+            "validation" | "parsing" | "url" | "uuid" => StatusCode::BAD_REQUEST,
+            "authentication" => StatusCode::UNAUTHORIZED,
+            "forbidden" => StatusCode::FORBIDDEN,
+            "rate_limit" => StatusCode::TOO_MANY_REQUESTS,
+            "already_exists" => StatusCode::CONFLICT,
+            "timeout" => StatusCode::GATEWAY_TIMEOUT,
+            "shutdown" => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        if status.is_server_error() {
+            error!("REST request failed: {}", self.0);
+        }
+        let body = ErrorBody {
+            error: self.0.to_string(),
+            category: self.0.category().to_string(),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+type ApiResult = std::result::Result<Json<Value>, ApiError>;
+
 impl RestServer {
     /// Create new REST server
     pub fn new(host: String, port: u16) -> Self {
-        Self { host, port }
+        Self {
+            host,
+            port,
+            heatmap: HeatmapCollector::default(),
+            feeds_heatmap: true,
+            market_stats: MarketStatsCollector::default(),
+            opportunity_store: Arc::new(InMemoryOpportunityStore::default()),
+            authenticator: Arc::new(Authenticator::disabled()),
+        }
+    }
+
+    /// Serve depth heatmaps from a shared collector instead of a private default one. The
+    /// server does not feed it, see `ServerManager::feed_heatmap`.
+    pub fn with_heatmap(mut self, heatmap: HeatmapCollector) -> Self {
+        self.heatmap = heatmap;
+        self.feeds_heatmap = false;
+        self
+    }
+
+    /// Serve market statistics from a shared collector instead of a private default one
+    pub fn with_market_stats(mut self, market_stats: MarketStatsCollector) -> Self {
+        self.market_stats = market_stats;
+        self
+    }
+
+    /// Record arbitrage opportunities into, and serve their history from, `store` instead of a
+    /// private in-memory one
+    pub fn with_opportunity_store(mut self, store: Arc<dyn OpportunityStore>) -> Self {
+        self.opportunity_store = store;
+        self
+    }
+
+    /// Require every request but health checks and the API document to authenticate with
+    /// `authenticator`, which the other servers may share
+    pub fn with_authenticator(mut self, authenticator: Arc<Authenticator>) -> Self {
+        self.authenticator = authenticator;
+        self
+    }
+
+    /// The endpoints of this server for `aggregator`, to serve from another listener. Unlike
+    /// `start`, it does not feed the heatmap, market statistics and opportunity history they
+    /// read from.
+    pub fn router(&self, aggregator: Arc<Aggregator>) -> Router {
+        create_app(
+            aggregator,
+            self.heatmap.clone(),
+            self.market_stats.clone(),
+            self.opportunity_store.clone(),
+            self.authenticator.clone(),
+        )
     }
 }
 
@@ -33,12 +165,22 @@ impl ServerTrait for RestServer {
             .await
             .map_err(|e| AggregatorError::network(format!("Failed to bind to {}: {}", addr, e)))?;
 
-        let app = create_app(aggregator);
+        let stopping = aggregator.stopping(ShutdownStage::Servers);
+        if self.feeds_heatmap {
+            crate::feed_heatmap(&aggregator, &self.heatmap);
+        }
+        self.market_stats.spawn(aggregator.subscribe_summaries());
+        spawn_opportunity_recorder(
+            self.opportunity_store.clone(),
+            aggregator.subscribe_arbitrage(),
+        );
+        let app = self.router(aggregator);
 
         info!("Starting REST server on {}", addr);
 
         let handle = tokio::spawn(async move {
             axum::serve(listener, app)
+                .with_graceful_shutdown(stopping)
                 .await
                 .map_err(|e| AggregatorError::network(format!("REST server error: {}", e)))
         });
@@ -46,7 +188,7 @@ impl ServerTrait for RestServer {
     }
 
     async fn stop(&self) -> Result<()> {
-        // REST server shutdown is handled by dropping the server
+        // The server finishes its requests and stops once the aggregator stops its servers
         Ok(())
     }
 
@@ -59,27 +201,891 @@ impl ServerTrait for RestServer {
     }
 }
 
-fn create_app(aggregator: Arc<Aggregator>) -> Router {
+fn create_app(
+    aggregator: Arc<Aggregator>,
+    heatmap: HeatmapCollector,
+    market_stats: MarketStatsCollector,
+    opportunity_store: Arc<dyn OpportunityStore>,
+    authenticator: Arc<Authenticator>,
+) -> Router {
     Router::new()
+        .route("/summaries", get(get_summaries_handler))
+        .route("/summaries/:pair", get(get_pair_summary_handler))
+        .route("/orderbook/:pair", get(get_pair_order_book_handler))
+        .route("/opportunities", get(get_opportunities_handler))
+        .route("/metrics/:exchange", get(get_metrics_handler))
+        .route("/exchanges", get(get_exchanges_handler))
         .route("/summary/:base/:quote", get(get_summary_handler))
+        .route("/book/:base/:quote", get(get_order_book_handler))
+        .route("/heatmap/:symbol", get(get_heatmap_handler))
+        .route("/stats/:symbol", get(get_market_stats_handler))
+        .route("/cost-to-fill/:base/:quote", get(get_cost_to_fill_handler))
+        .route("/backpressure", get(get_backpressure_handler))
+        .route("/errors", get(get_errors_handler))
+        .route(
+            "/opportunities/history",
+            get(get_opportunity_history_handler),
+        )
+        .route(
+            "/opportunities/exchange-pairs",
+            get(get_exchange_pair_matrix_handler),
+        )
+        .route(
+            "/admin/exchanges/:exchange/enable",
+            post(enable_exchange_handler),
+        )
+        .route(
+            "/admin/exchanges/:exchange/disable",
+            post(disable_exchange_handler),
+        )
+        .route(
+            "/admin/exchanges/:exchange/reconnect",
+            post(reconnect_exchange_handler),
+        )
+        .route("/admin/pairs", post(add_pair_handler))
+        .route("/admin/pairs/:pair", delete(remove_pair_handler))
+        .route(
+            "/admin/analysis",
+            get(get_analysis_handler).patch(update_analysis_handler),
+        )
+        .route("/admin/shutdown", post(shutdown_handler))
+        // Only the routes above require authentication
+        .route_layer(middleware::from_fn_with_state(
+            authenticator,
+            authenticate_request,
+        ))
+        .route("/health", get(get_health_handler))
+        .route("/api-docs", get(get_api_docs_handler))
         .layer(Extension(aggregator))
+        .layer(Extension(heatmap))
+        .layer(Extension(market_stats))
+        .layer(Extension(opportunity_store))
+}
+
+/// Lets a request through once its client authenticates, with an `Authorization: Bearer` or
+/// `X-API-Key` header, and hands its `Principal` to the handler as an extension
+async fn authenticate_request(
+    State(authenticator): State<Arc<Authenticator>>,
+    mut request: Request,
+    next: Next,
+) -> std::result::Result<Response, ApiError> {
+    let headers = request.headers();
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let principal = authenticator.authenticate(auth::credential(
+        header(header::AUTHORIZATION.as_str()),
+        header("x-api-key"),
+    ))?;
+    request.extensions_mut().insert(principal);
+    Ok(next.run(request).await)
+}
+
+/// Resolves a pair written in a path, such as `BTC-USDT`, `BTC_USDT` or `BTCUSDT`, against the
+/// configured trading pairs
+fn parse_pair(config: &Config, pair: &str) -> Result<TradingPair> {
+    config.pair_for_symbol(pair).ok_or_else(|| {
+        AggregatorError::validation("pair", format!("'{}' is not a trading pair", pair))
+    })
+}
+
+/// Resolves an exchange name written in a path, configured custom exchanges included
+fn parse_exchange(config: &Config, name: &str) -> Result<Exchange> {
+    config
+        .exchanges
+        .keys()
+        .find(|exchange| exchange.to_string().eq_ignore_ascii_case(name))
+        .cloned()
+        .map_or_else(|| name.parse(), Ok)
+}
+
+fn summary_json(summary: &Summary) -> Value {
+    json!({
+        "symbol": summary.symbol,
+        "spread": summary.spread,
+        "bids": summary.bids,
+        "asks": summary.asks,
+        "timestamp": summary.timestamp,
+    })
+}
+
+/// Handler for the summaries of every pair, sorted by symbol
+#[utoipa::path(get, path = "/summaries", responses((status = 200, body = [Summary])))]
+async fn get_summaries_handler(Extension(aggregator): Extension<Arc<Aggregator>>) -> Json<Value> {
+    let mut summaries: Vec<Summary> = aggregator.get_all_summaries().await.into_values().collect();
+    summaries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    Json(Value::Array(summaries.iter().map(summary_json).collect()))
+}
+
+/// Handler for the summary of a pair written as `BTC-USDT`, `BTC_USDT` or `BTCUSDT`
+#[utoipa::path(
+    get,
+    path = "/summaries/{pair}",
+    params(("pair" = String, Path, description = "Trading pair, such as BTC-USDT, BTC_USDT or BTCUSDT")),
+    responses(
+        (status = 200, body = Summary),
+        (status = 400, description = "Unknown pair", body = ErrorBody),
+        (status = 404, description = "No summary yet", body = ErrorBody),
+    )
+)]
+async fn get_pair_summary_handler(
+    Path(pair): Path<String>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    let pair = parse_pair(aggregator.config().await.as_ref(), &pair)?;
+    summary_response(&aggregator, &pair).await
 }
 
 /// Handler for getting a summary
+#[utoipa::path(
+    get,
+    path = "/summary/{base}/{quote}",
+    params(
+        ("base" = String, Path, description = "Base currency, such as BTC"),
+        ("quote" = String, Path, description = "Quote currency, such as USDT"),
+    ),
+    responses(
+        (status = 200, body = Summary),
+        (status = 404, description = "No summary yet", body = ErrorBody),
+    )
+)]
 async fn get_summary_handler(
     Path((base, quote)): Path<(String, String)>,
-    axum::extract::Extension(aggregator): axum::extract::Extension<Arc<Aggregator>>,
-) -> Json<serde_json::Value> {
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    summary_response(&aggregator, &TradingPair::new(&base, &quote)).await
+}
+
+async fn summary_response(aggregator: &Aggregator, pair: &TradingPair) -> ApiResult {
+    match aggregator.get_summary(pair).await {
+        Some(summary) => Ok(Json(summary_json(&summary))),
+        None => Err(AggregatorError::not_found("summary".to_string(), pair.to_string()).into()),
+    }
+}
+
+/// Query parameters of the order book endpoints
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct OrderBookQuery {
+    /// Levels per side, `orderbook.max_depth` by default
+    depth: Option<usize>,
+}
+
+/// What the order book endpoints answer: the book with each exchange's share of its
+/// consolidated levels
+#[derive(Debug, Serialize, ToSchema)]
+struct OrderBookBody {
+    #[serde(flatten)]
+    book: OrderBookView,
+    attribution: HashMap<Exchange, ExchangeAttribution>,
+}
+
+/// Handler for getting the consolidated book of a pair written as `BTC-USDT`, `BTC_USDT` or
+/// `BTCUSDT`, taking the same `?depth=` as the book endpoint
+#[utoipa::path(
+    get,
+    path = "/orderbook/{pair}",
+    params(("pair" = String, Path, description = "Trading pair, such as BTC-USDT, BTC_USDT or BTCUSDT"), OrderBookQuery),
+    responses(
+        (status = 200, body = OrderBookBody),
+        (status = 400, description = "Unknown pair", body = ErrorBody),
+        (status = 404, description = "No book yet", body = ErrorBody),
+    )
+)]
+async fn get_pair_order_book_handler(
+    Path(pair): Path<String>,
+    Query(query): Query<OrderBookQuery>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    let pair = parse_pair(aggregator.config().await.as_ref(), &pair)?;
+    order_book_response(&aggregator, &pair, query).await
+}
+
+/// Handler for getting the consolidated book of a pair to `?depth=<levels>` per side, the
+/// configured `orderbook.max_depth` by default, with each exchange's levels and share
+#[utoipa::path(
+    get,
+    path = "/book/{base}/{quote}",
+    params(
+        ("base" = String, Path, description = "Base currency, such as BTC"),
+        ("quote" = String, Path, description = "Quote currency, such as USDT"),
+        OrderBookQuery,
+    ),
+    responses(
+        (status = 200, body = OrderBookBody),
+        (status = 404, description = "No book yet", body = ErrorBody),
+    )
+)]
+async fn get_order_book_handler(
+    Path((base, quote)): Path<(String, String)>,
+    Query(query): Query<OrderBookQuery>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    order_book_response(&aggregator, &TradingPair::new(&base, &quote), query).await
+}
+
+async fn order_book_response(
+    aggregator: &Aggregator,
+    pair: &TradingPair,
+    query: OrderBookQuery,
+) -> ApiResult {
+    let depth = match query.depth {
+        Some(depth) => depth,
+        None => aggregator.config().await.orderbook.max_depth,
+    };
+
+    match aggregator.get_order_book(pair, depth).await {
+        Some(book) => Ok(Json(json!(OrderBookBody {
+            attribution: book.attribution(),
+            book,
+        }))),
+        None => Err(AggregatorError::not_found("order book".to_string(), pair.to_string()).into()),
+    }
+}
+
+/// Handler for the feed metrics of an exchange
+#[utoipa::path(
+    get,
+    path = "/metrics/{exchange}",
+    params(("exchange" = String, Path, description = "Exchange name, such as binance")),
+    responses(
+        (status = 200, body = Metrics),
+        (status = 400, description = "Unknown exchange", body = ErrorBody),
+        (status = 404, description = "No metrics yet", body = ErrorBody),
+    )
+)]
+async fn get_metrics_handler(
+    Path(exchange): Path<String>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    let exchange = parse_exchange(aggregator.config().await.as_ref(), &exchange)?;
+    match aggregator.get_metrics(&exchange).await {
+        Some(metrics) => Ok(Json(json!(metrics))),
+        None => Err(AggregatorError::not_found("metrics".to_string(), exchange.to_string()).into()),
+    }
+}
+
+/// A configured exchange, as the exchanges endpoint lists it
+///
+/// - `health`: Its latest health check, `null` until the first.
+/// - `metrics`: Its feed metrics, `null` until it has sent updates.
+#[derive(Debug, Serialize, ToSchema)]
+struct ExchangeBody {
+    exchange: Exchange,
+    enabled: bool,
+    health: Option<HealthStatus>,
+    metrics: Option<Metrics>,
+}
+
+/// Handler for every configured exchange, sorted by name, with whether it is enabled, its
+/// latest health check and its feed metrics
+#[utoipa::path(get, path = "/exchanges", responses((status = 200, body = [ExchangeBody])))]
+async fn get_exchanges_handler(Extension(aggregator): Extension<Arc<Aggregator>>) -> Json<Value> {
+    let config = aggregator.config().await;
+    let mut exchanges: Vec<(&Exchange, bool)> = config
+        .exchanges
+        .iter()
+        .map(|(exchange, exchange_config)| (exchange, exchange_config.enabled))
+        .collect();
+    exchanges.sort_by_key(|(exchange, _)| exchange.to_string());
+
+    let mut body = Vec::with_capacity(exchanges.len());
+    for (exchange, enabled) in exchanges {
+        body.push(ExchangeBody {
+            exchange: exchange.clone(),
+            enabled,
+            health: aggregator.get_health_status(exchange).await,
+            metrics: aggregator.get_metrics(exchange).await,
+        });
+    }
+    Json(json!(body))
+}
+
+/// Handler for getting the depth heatmap of a symbol
+#[utoipa::path(
+    get,
+    path = "/heatmap/{symbol}",
+    params(("symbol" = String, Path, description = "Symbol, such as BTCUSDT")),
+    responses(
+        (status = 200, body = HeatmapMatrix),
+        (status = 404, description = "No heatmap yet", body = ErrorBody),
+    )
+)]
+async fn get_heatmap_handler(
+    Path(symbol): Path<String>,
+    Extension(heatmap): Extension<HeatmapCollector>,
+) -> ApiResult {
+    let symbol = symbol.to_uppercase();
+    match heatmap.matrix(&symbol).await {
+        Some(matrix) => Ok(Json(json!(matrix))),
+        None => Err(AggregatorError::not_found("heatmap".to_string(), symbol).into()),
+    }
+}
+
+/// Query parameters of the market statistics endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct MarketStatsQuery {
+    /// Seconds the statistics cover, 300 by default
+    window: Option<u64>,
+}
+
+/// Handler for getting the rolling volatility and spread statistics of a symbol on every
+/// exchange, over `?window=<seconds>`
+#[utoipa::path(
+    get,
+    path = "/stats/{symbol}",
+    params(("symbol" = String, Path, description = "Symbol, such as BTCUSDT"), MarketStatsQuery),
+    responses(
+        (status = 200, body = [MarketStatsSnapshot]),
+        (status = 404, description = "No statistics yet", body = ErrorBody),
+    )
+)]
+async fn get_market_stats_handler(
+    Path(symbol): Path<String>,
+    Query(query): Query<MarketStatsQuery>,
+    Extension(market_stats): Extension<MarketStatsCollector>,
+) -> ApiResult {
+    let window = query.window.unwrap_or(DEFAULT_STATS_WINDOW_SECS);
+    let symbol = symbol.to_uppercase();
+    let snapshots = market_stats.symbol_snapshots(&symbol, window).await;
+    if snapshots.is_empty() {
+        Err(AggregatorError::not_found("market stats".to_string(), symbol).into())
+    } else {
+        Ok(Json(json!(snapshots)))
+    }
+}
+
+/// Query parameters of the cost-to-fill endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct CostToFillQuery {
+    /// `buy` to walk the asks, `sell` to walk the bids
+    side: String,
+    /// Quantity to fill, in base currency
+    quantity: f64,
+}
+
+/// Handler for estimating the execution of a market order against a summary
+#[utoipa::path(
+    get,
+    path = "/cost-to-fill/{base}/{quote}",
+    params(
+        ("base" = String, Path, description = "Base currency, such as BTC"),
+        ("quote" = String, Path, description = "Quote currency, such as USDT"),
+        CostToFillQuery,
+    ),
+    responses(
+        (status = 200, body = FillEstimate),
+        (status = 400, description = "Side is neither buy nor sell", body = ErrorBody),
+        (status = 404, description = "No summary or no liquidity", body = ErrorBody),
+    )
+)]
+async fn get_cost_to_fill_handler(
+    Path((base, quote)): Path<(String, String)>,
+    Query(query): Query<CostToFillQuery>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    let side = match query.side.to_lowercase().as_str() {
+        "buy" => TradeSide::Buy,
+        "sell" => TradeSide::Sell,
+        _ => return Err(AggregatorError::validation("side", "must be buy or sell").into()),
+    };
     let pair = TradingPair::new(&base, &quote);
 
-    match aggregator.get_summary(&pair).await {
-        Some(summary) => Json(json!({
-            "symbol": summary.symbol,
-            "spread": summary.spread,
-            "bids": summary.bids,
-            "asks": summary.asks,
-            "timestamp": summary.timestamp,
-        })),
-        None => Json(json!({ "error": "Summary not found" })),
+    let summary = aggregator
+        .get_summary(&pair)
+        .await
+        .ok_or_else(|| AggregatorError::not_found("summary".to_string(), pair.to_string()))?;
+    match summary.cost_to_fill(side, query.quantity) {
+        Some(estimate) => Ok(Json(json!(estimate))),
+        None => Err(AggregatorError::not_found(
+            "liquidity".to_string(),
+            format!("{} {}", query.quantity, pair),
+        )
+        .into()),
+    }
+}
+
+/// Handler for the events delivered to, dropped for and conflated for bounded subscribers such
+/// as WebSocket and gRPC clients, by topic
+#[utoipa::path(
+    get,
+    path = "/backpressure",
+    responses((status = 200, body = BTreeMap<String, BackpressureSnapshot>))
+)]
+async fn get_backpressure_handler(
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> Json<Value> {
+    Json(json!(aggregator.events().backpressure_by_topic()))
+}
+
+/// Handler for the `SystemHealth` of the aggregator, answering 503 while no exchange is healthy
+/// so load balancers stop routing to it
+#[utoipa::path(
+    get,
+    path = "/health",
+    security(()),
+    responses(
+        (status = 200, description = "Healthy or degraded", body = SystemHealth),
+        (status = 503, description = "No exchange is healthy", body = SystemHealth),
+    )
+)]
+async fn get_health_handler(
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> (StatusCode, Json<Value>) {
+    let health = aggregator.system_health().await;
+    let status = if health.level == HealthLevel::Unhealthy {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(json!(health)))
+}
+
+/// Query parameters of the errors endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ErrorsQuery {
+    /// Only list errors of this category, such as `parsing`
+    category: Option<String>,
+    /// Most errors to list
+    limit: Option<usize>,
+}
+
+/// Handler for the errors the aggregator ran into: counts per category since start and the
+/// latest errors, newest first, narrowed by `?category=` and `?limit=`
+#[utoipa::path(
+    get,
+    path = "/errors",
+    params(ErrorsQuery),
+    responses((status = 200, body = ErrorReport))
+)]
+async fn get_errors_handler(
+    Query(query): Query<ErrorsQuery>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> Json<Value> {
+    let report = aggregator
+        .errors()
+        .report(query.category.as_deref(), query.limit);
+    Json(json!(report))
+}
+
+/// Query parameters of the opportunity history endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct OpportunityHistoryQuery {
+    /// Only opportunities of this symbol, such as BTCUSDT
+    symbol: Option<String>,
+    /// Only opportunities buying on this exchange
+    buy_exchange: Option<String>,
+    /// Only opportunities selling on this exchange
+    sell_exchange: Option<String>,
+    /// Only opportunities found at or after this time, RFC 3339
+    from: Option<DateTime<Utc>>,
+    /// Only opportunities found at or before this time, RFC 3339
+    to: Option<DateTime<Utc>>,
+    /// Only opportunities with at least this profit percentage
+    min_profit: Option<f64>,
+    /// Most opportunities to return
+    limit: Option<usize>,
+}
+
+impl OpportunityHistoryQuery {
+    /// Converts the request parameters into a store query, failing on unknown exchange names
+    fn into_store_query(self) -> Result<OpportunityQuery> {
+        let parse_exchange =
+            |name: Option<String>| name.map(|name| name.parse::<Exchange>()).transpose();
+        Ok(OpportunityQuery {
+            symbol: self.symbol,
+            buy_exchange: parse_exchange(self.buy_exchange)?,
+            sell_exchange: parse_exchange(self.sell_exchange)?,
+            from: self.from,
+            to: self.to,
+            min_profit: self.min_profit,
+            limit: self.limit,
+        })
+    }
+}
+
+/// Handler for looking up past arbitrage opportunities, newest first, filtered by `?symbol=`,
+/// `?buy_exchange=`, `?sell_exchange=`, `?from=` and `?to=` (RFC 3339), `?min_profit=` and
+/// `?limit=`
+#[utoipa::path(
+    get,
+    path = "/opportunities/history",
+    params(OpportunityHistoryQuery),
+    responses(
+        (status = 200, body = [ArbitrageOpportunity]),
+        (status = 400, description = "Unknown exchange", body = ErrorBody),
+    )
+)]
+async fn get_opportunity_history_handler(
+    Query(query): Query<OpportunityHistoryQuery>,
+    Extension(store): Extension<Arc<dyn OpportunityStore>>,
+) -> ApiResult {
+    let query = query.into_store_query()?;
+    let query = OpportunityQuery {
+        limit: Some(query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT)),
+        ..query
+    };
+    Ok(Json(json!(store.query(&query).await?)))
+}
+
+/// Handler for counting past arbitrage opportunities and averaging their profit by buy and
+/// sell exchange, taking the same filters as the history endpoint; without `?limit=` every
+/// matching opportunity is counted
+#[utoipa::path(
+    get,
+    path = "/opportunities/exchange-pairs",
+    params(OpportunityHistoryQuery),
+    responses(
+        (status = 200, body = ExchangePairMatrix),
+        (status = 400, description = "Unknown exchange", body = ErrorBody),
+    )
+)]
+async fn get_exchange_pair_matrix_handler(
+    Query(query): Query<OpportunityHistoryQuery>,
+    Extension(store): Extension<Arc<dyn OpportunityStore>>,
+) -> ApiResult {
+    let query = query.into_store_query()?;
+    Ok(Json(json!(store.exchange_pair_matrix(&query).await?)))
+}
+
+/// Handler for the latest arbitrage opportunities, the same as the history endpoint
+#[utoipa::path(
+    get,
+    path = "/opportunities",
+    params(OpportunityHistoryQuery),
+    responses(
+        (status = 200, body = [ArbitrageOpportunity]),
+        (status = 400, description = "Unknown exchange", body = ErrorBody),
+    )
+)]
+async fn get_opportunities_handler(
+    query: Query<OpportunityHistoryQuery>,
+    store: Extension<Arc<dyn OpportunityStore>>,
+) -> ApiResult {
+    get_opportunity_history_handler(query, store).await
+}
+
+/// What an admin action answers
+///
+/// - `changed`: `false` when the action was already in effect, such as enabling an enabled
+///   exchange.
+#[derive(Debug, Serialize, ToSchema)]
+struct AdminChange {
+    changed: bool,
+}
+
+/// Handler enabling an exchange, with default settings if it has none, and starting its
+/// connector
+#[utoipa::path(
+    post,
+    path = "/admin/exchanges/{exchange}/enable",
+    params(("exchange" = String, Path, description = "Exchange name, such as binance")),
+    responses(
+        (status = 200, body = AdminChange),
+        (status = 400, description = "Unknown exchange", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+    )
+)]
+async fn enable_exchange_handler(
+    Path(exchange): Path<String>,
+    Extension(principal): Extension<Principal>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    principal.require_admin()?;
+    let exchange = parse_exchange(aggregator.config().await.as_ref(), &exchange)?;
+    info!("{} is enabling {}", principal.name, exchange);
+    let changed = aggregator.enable_exchange(exchange).await?;
+    Ok(Json(json!(AdminChange { changed })))
+}
+
+/// Handler disabling an exchange and stopping its connector
+#[utoipa::path(
+    post,
+    path = "/admin/exchanges/{exchange}/disable",
+    params(("exchange" = String, Path, description = "Exchange name, such as binance")),
+    responses(
+        (status = 200, body = AdminChange),
+        (status = 400, description = "Unknown exchange, or the last one enabled", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+    )
+)]
+async fn disable_exchange_handler(
+    Path(exchange): Path<String>,
+    Extension(principal): Extension<Principal>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    principal.require_admin()?;
+    let exchange = parse_exchange(aggregator.config().await.as_ref(), &exchange)?;
+    info!("{} is disabling {}", principal.name, exchange);
+    let changed = aggregator.disable_exchange(&exchange).await?;
+    Ok(Json(json!(AdminChange { changed })))
+}
+
+/// Handler restarting the connector of an exchange, which reconnects and rebuilds its books
+#[utoipa::path(
+    post,
+    path = "/admin/exchanges/{exchange}/reconnect",
+    params(("exchange" = String, Path, description = "Exchange name, such as binance")),
+    responses(
+        (status = 200, body = AdminChange),
+        (status = 400, description = "Unknown exchange", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 404, description = "The exchange's connector is not running", body = ErrorBody),
+    )
+)]
+async fn reconnect_exchange_handler(
+    Path(exchange): Path<String>,
+    Extension(principal): Extension<Principal>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    principal.require_admin()?;
+    let exchange = parse_exchange(aggregator.config().await.as_ref(), &exchange)?;
+    info!("{} is reconnecting {}", principal.name, exchange);
+    if !aggregator.reconnect_exchange(&exchange).await? {
+        let exchange = exchange.to_string();
+        return Err(AggregatorError::not_found("running connector", exchange.as_str()).into());
+    }
+    Ok(Json(json!(AdminChange { changed: true })))
+}
+
+/// Handler subscribing to a trading pair on every enabled exchange
+#[utoipa::path(
+    post,
+    path = "/admin/pairs",
+    request_body = TradingPair,
+    responses(
+        (status = 200, body = AdminChange),
+        (status = 400, description = "Invalid pair", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+    )
+)]
+async fn add_pair_handler(
+    Extension(principal): Extension<Principal>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+    Json(pair): Json<TradingPair>,
+) -> ApiResult {
+    principal.require_admin()?;
+    info!("{} is adding {}", principal.name, pair);
+    let changed = aggregator.add_trading_pair(pair).await?;
+    Ok(Json(json!(AdminChange { changed })))
+}
+
+/// Handler unsubscribing from a trading pair and dropping its summaries
+#[utoipa::path(
+    delete,
+    path = "/admin/pairs/{pair}",
+    params(("pair" = String, Path, description = "Trading pair, such as BTC-USDT")),
+    responses(
+        (status = 200, body = AdminChange),
+        (status = 400, description = "Unknown pair, or the last one", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+    )
+)]
+async fn remove_pair_handler(
+    Path(pair): Path<String>,
+    Extension(principal): Extension<Principal>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    principal.require_admin()?;
+    let pair = parse_pair(aggregator.config().await.as_ref(), &pair)?;
+    info!("{} is removing {}", principal.name, pair);
+    let changed = aggregator.remove_trading_pair(&pair).await?;
+    Ok(Json(json!(AdminChange { changed })))
+}
+
+/// Handler for the analysis settings in effect
+#[utoipa::path(
+    get,
+    path = "/admin/analysis",
+    responses(
+        (status = 200, body = AnalysisConfig),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+    )
+)]
+async fn get_analysis_handler(
+    Extension(principal): Extension<Principal>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> ApiResult {
+    principal.require_admin()?;
+    Ok(Json(json!(aggregator.config().await.analysis)))
+}
+
+/// Handler changing the analysis settings the body sets, such as
+/// `{"min_profit_percentage": 0.2}`, and answering with the settings now in effect. They apply
+/// from the next update or arbitrage run.
+#[utoipa::path(
+    patch,
+    path = "/admin/analysis",
+    request_body(content = Object, description = "The settings of AnalysisConfig to change"),
+    responses(
+        (status = 200, body = AnalysisConfig),
+        (status = 400, description = "Unknown or invalid setting", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+    )
+)]
+async fn update_analysis_handler(
+    Extension(principal): Extension<Principal>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+    Json(changes): Json<Value>,
+) -> ApiResult {
+    principal.require_admin()?;
+    let mut analysis = json!(aggregator.config().await.analysis);
+    merge_settings(&mut analysis, changes, "analysis")?;
+    let analysis: AnalysisConfig = serde_json::from_value(analysis)
+        .map_err(|e| AggregatorError::validation("analysis", e.to_string()))?;
+    info!("{} is updating the analysis settings", principal.name);
+    aggregator.update_analysis(analysis).await?;
+    Ok(Json(json!(aggregator.config().await.analysis)))
+}
+
+/// Overwrites the settings of `current` that `changes` sets, recursing into sections. A
+/// setting `current` does not have is rejected rather than ignored, so a misspelled one is
+/// noticed.
+fn merge_settings(current: &mut Value, changes: Value, path: &str) -> Result<()> {
+    match (current, changes) {
+        (Value::Object(current), Value::Object(changes)) => {
+            for (key, change) in changes {
+                let field = format!("{}.{}", path, key);
+                let Some(setting) = current.get_mut(&key) else {
+                    return Err(AggregatorError::validation(field, "is not a setting"));
+                };
+                merge_settings(setting, change, &field)?;
+            }
+            Ok(())
+        }
+        (current, change) => {
+            *current = change;
+            Ok(())
+        }
+    }
+}
+
+/// Query parameters of the shutdown endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ShutdownQuery {
+    /// Seconds the aggregator has to stop before what is still running is aborted, 30 by
+    /// default
+    timeout_secs: Option<u64>,
+}
+
+/// Handler starting a graceful shutdown, see `Aggregator::shutdown`. It answers at once, and
+/// this server stops with the others once the connectors and analysis have.
+#[utoipa::path(
+    post,
+    path = "/admin/shutdown",
+    params(ShutdownQuery),
+    responses(
+        (status = 202, description = "Shutting down"),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+    )
+)]
+async fn shutdown_handler(
+    Query(query): Query<ShutdownQuery>,
+    Extension(principal): Extension<Principal>,
+    Extension(aggregator): Extension<Arc<Aggregator>>,
+) -> std::result::Result<(StatusCode, Json<Value>), ApiError> {
+    principal.require_admin()?;
+    let timeout_secs = query.timeout_secs.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS);
+    info!("{} is shutting the aggregator down", principal.name);
+    // Waiting here would have the shutdown wait for this request in turn
+    tokio::spawn(async move {
+        aggregator
+            .shutdown(std::time::Duration::from_secs(timeout_secs))
+            .await
+    });
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "shutting_down": true, "timeout_secs": timeout_secs })),
+    ))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "aggre-gate REST API",
+        description = "Consolidated order books, arbitrage opportunities and feed health of the \
+                       crypto orderbook aggregator"
+    ),
+    paths(
+        get_summaries_handler,
+        get_pair_summary_handler,
+        get_summary_handler,
+        get_pair_order_book_handler,
+        get_order_book_handler,
+        get_opportunities_handler,
+        get_opportunity_history_handler,
+        get_exchange_pair_matrix_handler,
+        get_metrics_handler,
+        get_exchanges_handler,
+        get_heatmap_handler,
+        get_market_stats_handler,
+        get_cost_to_fill_handler,
+        get_backpressure_handler,
+        get_health_handler,
+        get_errors_handler,
+        enable_exchange_handler,
+        disable_exchange_handler,
+        reconnect_exchange_handler,
+        add_pair_handler,
+        remove_pair_handler,
+        get_analysis_handler,
+        update_analysis_handler,
+        shutdown_handler,
+        get_api_docs_handler,
+    ),
+    modifiers(&SecurityAddon),
+    security(("api_key" = []), ("bearer" = []))
+)]
+struct ApiDoc;
+
+/// Declares the credentials `authenticate_request` accepts
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
     }
 }
+
+/// The OpenAPI document of every REST endpoint, for generating clients without running the
+/// server
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
+
+/// `openapi()` as JSON, built on the first request
+static API_DOCS: Lazy<String> = Lazy::new(|| {
+    openapi()
+        .to_pretty_json()
+        .expect("the OpenAPI document serializes")
+});
+
+/// Handler for the OpenAPI document
+#[utoipa::path(
+    get,
+    path = "/api-docs",
+    security(()),
+    responses((status = 200, description = "This OpenAPI document", body = Object))
+)]
+async fn get_api_docs_handler() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        API_DOCS.as_str(),
+    )
+}
@@ -2,26 +2,56 @@
 
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
-use tracing::{error, info, warn};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::{accept_async, accept_hdr_async, tungstenite::Message};
+use tracing::{debug, error, info, warn};
 
-use crate::Server as ServerTrait;
-use aggregator_core::{Aggregator, AggregatorError, Result, Summary};
+use crate::auth::{self, Authenticator, Principal};
+use crate::{compact_symbol, Server as ServerTrait};
+use aggregator_core::{
+    Aggregator, AggregatorError, ArbitrageOpportunity, BackpressureConfig, BackpressureSnapshot,
+    BookDeltaEncoder, BookUpdate, BoundedReceiver, Conflate, Exchange, HealthStatus,
+    PriceLevelUpdate, Result, ShutdownStage, Summary,
+};
+use analysis_tools::{HeatmapCollector, MarketStatsCollector};
 
 /// WebSocket server implementation
 pub struct WebSocketServer {
     host: String,
     port: u16,
     max_connections: usize,
+    heatmap: HeatmapCollector,
+    /// Whether `start` feeds `heatmap`; a shared one is fed by whoever shares it
+    feeds_heatmap: bool,
+    market_stats: MarketStatsCollector,
+    backpressure: BackpressureConfig,
+    authenticator: Arc<Authenticator>,
 }
 
+/// Window market statistics cover when a request does not ask for one
+const DEFAULT_STATS_WINDOW_SECS: u64 = 300;
+
+/// Most subscriptions one connection can hold
+const MAX_SUBSCRIPTIONS: usize = 100;
+
+/// How long a client turned away for `max_connections` has to complete the handshake before
+/// it is dropped without a close frame
+const REJECT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
 impl WebSocketServer {
     /// Create new WebSocket server
     pub fn new(host: String, port: u16, max_connections: usize) -> Self {
@@ -29,8 +59,40 @@ impl WebSocketServer {
             host,
             port,
             max_connections,
+            heatmap: HeatmapCollector::default(),
+            feeds_heatmap: true,
+            market_stats: MarketStatsCollector::default(),
+            backpressure: BackpressureConfig::default(),
+            authenticator: Arc::new(Authenticator::disabled()),
         }
     }
+
+    /// Serve depth heatmaps from a shared collector instead of a private default one. The
+    /// server does not feed it, see `ServerManager::feed_heatmap`.
+    pub fn with_heatmap(mut self, heatmap: HeatmapCollector) -> Self {
+        self.heatmap = heatmap;
+        self.feeds_heatmap = false;
+        self
+    }
+
+    /// Serve market statistics from a shared collector instead of a private default one
+    pub fn with_market_stats(mut self, market_stats: MarketStatsCollector) -> Self {
+        self.market_stats = market_stats;
+        self
+    }
+
+    /// Buffer each client's summaries under `backpressure` instead of the default policy
+    pub fn with_backpressure(mut self, backpressure: BackpressureConfig) -> Self {
+        self.backpressure = backpressure;
+        self
+    }
+
+    /// Require clients to authenticate with `authenticator` during the handshake, which the
+    /// other servers may share
+    pub fn with_authenticator(mut self, authenticator: Arc<Authenticator>) -> Self {
+        self.authenticator = authenticator;
+        self
+    }
 }
 
 #[async_trait]
@@ -45,55 +107,42 @@ impl ServerTrait for WebSocketServer {
 
         let connection_count = Arc::new(AtomicUsize::new(0));
         let max_connections = self.max_connections;
+        let heatmap = self.heatmap.clone();
+        if self.feeds_heatmap {
+            crate::feed_heatmap(&aggregator, &heatmap);
+        }
+        let market_stats = self.market_stats.clone();
+        market_stats.spawn(aggregator.subscribe_summaries());
+
+        let backpressure = self.backpressure.clone();
+        let authenticator = self.authenticator.clone();
+        let stopping = aggregator.stopping(ShutdownStage::Servers);
 
         let handle = tokio::spawn(async move {
-            let mut summary_receiver = aggregator.subscribe_summaries();
-            let client_senders = Arc::new(RwLock::new(
-                HashMap::<usize, broadcast::Sender<String>>::new(),
-            ));
+            tokio::pin!(stopping);
             let client_id_counter = Arc::new(AtomicUsize::new(0));
 
-            // Spawn background task to broadcast summaries to all connected clients
-            let broadcast_task = {
-                let client_senders = client_senders.clone();
-                tokio::spawn(async move {
-                    while let Ok(summary) = summary_receiver.recv().await {
-                        let message = json!({
-                            "type": "summary",
-                            "data": {
-                                "symbol": summary.symbol,
-                                "spread": summary.spread,
-                                "bids": summary.bids,
-                                "asks": summary.asks,
-                                "timestamp": summary.timestamp,
-                            }
-                        })
-                        .to_string();
-
-                        let senders = client_senders.read().await;
-                        for (client_id, sender) in senders.iter() {
-                            if let Err(e) = sender.send(message.clone()) {
-                                warn!("Failed to send message to client {}: {}", client_id, e);
-                            }
-                        }
-                    }
-                })
-            };
-
-            // Accept incoming connections
+            // Accept incoming connections until the aggregator stops its servers
             loop {
-                match listener.accept().await {
+                let accepted = tokio::select! {
+                    accepted = listener.accept() => accepted,
+                    _ = &mut stopping => {
+                        info!("WebSocket server shutting down");
+                        break;
+                    }
+                };
+                match accepted {
                     Ok((stream, addr)) => {
-                        let current_connections = connection_count.load(Ordering::Relaxed);
-                        if current_connections >= max_connections {
+                        let Some(slot) =
+                            ConnectionSlot::acquire(&connection_count, max_connections)
+                        else {
                             warn!(
                                 "Maximum connections reached, rejecting connection from {}",
                                 addr
                             );
+                            tokio::spawn(reject_connection(stream));
                             continue;
-                        }
-
-                        connection_count.fetch_add(1, Ordering::Relaxed);
+                        };
                         let client_id = client_id_counter.fetch_add(1, Ordering::Relaxed);
 
                         info!(
@@ -101,20 +150,23 @@ impl ServerTrait for WebSocketServer {
                             addr, client_id
                         );
 
-                        let connection_count_clone = connection_count.clone();
-                        let client_senders_clone = client_senders.clone();
-
+                        let connection = Connection {
+                            client_id,
+                            aggregator: aggregator.clone(),
+                            backpressure: backpressure.clone(),
+                            authenticator: authenticator.clone(),
+                            principal: Principal::anonymous(),
+                            heatmap: heatmap.clone(),
+                            market_stats: market_stats.clone(),
+                            subscriptions: Subscriptions::default(),
+                            feeds: Feeds::default(),
+                            books: BookDeltaEncoder::new(),
+                        };
                         tokio::spawn(async move {
-                            if let Err(e) = handle_connection(
-                                stream,
-                                client_id,
-                                client_senders_clone,
-                                connection_count_clone,
-                            )
-                            .await
-                            {
+                            if let Err(e) = connection.run(stream).await {
                                 error!("Error handling connection from {}: {}", addr, e);
                             }
+                            drop(slot);
                         });
                     }
                     Err(e) => {
@@ -122,13 +174,14 @@ impl ServerTrait for WebSocketServer {
                     }
                 }
             }
+            Ok(())
         });
 
         Ok(handle)
     }
 
     async fn stop(&self) -> Result<()> {
-        // WebSocket server shutdown is handled by dropping the listener
+        // The listener closes once the aggregator stops its servers
         Ok(())
     }
 
@@ -141,46 +194,659 @@ impl ServerTrait for WebSocketServer {
     }
 }
 
-async fn handle_connection(
-    stream: TcpStream,
+/// A connection counted against `max_connections` until it is dropped
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    /// Counts a new connection, unless `max_connections` are already open
+    fn acquire(count: &Arc<AtomicUsize>, max_connections: usize) -> Option<Self> {
+        count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |open| {
+                (open < max_connections).then_some(open + 1)
+            })
+            .ok()
+            .map(|_| Self(count.clone()))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// The credential a client opened the connection with: an `Authorization: Bearer` or
+/// `X-API-Key` header, or for browsers, which cannot set headers, an `api_key` or `token` query
+/// parameter, percent-decoded
+fn handshake_credential(request: &Request) -> Option<Cow<'_, str>> {
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    if let Some(credential) = auth::credential(header("authorization"), header("x-api-key")) {
+        return Some(Cow::Borrowed(credential));
+    }
+    request
+        .uri()
+        .query()?
+        .split('&')
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| matches!(*name, "api_key" | "token"))
+        .and_then(|(_, value)| percent_decode_str(value).decode_utf8().ok())
+        .filter(|value| !value.is_empty())
+}
+
+/// Refuses a handshake with the status the authentication error calls for and the error as
+/// its body
+fn handshake_rejection(error: &AggregatorError) -> ErrorResponse {
+    let status = match error.category() {
+        "rate_limit" => StatusCode::TOO_MANY_REQUESTS,
+        "forbidden" => StatusCode::FORBIDDEN,
+        _ => StatusCode::UNAUTHORIZED,
+    };
+    let mut response = ErrorResponse::new(Some(error.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+/// Completes the handshake of a client over `max_connections` only to close it with "try again
+/// later", so it can tell being turned away from a network failure
+async fn reject_connection(stream: TcpStream) {
+    let Ok(Ok(mut ws_stream)) =
+        tokio::time::timeout(REJECT_HANDSHAKE_TIMEOUT, accept_async(stream)).await
+    else {
+        return;
+    };
+    let close = CloseFrame {
+        code: CloseCode::Again,
+        reason: "Too many connections".into(),
+    };
+    let _ = ws_stream.close(Some(close)).await;
+}
+
+/// Streams a client can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Channel {
+    /// Consolidated summaries of a pair, as they change
+    Summary,
+    /// The level changes each exchange sends, before they are aggregated
+    OrderbookDelta,
+    /// Arbitrage opportunities as they are found
+    Arbitrage,
+    /// Exchange health as it is reported
+    Health,
+}
+
+impl Channel {
+    /// The name clients subscribe with, which is also the one `ApiKeyConfig::channels` lists
+    fn name(self) -> &'static str {
+        match self {
+            Channel::Summary => "summary",
+            Channel::OrderbookDelta => "orderbook-delta",
+            Channel::Arbitrage => "arbitrage",
+            Channel::Health => "health",
+        }
+    }
+}
+
+/// What a subscription narrows its channel to; a field left out matches everything. Symbols
+/// are compared without separators or case, so `BTC/USDT` matches `BTCUSDT`. On the summary
+/// channel, `exchange` keeps that exchange's levels of each summary.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Filter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exchange: Option<Exchange>,
+}
+
+impl Filter {
+    fn wants_symbol(&self, symbol: &str) -> bool {
+        self.symbol
+            .as_deref()
+            .is_none_or(|wanted| compact_symbol(wanted) == compact_symbol(symbol))
+    }
+
+    fn wants_exchange(&self, exchange: &Exchange) -> bool {
+        self.exchange
+            .as_ref()
+            .is_none_or(|wanted| wanted == exchange)
+    }
+}
+
+/// A subscription request, such as
+/// `{"op": "subscribe", "channel": "summary", "symbol": "BTC/USDT"}`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Operation {
+    Subscribe {
+        channel: Channel,
+        #[serde(flatten)]
+        filter: Filter,
+    },
+    Unsubscribe {
+        channel: Channel,
+        #[serde(flatten)]
+        filter: Filter,
+    },
+}
+
+/// The subscriptions of one connection, by channel
+#[derive(Debug, Default)]
+struct Subscriptions {
+    channels: HashMap<Channel, Vec<Filter>>,
+}
+
+impl Subscriptions {
+    /// Adds a subscription, doing nothing if the connection already has it
+    fn subscribe(&mut self, channel: Channel, filter: Filter) -> Result<()> {
+        if self.filters(channel).contains(&filter) {
+            return Ok(());
+        }
+        let count: usize = self.channels.values().map(Vec::len).sum();
+        if count >= MAX_SUBSCRIPTIONS {
+            return Err(AggregatorError::validation(
+                "subscriptions",
+                format!("at most {} per connection", MAX_SUBSCRIPTIONS),
+            ));
+        }
+        // Only once accepted, so a refused subscription leaves no channel behind
+        self.channels.entry(channel).or_default().push(filter);
+        Ok(())
+    }
+
+    fn unsubscribe(&mut self, channel: Channel, filter: &Filter) -> Result<()> {
+        let filters = self.channels.entry(channel).or_default();
+        let before = filters.len();
+        filters.retain(|subscribed| subscribed != filter);
+        let removed = filters.len() < before;
+        if filters.is_empty() {
+            self.channels.remove(&channel);
+        }
+        if removed {
+            Ok(())
+        } else {
+            Err(AggregatorError::not_found(
+                "subscription".to_string(),
+                format!("{} {}", json!(channel), json!(filter)),
+            ))
+        }
+    }
+
+    fn has(&self, channel: Channel) -> bool {
+        self.channels.contains_key(&channel)
+    }
+
+    fn filters(&self, channel: Channel) -> &[Filter] {
+        self.channels.get(&channel).map_or(&[], Vec::as_slice)
+    }
+
+    /// `summary` narrowed to the levels of the exchanges its subscriptions ask for, if any
+    /// subscription wants its symbol
+    fn summary(&self, summary: &Summary) -> Option<Summary> {
+        let filters: Vec<&Filter> = self
+            .filters(Channel::Summary)
+            .iter()
+            .filter(|filter| filter.wants_symbol(&summary.symbol))
+            .collect();
+        if filters.is_empty() {
+            return None;
+        }
+        let mut summary = summary.clone();
+        if filters.iter().all(|filter| filter.exchange.is_some()) {
+            let wants = |exchange: &Exchange| filters.iter().any(|f| f.wants_exchange(exchange));
+            summary.bids.retain(|level| wants(&level.exchange));
+            summary.asks.retain(|level| wants(&level.exchange));
+            summary.spread = match (summary.bids.first(), summary.asks.first()) {
+                (Some(best_bid), Some(best_ask)) => best_ask.price - best_bid.price,
+                _ => 0.0,
+            };
+        }
+        Some(summary)
+    }
+
+    fn wants_summary_symbol(&self, symbol: &str) -> bool {
+        self.filters(Channel::Summary)
+            .iter()
+            .any(|filter| filter.wants_symbol(symbol))
+    }
+
+    fn wants_price_levels(&self, update: &PriceLevelUpdate) -> bool {
+        self.filters(Channel::OrderbookDelta).iter().any(|filter| {
+            filter.wants_symbol(&update.symbol) && filter.wants_exchange(&update.exchange)
+        })
+    }
+
+    /// Whether a subscription wants `opportunity`, an exchange filter matching either side
+    fn wants_opportunity(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        self.filters(Channel::Arbitrage).iter().any(|filter| {
+            filter.wants_symbol(&opportunity.symbol)
+                && (filter.wants_exchange(&opportunity.buy_exchange)
+                    || filter.wants_exchange(&opportunity.sell_exchange))
+        })
+    }
+
+    fn wants_health(&self, status: &HealthStatus) -> bool {
+        self.filters(Channel::Health)
+            .iter()
+            .any(|filter| filter.wants_exchange(&status.exchange))
+    }
+}
+
+/// The event streams a connection reads, each opened by the first subscription to its channel
+/// and closed with the last
+#[derive(Default)]
+struct Feeds {
+    summaries: Option<BoundedReceiver<Summary>>,
+    arbitrage: Option<BoundedReceiver<ArbitrageOpportunity>>,
+    price_levels: Option<broadcast::Receiver<PriceLevelUpdate>>,
+    health: Option<broadcast::Receiver<HealthStatus>>,
+}
+
+/// What woke a connection up
+enum Step {
+    Client(Option<std::result::Result<Message, tokio_tungstenite::tungstenite::Error>>),
+    Summary(Option<Summary>),
+    Arbitrage(Option<ArbitrageOpportunity>),
+    PriceLevels(Option<PriceLevelUpdate>),
+    Health(Option<HealthStatus>),
+}
+
+/// One client connection and what it has subscribed to
+struct Connection {
     client_id: usize,
-    client_senders: Arc<RwLock<HashMap<usize, broadcast::Sender<String>>>>,
-    connection_count: Arc<AtomicUsize>,
-) -> Result<()> {
-    let ws_stream = accept_async(stream)
-        .await
-        .map_err(|e| AggregatorError::network(format!("WebSocket handshake failed: {}", e)))?;
+    aggregator: Arc<Aggregator>,
+    backpressure: BackpressureConfig,
+    authenticator: Arc<Authenticator>,
+    /// Who the client authenticated as during the handshake
+    principal: Principal,
+    heatmap: HeatmapCollector,
+    market_stats: MarketStatsCollector,
+    subscriptions: Subscriptions,
+    feeds: Feeds,
+    /// The summary channel's books as last sent, which deltas are computed against
+    books: BookDeltaEncoder,
+}
+
+impl Connection {
+    async fn run(mut self, stream: TcpStream) -> Result<()> {
+        // Set by the handshake once it reads the client's credential
+        let mut authenticated = None;
+        // The callback's error is the HTTP response tungstenite sends
+        #[allow(clippy::result_large_err)]
+        let handshake = accept_hdr_async(stream, |request: &Request, response: Response| {
+            let result = self
+                .authenticator
+                .authenticate(handshake_credential(request).as_deref());
+            let reply = match &result {
+                Ok(_) => Ok(response),
+                Err(e) => Err(handshake_rejection(e)),
+            };
+            authenticated = Some(result);
+            reply
+        })
+        .await;
+        let ws_stream = match (handshake, authenticated) {
+            (Ok(ws_stream), Some(Ok(principal))) => {
+                self.principal = principal;
+                ws_stream
+            }
+            // The client was answered with why it was refused
+            (_, Some(Err(e))) => {
+                warn!("Rejected WebSocket client {}: {}", self.client_id, e);
+                return Ok(());
+            }
+            (Err(e), _) => {
+                return Err(AggregatorError::network(format!(
+                    "WebSocket handshake failed: {}",
+                    e
+                )))
+            }
+            (Ok(_), None) => {
+                return Err(AggregatorError::Internal {
+                    message: "WebSocket handshake completed without authenticating".to_string(),
+                })
+            }
+        };
+        let (mut tx, mut rx) = ws_stream.split();
+
+        'connection: loop {
+            let step = tokio::select! {
+                message = rx.next() => Step::Client(message),
+                summary = next_bounded(&mut self.feeds.summaries) => Step::Summary(summary),
+                opportunity = next_bounded(&mut self.feeds.arbitrage) => Step::Arbitrage(opportunity),
+                update = next_broadcast(&mut self.feeds.price_levels) => Step::PriceLevels(update),
+                status = next_broadcast(&mut self.feeds.health) => Step::Health(status),
+            };
+
+            let replies = match step {
+                Step::Client(Some(Ok(Message::Text(text)))) => self.handle_text(&text).await,
+                // Pings are answered by the stream itself, and binary frames are ignored
+                Step::Client(Some(Ok(Message::Close(_)))) | Step::Client(Some(Err(_))) => break,
+                Step::Client(Some(Ok(_))) => continue,
+                Step::Client(None) => break,
+                Step::Summary(Some(summary)) => self
+                    .subscriptions
+                    .summary(&summary)
+                    .and_then(|summary| self.books.encode(&summary))
+                    .map(|update| json!(update).to_string())
+                    .into_iter()
+                    .collect(),
+                Step::Arbitrage(Some(opportunity)) => {
+                    if self.subscriptions.wants_opportunity(&opportunity) {
+                        vec![json!({ "type": "arbitrage", "data": opportunity }).to_string()]
+                    } else {
+                        Vec::new()
+                    }
+                }
+                Step::PriceLevels(Some(update)) => {
+                    if self.subscriptions.wants_price_levels(&update) {
+                        vec![delta_message(&update)]
+                    } else {
+                        Vec::new()
+                    }
+                }
+                Step::Health(Some(status)) => {
+                    if self.subscriptions.wants_health(&status) {
+                        vec![json!({ "type": "health", "data": status }).to_string()]
+                    } else {
+                        Vec::new()
+                    }
+                }
+                // Otherwise the aggregator closed the feed, so there is nothing left to read
+                Step::Summary(None) => match self.feeds.summaries.take() {
+                    Some(summaries) if summaries.is_disconnected() => {
+                        close_slow(&mut tx, self.client_id, "summaries", summaries.stats()).await;
+                        break;
+                    }
+                    _ => Vec::new(),
+                },
+                Step::Arbitrage(None) => match self.feeds.arbitrage.take() {
+                    Some(arbitrage) if arbitrage.is_disconnected() => {
+                        close_slow(&mut tx, self.client_id, "opportunities", arbitrage.stats())
+                            .await;
+                        break;
+                    }
+                    _ => Vec::new(),
+                },
+                Step::PriceLevels(None) => {
+                    self.feeds.price_levels = None;
+                    Vec::new()
+                }
+                Step::Health(None) => {
+                    self.feeds.health = None;
+                    Vec::new()
+                }
+            };
+
+            for reply in replies {
+                if tx.send(Message::Text(reply)).await.is_err() {
+                    break 'connection;
+                }
+            }
+        }
+
+        info!(
+            "WebSocket connection closed (client_id: {})",
+            self.client_id
+        );
+        Ok(())
+    }
+
+    /// Answers a text frame: a subscription operation if it has an `op`, else a request such
+    /// as a heatmap
+    async fn handle_text(&mut self, text: &str) -> Vec<String> {
+        if let Err(e) = self.authenticator.check_rate(&self.principal) {
+            return vec![error_message(&e.to_string())];
+        }
+        let Ok(request) = serde_json::from_str::<serde_json::Value>(text) else {
+            return vec![error_message("Requests must be JSON objects")];
+        };
+        if request.get("op").is_none() {
+            return handle_client_request(
+                &request,
+                &self.heatmap,
+                &self.market_stats,
+                &self.aggregator,
+            )
+            .await
+            .into_iter()
+            .collect();
+        }
+        match serde_json::from_value::<Operation>(request) {
+            Ok(Operation::Subscribe { channel, filter }) => {
+                if let Err(e) = self
+                    .principal
+                    .require_channel(channel.name())
+                    .and_then(|_| self.subscriptions.subscribe(channel, filter.clone()))
+                {
+                    return vec![error_message(&e.to_string())];
+                }
+                self.open_feed(channel);
+                let mut replies = vec![ack_message("subscribed", channel, &filter)];
+                replies.extend(self.current_state(channel, &filter).await);
+                replies
+            }
+            Ok(Operation::Unsubscribe { channel, filter }) => {
+                if let Err(e) = self.subscriptions.unsubscribe(channel, &filter) {
+                    return vec![error_message(&e.to_string())];
+                }
+                if !self.subscriptions.has(channel) {
+                    self.close_feed(channel);
+                }
+                let subscriptions = &self.subscriptions;
+                self.books
+                    .retain(|symbol| subscriptions.wants_summary_symbol(symbol));
+                vec![ack_message("unsubscribed", channel, &filter)]
+            }
+            Err(e) => vec![error_message(&format!("Invalid operation: {}", e))],
+        }
+    }
 
-    let (mut tx, mut rx) = ws_stream.split();
-    let (bcast_tx, bcast_rx) = broadcast::channel::<String>(100);
+    fn open_feed(&mut self, channel: Channel) {
+        // Each client reads summaries and opportunities through its own bounded buffer, so a
+        // slow one cannot make the others miss them
+        let feeds = &mut self.feeds;
+        let aggregator = &self.aggregator;
+        match channel {
+            Channel::Summary => {
+                feeds.summaries.get_or_insert_with(|| {
+                    aggregator.subscribe_summaries_bounded(&self.backpressure)
+                });
+            }
+            Channel::Arbitrage => {
+                feeds.arbitrage.get_or_insert_with(|| {
+                    aggregator.subscribe_arbitrage_bounded(&self.backpressure)
+                });
+            }
+            Channel::OrderbookDelta => {
+                feeds
+                    .price_levels
+                    .get_or_insert_with(|| aggregator.subscribe_price_levels());
+            }
+            Channel::Health => {
+                feeds
+                    .health
+                    .get_or_insert_with(|| aggregator.subscribe::<HealthStatus>());
+            }
+        }
+    }
 
-    client_senders.write().await.insert(client_id, bcast_tx);
+    fn close_feed(&mut self, channel: Channel) {
+        match channel {
+            Channel::Summary => self.feeds.summaries = None,
+            Channel::Arbitrage => self.feeds.arbitrage = None,
+            Channel::OrderbookDelta => self.feeds.price_levels = None,
+            Channel::Health => self.feeds.health = None,
+        }
+    }
 
-    let mut bcast_rx = bcast_rx;
+    /// What a new subscription starts from, so the client does not wait for the next change: a
+    /// snapshot of each book it matches, which later deltas build on, or the latest health of
+    /// each exchange. Exchange deltas and opportunities only stream from now on.
+    async fn current_state(&mut self, channel: Channel, filter: &Filter) -> Vec<String> {
+        match channel {
+            Channel::Summary => {
+                let mut summaries: Vec<Summary> = self
+                    .aggregator
+                    .get_all_summaries()
+                    .await
+                    .into_values()
+                    .filter(|summary| filter.wants_symbol(&summary.symbol))
+                    .filter_map(|summary| self.subscriptions.summary(&summary))
+                    .collect();
+                summaries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+                summaries
+                    .iter()
+                    .map(|summary| {
+                        let snapshot = self.books.snapshot(summary);
+                        json!(BookUpdate::Snapshot(snapshot)).to_string()
+                    })
+                    .collect()
+            }
+            Channel::Health => {
+                let mut statuses: Vec<HealthStatus> = self
+                    .aggregator
+                    .get_all_health_statuses()
+                    .await
+                    .into_values()
+                    .filter(|status| filter.wants_exchange(&status.exchange))
+                    .collect();
+                statuses.sort_by(|a, b| a.exchange.cmp(&b.exchange));
+                statuses
+                    .into_iter()
+                    .map(|status| json!({ "type": "health", "data": status }).to_string())
+                    .collect()
+            }
+            Channel::OrderbookDelta | Channel::Arbitrage => Vec::new(),
+        }
+    }
+}
+
+/// The next event of a bounded feed, or never if the feed is not open
+async fn next_bounded<T: Conflate + Clone + Send + 'static>(
+    feed: &mut Option<BoundedReceiver<T>>,
+) -> Option<T> {
+    match feed {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
 
-    let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = bcast_rx.recv().await {
-            if tx.send(Message::Text(msg)).await.is_err() {
-                break;
+/// The next event of a broadcast feed, skipping what was missed while lagging, or never if the
+/// feed is not open. `None` once the aggregator has closed it.
+async fn next_broadcast<T: Clone>(feed: &mut Option<broadcast::Receiver<T>>) -> Option<T> {
+    let Some(receiver) = feed else {
+        return std::future::pending().await;
+    };
+    loop {
+        match receiver.recv().await {
+            Ok(event) => return Some(event),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                debug!("WebSocket client lagged, skipping {} events", skipped);
             }
+            Err(broadcast::error::RecvError::Closed) => return None,
         }
-    });
+    }
+}
+
+/// Closes the connection of a client that read `what` too slowly for its buffer
+async fn close_slow<S: futures_util::Sink<Message> + Unpin>(
+    tx: &mut S,
+    client_id: usize,
+    what: &str,
+    stats: BackpressureSnapshot,
+) {
+    warn!(
+        "Disconnecting slow WebSocket client {}: {:?}",
+        client_id, stats
+    );
+    let close = CloseFrame {
+        code: CloseCode::Again,
+        reason: format!("Too slow to keep up with {}", what).into(),
+    };
+    let _ = tx.send(Message::Close(Some(close))).await;
+}
 
-    let mut recv_task = tokio::spawn(async move {
-        while let Some(Ok(msg)) = rx.next().await {
-            // Handle incoming messages from client (e.g., subscription requests)
-            // For now, we just ignore them
+/// The levels an exchange changed, a quantity of 0 removing a level
+fn delta_message(update: &PriceLevelUpdate) -> String {
+    let levels = |levels: Vec<(f64, f64)>| -> Vec<serde_json::Value> {
+        levels
+            .into_iter()
+            .map(|(price, quantity)| json!({ "price": price, "quantity": quantity }))
+            .collect()
+    };
+    json!({
+        "type": "orderbook-delta",
+        "data": {
+            "exchange": update.exchange,
+            "symbol": update.symbol,
+            "bids": levels(update.bids.iter().map(|bid| (bid.price, bid.quantity)).collect()),
+            "asks": levels(update.asks.iter().map(|ask| (ask.price, ask.quantity)).collect()),
+            "timestamp": update.timestamp,
         }
-    });
+    })
+    .to_string()
+}
 
-    tokio::select! {
-        _ = &mut send_task => (),
-        _ = &mut recv_task => (),
+fn ack_message(kind: &str, channel: Channel, filter: &Filter) -> String {
+    let mut message = json!({ "type": kind, "channel": channel });
+    if let (Some(message), serde_json::Value::Object(filter)) =
+        (message.as_object_mut(), json!(filter))
+    {
+        message.extend(filter);
     }
+    message.to_string()
+}
 
-    info!("WebSocket connection closed (client_id: {})", client_id);
-    client_senders.write().await.remove(&client_id);
-    connection_count.fetch_sub(1, Ordering::Relaxed);
+fn error_message(message: &str) -> String {
+    json!({ "type": "error", "message": message }).to_string()
+}
 
-    Ok(())
+/// Answers a client request such as `{"type": "heatmap", "symbol": "BTCUSDT"}`,
+/// `{"type": "stats", "symbol": "BTCUSDT", "window": 60}` or
+/// `{"type": "errors", "category": "parsing", "limit": 20}`
+async fn handle_client_request(
+    request: &serde_json::Value,
+    heatmap: &HeatmapCollector,
+    market_stats: &MarketStatsCollector,
+    aggregator: &Aggregator,
+) -> Option<String> {
+    match request.get("type").and_then(|t| t.as_str()) {
+        Some("heatmap") => {
+            let symbol = request.get("symbol")?.as_str()?.to_uppercase();
+            let reply = match heatmap.matrix(&symbol).await {
+                Some(matrix) => json!({ "type": "heatmap", "data": matrix }),
+                None => json!({ "type": "error", "message": "Heatmap not found" }),
+            };
+            Some(reply.to_string())
+        }
+        Some("stats") => {
+            let symbol = request.get("symbol")?.as_str()?.to_uppercase();
+            let window = request
+                .get("window")
+                .and_then(|w| w.as_u64())
+                .unwrap_or(DEFAULT_STATS_WINDOW_SECS);
+            let snapshots = market_stats.symbol_snapshots(&symbol, window).await;
+            let reply = if snapshots.is_empty() {
+                json!({ "type": "error", "message": "Market stats not found" })
+            } else {
+                json!({ "type": "stats", "data": snapshots })
+            };
+            Some(reply.to_string())
+        }
+        Some("errors") => {
+            let category = request.get("category").and_then(|c| c.as_str());
+            let limit = request
+                .get("limit")
+                .and_then(|l| l.as_u64())
+                .map(|limit| limit as usize);
+            let report = aggregator.errors().report(category, limit);
+            Some(json!({ "type": "errors", "data": report }).to_string())
+        }
+        _ => None,
+    }
 }
@@ -0,0 +1,81 @@
+// The common helpers use the auth module, which comes with any of the servers
+#![cfg(any(feature = "grpc", feature = "rest", feature = "websocket"))]
+
+mod common;
+
+use aggregator_core::{Config, Exchange, OrderBookImplementation, StorageBackend, TradingPair};
+use common::{price_level_update, publish, start_feed};
+use server_implementations::aggregator_from_config;
+#[cfg(all(feature = "rest", feature = "websocket"))]
+use {
+    common::{aggregator, free_port, TIMEOUT},
+    server_implementations::create_servers_from_config,
+};
+
+#[cfg(test)]
+mod aggregator_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_configured_order_book_implementation_is_used() {
+        let mut config = Config::default();
+        config.orderbook.implementation = OrderBookImplementation::HashMap;
+        let aggregator = aggregator_from_config(config).await.unwrap();
+        let feed = start_feed(&aggregator).await;
+
+        let update = price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            &[(50000.0, 1.0)],
+            &[(50001.0, 1.0)],
+        );
+        publish(&aggregator, &feed, update).await;
+
+        let pair = TradingPair::new("BTC", "USDT");
+        let name = aggregator.book_name(&pair).await.unwrap();
+        assert!(name.contains("ConsolidatedOrderBook"), "{}", name);
+        assert!(name.contains("HashMapOrderBook"), "{}", name);
+    }
+
+    #[tokio::test]
+    async fn test_enabled_storage_is_attached() {
+        let mut config = Config::default();
+        config.storage.enabled = true;
+        config.storage.backend = StorageBackend::Memory;
+        let aggregator = aggregator_from_config(config).await.unwrap();
+
+        assert_eq!(aggregator.storage().unwrap().name(), "memory");
+    }
+
+    #[tokio::test]
+    async fn test_disabled_storage_is_not_attached() {
+        let mut config = Config::default();
+        config.storage.enabled = false;
+        let aggregator = aggregator_from_config(config).await.unwrap();
+
+        assert!(aggregator.storage().is_none());
+    }
+
+    #[cfg(all(feature = "rest", feature = "websocket"))]
+    #[tokio::test]
+    async fn test_shared_heatmap_is_fed_once_and_stops_with_the_servers() {
+        let mut config = Config::default();
+        config.server.rest.enabled = true;
+        config.server.rest.port = free_port();
+        config.server.websocket.enabled = true;
+        config.server.websocket.port = free_port();
+        config.metrics.enabled = false;
+        let manager = create_servers_from_config(&config);
+        let aggregator = aggregator(&[Exchange::Binance]);
+        manager.start_all(aggregator.clone()).await.unwrap();
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+        let collectors = report
+            .stopped
+            .iter()
+            .filter(|component| component.as_str() == "heatmap collector")
+            .count();
+        assert_eq!(collectors, 1, "{:?}", report.stopped);
+    }
+}
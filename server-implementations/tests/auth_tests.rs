@@ -0,0 +1,221 @@
+// The auth module comes with any of the servers
+#![cfg(any(feature = "grpc", feature = "rest", feature = "websocket"))]
+
+mod common;
+
+use aggregator_core::{AuthConfig, AuthRole, JwtConfig, RateLimitConfig};
+use common::api_key;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde_json::json;
+use server_implementations::auth::{credential, Authenticator, Principal};
+
+#[cfg(test)]
+mod auth_tests {
+    use super::*;
+
+    const SECRET: &str = "jwt-s3cr3t";
+
+    fn jwt_authenticator() -> Authenticator {
+        Authenticator::new(&AuthConfig {
+            enabled: true,
+            api_keys: vec![api_key("ops", "ops-key", AuthRole::Admin)],
+            jwt: Some(JwtConfig {
+                secret: SECRET.to_string(),
+                issuer: None,
+                audience: None,
+            }),
+            ..AuthConfig::default()
+        })
+    }
+
+    /// A token for `sub` expiring `expires_in` seconds from now, signed with `secret`
+    fn token(algorithm: Algorithm, secret: &str, sub: &str, expires_in: i64) -> String {
+        let claims = json!({
+            "sub": sub,
+            "exp": chrono::Utc::now().timestamp() + expires_in,
+            "role": "admin",
+            "channels": ["summary"],
+        });
+        jsonwebtoken::encode(
+            &Header::new(algorithm),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_credential_extraction() {
+        assert_eq!(credential(Some("Bearer tok"), Some("key")), Some("tok"));
+        assert_eq!(credential(Some("Basic dXNlcg=="), Some("key")), Some("key"));
+        assert_eq!(credential(None, Some(" key ")), Some("key"));
+        assert_eq!(credential(Some("Bearer  "), None), None);
+        assert_eq!(credential(None, Some("")), None);
+        assert_eq!(credential(None, None), None);
+    }
+
+    #[test]
+    fn test_disabled_authenticator_lets_everyone_in() {
+        let authenticator = Authenticator::disabled();
+        assert!(!authenticator.is_enabled());
+        let principal = authenticator.authenticate(None).unwrap();
+        assert_eq!(principal, Principal::anonymous());
+        assert!(principal.require_channel("arbitrage").is_ok());
+        assert_eq!(
+            principal.require_admin().unwrap_err().category(),
+            "forbidden"
+        );
+    }
+
+    #[test]
+    fn test_api_keys() {
+        let mut dashboard = api_key("dashboard", "dash-key", AuthRole::ReadOnly);
+        dashboard.channels = vec!["summary".to_string()];
+        let authenticator = Authenticator::new(&AuthConfig {
+            enabled: true,
+            api_keys: vec![dashboard, api_key("ops", "ops-key", AuthRole::Admin)],
+            ..AuthConfig::default()
+        });
+
+        for credential in [None, Some("nope")] {
+            let error = authenticator.authenticate(credential).unwrap_err();
+            assert_eq!(error.category(), "authentication");
+        }
+
+        let principal = authenticator.authenticate(Some("dash-key")).unwrap();
+        assert_eq!(principal.name, "dashboard");
+        assert!(!principal.is_admin());
+        assert!(principal.require_channel("summary").is_ok());
+        let error = principal.require_channel("arbitrage").unwrap_err();
+        assert_eq!(error.category(), "forbidden");
+        assert_eq!(
+            principal.require_admin().unwrap_err().category(),
+            "forbidden"
+        );
+
+        let principal = authenticator.authenticate(Some("ops-key")).unwrap();
+        assert!(principal.require_admin().is_ok());
+        assert!(principal.require_channel("arbitrage").is_ok());
+
+        let debug = format!("{:?}", authenticator);
+        assert!(debug.contains("dashboard"));
+        assert!(!debug.contains("dash-key"));
+        assert!(!debug.contains("ops-key"));
+    }
+
+    #[test]
+    fn test_rate_limit_is_shared_by_keys_of_a_name() {
+        let limit = RateLimitConfig {
+            requests_per_second: 0,
+            burst_size: 2,
+        };
+        let mut first = api_key("shared", "key-1", AuthRole::ReadOnly);
+        first.rate_limit = Some(limit.clone());
+        let mut second = api_key("shared", "key-2", AuthRole::ReadOnly);
+        second.rate_limit = Some(limit);
+        let authenticator = Authenticator::new(&AuthConfig {
+            enabled: true,
+            api_keys: vec![first, second, api_key("other", "key-3", AuthRole::ReadOnly)],
+            ..AuthConfig::default()
+        });
+
+        authenticator.authenticate(Some("key-1")).unwrap();
+        authenticator.authenticate(Some("key-2")).unwrap();
+        let error = authenticator.authenticate(Some("key-1")).unwrap_err();
+        assert_eq!(error.category(), "rate_limit");
+        // Other clients draw from their own budget
+        authenticator.authenticate(Some("key-3")).unwrap();
+    }
+
+    #[test]
+    fn test_valid_token() {
+        let authenticator = jwt_authenticator();
+        let principal = authenticator
+            .authenticate(Some(&token(Algorithm::HS256, SECRET, "bot", 60)))
+            .unwrap();
+        assert_eq!(principal.name, "bot");
+        assert!(principal.is_admin());
+        assert_eq!(principal.channels, vec!["summary".to_string()]);
+        // API keys keep working alongside tokens
+        assert!(authenticator.authenticate(Some("ops-key")).is_ok());
+        assert!(!format!("{:?}", authenticator).contains(SECRET));
+    }
+
+    #[test]
+    fn test_invalid_tokens_are_refused() {
+        let authenticator = jwt_authenticator();
+        let tokens = [
+            // Expired past the default leeway
+            token(Algorithm::HS256, SECRET, "bot", -3600),
+            token(Algorithm::HS256, "another-secret", "bot", 60),
+            // Only HS256 is accepted, even signed with the secret
+            token(Algorithm::HS512, SECRET, "bot", 60),
+            "not.a.token".to_string(),
+        ];
+        for token in tokens {
+            let error = authenticator.authenticate(Some(&token)).unwrap_err();
+            assert_eq!(error.category(), "authentication", "{}", token);
+        }
+    }
+
+    #[test]
+    fn test_tokens_need_a_jwt_config() {
+        let authenticator = Authenticator::new(&AuthConfig {
+            enabled: true,
+            api_keys: vec![api_key("ops", "ops-key", AuthRole::Admin)],
+            ..AuthConfig::default()
+        });
+        let token = token(Algorithm::HS256, SECRET, "bot", 60);
+        let error = authenticator.authenticate(Some(&token)).unwrap_err();
+        assert_eq!(error.category(), "authentication");
+    }
+
+    #[tokio::test]
+    async fn test_refilled_rate_limits_are_dropped() {
+        let mut api_keys: Vec<_> = (0..1024)
+            .map(|n| {
+                api_key(
+                    &format!("client{}", n),
+                    &format!("key{}", n),
+                    AuthRole::ReadOnly,
+                )
+            })
+            .collect();
+        let mut exhausted = api_key("exhausted", "spent", AuthRole::ReadOnly);
+        exhausted.rate_limit = Some(RateLimitConfig {
+            requests_per_second: 0,
+            burst_size: 1,
+        });
+        api_keys.push(exhausted);
+        let authenticator = Authenticator::new(&AuthConfig {
+            enabled: true,
+            api_keys,
+            rate_limit: RateLimitConfig {
+                requests_per_second: 1000,
+                burst_size: 1,
+            },
+            ..AuthConfig::default()
+        });
+
+        authenticator.authenticate(Some("spent")).unwrap();
+        let error = authenticator.authenticate(Some("spent")).unwrap_err();
+        assert_eq!(error.category(), "rate_limit");
+        for n in 0..1023 {
+            authenticator
+                .authenticate(Some(&format!("key{}", n)))
+                .unwrap();
+        }
+        assert_eq!(authenticator.tracked_clients(), 1024);
+
+        // The next new client sweeps out every limiter that has refilled since
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        authenticator.authenticate(Some("key1023")).unwrap();
+        assert_eq!(authenticator.tracked_clients(), 2);
+        // A client short of requests keeps its limit
+        let error = authenticator.authenticate(Some("spent")).unwrap_err();
+        assert_eq!(error.category(), "rate_limit");
+        // Clients dropped start over with a full budget
+        authenticator.authenticate(Some("key0")).unwrap();
+        assert_eq!(authenticator.tracked_clients(), 3);
+    }
+}
@@ -0,0 +1,167 @@
+//! Common test utilities for the server tests
+
+#![allow(dead_code)]
+
+use aggregator_core::{
+    Aggregator, ApiKeyConfig, Ask, AuthConfig, AuthRole, Bid, Config, Exchange, OrderBookService,
+    PriceLevelUpdate, ReplayPace, ReplaySource, Result, Summary, TradingPair,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use server_implementations::auth::Authenticator;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// How long a test waits for something the servers or the aggregator should do
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A local port nothing listens on, for a server to bind
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// An aggregator reading from `exchanges` only, with the default trading pairs
+pub fn aggregator(exchanges: &[Exchange]) -> Arc<Aggregator> {
+    let mut config = Config::default();
+    for (exchange, exchange_config) in config.exchanges.iter_mut() {
+        exchange_config.enabled = exchanges.contains(exchange);
+    }
+    Arc::new(Aggregator::new(config))
+}
+
+/// A connector that sends nothing and waits to be stopped
+struct IdleConnector;
+
+#[async_trait]
+impl OrderBookService for IdleConnector {
+    async fn spawn_order_book_service(
+        &self,
+        _pairs: &[TradingPair],
+        _order_book_depth: usize,
+        _exchange_stream_buffer: usize,
+        _price_level_tx: mpsc::Sender<PriceLevelUpdate>,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> Result<Vec<JoinHandle<Result<()>>>> {
+        Ok(vec![tokio::spawn(async move {
+            let _ = shutdown.recv().await;
+            Ok(())
+        })])
+    }
+}
+
+/// Registers a connector for `exchange` on `aggregator`, so it runs once enabled
+pub async fn register_idle_connector(aggregator: &Aggregator, exchange: &Exchange) {
+    aggregator
+        .connectors()
+        .register(&exchange.to_string(), |_config| Ok(Arc::new(IdleConnector)))
+        .await;
+}
+
+pub fn price_level_update(
+    symbol: &str,
+    exchange: Exchange,
+    bids: &[(f64, f64)],
+    asks: &[(f64, f64)],
+) -> PriceLevelUpdate {
+    let timestamp = Utc::now();
+    PriceLevelUpdate {
+        id: Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        exchange: exchange.clone(),
+        bids: bids
+            .iter()
+            .map(|&(price, quantity)| Bid {
+                price,
+                quantity,
+                exchange: exchange.clone(),
+                timestamp,
+            })
+            .collect(),
+        asks: asks
+            .iter()
+            .map(|&(price, quantity)| Ask {
+                price,
+                quantity,
+                exchange: exchange.clone(),
+                timestamp,
+            })
+            .collect(),
+        timestamp,
+        funding: None,
+        event_time: None,
+        market_type: None,
+    }
+}
+
+/// Updates handed to the aggregator as the test sends them, until the sender is dropped
+struct LiveFeed(mpsc::UnboundedReceiver<PriceLevelUpdate>);
+
+#[async_trait]
+impl ReplaySource for LiveFeed {
+    async fn next_update(&mut self) -> Option<Result<PriceLevelUpdate>> {
+        self.0.recv().await.map(Ok)
+    }
+}
+
+/// Starts `aggregator` on updates sent to the returned feed instead of live connectors
+pub async fn start_feed(aggregator: &Aggregator) -> mpsc::UnboundedSender<PriceLevelUpdate> {
+    let (feed, updates) = mpsc::unbounded_channel();
+    aggregator
+        .start_replay(LiveFeed(updates), ReplayPace::Unpaced)
+        .await
+        .unwrap();
+    feed
+}
+
+/// Sends `update` through `feed` and waits until the aggregator has published its summary
+pub async fn publish(
+    aggregator: &Aggregator,
+    feed: &mpsc::UnboundedSender<PriceLevelUpdate>,
+    update: PriceLevelUpdate,
+) -> Summary {
+    let mut summaries = aggregator.subscribe_summaries();
+    let symbol = update.symbol.clone();
+    feed.send(update).unwrap();
+    next_summary(&mut summaries, &symbol).await
+}
+
+/// The next summary of `symbol`, skipping those of other symbols
+pub async fn next_summary(summaries: &mut broadcast::Receiver<Summary>, symbol: &str) -> Summary {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            let summary = summaries.recv().await.unwrap();
+            if summary.symbol == symbol {
+                return summary;
+            }
+        }
+    })
+    .await
+    .expect("no summary published")
+}
+
+/// An API key named `name` with `role`, allowed on every channel
+pub fn api_key(name: &str, key: &str, role: AuthRole) -> ApiKeyConfig {
+    ApiKeyConfig {
+        name: name.to_string(),
+        key: key.to_string(),
+        role,
+        channels: Vec::new(),
+        rate_limit: None,
+    }
+}
+
+/// An enabled authenticator accepting `api_keys`, with the default rate limit
+pub fn authenticator(api_keys: Vec<ApiKeyConfig>) -> Arc<Authenticator> {
+    Arc::new(Authenticator::new(&AuthConfig {
+        enabled: true,
+        api_keys,
+        ..AuthConfig::default()
+    }))
+}
@@ -0,0 +1,237 @@
+mod common;
+
+use aggregator_core::{Aggregator, AuthRole, Exchange};
+use common::{
+    aggregator, api_key, authenticator, free_port, price_level_update, publish, start_feed, TIMEOUT,
+};
+use server_implementations::auth::Authenticator;
+use server_implementations::grpc::orderbook::orderbook_aggregator_client::OrderbookAggregatorClient;
+use server_implementations::grpc::orderbook::BookSummaryRequest;
+use server_implementations::grpc::orderbook_service::orderbook_service_client::OrderbookServiceClient;
+use server_implementations::grpc::orderbook_service::{GetSummaryRequest, StreamSummariesRequest};
+use server_implementations::grpc::GrpcServer;
+use server_implementations::Server;
+use std::sync::Arc;
+use tonic::transport::Channel;
+use tonic::Code;
+
+#[cfg(test)]
+mod grpc_tests {
+    use super::*;
+
+    /// Starts a gRPC server for `aggregator` and returns the port it listens on
+    async fn start_server(aggregator: &Arc<Aggregator>) -> u16 {
+        start_authenticated(aggregator, Arc::new(Authenticator::disabled())).await
+    }
+
+    async fn start_authenticated(
+        aggregator: &Arc<Aggregator>,
+        authenticator: Arc<Authenticator>,
+    ) -> u16 {
+        let port = free_port();
+        GrpcServer::new("127.0.0.1".to_string(), port)
+            .with_authenticator(authenticator)
+            .start(aggregator.clone())
+            .await
+            .unwrap();
+        port
+    }
+
+    /// Connects to the server on `port`, retrying until it listens
+    async fn connect(port: u16) -> Channel {
+        let endpoint = Channel::from_shared(format!("http://127.0.0.1:{}", port)).unwrap();
+        tokio::time::timeout(TIMEOUT, async {
+            loop {
+                match endpoint.connect().await {
+                    Ok(channel) => return channel,
+                    Err(_) => tokio::time::sleep(std::time::Duration::from_millis(20)).await,
+                }
+            }
+        })
+        .await
+        .expect("gRPC server did not start")
+    }
+
+    #[tokio::test]
+    async fn test_get_summary() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let feed = start_feed(&aggregator).await;
+        let port = start_server(&aggregator).await;
+        let mut client = OrderbookServiceClient::new(connect(port).await);
+
+        let status = client
+            .get_summary(GetSummaryRequest {
+                base: "BTC".to_string(),
+                quote: "USDT".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        let update = price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            &[(100.0, 1.0)],
+            &[(101.0, 2.0)],
+        );
+        publish(&aggregator, &feed, update).await;
+        let summary = client
+            .get_summary(GetSummaryRequest {
+                base: "BTC".to_string(),
+                quote: "USDT".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .summary
+            .unwrap();
+        assert_eq!(summary.symbol, "BTCUSDT");
+        assert_eq!(summary.spread, 1.0);
+        assert_eq!(summary.bids[0].price, 100.0);
+        assert_eq!(summary.bids[0].exchange, "binance");
+        assert_eq!(summary.asks[0].quantity, 2.0);
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_stream_summaries() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let feed = start_feed(&aggregator).await;
+        let port = start_server(&aggregator).await;
+        let mut client = OrderbookServiceClient::new(connect(port).await);
+
+        // The server subscribes before it answers with the stream
+        let mut stream = client
+            .stream_summaries(StreamSummariesRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+        for bid in [100.0, 100.5] {
+            let update =
+                price_level_update("BTCUSDT", Exchange::Binance, &[(bid, 1.0)], &[(101.0, 1.0)]);
+            feed.send(update).unwrap();
+        }
+
+        for bid in [100.0, 100.5] {
+            let summary = tokio::time::timeout(TIMEOUT, stream.message())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert_eq!(summary.symbol, "BTCUSDT");
+            assert_eq!(summary.bids[0].price, bid);
+        }
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_book_summary_stream_filters() {
+        let aggregator = aggregator(&[Exchange::Binance, Exchange::Bybit]);
+        let feed = start_feed(&aggregator).await;
+        let port = start_server(&aggregator).await;
+        let mut client = OrderbookAggregatorClient::new(connect(port).await);
+
+        let status = client
+            .book_summary_stream(BookSummaryRequest {
+                symbols: Vec::new(),
+                exchanges: vec!["nowhere".to_string()],
+                depth: 0,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let mut stream = client
+            .book_summary_stream(BookSummaryRequest {
+                symbols: vec!["eth/usdt".to_string()],
+                exchanges: vec!["bybit".to_string()],
+                depth: 1,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        // Another pair, and a book of the pair without a level of the exchange, are skipped
+        let updates = [
+            price_level_update("BTCUSDT", Exchange::Bybit, &[(100.0, 1.0)], &[(101.0, 1.0)]),
+            price_level_update(
+                "ETHUSDT",
+                Exchange::Binance,
+                &[(2000.0, 1.0)],
+                &[(2001.5, 1.0)],
+            ),
+            price_level_update(
+                "ETHUSDT",
+                Exchange::Bybit,
+                &[(2001.0, 1.0), (1999.0, 2.0)],
+                &[(2002.0, 1.0), (2003.0, 2.0)],
+            ),
+        ];
+        for update in updates {
+            feed.send(update).unwrap();
+        }
+
+        let summary = tokio::time::timeout(TIMEOUT, stream.message())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+            .summary
+            .unwrap();
+        assert_eq!(summary.symbol, "ETHUSDT");
+        assert_eq!(summary.bids.len(), 1);
+        assert_eq!(summary.asks.len(), 1);
+        assert_eq!(summary.bids[0].exchange, "bybit");
+        assert_eq!(summary.bids[0].price, 2001.0);
+        assert_eq!(summary.asks[0].exchange, "bybit");
+        assert_eq!(summary.asks[0].price, 2002.0);
+        // The spread is the filtered book's, not the consolidated one's
+        assert_eq!(summary.spread, 1.0);
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_calls_authenticate() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let mut health_only = api_key("monitor", "monitor-key", AuthRole::ReadOnly);
+        health_only.channels = vec!["health".to_string()];
+        let keys = vec![
+            api_key("dashboard", "read-key", AuthRole::ReadOnly),
+            health_only,
+        ];
+        let port = start_authenticated(&aggregator, authenticator(keys)).await;
+        let mut client = OrderbookServiceClient::new(connect(port).await);
+        let request = |key: Option<&str>| {
+            let mut request = tonic::Request::new(StreamSummariesRequest {});
+            if let Some(key) = key {
+                request
+                    .metadata_mut()
+                    .insert("x-api-key", key.parse().unwrap());
+            }
+            request
+        };
+
+        for key in [None, Some("wrong-key")] {
+            let status = client.stream_summaries(request(key)).await.unwrap_err();
+            assert_eq!(status.code(), Code::Unauthenticated);
+        }
+        // A key limited to other channels is let in, but not to the summaries
+        let status = client
+            .stream_summaries(request(Some("monitor-key")))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+        assert!(client
+            .stream_summaries(request(Some("read-key")))
+            .await
+            .is_ok());
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+}
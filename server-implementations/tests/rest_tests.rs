@@ -0,0 +1,540 @@
+mod common;
+
+use aggregator_core::{Aggregator, AuthRole, Exchange, RateLimitConfig, ShutdownStage};
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use common::{
+    aggregator, api_key, authenticator, price_level_update, publish, register_idle_connector,
+    start_feed, TIMEOUT,
+};
+use serde_json::{json, Value};
+use server_implementations::auth::Authenticator;
+use server_implementations::rest::{openapi, RestServer};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[cfg(test)]
+mod rest_tests {
+    use super::*;
+
+    fn router(aggregator: &Arc<Aggregator>) -> Router {
+        RestServer::new("127.0.0.1".to_string(), 0).router(aggregator.clone())
+    }
+
+    fn authenticated_router(
+        aggregator: &Arc<Aggregator>,
+        authenticator: Arc<Authenticator>,
+    ) -> Router {
+        RestServer::new("127.0.0.1".to_string(), 0)
+            .with_authenticator(authenticator)
+            .router(aggregator.clone())
+    }
+
+    /// A GET of `uri` with `header` set to `value`
+    fn get_with(uri: &str, header: &str, value: &str) -> Request<Body> {
+        Request::get(uri)
+            .header(header, value)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    /// A `method` request of `uri` with the API key `key`, and `body` as JSON if any
+    fn keyed(method: &str, uri: &str, key: &str, body: Option<Value>) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("x-api-key", key);
+        match body {
+            Some(body) => request
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap()
+    }
+
+    /// Sends `request` through `router`, returning the status and the JSON body. A body that
+    /// is not JSON, such as a rejection by an extractor, is returned as a string, and an empty
+    /// one as `Null`.
+    async fn send(router: &Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = if body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()))
+        };
+        (status, body)
+    }
+
+    async fn get(router: &Router, uri: &str) -> (StatusCode, Value) {
+        send(router, Request::get(uri).body(Body::empty()).unwrap()).await
+    }
+
+    /// Asserts an error response carries `status` and the `ErrorBody` of `category`
+    fn assert_error(response: (StatusCode, Value), status: StatusCode, category: &str) {
+        let (actual, body) = response;
+        assert_eq!(actual, status, "{}", body);
+        assert_eq!(body["category"], category);
+        assert!(body["error"]
+            .as_str()
+            .is_some_and(|error| !error.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_summary_responses() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let feed = start_feed(&aggregator).await;
+        let router = router(&aggregator);
+
+        assert_error(
+            get(&router, "/summary/BTC/USDT").await,
+            StatusCode::NOT_FOUND,
+            "not_found",
+        );
+        // A pair not configured can only be spelled out with a separator
+        assert_error(
+            get(&router, "/summaries/DOGEUSDT").await,
+            StatusCode::BAD_REQUEST,
+            "validation",
+        );
+        assert_error(
+            get(&router, "/summaries/DOGE-USDT").await,
+            StatusCode::NOT_FOUND,
+            "not_found",
+        );
+
+        let update = price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            &[(100.0, 1.0)],
+            &[(101.0, 2.0)],
+        );
+        publish(&aggregator, &feed, update).await;
+        for uri in [
+            "/summary/BTC/USDT",
+            "/summaries/btc_usdt",
+            "/summaries/BTCUSDT",
+        ] {
+            let (status, body) = get(&router, uri).await;
+            assert_eq!(status, StatusCode::OK, "{}", uri);
+            assert_eq!(body["symbol"], "BTCUSDT");
+            assert_eq!(body["spread"], 1.0);
+            assert_eq!(body["bids"][0]["price"], 100.0);
+        }
+        let (status, body) = get(&router, "/summaries").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 1);
+
+        let (status, body) = get(&router, "/orderbook/BTC-USDT?depth=1").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["attribution"]["Binance"]["bid_levels"], 1);
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_error_categories_map_to_statuses() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let feed = start_feed(&aggregator).await;
+        let router = router(&aggregator);
+        let update = price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            &[(100.0, 1.0)],
+            &[(101.0, 2.0)],
+        );
+        publish(&aggregator, &feed, update).await;
+
+        // Validation and parsing errors are the client's
+        assert_error(
+            get(&router, "/cost-to-fill/BTC/USDT?side=hold&quantity=1").await,
+            StatusCode::BAD_REQUEST,
+            "validation",
+        );
+        assert_error(
+            get(&router, "/metrics/nowhere").await,
+            StatusCode::BAD_REQUEST,
+            "parsing",
+        );
+        assert_error(
+            get(&router, "/opportunities/history?buy_exchange=nowhere").await,
+            StatusCode::BAD_REQUEST,
+            "parsing",
+        );
+        // Nothing to answer with is not found
+        assert_error(
+            get(&router, "/cost-to-fill/ETH/USDT?side=buy&quantity=1").await,
+            StatusCode::NOT_FOUND,
+            "not_found",
+        );
+        assert_error(
+            get(&router, "/heatmap/BTCUSDT").await,
+            StatusCode::NOT_FOUND,
+            "not_found",
+        );
+        assert_error(
+            get(&router, "/stats/BTCUSDT").await,
+            StatusCode::NOT_FOUND,
+            "not_found",
+        );
+
+        let (status, body) = get(&router, "/cost-to-fill/BTC/USDT?side=buy&quantity=1").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["average_price"], 101.0);
+        // Thin books fill what they can
+        let (status, body) = get(&router, "/cost-to-fill/BTC/USDT?side=buy&quantity=5").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["filled_quantity"], 2.0);
+        let (status, body) = get(&router, "/opportunities/history").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, Value::Array(Vec::new()));
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_health_status_follows_the_system_health() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let router = router(&aggregator);
+
+        // No exchange has reported yet
+        let (status, body) = get(&router, "/health").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["level"], "Unhealthy");
+    }
+
+    #[tokio::test]
+    async fn test_requests_authenticate() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let mut limited = api_key("limited", "limited-key", AuthRole::ReadOnly);
+        limited.rate_limit = Some(RateLimitConfig {
+            requests_per_second: 0,
+            burst_size: 2,
+        });
+        let keys = vec![
+            api_key("dashboard", "read-key", AuthRole::ReadOnly),
+            limited,
+        ];
+        let router = authenticated_router(&aggregator, authenticator(keys));
+
+        assert_error(
+            get(&router, "/summaries").await,
+            StatusCode::UNAUTHORIZED,
+            "authentication",
+        );
+        assert_error(
+            send(&router, get_with("/summaries", "x-api-key", "wrong-key")).await,
+            StatusCode::UNAUTHORIZED,
+            "authentication",
+        );
+        for request in [
+            get_with("/summaries", "authorization", "Bearer read-key"),
+            get_with("/summaries", "x-api-key", "read-key"),
+        ] {
+            let (status, body) = send(&router, request).await;
+            assert_eq!(status, StatusCode::OK, "{}", body);
+        }
+
+        // A read-only key may not use the admin routes
+        assert_error(
+            send(
+                &router,
+                get_with("/admin/analysis", "x-api-key", "read-key"),
+            )
+            .await,
+            StatusCode::FORBIDDEN,
+            "forbidden",
+        );
+
+        for _ in 0..2 {
+            let (status, _) =
+                send(&router, get_with("/summaries", "x-api-key", "limited-key")).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        assert_error(
+            send(&router, get_with("/summaries", "x-api-key", "limited-key")).await,
+            StatusCode::TOO_MANY_REQUESTS,
+            "rate_limit",
+        );
+
+        // Health checks and the API document stay open
+        let (status, _) = get(&router, "/health").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let (status, body) = get(&router, "/api-docs").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["paths"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_admin_exchanges() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        register_idle_connector(&aggregator, &Exchange::Bybit).await;
+        let keys = vec![api_key("ops", "admin-key", AuthRole::Admin)];
+        let router = authenticated_router(&aggregator, authenticator(keys));
+        let admin = |uri: &str| keyed("POST", uri, "admin-key", None);
+
+        for (uri, changed) in [
+            ("/admin/exchanges/bybit/enable", true),
+            ("/admin/exchanges/Bybit/enable", false),
+            ("/admin/exchanges/bybit/reconnect", true),
+            ("/admin/exchanges/bybit/disable", true),
+            ("/admin/exchanges/bybit/disable", false),
+        ] {
+            let (status, body) = send(&router, admin(uri)).await;
+            assert_eq!(status, StatusCode::OK, "{}: {}", uri, body);
+            assert_eq!(body, json!({ "changed": changed }), "{}", uri);
+        }
+        let config = aggregator.config().await;
+        assert!(!config.exchanges[&Exchange::Bybit].enabled);
+
+        // Replaying, Binance has no connector to restart
+        assert_error(
+            send(&router, admin("/admin/exchanges/binance/reconnect")).await,
+            StatusCode::NOT_FOUND,
+            "not_found",
+        );
+        assert_error(
+            send(&router, admin("/admin/exchanges/binance/disable")).await,
+            StatusCode::BAD_REQUEST,
+            "validation",
+        );
+        for action in ["enable", "disable", "reconnect"] {
+            let uri = format!("/admin/exchanges/nowhere/{}", action);
+            assert_error(
+                send(&router, admin(&uri)).await,
+                StatusCode::BAD_REQUEST,
+                "parsing",
+            );
+        }
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_admin_pairs() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let keys = vec![api_key("ops", "admin-key", AuthRole::Admin)];
+        let router = authenticated_router(&aggregator, authenticator(keys));
+        let add = |base: &str, quote: &str| {
+            let pair = json!({ "base": base, "quote": quote });
+            keyed("POST", "/admin/pairs", "admin-key", Some(pair))
+        };
+        let remove = |pair: &str| {
+            keyed(
+                "DELETE",
+                &format!("/admin/pairs/{}", pair),
+                "admin-key",
+                None,
+            )
+        };
+
+        let (status, body) = send(&router, add("SOL", "USDT")).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body, json!({ "changed": true }));
+        let (_, body) = send(&router, add("BTC", "USDT")).await;
+        assert_eq!(body, json!({ "changed": false }));
+        assert_eq!(aggregator.config().await.trading_pairs.len(), 4);
+
+        let (status, body) = send(&router, remove("SOL-USDT")).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body, json!({ "changed": true }));
+        let (status, body) = send(&router, remove("SOL-USDT")).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body, json!({ "changed": false }));
+        // A pair not configured can only be spelled out with a separator
+        assert_error(
+            send(&router, remove("DOGEUSDT")).await,
+            StatusCode::BAD_REQUEST,
+            "validation",
+        );
+        assert_eq!(aggregator.config().await.trading_pairs.len(), 3);
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_admin_analysis_settings() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let keys = vec![api_key("ops", "admin-key", AuthRole::Admin)];
+        let router = authenticated_router(&aggregator, authenticator(keys));
+        let patch = |changes: Value| keyed("PATCH", "/admin/analysis", "admin-key", Some(changes));
+
+        let (status, before) =
+            send(&router, keyed("GET", "/admin/analysis", "admin-key", None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(before, json!(aggregator.config().await.analysis));
+
+        // Only the settings the body sets change, within sections too
+        let changes =
+            json!({ "min_profit_percentage": 0.2, "circuit_breaker": { "cooldown_secs": 5 } });
+        let (status, after) = send(&router, patch(changes)).await;
+        assert_eq!(status, StatusCode::OK, "{}", after);
+        let mut expected = before.clone();
+        expected["min_profit_percentage"] = json!(0.2);
+        expected["circuit_breaker"]["cooldown_secs"] = json!(5);
+        assert_eq!(after, expected);
+        assert_eq!(json!(aggregator.config().await.analysis), expected);
+
+        for changes in [
+            json!({ "min_profit": 0.2 }),
+            json!({ "circuit_breaker": { "cooldown": 5 } }),
+            json!({ "min_volume": "lots" }),
+        ] {
+            assert_error(
+                send(&router, patch(changes)).await,
+                StatusCode::BAD_REQUEST,
+                "validation",
+            );
+        }
+        assert_eq!(json!(aggregator.config().await.analysis), expected);
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_refuse_other_roles() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let keys = vec![api_key("dashboard", "read-key", AuthRole::ReadOnly)];
+        let pair = json!({ "base": "SOL", "quote": "USDT" });
+        let changes = json!({ "min_profit_percentage": 0.2 });
+        let requests = [
+            ("POST", "/admin/exchanges/bybit/enable", None),
+            ("POST", "/admin/exchanges/binance/disable", None),
+            ("POST", "/admin/exchanges/binance/reconnect", None),
+            ("POST", "/admin/pairs", Some(pair)),
+            ("DELETE", "/admin/pairs/BTC-USDT", None),
+            ("GET", "/admin/analysis", None),
+            ("PATCH", "/admin/analysis", Some(changes)),
+            ("POST", "/admin/shutdown", None),
+        ];
+
+        // Neither a read-only key nor, without authentication, an anonymous client
+        let routers = [
+            authenticated_router(&aggregator, authenticator(keys)),
+            router(&aggregator),
+        ];
+        for router in &routers {
+            for (method, uri, body) in &requests {
+                assert_error(
+                    send(router, keyed(method, uri, "read-key", body.clone())).await,
+                    StatusCode::FORBIDDEN,
+                    "forbidden",
+                );
+            }
+        }
+        let config = aggregator.config().await;
+        assert!(!config.exchanges[&Exchange::Bybit].enabled);
+        assert_eq!(config.trading_pairs.len(), 3);
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_admin_shutdown() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let _feed = start_feed(&aggregator).await;
+        let keys = vec![api_key("ops", "admin-key", AuthRole::Admin)];
+        let router = authenticated_router(&aggregator, authenticator(keys));
+        let stopped = aggregator.stopping(ShutdownStage::Servers);
+
+        let request = keyed("POST", "/admin/shutdown?timeout_secs=1", "admin-key", None);
+        let (status, body) = send(&router, request).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(body, json!({ "shutting_down": true, "timeout_secs": 1 }));
+        // It answers before the aggregator has stopped, then stops it
+        tokio::time::timeout(TIMEOUT, stopped)
+            .await
+            .expect("the aggregator did not shut down");
+    }
+
+    /// Every route `create_app` mounts, as `(method, OpenAPI path)`
+    const ROUTES: [(&str, &str); 25] = [
+        ("GET", "/summaries"),
+        ("GET", "/summaries/{pair}"),
+        ("GET", "/orderbook/{pair}"),
+        ("GET", "/opportunities"),
+        ("GET", "/metrics/{exchange}"),
+        ("GET", "/exchanges"),
+        ("GET", "/summary/{base}/{quote}"),
+        ("GET", "/book/{base}/{quote}"),
+        ("GET", "/heatmap/{symbol}"),
+        ("GET", "/stats/{symbol}"),
+        ("GET", "/cost-to-fill/{base}/{quote}"),
+        ("GET", "/backpressure"),
+        ("GET", "/errors"),
+        ("GET", "/opportunities/history"),
+        ("GET", "/opportunities/exchange-pairs"),
+        ("POST", "/admin/exchanges/{exchange}/enable"),
+        ("POST", "/admin/exchanges/{exchange}/disable"),
+        ("POST", "/admin/exchanges/{exchange}/reconnect"),
+        ("POST", "/admin/pairs"),
+        ("DELETE", "/admin/pairs/{pair}"),
+        ("GET", "/admin/analysis"),
+        ("PATCH", "/admin/analysis"),
+        ("POST", "/admin/shutdown"),
+        ("GET", "/health"),
+        ("GET", "/api-docs"),
+    ];
+
+    #[tokio::test]
+    async fn test_openapi_lists_every_route() {
+        let spec = openapi();
+        let mut documented = Vec::new();
+        for (path, item) in &spec.paths.paths {
+            let operations = [
+                ("GET", &item.get),
+                ("POST", &item.post),
+                ("PATCH", &item.patch),
+                ("DELETE", &item.delete),
+                ("PUT", &item.put),
+            ];
+            for (method, operation) in operations {
+                if operation.is_some() {
+                    documented.push((method, path.as_str()));
+                }
+            }
+        }
+        documented.sort_unstable();
+        let mut mounted = ROUTES.to_vec();
+        mounted.sort_unstable();
+        assert_eq!(documented, mounted);
+
+        // And each documented route is one the router answers, rather than its fallback
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let router = router(&aggregator);
+        for (method, path) in documented {
+            let uri = path.replace(['{', '}'], "");
+            let request = Request::builder()
+                .method(method)
+                .uri(&uri)
+                .body(Body::empty())
+                .unwrap();
+            let (status, body) = send(&router, request).await;
+            assert_ne!(
+                status,
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{} {}",
+                method,
+                path
+            );
+            assert!(
+                status != StatusCode::NOT_FOUND || body != Value::Null,
+                "{} {} is not routed",
+                method,
+                path
+            );
+        }
+    }
+}
@@ -0,0 +1,282 @@
+mod common;
+
+use aggregator_core::{Aggregator, AuthRole, BookUpdate, Exchange};
+use common::{
+    aggregator, api_key, authenticator, free_port, price_level_update, publish, start_feed, TIMEOUT,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use server_implementations::auth::Authenticator;
+use server_implementations::websocket::WebSocketServer;
+use server_implementations::Server;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::{Error, Message};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+#[cfg(test)]
+mod websocket_tests {
+    use super::*;
+
+    /// Starts a WebSocket server for `aggregator` and returns the port it listens on
+    async fn start_server(aggregator: &Arc<Aggregator>, max_connections: usize) -> u16 {
+        let authenticator = Arc::new(Authenticator::disabled());
+        start_authenticated(aggregator, max_connections, authenticator).await
+    }
+
+    async fn start_authenticated(
+        aggregator: &Arc<Aggregator>,
+        max_connections: usize,
+        authenticator: Arc<Authenticator>,
+    ) -> u16 {
+        let port = free_port();
+        WebSocketServer::new("127.0.0.1".to_string(), port, max_connections)
+            .with_authenticator(authenticator)
+            .start(aggregator.clone())
+            .await
+            .unwrap();
+        port
+    }
+
+    /// The status the handshake to `url` is refused with
+    async fn refused(url: &str) -> StatusCode {
+        match connect_async(url).await {
+            Err(Error::Http(response)) => response.status(),
+            Err(e) => panic!("Expected a refused handshake, got {}", e),
+            Ok(_) => panic!("Expected a refused handshake to {}", url),
+        }
+    }
+
+    /// Connects to the server on `port`, retrying until it listens
+    async fn connect(port: u16) -> Client {
+        let url = format!("ws://127.0.0.1:{}", port);
+        tokio::time::timeout(TIMEOUT, async {
+            loop {
+                match connect_async(&url).await {
+                    Ok((client, _)) => return client,
+                    Err(_) => tokio::time::sleep(std::time::Duration::from_millis(20)).await,
+                }
+            }
+        })
+        .await
+        .expect("WebSocket server did not start")
+    }
+
+    async fn send(client: &mut Client, message: Value) {
+        client
+            .send(Message::Text(message.to_string()))
+            .await
+            .unwrap();
+    }
+
+    /// The next message from the server: a text frame as JSON, or `None` for a close frame
+    async fn next(client: &mut Client) -> Option<Value> {
+        loop {
+            let message = tokio::time::timeout(TIMEOUT, client.next())
+                .await
+                .expect("no message from the server")?
+                .ok()?;
+            match message {
+                Message::Text(text) => return Some(serde_json::from_str(&text).unwrap()),
+                Message::Close(_) => return None,
+                _ => {}
+            }
+        }
+    }
+
+    fn subscribe(channel: &str, symbol: &str) -> Value {
+        json!({ "op": "subscribe", "channel": channel, "symbol": symbol })
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_unsubscribe_are_acknowledged() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let port = start_server(&aggregator, 10).await;
+        let mut client = connect(port).await;
+
+        send(&mut client, subscribe("summary", "BTC/USDT")).await;
+        assert_eq!(
+            next(&mut client).await.unwrap(),
+            json!({ "type": "subscribed", "channel": "summary", "symbol": "BTC/USDT" })
+        );
+        // Subscribing again changes nothing and is acknowledged the same
+        send(&mut client, subscribe("summary", "BTC/USDT")).await;
+        assert_eq!(next(&mut client).await.unwrap()["type"], "subscribed");
+
+        let unsubscribe =
+            json!({ "op": "unsubscribe", "channel": "summary", "symbol": "BTC/USDT" });
+        send(&mut client, unsubscribe.clone()).await;
+        assert_eq!(
+            next(&mut client).await.unwrap(),
+            json!({ "type": "unsubscribed", "channel": "summary", "symbol": "BTC/USDT" })
+        );
+        send(&mut client, unsubscribe).await;
+        assert_eq!(next(&mut client).await.unwrap()["type"], "error");
+
+        send(
+            &mut client,
+            json!({ "op": "subscribe", "channel": "trades" }),
+        )
+        .await;
+        let reply = next(&mut client).await.unwrap();
+        assert_eq!(reply["type"], "error");
+        assert!(reply["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid operation"));
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_over_the_limit_are_refused() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let port = start_server(&aggregator, 10).await;
+        let mut client = connect(port).await;
+
+        for n in 0..100 {
+            let symbol = format!("COIN{}USDT", n);
+            send(&mut client, subscribe("orderbook-delta", &symbol)).await;
+            assert_eq!(next(&mut client).await.unwrap()["type"], "subscribed");
+        }
+        // Neither another subscription of the channel nor one of another channel fits
+        for channel in ["orderbook-delta", "arbitrage"] {
+            send(&mut client, subscribe(channel, "BTCUSDT")).await;
+            let reply = next(&mut client).await.unwrap();
+            assert_eq!(reply["type"], "error");
+            assert!(reply["message"].as_str().unwrap().contains("at most 100"));
+        }
+
+        // Unsubscribing makes room again
+        let unsubscribe =
+            json!({ "op": "unsubscribe", "channel": "orderbook-delta", "symbol": "COIN0USDT" });
+        send(&mut client, unsubscribe).await;
+        assert_eq!(next(&mut client).await.unwrap()["type"], "unsubscribed");
+        send(&mut client, subscribe("arbitrage", "BTCUSDT")).await;
+        assert_eq!(next(&mut client).await.unwrap()["type"], "subscribed");
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_connections_over_max_connections_are_closed() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let port = start_server(&aggregator, 1).await;
+        let mut first = connect(port).await;
+        send(&mut first, subscribe("summary", "BTCUSDT")).await;
+        assert_eq!(next(&mut first).await.unwrap()["type"], "subscribed");
+
+        // Turned away with "try again later" once the handshake completes
+        let mut second = connect(port).await;
+        let close = tokio::time::timeout(TIMEOUT, second.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let Message::Close(Some(frame)) = close else {
+            panic!("Expected a close frame, got {:?}", close);
+        };
+        assert_eq!(frame.code, CloseCode::Again);
+        assert_eq!(frame.reason, "Too many connections");
+
+        // The first connection is unaffected, and its slot frees up once it closes
+        send(&mut first, subscribe("summary", "ETHUSDT")).await;
+        assert_eq!(next(&mut first).await.unwrap()["type"], "subscribed");
+        first.close(None).await.unwrap();
+        tokio::time::timeout(TIMEOUT, async {
+            loop {
+                // Until then a new client is closed, which may fail the request too
+                let mut client = connect(port).await;
+                let request = Message::Text(subscribe("summary", "BTCUSDT").to_string());
+                if client.send(request).await.is_ok() && next(&mut client).await.is_some() {
+                    break;
+                }
+            }
+        })
+        .await
+        .expect("the slot of the closed connection was not freed");
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_book_deltas_follow_the_snapshot_without_gaps() {
+        let aggregator = aggregator(&[Exchange::Binance, Exchange::Bybit]);
+        let feed = start_feed(&aggregator).await;
+        let update = price_level_update(
+            "BTCUSDT",
+            Exchange::Binance,
+            &[(100.0, 1.0)],
+            &[(101.0, 1.0)],
+        );
+        publish(&aggregator, &feed, update).await;
+        let port = start_server(&aggregator, 10).await;
+        let mut client = connect(port).await;
+
+        send(&mut client, subscribe("summary", "BTCUSDT")).await;
+        assert_eq!(next(&mut client).await.unwrap()["type"], "subscribed");
+        let snapshot = serde_json::from_value(next(&mut client).await.unwrap()).unwrap();
+        let BookUpdate::Snapshot(mut book) = snapshot else {
+            panic!("Expected a snapshot, got {:?}", snapshot);
+        };
+        assert_eq!(book.bids.len(), 1);
+
+        // An update that changes nothing is not sent and does not use up a sequence number
+        let updates = [
+            price_level_update("BTCUSDT", Exchange::Bybit, &[(99.5, 2.0)], &[]),
+            price_level_update("BTCUSDT", Exchange::Binance, &[(100.0, 1.0)], &[]),
+            price_level_update("BTCUSDT", Exchange::Binance, &[(100.0, 0.0)], &[]),
+            price_level_update("BTCUSDT", Exchange::Bybit, &[], &[(100.5, 3.0)]),
+        ];
+        let mut last = None;
+        for update in updates {
+            last = Some(publish(&aggregator, &feed, update).await);
+        }
+
+        for _ in 0..3 {
+            let delta = serde_json::from_value(next(&mut client).await.unwrap()).unwrap();
+            let BookUpdate::Delta(delta) = delta else {
+                panic!("Expected a delta, got {:?}", delta);
+            };
+            assert_eq!(delta.sequence, book.sequence + 1);
+            book.apply(&delta).unwrap();
+        }
+        let last = last.unwrap();
+        assert_eq!(book.bids.len(), last.bids.len());
+        assert_eq!(book.bids[0].price, 99.5);
+        assert_eq!(book.asks[0].price, 100.5);
+        assert_eq!(book.spread, last.spread);
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_authenticates_the_client() {
+        let aggregator = aggregator(&[Exchange::Binance]);
+        let keys = vec![api_key("browser", "k+y/=", AuthRole::ReadOnly)];
+        let port = start_authenticated(&aggregator, 10, authenticator(keys)).await;
+        let url = format!("ws://127.0.0.1:{}", port);
+
+        assert_eq!(refused(&url).await, StatusCode::UNAUTHORIZED);
+        let wrong = format!("{}/?api_key=k+y/=x", url);
+        assert_eq!(refused(&wrong).await, StatusCode::UNAUTHORIZED);
+
+        // Query parameters are percent-decoded
+        for query in ["api_key=k%2By%2F%3D", "token=k%2by/="] {
+            let (mut client, _) = connect_async(format!("{}/?{}", url, query)).await.unwrap();
+            send(&mut client, subscribe("summary", "BTCUSDT")).await;
+            assert_eq!(next(&mut client).await.unwrap()["type"], "subscribed");
+        }
+
+        let report = aggregator.shutdown(TIMEOUT).await;
+        assert!(report.is_clean(), "{:?}", report.failed);
+    }
+}